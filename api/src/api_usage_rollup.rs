@@ -0,0 +1,263 @@
+use std::{collections::BTreeMap, pin::pin};
+
+use futures::future::Either;
+use rustis::commands::{GenericCommands, ListCommands, SetCommands};
+use time::OffsetDateTime;
+
+use crate::{models::redis::ApiUsageEvent, prelude::*, utils::layers::api_usage_bucket_start};
+
+/// Runs a background task that periodically aggregates the API usage events
+/// recorded in Redis into the `workspace_api_usage` table. Only buckets that
+/// have completed (with an extra bucket of grace for requests that were still
+/// in-flight) are aggregated, so that each bucket is written exactly once and
+/// the usage endpoint never has to scan the raw events.
+#[instrument(skip(state))]
+pub async fn run(state: &AppState) {
+	let mut interval = tokio::time::interval(constants::API_USAGE_ROLLUP_INTERVAL.unsigned_abs());
+
+	let mut exit_signal = pin!(crate::exit_signal());
+
+	loop {
+		let Either::Right(_) =
+			futures::future::select(&mut exit_signal, pin!(interval.tick())).await
+		else {
+			// Left branch is the exit signal
+			info!("Received SIGINT, stopping API usage rollup");
+			break;
+		};
+
+		if let Err(err) = rollup_pending_buckets(state).await {
+			warn!("Failed to rollup API usage: {err}");
+		}
+	}
+}
+
+/// Aggregates all the completed buckets that are pending in Redis into the
+/// database
+async fn rollup_pending_buckets(state: &AppState) -> Result<(), ErrorType> {
+	let pending_buckets: Vec<i64> = state
+		.redis
+		.get()
+		.smembers(redis::keys::api_usage_pending_buckets())
+		.await
+		.map_err(ErrorType::server_error)?;
+
+	let current_bucket = api_usage_bucket_start(OffsetDateTime::now_utc());
+	let bucket_size = constants::API_USAGE_BUCKET_SIZE.whole_seconds();
+
+	for bucket_start in pending_buckets
+		.into_iter()
+		.filter(|bucket_start| bucket_start + bucket_size < current_bucket)
+	{
+		// Claim the bucket, so that other instances of the API don't aggregate
+		// the same bucket twice
+		let claimed: usize = state
+			.redis
+			.get()
+			.srem(redis::keys::api_usage_pending_buckets(), bucket_start)
+			.await
+			.map_err(ErrorType::server_error)?;
+		if claimed == 0 {
+			continue;
+		}
+
+		if let Err(err) = rollup_bucket(state, bucket_start).await {
+			// Put the bucket back so that it is retried on the next run
+			_ = state
+				.redis
+				.get()
+				.sadd(redis::keys::api_usage_pending_buckets(), bucket_start)
+				.await;
+			return Err(err);
+		}
+	}
+
+	Ok(())
+}
+
+/// Aggregates the events of a single bucket into the database and removes them
+/// from Redis
+#[instrument(skip(state))]
+async fn rollup_bucket(state: &AppState, bucket_start: i64) -> Result<(), ErrorType> {
+	let bucket_key = redis::keys::api_usage_bucket(bucket_start);
+	let events: Vec<String> = state
+		.redis
+		.get()
+		.lrange(&bucket_key, 0, -1)
+		.await
+		.map_err(ErrorType::server_error)?;
+
+	let mut usage = BTreeMap::<(Uuid, String), (Vec<u64>, i64)>::new();
+	for event in events
+		.iter()
+		.filter_map(|event| serde_json::from_str::<ApiUsageEvent>(event).ok())
+	{
+		let (latencies, error_count) = usage
+			.entry((event.workspace_id, event.endpoint))
+			.or_default();
+		latencies.push(event.latency_millis);
+		if event.status_code >= 400 {
+			*error_count += 1;
+		}
+	}
+
+	let bucket_start_time =
+		OffsetDateTime::from_unix_timestamp(bucket_start).map_err(ErrorType::server_error)?;
+
+	let mut database = state.database.begin().await?;
+
+	for ((workspace_id, endpoint), (latencies, error_count)) in usage {
+		// Events for workspaces that don't exist (anymore) are dropped
+		query!(
+			r#"
+			INSERT INTO
+				workspace_api_usage(
+					workspace_id,
+					endpoint,
+					bucket_start,
+					request_count,
+					error_count,
+					latency_histogram,
+					max_latency_millis
+				)
+			SELECT
+				$1,
+				$2,
+				$3,
+				$4,
+				$5,
+				$6,
+				$7
+			WHERE
+				EXISTS(
+					SELECT
+						1
+					FROM
+						workspace
+					WHERE
+						id = $1
+				)
+			ON CONFLICT(workspace_id, endpoint, bucket_start) DO UPDATE SET
+				request_count = workspace_api_usage.request_count + EXCLUDED.request_count,
+				error_count = workspace_api_usage.error_count + EXCLUDED.error_count,
+				latency_histogram = ARRAY(
+					SELECT
+						histogram.existing + histogram.added
+					FROM
+						UNNEST(
+							workspace_api_usage.latency_histogram,
+							EXCLUDED.latency_histogram
+						) WITH ORDINALITY AS histogram(existing, added, index)
+					ORDER BY
+						histogram.index
+				),
+				max_latency_millis = GREATEST(
+					workspace_api_usage.max_latency_millis,
+					EXCLUDED.max_latency_millis
+				);
+			"#,
+			workspace_id as _,
+			endpoint,
+			bucket_start_time,
+			i64::try_from(latencies.len()).unwrap_or(i64::MAX),
+			error_count,
+			&latency_histogram(&latencies),
+			i64::try_from(latencies.iter().copied().max().unwrap_or_default()).unwrap_or(i64::MAX),
+		)
+		.execute(&mut *database)
+		.await?;
+	}
+
+	database.commit().await?;
+
+	state
+		.redis
+		.get()
+		.del(bucket_key)
+		.await
+		.map_err(ErrorType::server_error)?;
+
+	Ok(())
+}
+
+/// Counts the latencies into the buckets of a latency histogram, with a bucket
+/// for each of the [`API_USAGE_LATENCY_BOUNDS_MILLIS`][1] and a last one for
+/// the latencies above all of them. Unlike percentiles, the histograms of a
+/// time bucket that is rolled up more than once (because of requests that were
+/// recorded late) can be merged by adding up their counts.
+///
+/// [1]: constants::API_USAGE_LATENCY_BOUNDS_MILLIS
+pub fn latency_histogram(latencies: &[u64]) -> Vec<i64> {
+	let mut histogram = vec![0; constants::API_USAGE_LATENCY_BOUNDS_MILLIS.len() + 1];
+	for latency in latencies {
+		let bucket =
+			constants::API_USAGE_LATENCY_BOUNDS_MILLIS.partition_point(|bound| bound < latency);
+		histogram[bucket] += 1;
+	}
+	histogram
+}
+
+/// Returns the latency at the given percentile of a latency histogram, using
+/// the nearest-rank method. Only the bucket of that latency is known, so the
+/// upper bound of the bucket is returned, capped by the highest latency that
+/// was recorded.
+pub fn histogram_percentile(histogram: &[i64], max_latency_millis: u64, percentile: u64) -> u64 {
+	let total = histogram
+		.iter()
+		.copied()
+		.map(i64::unsigned_abs)
+		.sum::<u64>();
+	let rank = (total * percentile).div_ceil(100).max(1);
+
+	let mut count = 0;
+	for (bucket, bucket_count) in histogram.iter().enumerate() {
+		count += bucket_count.unsigned_abs();
+		if count >= rank {
+			return constants::API_USAGE_LATENCY_BOUNDS_MILLIS
+				.get(bucket)
+				.map_or(max_latency_millis, |bound| (*bound).min(max_latency_millis));
+		}
+	}
+
+	0
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn percentiles_are_read_from_the_histogram() {
+		let latencies = [3, 8, 8, 20, 40, 40, 40, 90, 200, 4_000];
+		let histogram = latency_histogram(&latencies);
+		assert_eq!(histogram.iter().sum::<i64>(), 10);
+
+		assert_eq!(histogram_percentile(&histogram, 4_000, 50), 50);
+		assert_eq!(histogram_percentile(&histogram, 4_000, 95), 4_000);
+		assert_eq!(histogram_percentile(&histogram, 3_000, 95), 3_000);
+		assert_eq!(histogram_percentile(&latency_histogram(&[]), 0, 50), 0);
+		assert_eq!(
+			histogram_percentile(&latency_histogram(&[60_000]), 60_000, 50),
+			60_000
+		);
+	}
+
+	#[test]
+	fn histograms_of_the_same_bucket_are_merged() {
+		let early = latency_histogram(&[3, 3, 3, 3, 3, 3, 3, 3, 3, 900]);
+		let late = latency_histogram(&[3, 3, 3, 3, 3, 3, 3, 3, 3, 3]);
+		let merged = early
+			.iter()
+			.zip(&late)
+			.map(|(early, late)| early + late)
+			.collect::<Vec<_>>();
+
+		let mut latencies = vec![3; 19];
+		latencies.push(900);
+		assert_eq!(merged, latency_histogram(&latencies));
+		// The p95 of the merged usage isn't the larger p95 of either part
+		assert_eq!(histogram_percentile(&early, 900, 95), 900);
+		assert_eq!(histogram_percentile(&merged, 900, 95), 5);
+		assert_eq!(histogram_percentile(&merged, 900, 50), 5);
+	}
+}
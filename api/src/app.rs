@@ -1,8 +1,10 @@
 use std::{
 	fmt::{self, Debug, Formatter},
 	net::{IpAddr, SocketAddr},
+	sync::Arc,
 };
 
+use arc_swap::ArcSwap;
 use axum::extract::FromRef;
 use models::{prelude::*, RequestUserData};
 use preprocess::Preprocessable;
@@ -10,25 +12,42 @@ use rustis::client::Client as RedisClient;
 use tokio::net::TcpListener;
 use typed_builder::TypedBuilder;
 
-use crate::{prelude::*, utils::config::AppConfig};
+use crate::{
+	prelude::*,
+	redis::RedisPool,
+	utils::{
+		config::{AppConfig, ReloadableConfig},
+		layers::ClientIpResolverLayer,
+		Clock,
+	},
+};
 
-/// Sets up the router and starts the server.
+/// Sets up the router and starts the server. Fails if the routes could not be
+/// set up or the server could not listen on the configured address.
 #[instrument(skip(state))]
-pub async fn serve(state: &AppState) {
+pub async fn serve(state: &AppState) -> Result<(), anyhow::Error> {
 	if cfg!(debug_assertions) {
-		let api_listener = TcpListener::bind(state.config.bind_address).await.unwrap();
+		let api_router = crate::routes::api_patr_cloud::setup_routes(&state).await;
+		let app_router = crate::routes::app_patr_cloud::setup_routes(&state)
+			.await
+			.context("failed to set up the routes for the dashboard")?;
+
+		let api_listener = TcpListener::bind(state.config.bind_address)
+			.await
+			.with_context(|| format!("failed to listen on `{}`", state.config.bind_address))?;
 
 		info!(
 			"API server running on http://{}",
 			api_listener.local_addr().unwrap()
 		);
 
-		let app_listener = TcpListener::bind(SocketAddr::from((
+		let app_address = SocketAddr::from((
 			state.config.bind_address.ip(),
 			state.config.bind_address.port() + 1,
-		)))
-		.await
-		.unwrap();
+		));
+		let app_listener = TcpListener::bind(app_address)
+			.await
+			.with_context(|| format!("failed to listen on `{}`", app_address))?;
 
 		info!(
 			"Frontend server running on http://{}",
@@ -39,8 +58,10 @@ pub async fn serve(state: &AppState) {
 			async {
 				axum::serve(
 					api_listener,
-					crate::routes::api_patr_cloud::setup_routes(&state)
-						.await
+					api_router
+						.layer(ClientIpResolverLayer::new(
+							state.config.trusted_proxies.clone(),
+						))
 						.into_make_service_with_connect_info::<SocketAddr>(),
 				)
 				.with_graceful_shutdown(crate::exit_signal())
@@ -50,8 +71,10 @@ pub async fn serve(state: &AppState) {
 			async {
 				axum::serve(
 					app_listener,
-					crate::routes::app_patr_cloud::setup_routes(&state)
-						.await
+					app_router
+						.layer(ClientIpResolverLayer::new(
+							state.config.trusted_proxies.clone(),
+						))
 						.into_make_service_with_connect_info::<SocketAddr>(),
 				)
 				.with_graceful_shutdown(crate::exit_signal())
@@ -61,7 +84,13 @@ pub async fn serve(state: &AppState) {
 		)
 		.await;
 	} else {
-		let tcp_listener = TcpListener::bind(state.config.bind_address).await.unwrap();
+		let router = crate::routes::setup_routes(state)
+			.await
+			.context("failed to set up the routes")?;
+
+		let tcp_listener = TcpListener::bind(state.config.bind_address)
+			.await
+			.with_context(|| format!("failed to listen on `{}`", state.config.bind_address))?;
 
 		info!(
 			"Listening for connections on http://{}",
@@ -70,14 +99,18 @@ pub async fn serve(state: &AppState) {
 
 		axum::serve(
 			tcp_listener,
-			crate::routes::setup_routes(state)
-				.await
+			router
+				.layer(ClientIpResolverLayer::new(
+					state.config.trusted_proxies.clone(),
+				))
 				.into_make_service_with_connect_info::<SocketAddr>(),
 		)
 		.with_graceful_shutdown(crate::exit_signal())
 		.await
 		.unwrap();
 	}
+
+	Ok(())
 }
 
 #[derive(Clone, FromRef)]
@@ -91,20 +124,38 @@ pub struct AppState {
 	/// The redis connection.
 	/// **Note:** This is NOT a transaction. The request object will contain a
 	/// transaction.
-	pub redis: RedisClient,
-	/// The application configuration.
+	pub redis: RedisPool,
+	/// The application configuration, as it was parsed at startup. Use
+	/// [`AppState::current_config`] to get the reloadable settings as they
+	/// currently are.
 	pub config: AppConfig,
+	/// The settings that can be changed while the API is running. These are
+	/// swapped out as a whole whenever the config is reloaded.
+	pub reloadable_config: Arc<ArcSwap<ReloadableConfig>>,
+	/// The source of the current time, for time-sensitive checks.
+	pub clock: Clock,
 }
 
 impl Debug for AppState {
 	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
 		f.debug_struct("AppState")
 			.field("database", &self.database)
-			.field("redis", &"[RedisClient]")
+			.field("redis", &"[RedisPool]")
+			.field("clock", &self.clock)
 			.finish()
 	}
 }
 
+impl AppState {
+	/// The application configuration, with the reloadable settings as they
+	/// currently are
+	pub fn current_config(&self) -> AppConfig {
+		let mut config = self.config.clone();
+		self.reloadable_config.load().apply_to(&mut config);
+		config
+	}
+}
+
 /// This struct represents a preprocessed request to the API. It contains the
 /// path, query, headers and preprocessed body of the request. This struct
 /// provides a builder API to make it easier to construct requests.
@@ -169,6 +220,8 @@ where
 	pub client_ip: IpAddr,
 	/// The application configuration.
 	pub config: AppConfig,
+	/// The source of the current time, for time-sensitive checks.
+	pub clock: Clock,
 }
 
 /// A request object that is passed through the tower layers and services for
@@ -191,6 +244,8 @@ where
 	pub client_ip: IpAddr,
 	/// The application configuration.
 	pub config: AppConfig,
+	/// The source of the current time, for time-sensitive checks.
+	pub clock: Clock,
 }
 
 /// A request object that is passed through the tower layers and services for
@@ -216,4 +271,6 @@ where
 	pub user_data: RequestUserData,
 	/// The application configuration.
 	pub config: AppConfig,
+	/// The source of the current time, for time-sensitive checks.
+	pub clock: Clock,
 }
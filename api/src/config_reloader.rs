@@ -0,0 +1,74 @@
+use std::pin::pin;
+
+use futures::future::Either;
+use tracing_subscriber::filter::LevelFilter;
+
+use crate::{
+	prelude::*,
+	utils::config::{self, AppConfig, ReloadableConfig},
+};
+
+/// Runs a background task that reloads the config whenever the API receives a
+/// `SIGHUP`. Only the [`ReloadableConfig`] is swapped out, and the log level is
+/// changed using the given function. The rest of the config, including all the
+/// secrets, stays as it was at startup. If the config can't be parsed, the
+/// current config is kept as is.
+#[instrument(skip(state, set_max_level))]
+pub async fn run(state: &AppState, set_max_level: impl Fn(LevelFilter)) {
+	#[cfg(unix)]
+	let mut hangup = match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup()) {
+		Ok(hangup) => hangup,
+		Err(err) => {
+			warn!("Unable to listen for SIGHUP. The config can't be reloaded: {err}");
+			return;
+		}
+	};
+
+	let mut exit_signal = pin!(crate::exit_signal());
+
+	loop {
+		#[cfg(unix)]
+		let reload_signal = hangup.recv();
+		#[cfg(not(unix))]
+		let reload_signal = std::future::pending::<Option<()>>();
+
+		let Either::Right(_) = futures::future::select(&mut exit_signal, pin!(reload_signal)).await
+		else {
+			// Left branch is the exit signal
+			info!("Received SIGINT, stopping config reloader");
+			break;
+		};
+
+		info!("Received SIGHUP, reloading the config");
+		match config::load_config() {
+			Ok(config) => reload(state, &config, &set_max_level),
+			Err(err) => {
+				error!("Unable to reload the config. Keeping the current config: {err:#}");
+			}
+		}
+	}
+}
+
+/// Swaps in the reloadable settings of the given config and logs which of
+/// them changed
+fn reload(state: &AppState, config: &AppConfig, set_max_level: &impl Fn(LevelFilter)) {
+	let reloaded = ReloadableConfig::from_config(config);
+	let changed = state.reloadable_config.load().changed_settings(&reloaded);
+
+	// The settings that aren't reloadable are kept from startup. Changing them
+	// needs a restart, which is worth pointing out
+	let mut expected = config.clone();
+	ReloadableConfig::from_config(&state.config).apply_to(&mut expected);
+	if serde_json::to_value(&expected).ok() != serde_json::to_value(&state.config).ok() {
+		warn!("Some of the changed settings can't be reloaded. Restart the API to apply them");
+	}
+
+	set_max_level(reloaded.logging.max_level_filter(&state.config.environment));
+	state.reloadable_config.store(reloaded.into());
+
+	if changed.is_empty() {
+		info!("Config reloaded. No settings were changed");
+	} else {
+		info!("Config reloaded. Changed settings: {}", changed.join(", "));
+	}
+}
@@ -0,0 +1,232 @@
+use std::{pin::pin, str::FromStr};
+
+use futures::future::Either;
+use models::api::workspace::{
+	database::DatabaseEngine,
+	runner::StreamRunnerDataForWorkspaceServerMsg,
+};
+use rustis::client::Client as RedisClient;
+
+use crate::{
+	prelude::*,
+	utils::{
+		config::AppConfig,
+		runner,
+		secret_store::{AppSecretStore, SecretStore},
+	},
+};
+
+/// Runs a background task that disables the rotated credentials of managed
+/// databases once their grace period is over, by asking the runners of the
+/// databases to drop their users, and removing them from the credentials that
+/// the databases accept. Dropping a user is idempotent, so running multiple
+/// instances of the API doesn't need any coordination.
+#[instrument(skip(state))]
+pub async fn run(state: &AppState) {
+	let mut interval =
+		tokio::time::interval(constants::DATABASE_CREDENTIAL_REVOKE_INTERVAL.unsigned_abs());
+
+	let mut exit_signal = pin!(crate::exit_signal());
+
+	loop {
+		let Either::Right(_) =
+			futures::future::select(&mut exit_signal, pin!(interval.tick())).await
+		else {
+			// Left branch is the exit signal
+			info!("Received SIGINT, stopping database credential revoker");
+			break;
+		};
+
+		if let Err(err) = revoke_expired_credentials(state).await {
+			warn!("Failed to revoke expired database credentials: {err}");
+		}
+	}
+}
+
+/// Revokes all the database credentials whose grace period is over. Each
+/// credential is revoked on its own, so that a runner that can't be reached
+/// doesn't hold back the credentials of the databases on other runners. Only
+/// the usernames are logged, never the passwords.
+async fn revoke_expired_credentials(state: &AppState) -> Result<(), ErrorType> {
+	let config = state.current_config();
+	let redis = state.redis.get();
+
+	let expired = query!(
+		r#"
+		SELECT
+			managed_database_credential.database_id,
+			managed_database_credential.username,
+			managed_database_credential.secret_id,
+			managed_database.workspace_id,
+			managed_database.runner,
+			managed_database.engine::TEXT AS "engine!",
+			managed_database.username AS "owner"
+		FROM
+			managed_database_credential
+		INNER JOIN
+			managed_database
+		ON
+			managed_database.id = managed_database_credential.database_id
+		WHERE
+			managed_database_credential.expires IS NOT NULL AND
+			managed_database_credential.expires <= $1;
+		"#,
+		state.clock.now(),
+	)
+	.fetch_all(&state.database)
+	.await?;
+
+	for credential in expired {
+		let database_id = Uuid::from(credential.database_id);
+		let username = credential.username.clone();
+
+		let engine =
+			DatabaseEngine::from_str(&credential.engine).map_err(ErrorType::server_error)?;
+		let result = revoke_credential(
+			state,
+			&config,
+			&redis,
+			ExpiredCredential {
+				workspace_id: credential.workspace_id.into(),
+				database_id,
+				runner_id: credential.runner.into(),
+				engine,
+				username: credential.username,
+				owner: credential.owner,
+				secret_id: credential.secret_id.into(),
+			},
+		)
+		.await;
+
+		match result {
+			Ok(()) => info!("Credentials `{username}` of database `{database_id}` revoked"),
+			Err(err) => warn!(
+				"Failed to revoke credentials `{username}` of database `{database_id}`: {err}"
+			),
+		}
+	}
+
+	Ok(())
+}
+
+/// A credential of a database whose grace period is over
+struct ExpiredCredential {
+	/// The workspace that the database belongs to
+	workspace_id: Uuid,
+	/// The ID of the database
+	database_id: Uuid,
+	/// The runner that the database runs on
+	runner_id: Uuid,
+	/// The engine of the database
+	engine: DatabaseEngine,
+	/// The username of the credential
+	username: String,
+	/// The user that the database was created with
+	owner: String,
+	/// The secret that the password of the credential is stored in
+	secret_id: Uuid,
+}
+
+/// Revokes a credential of a database. The runner of the database is asked to
+/// drop the user from the database engine first, and the credential is only
+/// removed once a runner has received the message. Otherwise, the credential
+/// is revoked again on the next run.
+async fn revoke_credential(
+	state: &AppState,
+	config: &AppConfig,
+	redis: &RedisClient,
+	ExpiredCredential {
+		workspace_id,
+		database_id,
+		runner_id,
+		engine,
+		username,
+		owner,
+		secret_id,
+	}: ExpiredCredential,
+) -> Result<(), ErrorType> {
+	runner::deliver_message(
+		redis,
+		&config.runner,
+		workspace_id,
+		runner_id,
+		&StreamRunnerDataForWorkspaceServerMsg::DatabaseUserDropped {
+			database_id,
+			engine,
+			username: username.clone(),
+			owner,
+		},
+	)
+	.await?;
+
+	let now = state.clock.now();
+	let mut database = state.database.begin().await?;
+
+	query!(
+		r#"
+		DELETE FROM
+			managed_database_credential
+		WHERE
+			database_id = $1 AND
+			username = $2;
+		"#,
+		database_id as _,
+		username,
+	)
+	.execute(&mut *database)
+	.await?;
+
+	query!(
+		r#"
+		SET CONSTRAINTS ALL DEFERRED;
+		"#
+	)
+	.execute(&mut *database)
+	.await?;
+
+	query!(
+		r#"
+		UPDATE
+			secret
+		SET
+			deleted = $2
+		WHERE
+			id = $1;
+		"#,
+		secret_id as _,
+		now,
+	)
+	.execute(&mut *database)
+	.await?;
+
+	query!(
+		r#"
+		UPDATE
+			resource
+		SET
+			deleted = $2
+		WHERE
+			id = $1;
+		"#,
+		secret_id as _,
+		now,
+	)
+	.execute(&mut *database)
+	.await?;
+
+	query!(
+		r#"
+		SET CONSTRAINTS ALL IMMEDIATE;
+		"#
+	)
+	.execute(&mut *database)
+	.await?;
+
+	AppSecretStore::new(&config.secrets, &mut *database)
+		.delete(workspace_id, secret_id)
+		.await?;
+
+	database.commit().await?;
+
+	Ok(())
+}
@@ -23,6 +23,7 @@ pub async fn initialize_user_data_tables(
 			password_reset_token TEXT,
 			password_reset_token_expiry TIMESTAMPTZ NULL,
 			password_reset_attempts INT NULL,
+			/* The TOTP secret, encrypted with a key derived from the pepper */
 			mfa_secret TEXT
 		);
 		"#
@@ -30,6 +31,17 @@ pub async fn initialize_user_data_tables(
 	.execute(&mut *connection)
 	.await?;
 
+	query!(
+		r#"
+		CREATE TABLE user_mfa_recovery_code(
+			user_id UUID NOT NULL,
+			code_hash TEXT NOT NULL
+		);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
 	Ok(())
 }
 
@@ -64,6 +76,15 @@ pub async fn initialize_user_data_indices(
 	.execute(&mut *connection)
 	.await?;
 
+	query!(
+		r#"
+		ALTER TABLE user_mfa_recovery_code
+			ADD CONSTRAINT user_mfa_recovery_code_pk PRIMARY KEY(user_id, code_hash);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
 	Ok(())
 }
 
@@ -103,5 +124,15 @@ pub async fn initialize_user_data_constraints(
 	.execute(&mut *connection)
 	.await?;
 
+	query!(
+		r#"
+		ALTER TABLE user_mfa_recovery_code
+			ADD CONSTRAINT user_mfa_recovery_code_fk_user_id
+				FOREIGN KEY(user_id) REFERENCES "user"(id);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
 	Ok(())
 }
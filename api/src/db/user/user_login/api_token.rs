@@ -40,6 +40,7 @@ pub async fn initialize_api_token_tables(
 			allowed_ips INET[],
 			created TIMESTAMPTZ NOT NULL,
 			revoked TIMESTAMPTZ,
+			last_used TIMESTAMPTZ, /* The last time the token authenticated a request */
 			login_type USER_LOGIN_TYPE GENERATED ALWAYS AS ('api_token') STORED
 		);
 		"#
@@ -0,0 +1,105 @@
+use crate::prelude::*;
+
+/// Initializes the auth audit log tables
+#[instrument(skip(connection))]
+pub async fn initialize_auth_audit_log_tables(
+	connection: &mut DatabaseConnection,
+) -> Result<(), sqlx::Error> {
+	info!("Setting up auth audit log tables");
+	query!(
+		r#"
+		CREATE TYPE AUTH_AUDIT_LOG_EVENT AS ENUM(
+			'impersonation_started',
+			'impersonated_request',
+			'impersonated_request_denied'
+		);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	// The login is not a foreign key, so that the log is kept after the login
+	// is deleted
+	query!(
+		r#"
+		CREATE TABLE auth_audit_log(
+			id UUID NOT NULL,
+			login_id UUID NOT NULL,
+			user_id UUID NOT NULL,
+			operator_id UUID,
+			event AUTH_AUDIT_LOG_EVENT NOT NULL,
+			details TEXT NOT NULL,
+			ip_address INET NOT NULL,
+			timestamp TIMESTAMPTZ NOT NULL
+		);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	Ok(())
+}
+
+/// Initializes the auth audit log indices
+#[instrument(skip(connection))]
+pub async fn initialize_auth_audit_log_indices(
+	connection: &mut DatabaseConnection,
+) -> Result<(), sqlx::Error> {
+	info!("Setting up auth audit log indices");
+	query!(
+		r#"
+		ALTER TABLE auth_audit_log
+		ADD CONSTRAINT auth_audit_log_pk
+		PRIMARY KEY(id);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		CREATE INDEX
+			auth_audit_log_idx_user_id_timestamp
+		ON
+			auth_audit_log(user_id, timestamp);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		CREATE INDEX
+			auth_audit_log_idx_operator_id_timestamp
+		ON
+			auth_audit_log(operator_id, timestamp)
+		WHERE
+			operator_id IS NOT NULL;
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	Ok(())
+}
+
+/// Initializes the auth audit log constraints
+#[instrument(skip(connection))]
+pub async fn initialize_auth_audit_log_constraints(
+	connection: &mut DatabaseConnection,
+) -> Result<(), sqlx::Error> {
+	info!("Setting up auth audit log constraints");
+	query!(
+		r#"
+		ALTER TABLE auth_audit_log
+			ADD CONSTRAINT auth_audit_log_fk_user_id
+				FOREIGN KEY(user_id) REFERENCES "user"(id),
+			ADD CONSTRAINT auth_audit_log_fk_operator_id
+				FOREIGN KEY(operator_id) REFERENCES "user"(id);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	Ok(())
+}
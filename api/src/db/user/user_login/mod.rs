@@ -1,5 +1,8 @@
 /// All API token related data of a user
 mod api_token;
+/// The audit log of authentication events, such as impersonated sessions and
+/// the requests made with them
+mod auth_audit_log;
 /// All web login related data of a user. Any login that is done through the
 /// web dashboard will be stored here.
 mod web_login;
@@ -38,6 +41,7 @@ pub async fn initialize_user_login_tables(
 
 	web_login::initialize_web_login_tables(&mut *connection).await?;
 	api_token::initialize_api_token_tables(&mut *connection).await?;
+	auth_audit_log::initialize_auth_audit_log_tables(&mut *connection).await?;
 
 	Ok(())
 }
@@ -63,6 +67,7 @@ pub async fn initialize_user_login_indices(
 
 	web_login::initialize_web_login_indices(&mut *connection).await?;
 	api_token::initialize_api_token_indices(&mut *connection).await?;
+	auth_audit_log::initialize_auth_audit_log_indices(&mut *connection).await?;
 
 	Ok(())
 }
@@ -85,6 +90,7 @@ pub async fn initialize_user_login_constraints(
 
 	web_login::initialize_web_login_constraints(&mut *connection).await?;
 	api_token::initialize_api_token_constraints(&mut *connection).await?;
+	auth_audit_log::initialize_auth_audit_log_constraints(&mut *connection).await?;
 
 	query!(
 		r#"
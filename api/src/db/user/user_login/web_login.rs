@@ -15,6 +15,7 @@ pub async fn initialize_web_login_tables(
 
 			refresh_token TEXT NOT NULL,
 			token_expiry TIMESTAMPTZ NOT NULL,
+			last_activity TIMESTAMPTZ, /* Debounced. NULL if never used since it was created */
 
 			created TIMESTAMPTZ NOT NULL,
 			created_ip INET NOT NULL,
@@ -32,6 +33,18 @@ pub async fn initialize_web_login_tables(
 	.execute(&mut *connection)
 	.await?;
 
+	query!(
+		r#"
+		CREATE TABLE web_login_impersonation(
+			login_id UUID NOT NULL,
+			operator_id UUID NOT NULL,
+			reason TEXT NOT NULL
+		);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
 	Ok(())
 }
 
@@ -62,6 +75,16 @@ pub async fn initialize_web_login_indices(
 	.execute(&mut *connection)
 	.await?;
 
+	query!(
+		r#"
+		ALTER TABLE web_login_impersonation
+		ADD CONSTRAINT web_login_impersonation_pk
+		PRIMARY KEY(login_id);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
 	Ok(())
 }
 
@@ -89,5 +112,19 @@ pub async fn initialize_web_login_constraints(
 	.execute(&mut *connection)
 	.await?;
 
+	// The impersonation is removed along with the web login when it is logged
+	// out of or revoked. The audit log keeps the record of it
+	query!(
+		r#"
+		ALTER TABLE web_login_impersonation
+			ADD CONSTRAINT web_login_impersonation_fk_login_id
+				FOREIGN KEY(login_id) REFERENCES web_login(login_id) ON DELETE CASCADE,
+			ADD CONSTRAINT web_login_impersonation_fk_operator_id
+				FOREIGN KEY(operator_id) REFERENCES "user"(id);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
 	Ok(())
 }
@@ -0,0 +1,87 @@
+use crate::prelude::*;
+
+/// Initializes all API usage-related tables
+#[instrument(skip(connection))]
+pub async fn initialize_api_usage_tables(
+	connection: &mut DatabaseConnection,
+) -> Result<(), sqlx::Error> {
+	info!("Setting up API usage tables");
+
+	query!(
+		r#"
+		CREATE TABLE workspace_api_usage(
+			workspace_id UUID NOT NULL,
+			endpoint TEXT NOT NULL,
+			bucket_start TIMESTAMPTZ NOT NULL,
+			request_count BIGINT NOT NULL,
+			error_count BIGINT NOT NULL,
+			latency_histogram BIGINT[] NOT NULL,
+			max_latency_millis BIGINT NOT NULL
+		);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	Ok(())
+}
+
+/// Initializes all API usage-related indices
+#[instrument(skip(connection))]
+pub async fn initialize_api_usage_indices(
+	connection: &mut DatabaseConnection,
+) -> Result<(), sqlx::Error> {
+	info!("Setting up API usage indices");
+
+	query!(
+		r#"
+		ALTER TABLE workspace_api_usage
+		ADD CONSTRAINT workspace_api_usage_pk
+		PRIMARY KEY(workspace_id, endpoint, bucket_start);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		CREATE INDEX
+			workspace_api_usage_idx_workspace_id_bucket_start
+		ON
+			workspace_api_usage
+		(workspace_id, bucket_start);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	Ok(())
+}
+
+/// Initializes all API usage-related constraints
+#[instrument(skip(connection))]
+pub async fn initialize_api_usage_constraints(
+	connection: &mut DatabaseConnection,
+) -> Result<(), sqlx::Error> {
+	info!("Setting up API usage constraints");
+
+	query!(
+		r#"
+		ALTER TABLE workspace_api_usage
+			ADD CONSTRAINT workspace_api_usage_fk_workspace_id
+				FOREIGN KEY(workspace_id) REFERENCES workspace(id),
+			ADD CONSTRAINT workspace_api_usage_chk_request_count_unsigned
+				CHECK(request_count >= 0),
+			ADD CONSTRAINT workspace_api_usage_chk_error_count_valid
+				CHECK(error_count >= 0 AND error_count <= request_count),
+			ADD CONSTRAINT workspace_api_usage_chk_latency_histogram_unsigned
+				CHECK(0 <= ALL(latency_histogram)),
+			ADD CONSTRAINT workspace_api_usage_chk_max_latency_millis_unsigned
+				CHECK(max_latency_millis >= 0);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	Ok(())
+}
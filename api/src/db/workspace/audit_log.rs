@@ -39,12 +39,24 @@ pub async fn initialize_workspace_tables(
 }
 
 /// Initializes all audit log-related indices
-#[instrument(skip(_connection))]
+#[instrument(skip(connection))]
 pub async fn initialize_workspace_indices(
-	_connection: &mut DatabaseConnection,
+	connection: &mut DatabaseConnection,
 ) -> Result<(), sqlx::Error> {
 	info!("Setting up audit logs indices");
 
+	// The activity of a workspace is listed newest first
+	query!(
+		r#"
+		CREATE INDEX
+			audit_log_idx_workspace_id_timestamp
+		ON
+			audit_log(workspace_id, timestamp);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
 	Ok(())
 }
 
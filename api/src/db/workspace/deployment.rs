@@ -11,9 +11,15 @@ pub async fn initialize_deployment_tables(
 		CREATE TYPE DEPLOYMENT_STATUS AS ENUM(
 			'created', /* Created, but nothing pushed to it yet */
 			'pushed', /* Something is pushed, but the system has not deployed it yet */
+			'pending', /* Deployment is waiting to be scheduled on the runner */
+			'pulling', /* The image of the deployment is being pulled */
 			'deploying', /* Something is pushed, and the system is currently deploying it */
+			'starting', /* The containers are created, but no replica is ready yet */
 			'running', /* Deployment is running successfully */
+			'degraded', /* Deployment is running, but not all replicas are ready */
+			'stopping', /* Deployment is being stopped */
 			'stopped', /* Deployment is stopped by the user */
+			'cold', /* Deployment is scaled to zero after not receiving requests */
 			'errored', /* Deployment is stopped because of too many errors */
 			'deleted' /* Deployment is deleted by the user */
 		);
@@ -44,6 +50,32 @@ pub async fn initialize_deployment_tables(
 	.execute(&mut *connection)
 	.await?;
 
+	query!(
+		r#"
+		CREATE TYPE DEPLOYMENT_LOG_LEVEL AS ENUM(
+			'trace',
+			'debug',
+			'info',
+			'warn',
+			'error'
+		);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		CREATE TYPE DEPLOYMENT_RECONCILIATION_STATE AS ENUM(
+			'in_sync',
+			'pending',
+			'failed'
+		);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
 	query!(
 		r#"
 		CREATE TABLE deployment(
@@ -59,6 +91,16 @@ pub async fn initialize_deployment_tables(
 			min_horizontal_scale SMALLINT NOT NULL DEFAULT 1,
 			max_horizontal_scale SMALLINT NOT NULL DEFAULT 1,
 			machine_type UUID NOT NULL,
+			template_id UUID, /* The template the deployment was created from */
+			cpu_request INTEGER, /* In millicores, NULL to let the runner decide */
+			cpu_limit INTEGER, /* In millicores, NULL for the machine type's capacity */
+			memory_request INTEGER, /* In MiB, NULL to let the runner decide */
+			memory_limit INTEGER, /* In MiB, NULL for the machine type's capacity */
+			scale_to_zero_after INTEGER, /* In seconds, NULL to never scale to zero */
+			last_request_at TIMESTAMPTZ, /* Reported by the ingress, debounced */
+			log_level DEPLOYMENT_LOG_LEVEL, /* NULL to capture all the logs */
+			max_concurrent_requests INTEGER, /* Per replica, NULL for no limit */
+			access_logging BOOLEAN NOT NULL DEFAULT FALSE, /* Can contain PII */
 			deploy_on_push BOOLEAN NOT NULL DEFAULT TRUE,
 			startup_probe_port INTEGER,
 			startup_probe_path VARCHAR(255),
@@ -67,6 +109,21 @@ pub async fn initialize_deployment_tables(
 			liveness_probe_path VARCHAR(255),
 			liveness_probe_port_type EXPOSED_PORT_TYPE,
 			current_live_digest TEXT,
+			canary_image_tag VARCHAR(255), /* NULL if there is no canary */
+			canary_weight SMALLINT, /* Percentage of requests sent to the canary */
+			pull_secret_id UUID,
+			ready_replicas SMALLINT, /* Reported by the runner, NULL if unknown */
+			total_replicas SMALLINT, /* Reported by the runner, NULL if unknown */
+			canary_ready_replicas SMALLINT, /* Reported by the runner, NULL if unknown */
+			replica_ids TEXT[] NOT NULL DEFAULT '{}', /* Reported by the runner */
+			build_git_url TEXT,
+			build_branch VARCHAR(255),
+			build_dockerfile_path VARCHAR(4096),
+			reconciliation_status DEPLOYMENT_RECONCILIATION_STATE NOT NULL DEFAULT 'pending',
+			reconciliation_error TEXT,
+			last_reconciliation_attempt TIMESTAMPTZ,
+			reconciliation_generation BIGINT NOT NULL DEFAULT 0, /* Bumped on every change */
+			updated TIMESTAMPTZ NOT NULL,
 			deleted TIMESTAMPTZ
 		);
 		"#
@@ -76,12 +133,627 @@ pub async fn initialize_deployment_tables(
 
 	query!(
 		r#"
-		CREATE TABLE deployment_environment_variable(
-			deployment_id UUID NOT NULL,
-			name VARCHAR(256) NOT NULL,
-			value TEXT,
-			secret_id UUID
-		);
+		CREATE TABLE deployment_environment_variable(
+			deployment_id UUID NOT NULL,
+			name VARCHAR(256) NOT NULL,
+			value TEXT,
+			secret_id UUID,
+			environment_specific BOOLEAN NOT NULL DEFAULT FALSE,
+			is_secret BOOLEAN NOT NULL DEFAULT FALSE
+		);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		CREATE TABLE deployment_exposed_port(
+			deployment_id UUID NOT NULL,
+			port INTEGER NOT NULL,
+			port_type EXPOSED_PORT_TYPE NOT NULL
+		);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		CREATE TABLE deployment_config_mounts(
+			path TEXT NOT NULL,
+			file BYTEA NOT NULL,
+			deployment_id UUID NOT NULL
+		);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		CREATE TABLE deployment_label(
+			deployment_id UUID NOT NULL,
+			key VARCHAR(63) NOT NULL,
+			value VARCHAR(63) NOT NULL
+		);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		CREATE TABLE deployment_dependency(
+			deployment_id UUID NOT NULL,
+			dependency_id UUID NOT NULL
+		);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		CREATE TABLE deployment_deploy_history(
+			deployment_id UUID NOT NULL,
+			image_digest TEXT NOT NULL,
+			repository_id UUID,
+			created TIMESTAMPTZ NOT NULL
+		);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		CREATE TABLE deployment_template(
+			id UUID NOT NULL,
+			name CITEXT NOT NULL,
+			workspace_id UUID NOT NULL,
+			machine_type UUID,
+			startup_probe_port INTEGER,
+			startup_probe_path VARCHAR(255),
+			liveness_probe_port INTEGER,
+			liveness_probe_path VARCHAR(255),
+			created TIMESTAMPTZ NOT NULL
+		);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		CREATE TABLE deployment_template_environment_variable(
+			template_id UUID NOT NULL,
+			name VARCHAR(256) NOT NULL,
+			value TEXT,
+			secret_id UUID
+		);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		CREATE TYPE DEPLOYMENT_SCHEDULE_CONFLICT_RESOLUTION AS ENUM(
+			'stop_wins',
+			'start_wins'
+		);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		CREATE TABLE deployment_schedule(
+			id UUID NOT NULL,
+			deployment_id UUID NOT NULL,
+			start_cron TEXT,
+			stop_cron TEXT,
+			timezone TEXT NOT NULL,
+			conflict_resolution DEPLOYMENT_SCHEDULE_CONFLICT_RESOLUTION NOT NULL,
+			created TIMESTAMPTZ NOT NULL
+		);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		CREATE TYPE DEPLOYMENT_CHANGE_STATUS AS ENUM(
+			'pending',
+			'approved',
+			'rejected'
+		);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		CREATE TABLE deployment_change_request(
+			id UUID NOT NULL,
+			deployment_id UUID NOT NULL,
+			requested_by UUID NOT NULL,
+			changes TEXT NOT NULL, /* The UpdateDeploymentRequest, as JSON */
+			deployment_updated TIMESTAMPTZ NOT NULL,
+			status DEPLOYMENT_CHANGE_STATUS NOT NULL,
+			reviewed_by UUID,
+			reviewed TIMESTAMPTZ,
+			created TIMESTAMPTZ NOT NULL
+		);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	// The values of the secret environment variables of a change request,
+	// which are masked in its changes. They are removed once it is reviewed.
+	query!(
+		r#"
+		CREATE TABLE deployment_change_request_secret_variable(
+			change_request_id UUID NOT NULL,
+			name TEXT NOT NULL,
+			value TEXT NOT NULL
+		);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		CREATE TYPE DEPLOYMENT_ALERT_METRIC AS ENUM(
+			'cpu_usage',
+			'memory_usage'
+		);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		CREATE TYPE DEPLOYMENT_ALERT_NOTIFICATION_CHANNEL AS ENUM(
+			'email',
+			'webhook'
+		);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		CREATE TABLE deployment_alert_rule(
+			id UUID NOT NULL,
+			deployment_id UUID NOT NULL,
+			metric DEPLOYMENT_ALERT_METRIC NOT NULL,
+			threshold SMALLINT NOT NULL, /* Percentage of the allocated resources */
+			duration_seconds BIGINT NOT NULL,
+			resolve_duration_seconds BIGINT NOT NULL,
+			notification_channel DEPLOYMENT_ALERT_NOTIFICATION_CHANNEL NOT NULL,
+			notification_target TEXT NOT NULL, /* The email address or the webhook URL */
+			breach_started TIMESTAMPTZ,
+			recovery_started TIMESTAMPTZ,
+			firing_since TIMESTAMPTZ,
+			created TIMESTAMPTZ NOT NULL
+		);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		CREATE TYPE DEPLOYMENT_EVENT_TYPE AS ENUM(
+			'scheduled_start',
+			'scheduled_stop',
+			'alert_fired',
+			'alert_resolved',
+			'scaled_to_zero',
+			'cold_started'
+		);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		CREATE TABLE deployment_event(
+			id UUID NOT NULL,
+			deployment_id UUID NOT NULL,
+			event_type DEPLOYMENT_EVENT_TYPE NOT NULL,
+			schedule_id UUID,
+			alert_rule_id UUID,
+			created TIMESTAMPTZ NOT NULL
+		);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		CREATE TYPE DEPLOYMENT_BUILD_STATUS AS ENUM(
+			'queued',
+			'building',
+			'succeeded',
+			'failed'
+		);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		CREATE TABLE deployment_build(
+			id UUID NOT NULL,
+			deployment_id UUID NOT NULL,
+			git_url TEXT NOT NULL,
+			branch VARCHAR(255) NOT NULL,
+			dockerfile_path VARCHAR(4096),
+			image_tag VARCHAR(255) NOT NULL,
+			status DEPLOYMENT_BUILD_STATUS NOT NULL DEFAULT 'queued',
+			image_digest TEXT,
+			error TEXT,
+			created TIMESTAMPTZ NOT NULL,
+			finished TIMESTAMPTZ
+		);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		CREATE TYPE IMAGE_VULNERABILITY_SEVERITY AS ENUM(
+			'unknown',
+			'low',
+			'medium',
+			'high',
+			'critical'
+		);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		CREATE TABLE deployment_image_scan(
+			id UUID NOT NULL,
+			deployment_id UUID NOT NULL,
+			image TEXT NOT NULL,
+			image_digest TEXT, /* Reported by the scanner, NULL if unknown */
+			scanned TIMESTAMPTZ NOT NULL
+		);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		CREATE TABLE deployment_image_vulnerability(
+			scan_id UUID NOT NULL,
+			vulnerability_id TEXT NOT NULL, /* Usually the CVE ID */
+			package TEXT NOT NULL,
+			installed_version TEXT NOT NULL,
+			fixed_version TEXT,
+			severity IMAGE_VULNERABILITY_SEVERITY NOT NULL,
+			title TEXT
+		);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		CREATE TYPE DEPLOYMENT_ERROR_PAGE_KIND AS ENUM(
+			'bad_gateway',
+			'service_unavailable',
+			'gateway_timeout',
+			'warming_up'
+		);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		CREATE TABLE deployment_error_page(
+			deployment_id UUID NOT NULL,
+			kind DEPLOYMENT_ERROR_PAGE_KIND NOT NULL,
+			html TEXT NOT NULL
+		);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		CREATE TABLE workspace_deployment_error_page(
+			workspace_id UUID NOT NULL,
+			kind DEPLOYMENT_ERROR_PAGE_KIND NOT NULL,
+			html TEXT NOT NULL
+		);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		CREATE TABLE deployment_canary_requests(
+			deployment_id UUID NOT NULL,
+			primary_requests BIGINT NOT NULL DEFAULT 0,
+			primary_server_errors BIGINT NOT NULL DEFAULT 0,
+			primary_latency_millis BIGINT NOT NULL DEFAULT 0, /* Summed */
+			canary_requests BIGINT NOT NULL DEFAULT 0,
+			canary_server_errors BIGINT NOT NULL DEFAULT 0,
+			canary_latency_millis BIGINT NOT NULL DEFAULT 0 /* Summed */
+		);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	Ok(())
+}
+
+/// Initializes the deployment indices
+#[instrument(skip(connection))]
+pub async fn initialize_deployment_indices(
+	connection: &mut DatabaseConnection,
+) -> Result<(), sqlx::Error> {
+	info!("Setting up deployment indices");
+	query!(
+		r#"
+		ALTER TABLE deployment_machine_type
+		ADD CONSTRAINT deployment_machine_type_pk
+		PRIMARY KEY(id);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		ALTER TABLE deployment
+			ADD CONSTRAINT deployment_pk PRIMARY KEY(id),
+			ADD CONSTRAINT deployment_uq_id_workspace_id
+				UNIQUE(id, workspace_id);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		ALTER TABLE deployment_environment_variable
+		ADD CONSTRAINT deployment_environment_variable_pk
+		PRIMARY KEY(deployment_id, name);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		ALTER TABLE deployment_exposed_port
+			ADD CONSTRAINT deployment_exposed_port_pk
+				PRIMARY KEY(deployment_id, port),
+			ADD CONSTRAINT deployment_exposed_port_uq_deployment_id_port_port_type
+				UNIQUE(deployment_id, port, port_type);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		ALTER TABLE deployment_config_mounts
+		ADD CONSTRAINT deployment_config_mounts_pk
+		PRIMARY KEY(deployment_id, path);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		ALTER TABLE deployment_label
+		ADD CONSTRAINT deployment_label_pk
+		PRIMARY KEY(deployment_id, key);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		CREATE INDEX
+			deployment_label_idx_key_value
+		ON
+			deployment_label
+		(key, value);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		ALTER TABLE deployment_dependency
+		ADD CONSTRAINT deployment_dependency_pk
+		PRIMARY KEY(deployment_id, dependency_id);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		CREATE INDEX
+			deployment_dependency_idx_dependency_id
+		ON
+			deployment_dependency
+		(dependency_id);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		ALTER TABLE deployment_deploy_history
+		ADD CONSTRAINT deployment_image_digest_pk
+		PRIMARY KEY(deployment_id, image_digest);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		CREATE INDEX
+			deployment_idx_name
+		ON
+			deployment
+		(name);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		CREATE INDEX
+			deployment_idx_image_name_image_tag
+		ON
+			deployment
+		(image_name, image_tag);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		CREATE INDEX
+			deployment_idx_registry_image_name_image_tag
+		ON
+			deployment
+		(registry, image_name, image_tag);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		CREATE UNIQUE INDEX
+			deployment_uq_workspace_id_name
+		ON
+			deployment(workspace_id, name)
+		WHERE
+			deleted IS NULL;
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		ALTER TABLE deployment_template
+		ADD CONSTRAINT deployment_template_pk
+		PRIMARY KEY(id);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		ALTER TABLE deployment_template_environment_variable
+		ADD CONSTRAINT deployment_template_environment_variable_pk
+		PRIMARY KEY(template_id, name);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		CREATE UNIQUE INDEX
+			deployment_template_uq_workspace_id_name
+		ON
+			deployment_template(workspace_id, name);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		ALTER TABLE deployment_schedule
+		ADD CONSTRAINT deployment_schedule_pk
+		PRIMARY KEY(id);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		CREATE INDEX
+			deployment_schedule_idx_deployment_id
+		ON
+			deployment_schedule(deployment_id);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		ALTER TABLE deployment_change_request
+		ADD CONSTRAINT deployment_change_request_pk
+		PRIMARY KEY(id);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		CREATE INDEX
+			deployment_change_request_idx_deployment_id_status
+		ON
+			deployment_change_request(deployment_id, status);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		ALTER TABLE deployment_change_request_secret_variable
+		ADD CONSTRAINT deployment_change_request_secret_variable_pk
+		PRIMARY KEY(change_request_id, name);
 		"#
 	)
 	.execute(&mut *connection)
@@ -89,11 +761,9 @@ pub async fn initialize_deployment_tables(
 
 	query!(
 		r#"
-		CREATE TABLE deployment_exposed_port(
-			deployment_id UUID NOT NULL,
-			port INTEGER NOT NULL,
-			port_type EXPOSED_PORT_TYPE NOT NULL
-		);
+		ALTER TABLE deployment_alert_rule
+		ADD CONSTRAINT deployment_alert_rule_pk
+		PRIMARY KEY(id);
 		"#
 	)
 	.execute(&mut *connection)
@@ -101,11 +771,10 @@ pub async fn initialize_deployment_tables(
 
 	query!(
 		r#"
-		CREATE TABLE deployment_config_mounts(
-			path TEXT NOT NULL,
-			file BYTEA NOT NULL,
-			deployment_id UUID NOT NULL
-		);
+		CREATE INDEX
+			deployment_alert_rule_idx_deployment_id
+		ON
+			deployment_alert_rule(deployment_id);
 		"#
 	)
 	.execute(&mut *connection)
@@ -113,31 +782,20 @@ pub async fn initialize_deployment_tables(
 
 	query!(
 		r#"
-		CREATE TABLE deployment_deploy_history(
-			deployment_id UUID NOT NULL,
-			image_digest TEXT NOT NULL,
-			repository_id UUID NOT NULL,
-			created TIMESTAMPTZ NOT NULL
-		);
+		ALTER TABLE deployment_event
+		ADD CONSTRAINT deployment_event_pk
+		PRIMARY KEY(id);
 		"#
 	)
 	.execute(&mut *connection)
 	.await?;
 
-	Ok(())
-}
-
-/// Initializes the deployment indices
-#[instrument(skip(connection))]
-pub async fn initialize_deployment_indices(
-	connection: &mut DatabaseConnection,
-) -> Result<(), sqlx::Error> {
-	info!("Setting up deployment indices");
 	query!(
 		r#"
-		ALTER TABLE deployment_machine_type
-		ADD CONSTRAINT deployment_machine_type_pk
-		PRIMARY KEY(id);
+		CREATE INDEX
+			deployment_event_idx_deployment_id_created
+		ON
+			deployment_event(deployment_id, created);
 		"#
 	)
 	.execute(&mut *connection)
@@ -145,10 +803,9 @@ pub async fn initialize_deployment_indices(
 
 	query!(
 		r#"
-		ALTER TABLE deployment
-			ADD CONSTRAINT deployment_pk PRIMARY KEY(id),
-			ADD CONSTRAINT deployment_uq_id_workspace_id
-				UNIQUE(id, workspace_id);
+		ALTER TABLE deployment_build
+		ADD CONSTRAINT deployment_build_pk
+		PRIMARY KEY(id);
 		"#
 	)
 	.execute(&mut *connection)
@@ -156,21 +813,25 @@ pub async fn initialize_deployment_indices(
 
 	query!(
 		r#"
-		ALTER TABLE deployment_environment_variable
-		ADD CONSTRAINT deployment_environment_variable_pk
-		PRIMARY KEY(deployment_id, name);
+		CREATE INDEX
+			deployment_build_idx_deployment_id_created
+		ON
+			deployment_build(deployment_id, created);
 		"#
 	)
 	.execute(&mut *connection)
 	.await?;
 
+	// Only one build of a deployment can be running at a time
 	query!(
 		r#"
-		ALTER TABLE deployment_exposed_port
-			ADD CONSTRAINT deployment_exposed_port_pk
-				PRIMARY KEY(deployment_id, port),
-			ADD CONSTRAINT deployment_exposed_port_uq_deployment_id_port_port_type
-				UNIQUE(deployment_id, port, port_type);
+		CREATE UNIQUE INDEX
+			deployment_build_uq_deployment_id_running
+		ON
+			deployment_build(deployment_id)
+		WHERE
+			status = 'queued' OR
+			status = 'building';
 		"#
 	)
 	.execute(&mut *connection)
@@ -178,9 +839,9 @@ pub async fn initialize_deployment_indices(
 
 	query!(
 		r#"
-		ALTER TABLE deployment_config_mounts
-		ADD CONSTRAINT deployment_config_mounts_pk
-		PRIMARY KEY(deployment_id, path);
+		ALTER TABLE deployment_image_scan
+		ADD CONSTRAINT deployment_image_scan_pk
+		PRIMARY KEY(id);
 		"#
 	)
 	.execute(&mut *connection)
@@ -188,9 +849,10 @@ pub async fn initialize_deployment_indices(
 
 	query!(
 		r#"
-		ALTER TABLE deployment_deploy_history
-		ADD CONSTRAINT deployment_image_digest_pk
-		PRIMARY KEY(deployment_id, image_digest);
+		CREATE INDEX
+			deployment_image_scan_idx_deployment_id_scanned
+		ON
+			deployment_image_scan(deployment_id, scanned);
 		"#
 	)
 	.execute(&mut *connection)
@@ -198,11 +860,9 @@ pub async fn initialize_deployment_indices(
 
 	query!(
 		r#"
-		CREATE INDEX
-			deployment_idx_name
-		ON
-			deployment
-		(name);
+		ALTER TABLE deployment_image_vulnerability
+		ADD CONSTRAINT deployment_image_vulnerability_pk
+		PRIMARY KEY(scan_id, vulnerability_id, package);
 		"#
 	)
 	.execute(&mut *connection)
@@ -210,11 +870,9 @@ pub async fn initialize_deployment_indices(
 
 	query!(
 		r#"
-		CREATE INDEX
-			deployment_idx_image_name_image_tag
-		ON
-			deployment
-		(image_name, image_tag);
+		ALTER TABLE deployment_error_page
+		ADD CONSTRAINT deployment_error_page_pk
+		PRIMARY KEY(deployment_id, kind);
 		"#
 	)
 	.execute(&mut *connection)
@@ -222,11 +880,9 @@ pub async fn initialize_deployment_indices(
 
 	query!(
 		r#"
-		CREATE INDEX
-			deployment_idx_registry_image_name_image_tag
-		ON
-			deployment
-		(registry, image_name, image_tag);
+		ALTER TABLE workspace_deployment_error_page
+		ADD CONSTRAINT workspace_deployment_error_page_pk
+		PRIMARY KEY(workspace_id, kind);
 		"#
 	)
 	.execute(&mut *connection)
@@ -234,12 +890,9 @@ pub async fn initialize_deployment_indices(
 
 	query!(
 		r#"
-		CREATE UNIQUE INDEX
-			deployment_uq_workspace_id_name
-		ON
-			deployment(workspace_id, name)
-		WHERE
-			deleted IS NULL;
+		ALTER TABLE deployment_canary_requests
+		ADD CONSTRAINT deployment_canary_requests_pk
+		PRIMARY KEY(deployment_id);
 		"#
 	)
 	.execute(&mut *connection)
@@ -275,8 +928,32 @@ pub async fn initialize_deployment_constraints(
 				max_horizontal_scale <= 256 AND
 				max_horizontal_scale >= min_horizontal_scale
 			),
+			ADD CONSTRAINT deployment_chk_ready_replicas_u8 CHECK(
+				ready_replicas >= 0 AND
+				ready_replicas <= 256
+			),
+			ADD CONSTRAINT deployment_chk_total_replicas_u8 CHECK(
+				total_replicas >= 0 AND
+				total_replicas <= 256
+			),
+			ADD CONSTRAINT deployment_chk_canary_ready_replicas_u8 CHECK(
+				canary_ready_replicas >= 0 AND
+				canary_ready_replicas <= 256
+			),
+			ADD CONSTRAINT deployment_chk_replica_ids_u8 CHECK(
+				CARDINALITY(replica_ids) <= 256
+			),
+			ADD CONSTRAINT deployment_chk_scale_to_zero_after_is_positive CHECK(
+				scale_to_zero_after > 0
+			),
+			ADD CONSTRAINT deployment_chk_max_concurrent_requests_is_positive CHECK(
+				max_concurrent_requests > 0
+			),
 			ADD CONSTRAINT deployment_fk_machine_type
 				FOREIGN KEY(machine_type) REFERENCES deployment_machine_type(id),
+			ADD CONSTRAINT deployment_fk_template_id
+				FOREIGN KEY(template_id) REFERENCES deployment_template(id)
+					ON DELETE SET NULL,
 			ADD CONSTRAINT deployment_fk_repository_id_workspace_id
 				FOREIGN KEY(repository_id, workspace_id)
 					REFERENCES container_registry_repository(id, workspace_id),
@@ -294,6 +971,18 @@ pub async fn initialize_deployment_constraints(
 			ADD CONSTRAINT deployment_chk_image_tag_is_valid CHECK(
 				image_tag != ''
 			),
+			ADD CONSTRAINT deployment_chk_canary_is_valid CHECK(
+				(
+					canary_image_tag IS NULL AND
+					canary_weight IS NULL
+				) OR (
+					canary_image_tag IS NOT NULL AND
+					canary_image_tag != '' AND
+					canary_weight IS NOT NULL AND
+					canary_weight >= 0 AND
+					canary_weight <= 100
+				)
+			),
 			ADD CONSTRAINT deployment_chk_startup_probe_is_valid CHECK(
 				(
 					startup_probe_port IS NULL AND
@@ -336,7 +1025,28 @@ pub async fn initialize_deployment_constraints(
 					DEFERRABLE INITIALLY IMMEDIATE,
 			ADD CONSTRAINT deployment_fk_current_live_digest
 				FOREIGN KEY(id, current_live_digest) REFERENCES
-					deployment_deploy_history(deployment_id, image_digest);
+					deployment_deploy_history(deployment_id, image_digest),
+			ADD CONSTRAINT deployment_fk_pull_secret_id
+				FOREIGN KEY(pull_secret_id) REFERENCES secret_pull_credential(secret_id),
+			ADD CONSTRAINT deployment_chk_reconciliation_error_is_valid CHECK(
+				(
+					reconciliation_status = 'failed' AND
+					reconciliation_error IS NOT NULL
+				) OR (
+					reconciliation_status != 'failed'
+				)
+			),
+			ADD CONSTRAINT deployment_chk_build_source_is_valid CHECK(
+				(
+					build_git_url IS NULL AND
+					build_branch IS NULL AND
+					build_dockerfile_path IS NULL
+				) OR (
+					build_git_url IS NOT NULL AND
+					build_branch IS NOT NULL AND
+					repository_id IS NOT NULL
+				)
+			);
 		"#
 	)
 	.execute(&mut *connection)
@@ -357,6 +1067,10 @@ pub async fn initialize_deployment_constraints(
 					value IS NULL AND
 					secret_id IS NOT NULL
 				)
+			),
+			ADD CONSTRAINT deployment_env_var_chk_is_secret_has_value CHECK(
+				is_secret = FALSE OR
+				value IS NOT NULL
 			);
 		"#
 	)
@@ -388,6 +1102,34 @@ pub async fn initialize_deployment_constraints(
 	.execute(&mut *connection)
 	.await?;
 
+	query!(
+		r#"
+		ALTER TABLE deployment_label
+			ADD CONSTRAINT deployment_label_chk_key_valid
+				CHECK(key ~ '^[a-zA-Z0-9]([a-zA-Z0-9_\-\.]*[a-zA-Z0-9])?$'),
+			ADD CONSTRAINT deployment_label_chk_value_valid
+				CHECK(value ~ '^([a-zA-Z0-9]([a-zA-Z0-9_\-\.]*[a-zA-Z0-9])?)?$'),
+			ADD CONSTRAINT deployment_label_fk_deployment_id
+				FOREIGN KEY(deployment_id) REFERENCES deployment(id);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		ALTER TABLE deployment_dependency
+			ADD CONSTRAINT deployment_dependency_chk_not_self
+				CHECK(deployment_id != dependency_id),
+			ADD CONSTRAINT deployment_dependency_fk_deployment_id
+				FOREIGN KEY(deployment_id) REFERENCES deployment(id),
+			ADD CONSTRAINT deployment_dependency_fk_dependency_id
+				FOREIGN KEY(dependency_id) REFERENCES deployment(id);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
 	query!(
 		r#"
 		ALTER TABLE deployment_deploy_history
@@ -400,5 +1142,245 @@ pub async fn initialize_deployment_constraints(
 	.execute(&mut *connection)
 	.await?;
 
+	query!(
+		r#"
+		ALTER TABLE deployment_template
+			ADD CONSTRAINT deployment_template_chk_name_is_trimmed CHECK(
+				name = TRIM(name)
+			),
+			ADD CONSTRAINT deployment_template_fk_workspace_id
+				FOREIGN KEY(workspace_id) REFERENCES workspace(id),
+			ADD CONSTRAINT deployment_template_fk_machine_type
+				FOREIGN KEY(machine_type) REFERENCES deployment_machine_type(id),
+			ADD CONSTRAINT deployment_template_chk_startup_probe_is_valid CHECK(
+				(
+					startup_probe_port IS NULL AND
+					startup_probe_path IS NULL
+				) OR (
+					startup_probe_port IS NOT NULL AND
+					startup_probe_path IS NOT NULL
+				)
+			),
+			ADD CONSTRAINT deployment_template_chk_liveness_probe_is_valid CHECK(
+				(
+					liveness_probe_port IS NULL AND
+					liveness_probe_path IS NULL
+				) OR (
+					liveness_probe_port IS NOT NULL AND
+					liveness_probe_path IS NOT NULL
+				)
+			);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		ALTER TABLE deployment_template_environment_variable
+			ADD CONSTRAINT deployment_template_environment_variable_fk_template_id
+				FOREIGN KEY(template_id) REFERENCES deployment_template(id)
+					ON DELETE CASCADE,
+			ADD CONSTRAINT deployment_template_environment_variable_fk_secret_id
+				FOREIGN KEY(secret_id) REFERENCES secret(id),
+			ADD CONSTRAINT deployment_template_env_var_chk_value_secret_id_either_not_null CHECK(
+				(
+					value IS NOT NULL AND
+					secret_id IS NULL
+				) OR (
+					value IS NULL AND
+					secret_id IS NOT NULL
+				)
+			);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		ALTER TABLE deployment_schedule
+			ADD CONSTRAINT deployment_schedule_fk_deployment_id
+				FOREIGN KEY(deployment_id) REFERENCES deployment(id)
+					ON DELETE CASCADE,
+			ADD CONSTRAINT deployment_schedule_chk_has_action CHECK(
+				start_cron IS NOT NULL OR
+				stop_cron IS NOT NULL
+			),
+			ADD CONSTRAINT deployment_schedule_chk_timezone_is_trimmed CHECK(
+				timezone = TRIM(timezone)
+			);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		ALTER TABLE deployment_change_request
+			ADD CONSTRAINT deployment_change_request_fk_deployment_id
+				FOREIGN KEY(deployment_id) REFERENCES deployment(id)
+					ON DELETE CASCADE,
+			ADD CONSTRAINT deployment_change_request_fk_requested_by
+				FOREIGN KEY(requested_by) REFERENCES "user"(id),
+			ADD CONSTRAINT deployment_change_request_fk_reviewed_by
+				FOREIGN KEY(reviewed_by) REFERENCES "user"(id),
+			ADD CONSTRAINT deployment_change_request_chk_reviewed CHECK(
+				(
+					status = 'pending' AND
+					reviewed_by IS NULL AND
+					reviewed IS NULL
+				) OR (
+					status != 'pending' AND
+					reviewed_by IS NOT NULL AND
+					reviewed IS NOT NULL
+				)
+			),
+			ADD CONSTRAINT deployment_change_request_chk_not_self_approved CHECK(
+				status != 'approved' OR
+				reviewed_by != requested_by
+			);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		ALTER TABLE deployment_change_request_secret_variable
+			ADD CONSTRAINT deployment_change_request_secret_variable_fk_change_request_id
+				FOREIGN KEY(change_request_id) REFERENCES deployment_change_request(id)
+					ON DELETE CASCADE;
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		ALTER TABLE deployment_alert_rule
+			ADD CONSTRAINT deployment_alert_rule_fk_deployment_id
+				FOREIGN KEY(deployment_id) REFERENCES deployment(id)
+					ON DELETE CASCADE,
+			ADD CONSTRAINT deployment_alert_rule_chk_threshold_is_percentage CHECK(
+				threshold >= 1 AND
+				threshold <= 100
+			),
+			ADD CONSTRAINT deployment_alert_rule_chk_durations_unsigned CHECK(
+				duration_seconds >= 0 AND
+				resolve_duration_seconds >= 0
+			),
+			ADD CONSTRAINT deployment_alert_rule_chk_notification_target_is_trimmed CHECK(
+				notification_target = TRIM(notification_target)
+			);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		ALTER TABLE deployment_event
+			ADD CONSTRAINT deployment_event_fk_deployment_id
+				FOREIGN KEY(deployment_id) REFERENCES deployment(id)
+					ON DELETE CASCADE,
+			ADD CONSTRAINT deployment_event_fk_schedule_id
+				FOREIGN KEY(schedule_id) REFERENCES deployment_schedule(id)
+					ON DELETE SET NULL,
+			ADD CONSTRAINT deployment_event_fk_alert_rule_id
+				FOREIGN KEY(alert_rule_id) REFERENCES deployment_alert_rule(id)
+					ON DELETE SET NULL;
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		ALTER TABLE deployment_build
+			ADD CONSTRAINT deployment_build_fk_deployment_id
+				FOREIGN KEY(deployment_id) REFERENCES deployment(id)
+					ON DELETE CASCADE,
+			ADD CONSTRAINT deployment_build_chk_finished_is_valid CHECK(
+				(
+					status IN ('queued', 'building') AND
+					finished IS NULL
+				) OR (
+					status IN ('succeeded', 'failed') AND
+					finished IS NOT NULL
+				)
+			),
+			ADD CONSTRAINT deployment_build_chk_image_digest_is_valid CHECK(
+				status != 'succeeded' OR
+				image_digest IS NOT NULL
+			);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		ALTER TABLE deployment_image_scan
+			ADD CONSTRAINT deployment_image_scan_fk_deployment_id
+				FOREIGN KEY(deployment_id) REFERENCES deployment(id)
+					ON DELETE CASCADE;
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		ALTER TABLE deployment_image_vulnerability
+			ADD CONSTRAINT deployment_image_vulnerability_fk_scan_id
+				FOREIGN KEY(scan_id) REFERENCES deployment_image_scan(id)
+					ON DELETE CASCADE;
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	// The size limit is also validated by the API, but is enforced here since
+	// the pages are sent to the ingress with every activity report
+	query!(
+		r#"
+		ALTER TABLE deployment_error_page
+			ADD CONSTRAINT deployment_error_page_fk_deployment_id
+				FOREIGN KEY(deployment_id) REFERENCES deployment(id)
+					ON DELETE CASCADE,
+			ADD CONSTRAINT deployment_error_page_chk_html_size CHECK(
+				OCTET_LENGTH(html) <= 65536
+			);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		ALTER TABLE workspace_deployment_error_page
+			ADD CONSTRAINT workspace_deployment_error_page_fk_workspace_id
+				FOREIGN KEY(workspace_id) REFERENCES workspace(id)
+					ON DELETE CASCADE,
+			ADD CONSTRAINT workspace_deployment_error_page_chk_html_size CHECK(
+				OCTET_LENGTH(html) <= 65536
+			);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		ALTER TABLE deployment_canary_requests
+			ADD CONSTRAINT deployment_canary_requests_fk_deployment_id
+				FOREIGN KEY(deployment_id) REFERENCES deployment(id)
+					ON DELETE CASCADE;
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
 	Ok(())
 }
@@ -0,0 +1,80 @@
+use crate::prelude::*;
+
+/// Initializes all egress policy-related tables
+#[instrument(skip(connection))]
+pub async fn initialize_egress_policy_tables(
+	connection: &mut DatabaseConnection,
+) -> Result<(), sqlx::Error> {
+	info!("Setting up egress policy tables");
+
+	query!(
+		r#"
+		CREATE TYPE EGRESS_RULE_ACTION AS ENUM(
+			'allow',
+			'deny'
+		);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		CREATE TABLE workspace_egress_rule(
+			workspace_id UUID NOT NULL,
+			rule TEXT NOT NULL,
+			action EGRESS_RULE_ACTION NOT NULL
+		);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	Ok(())
+}
+
+/// Initializes all egress policy-related indices
+#[instrument(skip(connection))]
+pub async fn initialize_egress_policy_indices(
+	connection: &mut DatabaseConnection,
+) -> Result<(), sqlx::Error> {
+	info!("Setting up egress policy indices");
+
+	query!(
+		r#"
+		ALTER TABLE workspace_egress_rule
+		ADD CONSTRAINT workspace_egress_rule_pk
+		PRIMARY KEY(workspace_id, rule);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	Ok(())
+}
+
+/// Initializes all egress policy-related constraints
+#[instrument(skip(connection))]
+pub async fn initialize_egress_policy_constraints(
+	connection: &mut DatabaseConnection,
+) -> Result<(), sqlx::Error> {
+	info!("Setting up egress policy constraints");
+
+	query!(
+		r#"
+		ALTER TABLE workspace_egress_rule
+			ADD CONSTRAINT workspace_egress_rule_fk_workspace_id
+				FOREIGN KEY(workspace_id) REFERENCES workspace(id),
+			ADD CONSTRAINT workspace_egress_rule_chk_rule_is_trimmed
+				CHECK(rule = TRIM(rule)),
+			ADD CONSTRAINT workspace_egress_rule_chk_rule_is_lowercase
+				CHECK(rule = LOWER(rule)),
+			ADD CONSTRAINT workspace_egress_rule_chk_rule_is_not_empty
+				CHECK(LENGTH(rule) > 0);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	Ok(())
+}
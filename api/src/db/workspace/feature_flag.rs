@@ -0,0 +1,67 @@
+use crate::prelude::*;
+
+/// Initializes all feature flag-related tables
+#[instrument(skip(connection))]
+pub async fn initialize_feature_flag_tables(
+	connection: &mut DatabaseConnection,
+) -> Result<(), sqlx::Error> {
+	info!("Setting up feature flag tables");
+
+	query!(
+		r#"
+		CREATE TABLE workspace_feature_flag(
+			workspace_id UUID NOT NULL,
+			name TEXT NOT NULL,
+			enabled BOOLEAN NOT NULL
+		);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	Ok(())
+}
+
+/// Initializes all feature flag-related indices
+#[instrument(skip(connection))]
+pub async fn initialize_feature_flag_indices(
+	connection: &mut DatabaseConnection,
+) -> Result<(), sqlx::Error> {
+	info!("Setting up feature flag indices");
+
+	query!(
+		r#"
+		ALTER TABLE workspace_feature_flag
+		ADD CONSTRAINT workspace_feature_flag_pk
+		PRIMARY KEY(workspace_id, name);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	Ok(())
+}
+
+/// Initializes all feature flag-related constraints
+#[instrument(skip(connection))]
+pub async fn initialize_feature_flag_constraints(
+	connection: &mut DatabaseConnection,
+) -> Result<(), sqlx::Error> {
+	info!("Setting up feature flag constraints");
+
+	query!(
+		r#"
+		ALTER TABLE workspace_feature_flag
+			ADD CONSTRAINT workspace_feature_flag_fk_workspace_id
+				FOREIGN KEY(workspace_id) REFERENCES workspace(id),
+			ADD CONSTRAINT workspace_feature_flag_chk_name_is_trimmed
+				CHECK(name = TRIM(name)),
+			ADD CONSTRAINT workspace_feature_flag_chk_name_is_not_empty
+				CHECK(LENGTH(name) > 0);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	Ok(())
+}
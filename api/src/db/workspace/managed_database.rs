@@ -80,6 +80,24 @@ pub async fn initialize_managed_database_tables(
 	.execute(&mut *connection)
 	.await?;
 
+	// The credentials that a managed database accepts. Only the current
+	// credentials have no expiry. Rotated credentials expire at the end of
+	// their grace period, and are removed once they have expired. The password
+	// is stored in the secret store, as the value of a secret of the workspace
+	query!(
+		r#"
+		CREATE TABLE managed_database_credential(
+			database_id UUID NOT NULL,
+			username TEXT NOT NULL,
+			secret_id UUID NOT NULL,
+			created TIMESTAMPTZ NOT NULL,
+			expires TIMESTAMPTZ
+		);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
 	Ok(())
 }
 
@@ -122,6 +140,53 @@ pub async fn initialize_managed_database_indices(
 	.execute(&mut *connection)
 	.await?;
 
+	query!(
+		r#"
+		ALTER TABLE managed_database_credential
+		ADD CONSTRAINT managed_database_credential_pk
+		PRIMARY KEY(database_id, username);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		CREATE UNIQUE INDEX
+			managed_database_credential_uq_database_id_current
+		ON
+			managed_database_credential(database_id)
+		WHERE
+			expires IS NULL;
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		CREATE UNIQUE INDEX
+			managed_database_credential_uq_secret_id
+		ON
+			managed_database_credential(secret_id);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		CREATE INDEX
+			managed_database_credential_idx_expires
+		ON
+			managed_database_credential(expires)
+		WHERE
+			expires IS NOT NULL;
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
 	Ok(())
 }
 
@@ -163,5 +228,19 @@ pub async fn initialize_managed_database_constraints(
 	.execute(&mut *connection)
 	.await?;
 
+	query!(
+		r#"
+		ALTER TABLE managed_database_credential
+			ADD CONSTRAINT managed_database_credential_fk_database_id
+				FOREIGN KEY(database_id) REFERENCES managed_database(id),
+			ADD CONSTRAINT managed_database_credential_fk_secret_id
+				FOREIGN KEY(secret_id) REFERENCES secret(id),
+			ADD CONSTRAINT managed_database_credential_chk_expires_after_created
+				CHECK(expires IS NULL OR expires >= created);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
 	Ok(())
 }
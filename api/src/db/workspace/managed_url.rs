@@ -12,7 +12,8 @@ pub async fn initialize_managed_url_tables(
 			'proxy_to_deployment',
 			'proxy_to_static_site',
 			'proxy_url',
-			'redirect'
+			'redirect',
+			'path_map'
 		);
 		"#,
 	)
@@ -43,6 +44,20 @@ pub async fn initialize_managed_url_tables(
 	.execute(&mut *connection)
 	.await?;
 
+	query!(
+		r#"
+		CREATE TABLE managed_url_path_rule(
+			managed_url_id UUID NOT NULL,
+			path_prefix TEXT NOT NULL,
+			deployment_id UUID NOT NULL,
+			port INTEGER NOT NULL,
+			workspace_id UUID NOT NULL
+		);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
 	Ok(())
 }
 
@@ -75,6 +90,16 @@ pub async fn initialize_managed_url_indices(
 	.execute(&mut *connection)
 	.await?;
 
+	query!(
+		r#"
+		ALTER TABLE managed_url_path_rule
+		ADD CONSTRAINT managed_url_path_rule_pk
+		PRIMARY KEY(managed_url_id, path_prefix);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
 	Ok(())
 }
 
@@ -127,6 +152,14 @@ pub async fn initialize_managed_url_constraints(
 					url IS NOT NULL AND
 					permanent_redirect IS NOT NULL AND
 					http_only IS NOT NULL
+				) OR (
+					url_type = 'path_map' AND
+					deployment_id IS NULL AND
+					port IS NULL AND
+					static_site_id IS NULL AND
+					url IS NULL AND
+					permanent_redirect IS NULL AND
+					http_only IS NULL
 				)
 			),
 			ADD CONSTRAINT managed_url_fk_domain_id
@@ -148,5 +181,28 @@ pub async fn initialize_managed_url_constraints(
 	.execute(&mut *connection)
 	.await?;
 
+	query!(
+		r#"
+		ALTER TABLE managed_url_path_rule
+			ADD CONSTRAINT managed_url_path_rule_fk_managed_url_id
+				FOREIGN KEY(managed_url_id) REFERENCES managed_url(id)
+					ON DELETE CASCADE,
+			ADD CONSTRAINT managed_url_path_rule_chk_path_prefix_is_absolute CHECK(
+				path_prefix LIKE '/%'
+			),
+			ADD CONSTRAINT managed_url_path_rule_chk_port_u16 CHECK(
+				port > 0 AND port <= 65535
+			),
+			ADD CONSTRAINT managed_url_path_rule_fk_deployment_id_port
+				FOREIGN KEY(deployment_id, port)
+					REFERENCES deployment_exposed_port(deployment_id, port)
+						DEFERRABLE INITIALLY IMMEDIATE,
+			ADD CONSTRAINT managed_url_path_rule_fk_deployment_id_workspace_id
+				FOREIGN KEY(deployment_id, workspace_id) REFERENCES deployment(id, workspace_id);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
 	Ok(())
 }
@@ -1,11 +1,17 @@
 use crate::prelude::*;
 
+/// The usage of the API by a workspace, aggregated into time buckets
+mod api_usage;
 /// All tables related to the audit logs go here
 mod audit_log;
 /// The data stored in the container registry
 mod container_registry;
 /// The list of domains that are added to a workspace
 mod domain;
+/// The rules of the egress policy of a workspace
+mod egress_policy;
+/// The feature flags that are overridden for a workspace
+mod feature_flag;
 
 /// The list of deployments that are present in a workspace
 mod deployment;
@@ -35,6 +41,12 @@ pub async fn initialize_workspace_tables(
 			id UUID NOT NULL,
 			name CITEXT NOT NULL,
 			super_admin_id UUID NOT NULL,
+			default_machine_type_id UUID,
+			max_replicas SMALLINT,
+			deployment_quota INTEGER,
+			runner_quota INTEGER,
+			block_critical_vulnerabilities BOOLEAN NOT NULL DEFAULT FALSE,
+			require_deployment_change_approval BOOLEAN NOT NULL DEFAULT FALSE,
 			deleted TIMESTAMPTZ
 		);
 		"#
@@ -54,9 +66,12 @@ pub async fn initialize_workspace_tables(
 	.execute(&mut *connection)
 	.await?;
 
+	api_usage::initialize_api_usage_tables(connection).await?;
 	audit_log::initialize_workspace_tables(connection).await?;
 	container_registry::initialize_container_registry_tables(connection).await?;
 	domain::initialize_domain_tables(connection).await?;
+	egress_policy::initialize_egress_policy_tables(connection).await?;
+	feature_flag::initialize_feature_flag_tables(connection).await?;
 
 	deployment::initialize_deployment_tables(connection).await?;
 	managed_database::initialize_managed_database_tables(connection).await?;
@@ -112,9 +127,12 @@ pub async fn initialize_workspace_indices(
 	.execute(&mut *connection)
 	.await?;
 
+	api_usage::initialize_api_usage_indices(connection).await?;
 	audit_log::initialize_workspace_indices(connection).await?;
 	container_registry::initialize_container_registry_indices(connection).await?;
 	domain::initialize_domain_indices(connection).await?;
+	egress_policy::initialize_egress_policy_indices(connection).await?;
+	feature_flag::initialize_feature_flag_indices(connection).await?;
 
 	deployment::initialize_deployment_indices(connection).await?;
 	managed_database::initialize_managed_database_indices(connection).await?;
@@ -140,15 +158,33 @@ pub async fn initialize_workspace_constraints(
 			ADD CONSTRAINT workspace_fk_id FOREIGN KEY(id) REFERENCES resource(id)
 				DEFERRABLE INITIALLY IMMEDIATE,
 			ADD CONSTRAINT workspace_fk_super_admin_id
-				FOREIGN KEY(super_admin_id) REFERENCES "user"(id);
+				FOREIGN KEY(super_admin_id) REFERENCES "user"(id),
+			ADD CONSTRAINT workspace_fk_default_machine_type_id
+				FOREIGN KEY(default_machine_type_id) REFERENCES deployment_machine_type(id)
+					ON DELETE SET NULL,
+			ADD CONSTRAINT workspace_chk_max_replicas_is_positive CHECK(
+				max_replicas IS NULL OR
+				max_replicas > 0
+			),
+			ADD CONSTRAINT workspace_chk_deployment_quota_is_non_negative CHECK(
+				deployment_quota IS NULL OR
+				deployment_quota >= 0
+			),
+			ADD CONSTRAINT workspace_chk_runner_quota_is_non_negative CHECK(
+				runner_quota IS NULL OR
+				runner_quota >= 0
+			);
 		"#
 	)
 	.execute(&mut *connection)
 	.await?;
 
+	api_usage::initialize_api_usage_constraints(connection).await?;
 	audit_log::initialize_workspace_constraints(connection).await?;
 	container_registry::initialize_container_registry_constraints(connection).await?;
 	domain::initialize_domain_constraints(connection).await?;
+	egress_policy::initialize_egress_policy_constraints(connection).await?;
+	feature_flag::initialize_feature_flag_constraints(connection).await?;
 
 	deployment::initialize_deployment_constraints(connection).await?;
 	managed_database::initialize_managed_database_constraints(connection).await?;
@@ -13,6 +13,7 @@ pub async fn initialize_runner_tables(
 			name TEXT NOT NULL,
 			workspace_id UUID NOT NULL,
 			cloudflare_tunnel_id TEXT NOT NULL,
+			updated TIMESTAMPTZ NOT NULL,
 			deleted TIMESTAMPTZ
 		);
 		"#
@@ -19,6 +19,33 @@ pub async fn initialize_secret_tables(
 	.execute(&mut *connection)
 	.await?;
 
+	// The registry and the username of pull secrets. The password is stored
+	// in the secret store, as the value of the secret
+	query!(
+		r#"
+		CREATE TABLE secret_pull_credential(
+			secret_id UUID NOT NULL,
+			registry TEXT NOT NULL,
+			username TEXT NOT NULL
+		);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		CREATE TABLE secret_value(
+			secret_id UUID NOT NULL,
+			workspace_id UUID NOT NULL,
+			value TEXT NOT NULL,
+			updated TIMESTAMPTZ NOT NULL
+		);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
 	Ok(())
 }
 
@@ -51,6 +78,37 @@ pub async fn initialize_secret_indices(
 	.execute(&mut *connection)
 	.await?;
 
+	query!(
+		r#"
+		ALTER TABLE secret_pull_credential
+		ADD CONSTRAINT secret_pull_credential_pk
+		PRIMARY KEY(secret_id);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		ALTER TABLE secret_value
+		ADD CONSTRAINT secret_value_pk
+		PRIMARY KEY(secret_id);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		CREATE INDEX
+			secret_value_idx_workspace_id
+		ON
+			secret_value(workspace_id);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
 	Ok(())
 }
 
@@ -73,5 +131,27 @@ pub async fn initialize_secret_constraints(
 	.execute(&mut *connection)
 	.await?;
 
+	query!(
+		r#"
+		ALTER TABLE secret_pull_credential
+			ADD CONSTRAINT secret_pull_credential_fk_secret_id
+				FOREIGN KEY(secret_id) REFERENCES secret(id),
+			ADD CONSTRAINT secret_pull_credential_chk_registry_is_trimmed
+				CHECK(registry = TRIM(registry));
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		ALTER TABLE secret_value
+			ADD CONSTRAINT secret_value_fk_secret_id
+				FOREIGN KEY(secret_id) REFERENCES secret(id);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
 	Ok(())
 }
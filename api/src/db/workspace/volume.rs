@@ -54,6 +54,9 @@ pub async fn initialize_volume_indices(
 		r#"
 		ALTER TABLE deployment_volume_mount
 			ADD CONSTRAINT deployment_volume_mount_pk PRIMARY KEY(deployment_id, volume_id),
+			ADD CONSTRAINT deployment_volume_mount_uq_volume_id UNIQUE(volume_id),
+			ADD CONSTRAINT deployment_volume_mount_uq_deployment_id_volume_mount_path
+				UNIQUE(deployment_id, volume_mount_path),
 			ADD CONSTRAINT deployment_volume_mount_fk_volume_id
 				FOREIGN KEY(volume_id) REFERENCES deployment_volume(id),
 			ADD CONSTRAINT deployment_volume_mount_fk_deployment_id
@@ -0,0 +1,725 @@
+use std::{
+	collections::BTreeMap,
+	net::{IpAddr, Ipv4Addr},
+	pin::pin,
+};
+
+use axum::http::{HeaderName, HeaderValue};
+use futures::future::Either;
+use models::api::workspace::deployment::alert_rule::{
+	AlertNotificationChannel,
+	DeploymentAlertMetric,
+	DeploymentAlertNotification,
+	DeploymentAlertState,
+};
+use rustis::commands::{SetCondition, SetExpiration, StringCommands};
+use serde::Deserialize;
+use time::{Duration, OffsetDateTime};
+
+use crate::{
+	models::{
+		deployment_alert::AlertNotificationChannelType,
+		deployment_event::DeploymentEventType,
+	},
+	prelude::*,
+	utils::{email, http_client},
+};
+
+/// The number of bytes in each unit of the memory of a machine type
+const MACHINE_TYPE_MEMORY_UNIT_BYTES: f64 = 256.0 * 1024.0 * 1024.0;
+
+/// The state of an alert rule, as stored in the database
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct AlertRuleState {
+	/// The time since which the usage has been above the threshold, if it is
+	/// above the threshold
+	breach_started: Option<OffsetDateTime>,
+	/// The time since which the usage of a firing alert has been below the
+	/// threshold, if it is below the threshold
+	recovery_started: Option<OffsetDateTime>,
+	/// The time since which the alert has been firing, if it is firing
+	firing_since: Option<OffsetDateTime>,
+}
+
+/// The response of Mimir to an instant query
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MimirResponse {
+	/// The result of the query
+	data: MimirData,
+}
+
+/// The result of an instant query to Mimir
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MimirData {
+	/// The series that matched the query. This is empty if there is no data
+	/// for the query
+	result: Vec<MimirVectorResult>,
+}
+
+/// A single series in the result of an instant query to Mimir
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MimirVectorResult {
+	/// The time of the sample (as a unix timestamp) and its value. Values are
+	/// returned as strings, so that they can be `NaN` or infinite
+	value: (f64, String),
+}
+
+/// Runs a background task that evaluates the alert rules of all deployments
+/// once every minute against the metrics in Mimir. An alert fires when the
+/// usage stays above the threshold of its rule for the duration of the rule,
+/// and is resolved when the usage stays below the threshold for the resolve
+/// duration of the rule. A notification is sent and an event is recorded on
+/// the deployment only when an alert fires or is resolved, so that a breached
+/// rule doesn't send a notification every minute. Each minute is claimed in
+/// Redis before it is evaluated, so that running multiple instances of the API
+/// doesn't send the same notification more than once.
+#[instrument(skip(state))]
+pub async fn run(state: &AppState) {
+	let mut interval =
+		tokio::time::interval(constants::DEPLOYMENT_ALERT_EVALUATOR_INTERVAL.unsigned_abs());
+
+	let mut exit_signal = pin!(crate::exit_signal());
+	let mut last_evaluated_minute = None;
+
+	loop {
+		let Either::Right(_) =
+			futures::future::select(&mut exit_signal, pin!(interval.tick())).await
+		else {
+			// Left branch is the exit signal
+			info!("Received SIGINT, stopping deployment alert evaluator");
+			break;
+		};
+
+		let now = OffsetDateTime::now_utc();
+		let Ok(minute) = now
+			.replace_second(0)
+			.and_then(|now| now.replace_nanosecond(0))
+		else {
+			continue;
+		};
+		if last_evaluated_minute == Some(minute) {
+			continue;
+		}
+		last_evaluated_minute = Some(minute);
+
+		if let Err(err) = evaluate_alert_rules(state, minute).await {
+			warn!("Failed to evaluate deployment alert rules: {err}");
+		}
+	}
+}
+
+/// Evaluates the alert rules of all deployments for the given minute, and
+/// sends the notifications of the alerts that fired or were resolved. The
+/// usage of the deployments is fetched from Mimir before the rules are locked,
+/// so that the rules aren't locked while waiting for Mimir.
+#[instrument(skip(state))]
+async fn evaluate_alert_rules(state: &AppState, minute: OffsetDateTime) -> Result<(), ErrorType> {
+	// Claim the minute, so that other instances of the API don't evaluate it
+	let claimed: bool = state
+		.redis
+		.get()
+		.set_with_options(
+			redis::keys::deployment_alert_minute_lock(minute.unix_timestamp()),
+			"",
+			SetCondition::NX,
+			SetExpiration::Ex(Duration::hours(1).whole_seconds().unsigned_abs()),
+			false,
+		)
+		.await
+		.map_err(ErrorType::server_error)?;
+	if !claimed {
+		return Ok(());
+	}
+
+	let rules = query!(
+		r#"
+		SELECT
+			deployment_alert_rule.id,
+			deployment_alert_rule.deployment_id,
+			deployment_alert_rule.metric AS "metric: DeploymentAlertMetric",
+			deployment_alert_rule.threshold,
+			deployment.workspace_id,
+			deployment_machine_type.cpu_count,
+			deployment_machine_type.memory_count
+		FROM
+			deployment_alert_rule
+		INNER JOIN
+			deployment
+		ON
+			deployment.id = deployment_alert_rule.deployment_id
+		INNER JOIN
+			deployment_machine_type
+		ON
+			deployment_machine_type.id = deployment.machine_type
+		WHERE
+			deployment.deleted IS NULL
+		ORDER BY
+			deployment_alert_rule.created;
+		"#,
+	)
+	.fetch_all(&state.database)
+	.await?;
+
+	let mut usages = BTreeMap::new();
+	for rule in rules {
+		let usage = match fetch_usage(
+			http_client(),
+			state,
+			rule.workspace_id.into(),
+			rule.deployment_id.into(),
+			rule.metric,
+		)
+		.await
+		{
+			Ok(usage) => usage,
+			Err(err) => {
+				warn!(
+					"Failed to fetch the {:?} of deployment `{}`: {err}",
+					rule.metric, rule.deployment_id
+				);
+				continue;
+			}
+		};
+
+		let allocated = match rule.metric {
+			DeploymentAlertMetric::CpuUsage => f64::from(rule.cpu_count),
+			DeploymentAlertMetric::MemoryUsage => {
+				f64::from(u32::try_from(rule.memory_count).map_err(ErrorType::server_error)?) *
+					MACHINE_TYPE_MEMORY_UNIT_BYTES
+			}
+		};
+		if allocated <= 0.0 {
+			continue;
+		}
+
+		// A deployment that isn't running has no usage, so its alerts are
+		// resolved like for any other deployment whose usage went down
+		let value = usage.unwrap_or(0.0) / allocated * 100.0;
+		usages.insert(Uuid::from(rule.id), value);
+	}
+
+	let mut database = state.database.begin().await?;
+
+	// The rules are read again once they are locked, since they could have
+	// been changed or deleted while the usages were being fetched
+	let rules = query!(
+		r#"
+		SELECT
+			deployment_alert_rule.id,
+			deployment_alert_rule.deployment_id,
+			deployment_alert_rule.metric AS "metric: DeploymentAlertMetric",
+			deployment_alert_rule.threshold,
+			deployment_alert_rule.duration_seconds,
+			deployment_alert_rule.resolve_duration_seconds,
+			deployment_alert_rule.notification_channel AS "notification_channel: AlertNotificationChannelType",
+			deployment_alert_rule.notification_target,
+			deployment_alert_rule.breach_started,
+			deployment_alert_rule.recovery_started,
+			deployment_alert_rule.firing_since,
+			deployment.workspace_id,
+			deployment.name::TEXT AS "deployment_name!"
+		FROM
+			deployment_alert_rule
+		INNER JOIN
+			deployment
+		ON
+			deployment.id = deployment_alert_rule.deployment_id
+		WHERE
+			deployment_alert_rule.id = ANY($1) AND
+			deployment.deleted IS NULL
+		ORDER BY
+			deployment_alert_rule.created
+		FOR UPDATE OF
+			deployment_alert_rule;
+		"#,
+		&usages
+			.keys()
+			.map(|id| (*id).into())
+			.collect::<Vec<sqlx::types::Uuid>>(),
+	)
+	.fetch_all(&mut *database)
+	.await?;
+
+	let mut notifications = Vec::new();
+
+	for rule in rules {
+		let Some(&value) = usages.get(&Uuid::from(rule.id)) else {
+			continue;
+		};
+
+		let current = AlertRuleState {
+			breach_started: rule.breach_started,
+			recovery_started: rule.recovery_started,
+			firing_since: rule.firing_since,
+		};
+		let (next, transition) = next_alert_state(
+			current,
+			value > f64::from(rule.threshold),
+			minute,
+			Duration::seconds(rule.duration_seconds),
+			Duration::seconds(rule.resolve_duration_seconds),
+		);
+
+		if next != current {
+			query!(
+				r#"
+				UPDATE
+					deployment_alert_rule
+				SET
+					breach_started = $1,
+					recovery_started = $2,
+					firing_since = $3
+				WHERE
+					id = $4;
+				"#,
+				next.breach_started,
+				next.recovery_started,
+				next.firing_since,
+				rule.id as _,
+			)
+			.execute(&mut *database)
+			.await?;
+		}
+
+		let Some(alert_state) = transition else {
+			continue;
+		};
+
+		info!(
+			"Alert rule `{}` of deployment `{}` {:?} at {value:.1}%",
+			rule.id, rule.deployment_id, alert_state
+		);
+
+		let event_type = match alert_state {
+			DeploymentAlertState::Fired => DeploymentEventType::AlertFired,
+			DeploymentAlertState::Resolved => DeploymentEventType::AlertResolved,
+		};
+
+		query!(
+			r#"
+			INSERT INTO
+				deployment_event(
+					id,
+					deployment_id,
+					event_type,
+					alert_rule_id,
+					created
+				)
+			VALUES
+				($1, $2, $3, $4, $5);
+			"#,
+			Uuid::new_v4() as _,
+			rule.deployment_id as _,
+			event_type as _,
+			rule.id as _,
+			minute,
+		)
+		.execute(&mut *database)
+		.await?;
+
+		notifications.push((
+			rule.notification_channel
+				.into_channel(rule.notification_target),
+			DeploymentAlertNotification {
+				alert_rule_id: rule.id.into(),
+				workspace_id: rule.workspace_id.into(),
+				deployment_id: rule.deployment_id.into(),
+				deployment_name: rule.deployment_name,
+				metric: rule.metric,
+				threshold: u8::try_from(rule.threshold).map_err(ErrorType::server_error)?,
+				value,
+				state: alert_state,
+				timestamp: minute,
+			},
+		));
+	}
+
+	database.commit().await?;
+
+	// The notifications are only sent once the state of the alerts is saved, so
+	// that a failure to save it never causes a notification to be sent twice
+	for (channel, notification) in notifications {
+		if let Err(err) = send_notification(state, &channel, &notification).await {
+			warn!(
+				"Failed to send the notification of alert rule `{}`: {err}",
+				notification.alert_rule_id
+			);
+		}
+	}
+
+	Ok(())
+}
+
+/// Computes the next state of an alert rule given whether its threshold is
+/// breached in the current minute. Returns the new state, along with the
+/// transition of the alert, if it fired or was resolved in this minute.
+fn next_alert_state(
+	current: AlertRuleState,
+	breached: bool,
+	now: OffsetDateTime,
+	duration: Duration,
+	resolve_duration: Duration,
+) -> (AlertRuleState, Option<DeploymentAlertState>) {
+	if breached {
+		let breach_started = current.breach_started.unwrap_or(now);
+		match current.firing_since {
+			Some(firing_since) => (
+				AlertRuleState {
+					breach_started: Some(breach_started),
+					recovery_started: None,
+					firing_since: Some(firing_since),
+				},
+				None,
+			),
+			None if now - breach_started >= duration => (
+				AlertRuleState {
+					breach_started: Some(breach_started),
+					recovery_started: None,
+					firing_since: Some(now),
+				},
+				Some(DeploymentAlertState::Fired),
+			),
+			None => (
+				AlertRuleState {
+					breach_started: Some(breach_started),
+					recovery_started: None,
+					firing_since: None,
+				},
+				None,
+			),
+		}
+	} else {
+		match current.firing_since {
+			Some(firing_since) => {
+				let recovery_started = current.recovery_started.unwrap_or(now);
+				if now - recovery_started >= resolve_duration {
+					(
+						AlertRuleState::default(),
+						Some(DeploymentAlertState::Resolved),
+					)
+				} else {
+					(
+						AlertRuleState {
+							breach_started: None,
+							recovery_started: Some(recovery_started),
+							firing_since: Some(firing_since),
+						},
+						None,
+					)
+				}
+			}
+			None => (AlertRuleState::default(), None),
+		}
+	}
+}
+
+/// Fetches the current usage of a deployment from Mimir. The CPU usage is in
+/// cores and the memory usage is in bytes. Returns `None` if there is no data
+/// for the deployment, which is the case when it isn't running.
+async fn fetch_usage(
+	client: &reqwest::Client,
+	state: &AppState,
+	workspace_id: Uuid,
+	deployment_id: Uuid,
+	metric: DeploymentAlertMetric,
+) -> Result<Option<f64>, ErrorType> {
+	let query = match metric {
+		DeploymentAlertMetric::CpuUsage => format!(
+			"sum(rate(container_cpu_usage_seconds_total{{deployment_id=\"{}\"}}[2m]))",
+			deployment_id
+		),
+		DeploymentAlertMetric::MemoryUsage => format!(
+			"sum(container_memory_working_set_bytes{{deployment_id=\"{}\"}})",
+			deployment_id
+		),
+	};
+
+	let response = client
+		.get(format!(
+			"{}/prometheus/api/v1/query",
+			state.config.opentelemetry.metrics.endpoint
+		))
+		.query(&[("query", query)])
+		.basic_auth(
+			&state.config.opentelemetry.metrics.username,
+			Some(&state.config.opentelemetry.metrics.password),
+		)
+		.header(
+			HeaderName::from_static("x-scope-orgid"),
+			HeaderValue::from_str(&workspace_id.to_string()).map_err(ErrorType::server_error)?,
+		)
+		.send()
+		.await?
+		.error_for_status()?
+		.json::<MimirResponse>()
+		.await?;
+
+	let Some(MimirVectorResult { value: (_, value) }) = response.data.result.into_iter().next()
+	else {
+		return Ok(None);
+	};
+
+	value
+		.parse::<f64>()
+		.map(Some)
+		.map_err(ErrorType::server_error)
+}
+
+/// Sends the notification of an alert to its channel
+async fn send_notification(
+	state: &AppState,
+	channel: &AlertNotificationChannel,
+	notification: &DeploymentAlertNotification,
+) -> Result<(), ErrorType> {
+	match channel {
+		AlertNotificationChannel::Email { email } => {
+			let (subject, body) = notification_email(notification);
+			email::send_email(&state.config.email, email, &subject, body).await?;
+		}
+		AlertNotificationChannel::Webhook { url } => {
+			let url = reqwest::Url::parse(url).map_err(ErrorType::server_error)?;
+			webhook_client(&url)
+				.await?
+				.post(url)
+				.json(notification)
+				.send()
+				.await?
+				.error_for_status()?;
+		}
+	}
+
+	Ok(())
+}
+
+/// The subject and the body of the email that notifies that an alert fired or
+/// was resolved
+fn notification_email(notification: &DeploymentAlertNotification) -> (String, String) {
+	let metric = match notification.metric {
+		DeploymentAlertMetric::CpuUsage => "CPU usage",
+		DeploymentAlertMetric::MemoryUsage => "memory usage",
+	};
+
+	match notification.state {
+		DeploymentAlertState::Fired => (
+			format!(
+				"Alert fired for deployment {}",
+				notification.deployment_name
+			),
+			format!(
+				"The {metric} of deployment {} is at {:.1}%, above the threshold of {}% \
+				of its alert rule, since {}.",
+				notification.deployment_name,
+				notification.value,
+				notification.threshold,
+				notification.timestamp,
+			),
+		),
+		DeploymentAlertState::Resolved => (
+			format!(
+				"Alert resolved for deployment {}",
+				notification.deployment_name
+			),
+			format!(
+				"The {metric} of deployment {} is back at {:.1}%, below the threshold of {}% \
+				of its alert rule, since {}.",
+				notification.deployment_name,
+				notification.value,
+				notification.threshold,
+				notification.timestamp,
+			),
+		),
+	}
+}
+
+/// Creates the client used to send a notification to a webhook. Webhooks are
+/// only sent to public addresses, so that alert rules can't be used to make
+/// requests to the internal network of the API. The host of the webhook is
+/// resolved once, and the client is pinned to the addresses it resolved to,
+/// so that the host can't resolve to another address by the time the request
+/// is made. Redirects aren't followed, since they could point anywhere.
+async fn webhook_client(url: &reqwest::Url) -> Result<reqwest::Client, ErrorType> {
+	let (Some(host), Some(port)) = (url.host_str(), url.port_or_known_default()) else {
+		return Err(ErrorType::server_error("webhook URL has no host"));
+	};
+
+	let addresses = tokio::net::lookup_host((host, port))
+		.await
+		.map_err(ErrorType::server_error)?
+		.collect::<Vec<_>>();
+	if addresses.is_empty() ||
+		!addresses
+			.iter()
+			.all(|address| is_public_address(address.ip()))
+	{
+		return Err(ErrorType::server_error(format!(
+			"webhook host `{host}` does not resolve to a public address"
+		)));
+	}
+
+	reqwest::Client::builder()
+		.timeout(constants::DEPLOYMENT_ALERT_WEBHOOK_TIMEOUT.unsigned_abs())
+		.redirect(reqwest::redirect::Policy::none())
+		.resolve_to_addrs(host, &addresses)
+		.build()
+		.map_err(ErrorType::server_error)
+}
+
+/// Checks if an address is reachable on the public internet, and isn't a
+/// loopback, private, link-local, shared, documentation or otherwise reserved
+/// address. IPv4 addresses mapped to IPv6 are checked as IPv4 addresses.
+fn is_public_address(address: IpAddr) -> bool {
+	match address {
+		IpAddr::V4(address) => is_public_ipv4_address(address),
+		IpAddr::V6(address) => {
+			if let Some(mapped) = address.to_ipv4_mapped() {
+				return is_public_ipv4_address(mapped);
+			}
+			let [first, second, ..] = address.segments();
+			!(address.is_unspecified() ||
+				address.is_loopback() ||
+				address.is_multicast() ||
+				// Unique local addresses (fc00::/7)
+				(first & 0xfe00) == 0xfc00 ||
+				// Link-local addresses (fe80::/10)
+				(first & 0xffc0) == 0xfe80 ||
+				// IPv4-compatible and NAT64 addresses, which can reach
+				// IPv4 addresses that aren't public
+				address.to_ipv4().is_some() ||
+				(first == 0x64 && second == 0xff9b) ||
+				// Documentation addresses (2001:db8::/32)
+				(first == 0x2001 && second == 0xdb8))
+		}
+	}
+}
+
+/// Checks if an IPv4 address is reachable on the public internet
+fn is_public_ipv4_address(address: Ipv4Addr) -> bool {
+	let [first, second, ..] = address.octets();
+	!(address.is_unspecified() ||
+		address.is_loopback() ||
+		address.is_private() ||
+		address.is_link_local() ||
+		address.is_broadcast() ||
+		address.is_documentation() ||
+		address.is_multicast() ||
+		// "This network" (0.0.0.0/8)
+		first == 0 ||
+		// Shared address space (100.64.0.0/10)
+		(first == 100 && (second & 0xc0) == 64) ||
+		// Benchmarking (198.18.0.0/15)
+		(first == 198 && (second & 0xfe) == 18) ||
+		// Reserved (240.0.0.0/4)
+		first >= 240)
+}
+
+#[cfg(test)]
+mod tests {
+	use models::api::workspace::deployment::alert_rule::DeploymentAlertState;
+	use time::{Duration, OffsetDateTime};
+
+	use super::{is_public_address, next_alert_state, AlertRuleState};
+
+	const DURATION: Duration = Duration::minutes(5);
+	const RESOLVE_DURATION: Duration = Duration::minutes(10);
+
+	/// Evaluates the given breaches one minute apart, returning the final state
+	/// and the transitions that happened along the way
+	fn evaluate(
+		state: AlertRuleState,
+		breaches: &[bool],
+	) -> (AlertRuleState, Vec<Option<DeploymentAlertState>>) {
+		let mut now = OffsetDateTime::UNIX_EPOCH;
+		let mut state = state;
+		let mut transitions = Vec::new();
+
+		for &breached in breaches {
+			let (next, transition) =
+				next_alert_state(state, breached, now, DURATION, RESOLVE_DURATION);
+			state = next;
+			transitions.push(transition);
+			now += Duration::minutes(1);
+		}
+
+		(state, transitions)
+	}
+
+	#[test]
+	fn fires_once_after_the_duration() {
+		let (state, transitions) = evaluate(AlertRuleState::default(), &[true; 10]);
+
+		assert_eq!(
+			transitions
+				.iter()
+				.filter(|transition| transition.is_some())
+				.count(),
+			1
+		);
+		assert_eq!(transitions[5], Some(DeploymentAlertState::Fired));
+		assert_eq!(
+			state.firing_since,
+			Some(OffsetDateTime::UNIX_EPOCH + Duration::minutes(5))
+		);
+	}
+
+	#[test]
+	fn short_breaches_do_not_fire() {
+		let (state, transitions) = evaluate(
+			AlertRuleState::default(),
+			&[true, true, true, false, true, true, true, false],
+		);
+
+		assert!(transitions.iter().all(Option::is_none));
+		assert_eq!(state, AlertRuleState::default());
+	}
+
+	#[test]
+	fn resolves_once_after_the_resolve_duration() {
+		let (firing, _) = evaluate(AlertRuleState::default(), &[true; 6]);
+		assert!(firing.firing_since.is_some());
+
+		// A breach in the middle of the recovery restarts it
+		let mut breaches = vec![false; 5];
+		breaches.push(true);
+		breaches.extend([false; 11]);
+		let (state, transitions) = evaluate(firing, &breaches);
+
+		assert_eq!(
+			transitions
+				.iter()
+				.filter(|transition| transition.is_some())
+				.count(),
+			1
+		);
+		assert_eq!(transitions[16], Some(DeploymentAlertState::Resolved));
+		assert_eq!(state, AlertRuleState::default());
+	}
+
+	#[test]
+	fn webhooks_are_only_sent_to_public_addresses() {
+		for address in ["1.1.1.1", "8.8.8.8", "2606:4700:4700::1111"] {
+			assert!(is_public_address(address.parse().unwrap()), "{address}");
+		}
+		for address in [
+			"0.0.0.0",
+			"127.0.0.1",
+			"10.0.0.1",
+			"172.16.0.1",
+			"192.168.1.1",
+			"169.254.169.254",
+			"100.64.0.1",
+			"255.255.255.255",
+			"::",
+			"::1",
+			"::ffff:127.0.0.1",
+			"::ffff:169.254.169.254",
+			"64:ff9b::a00:1",
+			"fd00::1",
+			"fe80::1",
+		] {
+			assert!(!is_public_address(address.parse().unwrap()), "{address}");
+		}
+	}
+}
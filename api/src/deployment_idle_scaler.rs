@@ -0,0 +1,145 @@
+use std::pin::pin;
+
+use futures::future::Either;
+use models::api::workspace::runner::StreamRunnerDataForWorkspaceServerMsg;
+use rustis::commands::{SetCondition, SetExpiration, StringCommands};
+use time::OffsetDateTime;
+
+use crate::{models::deployment_event::DeploymentEventType, prelude::*, utils::runner};
+
+/// Runs a background task that scales the deployments that have not received
+/// any requests for their `scale_to_zero_after` period to zero. The requests
+/// are reported by the ingress through the deployment activity endpoint, and a
+/// deployment that never received a request is idle since it was last updated.
+/// The deployments are marked as [`Cold`][1] and their runners are asked to
+/// reconcile them, which removes all their replicas. The next request to a
+/// cold deployment starts it again. A lock in Redis is held for each interval,
+/// so that running multiple instances of the API doesn't scale the same
+/// deployments more than once.
+///
+/// [1]: models::api::workspace::deployment::DeploymentStatus::Cold
+#[instrument(skip(state))]
+pub async fn run(state: &AppState) {
+	let mut interval =
+		tokio::time::interval(constants::DEPLOYMENT_IDLE_SCALER_INTERVAL.unsigned_abs());
+
+	let mut exit_signal = pin!(crate::exit_signal());
+
+	loop {
+		let Either::Right(_) =
+			futures::future::select(&mut exit_signal, pin!(interval.tick())).await
+		else {
+			// Left branch is the exit signal
+			info!("Received SIGINT, stopping deployment idle scaler");
+			break;
+		};
+
+		if let Err(err) = scale_idle_deployments(state).await {
+			warn!("Failed to scale idle deployments to zero: {err}");
+		}
+	}
+}
+
+/// Scales all the deployments that have been idle for long enough to zero
+#[instrument(skip(state))]
+async fn scale_idle_deployments(state: &AppState) -> Result<(), ErrorType> {
+	// Claim the interval, so that other instances of the API don't scale the
+	// same deployments
+	let claimed: bool = state
+		.redis
+		.get()
+		.set_with_options(
+			redis::keys::deployment_idle_scale_lock(),
+			"",
+			SetCondition::NX,
+			SetExpiration::Ex(
+				constants::DEPLOYMENT_IDLE_SCALER_INTERVAL
+					.whole_seconds()
+					.unsigned_abs(),
+			),
+			false,
+		)
+		.await
+		.map_err(ErrorType::server_error)?;
+	if !claimed {
+		return Ok(());
+	}
+
+	let now = OffsetDateTime::now_utc();
+	let mut database = state.database.begin().await?;
+
+	let deployments = query!(
+		r#"
+		UPDATE
+			deployment
+		SET
+			status = 'cold',
+			reconciliation_status = 'pending',
+			reconciliation_generation = reconciliation_generation + 1,
+			updated = $1
+		WHERE
+			scale_to_zero_after IS NOT NULL AND
+			status IN ('deploying', 'starting', 'running', 'degraded') AND
+			COALESCE(last_request_at, updated) <
+				$1 - MAKE_INTERVAL(secs => scale_to_zero_after) AND
+			deleted IS NULL
+		RETURNING
+			id,
+			workspace_id,
+			runner;
+		"#,
+		now,
+	)
+	.fetch_all(&mut *database)
+	.await?;
+
+	for deployment in &deployments {
+		info!("Scaling idle deployment `{}` to zero", deployment.id);
+
+		query!(
+			r#"
+			INSERT INTO
+				deployment_event(
+					id,
+					deployment_id,
+					event_type,
+					created
+				)
+			VALUES
+				($1, $2, $3, $4);
+			"#,
+			Uuid::new_v4() as _,
+			deployment.id as _,
+			DeploymentEventType::ScaledToZero as _,
+			now,
+		)
+		.execute(&mut *database)
+		.await?;
+	}
+
+	database.commit().await?;
+
+	// The deployments are already marked as pending reconciliation, so failing
+	// to reach a runner here doesn't affect the other deployments
+	let runner_config = state.reloadable_config.load().runner.clone();
+	for deployment in deployments {
+		if let Err(err) = runner::send_message(
+			&state.redis.get(),
+			&runner_config,
+			deployment.workspace_id.into(),
+			deployment.runner.into(),
+			&StreamRunnerDataForWorkspaceServerMsg::DeploymentReconciliationRequested {
+				id: deployment.id.into(),
+			},
+		)
+		.await
+		{
+			warn!(
+				"Failed to ask the runner to scale deployment `{}` to zero: {err}",
+				deployment.id
+			);
+		}
+	}
+
+	Ok(())
+}
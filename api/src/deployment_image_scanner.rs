@@ -0,0 +1,474 @@
+use std::pin::pin;
+
+use futures::future::Either;
+use models::api::workspace::deployment::PatrRegistry;
+use rustis::{client::Client as RedisClient, commands::SetCommands};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::{
+	prelude::*,
+	utils::{
+		config::{AppConfig, ImageScannerBackend},
+		image_scanner::{AppImageScanner, ImageScanner, ScannedImage},
+	},
+};
+
+/// A request to scan an image of a deployment, queued in Redis until the
+/// image scanner picks it up
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageScanRequest {
+	/// The deployment that the image belongs to
+	pub deployment_id: Uuid,
+	/// The image to scan, including its tag or digest
+	pub image: String,
+}
+
+/// Runs a background task that scans the images that were requested to be
+/// scanned, and stores the results for the deployments they belong to. Scans
+/// can take minutes, so they are never run while handling a request, which
+/// would hold a database transaction open for as long as the scan takes.
+/// Requests are queued in Redis, so that they are kept even if the request
+/// that queued them fails, and each one is claimed before it is scanned, so
+/// that running multiple instances of the API doesn't scan an image twice.
+#[instrument(skip(state))]
+pub async fn run(state: &AppState) {
+	let mut interval =
+		tokio::time::interval(constants::DEPLOYMENT_IMAGE_SCANNER_INTERVAL.unsigned_abs());
+
+	let mut exit_signal = pin!(crate::exit_signal());
+
+	loop {
+		let Either::Right(_) =
+			futures::future::select(&mut exit_signal, pin!(interval.tick())).await
+		else {
+			// Left branch is the exit signal
+			info!("Received SIGINT, stopping deployment image scanner");
+			break;
+		};
+
+		if let Err(err) = scan_requested_images(state).await {
+			warn!("Failed to scan the requested images: {err}");
+		}
+	}
+}
+
+/// Queues an image of a deployment to be scanned by the image scanner. Asking
+/// for the same image to be scanned again before it is picked up does nothing.
+pub async fn request_scan(
+	redis: &RedisClient,
+	request: &ImageScanRequest,
+) -> Result<(), ErrorType> {
+	redis
+		.sadd(
+			redis::keys::deployment_image_scan_requests(),
+			serde_json::to_string(request).map_err(ErrorType::server_error)?,
+		)
+		.await
+		.map_err(ErrorType::server_error)?;
+
+	Ok(())
+}
+
+/// The image that a deployment runs, in the same form that the runners pull
+/// it in
+pub struct DeploymentImage {
+	/// The image, including its tag
+	pub image: String,
+	/// The digest that the tag of the image points to, if it is known. Only
+	/// the tags of images in the Patr registry can be resolved by the API
+	pub digest: Option<String>,
+}
+
+/// Gets the image that a deployment currently runs, or the image of its canary
+pub async fn get_deployment_image(
+	connection: &mut DatabaseConnection,
+	workspace_id: Uuid,
+	deployment_id: Uuid,
+	canary: bool,
+) -> Result<DeploymentImage, ErrorType> {
+	let deployment = query!(
+		r#"
+		SELECT
+			deployment.registry,
+			deployment.repository_id,
+			deployment.image_name,
+			deployment.image_tag,
+			deployment.canary_image_tag,
+			deployment.current_live_digest,
+			container_registry_repository.name as "repository_name?"
+		FROM
+			deployment
+		LEFT JOIN
+			container_registry_repository
+		ON
+			container_registry_repository.id = deployment.repository_id
+		WHERE
+			deployment.id = $1 AND
+			deployment.workspace_id = $2 AND
+			deployment.deleted IS NULL;
+		"#,
+		deployment_id as _,
+		workspace_id as _,
+	)
+	.fetch_optional(&mut *connection)
+	.await?
+	.or_not_found()?;
+
+	let tag = if canary {
+		deployment.canary_image_tag.or_not_found()?
+	} else {
+		deployment.image_tag
+	};
+
+	let (image_name, digest) = if deployment.registry == PatrRegistry.to_string() {
+		let digest = match deployment.repository_id {
+			Some(repository_id) => query!(
+				r#"
+				SELECT
+					manifest_digest
+				FROM
+					container_registry_repository_tag
+				WHERE
+					repository_id = $1 AND
+					tag = $2;
+				"#,
+				repository_id as _,
+				tag,
+			)
+			.fetch_optional(&mut *connection)
+			.await?
+			.map(|row| row.manifest_digest),
+			None => None,
+		};
+		// The digest that the deployment was last pinned to is only used for
+		// its own image tag, and only if the tag isn't known to the
+		// registry
+		let digest = if canary {
+			digest
+		} else {
+			digest.or(deployment.current_live_digest)
+		};
+		(deployment.repository_name, digest)
+	} else {
+		(deployment.image_name, None)
+	};
+	let image_name = image_name.ok_or_else(|| {
+		ErrorType::server_error(format!("deployment `{deployment_id}` has no image"))
+	})?;
+
+	Ok(DeploymentImage {
+		image: format!("{}/{}:{}", deployment.registry, image_name, tag),
+		digest,
+	})
+}
+
+/// Queues the current image of a deployment, or the image of its canary, to
+/// be scanned
+pub async fn request_deployment_scan(
+	connection: &mut DatabaseConnection,
+	redis: &RedisClient,
+	workspace_id: Uuid,
+	deployment_id: Uuid,
+	canary: bool,
+) -> Result<(), ErrorType> {
+	let DeploymentImage { image, .. } =
+		get_deployment_image(connection, workspace_id, deployment_id, canary).await?;
+
+	request_scan(
+		redis,
+		&ImageScanRequest {
+			deployment_id,
+			image,
+		},
+	)
+	.await
+}
+
+/// Checks that the image of a deployment, or the image of its canary, can be
+/// deployed under the policy of its workspace. If the workspace blocks images
+/// with critical vulnerabilities, the image has to have been scanned already:
+/// a scan of the same digest is used if the digest of the image is known, and
+/// a recent scan of the same tag otherwise, since a tag can be moved to a
+/// different image at any time. If there is no such scan, a scan is queued and
+/// the deploy fails with [`ErrorType::ImageScanPending`], to be retried once
+/// the scan has finished. Images are never scanned here, since a scan can take
+/// minutes and this runs inside the transaction of the deploy.
+pub async fn ensure_image_allowed(
+	connection: &mut DatabaseConnection,
+	redis: &RedisClient,
+	config: &AppConfig,
+	workspace_id: Uuid,
+	deployment_id: Uuid,
+	canary: bool,
+	now: OffsetDateTime,
+) -> Result<(), ErrorType> {
+	let block_critical_vulnerabilities = query!(
+		r#"
+		SELECT
+			block_critical_vulnerabilities
+		FROM
+			workspace
+		WHERE
+			id = $1;
+		"#,
+		workspace_id as _,
+	)
+	.fetch_optional(&mut *connection)
+	.await?
+	.or_not_found()?
+	.block_critical_vulnerabilities;
+
+	if !block_critical_vulnerabilities {
+		return Ok(());
+	}
+
+	// Without a scanner, there is no way to tell if the image is safe to deploy
+	if matches!(config.image_scan.scanner, ImageScannerBackend::Disabled) {
+		return Err(ErrorType::ImageScanFailed);
+	}
+
+	let DeploymentImage { image, digest } =
+		get_deployment_image(connection, workspace_id, deployment_id, canary).await?;
+
+	let scan = query!(
+		r#"
+		SELECT
+			id
+		FROM
+			deployment_image_scan
+		WHERE
+			deployment_id = $1 AND
+			(
+				image_digest = $2 OR
+				(
+					image = $3 AND
+					scanned > $4 AND
+					(
+						$2 IS NULL OR
+						image_digest IS NULL
+					)
+				)
+			)
+		ORDER BY
+			scanned DESC
+		LIMIT 1;
+		"#,
+		deployment_id as _,
+		digest,
+		image,
+		now - constants::IMAGE_TAG_SCAN_TTL,
+	)
+	.fetch_optional(&mut *connection)
+	.await?;
+
+	let Some(scan) = scan else {
+		info!("Image `{image}` of deployment `{deployment_id}` has not been scanned yet");
+		request_scan(
+			redis,
+			&ImageScanRequest {
+				deployment_id,
+				image,
+			},
+		)
+		.await?;
+		return Err(ErrorType::ImageScanPending);
+	};
+
+	let critical = query!(
+		r#"
+		SELECT
+			COUNT(*) as "count!"
+		FROM
+			deployment_image_vulnerability
+		WHERE
+			scan_id = $1 AND
+			severity = 'critical';
+		"#,
+		scan.id as _,
+	)
+	.fetch_one(&mut *connection)
+	.await?
+	.count;
+
+	if critical > 0 {
+		info!("Blocking deploy of `{image}`, which has {critical} critical vulnerabilities");
+		return Err(ErrorType::ImageScanBlocked);
+	}
+
+	Ok(())
+}
+
+/// Scans all the images that are queued to be scanned. A scan that fails is
+/// not retried, since it is requested again the next time the image is
+/// deployed.
+async fn scan_requested_images(state: &AppState) -> Result<(), ErrorType> {
+	let requests: Vec<String> = state
+		.redis
+		.get()
+		.smembers(redis::keys::deployment_image_scan_requests())
+		.await
+		.map_err(ErrorType::server_error)?;
+	if requests.is_empty() {
+		return Ok(());
+	}
+
+	let scanner = AppImageScanner::new(&state.current_config().image_scan);
+
+	for request in requests {
+		// Claim the request, so that other instances of the API don't scan the
+		// same image
+		let claimed: usize = state
+			.redis
+			.get()
+			.srem(redis::keys::deployment_image_scan_requests(), &request)
+			.await
+			.map_err(ErrorType::server_error)?;
+		if claimed == 0 {
+			continue;
+		}
+
+		let request = match serde_json::from_str::<ImageScanRequest>(&request) {
+			Ok(request) => request,
+			Err(err) => {
+				warn!("Ignoring invalid image scan request `{request}`: {err}");
+				continue;
+			}
+		};
+
+		if let Err(err) = scan_image(state, &scanner, &request).await {
+			warn!(
+				"Failed to scan image `{}` of deployment `{}`: {err}",
+				request.image, request.deployment_id
+			);
+		}
+	}
+
+	Ok(())
+}
+
+/// Scans an image of a deployment, and stores the result along with the
+/// previous scans of the deployment. Only the latest
+/// [`constants::MAX_IMAGE_SCANS_PER_DEPLOYMENT`] scans of each deployment are
+/// kept, which is enough to cover the images of both its primary replicas and
+/// its canary.
+async fn scan_image(
+	state: &AppState,
+	scanner: &AppImageScanner,
+	ImageScanRequest {
+		deployment_id,
+		image,
+	}: &ImageScanRequest,
+) -> Result<(), ErrorType> {
+	info!("Scanning image `{image}` of deployment `{deployment_id}`");
+
+	let ScannedImage {
+		digest,
+		mut vulnerabilities,
+	} = scanner.scan(image).await?;
+	vulnerabilities.sort_by(|a, b| b.severity.cmp(&a.severity).then_with(|| a.id.cmp(&b.id)));
+	vulnerabilities.dedup_by(|a, b| a.id == b.id && a.package == b.package);
+
+	let mut database = state.database.begin().await?;
+
+	let deployment = query!(
+		r#"
+		SELECT
+			id
+		FROM
+			deployment
+		WHERE
+			id = $1 AND
+			deleted IS NULL
+		FOR SHARE;
+		"#,
+		deployment_id as _,
+	)
+	.fetch_optional(&mut *database)
+	.await?;
+	if deployment.is_none() {
+		debug!("Deployment `{deployment_id}` was deleted while its image was scanned");
+		return Ok(());
+	}
+
+	let scan_id = Uuid::new_v4();
+	query!(
+		r#"
+		INSERT INTO
+			deployment_image_scan(
+				id,
+				deployment_id,
+				image,
+				image_digest,
+				scanned
+			)
+		VALUES
+			($1, $2, $3, $4, $5);
+		"#,
+		scan_id as _,
+		deployment_id as _,
+		image,
+		digest,
+		OffsetDateTime::now_utc(),
+	)
+	.execute(&mut *database)
+	.await?;
+
+	for vulnerability in &vulnerabilities {
+		query!(
+			r#"
+			INSERT INTO
+				deployment_image_vulnerability(
+					scan_id,
+					vulnerability_id,
+					package,
+					installed_version,
+					fixed_version,
+					severity,
+					title
+				)
+			VALUES
+				($1, $2, $3, $4, $5, $6, $7);
+			"#,
+			scan_id as _,
+			vulnerability.id,
+			vulnerability.package,
+			vulnerability.installed_version,
+			vulnerability.fixed_version,
+			vulnerability.severity as _,
+			vulnerability.title,
+		)
+		.execute(&mut *database)
+		.await?;
+	}
+
+	// The vulnerabilities of the older scans are deleted along with them
+	query!(
+		r#"
+		DELETE FROM
+			deployment_image_scan
+		WHERE
+			deployment_id = $1 AND
+			id NOT IN (
+				SELECT
+					id
+				FROM
+					deployment_image_scan
+				WHERE
+					deployment_id = $1
+				ORDER BY
+					scanned DESC
+				LIMIT $2
+			);
+		"#,
+		deployment_id as _,
+		constants::MAX_IMAGE_SCANS_PER_DEPLOYMENT,
+	)
+	.execute(&mut *database)
+	.await?;
+
+	database.commit().await?;
+
+	Ok(())
+}
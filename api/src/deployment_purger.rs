@@ -0,0 +1,321 @@
+use std::pin::pin;
+
+use futures::future::Either;
+use rustis::commands::{SetCondition, SetExpiration, StringCommands};
+use time::OffsetDateTime;
+
+use crate::prelude::*;
+
+/// Runs a background task that permanently removes the deployments that were
+/// deleted more than [`constants::DEPLOYMENT_RESTORE_GRACE_PERIOD`] ago, along
+/// with all their configuration. A lock in Redis is held for each interval, so
+/// that running multiple instances of the API doesn't purge the same
+/// deployments concurrently.
+#[instrument(skip(state))]
+pub async fn run(state: &AppState) {
+	let mut interval = tokio::time::interval(constants::DEPLOYMENT_PURGE_INTERVAL.unsigned_abs());
+
+	let mut exit_signal = pin!(crate::exit_signal());
+
+	loop {
+		let Either::Right(_) =
+			futures::future::select(&mut exit_signal, pin!(interval.tick())).await
+		else {
+			// Left branch is the exit signal
+			info!("Received SIGINT, stopping deployment purger");
+			break;
+		};
+
+		if let Err(err) = purge_expired_deployments(state).await {
+			warn!("Failed to purge deleted deployments: {err}");
+		}
+	}
+}
+
+/// The time before which deleted deployments can no longer be restored, and are
+/// purged instead
+pub fn purge_cutoff(now: OffsetDateTime) -> OffsetDateTime {
+	now - constants::DEPLOYMENT_RESTORE_GRACE_PERIOD
+}
+
+/// Permanently removes all the deployments whose grace period is over. Each
+/// deployment is removed in its own transaction, so that a deployment that
+/// can't be removed doesn't prevent the others from being removed.
+async fn purge_expired_deployments(state: &AppState) -> Result<(), ErrorType> {
+	// Claim the interval, so that other instances of the API don't purge the
+	// same deployments
+	let claimed: bool = state
+		.redis
+		.get()
+		.set_with_options(
+			redis::keys::deployment_purge_lock(),
+			"",
+			SetCondition::NX,
+			SetExpiration::Ex(
+				constants::DEPLOYMENT_PURGE_INTERVAL
+					.whole_seconds()
+					.unsigned_abs(),
+			),
+			false,
+		)
+		.await
+		.map_err(ErrorType::server_error)?;
+	if !claimed {
+		return Ok(());
+	}
+
+	let mut database = state.database.begin().await?;
+
+	let deployments = query!(
+		r#"
+		SELECT
+			id
+		FROM
+			deployment
+		WHERE
+			deleted IS NOT NULL AND
+			deleted < $1;
+		"#,
+		purge_cutoff(state.clock.now()),
+	)
+	.fetch_all(&mut *database)
+	.await?;
+
+	database.commit().await?;
+
+	for deployment in deployments {
+		let deployment_id = Uuid::from(deployment.id);
+
+		let mut database = state.database.begin().await?;
+		if let Err(err) = purge_deployment(&mut *database, deployment_id).await {
+			warn!("Failed to purge deployment `{deployment_id}`: {err}");
+			database.rollback().await?;
+			continue;
+		}
+		database.commit().await?;
+
+		info!("Deployment `{deployment_id}` purged");
+	}
+
+	Ok(())
+}
+
+/// Permanently removes a deleted deployment and all its configuration from the
+/// database. The resource of the deployment is kept, marked as deleted.
+#[instrument(skip(connection))]
+async fn purge_deployment(
+	connection: &mut DatabaseConnection,
+	deployment_id: Uuid,
+) -> Result<(), ErrorType> {
+	query!(
+		r#"
+		DELETE FROM
+			deployment_environment_variable
+		WHERE
+			deployment_id = $1;
+		"#,
+		deployment_id as _
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		DELETE FROM
+			deployment_config_mounts
+		WHERE
+			deployment_id = $1;
+		"#,
+		deployment_id as _
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		DELETE FROM
+			deployment_label
+		WHERE
+			deployment_id = $1;
+		"#,
+		deployment_id as _
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	// Deleted deployments that depended on this deployment can't be restored
+	// with the dependency anymore
+	query!(
+		r#"
+		DELETE FROM
+			deployment_dependency
+		WHERE
+			deployment_id = $1 OR
+			dependency_id = $1;
+		"#,
+		deployment_id as _
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	// Detach the volumes, so that they can be mounted by other deployments
+	query!(
+		r#"
+		DELETE FROM
+			deployment_volume_mount
+		WHERE
+			deployment_id = $1;
+		"#,
+		deployment_id as _
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		DELETE FROM
+			deployment_event
+		WHERE
+			deployment_id = $1;
+		"#,
+		deployment_id as _
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		DELETE FROM
+			deployment_schedule
+		WHERE
+			deployment_id = $1;
+		"#,
+		deployment_id as _
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		DELETE FROM
+			deployment_alert_rule
+		WHERE
+			deployment_id = $1;
+		"#,
+		deployment_id as _
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		DELETE FROM
+			deployment_build
+		WHERE
+			deployment_id = $1;
+		"#,
+		deployment_id as _
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		SET CONSTRAINTS ALL DEFERRED;
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		UPDATE
+			deployment
+		SET
+			current_live_digest = NULL
+		WHERE
+			id = $1;
+		"#,
+		deployment_id as _
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		DELETE FROM
+			deployment_deploy_history
+		WHERE
+			deployment_id = $1;
+		"#,
+		deployment_id as _
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		DELETE FROM
+			deployment_exposed_port
+		WHERE
+			deployment_id = $1;
+		"#,
+		deployment_id as _
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		DELETE FROM
+			deployment
+		WHERE
+			id = $1;
+		"#,
+		deployment_id as _
+	)
+	.execute(&mut *connection)
+	.await
+	.map_err(|err| match err {
+		sqlx::Error::Database(err) if err.is_foreign_key_violation() => ErrorType::ResourceInUse,
+		err => ErrorType::server_error(err),
+	})?;
+
+	query!(
+		r#"
+		SET CONSTRAINTS ALL IMMEDIATE;
+		"#
+	)
+	.execute(&mut *connection)
+	.await
+	.map_err(|err| match err {
+		sqlx::Error::Database(err) if err.is_foreign_key_violation() => ErrorType::ResourceInUse,
+		err => ErrorType::server_error(err),
+	})?;
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use time::{Duration, OffsetDateTime};
+
+	use super::purge_cutoff;
+	use crate::{prelude::*, utils::Clock};
+
+	#[test]
+	fn deployments_are_purged_once_they_cannot_be_restored() {
+		let clock = Clock::stopped_at(OffsetDateTime::UNIX_EPOCH + Duration::days(30));
+		let deleted = clock.now();
+
+		// Restoring and purging are only ever checked against the cutoff with
+		// strict comparisons, so the deployment is never both
+		assert!(deleted > purge_cutoff(clock.now()));
+		clock.advance(constants::DEPLOYMENT_RESTORE_GRACE_PERIOD - Duration::seconds(1));
+		assert!(deleted > purge_cutoff(clock.now()));
+		clock.advance(Duration::seconds(1));
+		assert_eq!(deleted, purge_cutoff(clock.now()));
+		clock.advance(Duration::seconds(1));
+		assert!(deleted < purge_cutoff(clock.now()));
+	}
+}
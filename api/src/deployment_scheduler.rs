@@ -0,0 +1,264 @@
+use std::pin::pin;
+
+use futures::future::Either;
+use models::api::workspace::{
+	deployment::{schedule::ScheduleConflictResolution, DeploymentStatus},
+	runner::StreamRunnerDataForWorkspaceServerMsg,
+};
+use rustis::commands::{SetCondition, SetExpiration, StringCommands};
+use time::{Duration, OffsetDateTime};
+use time_tz::OffsetDateTimeExt;
+
+use crate::{
+	deployment_image_scanner,
+	models::deployment_event::DeploymentEventType,
+	prelude::*,
+	utils::{runner, CronExpression},
+};
+
+/// The action that a schedule performs on a deployment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ScheduledAction {
+	/// The deployment is started
+	Start,
+	/// The deployment is stopped
+	Stop,
+}
+
+/// Runs a background task that evaluates the schedules of all deployments once
+/// every minute, and starts or stops the deployments whose cron expressions
+/// match that minute. Each minute is claimed in Redis before it is evaluated,
+/// so that running multiple instances of the API doesn't perform the same
+/// action more than once.
+#[instrument(skip(state))]
+pub async fn run(state: &AppState) {
+	let mut interval =
+		tokio::time::interval(constants::DEPLOYMENT_SCHEDULER_INTERVAL.unsigned_abs());
+
+	let mut exit_signal = pin!(crate::exit_signal());
+	let mut last_evaluated_minute = None;
+
+	loop {
+		let Either::Right(_) =
+			futures::future::select(&mut exit_signal, pin!(interval.tick())).await
+		else {
+			// Left branch is the exit signal
+			info!("Received SIGINT, stopping deployment scheduler");
+			break;
+		};
+
+		let now = OffsetDateTime::now_utc();
+		let Ok(minute) = now
+			.replace_second(0)
+			.and_then(|now| now.replace_nanosecond(0))
+		else {
+			continue;
+		};
+		if last_evaluated_minute == Some(minute) {
+			continue;
+		}
+		last_evaluated_minute = Some(minute);
+
+		if let Err(err) = evaluate_schedules(state, minute).await {
+			warn!("Failed to evaluate deployment schedules: {err}");
+		}
+	}
+}
+
+/// Evaluates the schedules of all deployments for the given minute. A
+/// deployment is only started if its image is allowed under the image scan
+/// policy of its workspace, the same as when it is started by a user. Once the
+/// changes are committed, the runners of the deployments that were started or
+/// stopped are asked to reconcile them right away.
+#[instrument(skip(state))]
+async fn evaluate_schedules(state: &AppState, minute: OffsetDateTime) -> Result<(), ErrorType> {
+	// Claim the minute, so that other instances of the API don't evaluate it
+	let claimed: bool = state
+		.redis
+		.get()
+		.set_with_options(
+			redis::keys::deployment_schedule_minute_lock(minute.unix_timestamp()),
+			"",
+			SetCondition::NX,
+			SetExpiration::Ex(Duration::hours(1).whole_seconds().unsigned_abs()),
+			false,
+		)
+		.await
+		.map_err(ErrorType::server_error)?;
+	if !claimed {
+		return Ok(());
+	}
+
+	let config = state.current_config();
+	let redis = state.redis.get();
+	let mut database = state.database.begin().await?;
+
+	let schedules = query!(
+		r#"
+		SELECT
+			deployment_schedule.id,
+			deployment_schedule.deployment_id,
+			deployment.workspace_id,
+			deployment.runner,
+			deployment_schedule.start_cron,
+			deployment_schedule.stop_cron,
+			deployment_schedule.timezone,
+			deployment_schedule.conflict_resolution AS "conflict_resolution: ScheduleConflictResolution",
+			deployment.status AS "status: DeploymentStatus"
+		FROM
+			deployment_schedule
+		INNER JOIN
+			deployment
+		ON
+			deployment.id = deployment_schedule.deployment_id
+		WHERE
+			deployment.deleted IS NULL
+		ORDER BY
+			deployment_schedule.created;
+		"#,
+	)
+	.fetch_all(&mut *database)
+	.await?;
+
+	let mut triggered = Vec::new();
+	for schedule in schedules {
+		let Some(timezone) = time_tz::timezones::get_by_name(&schedule.timezone) else {
+			warn!(
+				"Schedule `{}` has an invalid timezone `{}`",
+				schedule.id, schedule.timezone
+			);
+			continue;
+		};
+		let local_minute = minute.to_timezone(timezone);
+
+		let matches = |cron: Option<&str>| {
+			cron.and_then(|cron| cron.parse::<CronExpression>().ok())
+				.is_some_and(|cron| cron.matches(local_minute))
+		};
+
+		let action = match (
+			matches(schedule.start_cron.as_deref()),
+			matches(schedule.stop_cron.as_deref()),
+		) {
+			(true, true) => match schedule.conflict_resolution {
+				ScheduleConflictResolution::StopWins => ScheduledAction::Stop,
+				ScheduleConflictResolution::StartWins => ScheduledAction::Start,
+			},
+			(true, false) => ScheduledAction::Start,
+			(false, true) => ScheduledAction::Stop,
+			(false, false) => continue,
+		};
+
+		let (new_status, event_type) = match (action, schedule.status) {
+			(ScheduledAction::Start, DeploymentStatus::Stopped) => (
+				DeploymentStatus::Deploying,
+				DeploymentEventType::ScheduledStart,
+			),
+			(ScheduledAction::Stop, status)
+				if !matches!(
+					status,
+					DeploymentStatus::Stopped | DeploymentStatus::Created
+				) =>
+			{
+				(
+					DeploymentStatus::Stopped,
+					DeploymentEventType::ScheduledStop,
+				)
+			}
+			// The deployment is already in the state the schedule wants it in
+			_ => continue,
+		};
+
+		if action == ScheduledAction::Start {
+			let allowed = deployment_image_scanner::ensure_image_allowed(
+				&mut *database,
+				&redis,
+				&config,
+				schedule.workspace_id.into(),
+				schedule.deployment_id.into(),
+				false,
+				minute,
+			)
+			.await;
+			if let Err(err) = allowed {
+				warn!(
+					"Schedule `{}` could not start deployment `{}`: {err}",
+					schedule.id, schedule.deployment_id
+				);
+				continue;
+			}
+		}
+
+		info!(
+			"Schedule `{}` triggered {:?} on deployment `{}`",
+			schedule.id, event_type, schedule.deployment_id
+		);
+
+		query!(
+			r#"
+			UPDATE
+				deployment
+			SET
+				status = $1,
+				reconciliation_status = 'pending',
+				reconciliation_generation = reconciliation_generation + 1,
+				updated = NOW()
+			WHERE
+				id = $2;
+			"#,
+			new_status as _,
+			schedule.deployment_id as _,
+		)
+		.execute(&mut *database)
+		.await?;
+
+		query!(
+			r#"
+			INSERT INTO
+				deployment_event(
+					id,
+					deployment_id,
+					event_type,
+					schedule_id,
+					created
+				)
+			VALUES
+				($1, $2, $3, $4, $5);
+			"#,
+			Uuid::new_v4() as _,
+			schedule.deployment_id as _,
+			event_type as _,
+			schedule.id as _,
+			minute,
+		)
+		.execute(&mut *database)
+		.await?;
+
+		triggered.push(schedule);
+	}
+
+	database.commit().await?;
+
+	// The deployments are already marked as pending reconciliation, so failing
+	// to reach a runner here doesn't affect the other deployments
+	for schedule in triggered {
+		if let Err(err) = runner::send_message(
+			&redis,
+			&config.runner,
+			schedule.workspace_id.into(),
+			schedule.runner.into(),
+			&StreamRunnerDataForWorkspaceServerMsg::DeploymentReconciliationRequested {
+				id: schedule.deployment_id.into(),
+			},
+		)
+		.await
+		{
+			warn!(
+				"Failed to ask the runner to reconcile deployment `{}` for schedule `{}`: {err}",
+				schedule.deployment_id, schedule.id
+			);
+		}
+	}
+
+	Ok(())
+}
@@ -2,12 +2,37 @@
 
 //! The main API server for Patr.
 
+/// This module is used to aggregate the API usage of each workspace into the
+/// database in the background, so that usage analytics don't have to be
+/// computed on every request.
+pub mod api_usage_rollup;
 /// This module contains the main application logic. Most of the app requests,
 /// states, and mounting of endpoints are done here
 pub mod app;
+/// This module is used to reload the settings that can be changed without
+/// restarting the API, whenever the API receives a `SIGHUP`.
+pub mod config_reloader;
+/// This module is used to disable the rotated credentials of managed
+/// databases in the background, once their grace period is over.
+pub mod database_credential_revoker;
 /// This module contains the database connection logic, as well as all the
 /// ORM entities.
 pub mod db;
+/// This module is used to evaluate the alert rules of deployments in the
+/// background and send notifications when they fire or are resolved.
+pub mod deployment_alert_evaluator;
+/// This module is used to scale deployments that have not received any
+/// requests for a while to zero in the background.
+pub mod deployment_idle_scaler;
+/// This module is used to scan the images of deployments for vulnerabilities
+/// in the background, outside of any request.
+pub mod deployment_image_scanner;
+/// This module is used to permanently remove deleted deployments in the
+/// background, once they can no longer be restored.
+pub mod deployment_purger;
+/// This module is used to start and stop deployments in the background based
+/// on their schedules.
+pub mod deployment_scheduler;
 /// This module contains the models used by the API. These are the structs that
 /// are used for encoding and decoding things that are not a part of the API
 /// (eg, JWT).
@@ -21,6 +46,9 @@ pub mod redis_publisher;
 /// This module contains the routes for the API. This is where the endpoints
 /// are mounted.
 pub mod routes;
+/// This module is used to report anonymized telemetry about the instance in
+/// the background, if it is enabled in the config.
+pub mod telemetry_reporter;
 /// This module contains all the utilities used by the API. This includes things
 /// like the config parser, the [`tower::Layer`]s that are used to parse the
 /// requests.
@@ -61,7 +89,7 @@ pub mod prelude {
 			UnprocessedAppRequest,
 		},
 		redis,
-		utils::{constants, RouterExt, TimeoutExt},
+		utils::{constants, OptionalRowExt, RouterExt, TimeoutExt},
 	};
 
 	/// The type of the database connection. A mutable reference to this should
@@ -90,22 +118,72 @@ pub mod prelude {
 #[tokio::main]
 #[tracing::instrument]
 async fn main() {
+	use std::sync::Arc;
+
 	use app::AppState;
-	use opentelemetry::{trace::TracerProvider as _, KeyValue};
-	use opentelemetry_otlp::{Protocol, SpanExporter, WithExportConfig};
-	use opentelemetry_sdk::{runtime::Tokio as OtelTokioRuntime, trace::TracerProvider, Resource};
-	use tracing::Level;
+	use arc_swap::ArcSwap;
+	use opentelemetry::{global, trace::TracerProvider as _, KeyValue};
+	use opentelemetry_otlp::{MetricExporter, Protocol, SpanExporter, WithExportConfig};
+	use opentelemetry_sdk::{
+		metrics::{PeriodicReader, SdkMeterProvider},
+		runtime::Tokio as OtelTokioRuntime,
+		trace::TracerProvider,
+		Resource,
+	};
 	use tracing_opentelemetry::OpenTelemetryLayer;
 	use tracing_subscriber::{
 		filter::LevelFilter,
 		fmt::{format::FmtSpan, Layer as FmtLayer},
 		prelude::*,
+		reload::Layer as ReloadLayer,
 	};
 
-	use crate::utils::config::RunningEnvironment;
+	use crate::utils::config::ReloadableConfig;
 
 	let config = utils::config::parse_config();
 
+	// The level filter is reloadable, so that the log levels can be changed
+	// along with the rest of the reloadable config
+	let (max_level_filter, max_level_handle) =
+		ReloadLayer::new(config.logging.max_level_filter(&config.environment));
+
+	// Exporting traces is best-effort, so that the API still runs with only the
+	// local logs if the exporter can't be set up
+	let (opentelemetry_layer, tracing_error) = if config.opentelemetry.enabled {
+		match SpanExporter::builder()
+			.with_tonic()
+			.with_endpoint(&config.opentelemetry.tracing.endpoint)
+			.with_protocol(Protocol::Grpc)
+			.build()
+		{
+			Ok(exporter) => (
+				Some(
+					OpenTelemetryLayer::new(
+						TracerProvider::builder()
+							.with_batch_exporter(exporter, OtelTokioRuntime)
+							.with_resource(Resource::new([KeyValue::new(
+								"service.name",
+								"Patr API",
+							)]))
+							.build()
+							.tracer("Patr API"),
+					)
+					.with_filter(
+						tracing_subscriber::filter::Targets::new()
+							.with_target(env!("CARGO_PKG_NAME"), LevelFilter::TRACE)
+							.with_target("frontend", LevelFilter::TRACE)
+							.with_target("models", LevelFilter::TRACE)
+							.with_target("access_log", LevelFilter::TRACE),
+					),
+				),
+				None,
+			),
+			Err(err) => (None, Some(err)),
+		}
+	} else {
+		(None, None)
+	};
+
 	tracing_subscriber::registry()
 		.with(
 			FmtLayer::new()
@@ -121,40 +199,41 @@ async fn main() {
 					tracing_subscriber::filter::Targets::new()
 						.with_target(env!("CARGO_PKG_NAME"), LevelFilter::TRACE)
 						.with_target("frontend", LevelFilter::TRACE)
-						.with_target("models", LevelFilter::TRACE),
+						.with_target("models", LevelFilter::TRACE)
+						.with_target("access_log", LevelFilter::TRACE),
 				)
-				.with_filter(LevelFilter::from_level(
-					if config.environment == RunningEnvironment::Development {
-						Level::TRACE
-					} else {
-						Level::DEBUG
-					},
-				)),
+				.with_filter(max_level_filter),
 		)
-		.with(
-			OpenTelemetryLayer::new(
-				TracerProvider::builder()
-					.with_batch_exporter(
-						SpanExporter::builder()
-							.with_tonic()
-							.with_endpoint(&config.opentelemetry.tracing.endpoint)
-							.with_protocol(Protocol::Grpc)
-							.build()
-							.expect("Failed to install OpenTelemetry tracing pipeline"),
-						OtelTokioRuntime,
-					)
+		.with(opentelemetry_layer)
+		.init();
+
+	if let Some(err) = tracing_error {
+		tracing::warn!("Unable to set up OpenTelemetry tracing. Traces won't be exported: {err}");
+	}
+
+	if config.opentelemetry.enabled {
+		// Without a meter provider, the metrics are recorded by a no-op meter
+		match MetricExporter::builder()
+			.with_tonic()
+			.with_endpoint(&config.opentelemetry.tracing.endpoint)
+			.with_protocol(Protocol::Grpc)
+			.build()
+		{
+			Ok(exporter) => global::set_meter_provider(
+				SdkMeterProvider::builder()
+					.with_reader(PeriodicReader::builder(exporter, OtelTokioRuntime).build())
 					.with_resource(Resource::new([KeyValue::new("service.name", "Patr API")]))
-					.build()
-					.tracer("Patr API"),
-			)
-			.with_filter(
-				tracing_subscriber::filter::Targets::new()
-					.with_target(env!("CARGO_PKG_NAME"), LevelFilter::TRACE)
-					.with_target("frontend", LevelFilter::TRACE)
-					.with_target("models", LevelFilter::TRACE),
+					.build(),
 			),
-		)
-		.init();
+			Err(err) => {
+				tracing::warn!(
+					"Unable to set up OpenTelemetry metrics. Metrics won't be exported: {err}"
+				);
+			}
+		}
+	} else {
+		tracing::info!("OpenTelemetry is disabled. Traces and metrics will not be exported");
+	}
 
 	tracing::info!("Config parsed. Running in {} mode", config.environment);
 
@@ -165,14 +244,44 @@ async fn main() {
 	let state = AppState {
 		database,
 		redis,
+		reloadable_config: Arc::new(ArcSwap::from_pointee(ReloadableConfig::from_config(
+			&config,
+		))),
 		config,
+		clock: utils::Clock::system(),
 	};
 
 	db::initialize(&state)
 		.await
 		.expect("error initializing database");
 
-	futures::future::join(app::serve(&state), redis_publisher::run(&state)).await;
+	futures::future::join3(
+		futures::future::join5(
+			async {
+				if let Err(err) = app::serve(&state).await {
+					tracing::error!("Error starting the server: {:?}", err);
+					std::process::exit(1);
+				}
+			},
+			redis_publisher::run(&state),
+			api_usage_rollup::run(&state),
+			deployment_scheduler::run(&state),
+			deployment_alert_evaluator::run(&state),
+		),
+		futures::future::join5(
+			database_credential_revoker::run(&state),
+			deployment_purger::run(&state),
+			deployment_idle_scaler::run(&state),
+			telemetry_reporter::run(&state),
+			config_reloader::run(&state, |level| {
+				if let Err(err) = max_level_handle.reload(level) {
+					tracing::error!("Unable to change the log level: {err}");
+				}
+			}),
+		),
+		deployment_image_scanner::run(&state),
+	)
+	.await;
 }
 
 /// Listen for the exit signal and stop the server when the signal is received.
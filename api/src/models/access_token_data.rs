@@ -88,6 +88,33 @@ impl AccessTokenData {
 	/// be considered expired, and the client should handle this by logging out
 	/// the user, or attempting to login again.
 	pub const REFRESH_TOKEN_VALIDITY: Duration = Duration::days(30);
+
+	/// Validate that the JWT was issued by the given issuer, and that the given
+	/// audience is one of the intended recipients of the JWT. This makes sure
+	/// that tokens issued by one instance of Patr are not valid on another.
+	pub fn validate_issuer_and_audience(
+		&self,
+		issuer: &str,
+		audience: &str,
+	) -> Result<(), ErrorType> {
+		if self.iss != issuer {
+			warn!("Invalid JWT issuer: {}", self.iss);
+			return Err(ErrorType::MalformedAccessToken);
+		}
+
+		if !self.aud.clone().into_iter().any(|item| item == audience) {
+			warn!(
+				"Invalid JWT audience: `{}`",
+				match &self.aud {
+					OneOrMore::One(aud) => aud.clone(),
+					OneOrMore::Multiple(aud) => format!("[{}]", aud.join(", ")),
+				}
+			);
+			return Err(ErrorType::MalformedAccessToken);
+		}
+
+		Ok(())
+	}
 }
 
 /// A module to help serialize and deserialize `OffsetDateTime` as seconds
@@ -111,3 +138,73 @@ mod datetime_as_seconds {
 		OffsetDateTime::from_unix_timestamp(i64::deserialize(deserializer)?).map_err(Error::custom)
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use models::{utils::OneOrMore, ErrorType};
+	use time::OffsetDateTime;
+
+	use super::AccessTokenData;
+	use crate::utils::constants;
+
+	fn token_data(iss: &str, aud: OneOrMore<String>) -> AccessTokenData {
+		let now = OffsetDateTime::now_utc();
+		AccessTokenData {
+			iss: iss.to_string(),
+			sub: Default::default(),
+			aud,
+			exp: now + constants::ACCESS_TOKEN_VALIDITY,
+			nbf: now,
+			iat: now,
+			jti: Default::default(),
+		}
+	}
+
+	#[test]
+	fn accepts_configured_issuer_and_audience() {
+		let token = token_data(
+			"https://patr.example.com",
+			OneOrMore::Multiple(vec![
+				"https://other.example.com".to_string(),
+				"https://app.example.com".to_string(),
+			]),
+		);
+
+		assert_eq!(
+			token.validate_issuer_and_audience(
+				"https://patr.example.com",
+				"https://app.example.com"
+			),
+			Ok(())
+		);
+	}
+
+	#[test]
+	fn rejects_mismatched_audience() {
+		let token = token_data(
+			constants::JWT_ISSUER,
+			OneOrMore::One(constants::PATR_JWT_AUDIENCE.to_string()),
+		);
+
+		assert_eq!(
+			token.validate_issuer_and_audience(constants::JWT_ISSUER, "https://app.example.com"),
+			Err(ErrorType::MalformedAccessToken)
+		);
+	}
+
+	#[test]
+	fn rejects_mismatched_issuer() {
+		let token = token_data(
+			constants::JWT_ISSUER,
+			OneOrMore::One(constants::PATR_JWT_AUDIENCE.to_string()),
+		);
+
+		assert_eq!(
+			token.validate_issuer_and_audience(
+				"https://patr.example.com",
+				constants::PATR_JWT_AUDIENCE
+			),
+			Err(ErrorType::MalformedAccessToken)
+		);
+	}
+}
@@ -0,0 +1,84 @@
+use std::net::IpAddr;
+
+use sqlx::types::ipnetwork::IpNetwork;
+use time::OffsetDateTime;
+
+use crate::prelude::*;
+
+/// The type of an event recorded in the auth audit log
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[sqlx(type_name = "AUTH_AUDIT_LOG_EVENT", rename_all = "snake_case")]
+pub enum AuthAuditLogEvent {
+	/// A support operator started an impersonated session for a user
+	ImpersonationStarted,
+	/// A request was made with an impersonated session
+	ImpersonatedRequest,
+	/// A request made with an impersonated session was rejected, since the
+	/// action is not allowed while impersonating a user
+	ImpersonatedRequestDenied,
+}
+
+/// An entry of the auth audit log. The user is the one whose login was used,
+/// and the operator is the support operator impersonating them, if any.
+#[derive(Debug, Clone)]
+pub struct AuthAuditLogEntry {
+	/// The login that the event happened on
+	pub login_id: Uuid,
+	/// The user that the login belongs to
+	pub user_id: Uuid,
+	/// The support operator that is impersonating the user, if any
+	pub operator_id: Option<Uuid>,
+	/// The type of the event
+	pub event: AuthAuditLogEvent,
+	/// The details of the event, such as the endpoint that was requested or
+	/// the reason given for the impersonation
+	pub details: String,
+	/// The IP address that the event originated from
+	pub ip_address: IpAddr,
+	/// When the event happened
+	pub timestamp: OffsetDateTime,
+}
+
+impl AuthAuditLogEntry {
+	/// Writes the entry to the auth audit log
+	pub async fn record(self, connection: &mut DatabaseConnection) -> Result<(), ErrorType> {
+		let Self {
+			login_id,
+			user_id,
+			operator_id,
+			event,
+			details,
+			ip_address,
+			timestamp,
+		} = self;
+
+		query!(
+			r#"
+			INSERT INTO
+				auth_audit_log(
+					id,
+					login_id,
+					user_id,
+					operator_id,
+					event,
+					details,
+					ip_address,
+					timestamp
+				)
+			VALUES
+				(gen_random_uuid(), $1, $2, $3, $4, $5, $6, $7);
+			"#,
+			login_id as _,
+			user_id as _,
+			operator_id as _,
+			event as _,
+			details,
+			IpNetwork::from(ip_address),
+			timestamp,
+		)
+		.execute(&mut *connection)
+		.await?;
+
+		Ok(())
+	}
+}
@@ -0,0 +1,35 @@
+use models::api::workspace::deployment::alert_rule::AlertNotificationChannel;
+
+/// The type of the channel that the notifications of an alert rule are sent
+/// to, as stored in the database along with the target of the channel
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[sqlx(
+	type_name = "DEPLOYMENT_ALERT_NOTIFICATION_CHANNEL",
+	rename_all = "snake_case"
+)]
+pub enum AlertNotificationChannelType {
+	/// The notification is sent by email, and the target is the email address
+	Email,
+	/// The notification is sent to a webhook, and the target is its URL
+	Webhook,
+}
+
+impl AlertNotificationChannelType {
+	/// Builds the notification channel from its type and the target stored
+	/// in the database
+	pub fn into_channel(self, target: String) -> AlertNotificationChannel {
+		match self {
+			Self::Email => AlertNotificationChannel::Email { email: target },
+			Self::Webhook => AlertNotificationChannel::Webhook { url: target },
+		}
+	}
+
+	/// Splits a notification channel into its type and target, so that it can
+	/// be stored in the database
+	pub fn from_channel(channel: AlertNotificationChannel) -> (Self, String) {
+		match channel {
+			AlertNotificationChannel::Email { email } => (Self::Email, email),
+			AlertNotificationChannel::Webhook { url } => (Self::Webhook, url),
+		}
+	}
+}
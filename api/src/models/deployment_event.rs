@@ -0,0 +1,17 @@
+/// The type of an event recorded on a deployment by a background task
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[sqlx(type_name = "DEPLOYMENT_EVENT_TYPE", rename_all = "snake_case")]
+pub enum DeploymentEventType {
+	/// The deployment was started by a schedule
+	ScheduledStart,
+	/// The deployment was stopped by a schedule
+	ScheduledStop,
+	/// An alert rule of the deployment fired
+	AlertFired,
+	/// A firing alert rule of the deployment was resolved
+	AlertResolved,
+	/// The deployment was scaled to zero after not receiving any requests
+	ScaledToZero,
+	/// A deployment that was scaled to zero was started by a request
+	ColdStarted,
+}
@@ -1,4 +1,11 @@
 /// Contains the struct that will be encoded in the JWT of the access token.
 pub mod access_token_data;
+/// Contains the entries of the auth audit log, such as the requests made with
+/// impersonated sessions.
+pub mod auth_audit_log;
+/// Contains the types used to store the alert rules of deployments.
+pub mod deployment_alert;
+/// Contains the types of the events recorded on deployments.
+pub mod deployment_event;
 /// Contains all the structs that will be stored in Redis
 pub mod redis;
@@ -15,3 +15,18 @@ pub struct UserPermissionCache {
 	/// The timestamp when the user's permissions were inserted into Redis
 	pub creation_time: OffsetDateTime,
 }
+
+/// A single request made to the API by a workspace, stored in Redis until it is
+/// aggregated into the database by the API usage rollup task
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiUsageEvent {
+	/// The workspace that the request was made for
+	pub workspace_id: Uuid,
+	/// The endpoint that was called, in the format `METHOD /path`
+	pub endpoint: String,
+	/// The status code of the response
+	pub status_code: u16,
+	/// How long the request took to be processed, in milliseconds
+	pub latency_millis: u64,
+}
@@ -1,3 +1,5 @@
+use std::net::IpAddr;
+
 use crate::prelude::*;
 
 /// The key used to store the permissions for a login ID
@@ -33,11 +35,49 @@ pub fn global_revocation_timestamp() -> String {
 	String::from("globalRevocationTimestamp")
 }
 
-/// The key used to store the mfa secret of a user
+/// The key used to debounce the updates to the last activity of a login. This
+/// key is set once the last activity of the login is written, and while it
+/// exists, the last activity of the login is not written to the database again
+pub fn login_activity_debounce(login_id: &Uuid) -> String {
+	format!("loginActivityDebounce:{}", login_id)
+}
+
+/// The key used to store the (encrypted) TOTP secret of a user while they are
+/// enabling MFA, until it is activated by verifying an OTP
 pub fn user_mfa_secret(user_id: &Uuid) -> String {
 	format!("mfa:{}", user_id)
 }
 
+/// The key used to store the hashes of the recovery codes of a user while they
+/// are enabling MFA, until it is activated by verifying an OTP
+pub fn user_mfa_recovery_codes(user_id: &Uuid) -> String {
+	format!("mfaRecoveryCodes:{}", user_id)
+}
+
+/// The key used to store the ID of the user that an MFA token was issued to,
+/// after their password was verified and until their OTP is verified
+pub fn mfa_login_token(mfa_token: &str) -> String {
+	format!("mfaLoginToken:{}", mfa_token)
+}
+
+/// The key used to count the number of invalid codes that were used to verify
+/// an MFA token
+pub fn mfa_login_attempts(mfa_token: &str) -> String {
+	format!("mfaLoginAttempts:{}", mfa_token)
+}
+
+/// The key used to count the number of accounts that were created from an IP
+/// address in the current signup rate limit window
+pub fn signup_count_for_ip(ip: &IpAddr) -> String {
+	format!("signupCountForIp:{}", ip)
+}
+
+/// The key used to count the number of accounts that were created with a
+/// recovery email on a domain in the current signup rate limit window
+pub fn signup_count_for_email_domain(domain: &str) -> String {
+	format!("signupCountForEmailDomain:{}", domain)
+}
+
 /// The key used to store the Redis lock for a runner. This is used to ensure
 /// that only one connection is allowed to stream data for a runner at a time,
 /// and that the connection is not lost.
@@ -49,3 +89,88 @@ pub fn runner_connection_lock(runner_id: &Uuid) -> String {
 pub fn runner_connection_lock_prefix() -> String {
 	String::from("runnerConnectionLock:")
 }
+
+/// The key used to store the list of API usage events that were recorded in the
+/// bucket starting at the given unix timestamp
+pub fn api_usage_bucket(bucket_start: i64) -> String {
+	format!("apiUsage:{}", bucket_start)
+}
+
+/// The key used to store the set of API usage buckets that have events which
+/// are yet to be aggregated into the database
+pub fn api_usage_pending_buckets() -> String {
+	String::from("apiUsagePendingBuckets")
+}
+
+/// The key used to claim the evaluation of the deployment schedules for the
+/// minute starting at the given unix timestamp, so that only one instance of
+/// the API evaluates it
+pub fn deployment_schedule_minute_lock(minute: i64) -> String {
+	format!("deploymentScheduleMinuteLock:{}", minute)
+}
+
+/// The key used to claim the evaluation of the deployment alert rules for the
+/// minute starting at the given unix timestamp, so that only one instance of
+/// the API evaluates it and sends its notifications
+pub fn deployment_alert_minute_lock(minute: i64) -> String {
+	format!("deploymentAlertMinuteLock:{}", minute)
+}
+
+/// The key used to claim the reporting of telemetry for the current interval,
+/// so that only one instance of the API reports it
+pub fn telemetry_report_lock() -> String {
+	String::from("telemetryReportLock")
+}
+
+/// The key used to store the number of consecutive failed password attempts
+/// for an account, keyed by the (normalized) username of the account. The
+/// account is locked once this reaches the configured limit, until the key
+/// expires
+pub fn failed_login_attempts(username: &str) -> String {
+	format!("failedLoginAttempts:{}", username.to_lowercase())
+}
+
+/// The key used to claim the purging of deleted deployments for the current
+/// interval, so that only one instance of the API purges them
+pub fn deployment_purge_lock() -> String {
+	String::from("deploymentPurgeLock")
+}
+
+/// The key used to claim the scaling of idle deployments to zero for the
+/// current interval, so that only one instance of the API scales them
+pub fn deployment_idle_scale_lock() -> String {
+	String::from("deploymentIdleScaleLock")
+}
+
+/// The key used to store the images of deployments that are waiting to be
+/// scanned for vulnerabilities
+pub fn deployment_image_scan_requests() -> String {
+	String::from("deploymentImageScanRequests")
+}
+
+/// The key used to store the stream of updates of a workspace, which is read
+/// by clients that poll for the updates instead of using a websocket
+pub fn workspace_updates(workspace_id: &Uuid) -> String {
+	format!("workspaceUpdates:{}", workspace_id)
+}
+
+/// The key used to store the ID of the newest update that was trimmed from the
+/// stream of updates of a workspace. Polls with a cursor before it have missed
+/// some of the updates after their cursor
+pub fn workspace_updates_trimmed(workspace_id: &Uuid) -> String {
+	format!("workspaceUpdatesTrimmed:{}", workspace_id)
+}
+
+/// The key used to claim adding a notification from the database to the
+/// updates of its workspace, given the hash of the notification. Every
+/// instance of the API receives every notification, so only the one that
+/// claims it adds it
+pub fn workspace_update_claim(notification_hash: &str) -> String {
+	format!("workspaceUpdateClaim:{}", notification_hash)
+}
+
+/// The channel that the ID of every update of a workspace is published on,
+/// once the update is added to the stream of updates of the workspace
+pub fn workspace_updates_channel(workspace_id: &Uuid) -> String {
+	format!("{}/updates", workspace_id)
+}
@@ -1,18 +1,122 @@
-use rustis::client::Client;
+use std::{
+	cell::RefCell,
+	sync::{
+		atomic::{AtomicUsize, Ordering},
+		Arc,
+	},
+};
+
+use opentelemetry::{
+	global,
+	metrics::{Counter, UpDownCounter},
+};
+use rustis::{
+	client::{Client, IntoConfig},
+	commands::{
+		ExpireOption,
+		GenericCommands,
+		PubSubCommands,
+		StreamCommands,
+		StringCommands,
+		XAddOptions,
+		XTrimOperator,
+		XTrimOptions,
+	},
+};
+use time::OffsetDateTime;
 
 use crate::{prelude::*, utils::config::RedisConfig};
 
 /// A list of all the keys to store data in Redis
 pub mod keys;
 
-/// Connect to a Redis server using the given configuration
+tokio::task_local! {
+	/// The revocation timestamps that were set while handling the current
+	/// request. These are only set once the database transaction of the
+	/// request is committed, so that permissions loaded from the database
+	/// before the commit are revoked as well.
+	static PENDING_REVOCATIONS: RefCell<Vec<String>>;
+
+	/// Set while handling a request whose changes are discarded, such as a
+	/// `HEAD` request. Unlike the database transaction of the request, writes
+	/// to Redis can't be rolled back, so they must be skipped when
+	/// [`is_read_only`] returns true.
+	static READ_ONLY: ();
+}
+
+/// A fixed-size pool of connections to the Redis server. Each connection
+/// multiplexes the commands sent on it, so a connection can be used by any
+/// number of requests at once. The connections are handed out in a round-robin
+/// fashion, so that a slow command only holds up the commands on the same
+/// connection, instead of every command sent by the API.
+///
+/// The number of open connections is exported as the
+/// `redis.client.connections` metric, and the number of commands that timed
+/// out as the `redis.client.command_timeouts` metric.
+#[derive(Clone)]
+pub struct RedisPool {
+	/// The connections in the pool
+	connections: Arc<PoolConnections>,
+	/// The index of the connection to hand out next
+	next: Arc<AtomicUsize>,
+	/// The metric of the number of commands that timed out
+	command_timeouts: Counter<u64>,
+}
+
+impl RedisPool {
+	/// Get a connection from the pool. The connection is shared with other
+	/// users of the pool, and is cheap to clone
+	pub fn get(&self) -> Client {
+		let clients = &self.connections.clients;
+		let index = self.next.fetch_add(1, Ordering::Relaxed) % clients.len();
+		clients[index].clone()
+	}
+
+	/// Record a failed Redis command in the metrics, if it failed because the
+	/// server didn't reply in time
+	pub fn record_error(&self, error: &rustis::Error) {
+		if matches!(error, rustis::Error::Timeout(_)) {
+			self.command_timeouts.add(1, &[]);
+		}
+	}
+}
+
+/// The connections of a [`RedisPool`]. The connections are closed once the
+/// last clone of the pool is dropped, so they are only counted in the
+/// `redis.client.connections` metric until then.
+struct PoolConnections {
+	/// The connections to the Redis server
+	clients: Box<[Client]>,
+	/// The metric of the number of open connections
+	metric: UpDownCounter<i64>,
+}
+
+impl PoolConnections {
+	/// Count the given connections in the metric, until they are dropped
+	fn new(clients: Vec<Client>, metric: UpDownCounter<i64>) -> Self {
+		metric.add(clients.len() as i64, &[]);
+		Self {
+			clients: clients.into(),
+			metric,
+		}
+	}
+}
+
+impl Drop for PoolConnections {
+	fn drop(&mut self) {
+		self.metric.add(-(self.clients.len() as i64), &[]);
+	}
+}
+
+/// Connect to a Redis server using the given configuration, opening as many
+/// connections as the size of the pool
 #[instrument(skip(config))]
-pub async fn connect(config: &RedisConfig) -> Client {
+pub async fn connect(config: &RedisConfig) -> RedisPool {
 	info!(
-		"Connecting to Redis server `{}:{}`",
-		config.host, config.port
+		"Connecting to Redis server `{}:{}` with {} connections",
+		config.host, config.port, config.pool_size
 	);
-	Client::connect(format!(
+	let mut client_config = format!(
 		"{}://{}{}:{}/{}",
 		if config.secure { "rediss" } else { "redis" },
 		if let Some((username, password)) = config.user.as_ref().zip(config.password.as_ref()) {
@@ -23,7 +127,252 @@ pub async fn connect(config: &RedisConfig) -> Client {
 		config.host,
 		config.port,
 		config.database
-	))
+	)
+	.into_config()
+	.expect("Invalid Redis config");
+	client_config.command_timeout = config.command_timeout();
+
+	let clients = futures::future::try_join_all(
+		(0..config.pool_size).map(|_| Client::connect(client_config.clone())),
+	)
 	.await
-	.expect("Failed to connect to Redis")
+	.expect("Failed to connect to Redis");
+
+	let meter = global::meter("Patr API");
+	let connections = PoolConnections::new(
+		clients,
+		meter
+			.i64_up_down_counter("redis.client.connections")
+			.with_description("The number of open connections to the Redis server")
+			.build(),
+	);
+
+	RedisPool {
+		connections: Arc::new(connections),
+		next: Arc::new(AtomicUsize::new(0)),
+		command_timeouts: meter
+			.u64_counter("redis.client.command_timeouts")
+			.with_description("The number of Redis commands that timed out")
+			.build(),
+	}
+}
+
+/// Set the revocation timestamp stored in the given key (one of the
+/// `*_revocation_timestamp` keys) to the given time. Any permissions that were
+/// cached at or before that time, and are covered by the key, are reloaded
+/// from the database on the next request. This must be called whenever the
+/// permissions of a user change, so that the change takes effect immediately.
+///
+/// When called while handling a request, the key is only queued, and the
+/// timestamp is set to the time of the commit of the database transaction of
+/// the request. Permissions that were loaded before the changes of the request
+/// were committed are therefore revoked too. If the request fails, the
+/// revocation is dropped along with the rest of the changes of the request.
+///
+/// The timestamp is kept for a little longer than cached permissions can be
+/// used, so that no cached permissions outlive it.
+pub async fn set_revocation_timestamp(
+	redis: &Client,
+	key: String,
+	now: OffsetDateTime,
+) -> Result<(), ErrorType> {
+	let Some(key) = queue_revocation(key) else {
+		return Ok(());
+	};
+
+	write_revocation_timestamp(redis, &key, now)
+		.await
+		.inspect_err(|err| {
+			error!("Error setting the revocation timestamp: `{}`", err);
+		})?;
+
+	Ok(())
+}
+
+/// Runs the given future while holding back the revocation timestamps that it
+/// sets, and returns the keys of the revocations that were held along with the
+/// output of the future. The held revocations must be set with
+/// [`set_pending_revocations`] once the changes they are for are committed.
+pub async fn hold_revocations<F>(future: F) -> (F::Output, Vec<String>)
+where
+	F: std::future::Future,
+{
+	PENDING_REVOCATIONS
+		.scope(RefCell::new(Vec::new()), async move {
+			let output = future.await;
+			let revocations = PENDING_REVOCATIONS.with(|revocations| revocations.take());
+			(output, revocations)
+		})
+		.await
+}
+
+/// Runs the given future as a read-only request. Writes to Redis made by the
+/// future must be skipped, since they can't be discarded along with the rest
+/// of its changes.
+pub async fn read_only<F>(future: F) -> F::Output
+where
+	F: std::future::Future,
+{
+	READ_ONLY.scope((), future).await
+}
+
+/// Returns true if the request that is currently being handled is read-only,
+/// in which case it must not write to Redis
+pub fn is_read_only() -> bool {
+	READ_ONLY.try_with(|_| ()).is_ok()
+}
+
+/// Sets the revocation timestamps that were held while handling a request to
+/// the given time. Failing to set a timestamp is only logged, since the changes
+/// of the request are already committed by then, and the cached permissions
+/// expire on their own after the hard TTL anyway.
+pub async fn set_pending_revocations(redis: &Client, keys: Vec<String>, now: OffsetDateTime) {
+	for key in keys {
+		if let Err(err) = write_revocation_timestamp(redis, &key, now).await {
+			warn!("Failed to set the revocation timestamp `{key}`: {err}");
+		}
+	}
+}
+
+/// Writes the revocation timestamp to the given key, without queueing it
+async fn write_revocation_timestamp(
+	redis: &Client,
+	key: &str,
+	now: OffsetDateTime,
+) -> Result<(), rustis::Error> {
+	redis
+		.setex(
+			key,
+			constants::CACHED_PERMISSIONS_VALIDITY
+				.whole_seconds()
+				.unsigned_abs() +
+				300,
+			now.unix_timestamp(),
+		)
+		.await
+}
+
+/// Adds the key to the revocations held for the current request, if any.
+/// Returns the key back if there is no request to hold it for.
+fn queue_revocation(key: String) -> Option<String> {
+	let mut key = Some(key);
+	_ = PENDING_REVOCATIONS.try_with(|revocations| {
+		revocations.borrow_mut().extend(key.take());
+	});
+	key
+}
+
+/// Publish an update of a workspace. The update is added to the stream of
+/// updates of the workspace, so that clients polling for updates can read
+/// every update after their cursor, and the ID of the update is then published
+/// on the channel of the workspace to wake up the polls that are waiting for
+/// it. Returns the ID of the update in the stream.
+///
+/// Once the stream is longer than [`constants::WORKSPACE_UPDATES_MAX_LEN`],
+/// the oldest updates are trimmed from it. The ID of the newest update that
+/// was trimmed is stored before trimming, so that polls with a cursor before it
+/// can be told that they missed some updates.
+pub async fn publish_workspace_update(
+	redis: &Client,
+	workspace_id: &Uuid,
+	data: &str,
+) -> Result<String, ErrorType> {
+	let stream = keys::workspace_updates(workspace_id);
+	let retention = constants::WORKSPACE_UPDATES_RETENTION
+		.whole_seconds()
+		.unsigned_abs();
+
+	let update_id: String = redis
+		.xadd(&stream, "*", [("data", data)], XAddOptions::default())
+		.await
+		.inspect_err(|err| {
+			error!("Error adding the update of workspace `{workspace_id}`: `{err}`");
+		})?;
+
+	redis.expire(&stream, retention, ExpireOption::None).await?;
+
+	let length: usize = redis.xlen(&stream).await?;
+	let excess = length.saturating_sub(constants::WORKSPACE_UPDATES_MAX_LEN);
+	let newest_trimmed = if excess > 0 {
+		redis
+			.xrange::<_, _, _, String>(&stream, "-", "+", Some(excess))
+			.await?
+			.pop()
+			.and_then(|entry| parse_stream_id(&entry.stream_id))
+	} else {
+		None
+	};
+	if let Some((millis, sequence)) = newest_trimmed {
+		redis
+			.setex(
+				keys::workspace_updates_trimmed(workspace_id),
+				retention,
+				format!("{millis}-{sequence}"),
+			)
+			.await?;
+		// Only the updates up to the one that was stored are trimmed, even if
+		// more updates were added in the meantime
+		redis
+			.xtrim(
+				&stream,
+				XTrimOptions::min_id(XTrimOperator::Equal, format!("{millis}-{}", sequence + 1)),
+			)
+			.await?;
+	}
+
+	redis
+		.publish(keys::workspace_updates_channel(workspace_id), &update_id)
+		.await?;
+
+	Ok(update_id)
+}
+
+/// Parses the ID of an entry in a Redis stream, which is of the format
+/// `<milliseconds>-<sequence>`. IDs are ordered by the milliseconds, and then
+/// by the sequence.
+pub fn parse_stream_id(id: &str) -> Option<(u64, u64)> {
+	let (millis, sequence) = id.split_once('-')?;
+	Some((millis.parse().ok()?, sequence.parse().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[tokio::test]
+	async fn revocations_are_held_while_handling_a_request() {
+		let user_id = Uuid::new_v4();
+		let workspace_id = Uuid::new_v4();
+
+		let (returned, held) = hold_revocations(async {
+			(
+				queue_revocation(keys::user_id_revocation_timestamp(&user_id)),
+				queue_revocation(keys::workspace_id_revocation_timestamp(&workspace_id)),
+			)
+		})
+		.await;
+
+		assert_eq!(returned, (None, None));
+		assert_eq!(
+			held,
+			vec![
+				keys::user_id_revocation_timestamp(&user_id),
+				keys::workspace_id_revocation_timestamp(&workspace_id),
+			]
+		);
+	}
+
+	#[tokio::test]
+	async fn revocations_are_set_right_away_outside_a_request() {
+		let key = keys::login_id_revocation_timestamp(&Uuid::new_v4());
+
+		assert_eq!(queue_revocation(key.clone()), Some(key));
+	}
+
+	#[tokio::test]
+	async fn only_requests_run_as_read_only_are_read_only() {
+		assert!(!is_read_only());
+		assert!(read_only(async { is_read_only() }).await);
+		assert!(!is_read_only());
+	}
 }
@@ -1,7 +1,9 @@
 use std::pin::pin;
 
 use futures::future::Either;
-use rustis::commands::PubSubCommands;
+use rustis::commands::{PubSubCommands, SetCondition, SetExpiration, StringCommands};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
 use sqlx::postgres::PgListener;
 
 use crate::prelude::*;
@@ -9,7 +11,10 @@ use crate::prelude::*;
 /// Runs a background task that listens to the database for notifications and
 /// publishes them to Redis. Any websocket connections that want to listen in on
 /// changes to the database can then subscribe to the Redis channel and receive
-/// the notifications.
+/// the notifications. Notifications about a workspace are also added to the
+/// updates of the workspace, for the clients that poll for them instead. Every
+/// instance of the API receives every notification, so each one is claimed
+/// before it is added, so that it is only added once.
 #[instrument(skip(state))]
 pub async fn run(state: &AppState) {
 	let mut listener = PgListener::connect_with(&state.database)
@@ -33,10 +38,83 @@ pub async fn run(state: &AppState) {
 		};
 
 		if let Ok(message) = message {
-			_ = state
-				.redis
-				.publish(message.channel(), message.payload())
+			let redis = state.redis.get();
+			_ = redis.publish(message.channel(), message.payload()).await;
+
+			let Some(workspace_id) = notification_workspace_id(message.payload()) else {
+				continue;
+			};
+
+			let claimed = redis
+				.set_with_options(
+					redis::keys::workspace_update_claim(&notification_hash(message.payload())),
+					"",
+					SetCondition::NX,
+					SetExpiration::Ex(
+						constants::WORKSPACE_UPDATE_CLAIM_TTL
+							.whole_seconds()
+							.unsigned_abs(),
+					),
+					false,
+				)
 				.await;
+			match claimed {
+				Ok(true) => {
+					_ = redis::publish_workspace_update(&redis, &workspace_id, message.payload())
+						.await;
+				}
+				Ok(false) => (),
+				Err(err) => warn!("Error claiming the update of workspace `{workspace_id}`: {err}"),
+			}
 		}
 	}
 }
+
+/// Gets the ID of the workspace that a notification from the database is
+/// about, if it is about one. Such notifications are JSON objects with a
+/// `workspaceId` field
+fn notification_workspace_id(payload: &str) -> Option<Uuid> {
+	/// The part of a notification that identifies its workspace
+	#[derive(Deserialize)]
+	#[serde(rename_all = "camelCase")]
+	struct WorkspaceNotification {
+		/// The ID of the workspace that the notification is about
+		workspace_id: Uuid,
+	}
+
+	serde_json::from_str::<WorkspaceNotification>(payload)
+		.ok()
+		.map(|notification| notification.workspace_id)
+}
+
+/// Hashes a notification from the database, to claim it by. Notifications
+/// don't have an ID, so identical notifications are only told apart by when
+/// they are received
+fn notification_hash(payload: &str) -> String {
+	Sha256::digest(payload.as_bytes())
+		.iter()
+		.map(|byte| format!("{byte:02x}"))
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn notifications_are_matched_to_their_workspace() {
+		let workspace_id = Uuid::new_v4();
+
+		assert_eq!(
+			notification_workspace_id(&format!(
+				r#"{{"workspaceId":"{workspace_id}","resource":"deployment"}}"#
+			)),
+			Some(workspace_id)
+		);
+		assert_eq!(
+			notification_workspace_id(r#"{"resource":"deployment"}"#),
+			None
+		);
+		assert_eq!(notification_workspace_id("not json"), None);
+	}
+}
@@ -32,6 +32,7 @@ pub async fn complete_sign_up(
 		redis: _,
 		client_ip,
 		config,
+		clock: _,
 	}: AppRequest<'_, CompleteSignUpRequest>,
 ) -> Result<AppResponse<CompleteSignUpRequest>, ErrorType> {
 	info!("Completing sign up for user: `{username}`");
@@ -232,7 +233,7 @@ pub async fn complete_sign_up(
 	})
 	.map_err(ErrorType::server_error)?
 	.to_string();
-	let refresh_token_expiry = now.add(constants::INACTIVE_REFRESH_TOKEN_VALIDITY);
+	let refresh_token_expiry = now.add(config.session.absolute_timeout());
 
 	let ip_info = ipinfo::IpInfo::new(ipinfo::IpInfoConfig {
 		token: { Some(config.ipinfo.token) },
@@ -372,9 +373,9 @@ pub async fn complete_sign_up(
 	trace!("Web login inserted into the database");
 
 	let access_token = AccessTokenData {
-		iss: constants::JWT_ISSUER.to_string(),
+		iss: config.jwt_issuer.clone(),
 		sub: login_id,
-		aud: OneOrMore::One(constants::PATR_JWT_AUDIENCE.to_string()),
+		aud: OneOrMore::One(config.jwt_audience.clone()),
 		exp: now.add(constants::ACCESS_TOKEN_VALIDITY),
 		nbf: now,
 		iat: now,
@@ -2,12 +2,31 @@ use std::ops::Add;
 
 use argon2::{password_hash::SaltString, Algorithm, PasswordHasher, Version};
 use axum::http::StatusCode;
-use models::api::auth::*;
+use models::{
+	api::auth::*,
+	utils::constants::{E164_PHONE_NUMBER_REGEX, EMAIL_REGEX},
+};
 use rand::Rng;
+use regex::Regex;
+use rustis::{
+	client::{BatchPreparedCommand, Client as RedisClient},
+	commands::{GenericCommands, SetCondition, SetExpiration, StringCommands},
+};
 use time::OffsetDateTime;
 
-use crate::prelude::*;
+use crate::{prelude::*, redis::keys as redis, utils::email};
 
+/// The handler to create a new account. The number of accounts that can be
+/// created from the same IP address, or with a recovery email on the same
+/// domain, is limited within a window (see
+/// [`SignupRateLimitConfig`][crate::utils::config::SignupRateLimitConfig]).
+/// The limits are checked before any of the expensive work is done, but only
+/// accounts that are actually created count towards them. The recovery method
+/// must be one that this instance supports (see
+/// [`RecoveryMethodsConfig`][crate::utils::config::RecoveryMethodsConfig]), and
+/// the recovery email or phone number is validated here instead of when
+/// preprocessing the request, so that an error specific to the field can be
+/// returned.
 pub async fn create_account(
 	AppRequest {
 		request:
@@ -28,10 +47,108 @@ pub async fn create_account(
 		redis,
 		client_ip,
 		config,
+		clock: _,
 	}: AppRequest<'_, CreateAccountRequest>,
 ) -> Result<AppResponse<CreateAccountRequest>, ErrorType> {
 	info!("Creating account");
 
+	config
+		.recovery_methods
+		.ensure_supported(recovery_method.method_type())?;
+
+	trace!("Validating recovery method");
+	let recovery_method = match recovery_method {
+		RecoveryMethod::Email { recovery_email } => {
+			let is_email_valid = Regex::new(EMAIL_REGEX)
+				.map_err(ErrorType::server_error)?
+				.is_match(&recovery_email);
+			if !is_email_valid {
+				return Err(ErrorType::InvalidEmail);
+			}
+
+			RecoveryMethod::Email {
+				recovery_email: recovery_email.to_lowercase(),
+			}
+		}
+		RecoveryMethod::PhoneNumber {
+			recovery_phone_country_code,
+			recovery_phone_number,
+		} => {
+			let country_code = recovery_phone_country_code.to_uppercase();
+			let phone_code = query!(
+				r#"
+				SELECT
+					phone_code
+				FROM
+					phone_number_country_code
+				WHERE
+					country_code = $1;
+				"#,
+				&country_code,
+			)
+			.fetch_optional(&mut **database)
+			.await?
+			.ok_or(ErrorType::InvalidPhoneNumber)?
+			.phone_code;
+
+			// Separators are commonly used when entering phone numbers, so they
+			// are removed before the number is validated and stored
+			let number = recovery_phone_number
+				.chars()
+				.filter(|char| !matches!(char, ' ' | '-' | '.' | '(' | ')'))
+				.collect::<String>();
+			let is_number_valid = number.len() >= 7 &&
+				Regex::new(E164_PHONE_NUMBER_REGEX)
+					.map_err(ErrorType::server_error)?
+					.is_match(&format!("+{phone_code}{number}"));
+			if !is_number_valid {
+				return Err(ErrorType::InvalidPhoneNumber);
+			}
+
+			RecoveryMethod::PhoneNumber {
+				recovery_phone_country_code: country_code,
+				recovery_phone_number: number,
+			}
+		}
+	};
+
+	let signup_rate_limit = &config.security.signup_rate_limit;
+	let email_domain = match &recovery_method {
+		RecoveryMethod::Email { recovery_email } => recovery_email
+			.rsplit_once('@')
+			.map(|(_, domain)| domain.to_lowercase()),
+		RecoveryMethod::PhoneNumber {
+			recovery_phone_country_code: _,
+			recovery_phone_number: _,
+		} => None,
+	};
+
+	trace!("Checking signup rate limits");
+	ensure_within_signup_limit(
+		redis,
+		redis::signup_count_for_ip(&client_ip),
+		signup_rate_limit.max_per_ip,
+		signup_rate_limit.window_seconds,
+	)
+	.await
+	.inspect_err(|_| info!("Too many accounts created from IP `{}`", client_ip))?;
+
+	if let Some(email_domain) = &email_domain {
+		ensure_within_signup_limit(
+			redis,
+			redis::signup_count_for_email_domain(email_domain),
+			signup_rate_limit.max_per_email_domain,
+			signup_rate_limit.window_seconds,
+		)
+		.await
+		.inspect_err(|_| {
+			info!(
+				"Too many accounts created with email domain `{}`",
+				email_domain
+			);
+		})?;
+	}
+
 	trace!("Checking if username is available");
 	// check if username is available
 	let is_username_available = super::is_username_valid(AppRequest {
@@ -63,10 +180,53 @@ pub async fn create_account(
 
 	match &recovery_method {
 		RecoveryMethod::PhoneNumber {
-			recovery_phone_country_code: _,
-			recovery_phone_number: _,
+			recovery_phone_country_code,
+			recovery_phone_number,
 		} => {
-			todo!("Check if phone is valid");
+			// Check if phone number is available
+			let is_phone_number_taken = query!(
+				r#"
+				SELECT
+					EXISTS(
+						SELECT
+							1
+						FROM
+							user_phone_number
+						WHERE
+							country_code = $1 AND
+							number = $2
+					) OR
+					EXISTS(
+						SELECT
+							1
+						FROM
+							user_unverified_phone_number
+						WHERE
+							country_code = $1 AND
+							phone_number = $2 AND
+							verification_token_expiry > NOW()
+					) OR
+					EXISTS(
+						SELECT
+							1
+						FROM
+							user_to_sign_up
+						WHERE
+							recovery_phone_country_code = $1 AND
+							recovery_phone_number = $2 AND
+							otp_expiry > NOW()
+					) AS "taken!";
+				"#,
+				recovery_phone_country_code,
+				recovery_phone_number,
+			)
+			.fetch_one(&mut **database)
+			.await?
+			.taken;
+
+			if is_phone_number_taken {
+				return Err(ErrorType::PhoneUnavailable);
+			}
 		}
 		RecoveryMethod::Email { recovery_email } => {
 			// Check if email is valid
@@ -223,7 +383,47 @@ pub async fn create_account(
 
 	trace!("User to sign up inserted into the database");
 
-	// TODO send OTP via email
+	// Only count the account now that it has been created, so that requests
+	// that failed validation don't count towards the limits
+	let signup_counters = [
+		Some(redis::signup_count_for_ip(&client_ip)),
+		email_domain
+			.as_deref()
+			.map(redis::signup_count_for_email_domain),
+	];
+	for key in signup_counters.into_iter().flatten() {
+		// The window starts with the first account that is created in it. The
+		// counter is created along with its expiry, so that it never outlives
+		// the window, and counting doesn't change the expiry
+		let mut transaction = redis.create_transaction();
+		transaction
+			.set_with_options(
+				&key,
+				0,
+				SetCondition::NX,
+				SetExpiration::Ex(signup_rate_limit.window_seconds),
+				false,
+			)
+			.forget();
+		transaction.incr(&key).forget();
+		transaction.execute::<()>().await?;
+	}
+
+	// There is no way to send text messages yet, so the OTP can only be sent
+	// to a recovery email
+	if let Some(recovery_email) = &recovery_email {
+		email::send_email(
+			&config.email,
+			recovery_email,
+			"Verify your Patr account",
+			format!(
+				"Use the code {otp} to finish creating your Patr account. The code expires \
+				in {} minutes.",
+				constants::OTP_VALIDITY.whole_minutes()
+			),
+		)
+		.await?;
+	}
 
 	AppResponse::builder()
 		.body(CreateAccountResponse)
@@ -232,3 +432,74 @@ pub async fn create_account(
 		.build()
 		.into_result()
 }
+
+/// Checks that another account can be created within the limit of the signup
+/// counter at the given key. If the limit is reached, the error contains the
+/// number of seconds until the window of the counter ends.
+async fn ensure_within_signup_limit(
+	redis: &mut RedisClient,
+	key: String,
+	limit: u64,
+	window_seconds: u64,
+) -> Result<(), ErrorType> {
+	// The count and its expiry are read together, so that the expiry is that
+	// of the window that was counted
+	let mut transaction = redis.create_transaction();
+	transaction.get::<_, Option<u64>>(&key).queue();
+	transaction.ttl(&key).queue();
+	let (count, ttl): (Option<u64>, i64) = transaction.execute().await?;
+
+	check_signup_limit(count.unwrap_or(0), ttl, limit, window_seconds)
+}
+
+/// Checks that another account can be created by a signup counter with the
+/// given count, given the time to live (in seconds) of the counter, as returned
+/// by Redis. The window of the counter ends once its time to live runs out.
+fn check_signup_limit(
+	count: u64,
+	ttl: i64,
+	limit: u64,
+	window_seconds: u64,
+) -> Result<(), ErrorType> {
+	if count < limit {
+		return Ok(());
+	}
+
+	// A negative TTL means that the counter has no expiry, which never happens
+	// since the counter is created along with its expiry
+	let remaining_seconds = u64::try_from(ttl).unwrap_or(window_seconds).max(1);
+
+	Err(ErrorType::TooManySignups(remaining_seconds))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::check_signup_limit;
+	use crate::prelude::*;
+
+	#[test]
+	fn accounts_can_be_created_until_the_limit_is_reached() {
+		assert_eq!(check_signup_limit(0, -2, 5, 3600), Ok(()));
+		assert_eq!(check_signup_limit(4, 3600, 5, 3600), Ok(()));
+		assert_eq!(
+			check_signup_limit(5, 3600, 5, 3600),
+			Err(ErrorType::TooManySignups(3600))
+		);
+	}
+
+	#[test]
+	fn rejected_signups_report_the_rest_of_the_window() {
+		assert_eq!(
+			check_signup_limit(5, 42, 5, 3600),
+			Err(ErrorType::TooManySignups(42))
+		);
+		assert_eq!(
+			check_signup_limit(5, 0, 5, 3600),
+			Err(ErrorType::TooManySignups(1))
+		);
+		assert_eq!(
+			check_signup_limit(5, -1, 5, 3600),
+			Err(ErrorType::TooManySignups(3600))
+		);
+	}
+}
@@ -1,5 +1,3 @@
-use std::ops::Add;
-
 use argon2::{password_hash::SaltString, Algorithm, PasswordHasher, Version};
 use axum::http::StatusCode;
 use models::api::auth::*;
@@ -25,10 +23,17 @@ pub async fn forgot_password(
 		redis: _,
 		client_ip: _,
 		config,
+		clock,
 	}: AppRequest<'_, ForgotPasswordRequest>,
 ) -> Result<AppResponse<ForgotPasswordRequest>, ErrorType> {
 	info!("Initiating forgot password request for user: `{user_id}`");
 
+	// The OTP can't be delivered to recovery methods that this instance doesn't
+	// support. This doesn't depend on the user, so it doesn't leak user data
+	config
+		.recovery_methods
+		.ensure_supported(preferred_recovery_option.method_type())?;
+
 	let user_data = query!(
 		r#"
 		SELECT
@@ -68,11 +73,11 @@ pub async fn forgot_password(
 	.await?
 	.ok_or(ErrorType::UserNotFound)?;
 
-	let now = OffsetDateTime::now_utc();
+	let now = clock.now();
 	let password_reset_token = rand::thread_rng()
 		.gen_range(constants::OTP_RANGE)
 		.to_string();
-	let password_reset_token_expiry = now.add(constants::OTP_VALIDITY);
+	let password_reset_token_expiry = now + config.security.password_reset.token_lifetime();
 	let hashed_password_reset_token = argon2::Argon2::new_with_secret(
 		config.password_pepper.as_ref(),
 		Algorithm::Argon2id,
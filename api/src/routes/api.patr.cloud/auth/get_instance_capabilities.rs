@@ -0,0 +1,41 @@
+use axum::http::StatusCode;
+use models::api::auth::*;
+
+use crate::{prelude::*, utils::config::RecoveryMethodsConfig};
+
+/// The handler to get the capabilities of this instance. The recovery methods
+/// are the ones enabled in the [`RecoveryMethodsConfig`]
+pub async fn get_instance_capabilities(
+	AppRequest {
+		request:
+			ProcessedApiRequest {
+				path: GetInstanceCapabilitiesPath,
+				query: (),
+				headers: GetInstanceCapabilitiesRequestHeaders { user_agent: _ },
+				body: GetInstanceCapabilitiesRequestProcessed,
+			},
+		database: _,
+		redis: _,
+		client_ip: _,
+		config,
+		clock: _,
+	}: AppRequest<'_, GetInstanceCapabilitiesRequest>,
+) -> Result<AppResponse<GetInstanceCapabilitiesRequest>, ErrorType> {
+	trace!("Getting the capabilities of the instance");
+
+	AppResponse::builder()
+		.body(instance_capabilities(&config.recovery_methods))
+		.headers(())
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
+
+/// The capabilities of an instance with the given recovery methods enabled
+pub(super) fn instance_capabilities(
+	recovery_methods: &RecoveryMethodsConfig,
+) -> GetInstanceCapabilitiesResponse {
+	GetInstanceCapabilitiesResponse {
+		recovery_methods: recovery_methods.supported(),
+	}
+}
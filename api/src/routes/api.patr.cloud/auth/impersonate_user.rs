@@ -0,0 +1,144 @@
+use axum::http::StatusCode;
+use models::api::auth::*;
+
+use crate::{
+	models::auth_audit_log::{AuthAuditLogEntry, AuthAuditLogEvent},
+	prelude::*,
+};
+
+/// The handler for a support operator to impersonate a user. Only the operators
+/// listed in the config can impersonate users, and an impersonated session
+/// can't be used to start another one. The session is a web login of the user
+/// that expires after the configured duration, and no refresh token is issued
+/// for it, so it can't be extended. The start of the session is written to the
+/// auth audit log, along with the reason given by the operator.
+pub async fn impersonate_user(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: ImpersonateUserPath,
+				query: (),
+				headers:
+					ImpersonateUserRequestHeaders {
+						authorization: _,
+						user_agent,
+					},
+				body: ImpersonateUserRequestProcessed { user_id, reason },
+			},
+		database,
+		redis: _,
+		client_ip,
+		config,
+		user_data,
+		clock,
+	}: AuthenticatedAppRequest<'_, ImpersonateUserRequest>,
+) -> Result<AppResponse<ImpersonateUserRequest>, ErrorType> {
+	info!(
+		"User `{}` is trying to impersonate user `{}`",
+		user_data.id, user_id
+	);
+
+	if user_data.impersonated_by.is_some() ||
+		!config
+			.security
+			.impersonation
+			.operators
+			.contains(&user_data.id)
+	{
+		warn!(
+			"User `{}` is not allowed to impersonate users",
+			user_data.id
+		);
+		return Err(ErrorType::Unauthorized);
+	}
+
+	if user_id == user_data.id {
+		debug!("Operators cannot impersonate themselves");
+		return Err(ErrorType::WrongParameters);
+	}
+
+	query!(
+		r#"
+		SELECT
+			id
+		FROM
+			"user"
+		WHERE
+			id = $1;
+		"#,
+		user_id as _,
+	)
+	.fetch_optional(&mut **database)
+	.await?
+	.ok_or(ErrorType::UserNotFound)?;
+
+	let (login_id, access_token, _) = super::create_web_login(
+		&mut **database,
+		&config,
+		client_ip,
+		user_agent.to_string(),
+		user_id,
+		config.security.impersonation.session_duration(),
+	)
+	.await?;
+
+	query!(
+		r#"
+		INSERT INTO
+			web_login_impersonation(
+				login_id,
+				operator_id,
+				reason
+			)
+		VALUES
+			($1, $2, $3);
+		"#,
+		login_id as _,
+		user_data.id as _,
+		&reason,
+	)
+	.execute(&mut **database)
+	.await?;
+
+	let expires_at = query!(
+		r#"
+		SELECT
+			token_expiry
+		FROM
+			web_login
+		WHERE
+			login_id = $1;
+		"#,
+		login_id as _,
+	)
+	.fetch_one(&mut **database)
+	.await?
+	.token_expiry;
+
+	AuthAuditLogEntry {
+		login_id,
+		user_id,
+		operator_id: Some(user_data.id),
+		event: AuthAuditLogEvent::ImpersonationStarted,
+		details: reason,
+		ip_address: client_ip,
+		timestamp: clock.now(),
+	}
+	.record(&mut **database)
+	.await?;
+
+	info!(
+		"User `{}` is impersonating user `{}` with login `{}` until {}",
+		user_data.id, user_id, login_id, expires_at
+	);
+
+	AppResponse::builder()
+		.body(ImpersonateUserResponse {
+			access_token,
+			expires_at,
+		})
+		.headers(())
+		.status_code(StatusCode::CREATED)
+		.build()
+		.into_result()
+}
@@ -16,6 +16,7 @@ pub async fn is_email_valid(
 		redis: _,
 		client_ip: _,
 		config: _,
+		clock: _,
 	}: AppRequest<'_, IsEmailValidRequest>,
 ) -> Result<AppResponse<IsEmailValidRequest>, ErrorType> {
 	info!("Checking for validity of Email: `{email}`");
@@ -16,6 +16,7 @@ pub async fn is_username_valid(
 		redis: _,
 		client_ip: _,
 		config: _,
+		clock: _,
 	}: AppRequest<'_, IsUsernameValidRequest>,
 ) -> Result<AppResponse<IsUsernameValidRequest>, ErrorType> {
 	info!("Checking for validity of username: `{username}`");
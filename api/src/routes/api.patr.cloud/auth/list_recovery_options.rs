@@ -15,7 +15,8 @@ pub async fn list_recovery_options(
 		database,
 		redis: _,
 		client_ip: _,
-		config: _,
+		config,
+		clock: _,
 	}: AppRequest<'_, ListRecoveryOptionsRequest>,
 ) -> Result<AppResponse<ListRecoveryOptionsRequest>, ErrorType> {
 	info!("Listing recovery options for user by query: `{user_id}`");
@@ -55,8 +56,15 @@ pub async fn list_recovery_options(
 	.await?
 	.ok_or(ErrorType::UserNotFound)?;
 
+	// Only the recovery options that this instance can send an OTP to are
+	// listed, since the others can't be used to reset the password
 	let recovery_phone_number = row
 		.recovery_phone_number
+		.filter(|_| {
+			config
+				.recovery_methods
+				.is_supported(RecoveryMethodType::PhoneNumber)
+		})
 		.map(|number| {
 			let mut chars = number.chars();
 			let first = if let Some(first) = chars.next() {
@@ -86,6 +94,11 @@ pub async fn list_recovery_options(
 	let recovery_email = row
 		.recovery_email
 		.as_deref()
+		.filter(|_| {
+			config
+				.recovery_methods
+				.is_supported(RecoveryMethodType::Email)
+		})
 		.and_then(|email| email.split_once('@'))
 		.map(|(local, domain)| {
 			let local = || {
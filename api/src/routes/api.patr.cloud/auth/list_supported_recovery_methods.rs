@@ -0,0 +1,36 @@
+use axum::http::StatusCode;
+use models::api::auth::*;
+
+use super::instance_capabilities;
+use crate::prelude::*;
+
+/// The handler to list the recovery methods that new accounts can be created
+/// with on this instance. This is only kept for older clients, and lists the
+/// same methods as the capabilities of the instance
+pub async fn list_supported_recovery_methods(
+	AppRequest {
+		request:
+			ProcessedApiRequest {
+				path: ListSupportedRecoveryMethodsPath,
+				query: (),
+				headers: ListSupportedRecoveryMethodsRequestHeaders { user_agent: _ },
+				body: ListSupportedRecoveryMethodsRequestProcessed,
+			},
+		database: _,
+		redis: _,
+		client_ip: _,
+		config,
+		clock: _,
+	}: AppRequest<'_, ListSupportedRecoveryMethodsRequest>,
+) -> Result<AppResponse<ListSupportedRecoveryMethodsRequest>, ErrorType> {
+	trace!("Listing supported recovery methods");
+
+	AppResponse::builder()
+		.body(ListSupportedRecoveryMethodsResponse {
+			recovery_methods: instance_capabilities(&config.recovery_methods).recovery_methods,
+		})
+		.headers(())
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
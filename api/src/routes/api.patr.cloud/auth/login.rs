@@ -1,24 +1,14 @@
-use std::{num::ParseFloatError, ops::Add};
-
-use argon2::{
-	password_hash::SaltString,
-	Algorithm,
-	PasswordHash,
-	PasswordHasher,
-	PasswordVerifier,
-	Version,
-};
+use argon2::{Algorithm, PasswordHash, PasswordVerifier, Version};
 use axum::http::StatusCode;
-use jsonwebtoken::EncodingKey;
 use models::api::auth::*;
-use sqlx::types::ipnetwork::IpNetwork;
-use time::OffsetDateTime;
-use totp_rs::{Algorithm as TotpAlgorithm, Secret, TOTP};
+use rustis::commands::StringCommands;
 
-use crate::{models::access_token_data::AccessTokenData, prelude::*};
+use crate::{prelude::*, redis::keys as redis, utils::email};
 
 /// The handler to login the user. This will return the access token and the
-/// refresh token.
+/// refresh token. If the user has MFA enabled, a short-lived MFA token is
+/// returned instead, which has to be verified along with an OTP (or a recovery
+/// code) using the [`verify_mfa_login`][super::verify_mfa_login] handler.
 pub async fn login(
 	AppRequest {
 		request:
@@ -26,16 +16,13 @@ pub async fn login(
 				path: LoginPath,
 				query: (),
 				headers: LoginRequestHeaders { user_agent },
-				body: LoginRequestProcessed {
-					user_id,
-					password,
-					mfa_otp,
-				},
+				body: LoginRequestProcessed { user_id, password },
 			},
 		database,
-		redis: _,
+		redis,
 		client_ip,
 		config,
+		clock: _,
 	}: AppRequest<'_, LoginRequest>,
 ) -> Result<AppResponse<LoginRequest>, ErrorType> {
 	trace!("Logging in user: {}", user_id);
@@ -46,6 +33,7 @@ pub async fn login(
 			"user".id,
 			"user".username,
 			"user".password,
+			"user".recovery_email,
 			"user".mfa_secret
 		FROM
 			"user"
@@ -78,6 +66,9 @@ pub async fn login(
 
 	trace!("Found user with ID: {}", user_data.id);
 
+	super::ensure_account_not_locked(redis, &config.security.account_lockout, &user_data.username)
+		.await?;
+
 	let success = argon2::Argon2::new_with_secret(
 		config.password_pepper.as_ref(),
 		Algorithm::Argon2id,
@@ -98,234 +89,96 @@ pub async fn login(
 	.is_ok();
 
 	if !success {
-		return Err(ErrorType::InvalidPassword);
-	}
-
-	trace!("Password hashes match");
-
-	if let Some(mfa_secret) = user_data.mfa_secret {
-		trace!("User has MFA secret");
-
-		let Some(mfa_otp) = mfa_otp else {
-			return Err(ErrorType::MfaRequired);
-		};
-
-		let totp = TOTP::new(
-			TotpAlgorithm::SHA1,
-			6,
-			1,
-			30,
-			Secret::Encoded(mfa_secret).to_bytes().inspect_err(|err| {
-				error!(
-					"Unable to parse MFA secret for userId `{}`: {}",
-					user_data.id,
-					err.to_string()
-				);
-			})?,
+		let locked = super::record_failed_login_attempt(
+			redis,
+			&config.security.account_lockout,
+			&user_data.username,
 		)
-		.inspect_err(|err| {
-			error!(
-				"Unable to parse TOTP for userId `{}`: {}",
-				user_data.id,
-				err.to_string()
-			);
-		})?;
+		.await?;
 
-		let mfa_valid = totp.check_current(&mfa_otp).inspect_err(|err| {
-			error!(
-				"System time error while checking TOTP for userId `{}`: {}",
-				user_data.id,
-				err.to_string()
+		if locked {
+			info!(
+				"Locking account of user `{}` after too many failed password attempts",
+				user_data.id
 			);
-		})?;
-
-		if !mfa_valid {
-			return Err(ErrorType::MfaOtpInvalid);
+			let cooldown_seconds = config.security.account_lockout.cooldown_seconds;
+			if let Some(recovery_email) = &user_data.recovery_email {
+				// The account is locked even if the email can't be sent
+				_ = email::send_email(
+					&config.email,
+					recovery_email,
+					"Your Patr account was locked",
+					format!(
+						"Your Patr account was locked for {} minutes after too many \
+						failed sign in attempts. If this wasn't you, reset your password \
+						to unlock your account and keep it secure.",
+						cooldown_seconds.div_ceil(60)
+					),
+				)
+				.await
+				.inspect_err(|err| {
+					warn!(
+						"Error notifying user `{}` of the lock: {err:?}",
+						user_data.id
+					);
+				});
+			}
+			return Err(ErrorType::AccountLocked(cooldown_seconds));
 		}
 
-		trace!("User MFA is valid");
-	}
-
-	let now = OffsetDateTime::now_utc();
-
-	let refresh_token = Uuid::new_v4();
-	let hashed_refresh_token = argon2::Argon2::new_with_secret(
-		config.password_pepper.as_ref(),
-		Algorithm::Argon2id,
-		Version::V0x13,
-		constants::HASHING_PARAMS,
-	)
-	.inspect_err(|err| {
-		error!("Error creating Argon2: `{}`", err);
-	})
-	.map_err(ErrorType::server_error)?
-	.hash_password(
-		refresh_token.as_bytes(),
-		SaltString::generate(&mut rand::thread_rng()).as_salt(),
-	)
-	.inspect_err(|err| {
-		error!("Error hashing refresh token: `{}`", err);
-	})
-	.map_err(ErrorType::server_error)?
-	.to_string();
-	let refresh_token_expiry = now.add(constants::INACTIVE_REFRESH_TOKEN_VALIDITY);
-
-	let ip_info = ipinfo::IpInfo::new(ipinfo::IpInfoConfig {
-		token: { Some(config.ipinfo.token) },
-		..Default::default()
-	})
-	.inspect_err(|err| {
-		info!("Error creating IpInfo: {err}");
-	})?
-	.lookup(client_ip.to_string().as_str())
-	.await
-	.inspect_err(|err| {
-		info!("Error looking up IP address: {err}");
-	})?;
-
-	if !cfg!(debug_assertions) && ip_info.bogon.unwrap_or(false) {
-		return Err(ErrorType::server_error(format!(
-			"cannot use bogon IP address: `{}`",
-			client_ip
-		)));
+		return Err(ErrorType::InvalidPassword);
 	}
 
-	let client_ip = IpNetwork::from(client_ip);
+	trace!("Password hashes match");
 
-	let (lat, lng) = if cfg!(debug_assertions) {
-		(0f64, 0f64)
-	} else {
-		ip_info
-			.loc
-			.split_once(',')
-			.map(|(lat, lng)| {
-				Ok::<_, ParseFloatError>((
-					lat.parse::<f64>().inspect_err(|err| {
-						info!("Error parsing latitude: `{lat}` - {err}");
-					})?,
-					lng.parse::<f64>().inspect_err(|err| {
-						info!("Error parsing longitude: `{lng}` - {err}");
-					})?,
-				))
-			})
-			.ok_or_else(|| {
-				ErrorType::server_error(format!("unknown latitude and longitude: {}", ip_info.loc))
-			})??
-	};
-	let country = ip_info.country;
-	let region = ip_info.region;
-	let city = ip_info.city;
-	let timezone = ip_info.timezone.unwrap_or_else(Default::default);
+	super::clear_failed_login_attempts(redis, &user_data.username).await?;
 
-	let user_agent = user_agent.to_string();
+	if user_data.mfa_secret.is_some() {
+		trace!("User has MFA enabled, issuing an MFA token");
 
-	let login_id = query!(
-		r#"
-		INSERT INTO
-			user_login(
-				login_id,
-				user_id,
-				login_type,
-				created
+		let mfa_token = Uuid::new_v4().to_string();
+		redis
+			.setex(
+				redis::mfa_login_token(&mfa_token),
+				constants::MFA_LOGIN_TOKEN_VALIDITY
+					.whole_seconds()
+					.unsigned_abs(),
+				user_data.id.to_string(),
 			)
-		VALUES
-			(
-				GENERATE_LOGIN_ID(),
-				$1,
-				'web_login',
-				$2
-			)
-		RETURNING login_id;
-		"#,
-		user_data.id,
-		now,
-	)
-	.fetch_one(&mut **database)
-	.await?
-	.login_id
-	.into();
-
-	query!(
-		r#"
-		INSERT INTO
-			web_login(
-				login_id,
-				original_login_id,
-				user_id,
-	
-				refresh_token,
-				token_expiry,
-	
-				created,
-				created_ip,
-				created_location,
-				created_user_agent,
-				created_country,
-				created_region,
-				created_city,
-				created_timezone
-			)
-		VALUES
-			(
-				$1,
-				NULL,
-				$2,
+			.await
+			.inspect_err(|err| {
+				error!(
+					"Error setting the MFA token for user `{}`: `{}`",
+					user_data.id, err
+				);
+			})?;
 
-				$3,
-				$4,
+		return AppResponse::builder()
+			.body(LoginResponse {
+				response: LoginResponseType::MfaRequired { mfa_token },
+			})
+			.headers(())
+			.status_code(StatusCode::ACCEPTED)
+			.build()
+			.into_result();
+	}
 
-				$5,
-				$6,
-				ST_SetSRID(POINT($7, $8)::GEOMETRY, 4326),
-				$9,
-				$10,
-				$11,
-				$12,
-				$13
-			);
-		"#,
-		login_id as _,
-		user_data.id,
-		hashed_refresh_token,
-		refresh_token_expiry,
-		now,
+	let (_, access_token, refresh_token) = super::create_web_login(
+		&mut **database,
+		&config,
 		client_ip,
-		lat,
-		lng,
-		user_agent,
-		country,
-		region,
-		city,
-		timezone,
+		user_agent.to_string(),
+		user_data.id.into(),
+		config.session.absolute_timeout(),
 	)
-	.execute(&mut **database)
 	.await?;
 
-	let access_token = AccessTokenData {
-		iss: constants::JWT_ISSUER.to_string(),
-		sub: login_id,
-		aud: OneOrMore::One(constants::PATR_JWT_AUDIENCE.to_string()),
-		exp: now.add(constants::ACCESS_TOKEN_VALIDITY),
-		nbf: now,
-		iat: now,
-		jti: Uuid::now_v1(),
-	};
-
-	let access_token = jsonwebtoken::encode(
-		&Default::default(),
-		&access_token,
-		&EncodingKey::from_secret(config.jwt_secret.as_ref()),
-	)
-	.inspect_err(|err| {
-		error!("Error encoding JWT: `{}`", err);
-	})?;
-
-	let refresh_token = format!("{login_id}.{refresh_token}");
-
 	AppResponse::builder()
 		.body(LoginResponse {
-			access_token,
-			refresh_token,
+			response: LoginResponseType::LoggedIn {
+				access_token,
+				refresh_token,
+			},
 		})
 		.headers(())
 		.status_code(StatusCode::ACCEPTED)
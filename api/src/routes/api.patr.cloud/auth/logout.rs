@@ -23,6 +23,7 @@ pub async fn logout(
 		client_ip: _,
 		user_data,
 		config,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, LogoutRequest>,
 ) -> Result<AppResponse<LogoutRequest>, ErrorType> {
 	info!("Logging out user: {}", user_data.id);
@@ -1,13 +1,31 @@
+use std::{net::IpAddr, num::ParseFloatError, ops::Add};
+
+use argon2::{password_hash::SaltString, Algorithm, PasswordHasher, Version};
 use axum::Router;
+use jsonwebtoken::EncodingKey;
+use rustis::{
+	client::{BatchPreparedCommand, Client as RedisClient},
+	commands::{ExpireOption, GenericCommands, StringCommands},
+};
+use sqlx::types::ipnetwork::IpNetwork;
+use time::OffsetDateTime;
 
-use crate::prelude::*;
+use crate::{
+	models::access_token_data::AccessTokenData,
+	prelude::*,
+	redis::keys as redis,
+	utils::config::{AccountLockoutConfig, AppConfig},
+};
 
 mod complete_sign_up;
 mod create_account;
 mod forgot_password;
+mod get_instance_capabilities;
+mod impersonate_user;
 mod is_email_valid;
 mod is_username_valid;
 mod list_recovery_options;
+mod list_supported_recovery_methods;
 mod login;
 mod logout;
 #[expect(unused_variables)]
@@ -15,19 +33,24 @@ mod oauth;
 mod renew_access_token;
 mod resend_otp;
 mod reset_password;
+mod verify_mfa_login;
 
 use self::{
 	complete_sign_up::*,
 	create_account::*,
 	forgot_password::*,
+	get_instance_capabilities::*,
+	impersonate_user::*,
 	is_email_valid::*,
 	is_username_valid::*,
 	list_recovery_options::*,
+	list_supported_recovery_methods::*,
 	login::*,
 	logout::*,
 	renew_access_token::*,
 	resend_otp::*,
 	reset_password::*,
+	verify_mfa_login::*,
 };
 
 /// Sets up the auth routes
@@ -36,7 +59,9 @@ pub async fn setup_routes(state: &AppState) -> Router {
 	Router::new()
 		.merge(oauth::setup_routes(state).await)
 		.mount_endpoint(login, state)
+		.mount_endpoint(verify_mfa_login, state)
 		.mount_auth_endpoint(logout, state)
+		.mount_auth_endpoint(impersonate_user, state)
 		.mount_endpoint(create_account, state)
 		.mount_endpoint(renew_access_token, state)
 		.mount_endpoint(forgot_password, state)
@@ -44,6 +69,350 @@ pub async fn setup_routes(state: &AppState) -> Router {
 		.mount_endpoint(is_username_valid, state)
 		.mount_endpoint(complete_sign_up, state)
 		.mount_endpoint(list_recovery_options, state)
+		.mount_endpoint(get_instance_capabilities, state)
+		.mount_endpoint(list_supported_recovery_methods, state)
 		.mount_endpoint(resend_otp, state)
 		.mount_endpoint(reset_password, state)
 }
+
+/// Checks if the account of the given user is locked because of too many
+/// consecutive failed password attempts. The lock expires on its own once the
+/// cooldown has passed since the last failed attempt. If the account is
+/// locked, the error contains the number of seconds until it is unlocked.
+async fn ensure_account_not_locked(
+	redis: &mut RedisClient,
+	lockout: &AccountLockoutConfig,
+	username: &str,
+) -> Result<(), ErrorType> {
+	// The attempts and their expiry are read together, so that the expiry is
+	// that of the attempts that were counted
+	let mut transaction = redis.create_transaction();
+	transaction
+		.get::<_, Option<u64>>(redis::failed_login_attempts(username))
+		.queue();
+	transaction
+		.ttl(redis::failed_login_attempts(username))
+		.queue();
+	let (failed_attempts, ttl): (Option<u64>, i64) = transaction.execute().await?;
+
+	check_account_lock(failed_attempts.unwrap_or(0), ttl, lockout).inspect_err(|_| {
+		debug!("Account of user `{username}` is locked");
+	})
+}
+
+/// Checks if an account with the given number of failed password attempts is
+/// locked, given the time to live (in seconds) of the attempts, as returned by
+/// Redis. The lock lasts for the rest of the time to live.
+fn check_account_lock(
+	failed_attempts: u64,
+	ttl: i64,
+	lockout: &AccountLockoutConfig,
+) -> Result<(), ErrorType> {
+	if failed_attempts < lockout.max_failed_attempts {
+		return Ok(());
+	}
+
+	// A negative TTL means that the attempts have no expiry, which never
+	// happens since the expiry is set along with the attempts
+	let remaining_seconds = u64::try_from(ttl)
+		.unwrap_or(lockout.cooldown_seconds)
+		.max(1);
+
+	Err(ErrorType::AccountLocked(remaining_seconds))
+}
+
+/// Records a failed password attempt for the given user, restarting the
+/// cooldown of the lock. Returns true if this attempt locked the account.
+async fn record_failed_login_attempt(
+	redis: &mut RedisClient,
+	lockout: &AccountLockoutConfig,
+	username: &str,
+) -> Result<bool, ErrorType> {
+	// The attempt is counted and its expiry set atomically, so that the
+	// attempts are never left without an expiry, which would lock the account
+	// forever
+	let mut transaction = redis.create_transaction();
+	transaction
+		.incr(redis::failed_login_attempts(username))
+		.queue();
+	transaction
+		.expire(
+			redis::failed_login_attempts(username),
+			lockout.cooldown_seconds,
+			ExpireOption::None,
+		)
+		.forget();
+	let failed_attempts: u64 = transaction.execute().await?;
+
+	Ok(failed_attempts == lockout.max_failed_attempts)
+}
+
+/// Clears the failed password attempts of the given user, unlocking their
+/// account if it was locked. This is done once the user has proven their
+/// identity, either by logging in or by resetting their password.
+async fn clear_failed_login_attempts(
+	redis: &mut RedisClient,
+	username: &str,
+) -> Result<(), ErrorType> {
+	redis.del(redis::failed_login_attempts(username)).await?;
+
+	Ok(())
+}
+
+/// Creates a new web login for the given user, once their identity has been
+/// verified (including the second factor, if they have multi-factor
+/// authentication enabled). The login expires after the given validity, and
+/// the access token never outlives it. Returns the ID of the login, along with
+/// its access token and refresh token.
+async fn create_web_login(
+	database: &mut DatabaseConnection,
+	config: &AppConfig,
+	client_ip: IpAddr,
+	user_agent: String,
+	user_id: Uuid,
+	validity: time::Duration,
+) -> Result<(Uuid, String, String), ErrorType> {
+	let now = OffsetDateTime::now_utc();
+
+	let refresh_token = Uuid::new_v4();
+	let hashed_refresh_token = argon2::Argon2::new_with_secret(
+		config.password_pepper.as_ref(),
+		Algorithm::Argon2id,
+		Version::V0x13,
+		constants::HASHING_PARAMS,
+	)
+	.inspect_err(|err| {
+		error!("Error creating Argon2: `{}`", err);
+	})
+	.map_err(ErrorType::server_error)?
+	.hash_password(
+		refresh_token.as_bytes(),
+		SaltString::generate(&mut rand::thread_rng()).as_salt(),
+	)
+	.inspect_err(|err| {
+		error!("Error hashing refresh token: `{}`", err);
+	})
+	.map_err(ErrorType::server_error)?
+	.to_string();
+	let refresh_token_expiry = now.add(validity);
+
+	let ip_info = ipinfo::IpInfo::new(ipinfo::IpInfoConfig {
+		token: { Some(config.ipinfo.token.clone()) },
+		..Default::default()
+	})
+	.inspect_err(|err| {
+		info!("Error creating IpInfo: {err}");
+	})?
+	.lookup(client_ip.to_string().as_str())
+	.await
+	.inspect_err(|err| {
+		info!("Error looking up IP address: {err}");
+	})?;
+
+	if !cfg!(debug_assertions) && ip_info.bogon.unwrap_or(false) {
+		return Err(ErrorType::server_error(format!(
+			"cannot use bogon IP address: `{}`",
+			client_ip
+		)));
+	}
+
+	let client_ip = IpNetwork::from(client_ip);
+
+	let (lat, lng) = if cfg!(debug_assertions) {
+		(0f64, 0f64)
+	} else {
+		ip_info
+			.loc
+			.split_once(',')
+			.map(|(lat, lng)| {
+				Ok::<_, ParseFloatError>((
+					lat.parse::<f64>().inspect_err(|err| {
+						info!("Error parsing latitude: `{lat}` - {err}");
+					})?,
+					lng.parse::<f64>().inspect_err(|err| {
+						info!("Error parsing longitude: `{lng}` - {err}");
+					})?,
+				))
+			})
+			.ok_or_else(|| {
+				ErrorType::server_error(format!("unknown latitude and longitude: {}", ip_info.loc))
+			})??
+	};
+	let country = ip_info.country;
+	let region = ip_info.region;
+	let city = ip_info.city;
+	let timezone = ip_info.timezone.unwrap_or_else(Default::default);
+
+	let login_id = query!(
+		r#"
+		INSERT INTO
+			user_login(
+				login_id,
+				user_id,
+				login_type,
+				created
+			)
+		VALUES
+			(
+				GENERATE_LOGIN_ID(),
+				$1,
+				'web_login',
+				$2
+			)
+		RETURNING login_id;
+		"#,
+		user_id as _,
+		now,
+	)
+	.fetch_one(&mut *database)
+	.await?
+	.login_id
+	.into();
+
+	query!(
+		r#"
+		INSERT INTO
+			web_login(
+				login_id,
+				original_login_id,
+				user_id,
+	
+				refresh_token,
+				token_expiry,
+	
+				created,
+				created_ip,
+				created_location,
+				created_user_agent,
+				created_country,
+				created_region,
+				created_city,
+				created_timezone
+			)
+		VALUES
+			(
+				$1,
+				NULL,
+				$2,
+
+				$3,
+				$4,
+
+				$5,
+				$6,
+				ST_SetSRID(POINT($7, $8)::GEOMETRY, 4326),
+				$9,
+				$10,
+				$11,
+				$12,
+				$13
+			);
+		"#,
+		login_id as _,
+		user_id as _,
+		hashed_refresh_token,
+		refresh_token_expiry,
+		now,
+		client_ip,
+		lat,
+		lng,
+		user_agent,
+		country,
+		region,
+		city,
+		timezone,
+	)
+	.execute(&mut *database)
+	.await?;
+
+	let access_token = AccessTokenData {
+		iss: config.jwt_issuer.clone(),
+		sub: login_id,
+		aud: OneOrMore::One(config.jwt_audience.clone()),
+		exp: now
+			.add(constants::ACCESS_TOKEN_VALIDITY)
+			.min(refresh_token_expiry),
+		nbf: now,
+		iat: now,
+		jti: Uuid::now_v1(),
+	};
+
+	let access_token = jsonwebtoken::encode(
+		&Default::default(),
+		&access_token,
+		&EncodingKey::from_secret(config.jwt_secret.as_ref()),
+	)
+	.inspect_err(|err| {
+		error!("Error encoding JWT: `{}`", err);
+	})?;
+
+	let refresh_token = format!("{login_id}.{refresh_token}");
+
+	Ok((login_id, access_token, refresh_token))
+}
+
+#[cfg(test)]
+mod tests {
+	use models::api::auth::RecoveryMethodType;
+
+	use super::*;
+	use crate::utils::config::RecoveryMethodsConfig;
+
+	#[test]
+	fn accounts_are_locked_once_the_limit_is_reached() {
+		let lockout = AccountLockoutConfig {
+			max_failed_attempts: 5,
+			cooldown_seconds: 900,
+		};
+
+		assert_eq!(check_account_lock(0, -2, &lockout), Ok(()));
+		assert_eq!(check_account_lock(4, 900, &lockout), Ok(()));
+		assert_eq!(
+			check_account_lock(5, 900, &lockout),
+			Err(ErrorType::AccountLocked(900))
+		);
+		assert_eq!(
+			check_account_lock(6, 120, &lockout),
+			Err(ErrorType::AccountLocked(120))
+		);
+	}
+
+	#[test]
+	fn locked_accounts_report_the_remaining_cooldown() {
+		let lockout = AccountLockoutConfig {
+			max_failed_attempts: 5,
+			cooldown_seconds: 900,
+		};
+
+		// The lock is about to expire, but the request must still wait
+		assert_eq!(
+			check_account_lock(5, 0, &lockout),
+			Err(ErrorType::AccountLocked(1))
+		);
+		assert_eq!(
+			check_account_lock(5, -1, &lockout),
+			Err(ErrorType::AccountLocked(900))
+		);
+	}
+
+	#[test]
+	fn capabilities_list_the_enabled_recovery_methods() {
+		let recovery_methods = RecoveryMethodsConfig {
+			email: true,
+			phone_number: true,
+		};
+		assert_eq!(
+			serde_json::to_value(instance_capabilities(&recovery_methods)).unwrap(),
+			serde_json::json!({ "recoveryMethods": ["email", "phoneNumber"] })
+		);
+
+		let recovery_methods = RecoveryMethodsConfig {
+			email: false,
+			phone_number: true,
+		};
+		assert_eq!(
+			instance_capabilities(&recovery_methods).recovery_methods,
+			[RecoveryMethodType::PhoneNumber]
+		);
+	}
+}
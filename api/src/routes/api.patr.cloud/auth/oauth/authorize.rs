@@ -24,6 +24,7 @@ pub async fn authorize(
 		redis: _,
 		client_ip,
 		config,
+		clock: _,
 	}: AppRequest<'_, OAuthAuthorizeRequest>,
 ) -> Result<AppResponse<OAuthAuthorizeRequest>, ErrorType> {
 	todo!()
@@ -15,6 +15,7 @@ pub async fn introspect(
 		redis: _,
 		client_ip,
 		config,
+		clock: _,
 	}: AppRequest<'_, OAuthIntrospectRequest>,
 ) -> Result<AppResponse<OAuthIntrospectRequest>, ErrorType> {
 	todo!()
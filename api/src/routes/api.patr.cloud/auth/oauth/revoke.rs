@@ -15,6 +15,7 @@ pub async fn revoke(
 		redis: _,
 		client_ip,
 		config,
+		clock: _,
 	}: AppRequest<'_, OAuthRevokeTokenRequest>,
 ) -> Result<AppResponse<OAuthRevokeTokenRequest>, ErrorType> {
 	todo!()
@@ -23,6 +23,7 @@ pub async fn token(
 		redis: _,
 		client_ip,
 		config,
+		clock: _,
 	}: AppRequest<'_, OAuthTokenRequest>,
 ) -> Result<AppResponse<OAuthTokenRequest>, ErrorType> {
 	todo!()
@@ -25,6 +25,7 @@ pub async fn renew_access_token(
 		redis: _,
 		client_ip: _,
 		config,
+		clock: _,
 	}: AppRequest<'_, RenewAccessTokenRequest>,
 ) -> Result<AppResponse<RenewAccessTokenRequest>, ErrorType> {
 	info!(
@@ -48,7 +49,8 @@ pub async fn renew_access_token(
 		r#"
         SELECT
             token_expiry,
-			refresh_token
+			refresh_token,
+			COALESCE(last_activity, created) AS "last_activity!"
         FROM
             web_login
         WHERE
@@ -68,6 +70,16 @@ pub async fn renew_access_token(
 		return Err(ErrorType::MalformedRefreshToken);
 	}
 
+	// Renewing the access token does not count as activity, so that an idle
+	// session cannot be kept alive just by renewing its access token
+	if now - row.last_activity > config.session.idle_timeout() {
+		debug!(
+			"Token was last active at {}. It is idle.",
+			row.last_activity
+		);
+		return Err(ErrorType::MalformedRefreshToken);
+	}
+
 	let success = argon2::Argon2::new_with_secret(
 		config.password_pepper.as_ref(),
 		Algorithm::Argon2id,
@@ -93,9 +105,9 @@ pub async fn renew_access_token(
 	}
 
 	let access_token = AccessTokenData {
-		iss: constants::JWT_ISSUER.to_string(),
+		iss: config.jwt_issuer.clone(),
 		sub: login_id,
-		aud: OneOrMore::One(constants::PATR_JWT_AUDIENCE.to_string()),
+		aud: OneOrMore::One(config.jwt_audience.clone()),
 		exp: now.add(constants::ACCESS_TOKEN_VALIDITY),
 		nbf: now,
 		iat: now,
@@ -25,6 +25,7 @@ pub async fn resend_otp(
 		redis: _,
 		client_ip: _,
 		config,
+		clock: _,
 	}: AppRequest<'_, ResendOtpRequest>,
 ) -> Result<AppResponse<ResendOtpRequest>, ErrorType> {
 	info!("Resending OTP to username: `{username}`");
@@ -12,6 +12,11 @@ use time::OffsetDateTime;
 
 use crate::prelude::*;
 
+/// The handler to reset the password of a user using the token that was sent
+/// to them. The token can only be used once. It is consumed atomically along
+/// with the password being changed, so that a token that is submitted twice
+/// fails the second time. Unknown users, expired tokens, used tokens and wrong
+/// tokens all fail with the same error, so that they can't be told apart.
 pub async fn reset_password(
 	AppRequest {
 		request:
@@ -27,9 +32,10 @@ pub async fn reset_password(
 					},
 			},
 		database,
-		redis: _,
+		redis,
 		client_ip: _,
 		config,
+		clock,
 	}: AppRequest<'_, ResetPasswordRequest>,
 ) -> Result<AppResponse<ResetPasswordRequest>, ErrorType> {
 	info!("Resetting password for user: `{user_id}`");
@@ -38,6 +44,7 @@ pub async fn reset_password(
 		r#"
 		SELECT
 			"user".id,
+			"user".username,
 			"user".password_reset_token,
 			"user".password_reset_token_expiry,
 			"user".password_reset_attempts
@@ -68,25 +75,16 @@ pub async fn reset_password(
 	)
 	.fetch_optional(&mut **database)
 	.await?
-	.ok_or(ErrorType::UserNotFound)?;
+	.ok_or_else(|| {
+		debug!("No user found to reset the password of");
+		ErrorType::ResetTokenInvalid
+	})?;
 
-	let now = OffsetDateTime::now_utc();
-
-	if user_data
-		.password_reset_token_expiry
-		.unwrap_or(OffsetDateTime::UNIX_EPOCH) <
-		now
-	{
-		debug!("Password reset token has expired");
-		return Err(ErrorType::InvalidPasswordResetToken);
-	}
-
-	if user_data.password_reset_attempts.unwrap_or(0) >
-		constants::MAX_PASSWORD_RESET_ATTEMPTS.into()
-	{
-		debug!("Password reset attempts exceeded");
-		return Err(ErrorType::InvalidPasswordResetToken);
-	}
+	check_reset_token(
+		user_data.password_reset_token_expiry,
+		user_data.password_reset_attempts,
+		clock.now(),
+	)?;
 
 	query!(
 		r#"
@@ -104,7 +102,7 @@ pub async fn reset_password(
 
 	let Some(password_reset_token) = user_data.password_reset_token else {
 		debug!("Password reset token is missing");
-		return Err(ErrorType::InvalidPasswordResetToken);
+		return Err(ErrorType::ResetTokenInvalid);
 	};
 
 	let success = argon2::Argon2::new_with_secret(
@@ -127,7 +125,7 @@ pub async fn reset_password(
 	.is_ok();
 
 	if !success {
-		return Err(ErrorType::InvalidPasswordResetToken);
+		return Err(ErrorType::ResetTokenInvalid);
 	}
 
 	let hashed_password = argon2::Argon2::new_with_secret(
@@ -150,20 +148,38 @@ pub async fn reset_password(
 	.map_err(ErrorType::server_error)?
 	.to_string();
 
-	query!(
+	// The token is consumed only if it hasn't been consumed (or replaced) since
+	// it was verified, so that concurrent requests with the same token can't
+	// both succeed
+	let consumed = query!(
 		r#"
 		UPDATE
 			"user"
 		SET
-			password = $1
+			password = $1,
+			password_reset_token = NULL,
+			password_reset_token_expiry = NULL,
+			password_reset_attempts = NULL
 		WHERE
-			id = $2;
+			id = $2 AND
+			password_reset_token = $3;
 		"#,
 		hashed_password,
-		user_data.id
+		user_data.id,
+		password_reset_token,
 	)
 	.execute(&mut **database)
-	.await?;
+	.await?
+	.rows_affected() >
+		0;
+
+	if !consumed {
+		debug!("Password reset token was already used");
+		return Err(ErrorType::ResetTokenInvalid);
+	}
+
+	// Resetting the password also unlocks the account, if it was locked
+	super::clear_failed_login_attempts(redis, &user_data.username).await?;
 
 	AppResponse::builder()
 		.body(ResetPasswordResponse)
@@ -172,3 +188,70 @@ pub async fn reset_password(
 		.build()
 		.into_result()
 }
+
+/// Checks if a password reset token can still be used, given when it expires
+/// and how many times it has been attempted. Expired tokens and tokens that
+/// have been attempted too many times fail with the same error as a wrong
+/// token.
+fn check_reset_token(
+	expiry: Option<OffsetDateTime>,
+	attempts: Option<i32>,
+	now: OffsetDateTime,
+) -> Result<(), ErrorType> {
+	match expiry {
+		Some(expiry) if expiry > now => (),
+		_ => {
+			debug!("Password reset token has expired");
+			return Err(ErrorType::ResetTokenInvalid);
+		}
+	}
+
+	if attempts.unwrap_or(0) > constants::MAX_PASSWORD_RESET_ATTEMPTS.into() {
+		debug!("Password reset attempts exceeded");
+		return Err(ErrorType::ResetTokenInvalid);
+	}
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use time::Duration;
+
+	use super::*;
+
+	#[test]
+	fn expired_and_missing_tokens_are_invalid() {
+		let now = OffsetDateTime::now_utc();
+
+		assert_eq!(
+			check_reset_token(Some(now + Duration::minutes(5)), None, now),
+			Ok(())
+		);
+		assert_eq!(
+			check_reset_token(Some(now), None, now),
+			Err(ErrorType::ResetTokenInvalid)
+		);
+		assert_eq!(
+			check_reset_token(Some(now - Duration::seconds(1)), Some(0), now),
+			Err(ErrorType::ResetTokenInvalid)
+		);
+		assert_eq!(
+			check_reset_token(None, None, now),
+			Err(ErrorType::ResetTokenInvalid)
+		);
+	}
+
+	#[test]
+	fn tokens_attempted_too_many_times_are_invalid() {
+		let now = OffsetDateTime::now_utc();
+		let expiry = Some(now + Duration::minutes(5));
+		let max_attempts = i32::from(constants::MAX_PASSWORD_RESET_ATTEMPTS);
+
+		assert_eq!(check_reset_token(expiry, Some(max_attempts), now), Ok(()));
+		assert_eq!(
+			check_reset_token(expiry, Some(max_attempts + 1), now),
+			Err(ErrorType::ResetTokenInvalid)
+		);
+	}
+}
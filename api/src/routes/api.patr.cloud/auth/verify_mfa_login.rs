@@ -0,0 +1,137 @@
+use axum::http::StatusCode;
+use models::api::auth::*;
+use rustis::commands::{ExpireOption, GenericCommands, StringCommands};
+
+use crate::{prelude::*, redis::keys as redis, utils::mfa};
+
+/// The handler to complete the login of a user that has MFA enabled. The MFA
+/// token issued by the [`login`][super::login] handler is verified along with
+/// either an OTP or a recovery code of the user. Recovery codes are removed
+/// once they are used. Too many invalid codes for the same MFA token
+/// invalidate the token, and the user has to login with their password again.
+/// A valid code clears the failed password attempts of the user.
+pub async fn verify_mfa_login(
+	AppRequest {
+		request:
+			ProcessedApiRequest {
+				path: VerifyMfaLoginPath,
+				query: (),
+				headers: VerifyMfaLoginRequestHeaders { user_agent },
+				body: VerifyMfaLoginRequestProcessed { mfa_token, code },
+			},
+		database,
+		redis,
+		client_ip,
+		config,
+		clock: _,
+	}: AppRequest<'_, VerifyMfaLoginRequest>,
+) -> Result<AppResponse<VerifyMfaLoginRequest>, ErrorType> {
+	trace!("Verifying MFA login");
+
+	let user_id = redis
+		.get::<_, Option<String>>(redis::mfa_login_token(&mfa_token))
+		.await?
+		.and_then(|user_id| user_id.parse::<Uuid>().ok())
+		.ok_or(ErrorType::MfaLoginTokenInvalid)?;
+
+	let user_data = query!(
+		r#"
+		SELECT
+			"user".username,
+			"user".mfa_secret
+		FROM
+			"user"
+		WHERE
+			id = $1;
+		"#,
+		user_id as _,
+	)
+	.fetch_optional(&mut **database)
+	.await?
+	.ok_or(ErrorType::MfaLoginTokenInvalid)?;
+	let mfa_secret = user_data
+		.mfa_secret
+		.ok_or(ErrorType::MfaLoginTokenInvalid)?;
+
+	let is_otp = code.len() <= 7 &&
+		code.chars()
+			.all(|char| char.is_ascii_digit() || char == '-');
+	let code_valid = if is_otp {
+		let otp = code.replace('-', "");
+		mfa::verify_totp(&mfa::decrypt_totp_secret(&config, &mfa_secret)?, &otp)?
+	} else {
+		query!(
+			r#"
+			DELETE FROM
+				user_mfa_recovery_code
+			WHERE
+				user_id = $1 AND
+				code_hash = $2
+			RETURNING
+				code_hash;
+			"#,
+			user_id as _,
+			mfa::hash_recovery_code(&code),
+		)
+		.fetch_optional(&mut **database)
+		.await?
+		.is_some()
+	};
+
+	if !code_valid {
+		let attempts: u64 = redis.incr(redis::mfa_login_attempts(&mfa_token)).await?;
+		redis
+			.expire(
+				redis::mfa_login_attempts(&mfa_token),
+				constants::MFA_LOGIN_TOKEN_VALIDITY
+					.whole_seconds()
+					.unsigned_abs(),
+				ExpireOption::None,
+			)
+			.await?;
+
+		if attempts >= constants::MAX_MFA_LOGIN_ATTEMPTS {
+			info!("Too many invalid MFA codes for user `{}`", user_id);
+			redis
+				.del([
+					redis::mfa_login_token(&mfa_token),
+					redis::mfa_login_attempts(&mfa_token),
+				])
+				.await?;
+		}
+
+		return Err(ErrorType::MfaOtpInvalid);
+	}
+
+	trace!("MFA code is valid");
+
+	// The MFA token can only be used once
+	redis
+		.del([
+			redis::mfa_login_token(&mfa_token),
+			redis::mfa_login_attempts(&mfa_token),
+		])
+		.await?;
+
+	super::clear_failed_login_attempts(redis, &user_data.username).await?;
+
+	let (_, access_token, refresh_token) = super::create_web_login(
+		&mut **database,
+		&config,
+		client_ip,
+		user_agent.to_string(),
+		user_id,
+		config.session.absolute_timeout(),
+	)
+	.await?;
+
+	AppResponse::builder()
+		.body(VerifyMfaLoginResponse {
+			access_token,
+			refresh_token,
+		})
+		.headers(())
+		.status_code(StatusCode::ACCEPTED)
+		.build()
+		.into_result()
+}
@@ -3,8 +3,15 @@ mod user;
 mod workspace;
 
 use axum::Router;
+use tower_http::compression::{
+	predicate::{DefaultPredicate, Predicate, SizeAbove},
+	CompressionLayer,
+};
 
-use crate::prelude::*;
+use crate::{
+	prelude::*,
+	utils::layers::{LoadShedderLayer, OptionsHandlerLayer},
+};
 
 /// Sets up the routes for the API
 #[instrument(skip(state))]
@@ -12,6 +19,18 @@ pub async fn setup_routes(state: &AppState) -> Router {
 	Router::new()
 		.with_state(state.clone())
 		.merge(auth::setup_routes(state).await)
+		.merge(super::download::setup_routes(state))
+		.merge(super::ready::setup_routes(state))
 		.merge(user::setup_routes(state).await)
 		.merge(workspace::setup_routes(state).await)
+		.merge(super::openapi::setup_routes(state))
+		.layer(OptionsHandlerLayer::new())
+		// The default predicate already skips images, gRPC and responses that
+		// are already compressed (having a `Content-Encoding` header)
+		.layer(CompressionLayer::new().compress_when(
+			DefaultPredicate::new().and(SizeAbove::new(state.config.compression.min_size_bytes)),
+		))
+		// Outermost, so that requests are rejected before any work is done on
+		// them
+		.layer(LoadShedderLayer::new(state.config.load_shedding.clone()))
 }
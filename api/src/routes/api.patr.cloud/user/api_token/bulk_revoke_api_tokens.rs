@@ -0,0 +1,106 @@
+use axum::http::StatusCode;
+use models::api::user::*;
+
+use crate::prelude::*;
+
+/// The handler to revoke multiple API tokens of the user at once. Each token
+/// is revoked on its own, and the result for each of them is returned in the
+/// same order as the request. Tokens that don't exist or that belong to
+/// another user are reported in their result instead of failing the request.
+/// Tokens that are already revoked are left as they are. The permissions of
+/// each revoked token are revoked along with it, so that none of its cached
+/// permissions can be used anymore.
+pub async fn bulk_revoke_api_tokens(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: BulkRevokeApiTokensPath,
+				query: (),
+				headers:
+					BulkRevokeApiTokensRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: BulkRevokeApiTokensRequestProcessed { token_ids },
+			},
+		database,
+		redis,
+		client_ip: _,
+		user_data,
+		config: _,
+		clock,
+	}: AuthenticatedAppRequest<'_, BulkRevokeApiTokensRequest>,
+) -> Result<AppResponse<BulkRevokeApiTokensRequest>, ErrorType> {
+	trace!(
+		"Bulk revoking {} API tokens for user: {}",
+		token_ids.len(),
+		user_data.id
+	);
+
+	if token_ids.is_empty() || token_ids.len() > constants::MAX_BULK_REVOKE_API_TOKENS {
+		return Err(ErrorType::WrongParameters);
+	}
+
+	let mut results = Vec::with_capacity(token_ids.len());
+	for token_id in token_ids {
+		let owner = query!(
+			r#"
+			SELECT
+				user_id
+			FROM
+				user_api_token
+			WHERE
+				token_id = $1;
+			"#,
+			token_id as _,
+		)
+		.fetch_optional(&mut **database)
+		.await?
+		.map(|row| row.user_id);
+
+		let error = match owner {
+			None => Some(ErrorType::ResourceDoesNotExist),
+			Some(owner) if owner != user_data.id.into() => Some(ErrorType::Unauthorized),
+			Some(_) => {
+				// Tokens that are already revoked keep the time they were
+				// revoked at
+				let revoked_tokens = query!(
+					r#"
+					UPDATE
+						user_api_token
+					SET
+						revoked = $2
+					WHERE
+						token_id = $1 AND
+						(revoked IS NULL OR revoked > $2);
+					"#,
+					token_id as _,
+					clock.now(),
+				)
+				.execute(&mut **database)
+				.await?
+				.rows_affected();
+
+				if revoked_tokens > 0 {
+					redis::set_revocation_timestamp(
+						redis,
+						redis::keys::login_id_revocation_timestamp(&token_id),
+						clock.now(),
+					)
+					.await?;
+				}
+
+				None
+			}
+		};
+
+		results.push(BulkRevokeApiTokenResult { token_id, error });
+	}
+
+	AppResponse::builder()
+		.body(BulkRevokeApiTokensResponse { results })
+		.headers(())
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
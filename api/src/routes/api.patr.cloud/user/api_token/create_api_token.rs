@@ -1,13 +1,22 @@
+use std::collections::BTreeMap;
+
 use argon2::{password_hash::SaltString, Algorithm, PasswordHasher, Version};
 use axum::http::StatusCode;
 use models::{
 	api::user::*,
 	rbac::{ResourcePermissionType, ResourcePermissionTypeDiscriminant, WorkspacePermission},
+	utils::ApiToken,
 };
 use time::OffsetDateTime;
 
 use crate::prelude::*;
 
+/// The handler to create an API token for the user. The token is granted the
+/// intersection of the requested permissions with the permissions of the user
+/// (as loaded by the authenticator), so that a token never has more access
+/// than the user who created it. The permissions that were actually granted
+/// are returned, and creating a token that wouldn't have any permissions fails
+/// with [`ErrorType::NoPermissionsGranted`].
 pub async fn create_api_token(
 	AuthenticatedAppRequest {
 		request:
@@ -37,10 +46,17 @@ pub async fn create_api_token(
 		client_ip: _,
 		user_data,
 		config,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, CreateApiTokenRequest>,
 ) -> Result<AppResponse<CreateApiTokenRequest>, ErrorType> {
 	info!("Creating API token");
 
+	let permissions = granted_permissions(&user_data.permissions, &permissions);
+	if permissions.is_empty() {
+		debug!("The user does not have any of the permissions requested for the token");
+		return Err(ErrorType::NoPermissionsGranted);
+	}
+
 	let now = OffsetDateTime::now_utc();
 
 	let refresh_token = Uuid::new_v4();
@@ -141,19 +157,9 @@ pub async fn create_api_token(
 
 	trace!("API token inserted");
 
-	for (workspace_id, permission) in permissions {
+	for (workspace_id, permission) in permissions.clone() {
 		trace!("Inserting permission for workspace ID: `{workspace_id}`");
 
-		let Some(user_permission) = user_data.permissions.get(&workspace_id) else {
-			debug!("The user does not have any permissions on workspace ID: `{workspace_id}`");
-			return Err(ErrorType::Unauthorized);
-		};
-
-		if !user_permission.is_superset_of(&permission) {
-			debug!("The user does not have adequate permissions on workspace ID: `{workspace_id}`");
-			return Err(ErrorType::Unauthorized);
-		}
-
 		match permission {
 			WorkspacePermission::SuperAdmin => {
 				trace!("Inserting permission as super admin");
@@ -327,10 +333,97 @@ pub async fn create_api_token(
 	AppResponse::builder()
 		.body(CreateApiTokenResponse {
 			id: token_id,
-			token: format!("patrv1.{}.{}", refresh_token, token_id),
+			token: ApiToken::new(refresh_token, token_id).to_string(),
+			granted_permissions: permissions,
 		})
 		.headers(())
 		.status_code(StatusCode::CREATED)
 		.build()
 		.into_result()
 }
+
+/// Intersects the permissions requested for a token with the permissions of
+/// the user creating it, for each workspace. Workspaces that the user doesn't
+/// have any of the requested permissions on are left out.
+fn granted_permissions(
+	user_permissions: &BTreeMap<Uuid, WorkspacePermission>,
+	requested_permissions: &BTreeMap<Uuid, WorkspacePermission>,
+) -> BTreeMap<Uuid, WorkspacePermission> {
+	requested_permissions
+		.iter()
+		.filter_map(|(workspace_id, requested)| {
+			let granted = user_permissions
+				.get(workspace_id)?
+				.intersection(requested)?;
+			Some((*workspace_id, granted))
+		})
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::BTreeSet;
+
+	use super::*;
+
+	#[test]
+	fn permissions_on_workspaces_the_user_is_not_in_are_dropped() {
+		let (workspace_id, other_workspace_id) = (Uuid::new_v4(), Uuid::new_v4());
+		let user_permissions = BTreeMap::from([(workspace_id, WorkspacePermission::SuperAdmin)]);
+		let requested_permissions = BTreeMap::from([
+			(workspace_id, WorkspacePermission::SuperAdmin),
+			(other_workspace_id, WorkspacePermission::SuperAdmin),
+		]);
+
+		assert_eq!(
+			granted_permissions(&user_permissions, &requested_permissions),
+			BTreeMap::from([(workspace_id, WorkspacePermission::SuperAdmin)])
+		);
+	}
+
+	#[test]
+	fn permissions_are_limited_to_the_ones_the_user_has() {
+		let (workspace_id, permission_id, resource_id) =
+			(Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+		let user_permission = WorkspacePermission::Member {
+			permissions: BTreeMap::from([(
+				permission_id,
+				ResourcePermissionType::Include(BTreeSet::from([resource_id])),
+			)]),
+		};
+		let user_permissions = BTreeMap::from([(workspace_id, user_permission.clone())]);
+		let requested_permissions =
+			BTreeMap::from([(workspace_id, WorkspacePermission::SuperAdmin)]);
+
+		assert_eq!(
+			granted_permissions(&user_permissions, &requested_permissions),
+			BTreeMap::from([(workspace_id, user_permission)])
+		);
+	}
+
+	#[test]
+	fn no_permissions_are_granted_without_common_permissions() {
+		let workspace_id = Uuid::new_v4();
+		let user_permissions = BTreeMap::from([(
+			workspace_id,
+			WorkspacePermission::Member {
+				permissions: BTreeMap::from([(
+					Uuid::new_v4(),
+					ResourcePermissionType::Exclude(BTreeSet::new()),
+				)]),
+			},
+		)]);
+		let requested_permissions = BTreeMap::from([(
+			workspace_id,
+			WorkspacePermission::Member {
+				permissions: BTreeMap::from([(
+					Uuid::new_v4(),
+					ResourcePermissionType::Exclude(BTreeSet::new()),
+				)]),
+			},
+		)]);
+
+		assert!(granted_permissions(&user_permissions, &requested_permissions).is_empty());
+		assert!(granted_permissions(&user_permissions, &BTreeMap::new()).is_empty());
+	}
+}
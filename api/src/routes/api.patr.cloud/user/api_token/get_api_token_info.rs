@@ -26,6 +26,7 @@ pub async fn get_api_token_info(
 		client_ip: _,
 		user_data,
 		config: _,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, GetApiTokenInfoRequest>,
 ) -> Result<AppResponse<GetApiTokenInfoRequest>, ErrorType> {
 	trace!("Getting info for API token: {}", token_id);
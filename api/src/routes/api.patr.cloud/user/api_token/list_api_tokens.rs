@@ -1,7 +1,12 @@
 use std::collections::BTreeMap;
 
-use models::{api::user::*, rbac::WorkspacePermission, utils::TotalCountHeader};
+use models::{
+	api::user::*,
+	rbac::WorkspacePermission,
+	utils::{ListOrder, TotalCountHeader},
+};
 use reqwest::StatusCode;
+use time::OffsetDateTime;
 
 use crate::prelude::*;
 
@@ -10,11 +15,17 @@ pub async fn list_api_tokens(
 		request:
 			ProcessedApiRequest {
 				path: ListApiTokensPath,
-				query: Paginated {
-					data: (),
-					count,
-					page,
-				},
+				query:
+					Paginated {
+						data:
+							ListApiTokensQuery {
+								status,
+								order_by,
+								order,
+							},
+						count,
+						page,
+					},
 				headers:
 					ListApiTokensRequestHeaders {
 						authorization: _,
@@ -27,10 +38,12 @@ pub async fn list_api_tokens(
 		client_ip: _,
 		user_data,
 		config: _,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, ListApiTokensRequest>,
 ) -> Result<AppResponse<ListApiTokensRequest>, ErrorType> {
 	trace!("Listing API tokens for user: {}", user_data.id);
 
+	let now = OffsetDateTime::now_utc();
 	let mut total_count = 0;
 	let tokens = query!(
 		r#"
@@ -41,18 +54,37 @@ pub async fn list_api_tokens(
 			token_exp,
 			allowed_ips,
 			created,
+			revoked,
+			last_used,
 			COUNT(*) OVER() AS "total_count!"
 		FROM
 			user_api_token
 		WHERE
 			user_id = $1 AND
-			revoked IS NULL
+			CASE $2::TEXT
+				WHEN 'active' THEN
+					(revoked IS NULL OR revoked > NOW()) AND
+					(token_exp IS NULL OR token_exp >= NOW())
+				WHEN 'expired' THEN
+					(revoked IS NULL OR revoked > NOW()) AND
+					token_exp < NOW()
+				WHEN 'revoked' THEN
+					revoked <= NOW()
+				ELSE
+					revoked IS NULL
+			END
 		ORDER BY
+			CASE WHEN $3 AND $4 THEN last_used END ASC NULLS LAST,
+			CASE WHEN $3 AND NOT $4 THEN last_used END DESC NULLS LAST,
+			CASE WHEN NOT $3 AND $4 THEN created END ASC,
 			created DESC
-		LIMIT $2
-		OFFSET $3;
+		LIMIT $5
+		OFFSET $6;
 		"#,
 		user_data.id as _,
+		status.map(|status| status.to_string()),
+		order_by.unwrap_or_default() == ApiTokenSortBy::LastUsed,
+		order.unwrap_or_default() == ListOrder::Ascending,
 		count as i32,
 		(count * page) as i32,
 	)
@@ -63,13 +95,17 @@ pub async fn list_api_tokens(
 		total_count = row.total_count;
 		WithId::new(
 			row.token_id,
-			UserApiToken {
-				name: row.name,
-				permissions: BTreeMap::<Uuid, WorkspacePermission>::new(),
-				token_nbf: row.token_nbf,
-				token_exp: row.token_exp,
-				allowed_ips: row.allowed_ips,
-				created: row.created,
+			ListedApiToken {
+				token: UserApiToken {
+					name: row.name,
+					permissions: BTreeMap::<Uuid, WorkspacePermission>::new(),
+					token_nbf: row.token_nbf,
+					token_exp: row.token_exp,
+					allowed_ips: row.allowed_ips,
+					created: row.created,
+				},
+				status: ApiTokenStatus::from_timestamps(row.token_exp, row.revoked, now),
+				last_used: row.last_used,
 			},
 		)
 	})
@@ -1,3 +1,4 @@
+mod bulk_revoke_api_tokens;
 mod create_api_token;
 mod get_api_token_info;
 mod list_api_tokens;
@@ -8,6 +9,7 @@ mod update_api_token;
 use axum::Router;
 
 pub use self::{
+	bulk_revoke_api_tokens::*,
 	create_api_token::*,
 	get_api_token_info::*,
 	list_api_tokens::*,
@@ -21,6 +23,7 @@ use crate::prelude::*;
 #[instrument(skip(state))]
 pub async fn setup_routes(state: &AppState) -> Router {
 	Router::new()
+		.mount_auth_endpoint(bulk_revoke_api_tokens, state)
 		.mount_auth_endpoint(create_api_token, state)
 		.mount_auth_endpoint(get_api_token_info, state)
 		.mount_auth_endpoint(list_api_tokens, state)
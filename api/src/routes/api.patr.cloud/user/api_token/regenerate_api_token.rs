@@ -1,5 +1,5 @@
 use argon2::{password_hash::SaltString, Algorithm, PasswordHasher, Version};
-use models::api::user::*;
+use models::{api::user::*, utils::ApiToken};
 use reqwest::StatusCode;
 
 use crate::prelude::*;
@@ -22,6 +22,7 @@ pub async fn regenerate_api_token(
 		client_ip: _,
 		user_data,
 		config,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, RegenerateApiTokenRequest>,
 ) -> Result<AppResponse<RegenerateApiTokenRequest>, ErrorType> {
 	trace!("Regenerating API token: {}", token_id);
@@ -66,7 +67,7 @@ pub async fn regenerate_api_token(
 
 	AppResponse::builder()
 		.body(RegenerateApiTokenResponse {
-			token: format!("patrv1.{}.{}", refresh_token, token_id),
+			token: ApiToken::new(refresh_token, token_id).to_string(),
 		})
 		.headers(())
 		.status_code(StatusCode::ACCEPTED)
@@ -22,6 +22,7 @@ pub async fn revoke_api_token(
 		client_ip: _,
 		user_data: _,
 		config: _,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, RevokeApiTokenRequest>,
 ) -> Result<AppResponse<RevokeApiTokenRequest>, ErrorType> {
 	trace!("Revoke API token: {}", token_id);
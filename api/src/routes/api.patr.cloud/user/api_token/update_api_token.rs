@@ -3,7 +3,6 @@ use models::{
 	rbac::{ResourcePermissionType, ResourcePermissionTypeDiscriminant, WorkspacePermission},
 };
 use reqwest::StatusCode;
-use rustis::commands::GenericCommands;
 
 use crate::prelude::*;
 
@@ -32,6 +31,7 @@ pub async fn update_api_token(
 		client_ip: _,
 		user_data,
 		config: _,
+		clock,
 	}: AuthenticatedAppRequest<'_, UpdateApiTokenRequest>,
 ) -> Result<AppResponse<UpdateApiTokenRequest>, ErrorType> {
 	trace!("Updating API token: {}", token_id);
@@ -307,9 +307,12 @@ pub async fn update_api_token(
 		}
 	}
 
-	redis
-		.del(redis::keys::permission_for_login_id(&token_id))
-		.await?;
+	redis::set_revocation_timestamp(
+		redis,
+		redis::keys::login_id_revocation_timestamp(&token_id),
+		clock.now(),
+	)
+	.await?;
 
 	AppResponse::builder()
 		.body(UpdateApiTokenResponse)
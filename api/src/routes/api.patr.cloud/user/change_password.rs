@@ -1,9 +1,8 @@
 use argon2::{Algorithm, PasswordHash, PasswordVerifier, Version};
 use axum::http::StatusCode;
 use models::api::user::*;
-use totp_rs::{Algorithm as TotpAlgorithm, Secret, TOTP};
 
-use crate::prelude::*;
+use crate::{prelude::*, utils::mfa};
 
 pub async fn change_password(
 	AuthenticatedAppRequest {
@@ -28,6 +27,7 @@ pub async fn change_password(
 		client_ip: _,
 		config,
 		user_data,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, ChangePasswordRequest>,
 ) -> Result<AppResponse<ChangePasswordRequest>, ErrorType> {
 	info!("Changing user password");
@@ -76,37 +76,8 @@ pub async fn change_password(
 			return Err(ErrorType::MfaRequired);
 		};
 
-		let mfa_valid = TOTP::new(
-			TotpAlgorithm::SHA1,
-			6,
-			1,
-			30,
-			Secret::Encoded(mfa_secret).to_bytes().map_err(|err| {
-				error!(
-					"Unable to parse MFA secret for userId `{}`: {}",
-					user_data.id,
-					err.to_string()
-				);
-				ErrorType::server_error(err)
-			})?,
-		)
-		.inspect_err(|err| {
-			error!(
-				"Unable to parse TOTP for userId `{}`: {}",
-				user_data.id,
-				err.to_string()
-			);
-		})
-		.map_err(ErrorType::server_error)?
-		.check_current(&mfa_otp)
-		.inspect_err(|err| {
-			error!(
-				"System time error while checking TOTP for userId `{}`: {}",
-				user_data.id,
-				err.to_string()
-			);
-		})
-		.map_err(ErrorType::server_error)?;
+		let mfa_valid =
+			mfa::verify_totp(&mfa::decrypt_totp_secret(&config, &mfa_secret)?, &mfa_otp)?;
 
 		if !mfa_valid {
 			info!("MFA OTP invalid for userId `{}`", user_data.id);
@@ -119,7 +90,10 @@ pub async fn change_password(
 		UPDATE
 			"user"
 		SET
-			password = $1
+			password = $1,
+			password_reset_token = NULL,
+			password_reset_token_expiry = NULL,
+			password_reset_attempts = NULL
 		WHERE
 			id = $2;
 		"#,
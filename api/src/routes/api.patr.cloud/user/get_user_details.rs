@@ -21,6 +21,7 @@ pub async fn get_user_details(
 		client_ip: _,
 		config: _,
 		user_data,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, GetUserDetailsRequest>,
 ) -> Result<AppResponse<GetUserDetailsRequest>, ErrorType> {
 	info!("Getting user details by UserID");
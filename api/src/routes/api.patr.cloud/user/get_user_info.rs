@@ -21,6 +21,7 @@ pub async fn get_user_info(
 		client_ip: _,
 		config: _,
 		user_data,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, GetUserInfoRequest>,
 ) -> Result<AppResponse<GetUserInfoRequest>, ErrorType> {
 	info!("Getting authenticated user info");
@@ -35,9 +36,15 @@ pub async fn get_user_info(
 			"user".mfa_secret,
 			"user".recovery_phone_country_code,
 			"user".recovery_phone_number,
-			"user".recovery_email
+			"user".recovery_email,
+			user_unverified_email.email AS "pending_recovery_email?"
 		FROM
 			"user"
+		LEFT JOIN
+			user_unverified_email
+		ON
+			user_unverified_email.user_id = "user".id AND
+			user_unverified_email.verification_token_expiry > NOW()
 		WHERE
 			"user".id = $1;
 		"#,
@@ -58,6 +65,7 @@ pub async fn get_user_info(
 		created: row.created,
 		is_mfa_enabled: row.mfa_secret.is_some(),
 		recovery_email: row.recovery_email,
+		pending_recovery_email: row.pending_recovery_email,
 		recovery_phone_number: row
 			.recovery_phone_country_code
 			.zip(row.recovery_phone_number)
@@ -21,6 +21,7 @@ pub async fn list_workspaces(
 		client_ip: _,
 		config: _,
 		user_data,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, ListUserWorkspacesRequest>,
 ) -> Result<AppResponse<ListUserWorkspacesRequest>, ErrorType> {
 	info!("Listing all user workspaces");
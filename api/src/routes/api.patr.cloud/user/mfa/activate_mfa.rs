@@ -1,10 +1,13 @@
 use axum::http::StatusCode;
 use models::{api::user::*, RequestUserData};
-use rustis::commands::StringCommands;
-use totp_rs::{Algorithm as TotpAlgorithm, Secret, TOTP};
+use rustis::commands::{GenericCommands, StringCommands};
 
-use crate::{prelude::*, redis::keys as redis};
+use crate::{prelude::*, redis::keys as redis, utils::mfa};
 
+/// The handler to activate MFA for a user, by verifying an OTP generated from
+/// the secret that was returned by the [`enable_totp`][super::enable_totp]
+/// handler. The secret is stored encrypted, along with the hashes of the
+/// recovery codes, replacing any existing ones.
 pub async fn activate_mfa(
 	AuthenticatedAppRequest {
 		request:
@@ -17,8 +20,9 @@ pub async fn activate_mfa(
 		database,
 		redis,
 		client_ip: _,
-		config: _,
+		config,
 		user_data: RequestUserData { id, .. },
+		clock: _,
 	}: AuthenticatedAppRequest<'_, ActivateMfaRequest>,
 ) -> Result<AppResponse<ActivateMfaRequest>, ErrorType> {
 	info!("Activating MFA for user");
@@ -36,45 +40,31 @@ pub async fn activate_mfa(
 	)
 	.fetch_optional(&mut **database)
 	.await?
-	.ok_or(ErrorType::UserNotFound)?;
+	.or_not_found_with(ErrorType::UserNotFound)?;
 
 	if mfa_detail.mfa_secret.is_some() {
 		return Err(ErrorType::MfaAlreadyActive);
 	}
 
-	let Some(secret) = redis
+	let Some(encrypted_secret) = redis
 		.get::<_, Option<String>>(redis::user_mfa_secret(&id))
 		.await?
 	else {
 		error!("MFA secret not found for userId `{}`", id);
 		return Err(ErrorType::MfaRequired);
 	};
+	let Some(recovery_code_hashes) = redis
+		.get::<_, Option<String>>(redis::user_mfa_recovery_codes(&id))
+		.await?
+	else {
+		error!("MFA recovery codes not found for userId `{}`", id);
+		return Err(ErrorType::MfaRequired);
+	};
+	let recovery_code_hashes = serde_json::from_str::<Vec<String>>(&recovery_code_hashes)
+		.map_err(ErrorType::server_error)?;
 
-	let mfa_valid = TOTP::new(
-		TotpAlgorithm::SHA1,
-		6,
-		1,
-		30,
-		Secret::Encoded(secret.clone())
-			.to_bytes()
-			.inspect_err(|err| {
-				error!(
-					"Unable to parse MFA secret for userId `{}`: {}",
-					id,
-					err.to_string()
-				);
-			})?,
-	)
-	.inspect_err(|err| {
-		error!(
-			"Unable to parse TOTP for userId `{}`: {}",
-			id,
-			err.to_string()
-		);
-	})?
-	.check_current(&otp)?;
-
-	if !mfa_valid {
+	let secret = mfa::decrypt_totp_secret(&config, &encrypted_secret)?;
+	if !mfa::verify_totp(&secret, &otp)? {
 		return Err(ErrorType::MfaOtpInvalid);
 	}
 
@@ -88,11 +78,46 @@ pub async fn activate_mfa(
 			id = $1;
 		"#,
 		id as _,
-		secret
+		encrypted_secret
+	)
+	.execute(&mut **database)
+	.await?;
+
+	query!(
+		r#"
+		DELETE FROM
+			user_mfa_recovery_code
+		WHERE
+			user_id = $1;
+		"#,
+		id as _,
+	)
+	.execute(&mut **database)
+	.await?;
+
+	query!(
+		r#"
+		INSERT INTO
+			user_mfa_recovery_code(
+				user_id,
+				code_hash
+			)
+		VALUES
+			($1, UNNEST($2::TEXT[]));
+		"#,
+		id as _,
+		&recovery_code_hashes,
 	)
 	.execute(&mut **database)
 	.await?;
 
+	redis
+		.del([
+			redis::user_mfa_secret(&id),
+			redis::user_mfa_recovery_codes(&id),
+		])
+		.await?;
+
 	AppResponse::builder()
 		.body(ActivateMfaResponse)
 		.headers(())
@@ -0,0 +1,81 @@
+use axum::http::StatusCode;
+use models::api::user::*;
+
+use crate::{prelude::*, utils::mfa};
+
+/// The handler to disable TOTP for a user. This requires a valid OTP, and
+/// removes the recovery codes of the user as well.
+pub async fn disable_totp(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: DisableTotpPath,
+				query: (),
+				headers: DisableTotpRequestHeaders { authorization: _ },
+				body: DisableTotpRequestProcessed { otp },
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config,
+		user_data,
+		clock: _,
+	}: AuthenticatedAppRequest<'_, DisableTotpRequest>,
+) -> Result<AppResponse<DisableTotpRequest>, ErrorType> {
+	info!("Disabling TOTP for user");
+
+	let mfa_detail = query!(
+		r#"
+		SELECT
+			"user".mfa_secret
+		FROM
+			"user"
+		WHERE
+			id = $1;
+		"#,
+		user_data.id as _
+	)
+	.fetch_one(&mut **database)
+	.await?;
+
+	let Some(secret) = mfa_detail.mfa_secret else {
+		return Err(ErrorType::MfaAlreadyInactive);
+	};
+
+	if !mfa::verify_totp(&mfa::decrypt_totp_secret(&config, &secret)?, &otp)? {
+		return Err(ErrorType::MfaOtpInvalid);
+	}
+
+	query!(
+		r#"
+		UPDATE
+			"user"
+		SET
+			mfa_secret = NULL
+		WHERE
+			id = $1;
+		"#,
+		user_data.id as _
+	)
+	.execute(&mut **database)
+	.await?;
+
+	query!(
+		r#"
+		DELETE FROM
+			user_mfa_recovery_code
+		WHERE
+			user_id = $1;
+		"#,
+		user_data.id as _
+	)
+	.execute(&mut **database)
+	.await?;
+
+	AppResponse::builder()
+		.body(DisableTotpResponse)
+		.headers(())
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
@@ -0,0 +1,99 @@
+use axum::http::StatusCode;
+use models::api::user::*;
+use rustis::commands::StringCommands;
+
+use crate::{prelude::*, redis::keys as redis, utils::mfa};
+
+/// The handler to start enabling TOTP for a user. A new secret and a new set of
+/// recovery codes are generated and kept in Redis until MFA is activated by
+/// verifying an OTP using the [`activate_mfa`][super::activate_mfa] handler.
+/// Only the hashes of the recovery codes are stored, so they're only ever
+/// shown to the user in this response.
+pub async fn enable_totp(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: EnableTotpPath,
+				query: (),
+				headers: EnableTotpRequestHeaders { authorization: _ },
+				body: EnableTotpRequestProcessed,
+			},
+		database,
+		redis,
+		client_ip: _,
+		config,
+		user_data,
+		clock: _,
+	}: AuthenticatedAppRequest<'_, EnableTotpRequest>,
+) -> Result<AppResponse<EnableTotpRequest>, ErrorType> {
+	info!("Enabling TOTP for user");
+
+	let mfa_detail = query!(
+		r#"
+		SELECT
+			"user".username,
+			"user".mfa_secret
+		FROM
+			"user"
+		WHERE
+			id = $1;
+		"#,
+		user_data.id as _
+	)
+	.fetch_one(&mut **database)
+	.await?;
+
+	if mfa_detail.mfa_secret.is_some() {
+		return Err(ErrorType::MfaAlreadyActive);
+	}
+
+	let secret = mfa::generate_totp_secret();
+	let recovery_codes = mfa::generate_recovery_codes();
+
+	redis
+		.setex(
+			redis::user_mfa_secret(&user_data.id),
+			constants::MFA_ENROLLMENT_VALIDITY
+				.whole_seconds()
+				.unsigned_abs(),
+			mfa::encrypt_totp_secret(&config, &secret)?,
+		)
+		.await
+		.inspect_err(|err| {
+			error!(
+				"Error setting the MFA secret for user `{}`: `{}`",
+				user_data.id, err
+			);
+		})?;
+	redis
+		.setex(
+			redis::user_mfa_recovery_codes(&user_data.id),
+			constants::MFA_ENROLLMENT_VALIDITY
+				.whole_seconds()
+				.unsigned_abs(),
+			serde_json::to_string(
+				&recovery_codes
+					.iter()
+					.map(|code| mfa::hash_recovery_code(code))
+					.collect::<Vec<_>>(),
+			)
+			.map_err(ErrorType::server_error)?,
+		)
+		.await
+		.inspect_err(|err| {
+			error!(
+				"Error setting the MFA recovery codes for user `{}`: `{}`",
+				user_data.id, err
+			);
+		})?;
+
+	AppResponse::builder()
+		.body(EnableTotpResponse {
+			provisioning_uri: mfa::totp_provisioning_uri(&mfa_detail.username, &secret),
+			recovery_codes,
+		})
+		.headers(())
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
@@ -1,10 +1,10 @@
 mod activate_mfa;
-mod deactivate_mfa;
-mod get_mfa_secret;
+mod disable_totp;
+mod enable_totp;
 
 use axum::Router;
 
-pub use self::{activate_mfa::*, deactivate_mfa::*, get_mfa_secret::*};
+pub use self::{activate_mfa::*, disable_totp::*, enable_totp::*};
 use crate::prelude::*;
 
 /// Sets up the MFA routes
@@ -12,6 +12,6 @@ use crate::prelude::*;
 pub async fn setup_routes(state: &AppState) -> Router {
 	Router::new()
 		.mount_auth_endpoint(activate_mfa, state)
-		.mount_auth_endpoint(deactivate_mfa, state)
-		.mount_auth_endpoint(get_mfa_secret, state)
+		.mount_auth_endpoint(disable_totp, state)
+		.mount_auth_endpoint(enable_totp, state)
 }
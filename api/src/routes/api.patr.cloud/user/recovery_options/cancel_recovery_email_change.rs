@@ -0,0 +1,49 @@
+use axum::http::StatusCode;
+use models::api::user::*;
+
+use crate::prelude::*;
+
+/// The handler to cancel the pending change of the recovery email of a user.
+/// Cancelling when there is no pending change is not an error.
+pub async fn cancel_recovery_email_change(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: CancelRecoveryEmailChangePath,
+				query: (),
+				headers:
+					CancelRecoveryEmailChangeRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: CancelRecoveryEmailChangeRequestProcessed,
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config: _,
+		user_data,
+		clock: _,
+	}: AuthenticatedAppRequest<'_, CancelRecoveryEmailChangeRequest>,
+) -> Result<AppResponse<CancelRecoveryEmailChangeRequest>, ErrorType> {
+	info!("Cancelling the pending recovery email change of user");
+
+	query!(
+		r#"
+		DELETE FROM
+			user_unverified_email
+		WHERE
+			user_id = $1;
+		"#,
+		user_data.id as _,
+	)
+	.execute(&mut **database)
+	.await?;
+
+	AppResponse::builder()
+		.body(CancelRecoveryEmailChangeResponse)
+		.headers(())
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
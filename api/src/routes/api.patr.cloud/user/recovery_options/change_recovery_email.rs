@@ -0,0 +1,156 @@
+use std::ops::Add;
+
+use argon2::{password_hash::SaltString, Algorithm, PasswordHasher, Version};
+use axum::http::StatusCode;
+use models::api::{auth::RecoveryMethodType, user::*};
+use rand::Rng;
+use time::OffsetDateTime;
+
+use crate::{prelude::*, utils::email};
+
+/// The handler to change the recovery email of a user. The new email is stored
+/// as unverified, along with the hash of an OTP that is sent to it, replacing
+/// any change that was pending. The current recovery email is left untouched
+/// until the new email is verified.
+pub async fn change_recovery_email(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: ChangeRecoveryEmailPath,
+				query: (),
+				headers:
+					ChangeRecoveryEmailRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: ChangeRecoveryEmailRequestProcessed { email },
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config,
+		user_data,
+		clock: _,
+	}: AuthenticatedAppRequest<'_, ChangeRecoveryEmailRequest>,
+) -> Result<AppResponse<ChangeRecoveryEmailRequest>, ErrorType> {
+	info!("Changing the recovery email of user");
+
+	// A recovery email that can't be sent an OTP could never be verified
+	config
+		.recovery_methods
+		.ensure_supported(RecoveryMethodType::Email)?;
+
+	let email = email.to_lowercase();
+
+	let is_email_taken = query!(
+		r#"
+		SELECT
+			email
+		FROM
+			user_email
+		WHERE
+			email = $1;
+		"#,
+		&email,
+	)
+	.fetch_optional(&mut **database)
+	.await?
+	.is_some();
+
+	if is_email_taken {
+		return Err(ErrorType::EmailUnavailable);
+	}
+
+	let now = OffsetDateTime::now_utc();
+	let otp = format!("{:06}", rand::thread_rng().gen_range(constants::OTP_RANGE));
+	let hashed_otp = argon2::Argon2::new_with_secret(
+		config.password_pepper.as_ref(),
+		Algorithm::Argon2id,
+		Version::V0x13,
+		constants::HASHING_PARAMS,
+	)
+	.inspect_err(|err| {
+		error!("Error creating Argon2: `{}`", err);
+	})
+	.map_err(ErrorType::server_error)?
+	.hash_password(
+		otp.as_bytes(),
+		SaltString::generate(&mut rand::thread_rng()).as_salt(),
+	)
+	.inspect_err(|err| {
+		error!("Error hashing OTP: `{}`", err);
+	})
+	.map_err(ErrorType::server_error)?
+	.to_string();
+	let otp_expiry = now.add(constants::OTP_VALIDITY);
+
+	// Only one change can be pending at a time
+	query!(
+		r#"
+		DELETE FROM
+			user_unverified_email
+		WHERE
+			user_id = $1;
+		"#,
+		user_data.id as _,
+	)
+	.execute(&mut **database)
+	.await?;
+
+	// Another user could be verifying the same email. Only take it over if
+	// their verification has expired
+	let rows_affected = query!(
+		r#"
+		INSERT INTO
+			user_unverified_email(
+				email,
+				user_id,
+				verification_token_hash,
+				verification_token_expiry
+			)
+		VALUES
+			($1, $2, $3, $4)
+		ON CONFLICT
+			(email)
+		DO UPDATE SET
+			user_id = EXCLUDED.user_id,
+			verification_token_hash = EXCLUDED.verification_token_hash,
+			verification_token_expiry = EXCLUDED.verification_token_expiry
+		WHERE
+			user_unverified_email.verification_token_expiry < NOW();
+		"#,
+		&email,
+		user_data.id as _,
+		hashed_otp,
+		otp_expiry,
+	)
+	.execute(&mut **database)
+	.await?
+	.rows_affected();
+
+	if rows_affected == 0 {
+		return Err(ErrorType::EmailUnavailable);
+	}
+
+	trace!("Unverified recovery email inserted into the database");
+
+	email::send_email(
+		&config.email,
+		&email,
+		"Verify your new recovery email",
+		format!(
+			"Use the code {otp} to verify this email as the recovery email of your Patr \
+			account. The code expires in {} minutes. If you didn't ask for this, you can \
+			ignore this email.",
+			constants::OTP_VALIDITY.whole_minutes()
+		),
+	)
+	.await?;
+
+	AppResponse::builder()
+		.body(ChangeRecoveryEmailResponse)
+		.headers(())
+		.status_code(StatusCode::ACCEPTED)
+		.build()
+		.into_result()
+}
@@ -1,4 +1,5 @@
-mod update_user_email;
+mod cancel_recovery_email_change;
+mod change_recovery_email;
 mod update_user_phone_number;
 mod verify_user_email;
 mod verify_user_phone_number;
@@ -6,7 +7,8 @@ mod verify_user_phone_number;
 use axum::Router;
 
 pub use self::{
-	update_user_email::*,
+	cancel_recovery_email_change::*,
+	change_recovery_email::*,
 	update_user_phone_number::*,
 	verify_user_email::*,
 	verify_user_phone_number::*,
@@ -17,7 +19,8 @@ use crate::prelude::*;
 #[instrument(skip(state))]
 pub async fn setup_routes(state: &AppState) -> Router {
 	Router::new()
-		.mount_auth_endpoint(update_user_email, state)
+		.mount_auth_endpoint(cancel_recovery_email_change, state)
+		.mount_auth_endpoint(change_recovery_email, state)
 		.mount_auth_endpoint(update_user_phone_number, state)
 		.mount_auth_endpoint(verify_user_email, state)
 		.mount_auth_endpoint(verify_user_phone_number, state)
@@ -1,4 +1,4 @@
-use models::api::user::*;
+use models::api::{auth::RecoveryMethodType, user::*};
 
 use crate::prelude::*;
 
@@ -15,7 +15,14 @@ pub async fn update_user_phone_number(
 		client_ip,
 		user_data,
 		config,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, UpdateUserPhoneNumberRequest>,
 ) -> Result<AppResponse<UpdateUserPhoneNumberRequest>, ErrorType> {
+	// A recovery phone number that can't be sent an OTP could never be
+	// verified
+	config
+		.recovery_methods
+		.ensure_supported(RecoveryMethodType::PhoneNumber)?;
+
 	todo!()
 }
@@ -1,21 +1,180 @@
+use argon2::{Algorithm, PasswordHash, PasswordVerifier, Version};
+use axum::http::StatusCode;
 use models::api::user::*;
 
-use crate::prelude::*;
+use crate::{prelude::*, utils::email};
 
+/// The handler to verify the new recovery email of a user. Once the OTP sent
+/// to the new email is verified, it replaces the current recovery email, and
+/// any password reset that is in progress is invalidated, since the reset token
+/// was sent to the old email.
 pub async fn verify_user_email(
 	AuthenticatedAppRequest {
-		request: ProcessedApiRequest {
-			path,
-			query,
-			headers,
-			body,
-		},
+		request:
+			ProcessedApiRequest {
+				path: VerifyUserEmailPath,
+				query: (),
+				headers:
+					VerifyUserEmailRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: VerifyUserEmailRequestProcessed {
+					email,
+					verification_token,
+				},
+			},
 		database,
-		redis,
-		client_ip,
-		user_data,
+		redis: _,
+		client_ip: _,
 		config,
+		user_data,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, VerifyUserEmailRequest>,
 ) -> Result<AppResponse<VerifyUserEmailRequest>, ErrorType> {
-	todo!()
+	info!("Verifying the new recovery email of user");
+
+	let email = email.to_lowercase();
+
+	let row = query!(
+		r#"
+		SELECT
+			verification_token_hash
+		FROM
+			user_unverified_email
+		WHERE
+			user_id = $1 AND
+			email = $2 AND
+			verification_token_expiry > NOW();
+		"#,
+		user_data.id as _,
+		&email,
+	)
+	.fetch_optional(&mut **database)
+	.await?
+	.ok_or(ErrorType::InvalidEmailVerificationToken)?;
+
+	let success = argon2::Argon2::new_with_secret(
+		config.password_pepper.as_ref(),
+		Algorithm::Argon2id,
+		Version::V0x13,
+		constants::HASHING_PARAMS,
+	)
+	.inspect_err(|err| {
+		error!("Error creating Argon2: `{}`", err);
+	})
+	.map_err(ErrorType::server_error)?
+	.verify_password(
+		verification_token.as_bytes(),
+		&PasswordHash::new(&row.verification_token_hash).map_err(ErrorType::server_error)?,
+	)
+	.is_ok();
+
+	if !success {
+		debug!("Email verification token is invalid");
+		return Err(ErrorType::InvalidEmailVerificationToken);
+	}
+
+	trace!("Email verification token is validated");
+
+	let old_email = query!(
+		r#"
+		SELECT
+			recovery_email
+		FROM
+			"user"
+		WHERE
+			id = $1;
+		"#,
+		user_data.id as _,
+	)
+	.fetch_one(&mut **database)
+	.await?
+	.recovery_email;
+
+	query!(
+		r#"
+		INSERT INTO
+			user_email(
+				user_id,
+				email
+			)
+		VALUES
+			($1, $2);
+		"#,
+		user_data.id as _,
+		&email,
+	)
+	.execute(&mut **database)
+	.await?;
+
+	query!(
+		r#"
+		UPDATE
+			"user"
+		SET
+			recovery_email = $2,
+			password_reset_token = NULL,
+			password_reset_token_expiry = NULL,
+			password_reset_attempts = NULL
+		WHERE
+			id = $1;
+		"#,
+		user_data.id as _,
+		&email,
+	)
+	.execute(&mut **database)
+	.await?;
+
+	if let Some(old_email) = &old_email {
+		query!(
+			r#"
+			DELETE FROM
+				user_email
+			WHERE
+				user_id = $1 AND
+				email = $2;
+			"#,
+			user_data.id as _,
+			old_email,
+		)
+		.execute(&mut **database)
+		.await?;
+	}
+
+	query!(
+		r#"
+		DELETE FROM
+			user_unverified_email
+		WHERE
+			user_id = $1;
+		"#,
+		user_data.id as _,
+	)
+	.execute(&mut **database)
+	.await?;
+
+	trace!("Recovery email updated");
+
+	// Let the owner of the old email know, in case the change wasn't made by
+	// them
+	if let Some(old_email) = &old_email {
+		email::send_email(
+			&config.email,
+			old_email,
+			"Your recovery email was changed",
+			format!(
+				"The recovery email of your Patr account was changed to {email}. If you \
+				didn't make this change, contact support right away."
+			),
+		)
+		.await?;
+	}
+
+	AppResponse::builder()
+		.body(VerifyUserEmailResponse)
+		.headers(())
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
 }
@@ -15,6 +15,7 @@ pub async fn verify_user_phone_number(
 		client_ip,
 		user_data,
 		config,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, VerifyUserPhoneNumberRequest>,
 ) -> Result<AppResponse<VerifyUserPhoneNumberRequest>, ErrorType> {
 	todo!()
@@ -24,6 +24,7 @@ pub async fn update_user_info(
 		client_ip: _,
 		config: _,
 		user_data,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, UpdateUserInfoRequest>,
 ) -> Result<AppResponse<UpdateUserInfoRequest>, ErrorType> {
 	info!("Updating user info");
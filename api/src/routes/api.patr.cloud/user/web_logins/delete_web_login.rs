@@ -15,6 +15,7 @@ pub async fn delete_web_login(
 		client_ip,
 		user_data,
 		config,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, DeleteWebLoginRequest>,
 ) -> Result<AppResponse<DeleteWebLoginRequest>, ErrorType> {
 	todo!()
@@ -15,6 +15,7 @@ pub async fn get_web_login_info(
 		client_ip,
 		user_data,
 		config,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, GetWebLoginInfoRequest>,
 ) -> Result<AppResponse<GetWebLoginInfoRequest>, ErrorType> {
 	todo!()
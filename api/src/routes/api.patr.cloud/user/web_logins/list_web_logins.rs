@@ -15,6 +15,7 @@ pub async fn list_web_logins(
 		client_ip,
 		user_data,
 		config,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, ListWebLoginsRequest>,
 ) -> Result<AppResponse<ListWebLoginsRequest>, ErrorType> {
 	todo!()
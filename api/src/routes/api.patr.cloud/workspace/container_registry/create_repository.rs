@@ -21,6 +21,7 @@ pub async fn create_repository(
 		client_ip: _,
 		config: _,
 		user_data: _,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, CreateContainerRepositoryRequest>,
 ) -> Result<AppResponse<CreateContainerRepositoryRequest>, ErrorType> {
 	info!(
@@ -25,6 +25,7 @@ pub async fn delete_repository(
 		client_ip: _,
 		config,
 		user_data,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, DeleteContainerRepositoryRequest>,
 ) -> Result<AppResponse<DeleteContainerRepositoryRequest>, ErrorType> {
 	info!(
@@ -26,6 +26,7 @@ pub async fn delete_repository_image(
 		client_ip: _,
 		config,
 		user_data,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, DeleteContainerRepositoryImageRequest>,
 ) -> Result<AppResponse<DeleteContainerRepositoryImageRequest>, ErrorType> {
 	info!("Starting: Delete container repository image");
@@ -0,0 +1,80 @@
+use axum::http::StatusCode;
+use models::{api::workspace::container_registry::*, prelude::*};
+
+use crate::{prelude::*, routes::download, utils::signed_url};
+
+/// Issues a signed URL to download a blob of a repository. The blob must be a
+/// part of one of the images of the repository, so that the permission on the
+/// repository can't be used to download the blobs of other repositories. The
+/// URL isn't checked against the permissions of the user once it's issued.
+pub async fn get_repository_blob_download_url(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path:
+					GetContainerRepositoryBlobDownloadUrlPath {
+						workspace_id: _,
+						repository_id,
+						digest,
+					},
+				query: (),
+				headers:
+					GetContainerRepositoryBlobDownloadUrlRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: GetContainerRepositoryBlobDownloadUrlRequestProcessed,
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config,
+		user_data: _,
+		clock,
+	}: AuthenticatedAppRequest<'_, GetContainerRepositoryBlobDownloadUrlRequest>,
+) -> Result<AppResponse<GetContainerRepositoryBlobDownloadUrlRequest>, ErrorType> {
+	info!("Issuing download URL for blob `{digest}` of repository `{repository_id}`");
+
+	query!(
+		r#"
+		SELECT
+			container_registry_manifest_blob.blob_digest
+		FROM
+			container_registry_manifest_blob
+		INNER JOIN
+			container_registry_repository_manifest
+		ON
+			container_registry_repository_manifest.manifest_digest =
+				container_registry_manifest_blob.manifest_digest
+		INNER JOIN
+			container_registry_repository
+		ON
+			container_registry_repository.id =
+				container_registry_repository_manifest.repository_id
+		WHERE
+			container_registry_repository.id = $1 AND
+			container_registry_repository.deleted IS NULL AND
+			container_registry_manifest_blob.blob_digest = $2
+		LIMIT 1;
+		"#,
+		repository_id as _,
+		digest,
+	)
+	.fetch_optional(&mut **database)
+	.await?
+	.or_not_found()?;
+
+	let expires_at = clock.now() + config.security.signed_urls.expiry();
+	let url = signed_url::sign_path(
+		&config.security.signed_urls,
+		&download::container_registry_blob_path(&digest),
+		expires_at,
+	)?;
+
+	AppResponse::builder()
+		.body(GetContainerRepositoryBlobDownloadUrlResponse { url, expires_at })
+		.headers(())
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
@@ -26,6 +26,7 @@ pub async fn get_repository_image_details(
 		client_ip: _,
 		config: _,
 		user_data: _,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, GetContainerRepositoryImageDetailsRequest>,
 ) -> Result<AppResponse<GetContainerRepositoryImageDetailsRequest>, ErrorType> {
 	info!("Starting: Get image details");
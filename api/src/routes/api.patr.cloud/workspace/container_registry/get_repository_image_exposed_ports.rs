@@ -35,6 +35,7 @@ pub async fn get_repository_image_exposed_ports(
 		client_ip: _,
 		config: _,
 		user_data,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, GetContainerRepositoryExposedPortsRequest>,
 ) -> Result<AppResponse<GetContainerRepositoryExposedPortsRequest>, ErrorType> {
 	info!("Starting: Get exposed ports");
@@ -24,6 +24,7 @@ pub async fn get_repository_info(
 		client_ip: _,
 		config: _,
 		user_data: _,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, GetContainerRepositoryInfoRequest>,
 ) -> Result<AppResponse<GetContainerRepositoryInfoRequest>, ErrorType> {
 	info!("Starting: Get repository info");
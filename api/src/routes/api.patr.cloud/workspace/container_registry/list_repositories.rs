@@ -25,6 +25,7 @@ pub async fn list_repositories(
 		client_ip: _,
 		config: _,
 		user_data,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, ListContainerRepositoriesRequest>,
 ) -> Result<AppResponse<ListContainerRepositoriesRequest>, ErrorType> {
 	info!("Listing container registry repositories");
@@ -29,6 +29,7 @@ pub async fn list_repository_tags(
 		client_ip: _,
 		config: _,
 		user_data: _,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, ListContainerRepositoryTagsRequest>,
 ) -> Result<AppResponse<ListContainerRepositoryTagsRequest>, ErrorType> {
 	info!("Listing tags for repository: {}", repository_id);
@@ -10,6 +10,7 @@ use crate::{prelude::*, utils::config::AppConfig};
 mod create_repository;
 mod delete_repository;
 mod delete_repository_image;
+mod get_repository_blob_download_url;
 mod get_repository_image_details;
 mod get_repository_image_exposed_ports;
 mod get_repository_info;
@@ -20,6 +21,7 @@ use self::{
 	create_repository::*,
 	delete_repository::*,
 	delete_repository_image::*,
+	get_repository_blob_download_url::*,
 	get_repository_image_details::*,
 	get_repository_image_exposed_ports::*,
 	get_repository_info::*,
@@ -33,6 +35,7 @@ pub async fn setup_routes(state: &AppState) -> Router {
 		.mount_auth_endpoint(create_repository, state)
 		.mount_auth_endpoint(delete_repository, state)
 		.mount_auth_endpoint(delete_repository_image, state)
+		.mount_auth_endpoint(get_repository_blob_download_url, state)
 		.mount_auth_endpoint(get_repository_image_details, state)
 		.mount_auth_endpoint(get_repository_image_exposed_ports, state)
 		.mount_auth_endpoint(get_repository_info, state)
@@ -21,6 +21,7 @@ pub async fn create_workspace(
 		client_ip,
 		config,
 		user_data,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, CreateWorkspaceRequest>,
 ) -> Result<AppResponse<CreateWorkspaceRequest>, ErrorType> {
 	info!("Creating workspace: `{name}`");
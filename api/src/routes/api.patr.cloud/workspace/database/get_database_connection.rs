@@ -0,0 +1,102 @@
+use std::str::FromStr;
+
+use axum::http::StatusCode;
+use models::api::workspace::database::*;
+
+use crate::{
+	prelude::*,
+	utils::secret_store::{AppSecretStore, SecretStore},
+};
+
+/// The handler to get the current credentials of a database and the status of
+/// their rotation. The password is read from the secret store. The credentials
+/// are secrets, and must never be logged.
+pub async fn get_database_connection(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: GetDatabaseConnectionPath {
+					workspace_id,
+					database_id,
+				},
+				query: (),
+				headers:
+					GetDatabaseConnectionRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: GetDatabaseConnectionRequestProcessed,
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config,
+		user_data: _,
+		clock,
+	}: AuthenticatedAppRequest<'_, GetDatabaseConnectionRequest>,
+) -> Result<AppResponse<GetDatabaseConnectionRequest>, ErrorType> {
+	info!("Getting the connection details of database `{database_id}`");
+
+	let engine = query!(
+		r#"
+		SELECT
+			engine::TEXT AS "engine!"
+		FROM
+			managed_database
+		WHERE
+			id = $1 AND
+			workspace_id = $2 AND
+			deleted IS NULL;
+		"#,
+		database_id as _,
+		workspace_id as _,
+	)
+	.fetch_optional(&mut **database)
+	.await?
+	.or_not_found()?
+	.engine;
+	let engine = DatabaseEngine::from_str(&engine).map_err(ErrorType::server_error)?;
+
+	let credentials = query!(
+		r#"
+		SELECT
+			username,
+			secret_id
+		FROM
+			managed_database_credential
+		WHERE
+			database_id = $1 AND
+			expires IS NULL;
+		"#,
+		database_id as _,
+	)
+	.fetch_optional(&mut **database)
+	.await?
+	.or_not_found()?;
+
+	let password = AppSecretStore::new(&config.secrets, &mut **database)
+		.get(workspace_id, credentials.secret_id.into())
+		.await?
+		.ok_or_else(|| ErrorType::server_error("the password of the database is missing"))?;
+
+	let rotation =
+		super::get_credential_rotation(&mut **database, database_id, clock.now()).await?;
+
+	let connection = models::api::workspace::database::DatabaseConnection {
+		host: format!("{database_id}.{}", constants::MANAGED_DATABASE_DOMAIN),
+		port: engine.default_port(),
+		username: credentials.username,
+		password,
+	};
+
+	AppResponse::builder()
+		.body(GetDatabaseConnectionResponse {
+			connection_string: connection.connection_string(&engine),
+			connection,
+			rotation,
+		})
+		.headers(())
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
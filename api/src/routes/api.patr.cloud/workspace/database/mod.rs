@@ -1,7 +1,49 @@
 use axum::{http::StatusCode, Router};
-use models::{api::workspace::database::*, utils::TotalCountHeader, ErrorType};
+use models::{
+	api::workspace::{database::*, runner::StreamRunnerDataForWorkspaceServerMsg},
+	utils::TotalCountHeader,
+	ErrorType,
+};
+use rand::{distributions::Alphanumeric, thread_rng, Rng};
+use rustis::client::Client as RedisClient;
+use time::OffsetDateTime;
 
-use crate::prelude::*;
+mod get_database_connection;
+mod rotate_database_credentials;
+
+pub use self::{get_database_connection::*, rotate_database_credentials::*};
+use crate::{
+	prelude::*,
+	utils::{
+		config::AppConfig,
+		runner,
+		secret_store::{AppSecretStore, SecretStore},
+	},
+};
+
+/// The username that databases are created with. Credentials issued by
+/// rotating get a random suffix added to it
+const DATABASE_OWNER_USERNAME: &str = "patr";
+
+/// The length of the random suffix of the usernames of rotated credentials
+const USERNAME_SUFFIX_LENGTH: usize = 8;
+
+/// The length of the generated passwords of databases
+const PASSWORD_LENGTH: usize = 32;
+
+/// A managed database that credentials are issued for
+struct CredentialTarget {
+	/// The workspace that the database belongs to
+	workspace_id: Uuid,
+	/// The ID of the database
+	database_id: Uuid,
+	/// The runner that the database runs on
+	runner_id: Uuid,
+	/// The engine of the database
+	engine: DatabaseEngine,
+	/// The user that the database was created with
+	owner: String,
+}
 
 #[instrument(skip(state))]
 pub async fn setup_routes(state: &AppState) -> Router {
@@ -10,7 +52,195 @@ pub async fn setup_routes(state: &AppState) -> Router {
 		.mount_auth_endpoint(create_database, state)
 		.mount_auth_endpoint(delete_database, state)
 		.mount_auth_endpoint(get_database, state)
+		.mount_auth_endpoint(get_database_connection, state)
 		.mount_auth_endpoint(list_database, state)
+		.mount_auth_endpoint(rotate_database_credentials, state)
+}
+
+/// Issues new credentials for a database, as its current credentials. The
+/// password is generated and stored in the secret store as a secret of the
+/// workspace, so it never ends up in the table of credentials. The runner of
+/// the database is asked to create the user on the database engine once the
+/// request is committed.
+async fn create_credential(
+	connection: &mut crate::prelude::DatabaseConnection,
+	redis: &RedisClient,
+	config: &AppConfig,
+	target: &CredentialTarget,
+	username: String,
+	now: OffsetDateTime,
+) -> Result<(), ErrorType> {
+	let CredentialTarget {
+		workspace_id,
+		database_id,
+		runner_id,
+		engine,
+		owner,
+	} = target;
+	let password = generate_password();
+
+	let secret_id = query!(
+		r#"
+		INSERT INTO
+			resource(
+				id,
+				resource_type_id,
+				owner_id,
+				created,
+				deleted
+			)
+		VALUES
+			(
+				GENERATE_RESOURCE_ID(),
+				(SELECT id FROM resource_type WHERE name = 'secret'),
+				$1,
+				$2,
+				NULL
+			)
+		RETURNING id;
+		"#,
+		workspace_id as _,
+		now,
+	)
+	.fetch_one(&mut *connection)
+	.await?
+	.id;
+
+	query!(
+		r#"
+		INSERT INTO
+			secret(
+				id,
+				name,
+				workspace_id,
+				deleted
+			)
+		VALUES
+			($1, $2, $3, NULL);
+		"#,
+		secret_id as _,
+		credential_secret_name(*database_id, &username),
+		workspace_id as _,
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	AppSecretStore::new(&config.secrets, &mut *connection)
+		.put(*workspace_id, secret_id.into(), &password)
+		.await?;
+
+	query!(
+		r#"
+		INSERT INTO
+			managed_database_credential(
+				database_id,
+				username,
+				secret_id,
+				created,
+				expires
+			)
+		VALUES
+			($1, $2, $3, $4, NULL);
+		"#,
+		database_id as _,
+		username,
+		secret_id as _,
+		now,
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	runner::send_message(
+		redis,
+		&config.runner,
+		*workspace_id,
+		*runner_id,
+		&StreamRunnerDataForWorkspaceServerMsg::DatabaseUserCreated {
+			database_id: *database_id,
+			engine: engine.clone(),
+			user: DatabaseUser {
+				username,
+				password,
+				owner: owner.clone(),
+			},
+		},
+	)
+	.await
+}
+
+/// The name of the secret that the password of a credential of a database is
+/// stored in
+fn credential_secret_name(database_id: Uuid, username: &str) -> String {
+	format!("database-{database_id}-{username}")
+}
+
+/// Generates the username of a credential issued by rotating the credentials
+/// of a database. The previous credentials keep working alongside the new
+/// ones, so the new credentials need a user of their own
+fn generate_rotated_username(owner: &str) -> String {
+	let suffix = thread_rng()
+		.sample_iter(Alphanumeric)
+		.map(|char| char.to_ascii_lowercase() as char)
+		.take(USERNAME_SUFFIX_LENGTH)
+		.collect::<String>();
+	format!("{owner}_{suffix}")
+}
+
+/// Generates the password of a credential of a database
+fn generate_password() -> String {
+	thread_rng()
+		.sample_iter(Alphanumeric)
+		.map(char::from)
+		.take(PASSWORD_LENGTH)
+		.collect()
+}
+
+/// Gets the status of the rotation of the credentials of a database. Returns
+/// [`ErrorType::ResourceDoesNotExist`] if the database has no current
+/// credentials.
+async fn get_credential_rotation(
+	connection: &mut crate::prelude::DatabaseConnection,
+	database_id: Uuid,
+	now: OffsetDateTime,
+) -> Result<DatabaseCredentialRotation, ErrorType> {
+	let current = query!(
+		r#"
+		SELECT
+			created
+		FROM
+			managed_database_credential
+		WHERE
+			database_id = $1 AND
+			expires IS NULL;
+		"#,
+		database_id as _,
+	)
+	.fetch_optional(&mut *connection)
+	.await?
+	.or_not_found()?;
+
+	// Credentials that have expired but haven't been removed yet are already
+	// disabled, so they aren't in their grace period anymore
+	let previous = query!(
+		r#"
+		SELECT
+			MAX(expires) AS "expires"
+		FROM
+			managed_database_credential
+		WHERE
+			database_id = $1 AND
+			expires > $2;
+		"#,
+		database_id as _,
+		now,
+	)
+	.fetch_one(&mut *connection)
+	.await?;
+
+	Ok(DatabaseCredentialRotation {
+		current_credentials_issued_at: current.created,
+		previous_credentials_expire_at: previous.expires,
+	})
 }
 
 async fn all_database_plan(
@@ -26,6 +256,7 @@ async fn all_database_plan(
 		redis: _,
 		client_ip: _,
 		config,
+		clock: _,
 	}: AppRequest<'_, ListAllDatabaseMachineTypeRequest>,
 ) -> Result<AppResponse<ListAllDatabaseMachineTypeRequest>, ErrorType> {
 	info!("Starting: Get database plans");
@@ -50,6 +281,9 @@ async fn all_database_plan(
 		.into_result()
 }
 
+/// The handler to create a managed database in the workspace. The database is
+/// created with its first credentials, which the runner creates on the
+/// database engine along with the database.
 async fn create_database(
 	AuthenticatedAppRequest {
 		request:
@@ -63,25 +297,118 @@ async fn create_database(
 						engine,
 						database_plan_id,
 						region,
-						version,
-						num_node,
+						version: _,
+						num_node: _,
 					},
 			},
 		database,
-		redis: _,
+		redis,
 		client_ip: _,
 		config,
-		user_data,
+		user_data: _,
+		clock,
 	}: AuthenticatedAppRequest<'_, CreateDatabaseRequest>,
 ) -> Result<AppResponse<CreateDatabaseRequest>, ErrorType> {
-	info!("Starting: Create database");
+	info!("Creating database `{name}` in workspace `{workspace_id}`");
 
-	// LOGIC
-	let id = Uuid::parse_str("a1a2a3a4b1b2c1c2d1d2d3d4d5d6d7d8")?;
+	let now = clock.now();
+
+	query!(
+		r#"
+		SELECT
+			id
+		FROM
+			runner
+		WHERE
+			id = $1 AND
+			workspace_id = $2 AND
+			deleted IS NULL;
+		"#,
+		region as _,
+		workspace_id as _,
+	)
+	.fetch_optional(&mut **database)
+	.await?
+	.or_not_found()?;
+
+	let database_id = query!(
+		r#"
+		INSERT INTO
+			resource(
+				id,
+				resource_type_id,
+				owner_id,
+				created,
+				deleted
+			)
+		VALUES
+			(
+				GENERATE_RESOURCE_ID(),
+				(SELECT id FROM resource_type WHERE name = 'database'),
+				$1,
+				$2,
+				NULL
+			)
+		RETURNING id;
+		"#,
+		workspace_id as _,
+		now,
+	)
+	.fetch_one(&mut **database)
+	.await?
+	.id;
+
+	query!(
+		r#"
+		INSERT INTO
+			managed_database(
+				id,
+				name,
+				workspace_id,
+				runner,
+				engine,
+				database_plan_id,
+				status,
+				username,
+				deleted
+			)
+		VALUES
+			($1, $2, $3, $4, $5::TEXT::MANAGED_DATABASE_ENGINE, $6, 'creating', $7, NULL);
+		"#,
+		database_id as _,
+		name as _,
+		workspace_id as _,
+		region as _,
+		engine.to_string(),
+		database_plan_id as _,
+		DATABASE_OWNER_USERNAME,
+	)
+	.execute(&mut **database)
+	.await
+	.map_err(|err| match err {
+		sqlx::Error::Database(err) if err.is_unique_violation() => ErrorType::ResourceAlreadyExists,
+		err => ErrorType::server_error(err),
+	})?;
+
+	create_credential(
+		&mut **database,
+		redis,
+		&config,
+		&CredentialTarget {
+			workspace_id,
+			database_id: database_id.into(),
+			runner_id: region,
+			engine,
+			owner: DATABASE_OWNER_USERNAME.to_string(),
+		},
+		DATABASE_OWNER_USERNAME.to_string(),
+		now,
+	)
+	.await?;
 
 	AppResponse::builder()
 		.body(CreateDatabaseResponse {
-			id: WithId::new(id, ()),
+			id: WithId::new(database_id.into(), ()),
 		})
 		.headers(())
 		.status_code(StatusCode::OK)
@@ -106,6 +433,7 @@ async fn delete_database(
 		client_ip: _,
 		config,
 		user_data,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, DeleteDatabaseRequest>,
 ) -> Result<AppResponse<DeleteDatabaseRequest>, ErrorType> {
 	info!("Starting: Delete database");
@@ -137,6 +465,7 @@ async fn get_database(
 		client_ip: _,
 		config,
 		user_data,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, GetDatabaseRequest>,
 ) -> Result<AppResponse<GetDatabaseRequest>, ErrorType> {
 	info!("Starting: Get database");
@@ -191,6 +520,7 @@ async fn list_database(
 		client_ip: _,
 		config,
 		user_data,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, ListDatabaseRequest>,
 ) -> Result<AppResponse<ListDatabaseRequest>, ErrorType> {
 	info!("Starting: List database");
@@ -228,3 +558,47 @@ async fn list_database(
 		.build()
 		.into_result()
 }
+
+#[cfg(test)]
+mod tests {
+	use models::utils::constants::RESOURCE_NAME_REGEX;
+	use regex::Regex;
+
+	use super::*;
+
+	#[test]
+	fn rotated_usernames_are_unique_and_keep_the_owner() {
+		let first = generate_rotated_username(DATABASE_OWNER_USERNAME);
+		let second = generate_rotated_username(DATABASE_OWNER_USERNAME);
+
+		assert_ne!(first, second);
+		for username in [first, second] {
+			let suffix = username
+				.strip_prefix("patr_")
+				.expect("the username should start with the owner");
+			assert_eq!(suffix.len(), USERNAME_SUFFIX_LENGTH);
+			assert!(suffix
+				.chars()
+				.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit()));
+		}
+	}
+
+	#[test]
+	fn passwords_are_random_and_alphanumeric() {
+		let (first, second) = (generate_password(), generate_password());
+
+		assert_ne!(first, second);
+		assert_eq!(first.len(), PASSWORD_LENGTH);
+		assert!(first.chars().all(|c| c.is_ascii_alphanumeric()));
+	}
+
+	#[test]
+	fn credential_secrets_have_valid_names() {
+		let name = credential_secret_name(
+			Uuid::new_v4(),
+			&generate_rotated_username(DATABASE_OWNER_USERNAME),
+		);
+
+		assert!(Regex::new(RESOURCE_NAME_REGEX).unwrap().is_match(&name));
+	}
+}
@@ -0,0 +1,115 @@
+use std::str::FromStr;
+
+use axum::http::StatusCode;
+use models::api::workspace::database::*;
+use time::Duration;
+
+use crate::prelude::*;
+
+/// The handler to issue new credentials for a database. The current
+/// credentials expire at the end of the grace period, after which they are
+/// disabled by the database credential revoker. Credentials that were already
+/// in their grace period keep their expiry, so rotating again doesn't cut
+/// short the time that apps have to pick up the change. The new user is
+/// created on the database engine by the runner of the database.
+pub async fn rotate_database_credentials(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: RotateDatabaseCredentialsPath {
+					workspace_id,
+					database_id,
+				},
+				query: (),
+				headers:
+					RotateDatabaseCredentialsRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: RotateDatabaseCredentialsRequestProcessed {
+					grace_period_seconds,
+				},
+			},
+		database,
+		redis,
+		client_ip: _,
+		config,
+		user_data: _,
+		clock,
+	}: AuthenticatedAppRequest<'_, RotateDatabaseCredentialsRequest>,
+) -> Result<AppResponse<RotateDatabaseCredentialsRequest>, ErrorType> {
+	info!("Rotating the credentials of database `{database_id}`");
+
+	let now = clock.now();
+	let grace_period = Duration::seconds(
+		grace_period_seconds
+			.unwrap_or(DEFAULT_DATABASE_CREDENTIAL_GRACE_PERIOD_SECONDS)
+			.into(),
+	);
+
+	// Lock the database, so that concurrent rotations don't both try to
+	// become the current credentials
+	let managed_database = query!(
+		r#"
+		SELECT
+			runner,
+			engine::TEXT AS "engine!",
+			username
+		FROM
+			managed_database
+		WHERE
+			id = $1 AND
+			workspace_id = $2 AND
+			deleted IS NULL
+		FOR UPDATE;
+		"#,
+		database_id as _,
+		workspace_id as _,
+	)
+	.fetch_optional(&mut **database)
+	.await?
+	.or_not_found()?;
+	let engine =
+		DatabaseEngine::from_str(&managed_database.engine).map_err(ErrorType::server_error)?;
+
+	query!(
+		r#"
+		UPDATE
+			managed_database_credential
+		SET
+			expires = $2
+		WHERE
+			database_id = $1 AND
+			expires IS NULL;
+		"#,
+		database_id as _,
+		now + grace_period,
+	)
+	.execute(&mut **database)
+	.await?;
+
+	super::create_credential(
+		&mut **database,
+		redis,
+		&config,
+		&super::CredentialTarget {
+			workspace_id,
+			database_id,
+			runner_id: managed_database.runner.into(),
+			engine,
+			owner: managed_database.username.clone(),
+		},
+		super::generate_rotated_username(&managed_database.username),
+		now,
+	)
+	.await?;
+
+	let rotation = super::get_credential_rotation(&mut **database, database_id, now).await?;
+
+	AppResponse::builder()
+		.body(RotateDatabaseCredentialsResponse { rotation })
+		.headers(())
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
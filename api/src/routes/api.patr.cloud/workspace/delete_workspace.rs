@@ -1,9 +1,5 @@
-use std::ops::Add;
-
 use axum::http::StatusCode;
 use models::api::workspace::*;
-use rustis::commands::StringCommands;
-use time::OffsetDateTime;
 
 use crate::prelude::*;
 
@@ -29,6 +25,7 @@ pub async fn delete_workspace(
 		client_ip: _,
 		config: _,
 		user_data,
+		clock,
 	}: AuthenticatedAppRequest<'_, DeleteWorkspaceRequest>,
 ) -> Result<AppResponse<DeleteWorkspaceRequest>, ErrorType> {
 	info!("Deleting workspace `{workspace_id}`");
@@ -47,7 +44,7 @@ pub async fn delete_workspace(
 	)
 	.fetch_optional(&mut **database)
 	.await?
-	.ok_or(ErrorType::ResourceDoesNotExist)?;
+	.or_not_found()?;
 
 	// Make sure the workspace is owned by the user
 	if workspace.super_admin_id != user_data.id.into() {
@@ -91,19 +88,12 @@ pub async fn delete_workspace(
 	.await?;
 
 	// Revoke all tokens that have access to the workspace
-	redis
-		.setex(
-			redis::keys::workspace_id_revocation_timestamp(&workspace.id.into()),
-			constants::CACHED_PERMISSIONS_VALIDITY
-				.whole_seconds()
-				.unsigned_abs()
-				.add(300),
-			OffsetDateTime::now_utc().unix_timestamp(),
-		)
-		.await
-		.inspect_err(|err| {
-			error!("Error setting the revocation timestamp: `{}`", err);
-		})?;
+	redis::set_revocation_timestamp(
+		redis,
+		redis::keys::workspace_id_revocation_timestamp(&workspace.id.into()),
+		clock.now(),
+	)
+	.await?;
 
 	AppResponse::builder()
 		.body(DeleteWorkspaceResponse)
@@ -0,0 +1,92 @@
+use axum::http::StatusCode;
+use models::api::workspace::{deployment::*, runner::StreamRunnerDataForWorkspaceServerMsg};
+
+use crate::{prelude::*, utils::runner};
+
+/// The handler to abort the canary of a deployment. The canary is removed, and
+/// the runner of the deployment is asked to stop its replicas right away. The
+/// ingress sends all the requests to the primary replicas again once its
+/// cached activity report expires, and the canary's host is routed to the
+/// primary replicas until then. The requests counted for the canary are
+/// removed along with it.
+pub async fn abort_deployment_canary(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: AbortDeploymentCanaryPath {
+					workspace_id,
+					deployment_id,
+				},
+				query: (),
+				headers:
+					AbortDeploymentCanaryRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: AbortDeploymentCanaryRequestProcessed,
+			},
+		database,
+		redis,
+		client_ip: _,
+		config,
+		user_data: _,
+		clock,
+	}: AuthenticatedAppRequest<'_, AbortDeploymentCanaryRequest>,
+) -> Result<AppResponse<AbortDeploymentCanaryRequest>, ErrorType> {
+	info!("Aborting the canary of deployment `{deployment_id}`");
+
+	let runner = query!(
+		r#"
+		UPDATE
+			deployment
+		SET
+			canary_image_tag = NULL,
+			canary_weight = NULL,
+			canary_ready_replicas = NULL,
+			reconciliation_status = 'pending',
+			reconciliation_generation = reconciliation_generation + 1,
+			updated = $1
+		WHERE
+			id = $2 AND
+			deleted IS NULL AND
+			canary_image_tag IS NOT NULL
+		RETURNING runner;
+		"#,
+		clock.now(),
+		deployment_id as _,
+	)
+	.fetch_optional(&mut **database)
+	.await?
+	.or_not_found()?
+	.runner;
+
+	query!(
+		r#"
+		DELETE FROM
+			deployment_canary_requests
+		WHERE
+			deployment_id = $1;
+		"#,
+		deployment_id as _,
+	)
+	.execute(&mut **database)
+	.await?;
+
+	runner::send_message(
+		redis,
+		&config.runner,
+		workspace_id,
+		runner.into(),
+		&StreamRunnerDataForWorkspaceServerMsg::DeploymentReconciliationRequested {
+			id: deployment_id,
+		},
+	)
+	.await?;
+
+	AppResponse::builder()
+		.body(AbortDeploymentCanaryResponse)
+		.headers(())
+		.status_code(StatusCode::ACCEPTED)
+		.build()
+		.into_result()
+}
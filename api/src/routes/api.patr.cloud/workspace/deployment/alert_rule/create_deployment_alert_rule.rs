@@ -0,0 +1,104 @@
+use axum::http::StatusCode;
+use models::api::workspace::deployment::alert_rule::*;
+use time::OffsetDateTime;
+
+use crate::{models::deployment_alert::AlertNotificationChannelType, prelude::*};
+
+/// The handler to create an alert rule for a deployment. The notification
+/// channel is validated here, so that invalid channels are never stored. The
+/// alert starts off as not firing, and is evaluated by the alert evaluator from
+/// then on.
+pub async fn create_deployment_alert_rule(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: CreateDeploymentAlertRulePath {
+					workspace_id,
+					deployment_id,
+				},
+				query: (),
+				headers:
+					CreateDeploymentAlertRuleRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body:
+					CreateDeploymentAlertRuleRequestProcessed {
+						metric,
+						threshold,
+						duration_seconds,
+						resolve_duration_seconds,
+						notification_channel,
+					},
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config: _,
+		user_data: _,
+		clock: _,
+	}: AuthenticatedAppRequest<'_, CreateDeploymentAlertRuleRequest>,
+) -> Result<AppResponse<CreateDeploymentAlertRuleRequest>, ErrorType> {
+	info!("Creating alert rule for deployment `{deployment_id}`");
+
+	let (channel_type, target) = AlertNotificationChannelType::from_channel(
+		super::validate_notification_channel(notification_channel)?,
+	);
+	super::ensure_deployment_exists(&mut **database, workspace_id, deployment_id).await?;
+
+	let alert_rule_id = query!(
+		r#"
+		INSERT INTO
+			deployment_alert_rule(
+				id,
+				deployment_id,
+				metric,
+				threshold,
+				duration_seconds,
+				resolve_duration_seconds,
+				notification_channel,
+				notification_target,
+				breach_started,
+				recovery_started,
+				firing_since,
+				created
+			)
+		VALUES
+			(
+				gen_random_uuid(),
+				$1,
+				$2,
+				$3,
+				$4,
+				$5,
+				$6,
+				$7,
+				NULL,
+				NULL,
+				NULL,
+				$8
+			)
+		RETURNING id;
+		"#,
+		deployment_id as _,
+		metric as _,
+		i16::from(threshold),
+		i64::from(duration_seconds),
+		i64::from(resolve_duration_seconds),
+		channel_type as _,
+		target,
+		OffsetDateTime::now_utc(),
+	)
+	.fetch_one(&mut **database)
+	.await?
+	.id;
+
+	AppResponse::builder()
+		.body(CreateDeploymentAlertRuleResponse {
+			id: WithId::from(alert_rule_id),
+		})
+		.headers(())
+		.status_code(StatusCode::CREATED)
+		.build()
+		.into_result()
+}
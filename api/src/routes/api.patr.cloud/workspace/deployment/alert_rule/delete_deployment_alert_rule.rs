@@ -0,0 +1,63 @@
+use axum::http::StatusCode;
+use models::api::workspace::deployment::alert_rule::*;
+
+use crate::prelude::*;
+
+/// The handler to delete an alert rule of a deployment. The events recorded by
+/// the rule are kept in the history of the deployment.
+pub async fn delete_deployment_alert_rule(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path:
+					DeleteDeploymentAlertRulePath {
+						workspace_id,
+						deployment_id,
+						alert_rule_id,
+					},
+				query: (),
+				headers:
+					DeleteDeploymentAlertRuleRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: DeleteDeploymentAlertRuleRequestProcessed,
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config: _,
+		user_data: _,
+		clock: _,
+	}: AuthenticatedAppRequest<'_, DeleteDeploymentAlertRuleRequest>,
+) -> Result<AppResponse<DeleteDeploymentAlertRuleRequest>, ErrorType> {
+	info!("Deleting alert rule `{alert_rule_id}` of deployment `{deployment_id}`");
+
+	super::ensure_deployment_exists(&mut **database, workspace_id, deployment_id).await?;
+
+	let rows_affected = query!(
+		r#"
+		DELETE FROM
+			deployment_alert_rule
+		WHERE
+			id = $1 AND
+			deployment_id = $2;
+		"#,
+		alert_rule_id as _,
+		deployment_id as _,
+	)
+	.execute(&mut **database)
+	.await?
+	.rows_affected();
+
+	if rows_affected == 0 {
+		return Err(ErrorType::ResourceDoesNotExist);
+	}
+
+	AppResponse::builder()
+		.body(DeleteDeploymentAlertRuleResponse)
+		.headers(())
+		.status_code(StatusCode::RESET_CONTENT)
+		.build()
+		.into_result()
+}
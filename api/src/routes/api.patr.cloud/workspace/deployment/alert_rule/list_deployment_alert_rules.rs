@@ -0,0 +1,83 @@
+use axum::http::StatusCode;
+use models::api::workspace::deployment::alert_rule::*;
+
+use crate::{models::deployment_alert::AlertNotificationChannelType, prelude::*};
+
+/// The handler to list all the alert rules of a deployment
+pub async fn list_deployment_alert_rules(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: ListDeploymentAlertRulesPath {
+					workspace_id,
+					deployment_id,
+				},
+				query: (),
+				headers:
+					ListDeploymentAlertRulesRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: ListDeploymentAlertRulesRequestProcessed,
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config: _,
+		user_data: _,
+		clock: _,
+	}: AuthenticatedAppRequest<'_, ListDeploymentAlertRulesRequest>,
+) -> Result<AppResponse<ListDeploymentAlertRulesRequest>, ErrorType> {
+	info!("Listing alert rules of deployment `{deployment_id}`");
+
+	super::ensure_deployment_exists(&mut **database, workspace_id, deployment_id).await?;
+
+	let alert_rules = query!(
+		r#"
+		SELECT
+			id,
+			metric AS "metric: DeploymentAlertMetric",
+			threshold,
+			duration_seconds,
+			resolve_duration_seconds,
+			notification_channel AS "notification_channel: AlertNotificationChannelType",
+			notification_target,
+			firing_since
+		FROM
+			deployment_alert_rule
+		WHERE
+			deployment_id = $1
+		ORDER BY
+			created;
+		"#,
+		deployment_id as _,
+	)
+	.fetch_all(&mut **database)
+	.await?
+	.into_iter()
+	.map(|row| {
+		Ok(WithId::new(
+			row.id,
+			DeploymentAlertRule {
+				metric: row.metric,
+				threshold: u8::try_from(row.threshold).map_err(ErrorType::server_error)?,
+				duration_seconds: u32::try_from(row.duration_seconds)
+					.map_err(ErrorType::server_error)?,
+				resolve_duration_seconds: u32::try_from(row.resolve_duration_seconds)
+					.map_err(ErrorType::server_error)?,
+				notification_channel: row
+					.notification_channel
+					.into_channel(row.notification_target),
+				firing_since: row.firing_since,
+			},
+		))
+	})
+	.collect::<Result<_, ErrorType>>()?;
+
+	AppResponse::builder()
+		.body(ListDeploymentAlertRulesResponse { alert_rules })
+		.headers(())
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
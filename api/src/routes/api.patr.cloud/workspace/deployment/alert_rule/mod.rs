@@ -0,0 +1,66 @@
+use axum::Router;
+use models::api::workspace::deployment::alert_rule::AlertNotificationChannel;
+
+use super::ensure_deployment_exists;
+use crate::prelude::*;
+
+mod create_deployment_alert_rule;
+mod delete_deployment_alert_rule;
+mod list_deployment_alert_rules;
+mod update_deployment_alert_rule;
+
+use self::{
+	create_deployment_alert_rule::*,
+	delete_deployment_alert_rule::*,
+	list_deployment_alert_rules::*,
+	update_deployment_alert_rule::*,
+};
+
+#[instrument(skip(state))]
+pub async fn setup_routes(state: &AppState) -> Router {
+	Router::new()
+		.mount_auth_endpoint(create_deployment_alert_rule, state)
+		.mount_auth_endpoint(delete_deployment_alert_rule, state)
+		.mount_auth_endpoint(list_deployment_alert_rules, state)
+		.mount_auth_endpoint(update_deployment_alert_rule, state)
+}
+
+/// Validates the notification channel of an alert rule and trims its target,
+/// so that the alert evaluator never has to deal with a channel that it cannot
+/// send notifications to
+fn validate_notification_channel(
+	channel: AlertNotificationChannel,
+) -> Result<AlertNotificationChannel, ErrorType> {
+	match channel {
+		AlertNotificationChannel::Email { email } => {
+			let email = email.trim();
+			let is_valid = email.split_once('@').is_some_and(|(local, domain)| {
+				!local.is_empty() &&
+					domain.contains('.') &&
+					!domain.starts_with('.') &&
+					!domain.ends_with('.') &&
+					!domain.contains('@')
+			}) && !email.contains(char::is_whitespace);
+			if !is_valid {
+				return Err(ErrorType::WrongParameters);
+			}
+
+			Ok(AlertNotificationChannel::Email {
+				email: email.to_string(),
+			})
+		}
+		AlertNotificationChannel::Webhook { url } => {
+			let url = url.trim();
+			let is_valid = reqwest::Url::parse(url).is_ok_and(|url| {
+				matches!(url.scheme(), "http" | "https") && url.host_str().is_some()
+			});
+			if !is_valid {
+				return Err(ErrorType::WrongParameters);
+			}
+
+			Ok(AlertNotificationChannel::Webhook {
+				url: url.to_string(),
+			})
+		}
+	}
+}
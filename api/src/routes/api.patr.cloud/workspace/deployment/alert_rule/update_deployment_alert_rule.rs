@@ -0,0 +1,86 @@
+use axum::http::StatusCode;
+use models::api::workspace::deployment::alert_rule::*;
+
+use crate::{models::deployment_alert::AlertNotificationChannelType, prelude::*};
+
+/// The handler to update an alert rule of a deployment. Only the fields that
+/// are given are updated, and the state of the alert is left as is, so that an
+/// update doesn't trigger a notification by itself.
+pub async fn update_deployment_alert_rule(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path:
+					UpdateDeploymentAlertRulePath {
+						workspace_id,
+						deployment_id,
+						alert_rule_id,
+					},
+				query: (),
+				headers:
+					UpdateDeploymentAlertRuleRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body:
+					UpdateDeploymentAlertRuleRequestProcessed {
+						threshold,
+						duration_seconds,
+						resolve_duration_seconds,
+						notification_channel,
+					},
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config: _,
+		user_data: _,
+		clock: _,
+	}: AuthenticatedAppRequest<'_, UpdateDeploymentAlertRuleRequest>,
+) -> Result<AppResponse<UpdateDeploymentAlertRuleRequest>, ErrorType> {
+	info!("Updating alert rule `{alert_rule_id}` of deployment `{deployment_id}`");
+
+	let (channel_type, target) = notification_channel
+		.map(super::validate_notification_channel)
+		.transpose()?
+		.map(AlertNotificationChannelType::from_channel)
+		.unzip();
+	super::ensure_deployment_exists(&mut **database, workspace_id, deployment_id).await?;
+
+	let rows_affected = query!(
+		r#"
+		UPDATE
+			deployment_alert_rule
+		SET
+			threshold = COALESCE($1, threshold),
+			duration_seconds = COALESCE($2, duration_seconds),
+			resolve_duration_seconds = COALESCE($3, resolve_duration_seconds),
+			notification_channel = COALESCE($4, notification_channel),
+			notification_target = COALESCE($5, notification_target)
+		WHERE
+			id = $6 AND
+			deployment_id = $7;
+		"#,
+		threshold.map(i16::from),
+		duration_seconds.map(i64::from),
+		resolve_duration_seconds.map(i64::from),
+		channel_type as _,
+		target,
+		alert_rule_id as _,
+		deployment_id as _,
+	)
+	.execute(&mut **database)
+	.await?
+	.rows_affected();
+
+	if rows_affected == 0 {
+		return Err(ErrorType::ResourceDoesNotExist);
+	}
+
+	AppResponse::builder()
+		.body(UpdateDeploymentAlertRuleResponse)
+		.headers(())
+		.status_code(StatusCode::ACCEPTED)
+		.build()
+		.into_result()
+}
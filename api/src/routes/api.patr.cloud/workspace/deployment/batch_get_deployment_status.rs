@@ -0,0 +1,112 @@
+use std::collections::BTreeMap;
+
+use axum::http::StatusCode;
+use models::{api::workspace::deployment::*, utils::constants};
+
+use crate::prelude::*;
+
+/// The handler to get the status of multiple deployments in a workspace at
+/// once. Requests with more than
+/// [`constants::MAX_DEPLOYMENT_STATUS_BATCH_SIZE`] IDs are clamped to that
+/// many, instead of being rejected, so that a client polling a large list still
+/// gets the start of it. Deployments that the user cannot view, or that are not
+/// in the workspace, are reported in their result instead of failing the
+/// request.
+pub async fn batch_get_deployment_status(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: BatchGetDeploymentStatusPath { workspace_id },
+				query: (),
+				headers:
+					BatchGetDeploymentStatusRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: BatchGetDeploymentStatusRequestProcessed { mut deployment_ids },
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config: _,
+		user_data,
+		clock: _,
+	}: AuthenticatedAppRequest<'_, BatchGetDeploymentStatusRequest>,
+) -> Result<AppResponse<BatchGetDeploymentStatusRequest>, ErrorType> {
+	trace!(
+		"Getting the status of {} deployments in workspace: {}",
+		deployment_ids.len(),
+		workspace_id
+	);
+
+	deployment_ids.truncate(constants::MAX_DEPLOYMENT_STATUS_BATCH_SIZE);
+
+	let deployments = query!(
+		r#"
+		SELECT
+			deployment.id,
+			status AS "status: DeploymentStatus",
+			ready_replicas,
+			total_replicas,
+			resource.id IS NOT NULL AS "can_view!"
+		FROM
+			deployment
+		LEFT JOIN
+			RESOURCES_WITH_PERMISSION_FOR_LOGIN_ID($3, $4) AS resource
+		ON
+			deployment.id = resource.id
+		WHERE
+			deployment.id = ANY($1::UUID[]) AND
+			workspace_id = $2 AND
+			deployment.deleted IS NULL;
+		"#,
+		&deployment_ids
+			.iter()
+			.map(|id| (*id).into())
+			.collect::<Vec<_>>(),
+		workspace_id as _,
+		user_data.login_id as _,
+		Permission::Deployment(DeploymentPermission::View) as _,
+	)
+	.fetch_all(&mut **database)
+	.await?
+	.into_iter()
+	.map(|row| {
+		let status = row.can_view.then(|| {
+			let replicas = row
+				.ready_replicas
+				.zip(row.total_replicas)
+				.map(|(ready, total)| DeploymentReplicaReadiness {
+					ready: ready as u16,
+					total: total as u16,
+				});
+			(row.status.with_replica_readiness(replicas), replicas)
+		});
+		(Uuid::from(row.id), status)
+	})
+	.collect::<BTreeMap<_, _>>();
+
+	let statuses = deployment_ids
+		.into_iter()
+		.map(|deployment_id| {
+			let (status, replicas, error) = match deployments.get(&deployment_id) {
+				None => (None, None, Some(ErrorType::ResourceDoesNotExist)),
+				Some(None) => (None, None, Some(ErrorType::Unauthorized)),
+				Some(Some((status, replicas))) => (Some(*status), *replicas, None),
+			};
+			DeploymentStatusResult {
+				deployment_id,
+				status,
+				replicas,
+				error,
+			}
+		})
+		.collect();
+
+	AppResponse::builder()
+		.body(BatchGetDeploymentStatusResponse { statuses })
+		.headers(())
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
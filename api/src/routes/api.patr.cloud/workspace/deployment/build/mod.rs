@@ -0,0 +1,24 @@
+use axum::Router;
+
+use crate::prelude::*;
+
+mod set_deployment_build_source;
+mod start_deployment_build;
+mod stream_deployment_build_logs;
+mod update_deployment_build;
+
+use self::{
+	set_deployment_build_source::*,
+	start_deployment_build::*,
+	stream_deployment_build_logs::*,
+	update_deployment_build::*,
+};
+
+#[instrument(skip(state))]
+pub async fn setup_routes(state: &AppState) -> Router {
+	Router::new()
+		.mount_auth_endpoint(set_deployment_build_source, state)
+		.mount_auth_endpoint(start_deployment_build, state)
+		.mount_auth_endpoint(update_deployment_build, state)
+		.mount_auth_endpoint(stream_deployment_build_logs, state)
+}
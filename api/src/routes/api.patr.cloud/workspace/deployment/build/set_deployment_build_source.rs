@@ -0,0 +1,104 @@
+use axum::http::StatusCode;
+use models::api::workspace::deployment::{build::*, *};
+
+use crate::prelude::*;
+
+/// The handler to set the git repository that the image of a deployment is
+/// built from. Since the built image is pushed to the deployment's repository,
+/// only deployments that use the Patr registry can have a build source.
+pub async fn set_deployment_build_source(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: SetDeploymentBuildSourcePath {
+					workspace_id: _,
+					deployment_id,
+				},
+				query: (),
+				headers:
+					SetDeploymentBuildSourceRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: SetDeploymentBuildSourceRequestProcessed { build_source },
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config: _,
+		user_data: _,
+		clock: _,
+	}: AuthenticatedAppRequest<'_, SetDeploymentBuildSourceRequest>,
+) -> Result<AppResponse<SetDeploymentBuildSourceRequest>, ErrorType> {
+	info!("Setting the build source of deployment `{deployment_id}`");
+
+	let registry = query!(
+		r#"
+		SELECT
+			registry
+		FROM
+			deployment
+		WHERE
+			id = $1 AND
+			deleted IS NULL;
+		"#,
+		deployment_id as _,
+	)
+	.fetch_optional(&mut **database)
+	.await?
+	.or_not_found()?
+	.registry;
+
+	if let Some(build_source) = &build_source {
+		if registry != PatrRegistry.to_string() {
+			return Err(ErrorType::BuildSourceRequiresPatrRegistry);
+		}
+
+		if build_source.git_url.trim().is_empty() ||
+			build_source.branch.trim().is_empty() ||
+			build_source
+				.dockerfile_path
+				.as_deref()
+				.is_some_and(|path| path.trim().is_empty())
+		{
+			return Err(ErrorType::WrongParameters);
+		}
+	}
+
+	let (git_url, branch, dockerfile_path) = build_source
+		.map(|source| {
+			(
+				Some(source.git_url),
+				Some(source.branch),
+				source.dockerfile_path,
+			)
+		})
+		.unwrap_or_default();
+
+	query!(
+		r#"
+		UPDATE
+			deployment
+		SET
+			build_git_url = $1,
+			build_branch = $2,
+			build_dockerfile_path = $3,
+			updated = NOW()
+		WHERE
+			id = $4;
+		"#,
+		git_url,
+		branch,
+		dockerfile_path,
+		deployment_id as _,
+	)
+	.execute(&mut **database)
+	.await?;
+
+	AppResponse::builder()
+		.body(SetDeploymentBuildSourceResponse)
+		.headers(())
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
@@ -0,0 +1,138 @@
+use axum::http::StatusCode;
+use models::api::workspace::{
+	deployment::{build::*, PatrRegistry},
+	runner::StreamRunnerDataForWorkspaceServerMsg,
+};
+use time::OffsetDateTime;
+
+use crate::{prelude::*, utils::runner};
+
+/// The handler to start building the image of a deployment from its build
+/// source. The build is queued here and picked up by the runner of the
+/// deployment, which pushes the built image to the deployment's repository with
+/// a tag unique to the build.
+pub async fn start_deployment_build(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: StartDeploymentBuildPath {
+					workspace_id,
+					deployment_id,
+				},
+				query: (),
+				headers:
+					StartDeploymentBuildRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: StartDeploymentBuildRequestProcessed,
+			},
+		database,
+		redis,
+		client_ip: _,
+		config,
+		user_data: _,
+		clock: _,
+	}: AuthenticatedAppRequest<'_, StartDeploymentBuildRequest>,
+) -> Result<AppResponse<StartDeploymentBuildRequest>, ErrorType> {
+	info!("Starting a build of deployment `{deployment_id}`");
+
+	let deployment = query!(
+		r#"
+		SELECT
+			deployment.runner,
+			deployment.build_git_url,
+			deployment.build_branch,
+			deployment.build_dockerfile_path,
+			container_registry_repository.name as "repository_name?"
+		FROM
+			deployment
+		LEFT JOIN
+			container_registry_repository
+		ON
+			container_registry_repository.id = deployment.repository_id
+		WHERE
+			deployment.id = $1 AND
+			deployment.deleted IS NULL;
+		"#,
+		deployment_id as _,
+	)
+	.fetch_optional(&mut **database)
+	.await?
+	.or_not_found()?;
+
+	let (Some(git_url), Some(branch)) = (deployment.build_git_url, deployment.build_branch) else {
+		return Err(ErrorType::NoBuildSource);
+	};
+	let repository_name = deployment
+		.repository_name
+		.ok_or(ErrorType::BuildSourceRequiresPatrRegistry)?;
+	let build_source = DeploymentBuildSource {
+		git_url,
+		branch,
+		dockerfile_path: deployment.build_dockerfile_path,
+	};
+
+	let build_id = Uuid::new_v4();
+	let image_tag = format!("build-{}", build_id);
+
+	query!(
+		r#"
+		INSERT INTO
+			deployment_build(
+				id,
+				deployment_id,
+				git_url,
+				branch,
+				dockerfile_path,
+				image_tag,
+				status,
+				created
+			)
+		VALUES
+			($1, $2, $3, $4, $5, $6, $7, $8);
+		"#,
+		build_id as _,
+		deployment_id as _,
+		build_source.git_url,
+		build_source.branch,
+		build_source.dockerfile_path,
+		image_tag,
+		DeploymentBuildStatus::Queued as _,
+		OffsetDateTime::now_utc(),
+	)
+	.execute(&mut **database)
+	.await
+	.map_err(|err| match err {
+		sqlx::Error::Database(err) if err.is_unique_violation() => {
+			ErrorType::BuildAlreadyInProgress
+		}
+		err => ErrorType::server_error(err),
+	})?;
+
+	runner::send_message(
+		redis,
+		&config.runner,
+		workspace_id,
+		deployment.runner.into(),
+		&StreamRunnerDataForWorkspaceServerMsg::DeploymentBuildRequested {
+			deployment_id,
+			build_id,
+			build_source,
+			image: format!(
+				"{}/{}/{}:{}",
+				PatrRegistry, workspace_id, repository_name, image_tag
+			),
+		},
+	)
+	.await?;
+
+	AppResponse::builder()
+		.body(StartDeploymentBuildResponse {
+			id: WithId::from(build_id),
+		})
+		.headers(())
+		.status_code(StatusCode::ACCEPTED)
+		.build()
+		.into_result()
+}
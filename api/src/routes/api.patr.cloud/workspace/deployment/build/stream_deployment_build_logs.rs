@@ -0,0 +1,96 @@
+use axum::{http::StatusCode, response::IntoResponse};
+use axum_typed_websockets::Message;
+use futures::StreamExt;
+use models::{
+	api::workspace::deployment::build::*,
+	utils::{GenericResponse, WebSocketUpgrade},
+};
+
+use super::super::tail_loki_logs;
+use crate::prelude::*;
+
+/// Route to stream the logs of a build of a deployment. The logs are tailed
+/// from Loki the same way as the logs of the deployment itself, starting from
+/// when the build was created so that the logs so far are sent first.
+pub async fn stream_deployment_build_logs(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path:
+					StreamDeploymentBuildLogsPath {
+						workspace_id,
+						deployment_id,
+						build_id,
+					},
+				query: (),
+				headers:
+					StreamDeploymentBuildLogsRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: WebSocketUpgrade(upgrade),
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config,
+		user_data: _,
+		clock: _,
+	}: AuthenticatedAppRequest<'_, StreamDeploymentBuildLogsRequest>,
+) -> Result<AppResponse<StreamDeploymentBuildLogsRequest>, ErrorType> {
+	info!("Streaming logs for build `{build_id}` of deployment `{deployment_id}`");
+
+	let created = query!(
+		r#"
+		SELECT
+			created
+		FROM
+			deployment_build
+		WHERE
+			id = $1 AND
+			deployment_id = $2;
+		"#,
+		build_id as _,
+		deployment_id as _,
+	)
+	.fetch_optional(&mut **database)
+	.await?
+	.or_not_found()?
+	.created;
+
+	let mut logs = tail_loki_logs(
+		&config,
+		workspace_id,
+		format!(r#"{{buildId="{}"}}"#, build_id),
+		Some(created),
+	)
+	.await?
+	.boxed();
+
+	AppResponse::builder()
+		.body(GenericResponse(
+			upgrade
+				.on_upgrade(move |mut websocket| async move {
+					while let Some(logs) = logs.next().await {
+						let Ok(()) = websocket
+							.send(Message::Item(StreamDeploymentBuildLogsServerMsg::LogData {
+								logs,
+							}))
+							.await
+							.inspect_err(|err| {
+								debug!("Failed to send build logs to client: {}", err);
+							})
+						else {
+							break;
+						};
+					}
+					_ = websocket.send(Message::Close(None)).await;
+					_ = websocket.close().await;
+				})
+				.into_response(),
+		))
+		.headers(())
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
@@ -0,0 +1,204 @@
+use axum::http::StatusCode;
+use models::api::workspace::deployment::{build::*, DeploymentStatus};
+use time::OffsetDateTime;
+
+use crate::{deployment_image_scanner, prelude::*};
+
+/// The handler for the runner to report the progress of a build of a
+/// deployment. When the build succeeds, the deployment is updated to run the
+/// built image, and the image is recorded in its deploy history. When the build
+/// fails, only the build is updated, so the deployment keeps running the image
+/// it was running before. If the built image isn't allowed under the image
+/// scan policy of the workspace yet, the deployment is switched to it but not
+/// redeployed, so that it only runs the image once it is started again after
+/// the image has been scanned.
+pub async fn update_deployment_build(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path:
+					UpdateDeploymentBuildPath {
+						workspace_id,
+						deployment_id,
+						build_id,
+					},
+				query: (),
+				headers:
+					UpdateDeploymentBuildRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body:
+					UpdateDeploymentBuildRequestProcessed {
+						status,
+						image_digest,
+						error,
+					},
+			},
+		database,
+		redis,
+		client_ip: _,
+		config,
+		user_data: _,
+		clock: _,
+	}: AuthenticatedAppRequest<'_, UpdateDeploymentBuildRequest>,
+) -> Result<AppResponse<UpdateDeploymentBuildRequest>, ErrorType> {
+	info!("Updating build `{build_id}` of deployment `{deployment_id}` to {status:?}");
+
+	let build = query!(
+		r#"
+		SELECT
+			deployment_build.image_tag,
+			deployment_build.status as "status: DeploymentBuildStatus",
+			deployment.repository_id
+		FROM
+			deployment_build
+		INNER JOIN
+			deployment
+		ON
+			deployment.id = deployment_build.deployment_id
+		WHERE
+			deployment_build.id = $1 AND
+			deployment_build.deployment_id = $2 AND
+			deployment.deleted IS NULL
+		FOR UPDATE;
+		"#,
+		build_id as _,
+		deployment_id as _,
+	)
+	.fetch_optional(&mut **database)
+	.await?
+	.or_not_found()?;
+
+	let is_valid_transition = match (build.status, status) {
+		(DeploymentBuildStatus::Queued, DeploymentBuildStatus::Building) => true,
+		(DeploymentBuildStatus::Queued | DeploymentBuildStatus::Building, status) => {
+			status.is_finished()
+		}
+		_ => false,
+	};
+	if !is_valid_transition {
+		return Err(ErrorType::InvalidBuildStatusTransition);
+	}
+
+	let image_digest = match status {
+		DeploymentBuildStatus::Succeeded => Some(image_digest.ok_or(ErrorType::WrongParameters)?),
+		_ => None,
+	};
+	let error = error.filter(|_| status == DeploymentBuildStatus::Failed);
+	let now = OffsetDateTime::now_utc();
+
+	query!(
+		r#"
+		UPDATE
+			deployment_build
+		SET
+			status = $1,
+			image_digest = $2,
+			error = $3,
+			finished = $4
+		WHERE
+			id = $5;
+		"#,
+		status as _,
+		image_digest,
+		error,
+		status.is_finished().then_some(now),
+		build_id as _,
+	)
+	.execute(&mut **database)
+	.await?;
+
+	// Only a successful build replaces the image that the deployment is running
+	if let (Some(image_digest), Some(repository_id)) = (image_digest, build.repository_id) {
+		query!(
+			r#"
+			INSERT INTO
+				deployment_deploy_history(
+					deployment_id,
+					image_digest,
+					repository_id,
+					created
+				)
+			VALUES
+				($1, $2, $3, $4)
+			ON CONFLICT
+				(deployment_id, image_digest)
+			DO NOTHING;
+			"#,
+			deployment_id as _,
+			image_digest,
+			repository_id,
+			now,
+		)
+		.execute(&mut **database)
+		.await?;
+
+		query!(
+			r#"
+			UPDATE
+				deployment
+			SET
+				image_tag = $1,
+				current_live_digest = $2,
+				updated = $3
+			WHERE
+				id = $4;
+			"#,
+			build.image_tag,
+			image_digest,
+			now,
+			deployment_id as _,
+		)
+		.execute(&mut **database)
+		.await?;
+
+		let allowed = deployment_image_scanner::ensure_image_allowed(
+			&mut **database,
+			redis,
+			&config,
+			workspace_id,
+			deployment_id,
+			false,
+			now,
+		)
+		.await;
+		match allowed {
+			Ok(()) => {
+				query!(
+					r#"
+					UPDATE
+						deployment
+					SET
+						status = $1,
+						reconciliation_status = 'pending',
+						reconciliation_generation = reconciliation_generation + 1,
+						updated = $2
+					WHERE
+						id = $3;
+					"#,
+					DeploymentStatus::Deploying as _,
+					now,
+					deployment_id as _,
+				)
+				.execute(&mut **database)
+				.await?;
+			}
+			Err(
+				err @ (ErrorType::ImageScanPending |
+				ErrorType::ImageScanBlocked |
+				ErrorType::ImageScanFailed),
+			) => {
+				info!("Not deploying build `{build_id}` of deployment `{deployment_id}`: {err}");
+			}
+			Err(err) => return Err(err),
+		}
+	}
+
+	AppResponse::builder()
+		.body(UpdateDeploymentBuildResponse)
+		.headers(())
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
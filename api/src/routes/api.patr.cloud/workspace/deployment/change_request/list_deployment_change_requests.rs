@@ -0,0 +1,82 @@
+use axum::http::StatusCode;
+use models::api::workspace::deployment::{change_request::*, UpdateDeploymentRequest};
+
+use crate::prelude::*;
+
+/// The handler to list the changes to a deployment that are waiting for
+/// approval. The values of secret environment variables in the changes are
+/// masked, since the changes can be viewed by anyone who can view the
+/// deployment.
+pub async fn list_deployment_change_requests(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path:
+					ListDeploymentChangeRequestsPath {
+						workspace_id,
+						deployment_id,
+					},
+				query: (),
+				headers:
+					ListDeploymentChangeRequestsRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: ListDeploymentChangeRequestsRequestProcessed,
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config: _,
+		user_data: _,
+		clock: _,
+	}: AuthenticatedAppRequest<'_, ListDeploymentChangeRequestsRequest>,
+) -> Result<AppResponse<ListDeploymentChangeRequestsRequest>, ErrorType> {
+	info!("Listing pending changes of deployment `{deployment_id}`");
+
+	super::ensure_deployment_exists(&mut **database, workspace_id, deployment_id).await?;
+
+	let secret_variables = super::get_secret_variables(&mut **database, deployment_id).await?;
+
+	let change_requests = query!(
+		r#"
+		SELECT
+			id,
+			requested_by,
+			changes,
+			created
+		FROM
+			deployment_change_request
+		WHERE
+			deployment_id = $1 AND
+			status = 'pending'
+		ORDER BY
+			created;
+		"#,
+		deployment_id as _,
+	)
+	.fetch_all(&mut **database)
+	.await?
+	.into_iter()
+	.map(|row| {
+		let mut changes = serde_json::from_str::<UpdateDeploymentRequest>(&row.changes)?;
+		super::mask_secret_variables(&mut changes, &secret_variables);
+
+		Ok(WithId::new(
+			row.id,
+			DeploymentChangeRequest {
+				requested_by: row.requested_by.into(),
+				changes,
+				created_at: row.created,
+			},
+		))
+	})
+	.collect::<Result<_, ErrorType>>()?;
+
+	AppResponse::builder()
+		.body(ListDeploymentChangeRequestsResponse { change_requests })
+		.headers(())
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
@@ -0,0 +1,247 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use axum::Router;
+use models::{
+	api::workspace::deployment::{EnvironmentVariableValue, UpdateDeploymentRequest},
+	utils::constants::MASKED_ENVIRONMENT_VARIABLE_VALUE,
+};
+use time::OffsetDateTime;
+
+use super::{apply_deployment_update, ensure_deployment_exists};
+use crate::prelude::*;
+
+mod list_deployment_change_requests;
+mod review_deployment_change;
+
+use self::{list_deployment_change_requests::*, review_deployment_change::*};
+
+#[instrument(skip(state))]
+pub async fn setup_routes(state: &AppState) -> Router {
+	Router::new()
+		.mount_auth_endpoint(list_deployment_change_requests, state)
+		.mount_auth_endpoint(review_deployment_change, state)
+}
+
+/// Stores a change to a deployment as a change request that is waiting for
+/// approval, along with the time the deployment was last updated at, so that
+/// the change isn't applied if the deployment is modified before it is
+/// approved. The values of the secret environment variables in the change are
+/// stored separately from the change, and are removed once it is reviewed.
+/// Returns the ID of the change request.
+pub(super) async fn create_change_request(
+	connection: &mut DatabaseConnection,
+	deployment_id: Uuid,
+	deployment_updated: OffsetDateTime,
+	requested_by: Uuid,
+	mut changes: UpdateDeploymentRequest,
+	now: OffsetDateTime,
+) -> Result<Uuid, ErrorType> {
+	let secret_variables = get_secret_variables(&mut *connection, deployment_id).await?;
+	let secret_values = take_secret_variables(&mut changes, &secret_variables);
+
+	let change_request_id = query!(
+		r#"
+		INSERT INTO
+			deployment_change_request(
+				id,
+				deployment_id,
+				requested_by,
+				changes,
+				deployment_updated,
+				status,
+				created
+			)
+		VALUES
+			(
+				gen_random_uuid(),
+				$1,
+				$2,
+				$3,
+				$4,
+				'pending',
+				$5
+			)
+		RETURNING id;
+		"#,
+		deployment_id as _,
+		requested_by as _,
+		serde_json::to_string(&changes)?,
+		deployment_updated,
+		now,
+	)
+	.fetch_one(&mut *connection)
+	.await?
+	.id;
+
+	query!(
+		r#"
+		INSERT INTO
+			deployment_change_request_secret_variable(
+				change_request_id,
+				name,
+				value
+			)
+		VALUES
+			(
+				UNNEST($1::UUID[]),
+				UNNEST($2::TEXT[]),
+				UNNEST($3::TEXT[])
+			);
+		"#,
+		&secret_values
+			.keys()
+			.map(|_| change_request_id)
+			.collect::<Vec<_>>(),
+		&secret_values.keys().cloned().collect::<Vec<_>>(),
+		&secret_values.into_values().collect::<Vec<_>>(),
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	Ok(change_request_id.into())
+}
+
+/// Gets the names of the environment variables of a deployment that are
+/// currently secret
+async fn get_secret_variables(
+	connection: &mut DatabaseConnection,
+	deployment_id: Uuid,
+) -> Result<BTreeSet<String>, ErrorType> {
+	let secret_variables = query!(
+		r#"
+		SELECT
+			name
+		FROM
+			deployment_environment_variable
+		WHERE
+			deployment_id = $1 AND
+			is_secret = TRUE;
+		"#,
+		deployment_id as _,
+	)
+	.fetch_all(&mut *connection)
+	.await?
+	.into_iter()
+	.map(|row| row.name)
+	.collect();
+
+	Ok(secret_variables)
+}
+
+/// Masks the values of the environment variables of a change that are secret,
+/// either because the change marks them as secret or because they are already
+/// secret in the deployment, so that the change can be shown to anyone who can
+/// view the deployment
+fn mask_secret_variables(
+	changes: &mut UpdateDeploymentRequest,
+	secret_variables: &BTreeSet<String>,
+) {
+	take_secret_variables(changes, secret_variables);
+}
+
+/// Masks the values of the secret environment variables of a change (see
+/// [`mask_secret_variables`]), and returns the values that were masked, by the
+/// name of the variable. Values that are already masked keep the existing
+/// value of the variable once the change is applied, so they aren't returned.
+fn take_secret_variables(
+	changes: &mut UpdateDeploymentRequest,
+	secret_variables: &BTreeSet<String>,
+) -> BTreeMap<String, String> {
+	let Some(environment_variables) = &mut changes.environment_variables else {
+		return BTreeMap::new();
+	};
+
+	// The secret flags in the change replace the existing ones, if given
+	let secret_variables = changes
+		.secret_variables
+		.as_ref()
+		.unwrap_or(secret_variables);
+
+	environment_variables
+		.iter_mut()
+		.filter(|(name, _)| secret_variables.contains(*name))
+		.filter_map(|(name, value)| match value {
+			EnvironmentVariableValue::String(value)
+				if value != MASKED_ENVIRONMENT_VARIABLE_VALUE =>
+			{
+				let value = std::mem::replace(value, MASKED_ENVIRONMENT_VARIABLE_VALUE.to_string());
+				Some((name.clone(), value))
+			}
+			_ => None,
+		})
+		.collect()
+}
+
+/// Puts back the values of the secret environment variables of a change that
+/// were taken out of it by [`take_secret_variables`]
+fn restore_secret_variables(
+	changes: &mut UpdateDeploymentRequest,
+	secret_values: BTreeMap<String, String>,
+) {
+	let Some(environment_variables) = &mut changes.environment_variables else {
+		return;
+	};
+
+	for (name, secret_value) in secret_values {
+		if let Some(EnvironmentVariableValue::String(value)) = environment_variables.get_mut(&name)
+		{
+			*value = secret_value;
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn secret_values_are_taken_out_of_changes_and_restored() {
+		let mut changes = UpdateDeploymentRequest {
+			environment_variables: Some(BTreeMap::from([
+				(
+					"API_KEY".to_string(),
+					EnvironmentVariableValue::String("hunter2".to_string()),
+				),
+				(
+					"KEPT_KEY".to_string(),
+					EnvironmentVariableValue::String(MASKED_ENVIRONMENT_VARIABLE_VALUE.to_string()),
+				),
+				(
+					"PORT".to_string(),
+					EnvironmentVariableValue::String("8080".to_string()),
+				),
+			])),
+			..UpdateDeploymentRequest::default()
+		};
+		let requested = changes.clone();
+		let secret_variables = BTreeSet::from(["API_KEY".to_string(), "KEPT_KEY".to_string()]);
+
+		let secret_values = take_secret_variables(&mut changes, &secret_variables);
+		assert_eq!(
+			secret_values,
+			BTreeMap::from([("API_KEY".to_string(), "hunter2".to_string())])
+		);
+		assert!(!serde_json::to_string(&changes).unwrap().contains("hunter2"));
+
+		restore_secret_variables(&mut changes, secret_values);
+		assert_eq!(changes, requested);
+	}
+
+	#[test]
+	fn secret_flags_in_the_change_replace_the_existing_ones() {
+		let mut changes = UpdateDeploymentRequest {
+			environment_variables: Some(BTreeMap::from([(
+				"TOKEN".to_string(),
+				EnvironmentVariableValue::String("abc".to_string()),
+			)])),
+			secret_variables: Some(BTreeSet::from(["TOKEN".to_string()])),
+			..UpdateDeploymentRequest::default()
+		};
+
+		let secret_values = take_secret_variables(&mut changes, &BTreeSet::new());
+		assert_eq!(
+			secret_values,
+			BTreeMap::from([("TOKEN".to_string(), "abc".to_string())])
+		);
+	}
+}
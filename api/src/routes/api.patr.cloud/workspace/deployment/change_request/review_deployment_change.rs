@@ -0,0 +1,231 @@
+use axum::http::StatusCode;
+use models::api::workspace::deployment::{change_request::*, UpdateDeploymentRequest};
+use time::OffsetDateTime;
+
+use crate::prelude::*;
+
+/// The handler to approve or reject a change to a deployment that is waiting
+/// for approval. An approved change is applied to the deployment in the same
+/// transaction, so a change that is no longer valid (for example, because the
+/// limits of the workspace were lowered since it was requested) fails to be
+/// approved and stays pending. A change can't be approved either if the
+/// deployment was modified after it was requested, since the change was made
+/// against the previous version of the deployment. The user who requested the
+/// change can reject it to withdraw it, but cannot approve it.
+pub async fn review_deployment_change(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path:
+					ReviewDeploymentChangePath {
+						workspace_id,
+						deployment_id,
+						change_request_id,
+					},
+				query: (),
+				headers:
+					ReviewDeploymentChangeRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: ReviewDeploymentChangeRequestProcessed { approve },
+			},
+		database,
+		redis,
+		client_ip: _,
+		config,
+		user_data,
+		clock,
+	}: AuthenticatedAppRequest<'_, ReviewDeploymentChangeRequest>,
+) -> Result<AppResponse<ReviewDeploymentChangeRequest>, ErrorType> {
+	info!("Reviewing change `{change_request_id}` of deployment `{deployment_id}`");
+
+	super::ensure_deployment_exists(&mut **database, workspace_id, deployment_id).await?;
+
+	// Lock the change, so that it can't be reviewed twice at the same time
+	let change_request = query!(
+		r#"
+		SELECT
+			requested_by,
+			changes,
+			deployment_updated
+		FROM
+			deployment_change_request
+		WHERE
+			id = $1 AND
+			deployment_id = $2 AND
+			status = 'pending'
+		FOR UPDATE;
+		"#,
+		change_request_id as _,
+		deployment_id as _,
+	)
+	.fetch_optional(&mut **database)
+	.await?
+	.or_not_found()?;
+
+	let status = review_status(approve, change_request.requested_by.into(), user_data.id)?;
+
+	// The values of the secret variables of the change aren't kept once it is
+	// reviewed
+	let secret_values = query!(
+		r#"
+		DELETE FROM
+			deployment_change_request_secret_variable
+		WHERE
+			change_request_id = $1
+		RETURNING
+			name,
+			value;
+		"#,
+		change_request_id as _,
+	)
+	.fetch_all(&mut **database)
+	.await?
+	.into_iter()
+	.map(|row| (row.name, row.value))
+	.collect();
+
+	query!(
+		r#"
+		UPDATE
+			deployment_change_request
+		SET
+			status = $2,
+			reviewed_by = $3,
+			reviewed = $4
+		WHERE
+			id = $1;
+		"#,
+		change_request_id as _,
+		status as _,
+		user_data.id as _,
+		clock.now(),
+	)
+	.execute(&mut **database)
+	.await?;
+
+	if approve {
+		let deployment = query!(
+			r#"
+			SELECT
+				updated
+			FROM
+				deployment
+			WHERE
+				id = $1 AND
+				deleted IS NULL
+			FOR UPDATE;
+			"#,
+			deployment_id as _,
+		)
+		.fetch_optional(&mut **database)
+		.await?
+		.ok_or(ErrorType::ResourceDoesNotExist)?;
+		ensure_change_is_current(change_request.deployment_updated, deployment.updated)?;
+
+		let mut changes = serde_json::from_str::<UpdateDeploymentRequest>(&change_request.changes)?;
+		super::restore_secret_variables(&mut changes, secret_values);
+		super::apply_deployment_update(
+			&mut **database,
+			redis,
+			&config,
+			workspace_id,
+			deployment_id,
+			changes,
+		)
+		.await?;
+	}
+
+	AppResponse::builder()
+		.body(ReviewDeploymentChangeResponse { status })
+		.headers(())
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
+
+/// The status that a change request is given when it is reviewed. The user who
+/// requested the change can only reject it.
+fn review_status(
+	approve: bool,
+	requested_by: Uuid,
+	reviewer: Uuid,
+) -> Result<DeploymentChangeStatus, ErrorType> {
+	if !approve {
+		return Ok(DeploymentChangeStatus::Rejected);
+	}
+
+	if requested_by == reviewer {
+		debug!("User `{reviewer}` tried to approve their own change");
+		return Err(ErrorType::SelfApprovalNotAllowed);
+	}
+
+	Ok(DeploymentChangeStatus::Approved)
+}
+
+/// Checks that a deployment wasn't modified since a change to it was requested,
+/// given the time the deployment was last updated at when the change was
+/// requested and the time it was last updated at now
+fn ensure_change_is_current(
+	requested_at_version: OffsetDateTime,
+	current_version: OffsetDateTime,
+) -> Result<(), ErrorType> {
+	if requested_at_version != current_version {
+		debug!("The deployment was modified since the change was requested");
+		return Err(ErrorType::ResourceModified);
+	}
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use time::Duration;
+
+	use super::*;
+
+	#[test]
+	fn users_cannot_approve_their_own_changes() {
+		let (requester, reviewer) = (Uuid::new_v4(), Uuid::new_v4());
+
+		assert!(matches!(
+			review_status(true, requester, requester),
+			Err(ErrorType::SelfApprovalNotAllowed)
+		));
+		assert!(matches!(
+			review_status(true, requester, reviewer),
+			Ok(DeploymentChangeStatus::Approved)
+		));
+	}
+
+	#[test]
+	fn changes_can_be_rejected_by_anyone_including_the_requester() {
+		let (requester, reviewer) = (Uuid::new_v4(), Uuid::new_v4());
+
+		assert!(matches!(
+			review_status(false, requester, requester),
+			Ok(DeploymentChangeStatus::Rejected)
+		));
+		assert!(matches!(
+			review_status(false, requester, reviewer),
+			Ok(DeploymentChangeStatus::Rejected)
+		));
+	}
+
+	#[test]
+	fn changes_to_a_modified_deployment_are_stale() {
+		let requested_at_version = OffsetDateTime::UNIX_EPOCH;
+
+		assert!(ensure_change_is_current(requested_at_version, requested_at_version).is_ok());
+		// For example, the deployment was updated directly after the approval
+		// policy of the workspace was turned off
+		assert!(matches!(
+			ensure_change_is_current(
+				requested_at_version,
+				requested_at_version + Duration::seconds(1)
+			),
+			Err(ErrorType::ResourceModified)
+		));
+	}
+}
@@ -1,9 +1,27 @@
 use axum::http::StatusCode;
-use models::api::workspace::{deployment::*, runner::StreamRunnerDataForWorkspaceServerMsg};
+use models::{
+	api::workspace::{deployment::*, runner::StreamRunnerDataForWorkspaceServerMsg},
+	utils::{ImageReference, StringifiedU16, DEFAULT_IMAGE_REGISTRY},
+	QuotaResource,
+};
 use rustis::commands::PubSubCommands;
 use time::OffsetDateTime;
 
-use crate::prelude::*;
+use super::{
+	ensure_resources_fit_machine_type,
+	ensure_volumes_can_be_attached,
+	get_workspace_default_machine_type,
+	set_deployment_dependencies,
+	validate_max_concurrent_requests,
+	validate_max_replicas,
+	validate_scale_to_zero_after,
+	validate_volume_mounts,
+};
+use crate::{deployment_image_scanner, prelude::*, utils::http_client};
+
+/// How long each request made to an external registry, to check if an image is
+/// private, can take before the image is assumed to be public
+const REGISTRY_PROBE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
 
 /// The handler to create a deployment in the workspace. This will create a new
 /// deployment in the workspace, and return the ID of the deployment.
@@ -24,27 +42,38 @@ pub async fn create_deployment(
 						registry,
 						image_tag,
 						runner,
-						machine_type,
+						mut machine_type,
+						template_id,
 						running_details:
 							DeploymentRunningDetails {
 								deploy_on_push,
 								min_horizontal_scale,
 								max_horizontal_scale,
 								ports,
-								environment_variables,
-								startup_probe,
-								liveness_probe,
+								mut environment_variables,
+								mut startup_probe,
+								mut liveness_probe,
 								config_mounts,
 								volumes,
+								resources,
+								scale_to_zero_after,
+								log_level,
+								max_concurrent_requests,
+								access_logging,
 							},
 						deploy_on_create,
+						pull_secret_id,
+						secret_variables,
+						labels,
+						depends_on,
 					},
 			},
 		database,
 		redis,
 		client_ip: _,
-		config: _,
+		config,
 		user_data: _,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, CreateDeploymentRequest>,
 ) -> Result<AppResponse<CreateDeploymentRequest>, ErrorType> {
 	info!(
@@ -52,6 +81,105 @@ pub async fn create_deployment(
 		name, workspace_id
 	);
 
+	if let Some(template_id) = template_id {
+		let template =
+			super::template::get_deployment_template(&mut **database, workspace_id, template_id)
+				.await?
+				.ok_or(ErrorType::ResourceDoesNotExist)?;
+
+		// Values explicitly set in the request always take precedence over the
+		// ones in the template
+		machine_type = machine_type.or(template.machine_type);
+		for (name, value) in template.environment_variables {
+			environment_variables.entry(name).or_insert(value);
+		}
+
+		// The probes of a template can only be used if the deployment exposes
+		// the port that the probe is on
+		let is_port_exposed =
+			|probe: &DeploymentProbe| ports.contains_key(&StringifiedU16::new(probe.port));
+		startup_probe = startup_probe.or(template.startup_probe.filter(is_port_exposed));
+		liveness_probe = liveness_probe.or(template.liveness_probe.filter(is_port_exposed));
+	}
+
+	// Fall back to the default machine type of the workspace, if it has one
+	if machine_type.is_none() {
+		machine_type = get_workspace_default_machine_type(&mut **database, workspace_id).await?;
+	}
+	let machine_type = machine_type.ok_or(ErrorType::WrongParameters)?;
+	let resources = resources.with_default_limits();
+
+	validate_volume_mounts(&volumes)?;
+	validate_scale_to_zero_after(scale_to_zero_after)?;
+	validate_max_replicas(&mut **database, &config, workspace_id, max_horizontal_scale).await?;
+	let max_concurrent_requests = validate_max_concurrent_requests(max_concurrent_requests)?;
+	Deployment::validate_labels(&labels)?;
+
+	// Store images on external registries in their canonical form, so that the
+	// implicit registry and tag are always explicit. An image pinned to a
+	// digest is deployed with that digest.
+	let (registry, image_tag, pinned_digest) = match registry {
+		DeploymentRegistry::ExternalRegistry {
+			registry,
+			image_name,
+		} => {
+			let reference = ImageReference::from_parts(&registry, &image_name, &image_tag)
+				.map_err(|err| ErrorType::InvalidImageReference(err.invalid_part()))?;
+			(
+				DeploymentRegistry::ExternalRegistry {
+					registry: reference.registry,
+					image_name: reference.repository,
+				},
+				reference.tag,
+				reference.digest,
+			)
+		}
+		registry => (registry, image_tag.to_string(), None),
+	};
+
+	if let Some(pull_secret_id) = pull_secret_id {
+		query!(
+			r#"
+			SELECT
+				secret_pull_credential.secret_id
+			FROM
+				secret_pull_credential
+			INNER JOIN
+				secret
+			ON
+				secret.id = secret_pull_credential.secret_id
+			WHERE
+				secret_pull_credential.secret_id = $1 AND
+				secret.workspace_id = $2 AND
+				secret.deleted IS NULL;
+			"#,
+			pull_secret_id as _,
+			workspace_id as _,
+		)
+		.fetch_optional(&mut **database)
+		.await?
+		.or_not_found()?;
+	} else if let DeploymentRegistry::ExternalRegistry {
+		registry,
+		image_name,
+	} = &registry
+	{
+		let reference = pinned_digest.as_deref().unwrap_or(&image_tag);
+		if is_image_private(&registry_url(registry), image_name, reference).await {
+			return Err(ErrorType::MissingPullSecret);
+		}
+	}
+
+	// The quota is checked right before the deployment is created, since the
+	// workspace stays locked from then on
+	super::super::ensure_within_quota(
+		&mut **database,
+		&config,
+		workspace_id,
+		QuotaResource::Deployment,
+	)
+	.await?;
+
 	let now = OffsetDateTime::now_utc();
 
 	let deployment_id = query!(
@@ -110,13 +238,25 @@ pub async fn create_deployment(
 				min_horizontal_scale,
 				max_horizontal_scale,
 				machine_type,
+				template_id,
+				cpu_request,
+				cpu_limit,
+				memory_request,
+				memory_limit,
+				scale_to_zero_after,
+				log_level,
+				max_concurrent_requests,
+				access_logging,
 				deploy_on_push,
 				startup_probe_port,
 				startup_probe_path,
 				startup_probe_port_type,
 				liveness_probe_port,
 				liveness_probe_path,
-				liveness_probe_port_type
+				liveness_probe_port_type,
+				pull_secret_id,
+				current_live_digest,
+				updated
 			)
 		VALUES
 			(
@@ -138,7 +278,19 @@ pub async fn create_deployment(
 				$16,
 				$17,
 				$18,
-				$19
+				$19,
+				$20,
+				$21,
+				$22,
+				$23,
+				$24,
+				$25,
+				$26,
+				$27,
+				$28,
+				$29,
+				$30,
+				$31
 			);
 		"#,
 		deployment_id as _,
@@ -146,7 +298,7 @@ pub async fn create_deployment(
 		registry.registry_url(),
 		registry.repository_id() as _,
 		registry.image_name(),
-		image_tag.as_ref(),
+		image_tag.as_str(),
 		if deploy_on_create {
 			DeploymentStatus::Running
 		} else {
@@ -157,6 +309,15 @@ pub async fn create_deployment(
 		min_horizontal_scale as i32,
 		max_horizontal_scale as i32,
 		machine_type as _,
+		template_id as _,
+		resources.cpu_request.map(|value| value as i32),
+		resources.cpu_limit.map(|value| value as i32),
+		resources.memory_request.map(|value| value as i32),
+		resources.memory_limit.map(|value| value as i32),
+		scale_to_zero_after.map(|value| value as i32),
+		log_level as _,
+		max_concurrent_requests.map(|value| value as i32),
+		access_logging,
 		deploy_on_push,
 		startup_probe.as_ref().map(|probe| probe.port as i32),
 		startup_probe.as_ref().map(|probe| probe.path.as_str()),
@@ -164,6 +325,9 @@ pub async fn create_deployment(
 		liveness_probe.as_ref().map(|probe| probe.port as i32),
 		liveness_probe.as_ref().map(|probe| probe.path.as_str()),
 		liveness_probe.as_ref().map(|_| ExposedPortType::Http) as _,
+		pull_secret_id as _,
+		pinned_digest.as_deref(),
+		now,
 	)
 	.execute(&mut **database)
 	.await
@@ -222,14 +386,16 @@ pub async fn create_deployment(
 				deployment_id,
 				name,
 				value,
-				secret_id
+				secret_id,
+				is_secret
 			)
 		VALUES
 			(
 				UNNEST($1::UUID[]),
 				UNNEST($2::TEXT[]),
 				UNNEST($3::TEXT[]),
-				UNNEST($4::UUID[])
+				UNNEST($4::UUID[]),
+				UNNEST($5::BOOLEAN[])
 			);
 		"#,
 		&environment_variables
@@ -248,6 +414,10 @@ pub async fn create_deployment(
 			.iter()
 			.map(|(_, value)| value.secret_id().map(Into::into))
 			.collect::<Vec<Option<sqlx::types::Uuid>>>() as _,
+		&environment_variables
+			.iter()
+			.map(|(name, value)| value.is_string() && secret_variables.contains(name))
+			.collect::<Vec<_>>(),
 	)
 	.execute(&mut **database)
 	.await?;
@@ -285,6 +455,40 @@ pub async fn create_deployment(
 	.execute(&mut **database)
 	.await?;
 
+	query!(
+		r#"
+		INSERT INTO
+			deployment_label(
+				deployment_id,
+				key,
+				value
+			)
+		VALUES
+			(
+				UNNEST($1::UUID[]),
+				UNNEST($2::TEXT[]),
+				UNNEST($3::TEXT[])
+			);
+		"#,
+		&labels.iter().map(|_| deployment_id).collect::<Vec<_>>(),
+		&labels.keys().cloned().collect::<Vec<_>>(),
+		&labels.values().cloned().collect::<Vec<_>>(),
+	)
+	.execute(&mut **database)
+	.await?;
+
+	trace!("Inserted labels for deployment");
+
+	set_deployment_dependencies(
+		&mut **database,
+		workspace_id,
+		deployment_id.into(),
+		&depends_on,
+	)
+	.await?;
+
+	trace!("Inserted dependencies for deployment");
+
 	query!(
 		r#"
 		INSERT INTO 
@@ -317,6 +521,9 @@ pub async fn create_deployment(
 		err => ErrorType::server_error(err),
 	})?;
 
+	ensure_volumes_can_be_attached(&mut **database, deployment_id.into()).await?;
+	ensure_resources_fit_machine_type(&mut **database, deployment_id.into()).await?;
+
 	if let DeploymentRegistry::PatrRegistry { repository_id, .. } = &registry {
 		let digest = query!(
 			r#"
@@ -362,6 +569,19 @@ pub async fn create_deployment(
 		}
 	}
 
+	if deploy_on_create {
+		deployment_image_scanner::ensure_image_allowed(
+			&mut **database,
+			redis,
+			&config,
+			workspace_id,
+			deployment_id,
+			false,
+			now,
+		)
+		.await?;
+	}
+
 	// TODO Temporary workaround until audit logs and triggers are implemented
 	redis
 		.publish(
@@ -372,11 +592,17 @@ pub async fn create_deployment(
 					Deployment {
 						name: name.to_string(),
 						registry,
-						image_tag: image_tag.to_string(),
+						image_tag,
 						runner,
 						status: DeploymentStatus::Deploying,
-						current_live_digest: None,
+						current_live_digest: pinned_digest,
 						machine_type,
+						pull_secret_id,
+						created_at: now,
+						updated_at: now,
+						labels,
+						depends_on,
+						canary: None,
 					},
 				),
 				running_details: DeploymentRunningDetails {
@@ -389,6 +615,11 @@ pub async fn create_deployment(
 					liveness_probe,
 					config_mounts,
 					volumes,
+					resources,
+					scale_to_zero_after,
+					log_level,
+					max_concurrent_requests,
+					access_logging,
 				},
 			})
 			.unwrap(),
@@ -404,3 +635,196 @@ pub async fn create_deployment(
 		.build()
 		.into_result()
 }
+
+/// The URL that the registry API of an external registry is served at. Docker
+/// Hub serves it on a different host than the one images are referred by.
+fn registry_url(registry: &str) -> String {
+	if registry == DEFAULT_IMAGE_REGISTRY {
+		"https://registry-1.docker.io".to_string()
+	} else {
+		format!("https://{registry}")
+	}
+}
+
+/// Checks if an image on an external registry requires credentials to be
+/// pulled. This is best-effort: the manifest of the image is requested
+/// anonymously, and the image is only considered private if the registry
+/// explicitly denies access to it. Any other failure (network errors,
+/// registries that don't follow the distribution spec, etc) is treated as the
+/// image being public, so that deployments aren't blocked by a flaky registry.
+async fn is_image_private(registry_url: &str, image_name: &str, reference: &str) -> bool {
+	let manifest_url = format!("{registry_url}/v2/{image_name}/manifests/{reference}");
+	let accept = [
+		"application/vnd.oci.image.index.v1+json",
+		"application/vnd.oci.image.manifest.v1+json",
+		"application/vnd.docker.distribution.manifest.list.v2+json",
+		"application/vnd.docker.distribution.manifest.v2+json",
+	]
+	.join(", ");
+
+	let Ok(response) = http_client()
+		.head(&manifest_url)
+		.header(reqwest::header::ACCEPT, &accept)
+		.timeout(REGISTRY_PROBE_TIMEOUT)
+		.send()
+		.await
+	else {
+		return false;
+	};
+
+	if response.status() != StatusCode::UNAUTHORIZED {
+		return response.status() == StatusCode::FORBIDDEN;
+	}
+
+	// Registries that support anonymous access still return a 401, with a
+	// bearer challenge that points to where an anonymous token can be fetched
+	let Some((realm, params)) = response
+		.headers()
+		.get(reqwest::header::WWW_AUTHENTICATE)
+		.and_then(|value| value.to_str().ok())
+		.and_then(|value| value.strip_prefix("Bearer "))
+		.map(parse_bearer_challenge)
+		.and_then(|mut params| {
+			let realm = params.remove("realm")?;
+			Some((realm, params))
+		})
+	else {
+		// Basic auth (or no challenge at all) means credentials are required
+		return true;
+	};
+
+	let Ok(token_response) = http_client()
+		.get(&realm)
+		.query(&params)
+		.timeout(REGISTRY_PROBE_TIMEOUT)
+		.send()
+		.await
+	else {
+		return false;
+	};
+
+	if matches!(
+		token_response.status(),
+		StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN
+	) {
+		return true;
+	}
+
+	/// The anonymous token returned by the token endpoint of a registry
+	#[derive(serde::Deserialize)]
+	struct TokenResponse {
+		/// The token, which some registries name `access_token` instead
+		#[serde(alias = "access_token")]
+		token: String,
+	}
+
+	let Ok(TokenResponse { token }) = token_response.json::<TokenResponse>().await else {
+		return false;
+	};
+
+	http_client()
+		.head(&manifest_url)
+		.header(reqwest::header::ACCEPT, &accept)
+		.bearer_auth(token)
+		.timeout(REGISTRY_PROBE_TIMEOUT)
+		.send()
+		.await
+		.is_ok_and(|response| {
+			matches!(
+				response.status(),
+				StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN
+			)
+		})
+}
+
+/// Parses the parameters of a bearer `WWW-Authenticate` challenge, in the
+/// format `realm="...",service="...",scope="..."`
+fn parse_bearer_challenge(challenge: &str) -> std::collections::BTreeMap<String, String> {
+	challenge
+		.split(',')
+		.filter_map(|param| {
+			let (key, value) = param.trim().split_once('=')?;
+			Some((key.to_string(), value.trim_matches('"').to_string()))
+		})
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use tokio::{
+		io::{AsyncReadExt, AsyncWriteExt},
+		net::TcpListener,
+	};
+
+	use super::*;
+
+	/// Runs a registry that only serves the manifests of images to clients with
+	/// an anonymous token, which it hands out from its token endpoint. Requests
+	/// for manifests made with the token are answered with the given status
+	/// line. Returns the URL of the registry.
+	async fn anonymous_token_registry(manifest_status: &'static str) -> String {
+		let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+		let url = format!("http://{}", listener.local_addr().unwrap());
+		let challenge = format!(r#"Bearer realm="{url}/token",service="registry""#);
+
+		tokio::spawn(async move {
+			while let Ok((mut stream, _)) = listener.accept().await {
+				let mut request = [0; 4096];
+				let read = stream.read(&mut request).await.unwrap_or_default();
+				let request = String::from_utf8_lossy(&request[..read]).to_lowercase();
+
+				let response = if request.starts_with("get /token") {
+					let body = r#"{"token":"anonymous"}"#;
+					format!(
+						"HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\
+						Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+						body.len()
+					)
+				} else if request.contains("authorization: bearer anonymous") {
+					format!(
+						"HTTP/1.1 {manifest_status}\r\nContent-Length: 0\r\n\
+						Connection: close\r\n\r\n"
+					)
+				} else {
+					format!(
+						"HTTP/1.1 401 Unauthorized\r\nWWW-Authenticate: {challenge}\r\n\
+						Content-Length: 0\r\nConnection: close\r\n\r\n"
+					)
+				};
+				_ = stream.write_all(response.as_bytes()).await;
+			}
+		});
+
+		url
+	}
+
+	#[tokio::test]
+	async fn images_denied_to_anonymous_clients_are_private() {
+		let registry = anonymous_token_registry("401 Unauthorized").await;
+		assert!(is_image_private(&registry, "team/private-app", "latest").await);
+
+		let registry = anonymous_token_registry("403 Forbidden").await;
+		assert!(is_image_private(&registry, "team/private-app", "latest").await);
+	}
+
+	#[tokio::test]
+	async fn images_served_to_anonymous_clients_are_public() {
+		let registry = anonymous_token_registry("200 OK").await;
+		assert!(!is_image_private(&registry, "library/nginx", "latest").await);
+	}
+
+	#[tokio::test]
+	async fn unreachable_registries_are_assumed_to_be_public() {
+		let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+		let registry = format!("http://{}", listener.local_addr().unwrap());
+		drop(listener);
+
+		assert!(!is_image_private(&registry, "team/private-app", "latest").await);
+	}
+
+	#[test]
+	fn docker_hub_images_are_checked_on_its_registry_host() {
+		assert_eq!(registry_url("docker.io"), "https://registry-1.docker.io");
+		assert_eq!(registry_url("ghcr.io"), "https://ghcr.io");
+	}
+}
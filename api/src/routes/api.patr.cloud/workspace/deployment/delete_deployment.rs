@@ -1,12 +1,17 @@
 use axum::http::StatusCode;
 use models::api::workspace::{deployment::*, runner::StreamRunnerDataForWorkspaceServerMsg};
 use rustis::commands::PubSubCommands;
+use time::OffsetDateTime;
 
 use crate::prelude::*;
 
-/// The handler to delete a deployment in the workspace. This will delete the
-/// deployment from the workspace, and remove all resources associated with the
-/// deployment.
+/// The handler to delete a deployment in the workspace. The deployment is only
+/// marked as deleted, which removes it from the workspace and its runner, but
+/// keeps its configuration so that it can be restored using the
+/// [`super::restore_deployment`] handler. Once
+/// [`constants::DEPLOYMENT_RESTORE_GRACE_PERIOD`] is over, the deployment is
+/// permanently removed by the [`crate::deployment_purger`]. Until then, the
+/// volumes of the deployment stay attached to it.
 pub async fn delete_deployment(
 	AuthenticatedAppRequest {
 		request:
@@ -28,6 +33,7 @@ pub async fn delete_deployment(
 		client_ip: _,
 		config: _,
 		user_data: _,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, DeleteDeploymentRequest>,
 ) -> Result<AppResponse<DeleteDeploymentRequest>, ErrorType> {
 	info!("Deleting deployment: {deployment_id}");
@@ -39,50 +45,82 @@ pub async fn delete_deployment(
 		FROM
 			deployment
 		WHERE
-			id = $1;
+			id = $1 AND
+			workspace_id = $2 AND
+			deleted IS NULL;
 		"#,
-		deployment_id as _
+		deployment_id as _,
+		workspace_id as _,
 	)
-	.fetch_one(&mut **database)
+	.fetch_optional(&mut **database)
 	.await?
+	.or_not_found()?
 	.runner;
 
-	query!(
+	// The deployment can't be removed while a managed URL still points to it
+	let is_in_use = query!(
 		r#"
-		DELETE FROM
-			deployment_environment_variable
+		SELECT
+			managed_url.id
+		FROM
+			managed_url
 		WHERE
-			deployment_id = $1;
-		"#,
-		deployment_id as _
-	)
-	.execute(&mut **database)
-	.await?;
-
-	query!(
-		r#"
-		DELETE FROM
-			deployment_config_mounts
+			managed_url.deployment_id = $1 AND
+			managed_url.deleted IS NULL
+		UNION ALL
+		SELECT
+			managed_url.id
+		FROM
+			managed_url_path_rule
+		INNER JOIN
+			managed_url
+		ON
+			managed_url.id = managed_url_path_rule.managed_url_id
 		WHERE
-			deployment_id = $1;
+			managed_url_path_rule.deployment_id = $1 AND
+			managed_url.deleted IS NULL;
 		"#,
-		deployment_id as _
+		deployment_id as _,
 	)
-	.execute(&mut **database)
-	.await?;
+	.fetch_optional(&mut **database)
+	.await?
+	.is_some();
 
-	query!(
+	if is_in_use {
+		return Err(ErrorType::ResourceInUse);
+	}
+
+	// Nor while other deployments depend on it, since they wouldn't be able to
+	// start without it
+	let has_dependents = query!(
 		r#"
-		DELETE FROM
-			deployment_deploy_history
+		SELECT
+			deployment.id
+		FROM
+			deployment_dependency
+		INNER JOIN
+			deployment
+		ON
+			deployment.id = deployment_dependency.deployment_id
 		WHERE
-			deployment_id = $1;
+			deployment_dependency.dependency_id = $1 AND
+			deployment.deleted IS NULL;
 		"#,
-		deployment_id as _
+		deployment_id as _,
 	)
-	.execute(&mut **database)
-	.await?;
+	.fetch_optional(&mut **database)
+	.await?
+	.is_some();
 
+	if has_dependents {
+		info!("Deployment `{deployment_id}` has other deployments depending on it");
+		return Err(ErrorType::ResourceInUse);
+	}
+
+	let now = OffsetDateTime::now_utc();
+
+	// The deployment and its resource are marked as deleted together, since
+	// the deleted timestamps of both have to match
 	query!(
 		r#"
 		SET CONSTRAINTS ALL DEFERRED;
@@ -93,32 +131,34 @@ pub async fn delete_deployment(
 
 	query!(
 		r#"
-		DELETE FROM
-			deployment_exposed_port
+		UPDATE
+			deployment
+		SET
+			deleted = $2
 		WHERE
-			deployment_id = $1;
+			id = $1;
 		"#,
-		deployment_id as _
+		deployment_id as _,
+		now,
 	)
 	.execute(&mut **database)
 	.await?;
 
-	// Delete the deployment in the database
+	// Mark the resource as deleted in the database
 	query!(
 		r#"
-		DELETE FROM
-			deployment
+		UPDATE
+			resource
+		SET
+			deleted = $2
 		WHERE
 			id = $1;
 		"#,
-		deployment_id as _
+		deployment_id as _,
+		now,
 	)
 	.execute(&mut **database)
-	.await
-	.map_err(|err| match err {
-		sqlx::Error::Database(err) if err.is_foreign_key_violation() => ErrorType::ResourceInUse,
-		err => ErrorType::server_error(err),
-	})?;
+	.await?;
 
 	query!(
 		r#"
@@ -128,25 +168,6 @@ pub async fn delete_deployment(
 	.execute(&mut **database)
 	.await?;
 
-	// Mark the resource as deleted in the database
-	query!(
-		r#"
-		UPDATE
-			resource
-		SET
-			deleted = NOW()
-		WHERE
-			id = $1;
-		"#,
-		deployment_id as _
-	)
-	.execute(&mut **database)
-	.await
-	.map_err(|err| match err {
-		sqlx::Error::Database(err) if err.is_foreign_key_violation() => ErrorType::ResourceInUse,
-		err => ErrorType::server_error(err),
-	})?;
-
 	// TODO Temporary workaround until audit logs and triggers are implemented
 	redis
 		.publish(
@@ -27,6 +27,7 @@ pub async fn delete_deploy_history(
 		client_ip: _,
 		config: _,
 		user_data: _,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, DeleteDeploymentDeployHistoryRequest>,
 ) -> Result<AppResponse<DeleteDeploymentDeployHistoryRequest>, ErrorType> {
 	info!(
@@ -30,6 +30,7 @@ pub async fn list_deploy_history(
 		client_ip: _,
 		config: _,
 		user_data: _,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, ListDeploymentDeployHistoryRequest>,
 ) -> Result<AppResponse<ListDeploymentDeployHistoryRequest>, ErrorType> {
 	info!("Listing deployment history");
@@ -0,0 +1,318 @@
+use axum::{
+	body::Body,
+	http::{header, HeaderValue, Response, StatusCode},
+};
+use models::{api::workspace::deployment::*, utils::GenericResponse};
+use time::{format_description::well_known::Rfc3339, Duration, OffsetDateTime};
+
+use super::get_deployment_logs::{DeploymentLogStream, LogDirection, LokiLogQuery};
+use crate::prelude::*;
+
+/// The position of a download in the logs of a deployment. Loki can only be
+/// paged by time, and several logs can share the same timestamp, so each batch
+/// starts at the timestamp of the last log of the previous batch, and the logs
+/// of that timestamp that were already sent are skipped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct LogCursor {
+	/// The time (inclusive) from which the next batch is read
+	start: OffsetDateTime,
+	/// The logs at exactly `start` that were already sent
+	sent_at_start: Vec<String>,
+}
+
+impl LogCursor {
+	/// Creates a cursor at the start of a download
+	fn new(start: OffsetDateTime) -> Self {
+		Self {
+			start,
+			sent_at_start: Vec::new(),
+		}
+	}
+
+	/// Takes a batch of logs read from this cursor, in forward order, and
+	/// returns the logs of the batch that weren't sent yet, along with the
+	/// cursor of the next batch. There is no next batch if this one wasn't
+	/// full, since there are no more logs left then.
+	fn advance(
+		self,
+		logs: Vec<DeploymentLog>,
+		batch_size: usize,
+	) -> (Vec<DeploymentLog>, Option<Self>) {
+		let is_full = logs.len() >= batch_size;
+		let Self {
+			start,
+			sent_at_start,
+		} = self;
+
+		// Logs with the same content and timestamp are skipped as many times as
+		// they were already sent
+		let mut to_skip = sent_at_start.clone();
+		let logs = logs
+			.into_iter()
+			.filter(|log| {
+				if log.timestamp != start {
+					return true;
+				}
+				match to_skip.iter().position(|sent| *sent == log.log) {
+					Some(index) => {
+						to_skip.swap_remove(index);
+						false
+					}
+					None => true,
+				}
+			})
+			.collect::<Vec<_>>();
+
+		if !is_full {
+			return (logs, None);
+		}
+
+		let next = match logs.last() {
+			Some(last) => {
+				let sent_at_start = if last.timestamp == start {
+					// The logs at the start that were sent by the previous
+					// batches are still to be skipped
+					sent_at_start
+				} else {
+					Vec::new()
+				};
+				Self {
+					start: last.timestamp,
+					sent_at_start: logs
+						.iter()
+						.filter(|log| log.timestamp == last.timestamp)
+						.map(|log| log.log.clone())
+						.chain(sent_at_start)
+						.collect(),
+				}
+			}
+			// Every log of the batch was already sent, which only happens when
+			// there are more logs at the same timestamp than fit in a batch.
+			// Those can't be read, so skip past them.
+			None => Self::new(start + Duration::nanoseconds(1)),
+		};
+
+		(logs, Some(next))
+	}
+}
+
+/// Route to download the logs of a deployment for a time range. The logs are
+/// read from Loki in batches and streamed to the user as they are read, so
+/// that the whole range is never held in memory. The range is clamped to the
+/// retention period of the logs, and truncated if it is longer than what can
+/// be downloaded in a single request.
+pub async fn download_deployment_logs(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: DownloadDeploymentLogsPath {
+					workspace_id,
+					deployment_id,
+				},
+				query:
+					DownloadDeploymentLogsQuery {
+						start_time,
+						end_time,
+						format,
+						search,
+					},
+				headers:
+					DownloadDeploymentLogsRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: DownloadDeploymentLogsRequestProcessed,
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config,
+		user_data: _,
+		clock: _,
+	}: AuthenticatedAppRequest<'_, DownloadDeploymentLogsRequest>,
+) -> Result<AppResponse<DownloadDeploymentLogsRequest>, ErrorType> {
+	info!("Downloading logs for deployment: {}", deployment_id);
+
+	query!(
+		r#"
+		SELECT
+			id
+		FROM
+			deployment
+		WHERE
+			id = $1 AND
+			workspace_id = $2 AND
+			deleted IS NULL;
+		"#,
+		deployment_id as _,
+		workspace_id as _,
+	)
+	.fetch_optional(&mut **database)
+	.await?
+	.or_not_found()?;
+
+	let now = OffsetDateTime::now_utc();
+	let end = end_time.unwrap_or(now).min(now);
+	if start_time >= end {
+		return Err(ErrorType::WrongParameters);
+	}
+
+	// Logs older than the retention period don't exist anymore
+	let start =
+		start_time.max(now - Duration::hours(i64::from(config.opentelemetry.logs.retention_hours)));
+	let max_range = Duration::hours(i64::from(
+		config.opentelemetry.logs.max_download_range_hours,
+	));
+	let (end, truncated) = if end - start > max_range {
+		(start + max_range, true)
+	} else {
+		(end, false)
+	};
+
+	let format = format.unwrap_or_default();
+	let client = reqwest::Client::new();
+	let endpoint = config.opentelemetry.logs.endpoint.clone();
+
+	let logs = futures::stream::try_unfold(Some(LogCursor::new(start)), move |cursor| {
+		let client = client.clone();
+		let endpoint = endpoint.clone();
+		let search = search.clone();
+		async move {
+			let Some(cursor) = cursor.filter(|cursor| cursor.start < end) else {
+				return Ok(None);
+			};
+
+			let logs = LokiLogQuery {
+				workspace_id,
+				deployment_id,
+				stream: DeploymentLogStream::Container,
+				replica_id: None,
+				start: Some(cursor.start),
+				end,
+				limit: constants::LOGS_DOWNLOAD_BATCH_SIZE,
+				search: search.as_deref(),
+				parse_json: false,
+				level: None,
+				direction: LogDirection::Forward,
+			}
+			.fetch(&client, &endpoint)
+			.await
+			.map_err(|err| std::io::Error::other(err.to_string()))?;
+
+			let (logs, next_cursor) = cursor.advance(
+				logs,
+				usize::try_from(constants::LOGS_DOWNLOAD_BATCH_SIZE).unwrap_or(usize::MAX),
+			);
+
+			let mut chunk = String::new();
+			for log in logs {
+				match format {
+					DeploymentLogFormat::Text => {
+						chunk.push_str(&format!(
+							"{} {}\n",
+							log.timestamp.format(&Rfc3339).unwrap_or_default(),
+							log.log.trim_end_matches('\n')
+						));
+					}
+					DeploymentLogFormat::Json => {
+						chunk
+							.push_str(&serde_json::to_string(&log).map_err(std::io::Error::other)?);
+						chunk.push('\n');
+					}
+				}
+			}
+
+			Ok::<_, std::io::Error>(Some((chunk, next_cursor)))
+		}
+	});
+
+	let mut response = Response::builder()
+		.status(StatusCode::OK)
+		.header(header::CONTENT_TYPE, format.content_type())
+		.header(
+			header::CONTENT_DISPOSITION,
+			format!(
+				"attachment; filename=\"{}-logs.{}\"",
+				deployment_id,
+				format.file_extension()
+			),
+		)
+		.body(Body::from_stream(logs))
+		.map_err(ErrorType::server_error)?;
+
+	if truncated {
+		response.headers_mut().insert(
+			LOGS_TRUNCATED_AT_HEADER,
+			HeaderValue::from_str(&end.format(&Rfc3339).map_err(ErrorType::server_error)?)
+				.map_err(ErrorType::server_error)?,
+		);
+	}
+
+	AppResponse::builder()
+		.body(GenericResponse(response))
+		.headers(())
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// A log with the given content at the given number of seconds after the
+	/// UNIX epoch
+	fn log(seconds: i64, content: &str) -> DeploymentLog {
+		DeploymentLog {
+			timestamp: OffsetDateTime::UNIX_EPOCH + Duration::seconds(seconds),
+			log: content.to_string(),
+			parsed: None,
+		}
+	}
+
+	#[test]
+	fn logs_sharing_a_timestamp_across_batches_are_all_sent_once() {
+		let cursor = LogCursor::new(OffsetDateTime::UNIX_EPOCH);
+
+		let (sent, cursor) = cursor.advance(vec![log(0, "a"), log(1, "b"), log(1, "c")], 3);
+		assert_eq!(sent, vec![log(0, "a"), log(1, "b"), log(1, "c")]);
+		let cursor = cursor.unwrap();
+		assert_eq!(cursor.start, log(1, "").timestamp);
+
+		// The next batch starts at the same timestamp, so that the logs at
+		// that timestamp that didn't fit in the previous batch aren't skipped
+		let (sent, cursor) = cursor.advance(vec![log(1, "b"), log(1, "c"), log(1, "d")], 3);
+		assert_eq!(sent, vec![log(1, "d")]);
+
+		let (sent, cursor) = cursor
+			.unwrap()
+			.advance(vec![log(1, "b"), log(1, "c"), log(1, "d")], 3);
+		assert!(sent.is_empty());
+
+		// More logs share the timestamp than fit in a batch, so the rest of
+		// them can't be read
+		let cursor = cursor.unwrap();
+		assert_eq!(
+			cursor,
+			LogCursor::new(log(1, "").timestamp + Duration::nanoseconds(1))
+		);
+
+		let (sent, cursor) = cursor.advance(vec![log(2, "e")], 3);
+		assert_eq!(sent, vec![log(2, "e")]);
+		assert_eq!(cursor, None);
+	}
+
+	#[test]
+	fn identical_logs_at_the_same_timestamp_are_not_deduplicated() {
+		let cursor = LogCursor::new(OffsetDateTime::UNIX_EPOCH);
+
+		let (sent, cursor) = cursor.advance(vec![log(0, "a"), log(1, "retry")], 2);
+		assert_eq!(sent, vec![log(0, "a"), log(1, "retry")]);
+
+		let (sent, cursor) = cursor
+			.unwrap()
+			.advance(vec![log(1, "retry"), log(1, "retry")], 2);
+		assert_eq!(sent, vec![log(1, "retry")]);
+		assert_eq!(cursor.unwrap().sent_at_start, vec!["retry", "retry"]);
+	}
+}
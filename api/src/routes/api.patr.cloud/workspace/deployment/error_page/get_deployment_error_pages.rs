@@ -0,0 +1,60 @@
+use axum::http::StatusCode;
+use models::api::workspace::deployment::error_page::*;
+
+use crate::prelude::*;
+
+/// The handler to get the custom error pages set on a deployment. The default
+/// error pages of the workspace are not included.
+pub async fn get_deployment_error_pages(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: GetDeploymentErrorPagesPath {
+					workspace_id,
+					deployment_id,
+				},
+				query: (),
+				headers:
+					GetDeploymentErrorPagesRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: GetDeploymentErrorPagesRequestProcessed,
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config: _,
+		user_data: _,
+		clock: _,
+	}: AuthenticatedAppRequest<'_, GetDeploymentErrorPagesRequest>,
+) -> Result<AppResponse<GetDeploymentErrorPagesRequest>, ErrorType> {
+	info!("Getting the error pages of deployment `{deployment_id}`");
+
+	super::ensure_deployment_exists(&mut **database, workspace_id, deployment_id).await?;
+
+	let error_pages = query!(
+		r#"
+		SELECT
+			kind AS "kind: DeploymentErrorPageKind",
+			html
+		FROM
+			deployment_error_page
+		WHERE
+			deployment_id = $1;
+		"#,
+		deployment_id as _,
+	)
+	.fetch_all(&mut **database)
+	.await?
+	.into_iter()
+	.map(|row| (row.kind, row.html))
+	.collect();
+
+	AppResponse::builder()
+		.body(GetDeploymentErrorPagesResponse { error_pages })
+		.headers(())
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
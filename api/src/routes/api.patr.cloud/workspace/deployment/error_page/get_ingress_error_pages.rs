@@ -0,0 +1,42 @@
+use axum::http::StatusCode;
+use models::api::workspace::deployment::error_page::*;
+
+use crate::prelude::*;
+
+/// The handler for the ingress to get the custom error pages that it should
+/// serve for a deployment. Only the ingress can get them, using the token that
+/// it is configured with. The pages are only fetched when the ingress has to
+/// serve one of them, instead of with every activity report of the deployment.
+pub async fn get_ingress_error_pages(
+	AppRequest {
+		request:
+			ProcessedApiRequest {
+				path: GetIngressErrorPagesPath { deployment_id },
+				query: (),
+				headers:
+					GetIngressErrorPagesRequestHeaders {
+						authorization,
+						user_agent: _,
+					},
+				body: GetIngressErrorPagesRequestProcessed,
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config,
+		clock: _,
+	}: AppRequest<'_, GetIngressErrorPagesRequest>,
+) -> Result<AppResponse<GetIngressErrorPagesRequest>, ErrorType> {
+	trace!("Getting the error pages of deployment `{deployment_id}` for the ingress");
+
+	super::super::verify_ingress_token(&config, &authorization)?;
+
+	let error_pages = super::get_effective_error_pages(&mut **database, deployment_id).await?;
+
+	AppResponse::builder()
+		.body(GetIngressErrorPagesResponse { error_pages })
+		.headers(())
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
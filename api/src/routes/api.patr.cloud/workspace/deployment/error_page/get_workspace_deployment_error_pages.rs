@@ -0,0 +1,55 @@
+use axum::http::StatusCode;
+use models::api::workspace::deployment::error_page::*;
+
+use crate::prelude::*;
+
+/// The handler to get the default custom error pages of the deployments in a
+/// workspace
+pub async fn get_workspace_deployment_error_pages(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: GetWorkspaceDeploymentErrorPagesPath { workspace_id },
+				query: (),
+				headers:
+					GetWorkspaceDeploymentErrorPagesRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: GetWorkspaceDeploymentErrorPagesRequestProcessed,
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config: _,
+		user_data: _,
+		clock: _,
+	}: AuthenticatedAppRequest<'_, GetWorkspaceDeploymentErrorPagesRequest>,
+) -> Result<AppResponse<GetWorkspaceDeploymentErrorPagesRequest>, ErrorType> {
+	info!("Getting the default deployment error pages of workspace `{workspace_id}`");
+
+	let error_pages = query!(
+		r#"
+		SELECT
+			kind AS "kind: DeploymentErrorPageKind",
+			html
+		FROM
+			workspace_deployment_error_page
+		WHERE
+			workspace_id = $1;
+		"#,
+		workspace_id as _,
+	)
+	.fetch_all(&mut **database)
+	.await?
+	.into_iter()
+	.map(|row| (row.kind, row.html))
+	.collect();
+
+	AppResponse::builder()
+		.body(GetWorkspaceDeploymentErrorPagesResponse { error_pages })
+		.headers(())
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
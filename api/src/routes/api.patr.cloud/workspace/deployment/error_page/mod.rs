@@ -0,0 +1,159 @@
+use axum::Router;
+use models::api::workspace::deployment::error_page::*;
+use sha2::{Digest, Sha256};
+
+use super::ensure_deployment_exists;
+use crate::prelude::*;
+
+mod get_deployment_error_pages;
+mod get_ingress_error_pages;
+mod get_workspace_deployment_error_pages;
+mod set_deployment_error_pages;
+mod set_workspace_deployment_error_pages;
+
+use self::{
+	get_deployment_error_pages::*,
+	get_ingress_error_pages::*,
+	get_workspace_deployment_error_pages::*,
+	set_deployment_error_pages::*,
+	set_workspace_deployment_error_pages::*,
+};
+
+#[instrument(skip(state))]
+pub async fn setup_routes(state: &AppState) -> Router {
+	Router::new()
+		.mount_auth_endpoint(get_deployment_error_pages, state)
+		.mount_endpoint(get_ingress_error_pages, state)
+		.mount_auth_endpoint(get_workspace_deployment_error_pages, state)
+		.mount_auth_endpoint(set_deployment_error_pages, state)
+		.mount_auth_endpoint(set_workspace_deployment_error_pages, state)
+}
+
+/// Validates each of the given error pages, so that none of them are stored if
+/// any of them is invalid
+fn validate_error_pages(error_pages: &DeploymentErrorPages) -> Result<(), ErrorType> {
+	error_pages.iter().try_for_each(|(kind, html)| {
+		validate_deployment_error_page(html).inspect_err(|_| {
+			debug!("Invalid `{kind:?}` error page of {} bytes", html.len());
+		})
+	})
+}
+
+/// Gets the error pages that the ingress should serve for a deployment. The
+/// error pages of the deployment take precedence over the default error pages
+/// of its workspace.
+pub(super) async fn get_effective_error_pages(
+	connection: &mut DatabaseConnection,
+	deployment_id: Uuid,
+) -> Result<DeploymentErrorPages, ErrorType> {
+	let error_pages = query!(
+		r#"
+		SELECT DISTINCT ON (kind)
+			kind AS "kind!: DeploymentErrorPageKind",
+			html AS "html!"
+		FROM
+			(
+				SELECT
+					deployment_error_page.kind,
+					deployment_error_page.html,
+					0 AS precedence
+				FROM
+					deployment_error_page
+				WHERE
+					deployment_error_page.deployment_id = $1
+				UNION ALL
+				SELECT
+					workspace_deployment_error_page.kind,
+					workspace_deployment_error_page.html,
+					1 AS precedence
+				FROM
+					workspace_deployment_error_page
+				INNER JOIN
+					deployment
+				ON
+					deployment.workspace_id = workspace_deployment_error_page.workspace_id
+				WHERE
+					deployment.id = $1
+			) AS error_page
+		ORDER BY
+			kind,
+			precedence;
+		"#,
+		deployment_id as _,
+	)
+	.fetch_all(&mut *connection)
+	.await?
+	.into_iter()
+	.map(|row| (row.kind, row.html))
+	.collect();
+
+	Ok(error_pages)
+}
+
+/// Gets the version of the given error pages, which the ingress caches the
+/// pages by. The version changes whenever any of the pages do, and is `None`
+/// if there aren't any pages.
+pub(super) fn error_pages_version(error_pages: &DeploymentErrorPages) -> Option<String> {
+	if error_pages.is_empty() {
+		return None;
+	}
+
+	let hash = error_pages
+		.iter()
+		.fold(Sha256::new(), |hash, (kind, html)| {
+			hash.chain_update(format!("{kind:?}:{}:", html.len()))
+				.chain_update(html)
+		})
+		.finalize();
+	Some(hash.iter().map(|byte| format!("{byte:02x}")).collect())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn error_pages_are_only_stored_if_all_of_them_are_valid() {
+		let valid = "<!DOCTYPE html><html><body>Down</body></html>".to_string();
+
+		assert_eq!(
+			validate_error_pages(&DeploymentErrorPages::from([
+				(DeploymentErrorPageKind::BadGateway, valid.clone()),
+				(DeploymentErrorPageKind::WarmingUp, valid.clone()),
+			])),
+			Ok(())
+		);
+		assert_eq!(
+			validate_error_pages(&DeploymentErrorPages::from([
+				(DeploymentErrorPageKind::BadGateway, valid),
+				(DeploymentErrorPageKind::WarmingUp, "Down".to_string()),
+			])),
+			Err(ErrorType::InvalidErrorPage)
+		);
+	}
+
+	#[test]
+	fn error_pages_version_changes_with_the_pages() {
+		let page = |html: &str| format!("<html><body>{html}</body></html>");
+		let pages = DeploymentErrorPages::from([
+			(DeploymentErrorPageKind::BadGateway, page("Bad gateway")),
+			(DeploymentErrorPageKind::WarmingUp, page("Starting")),
+		]);
+		let version = error_pages_version(&pages);
+
+		assert_eq!(error_pages_version(&DeploymentErrorPages::new()), None);
+		assert!(version.is_some());
+		assert_eq!(error_pages_version(&pages.clone()), version);
+
+		let mut edited = pages.clone();
+		edited.insert(DeploymentErrorPageKind::WarmingUp, page("Starting up"));
+		assert_ne!(error_pages_version(&edited), version);
+
+		// The same page served in a different situation is a different version
+		let moved = DeploymentErrorPages::from([
+			(DeploymentErrorPageKind::GatewayTimeout, page("Bad gateway")),
+			(DeploymentErrorPageKind::WarmingUp, page("Starting")),
+		]);
+		assert_ne!(error_pages_version(&moved), version);
+	}
+}
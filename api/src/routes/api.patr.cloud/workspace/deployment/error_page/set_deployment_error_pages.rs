@@ -0,0 +1,76 @@
+use axum::http::StatusCode;
+use models::api::workspace::deployment::error_page::*;
+
+use crate::prelude::*;
+
+/// The handler to set the custom error pages of a deployment, replacing the
+/// existing ones. The ingress picks up the new error pages once its cached
+/// activity report of the deployment expires.
+pub async fn set_deployment_error_pages(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: SetDeploymentErrorPagesPath {
+					workspace_id,
+					deployment_id,
+				},
+				query: (),
+				headers:
+					SetDeploymentErrorPagesRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: SetDeploymentErrorPagesRequestProcessed { error_pages },
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config: _,
+		user_data: _,
+		clock: _,
+	}: AuthenticatedAppRequest<'_, SetDeploymentErrorPagesRequest>,
+) -> Result<AppResponse<SetDeploymentErrorPagesRequest>, ErrorType> {
+	info!("Setting the error pages of deployment `{deployment_id}`");
+
+	super::validate_error_pages(&error_pages)?;
+	super::ensure_deployment_exists(&mut **database, workspace_id, deployment_id).await?;
+
+	query!(
+		r#"
+		DELETE FROM
+			deployment_error_page
+		WHERE
+			deployment_id = $1;
+		"#,
+		deployment_id as _,
+	)
+	.execute(&mut **database)
+	.await?;
+
+	for (kind, html) in error_pages {
+		query!(
+			r#"
+			INSERT INTO
+				deployment_error_page(
+					deployment_id,
+					kind,
+					html
+				)
+			VALUES
+				($1, $2, $3);
+			"#,
+			deployment_id as _,
+			kind as _,
+			html,
+		)
+		.execute(&mut **database)
+		.await?;
+	}
+
+	AppResponse::builder()
+		.body(SetDeploymentErrorPagesResponse)
+		.headers(())
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
@@ -0,0 +1,72 @@
+use axum::http::StatusCode;
+use models::api::workspace::deployment::error_page::*;
+
+use crate::prelude::*;
+
+/// The handler to set the default custom error pages of the deployments in a
+/// workspace, replacing the existing ones. These are served for deployments
+/// that don't have an error page of their own for the situation.
+pub async fn set_workspace_deployment_error_pages(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: SetWorkspaceDeploymentErrorPagesPath { workspace_id },
+				query: (),
+				headers:
+					SetWorkspaceDeploymentErrorPagesRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: SetWorkspaceDeploymentErrorPagesRequestProcessed { error_pages },
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config: _,
+		user_data: _,
+		clock: _,
+	}: AuthenticatedAppRequest<'_, SetWorkspaceDeploymentErrorPagesRequest>,
+) -> Result<AppResponse<SetWorkspaceDeploymentErrorPagesRequest>, ErrorType> {
+	info!("Setting the default deployment error pages of workspace `{workspace_id}`");
+
+	super::validate_error_pages(&error_pages)?;
+
+	query!(
+		r#"
+		DELETE FROM
+			workspace_deployment_error_page
+		WHERE
+			workspace_id = $1;
+		"#,
+		workspace_id as _,
+	)
+	.execute(&mut **database)
+	.await?;
+
+	for (kind, html) in error_pages {
+		query!(
+			r#"
+			INSERT INTO
+				workspace_deployment_error_page(
+					workspace_id,
+					kind,
+					html
+				)
+			VALUES
+				($1, $2, $3);
+			"#,
+			workspace_id as _,
+			kind as _,
+			html,
+		)
+		.execute(&mut **database)
+		.await?;
+	}
+
+	AppResponse::builder()
+		.body(SetWorkspaceDeploymentErrorPagesResponse)
+		.headers(())
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
@@ -0,0 +1,40 @@
+use axum::http::StatusCode;
+use models::api::workspace::deployment::*;
+
+use crate::prelude::*;
+
+/// The handler to get the default machine type of a workspace. This is the
+/// machine type used for new deployments that don't specify one.
+pub async fn get_default_machine_type(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: GetDefaultDeploymentMachineTypePath { workspace_id },
+				query: (),
+				headers:
+					GetDefaultDeploymentMachineTypeRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: GetDefaultDeploymentMachineTypeRequestProcessed,
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config: _,
+		user_data: _,
+		clock: _,
+	}: AuthenticatedAppRequest<'_, GetDefaultDeploymentMachineTypeRequest>,
+) -> Result<AppResponse<GetDefaultDeploymentMachineTypeRequest>, ErrorType> {
+	info!("Getting the default machine type of workspace `{workspace_id}`");
+
+	let machine_type_id =
+		super::get_workspace_default_machine_type(&mut **database, workspace_id).await?;
+
+	AppResponse::builder()
+		.body(GetDefaultDeploymentMachineTypeResponse { machine_type_id })
+		.headers(())
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
@@ -0,0 +1,74 @@
+use axum::http::StatusCode;
+use models::api::workspace::deployment::*;
+use time::{Duration, OffsetDateTime};
+
+use super::get_deployment_logs::{DeploymentLogStream, LogDirection, LokiLogQuery};
+use crate::{prelude::*, utils::http_client};
+
+/// Route to get the access logs of a deployment. These are read from Loki the
+/// same way as the logs of the deployment, but from its access log stream.
+/// Access logs are only retained for a short while, so anything older than
+/// the configured retention is never queried for.
+pub async fn get_deployment_access_logs(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: GetDeploymentAccessLogsPath {
+					workspace_id,
+					deployment_id,
+				},
+				query: GetDeploymentAccessLogsQuery {
+					end_time,
+					limit,
+					search,
+				},
+				headers:
+					GetDeploymentAccessLogsRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: GetDeploymentAccessLogsRequestProcessed,
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config,
+		user_data: _,
+		clock: _,
+	}: AuthenticatedAppRequest<'_, GetDeploymentAccessLogsRequest>,
+) -> Result<AppResponse<GetDeploymentAccessLogsRequest>, ErrorType> {
+	info!("Getting access logs for deployment: {}", deployment_id);
+
+	super::ensure_deployment_exists(&mut **database, workspace_id, deployment_id).await?;
+
+	let now = OffsetDateTime::now_utc();
+	let retention = Duration::hours(i64::from(
+		config.opentelemetry.logs.access_log_retention_hours,
+	));
+
+	let logs = LokiLogQuery {
+		workspace_id,
+		deployment_id,
+		stream: DeploymentLogStream::Access,
+		replica_id: None,
+		start: Some(now - retention),
+		end: end_time.unwrap_or(now),
+		limit: limit.unwrap_or(100),
+		search: search.as_deref(),
+		parse_json: false,
+		level: None,
+		direction: LogDirection::Backward,
+	}
+	.fetch(http_client(), &config.opentelemetry.logs.endpoint)
+	.await?
+	.into_iter()
+	.filter_map(|log| serde_json::from_str::<DeploymentAccessLog>(&log.log).ok())
+	.collect();
+
+	AppResponse::builder()
+		.body(GetDeploymentAccessLogsResponse { logs })
+		.headers(())
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
@@ -1,19 +1,28 @@
+use std::collections::BTreeMap;
+
 use axum::http::StatusCode;
-use models::{api::workspace::deployment::*, utils::StringifiedU16};
+use models::{
+	api::workspace::deployment::{build::*, *},
+	utils::{constants::MASKED_ENVIRONMENT_VARIABLE_VALUE, StringifiedU16},
+};
 
 use crate::prelude::*;
 
 /// The handler to get the deployment info in the workspace. This will return
-/// the deployment details for the given deployment ID.
+/// the deployment details for the given deployment ID. The values of secret
+/// environment variables are masked, unless they are explicitly revealed by a
+/// user that can edit the deployment.
 pub async fn get_deployment_info(
 	AuthenticatedAppRequest {
 		request:
 			ProcessedApiRequest {
 				path: GetDeploymentInfoPath {
-					workspace_id: _,
+					workspace_id,
 					deployment_id,
 				},
-				query: (),
+				query: GetDeploymentInfoQuery {
+					reveal_secret_values,
+				},
 				headers:
 					GetDeploymentInfoRequestHeaders {
 						authorization: _,
@@ -25,11 +34,41 @@ pub async fn get_deployment_info(
 		redis: _,
 		client_ip: _,
 		config: _,
-		user_data: _,
+		user_data,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, GetDeploymentInfoRequest>,
 ) -> Result<AppResponse<GetDeploymentInfoRequest>, ErrorType> {
 	info!("Getting deployment info");
 
+	if reveal_secret_values {
+		let edit_permission_id = query!(
+			r#"
+			SELECT
+				id
+			FROM
+				permission
+			WHERE
+				name = $1;
+			"#,
+			Permission::Deployment(DeploymentPermission::Edit).to_string(),
+		)
+		.fetch_optional(&mut **database)
+		.await?
+		.ok_or(ErrorType::Unauthorized)?
+		.id;
+
+		let can_edit = user_data
+			.permissions
+			.get(&workspace_id)
+			.is_some_and(|permission| {
+				permission.has_permission_on_resource(edit_permission_id.into(), deployment_id)
+			});
+		if !can_edit {
+			info!("User cannot edit the deployment, so secret values cannot be revealed");
+			return Err(ErrorType::Unauthorized);
+		}
+	}
+
 	let ports = query!(
 		r#"
 		SELECT
@@ -53,7 +92,9 @@ pub async fn get_deployment_info(
 		SELECT
 			name,
 			value,
-			secret_id
+			secret_id,
+			environment_specific,
+			is_secret
 		FROM
 			deployment_environment_variable
 		WHERE
@@ -62,19 +103,37 @@ pub async fn get_deployment_info(
 		deployment_id as _
 	)
 	.fetch_all(&mut **database)
-	.await?
-	.into_iter()
-	.filter_map(|env| match (env.value, env.secret_id) {
-		(Some(value), None) => Some((env.name, EnvironmentVariableValue::String(value))),
-		(None, Some(secret_id)) => Some((
-			env.name,
-			EnvironmentVariableValue::Secret {
-				from_secret: secret_id.into(),
-			},
-		)),
-		_ => None,
-	})
-	.collect();
+	.await?;
+
+	let environment_specific_variables = environment_variables
+		.iter()
+		.filter(|env| env.environment_specific)
+		.map(|env| env.name.clone())
+		.collect();
+
+	let secret_variables = environment_variables
+		.iter()
+		.filter(|env| env.is_secret)
+		.map(|env| env.name.clone())
+		.collect();
+
+	let environment_variables = environment_variables
+		.into_iter()
+		.filter_map(|env| match (env.value, env.secret_id) {
+			(Some(_), None) if env.is_secret && !reveal_secret_values => Some((
+				env.name,
+				EnvironmentVariableValue::String(MASKED_ENVIRONMENT_VARIABLE_VALUE.to_string()),
+			)),
+			(Some(value), None) => Some((env.name, EnvironmentVariableValue::String(value))),
+			(None, Some(secret_id)) => Some((
+				env.name,
+				EnvironmentVariableValue::Secret {
+					from_secret: secret_id.into(),
+				},
+			)),
+			_ => None,
+		})
+		.collect();
 
 	let config_mounts = query!(
 		r#"
@@ -94,6 +153,42 @@ pub async fn get_deployment_info(
 	.map(|mount| (mount.path, mount.file.into()))
 	.collect();
 
+	let labels = super::get_deployment_labels(&mut **database, &[deployment_id])
+		.await?
+		.remove(&deployment_id)
+		.unwrap_or_default();
+
+	let workspace_dependency_graph =
+		super::get_workspace_dependency_graph(&mut **database, workspace_id).await?;
+	let depends_on = workspace_dependency_graph
+		.get(&deployment_id)
+		.cloned()
+		.unwrap_or_default();
+	let dependents = workspace_dependency_graph
+		.iter()
+		.filter(|(_, dependencies)| dependencies.contains(&deployment_id))
+		.map(|(id, _)| *id)
+		.collect();
+
+	// Only the part of the graph that the deployment depends on is returned,
+	// including the deployments it depends on indirectly
+	let mut dependency_graph = BTreeMap::new();
+	if !depends_on.is_empty() {
+		let mut pending = vec![deployment_id];
+		while let Some(id) = pending.pop() {
+			if dependency_graph.contains_key(&id) {
+				continue;
+			}
+
+			let dependencies = workspace_dependency_graph
+				.get(&id)
+				.cloned()
+				.unwrap_or_default();
+			pending.extend(dependencies.iter().copied());
+			dependency_graph.insert(id, dependencies);
+		}
+	}
+
 	let volumes = query!(
 		r#"
 		SELECT
@@ -112,10 +207,86 @@ pub async fn get_deployment_info(
 	.map(|row| (row.volume_id.into(), row.volume_mount_path))
 	.collect();
 
-	let deployment = query!(
+	let latest_build = query!(
 		r#"
 		SELECT
 			id,
+			git_url,
+			branch,
+			dockerfile_path,
+			image_tag,
+			status as "status: DeploymentBuildStatus",
+			image_digest,
+			error,
+			created,
+			finished
+		FROM
+			deployment_build
+		WHERE
+			deployment_id = $1
+		ORDER BY
+			created DESC
+		LIMIT 1;
+		"#,
+		deployment_id as _,
+	)
+	.fetch_optional(&mut **database)
+	.await?
+	.map(|row| {
+		WithId::new(
+			row.id,
+			DeploymentBuild {
+				source: DeploymentBuildSource {
+					git_url: row.git_url,
+					branch: row.branch,
+					dockerfile_path: row.dockerfile_path,
+				},
+				image_tag: row.image_tag,
+				status: row.status,
+				image_digest: row.image_digest,
+				error: row.error,
+				created: row.created,
+				finished: row.finished,
+			},
+		)
+	});
+
+	// The requests are only counted while the deployment has a canary
+	let canary_requests = query!(
+		r#"
+		SELECT
+			primary_requests,
+			primary_server_errors,
+			primary_latency_millis,
+			canary_requests,
+			canary_server_errors,
+			canary_latency_millis
+		FROM
+			deployment_canary_requests
+		WHERE
+			deployment_id = $1;
+		"#,
+		deployment_id as _,
+	)
+	.fetch_optional(&mut **database)
+	.await?
+	.map(|row| DeploymentCanaryRequests {
+		primary: DeploymentVersionRequests {
+			requests: row.primary_requests as u64,
+			server_errors: row.primary_server_errors as u64,
+			total_latency_millis: row.primary_latency_millis as u64,
+		},
+		canary: DeploymentVersionRequests {
+			requests: row.canary_requests as u64,
+			server_errors: row.canary_server_errors as u64,
+			total_latency_millis: row.canary_latency_millis as u64,
+		},
+	});
+
+	let deployment = query!(
+		r#"
+		SELECT
+			deployment.id,
 			name,
 			registry,
 			repository_id,
@@ -127,67 +298,139 @@ pub async fn get_deployment_info(
 			min_horizontal_scale,
 			max_horizontal_scale,
 			machine_type,
+			cpu_request,
+			cpu_limit,
+			memory_request,
+			memory_limit,
+			scale_to_zero_after,
+			log_level as "log_level: DeploymentLogLevel",
+			max_concurrent_requests,
+			access_logging,
 			deploy_on_push,
 			startup_probe_port,
 			startup_probe_path,
 			liveness_probe_port,
 			liveness_probe_path,
-			current_live_digest
+			current_live_digest,
+			canary_image_tag,
+			canary_weight,
+			pull_secret_id,
+			ready_replicas,
+			total_replicas,
+			build_git_url,
+			build_branch,
+			build_dockerfile_path,
+			reconciliation_status as "reconciliation_status: DeploymentReconciliationState",
+			reconciliation_error,
+			last_reconciliation_attempt,
+			reconciliation_generation,
+			resource.created,
+			deployment.updated
 		FROM
 			deployment
+		INNER JOIN
+			resource
+		ON
+			deployment.id = resource.id
 		WHERE
-			id = $1 AND
-			deleted IS NULL;
+			deployment.id = $1 AND
+			deployment.deleted IS NULL;
 		"#,
 		deployment_id as _
 	)
 	.fetch_optional(&mut **database)
 	.await?
-	.map(|row| GetDeploymentInfoResponse {
-		deployment: WithId::new(
-			row.id,
-			Deployment {
-				name: row.name,
-				registry: if row.registry == PatrRegistry.to_string() {
-					DeploymentRegistry::PatrRegistry {
-						registry: PatrRegistry,
-						repository_id: row.repository_id.unwrap().into(),
-					}
-				} else {
-					DeploymentRegistry::ExternalRegistry {
-						registry: row.registry,
-						image_name: row.image_name.unwrap(),
-					}
-				},
-				image_tag: row.image_tag,
-				status: row.status,
-				runner: row.runner.into(),
-				machine_type: row.machine_type.into(),
-				current_live_digest: row.current_live_digest,
-			},
-		),
-		running_details: DeploymentRunningDetails {
-			deploy_on_push: row.deploy_on_push,
-			min_horizontal_scale: row.min_horizontal_scale as u16,
-			max_horizontal_scale: row.max_horizontal_scale as u16,
-			ports,
-			environment_variables,
-			startup_probe: row.startup_probe_port.zip(row.startup_probe_path).map(
-				|(port, path)| DeploymentProbe {
-					port: port as u16,
-					path,
+	.map(|row| {
+		let replicas = row
+			.ready_replicas
+			.zip(row.total_replicas)
+			.map(|(ready, total)| DeploymentReplicaReadiness {
+				ready: ready as u16,
+				total: total as u16,
+			});
+		Ok::<_, ErrorType>(GetDeploymentInfoResponse {
+			deployment: WithId::new(
+				row.id,
+				Deployment {
+					name: row.name,
+					registry: super::deployment_registry(
+						row.registry,
+						row.repository_id.map(Into::into),
+						row.image_name,
+					)?,
+					image_tag: row.image_tag,
+					status: row.status.with_replica_readiness(replicas),
+					runner: row.runner.into(),
+					machine_type: row.machine_type.into(),
+					current_live_digest: row.current_live_digest,
+					pull_secret_id: row.pull_secret_id.map(Into::into),
+					created_at: row.created,
+					updated_at: row.updated,
+					labels,
+					depends_on,
+					canary: row.canary_image_tag.zip(row.canary_weight).map(
+						|(image_tag, weight)| DeploymentCanary {
+							image_tag,
+							weight: weight as u8,
+						},
+					),
 				},
 			),
-			liveness_probe: row.liveness_probe_port.zip(row.liveness_probe_path).map(
-				|(port, path)| DeploymentProbe {
-					port: port as u16,
-					path,
+			running_details: DeploymentRunningDetails {
+				deploy_on_push: row.deploy_on_push,
+				min_horizontal_scale: row.min_horizontal_scale as u16,
+				max_horizontal_scale: row.max_horizontal_scale as u16,
+				ports,
+				environment_variables,
+				startup_probe: row.startup_probe_port.zip(row.startup_probe_path).map(
+					|(port, path)| DeploymentProbe {
+						port: port as u16,
+						path,
+					},
+				),
+				liveness_probe: row.liveness_probe_port.zip(row.liveness_probe_path).map(
+					|(port, path)| DeploymentProbe {
+						port: port as u16,
+						path,
+					},
+				),
+				config_mounts,
+				volumes,
+				resources: DeploymentResources {
+					cpu_request: row.cpu_request.map(|value| value as u32),
+					cpu_limit: row.cpu_limit.map(|value| value as u32),
+					memory_request: row.memory_request.map(|value| value as u32),
+					memory_limit: row.memory_limit.map(|value| value as u32),
 				},
-			),
-			config_mounts,
-			volumes,
-		},
+				scale_to_zero_after: row.scale_to_zero_after.map(|value| value as u32),
+				log_level: row.log_level,
+				max_concurrent_requests: row.max_concurrent_requests.map(|value| value as u32),
+				access_logging: row.access_logging,
+			},
+			environment_specific_variables,
+			secret_variables,
+			build_source: row
+				.build_git_url
+				.zip(row.build_branch)
+				.map(|(git_url, branch)| DeploymentBuildSource {
+					git_url,
+					branch,
+					dockerfile_path: row.build_dockerfile_path,
+				}),
+			latest_build,
+			reconciliation_status: DeploymentReconciliationStatus {
+				state: row.reconciliation_status,
+				reason: row.reconciliation_error,
+				last_attempt: row.last_reconciliation_attempt,
+				generation: row.reconciliation_generation as u64,
+			},
+			dependents,
+			dependency_graph,
+			replicas,
+			canary_requests,
+		})
 	})
+	.transpose()?
 	.ok_or(ErrorType::ResourceDoesNotExist)?;
 
 	AppResponse::builder()
@@ -23,9 +23,298 @@ struct LokiMatrixResult {
 	values: Vec<(i128, String)>,
 }
 
+/// The label that the logs of each replica of a deployment are pushed to Loki
+/// with, which is set to the name of the pod (or container) of the replica
+pub(super) const REPLICA_LABEL: &str = "pod";
+
+/// The fields that the level of a log that is a JSON object is read from, in
+/// the same order as [`ParsedDeploymentLog::parse`] reads them, along with the
+/// labels that they are extracted to in LogQL
+const LOG_LEVEL_FIELDS: [(&str, &str); 3] = [
+	("log_level", "level"),
+	("log_lvl", "lvl"),
+	("log_severity", "severity"),
+];
+
+/// The order in which logs are read from Loki
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum LogDirection {
+	/// Oldest logs first
+	Forward,
+	/// Newest logs first
+	Backward,
+}
+
+/// The streams of logs that a deployment has in Loki. The access logs reported
+/// by the ingress are pushed with the `logStream="access"` label, so that they
+/// are kept apart from the logs of the containers of the deployment, and can
+/// be retained for less time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum DeploymentLogStream {
+	/// The logs of the containers of the deployment
+	Container,
+	/// The requests made to the deployment through a managed URL
+	Access,
+}
+
+impl DeploymentLogStream {
+	/// The value of the `logStream` label of the access logs of a deployment
+	pub const ACCESS_LOG_STREAM: &'static str = "access";
+
+	/// The Loki stream selector for the logs of a deployment in this stream,
+	/// optionally restricted to a single replica of the deployment. Container
+	/// logs don't have the `logStream` label, which Loki matches as an empty
+	/// value
+	pub fn selector(self, deployment_id: Uuid, replica_id: Option<&str>) -> String {
+		let operator = match self {
+			Self::Container => "!=",
+			Self::Access => "=",
+		};
+		let replica = replica_id
+			.map(|replica_id| format!(", {}={}", REPLICA_LABEL, logql_string(replica_id)))
+			.unwrap_or_default();
+		format!(
+			r#"{{deploymentId="{}", logStream{}"{}"{}}}"#,
+			deployment_id,
+			operator,
+			Self::ACCESS_LOG_STREAM,
+			replica
+		)
+	}
+}
+
+/// A query for the logs of a deployment in Loki. This is the common read path
+/// for all the endpoints that read the logs of a deployment.
+#[derive(Debug, Clone)]
+pub(super) struct LokiLogQuery<'a> {
+	/// The workspace that the deployment belongs to
+	pub workspace_id: Uuid,
+	/// The deployment to get the logs of
+	pub deployment_id: Uuid,
+	/// The stream of logs of the deployment to get
+	pub stream: DeploymentLogStream,
+	/// The replica of the deployment to get the logs of. If not set, the logs
+	/// of all the replicas are merged together
+	pub replica_id: Option<&'a str>,
+	/// The time (inclusive) from which logs should be fetched. If not set, Loki
+	/// decides the start of the range
+	pub start: Option<OffsetDateTime>,
+	/// The time (exclusive) up until which logs should be fetched
+	pub end: OffsetDateTime,
+	/// The maximum number of logs to fetch
+	pub limit: u32,
+	/// The search query to filter logs by
+	pub search: Option<&'a str>,
+	/// Whether logs that are JSON objects should be parsed. If set, the search
+	/// query and the level filter are also applied to the fields of parsed
+	/// logs
+	pub parse_json: bool,
+	/// The level to filter logs by. The level is read from the fields of logs
+	/// that are JSON objects, so logs that aren't are left out
+	pub level: Option<&'a str>,
+	/// The order in which the logs should be fetched
+	pub direction: LogDirection,
+}
+
+impl LokiLogQuery<'_> {
+	/// The LogQL query of the logs. The search query and the level are
+	/// filtered by Loki, so that the limit of the query applies to the logs
+	/// that match them
+	fn logql(&self) -> String {
+		let mut query = self.stream.selector(self.deployment_id, self.replica_id);
+		if let Some(search) = self.search {
+			query.push_str(&format!(" |= {}", logql_string(search)));
+		}
+		if let Some(level) = self.level {
+			let fields = LOG_LEVEL_FIELDS
+				.iter()
+				.map(|(label, field)| format!("{label}={}", logql_string(field)))
+				.collect::<Vec<_>>()
+				.join(", ");
+			let level = logql_string(&format!("(?i){}", regex::escape(level)));
+			let matchers = LOG_LEVEL_FIELDS
+				.iter()
+				.map(|(label, _)| format!("{label}=~{level}"))
+				.collect::<Vec<_>>()
+				.join(" or ");
+			query.push_str(&format!(" | json {fields} | {matchers}"));
+		}
+		query
+	}
+
+	/// Fetches the logs from Loki, sorted in the direction of the query
+	pub async fn fetch(
+		&self,
+		client: &reqwest::Client,
+		endpoint: &str,
+	) -> Result<Vec<DeploymentLog>, ErrorType> {
+		let mut params = vec![
+			("limit", self.limit.to_string()),
+			("end", self.end.unix_timestamp_nanos().to_string()),
+			(
+				"direction",
+				match self.direction {
+					LogDirection::Forward => "forward",
+					LogDirection::Backward => "backward",
+				}
+				.to_string(),
+			),
+			("query", self.logql()),
+		];
+		if let Some(start) = self.start {
+			params.push(("start", start.unix_timestamp_nanos().to_string()));
+		}
+
+		let loki_response = client
+			.get(format!("{}/loki/api/v1/query_range", endpoint))
+			.query(&params)
+			.header(
+				HeaderName::from_static("x-scope-orgid"),
+				HeaderValue::from_str(&self.workspace_id.to_string()).unwrap(),
+			)
+			.send()
+			.await?
+			.text()
+			.await?;
+
+		trace!("{}", &loki_response);
+		let Ok(LokiResponse {
+			data: LokiData { result },
+		}) = serde_json::from_str::<LokiResponse>(&loki_response)
+		else {
+			error!("Cannot parse Loki response: {}", loki_response);
+			return Err(ErrorType::server_error(format!(
+				"Failed to parse Loki response"
+			)));
+		};
+
+		// Each stream of the deployment is sorted on its own, so merge them
+		let mut logs = result
+			.into_iter()
+			.flat_map(|LokiMatrixResult { values }| values)
+			.map(|(timestamp, log)| DeploymentLog {
+				timestamp: OffsetDateTime::from_unix_timestamp_nanos(timestamp)
+					.unwrap_or(OffsetDateTime::UNIX_EPOCH),
+				log,
+				parsed: None,
+			})
+			.collect::<Vec<_>>();
+		if self.parse_json {
+			for log in &mut logs {
+				log.parsed = ParsedDeploymentLog::parse(&log.log);
+			}
+			logs.retain(|log| matches_parsed_filters(log, self.search, self.level));
+		}
+		match self.direction {
+			LogDirection::Forward => logs.sort_by_key(|log| log.timestamp),
+			LogDirection::Backward => logs.sort_by_key(|log| std::cmp::Reverse(log.timestamp)),
+		}
+		logs.truncate(usize::try_from(self.limit).unwrap_or(usize::MAX));
+
+		Ok(logs)
+	}
+}
+
+/// Quotes a string to be used in a LogQL query
+fn logql_string(value: &str) -> String {
+	format!(
+		r#""{}""#,
+		value
+			.replace('\\', r"\\")
+			.replace('"', r#"\""#)
+			.replace('\n', r"\n")
+	)
+}
+
+/// Lists the replicas of a deployment, as they were last reported by its
+/// runner along with their readiness. The replicas are sorted by their ID.
+pub(super) async fn fetch_deployment_replicas(
+	connection: &mut DatabaseConnection,
+	workspace_id: Uuid,
+	deployment_id: Uuid,
+) -> Result<Vec<String>, ErrorType> {
+	let mut replicas = query!(
+		r#"
+		SELECT
+			replica_ids
+		FROM
+			deployment
+		WHERE
+			id = $1 AND
+			workspace_id = $2 AND
+			deleted IS NULL;
+		"#,
+		deployment_id as _,
+		workspace_id as _,
+	)
+	.fetch_optional(&mut *connection)
+	.await?
+	.or_not_found()?
+	.replica_ids;
+	replicas.sort();
+
+	Ok(replicas)
+}
+
+/// Checks that the replica belongs to the deployment, failing with
+/// [`ErrorType::ReplicaNotFound`] if it doesn't
+pub(super) async fn ensure_replica_exists(
+	connection: &mut DatabaseConnection,
+	workspace_id: Uuid,
+	deployment_id: Uuid,
+	replica_id: &str,
+) -> Result<(), ErrorType> {
+	let replicas = fetch_deployment_replicas(connection, workspace_id, deployment_id).await?;
+
+	if replicas.iter().any(|replica| replica == replica_id) {
+		Ok(())
+	} else {
+		debug!("Replica `{replica_id}` not found for deployment `{deployment_id}`");
+		Err(ErrorType::ReplicaNotFound)
+	}
+}
+
+/// Checks if a log matches the search query and the level filter, after it has
+/// been parsed. Loki only searches the raw log line, so for parsed logs the
+/// search query is matched against the values of their fields instead, so that
+/// matching the JSON keys or syntax doesn't count. Logs that couldn't be parsed
+/// are kept as they are, unless a level is being filtered by, since their level
+/// is unknown.
+pub(super) fn matches_parsed_filters(
+	log: &DeploymentLog,
+	search: Option<&str>,
+	level: Option<&str>,
+) -> bool {
+	let Some(parsed) = &log.parsed else {
+		return level.is_none();
+	};
+
+	let level_matches = level.map_or(true, |level| {
+		parsed
+			.level
+			.as_deref()
+			.is_some_and(|parsed_level| parsed_level.eq_ignore_ascii_case(level))
+	});
+	let search_matches = search.map_or(true, |search| {
+		[&parsed.level, &parsed.timestamp, &parsed.message]
+			.into_iter()
+			.flatten()
+			.any(|value| value.contains(search)) ||
+			parsed.fields.values().any(|value| match value {
+				serde_json::Value::String(value) => value.contains(search),
+				value => value.to_string().contains(search),
+			})
+	});
+
+	level_matches && search_matches
+}
+
 /// Route to get the logs of a deployment. This will fetch logs from Loki
 /// and return them to the user. The logs can be filtered by time and search
-/// query.
+/// query. Logs that are JSON objects can optionally be parsed, in which case
+/// they can also be filtered by their level. If a replica is given, only the
+/// logs of that replica are returned, instead of the merged logs of all the
+/// replicas of the deployment.
 pub async fn get_deployment_logs(
 	AuthenticatedAppRequest {
 		request:
@@ -34,11 +323,15 @@ pub async fn get_deployment_logs(
 					workspace_id,
 					deployment_id,
 				},
-				query: GetDeploymentLogsQuery {
-					end_time,
-					limit,
-					search,
-				},
+				query:
+					GetDeploymentLogsQuery {
+						end_time,
+						limit,
+						search,
+						parse_json,
+						level,
+						replica_id,
+					},
 				headers:
 					GetDeploymentLogsRequestHeaders {
 						authorization: _,
@@ -51,6 +344,7 @@ pub async fn get_deployment_logs(
 		client_ip: _,
 		config,
 		user_data: _,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, GetDeploymentLogsRequest>,
 ) -> Result<AppResponse<GetDeploymentLogsRequest>, ErrorType> {
 	info!("Getting logs for deployment: {}", deployment_id);
@@ -69,67 +363,29 @@ pub async fn get_deployment_logs(
 	)
 	.fetch_optional(&mut **database)
 	.await?
-	.ok_or(ErrorType::ResourceDoesNotExist)?;
-
-	let loki_response = reqwest::Client::new()
-		.get(format!(
-			"{}/loki/api/v1/query_range",
-			config.opentelemetry.logs.endpoint
-		))
-		.query(&[
-			("limit", limit.unwrap_or(100).to_string()),
-			(
-				"end",
-				end_time
-					.unwrap_or(OffsetDateTime::now_utc())
-					.unix_timestamp_nanos()
-					.to_string(),
-			),
-			(
-				"query",
-				format!(
-					"{{deploymentId=\"{}\"}}{}",
-					deployment_id,
-					search
-						.map(|search| format!(" |= `{}`", search))
-						.unwrap_or_default()
-				),
-			),
-		])
-		.header(
-			HeaderName::from_static("x-scope-orgid"),
-			HeaderValue::from_str(&workspace_id.to_string()).unwrap(),
-		)
-		.send()
-		.await?
-		.text()
-		.await?;
-
-	trace!("{}", &loki_response);
-	let Ok(LokiResponse {
-		data: LokiData { result },
-	}) = serde_json::from_str::<LokiResponse>(&loki_response)
-	else {
-		error!("Cannot parse Loki response: {}", loki_response);
-		return Err(ErrorType::server_error(format!(
-			"Failed to parse Loki response"
-		)));
-	};
+	.or_not_found()?;
+
+	if let Some(replica_id) = &replica_id {
+		ensure_replica_exists(&mut **database, workspace_id, deployment_id, replica_id).await?;
+	}
+
+	let client = reqwest::Client::new();
 
-	let logs = result
-		.into_iter()
-		.next()
-		.map(|LokiMatrixResult { values }| {
-			values
-				.into_iter()
-				.map(|(timestamp, log)| DeploymentLog {
-					timestamp: OffsetDateTime::from_unix_timestamp_nanos(timestamp)
-						.unwrap_or(OffsetDateTime::UNIX_EPOCH),
-					log,
-				})
-				.collect()
-		})
-		.unwrap_or_default();
+	let logs = LokiLogQuery {
+		workspace_id,
+		deployment_id,
+		stream: DeploymentLogStream::Container,
+		replica_id: replica_id.as_deref(),
+		start: None,
+		end: end_time.unwrap_or(OffsetDateTime::now_utc()),
+		limit: limit.unwrap_or(100),
+		search: search.as_deref(),
+		parse_json,
+		level: level.as_deref(),
+		direction: LogDirection::Backward,
+	}
+	.fetch(&client, &config.opentelemetry.logs.endpoint)
+	.await?;
 
 	AppResponse::builder()
 		.body(GetDeploymentLogsResponse { logs })
@@ -138,3 +394,58 @@ pub async fn get_deployment_logs(
 		.build()
 		.into_result()
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// A query for the container logs of a deployment
+	fn query<'a>(search: Option<&'a str>, level: Option<&'a str>) -> LokiLogQuery<'a> {
+		LokiLogQuery {
+			workspace_id: Uuid::nil(),
+			deployment_id: Uuid::nil(),
+			stream: DeploymentLogStream::Container,
+			replica_id: None,
+			start: None,
+			end: OffsetDateTime::UNIX_EPOCH,
+			limit: 100,
+			search,
+			parse_json: false,
+			level,
+			direction: LogDirection::Backward,
+		}
+	}
+
+	#[test]
+	fn search_is_filtered_by_loki() {
+		let selector = DeploymentLogStream::Container.selector(Uuid::nil(), None);
+
+		assert_eq!(query(None, None).logql(), selector);
+		assert_eq!(
+			query(Some(r#"say "hi" `now`"#), None).logql(),
+			format!(r#"{selector} |= "say \"hi\" `now`""#)
+		);
+	}
+
+	#[test]
+	fn level_is_filtered_by_loki_without_parsing_the_logs() {
+		let selector = DeploymentLogStream::Container.selector(Uuid::nil(), None);
+
+		assert_eq!(
+			query(Some("timeout"), Some("error")).logql(),
+			format!(
+				concat!(
+					r#"{} |= "timeout" "#,
+					r#"| json log_level="level", log_lvl="lvl", log_severity="severity" "#,
+					r#"| log_level=~"(?i)error" or log_lvl=~"(?i)error" or "#,
+					r#"log_severity=~"(?i)error""#
+				),
+				selector
+			)
+		);
+		// The level is matched literally
+		assert!(query(None, Some("err.*"))
+			.logql()
+			.ends_with(r#"log_severity=~"(?i)err\\.\\*""#));
+	}
+}
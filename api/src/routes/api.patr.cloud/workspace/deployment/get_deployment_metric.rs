@@ -47,6 +47,7 @@ pub async fn get_deployment_metric(
 		client_ip: _,
 		config,
 		user_data: _,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, GetDeploymentMetricRequest>,
 ) -> Result<AppResponse<GetDeploymentMetricRequest>, ErrorType> {
 	info!(
@@ -0,0 +1,190 @@
+use std::collections::BTreeMap;
+
+use axum::http::StatusCode;
+use models::api::workspace::deployment::*;
+
+use crate::prelude::*;
+
+/// The handler to get the effective config of a deployment. The config stored
+/// on the deployment is filled in with the defaults that are applied when the
+/// deployment is run, and the values that match the template the deployment
+/// was created from are marked as coming from the template. Templates are only
+/// applied when a deployment is created, so a value is only marked as coming
+/// from the template as long as both of them still have the same value.
+pub async fn get_effective_deployment_config(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path:
+					GetEffectiveDeploymentConfigPath {
+						workspace_id,
+						deployment_id,
+					},
+				query: (),
+				headers:
+					GetEffectiveDeploymentConfigRequestHeaders {
+						authorization,
+						user_agent,
+					},
+				body: GetEffectiveDeploymentConfigRequestProcessed,
+			},
+		database,
+		redis,
+		client_ip,
+		config,
+		user_data,
+		clock,
+	}: AuthenticatedAppRequest<'_, GetEffectiveDeploymentConfigRequest>,
+) -> Result<AppResponse<GetEffectiveDeploymentConfigRequest>, ErrorType> {
+	info!("Getting effective config of deployment `{deployment_id}`");
+
+	let GetDeploymentInfoResponse {
+		deployment,
+		mut running_details,
+		environment_specific_variables: _,
+		secret_variables: _,
+		build_source: _,
+		latest_build: _,
+		reconciliation_status: _,
+		dependents: _,
+		dependency_graph: _,
+		replicas: _,
+		canary_requests: _,
+	} = super::get_deployment_info(AuthenticatedAppRequest {
+		request: ProcessedApiRequest::builder()
+			.path(GetDeploymentInfoPath {
+				workspace_id,
+				deployment_id,
+			})
+			.query(GetDeploymentInfoQuery {
+				reveal_secret_values: false,
+			})
+			.headers(GetDeploymentInfoRequestHeaders {
+				authorization,
+				user_agent,
+			})
+			.body(GetDeploymentInfoRequestProcessed)
+			.build(),
+		database,
+		redis,
+		client_ip,
+		config,
+		user_data,
+		clock,
+	})
+	.await?
+	.body;
+
+	let machine_type_id = deployment.data.machine_type;
+	let machine_type = query!(
+		r#"
+		SELECT
+			cpu_count,
+			memory_count
+		FROM
+			deployment_machine_type
+		WHERE
+			id = $1;
+		"#,
+		machine_type_id as _,
+	)
+	.fetch_one(&mut **database)
+	.await
+	.map(|row| DeploymentMachineType {
+		cpu_count: row.cpu_count as u16,
+		memory_count: row.memory_count as u32,
+	})?;
+
+	let mut inherited_values = BTreeMap::new();
+
+	// Unset limits default to the capacity of the machine type. Unset requests
+	// are left to the runner, so they stay unset
+	let resources = &mut running_details.resources;
+	if resources.cpu_limit.is_none() {
+		resources.cpu_limit =
+			Some(u32::from(machine_type.cpu_count) * DeploymentResources::MILLICORES_PER_CPU);
+		inherited_values.insert(
+			"resources.cpuLimit".to_string(),
+			DeploymentConfigValueSource::MachineType,
+		);
+	}
+	if resources.memory_limit.is_none() {
+		resources.memory_limit =
+			Some(machine_type.memory_count * DeploymentResources::MIB_PER_MEMORY_UNIT);
+		inherited_values.insert(
+			"resources.memoryLimit".to_string(),
+			DeploymentConfigValueSource::MachineType,
+		);
+	}
+
+	let template_id = query!(
+		r#"
+		SELECT
+			template_id
+		FROM
+			deployment
+		WHERE
+			id = $1;
+		"#,
+		deployment_id as _,
+	)
+	.fetch_one(&mut **database)
+	.await?
+	.template_id
+	.map(Uuid::from);
+
+	let template = match template_id {
+		Some(template_id) => {
+			super::template::get_deployment_template(&mut **database, workspace_id, template_id)
+				.await?
+		}
+		None => None,
+	};
+
+	if let Some(template) = &template {
+		if template.machine_type == Some(machine_type_id) {
+			inherited_values.insert(
+				"machineType".to_string(),
+				DeploymentConfigValueSource::Template,
+			);
+		}
+
+		for (name, value) in &running_details.environment_variables {
+			if template.environment_variables.get(name) == Some(value) {
+				inherited_values.insert(
+					format!("environmentVariables.{name}"),
+					DeploymentConfigValueSource::Template,
+				);
+			}
+		}
+
+		for (field, probe, template_probe) in [
+			(
+				"startupProbe",
+				&running_details.startup_probe,
+				&template.startup_probe,
+			),
+			(
+				"livenessProbe",
+				&running_details.liveness_probe,
+				&template.liveness_probe,
+			),
+		] {
+			if probe.is_some() && probe == template_probe {
+				inherited_values.insert(field.to_string(), DeploymentConfigValueSource::Template);
+			}
+		}
+	}
+
+	AppResponse::builder()
+		.body(GetEffectiveDeploymentConfigResponse {
+			running_details,
+			machine_type: WithId::new(machine_type_id, machine_type),
+			template_id: template.and(template_id),
+			inherited_values,
+		})
+		.headers(())
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
@@ -0,0 +1,46 @@
+use axum::http::StatusCode;
+use models::api::workspace::deployment::image_scan::*;
+
+use crate::prelude::*;
+
+/// The handler to get the result of the last scan of a deployment's image.
+/// Fails if the image of the deployment was never scanned.
+pub async fn get_deployment_image_scan(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: GetDeploymentImageScanPath {
+					workspace_id,
+					deployment_id,
+				},
+				query: (),
+				headers:
+					GetDeploymentImageScanRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: GetDeploymentImageScanRequestProcessed,
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config: _,
+		user_data: _,
+		clock: _,
+	}: AuthenticatedAppRequest<'_, GetDeploymentImageScanRequest>,
+) -> Result<AppResponse<GetDeploymentImageScanRequest>, ErrorType> {
+	info!("Getting the image scan of deployment `{deployment_id}`");
+
+	super::ensure_deployment_exists(&mut **database, workspace_id, deployment_id).await?;
+
+	let scan = super::get_last_scan(&mut **database, deployment_id)
+		.await?
+		.or_not_found()?;
+
+	AppResponse::builder()
+		.body(GetDeploymentImageScanResponse { scan })
+		.headers(())
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
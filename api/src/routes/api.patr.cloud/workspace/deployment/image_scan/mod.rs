@@ -0,0 +1,107 @@
+use axum::Router;
+use models::api::workspace::deployment::image_scan::*;
+use time::OffsetDateTime;
+
+use super::ensure_deployment_exists;
+use crate::prelude::*;
+
+mod get_deployment_image_scan;
+mod scan_deployment_image;
+
+use self::{get_deployment_image_scan::*, scan_deployment_image::*};
+
+#[instrument(skip(state))]
+pub async fn setup_routes(state: &AppState) -> Router {
+	Router::new()
+		.mount_auth_endpoint(get_deployment_image_scan, state)
+		.mount_auth_endpoint(scan_deployment_image, state)
+}
+
+/// Gets the result of the last scan of a deployment's image, if it was ever
+/// scanned
+async fn get_last_scan(
+	connection: &mut DatabaseConnection,
+	deployment_id: Uuid,
+) -> Result<Option<DeploymentImageScan>, ErrorType> {
+	let Some(scan) = query!(
+		r#"
+		SELECT
+			id,
+			image,
+			image_digest,
+			scanned
+		FROM
+			deployment_image_scan
+		WHERE
+			deployment_id = $1
+		ORDER BY
+			scanned DESC
+		LIMIT 1;
+		"#,
+		deployment_id as _,
+	)
+	.fetch_optional(&mut *connection)
+	.await?
+	else {
+		return Ok(None);
+	};
+
+	get_scan_result(
+		connection,
+		scan.id.into(),
+		scan.image,
+		scan.image_digest,
+		scan.scanned,
+	)
+	.await
+	.map(Some)
+}
+
+/// Gets the vulnerabilities found by a scan, along with the details of the scan
+async fn get_scan_result(
+	connection: &mut DatabaseConnection,
+	scan_id: Uuid,
+	image: String,
+	image_digest: Option<String>,
+	scanned: OffsetDateTime,
+) -> Result<DeploymentImageScan, ErrorType> {
+	let vulnerabilities = query!(
+		r#"
+		SELECT
+			vulnerability_id,
+			package,
+			installed_version,
+			fixed_version,
+			severity as "severity: ImageVulnerabilitySeverity",
+			title
+		FROM
+			deployment_image_vulnerability
+		WHERE
+			scan_id = $1
+		ORDER BY
+			severity DESC,
+			vulnerability_id;
+		"#,
+		scan_id as _,
+	)
+	.fetch_all(&mut *connection)
+	.await?
+	.into_iter()
+	.map(|vulnerability| ImageVulnerability {
+		id: vulnerability.vulnerability_id,
+		package: vulnerability.package,
+		installed_version: vulnerability.installed_version,
+		fixed_version: vulnerability.fixed_version,
+		severity: vulnerability.severity,
+		title: vulnerability.title,
+	})
+	.collect::<Vec<_>>();
+
+	Ok(DeploymentImageScan {
+		image,
+		image_digest,
+		scanned,
+		counts: ImageVulnerabilityCounts::from_vulnerabilities(&vulnerabilities),
+		vulnerabilities,
+	})
+}
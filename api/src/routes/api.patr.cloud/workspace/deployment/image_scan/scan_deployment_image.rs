@@ -0,0 +1,57 @@
+use axum::http::StatusCode;
+use models::api::workspace::deployment::image_scan::*;
+
+use crate::{deployment_image_scanner, prelude::*, utils::config::ImageScannerBackend};
+
+/// The handler to scan the current image of a deployment for vulnerabilities.
+/// The image is only queued to be scanned, since a scan can take minutes, and
+/// the result is stored along with the previous scans of the deployment once
+/// the scan has finished.
+pub async fn scan_deployment_image(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: ScanDeploymentImagePath {
+					workspace_id,
+					deployment_id,
+				},
+				query: (),
+				headers:
+					ScanDeploymentImageRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: ScanDeploymentImageRequestProcessed,
+			},
+		database,
+		redis,
+		client_ip: _,
+		config,
+		user_data: _,
+		clock: _,
+	}: AuthenticatedAppRequest<'_, ScanDeploymentImageRequest>,
+) -> Result<AppResponse<ScanDeploymentImageRequest>, ErrorType> {
+	info!("Scanning the image of deployment `{deployment_id}`");
+
+	super::ensure_deployment_exists(&mut **database, workspace_id, deployment_id).await?;
+
+	if matches!(config.image_scan.scanner, ImageScannerBackend::Disabled) {
+		return Err(ErrorType::ImageScanFailed);
+	}
+
+	deployment_image_scanner::request_deployment_scan(
+		&mut **database,
+		redis,
+		workspace_id,
+		deployment_id,
+		false,
+	)
+	.await?;
+
+	AppResponse::builder()
+		.body(ScanDeploymentImageResponse)
+		.headers(())
+		.status_code(StatusCode::ACCEPTED)
+		.build()
+		.into_result()
+}
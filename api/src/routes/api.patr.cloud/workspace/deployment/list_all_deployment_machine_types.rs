@@ -19,6 +19,7 @@ pub async fn machine_type(
 		redis: _,
 		client_ip: _,
 		config: _,
+		clock: _,
 	}: AppRequest<'_, ListAllDeploymentMachineTypeRequest>,
 ) -> Result<AppResponse<ListAllDeploymentMachineTypeRequest>, ErrorType> {
 	info!("Listing all deployment machine types");
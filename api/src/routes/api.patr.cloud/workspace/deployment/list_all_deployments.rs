@@ -0,0 +1,209 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use axum::http::StatusCode;
+use models::api::{workspace::deployment::*, AggregateWarning};
+use sqlx::Connection;
+
+use crate::prelude::*;
+
+/// The handler to list the deployments across all the workspaces that the user
+/// is a part of. The deployments of each workspace are loaded separately, so
+/// that a workspace that fails to load only adds a warning to the response,
+/// instead of failing the whole request.
+pub async fn list_all_deployments(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: ListAllDeploymentsPath,
+				query: (),
+				headers:
+					ListAllDeploymentsRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: ListAllDeploymentsRequestProcessed,
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config: _,
+		user_data,
+		clock: _,
+	}: AuthenticatedAppRequest<'_, ListAllDeploymentsRequest>,
+) -> Result<AppResponse<ListAllDeploymentsRequest>, ErrorType> {
+	info!("Listing the deployments in all workspaces of the user");
+
+	let workspaces = query!(
+		r#"
+		SELECT DISTINCT
+			workspace.id,
+			workspace.name::TEXT AS "name!"
+		FROM
+			workspace
+		LEFT JOIN
+			workspace_user
+		ON
+			workspace.id = workspace_user.workspace_id
+		WHERE
+			(
+				workspace.super_admin_id = $1 OR
+				workspace_user.user_id = $1
+			) AND
+			workspace.deleted IS NULL
+		ORDER BY
+			workspace.id;
+		"#,
+		user_data.id as _,
+	)
+	.fetch_all(&mut **database)
+	.await?;
+
+	let mut deployments = Vec::new();
+	let mut warnings = Vec::new();
+
+	for workspace in workspaces {
+		let workspace_id = Uuid::from(workspace.id);
+
+		// Each workspace is loaded in its own savepoint, since a failed query
+		// would otherwise abort the transaction for the rest of the workspaces
+		let result = async {
+			let mut savepoint = (&mut **database).begin().await?;
+			let deployments =
+				list_workspace_deployments(&mut *savepoint, workspace_id, user_data.login_id)
+					.await?;
+			savepoint.commit().await?;
+			Ok::<_, ErrorType>(deployments)
+		}
+		.await;
+
+		match result {
+			Ok(workspace_deployments) => {
+				deployments.extend(workspace_deployments.into_iter().map(|deployment| {
+					WorkspaceDeployment {
+						workspace_id,
+						deployment,
+					}
+				}));
+			}
+			Err(error) => {
+				warn!("Unable to list the deployments of workspace `{workspace_id}`: {error:?}");
+				warnings.push(AggregateWarning {
+					source_id: workspace_id,
+					source_name: workspace.name,
+					message: error.message().into(),
+				});
+			}
+		}
+	}
+
+	AppResponse::builder()
+		.body(ListAllDeploymentsResponse {
+			deployments,
+			warnings,
+		})
+		.headers(())
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
+
+/// Lists the deployments in a workspace that the given login can view, with
+/// their labels and dependencies
+async fn list_workspace_deployments(
+	connection: &mut DatabaseConnection,
+	workspace_id: Uuid,
+	login_id: Uuid,
+) -> Result<Vec<WithId<Deployment>>, ErrorType> {
+	let mut deployments = query!(
+		r#"
+		SELECT
+			deployment.id,
+			name,
+			registry,
+			repository_id,
+			image_name,
+			image_tag,
+			status AS "status: DeploymentStatus",
+			runner,
+			machine_type,
+			current_live_digest,
+			canary_image_tag,
+			canary_weight,
+			pull_secret_id,
+			ready_replicas,
+			total_replicas,
+			resource.created,
+			deployment.updated
+		FROM
+			deployment
+		INNER JOIN
+			RESOURCES_WITH_PERMISSION_FOR_LOGIN_ID($2, $3) AS resource
+		ON
+			deployment.id = resource.id
+		WHERE
+			workspace_id = $1 AND
+			deployment.deleted IS NULL
+		ORDER BY
+			resource.created DESC,
+			deployment.id DESC;
+		"#,
+		workspace_id as _,
+		login_id as _,
+		Permission::Deployment(DeploymentPermission::View) as _,
+	)
+	.fetch_all(&mut *connection)
+	.await?
+	.into_iter()
+	.map(|row| {
+		Ok(WithId::new(
+			row.id,
+			Deployment {
+				name: row.name,
+				registry: super::deployment_registry(
+					row.registry,
+					row.repository_id.map(Into::into),
+					row.image_name,
+				)?,
+				image_tag: row.image_tag,
+				status: row.status.with_replica_readiness(
+					row.ready_replicas
+						.zip(row.total_replicas)
+						.map(|(ready, total)| DeploymentReplicaReadiness {
+							ready: ready as u16,
+							total: total as u16,
+						}),
+				),
+				runner: row.runner.into(),
+				machine_type: row.machine_type.into(),
+				current_live_digest: row.current_live_digest,
+				pull_secret_id: row.pull_secret_id.map(Into::into),
+				created_at: row.created,
+				updated_at: row.updated,
+				labels: BTreeMap::new(),
+				depends_on: BTreeSet::new(),
+				canary: row
+					.canary_image_tag
+					.zip(row.canary_weight)
+					.map(|(image_tag, weight)| DeploymentCanary {
+						image_tag,
+						weight: weight as u8,
+					}),
+			},
+		))
+	})
+	.collect::<Result<Vec<_>, ErrorType>>()?;
+
+	let deployment_ids = deployments
+		.iter()
+		.map(|deployment| deployment.id)
+		.collect::<Vec<_>>();
+	let mut labels = super::get_deployment_labels(&mut *connection, &deployment_ids).await?;
+	let mut dependencies =
+		super::get_deployment_dependencies(&mut *connection, &deployment_ids).await?;
+	for deployment in &mut deployments {
+		deployment.data.labels = labels.remove(&deployment.id).unwrap_or_default();
+		deployment.data.depends_on = dependencies.remove(&deployment.id).unwrap_or_default();
+	}
+
+	Ok(deployments)
+}
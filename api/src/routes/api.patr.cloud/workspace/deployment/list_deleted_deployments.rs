@@ -0,0 +1,140 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use axum::http::StatusCode;
+use models::{api::workspace::deployment::*, utils::TotalCountHeader};
+
+use crate::prelude::*;
+
+/// The handler to list the deleted deployments in the workspace that can still
+/// be restored, with the most recently deleted deployments first.
+pub async fn list_deleted_deployments(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: ListDeletedDeploymentsPath { workspace_id },
+				query: Paginated {
+					data: (),
+					count,
+					page,
+				},
+				headers:
+					ListDeletedDeploymentsRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: ListDeletedDeploymentsRequestProcessed,
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config: _,
+		user_data,
+		clock,
+	}: AuthenticatedAppRequest<'_, ListDeletedDeploymentsRequest>,
+) -> Result<AppResponse<ListDeletedDeploymentsRequest>, ErrorType> {
+	info!("Listing deleted deployments in workspace: {}", workspace_id);
+
+	let mut total_count = 0;
+	let mut deployments = query!(
+		r#"
+		SELECT
+			deployment.id,
+			name,
+			registry,
+			repository_id,
+			image_name,
+			image_tag,
+			status AS "status: DeploymentStatus",
+			runner,
+			machine_type,
+			current_live_digest,
+			canary_image_tag,
+			canary_weight,
+			pull_secret_id,
+			deployment.deleted AS "deleted!",
+			resource.created,
+			deployment.updated,
+			COUNT(*) OVER() AS "total_count!"
+		FROM
+			deployment
+		INNER JOIN
+			RESOURCES_WITH_PERMISSION_FOR_LOGIN_ID($2, $3) AS resource
+		ON
+			deployment.id = resource.id
+		WHERE
+			workspace_id = $1 AND
+			deployment.deleted IS NOT NULL AND
+			deployment.deleted > $4
+		ORDER BY
+			deployment.deleted DESC
+		LIMIT $5
+		OFFSET $6;
+		"#,
+		workspace_id as _,
+		user_data.login_id as _,
+		Permission::Deployment(DeploymentPermission::View) as _,
+		crate::deployment_purger::purge_cutoff(clock.now()),
+		count as i32,
+		(count * page) as i32,
+	)
+	.fetch_all(&mut **database)
+	.await?
+	.into_iter()
+	.map(|row| {
+		total_count = row.total_count;
+		Ok(WithId::new(
+			row.id,
+			DeletedDeployment {
+				deployment: Deployment {
+					name: row.name,
+					registry: super::deployment_registry(
+						row.registry,
+						row.repository_id.map(Into::into),
+						row.image_name,
+					)?,
+					image_tag: row.image_tag,
+					status: row.status,
+					runner: row.runner.into(),
+					machine_type: row.machine_type.into(),
+					current_live_digest: row.current_live_digest,
+					pull_secret_id: row.pull_secret_id.map(Into::into),
+					created_at: row.created,
+					updated_at: row.updated,
+					labels: BTreeMap::new(),
+					depends_on: BTreeSet::new(),
+					canary: row.canary_image_tag.zip(row.canary_weight).map(
+						|(image_tag, weight)| DeploymentCanary {
+							image_tag,
+							weight: weight as u8,
+						},
+					),
+				},
+				deleted: row.deleted,
+				purge_after: row.deleted + constants::DEPLOYMENT_RESTORE_GRACE_PERIOD,
+			},
+		))
+	})
+	.collect::<Result<Vec<_>, ErrorType>>()?;
+
+	let deployment_ids = deployments
+		.iter()
+		.map(|deployment| deployment.id)
+		.collect::<Vec<_>>();
+	let mut labels = super::get_deployment_labels(&mut **database, &deployment_ids).await?;
+	let mut dependencies =
+		super::get_deployment_dependencies(&mut **database, &deployment_ids).await?;
+	for deployment in &mut deployments {
+		deployment.data.deployment.labels = labels.remove(&deployment.id).unwrap_or_default();
+		deployment.data.deployment.depends_on =
+			dependencies.remove(&deployment.id).unwrap_or_default();
+	}
+
+	AppResponse::builder()
+		.body(ListDeletedDeploymentsResponse { deployments })
+		.headers(ListDeletedDeploymentsResponseHeaders {
+			total_count: TotalCountHeader(total_count as _),
+		})
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
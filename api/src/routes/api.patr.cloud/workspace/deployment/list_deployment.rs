@@ -1,20 +1,34 @@
+use std::collections::{BTreeMap, BTreeSet};
+
 use axum::http::StatusCode;
-use models::{api::workspace::deployment::*, utils::TotalCountHeader};
+use models::{
+	api::workspace::deployment::*,
+	utils::{ListOrder, ListOrderBy, TotalCountHeader},
+};
 
 use crate::prelude::*;
 
 /// The handler to list all deployments in the workspace. This will return
-/// all the deployments in the workspace.
+/// all the deployments in the workspace, or only the ones that have all the
+/// labels in the label selector, if one is given. Deployments are sorted by the
+/// given field, and then by their creation time and ID, so that the order is
+/// stable and each deployment appears on exactly one page.
 pub async fn list_deployment(
 	AuthenticatedAppRequest {
 		request:
 			ProcessedApiRequest {
 				path: ListDeploymentPath { workspace_id },
-				query: Paginated {
-					data: (),
-					count,
-					page,
-				},
+				query:
+					Paginated {
+						data:
+							ListDeploymentQuery {
+								order_by,
+								order,
+								label_selector,
+							},
+						count,
+						page,
+					},
 				headers:
 					ListDeploymentRequestHeaders {
 						authorization: _,
@@ -27,12 +41,16 @@ pub async fn list_deployment(
 		client_ip: _,
 		config: _,
 		user_data,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, ListDeploymentRequest>,
 ) -> Result<AppResponse<ListDeploymentRequest>, ErrorType> {
 	info!("Listing all deployments in workspace: {}", workspace_id);
 
+	let label_selector = label_selector.unwrap_or_default().0;
+	let sort = DeploymentSort::new(order_by, order);
+
 	let mut total_count = 0;
-	let deployments = query!(
+	let mut deployments = query!(
 		r#"
 		SELECT
 			deployment.id,
@@ -45,6 +63,13 @@ pub async fn list_deployment(
 			runner,
 			machine_type,
 			current_live_digest,
+			canary_image_tag,
+			canary_weight,
+			pull_secret_id,
+			ready_replicas,
+			total_replicas,
+			resource.created,
+			deployment.updated,
 			COUNT(*) OVER() AS "total_count!"
 		FROM
 			deployment
@@ -54,9 +79,35 @@ pub async fn list_deployment(
 			deployment.id = resource.id
 		WHERE
 			workspace_id = $1 AND
-			deployment.deleted IS NULL
+			deployment.deleted IS NULL AND
+			NOT EXISTS(
+				SELECT
+					1
+				FROM
+					UNNEST($10::TEXT[], $11::TEXT[]) AS selector(key, value)
+				WHERE
+					NOT EXISTS(
+						SELECT
+							1
+						FROM
+							deployment_label
+						WHERE
+							deployment_label.deployment_id = deployment.id AND
+							deployment_label.key = selector.key AND
+							deployment_label.value = selector.value
+					)
+			)
 		ORDER BY
-			resource.created DESC
+			CASE WHEN $6 AND $8 THEN deployment.name END ASC,
+			CASE WHEN $6 AND NOT $8 THEN deployment.name END DESC,
+			CASE WHEN $7 AND $8 THEN deployment.status::TEXT END ASC,
+			CASE WHEN $7 AND NOT $8 THEN deployment.status::TEXT END DESC,
+			CASE WHEN $9 AND $8 THEN deployment.updated END ASC,
+			CASE WHEN $9 AND NOT $8 THEN deployment.updated END DESC,
+			CASE WHEN $8 THEN resource.created END ASC,
+			CASE WHEN $8 THEN deployment.id END ASC,
+			resource.created DESC,
+			deployment.id DESC
 		LIMIT $4
 		OFFSET $5;
 		"#,
@@ -65,36 +116,67 @@ pub async fn list_deployment(
 		Permission::Deployment(DeploymentPermission::View) as _,
 		count as i32,
 		(count * page) as i32,
+		sort.by_name,
+		sort.by_status,
+		sort.ascending,
+		sort.by_last_updated,
+		&label_selector.keys().cloned().collect::<Vec<_>>(),
+		&label_selector.values().cloned().collect::<Vec<_>>(),
 	)
 	.fetch_all(&mut **database)
 	.await?
 	.into_iter()
 	.map(|row| {
 		total_count = row.total_count;
-		WithId::new(
+		Ok(WithId::new(
 			row.id,
 			Deployment {
 				name: row.name,
-				registry: if row.registry == PatrRegistry.to_string() {
-					DeploymentRegistry::PatrRegistry {
-						registry: PatrRegistry,
-						repository_id: row.repository_id.unwrap().into(),
-					}
-				} else {
-					DeploymentRegistry::ExternalRegistry {
-						registry: row.registry,
-						image_name: row.image_name.unwrap(),
-					}
-				},
+				registry: super::deployment_registry(
+					row.registry,
+					row.repository_id.map(Into::into),
+					row.image_name,
+				)?,
 				image_tag: row.image_tag,
-				status: row.status,
+				status: row.status.with_replica_readiness(
+					row.ready_replicas
+						.zip(row.total_replicas)
+						.map(|(ready, total)| DeploymentReplicaReadiness {
+							ready: ready as u16,
+							total: total as u16,
+						}),
+				),
 				runner: row.runner.into(),
 				machine_type: row.machine_type.into(),
 				current_live_digest: row.current_live_digest,
+				pull_secret_id: row.pull_secret_id.map(Into::into),
+				created_at: row.created,
+				updated_at: row.updated,
+				labels: BTreeMap::new(),
+				depends_on: BTreeSet::new(),
+				canary: row
+					.canary_image_tag
+					.zip(row.canary_weight)
+					.map(|(image_tag, weight)| DeploymentCanary {
+						image_tag,
+						weight: weight as u8,
+					}),
 			},
-		)
+		))
 	})
-	.collect();
+	.collect::<Result<Vec<_>, ErrorType>>()?;
+
+	let deployment_ids = deployments
+		.iter()
+		.map(|deployment| deployment.id)
+		.collect::<Vec<_>>();
+	let mut labels = super::get_deployment_labels(&mut **database, &deployment_ids).await?;
+	let mut dependencies =
+		super::get_deployment_dependencies(&mut **database, &deployment_ids).await?;
+	for deployment in &mut deployments {
+		deployment.data.labels = labels.remove(&deployment.id).unwrap_or_default();
+		deployment.data.depends_on = dependencies.remove(&deployment.id).unwrap_or_default();
+	}
 
 	AppResponse::builder()
 		.body(ListDeploymentResponse { deployments })
@@ -105,3 +187,69 @@ pub async fn list_deployment(
 		.build()
 		.into_result()
 }
+
+/// The flags that pick the fields the deployments are sorted by in the query
+/// that lists them, since the parameters of a query can't name a column.
+/// Deployments are sorted by their creation time when none of the fields are
+/// picked, and by their creation time and ID after the picked field otherwise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct DeploymentSort {
+	/// Whether to sort the deployments by their name
+	by_name: bool,
+	/// Whether to sort the deployments by their status
+	by_status: bool,
+	/// Whether to sort the deployments by when they were last updated
+	by_last_updated: bool,
+	/// Whether to sort the deployments in ascending order
+	ascending: bool,
+}
+
+impl DeploymentSort {
+	/// Picks the flags for the field and the order given in the query,
+	/// defaulting to the newest deployments first
+	fn new(order_by: Option<ListOrderBy>, order: Option<ListOrder>) -> Self {
+		let order_by = order_by.unwrap_or_default();
+		Self {
+			by_name: order_by == ListOrderBy::Name,
+			by_status: order_by == ListOrderBy::Status,
+			by_last_updated: order_by == ListOrderBy::LastUpdated,
+			ascending: order.unwrap_or_default() == ListOrder::Ascending,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn deployments_are_sorted_by_the_chosen_field() {
+		let default = DeploymentSort::new(None, None);
+		assert_eq!(
+			default,
+			DeploymentSort {
+				by_name: false,
+				by_status: false,
+				by_last_updated: false,
+				ascending: ListOrder::default() == ListOrder::Ascending,
+			}
+		);
+		assert_eq!(
+			DeploymentSort::new(Some(ListOrderBy::Created), None),
+			default
+		);
+
+		let by_name = DeploymentSort::new(Some(ListOrderBy::Name), Some(ListOrder::Ascending));
+		assert!(by_name.by_name && by_name.ascending);
+		assert!(!by_name.by_status && !by_name.by_last_updated);
+
+		let by_status = DeploymentSort::new(Some(ListOrderBy::Status), Some(ListOrder::Descending));
+		assert!(by_status.by_status && !by_status.ascending);
+		assert!(!by_status.by_name && !by_status.by_last_updated);
+
+		let by_last_updated =
+			DeploymentSort::new(Some(ListOrderBy::LastUpdated), Some(ListOrder::Ascending));
+		assert!(by_last_updated.by_last_updated && by_last_updated.ascending);
+		assert!(!by_last_updated.by_name && !by_last_updated.by_status);
+	}
+}
@@ -0,0 +1,43 @@
+use axum::http::StatusCode;
+use models::api::workspace::deployment::*;
+
+use super::get_deployment_logs::fetch_deployment_replicas;
+use crate::prelude::*;
+
+/// Route to list the replicas of a deployment, as they were last reported by
+/// its runner.
+pub async fn list_deployment_replicas(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: ListDeploymentReplicasPath {
+					workspace_id,
+					deployment_id,
+				},
+				query: (),
+				headers:
+					ListDeploymentReplicasRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: ListDeploymentReplicasRequestProcessed,
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config: _,
+		user_data: _,
+		clock: _,
+	}: AuthenticatedAppRequest<'_, ListDeploymentReplicasRequest>,
+) -> Result<AppResponse<ListDeploymentReplicasRequest>, ErrorType> {
+	info!("Listing replicas of deployment: {}", deployment_id);
+
+	let replicas = fetch_deployment_replicas(&mut **database, workspace_id, deployment_id).await?;
+
+	AppResponse::builder()
+		.body(ListDeploymentReplicasResponse { replicas })
+		.headers(())
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
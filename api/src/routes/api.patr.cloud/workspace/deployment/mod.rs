@@ -1,35 +1,111 @@
+use std::collections::{BTreeMap, BTreeSet};
+
 use axum::Router;
+use models::{
+	api::workspace::deployment::{
+		Deployment,
+		DeploymentMachineType,
+		DeploymentRegistry,
+		DeploymentResources,
+		PatrRegistry,
+	},
+	utils::BearerToken,
+};
+use ring::{hmac, rand::SystemRandom};
 
+/// Alert rules that notify when the resource usage of a deployment stays above
+/// a threshold.
+pub mod alert_rule;
+/// Building the image of a deployment from a git repository, and streaming the
+/// logs of the builds.
+pub mod build;
+/// Changes to deployments that are waiting for approval, for workspaces that
+/// require a second user to approve every change.
+pub mod change_request;
 /// The history of deploys for a deployment. This includes the status of the
 /// deploy, and the time it was deployed.
 pub mod deploy_history;
+/// Custom error pages that the ingress serves when a deployment is
+/// unavailable, set on a deployment or as the defaults of a workspace.
+pub mod error_page;
+/// Scanning the images of deployments for vulnerabilities, and blocking the
+/// deploys of images with critical vulnerabilities.
+pub mod image_scan;
+/// Schedules that automatically start and stop a deployment based on cron
+/// expressions.
+pub mod schedule;
+/// Workspace-level deployment templates, which are used to fill in the values
+/// that are not set when creating a deployment.
+pub mod template;
 
+mod abort_deployment_canary;
+mod batch_get_deployment_status;
 mod create_deployment;
 mod delete_deployment;
+mod download_deployment_logs;
+mod get_default_machine_type;
+mod get_deployment_access_logs;
 mod get_deployment_info;
 mod get_deployment_logs;
 mod get_deployment_metric;
+mod get_effective_deployment_config;
 mod list_all_deployment_machine_types;
+mod list_all_deployments;
+mod list_deleted_deployments;
 mod list_deployment;
+mod list_deployment_replicas;
+mod promote_deployment;
+mod promote_deployment_canary;
+mod reconcile_deployment;
+mod report_deployment_access_logs;
+mod report_deployment_activity;
+mod report_deployment_readiness;
+mod report_deployment_reconciliation;
+mod restore_deployment;
+mod set_default_machine_type;
+mod set_deployment_canary;
 mod start_deployment;
 mod stop_deployment;
 mod stream_deployment_logs;
+mod test_deployment_port;
 mod update_deployment;
+mod validate_deployment_config;
 
 use self::{
+	abort_deployment_canary::*,
+	batch_get_deployment_status::*,
 	create_deployment::*,
 	delete_deployment::*,
+	download_deployment_logs::*,
+	get_default_machine_type::*,
+	get_deployment_access_logs::*,
 	get_deployment_info::*,
 	get_deployment_logs::*,
 	get_deployment_metric::*,
+	get_effective_deployment_config::*,
 	list_all_deployment_machine_types::*,
+	list_all_deployments::*,
+	list_deleted_deployments::*,
 	list_deployment::*,
+	list_deployment_replicas::*,
+	promote_deployment::*,
+	promote_deployment_canary::*,
+	reconcile_deployment::*,
+	report_deployment_access_logs::*,
+	report_deployment_activity::*,
+	report_deployment_readiness::*,
+	report_deployment_reconciliation::*,
+	restore_deployment::*,
+	set_default_machine_type::*,
+	set_deployment_canary::*,
 	start_deployment::*,
 	stop_deployment::*,
 	stream_deployment_logs::*,
+	test_deployment_port::*,
 	update_deployment::*,
+	validate_deployment_config::*,
 };
-use crate::prelude::*;
+use crate::{prelude::*, utils::config::AppConfig};
 
 /*
 Figure out how to structure:
@@ -42,16 +118,669 @@ Figure out how to structure:
 #[instrument(skip(state))]
 pub async fn setup_routes(state: &AppState) -> Router {
 	Router::new()
+		.merge(alert_rule::setup_routes(state).await)
+		.merge(build::setup_routes(state).await)
+		.merge(change_request::setup_routes(state).await)
 		.merge(deploy_history::setup_routes(state).await)
+		.merge(error_page::setup_routes(state).await)
+		.merge(image_scan::setup_routes(state).await)
+		.merge(schedule::setup_routes(state).await)
+		.merge(template::setup_routes(state).await)
 		.mount_endpoint(machine_type, state)
 		.mount_auth_endpoint(list_deployment, state)
+		.mount_auth_endpoint(list_all_deployments, state)
+		.mount_auth_endpoint(list_deployment_replicas, state)
+		.mount_auth_endpoint(batch_get_deployment_status, state)
 		.mount_auth_endpoint(create_deployment, state)
 		.mount_auth_endpoint(get_deployment_info, state)
+		.mount_auth_endpoint(get_effective_deployment_config, state)
 		.mount_auth_endpoint(start_deployment, state)
 		.mount_auth_endpoint(stop_deployment, state)
 		.mount_auth_endpoint(get_deployment_logs, state)
+		.mount_auth_endpoint(download_deployment_logs, state)
+		.mount_auth_endpoint(get_deployment_access_logs, state)
 		.mount_auth_endpoint(delete_deployment, state)
+		.mount_auth_endpoint(list_deleted_deployments, state)
+		.mount_auth_endpoint(restore_deployment, state)
 		.mount_auth_endpoint(update_deployment, state)
+		.mount_auth_endpoint(promote_deployment, state)
+		.mount_auth_endpoint(set_deployment_canary, state)
+		.mount_auth_endpoint(promote_deployment_canary, state)
+		.mount_auth_endpoint(abort_deployment_canary, state)
 		.mount_auth_endpoint(get_deployment_metric, state)
 		.mount_auth_endpoint(stream_deployment_logs, state)
+		.mount_auth_endpoint(test_deployment_port, state)
+		.mount_auth_endpoint(reconcile_deployment, state)
+		.mount_endpoint(report_deployment_activity, state)
+		.mount_endpoint(report_deployment_access_logs, state)
+		.mount_auth_endpoint(report_deployment_readiness, state)
+		.mount_auth_endpoint(report_deployment_reconciliation, state)
+		.mount_auth_endpoint(validate_deployment_config, state)
+		.mount_auth_endpoint(get_default_machine_type, state)
+		.mount_auth_endpoint(set_default_machine_type, state)
+}
+
+/// Gets the machine type that new deployments in the workspace use when they
+/// don't specify one, if the workspace has one set
+async fn get_workspace_default_machine_type(
+	connection: &mut DatabaseConnection,
+	workspace_id: Uuid,
+) -> Result<Option<Uuid>, ErrorType> {
+	let machine_type = query!(
+		r#"
+		SELECT
+			default_machine_type_id
+		FROM
+			workspace
+		WHERE
+			id = $1 AND
+			deleted IS NULL;
+		"#,
+		workspace_id as _,
+	)
+	.fetch_optional(&mut *connection)
+	.await?
+	.or_not_found()?
+	.default_machine_type_id
+	.map(Into::into);
+
+	Ok(machine_type)
+}
+
+/// The maximum number of replicas that a deployment in a workspace can be
+/// scaled to. This is the limit of the workspace, if it has one, but never
+/// more than the limit of the instance, which also applies to the workspaces
+/// that don't have a limit of their own
+pub(super) fn effective_max_replicas(
+	instance_max_replicas: u16,
+	workspace_max_replicas: Option<i16>,
+) -> u16 {
+	workspace_max_replicas
+		.and_then(|max_replicas| u16::try_from(max_replicas).ok())
+		.unwrap_or(instance_max_replicas)
+		.min(instance_max_replicas)
+}
+
+/// Gets the maximum number of replicas that a deployment in the workspace can
+/// be scaled to. See [`effective_max_replicas`]
+pub(super) async fn get_workspace_max_replicas(
+	connection: &mut DatabaseConnection,
+	config: &AppConfig,
+	workspace_id: Uuid,
+) -> Result<u16, ErrorType> {
+	let workspace_max_replicas = query!(
+		r#"
+		SELECT
+			max_replicas
+		FROM
+			workspace
+		WHERE
+			id = $1 AND
+			deleted IS NULL;
+		"#,
+		workspace_id as _,
+	)
+	.fetch_optional(&mut *connection)
+	.await?
+	.or_not_found()?
+	.max_replicas;
+
+	Ok(effective_max_replicas(
+		config.deployment.max_replicas,
+		workspace_max_replicas,
+	))
+}
+
+/// Checks that a deployment isn't scaled to more replicas than the workspace
+/// allows
+async fn validate_max_replicas(
+	connection: &mut DatabaseConnection,
+	config: &AppConfig,
+	workspace_id: Uuid,
+	max_horizontal_scale: u16,
+) -> Result<(), ErrorType> {
+	let max_replicas = get_workspace_max_replicas(connection, config, workspace_id).await?;
+	check_max_replicas(max_horizontal_scale, max_replicas).inspect_err(|_| {
+		debug!(
+			"Cannot scale to {max_horizontal_scale} replicas, the limit of workspace \
+			`{workspace_id}` is {max_replicas}"
+		);
+	})
+}
+
+/// Checks that a deployment can be scaled to `max_horizontal_scale` replicas,
+/// given the limit of its workspace
+fn check_max_replicas(max_horizontal_scale: u16, max_replicas: u16) -> Result<(), ErrorType> {
+	if max_horizontal_scale > max_replicas {
+		return Err(ErrorType::ReplicaLimitExceeded(max_replicas));
+	}
+
+	Ok(())
+}
+
+/// Checks that a request to one of the endpoints that only the ingress can use
+/// is made with the token that the ingress is configured with. If the API has
+/// no token configured, these requests are always rejected. The tokens are
+/// compared in constant time, so that the token can't be guessed from how long
+/// the comparison takes
+fn verify_ingress_token(config: &AppConfig, authorization: &BearerToken) -> Result<(), ErrorType> {
+	let is_ingress = config
+		.cloudflare
+		.ingress_token
+		.as_deref()
+		.is_some_and(|token| tokens_match(token, authorization.0.token()));
+	if !is_ingress {
+		return Err(ErrorType::Unauthorized);
+	}
+
+	Ok(())
+}
+
+/// Compares two tokens in constant time. The tokens are signed with a random
+/// key, and the signatures are compared using [`hmac::verify`], which doesn't
+/// return early on the first byte that differs
+fn tokens_match(expected: &str, given: &str) -> bool {
+	let Ok(key) = hmac::Key::generate(hmac::HMAC_SHA256, &SystemRandom::new()) else {
+		return false;
+	};
+
+	hmac::verify(
+		&key,
+		given.as_bytes(),
+		hmac::sign(&key, expected.as_bytes()).as_ref(),
+	)
+	.is_ok()
+}
+
+/// Checks that the deployment exists in the given workspace and has not been
+/// deleted
+async fn ensure_deployment_exists(
+	connection: &mut DatabaseConnection,
+	workspace_id: Uuid,
+	deployment_id: Uuid,
+) -> Result<(), ErrorType> {
+	query!(
+		r#"
+		SELECT
+			id
+		FROM
+			deployment
+		WHERE
+			id = $1 AND
+			workspace_id = $2 AND
+			deleted IS NULL;
+		"#,
+		deployment_id as _,
+		workspace_id as _,
+	)
+	.fetch_optional(&mut *connection)
+	.await?
+	.or_not_found()?;
+
+	Ok(())
+}
+
+/// Gets the labels of each of the given deployments. Deployments that don't
+/// have any labels are left out of the returned map
+async fn get_deployment_labels(
+	connection: &mut DatabaseConnection,
+	deployment_ids: &[Uuid],
+) -> Result<BTreeMap<Uuid, BTreeMap<String, String>>, ErrorType> {
+	let rows = query!(
+		r#"
+		SELECT
+			deployment_id,
+			key,
+			value
+		FROM
+			deployment_label
+		WHERE
+			deployment_id = ANY($1);
+		"#,
+		&deployment_ids
+			.iter()
+			.map(|id| (*id).into())
+			.collect::<Vec<sqlx::types::Uuid>>(),
+	)
+	.fetch_all(&mut *connection)
+	.await?;
+
+	let mut labels = BTreeMap::<Uuid, BTreeMap<String, String>>::new();
+	for row in rows {
+		labels
+			.entry(row.deployment_id.into())
+			.or_default()
+			.insert(row.key, row.value);
+	}
+
+	Ok(labels)
+}
+
+/// Gets the deployments that each of the given deployments depends on.
+/// Deployments that don't depend on anything are left out of the returned map
+async fn get_deployment_dependencies(
+	connection: &mut DatabaseConnection,
+	deployment_ids: &[Uuid],
+) -> Result<BTreeMap<Uuid, BTreeSet<Uuid>>, ErrorType> {
+	let rows = query!(
+		r#"
+		SELECT
+			deployment_id,
+			dependency_id
+		FROM
+			deployment_dependency
+		WHERE
+			deployment_id = ANY($1);
+		"#,
+		&deployment_ids
+			.iter()
+			.map(|id| (*id).into())
+			.collect::<Vec<sqlx::types::Uuid>>(),
+	)
+	.fetch_all(&mut *connection)
+	.await?;
+
+	let mut dependencies = BTreeMap::<Uuid, BTreeSet<Uuid>>::new();
+	for row in rows {
+		dependencies
+			.entry(row.deployment_id.into())
+			.or_default()
+			.insert(row.dependency_id.into());
+	}
+
+	Ok(dependencies)
+}
+
+/// Gets the deployments that each deployment in the workspace depends on.
+/// Deleted deployments, and deployments that don't depend on anything, are
+/// left out of the returned map
+async fn get_workspace_dependency_graph(
+	connection: &mut DatabaseConnection,
+	workspace_id: Uuid,
+) -> Result<BTreeMap<Uuid, BTreeSet<Uuid>>, ErrorType> {
+	let rows = query!(
+		r#"
+		SELECT
+			deployment_dependency.deployment_id,
+			deployment_dependency.dependency_id
+		FROM
+			deployment_dependency
+		INNER JOIN
+			deployment
+		ON
+			deployment.id = deployment_dependency.deployment_id
+		WHERE
+			deployment.workspace_id = $1 AND
+			deployment.deleted IS NULL;
+		"#,
+		workspace_id as _,
+	)
+	.fetch_all(&mut *connection)
+	.await?;
+
+	let mut graph = BTreeMap::<Uuid, BTreeSet<Uuid>>::new();
+	for row in rows {
+		graph
+			.entry(row.deployment_id.into())
+			.or_default()
+			.insert(row.dependency_id.into());
+	}
+
+	Ok(graph)
+}
+
+/// Checks that a deployment can depend on the given deployments. They must
+/// all be deployments in the same workspace that haven't been deleted, and
+/// must not make the deployment depend on itself, directly or indirectly.
+async fn validate_deployment_dependencies(
+	connection: &mut DatabaseConnection,
+	workspace_id: Uuid,
+	deployment_id: Uuid,
+	depends_on: &BTreeSet<Uuid>,
+) -> Result<(), ErrorType> {
+	let existing = query!(
+		r#"
+		SELECT
+			COUNT(*) AS "count!"
+		FROM
+			deployment
+		WHERE
+			id = ANY($1) AND
+			workspace_id = $2 AND
+			deleted IS NULL;
+		"#,
+		&depends_on
+			.iter()
+			.map(|id| (*id).into())
+			.collect::<Vec<sqlx::types::Uuid>>(),
+		workspace_id as _,
+	)
+	.fetch_one(&mut *connection)
+	.await?
+	.count;
+
+	if existing as usize != depends_on.len() {
+		return Err(ErrorType::ResourceDoesNotExist);
+	}
+
+	let graph = get_workspace_dependency_graph(&mut *connection, workspace_id).await?;
+	Deployment::validate_dependencies(deployment_id, depends_on, &graph)
+}
+
+/// Replaces the deployments that a deployment depends on, after checking that
+/// it can depend on them using [`validate_deployment_dependencies`]. The
+/// workspace is locked while the dependencies are checked and replaced, so
+/// that two deployments being updated at the same time can't end up depending
+/// on each other.
+async fn set_deployment_dependencies(
+	connection: &mut DatabaseConnection,
+	workspace_id: Uuid,
+	deployment_id: Uuid,
+	depends_on: &BTreeSet<Uuid>,
+) -> Result<(), ErrorType> {
+	query!(
+		r#"
+		SELECT
+			id
+		FROM
+			workspace
+		WHERE
+			id = $1
+		FOR UPDATE;
+		"#,
+		workspace_id as _,
+	)
+	.fetch_optional(&mut *connection)
+	.await?
+	.or_not_found()?;
+
+	validate_deployment_dependencies(&mut *connection, workspace_id, deployment_id, depends_on)
+		.await?;
+
+	query!(
+		r#"
+		DELETE FROM
+			deployment_dependency
+		WHERE
+			deployment_id = $1;
+		"#,
+		deployment_id as _,
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		INSERT INTO
+			deployment_dependency(
+				deployment_id,
+				dependency_id
+			)
+		SELECT
+			$1,
+			UNNEST($2::UUID[]);
+		"#,
+		deployment_id as _,
+		&depends_on
+			.iter()
+			.map(|id| (*id).into())
+			.collect::<Vec<sqlx::types::Uuid>>(),
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	Ok(())
+}
+
+/// Checks that the paths that the volumes of a deployment are mounted on are
+/// absolute, and that no two volumes are mounted on the same path. Trailing
+/// slashes are ignored, so `/data` and `/data/` are considered the same path.
+fn validate_volume_mounts(volumes: &BTreeMap<Uuid, String>) -> Result<(), ErrorType> {
+	let mut mount_paths = BTreeSet::new();
+	for mount_path in volumes.values() {
+		if !mount_path.starts_with('/') {
+			return Err(ErrorType::WrongParameters);
+		}
+
+		let mount_path = match mount_path.trim_end_matches('/') {
+			"" => "/",
+			mount_path => mount_path,
+		};
+		if !mount_paths.insert(mount_path) {
+			return Err(ErrorType::VolumeMountConflict);
+		}
+	}
+
+	Ok(())
+}
+
+/// Checks that the period of inactivity after which a deployment is scaled to
+/// zero is not shorter than [`constants::MIN_SCALE_TO_ZERO_AFTER_SECONDS`]
+fn validate_scale_to_zero_after(scale_to_zero_after: Option<u32>) -> Result<(), ErrorType> {
+	if scale_to_zero_after
+		.is_some_and(|seconds| seconds < constants::MIN_SCALE_TO_ZERO_AFTER_SECONDS)
+	{
+		return Err(ErrorType::WrongParameters);
+	}
+
+	Ok(())
+}
+
+/// Checks that the maximum number of requests that each replica of a deployment
+/// handles at once can be stored. A limit of zero means that the number of
+/// requests is not limited, so it is stored as no limit at all.
+fn validate_max_concurrent_requests(
+	max_concurrent_requests: Option<u32>,
+) -> Result<Option<u32>, ErrorType> {
+	match max_concurrent_requests {
+		Some(0) | None => Ok(None),
+		Some(limit) if i32::try_from(limit).is_err() => Err(ErrorType::WrongParameters),
+		Some(limit) => Ok(Some(limit)),
+	}
+}
+
+/// Checks that a deployment with volumes mounted cannot be scaled beyond one
+/// replica, since a volume can only be attached to one replica at a time. This
+/// is checked against the stored deployment, so it must be called after the
+/// scale or the volumes of the deployment are changed.
+async fn ensure_volumes_can_be_attached(
+	connection: &mut DatabaseConnection,
+	deployment_id: Uuid,
+) -> Result<(), ErrorType> {
+	let deployment = query!(
+		r#"
+		SELECT
+			max_horizontal_scale,
+			EXISTS(
+				SELECT
+					1
+				FROM
+					deployment_volume_mount
+				WHERE
+					deployment_id = $1
+			) AS "has_volumes!"
+		FROM
+			deployment
+		WHERE
+			id = $1;
+		"#,
+		deployment_id as _,
+	)
+	.fetch_optional(&mut *connection)
+	.await?
+	.or_not_found()?;
+
+	if deployment.has_volumes && deployment.max_horizontal_scale > 1 {
+		return Err(ErrorType::CannotScaleWithVolume);
+	}
+
+	Ok(())
+}
+
+/// Checks that the CPU and memory requests of a deployment are not more than
+/// their limits, and that the limits fit in the capacity of the deployment's
+/// machine type. This is checked against the stored deployment, so it must be
+/// called after the resources or the machine type of the deployment are
+/// changed.
+async fn ensure_resources_fit_machine_type(
+	connection: &mut DatabaseConnection,
+	deployment_id: Uuid,
+) -> Result<(), ErrorType> {
+	let deployment = query!(
+		r#"
+		SELECT
+			deployment.cpu_request,
+			deployment.cpu_limit,
+			deployment.memory_request,
+			deployment.memory_limit,
+			deployment_machine_type.cpu_count,
+			deployment_machine_type.memory_count
+		FROM
+			deployment
+		INNER JOIN
+			deployment_machine_type
+		ON
+			deployment.machine_type = deployment_machine_type.id
+		WHERE
+			deployment.id = $1;
+		"#,
+		deployment_id as _,
+	)
+	.fetch_optional(&mut *connection)
+	.await?
+	.or_not_found()?;
+
+	let resources = DeploymentResources {
+		cpu_request: deployment.cpu_request.map(|value| value as u32),
+		cpu_limit: deployment.cpu_limit.map(|value| value as u32),
+		memory_request: deployment.memory_request.map(|value| value as u32),
+		memory_limit: deployment.memory_limit.map(|value| value as u32),
+	};
+	let machine_type = DeploymentMachineType {
+		cpu_count: deployment.cpu_count as u16,
+		memory_count: deployment.memory_count as u32,
+	};
+
+	resources.validate(&machine_type)
+}
+
+/// Creates the registry of a deployment from the columns it is stored as. The
+/// Patr registry is stored with the ID of the repository, and any other
+/// registry with the name of the image. A deployment that is missing either
+/// fails with a server error, instead of the request panicking.
+fn deployment_registry(
+	registry: String,
+	repository_id: Option<Uuid>,
+	image_name: Option<String>,
+) -> Result<DeploymentRegistry, ErrorType> {
+	if registry == PatrRegistry.to_string() {
+		Ok(DeploymentRegistry::PatrRegistry {
+			registry: PatrRegistry,
+			repository_id: repository_id.ok_or_else(|| {
+				ErrorType::server_error("Deployment on the Patr registry has no repository")
+			})?,
+		})
+	} else {
+		Ok(DeploymentRegistry::ExternalRegistry {
+			image_name: image_name.ok_or_else(|| {
+				ErrorType::server_error(format!("Deployment on `{registry}` has no image"))
+			})?,
+			registry,
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::BTreeMap;
+
+	use models::api::workspace::deployment::{DeploymentRegistry, PatrRegistry};
+
+	use super::{
+		check_max_replicas,
+		deployment_registry,
+		effective_max_replicas,
+		tokens_match,
+		validate_volume_mounts,
+	};
+	use crate::prelude::*;
+
+	#[test]
+	fn only_the_same_token_matches() {
+		assert!(tokens_match("ingress-token", "ingress-token"));
+		assert!(!tokens_match("ingress-token", "ingress-tokem"));
+		assert!(!tokens_match("ingress-token", "ingress-token-2"));
+		assert!(!tokens_match("ingress-token", ""));
+	}
+
+	#[test]
+	fn rejects_conflicting_volume_mounts() {
+		let volumes = BTreeMap::from([
+			(Uuid::new_v4(), "/data".to_string()),
+			(Uuid::new_v4(), "/var/lib/app".to_string()),
+		]);
+		assert_eq!(validate_volume_mounts(&volumes), Ok(()));
+
+		let volumes = BTreeMap::from([
+			(Uuid::new_v4(), "/data".to_string()),
+			(Uuid::new_v4(), "/data/".to_string()),
+		]);
+		assert_eq!(
+			validate_volume_mounts(&volumes),
+			Err(ErrorType::VolumeMountConflict)
+		);
+
+		let volumes = BTreeMap::from([(Uuid::new_v4(), "data".to_string())]);
+		assert_eq!(
+			validate_volume_mounts(&volumes),
+			Err(ErrorType::WrongParameters)
+		);
+	}
+
+	#[test]
+	fn the_limit_of_the_workspace_never_exceeds_the_instance() {
+		assert_eq!(effective_max_replicas(20, None), 20);
+		assert_eq!(effective_max_replicas(20, Some(5)), 5);
+		assert_eq!(effective_max_replicas(20, Some(50)), 20);
+		// Values that can't be stored are never trusted
+		assert_eq!(effective_max_replicas(20, Some(-1)), 20);
+	}
+
+	#[test]
+	fn deployments_can_be_scaled_up_to_the_limit() {
+		assert_eq!(check_max_replicas(1, 20), Ok(()));
+		assert_eq!(check_max_replicas(20, 20), Ok(()));
+		assert_eq!(
+			check_max_replicas(21, 20),
+			Err(ErrorType::ReplicaLimitExceeded(20))
+		);
+	}
+
+	#[test]
+	fn registries_without_their_image_are_server_errors() {
+		let repository_id = Uuid::new_v4();
+		assert_eq!(
+			deployment_registry(PatrRegistry.to_string(), Some(repository_id), None),
+			Ok(DeploymentRegistry::PatrRegistry {
+				registry: PatrRegistry,
+				repository_id,
+			})
+		);
+		assert_eq!(
+			deployment_registry("docker.io".to_string(), None, Some("nginx".to_string())),
+			Ok(DeploymentRegistry::ExternalRegistry {
+				registry: "docker.io".to_string(),
+				image_name: "nginx".to_string(),
+			})
+		);
+
+		assert_eq!(
+			deployment_registry(PatrRegistry.to_string(), None, Some("nginx".to_string())),
+			Err(ErrorType::InternalServerError)
+		);
+		assert_eq!(
+			deployment_registry("docker.io".to_string(), Some(repository_id), None),
+			Err(ErrorType::InternalServerError)
+		);
+	}
 }
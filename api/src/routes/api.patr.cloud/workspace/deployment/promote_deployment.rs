@@ -0,0 +1,514 @@
+use axum::http::StatusCode;
+use models::api::workspace::{deployment::*, runner::StreamRunnerDataForWorkspaceServerMsg};
+
+use super::{deployment_registry, ensure_volumes_can_be_attached};
+use crate::{deployment_image_scanner, prelude::*, utils::runner};
+
+/// The handler to promote a deployment to another deployment, such as from a
+/// staging deployment to a production deployment. The image of the source
+/// deployment is copied to the target deployment, along with the selected
+/// parts of its configuration. Environment variables that are marked as
+/// environment-specific on either deployment are left untouched. The target
+/// deployment is then redeployed, creating a new revision in its deploy
+/// history, and its runner is asked to reconcile it right away. The promoted
+/// image has to be allowed under the image scan policy of the workspace, like
+/// any other deploy.
+pub async fn promote_deployment(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: PromoteDeploymentPath {
+					workspace_id,
+					deployment_id,
+				},
+				query: (),
+				headers:
+					PromoteDeploymentRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body:
+					PromoteDeploymentRequestProcessed {
+						source_deployment_id,
+						config,
+					},
+			},
+		database,
+		redis,
+		client_ip: _,
+		config: app_config,
+		user_data,
+		clock,
+	}: AuthenticatedAppRequest<'_, PromoteDeploymentRequest>,
+) -> Result<AppResponse<PromoteDeploymentRequest>, ErrorType> {
+	info!(
+		"Promoting deployment `{}` to deployment `{}`",
+		source_deployment_id, deployment_id
+	);
+
+	if source_deployment_id == deployment_id {
+		return Err(ErrorType::WrongParameters);
+	}
+
+	let runner_id = query!(
+		r#"
+		SELECT
+			runner
+		FROM
+			deployment
+		WHERE
+			id = $1 AND
+			deleted IS NULL;
+		"#,
+		deployment_id as _,
+	)
+	.fetch_optional(&mut **database)
+	.await?
+	.ok_or(ErrorType::ResourceDoesNotExist)?
+	.runner;
+
+	// The source deployment must be in the same workspace, and the user must
+	// be able to view it
+	let source = query!(
+		r#"
+		SELECT
+			registry,
+			repository_id,
+			image_name,
+			image_tag,
+			current_live_digest
+		FROM
+			deployment
+		INNER JOIN
+			RESOURCES_WITH_PERMISSION_FOR_LOGIN_ID($3, $4) AS resource
+		ON
+			deployment.id = resource.id
+		WHERE
+			deployment.id = $1 AND
+			workspace_id = $2 AND
+			deployment.deleted IS NULL;
+		"#,
+		source_deployment_id as _,
+		workspace_id as _,
+		user_data.login_id as _,
+		Permission::Deployment(DeploymentPermission::View) as _,
+	)
+	.fetch_optional(&mut **database)
+	.await?
+	.ok_or(ErrorType::ResourceDoesNotExist)?;
+
+	// BEGIN DEFERRED CONSTRAINT
+	query!(
+		r#"
+		SET CONSTRAINTS ALL DEFERRED;
+		"#,
+	)
+	.execute(&mut **database)
+	.await?;
+
+	if config.contains(&PromotedDeploymentConfig::Ports) {
+		query!(
+			r#"
+			DELETE FROM
+				deployment_exposed_port
+			WHERE
+				deployment_id = $1;
+			"#,
+			deployment_id as _,
+		)
+		.execute(&mut **database)
+		.await?;
+
+		query!(
+			r#"
+			INSERT INTO
+				deployment_exposed_port(
+					deployment_id,
+					port,
+					port_type
+				)
+			SELECT
+				$1,
+				port,
+				port_type
+			FROM
+				deployment_exposed_port
+			WHERE
+				deployment_id = $2;
+			"#,
+			deployment_id as _,
+			source_deployment_id as _,
+		)
+		.execute(&mut **database)
+		.await?;
+	}
+
+	// The revision is recorded before the target is pinned to the live digest
+	// of the source, since the live digest has to be in the deploy history
+	let registry = deployment_registry(
+		source.registry,
+		source.repository_id.map(Into::into),
+		source.image_name,
+	)?;
+	if let Some((revision, repository_id)) =
+		promoted_revision(&registry, &source.image_tag, source.current_live_digest)
+	{
+		query!(
+			r#"
+			INSERT INTO
+				deployment_deploy_history(
+					deployment_id,
+					image_digest,
+					repository_id,
+					created
+				)
+			VALUES
+				($1, $2, $3, $4)
+			ON CONFLICT
+				(deployment_id, image_digest)
+			DO NOTHING;
+			"#,
+			deployment_id as _,
+			revision,
+			repository_id as _,
+			clock.now(),
+		)
+		.execute(&mut **database)
+		.await?;
+	}
+
+	// The image is always promoted. The rest of the configuration is only
+	// copied if it was selected
+	query!(
+		r#"
+		UPDATE
+			deployment
+		SET
+			reconciliation_status = 'pending',
+			reconciliation_generation = reconciliation_generation + 1,
+			updated = NOW(),
+			registry = source.registry,
+			repository_id = source.repository_id,
+			image_name = source.image_name,
+			image_tag = source.image_tag,
+			pull_secret_id = source.pull_secret_id,
+			current_live_digest = source.current_live_digest,
+			machine_type = (
+				CASE
+					WHEN $3 THEN
+						source.machine_type
+					ELSE
+						deployment.machine_type
+				END
+			),
+			cpu_request = (
+				CASE
+					WHEN $3 THEN
+						source.cpu_request
+					ELSE
+						deployment.cpu_request
+				END
+			),
+			cpu_limit = (
+				CASE
+					WHEN $3 THEN
+						source.cpu_limit
+					ELSE
+						deployment.cpu_limit
+				END
+			),
+			memory_request = (
+				CASE
+					WHEN $3 THEN
+						source.memory_request
+					ELSE
+						deployment.memory_request
+				END
+			),
+			memory_limit = (
+				CASE
+					WHEN $3 THEN
+						source.memory_limit
+					ELSE
+						deployment.memory_limit
+				END
+			),
+			min_horizontal_scale = (
+				CASE
+					WHEN $4 THEN
+						source.min_horizontal_scale
+					ELSE
+						deployment.min_horizontal_scale
+				END
+			),
+			max_horizontal_scale = (
+				CASE
+					WHEN $4 THEN
+						source.max_horizontal_scale
+					ELSE
+						deployment.max_horizontal_scale
+				END
+			),
+			scale_to_zero_after = (
+				CASE
+					WHEN $4 THEN
+						source.scale_to_zero_after
+					ELSE
+						deployment.scale_to_zero_after
+				END
+			),
+			max_concurrent_requests = (
+				CASE
+					WHEN $4 THEN
+						source.max_concurrent_requests
+					ELSE
+						deployment.max_concurrent_requests
+				END
+			),
+			startup_probe_port = (
+				CASE
+					WHEN $5 THEN
+						source.startup_probe_port
+					ELSE
+						deployment.startup_probe_port
+				END
+			),
+			startup_probe_path = (
+				CASE
+					WHEN $5 THEN
+						source.startup_probe_path
+					ELSE
+						deployment.startup_probe_path
+				END
+			),
+			startup_probe_port_type = (
+				CASE
+					WHEN $5 THEN
+						source.startup_probe_port_type
+					ELSE
+						deployment.startup_probe_port_type
+				END
+			),
+			liveness_probe_port = (
+				CASE
+					WHEN $5 THEN
+						source.liveness_probe_port
+					ELSE
+						deployment.liveness_probe_port
+				END
+			),
+			liveness_probe_path = (
+				CASE
+					WHEN $5 THEN
+						source.liveness_probe_path
+					ELSE
+						deployment.liveness_probe_path
+				END
+			),
+			liveness_probe_port_type = (
+				CASE
+					WHEN $5 THEN
+						source.liveness_probe_port_type
+					ELSE
+						deployment.liveness_probe_port_type
+				END
+			),
+			status = $6
+		FROM
+			deployment AS source
+		WHERE
+			deployment.id = $1 AND
+			source.id = $2;
+		"#,
+		deployment_id as _,
+		source_deployment_id as _,
+		config.contains(&PromotedDeploymentConfig::MachineType),
+		config.contains(&PromotedDeploymentConfig::Scaling),
+		config.contains(&PromotedDeploymentConfig::Probes),
+		DeploymentStatus::Deploying as _,
+	)
+	.execute(&mut **database)
+	.await?;
+
+	deployment_image_scanner::ensure_image_allowed(
+		&mut **database,
+		redis,
+		&app_config,
+		workspace_id,
+		deployment_id,
+		false,
+		clock.now(),
+	)
+	.await?;
+
+	// The scale of the source may be too high for the volumes of the target
+	if config.contains(&PromotedDeploymentConfig::Scaling) {
+		ensure_volumes_can_be_attached(&mut **database, deployment_id).await?;
+	}
+
+	// END DEFERRED CONSTRAINT
+	query!(
+		r#"
+		SET CONSTRAINTS ALL IMMEDIATE;
+		"#,
+	)
+	.execute(&mut **database)
+	.await?;
+
+	if config.contains(&PromotedDeploymentConfig::EnvironmentVariables) {
+		// Environment-specific variables on the target are kept as they are,
+		// and the ones on the source are never copied over
+		query!(
+			r#"
+			DELETE FROM
+				deployment_environment_variable
+			WHERE
+				deployment_id = $1 AND
+				environment_specific = FALSE;
+			"#,
+			deployment_id as _,
+		)
+		.execute(&mut **database)
+		.await?;
+
+		query!(
+			r#"
+			INSERT INTO
+				deployment_environment_variable(
+					deployment_id,
+					name,
+					value,
+					secret_id,
+					environment_specific,
+					is_secret
+				)
+			SELECT
+				$1,
+				name,
+				value,
+				secret_id,
+				FALSE,
+				is_secret
+			FROM
+				deployment_environment_variable
+			WHERE
+				deployment_id = $2 AND
+				environment_specific = FALSE
+			ON CONFLICT
+				(deployment_id, name)
+			DO NOTHING;
+			"#,
+			deployment_id as _,
+			source_deployment_id as _,
+		)
+		.execute(&mut **database)
+		.await?;
+	}
+
+	if config.contains(&PromotedDeploymentConfig::ConfigMounts) {
+		query!(
+			r#"
+			DELETE FROM
+				deployment_config_mounts
+			WHERE
+				deployment_id = $1;
+			"#,
+			deployment_id as _,
+		)
+		.execute(&mut **database)
+		.await?;
+
+		query!(
+			r#"
+			INSERT INTO
+				deployment_config_mounts(
+					deployment_id,
+					path,
+					file
+				)
+			SELECT
+				$1,
+				path,
+				file
+			FROM
+				deployment_config_mounts
+			WHERE
+				deployment_id = $2;
+			"#,
+			deployment_id as _,
+			source_deployment_id as _,
+		)
+		.execute(&mut **database)
+		.await?;
+	}
+
+	runner::send_message(
+		redis,
+		&app_config.runner,
+		workspace_id,
+		runner_id.into(),
+		&StreamRunnerDataForWorkspaceServerMsg::DeploymentReconciliationRequested {
+			id: deployment_id,
+		},
+	)
+	.await?;
+
+	AppResponse::builder()
+		.body(PromoteDeploymentResponse)
+		.headers(())
+		.status_code(StatusCode::ACCEPTED)
+		.build()
+		.into_result()
+}
+
+/// The revision that promoting an image records in the deploy history of the
+/// target deployment, along with the repository of the image. Images on the
+/// Patr registry are recorded by their digest, if they have one. The digest of
+/// an image on any other registry isn't known, so it is recorded by its
+/// reference instead, with the tag that it was promoted with.
+fn promoted_revision(
+	registry: &DeploymentRegistry,
+	image_tag: &str,
+	digest: Option<String>,
+) -> Option<(String, Option<Uuid>)> {
+	match registry {
+		DeploymentRegistry::PatrRegistry { repository_id, .. } => {
+			digest.map(|digest| (digest, Some(*repository_id)))
+		}
+		DeploymentRegistry::ExternalRegistry {
+			registry,
+			image_name,
+		} => Some((format!("{registry}/{image_name}:{image_tag}"), None)),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn images_from_every_registry_are_recorded_as_revisions() {
+		let repository_id = Uuid::new_v4();
+		let patr = DeploymentRegistry::PatrRegistry {
+			registry: PatrRegistry,
+			repository_id,
+		};
+		assert_eq!(
+			promoted_revision(&patr, "latest", Some("sha256:abc".to_string())),
+			Some(("sha256:abc".to_string(), Some(repository_id)))
+		);
+		assert_eq!(promoted_revision(&patr, "latest", None), None);
+
+		let external = DeploymentRegistry::ExternalRegistry {
+			registry: "registry.hub.docker.com".to_string(),
+			image_name: "library/nginx".to_string(),
+		};
+		assert_eq!(
+			promoted_revision(&external, "1.27", None),
+			Some((
+				"registry.hub.docker.com/library/nginx:1.27".to_string(),
+				None
+			))
+		);
+	}
+}
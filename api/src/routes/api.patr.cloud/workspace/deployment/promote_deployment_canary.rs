@@ -0,0 +1,177 @@
+use axum::http::StatusCode;
+use models::api::workspace::{deployment::*, runner::StreamRunnerDataForWorkspaceServerMsg};
+
+use crate::{deployment_image_scanner, prelude::*, utils::runner};
+
+/// The handler to promote the canary of a deployment. The image tag of the
+/// canary becomes the image tag of the deployment and the canary is removed,
+/// so all the requests are sent to the deployment once it is redeployed. For
+/// deployments on the Patr registry, the image that the tag points to is
+/// recorded as a new revision in the deploy history, and the deployment is
+/// pinned to it, the same way as when a deployment is promoted to another.
+/// Since the tag may have been moved since the canary was set, the image is
+/// checked against the image scan policy of the workspace again. The requests
+/// counted for the canary are removed along with it.
+pub async fn promote_deployment_canary(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: PromoteDeploymentCanaryPath {
+					workspace_id,
+					deployment_id,
+				},
+				query: (),
+				headers:
+					PromoteDeploymentCanaryRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: PromoteDeploymentCanaryRequestProcessed,
+			},
+		database,
+		redis,
+		client_ip: _,
+		config,
+		user_data: _,
+		clock,
+	}: AuthenticatedAppRequest<'_, PromoteDeploymentCanaryRequest>,
+) -> Result<AppResponse<PromoteDeploymentCanaryRequest>, ErrorType> {
+	info!("Promoting the canary of deployment `{deployment_id}`");
+
+	let now = clock.now();
+
+	let deployment = query!(
+		r#"
+		SELECT
+			registry,
+			repository_id,
+			runner,
+			canary_image_tag AS "canary_image_tag!"
+		FROM
+			deployment
+		WHERE
+			id = $1 AND
+			deleted IS NULL AND
+			canary_image_tag IS NOT NULL
+		FOR UPDATE;
+		"#,
+		deployment_id as _,
+	)
+	.fetch_optional(&mut **database)
+	.await?
+	.or_not_found()?;
+
+	deployment_image_scanner::ensure_image_allowed(
+		&mut **database,
+		redis,
+		&config,
+		workspace_id,
+		deployment_id,
+		true,
+		now,
+	)
+	.await?;
+
+	// The revision is recorded before the deployment is pinned to it, since
+	// the live digest has to be in the deploy history
+	let mut digest = None;
+	if let Some(repository_id) = deployment
+		.repository_id
+		.filter(|_| deployment.registry == PatrRegistry.to_string())
+	{
+		digest = query!(
+			r#"
+			SELECT
+				manifest_digest
+			FROM
+				container_registry_repository_tag
+			WHERE
+				repository_id = $1 AND
+				tag = $2;
+			"#,
+			repository_id as _,
+			deployment.canary_image_tag,
+		)
+		.fetch_optional(&mut **database)
+		.await?
+		.map(|row| row.manifest_digest);
+
+		if let Some(digest) = &digest {
+			query!(
+				r#"
+				INSERT INTO
+					deployment_deploy_history(
+						deployment_id,
+						image_digest,
+						repository_id,
+						created
+					)
+				VALUES
+					($1, $2, $3, $4)
+				ON CONFLICT
+					(deployment_id, image_digest)
+				DO NOTHING;
+				"#,
+				deployment_id as _,
+				digest as _,
+				repository_id as _,
+				now as _,
+			)
+			.execute(&mut **database)
+			.await?;
+		}
+	}
+
+	query!(
+		r#"
+		UPDATE
+			deployment
+		SET
+			image_tag = canary_image_tag,
+			current_live_digest = $1,
+			canary_image_tag = NULL,
+			canary_weight = NULL,
+			canary_ready_replicas = NULL,
+			reconciliation_status = 'pending',
+			reconciliation_generation = reconciliation_generation + 1,
+			updated = $2
+		WHERE
+			id = $3;
+		"#,
+		digest,
+		now,
+		deployment_id as _,
+	)
+	.execute(&mut **database)
+	.await?;
+
+	query!(
+		r#"
+		DELETE FROM
+			deployment_canary_requests
+		WHERE
+			deployment_id = $1;
+		"#,
+		deployment_id as _,
+	)
+	.execute(&mut **database)
+	.await?;
+
+	runner::send_message(
+		redis,
+		&config.runner,
+		workspace_id,
+		deployment.runner.into(),
+		&StreamRunnerDataForWorkspaceServerMsg::DeploymentReconciliationRequested {
+			id: deployment_id,
+		},
+	)
+	.await?;
+
+	AppResponse::builder()
+		.body(PromoteDeploymentCanaryResponse)
+		.headers(())
+		.status_code(StatusCode::ACCEPTED)
+		.build()
+		.into_result()
+}
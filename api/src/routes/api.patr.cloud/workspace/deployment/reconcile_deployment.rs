@@ -0,0 +1,71 @@
+use axum::http::StatusCode;
+use models::api::workspace::{deployment::*, runner::StreamRunnerDataForWorkspaceServerMsg};
+
+use crate::{prelude::*, utils::runner};
+
+/// The handler to force the runner of a deployment to reconcile it again. The
+/// deployment is marked as pending until the runner reports the result of the
+/// reconciliation back.
+pub async fn reconcile_deployment(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: ReconcileDeploymentPath {
+					workspace_id,
+					deployment_id,
+				},
+				query: (),
+				headers:
+					ReconcileDeploymentRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: ReconcileDeploymentRequestProcessed,
+			},
+		database,
+		redis,
+		client_ip: _,
+		config,
+		user_data: _,
+		clock: _,
+	}: AuthenticatedAppRequest<'_, ReconcileDeploymentRequest>,
+) -> Result<AppResponse<ReconcileDeploymentRequest>, ErrorType> {
+	info!("Reconciling deployment `{deployment_id}`");
+
+	let runner = query!(
+		r#"
+		UPDATE
+			deployment
+		SET
+			reconciliation_status = 'pending',
+			reconciliation_generation = reconciliation_generation + 1
+		WHERE
+			id = $1 AND
+			deleted IS NULL
+		RETURNING runner;
+		"#,
+		deployment_id as _,
+	)
+	.fetch_optional(&mut **database)
+	.await?
+	.or_not_found()?
+	.runner;
+
+	runner::send_message(
+		redis,
+		&config.runner,
+		workspace_id,
+		runner.into(),
+		&StreamRunnerDataForWorkspaceServerMsg::DeploymentReconciliationRequested {
+			id: deployment_id,
+		},
+	)
+	.await?;
+
+	AppResponse::builder()
+		.body(ReconcileDeploymentResponse)
+		.headers(())
+		.status_code(StatusCode::ACCEPTED)
+		.build()
+		.into_result()
+}
@@ -0,0 +1,216 @@
+use std::collections::BTreeMap;
+
+use axum::http::{HeaderName, HeaderValue, StatusCode};
+use models::{api::workspace::deployment::*, utils::constants};
+use serde_json::json;
+
+use super::get_deployment_logs::DeploymentLogStream;
+use crate::{prelude::*, utils::http_client};
+
+/// The handler for the ingress to report the requests made to deployments
+/// through managed URLs. The requests are pushed to Loki as
+/// [`DeploymentAccessLog`]s in the access log stream of each deployment, which
+/// is kept apart from the logs of its containers, with a single push for each
+/// workspace in the batch. Requests to deployments that don't have access
+/// logging enabled are not logged, since the ingress only learns that it was
+/// turned off once its cached activity report expires.
+pub async fn report_deployment_access_logs(
+	AppRequest {
+		request:
+			ProcessedApiRequest {
+				path: ReportDeploymentAccessLogsPath,
+				query: (),
+				headers:
+					ReportDeploymentAccessLogsRequestHeaders {
+						authorization,
+						user_agent: _,
+					},
+				body: ReportDeploymentAccessLogsRequestProcessed { logs },
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config,
+		clock: _,
+	}: AppRequest<'_, ReportDeploymentAccessLogsRequest>,
+) -> Result<AppResponse<ReportDeploymentAccessLogsRequest>, ErrorType> {
+	trace!("Reporting {} access logs", logs.len());
+
+	super::verify_ingress_token(&config, &authorization)?;
+
+	if logs.len() > constants::MAX_ACCESS_LOG_BATCH_SIZE {
+		debug!("Too many access logs reported in a single batch");
+		return Err(ErrorType::WrongParameters);
+	}
+	if !logs.iter().all(ReportedDeploymentAccessLog::is_valid) {
+		debug!("Invalid access log reported");
+		return Err(ErrorType::WrongParameters);
+	}
+
+	let workspaces = query!(
+		r#"
+		SELECT
+			id,
+			workspace_id
+		FROM
+			deployment
+		WHERE
+			id = ANY($1) AND
+			access_logging = TRUE AND
+			deleted IS NULL;
+		"#,
+		&logs
+			.iter()
+			.map(|log| log.deployment_id.into())
+			.collect::<Vec<sqlx::types::Uuid>>(),
+	)
+	.fetch_all(&mut **database)
+	.await?
+	.into_iter()
+	.map(|row| (row.id.into(), row.workspace_id.into()))
+	.collect::<BTreeMap<Uuid, Uuid>>();
+
+	for (workspace_id, streams) in group_by_workspace(logs, &workspaces)? {
+		http_client()
+			.post(format!(
+				"{}/loki/api/v1/push",
+				config.opentelemetry.logs.endpoint
+			))
+			.header(
+				HeaderName::from_static("x-scope-orgid"),
+				HeaderValue::from_str(&workspace_id.to_string()).unwrap(),
+			)
+			.json(&json!({
+				"streams": streams
+					.into_iter()
+					.map(|(deployment_id, values)| {
+						json!({
+							"stream": {
+								"deploymentId": deployment_id.to_string(),
+								"logStream": DeploymentLogStream::ACCESS_LOG_STREAM,
+							},
+							"values": values,
+						})
+					})
+					.collect::<Vec<_>>(),
+			}))
+			.send()
+			.await?
+			.error_for_status()?;
+	}
+
+	AppResponse::builder()
+		.body(ReportDeploymentAccessLogsResponse)
+		.headers(())
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
+
+/// Groups the access logs that were reported by the workspace (the Loki tenant)
+/// and the deployment (the Loki stream) that they belong to, given the
+/// workspace of each deployment that has access logging enabled. Each log is
+/// turned into the timestamp (in nanoseconds) and the line that is pushed to
+/// Loki, sorted by the timestamp. Logs of any other deployment are dropped.
+fn group_by_workspace(
+	logs: Vec<ReportedDeploymentAccessLog>,
+	workspaces: &BTreeMap<Uuid, Uuid>,
+) -> Result<BTreeMap<Uuid, BTreeMap<Uuid, Vec<[String; 2]>>>, ErrorType> {
+	let mut grouped = BTreeMap::<Uuid, BTreeMap<Uuid, Vec<(i128, String)>>>::new();
+
+	for log in logs {
+		let Some(workspace_id) = workspaces.get(&log.deployment_id) else {
+			continue;
+		};
+
+		let timestamp = log.timestamp.unix_timestamp_nanos();
+		let line = serde_json::to_string(&DeploymentAccessLog {
+			timestamp: log.timestamp,
+			method: log.method,
+			path: log.path,
+			status: log.status,
+			latency_millis: log.latency_millis,
+			canary: log.canary,
+		})?;
+		grouped
+			.entry(*workspace_id)
+			.or_default()
+			.entry(log.deployment_id)
+			.or_default()
+			.push((timestamp, line));
+	}
+
+	Ok(grouped
+		.into_iter()
+		.map(|(workspace_id, streams)| {
+			let streams = streams
+				.into_iter()
+				.map(|(deployment_id, mut values)| {
+					values.sort_by_key(|(timestamp, _)| *timestamp);
+					let values = values
+						.into_iter()
+						.map(|(timestamp, line)| [timestamp.to_string(), line])
+						.collect();
+					(deployment_id, values)
+				})
+				.collect();
+			(workspace_id, streams)
+		})
+		.collect())
+}
+
+#[cfg(test)]
+mod tests {
+	use time::{Duration, OffsetDateTime};
+
+	use super::*;
+
+	#[test]
+	fn logs_are_grouped_by_workspace_and_deployment_in_order() {
+		let (workspace, other_workspace) = (Uuid::new_v4(), Uuid::new_v4());
+		let (first, second, third, not_logged) = (
+			Uuid::new_v4(),
+			Uuid::new_v4(),
+			Uuid::new_v4(),
+			Uuid::new_v4(),
+		);
+		let workspaces = BTreeMap::from([
+			(first, workspace),
+			(second, workspace),
+			(third, other_workspace),
+		]);
+		let log = |deployment_id, seconds| ReportedDeploymentAccessLog {
+			deployment_id,
+			timestamp: OffsetDateTime::UNIX_EPOCH + Duration::seconds(seconds),
+			method: "GET".to_string(),
+			path: "/".to_string(),
+			status: 200,
+			latency_millis: 1,
+			canary: false,
+		};
+
+		let grouped = group_by_workspace(
+			vec![
+				log(first, 2),
+				log(not_logged, 1),
+				log(third, 1),
+				log(first, 1),
+				log(second, 3),
+			],
+			&workspaces,
+		)
+		.unwrap();
+
+		assert_eq!(grouped.len(), 2);
+		assert_eq!(grouped[&workspace].len(), 2);
+		assert_eq!(grouped[&other_workspace].len(), 1);
+		let timestamps = grouped[&workspace][&first]
+			.iter()
+			.map(|[timestamp, _]| timestamp.clone())
+			.collect::<Vec<_>>();
+		assert_eq!(timestamps, ["1000000000", "2000000000"]);
+		assert!(grouped
+			.values()
+			.all(|streams| !streams.contains_key(&not_logged)));
+	}
+}
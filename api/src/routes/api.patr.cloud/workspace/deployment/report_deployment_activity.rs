@@ -0,0 +1,324 @@
+use axum::http::StatusCode;
+use models::api::workspace::{deployment::*, runner::StreamRunnerDataForWorkspaceServerMsg};
+use opentelemetry::{global, KeyValue};
+use time::OffsetDateTime;
+
+use super::error_page;
+use crate::{
+	deployment_image_scanner,
+	models::deployment_event::DeploymentEventType,
+	prelude::*,
+	utils::runner,
+};
+
+/// The handler for the ingress to report that a deployment received a request.
+/// Only the ingress can report activity, using the token that it is configured
+/// with, since the activity decides whether a deployment is running. The time
+/// of the request is only tracked for deployments that are scaled to
+/// zero when idle. It is recorded so that the deployment isn't scaled down by
+/// the [`deployment_idle_scaler`][crate::deployment_idle_scaler], and if it was
+/// already scaled down, its runner is asked to start it again right away, as
+/// long as its image is still allowed under the image scan policy of its
+/// workspace.
+///
+/// For deployments that limit their concurrent requests, the combined limit of
+/// their ready replicas is returned for the ingress to enforce, and the number
+/// of requests that the ingress is forwarding is exported as the
+/// `deployment.concurrent_requests` metric, along with the limit as the
+/// `deployment.concurrent_requests.limit` metric.
+///
+/// For deployments with a canary, the weight of the canary is returned, for the
+/// ingress to split the requests between the canary and the primary replicas.
+/// The weight is only returned once the runner has reported the readiness of
+/// the canary, so that runners that can't run canaries never have requests sent
+/// to one. The requests that the ingress sent to each version since its last
+/// report are added to the counts of the canary.
+///
+/// The version of the custom error pages of the deployment, along with the
+/// defaults of its workspace, is returned for the ingress to know when to fetch
+/// them again. The pages themselves are only fetched when one of them has to be
+/// served.
+///
+/// The number of ready replicas of the deployment and its canary, as last
+/// reported by the runner, are returned for the ingress to only forward
+/// requests to replicas that are ready.
+pub async fn report_deployment_activity(
+	AppRequest {
+		request:
+			ProcessedApiRequest {
+				path: ReportDeploymentActivityPath { deployment_id },
+				query: (),
+				headers:
+					ReportDeploymentActivityRequestHeaders {
+						authorization,
+						user_agent: _,
+					},
+				body:
+					ReportDeploymentActivityRequestProcessed {
+						concurrent_requests,
+						requests,
+					},
+			},
+		database,
+		redis,
+		client_ip: _,
+		config,
+		clock: _,
+	}: AppRequest<'_, ReportDeploymentActivityRequest>,
+) -> Result<AppResponse<ReportDeploymentActivityRequest>, ErrorType> {
+	trace!("Reporting activity on deployment `{deployment_id}`");
+
+	super::verify_ingress_token(&config, &authorization)?;
+
+	let now = OffsetDateTime::now_utc();
+
+	// Lock the deployment, so that concurrent requests don't start it twice
+	let deployment = query!(
+		r#"
+		SELECT
+			workspace_id,
+			runner,
+			status AS "status: DeploymentStatus",
+			scale_to_zero_after,
+			max_concurrent_requests,
+			min_horizontal_scale,
+			ready_replicas,
+			canary_ready_replicas,
+			access_logging,
+			canary_weight
+		FROM
+			deployment
+		WHERE
+			id = $1 AND
+			deleted IS NULL
+		FOR UPDATE;
+		"#,
+		deployment_id as _,
+	)
+	.fetch_optional(&mut **database)
+	.await?
+	.ok_or(ErrorType::ResourceDoesNotExist)?;
+
+	// The limit is per replica, and a deployment without any ready replicas is
+	// given the limit of one replica. Runners that don't report the readiness
+	// of the replicas run the minimum number of replicas of the deployment
+	let ready_replicas = deployment
+		.ready_replicas
+		.unwrap_or(deployment.min_horizontal_scale)
+		.max(1) as u32;
+	let max_concurrent_requests = deployment
+		.max_concurrent_requests
+		.map(|limit| (limit as u32).saturating_mul(ready_replicas));
+	let canary_weight = deployment
+		.canary_weight
+		.filter(|_| deployment.canary_ready_replicas.is_some())
+		.map(|weight| weight as u8);
+	let error_pages_version = error_page::error_pages_version(
+		&error_page::get_effective_error_pages(&mut **database, deployment_id).await?,
+	);
+
+	let activity = ReportDeploymentActivityResponse {
+		warming: false,
+		max_concurrent_requests,
+		access_logging: deployment.access_logging,
+		canary_weight,
+		error_pages_version,
+		ready_replicas: deployment.ready_replicas.map(|ready| ready as u16),
+		canary_ready_replicas: canary_weight
+			.and(deployment.canary_ready_replicas)
+			.map(|ready| ready as u16),
+	};
+
+	if let Some(limit) = max_concurrent_requests {
+		let meter = global::meter("Patr API");
+		let attributes = [KeyValue::new("deployment.id", deployment_id.to_string())];
+
+		meter
+			.u64_gauge("deployment.concurrent_requests.limit")
+			.with_description("The maximum number of requests forwarded to a deployment at once")
+			.build()
+			.record(limit.into(), &attributes);
+		if let Some(concurrent_requests) = concurrent_requests {
+			meter
+				.u64_gauge("deployment.concurrent_requests")
+				.with_description("The number of requests being forwarded to a deployment")
+				.build()
+				.record(concurrent_requests.into(), &attributes);
+		}
+	}
+
+	if let Some(DeploymentCanaryRequests { primary, canary }) = requests {
+		query!(
+			r#"
+			INSERT INTO
+				deployment_canary_requests(
+					deployment_id,
+					primary_requests,
+					primary_server_errors,
+					primary_latency_millis,
+					canary_requests,
+					canary_server_errors,
+					canary_latency_millis
+				)
+			SELECT
+				id,
+				$2,
+				$3,
+				$4,
+				$5,
+				$6,
+				$7
+			FROM
+				deployment
+			WHERE
+				id = $1 AND
+				canary_image_tag IS NOT NULL
+			ON CONFLICT
+				(deployment_id)
+			DO UPDATE SET
+				primary_requests =
+					deployment_canary_requests.primary_requests + EXCLUDED.primary_requests,
+				primary_server_errors =
+					deployment_canary_requests.primary_server_errors +
+					EXCLUDED.primary_server_errors,
+				primary_latency_millis =
+					deployment_canary_requests.primary_latency_millis +
+					EXCLUDED.primary_latency_millis,
+				canary_requests =
+					deployment_canary_requests.canary_requests + EXCLUDED.canary_requests,
+				canary_server_errors =
+					deployment_canary_requests.canary_server_errors +
+					EXCLUDED.canary_server_errors,
+				canary_latency_millis =
+					deployment_canary_requests.canary_latency_millis +
+					EXCLUDED.canary_latency_millis;
+			"#,
+			deployment_id as _,
+			primary.requests as i64,
+			primary.server_errors as i64,
+			primary.total_latency_millis as i64,
+			canary.requests as i64,
+			canary.server_errors as i64,
+			canary.total_latency_millis as i64,
+		)
+		.execute(&mut **database)
+		.await?;
+	}
+
+	if deployment.scale_to_zero_after.is_none() {
+		return activity_response(activity);
+	}
+
+	query!(
+		r#"
+		UPDATE
+			deployment
+		SET
+			last_request_at = $1
+		WHERE
+			id = $2;
+		"#,
+		now,
+		deployment_id as _,
+	)
+	.execute(&mut **database)
+	.await?;
+
+	if deployment.status != DeploymentStatus::Cold {
+		return activity_response(activity);
+	}
+
+	// The deployment stays scaled down if its image isn't allowed, and the
+	// ingress serves the error page of a deployment that isn't running
+	let allowed = deployment_image_scanner::ensure_image_allowed(
+		&mut **database,
+		redis,
+		&config,
+		deployment.workspace_id.into(),
+		deployment_id,
+		false,
+		now,
+	)
+	.await;
+	match allowed {
+		Ok(()) => (),
+		Err(
+			err @ (ErrorType::ImageScanPending |
+			ErrorType::ImageScanBlocked |
+			ErrorType::ImageScanFailed),
+		) => {
+			info!("Not starting deployment `{deployment_id}` that was scaled to zero: {err}");
+			return activity_response(activity);
+		}
+		Err(err) => return Err(err),
+	}
+
+	info!("Starting deployment `{deployment_id}` that was scaled to zero");
+
+	query!(
+		r#"
+		UPDATE
+			deployment
+		SET
+			status = $1,
+			reconciliation_status = 'pending',
+			reconciliation_generation = reconciliation_generation + 1,
+			updated = $2
+		WHERE
+			id = $3;
+		"#,
+		DeploymentStatus::Deploying as _,
+		now,
+		deployment_id as _,
+	)
+	.execute(&mut **database)
+	.await?;
+
+	query!(
+		r#"
+		INSERT INTO
+			deployment_event(
+				id,
+				deployment_id,
+				event_type,
+				created
+			)
+		VALUES
+			($1, $2, $3, $4);
+		"#,
+		Uuid::new_v4() as _,
+		deployment_id as _,
+		DeploymentEventType::ColdStarted as _,
+		now,
+	)
+	.execute(&mut **database)
+	.await?;
+
+	runner::send_message(
+		redis,
+		&config.runner,
+		deployment.workspace_id.into(),
+		deployment.runner.into(),
+		&StreamRunnerDataForWorkspaceServerMsg::DeploymentReconciliationRequested {
+			id: deployment_id,
+		},
+	)
+	.await?;
+
+	activity_response(ReportDeploymentActivityResponse {
+		warming: true,
+		..activity
+	})
+}
+
+/// Creates the response for the activity reported on a deployment
+fn activity_response(
+	activity: ReportDeploymentActivityResponse,
+) -> Result<AppResponse<ReportDeploymentActivityRequest>, ErrorType> {
+	AppResponse::builder()
+		.body(activity)
+		.headers(())
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
@@ -0,0 +1,156 @@
+use axum::http::StatusCode;
+use models::{api::workspace::deployment::*, utils::constants};
+
+use crate::prelude::*;
+
+/// The handler for the runner to report how many replicas of a deployment are
+/// ready to receive requests. The readiness refines the status of the
+/// deployment, and is returned to the ingress when it reports activity on the
+/// deployment, so that it only forwards requests to replicas that are ready.
+/// The IDs of the replicas are stored as well, so that the logs of a single
+/// replica can be asked for.
+pub async fn report_deployment_readiness(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path:
+					ReportDeploymentReadinessPath {
+						workspace_id: _,
+						deployment_id,
+					},
+				query: (),
+				headers:
+					ReportDeploymentReadinessRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body:
+					ReportDeploymentReadinessRequestProcessed {
+						replicas,
+						canary_ready_replicas,
+						replica_ids,
+					},
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config: _,
+		user_data: _,
+		clock: _,
+	}: AuthenticatedAppRequest<'_, ReportDeploymentReadinessRequest>,
+) -> Result<AppResponse<ReportDeploymentReadinessRequest>, ErrorType> {
+	trace!(
+		"Deployment `{deployment_id}` has {}/{} replicas ready",
+		replicas.ready,
+		replicas.total
+	);
+
+	if !is_valid_readiness(replicas, canary_ready_replicas, &replica_ids) {
+		debug!("Invalid readiness reported for deployment `{deployment_id}`");
+		return Err(ErrorType::WrongParameters);
+	}
+
+	query!(
+		r#"
+		UPDATE
+			deployment
+		SET
+			ready_replicas = $1,
+			total_replicas = $2,
+			canary_ready_replicas = $3,
+			replica_ids = $4
+		WHERE
+			id = $5 AND
+			deleted IS NULL
+		RETURNING id;
+		"#,
+		i16::try_from(replicas.ready).map_err(ErrorType::server_error)?,
+		i16::try_from(replicas.total).map_err(ErrorType::server_error)?,
+		canary_ready_replicas
+			.map(i16::try_from)
+			.transpose()
+			.map_err(ErrorType::server_error)?,
+		&replica_ids,
+		deployment_id as _,
+	)
+	.fetch_optional(&mut **database)
+	.await?
+	.or_not_found()?;
+
+	AppResponse::builder()
+		.body(ReportDeploymentReadinessResponse)
+		.headers(())
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
+
+/// Checks if the readiness reported by a runner can be stored. The canary can
+/// have at most as many replicas as the deployment has in total, since they
+/// are taken out of its replicas. The same goes for the IDs of the replicas,
+/// which can't be empty.
+fn is_valid_readiness(
+	replicas: DeploymentReplicaReadiness,
+	canary_ready_replicas: Option<u16>,
+	replica_ids: &[String],
+) -> bool {
+	replicas.is_valid() &&
+		canary_ready_replicas.map_or(true, |ready| ready <= constants::MAX_REPORTED_REPLICAS) &&
+		replica_ids.len() <= usize::from(constants::MAX_REPORTED_REPLICAS) &&
+		replica_ids.iter().all(|replica_id| !replica_id.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn readiness_must_fit_the_number_of_replicas() {
+		let replicas = |ready, total| DeploymentReplicaReadiness { ready, total };
+
+		assert!(is_valid_readiness(replicas(0, 0), None, &[]));
+		assert!(is_valid_readiness(replicas(2, 3), Some(1), &[]));
+		assert!(is_valid_readiness(
+			replicas(
+				constants::MAX_REPORTED_REPLICAS,
+				constants::MAX_REPORTED_REPLICAS
+			),
+			Some(constants::MAX_REPORTED_REPLICAS),
+			&[]
+		));
+
+		assert!(!is_valid_readiness(replicas(3, 2), None, &[]));
+		// These would have wrapped around when stored
+		assert!(!is_valid_readiness(replicas(0, u16::MAX), None, &[]));
+		assert!(!is_valid_readiness(replicas(40000, 40000), None, &[]));
+		assert!(!is_valid_readiness(
+			replicas(1, 1),
+			Some(constants::MAX_REPORTED_REPLICAS + 1),
+			&[]
+		));
+	}
+
+	#[test]
+	fn replica_ids_must_fit_the_number_of_replicas() {
+		let replicas = DeploymentReplicaReadiness { ready: 1, total: 2 };
+		let replica_ids = |count| {
+			(0..count)
+				.map(|index| format!("deployment-{index}"))
+				.collect::<Vec<_>>()
+		};
+
+		assert!(is_valid_readiness(replicas, None, &replica_ids(2)));
+		assert!(is_valid_readiness(
+			replicas,
+			None,
+			&replica_ids(constants::MAX_REPORTED_REPLICAS)
+		));
+
+		assert!(!is_valid_readiness(
+			replicas,
+			None,
+			&replica_ids(constants::MAX_REPORTED_REPLICAS + 1)
+		));
+		assert!(!is_valid_readiness(replicas, None, &[String::new()]));
+	}
+}
@@ -0,0 +1,98 @@
+use axum::http::StatusCode;
+use models::api::workspace::deployment::*;
+use time::OffsetDateTime;
+
+use crate::prelude::*;
+
+/// The handler for the runner to report the result of reconciling a
+/// deployment. The deployment is in sync if the runner did not report an
+/// error, and failed otherwise. Reports for a generation of the deployment
+/// that has been changed since are ignored, so that a runner that was slow to
+/// report doesn't mark changes it hasn't seen yet as applied.
+pub async fn report_deployment_reconciliation(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path:
+					ReportDeploymentReconciliationPath {
+						workspace_id: _,
+						deployment_id,
+					},
+				query: (),
+				headers:
+					ReportDeploymentReconciliationRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: ReportDeploymentReconciliationRequestProcessed { error, generation },
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config: _,
+		user_data: _,
+		clock: _,
+	}: AuthenticatedAppRequest<'_, ReportDeploymentReconciliationRequest>,
+) -> Result<AppResponse<ReportDeploymentReconciliationRequest>, ErrorType> {
+	info!("Reporting the reconciliation of deployment `{deployment_id}`");
+
+	let state = if error.is_some() {
+		DeploymentReconciliationState::Failed
+	} else {
+		DeploymentReconciliationState::InSync
+	};
+
+	let current_generation = query!(
+		r#"
+		SELECT
+			reconciliation_generation
+		FROM
+			deployment
+		WHERE
+			id = $1 AND
+			deleted IS NULL
+		FOR UPDATE;
+		"#,
+		deployment_id as _,
+	)
+	.fetch_optional(&mut **database)
+	.await?
+	.or_not_found()?
+	.reconciliation_generation;
+
+	if u64::try_from(current_generation).ok() != Some(generation) {
+		info!("Deployment `{deployment_id}` changed since generation {generation}. Ignoring");
+		return AppResponse::builder()
+			.body(ReportDeploymentReconciliationResponse)
+			.headers(())
+			.status_code(StatusCode::OK)
+			.build()
+			.into_result();
+	}
+
+	query!(
+		r#"
+		UPDATE
+			deployment
+		SET
+			reconciliation_status = $1,
+			reconciliation_error = $2,
+			last_reconciliation_attempt = $3
+		WHERE
+			id = $4;
+		"#,
+		state as _,
+		error,
+		OffsetDateTime::now_utc(),
+		deployment_id as _,
+	)
+	.execute(&mut **database)
+	.await?;
+
+	AppResponse::builder()
+		.body(ReportDeploymentReconciliationResponse)
+		.headers(())
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
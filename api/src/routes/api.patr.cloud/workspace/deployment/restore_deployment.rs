@@ -0,0 +1,151 @@
+use axum::http::StatusCode;
+use models::api::workspace::{deployment::*, runner::StreamRunnerDataForWorkspaceServerMsg};
+
+use crate::{prelude::*, utils::runner};
+
+/// The handler to restore a deleted deployment, as long as it has not been
+/// permanently removed yet. The resource of the deployment is registered again,
+/// and the runner is asked to apply the deployment with the configuration it
+/// had when it was deleted.
+pub async fn restore_deployment(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: RestoreDeploymentPath {
+					workspace_id,
+					deployment_id,
+				},
+				query: (),
+				headers:
+					RestoreDeploymentRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: RestoreDeploymentRequestProcessed,
+			},
+		database,
+		redis,
+		client_ip: _,
+		config,
+		user_data: _,
+		clock,
+	}: AuthenticatedAppRequest<'_, RestoreDeploymentRequest>,
+) -> Result<AppResponse<RestoreDeploymentRequest>, ErrorType> {
+	info!("Restoring deployment: {deployment_id}");
+
+	let runner = query!(
+		r#"
+		SELECT
+			deployment.runner
+		FROM
+			deployment
+		INNER JOIN
+			runner
+		ON
+			runner.id = deployment.runner
+		WHERE
+			deployment.id = $1 AND
+			deployment.workspace_id = $2 AND
+			deployment.deleted IS NOT NULL AND
+			deployment.deleted > $3 AND
+			runner.deleted IS NULL;
+		"#,
+		deployment_id as _,
+		workspace_id as _,
+		crate::deployment_purger::purge_cutoff(clock.now()),
+	)
+	.fetch_optional(&mut **database)
+	.await?
+	.or_not_found()?
+	.runner;
+
+	query!(
+		r#"
+		SET CONSTRAINTS ALL DEFERRED;
+		"#
+	)
+	.execute(&mut **database)
+	.await?;
+
+	// Another deployment could have been created with the same name since this
+	// one was deleted
+	query!(
+		r#"
+		UPDATE
+			deployment
+		SET
+			deleted = NULL,
+			reconciliation_status = 'pending',
+			reconciliation_generation = reconciliation_generation + 1,
+			reconciliation_error = NULL,
+			updated = NOW()
+		WHERE
+			id = $1;
+		"#,
+		deployment_id as _,
+	)
+	.execute(&mut **database)
+	.await
+	.map_err(|err| match err {
+		sqlx::Error::Database(err) if err.is_unique_violation() => ErrorType::ResourceAlreadyExists,
+		err => ErrorType::server_error(err),
+	})?;
+
+	query!(
+		r#"
+		UPDATE
+			resource
+		SET
+			deleted = NULL
+		WHERE
+			id = $1;
+		"#,
+		deployment_id as _,
+	)
+	.execute(&mut **database)
+	.await?;
+
+	query!(
+		r#"
+		SET CONSTRAINTS ALL IMMEDIATE;
+		"#
+	)
+	.execute(&mut **database)
+	.await?;
+
+	// The deployments it depended on could have been deleted since, in which
+	// case the deployment doesn't depend on them anymore
+	query!(
+		r#"
+		DELETE FROM
+			deployment_dependency
+		USING
+			deployment
+		WHERE
+			deployment_dependency.deployment_id = $1 AND
+			deployment.id = deployment_dependency.dependency_id AND
+			deployment.deleted IS NOT NULL;
+		"#,
+		deployment_id as _,
+	)
+	.execute(&mut **database)
+	.await?;
+
+	runner::send_message(
+		redis,
+		&config.runner,
+		workspace_id,
+		runner.into(),
+		&StreamRunnerDataForWorkspaceServerMsg::DeploymentReconciliationRequested {
+			id: deployment_id,
+		},
+	)
+	.await?;
+
+	AppResponse::builder()
+		.body(RestoreDeploymentResponse)
+		.headers(())
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
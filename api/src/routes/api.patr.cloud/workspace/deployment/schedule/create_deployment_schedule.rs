@@ -0,0 +1,87 @@
+use axum::http::StatusCode;
+use models::api::workspace::deployment::schedule::*;
+use time::OffsetDateTime;
+
+use crate::prelude::*;
+
+/// The handler to create a schedule for a deployment. The cron expressions and
+/// the timezone are validated here, so that invalid schedules are never stored.
+pub async fn create_deployment_schedule(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: CreateDeploymentSchedulePath {
+					workspace_id,
+					deployment_id,
+				},
+				query: (),
+				headers:
+					CreateDeploymentScheduleRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body:
+					CreateDeploymentScheduleRequestProcessed {
+						start_cron,
+						stop_cron,
+						timezone,
+						conflict_resolution,
+					},
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config: _,
+		user_data: _,
+		clock: _,
+	}: AuthenticatedAppRequest<'_, CreateDeploymentScheduleRequest>,
+) -> Result<AppResponse<CreateDeploymentScheduleRequest>, ErrorType> {
+	info!("Creating schedule for deployment `{deployment_id}`");
+
+	super::validate_schedule(start_cron.as_deref(), stop_cron.as_deref(), &timezone)?;
+	super::ensure_deployment_exists(&mut **database, workspace_id, deployment_id).await?;
+
+	let schedule_id = query!(
+		r#"
+		INSERT INTO
+			deployment_schedule(
+				id,
+				deployment_id,
+				start_cron,
+				stop_cron,
+				timezone,
+				conflict_resolution,
+				created
+			)
+		VALUES
+			(
+				gen_random_uuid(),
+				$1,
+				$2,
+				$3,
+				$4,
+				$5,
+				$6
+			)
+		RETURNING id;
+		"#,
+		deployment_id as _,
+		start_cron,
+		stop_cron,
+		timezone,
+		conflict_resolution as _,
+		OffsetDateTime::now_utc(),
+	)
+	.fetch_one(&mut **database)
+	.await?
+	.id;
+
+	AppResponse::builder()
+		.body(CreateDeploymentScheduleResponse {
+			id: WithId::from(schedule_id),
+		})
+		.headers(())
+		.status_code(StatusCode::CREATED)
+		.build()
+		.into_result()
+}
@@ -0,0 +1,63 @@
+use axum::http::StatusCode;
+use models::api::workspace::deployment::schedule::*;
+
+use crate::prelude::*;
+
+/// The handler to delete a schedule of a deployment. The deployment itself is
+/// left in whatever state it currently is in.
+pub async fn delete_deployment_schedule(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path:
+					DeleteDeploymentSchedulePath {
+						workspace_id,
+						deployment_id,
+						schedule_id,
+					},
+				query: (),
+				headers:
+					DeleteDeploymentScheduleRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: DeleteDeploymentScheduleRequestProcessed,
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config: _,
+		user_data: _,
+		clock: _,
+	}: AuthenticatedAppRequest<'_, DeleteDeploymentScheduleRequest>,
+) -> Result<AppResponse<DeleteDeploymentScheduleRequest>, ErrorType> {
+	info!("Deleting schedule `{schedule_id}` of deployment `{deployment_id}`");
+
+	super::ensure_deployment_exists(&mut **database, workspace_id, deployment_id).await?;
+
+	let rows_affected = query!(
+		r#"
+		DELETE FROM
+			deployment_schedule
+		WHERE
+			id = $1 AND
+			deployment_id = $2;
+		"#,
+		schedule_id as _,
+		deployment_id as _,
+	)
+	.execute(&mut **database)
+	.await?
+	.rows_affected();
+
+	if rows_affected == 0 {
+		return Err(ErrorType::ResourceDoesNotExist);
+	}
+
+	AppResponse::builder()
+		.body(DeleteDeploymentScheduleResponse)
+		.headers(())
+		.status_code(StatusCode::RESET_CONTENT)
+		.build()
+		.into_result()
+}
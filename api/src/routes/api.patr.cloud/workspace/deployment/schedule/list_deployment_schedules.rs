@@ -0,0 +1,74 @@
+use axum::http::StatusCode;
+use models::api::workspace::deployment::schedule::*;
+
+use crate::prelude::*;
+
+/// The handler to list all the schedules of a deployment
+pub async fn list_deployment_schedules(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: ListDeploymentSchedulesPath {
+					workspace_id,
+					deployment_id,
+				},
+				query: (),
+				headers:
+					ListDeploymentSchedulesRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: ListDeploymentSchedulesRequestProcessed,
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config: _,
+		user_data: _,
+		clock: _,
+	}: AuthenticatedAppRequest<'_, ListDeploymentSchedulesRequest>,
+) -> Result<AppResponse<ListDeploymentSchedulesRequest>, ErrorType> {
+	info!("Listing schedules of deployment `{deployment_id}`");
+
+	super::ensure_deployment_exists(&mut **database, workspace_id, deployment_id).await?;
+
+	let schedules = query!(
+		r#"
+		SELECT
+			id,
+			start_cron,
+			stop_cron,
+			timezone,
+			conflict_resolution AS "conflict_resolution: ScheduleConflictResolution"
+		FROM
+			deployment_schedule
+		WHERE
+			deployment_id = $1
+		ORDER BY
+			created;
+		"#,
+		deployment_id as _,
+	)
+	.fetch_all(&mut **database)
+	.await?
+	.into_iter()
+	.map(|row| {
+		WithId::new(
+			row.id,
+			DeploymentSchedule {
+				start_cron: row.start_cron,
+				stop_cron: row.stop_cron,
+				timezone: row.timezone,
+				conflict_resolution: row.conflict_resolution,
+			},
+		)
+	})
+	.collect();
+
+	AppResponse::builder()
+		.body(ListDeploymentSchedulesResponse { schedules })
+		.headers(())
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
@@ -0,0 +1,47 @@
+use axum::Router;
+
+use super::ensure_deployment_exists;
+use crate::{prelude::*, utils::CronExpression};
+
+mod create_deployment_schedule;
+mod delete_deployment_schedule;
+mod list_deployment_schedules;
+mod update_deployment_schedule;
+
+use self::{
+	create_deployment_schedule::*,
+	delete_deployment_schedule::*,
+	list_deployment_schedules::*,
+	update_deployment_schedule::*,
+};
+
+#[instrument(skip(state))]
+pub async fn setup_routes(state: &AppState) -> Router {
+	Router::new()
+		.mount_auth_endpoint(create_deployment_schedule, state)
+		.mount_auth_endpoint(delete_deployment_schedule, state)
+		.mount_auth_endpoint(list_deployment_schedules, state)
+		.mount_auth_endpoint(update_deployment_schedule, state)
+}
+
+/// Validates the cron expressions and the timezone of a schedule, so that the
+/// scheduler never has to deal with a schedule that it cannot evaluate
+fn validate_schedule(
+	start_cron: Option<&str>,
+	stop_cron: Option<&str>,
+	timezone: &str,
+) -> Result<(), ErrorType> {
+	if start_cron.is_none() && stop_cron.is_none() {
+		return Err(ErrorType::WrongParameters);
+	}
+
+	for cron in start_cron.into_iter().chain(stop_cron) {
+		cron.parse::<CronExpression>()?;
+	}
+
+	if time_tz::timezones::get_by_name(timezone).is_none() {
+		return Err(ErrorType::WrongParameters);
+	}
+
+	Ok(())
+}
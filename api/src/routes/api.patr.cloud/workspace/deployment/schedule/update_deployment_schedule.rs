@@ -0,0 +1,107 @@
+use axum::http::StatusCode;
+use models::api::workspace::deployment::schedule::*;
+
+use crate::prelude::*;
+
+/// The handler to update a schedule of a deployment. The updated schedule is
+/// validated as a whole, so that it is never left without any action or with
+/// an invalid cron expression.
+pub async fn update_deployment_schedule(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path:
+					UpdateDeploymentSchedulePath {
+						workspace_id,
+						deployment_id,
+						schedule_id,
+					},
+				query: (),
+				headers:
+					UpdateDeploymentScheduleRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body:
+					UpdateDeploymentScheduleRequestProcessed {
+						start_cron,
+						stop_cron,
+						timezone,
+						conflict_resolution,
+					},
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config: _,
+		user_data: _,
+		clock: _,
+	}: AuthenticatedAppRequest<'_, UpdateDeploymentScheduleRequest>,
+) -> Result<AppResponse<UpdateDeploymentScheduleRequest>, ErrorType> {
+	info!("Updating schedule `{schedule_id}` of deployment `{deployment_id}`");
+
+	super::ensure_deployment_exists(&mut **database, workspace_id, deployment_id).await?;
+
+	let schedule = query!(
+		r#"
+		SELECT
+			start_cron,
+			stop_cron,
+			timezone
+		FROM
+			deployment_schedule
+		WHERE
+			id = $1 AND
+			deployment_id = $2
+		FOR UPDATE;
+		"#,
+		schedule_id as _,
+		deployment_id as _,
+	)
+	.fetch_optional(&mut **database)
+	.await?
+	.or_not_found()?;
+
+	// An empty cron expression removes that action from the schedule
+	let start_cron = match start_cron {
+		Some(cron) if cron.is_empty() => None,
+		Some(cron) => Some(cron),
+		None => schedule.start_cron,
+	};
+	let stop_cron = match stop_cron {
+		Some(cron) if cron.is_empty() => None,
+		Some(cron) => Some(cron),
+		None => schedule.stop_cron,
+	};
+	let timezone = timezone.unwrap_or(schedule.timezone);
+
+	super::validate_schedule(start_cron.as_deref(), stop_cron.as_deref(), &timezone)?;
+
+	query!(
+		r#"
+		UPDATE
+			deployment_schedule
+		SET
+			start_cron = $1,
+			stop_cron = $2,
+			timezone = $3,
+			conflict_resolution = COALESCE($4, conflict_resolution)
+		WHERE
+			id = $5;
+		"#,
+		start_cron,
+		stop_cron,
+		timezone,
+		conflict_resolution as _,
+		schedule_id as _,
+	)
+	.execute(&mut **database)
+	.await?;
+
+	AppResponse::builder()
+		.body(UpdateDeploymentScheduleResponse)
+		.headers(())
+		.status_code(StatusCode::ACCEPTED)
+		.build()
+		.into_result()
+}
@@ -0,0 +1,71 @@
+use axum::http::StatusCode;
+use models::api::workspace::deployment::*;
+
+use crate::prelude::*;
+
+/// The handler to set the default machine type of a workspace. The machine
+/// type must be one of the available machine types. Setting it to `None`
+/// removes the default, after which new deployments must specify a machine
+/// type explicitly.
+pub async fn set_default_machine_type(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: SetDefaultDeploymentMachineTypePath { workspace_id },
+				query: (),
+				headers:
+					SetDefaultDeploymentMachineTypeRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: SetDefaultDeploymentMachineTypeRequestProcessed { machine_type_id },
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config: _,
+		user_data: _,
+		clock: _,
+	}: AuthenticatedAppRequest<'_, SetDefaultDeploymentMachineTypeRequest>,
+) -> Result<AppResponse<SetDefaultDeploymentMachineTypeRequest>, ErrorType> {
+	info!("Setting the default machine type of workspace `{workspace_id}`");
+
+	if let Some(machine_type_id) = machine_type_id {
+		query!(
+			r#"
+			SELECT
+				id
+			FROM
+				deployment_machine_type
+			WHERE
+				id = $1;
+			"#,
+			machine_type_id as _,
+		)
+		.fetch_optional(&mut **database)
+		.await?
+		.ok_or(ErrorType::ResourceDoesNotExist)?;
+	}
+
+	query!(
+		r#"
+		UPDATE
+			workspace
+		SET
+			default_machine_type_id = $1
+		WHERE
+			id = $2;
+		"#,
+		machine_type_id as _,
+		workspace_id as _,
+	)
+	.execute(&mut **database)
+	.await?;
+
+	AppResponse::builder()
+		.body(SetDefaultDeploymentMachineTypeResponse)
+		.headers(())
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
@@ -0,0 +1,163 @@
+use axum::http::StatusCode;
+use models::api::workspace::{deployment::*, runner::StreamRunnerDataForWorkspaceServerMsg};
+
+use crate::{deployment_image_scanner, prelude::*, utils::runner};
+
+/// The handler to start a canary of a deployment, or to change the image tag
+/// or the weight of its existing canary. The canary runs alongside the primary
+/// replicas of the deployment, so deployments with volumes can't have one,
+/// since their volumes can only be attached to one replica. The replicas of the
+/// canary are taken out of the maximum replicas of the deployment, so the
+/// deployment has to be able to run at least two replicas. The runner of the
+/// deployment is asked to reconcile it right away, and the ingress picks up
+/// the new weight once its cached activity report expires. The image of the
+/// canary has to be allowed under the image scan policy of the workspace.
+///
+/// Changing the image tag of the canary starts a new canary, so the requests
+/// counted for the old one are reset.
+pub async fn set_deployment_canary(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: SetDeploymentCanaryPath {
+					workspace_id,
+					deployment_id,
+				},
+				query: (),
+				headers:
+					SetDeploymentCanaryRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: SetDeploymentCanaryRequestProcessed { image_tag, weight },
+			},
+		database,
+		redis,
+		client_ip: _,
+		config,
+		user_data: _,
+		clock,
+	}: AuthenticatedAppRequest<'_, SetDeploymentCanaryRequest>,
+) -> Result<AppResponse<SetDeploymentCanaryRequest>, ErrorType> {
+	info!("Setting canary `{image_tag}` with weight {weight} on deployment `{deployment_id}`");
+
+	let deployment = query!(
+		r#"
+		SELECT
+			image_tag,
+			canary_image_tag,
+			min_horizontal_scale,
+			max_horizontal_scale,
+			runner,
+			EXISTS(
+				SELECT
+					1
+				FROM
+					deployment_volume_mount
+				WHERE
+					deployment_id = deployment.id
+			) AS "has_volumes!"
+		FROM
+			deployment
+		WHERE
+			id = $1 AND
+			deleted IS NULL
+		FOR UPDATE;
+		"#,
+		deployment_id as _,
+	)
+	.fetch_optional(&mut **database)
+	.await?
+	.or_not_found()?;
+
+	// A canary of the same tag would run the same image as the deployment
+	if image_tag.is_empty() || image_tag == deployment.image_tag {
+		return Err(ErrorType::WrongParameters);
+	}
+
+	if deployment.has_volumes {
+		return Err(ErrorType::CannotScaleWithVolume);
+	}
+
+	let canary = DeploymentCanary { image_tag, weight };
+	let replicas = canary.replicas(
+		deployment.min_horizontal_scale as u16,
+		deployment.max_horizontal_scale as u16,
+	);
+	if replicas.is_none() {
+		return Err(ErrorType::WrongParameters);
+	}
+
+	// The readiness of the old canary doesn't say anything about the new one,
+	// so requests aren't sent to it until its runner reports that it's ready
+	let is_new_canary = deployment.canary_image_tag.as_ref() != Some(&canary.image_tag);
+
+	query!(
+		r#"
+		UPDATE
+			deployment
+		SET
+			canary_image_tag = $1,
+			canary_weight = $2,
+			canary_ready_replicas = CASE
+				WHEN $3 THEN NULL
+				ELSE canary_ready_replicas
+			END,
+			reconciliation_status = 'pending',
+			reconciliation_generation = reconciliation_generation + 1,
+			updated = $4
+		WHERE
+			id = $5;
+		"#,
+		canary.image_tag,
+		i16::from(canary.weight),
+		is_new_canary,
+		clock.now(),
+		deployment_id as _,
+	)
+	.execute(&mut **database)
+	.await?;
+
+	if is_new_canary {
+		query!(
+			r#"
+			DELETE FROM
+				deployment_canary_requests
+			WHERE
+				deployment_id = $1;
+			"#,
+			deployment_id as _,
+		)
+		.execute(&mut **database)
+		.await?;
+	}
+
+	deployment_image_scanner::ensure_image_allowed(
+		&mut **database,
+		redis,
+		&config,
+		workspace_id,
+		deployment_id,
+		true,
+		clock.now(),
+	)
+	.await?;
+
+	runner::send_message(
+		redis,
+		&config.runner,
+		workspace_id,
+		deployment.runner.into(),
+		&StreamRunnerDataForWorkspaceServerMsg::DeploymentReconciliationRequested {
+			id: deployment_id,
+		},
+	)
+	.await?;
+
+	AppResponse::builder()
+		.body(SetDeploymentCanaryResponse)
+		.headers(())
+		.status_code(StatusCode::ACCEPTED)
+		.build()
+		.into_result()
+}
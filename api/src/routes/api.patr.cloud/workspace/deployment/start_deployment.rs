@@ -1,18 +1,21 @@
 use axum::http::StatusCode;
-use models::api::workspace::deployment::*;
+use models::api::workspace::{deployment::*, runner::StreamRunnerDataForWorkspaceServerMsg};
 use time::OffsetDateTime;
 
-use crate::prelude::*;
+use crate::{deployment_image_scanner, prelude::*, utils::runner};
 
 /// The handler to start a deployment in the workspace. This will start
 /// the deployment. In case the deployment is already running, it will
-/// do nothing.
+/// do nothing. The runner of the deployment is asked to reconcile it right
+/// away, once the deployment is started. If the workspace blocks images with
+/// critical vulnerabilities, the image has to have been scanned before the
+/// deployment can be started.
 pub async fn start_deployment(
 	AuthenticatedAppRequest {
 		request:
 			ProcessedApiRequest {
 				path: StartDeploymentPath {
-					workspace_id: _,
+					workspace_id,
 					deployment_id,
 				},
 				query: StartDeploymentQuery { force_restart },
@@ -24,17 +27,18 @@ pub async fn start_deployment(
 				body: StartDeploymentRequestProcessed,
 			},
 		database,
-		redis: _,
+		redis,
 		client_ip: _,
 		config,
 		user_data,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, StartDeploymentRequest>,
 ) -> Result<AppResponse<StartDeploymentRequest>, ErrorType> {
 	info!("Starting deployment: {}", deployment_id);
 
 	let now = OffsetDateTime::now_utc();
 
-	let (registry, image_tag, region) = query!(
+	let (registry, image_tag, runner) = query!(
 		r#"
 		SELECT
 			registry,
@@ -68,6 +72,17 @@ pub async fn start_deployment(
 	})
 	.ok_or(ErrorType::ResourceDoesNotExist)?;
 
+	deployment_image_scanner::ensure_image_allowed(
+		&mut **database,
+		redis,
+		&config,
+		workspace_id,
+		deployment_id,
+		false,
+		now,
+	)
+	.await?;
+
 	if let DeploymentRegistry::PatrRegistry { repository_id, .. } = &registry {
 		let digest = query!(
 			r#"
@@ -138,7 +153,10 @@ pub async fn start_deployment(
 		UPDATE
 			deployment
 		SET
-			status = $1
+			status = $1,
+			reconciliation_status = 'pending',
+			reconciliation_generation = reconciliation_generation + 1,
+			updated = NOW()
 		WHERE
 			id = $2;
 		"#,
@@ -148,6 +166,17 @@ pub async fn start_deployment(
 	.execute(&mut **database)
 	.await?;
 
+	runner::send_message(
+		redis,
+		&config.runner,
+		workspace_id,
+		runner.into(),
+		&StreamRunnerDataForWorkspaceServerMsg::DeploymentReconciliationRequested {
+			id: deployment_id,
+		},
+	)
+	.await?;
+
 	AppResponse::builder()
 		.body(StartDeploymentResponse)
 		.headers(())
@@ -1,17 +1,18 @@
 use axum::http::StatusCode;
-use models::api::workspace::deployment::*;
+use models::api::workspace::{deployment::*, runner::StreamRunnerDataForWorkspaceServerMsg};
 
-use crate::prelude::*;
+use crate::{prelude::*, utils::runner};
 
 /// The handler to stop a deployment in the workspace. This will stop
 /// the deployment. In case the deployment is already stopped, it will
-/// do nothing.
+/// do nothing. The runner of the deployment is asked to reconcile it right
+/// away, once the deployment is stopped.
 pub async fn stop_deployment(
 	AuthenticatedAppRequest {
 		request:
 			ProcessedApiRequest {
 				path: StopDeploymentPath {
-					workspace_id: _,
+					workspace_id,
 					deployment_id,
 				},
 				query: _,
@@ -23,28 +24,46 @@ pub async fn stop_deployment(
 				body: StopDeploymentRequestProcessed,
 			},
 		database,
-		redis: _,
+		redis,
 		client_ip: _,
-		config: _,
+		config,
 		user_data: _,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, StopDeploymentRequest>,
 ) -> Result<AppResponse<StopDeploymentRequest>, ErrorType> {
 	info!("Starting: Stop deployment");
 
 	// Updating deployment status
-	query!(
+	let runner = query!(
 		r#"
 		UPDATE
 			deployment
 		SET
-			status = $1
+			status = $1,
+			reconciliation_status = 'pending',
+			reconciliation_generation = reconciliation_generation + 1,
+			updated = NOW()
 		WHERE
-			id = $2;
+			id = $2
+		RETURNING runner;
 		"#,
 		DeploymentStatus::Stopped as _,
 		deployment_id as _
 	)
-	.execute(&mut **database)
+	.fetch_optional(&mut **database)
+	.await?
+	.or_not_found()?
+	.runner;
+
+	runner::send_message(
+		redis,
+		&config.runner,
+		workspace_id,
+		runner.into(),
+		&StreamRunnerDataForWorkspaceServerMsg::DeploymentReconciliationRequested {
+			id: deployment_id,
+		},
+	)
 	.await?;
 
 	AppResponse::builder()
@@ -3,7 +3,7 @@ use axum::{
 	response::IntoResponse,
 };
 use axum_typed_websockets::Message;
-use futures::StreamExt;
+use futures::{stream, Stream, StreamExt};
 use models::{
 	api::workspace::deployment::*,
 	utils::{GenericResponse, WebSocketUpgrade},
@@ -13,7 +13,8 @@ use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
 use tokio_tungstenite::tungstenite::{client::IntoClientRequest, Message as RawMessage};
 
-use crate::prelude::*;
+use super::get_deployment_logs::{ensure_replica_exists, DeploymentLogStream};
+use crate::{prelude::*, utils::config::AppConfig};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -28,7 +29,9 @@ struct LokiStreams {
 }
 
 /// Route to stream the logs of a deployment. This will stream logs from Loki
-/// and return them to the user. The logs can be filtered by the start time.
+/// and return them to the user. The logs can be filtered by the start time, and
+/// logs that are JSON objects can optionally be parsed. If a replica is given,
+/// only the logs of that replica are streamed.
 pub async fn stream_deployment_logs(
 	AuthenticatedAppRequest {
 		request:
@@ -37,7 +40,12 @@ pub async fn stream_deployment_logs(
 					workspace_id,
 					deployment_id,
 				},
-				query: StreamDeploymentLogsQuery { start_time },
+				query:
+					StreamDeploymentLogsQuery {
+						start_time,
+						parse_json,
+						replica_id,
+					},
 				headers:
 					StreamDeploymentLogsRequestHeaders {
 						authorization: _,
@@ -50,6 +58,7 @@ pub async fn stream_deployment_logs(
 		client_ip: _,
 		config,
 		user_data: _,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, StreamDeploymentLogsRequest>,
 ) -> Result<AppResponse<StreamDeploymentLogsRequest>, ErrorType> {
 	info!("Streaming logs for deployment: {}", deployment_id);
@@ -70,6 +79,66 @@ pub async fn stream_deployment_logs(
 	.await?
 	.ok_or(ErrorType::ResourceDoesNotExist)?;
 
+	if let Some(replica_id) = &replica_id {
+		ensure_replica_exists(&mut **database, workspace_id, deployment_id, replica_id).await?;
+	}
+
+	let mut logs = tail_loki_logs(
+		&config,
+		workspace_id,
+		DeploymentLogStream::Container.selector(deployment_id, replica_id.as_deref()),
+		start_time,
+	)
+	.await?
+	.map(move |mut logs| {
+		if parse_json {
+			for log in &mut logs {
+				log.parsed = ParsedDeploymentLog::parse(&log.log);
+			}
+		}
+		logs
+	})
+	.boxed();
+
+	AppResponse::builder()
+		.body(GenericResponse(
+			upgrade
+				.on_upgrade(move |mut websocket| async move {
+					while let Some(logs) = logs.next().await {
+						let Ok(()) = websocket
+							.send(Message::Item(StreamDeploymentLogsServerMsg::LogData {
+								logs,
+							}))
+							.await
+							.inspect_err(|err| {
+								debug!("Failed to send logs to client: {}", err);
+							})
+						else {
+							break;
+						};
+					}
+					_ = websocket.send(Message::Close(None)).await;
+					_ = websocket.close().await;
+				})
+				.into_response(),
+		))
+		.headers(())
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
+
+/// Tails the logs matching the given Loki label query (for example,
+/// `{deploymentId="..."}`) in the given workspace, starting from the given time
+/// (or now, if not given). Each item of the returned stream is a batch of new
+/// logs. The stream ends when Loki closes the connection or sends something
+/// that can't be parsed.
+pub(super) async fn tail_loki_logs(
+	config: &AppConfig,
+	workspace_id: Uuid,
+	label_query: String,
+	start_time: Option<OffsetDateTime>,
+) -> Result<impl Stream<Item = Vec<DeploymentLog>> + Send, ErrorType> {
 	let mut client_request = Uri::builder()
 		.scheme(
 			if config.opentelemetry.logs.endpoint.starts_with("https") {
@@ -88,13 +157,16 @@ pub async fn stream_deployment_logs(
 		)
 		.path_and_query(format!(
 			"/loki/api/v1/tail?{}",
-			serde_urlencoded::to_string(&[(
-				"start",
-				start_time
-					.unwrap_or(OffsetDateTime::now_utc())
-					.unix_timestamp_nanos()
-					.to_string(),
-			)])?
+			serde_urlencoded::to_string([
+				("query", label_query),
+				(
+					"start",
+					start_time
+						.unwrap_or(OffsetDateTime::now_utc())
+						.unix_timestamp_nanos()
+						.to_string(),
+				),
+			])?
 		))
 		.build()?
 		.into_client_request()?;
@@ -104,66 +176,46 @@ pub async fn stream_deployment_logs(
 	);
 	*client_request.method_mut() = Method::GET;
 
-	let (mut stream, _) = tokio_tungstenite::connect_async(client_request)
+	let (loki, _) = tokio_tungstenite::connect_async(client_request)
 		.await
 		.inspect_err(|err| error!("Failed to stream from Loki: {}", err))?;
 
-	AppResponse::builder()
-		.body(GenericResponse(
-			upgrade
-				.on_upgrade(move |mut websocket| async move {
-					while let Some(data) = stream.next().await {
-						let Ok(data) = data.inspect_err(|err| {
-							debug!("Failed to get data from Loki: {}", err);
-						}) else {
-							break;
-						};
+	Ok(stream::unfold(loki, |mut loki| async move {
+		loop {
+			let data = loki
+				.next()
+				.await?
+				.inspect_err(|err| {
+					debug!("Failed to get data from Loki: {}", err);
+				})
+				.ok()?;
 
-						let bytes = match data {
-							RawMessage::Text(text) => text.into_bytes(),
-							RawMessage::Binary(bin) => bin,
-							RawMessage::Close(_) => break,
-							_ => continue,
-						};
+			let bytes = match data {
+				RawMessage::Text(text) => text.into_bytes(),
+				RawMessage::Binary(bin) => bin,
+				RawMessage::Close(_) => return None,
+				_ => continue,
+			};
 
-						let Ok(message) = serde_json::from_slice::<LokiResponse>(&bytes)
-							.inspect_err(|err| {
-								debug!("Failed to parse Loki message: {}", err);
-							})
-						else {
-							break;
-						};
-
-						let logs = message
-							.streams
-							.values
-							.into_iter()
-							.map(|(timestamp, log)| DeploymentLog {
-								timestamp: OffsetDateTime::from_unix_timestamp_nanos(timestamp)
-									.unwrap_or(OffsetDateTime::UNIX_EPOCH),
-								log,
-							})
-							.collect();
+			let message = serde_json::from_slice::<LokiResponse>(&bytes)
+				.inspect_err(|err| {
+					debug!("Failed to parse Loki message: {}", err);
+				})
+				.ok()?;
 
-						let Ok(()) = websocket
-							.send(Message::Item(StreamDeploymentLogsServerMsg::LogData {
-								logs,
-							}))
-							.await
-							.inspect_err(|err| {
-								debug!("Failed to send logs to client: {}", err);
-							})
-						else {
-							break;
-						};
-					}
-					_ = websocket.send(Message::Close(None)).await;
-					_ = websocket.close().await;
+			let logs = message
+				.streams
+				.values
+				.into_iter()
+				.map(|(timestamp, log)| DeploymentLog {
+					timestamp: OffsetDateTime::from_unix_timestamp_nanos(timestamp)
+						.unwrap_or(OffsetDateTime::UNIX_EPOCH),
+					log,
+					parsed: None,
 				})
-				.into_response(),
-		))
-		.headers(())
-		.status_code(StatusCode::OK)
-		.build()
-		.into_result()
+				.collect();
+
+			return Some((logs, loki));
+		}
+	}))
 }
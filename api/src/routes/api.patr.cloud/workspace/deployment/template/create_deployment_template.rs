@@ -0,0 +1,133 @@
+use axum::http::StatusCode;
+use models::api::workspace::deployment::template::*;
+use time::OffsetDateTime;
+
+use crate::prelude::*;
+
+/// The handler to create a deployment template in the workspace. This will
+/// create a new template, and return the ID of the template.
+pub async fn create_deployment_template(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: CreateDeploymentTemplatePath { workspace_id },
+				query: (),
+				headers:
+					CreateDeploymentTemplateRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body:
+					CreateDeploymentTemplateRequestProcessed {
+						name,
+						machine_type,
+						environment_variables,
+						startup_probe,
+						liveness_probe,
+					},
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config: _,
+		user_data: _,
+		clock: _,
+	}: AuthenticatedAppRequest<'_, CreateDeploymentTemplateRequest>,
+) -> Result<AppResponse<CreateDeploymentTemplateRequest>, ErrorType> {
+	info!(
+		"Creating deployment template with name `{}` in workspace: {}",
+		name, workspace_id
+	);
+
+	let template_id = query!(
+		r#"
+		INSERT INTO
+			deployment_template(
+				id,
+				name,
+				workspace_id,
+				machine_type,
+				startup_probe_port,
+				startup_probe_path,
+				liveness_probe_port,
+				liveness_probe_path,
+				created
+			)
+		VALUES
+			(
+				gen_random_uuid(),
+				$1,
+				$2,
+				$3,
+				$4,
+				$5,
+				$6,
+				$7,
+				$8
+			)
+		RETURNING id;
+		"#,
+		name as _,
+		workspace_id as _,
+		machine_type as _,
+		startup_probe.as_ref().map(|probe| probe.port as i32),
+		startup_probe.as_ref().map(|probe| probe.path.as_str()),
+		liveness_probe.as_ref().map(|probe| probe.port as i32),
+		liveness_probe.as_ref().map(|probe| probe.path.as_str()),
+		OffsetDateTime::now_utc() as _,
+	)
+	.fetch_one(&mut **database)
+	.await
+	.map_err(|err| match err {
+		sqlx::Error::Database(err) if err.is_unique_violation() => ErrorType::ResourceAlreadyExists,
+		sqlx::Error::Database(err) if err.is_foreign_key_violation() => ErrorType::WrongParameters,
+		err => ErrorType::server_error(err),
+	})?
+	.id;
+
+	query!(
+		r#"
+		INSERT INTO
+			deployment_template_environment_variable(
+				template_id,
+				name,
+				value,
+				secret_id
+			)
+		VALUES
+			(
+				UNNEST($1::UUID[]),
+				UNNEST($2::TEXT[]),
+				UNNEST($3::TEXT[]),
+				UNNEST($4::UUID[])
+			);
+		"#,
+		&environment_variables
+			.iter()
+			.map(|_| template_id)
+			.collect::<Vec<_>>(),
+		&environment_variables
+			.iter()
+			.map(|(name, _)| name.clone())
+			.collect::<Vec<_>>(),
+		&environment_variables
+			.iter()
+			.map(|(_, value)| value.value().cloned())
+			.collect::<Vec<Option<String>>>() as _,
+		&environment_variables
+			.iter()
+			.map(|(_, value)| value.secret_id().map(Into::into))
+			.collect::<Vec<Option<sqlx::types::Uuid>>>() as _,
+	)
+	.execute(&mut **database)
+	.await?;
+
+	AppResponse::builder()
+		.body(CreateDeploymentTemplateResponse {
+			id: WithId::from(template_id),
+		})
+		.headers(())
+		.status_code(StatusCode::CREATED)
+		.build()
+		.into_result()
+}
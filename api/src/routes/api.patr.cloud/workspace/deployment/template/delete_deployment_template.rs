@@ -0,0 +1,60 @@
+use axum::http::StatusCode;
+use models::api::workspace::deployment::template::*;
+
+use crate::prelude::*;
+
+/// The handler to delete a deployment template. The values of a template are
+/// copied into a deployment when it is created, so deployments that were
+/// created from this template are not affected.
+pub async fn delete_deployment_template(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: DeleteDeploymentTemplatePath {
+					workspace_id,
+					template_id,
+				},
+				query: (),
+				headers:
+					DeleteDeploymentTemplateRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: DeleteDeploymentTemplateRequestProcessed,
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config: _,
+		user_data: _,
+		clock: _,
+	}: AuthenticatedAppRequest<'_, DeleteDeploymentTemplateRequest>,
+) -> Result<AppResponse<DeleteDeploymentTemplateRequest>, ErrorType> {
+	info!("Deleting deployment template ID: `{template_id}`");
+
+	let rows_affected = query!(
+		r#"
+		DELETE FROM
+			deployment_template
+		WHERE
+			id = $1 AND
+			workspace_id = $2;
+		"#,
+		template_id as _,
+		workspace_id as _,
+	)
+	.execute(&mut **database)
+	.await?
+	.rows_affected();
+
+	if rows_affected == 0 {
+		return Err(ErrorType::ResourceDoesNotExist);
+	}
+
+	AppResponse::builder()
+		.body(DeleteDeploymentTemplateResponse)
+		.headers(())
+		.status_code(StatusCode::RESET_CONTENT)
+		.build()
+		.into_result()
+}
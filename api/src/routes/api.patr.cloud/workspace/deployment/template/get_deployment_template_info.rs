@@ -0,0 +1,45 @@
+use axum::http::StatusCode;
+use models::api::workspace::deployment::template::*;
+
+use crate::prelude::*;
+
+/// The handler to get the details of a deployment template in the workspace.
+pub async fn get_deployment_template_info(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: GetDeploymentTemplateInfoPath {
+					workspace_id,
+					template_id,
+				},
+				query: (),
+				headers:
+					GetDeploymentTemplateInfoRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: GetDeploymentTemplateInfoRequestProcessed,
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config: _,
+		user_data: _,
+		clock: _,
+	}: AuthenticatedAppRequest<'_, GetDeploymentTemplateInfoRequest>,
+) -> Result<AppResponse<GetDeploymentTemplateInfoRequest>, ErrorType> {
+	trace!("Getting deployment template info: {}", template_id);
+
+	let template = super::get_deployment_template(&mut **database, workspace_id, template_id)
+		.await?
+		.ok_or(ErrorType::ResourceDoesNotExist)?;
+
+	AppResponse::builder()
+		.body(GetDeploymentTemplateInfoResponse {
+			template: WithId::new(template_id, template),
+		})
+		.headers(())
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
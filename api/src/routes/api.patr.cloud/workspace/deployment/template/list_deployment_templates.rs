@@ -0,0 +1,138 @@
+use std::collections::BTreeMap;
+
+use axum::http::StatusCode;
+use models::{
+	api::workspace::deployment::{template::*, DeploymentProbe, EnvironmentVariableValue},
+	utils::TotalCountHeader,
+};
+
+use crate::prelude::*;
+
+/// The handler to list all the deployment templates in the workspace.
+pub async fn list_deployment_templates(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: ListDeploymentTemplatesPath { workspace_id },
+				query: Paginated {
+					data: (),
+					count,
+					page,
+				},
+				headers:
+					ListDeploymentTemplatesRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: ListDeploymentTemplatesRequestProcessed,
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config: _,
+		user_data: _,
+		clock: _,
+	}: AuthenticatedAppRequest<'_, ListDeploymentTemplatesRequest>,
+) -> Result<AppResponse<ListDeploymentTemplatesRequest>, ErrorType> {
+	trace!("Listing deployment templates in workspace ID: `{workspace_id}`");
+
+	let mut total_count = 0;
+	let rows = query!(
+		r#"
+		SELECT
+			id,
+			name::TEXT AS "name!",
+			machine_type,
+			startup_probe_port,
+			startup_probe_path,
+			liveness_probe_port,
+			liveness_probe_path,
+			COUNT(*) OVER() AS "total_count!"
+		FROM
+			deployment_template
+		WHERE
+			workspace_id = $1
+		ORDER BY
+			created DESC
+		LIMIT $2
+		OFFSET $3;
+		"#,
+		workspace_id as _,
+		count as i32,
+		(page * count) as i32
+	)
+	.fetch_all(&mut **database)
+	.await?;
+
+	let mut environment_variables = query!(
+		r#"
+		SELECT
+			template_id,
+			name,
+			value,
+			secret_id
+		FROM
+			deployment_template_environment_variable
+		WHERE
+			template_id = ANY($1);
+		"#,
+		&rows.iter().map(|row| row.id).collect::<Vec<_>>()
+	)
+	.fetch_all(&mut **database)
+	.await?
+	.into_iter()
+	.fold(
+		BTreeMap::<Uuid, BTreeMap<String, EnvironmentVariableValue>>::new(),
+		|mut map, env| {
+			let value = match (env.value, env.secret_id) {
+				(Some(value), None) => EnvironmentVariableValue::String(value),
+				(None, Some(secret_id)) => EnvironmentVariableValue::Secret {
+					from_secret: secret_id.into(),
+				},
+				_ => return map,
+			};
+			map.entry(env.template_id.into())
+				.or_default()
+				.insert(env.name, value);
+			map
+		},
+	);
+
+	let templates = rows
+		.into_iter()
+		.map(|row| {
+			total_count = row.total_count;
+			WithId::new(
+				row.id,
+				DeploymentTemplate {
+					name: row.name,
+					machine_type: row.machine_type.map(Into::into),
+					environment_variables: environment_variables
+						.remove(&row.id.into())
+						.unwrap_or_default(),
+					startup_probe: row.startup_probe_port.zip(row.startup_probe_path).map(
+						|(port, path)| DeploymentProbe {
+							port: port as u16,
+							path,
+						},
+					),
+					liveness_probe: row.liveness_probe_port.zip(row.liveness_probe_path).map(
+						|(port, path)| DeploymentProbe {
+							port: port as u16,
+							path,
+						},
+					),
+				},
+			)
+		})
+		.collect();
+
+	AppResponse::builder()
+		.body(ListDeploymentTemplatesResponse { templates })
+		.headers(ListDeploymentTemplatesResponseHeaders {
+			total_count: TotalCountHeader(total_count as _),
+		})
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
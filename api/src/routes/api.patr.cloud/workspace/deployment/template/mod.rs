@@ -0,0 +1,113 @@
+use axum::Router;
+use models::api::workspace::deployment::{
+	template::DeploymentTemplate,
+	DeploymentProbe,
+	EnvironmentVariableValue,
+};
+
+use crate::prelude::*;
+
+mod create_deployment_template;
+mod delete_deployment_template;
+mod get_deployment_template_info;
+mod list_deployment_templates;
+mod update_deployment_template;
+
+use self::{
+	create_deployment_template::*,
+	delete_deployment_template::*,
+	get_deployment_template_info::*,
+	list_deployment_templates::*,
+	update_deployment_template::*,
+};
+
+#[instrument(skip(state))]
+pub async fn setup_routes(state: &AppState) -> Router {
+	Router::new()
+		.mount_auth_endpoint(create_deployment_template, state)
+		.mount_auth_endpoint(delete_deployment_template, state)
+		.mount_auth_endpoint(get_deployment_template_info, state)
+		.mount_auth_endpoint(list_deployment_templates, state)
+		.mount_auth_endpoint(update_deployment_template, state)
+}
+
+/// Gets the deployment template with the given ID in the given workspace,
+/// along with its environment variables. Returns `None` if the template does
+/// not exist in the workspace.
+pub async fn get_deployment_template(
+	connection: &mut DatabaseConnection,
+	workspace_id: Uuid,
+	template_id: Uuid,
+) -> Result<Option<DeploymentTemplate>, ErrorType> {
+	let Some(template) = query!(
+		r#"
+		SELECT
+			name::TEXT AS "name!",
+			machine_type,
+			startup_probe_port,
+			startup_probe_path,
+			liveness_probe_port,
+			liveness_probe_path
+		FROM
+			deployment_template
+		WHERE
+			id = $1 AND
+			workspace_id = $2;
+		"#,
+		template_id as _,
+		workspace_id as _,
+	)
+	.fetch_optional(&mut *connection)
+	.await?
+	else {
+		return Ok(None);
+	};
+
+	let environment_variables = query!(
+		r#"
+		SELECT
+			name,
+			value,
+			secret_id
+		FROM
+			deployment_template_environment_variable
+		WHERE
+			template_id = $1;
+		"#,
+		template_id as _
+	)
+	.fetch_all(&mut *connection)
+	.await?
+	.into_iter()
+	.filter_map(|env| match (env.value, env.secret_id) {
+		(Some(value), None) => Some((env.name, EnvironmentVariableValue::String(value))),
+		(None, Some(secret_id)) => Some((
+			env.name,
+			EnvironmentVariableValue::Secret {
+				from_secret: secret_id.into(),
+			},
+		)),
+		_ => None,
+	})
+	.collect();
+
+	Ok(Some(DeploymentTemplate {
+		name: template.name,
+		machine_type: template.machine_type.map(Into::into),
+		environment_variables,
+		startup_probe: template
+			.startup_probe_port
+			.zip(template.startup_probe_path)
+			.map(|(port, path)| DeploymentProbe {
+				port: port as u16,
+				path,
+			}),
+		liveness_probe: template
+			.liveness_probe_port
+			.zip(template.liveness_probe_path)
+			.map(|(port, path)| DeploymentProbe {
+				port: port as u16,
+				path,
+			}),
+	}))
+}
@@ -0,0 +1,148 @@
+use axum::http::StatusCode;
+use models::api::workspace::deployment::template::*;
+
+use crate::prelude::*;
+
+/// The handler to update a deployment template. Deployments that were already
+/// created from this template keep their existing configuration.
+pub async fn update_deployment_template(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: UpdateDeploymentTemplatePath {
+					workspace_id,
+					template_id,
+				},
+				query: (),
+				headers:
+					UpdateDeploymentTemplateRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body:
+					UpdateDeploymentTemplateRequestProcessed {
+						name,
+						machine_type,
+						environment_variables,
+						startup_probe,
+						liveness_probe,
+					},
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config: _,
+		user_data: _,
+		clock: _,
+	}: AuthenticatedAppRequest<'_, UpdateDeploymentTemplateRequest>,
+) -> Result<AppResponse<UpdateDeploymentTemplateRequest>, ErrorType> {
+	info!("Updating deployment template ID: `{template_id}`");
+
+	query!(
+		r#"
+		UPDATE
+			deployment_template
+		SET
+			name = COALESCE($1, name),
+			machine_type = CASE WHEN $2 THEN $3 ELSE machine_type END,
+			startup_probe_port = CASE WHEN $4 THEN $5 ELSE startup_probe_port END,
+			startup_probe_path = CASE WHEN $4 THEN $6 ELSE startup_probe_path END,
+			liveness_probe_port = CASE WHEN $7 THEN $8 ELSE liveness_probe_port END,
+			liveness_probe_path = CASE WHEN $7 THEN $9 ELSE liveness_probe_path END
+		WHERE
+			id = $10 AND
+			workspace_id = $11
+		RETURNING id;
+		"#,
+		name as _,
+		// A field that is `null` is removed, and a missing one is unchanged
+		machine_type.is_some(),
+		machine_type.flatten() as _,
+		startup_probe.is_some(),
+		startup_probe
+			.as_ref()
+			.and_then(Option::as_ref)
+			.map(|probe| probe.port as i32),
+		startup_probe
+			.as_ref()
+			.and_then(Option::as_ref)
+			.map(|probe| probe.path.as_str()),
+		liveness_probe.is_some(),
+		liveness_probe
+			.as_ref()
+			.and_then(Option::as_ref)
+			.map(|probe| probe.port as i32),
+		liveness_probe
+			.as_ref()
+			.and_then(Option::as_ref)
+			.map(|probe| probe.path.as_str()),
+		template_id as _,
+		workspace_id as _,
+	)
+	.fetch_optional(&mut **database)
+	.await
+	.map_err(|err| match err {
+		sqlx::Error::Database(err) if err.is_unique_violation() => ErrorType::ResourceAlreadyExists,
+		sqlx::Error::Database(err) if err.is_foreign_key_violation() => ErrorType::WrongParameters,
+		err => ErrorType::server_error(err),
+	})?
+	.ok_or(ErrorType::ResourceDoesNotExist)?;
+
+	if let Some(environment_variables) = environment_variables {
+		query!(
+			r#"
+			DELETE FROM
+				deployment_template_environment_variable
+			WHERE
+				template_id = $1;
+			"#,
+			template_id as _
+		)
+		.execute(&mut **database)
+		.await?;
+
+		query!(
+			r#"
+			INSERT INTO
+				deployment_template_environment_variable(
+					template_id,
+					name,
+					value,
+					secret_id
+				)
+			VALUES
+				(
+					UNNEST($1::UUID[]),
+					UNNEST($2::TEXT[]),
+					UNNEST($3::TEXT[]),
+					UNNEST($4::UUID[])
+				);
+			"#,
+			&environment_variables
+				.iter()
+				.map(|_| template_id.into())
+				.collect::<Vec<sqlx::types::Uuid>>(),
+			&environment_variables
+				.iter()
+				.map(|(name, _)| name.clone())
+				.collect::<Vec<_>>(),
+			&environment_variables
+				.iter()
+				.map(|(_, value)| value.value().cloned())
+				.collect::<Vec<Option<String>>>() as _,
+			&environment_variables
+				.iter()
+				.map(|(_, value)| value.secret_id().map(Into::into))
+				.collect::<Vec<Option<sqlx::types::Uuid>>>() as _,
+		)
+		.execute(&mut **database)
+		.await?;
+	}
+
+	AppResponse::builder()
+		.body(UpdateDeploymentTemplateResponse)
+		.headers(())
+		.status_code(StatusCode::ACCEPTED)
+		.build()
+		.into_result()
+}
@@ -0,0 +1,265 @@
+use std::{
+	io::ErrorKind,
+	net::{Ipv4Addr, Ipv6Addr, SocketAddr},
+	time::{Duration, Instant},
+};
+
+use axum::http::StatusCode;
+use models::api::workspace::deployment::*;
+use tokio::net::{TcpStream, UdpSocket};
+
+use crate::{prelude::*, utils::http_client};
+
+/// The handler to test whether an exposed port of a deployment is reachable.
+/// This will make a TCP connection to the port from within the platform
+/// network, and in case of HTTP ports, make a GET request as well. UDP ports
+/// are sent an empty datagram instead.
+pub async fn test_deployment_port(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path:
+					TestDeploymentPortPath {
+						workspace_id,
+						deployment_id,
+						port,
+					},
+				query: (),
+				headers:
+					TestDeploymentPortRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: TestDeploymentPortRequestProcessed,
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config,
+		user_data: _,
+		clock: _,
+	}: AuthenticatedAppRequest<'_, TestDeploymentPortRequest>,
+) -> Result<AppResponse<TestDeploymentPortRequest>, ErrorType> {
+	info!("Testing port `{port}` of deployment `{deployment_id}`");
+
+	let port_type = query!(
+		r#"
+		SELECT
+			deployment_exposed_port.port_type AS "port_type: ExposedPortType"
+		FROM
+			deployment_exposed_port
+		INNER JOIN
+			deployment
+		ON
+			deployment.id = deployment_exposed_port.deployment_id
+		WHERE
+			deployment.id = $1 AND
+			deployment.workspace_id = $2 AND
+			deployment.deleted IS NULL AND
+			deployment_exposed_port.port = $3;
+		"#,
+		deployment_id as _,
+		workspace_id as _,
+		i32::from(port),
+	)
+	.fetch_optional(&mut **database)
+	.await?
+	.ok_or(ErrorType::ResourceDoesNotExist)?
+	.port_type;
+
+	let host = config
+		.port_test
+		.host_template
+		.replace("{deploymentId}", &deployment_id.to_string())
+		.replace("{workspaceId}", &workspace_id.to_string());
+	let timeout = Duration::from_millis(config.port_test.timeout_millis);
+
+	let response = test_port(&host, port, port_type, timeout).await;
+
+	AppResponse::builder()
+		.body(response)
+		.headers(())
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
+
+/// Tests whether the given port is reachable, in the way that makes sense for
+/// the type of the port, within the given timeout
+async fn test_port(
+	host: &str,
+	port: u16,
+	port_type: ExposedPortType,
+	timeout: Duration,
+) -> TestDeploymentPortResponse {
+	let start = Instant::now();
+	let result = match port_type {
+		ExposedPortType::Tcp => test_tcp_port(host, port, timeout).await.map(|()| None),
+		ExposedPortType::Http => test_http_port(host, port, timeout).await.map(Some),
+		ExposedPortType::Udp => test_udp_port(host, port, timeout).await.map(|()| None),
+	};
+
+	match result {
+		Ok(http_status) => TestDeploymentPortResponse {
+			reachable: true,
+			latency_millis: u64::try_from(start.elapsed().as_millis()).ok(),
+			http_status,
+			error: None,
+		},
+		Err(error) => TestDeploymentPortResponse {
+			reachable: false,
+			latency_millis: None,
+			http_status: None,
+			error: Some(error),
+		},
+	}
+}
+
+/// Makes a TCP connection to the port
+async fn test_tcp_port(host: &str, port: u16, timeout: Duration) -> Result<(), String> {
+	TcpStream::connect((host, port))
+		.timeout(timeout)
+		.await
+		.map_err(|_| "Connection timed out".to_string())?
+		.map_err(|err| err.to_string())?;
+
+	Ok(())
+}
+
+/// Makes a TCP connection to the port, followed by a GET request, returning
+/// the status code of the response
+async fn test_http_port(host: &str, port: u16, timeout: Duration) -> Result<u16, String> {
+	let start = Instant::now();
+	test_tcp_port(host, port, timeout).await?;
+
+	let response = http_client()
+		.get(format!("http://{host}:{port}/"))
+		.timeout(timeout.saturating_sub(start.elapsed()))
+		.send()
+		.await
+		.map_err(|err| err.to_string())?;
+
+	Ok(response.status().as_u16())
+}
+
+/// Sends an empty datagram to the port. Since UDP is connectionless, a port
+/// can only be known to be unreachable if the host refuses the datagram. A
+/// port that doesn't respond within the timeout is considered reachable, since
+/// most UDP services don't respond to an empty datagram.
+async fn test_udp_port(host: &str, port: u16, timeout: Duration) -> Result<(), String> {
+	let start = Instant::now();
+	let address = tokio::net::lookup_host((host, port))
+		.timeout(timeout)
+		.await
+		.map_err(|_| "Connection timed out".to_string())?
+		.map_err(|err| err.to_string())?
+		.next()
+		.ok_or_else(|| format!("Unable to resolve `{host}`"))?;
+
+	let local_address = if address.is_ipv4() {
+		SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0))
+	} else {
+		SocketAddr::from((Ipv6Addr::UNSPECIFIED, 0))
+	};
+	let socket = UdpSocket::bind(local_address)
+		.await
+		.map_err(|err| err.to_string())?;
+	socket
+		.connect(address)
+		.await
+		.map_err(|err| err.to_string())?;
+	socket.send(&[]).await.map_err(|err| err.to_string())?;
+
+	// A refused datagram shows up as an error on the next receive
+	let mut buffer = [0; 1];
+	match socket
+		.recv(&mut buffer)
+		.timeout(timeout.saturating_sub(start.elapsed()))
+		.await
+	{
+		Ok(Err(err)) if err.kind() == ErrorKind::ConnectionRefused => Err(err.to_string()),
+		Ok(_) | Err(_) => Ok(()),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use tokio::{
+		io::{AsyncReadExt, AsyncWriteExt},
+		net::TcpListener,
+	};
+
+	use super::*;
+
+	/// The timeout used for the tests
+	const TIMEOUT: Duration = Duration::from_secs(2);
+
+	/// Gets a local port that nothing is listening on
+	async fn unused_port() -> u16 {
+		let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+		listener.local_addr().unwrap().port()
+	}
+
+	#[tokio::test]
+	async fn tcp_ports_are_reachable_when_listening() {
+		let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+		let port = listener.local_addr().unwrap().port();
+
+		let response = test_port("127.0.0.1", port, ExposedPortType::Tcp, TIMEOUT).await;
+		assert!(response.reachable, "{response:?}");
+		assert_eq!(response.http_status, None);
+
+		let response = test_port(
+			"127.0.0.1",
+			unused_port().await,
+			ExposedPortType::Tcp,
+			TIMEOUT,
+		)
+		.await;
+		assert!(!response.reachable);
+		assert!(response.error.is_some());
+	}
+
+	#[tokio::test]
+	async fn http_ports_report_the_status_code() {
+		let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+		let port = listener.local_addr().unwrap().port();
+		tokio::spawn(async move {
+			while let Ok((mut stream, _)) = listener.accept().await {
+				_ = stream.read(&mut [0; 1024]).await;
+				_ = stream
+					.write_all(b"HTTP/1.1 204 No Content\r\nConnection: close\r\n\r\n")
+					.await;
+			}
+		});
+
+		let response = test_port("127.0.0.1", port, ExposedPortType::Http, TIMEOUT).await;
+		assert!(response.reachable, "{response:?}");
+		assert_eq!(response.http_status, Some(204));
+
+		let response = test_port(
+			"127.0.0.1",
+			unused_port().await,
+			ExposedPortType::Http,
+			TIMEOUT,
+		)
+		.await;
+		assert!(!response.reachable);
+		assert_eq!(response.http_status, None);
+	}
+
+	#[tokio::test]
+	async fn udp_ports_are_unreachable_only_when_refused() {
+		let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+		let port = socket.local_addr().unwrap().port();
+
+		let response = test_port("127.0.0.1", port, ExposedPortType::Udp, TIMEOUT).await;
+		assert!(response.reachable, "{response:?}");
+		assert_eq!(response.http_status, None);
+
+		drop(socket);
+		let response = test_port("127.0.0.1", port, ExposedPortType::Udp, TIMEOUT).await;
+		assert!(!response.reachable, "{response:?}");
+		assert!(response.error.is_some());
+	}
+}
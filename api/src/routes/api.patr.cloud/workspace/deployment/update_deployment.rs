@@ -1,19 +1,41 @@
 use axum::http::StatusCode;
-use models::api::workspace::deployment::*;
+use models::{api::workspace::deployment::*, utils::constants::MASKED_ENVIRONMENT_VARIABLE_VALUE};
+use preprocess::Preprocessable;
+use rustis::client::Client as RedisClient;
+use time::OffsetDateTime;
 
-use crate::prelude::*;
+use super::{
+	change_request::create_change_request,
+	ensure_resources_fit_machine_type,
+	ensure_volumes_can_be_attached,
+	set_deployment_dependencies,
+	validate_max_concurrent_requests,
+	validate_max_replicas,
+	validate_scale_to_zero_after,
+	validate_volume_mounts,
+};
+use crate::{deployment_image_scanner, prelude::*, utils::config::AppConfig};
 
 /// Update deployment details. This endpoint is used to update the deployment
 /// details. The deployment details that can be updated are the name, machine
 /// type, deploy on push, min horizontal scale, max horizontal scale, ports,
-/// environment variables, startup probe, liveness probe, config mounts, and
-/// volumes. At least one of the values must be updated.
+/// environment variables (and which of them are environment-specific or
+/// secret), startup probe, liveness probe, config mounts, volumes, CPU and
+/// memory requests and limits, the period of inactivity after which it is
+/// scaled to zero, the minimum level of the logs that are captured, the maximum
+/// number of requests that each replica handles at once, the labels of the
+/// deployment, and the deployments it depends on. At least one of the values
+/// must be updated, and only the values that are provided are updated. If an
+/// `If-Match` header is given, the deployment is only updated if it hasn't been
+/// modified since the version in the header. If the workspace requires changes
+/// to deployments to be approved, the change is stored as a change request
+/// instead, and is only applied once another user approves it.
 pub async fn update_deployment(
 	AuthenticatedAppRequest {
 		request:
 			ProcessedApiRequest {
 				path: UpdateDeploymentPath {
-					workspace_id: _,
+					workspace_id,
 					deployment_id,
 				},
 				query: (),
@@ -21,6 +43,7 @@ pub async fn update_deployment(
 					UpdateDeploymentRequestHeaders {
 						authorization: _,
 						user_agent: _,
+						if_match,
 					},
 				body:
 					UpdateDeploymentRequestProcessed {
@@ -32,38 +55,57 @@ pub async fn update_deployment(
 						max_horizontal_scale,
 						ports,
 						environment_variables,
+						environment_specific_variables,
+						secret_variables,
 						startup_probe,
 						liveness_probe,
 						config_mounts,
 						volumes,
+						resources,
+						scale_to_zero_after,
+						log_level,
+						max_concurrent_requests,
+						access_logging,
+						labels,
+						depends_on,
 					},
 			},
 		database,
-		redis: _,
+		redis,
 		client_ip: _,
-		config: _,
-		user_data: _,
+		config,
+		user_data,
+		clock,
 	}: AuthenticatedAppRequest<'_, UpdateDeploymentRequest>,
 ) -> Result<AppResponse<UpdateDeploymentRequest>, ErrorType> {
 	info!("Updating deployment: {}", deployment_id);
 
+	let changes = UpdateDeploymentRequest {
+		name,
+		machine_type,
+		deploy_on_push,
+		runner,
+		min_horizontal_scale,
+		max_horizontal_scale,
+		ports,
+		environment_variables,
+		environment_specific_variables,
+		secret_variables,
+		startup_probe,
+		liveness_probe,
+		config_mounts,
+		volumes,
+		resources,
+		scale_to_zero_after,
+		log_level,
+		max_concurrent_requests,
+		access_logging,
+		labels,
+		depends_on,
+	};
+
 	// Validate if at least value is to be updated
-	if name
-		.as_ref()
-		.map(|_| 0)
-		.or(machine_type.as_ref().map(|_| 0))
-		.or(deploy_on_push.as_ref().map(|_| 0))
-		.or(runner.as_ref().map(|_| 0))
-		.or(min_horizontal_scale.as_ref().map(|_| 0))
-		.or(max_horizontal_scale.as_ref().map(|_| 0))
-		.or(ports.as_ref().map(|_| 0))
-		.or(environment_variables.as_ref().map(|_| 0))
-		.or(startup_probe.as_ref().map(|_| 0))
-		.or(liveness_probe.as_ref().map(|_| 0))
-		.or(config_mounts.as_ref().map(|_| 0))
-		.or(volumes.as_ref().map(|_| 0))
-		.is_none()
-	{
+	if changes.is_none() {
 		debug!(
 			"No parameters provided for updating deployment: {}",
 			deployment_id
@@ -71,15 +113,23 @@ pub async fn update_deployment(
 		return Err(ErrorType::WrongParameters);
 	}
 
-	query!(
+	// Lock the deployment, so that it isn't modified by another request between
+	// checking its version and updating it
+	let deployment = query!(
 		r#"
 		SELECT
-			id
+			deployment.updated,
+			workspace.require_deployment_change_approval
 		FROM
 			deployment
+		INNER JOIN
+			workspace
+		ON
+			deployment.workspace_id = workspace.id
 		WHERE
-			id = $1 AND
-			deleted IS NULL;
+			deployment.id = $1 AND
+			deployment.deleted IS NULL
+		FOR UPDATE OF deployment;
 		"#,
 		deployment_id as _,
 	)
@@ -87,20 +137,171 @@ pub async fn update_deployment(
 	.await?
 	.ok_or(ErrorType::ResourceDoesNotExist)?;
 
+	if let Some(if_match) = if_match {
+		if !if_match.precondition_passes(&Deployment::etag_for(deployment.updated)) {
+			debug!(
+				"Deployment `{}` was modified since the version it was fetched at",
+				deployment_id
+			);
+			return Err(ErrorType::ResourceModified);
+		}
+	}
+
+	if deployment.require_deployment_change_approval {
+		info!("Deployment `{deployment_id}` needs the change to be approved before it is applied");
+
+		// Invalid changes are rejected right away, instead of when they are
+		// approved
+		validate_deployment_changes(
+			&mut **database,
+			&config,
+			workspace_id,
+			deployment_id,
+			changes.clone(),
+		)
+		.await?;
+
+		let change_request_id = create_change_request(
+			&mut **database,
+			deployment_id,
+			deployment.updated,
+			user_data.id,
+			changes,
+			clock.now(),
+		)
+		.await?;
+
+		return AppResponse::builder()
+			.body(UpdateDeploymentResponse {
+				updated_at: deployment.updated,
+				change_request_id: Some(change_request_id.into()),
+			})
+			.headers(())
+			.status_code(StatusCode::ACCEPTED)
+			.build()
+			.into_result();
+	}
+
+	let updated_at = apply_deployment_update(
+		&mut **database,
+		redis,
+		&config,
+		workspace_id,
+		deployment_id,
+		changes,
+	)
+	.await?;
+
+	AppResponse::builder()
+		.body(UpdateDeploymentResponse {
+			updated_at,
+			change_request_id: None,
+		})
+		.headers(())
+		.status_code(StatusCode::ACCEPTED)
+		.build()
+		.into_result()
+}
+
+/// Validates the changes to a deployment, without applying them. Changes that
+/// need the deployment to be checked against the rest of the workspace (such as
+/// the volumes being available, or the resources fitting the machine type) are
+/// only validated once they are applied.
+pub(super) async fn validate_deployment_changes(
+	connection: &mut DatabaseConnection,
+	config: &AppConfig,
+	workspace_id: Uuid,
+	deployment_id: Uuid,
+	changes: UpdateDeploymentRequest,
+) -> Result<UpdateDeploymentRequestProcessed, ErrorType> {
+	let changes = changes.preprocess().map_err(|err| {
+		debug!("Invalid changes for deployment `{deployment_id}`: {err:?}");
+		ErrorType::WrongParameters
+	})?;
+
+	validate_scale_to_zero_after(changes.scale_to_zero_after.flatten())?;
+	if let Some(max_horizontal_scale) = changes.max_horizontal_scale {
+		validate_max_replicas(&mut *connection, config, workspace_id, max_horizontal_scale).await?;
+	}
+	validate_max_concurrent_requests(changes.max_concurrent_requests.flatten())?;
+	if let Some(labels) = &changes.labels {
+		Deployment::validate_labels(labels)?;
+	}
+	if changes.ports.as_ref().is_some_and(|ports| ports.is_empty()) {
+		return Err(ErrorType::WrongParameters);
+	}
+	if let Some(volumes) = &changes.volumes {
+		validate_volume_mounts(volumes)?;
+	}
+
+	Ok(changes)
+}
+
+/// Applies the changes to a deployment. This is used both to update a
+/// deployment directly, and to apply a change request once it is approved, so
+/// the changes are validated here again in case the workspace changed since
+/// they were requested. Logs that were dropped before
+/// the log level was lowered are not recovered. A deployment that is currently
+/// scaled to zero is started again if it is no longer meant to be scaled to
+/// zero. Since that pulls its image again, as does moving it to another
+/// runner, the image then has to be allowed under the image scan policy of the
+/// workspace. The maximum replicas of a deployment with a canary can't be
+/// lowered past what both the canary and the primary replicas need. Returns
+/// the time the deployment was updated at.
+pub(super) async fn apply_deployment_update(
+	connection: &mut DatabaseConnection,
+	redis: &RedisClient,
+	config: &AppConfig,
+	workspace_id: Uuid,
+	deployment_id: Uuid,
+	changes: UpdateDeploymentRequest,
+) -> Result<OffsetDateTime, ErrorType> {
+	let UpdateDeploymentRequestProcessed {
+		name,
+		machine_type,
+		deploy_on_push,
+		runner,
+		min_horizontal_scale,
+		max_horizontal_scale,
+		ports,
+		environment_variables,
+		environment_specific_variables,
+		secret_variables,
+		startup_probe,
+		liveness_probe,
+		config_mounts,
+		volumes,
+		resources,
+		scale_to_zero_after,
+		log_level,
+		max_concurrent_requests,
+		access_logging,
+		labels,
+		depends_on,
+	} = validate_deployment_changes(
+		&mut *connection,
+		config,
+		workspace_id,
+		deployment_id,
+		changes,
+	)
+	.await?;
+
+	let resources = resources.map(DeploymentResources::with_default_limits);
+	let max_concurrent_requests = max_concurrent_requests
+		.map(validate_max_concurrent_requests)
+		.transpose()?;
+
 	// BEGIN DEFERRED CONSTRAINT
 	query!(
 		r#"
 		SET CONSTRAINTS ALL DEFERRED;
 		"#,
 	)
-	.execute(&mut **database)
+	.execute(&mut *connection)
 	.await?;
 
 	if let Some(ports) = ports {
-		if ports.is_empty() {
-			return Err(ErrorType::WrongParameters);
-		}
-
 		// Updating deployment port in database
 		query!(
 			r#"
@@ -111,7 +312,7 @@ pub async fn update_deployment(
 			"#,
 			deployment_id as _,
 		)
-		.execute(&mut **database)
+		.execute(&mut *connection)
 		.await?;
 
 		query!(
@@ -142,16 +343,19 @@ pub async fn update_deployment(
 				.map(|(_, port_type)| port_type.to_string())
 				.collect::<Vec<String>>() as _,
 		)
-		.execute(&mut **database)
+		.execute(&mut *connection)
 		.await?;
 	}
 
 	// Updating deployment details
-	query!(
+	let updated_deployment = query!(
 		r#"
 		UPDATE
 			deployment
 		SET
+			reconciliation_status = 'pending',
+			reconciliation_generation = reconciliation_generation + 1,
+			updated = NOW(),
 			name = COALESCE($1, name),
 			machine_type = COALESCE($2, machine_type),
 			deploy_on_push = COALESCE($3, deploy_on_push),
@@ -162,6 +366,8 @@ pub async fn update_deployment(
 				CASE
 					WHEN $7 = 0 THEN
 						NULL
+					WHEN $7 IS NULL THEN
+						startup_probe_port
 					ELSE
 						$7
 				END
@@ -170,6 +376,8 @@ pub async fn update_deployment(
 				CASE
 					WHEN $7 = 0 THEN
 						NULL
+					WHEN $7 IS NULL THEN
+						startup_probe_path
 					ELSE
 						$8
 				END
@@ -188,6 +396,8 @@ pub async fn update_deployment(
 				CASE
 					WHEN $9 = 0 THEN
 						NULL
+					WHEN $9 IS NULL THEN
+						liveness_probe_port
 					ELSE
 						$9
 				END
@@ -196,6 +406,8 @@ pub async fn update_deployment(
 				CASE
 					WHEN $9 = 0 THEN
 						NULL
+					WHEN $9 IS NULL THEN
+						liveness_probe_path
 					ELSE
 						$10
 				END
@@ -209,9 +421,32 @@ pub async fn update_deployment(
 					ELSE
 						'http'::EXPOSED_PORT_TYPE
 				END
+			),
+			cpu_request = COALESCE($12, cpu_request),
+			cpu_limit = COALESCE($13, cpu_limit),
+			memory_request = COALESCE($14, memory_request),
+			memory_limit = COALESCE($15, memory_limit),
+			scale_to_zero_after = CASE WHEN $16 THEN $17 ELSE scale_to_zero_after END,
+			log_level = CASE WHEN $18 THEN $19 ELSE log_level END,
+			max_concurrent_requests = CASE WHEN $20 THEN $21 ELSE max_concurrent_requests END,
+			access_logging = COALESCE($22, access_logging),
+			status = (
+				CASE
+					WHEN status = 'cold' AND $16 AND $17 IS NULL THEN
+						'deploying'
+					ELSE
+						status
+				END
 			)
 		WHERE
-			id = $11;
+			id = $11
+		RETURNING
+			updated,
+			status AS "status: DeploymentStatus",
+			min_horizontal_scale,
+			max_horizontal_scale,
+			canary_image_tag,
+			canary_weight;
 		"#,
 		name as _,
 		machine_type as _,
@@ -219,14 +454,73 @@ pub async fn update_deployment(
 		runner as _,
 		min_horizontal_scale.map(|v| v as i16),
 		max_horizontal_scale.map(|v| v as i16),
-		startup_probe.as_ref().map(|probe| probe.port as i32),
-		startup_probe.as_ref().map(|probe| probe.path.as_str()),
-		liveness_probe.as_ref().map(|probe| probe.port as i32),
-		liveness_probe.as_ref().map(|probe| probe.path.as_str()),
-		deployment_id as _
+		// A port of 0 removes the probe, and no port leaves it unchanged
+		startup_probe
+			.as_ref()
+			.map(|probe| probe.as_ref().map_or(0, |probe| probe.port as i32)),
+		startup_probe
+			.as_ref()
+			.and_then(Option::as_ref)
+			.map(|probe| probe.path.as_str()),
+		liveness_probe
+			.as_ref()
+			.map(|probe| probe.as_ref().map_or(0, |probe| probe.port as i32)),
+		liveness_probe
+			.as_ref()
+			.and_then(Option::as_ref)
+			.map(|probe| probe.path.as_str()),
+		deployment_id as _,
+		// Only the resources that are set are updated, and limits that aren't
+		// set default to the requests that are
+		resources.and_then(|resources| resources.cpu_request.map(|value| value as i32)),
+		resources.and_then(|resources| resources.cpu_limit.map(|value| value as i32)),
+		resources.and_then(|resources| resources.memory_request.map(|value| value as i32)),
+		resources.and_then(|resources| resources.memory_limit.map(|value| value as i32)),
+		scale_to_zero_after.is_some(),
+		scale_to_zero_after.flatten().map(|value| value as i32),
+		log_level.is_some(),
+		log_level.flatten() as _,
+		max_concurrent_requests.is_some(),
+		max_concurrent_requests.flatten().map(|value| value as i32),
+		access_logging,
 	)
-	.execute(&mut **database)
+	.fetch_one(&mut *connection)
 	.await?;
+	let updated_at = updated_deployment.updated;
+
+	// The replicas of a canary are taken out of the maximum replicas of the
+	// deployment, so the deployment can't be scaled down past what it needs
+	if let (Some(image_tag), Some(weight)) = (
+		updated_deployment.canary_image_tag,
+		updated_deployment.canary_weight,
+	) {
+		let canary = DeploymentCanary {
+			image_tag,
+			weight: weight as u8,
+		};
+		let replicas = canary.replicas(
+			updated_deployment.min_horizontal_scale as u16,
+			updated_deployment.max_horizontal_scale as u16,
+		);
+		if replicas.is_none() {
+			return Err(ErrorType::WrongParameters);
+		}
+	}
+
+	let restarted = scale_to_zero_after == Some(None) &&
+		updated_deployment.status == DeploymentStatus::Deploying;
+	if restarted || runner.is_some() {
+		deployment_image_scanner::ensure_image_allowed(
+			&mut *connection,
+			redis,
+			config,
+			workspace_id,
+			deployment_id,
+			false,
+			updated_at,
+		)
+		.await?;
+	}
 
 	// END DEFERRED CONSTRAINT
 	query!(
@@ -234,10 +528,56 @@ pub async fn update_deployment(
 		SET CONSTRAINTS ALL IMMEDIATE;
 		"#,
 	)
-	.execute(&mut **database)
+	.execute(&mut *connection)
 	.await?;
 
-	if let Some(environment_variables) = environment_variables {
+	if let Some(mut environment_variables) = environment_variables {
+		let existing_variables = query!(
+			r#"
+			SELECT
+				name,
+				value,
+				environment_specific,
+				is_secret
+			FROM
+				deployment_environment_variable
+			WHERE
+				deployment_id = $1;
+			"#,
+			deployment_id as _,
+		)
+		.fetch_all(&mut *connection)
+		.await?;
+
+		// Variables keep their environment-specific and secret flags unless
+		// they are updated
+		let environment_specific_variables = environment_specific_variables.unwrap_or_else(|| {
+			existing_variables
+				.iter()
+				.filter(|env| env.environment_specific)
+				.map(|env| env.name.clone())
+				.collect()
+		});
+		let secret_variables = secret_variables.unwrap_or_else(|| {
+			existing_variables
+				.iter()
+				.filter(|env| env.is_secret)
+				.map(|env| env.name.clone())
+				.collect()
+		});
+
+		// Secret variables that are sent back with their masked value keep
+		// their existing value
+		for env in existing_variables.into_iter().filter(|env| env.is_secret) {
+			if let (Some(EnvironmentVariableValue::String(value)), Some(existing_value)) =
+				(environment_variables.get_mut(&env.name), env.value)
+			{
+				if value == MASKED_ENVIRONMENT_VARIABLE_VALUE {
+					*value = existing_value;
+				}
+			}
+		}
+
 		query!(
 			r#"
 			DELETE FROM
@@ -247,7 +587,7 @@ pub async fn update_deployment(
 			"#,
 			deployment_id as _,
 		)
-		.execute(&mut **database)
+		.execute(&mut *connection)
 		.await?;
 
 		query!(
@@ -257,14 +597,18 @@ pub async fn update_deployment(
 					deployment_id,
 					name,
 					value,
-					secret_id
+					secret_id,
+					environment_specific,
+					is_secret
 				)
 			VALUES
 				(
 					UNNEST($1::UUID[]),
 					UNNEST($2::TEXT[]),
 					UNNEST($3::TEXT[]),
-					UNNEST($4::UUID[])
+					UNNEST($4::UUID[]),
+					UNNEST($5::BOOLEAN[]),
+					UNNEST($6::BOOLEAN[])
 				);
 			"#,
 			&environment_variables
@@ -283,9 +627,57 @@ pub async fn update_deployment(
 				.iter()
 				.map(|(_, value)| value.secret_id().map(Into::into))
 				.collect::<Vec<Option<sqlx::types::Uuid>>>() as _,
+			&environment_variables
+				.keys()
+				.map(|name| environment_specific_variables.contains(name))
+				.collect::<Vec<_>>(),
+			&environment_variables
+				.iter()
+				.map(|(name, value)| value.is_string() && secret_variables.contains(name))
+				.collect::<Vec<_>>(),
 		)
-		.execute(&mut **database)
+		.execute(&mut *connection)
 		.await?;
+	} else {
+		if let Some(environment_specific_variables) = environment_specific_variables {
+			query!(
+				r#"
+				UPDATE
+					deployment_environment_variable
+				SET
+					environment_specific = (name = ANY($2))
+				WHERE
+					deployment_id = $1;
+				"#,
+				deployment_id as _,
+				&environment_specific_variables
+					.into_iter()
+					.collect::<Vec<_>>(),
+			)
+			.execute(&mut *connection)
+			.await?;
+		}
+
+		if let Some(secret_variables) = secret_variables {
+			// Only variables with a plain value can be secret
+			query!(
+				r#"
+				UPDATE
+					deployment_environment_variable
+				SET
+					is_secret = (
+						name = ANY($2) AND
+						value IS NOT NULL
+					)
+				WHERE
+					deployment_id = $1;
+				"#,
+				deployment_id as _,
+				&secret_variables.into_iter().collect::<Vec<_>>(),
+			)
+			.execute(&mut *connection)
+			.await?;
+		}
 	}
 
 	if let Some(config_mounts) = config_mounts {
@@ -298,7 +690,7 @@ pub async fn update_deployment(
 			"#,
 			deployment_id as _,
 		)
-		.execute(&mut **database)
+		.execute(&mut *connection)
 		.await?;
 
 		query!(
@@ -329,10 +721,54 @@ pub async fn update_deployment(
 				.map(|(_, file)| file.to_vec())
 				.collect::<Vec<_>>(),
 		)
-		.execute(&mut **database)
+		.execute(&mut *connection)
 		.await?;
 	}
 
+	if let Some(labels) = labels {
+		query!(
+			r#"
+			DELETE FROM
+				deployment_label
+			WHERE
+				deployment_id = $1;
+			"#,
+			deployment_id as _,
+		)
+		.execute(&mut *connection)
+		.await?;
+
+		query!(
+			r#"
+			INSERT INTO
+				deployment_label(
+					deployment_id,
+					key,
+					value
+				)
+			VALUES
+				(
+					UNNEST($1::UUID[]),
+					UNNEST($2::TEXT[]),
+					UNNEST($3::TEXT[])
+				);
+			"#,
+			&labels
+				.iter()
+				.map(|_| deployment_id.into())
+				.collect::<Vec<_>>(),
+			&labels.keys().cloned().collect::<Vec<_>>(),
+			&labels.values().cloned().collect::<Vec<_>>(),
+		)
+		.execute(&mut *connection)
+		.await?;
+	}
+
+	if let Some(depends_on) = &depends_on {
+		set_deployment_dependencies(&mut *connection, workspace_id, deployment_id, depends_on)
+			.await?;
+	}
+
 	if let Some(updated_volumes) = &volumes {
 		query!(
 			r#"
@@ -343,7 +779,7 @@ pub async fn update_deployment(
 			"#,
 			deployment_id as _,
 		)
-		.execute(&mut **database)
+		.execute(&mut *connection)
 		.await?;
 
 		query!(
@@ -374,7 +810,7 @@ pub async fn update_deployment(
 				.map(|(_, volume_mount_path)| volume_mount_path.clone())
 				.collect::<Vec<_>>(),
 		)
-		.execute(&mut **database)
+		.execute(&mut *connection)
 		.await
 		.map_err(|err| match err {
 			sqlx::Error::Database(err) if err.is_unique_violation() => ErrorType::ResourceInUse,
@@ -385,10 +821,13 @@ pub async fn update_deployment(
 		})?;
 	}
 
-	AppResponse::builder()
-		.body(UpdateDeploymentResponse)
-		.headers(())
-		.status_code(StatusCode::ACCEPTED)
-		.build()
-		.into_result()
+	if volumes.is_some() || max_horizontal_scale.is_some() {
+		ensure_volumes_can_be_attached(&mut *connection, deployment_id).await?;
+	}
+
+	if resources.is_some() || machine_type.is_some() {
+		ensure_resources_fit_machine_type(&mut *connection, deployment_id).await?;
+	}
+
+	Ok(updated_at)
 }
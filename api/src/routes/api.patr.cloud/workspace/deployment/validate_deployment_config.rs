@@ -0,0 +1,461 @@
+use axum::http::StatusCode;
+use models::{api::workspace::deployment::*, utils::ImageReference};
+use preprocess::Preprocessable;
+
+use super::{
+	validate_deployment_dependencies,
+	validate_max_concurrent_requests,
+	validate_max_replicas,
+	validate_scale_to_zero_after,
+	validate_volume_mounts,
+};
+use crate::prelude::*;
+
+/// The handler to validate the config of a deployment without creating it.
+/// The config is checked the same way it would be when creating a deployment,
+/// but instead of stopping at the first error, all the errors found are
+/// returned. If the config cannot be parsed at all, only that error is
+/// returned, since none of the other checks can be made. Nothing is created or
+/// modified in the workspace.
+pub async fn validate_deployment_config(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: ValidateDeploymentConfigPath { workspace_id },
+				query: (),
+				headers:
+					ValidateDeploymentConfigRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: ValidateDeploymentConfigRequestProcessed { config },
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config: app_config,
+		user_data: _,
+		clock: _,
+	}: AuthenticatedAppRequest<'_, ValidateDeploymentConfigRequest>,
+) -> Result<AppResponse<ValidateDeploymentConfigRequest>, ErrorType> {
+	info!("Validating deployment config in workspace `{workspace_id}`");
+
+	let mut errors = Vec::new();
+
+	let request = match serde_json::from_value::<CreateDeploymentRequest>(
+		serde_json::Value::Object(config.into_iter().collect()),
+	) {
+		Ok(request) => request,
+		Err(err) => {
+			debug!("Unable to parse deployment config: {err}");
+			errors.push(DeploymentConfigError {
+				field: None,
+				error: ErrorType::WrongParameters,
+				message: err.to_string(),
+			});
+			return validation_response(errors, None);
+		}
+	};
+
+	if let Err(err) = request.clone().preprocess() {
+		errors.push(DeploymentConfigError {
+			field: Some(err.field.to_string()),
+			error: ErrorType::WrongParameters,
+			message: err.message.to_string(),
+		});
+	}
+
+	let CreateDeploymentRequest {
+		name,
+		registry,
+		image_tag,
+		runner,
+		mut machine_type,
+		template_id,
+		pull_secret_id,
+		running_details:
+			DeploymentRunningDetails {
+				deploy_on_push: _,
+				min_horizontal_scale,
+				max_horizontal_scale,
+				ports,
+				environment_variables,
+				startup_probe,
+				liveness_probe,
+				config_mounts: _,
+				volumes,
+				resources,
+				scale_to_zero_after,
+				log_level: _,
+				max_concurrent_requests,
+				access_logging: _,
+			},
+		deploy_on_create: _,
+		secret_variables: _,
+		labels,
+		depends_on,
+	} = request;
+
+	let mut push_error = |field: String, error: ErrorType| {
+		errors.push(DeploymentConfigError {
+			field: Some(field),
+			message: error.detailed_message(),
+			error,
+		});
+	};
+
+	let name_taken = query!(
+		r#"
+		SELECT
+			id
+		FROM
+			deployment
+		WHERE
+			name = $1 AND
+			workspace_id = $2 AND
+			deleted IS NULL;
+		"#,
+		name.trim(),
+		workspace_id as _,
+	)
+	.fetch_optional(&mut **database)
+	.await?
+	.is_some();
+	if name_taken {
+		push_error("name".to_string(), ErrorType::ResourceAlreadyExists);
+	}
+
+	let runner_exists = query!(
+		r#"
+		SELECT
+			id
+		FROM
+			runner
+		WHERE
+			id = $1 AND
+			workspace_id = $2 AND
+			deleted IS NULL;
+		"#,
+		runner as _,
+		workspace_id as _,
+	)
+	.fetch_optional(&mut **database)
+	.await?
+	.is_some();
+	if !runner_exists {
+		push_error("runner".to_string(), ErrorType::ResourceDoesNotExist);
+	}
+
+	if let Some(template_id) = template_id {
+		let template =
+			super::template::get_deployment_template(&mut **database, workspace_id, template_id)
+				.await?;
+		match template {
+			Some(template) => machine_type = machine_type.or(template.machine_type),
+			None => push_error("templateId".to_string(), ErrorType::ResourceDoesNotExist),
+		}
+	}
+
+	if machine_type.is_none() {
+		machine_type =
+			super::get_workspace_default_machine_type(&mut **database, workspace_id).await?;
+	}
+
+	let mut resource_impact = None;
+	if let Some(machine_type) = machine_type {
+		let machine = query!(
+			r#"
+			SELECT
+				cpu_count,
+				memory_count
+			FROM
+				deployment_machine_type
+			WHERE
+				id = $1;
+			"#,
+			machine_type as _,
+		)
+		.fetch_optional(&mut **database)
+		.await?;
+
+		if let Some(machine) = machine {
+			let usage = query!(
+				r#"
+				SELECT
+					COALESCE(
+						SUM(
+							deployment_machine_type.cpu_count::BIGINT *
+							deployment.max_horizontal_scale
+						),
+						0
+					)::BIGINT AS "cpu_count!",
+					COALESCE(
+						SUM(
+							deployment_machine_type.memory_count::BIGINT *
+							deployment.max_horizontal_scale
+						),
+						0
+					)::BIGINT AS "memory_count!"
+				FROM
+					deployment
+				INNER JOIN
+					deployment_machine_type
+				ON
+					deployment.machine_type = deployment_machine_type.id
+				WHERE
+					deployment.workspace_id = $1 AND
+					deployment.deleted IS NULL;
+				"#,
+				workspace_id as _,
+			)
+			.fetch_one(&mut **database)
+			.await?;
+
+			let capacity = DeploymentMachineType {
+				cpu_count: machine.cpu_count as u16,
+				memory_count: machine.memory_count as u32,
+			};
+			for error in resources.with_default_limits().errors(&capacity) {
+				push_error(error.field().unwrap_or("resources").to_string(), error);
+			}
+
+			let cpu_count = machine.cpu_count as u32;
+			let memory_count = machine.memory_count as u64;
+			let max_cpu_count = cpu_count.checked_mul(max_horizontal_scale.into());
+			let max_memory_count = memory_count.checked_mul(max_horizontal_scale.into());
+			if let Some((max_cpu_count, max_memory_count)) = max_cpu_count.zip(max_memory_count) {
+				resource_impact = Some(DeploymentResourceImpact {
+					cpu_count,
+					memory_count,
+					max_cpu_count,
+					max_memory_count,
+					workspace_cpu_count: u32::try_from(usage.cpu_count).unwrap_or(u32::MAX),
+					workspace_memory_count: u64::try_from(usage.memory_count).unwrap_or(u64::MAX),
+				});
+			} else {
+				push_error("maxHorizontalScale".to_string(), ErrorType::WrongParameters);
+			}
+		} else {
+			push_error("machineType".to_string(), ErrorType::ResourceDoesNotExist);
+		}
+	} else {
+		// A machine type is required if neither the template nor the workspace
+		// have one
+		push_error("machineType".to_string(), ErrorType::WrongParameters);
+	}
+
+	if min_horizontal_scale > max_horizontal_scale {
+		push_error("minHorizontalScale".to_string(), ErrorType::WrongParameters);
+	}
+
+	if let Err(error) = validate_max_replicas(
+		&mut **database,
+		&app_config,
+		workspace_id,
+		max_horizontal_scale,
+	)
+	.await
+	{
+		push_error("maxHorizontalScale".to_string(), error);
+	}
+
+	if let Err(error) = validate_scale_to_zero_after(scale_to_zero_after) {
+		push_error("scaleToZeroAfter".to_string(), error);
+	}
+
+	if let Err(error) = validate_max_concurrent_requests(max_concurrent_requests) {
+		push_error("maxConcurrentRequests".to_string(), error);
+	}
+
+	if let Err(error) = Deployment::validate_labels(&labels) {
+		push_error("labels".to_string(), error);
+	}
+
+	// The deployment doesn't exist yet, so nothing can depend on it, and any
+	// ID that isn't in the workspace can be used in its place
+	if let Err(error) =
+		validate_deployment_dependencies(&mut **database, workspace_id, Uuid::nil(), &depends_on)
+			.await
+	{
+		push_error("dependsOn".to_string(), error);
+	}
+
+	match registry {
+		DeploymentRegistry::ExternalRegistry {
+			registry,
+			image_name,
+		} => {
+			if let Err(err) = ImageReference::from_parts(&registry, &image_name, &image_tag) {
+				push_error(
+					"imageName".to_string(),
+					ErrorType::InvalidImageReference(err.invalid_part()),
+				);
+			}
+		}
+		DeploymentRegistry::PatrRegistry {
+			registry: _,
+			repository_id,
+		} => {
+			let repository_exists = query!(
+				r#"
+				SELECT
+					id
+				FROM
+					container_registry_repository
+				WHERE
+					id = $1 AND
+					workspace_id = $2 AND
+					deleted IS NULL;
+				"#,
+				repository_id as _,
+				workspace_id as _,
+			)
+			.fetch_optional(&mut **database)
+			.await?
+			.is_some();
+			if !repository_exists {
+				push_error("repositoryId".to_string(), ErrorType::ResourceDoesNotExist);
+			}
+		}
+	}
+
+	if let Some(pull_secret_id) = pull_secret_id {
+		let pull_secret_exists = query!(
+			r#"
+			SELECT
+				secret_pull_credential.secret_id
+			FROM
+				secret_pull_credential
+			INNER JOIN
+				secret
+			ON
+				secret.id = secret_pull_credential.secret_id
+			WHERE
+				secret_pull_credential.secret_id = $1 AND
+				secret.workspace_id = $2 AND
+				secret.deleted IS NULL;
+			"#,
+			pull_secret_id as _,
+			workspace_id as _,
+		)
+		.fetch_optional(&mut **database)
+		.await?
+		.is_some();
+		if !pull_secret_exists {
+			push_error("pullSecretId".to_string(), ErrorType::ResourceDoesNotExist);
+		}
+	}
+
+	for (env_name, value) in &environment_variables {
+		let Some(secret_id) = value.secret_id() else {
+			continue;
+		};
+
+		let secret_exists = query!(
+			r#"
+			SELECT
+				id
+			FROM
+				secret
+			WHERE
+				id = $1 AND
+				workspace_id = $2 AND
+				deleted IS NULL;
+			"#,
+			secret_id as _,
+			workspace_id as _,
+		)
+		.fetch_optional(&mut **database)
+		.await?
+		.is_some();
+		if !secret_exists {
+			push_error(
+				format!("environmentVariables.{env_name}"),
+				ErrorType::ResourceDoesNotExist,
+			);
+		}
+	}
+
+	for (field, probe) in [
+		("startupProbe", &startup_probe),
+		("livenessProbe", &liveness_probe),
+	] {
+		if let Some(probe) = probe {
+			if !ports.contains_key(&StringifiedU16::new(probe.port)) {
+				push_error(format!("{field}.port"), ErrorType::WrongParameters);
+			}
+		}
+	}
+
+	if let Err(error) = validate_volume_mounts(&volumes) {
+		push_error("volumes".to_string(), error);
+	}
+
+	if !volumes.is_empty() && max_horizontal_scale > 1 {
+		push_error(
+			"maxHorizontalScale".to_string(),
+			ErrorType::CannotScaleWithVolume,
+		);
+	}
+
+	for volume_id in volumes.keys() {
+		let volume = query!(
+			r#"
+			SELECT
+				EXISTS(
+					SELECT
+						1
+					FROM
+						deployment_volume_mount
+					WHERE
+						volume_id = deployment_volume.id
+				) AS "in_use!"
+			FROM
+				deployment_volume
+			INNER JOIN
+				resource
+			ON
+				resource.id = deployment_volume.id
+			WHERE
+				deployment_volume.id = $1 AND
+				resource.owner_id = $2 AND
+				deployment_volume.deleted IS NULL;
+			"#,
+			volume_id as _,
+			workspace_id as _,
+		)
+		.fetch_optional(&mut **database)
+		.await?;
+
+		match volume {
+			Some(volume) if volume.in_use => {
+				push_error(format!("volumes.{volume_id}"), ErrorType::ResourceInUse);
+			}
+			Some(_) => (),
+			None => push_error(
+				format!("volumes.{volume_id}"),
+				ErrorType::ResourceDoesNotExist,
+			),
+		}
+	}
+
+	validation_response(errors, resource_impact)
+}
+
+/// Creates the response for the validation of a deployment config from the
+/// errors found. The config is only valid if no errors were found.
+fn validation_response(
+	errors: Vec<DeploymentConfigError>,
+	resource_impact: Option<DeploymentResourceImpact>,
+) -> Result<AppResponse<ValidateDeploymentConfigRequest>, ErrorType> {
+	AppResponse::builder()
+		.body(ValidateDeploymentConfigResponse {
+			valid: errors.is_empty(),
+			errors,
+			resource_impact,
+		})
+		.headers(())
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
@@ -30,6 +30,7 @@ async fn is_domain_personal(
 		redis: _,
 		client_ip: _,
 		config,
+		clock: _,
 	}: AppRequest<'_, IsDomainPersonalRequest>,
 ) -> Result<AppResponse<IsDomainPersonalRequest>, ErrorType> {
 	info!("Starting: Check for is domain personal");
@@ -60,10 +61,37 @@ async fn add_dns_record(
 		client_ip: _,
 		config,
 		user_data,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, AddDNSRecordRequest>,
 ) -> Result<AppResponse<AddDNSRecordRequest>, ErrorType> {
 	info!("Starting: Add DNS record");
 
+	body.r#type.validate().inspect_err(|error| {
+		debug!("Invalid {} record: {error:?}", body.r#type);
+	})?;
+
+	let existing_types = query!(
+		r#"
+		SELECT
+			type::TEXT AS "type!"
+		FROM
+			patr_domain_dns_record
+		WHERE
+			domain_id = $1 AND
+			name = $2;
+		"#,
+		path.domain_id as _,
+		body.name,
+	)
+	.fetch_all(&mut **database)
+	.await?;
+
+	body.r#type
+		.validate_coexistence(existing_types.iter().map(|record| record.r#type.as_str()))
+		.inspect_err(|_| {
+			debug!("{} record conflicts with the existing records", body.r#type);
+		})?;
+
 	// LOGIC
 
 	AppResponse::builder()
@@ -87,6 +115,7 @@ async fn add_domain_to_workspace(
 		client_ip: _,
 		config,
 		user_data,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, AddDomainToWorkspaceRequest>,
 ) -> Result<AppResponse<AddDomainToWorkspaceRequest>, ErrorType> {
 	info!("Starting: Add domain to workspace");
@@ -114,6 +143,7 @@ async fn delete_dns_record(
 		client_ip: _,
 		config,
 		user_data,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, DeleteDNSRecordRequest>,
 ) -> Result<AppResponse<DeleteDNSRecordRequest>, ErrorType> {
 	info!("Starting: Delete DNS record");
@@ -141,6 +171,7 @@ async fn delete_domain_in_workspace(
 		client_ip: _,
 		config,
 		user_data,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, DeleteDomainInWorkspaceRequest>,
 ) -> Result<AppResponse<DeleteDomainInWorkspaceRequest>, ErrorType> {
 	info!("Starting: Delete domain in workspace");
@@ -168,6 +199,7 @@ async fn get_doamin_dns_record(
 		client_ip: _,
 		config,
 		user_data,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, GetDomainDNSRecordRequest>,
 ) -> Result<AppResponse<GetDomainDNSRecordRequest>, ErrorType> {
 	info!("Starting: Get domain DNS record");
@@ -197,6 +229,7 @@ async fn get_domain_info_in_workspace(
 		client_ip: _,
 		config,
 		user_data,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, GetDomainInfoInWorkspaceRequest>,
 ) -> Result<AppResponse<GetDomainInfoInWorkspaceRequest>, ErrorType> {
 	info!("Starting: Get domain info in workspace");
@@ -226,6 +259,7 @@ async fn get_domains_for_workspace(
 		client_ip: _,
 		config,
 		user_data,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, GetDomainsForWorkspaceRequest>,
 ) -> Result<AppResponse<GetDomainsForWorkspaceRequest>, ErrorType> {
 	info!("Starting: Get domains for workspace");
@@ -255,10 +289,44 @@ async fn update_domain_dns_record(
 		client_ip: _,
 		config,
 		user_data,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, UpdateDomainDNSRecordRequest>,
 ) -> Result<AppResponse<UpdateDomainDNSRecordRequest>, ErrorType> {
 	info!("Starting: Update domain DNS record");
 
+	let record = query!(
+		r#"
+		SELECT
+			type::TEXT AS "type!",
+			value,
+			priority,
+			proxied
+		FROM
+			patr_domain_dns_record
+		WHERE
+			id = $1 AND
+			domain_id = $2;
+		"#,
+		path.record_id as _,
+		path.domain_id as _,
+	)
+	.fetch_optional(&mut **database)
+	.await?
+	.or_not_found()?;
+
+	// The record is validated with the updated values, since a target that is
+	// valid for one type of record may not be valid for another
+	dns_record_value(
+		&record.r#type,
+		body.target.clone().unwrap_or(record.value),
+		body.priority.map(i32::from).or(record.priority),
+		body.proxied.or(record.proxied),
+	)
+	.and_then(|value| value.validate())
+	.inspect_err(|error| {
+		debug!("Invalid {} record: {error:?}", record.r#type);
+	})?;
+
 	// LOGIC
 
 	AppResponse::builder()
@@ -269,6 +337,42 @@ async fn update_domain_dns_record(
 		.into_result()
 }
 
+/// Creates the value of a DNS record from the columns it is stored as. If the
+/// value cannot be parsed for the type of the record, the error has the name
+/// of the invalid field.
+fn dns_record_value(
+	r#type: &str,
+	value: String,
+	priority: Option<i32>,
+	proxied: Option<bool>,
+) -> Result<DnsRecordValue, ErrorType> {
+	let invalid_target = |_| ErrorType::InvalidDnsRecord("target");
+	let proxied = proxied.unwrap_or(false);
+
+	Ok(match r#type {
+		"A" => DnsRecordValue::A {
+			target: value.parse().map_err(invalid_target)?,
+			proxied,
+		},
+		"AAAA" => DnsRecordValue::AAAA {
+			target: value.parse().map_err(invalid_target)?,
+			proxied,
+		},
+		"MX" => DnsRecordValue::MX {
+			priority: priority
+				.and_then(|priority| u16::try_from(priority).ok())
+				.ok_or(ErrorType::InvalidDnsRecord("priority"))?,
+			target: value,
+		},
+		"TXT" => DnsRecordValue::TXT { target: value },
+		"CNAME" => DnsRecordValue::CNAME {
+			target: value,
+			proxied,
+		},
+		_ => return Err(ErrorType::InvalidDnsRecord("type")),
+	})
+}
+
 async fn verify_domain_in_workspace(
 	AuthenticatedAppRequest {
 		request: ProcessedApiRequest {
@@ -282,6 +386,7 @@ async fn verify_domain_in_workspace(
 		client_ip: _,
 		config,
 		user_data,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, VerifyDomainInWorkspaceRequest>,
 ) -> Result<AppResponse<VerifyDomainInWorkspaceRequest>, ErrorType> {
 	info!("Starting: Check to verify domain in workspace");
@@ -295,3 +400,35 @@ async fn verify_domain_in_workspace(
 		.build()
 		.into_result()
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn invalid_stored_records_name_the_invalid_field() {
+		assert_eq!(
+			dns_record_value("A", "1.1.1.1".to_string(), None, Some(true)),
+			Ok(DnsRecordValue::A {
+				target: "1.1.1.1".parse().unwrap(),
+				proxied: true,
+			})
+		);
+		assert_eq!(
+			dns_record_value("A", "2606:4700:4700::1111".to_string(), None, None),
+			Err(ErrorType::InvalidDnsRecord("target"))
+		);
+		assert_eq!(
+			dns_record_value("MX", "mail.example.com".to_string(), None, None),
+			Err(ErrorType::InvalidDnsRecord("priority"))
+		);
+		assert_eq!(
+			dns_record_value("MX", "mail.example.com".to_string(), Some(-1), None),
+			Err(ErrorType::InvalidDnsRecord("priority"))
+		);
+		assert_eq!(
+			dns_record_value("SRV", "example.com".to_string(), None, None),
+			Err(ErrorType::InvalidDnsRecord("type"))
+		);
+	}
+}
@@ -0,0 +1,72 @@
+use axum::http::StatusCode;
+use models::api::workspace::egress_policy::*;
+
+use crate::prelude::*;
+
+/// The handler to remove the egress policy of a workspace. The change is
+/// recorded in the audit log, and the runners of the workspace are sent an
+/// empty policy, so that they allow all destinations again.
+pub async fn delete_egress_policy(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: DeleteEgressPolicyPath { workspace_id },
+				query: (),
+				headers:
+					DeleteEgressPolicyRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: DeleteEgressPolicyRequestProcessed,
+			},
+		database,
+		redis,
+		client_ip: _,
+		config,
+		user_data,
+		clock,
+	}: AuthenticatedAppRequest<'_, DeleteEgressPolicyRequest>,
+) -> Result<AppResponse<DeleteEgressPolicyRequest>, ErrorType> {
+	info!("Removing the egress policy of the workspace `{workspace_id}`");
+
+	let removed = query!(
+		r#"
+		DELETE FROM
+			workspace_egress_rule
+		WHERE
+			workspace_id = $1;
+		"#,
+		workspace_id as _,
+	)
+	.execute(&mut **database)
+	.await?
+	.rows_affected();
+
+	if removed == 0 {
+		return Err(ErrorType::ResourceDoesNotExist);
+	}
+
+	super::record_egress_policy_change(
+		&mut **database,
+		workspace_id,
+		user_data.login_id,
+		clock.now(),
+	)
+	.await?;
+
+	super::notify_runners(
+		&mut **database,
+		redis,
+		&config,
+		workspace_id,
+		WorkspaceEgressPolicy::default(),
+	)
+	.await?;
+
+	AppResponse::builder()
+		.body(DeleteEgressPolicyResponse)
+		.headers(())
+		.status_code(StatusCode::RESET_CONTENT)
+		.build()
+		.into_result()
+}
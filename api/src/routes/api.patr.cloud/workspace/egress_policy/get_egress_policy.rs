@@ -0,0 +1,64 @@
+use axum::http::StatusCode;
+use models::api::workspace::egress_policy::*;
+
+use crate::prelude::*;
+
+/// The handler to get the egress policy of a workspace. A workspace without
+/// any rules has an empty policy, which allows all destinations.
+pub async fn get_egress_policy(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: GetEgressPolicyPath { workspace_id },
+				query: (),
+				headers:
+					GetEgressPolicyRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: GetEgressPolicyRequestProcessed,
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config: _,
+		user_data: _,
+		clock: _,
+	}: AuthenticatedAppRequest<'_, GetEgressPolicyRequest>,
+) -> Result<AppResponse<GetEgressPolicyRequest>, ErrorType> {
+	info!("Getting the egress policy of the workspace `{workspace_id}`");
+
+	let mut policy = WorkspaceEgressPolicy::default();
+
+	query!(
+		r#"
+		SELECT
+			rule,
+			action = 'allow' AS "allowed!"
+		FROM
+			workspace_egress_rule
+		WHERE
+			workspace_id = $1
+		ORDER BY
+			rule;
+		"#,
+		workspace_id as _,
+	)
+	.fetch_all(&mut **database)
+	.await?
+	.into_iter()
+	.for_each(|row| {
+		if row.allowed {
+			policy.allow.push(row.rule);
+		} else {
+			policy.deny.push(row.rule);
+		}
+	});
+
+	AppResponse::builder()
+		.body(GetEgressPolicyResponse { policy })
+		.headers(())
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
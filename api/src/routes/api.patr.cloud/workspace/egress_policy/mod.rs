@@ -0,0 +1,110 @@
+use axum::Router;
+use models::api::workspace::{
+	egress_policy::WorkspaceEgressPolicy,
+	runner::StreamRunnerDataForWorkspaceServerMsg,
+};
+use rustis::client::Client as RedisClient;
+use time::OffsetDateTime;
+
+use crate::{
+	prelude::*,
+	utils::{config::AppConfig, runner},
+};
+
+mod delete_egress_policy;
+mod get_egress_policy;
+mod set_egress_policy;
+
+pub use self::{delete_egress_policy::*, get_egress_policy::*, set_egress_policy::*};
+
+#[instrument(skip(state))]
+pub async fn setup_routes(state: &AppState) -> Router {
+	Router::new()
+		.mount_auth_endpoint(delete_egress_policy, state)
+		.mount_auth_endpoint(get_egress_policy, state)
+		.mount_auth_endpoint(set_egress_policy, state)
+}
+
+/// Records a change to the egress policy of a workspace in the audit log of
+/// the workspace, so that the workspace admins can see who changed it
+async fn record_egress_policy_change(
+	connection: &mut DatabaseConnection,
+	workspace_id: Uuid,
+	login_id: Uuid,
+	now: OffsetDateTime,
+) -> Result<(), ErrorType> {
+	query!(
+		r#"
+		INSERT INTO
+			audit_log(
+				id,
+				workspace_id,
+				resource_id,
+				timestamp,
+				action,
+				login_id
+			)
+		VALUES
+			($1, $2, $2, $3, 'update', $4);
+		"#,
+		Uuid::new_v4() as _,
+		workspace_id as _,
+		now,
+		login_id as _,
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	Ok(())
+}
+
+/// Sends the new egress policy of a workspace to all the runners of the
+/// workspace, so that they can enforce it. A runner that can't be reached
+/// fetches the latest policy when it reconnects, so failing to reach one
+/// doesn't fail the request.
+async fn notify_runners(
+	connection: &mut DatabaseConnection,
+	redis: &RedisClient,
+	config: &AppConfig,
+	workspace_id: Uuid,
+	policy: WorkspaceEgressPolicy,
+) -> Result<(), ErrorType> {
+	let runners = query!(
+		r#"
+		SELECT
+			id
+		FROM
+			runner
+		WHERE
+			workspace_id = $1 AND
+			deleted IS NULL;
+		"#,
+		workspace_id as _,
+	)
+	.fetch_all(&mut *connection)
+	.await?;
+
+	let message = StreamRunnerDataForWorkspaceServerMsg::WorkspaceEgressPolicyUpdated {
+		workspace_id,
+		policy,
+	};
+
+	for runner in runners {
+		if let Err(err) = runner::send_message(
+			redis,
+			&config.runner,
+			workspace_id,
+			runner.id.into(),
+			&message,
+		)
+		.await
+		{
+			warn!(
+				"Failed to send the egress policy to runner `{}`: {err}",
+				runner.id
+			);
+		}
+	}
+
+	Ok(())
+}
@@ -0,0 +1,102 @@
+use axum::http::StatusCode;
+use models::api::workspace::egress_policy::*;
+
+use crate::prelude::*;
+
+/// The handler to set the egress policy of a workspace. All the rules are
+/// checked to be valid CIDR ranges or domains, and are stored in their
+/// normalized form. The existing rules are replaced, the change is recorded in
+/// the audit log, and the runners of the workspace are sent the new policy.
+pub async fn set_egress_policy(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: SetEgressPolicyPath { workspace_id },
+				query: (),
+				headers:
+					SetEgressPolicyRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: SetEgressPolicyRequestProcessed { allow, deny },
+			},
+		database,
+		redis,
+		client_ip: _,
+		config,
+		user_data,
+		clock,
+	}: AuthenticatedAppRequest<'_, SetEgressPolicyRequest>,
+) -> Result<AppResponse<SetEgressPolicyRequest>, ErrorType> {
+	info!("Setting the egress policy of the workspace `{workspace_id}`");
+
+	let policy = WorkspaceEgressPolicy { allow, deny };
+	policy.validate().inspect_err(|_| {
+		debug!("Invalid egress policy for workspace `{workspace_id}`: {policy:?}");
+	})?;
+
+	let normalize = |rules: Vec<String>| {
+		rules
+			.into_iter()
+			.map(|rule| rule.parse::<EgressRule>().map(|rule| rule.to_string()))
+			.collect::<Result<Vec<_>, _>>()
+	};
+	let policy = WorkspaceEgressPolicy {
+		allow: normalize(policy.allow)?,
+		deny: normalize(policy.deny)?,
+	};
+
+	query!(
+		r#"
+		DELETE FROM
+			workspace_egress_rule
+		WHERE
+			workspace_id = $1;
+		"#,
+		workspace_id as _,
+	)
+	.execute(&mut **database)
+	.await?;
+
+	query!(
+		r#"
+		INSERT INTO
+			workspace_egress_rule(
+				workspace_id,
+				rule,
+				action
+			)
+		SELECT
+			$1,
+			UNNEST($2::TEXT[]),
+			'allow'::EGRESS_RULE_ACTION
+		UNION ALL
+		SELECT
+			$1,
+			UNNEST($3::TEXT[]),
+			'deny'::EGRESS_RULE_ACTION;
+		"#,
+		workspace_id as _,
+		&policy.allow,
+		&policy.deny,
+	)
+	.execute(&mut **database)
+	.await?;
+
+	super::record_egress_policy_change(
+		&mut **database,
+		workspace_id,
+		user_data.login_id,
+		clock.now(),
+	)
+	.await?;
+
+	super::notify_runners(&mut **database, redis, &config, workspace_id, policy).await?;
+
+	AppResponse::builder()
+		.body(SetEgressPolicyResponse)
+		.headers(())
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
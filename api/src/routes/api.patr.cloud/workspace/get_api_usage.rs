@@ -0,0 +1,103 @@
+use axum::http::StatusCode;
+use models::api::workspace::*;
+use time::OffsetDateTime;
+
+use crate::{api_usage_rollup::histogram_percentile, prelude::*};
+
+/// The handler to get the usage of the API by a workspace. The usage is read
+/// from the aggregated buckets written by the API usage rollup task, so the
+/// most recent few minutes of usage will not be available yet.
+pub async fn get_api_usage(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: GetApiUsagePath { workspace_id },
+				query: GetApiUsageQuery {
+					start_time,
+					end_time,
+					endpoint,
+				},
+				headers:
+					GetApiUsageRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: GetApiUsageRequestProcessed,
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config: _,
+		user_data: _,
+		clock: _,
+	}: AuthenticatedAppRequest<'_, GetApiUsageRequest>,
+) -> Result<AppResponse<GetApiUsageRequest>, ErrorType> {
+	info!("Getting the API usage of the workspace `{workspace_id}`");
+
+	let end_time = end_time.unwrap_or_else(OffsetDateTime::now_utc);
+
+	if start_time > end_time || end_time - start_time > constants::MAX_API_USAGE_QUERY_RANGE {
+		return Err(ErrorType::WrongParameters);
+	}
+
+	let usage = query!(
+		r#"
+		SELECT
+			bucket_start,
+			endpoint,
+			request_count,
+			error_count,
+			latency_histogram,
+			max_latency_millis
+		FROM
+			workspace_api_usage
+		WHERE
+			workspace_id = $1 AND
+			bucket_start >= $2 AND
+			bucket_start < $3 AND
+			($4::TEXT IS NULL OR endpoint = $4)
+		ORDER BY
+			bucket_start,
+			endpoint;
+		"#,
+		workspace_id as _,
+		start_time,
+		end_time,
+		endpoint,
+	)
+	.fetch_all(&mut **database)
+	.await?
+	.into_iter()
+	.map(|row| {
+		let max_latency_millis = row.max_latency_millis.unsigned_abs();
+		ApiUsageBucket {
+			bucket_start: row.bucket_start,
+			endpoint: row.endpoint,
+			request_count: row.request_count.unsigned_abs(),
+			error_count: row.error_count.unsigned_abs(),
+			p50_latency_millis: histogram_percentile(
+				&row.latency_histogram,
+				max_latency_millis,
+				50,
+			),
+			p95_latency_millis: histogram_percentile(
+				&row.latency_histogram,
+				max_latency_millis,
+				95,
+			),
+		}
+	})
+	.collect();
+
+	AppResponse::builder()
+		.body(GetApiUsageResponse {
+			bucket_size_seconds: constants::API_USAGE_BUCKET_SIZE
+				.whole_seconds()
+				.unsigned_abs(),
+			usage,
+		})
+		.headers(())
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
@@ -0,0 +1,61 @@
+use axum::http::StatusCode;
+use models::api::workspace::*;
+
+use crate::prelude::*;
+
+/// The handler to get the feature flags of a workspace. The flags configured
+/// for the instance are used as the defaults, which can then be overridden for
+/// each workspace in the database.
+pub async fn get_feature_flags(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: GetFeatureFlagsPath { workspace_id },
+				query: (),
+				headers:
+					GetFeatureFlagsRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: GetFeatureFlagsRequestProcessed,
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config,
+		user_data: _,
+		clock: _,
+	}: AuthenticatedAppRequest<'_, GetFeatureFlagsRequest>,
+) -> Result<AppResponse<GetFeatureFlagsRequest>, ErrorType> {
+	info!("Getting feature flags of the workspace `{workspace_id}`");
+
+	let mut flags = config.feature_flags.clone();
+
+	query!(
+		r#"
+		SELECT
+			name,
+			enabled
+		FROM
+			workspace_feature_flag
+		WHERE
+			workspace_id = $1;
+		"#,
+		workspace_id as _,
+	)
+	.fetch_all(&mut **database)
+	.await?
+	.into_iter()
+	.for_each(|row| {
+		flags.insert(row.name, row.enabled);
+	});
+
+	AppResponse::builder()
+		.body(GetFeatureFlagsResponse {
+			flags: FeatureFlags(flags),
+		})
+		.headers(())
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
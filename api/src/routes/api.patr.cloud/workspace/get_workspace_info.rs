@@ -4,7 +4,10 @@ use models::api::workspace::*;
 use crate::prelude::*;
 
 /// The handler to get the information of a workspace. This includes the
-/// workspace's name, the user who created it, and the date it was created.
+/// workspace's name, the user who created it, and the date it was created, as
+/// well as whether the requesting user is the super admin of the workspace,
+/// the maximum number of replicas its deployments can be scaled to, and whether
+/// it blocks deploying images with critical vulnerabilities.
 pub async fn get_workspace_info(
 	AuthenticatedAppRequest {
 		request:
@@ -21,8 +24,9 @@ pub async fn get_workspace_info(
 		database,
 		redis: _,
 		client_ip: _,
-		config: _,
-		user_data: _,
+		config,
+		user_data,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, GetWorkspaceInfoRequest>,
 ) -> Result<AppResponse<GetWorkspaceInfoRequest>, ErrorType> {
 	info!("Getting information about the workspace `{workspace_id}`");
@@ -41,7 +45,9 @@ pub async fn get_workspace_info(
 	)
 	.fetch_optional(&mut **database)
 	.await?
-	.ok_or(ErrorType::ResourceDoesNotExist)?;
+	.or_not_found()?;
+
+	let is_super_admin = workspace.super_admin_id == user_data.id.into();
 
 	AppResponse::builder()
 		.body(GetWorkspaceInfoResponse {
@@ -52,6 +58,13 @@ pub async fn get_workspace_info(
 					super_admin_id: workspace.super_admin_id.into(),
 				},
 			),
+			is_super_admin,
+			max_replicas: super::deployment::effective_max_replicas(
+				config.deployment.max_replicas,
+				workspace.max_replicas,
+			),
+			block_critical_vulnerabilities: workspace.block_critical_vulnerabilities,
+			require_deployment_change_approval: workspace.require_deployment_change_approval,
 		})
 		.headers(())
 		.status_code(StatusCode::OK)
@@ -23,6 +23,7 @@ pub async fn is_name_available(
 		client_ip: _,
 		config: _,
 		user_data: _,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, IsWorkspaceNameAvailableRequest>,
 ) -> Result<AppResponse<IsWorkspaceNameAvailableRequest>, ErrorType> {
 	info!("Checking if workspace name `{name}` is available");
@@ -0,0 +1,400 @@
+use axum::http::StatusCode;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD as BASE64_URL, Engine};
+use models::api::workspace::*;
+use time::OffsetDateTime;
+
+use crate::prelude::*;
+
+/// The handler to list the activity of a workspace. The activity is merged
+/// from the events of the deployments, the deploy history, the API tokens that
+/// have access to the workspace, the audit log and the creation and deletion
+/// of resources. The entries are ordered by their timestamp, and then by an ID
+/// that is unique to each entry, so that the order is the same across pages.
+/// The cursor is the position of the last entry of a page in that order.
+pub async fn list_workspace_activity(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: ListWorkspaceActivityPath { workspace_id },
+				query: ListWorkspaceActivityQuery {
+					cursor,
+					limit,
+					category,
+				},
+				headers:
+					ListWorkspaceActivityRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: ListWorkspaceActivityRequestProcessed,
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config: _,
+		user_data: _,
+		clock: _,
+	}: AuthenticatedAppRequest<'_, ListWorkspaceActivityRequest>,
+) -> Result<AppResponse<ListWorkspaceActivityRequest>, ErrorType> {
+	info!("Listing the activity of the workspace `{workspace_id}`");
+
+	let (before_timestamp, before_id) = match cursor {
+		Some(cursor) => {
+			let (timestamp, id) = decode_cursor(&cursor).ok_or(ErrorType::WrongParameters)?;
+			(Some(timestamp), Some(id))
+		}
+		None => (None, None),
+	};
+	let limit = limit.unwrap_or(constants::DEFAULT_WORKSPACE_ACTIVITY_LIMIT);
+
+	// One more entry than requested is fetched, to know if there is a next
+	// page. The category, the cursor and the limit are applied to each of the
+	// sources before they are merged, so that only the entries that can be on
+	// the page are read from each source
+	let mut rows = query!(
+		r#"
+		(
+			SELECT
+				CONCAT('deploymentEvent:', deployment_event.id) AS "id!",
+				deployment_event.created AS "timestamp!",
+				'deployment' AS "category!",
+				CASE deployment_event.event_type
+					WHEN 'scheduled_start' THEN 'scheduledStart'
+					WHEN 'scheduled_stop' THEN 'scheduledStop'
+					WHEN 'alert_fired' THEN 'alertFired'
+					WHEN 'alert_resolved' THEN 'alertResolved'
+					WHEN 'scaled_to_zero' THEN 'scaledToZero'
+					ELSE 'coldStarted'
+				END AS "action!",
+				deployment_event.deployment_id AS resource_id,
+				NULL::UUID AS user_id
+			FROM
+				deployment_event
+			INNER JOIN
+				deployment
+			ON
+				deployment.id = deployment_event.deployment_id
+			WHERE
+				deployment.workspace_id = $1 AND
+				($2::TEXT IS NULL OR $2 = 'deployment') AND
+				(
+					$3::TIMESTAMPTZ IS NULL OR
+					deployment_event.created < $3 OR
+					(
+						deployment_event.created = $3 AND
+						CONCAT('deploymentEvent:', deployment_event.id) < $4
+					)
+				)
+			ORDER BY
+				2 DESC,
+				1 DESC
+			LIMIT $5
+		)
+		UNION ALL
+		(
+			SELECT
+				CONCAT(
+					'deployed:',
+					deployment_deploy_history.deployment_id,
+					':',
+					deployment_deploy_history.image_digest
+				),
+				deployment_deploy_history.created,
+				'deployment',
+				'deployed',
+				deployment_deploy_history.deployment_id,
+				NULL::UUID
+			FROM
+				deployment_deploy_history
+			INNER JOIN
+				deployment
+			ON
+				deployment.id = deployment_deploy_history.deployment_id
+			WHERE
+				deployment.workspace_id = $1 AND
+				($2::TEXT IS NULL OR $2 = 'deployment') AND
+				(
+					$3::TIMESTAMPTZ IS NULL OR
+					deployment_deploy_history.created < $3 OR
+					(
+						deployment_deploy_history.created = $3 AND
+						CONCAT(
+							'deployed:',
+							deployment_deploy_history.deployment_id,
+							':',
+							deployment_deploy_history.image_digest
+						) < $4
+					)
+				)
+			ORDER BY
+				2 DESC,
+				1 DESC
+			LIMIT $5
+		)
+		UNION ALL
+		(
+			SELECT
+				CONCAT('tokenCreated:', user_api_token.token_id),
+				user_api_token.created,
+				'apiToken',
+				'created',
+				user_api_token.token_id,
+				user_api_token.user_id
+			FROM
+				user_api_token
+			INNER JOIN
+				user_api_token_workspace_permission_type
+			ON
+				user_api_token_workspace_permission_type.token_id = user_api_token.token_id
+			WHERE
+				user_api_token_workspace_permission_type.workspace_id = $1 AND
+				($2::TEXT IS NULL OR $2 = 'apiToken') AND
+				(
+					$3::TIMESTAMPTZ IS NULL OR
+					user_api_token.created < $3 OR
+					(
+						user_api_token.created = $3 AND
+						CONCAT('tokenCreated:', user_api_token.token_id) < $4
+					)
+				)
+			ORDER BY
+				2 DESC,
+				1 DESC
+			LIMIT $5
+		)
+		UNION ALL
+		(
+			SELECT
+				CONCAT('tokenRevoked:', user_api_token.token_id),
+				user_api_token.revoked,
+				'apiToken',
+				'revoked',
+				user_api_token.token_id,
+				user_api_token.user_id
+			FROM
+				user_api_token
+			INNER JOIN
+				user_api_token_workspace_permission_type
+			ON
+				user_api_token_workspace_permission_type.token_id = user_api_token.token_id
+			WHERE
+				user_api_token_workspace_permission_type.workspace_id = $1 AND
+				user_api_token.revoked IS NOT NULL AND
+				($2::TEXT IS NULL OR $2 = 'apiToken') AND
+				(
+					$3::TIMESTAMPTZ IS NULL OR
+					user_api_token.revoked < $3 OR
+					(
+						user_api_token.revoked = $3 AND
+						CONCAT('tokenRevoked:', user_api_token.token_id) < $4
+					)
+				)
+			ORDER BY
+				2 DESC,
+				1 DESC
+			LIMIT $5
+		)
+		UNION ALL
+		(
+			SELECT
+				*
+			FROM
+				(
+					SELECT
+						CONCAT('audit:', audit_log.id) AS id,
+						audit_log.timestamp,
+						CASE
+							WHEN audit_log.resource_id = $1 THEN 'workspace'
+							WHEN "user".id IS NOT NULL THEN 'member'
+							WHEN resource_type.name = 'deployment' THEN 'deployment'
+							WHEN resource_type.name IN ('domain', 'dns_record') THEN 'dns'
+							ELSE 'resource'
+						END AS category,
+						CASE audit_log.action
+							WHEN 'create' THEN 'created'
+							WHEN 'update' THEN 'updated'
+							ELSE 'deleted'
+						END,
+						audit_log.resource_id,
+						user_login.user_id
+					FROM
+						audit_log
+					LEFT JOIN
+						"user"
+					ON
+						"user".id = audit_log.resource_id
+					LEFT JOIN
+						resource
+					ON
+						resource.id = audit_log.resource_id
+					LEFT JOIN
+						resource_type
+					ON
+						resource_type.id = resource.resource_type_id
+					LEFT JOIN
+						user_login
+					ON
+						user_login.login_id = audit_log.login_id
+					WHERE
+						audit_log.workspace_id = $1 AND
+						($3::TIMESTAMPTZ IS NULL OR audit_log.timestamp <= $3)
+				) AS audit_activity
+			WHERE
+				($2::TEXT IS NULL OR category = $2) AND
+				($3::TIMESTAMPTZ IS NULL OR timestamp < $3 OR id < $4)
+			ORDER BY
+				2 DESC,
+				1 DESC
+			LIMIT $5
+		)
+		UNION ALL
+		(
+			SELECT
+				*
+			FROM
+				(
+					SELECT
+						CONCAT(event.action, ':', resource.id) AS id,
+						event.timestamp,
+						CASE
+							WHEN resource_type.name = 'workspace' THEN 'workspace'
+							WHEN resource_type.name = 'deployment' THEN 'deployment'
+							WHEN resource_type.name IN ('domain', 'dns_record') THEN 'dns'
+							ELSE 'resource'
+						END AS category,
+						event.action,
+						resource.id,
+						NULL::UUID
+					FROM
+						resource
+					LEFT JOIN
+						resource_type
+					ON
+						resource_type.id = resource.resource_type_id
+					CROSS JOIN LATERAL
+						(
+							VALUES
+								('created', resource.created),
+								('deleted', resource.deleted)
+						) AS event(action, timestamp)
+					WHERE
+						resource.owner_id = $1 AND
+						event.timestamp IS NOT NULL AND
+						($3::TIMESTAMPTZ IS NULL OR event.timestamp <= $3)
+				) AS resource_activity
+			WHERE
+				($2::TEXT IS NULL OR category = $2) AND
+				($3::TIMESTAMPTZ IS NULL OR timestamp < $3 OR id < $4)
+			ORDER BY
+				2 DESC,
+				1 DESC
+			LIMIT $5
+		)
+		ORDER BY
+			2 DESC,
+			1 DESC
+		LIMIT $5;
+		"#,
+		workspace_id as _,
+		category.map(|category| category.to_string()),
+		before_timestamp,
+		before_id,
+		i64::from(limit) + 1,
+	)
+	.fetch_all(&mut **database)
+	.await?;
+
+	let next_cursor = if rows.len() > limit as usize {
+		rows.truncate(limit as usize);
+		rows.last().map(|row| encode_cursor(row.timestamp, &row.id))
+	} else {
+		None
+	};
+
+	let activity = rows
+		.into_iter()
+		.map(|row| {
+			Ok(WorkspaceActivity {
+				timestamp: row.timestamp,
+				category: row.category.parse().map_err(ErrorType::server_error)?,
+				action: row.action,
+				resource_id: row.resource_id.map(Into::into),
+				user_id: row.user_id.map(Into::into),
+			})
+		})
+		.collect::<Result<_, ErrorType>>()?;
+
+	AppResponse::builder()
+		.body(ListWorkspaceActivityResponse {
+			activity,
+			next_cursor,
+		})
+		.headers(())
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
+
+/// Encodes the position of an entry in the activity feed as a cursor. The
+/// cursor is opaque to the clients, so that its format can be changed later
+fn encode_cursor(timestamp: OffsetDateTime, id: &str) -> String {
+	BASE64_URL.encode(format!("{}|{id}", timestamp.unix_timestamp_nanos()))
+}
+
+/// Decodes a cursor created by [`encode_cursor`], returning `None` if the
+/// cursor is malformed
+fn decode_cursor(cursor: &str) -> Option<(OffsetDateTime, String)> {
+	let cursor = String::from_utf8(BASE64_URL.decode(cursor).ok()?).ok()?;
+	let (timestamp, id) = cursor.split_once('|')?;
+	let timestamp = OffsetDateTime::from_unix_timestamp_nanos(timestamp.parse().ok()?).ok()?;
+
+	Some((timestamp, id.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+	use base64::Engine;
+	use time::OffsetDateTime;
+
+	use super::{decode_cursor, encode_cursor, BASE64_URL};
+
+	#[test]
+	fn cursor_round_trips() {
+		let timestamp = OffsetDateTime::from_unix_timestamp_nanos(1_700_000_000_123_456_000)
+			.expect("timestamp to be valid");
+
+		for id in [
+			"deploymentEvent:0123456789abcdef0123456789abcdef",
+			"deployed:0123456789abcdef0123456789abcdef:sha256:abc",
+			"audit:with|separator",
+		] {
+			let cursor = encode_cursor(timestamp, id);
+			assert_eq!(decode_cursor(&cursor), Some((timestamp, id.to_string())));
+		}
+	}
+
+	#[test]
+	fn cursors_before_the_epoch_round_trip() {
+		let timestamp = OffsetDateTime::from_unix_timestamp(-1).expect("timestamp to be valid");
+
+		let cursor = encode_cursor(timestamp, "created:id");
+		assert_eq!(
+			decode_cursor(&cursor),
+			Some((timestamp, "created:id".to_string()))
+		);
+	}
+
+	#[test]
+	fn malformed_cursors_are_rejected() {
+		for cursor in [
+			"".to_string(),
+			"not base64!".to_string(),
+			BASE64_URL.encode("no separator"),
+			BASE64_URL.encode("not a number|audit:id"),
+			BASE64_URL.encode(format!("{}|audit:id", i128::MAX)),
+			BASE64_URL.encode([0xff, 0xfe, b'|']),
+		] {
+			assert_eq!(decode_cursor(&cursor), None, "cursor `{cursor}`");
+		}
+	}
+}
@@ -0,0 +1,137 @@
+use std::collections::BTreeMap;
+
+use axum::http::StatusCode;
+use models::{
+	api::{
+		user::{ApiTokenStatus, ListedApiToken, UserApiToken},
+		workspace::*,
+	},
+	rbac::WorkspacePermission,
+	utils::TotalCountHeader,
+};
+use time::OffsetDateTime;
+
+use crate::prelude::*;
+
+/// The handler to list all the API tokens that have been granted access to a
+/// workspace, across all the users of the workspace. The permissions of the
+/// tokens are not listed.
+pub async fn list_workspace_api_tokens(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: ListWorkspaceApiTokensPath { workspace_id },
+				query:
+					Paginated {
+						data: ListWorkspaceApiTokensQuery { status, user_id },
+						count,
+						page,
+					},
+				headers:
+					ListWorkspaceApiTokensRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: ListWorkspaceApiTokensRequestProcessed,
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config: _,
+		user_data: _,
+		clock: _,
+	}: AuthenticatedAppRequest<'_, ListWorkspaceApiTokensRequest>,
+) -> Result<AppResponse<ListWorkspaceApiTokensRequest>, ErrorType> {
+	info!("Listing the API tokens of the workspace `{workspace_id}`");
+
+	let now = OffsetDateTime::now_utc();
+	let mut total_count = 0;
+	let tokens = query!(
+		r#"
+		SELECT
+			user_api_token.token_id,
+			user_api_token.name,
+			user_api_token.user_id,
+			"user".username,
+			user_api_token.token_nbf,
+			user_api_token.token_exp,
+			user_api_token.allowed_ips,
+			user_api_token.created,
+			user_api_token.revoked,
+			user_api_token.last_used,
+			user_api_token_workspace_permission_type.token_permission_type = 'super_admin'
+				AS "is_super_admin!",
+			COUNT(*) OVER() AS "total_count!"
+		FROM
+			user_api_token
+		INNER JOIN
+			user_api_token_workspace_permission_type
+		ON
+			user_api_token_workspace_permission_type.token_id = user_api_token.token_id
+		INNER JOIN
+			"user"
+		ON
+			"user".id = user_api_token.user_id
+		WHERE
+			user_api_token_workspace_permission_type.workspace_id = $1 AND
+			($2::UUID IS NULL OR user_api_token.user_id = $2) AND
+			CASE $3::TEXT
+				WHEN 'active' THEN
+					(user_api_token.revoked IS NULL OR user_api_token.revoked > NOW()) AND
+					(user_api_token.token_exp IS NULL OR user_api_token.token_exp >= NOW())
+				WHEN 'expired' THEN
+					(user_api_token.revoked IS NULL OR user_api_token.revoked > NOW()) AND
+					user_api_token.token_exp < NOW()
+				WHEN 'revoked' THEN
+					user_api_token.revoked <= NOW()
+				ELSE
+					user_api_token.revoked IS NULL
+			END
+		ORDER BY
+			user_api_token.created DESC
+		LIMIT $4
+		OFFSET $5;
+		"#,
+		workspace_id as _,
+		user_id as _,
+		status.map(|status| status.to_string()),
+		count as i32,
+		(count * page) as i32,
+	)
+	.fetch_all(&mut **database)
+	.await?
+	.into_iter()
+	.map(|row| {
+		total_count = row.total_count;
+		WithId::new(
+			row.token_id,
+			WorkspaceApiToken {
+				token: ListedApiToken {
+					token: UserApiToken {
+						name: row.name,
+						permissions: BTreeMap::<Uuid, WorkspacePermission>::new(),
+						token_nbf: row.token_nbf,
+						token_exp: row.token_exp,
+						allowed_ips: row.allowed_ips,
+						created: row.created,
+					},
+					status: ApiTokenStatus::from_timestamps(row.token_exp, row.revoked, now),
+					last_used: row.last_used,
+				},
+				user_id: row.user_id.into(),
+				username: row.username,
+				is_super_admin: row.is_super_admin,
+			},
+		)
+	})
+	.collect();
+
+	AppResponse::builder()
+		.body(ListWorkspaceApiTokensResponse { tokens })
+		.headers(ListWorkspaceApiTokensResponseHeaders {
+			total_count: TotalCountHeader(total_count as _),
+		})
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
@@ -6,8 +6,8 @@ use crate::prelude::*;
 /// The handler to create a new managed URL in a workspace. This will create a
 /// new managed URL with the provided subdomain, domain, and path. The URL type
 /// can be a proxy to a deployment, a proxy to a static site, a proxy to a URL,
-/// or a redirect to a URL. The URL type will determine how the managed URL
-/// behaves.
+/// a redirect to a URL, or a path map that routes path prefixes to different
+/// deployments. The URL type will determine how the managed URL behaves.
 pub async fn create_managed_url(
 	AuthenticatedAppRequest {
 		request:
@@ -32,6 +32,7 @@ pub async fn create_managed_url(
 		client_ip: _,
 		config: _,
 		user_data: _,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, CreateManagedURLRequest>,
 ) -> Result<AppResponse<CreateManagedURLRequest>, ErrorType> {
 	info!(
@@ -67,8 +68,10 @@ pub async fn create_managed_url(
 
 	info!("Creating ManagedURL: `{}.{}{}`", sub_domain, domain, path);
 
-	let (url_type, deployment_id, port, static_site_id, url, permanent_redirect, http_only) =
-		match url_type {
+	let url_type = super::validate_url_type(url_type)?;
+
+	let (discriminant, deployment_id, port, static_site_id, url, permanent_redirect, http_only) =
+		match url_type.clone() {
 			ManagedUrlType::ProxyDeployment {
 				deployment_id,
 				port,
@@ -115,6 +118,15 @@ pub async fn create_managed_url(
 				Some(managed_url_permanent_redirect),
 				Some(managed_url_http_only),
 			),
+			ManagedUrlType::PathMap { rules: _ } => (
+				ManagedUrlTypeDiscriminant::PathMap,
+				None,
+				None,
+				None,
+				None,
+				None,
+				None,
+			),
 		};
 
 	let id = query!(
@@ -145,6 +157,16 @@ pub async fn create_managed_url(
 	})?
 	.id;
 
+	super::ensure_no_rule_conflicts(
+		&mut **database,
+		id.into(),
+		&sub_domain,
+		domain_id,
+		&path,
+		&url_type,
+	)
+	.await?;
+
 	query!(
 		r#"
 		INSERT INTO
@@ -186,7 +208,7 @@ pub async fn create_managed_url(
 		&sub_domain,
 		domain_id as _,
 		path,
-		url_type as _,
+		discriminant as _,
 		deployment_id as _,
 		port.map(|port| port as i32),
 		static_site_id as _,
@@ -196,7 +218,15 @@ pub async fn create_managed_url(
 		http_only,
 	)
 	.execute(&mut **database)
-	.await?;
+	.await
+	.map_err(|err| match err {
+		sqlx::Error::Database(err) if err.is_unique_violation() => {
+			ErrorType::ManagedUrlRuleConflict
+		}
+		err => ErrorType::server_error(err),
+	})?;
+
+	super::set_path_rules(&mut **database, id.into(), workspace_id, &url_type).await?;
 
 	AppResponse::builder()
 		.body(CreateManagedURLResponse {
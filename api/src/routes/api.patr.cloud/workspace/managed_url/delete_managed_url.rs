@@ -27,6 +27,7 @@ pub async fn delete_managed_url(
 		client_ip: _,
 		config: _,
 		user_data: _,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, DeleteManagedURLRequest>,
 ) -> Result<AppResponse<DeleteManagedURLRequest>, ErrorType> {
 	info!("Deleting ManagedURL `{}`", managed_url_id);
@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use axum::http::StatusCode;
 use models::{api::workspace::managed_url::*, prelude::*};
 
@@ -33,10 +35,39 @@ pub async fn list_managed_url(
 		client_ip: _,
 		config: _,
 		user_data,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, ListManagedURLRequest>,
 ) -> Result<AppResponse<ListManagedURLRequest>, ErrorType> {
 	info!("Listing ManagedURLs in workspace `{}`", workspace_id);
 
+	let mut path_rules = BTreeMap::<Uuid, Vec<ManagedUrlPathRule>>::new();
+	for row in query!(
+		r#"
+		SELECT
+			managed_url_id,
+			path_prefix,
+			deployment_id,
+			port
+		FROM
+			managed_url_path_rule
+		WHERE
+			workspace_id = $1;
+		"#,
+		workspace_id as _,
+	)
+	.fetch_all(&mut **database)
+	.await?
+	{
+		path_rules
+			.entry(row.managed_url_id.into())
+			.or_default()
+			.push(ManagedUrlPathRule {
+				path_prefix: row.path_prefix,
+				deployment_id: row.deployment_id.into(),
+				port: row.port as u16,
+			});
+	}
+
 	let mut total_count = 0;
 
 	let urls = query!(
@@ -126,6 +157,11 @@ pub async fn list_managed_url(
 								as u16,
 						}
 					}
+					ManagedUrlTypeDiscriminant::PathMap => {
+						let mut rules = path_rules.remove(&row.id.into()).unwrap_or_default();
+						rules.sort_by(ManagedUrlPathRule::cmp_specificity);
+						ManagedUrlType::PathMap { rules }
+					}
 				},
 				is_configured: row.is_configured,
 			},
@@ -1,4 +1,7 @@
+use std::collections::{BTreeMap, BTreeSet};
+
 use axum::Router;
+use models::api::workspace::managed_url::*;
 
 use crate::prelude::*;
 
@@ -26,3 +29,275 @@ pub async fn setup_routes(state: &AppState) -> Router {
 		.mount_auth_endpoint(update_managed_url, state)
 		.mount_auth_endpoint(verify_configuration, state)
 }
+
+/// Normalizes a path so that paths that route the same requests compare equal.
+/// The path always starts with a `/` and never ends with one, unless it is the
+/// root path.
+fn normalize_path(path: &str) -> String {
+	format!("/{}", path.trim().trim_matches('/'))
+}
+
+/// Joins the path of a managed URL with the prefix of one of its path rules,
+/// giving the path on the host that the rule matches
+fn join_path(path: &str, path_prefix: &str) -> String {
+	normalize_path(&format!("{}/{}", path, path_prefix))
+}
+
+/// Gives the paths on its host that a managed URL routes. A path map routes the
+/// path of each of its rules (given by their prefixes), while every other type
+/// of managed URL only routes its own path.
+fn routed_paths<'a>(
+	path: &str,
+	path_prefixes: impl IntoIterator<Item = &'a str>,
+) -> BTreeSet<String> {
+	let paths = path_prefixes
+		.into_iter()
+		.map(|path_prefix| join_path(path, path_prefix))
+		.collect::<BTreeSet<_>>();
+
+	if paths.is_empty() {
+		BTreeSet::from([normalize_path(path)])
+	} else {
+		paths
+	}
+}
+
+/// Validates the type of a managed URL, and normalizes it so that it can be
+/// stored. The URLs of proxies and redirects must be valid URLs, and the path
+/// rules of a path map are ordered by specificity. Two path rules with the same
+/// prefix conflict with each other.
+fn validate_url_type(url_type: ManagedUrlType) -> Result<ManagedUrlType, ErrorType> {
+	match url_type {
+		ManagedUrlType::ProxyUrl { url, http_only } => Ok(ManagedUrlType::ProxyUrl {
+			url: validate_url(url)?,
+			http_only,
+		}),
+		ManagedUrlType::Redirect {
+			url,
+			permanent_redirect,
+			http_only,
+		} => Ok(ManagedUrlType::Redirect {
+			url: validate_url(url)?,
+			permanent_redirect,
+			http_only,
+		}),
+		ManagedUrlType::PathMap { rules } => {
+			if rules.is_empty() {
+				return Err(ErrorType::WrongParameters);
+			}
+
+			let mut rules = rules
+				.into_iter()
+				.map(|rule| ManagedUrlPathRule {
+					path_prefix: normalize_path(&rule.path_prefix),
+					..rule
+				})
+				.collect::<Vec<_>>();
+			rules.sort_by(ManagedUrlPathRule::cmp_specificity);
+
+			if rules
+				.windows(2)
+				.any(|pair| pair[0].path_prefix == pair[1].path_prefix)
+			{
+				return Err(ErrorType::ManagedUrlRuleConflict);
+			}
+
+			Ok(ManagedUrlType::PathMap { rules })
+		}
+		url_type => Ok(url_type),
+	}
+}
+
+/// Validates the URL that a managed URL proxies or redirects to. The scheme of
+/// the URL is optional, since it is decided by whether the managed URL is HTTP
+/// only.
+fn validate_url(url: String) -> Result<String, ErrorType> {
+	let url = url.trim().to_string();
+	let is_valid = !url.is_empty() &&
+		!url.contains(char::is_whitespace) &&
+		reqwest::Url::parse(&url)
+			.or_else(|_| reqwest::Url::parse(&format!("https://{}", url)))
+			.is_ok_and(|parsed| parsed.has_host());
+
+	if is_valid {
+		Ok(url)
+	} else {
+		Err(ErrorType::WrongParameters)
+	}
+}
+
+/// Checks that none of the paths that a managed URL routes on its host are
+/// already routed by another managed URL on the same host. Paths that only
+/// overlap (such as `/api` and `/api/v1`) don't conflict, since the most
+/// specific one is matched first.
+async fn ensure_no_rule_conflicts(
+	connection: &mut DatabaseConnection,
+	managed_url_id: Uuid,
+	sub_domain: &str,
+	domain_id: Uuid,
+	path: &str,
+	url_type: &ManagedUrlType,
+) -> Result<(), ErrorType> {
+	let paths = routed_paths(
+		path,
+		match url_type {
+			ManagedUrlType::PathMap { rules } => {
+				rules.iter().map(|rule| rule.path_prefix.as_str()).collect()
+			}
+			_ => vec![],
+		},
+	);
+
+	let mut others = BTreeMap::<Uuid, (String, Vec<String>)>::new();
+	for row in query!(
+		r#"
+		SELECT
+			managed_url.id,
+			managed_url.path,
+			managed_url_path_rule.path_prefix as "path_prefix?"
+		FROM
+			managed_url
+		LEFT JOIN
+			managed_url_path_rule
+		ON
+			managed_url_path_rule.managed_url_id = managed_url.id
+		WHERE
+			managed_url.sub_domain = $1 AND
+			managed_url.domain_id = $2 AND
+			managed_url.id != $3 AND
+			managed_url.deleted IS NULL;
+		"#,
+		sub_domain,
+		domain_id as _,
+		managed_url_id as _,
+	)
+	.fetch_all(&mut *connection)
+	.await?
+	{
+		let (_, path_prefixes) = others
+			.entry(row.id.into())
+			.or_insert_with(|| (row.path, Vec::new()));
+		path_prefixes.extend(row.path_prefix);
+	}
+
+	let conflicts = others.values().any(|(path, path_prefixes)| {
+		!routed_paths(path, path_prefixes.iter().map(String::as_str)).is_disjoint(&paths)
+	});
+	if conflicts {
+		return Err(ErrorType::ManagedUrlRuleConflict);
+	}
+
+	Ok(())
+}
+
+/// Replaces the path rules of a managed URL with the given rules. The rules of
+/// a managed URL that is not a path map are removed.
+async fn set_path_rules(
+	connection: &mut DatabaseConnection,
+	managed_url_id: Uuid,
+	workspace_id: Uuid,
+	url_type: &ManagedUrlType,
+) -> Result<(), ErrorType> {
+	query!(
+		r#"
+		DELETE FROM
+			managed_url_path_rule
+		WHERE
+			managed_url_id = $1;
+		"#,
+		managed_url_id as _,
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	let ManagedUrlType::PathMap { rules } = url_type else {
+		return Ok(());
+	};
+
+	for rule in rules {
+		query!(
+			r#"
+			INSERT INTO
+				managed_url_path_rule(
+					managed_url_id,
+					path_prefix,
+					deployment_id,
+					port,
+					workspace_id
+				)
+			VALUES
+				($1, $2, $3, $4, $5);
+			"#,
+			managed_url_id as _,
+			rule.path_prefix,
+			rule.deployment_id as _,
+			rule.port as i32,
+			workspace_id as _,
+		)
+		.execute(&mut *connection)
+		.await?;
+	}
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use models::api::workspace::managed_url::*;
+
+	use super::validate_url_type;
+	use crate::prelude::*;
+
+	fn rule(path_prefix: &str) -> ManagedUrlPathRule {
+		ManagedUrlPathRule {
+			path_prefix: path_prefix.to_string(),
+			deployment_id: Uuid::new_v4(),
+			port: 8080,
+		}
+	}
+
+	#[test]
+	fn validates_path_map_rules() {
+		let url_type = validate_url_type(ManagedUrlType::PathMap {
+			rules: vec![rule("/"), rule("api/"), rule("/api/v1")],
+		})
+		.unwrap();
+		let ManagedUrlType::PathMap { rules } = url_type else {
+			panic!("url type should still be a path map");
+		};
+		assert_eq!(
+			rules
+				.iter()
+				.map(|rule| rule.path_prefix.as_str())
+				.collect::<Vec<_>>(),
+			["/api/v1", "/api", "/"]
+		);
+
+		assert_eq!(
+			validate_url_type(ManagedUrlType::PathMap {
+				rules: vec![rule("/api"), rule("/api/")],
+			}),
+			Err(ErrorType::ManagedUrlRuleConflict)
+		);
+		assert_eq!(
+			validate_url_type(ManagedUrlType::PathMap { rules: vec![] }),
+			Err(ErrorType::WrongParameters)
+		);
+	}
+
+	#[test]
+	fn validates_redirect_urls() {
+		let redirect = |url: &str| ManagedUrlType::Redirect {
+			url: url.to_string(),
+			permanent_redirect: true,
+			http_only: false,
+		};
+
+		assert!(validate_url_type(redirect("https://patr.cloud/docs")).is_ok());
+		assert!(validate_url_type(redirect("patr.cloud")).is_ok());
+		assert_eq!(
+			validate_url_type(redirect("not a url")),
+			Err(ErrorType::WrongParameters)
+		);
+	}
+}
@@ -31,15 +31,17 @@ pub async fn update_managed_url(
 		client_ip: _,
 		config: _,
 		user_data: _,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, UpdateManagedURLRequest>,
 ) -> Result<AppResponse<UpdateManagedURLRequest>, ErrorType> {
 	info!("Creating ManagedURL with ID: `{}`", managed_url_id);
 
 	// Check to make sure that the Managed URL exist
-	query!(
+	let managed_url = query!(
 		r#"
         SELECT
-            managed_url.id
+            managed_url.sub_domain,
+            managed_url.domain_id
         FROM
             managed_url
         INNER JOIN
@@ -56,9 +58,20 @@ pub async fn update_managed_url(
 	)
 	.fetch_optional(&mut **database)
 	.await?
-	.ok_or(ErrorType::ResourceDoesNotExist)?;
+	.or_not_found()?;
 
 	let path = format!("/{}", path.trim_start_matches('/'));
+	let managed_url_type = super::validate_url_type(managed_url_type)?;
+
+	super::ensure_no_rule_conflicts(
+		&mut **database,
+		managed_url_id,
+		&managed_url.sub_domain,
+		managed_url.domain_id.into(),
+		&path,
+		&managed_url_type,
+	)
+	.await?;
 
 	let url_type;
 	let deployment_id;
@@ -68,12 +81,12 @@ pub async fn update_managed_url(
 	let permanent_redirect;
 	let http_only;
 
-	match managed_url_type {
+	match managed_url_type.clone() {
 		ManagedUrlType::ProxyDeployment {
 			deployment_id: managed_url_deployment_id,
 			port: managed_url_port,
 		} => {
-			url_type = "proxy_deployment";
+			url_type = ManagedUrlTypeDiscriminant::ProxyDeployment;
 			deployment_id = Some(managed_url_deployment_id);
 			port = Some(managed_url_port);
 			static_site_id = None;
@@ -84,7 +97,7 @@ pub async fn update_managed_url(
 		ManagedUrlType::ProxyStaticSite {
 			static_site_id: managed_url_static_site_id,
 		} => {
-			url_type = "proxy_static_site";
+			url_type = ManagedUrlTypeDiscriminant::ProxyStaticSite;
 			deployment_id = None;
 			port = None;
 			static_site_id = Some(managed_url_static_site_id);
@@ -96,7 +109,7 @@ pub async fn update_managed_url(
 			url: managed_url_url,
 			http_only: managed_url_http_only,
 		} => {
-			url_type = "proxy_url";
+			url_type = ManagedUrlTypeDiscriminant::ProxyUrl;
 			deployment_id = None;
 			port = None;
 			static_site_id = None;
@@ -109,7 +122,7 @@ pub async fn update_managed_url(
 			permanent_redirect: managed_url_permanent_redirect,
 			http_only: managed_url_http_only,
 		} => {
-			url_type = "redirect";
+			url_type = ManagedUrlTypeDiscriminant::Redirect;
 			deployment_id = None;
 			port = None;
 			static_site_id = None;
@@ -117,6 +130,15 @@ pub async fn update_managed_url(
 			permanent_redirect = Some(managed_url_permanent_redirect);
 			http_only = Some(managed_url_http_only);
 		}
+		ManagedUrlType::PathMap { rules: _ } => {
+			url_type = ManagedUrlTypeDiscriminant::PathMap;
+			deployment_id = None;
+			port = None;
+			static_site_id = None;
+			url = None;
+			permanent_redirect = None;
+			http_only = None;
+		}
 	}
 	query!(
 		r#"
@@ -145,6 +167,20 @@ pub async fn update_managed_url(
 		http_only,
 	)
 	.execute(&mut **database)
+	.await
+	.map_err(|err| match err {
+		sqlx::Error::Database(err) if err.is_unique_violation() => {
+			ErrorType::ManagedUrlRuleConflict
+		}
+		err => ErrorType::server_error(err),
+	})?;
+
+	super::set_path_rules(
+		&mut **database,
+		managed_url_id,
+		workspace_id,
+		&managed_url_type,
+	)
 	.await?;
 
 	AppResponse::builder()
@@ -33,6 +33,7 @@ pub async fn verify_configuration(
 		client_ip: _,
 		config: _,
 		user_data: _,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, VerifyManagedURLConfigurationRequest>,
 ) -> Result<AppResponse<VerifyManagedURLConfigurationRequest>, ErrorType> {
 	info!("Verifying configuration of ManagedURL");
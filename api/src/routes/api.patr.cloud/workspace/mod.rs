@@ -1,6 +1,7 @@
 use axum::Router;
+use models::{QuotaResource, QuotaUsage};
 
-use crate::prelude::*;
+use crate::{prelude::*, utils::config::AppConfig};
 
 // mod container_registry;
 #[allow(unreachable_code, unused_variables)]
@@ -8,6 +9,7 @@ mod database;
 mod deployment;
 #[allow(unreachable_code, unused_variables)]
 mod domain;
+mod egress_policy;
 mod managed_url;
 mod rbac;
 mod runner;
@@ -24,12 +26,39 @@ mod create_workspace;
 /// other resources. This is a destructive operation and cannot be undone.
 /// The workspace must be empty before it can be deleted.
 mod delete_workspace;
+/// The handler to get the usage of the API by a workspace, aggregated into
+/// time buckets per endpoint. This is used to show the analytics of the
+/// integrations of a workspace.
+mod get_api_usage;
+/// The handler to get the feature flags of a workspace. This is used by the
+/// frontend to hide UI for features that aren't enabled for the workspace.
+mod get_feature_flags;
 /// The handler to get the information of a workspace. This includes the
 /// workspace's name, the user who created it, and the date it was created.
 mod get_workspace_info;
 /// The handler to check if a workspace name is available. This is used when
 /// creating a new workspace to ensure that the name is unique.
 mod is_name_available;
+/// The handler to list the activity of a workspace, merged from all of its
+/// event logs into a single chronological feed. This powers the activity page
+/// of the dashboard.
+mod list_workspace_activity;
+/// The handler to list the API tokens that have been granted access to a
+/// workspace, across all of its users. This lets the super admin of the
+/// workspace see which tokens can access it.
+mod list_workspace_api_tokens;
+/// The handler to wait for the next updates of a workspace, for clients that
+/// can't hold a websocket open. Updates are read from a Redis stream, so that
+/// a client can resume from its cursor without missing any between polls.
+mod poll_workspace_updates;
+/// The handler to revoke an API token that has been granted access to a
+/// workspace, so that the super admin can cut off the access of a user that
+/// has left the workspace.
+mod revoke_workspace_api_token;
+/// The handler to search for resources in a workspace by their name. This
+/// powers the search box of the dashboard, and only returns the resources
+/// that the user has permission to view.
+mod search_workspace_resources;
 /// The handler to update the information of a workspace. At the moment, only
 /// the name can be updated. However, this will be expanded in the future. At
 /// least one parameter must be provided for the update.
@@ -38,8 +67,15 @@ mod update_workspace_info;
 use self::{
 	create_workspace::*,
 	delete_workspace::*,
+	get_api_usage::*,
+	get_feature_flags::*,
 	get_workspace_info::*,
 	is_name_available::*,
+	list_workspace_activity::*,
+	list_workspace_api_tokens::*,
+	poll_workspace_updates::*,
+	revoke_workspace_api_token::*,
+	search_workspace_resources::*,
 	update_workspace_info::*,
 };
 
@@ -48,6 +84,7 @@ pub async fn setup_routes(state: &AppState) -> Router {
 	Router::new()
 		// .merge(container_registry::setup_routes(state).await)
 		.merge(domain::setup_routes(state).await)
+		.merge(egress_policy::setup_routes(state).await)
 		.merge(database::setup_routes(state).await)
 		.merge(deployment::setup_routes(state).await)
 		.merge(managed_url::setup_routes(state).await)
@@ -58,7 +95,177 @@ pub async fn setup_routes(state: &AppState) -> Router {
 		.merge(volume::setup_routes(state).await)
 		.mount_auth_endpoint(create_workspace, state)
 		.mount_auth_endpoint(delete_workspace, state)
+		.mount_auth_endpoint(get_api_usage, state)
+		.mount_auth_endpoint(get_feature_flags, state)
 		.mount_auth_endpoint(get_workspace_info, state)
 		.mount_auth_endpoint(is_name_available, state)
+		.mount_auth_endpoint(list_workspace_activity, state)
+		.mount_auth_endpoint(list_workspace_api_tokens, state)
+		.mount_auth_endpoint(poll_workspace_updates, state)
+		.mount_auth_endpoint(revoke_workspace_api_token, state)
+		.mount_auth_endpoint(search_workspace_resources, state)
 		.mount_auth_endpoint(update_workspace_info, state)
 }
+
+/// Ensures that the workspace can have another resource of the given kind,
+/// without exceeding its quota. The workspace is locked until the end of the
+/// transaction, so that concurrent requests can't both create the last
+/// resource that the quota allows. If the quota is exceeded, the error contains
+/// the number of resources counted here along with the quota.
+async fn ensure_within_quota(
+	connection: &mut DatabaseConnection,
+	config: &AppConfig,
+	workspace_id: Uuid,
+	resource: QuotaResource,
+) -> Result<(), ErrorType> {
+	let workspace = query!(
+		r#"
+		SELECT
+			deployment_quota,
+			runner_quota
+		FROM
+			workspace
+		WHERE
+			id = $1 AND
+			deleted IS NULL
+		FOR UPDATE;
+		"#,
+		workspace_id as _,
+	)
+	.fetch_optional(&mut *connection)
+	.await?
+	.or_not_found()?;
+
+	let workspace_quota = match resource {
+		QuotaResource::Deployment => workspace.deployment_quota,
+		QuotaResource::Runner => workspace.runner_quota,
+	};
+	let Some(limit) = effective_quota(workspace_quota, config.quota.limit(resource)) else {
+		return Ok(());
+	};
+
+	let current = match resource {
+		QuotaResource::Deployment => {
+			query!(
+				r#"
+				SELECT
+					COUNT(*) AS "count!"
+				FROM
+					deployment
+				WHERE
+					workspace_id = $1 AND
+					deleted IS NULL;
+				"#,
+				workspace_id as _,
+			)
+			.fetch_one(&mut *connection)
+			.await?
+			.count
+		}
+		QuotaResource::Runner => {
+			query!(
+				r#"
+				SELECT
+					COUNT(*) AS "count!"
+				FROM
+					runner
+				WHERE
+					workspace_id = $1 AND
+					deleted IS NULL;
+				"#,
+				workspace_id as _,
+			)
+			.fetch_one(&mut *connection)
+			.await?
+			.count
+		}
+	};
+
+	check_quota(resource, current, limit).inspect_err(|_| {
+		debug!("Workspace `{workspace_id}` is using {current}/{limit} of its {resource:?} quota");
+	})
+}
+
+/// The quota of a workspace on a kind of resource, if it is limited. The quota
+/// of the workspace itself takes precedence over the default quota of the
+/// instance, so that a workspace can be allowed more (or fewer) resources than
+/// the others.
+fn effective_quota(workspace_quota: Option<i32>, default_quota: Option<u32>) -> Option<u32> {
+	match workspace_quota {
+		Some(quota) => Some(u32::try_from(quota).unwrap_or(0)),
+		None => default_quota,
+	}
+}
+
+/// Checks that another resource of the given kind can be created by a
+/// workspace that has `current` of them, given its quota
+fn check_quota(resource: QuotaResource, current: i64, limit: u32) -> Result<(), ErrorType> {
+	let current = u32::try_from(current).unwrap_or(u32::MAX);
+	if current >= limit {
+		return Err(ErrorType::QuotaExceeded(QuotaUsage {
+			resource,
+			current,
+			limit,
+		}));
+	}
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use models::ApiErrorResponse;
+
+	use super::*;
+
+	#[test]
+	fn the_quota_of_the_workspace_takes_precedence() {
+		assert_eq!(effective_quota(None, None), None);
+		assert_eq!(effective_quota(None, Some(10)), Some(10));
+		assert_eq!(effective_quota(Some(50), Some(10)), Some(50));
+		assert_eq!(effective_quota(Some(2), Some(10)), Some(2));
+		assert_eq!(effective_quota(Some(2), None), Some(2));
+		assert_eq!(effective_quota(Some(-1), None), Some(0));
+	}
+
+	#[test]
+	fn resources_can_be_created_until_the_quota_is_reached() {
+		assert_eq!(check_quota(QuotaResource::Deployment, 0, 10), Ok(()));
+		assert_eq!(check_quota(QuotaResource::Deployment, 9, 10), Ok(()));
+		assert_eq!(
+			check_quota(QuotaResource::Deployment, 10, 10),
+			Err(ErrorType::QuotaExceeded(QuotaUsage {
+				resource: QuotaResource::Deployment,
+				current: 10,
+				limit: 10,
+			}))
+		);
+		assert_eq!(
+			check_quota(QuotaResource::Runner, 0, 0),
+			Err(ErrorType::QuotaExceeded(QuotaUsage {
+				resource: QuotaResource::Runner,
+				current: 0,
+				limit: 0,
+			}))
+		);
+	}
+
+	#[test]
+	fn the_quota_error_reports_what_was_counted() {
+		// Resources created past a lowered quota are still reported as is
+		let Err(error) = check_quota(QuotaResource::Runner, 12, 10) else {
+			panic!("the quota was exceeded");
+		};
+		let body = ApiErrorResponse::error(error).body;
+
+		assert_eq!(body.code, error);
+		assert_eq!(
+			body.quota,
+			Some(QuotaUsage {
+				resource: QuotaResource::Runner,
+				current: 12,
+				limit: 10,
+			})
+		);
+	}
+}
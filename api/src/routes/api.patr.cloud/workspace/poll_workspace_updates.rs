@@ -0,0 +1,337 @@
+use std::collections::BTreeMap;
+
+use axum::http::StatusCode;
+use models::{api::workspace::*, rbac::WorkspacePermission};
+use rustis::{
+	client::Client as RedisClient,
+	commands::{StreamCommands, StreamEntry, StringCommands},
+};
+use serde::Deserialize;
+use tokio::time::Instant;
+
+use crate::{
+	prelude::*,
+	utils::long_poll::{self, WorkspaceUpdateListener},
+};
+
+/// The handler to wait for the next updates of a workspace. The updates are
+/// read from the stream of updates of the workspace, after the cursor given by
+/// the client. If there aren't any yet, the response is deferred until the
+/// transaction of the request is committed, and then waits for the next
+/// update of the workspace, up to the timeout (capped by the config, since
+/// each poll holds on to a request). The polls on an instance share a single
+/// subscription to the updates of each workspace.
+///
+/// Only the updates about resources that the user can view are returned.
+pub async fn poll_workspace_updates(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: PollWorkspaceUpdatesPath { workspace_id },
+				query: PollWorkspaceUpdatesQuery {
+					cursor,
+					timeout_seconds,
+				},
+				headers:
+					PollWorkspaceUpdatesRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: PollWorkspaceUpdatesRequestProcessed,
+			},
+		database,
+		redis,
+		client_ip: _,
+		config,
+		user_data,
+		clock: _,
+	}: AuthenticatedAppRequest<'_, PollWorkspaceUpdatesRequest>,
+) -> Result<AppResponse<PollWorkspaceUpdatesRequest>, ErrorType> {
+	info!("Polling for the updates of workspace `{workspace_id}`");
+
+	let permission = user_data
+		.permissions
+		.get(&workspace_id)
+		.cloned()
+		.ok_or(ErrorType::Unauthorized)?;
+
+	// The permissions to view each kind of resource, by the kind of resource
+	let view_permissions = query!(
+		r#"
+		SELECT
+			id,
+			name
+		FROM
+			permission
+		WHERE
+			name LIKE '%::view';
+		"#
+	)
+	.fetch_all(&mut **database)
+	.await?
+	.into_iter()
+	.filter_map(|row| {
+		let resource = row.name.strip_suffix("::view")?.to_string();
+		Some((resource, row.id.into()))
+	})
+	.collect::<BTreeMap<String, Uuid>>();
+	let visibility = UpdateVisibility {
+		permission,
+		view_permissions,
+	};
+
+	let timeout = config.long_poll.timeout(timeout_seconds);
+	let stream = redis::keys::workspace_updates(&workspace_id);
+
+	let cursor = match cursor {
+		Some(cursor) => {
+			let Some(parsed) = redis::parse_stream_id(&cursor) else {
+				debug!("Invalid cursor `{cursor}` for the updates of workspace `{workspace_id}`");
+				return Err(ErrorType::WrongParameters);
+			};
+
+			let newest_trimmed = redis
+				.get::<_, Option<String>>(redis::keys::workspace_updates_trimmed(&workspace_id))
+				.await?
+				.as_deref()
+				.and_then(redis::parse_stream_id);
+			let oldest = redis
+				.xrange::<_, _, _, String>(&stream, "-", "+", Some(1))
+				.await?
+				.into_iter()
+				.next()
+				.and_then(|entry| redis::parse_stream_id(&entry.stream_id));
+			if is_cursor_expired(parsed, newest_trimmed, oldest) {
+				debug!("Cursor `{cursor}` for the updates of workspace `{workspace_id}` expired");
+				return Err(ErrorType::CursorExpired);
+			}
+
+			cursor
+		}
+		// Without a cursor, only the updates after the latest one are returned
+		None => redis
+			.xrevrange::<_, _, _, String>(&stream, "+", "-", Some(1))
+			.await?
+			.into_iter()
+			.next()
+			.map_or_else(|| String::from("0-0"), |entry| entry.stream_id),
+	};
+
+	let (updates, cursor) = get_updates_after(redis, &stream, cursor, &visibility).await?;
+
+	// Wait for the next update once the transaction of the request is
+	// committed, so that the transaction isn't held open while waiting
+	if updates.is_empty() && !timeout.is_zero() {
+		let slot = long_poll::acquire_poll_slot(&config.long_poll)?;
+		let redis = redis.clone();
+		let cursor = cursor.clone();
+		long_poll::defer_response(async move {
+			let deadline = Instant::now() + timeout;
+			let mut listener = WorkspaceUpdateListener::start(&redis, workspace_id).await?;
+
+			// An update could have been published before listening started, so
+			// check for it again before waiting
+			let (mut updates, mut cursor) =
+				get_updates_after(&redis, &stream, cursor, &visibility).await?;
+			while updates.is_empty() &&
+				listener
+					.wait(deadline.saturating_duration_since(Instant::now()))
+					.await
+			{
+				(updates, cursor) = get_updates_after(&redis, &stream, cursor, &visibility).await?;
+			}
+			drop(slot);
+
+			AppResponse::builder()
+				.body(PollWorkspaceUpdatesResponse { updates, cursor })
+				.headers(())
+				.status_code(StatusCode::OK)
+				.build()
+				.into_result()
+		});
+	}
+
+	AppResponse::builder()
+		.body(PollWorkspaceUpdatesResponse { updates, cursor })
+		.headers(())
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
+
+/// Decides which updates of a workspace a user can view
+struct UpdateVisibility {
+	/// The permissions of the user on the workspace
+	permission: WorkspacePermission,
+	/// The IDs of the permissions to view each kind of resource, by the kind
+	/// of resource (such as `deployment`)
+	view_permissions: BTreeMap<String, Uuid>,
+}
+
+impl UpdateVisibility {
+	/// Checks if the user can view the given update. Updates are about the
+	/// resource given by their `resource` (the kind of resource) and
+	/// `resourceId` fields, and can only be viewed by the users that have the
+	/// permission to view that resource. Updates that aren't about a resource
+	/// can only be viewed by the super admin of the workspace.
+	fn can_view(&self, data: &serde_json::Value) -> bool {
+		/// The part of an update that identifies the resource it is about
+		#[derive(Deserialize)]
+		#[serde(rename_all = "camelCase")]
+		struct UpdatedResource {
+			/// The kind of resource that the update is about
+			resource: String,
+			/// The ID of the resource that the update is about
+			resource_id: Uuid,
+		}
+
+		if self.permission.is_super_admin() {
+			return true;
+		}
+
+		let Ok(UpdatedResource {
+			resource,
+			resource_id,
+		}) = UpdatedResource::deserialize(data)
+		else {
+			return false;
+		};
+
+		self.view_permissions
+			.get(&resource)
+			.is_some_and(|permission_id| {
+				self.permission
+					.has_permission_on_resource(*permission_id, resource_id)
+			})
+	}
+}
+
+/// Gets the updates in the given stream after the given cursor that the user
+/// can view, up to the maximum number of updates read by a single poll.
+/// Returns the updates along with the cursor to read the next ones from, which
+/// is after the updates that the user can't view as well.
+async fn get_updates_after(
+	redis: &RedisClient,
+	stream: &str,
+	cursor: String,
+	visibility: &UpdateVisibility,
+) -> Result<(Vec<WorkspaceUpdate>, String), ErrorType> {
+	let entries: Vec<StreamEntry<String>> = redis
+		.xrange(
+			stream,
+			format!("({cursor}"),
+			"+",
+			Some(constants::WORKSPACE_UPDATES_PER_POLL),
+		)
+		.await?;
+
+	let cursor = entries
+		.last()
+		.map_or(cursor, |entry| entry.stream_id.clone());
+	let updates = entries
+		.into_iter()
+		.filter_map(|mut entry| {
+			let data = entry.items.remove("data")?;
+			Some(WorkspaceUpdate {
+				data: serde_json::from_str(&data).unwrap_or(serde_json::Value::String(data)),
+				cursor: entry.stream_id,
+			})
+		})
+		.filter(|update| visibility.can_view(&update.data))
+		.collect();
+
+	Ok((updates, cursor))
+}
+
+/// Checks if some of the updates after a cursor could have been removed from
+/// the stream of updates, given the ID of the newest update that was trimmed
+/// from the stream and the ID of the oldest update still in it. Cursors are
+/// always the ID of an update, so a cursor older than the oldest update means
+/// that the stream expired since, unless the cursor is from before there were
+/// any updates.
+fn is_cursor_expired(
+	cursor: (u64, u64),
+	newest_trimmed: Option<(u64, u64)>,
+	oldest: Option<(u64, u64)>,
+) -> bool {
+	match newest_trimmed {
+		Some(newest_trimmed) => cursor < newest_trimmed,
+		None => cursor != (0, 0) && oldest.map_or(true, |oldest| cursor < oldest),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::BTreeSet;
+
+	use models::rbac::ResourcePermissionType;
+
+	use super::*;
+
+	#[test]
+	fn cursors_must_be_stream_ids() {
+		assert!(redis::parse_stream_id("0-0").is_some());
+		assert!(redis::parse_stream_id("1700000000000-12").is_some());
+
+		assert!(redis::parse_stream_id("").is_none());
+		assert!(redis::parse_stream_id("1700000000000").is_none());
+		assert!(redis::parse_stream_id("1700000000000-").is_none());
+		assert!(redis::parse_stream_id("-1-0").is_none());
+		assert!(redis::parse_stream_id("$").is_none());
+		assert!(redis::parse_stream_id("1700000000000-0+").is_none());
+	}
+
+	#[test]
+	fn cursors_before_trimmed_updates_are_expired() {
+		let trimmed = Some((1700000000000, 5));
+		let oldest = Some((1700000000000, 6));
+
+		assert!(is_cursor_expired((0, 0), trimmed, oldest));
+		assert!(is_cursor_expired((1700000000000, 4), trimmed, oldest));
+		assert!(!is_cursor_expired((1700000000000, 5), trimmed, oldest));
+		assert!(!is_cursor_expired((1700000000001, 0), trimmed, oldest));
+	}
+
+	#[test]
+	fn cursors_before_an_expired_stream_are_expired() {
+		let oldest = Some((1700000000000, 0));
+
+		assert!(!is_cursor_expired((0, 0), None, None));
+		assert!(!is_cursor_expired((0, 0), None, oldest));
+		assert!(!is_cursor_expired((1700000000000, 0), None, oldest));
+		assert!(is_cursor_expired((1600000000000, 0), None, oldest));
+		assert!(is_cursor_expired((1600000000000, 0), None, None));
+	}
+
+	#[test]
+	fn members_only_see_updates_about_resources_they_can_view() {
+		let (view_deployment, visible, hidden) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+		let visibility = UpdateVisibility {
+			permission: WorkspacePermission::Member {
+				permissions: BTreeMap::from([(
+					view_deployment,
+					ResourcePermissionType::Include(BTreeSet::from([visible])),
+				)]),
+			},
+			view_permissions: BTreeMap::from([("deployment".to_string(), view_deployment)]),
+		};
+		let update = |resource: &str, resource_id: Uuid| {
+			serde_json::json!({
+				"resource": resource,
+				"resourceId": resource_id.to_string(),
+			})
+		};
+
+		assert!(visibility.can_view(&update("deployment", visible)));
+		assert!(!visibility.can_view(&update("deployment", hidden)));
+		assert!(!visibility.can_view(&update("volume", visible)));
+		assert!(!visibility.can_view(&serde_json::json!({ "workspaceId": visible })));
+
+		let super_admin = UpdateVisibility {
+			permission: WorkspacePermission::SuperAdmin,
+			..visibility
+		};
+		assert!(super_admin.can_view(&update("deployment", hidden)));
+		assert!(super_admin.can_view(&serde_json::json!({ "workspaceId": visible })));
+	}
+}
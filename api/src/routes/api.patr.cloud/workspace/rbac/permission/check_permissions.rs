@@ -0,0 +1,111 @@
+use std::collections::BTreeMap;
+
+use axum::http::StatusCode;
+use models::api::workspace::rbac::*;
+
+use crate::prelude::*;
+
+/// The handler to check which of the given permissions the currently
+/// authenticated user has in the workspace. Permissions that do not exist in
+/// the database are considered denied, and so are all the permissions on a
+/// resource that doesn't belong to the workspace.
+pub async fn check_permissions(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: CheckPermissionsPath { workspace_id },
+				query: (),
+				headers:
+					CheckPermissionsRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: CheckPermissionsRequestProcessed {
+					permissions,
+					resource_id,
+				},
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config: _,
+		user_data,
+		clock: _,
+	}: AuthenticatedAppRequest<'_, CheckPermissionsRequest>,
+) -> Result<AppResponse<CheckPermissionsRequest>, ErrorType> {
+	info!("Checking permissions of current request");
+
+	let workspace_permission = user_data
+		.permissions
+		.get(&workspace_id)
+		.ok_or(ErrorType::WrongParameters)?;
+	let resource_id = resource_id.unwrap_or(workspace_id);
+
+	let names = permissions
+		.iter()
+		.map(|permission| permission.to_string())
+		.collect::<Vec<_>>();
+
+	let mut permissions = names
+		.iter()
+		.map(|name| (name.clone(), false))
+		.collect::<BTreeMap<_, _>>();
+
+	// An exclude permission allows every resource that isn't excluded, even
+	// the ones of other workspaces, so the resource has to be checked first
+	let resource = query!(
+		r#"
+		SELECT
+			id
+		FROM
+			resource
+		WHERE
+			id = $1 AND
+			owner_id = $2 AND
+			deleted IS NULL;
+		"#,
+		resource_id as _,
+		workspace_id as _,
+	)
+	.fetch_optional(&mut **database)
+	.await?;
+
+	if resource.is_none() {
+		trace!("Resource `{resource_id}` does not belong to the workspace");
+		return AppResponse::builder()
+			.body(CheckPermissionsResponse { permissions })
+			.headers(())
+			.status_code(StatusCode::OK)
+			.build()
+			.into_result();
+	}
+
+	query!(
+		r#"
+		SELECT
+			id,
+			name
+		FROM
+			permission
+		WHERE
+			name = ANY($1);
+		"#,
+		&names
+	)
+	.fetch_all(&mut **database)
+	.await?
+	.into_iter()
+	.for_each(|row| {
+		permissions.insert(
+			row.name,
+			workspace_permission.has_permission_on_resource(row.id.into(), resource_id),
+		);
+	});
+
+	AppResponse::builder()
+		.body(CheckPermissionsResponse { permissions })
+		.headers(())
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
@@ -23,6 +23,7 @@ pub async fn get_current_permissions(
 		client_ip: _,
 		config: _,
 		user_data,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, GetCurrentPermissionsRequest>,
 ) -> Result<AppResponse<GetCurrentPermissionsRequest>, ErrorType> {
 	info!("Get permissions of current request");
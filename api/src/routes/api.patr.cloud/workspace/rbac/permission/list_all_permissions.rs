@@ -1,5 +1,10 @@
+use std::str::FromStr;
+
 use axum::http::StatusCode;
-use models::api::workspace::rbac::{Permission as PermissionModel, *};
+use models::{
+	api::workspace::rbac::{Permission as PermissionModel, *},
+	rbac::Permission,
+};
 
 use crate::prelude::*;
 
@@ -7,7 +12,8 @@ use crate::prelude::*;
 /// permissions that are available to a user. This will not return the
 /// permissions of the user, but all permissions that are available in the
 /// database. This is useful for the user to know what permissions are available
-/// to them.
+/// to them. The permissions can optionally be filtered by a search term, which
+/// is matched against the name and description, and by a category.
 pub async fn list_all_permissions(
 	AuthenticatedAppRequest {
 		request:
@@ -18,7 +24,7 @@ pub async fn list_all_permissions(
 						// might have different permissions based on workspace
 						workspace_id: _,
 					},
-				query: (),
+				query: ListAllPermissionsQuery { search, category },
 				headers:
 					ListAllPermissionsRequestHeaders {
 						authorization: _,
@@ -31,10 +37,15 @@ pub async fn list_all_permissions(
 		client_ip: _,
 		config: _,
 		user_data: _,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, ListAllPermissionsRequest>,
 ) -> Result<AppResponse<ListAllPermissionsRequest>, ErrorType> {
 	info!("Listing all permissions in the database");
 
+	let search = search
+		.map(|search| search.trim().to_lowercase())
+		.filter(|search| !search.is_empty());
+
 	let permissions = query!(
 		r#"
 		SELECT
@@ -47,14 +58,28 @@ pub async fn list_all_permissions(
 	.await?
 	.into_iter()
 	.map(|row| {
+		let permission = Permission::from_str(&row.name).ok();
 		WithId::new(
 			row.id,
 			PermissionModel {
+				category: permission.map(|permission| permission.category().to_string()),
+				resource_type: permission.map(|permission| permission.resource_type()),
 				name: row.name,
 				description: row.description,
 			},
 		)
 	})
+	.filter(|permission| {
+		category.as_ref().map_or(true, |category| {
+			permission.data.category.as_ref() == Some(category)
+		})
+	})
+	.filter(|permission| {
+		search.as_ref().map_or(true, |search| {
+			permission.data.name.to_lowercase().contains(search) ||
+				permission.data.description.to_lowercase().contains(search)
+		})
+	})
 	.collect();
 
 	AppResponse::builder()
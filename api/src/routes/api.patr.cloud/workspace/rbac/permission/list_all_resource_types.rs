@@ -31,6 +31,7 @@ pub async fn list_all_resource_types(
 		client_ip: _,
 		config: _,
 		user_data: _,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, ListAllResourceTypesRequest>,
 ) -> Result<AppResponse<ListAllResourceTypesRequest>, ErrorType> {
 	info!("Listing all resource types in the database");
@@ -32,6 +32,7 @@ pub async fn create_new_role(
 		client_ip: _,
 		config: _,
 		user_data: _,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, CreateNewRoleRequest>,
 ) -> Result<AppResponse<CreateNewRoleRequest>, ErrorType> {
 	info!("Creating new role: {} in workspace: {}", name, workspace_id);
@@ -1,7 +1,5 @@
 use axum::http::StatusCode;
 use models::api::workspace::rbac::role::*;
-use rustis::commands::StringCommands;
-use time::OffsetDateTime;
 
 use crate::prelude::*;
 
@@ -29,6 +27,7 @@ pub async fn delete_role(
 		client_ip: _,
 		config: _,
 		user_data: _,
+		clock,
 	}: AuthenticatedAppRequest<'_, DeleteRoleRequest>,
 ) -> Result<AppResponse<DeleteRoleRequest>, ErrorType> {
 	info!("Deleting role: {} in workspace: {}", role_id, workspace_id);
@@ -114,18 +113,12 @@ pub async fn delete_role(
 
 	trace!("Deleted the role");
 
-	redis
-		.setex(
-			redis::keys::workspace_id_revocation_timestamp(&workspace_id),
-			constants::CACHED_PERMISSIONS_VALIDITY
-				.whole_seconds()
-				.unsigned_abs(),
-			OffsetDateTime::now_utc().unix_timestamp(),
-		)
-		.await
-		.inspect_err(|err| {
-			error!("Error setting the revocation timestamp: `{}`", err);
-		})?;
+	redis::set_revocation_timestamp(
+		redis,
+		redis::keys::workspace_id_revocation_timestamp(&workspace_id),
+		clock.now(),
+	)
+	.await?;
 
 	trace!("Revocation timestamp set");
 
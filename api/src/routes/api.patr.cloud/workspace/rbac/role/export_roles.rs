@@ -0,0 +1,164 @@
+use std::collections::BTreeMap;
+
+use axum::http::StatusCode;
+use models::{
+	api::workspace::rbac::role::*,
+	rbac::{ResourcePermissionType, ResourcePermissionTypeDiscriminant},
+};
+
+use crate::prelude::*;
+
+/// The handler to export all the roles in a workspace, along with their
+/// permissions. Permissions are exported by their name, so that the roles can
+/// be imported into any other workspace.
+pub async fn export_roles(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: ExportWorkspaceRolesPath { workspace_id },
+				query: (),
+				headers:
+					ExportWorkspaceRolesRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: ExportWorkspaceRolesRequestProcessed,
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config: _,
+		user_data: _,
+		clock: _,
+	}: AuthenticatedAppRequest<'_, ExportWorkspaceRolesRequest>,
+) -> Result<AppResponse<ExportWorkspaceRolesRequest>, ErrorType> {
+	info!("Exporting all roles in workspace: {}", workspace_id);
+
+	let mut permissions = query!(
+		r#"
+		SELECT
+			role_resource_permissions_type.role_id,
+			permission.name,
+			role_resource_permissions_type.permission_type AS "permission_type: ResourcePermissionTypeDiscriminant"
+		FROM
+			role_resource_permissions_type
+		INNER JOIN
+			role
+		ON
+			role.id = role_resource_permissions_type.role_id
+		INNER JOIN
+			permission
+		ON
+			permission.id = role_resource_permissions_type.permission_id
+		WHERE
+			role.owner_id = $1;
+		"#,
+		workspace_id as _,
+	)
+	.fetch_all(&mut **database)
+	.await?
+	.into_iter()
+	.fold(
+		BTreeMap::<Uuid, BTreeMap<String, ResourcePermissionType>>::new(),
+		|mut map, row| {
+			map.entry(row.role_id.into()).or_default().insert(
+				row.name,
+				match row.permission_type {
+					ResourcePermissionTypeDiscriminant::Include => {
+						ResourcePermissionType::Include(Default::default())
+					}
+					ResourcePermissionTypeDiscriminant::Exclude => {
+						ResourcePermissionType::Exclude(Default::default())
+					}
+				},
+			);
+			map
+		},
+	);
+
+	trace!("Permission types fetched. Fetching resources.");
+
+	let resources = query!(
+		r#"
+		SELECT
+			resources.role_id AS "role_id!",
+			permission.name,
+			resources.resource_id AS "resource_id!"
+		FROM
+			(
+				SELECT
+					role_id,
+					permission_id,
+					resource_id
+				FROM
+					role_resource_permissions_include
+				UNION ALL
+				SELECT
+					role_id,
+					permission_id,
+					resource_id
+				FROM
+					role_resource_permissions_exclude
+			) resources
+		INNER JOIN
+			role
+		ON
+			role.id = resources.role_id
+		INNER JOIN
+			permission
+		ON
+			permission.id = resources.permission_id
+		WHERE
+			role.owner_id = $1;
+		"#,
+		workspace_id as _,
+	)
+	.fetch_all(&mut **database)
+	.await?;
+
+	for row in resources {
+		if let Some(permission) = permissions
+			.get_mut(&row.role_id.into())
+			.and_then(|role_permissions| role_permissions.get_mut(&row.name))
+		{
+			permission.insert(row.resource_id.into());
+		}
+	}
+
+	let roles = query!(
+		r#"
+		SELECT
+			id,
+			name,
+			description
+		FROM
+			role
+		WHERE
+			owner_id = $1
+		ORDER BY
+			name;
+		"#,
+		workspace_id as _,
+	)
+	.fetch_all(&mut **database)
+	.await?
+	.into_iter()
+	.map(|row| ExportedRole {
+		permissions: permissions.remove(&row.id.into()).unwrap_or_default(),
+		role: Role {
+			name: row.name,
+			description: row.description,
+		},
+	})
+	.collect();
+
+	AppResponse::builder()
+		.body(ExportWorkspaceRolesResponse {
+			exported_from: workspace_id,
+			roles,
+		})
+		.headers(())
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
@@ -31,6 +31,7 @@ pub async fn get_role_info(
 		client_ip: _,
 		config: _,
 		user_data: _,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, GetRoleInfoRequest>,
 ) -> Result<AppResponse<GetRoleInfoRequest>, ErrorType> {
 	info!(
@@ -53,7 +54,7 @@ pub async fn get_role_info(
 	)
 	.fetch_optional(&mut **database)
 	.await?
-	.ok_or(ErrorType::RoleDoesNotExist)?;
+	.or_not_found_with(ErrorType::RoleDoesNotExist)?;
 
 	trace!("Basic role details fetched");
 
@@ -0,0 +1,408 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use axum::http::StatusCode;
+use models::{
+	api::workspace::rbac::role::*,
+	rbac::{ResourcePermissionType, ResourcePermissionTypeDiscriminant},
+	utils::constants::RESOURCE_NAME_REGEX,
+};
+use regex::Regex;
+
+use crate::prelude::*;
+
+/// The handler to import roles into a workspace. This recreates the roles from
+/// a document exported using the [`super::export_roles`] handler, mapping the
+/// names of the permissions to their IDs. Permissions on the workspace that the
+/// roles were exported from are granted on this workspace instead. Other
+/// resources in the document that don't belong to this workspace are left out,
+/// which doesn't change what the role can access, since those resources can't
+/// be accessed from this workspace anyway. Roles whose name is already taken
+/// are either renamed or skipped and reported back, depending on the request.
+pub async fn import_roles(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: ImportWorkspaceRolesPath { workspace_id },
+				query: (),
+				headers:
+					ImportWorkspaceRolesRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body:
+					ImportWorkspaceRolesRequestProcessed {
+						exported_from,
+						roles,
+						rename_on_conflict,
+					},
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config: _,
+		user_data,
+		clock: _,
+	}: AuthenticatedAppRequest<'_, ImportWorkspaceRolesRequest>,
+) -> Result<AppResponse<ImportWorkspaceRolesRequest>, ErrorType> {
+	info!(
+		"Importing {} roles into workspace: {}",
+		roles.len(),
+		workspace_id
+	);
+
+	let workspace = query!(
+		r#"
+		SELECT
+			super_admin_id
+		FROM
+			workspace
+		WHERE
+			id = $1 AND
+			deleted IS NULL;
+		"#,
+		workspace_id as _,
+	)
+	.fetch_optional(&mut **database)
+	.await?
+	.or_not_found()?;
+
+	// Only the super admin can import roles
+	if workspace.super_admin_id != user_data.id.into() {
+		return Err(ErrorType::Unauthorized);
+	}
+
+	let name_regex = Regex::new(RESOURCE_NAME_REGEX).map_err(ErrorType::server_error)?;
+	if roles
+		.iter()
+		.any(|role| !name_regex.is_match(&role.role.name))
+	{
+		return Err(ErrorType::WrongParameters);
+	}
+
+	let permission_ids = query!(
+		r#"
+		SELECT
+			id,
+			name
+		FROM
+			permission;
+		"#
+	)
+	.fetch_all(&mut **database)
+	.await?
+	.into_iter()
+	.map(|row| (row.name, Uuid::from(row.id)))
+	.collect::<BTreeMap<_, _>>();
+
+	let roles = roles
+		.into_iter()
+		.map(|ExportedRole { role, permissions }| {
+			import_permissions(permissions, &permission_ids, exported_from, workspace_id)
+				.map(|permissions| (role, permissions))
+		})
+		.collect::<Result<Vec<_>, _>>()?;
+
+	let mut taken_names = query!(
+		r#"
+		SELECT
+			name
+		FROM
+			role
+		WHERE
+			owner_id = $1;
+		"#,
+		workspace_id as _,
+	)
+	.fetch_all(&mut **database)
+	.await?
+	.into_iter()
+	.map(|row| row.name)
+	.collect::<BTreeSet<_>>();
+
+	let mut imported = Vec::new();
+	let mut conflicts = Vec::new();
+
+	for (Role { name, description }, permissions) in roles {
+		let name = if !taken_names.contains(&name) {
+			name
+		} else if rename_on_conflict {
+			(1..)
+				.map(|attempt| match attempt {
+					1 => format!("{name}-imported"),
+					attempt => format!("{name}-imported-{attempt}"),
+				})
+				.find(|candidate| !taken_names.contains(candidate))
+				.unwrap_or(name)
+		} else {
+			trace!("Role `{}` already exists. Skipping.", name);
+			conflicts.push(name);
+			continue;
+		};
+		taken_names.insert(name.clone());
+
+		let role_id = query!(
+			r#"
+			INSERT INTO
+				role(
+					id,
+					owner_id,
+					name,
+					description
+				)
+			VALUES
+				(
+					GENERATE_ROLE_ID(),
+					$1,
+					$2,
+					$3
+				)
+			RETURNING id;
+			"#,
+			workspace_id as _,
+			&name as _,
+			&description as _,
+		)
+		.fetch_one(&mut **database)
+		.await
+		.map_err(|e| match e {
+			sqlx::Error::Database(dbe) if dbe.is_unique_violation() => ErrorType::RoleAlreadyExists,
+			other => other.into(),
+		})?
+		.id;
+
+		trace!("Role `{}` created. Inserting permissions.", name);
+
+		for (permission_id, permission) in permissions {
+			let permission_type = ResourcePermissionTypeDiscriminant::from(&permission);
+			query!(
+				r#"
+				INSERT INTO
+					role_resource_permissions_type(
+						role_id,
+						permission_id,
+						permission_type
+					)
+				VALUES
+					(
+						$1,
+						$2,
+						$3
+					);
+				"#,
+				role_id as _,
+				permission_id as _,
+				permission_type as _,
+			)
+			.execute(&mut **database)
+			.await?;
+
+			match permission {
+				ResourcePermissionType::Include(resources) => {
+					query!(
+						r#"
+						INSERT INTO
+							role_resource_permissions_include(
+								role_id,
+								permission_id,
+								resource_id
+							)
+						SELECT
+							$1,
+							$2,
+							resource.id
+						FROM
+							resource
+						WHERE
+							resource.id = ANY($3) AND
+							resource.owner_id = $4;
+						"#,
+						role_id as _,
+						permission_id as _,
+						&resources.into_iter().map(|r| r.into()).collect::<Vec<_>>(),
+						workspace_id as _,
+					)
+					.execute(&mut **database)
+					.await?;
+				}
+				ResourcePermissionType::Exclude(resources) => {
+					query!(
+						r#"
+						INSERT INTO
+							role_resource_permissions_exclude(
+								role_id,
+								permission_id,
+								resource_id
+							)
+						SELECT
+							$1,
+							$2,
+							resource.id
+						FROM
+							resource
+						WHERE
+							resource.id = ANY($3) AND
+							resource.owner_id = $4;
+						"#,
+						role_id as _,
+						permission_id as _,
+						&resources.into_iter().map(|r| r.into()).collect::<Vec<_>>(),
+						workspace_id as _,
+					)
+					.execute(&mut **database)
+					.await?;
+				}
+			}
+		}
+
+		imported.push(WithId::new(role_id, Role { name, description }));
+	}
+
+	AppResponse::builder()
+		.body(ImportWorkspaceRolesResponse {
+			imported,
+			conflicts,
+		})
+		.headers(())
+		.status_code(StatusCode::CREATED)
+		.build()
+		.into_result()
+}
+
+/// Maps the permissions of an exported role, keyed by the name of the
+/// permission, to the IDs of the permissions. Grants on the workspace that the
+/// role was exported from are moved to the workspace that it is imported into,
+/// so that permissions on the workspace itself are kept.
+fn import_permissions(
+	permissions: BTreeMap<String, ResourcePermissionType>,
+	permission_ids: &BTreeMap<String, Uuid>,
+	exported_from: Uuid,
+	workspace_id: Uuid,
+) -> Result<BTreeMap<Uuid, ResourcePermissionType>, ErrorType> {
+	permissions
+		.into_iter()
+		.map(|(name, permission)| {
+			let permission_id = *permission_ids
+				.get(&name)
+				.ok_or(ErrorType::UnknownPermission)?;
+			let remap = |resources: BTreeSet<Uuid>| {
+				resources
+					.into_iter()
+					.map(|resource_id| {
+						if resource_id == exported_from {
+							workspace_id
+						} else {
+							resource_id
+						}
+					})
+					.collect()
+			};
+			let permission = match permission {
+				ResourcePermissionType::Include(resources) => {
+					ResourcePermissionType::Include(remap(resources))
+				}
+				ResourcePermissionType::Exclude(resources) => {
+					ResourcePermissionType::Exclude(remap(resources))
+				}
+			};
+			Ok((permission_id, permission))
+		})
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use models::rbac::WorkspacePermission;
+
+	use super::*;
+
+	#[test]
+	fn exported_roles_keep_their_permissions_when_imported() {
+		let exported_from = Uuid::new_v4();
+		let workspace_id = Uuid::new_v4();
+		let deployment_id = Uuid::new_v4();
+		let permission_ids = BTreeMap::from([
+			("workspace::edit".to_string(), Uuid::new_v4()),
+			("workspace::delete".to_string(), Uuid::new_v4()),
+			("workspace::deployment::info".to_string(), Uuid::new_v4()),
+		]);
+		let exported_permissions = BTreeMap::from([
+			(
+				"workspace::edit".to_string(),
+				ResourcePermissionType::Include(BTreeSet::from([exported_from])),
+			),
+			(
+				"workspace::delete".to_string(),
+				ResourcePermissionType::Exclude(BTreeSet::from([exported_from])),
+			),
+			(
+				"workspace::deployment::info".to_string(),
+				ResourcePermissionType::Include(BTreeSet::from([deployment_id])),
+			),
+		]);
+
+		// The permissions of the role in the workspace it was exported from
+		let source = WorkspacePermission::Member {
+			permissions: import_permissions(
+				exported_permissions.clone(),
+				&permission_ids,
+				exported_from,
+				exported_from,
+			)
+			.unwrap(),
+		};
+
+		let document = serde_json::to_string(&ExportWorkspaceRolesResponse {
+			exported_from,
+			roles: vec![ExportedRole {
+				role: Role {
+					name: "admin".to_string(),
+					description: "".to_string(),
+				},
+				permissions: exported_permissions,
+			}],
+		})
+		.unwrap();
+		let ExportWorkspaceRolesResponse {
+			exported_from,
+			mut roles,
+		} = serde_json::from_str(&document).unwrap();
+		let ExportedRole { role, permissions } = roles.remove(0);
+		assert_eq!(role.name, "admin");
+
+		let imported = WorkspacePermission::Member {
+			permissions: import_permissions(
+				permissions,
+				&permission_ids,
+				exported_from,
+				workspace_id,
+			)
+			.unwrap(),
+		};
+
+		// The role has the same permissions on the workspace it is imported
+		// into as it had on the workspace it was exported from
+		for permission_id in permission_ids.values() {
+			assert_eq!(
+				imported.has_permission_on_resource(*permission_id, workspace_id),
+				source.has_permission_on_resource(*permission_id, exported_from),
+			);
+		}
+		let edit = permission_ids["workspace::edit"];
+		assert!(imported.has_permission_on_resource(edit, workspace_id));
+		assert!(!imported.has_permission_on_resource(edit, exported_from));
+	}
+
+	#[test]
+	fn roles_with_unknown_permissions_are_not_imported() {
+		let workspace_id = Uuid::new_v4();
+		let permissions = BTreeMap::from([(
+			"workspace::unknown".to_string(),
+			ResourcePermissionType::Include(BTreeSet::new()),
+		)]);
+
+		assert!(matches!(
+			import_permissions(permissions, &BTreeMap::new(), Uuid::new_v4(), workspace_id),
+			Err(ErrorType::UnknownPermission)
+		));
+	}
+}
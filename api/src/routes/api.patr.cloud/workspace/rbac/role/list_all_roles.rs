@@ -30,6 +30,7 @@ pub async fn list_all_roles(
 		client_ip: _,
 		config: _,
 		user_data: _,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, ListAllRolesRequest>,
 ) -> Result<AppResponse<ListAllRolesRequest>, ErrorType> {
 	info!("Listing all roles for workspace: {}", workspace_id);
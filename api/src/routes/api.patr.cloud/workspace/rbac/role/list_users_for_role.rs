@@ -30,6 +30,7 @@ pub async fn list_users_for_role(
 		client_ip: _,
 		config: _,
 		user_data: _,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, ListUsersForRoleRequest>,
 ) -> Result<AppResponse<ListUsersForRoleRequest>, ErrorType> {
 	info!("Listing all users for role: {}", role_id);
@@ -4,7 +4,9 @@ use crate::prelude::*;
 
 mod create_new_role;
 mod delete_role;
+mod export_roles;
 mod get_role_info;
+mod import_roles;
 mod list_all_roles;
 mod list_users_for_role;
 mod update_role;
@@ -12,7 +14,9 @@ mod update_role;
 use self::{
 	create_new_role::*,
 	delete_role::*,
+	export_roles::*,
 	get_role_info::*,
+	import_roles::*,
 	list_all_roles::*,
 	list_users_for_role::*,
 	update_role::*,
@@ -23,7 +27,9 @@ pub async fn setup_routes(state: &AppState) -> Router {
 	Router::new()
 		.mount_auth_endpoint(create_new_role, state)
 		.mount_auth_endpoint(delete_role, state)
+		.mount_auth_endpoint(export_roles, state)
 		.mount_auth_endpoint(get_role_info, state)
+		.mount_auth_endpoint(import_roles, state)
 		.mount_auth_endpoint(list_all_roles, state)
 		.mount_auth_endpoint(list_users_for_role, state)
 		.mount_auth_endpoint(update_role, state)
@@ -3,8 +3,6 @@ use models::{
 	api::workspace::rbac::role::*,
 	rbac::{ResourcePermissionType, ResourcePermissionTypeDiscriminant},
 };
-use rustis::commands::StringCommands;
-use time::OffsetDateTime;
 
 use crate::prelude::*;
 
@@ -36,6 +34,7 @@ pub async fn update_role(
 		client_ip: _,
 		config: _,
 		user_data: _,
+		clock,
 	}: AuthenticatedAppRequest<'_, UpdateRoleRequest>,
 ) -> Result<AppResponse<UpdateRoleRequest>, ErrorType> {
 	info!("Updating role: {}", role_id);
@@ -193,18 +192,12 @@ pub async fn update_role(
 		trace!("Role permissions inserted");
 	}
 
-	redis
-		.setex(
-			redis::keys::workspace_id_revocation_timestamp(&workspace_id),
-			constants::CACHED_PERMISSIONS_VALIDITY
-				.whole_seconds()
-				.unsigned_abs(),
-			OffsetDateTime::now_utc().unix_timestamp(),
-		)
-		.await
-		.inspect_err(|err| {
-			error!("Error setting the revocation timestamp: `{}`", err);
-		})?;
+	redis::set_revocation_timestamp(
+		redis,
+		redis::keys::workspace_id_revocation_timestamp(&workspace_id),
+		clock.now(),
+	)
+	.await?;
 
 	trace!("Revocation timestamp set");
 
@@ -29,6 +29,7 @@ pub async fn list_users_in_workspace(
 		client_ip: _,
 		config: _,
 		user_data: _,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, ListUsersInWorkspaceRequest>,
 ) -> Result<AppResponse<ListUsersInWorkspaceRequest>, ErrorType> {
 	info!("Listing all users in workspace `{workspace_id}`");
@@ -1,7 +1,5 @@
 use axum::http::StatusCode;
 use models::api::workspace::rbac::user::*;
-use rustis::commands::StringCommands;
-use time::OffsetDateTime;
 
 use crate::prelude::*;
 
@@ -27,7 +25,8 @@ pub async fn remove_user_from_workspace(
 		redis,
 		client_ip: _,
 		config: _,
-		user_data: _,
+		user_data,
+		clock,
 	}: AuthenticatedAppRequest<'_, RemoveUserFromWorkspaceRequest>,
 ) -> Result<AppResponse<RemoveUserFromWorkspaceRequest>, ErrorType> {
 	info!("Removing user `{user_id}` from workspace `{workspace_id}`");
@@ -46,20 +45,39 @@ pub async fn remove_user_from_workspace(
 	.execute(&mut **database)
 	.await?;
 
+	// Recorded in the audit log, so that it shows up in the activity of the
+	// workspace
+	query!(
+		r#"
+		INSERT INTO
+			audit_log(
+				id,
+				workspace_id,
+				resource_id,
+				timestamp,
+				action,
+				login_id
+			)
+		VALUES
+			($1, $2, $3, $4, 'delete', $5);
+		"#,
+		Uuid::new_v4() as _,
+		workspace_id as _,
+		user_id as _,
+		clock.now(),
+		user_data.login_id as _,
+	)
+	.execute(&mut **database)
+	.await?;
+
 	info!("User removed. Setting revocation timestamp");
 
-	redis
-		.setex(
-			redis::keys::user_id_revocation_timestamp(&user_id),
-			constants::CACHED_PERMISSIONS_VALIDITY
-				.whole_seconds()
-				.unsigned_abs(),
-			OffsetDateTime::now_utc().unix_timestamp(),
-		)
-		.await
-		.inspect_err(|err| {
-			error!("Error setting the revocation timestamp: `{}`", err);
-		})?;
+	redis::set_revocation_timestamp(
+		redis,
+		redis::keys::user_id_revocation_timestamp(&user_id),
+		clock.now(),
+	)
+	.await?;
 
 	AppResponse::builder()
 		.body(RemoveUserFromWorkspaceResponse)
@@ -1,7 +1,5 @@
 use axum::http::StatusCode;
 use models::api::workspace::rbac::user::*;
-use rustis::commands::StringCommands;
-use time::OffsetDateTime;
 
 use crate::prelude::*;
 
@@ -28,7 +26,8 @@ pub async fn update_user_roles_in_workspace(
 		redis,
 		client_ip: _,
 		config: _,
-		user_data: _,
+		user_data,
+		clock,
 	}: AuthenticatedAppRequest<'_, UpdateUserRolesInWorkspaceRequest>,
 ) -> Result<AppResponse<UpdateUserRolesInWorkspaceRequest>, ErrorType> {
 	info!("Updating user `{user_id}`'s roles in workspace `{workspace_id}`");
@@ -68,20 +67,39 @@ pub async fn update_user_roles_in_workspace(
 	.execute(&mut **database)
 	.await?;
 
+	// Recorded in the audit log, so that it shows up in the activity of the
+	// workspace
+	query!(
+		r#"
+		INSERT INTO
+			audit_log(
+				id,
+				workspace_id,
+				resource_id,
+				timestamp,
+				action,
+				login_id
+			)
+		VALUES
+			($1, $2, $3, $4, 'update', $5);
+		"#,
+		Uuid::new_v4() as _,
+		workspace_id as _,
+		user_id as _,
+		clock.now(),
+		user_data.login_id as _,
+	)
+	.execute(&mut **database)
+	.await?;
+
 	info!("User's roles updated. Setting revocation timestamp");
 
-	redis
-		.setex(
-			redis::keys::user_id_revocation_timestamp(&user_id),
-			constants::CACHED_PERMISSIONS_VALIDITY
-				.whole_seconds()
-				.unsigned_abs(),
-			OffsetDateTime::now_utc().unix_timestamp(),
-		)
-		.await
-		.inspect_err(|err| {
-			error!("Error setting the revocation timestamp: `{}`", err);
-		})?;
+	redis::set_revocation_timestamp(
+		redis,
+		redis::keys::user_id_revocation_timestamp(&user_id),
+		clock.now(),
+	)
+	.await?;
 
 	AppResponse::builder()
 		.body(UpdateUserRolesInWorkspaceResponse)
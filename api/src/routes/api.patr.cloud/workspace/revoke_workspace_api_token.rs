@@ -0,0 +1,146 @@
+use axum::http::StatusCode;
+use models::api::workspace::*;
+
+use crate::prelude::*;
+
+/// The handler to revoke an API token that has been granted access to a
+/// workspace. Only the permissions of the token on this workspace are removed,
+/// since the admin of one workspace can't revoke the access of the token to the
+/// other workspaces of its owner.
+pub async fn revoke_workspace_api_token(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: RevokeWorkspaceApiTokenPath {
+					workspace_id,
+					token_id,
+				},
+				query: (),
+				headers:
+					RevokeWorkspaceApiTokenRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: RevokeWorkspaceApiTokenRequestProcessed,
+			},
+		database,
+		redis,
+		client_ip: _,
+		config: _,
+		user_data,
+		clock,
+	}: AuthenticatedAppRequest<'_, RevokeWorkspaceApiTokenRequest>,
+) -> Result<AppResponse<RevokeWorkspaceApiTokenRequest>, ErrorType> {
+	info!(
+		"Revoking the API token `{token_id}` of the workspace `{workspace_id}` by user `{}`",
+		user_data.id
+	);
+
+	query!(
+		r#"
+		SELECT
+			user_api_token.token_id
+		FROM
+			user_api_token
+		INNER JOIN
+			user_api_token_workspace_permission_type
+		ON
+			user_api_token_workspace_permission_type.token_id = user_api_token.token_id
+		WHERE
+			user_api_token.token_id = $1 AND
+			user_api_token_workspace_permission_type.workspace_id = $2;
+		"#,
+		token_id as _,
+		workspace_id as _,
+	)
+	.fetch_optional(&mut **database)
+	.await?
+	.ok_or(ErrorType::ResourceDoesNotExist)?;
+
+	query!(
+		r#"
+		DELETE FROM
+			user_api_token_workspace_super_admin
+		WHERE
+			token_id = $1 AND
+			workspace_id = $2;
+		"#,
+		token_id as _,
+		workspace_id as _,
+	)
+	.execute(&mut **database)
+	.await?;
+
+	query!(
+		r#"
+		DELETE FROM
+			user_api_token_resource_permissions_include
+		WHERE
+			token_id = $1 AND
+			workspace_id = $2;
+		"#,
+		token_id as _,
+		workspace_id as _,
+	)
+	.execute(&mut **database)
+	.await?;
+
+	query!(
+		r#"
+		DELETE FROM
+			user_api_token_resource_permissions_exclude
+		WHERE
+			token_id = $1 AND
+			workspace_id = $2;
+		"#,
+		token_id as _,
+		workspace_id as _,
+	)
+	.execute(&mut **database)
+	.await?;
+
+	query!(
+		r#"
+		DELETE FROM
+			user_api_token_resource_permissions_type
+		WHERE
+			token_id = $1 AND
+			workspace_id = $2;
+		"#,
+		token_id as _,
+		workspace_id as _,
+	)
+	.execute(&mut **database)
+	.await?;
+
+	query!(
+		r#"
+		DELETE FROM
+			user_api_token_workspace_permission_type
+		WHERE
+			token_id = $1 AND
+			workspace_id = $2;
+		"#,
+		token_id as _,
+		workspace_id as _,
+	)
+	.execute(&mut **database)
+	.await?;
+
+	trace!("Permissions of the token on the workspace deleted");
+
+	// The cached permissions of the token are reloaded without the workspace
+	redis::set_revocation_timestamp(
+		redis,
+		redis::keys::login_id_revocation_timestamp(&token_id),
+		clock.now(),
+	)
+	.await?;
+
+	AppResponse::builder()
+		.body(RevokeWorkspaceApiTokenResponse)
+		.headers(())
+		.status_code(StatusCode::RESET_CONTENT)
+		.build()
+		.into_result()
+}
@@ -1,5 +1,5 @@
 use axum::http::StatusCode;
-use models::{api::workspace::runner::*, prelude::*};
+use models::{api::workspace::runner::*, prelude::*, QuotaResource};
 
 use crate::prelude::*;
 
@@ -19,12 +19,21 @@ pub async fn add_runner_to_workspace(
 		database,
 		redis: _,
 		client_ip: _,
-		config: _,
+		config,
 		user_data: _,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, AddRunnerToWorkspaceRequest>,
 ) -> Result<AppResponse<AddRunnerToWorkspaceRequest>, ErrorType> {
 	info!("Creating Runner with name: `{name}`");
 
+	super::super::ensure_within_quota(
+		&mut **database,
+		&config,
+		workspace_id,
+		QuotaResource::Runner,
+	)
+	.await?;
+
 	let id = query!(
 		r#"
 		INSERT INTO
@@ -60,14 +69,16 @@ pub async fn add_runner_to_workspace(
 				id,
 				name,
 				workspace_id,
-				cloudflare_tunnel_id
+				cloudflare_tunnel_id,
+				updated
 			)
 		VALUES
 			(
 				$1,
 				$2,
 				$3,
-				'qwertyuiop'
+				'qwertyuiop',
+				NOW()
 			);
 		"#,
 		id as _,
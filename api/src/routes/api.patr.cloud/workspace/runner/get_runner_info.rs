@@ -25,6 +25,7 @@ pub async fn get_runner_info(
 		client_ip: _,
 		config: _,
 		user_data: _,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, GetRunnerInfoRequest>,
 ) -> Result<AppResponse<GetRunnerInfoRequest>, ErrorType> {
 	info!("Getting information about the workspace `{workspace_id}`");
@@ -32,20 +33,25 @@ pub async fn get_runner_info(
 	let runner = query!(
 		r#"
 		SELECT
-			*
+			runner.*,
+			resource.created
 		FROM
 			runner
+		INNER JOIN
+			resource
+		ON
+			runner.id = resource.id
 		WHERE
-			id = $1 AND
-            workspace_id = $2 AND
-			deleted IS NULL;
+			runner.id = $1 AND
+            runner.workspace_id = $2 AND
+			runner.deleted IS NULL;
 		"#,
 		&runner_id as _,
 		&workspace_id as _,
 	)
 	.fetch_optional(&mut **database)
 	.await?
-	.ok_or(ErrorType::ResourceDoesNotExist)?;
+	.or_not_found()?;
 
 	let connected = redis
 		.get::<_, Option<String>>(redis::keys::runner_connection_lock(&runner_id))
@@ -60,6 +66,8 @@ pub async fn get_runner_info(
 					name: runner.name,
 					connected,
 					last_seen: None, // TODO
+					created_at: runner.created,
+					updated_at: runner.updated,
 				},
 			),
 		})
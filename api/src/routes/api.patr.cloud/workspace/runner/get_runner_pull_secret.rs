@@ -0,0 +1,92 @@
+use axum::http::StatusCode;
+use models::api::workspace::runner::*;
+
+use crate::{
+	prelude::*,
+	utils::secret_store::{AppSecretStore, SecretStore},
+};
+
+/// The handler for a runner to get the credentials of a pull secret. The pull
+/// secret must be used by a deployment that runs on the runner, so that a
+/// runner can't read the credentials of the rest of the workspace. The password
+/// is read from the secret store, and is never logged.
+pub async fn get_runner_pull_secret(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path:
+					GetRunnerPullSecretPath {
+						workspace_id,
+						runner_id,
+						secret_id,
+					},
+				query: (),
+				headers:
+					GetRunnerPullSecretRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: GetRunnerPullSecretRequestProcessed,
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config,
+		user_data: _,
+		clock: _,
+	}: AuthenticatedAppRequest<'_, GetRunnerPullSecretRequest>,
+) -> Result<AppResponse<GetRunnerPullSecretRequest>, ErrorType> {
+	info!("Getting pull secret `{secret_id}` for runner `{runner_id}`");
+
+	let credential = query!(
+		r#"
+		SELECT
+			secret_pull_credential.registry,
+			secret_pull_credential.username
+		FROM
+			secret_pull_credential
+		INNER JOIN
+			secret
+		ON
+			secret.id = secret_pull_credential.secret_id
+		WHERE
+			secret_pull_credential.secret_id = $1 AND
+			secret.workspace_id = $2 AND
+			secret.deleted IS NULL AND
+			EXISTS(
+				SELECT
+					1
+				FROM
+					deployment
+				WHERE
+					deployment.pull_secret_id = $1 AND
+					deployment.runner = $3 AND
+					deployment.deleted IS NULL
+			);
+		"#,
+		secret_id as _,
+		workspace_id as _,
+		runner_id as _,
+	)
+	.fetch_optional(&mut **database)
+	.await?
+	.or_not_found()?;
+
+	let password = AppSecretStore::new(&config.secrets, &mut **database)
+		.get(workspace_id, secret_id)
+		.await?
+		.ok_or_else(|| ErrorType::server_error("the password of the pull secret is missing"))?;
+
+	AppResponse::builder()
+		.body(GetRunnerPullSecretResponse {
+			credentials: RegistryCredentials {
+				registry: credential.registry,
+				username: credential.username,
+				password,
+			},
+		})
+		.headers(())
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
@@ -1,19 +1,24 @@
 use axum::http::StatusCode;
 use models::{api::workspace::runner::*, prelude::*};
-use rustis::commands::{GenericCommands, ScanOptions};
+use rustis::commands::StringCommands;
 
 use crate::prelude::*;
 
+/// The handler to list the runners of a workspace that the user has access
+/// to, optionally filtered by whether they are online or offline. Offline
+/// runners are listed as well, so that they can be cleaned up. A runner is
+/// online if it currently holds a connection to the API.
 pub async fn list_runners_for_workspace(
 	AuthenticatedAppRequest {
 		request:
 			ProcessedApiRequest {
 				path: ListRunnersForWorkspacePath { workspace_id },
-				query: Paginated {
-					data: (),
-					count,
-					page,
-				},
+				query:
+					Paginated {
+						data: ListRunnersForWorkspaceQuery { status },
+						count,
+						page,
+					},
 				headers:
 					ListRunnersForWorkspaceRequestHeaders {
 						authorization: _,
@@ -26,16 +31,48 @@ pub async fn list_runners_for_workspace(
 		client_ip: _,
 		config: _,
 		user_data,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, ListRunnersForWorkspaceRequest>,
 ) -> Result<AppResponse<ListRunnersForWorkspaceRequest>, ErrorType> {
 	info!("Listing runners in workspace `{}`", workspace_id);
 
-	let (_, connected_runners) = redis
-		.scan::<_, Vec<String>>(
-			0,
-			ScanOptions::default().match_pattern(redis::keys::runner_connection_lock_prefix()),
-		)
-		.await?;
+	let runner_ids = query!(
+		r#"
+		SELECT
+			id
+		FROM
+			runner
+		WHERE
+			workspace_id = $1 AND
+			deleted IS NULL;
+		"#,
+		workspace_id as _,
+	)
+	.fetch_all(&mut **database)
+	.await?
+	.into_iter()
+	.map(|row| row.id)
+	.collect::<Vec<_>>();
+
+	// A runner holds its connection lock for as long as it is connected
+	let connected_runners = if runner_ids.is_empty() {
+		Vec::new()
+	} else {
+		let connection_locks = redis
+			.mget::<_, _, Option<String>, Vec<Option<String>>>(
+				runner_ids
+					.iter()
+					.map(|id| redis::keys::runner_connection_lock(&(*id).into()))
+					.collect::<Vec<_>>(),
+			)
+			.await?;
+
+		runner_ids
+			.into_iter()
+			.zip(connection_locks)
+			.filter_map(|(id, lock)| lock.map(|_| id))
+			.collect::<Vec<_>>()
+	};
 
 	let mut total_count = 0;
 	let runners = query!(
@@ -43,6 +80,8 @@ pub async fn list_runners_for_workspace(
 		SELECT
 			runner.id,
             name,
+			resource.created,
+			runner.updated,
 			COUNT(*) OVER() AS "total_count!"
 		FROM
 			runner
@@ -52,7 +91,11 @@ pub async fn list_runners_for_workspace(
 			runner.id = resource.id
 		WHERE
 			workspace_id = $1 AND
-			runner.deleted IS NULL
+			runner.deleted IS NULL AND
+			(
+				$6::BOOLEAN IS NULL OR
+				(runner.id = ANY($7)) = $6
+			)
 		ORDER BY
 			resource.created DESC
 		LIMIT $4
@@ -63,6 +106,8 @@ pub async fn list_runners_for_workspace(
 		Permission::Runner(RunnerPermission::View) as _,
 		count as i32,
 		(count * page) as i32,
+		status.map(|status| status == RunnerStatus::Online),
+		&connected_runners,
 	)
 	.fetch_all(&mut **database)
 	.await?
@@ -73,9 +118,10 @@ pub async fn list_runners_for_workspace(
 			row.id,
 			Runner {
 				name: row.name,
-				connected: connected_runners
-					.contains(&redis::keys::runner_connection_lock(&row.id.into())),
+				connected: connected_runners.contains(&row.id),
 				last_seen: None, // TODO
+				created_at: row.created,
+				updated_at: row.updated,
 			},
 		)
 	})
@@ -4,6 +4,7 @@ use crate::prelude::*;
 
 mod add_runner_to_workspace;
 mod get_runner_info;
+mod get_runner_pull_secret;
 mod list_runners_for_workspace;
 mod remove_runner_from_workspace;
 mod stream_runner_data_for_workspace;
@@ -11,6 +12,7 @@ mod stream_runner_data_for_workspace;
 use self::{
 	add_runner_to_workspace::*,
 	get_runner_info::*,
+	get_runner_pull_secret::*,
 	list_runners_for_workspace::*,
 	remove_runner_from_workspace::*,
 	stream_runner_data_for_workspace::*,
@@ -24,4 +26,5 @@ pub async fn setup_routes(state: &AppState) -> Router {
 		.mount_auth_endpoint(remove_runner_from_workspace, state)
 		.mount_auth_endpoint(list_runners_for_workspace, state)
 		.mount_auth_endpoint(get_runner_info, state)
+		.mount_auth_endpoint(get_runner_pull_secret, state)
 }
@@ -32,6 +32,7 @@ pub async fn stream_runner_data_for_workspace(
 		client_ip: _,
 		config: _,
 		user_data: _,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, StreamRunnerDataForWorkspaceRequest>,
 ) -> Result<AppResponse<StreamRunnerDataForWorkspaceRequest>, ErrorType> {
 	// Try to acquire a lock on redis first
@@ -0,0 +1,147 @@
+use std::str::FromStr;
+
+use axum::http::StatusCode;
+use models::{api::workspace::*, rbac::ResourceType};
+
+use crate::prelude::*;
+
+/// The handler to search for resources in a workspace by their name. Only the
+/// resources that the user has permission to view are searched, so that the
+/// results don't leak the names of resources that the user cannot access. The
+/// results are ranked by how well their name matches the query, with exact
+/// matches first, then names starting with the query, and then names that
+/// contain it. Shorter names are ranked higher within each of them.
+pub async fn search_workspace_resources(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: SearchWorkspaceResourcesPath { workspace_id },
+				query: SearchWorkspaceResourcesQuery { query },
+				headers:
+					SearchWorkspaceResourcesRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: SearchWorkspaceResourcesRequestProcessed,
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config: _,
+		user_data,
+		clock: _,
+	}: AuthenticatedAppRequest<'_, SearchWorkspaceResourcesRequest>,
+) -> Result<AppResponse<SearchWorkspaceResourcesRequest>, ErrorType> {
+	info!("Searching for resources in workspace `{workspace_id}`");
+
+	// Escape the query so that the wildcards in it are matched literally
+	let escaped = query
+		.replace('\\', "\\\\")
+		.replace('%', "\\%")
+		.replace('_', "\\_");
+
+	let results = query!(
+		r#"
+		SELECT
+			id AS "id!",
+			name AS "name!",
+			resource_type AS "resource_type!"
+		FROM (
+			SELECT
+				deployment.id,
+				deployment.name::TEXT AS name,
+				'deployment' AS resource_type
+			FROM
+				deployment
+			INNER JOIN
+				RESOURCES_WITH_PERMISSION_FOR_LOGIN_ID($2, $3) AS resource
+			ON
+				deployment.id = resource.id
+			WHERE
+				deployment.workspace_id = $1 AND
+				deployment.deleted IS NULL
+			UNION ALL
+			SELECT
+				runner.id,
+				runner.name::TEXT AS name,
+				'runner' AS resource_type
+			FROM
+				runner
+			INNER JOIN
+				RESOURCES_WITH_PERMISSION_FOR_LOGIN_ID($2, $4) AS resource
+			ON
+				runner.id = resource.id
+			WHERE
+				runner.workspace_id = $1 AND
+				runner.deleted IS NULL
+			UNION ALL
+			SELECT
+				managed_database.id,
+				managed_database.name::TEXT AS name,
+				'database' AS resource_type
+			FROM
+				managed_database
+			INNER JOIN
+				RESOURCES_WITH_PERMISSION_FOR_LOGIN_ID($2, $5) AS resource
+			ON
+				managed_database.id = resource.id
+			WHERE
+				managed_database.workspace_id = $1 AND
+				managed_database.deleted IS NULL
+			UNION ALL
+			SELECT
+				secret.id,
+				secret.name::TEXT AS name,
+				'secret' AS resource_type
+			FROM
+				secret
+			INNER JOIN
+				RESOURCES_WITH_PERMISSION_FOR_LOGIN_ID($2, $6) AS resource
+			ON
+				secret.id = resource.id
+			WHERE
+				secret.workspace_id = $1 AND
+				secret.deleted IS NULL
+		) AS resources
+		WHERE
+			name ILIKE '%' || $7 || '%'
+		ORDER BY
+			CASE
+				WHEN LOWER(name) = LOWER($8) THEN 0
+				WHEN name ILIKE $7 || '%' THEN 1
+				ELSE 2
+			END,
+			LENGTH(name),
+			name
+		LIMIT $9;
+		"#,
+		workspace_id as _,
+		user_data.login_id as _,
+		Permission::Deployment(DeploymentPermission::View) as _,
+		Permission::Runner(RunnerPermission::View) as _,
+		Permission::Database(DatabasePermission::View) as _,
+		Permission::Secret(SecretPermission::View) as _,
+		escaped,
+		query,
+		constants::MAX_WORKSPACE_SEARCH_RESULTS as i64,
+	)
+	.fetch_all(&mut **database)
+	.await?
+	.into_iter()
+	.map(|row| {
+		Ok(WorkspaceSearchResult {
+			id: row.id.into(),
+			name: row.name,
+			resource_type: ResourceType::from_str(&row.resource_type)
+				.map_err(ErrorType::server_error)?,
+		})
+	})
+	.collect::<Result<_, ErrorType>>()?;
+
+	AppResponse::builder()
+		.body(SearchWorkspaceResourcesResponse { results })
+		.headers(())
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
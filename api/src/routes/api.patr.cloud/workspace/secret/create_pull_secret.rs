@@ -0,0 +1,124 @@
+use axum::http::StatusCode;
+use models::api::workspace::secret::*;
+use time::OffsetDateTime;
+
+use crate::{
+	prelude::*,
+	utils::secret_store::{AppSecretStore, SecretStore},
+};
+
+/// The handler to create a pull secret in the workspace. The credentials are
+/// stored so that deployments referencing this secret can pull their image
+/// from a private registry, with the password kept in the secret store like
+/// the values of other secrets. They are only ever returned to the runners of
+/// the deployments that use them.
+pub async fn create_pull_secret(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: CreatePullSecretPath { workspace_id },
+				query: (),
+				headers:
+					CreatePullSecretRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body:
+					CreatePullSecretRequestProcessed {
+						name,
+						registry,
+						username,
+						password,
+					},
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config,
+		user_data: _,
+		clock: _,
+	}: AuthenticatedAppRequest<'_, CreatePullSecretRequest>,
+) -> Result<AppResponse<CreatePullSecretRequest>, ErrorType> {
+	info!("Creating pull secret `{name}` for registry `{registry}` in workspace `{workspace_id}`");
+
+	let secret_id = query!(
+		r#"
+		INSERT INTO
+			resource(
+				id,
+				resource_type_id,
+				owner_id,
+				created,
+				deleted
+			)
+		VALUES
+			(
+				GENERATE_RESOURCE_ID(),
+				(SELECT id FROM resource_type WHERE name = 'secret'),
+				$1,
+				$2,
+				NULL
+			)
+		RETURNING id;
+		"#,
+		workspace_id as _,
+		OffsetDateTime::now_utc(),
+	)
+	.fetch_one(&mut **database)
+	.await?
+	.id;
+
+	query!(
+		r#"
+		INSERT INTO
+			secret(
+				id,
+				name,
+				workspace_id,
+				deleted
+			)
+		VALUES
+			($1, $2, $3, NULL);
+		"#,
+		secret_id as _,
+		name.as_ref(),
+		workspace_id as _,
+	)
+	.execute(&mut **database)
+	.await
+	.map_err(|err| match err {
+		sqlx::Error::Database(err) if err.is_unique_violation() => ErrorType::ResourceAlreadyExists,
+		err => ErrorType::server_error(err),
+	})?;
+
+	query!(
+		r#"
+		INSERT INTO
+			secret_pull_credential(
+				secret_id,
+				registry,
+				username
+			)
+		VALUES
+			($1, $2, $3);
+		"#,
+		secret_id as _,
+		registry.as_ref(),
+		username.as_ref(),
+	)
+	.execute(&mut **database)
+	.await?;
+
+	AppSecretStore::new(&config.secrets, &mut **database)
+		.put(workspace_id, secret_id.into(), &password)
+		.await?;
+
+	AppResponse::builder()
+		.body(CreatePullSecretResponse {
+			id: WithId::from(secret_id),
+		})
+		.headers(())
+		.status_code(StatusCode::CREATED)
+		.build()
+		.into_result()
+}
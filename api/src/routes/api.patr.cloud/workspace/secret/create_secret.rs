@@ -0,0 +1,98 @@
+use axum::http::StatusCode;
+use models::api::workspace::secret::*;
+use time::OffsetDateTime;
+
+use crate::{
+	prelude::*,
+	utils::secret_store::{AppSecretStore, SecretStore},
+};
+
+/// The handler to create a secret in the workspace. The name of the secret is
+/// stored in the database as a resource, and its value is stored in the
+/// secret store that is configured for the API.
+pub async fn create_secret(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: CreateSecretPath { workspace_id },
+				query: (),
+				headers:
+					CreateSecretRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: CreateSecretRequestProcessed { name, value },
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config,
+		user_data: _,
+		clock: _,
+	}: AuthenticatedAppRequest<'_, CreateSecretRequest>,
+) -> Result<AppResponse<CreateSecretRequest>, ErrorType> {
+	info!("Creating secret `{name}` in workspace `{workspace_id}`");
+
+	let secret_id = query!(
+		r#"
+		INSERT INTO
+			resource(
+				id,
+				resource_type_id,
+				owner_id,
+				created,
+				deleted
+			)
+		VALUES
+			(
+				GENERATE_RESOURCE_ID(),
+				(SELECT id FROM resource_type WHERE name = 'secret'),
+				$1,
+				$2,
+				NULL
+			)
+		RETURNING id;
+		"#,
+		workspace_id as _,
+		OffsetDateTime::now_utc(),
+	)
+	.fetch_one(&mut **database)
+	.await?
+	.id;
+
+	query!(
+		r#"
+		INSERT INTO
+			secret(
+				id,
+				name,
+				workspace_id,
+				deleted
+			)
+		VALUES
+			($1, $2, $3, NULL);
+		"#,
+		secret_id as _,
+		name.as_ref(),
+		workspace_id as _,
+	)
+	.execute(&mut **database)
+	.await
+	.map_err(|err| match err {
+		sqlx::Error::Database(err) if err.is_unique_violation() => ErrorType::ResourceAlreadyExists,
+		err => ErrorType::server_error(err),
+	})?;
+
+	AppSecretStore::new(&config.secrets, &mut **database)
+		.put(workspace_id, secret_id.into(), &value)
+		.await?;
+
+	AppResponse::builder()
+		.body(CreateSecretResponse {
+			id: WithId::from(secret_id),
+		})
+		.headers(())
+		.status_code(StatusCode::CREATED)
+		.build()
+		.into_result()
+}
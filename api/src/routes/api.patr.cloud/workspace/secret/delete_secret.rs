@@ -0,0 +1,147 @@
+use axum::http::StatusCode;
+use models::api::workspace::secret::*;
+
+use crate::{
+	prelude::*,
+	utils::secret_store::{AppSecretStore, SecretStore},
+};
+
+/// The handler to delete a secret in the workspace. A secret that is used by a
+/// deployment or a deployment template cannot be deleted. The value of the
+/// secret is deleted from the secret store only after the secret is marked as
+/// deleted, so that a failure to delete it leaves the secret intact.
+pub async fn delete_secret(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: DeleteSecretPath {
+					workspace_id,
+					secret_id,
+				},
+				query: (),
+				headers:
+					DeleteSecretRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: DeleteSecretRequestProcessed,
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config,
+		user_data: _,
+		clock: _,
+	}: AuthenticatedAppRequest<'_, DeleteSecretRequest>,
+) -> Result<AppResponse<DeleteSecretRequest>, ErrorType> {
+	info!("Deleting secret `{secret_id}` in workspace `{workspace_id}`");
+
+	super::ensure_secret_exists(&mut **database, workspace_id, secret_id).await?;
+
+	let in_use = query!(
+		r#"
+		SELECT
+			EXISTS(
+				SELECT
+					1
+				FROM
+					deployment_environment_variable
+				INNER JOIN
+					deployment
+				ON
+					deployment.id = deployment_environment_variable.deployment_id
+				WHERE
+					deployment_environment_variable.secret_id = $1 AND
+					deployment.deleted IS NULL
+			) OR
+			EXISTS(
+				SELECT
+					1
+				FROM
+					deployment
+				WHERE
+					pull_secret_id = $1 AND
+					deleted IS NULL
+			) OR
+			EXISTS(
+				SELECT
+					1
+				FROM
+					deployment_template_environment_variable
+				WHERE
+					secret_id = $1
+			) OR
+			EXISTS(
+				SELECT
+					1
+				FROM
+					managed_database_credential
+				WHERE
+					secret_id = $1
+			) AS "in_use!";
+		"#,
+		secret_id as _,
+	)
+	.fetch_one(&mut **database)
+	.await?
+	.in_use;
+
+	if in_use {
+		return Err(ErrorType::ResourceInUse);
+	}
+
+	query!(
+		r#"
+		SET CONSTRAINTS ALL DEFERRED;
+		"#
+	)
+	.execute(&mut **database)
+	.await?;
+
+	query!(
+		r#"
+		UPDATE
+			secret
+		SET
+			deleted = NOW()
+		WHERE
+			id = $1;
+		"#,
+		secret_id as _,
+	)
+	.execute(&mut **database)
+	.await?;
+
+	query!(
+		r#"
+		UPDATE
+			resource
+		SET
+			deleted = NOW()
+		WHERE
+			id = $1;
+		"#,
+		secret_id as _,
+	)
+	.execute(&mut **database)
+	.await?;
+
+	query!(
+		r#"
+		SET CONSTRAINTS ALL IMMEDIATE;
+		"#
+	)
+	.execute(&mut **database)
+	.await?;
+
+	AppSecretStore::new(&config.secrets, &mut **database)
+		.delete(workspace_id, secret_id)
+		.await?;
+
+	AppResponse::builder()
+		.body(DeleteSecretResponse)
+		.headers(())
+		.status_code(StatusCode::RESET_CONTENT)
+		.build()
+		.into_result()
+}
@@ -0,0 +1,122 @@
+use std::collections::BTreeMap;
+
+use axum::http::StatusCode;
+use models::{api::workspace::secret::*, utils::TotalCountHeader};
+
+use crate::{
+	prelude::*,
+	utils::secret_store::{AppSecretStore, SecretStore},
+};
+
+/// The handler to list the secrets in the workspace that the user has access
+/// to. The values of the secrets are never read, only the metadata that the
+/// secret store has about them.
+pub async fn list_secrets_for_workspace(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: ListSecretsForWorkspacePath { workspace_id },
+				query: Paginated {
+					data: (),
+					count,
+					page,
+				},
+				headers:
+					ListSecretsForWorkspaceRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: ListSecretsForWorkspaceRequestProcessed,
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config,
+		user_data,
+		clock: _,
+	}: AuthenticatedAppRequest<'_, ListSecretsForWorkspaceRequest>,
+) -> Result<AppResponse<ListSecretsForWorkspaceRequest>, ErrorType> {
+	info!("Listing secrets in workspace `{workspace_id}`");
+
+	let mut total_count = 0;
+	let rows = query!(
+		r#"
+		SELECT
+			secret.id,
+			secret.name::TEXT AS "name!",
+			secret_pull_credential.registry AS "pull_secret_registry?",
+			(
+				SELECT
+					deployment_environment_variable.deployment_id
+				FROM
+					deployment_environment_variable
+				INNER JOIN
+					deployment
+				ON
+					deployment.id = deployment_environment_variable.deployment_id
+				WHERE
+					deployment_environment_variable.secret_id = secret.id AND
+					deployment.deleted IS NULL
+				LIMIT 1
+			) AS deployment_id,
+			COUNT(*) OVER() AS "total_count!"
+		FROM
+			secret
+		INNER JOIN
+			RESOURCES_WITH_PERMISSION_FOR_LOGIN_ID($2, $3) AS resource
+		ON
+			secret.id = resource.id
+		LEFT JOIN
+			secret_pull_credential
+		ON
+			secret_pull_credential.secret_id = secret.id
+		WHERE
+			secret.workspace_id = $1 AND
+			secret.deleted IS NULL
+		ORDER BY
+			resource.created DESC
+		LIMIT $4
+		OFFSET $5;
+		"#,
+		workspace_id as _,
+		user_data.login_id as _,
+		Permission::Secret(SecretPermission::View) as _,
+		count as i32,
+		(count * page) as i32,
+	)
+	.fetch_all(&mut **database)
+	.await?;
+
+	let value_updated = AppSecretStore::new(&config.secrets, &mut **database)
+		.list_metadata(workspace_id)
+		.await?
+		.into_iter()
+		.map(|metadata| (metadata.secret_id, metadata.updated))
+		.collect::<BTreeMap<_, _>>();
+
+	let secrets = rows
+		.into_iter()
+		.map(|row| {
+			total_count = row.total_count;
+			let id = Uuid::from(row.id);
+			WithId::new(
+				id,
+				Secret {
+					name: row.name,
+					deployment_id: row.deployment_id.map(Into::into),
+					pull_secret_registry: row.pull_secret_registry,
+					value_updated: value_updated.get(&id).copied().flatten(),
+				},
+			)
+		})
+		.collect();
+
+	AppResponse::builder()
+		.body(ListSecretsForWorkspaceResponse { secrets })
+		.headers(ListSecretsForWorkspaceResponseHeaders {
+			total_count: TotalCountHeader(total_count as _),
+		})
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
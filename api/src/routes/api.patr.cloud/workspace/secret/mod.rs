@@ -1,11 +1,32 @@
-use axum::{http::StatusCode, Router};
-use models::{api::workspace::secret::*, ErrorType};
+use axum::Router;
 
 use crate::prelude::*;
 
+/// The handler to create a pull secret, which stores the credentials of a
+/// private registry that deployments can pull their images from
+mod create_pull_secret;
+/// The handler to create a secret, whose value is stored in the configured
+/// secret store
+mod create_secret;
+/// The handler to delete a secret along with its value
+mod delete_secret;
+/// The handler to list the secrets of a workspace, without their values
+mod list_secrets_for_workspace;
+/// The handler to update the name and / or the value of a secret
+mod update_secret;
+
+use self::{
+	create_pull_secret::*,
+	create_secret::*,
+	delete_secret::*,
+	list_secrets_for_workspace::*,
+	update_secret::*,
+};
+
 #[instrument(skip(state))]
 pub async fn setup_routes(state: &AppState) -> Router {
 	Router::new()
+		.mount_auth_endpoint(create_pull_secret, state)
 		.mount_auth_endpoint(create_secret, state)
 		.mount_auth_endpoint(delete_secret, state)
 		.mount_auth_endpoint(list_secrets_for_workspace, state)
@@ -13,117 +34,30 @@ pub async fn setup_routes(state: &AppState) -> Router {
 		.with_state(state.clone())
 }
 
-async fn create_secret(
-	AuthenticatedAppRequest {
-		request:
-			ProcessedApiRequest {
-				path: CreateSecretPath { workspace_id },
-				query: _,
-				headers,
-				body,
-			},
-		database,
-		redis: _,
-		client_ip: _,
-		config,
-		user_data,
-	}: AuthenticatedAppRequest<'_, CreateSecretRequest>,
-) -> Result<AppResponse<CreateSecretRequest>, ErrorType> {
-	info!("Starting: Create secret");
-
-	// LOGIC
-
-	AppResponse::builder()
-		.body(CreateSecretResponse { id: todo!() })
-		.headers(())
-		.status_code(StatusCode::OK)
-		.build()
-		.into_result()
-}
-
-async fn delete_secret(
-	AuthenticatedAppRequest {
-		request:
-			ProcessedApiRequest {
-				path: DeleteSecretPath {
-					workspace_id,
-					secret_id,
-				},
-				query: _,
-				headers: _,
-				body,
-			},
-		database,
-		redis: _,
-		client_ip: _,
-		config,
-		user_data,
-	}: AuthenticatedAppRequest<'_, DeleteSecretRequest>,
-) -> Result<AppResponse<DeleteSecretRequest>, ErrorType> {
-	info!("Starting: Delete secret");
-
-	// LOGIC
-
-	AppResponse::builder()
-		.body(DeleteSecretResponse)
-		.headers(())
-		.status_code(StatusCode::OK)
-		.build()
-		.into_result()
-}
-
-async fn list_secrets_for_workspace(
-	AuthenticatedAppRequest {
-		request: ProcessedApiRequest {
-			path,
-			query: _,
-			headers,
-			body,
-		},
-		database,
-		redis: _,
-		client_ip: _,
-		config,
-		user_data,
-	}: AuthenticatedAppRequest<'_, ListSecretsForWorkspaceRequest>,
-) -> Result<AppResponse<ListSecretsForWorkspaceRequest>, ErrorType> {
-	info!("Starting: List secrets for a workspace");
-
-	// LOGIC
-
-	AppResponse::builder()
-		.body(ListSecretsForWorkspaceResponse { secrets: todo!() })
-		.headers(ListSecretsForWorkspaceResponseHeaders {
-			total_count: todo!(),
-		})
-		.status_code(StatusCode::OK)
-		.build()
-		.into_result()
-}
-
-async fn update_secret(
-	AuthenticatedAppRequest {
-		request: ProcessedApiRequest {
-			path,
-			query: _,
-			headers,
-			body,
-		},
-		database,
-		redis: _,
-		client_ip: _,
-		config,
-		user_data,
-	}: AuthenticatedAppRequest<'_, UpdateSecretRequest>,
-) -> Result<AppResponse<UpdateSecretRequest>, ErrorType> {
-	info!("Starting: Update secret");
-
-	// LOGIC
-
-	AppResponse::builder()
-		.body(UpdateSecretResponse)
-		.headers(())
-		.status_code(StatusCode::OK)
-		.build()
-		.into_result()
+/// Checks that the secret exists in the given workspace and has not been
+/// deleted
+async fn ensure_secret_exists(
+	connection: &mut DatabaseConnection,
+	workspace_id: Uuid,
+	secret_id: Uuid,
+) -> Result<(), ErrorType> {
+	query!(
+		r#"
+		SELECT
+			id
+		FROM
+			secret
+		WHERE
+			id = $1 AND
+			workspace_id = $2 AND
+			deleted IS NULL;
+		"#,
+		secret_id as _,
+		workspace_id as _,
+	)
+	.fetch_optional(&mut *connection)
+	.await?
+	.or_not_found()?;
+
+	Ok(())
 }
@@ -0,0 +1,78 @@
+use axum::http::StatusCode;
+use models::api::workspace::secret::*;
+
+use crate::{
+	prelude::*,
+	utils::secret_store::{AppSecretStore, SecretStore},
+};
+
+/// The handler to update a secret in the workspace. The name is updated in the
+/// database, and the value is updated in the configured secret store.
+pub async fn update_secret(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: UpdateSecretPath {
+					workspace_id,
+					secret_id,
+				},
+				query: (),
+				headers:
+					UpdateSecretRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: UpdateSecretRequestProcessed { name, value },
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config,
+		user_data: _,
+		clock: _,
+	}: AuthenticatedAppRequest<'_, UpdateSecretRequest>,
+) -> Result<AppResponse<UpdateSecretRequest>, ErrorType> {
+	info!("Updating secret `{secret_id}` in workspace `{workspace_id}`");
+
+	if name.is_none() && value.is_none() {
+		return Err(ErrorType::WrongParameters);
+	}
+
+	super::ensure_secret_exists(&mut **database, workspace_id, secret_id).await?;
+
+	if let Some(name) = name {
+		query!(
+			r#"
+			UPDATE
+				secret
+			SET
+				name = $1
+			WHERE
+				id = $2;
+			"#,
+			name.as_ref(),
+			secret_id as _,
+		)
+		.execute(&mut **database)
+		.await
+		.map_err(|err| match err {
+			sqlx::Error::Database(err) if err.is_unique_violation() => {
+				ErrorType::ResourceAlreadyExists
+			}
+			err => ErrorType::server_error(err),
+		})?;
+	}
+
+	if let Some(value) = value {
+		AppSecretStore::new(&config.secrets, &mut **database)
+			.put(workspace_id, secret_id, &value)
+			.await?;
+	}
+
+	AppResponse::builder()
+		.body(UpdateSecretResponse)
+		.headers(())
+		.status_code(StatusCode::ACCEPTED)
+		.build()
+		.into_result()
+}
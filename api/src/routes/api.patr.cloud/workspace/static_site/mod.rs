@@ -1,13 +1,14 @@
 use axum::{http::StatusCode, Router};
 use models::api::workspace::static_site::*;
 
-use crate::prelude::*;
+use crate::{prelude::*, routes::download, utils::signed_url};
 
 #[instrument(skip(state))]
 pub async fn setup_routes(state: &AppState) -> Router {
 	Router::new()
 		.mount_auth_endpoint(create_static_site, state)
 		.mount_auth_endpoint(delete_static_site, state)
+		.mount_auth_endpoint(get_static_site_asset_download_url, state)
 		.mount_auth_endpoint(get_static_site_info, state)
 		.mount_auth_endpoint(list_static_site, state)
 		.mount_auth_endpoint(list_upload_history, state)
@@ -31,6 +32,7 @@ async fn create_static_site(
 		client_ip: _,
 		config,
 		user_data,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, CreateStaticSiteRequest>,
 ) -> Result<AppResponse<CreateStaticSiteRequest>, ErrorType> {
 	info!("Starting: Create static site");
@@ -58,6 +60,7 @@ async fn delete_static_site(
 		client_ip: _,
 		config,
 		user_data,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, DeleteStaticSiteRequest>,
 ) -> Result<AppResponse<DeleteStaticSiteRequest>, ErrorType> {
 	info!("Starting: Delete static site");
@@ -72,6 +75,106 @@ async fn delete_static_site(
 		.into_result()
 }
 
+/// Issues a signed URL to download an asset of a static site, from the upload
+/// that is currently live unless a specific upload is requested. The URL isn't
+/// checked against the permissions of the user once it's issued.
+async fn get_static_site_asset_download_url(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path:
+					GetStaticSiteAssetDownloadUrlPath {
+						workspace_id: _,
+						static_site_id,
+					},
+				query: GetStaticSiteAssetDownloadUrlQuery { path, upload_id },
+				headers:
+					GetStaticSiteAssetDownloadUrlRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: GetStaticSiteAssetDownloadUrlRequestProcessed,
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config,
+		user_data: _,
+		clock,
+	}: AuthenticatedAppRequest<'_, GetStaticSiteAssetDownloadUrlRequest>,
+) -> Result<AppResponse<GetStaticSiteAssetDownloadUrlRequest>, ErrorType> {
+	info!("Issuing download URL for asset `{path}` of static site `{static_site_id}`");
+
+	if path.split('/').any(|segment| segment == "..") {
+		return Err(ErrorType::WrongParameters);
+	}
+
+	// The assets are downloaded from the bucket that the ingress serves them
+	// from, so a URL is only issued if the API can access it
+	if config.cloudflare.static_site_storage.is_none() {
+		return Err(ErrorType::server_error(
+			"Cannot issue download URL, since no static site storage is configured",
+		));
+	}
+
+	let live_upload = query!(
+		r#"
+		SELECT
+			current_live_upload
+		FROM
+			static_site
+		WHERE
+			id = $1 AND
+			deleted IS NULL;
+		"#,
+		static_site_id as _,
+	)
+	.fetch_optional(&mut **database)
+	.await?
+	.or_not_found()?
+	.current_live_upload;
+
+	let upload_id = match upload_id {
+		Some(upload_id) => {
+			query!(
+				r#"
+				SELECT
+					upload_id
+				FROM
+					static_site_upload_history
+				WHERE
+					upload_id = $1 AND
+					static_site_id = $2;
+				"#,
+				upload_id as _,
+				static_site_id as _,
+			)
+			.fetch_optional(&mut **database)
+			.await?
+			.or_not_found()?;
+			upload_id
+		}
+		// A static site that was never uploaded to has nothing to download
+		None => live_upload
+			.map(Into::into)
+			.ok_or(ErrorType::ResourceDoesNotExist)?,
+	};
+
+	let expires_at = clock.now() + config.security.signed_urls.expiry();
+	let url = signed_url::sign_path(
+		&config.security.signed_urls,
+		&download::static_site_asset_path(static_site_id, upload_id, &path),
+		expires_at,
+	)?;
+
+	AppResponse::builder()
+		.body(GetStaticSiteAssetDownloadUrlResponse { url, expires_at })
+		.headers(())
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
+
 async fn get_static_site_info(
 	AuthenticatedAppRequest {
 		request: ProcessedApiRequest {
@@ -85,6 +188,7 @@ async fn get_static_site_info(
 		client_ip: _,
 		config,
 		user_data,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, GetStaticSiteInfoRequest>,
 ) -> Result<AppResponse<GetStaticSiteInfoRequest>, ErrorType> {
 	info!("Starting: Get static site info");
@@ -115,6 +219,7 @@ async fn list_static_site(
 		client_ip: _,
 		config,
 		user_data,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, ListStaticSiteRequest>,
 ) -> Result<AppResponse<ListStaticSiteRequest>, ErrorType> {
 	info!("Starting: List static site");
@@ -146,6 +251,7 @@ async fn list_upload_history(
 		client_ip: _,
 		config,
 		user_data,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, ListStaticSiteUploadHistoryRequest>,
 ) -> Result<AppResponse<ListStaticSiteUploadHistoryRequest>, ErrorType> {
 	info!("Starting: List static site upload history");
@@ -175,6 +281,7 @@ async fn revert_static_site(
 		client_ip: _,
 		config,
 		user_data,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, RevertStaticSiteRequest>,
 ) -> Result<AppResponse<RevertStaticSiteRequest>, ErrorType> {
 	info!("Starting: Revert static site");
@@ -202,6 +309,7 @@ async fn start_static_site(
 		client_ip: _,
 		config,
 		user_data,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, StartStaticSiteRequest>,
 ) -> Result<AppResponse<StartStaticSiteRequest>, ErrorType> {
 	info!("Starting: Start static site");
@@ -229,6 +337,7 @@ async fn stop_static_site(
 		client_ip: _,
 		config,
 		user_data,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, StopStaticSiteRequest>,
 ) -> Result<AppResponse<StopStaticSiteRequest>, ErrorType> {
 	info!("Starting: Stop static site");
@@ -256,6 +365,7 @@ async fn update_static_site(
 		client_ip: _,
 		config,
 		user_data,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, UpdateStaticSiteRequest>,
 ) -> Result<AppResponse<UpdateStaticSiteRequest>, ErrorType> {
 	info!("Starting: Update static site");
@@ -283,6 +393,7 @@ async fn upload_static_site(
 		client_ip: _,
 		config,
 		user_data,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, UploadStaticSiteRequest>,
 ) -> Result<AppResponse<UploadStaticSiteRequest>, ErrorType> {
 	info!("Starting: Upload static site");
@@ -4,8 +4,11 @@ use models::api::workspace::*;
 use crate::prelude::*;
 
 /// The handler to update the information of a workspace. At the moment, only
-/// the name can be updated. However, this will be expanded in the future. At
-/// least one parameter must be provided for the update.
+/// the name, the maximum number of replicas of its deployments, whether it
+/// blocks deploying images with critical vulnerabilities and whether changes to
+/// its deployments need to be approved can be updated. However, this will be
+/// expanded in the future. At least one parameter must be provided for the
+/// update.
 pub async fn update_workspace_info(
 	AuthenticatedAppRequest {
 		request:
@@ -17,22 +20,55 @@ pub async fn update_workspace_info(
 						authorization,
 						user_agent,
 					},
-				body: UpdateWorkspaceInfoRequestProcessed { name },
+				body:
+					UpdateWorkspaceInfoRequestProcessed {
+						name,
+						max_replicas,
+						block_critical_vulnerabilities,
+						require_deployment_change_approval,
+					},
 			},
 		database,
 		redis,
 		client_ip,
 		config,
 		user_data,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, UpdateWorkspaceInfoRequest>,
 ) -> Result<AppResponse<UpdateWorkspaceInfoRequest>, ErrorType> {
 	info!("Updating information for workspace `{workspace_id}`");
 
 	// If more parameters are added, add them here
-	if name.is_none() {
+	if name.is_none() &&
+		max_replicas.is_none() &&
+		block_critical_vulnerabilities.is_none() &&
+		require_deployment_change_approval.is_none()
+	{
 		return Err(ErrorType::WrongParameters);
 	}
 
+	// A workspace can lower its own limit, but never raise it. The limit is
+	// otherwise only changed by the administrators of the instance
+	let max_replicas = match max_replicas {
+		Some(max_replicas) => {
+			let current_max_replicas = super::deployment::get_workspace_max_replicas(
+				&mut **database,
+				&config,
+				workspace_id,
+			)
+			.await?;
+			if max_replicas > current_max_replicas {
+				return Err(ErrorType::ReplicaLimitExceeded(current_max_replicas));
+			}
+
+			Some(
+				i16::try_from(max_replicas)
+					.map_err(|_| ErrorType::ReplicaLimitExceeded(current_max_replicas))?,
+			)
+		}
+		None => None,
+	};
+
 	if let Some(ref name) = name {
 		let available = super::is_name_available(AuthenticatedAppRequest {
 			request: ProcessedApiRequest {
@@ -66,11 +102,17 @@ pub async fn update_workspace_info(
         UPDATE
             workspace
         SET
-            name = COALESCE($1, name)
+            name = COALESCE($1, name),
+            max_replicas = COALESCE($2, max_replicas),
+            block_critical_vulnerabilities = COALESCE($3, block_critical_vulnerabilities),
+            require_deployment_change_approval = COALESCE($4, require_deployment_change_approval)
 		WHERE
-			id = $2;
+			id = $5;
         "#,
 		name.as_deref(),
+		max_replicas,
+		block_critical_vulnerabilities,
+		require_deployment_change_approval,
 		&workspace_id as _,
 	)
 	.execute(&mut **database)
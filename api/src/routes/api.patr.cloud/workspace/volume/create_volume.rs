@@ -22,6 +22,7 @@ pub async fn create_volume(
 		client_ip: _,
 		config: _,
 		user_data: _,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, CreateVolumeRequest>,
 ) -> Result<AppResponse<CreateVolumeRequest>, ErrorType> {
 	trace!("Creating volume with name: {name}");
@@ -24,10 +24,33 @@ pub async fn delete_volume(
 		client_ip: _,
 		config: _,
 		user_data: _,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, DeleteVolumeRequest>,
 ) -> Result<AppResponse<DeleteVolumeRequest>, ErrorType> {
 	trace!("Deleting volume ID: `{volume_id}`");
 
+	// A volume that is still mounted by a deployment cannot be deleted
+	let is_mounted = query!(
+		r#"
+		SELECT
+			deployment_id
+		FROM
+			deployment_volume_mount
+		WHERE
+			volume_id = $1
+		LIMIT 1;
+		"#,
+		volume_id as _
+	)
+	.fetch_optional(&mut **database)
+	.await?
+	.is_some();
+
+	if is_mounted {
+		debug!("Volume `{volume_id}` is still mounted by a deployment");
+		return Err(ErrorType::ResourceInUse);
+	}
+
 	query!(
 		r#"
 		DELETE FROM
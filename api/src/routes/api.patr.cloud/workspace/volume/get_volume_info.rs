@@ -24,6 +24,7 @@ pub async fn get_volume_info(
 		client_ip: _,
 		config: _,
 		user_data: _,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, GetVolumeInfoRequest>,
 ) -> Result<AppResponse<GetVolumeInfoRequest>, ErrorType> {
 	trace!("Getting volume info: {}", volume_id);
@@ -46,7 +47,7 @@ pub async fn get_volume_info(
 	)
 	.fetch_optional(&mut **database)
 	.await?
-	.ok_or(ErrorType::ResourceDoesNotExist)?;
+	.or_not_found()?;
 
 	AppResponse::builder()
 		.body(GetVolumeInfoResponse {
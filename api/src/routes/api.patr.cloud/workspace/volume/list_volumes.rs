@@ -25,6 +25,7 @@ pub async fn list_volumes(
 		client_ip: _,
 		config: _,
 		user_data: _,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, ListVolumesInWorkspaceRequest>,
 ) -> Result<AppResponse<ListVolumesInWorkspaceRequest>, ErrorType> {
 	trace!("Listing volumes in workspace ID: `{workspace_id}`");
@@ -23,6 +23,7 @@ pub async fn update_volume(
 		client_ip,
 		config,
 		user_data,
+		clock: _,
 	}: AuthenticatedAppRequest<'_, UpdateVolumeRequest>,
 ) -> Result<AppResponse<UpdateVolumeRequest>, ErrorType> {
 	let volume = super::get_volume_info(AuthenticatedAppRequest {
@@ -1,54 +1,232 @@
-use axum::Router;
+use std::convert::Infallible;
+
+use axum::{
+	body::Body,
+	http::{header, HeaderValue, Request, StatusCode},
+	middleware,
+	response::Response,
+	Router,
+};
 use leptos_axum::LeptosRoutes;
 use tokio::fs;
-use tower_http::services::ServeFile;
+use tower::{service_fn, ServiceExt};
+use tower_http::services::ServeDir;
 
-use crate::prelude::*;
+use crate::{prelude::*, utils::config::RunningEnvironment};
 
-/// Sets up the routes for the web dashboard
+/// Sets up the routes for the web dashboard. Fails if the leptos configuration
+/// cannot be loaded or the site root cannot be read. In development, a missing
+/// site root is only warned about, and no routes are set up for the dashboard.
 #[instrument(skip(state))]
-pub async fn setup_routes(state: &AppState) -> Router {
-	let config = leptos::get_configuration(
-		if option_env!("LEPTOS_OUTPUT_NAME").is_some() {
-			None
-		} else {
-			Some(concat!(env!("CARGO_MANIFEST_DIR"), "/../Cargo.toml"))
-		},
-	)
-	.await
-	.expect("failed to get configuration");
-
-	read_files(&config.leptos_options.site_root)
+pub async fn setup_routes(state: &AppState) -> Result<Router, anyhow::Error> {
+	let config_file = if option_env!("LEPTOS_OUTPUT_NAME").is_some() {
+		None
+	} else {
+		Some(concat!(env!("CARGO_MANIFEST_DIR"), "/../Cargo.toml"))
+	};
+	let config = leptos::get_configuration(config_file)
 		.await
-		.into_iter()
-		.fold(Router::new(), |router, file| {
-			router.route_service(
-				file.trim_start_matches(config.leptos_options.site_root.as_str()),
-				ServeFile::new(file.as_str()),
+		.with_context(|| {
+			format!(
+				"failed to get the leptos configuration from `{}`",
+				config_file.unwrap_or("the environment")
 			)
-		})
+		})?;
+
+	let site_root = config.leptos_options.site_root.as_str();
+	if state.config.environment == RunningEnvironment::Development &&
+		!fs::try_exists(site_root).await.unwrap_or(false)
+	{
+		warn!(
+			"Site root `{}` does not exist. The dashboard will not be served",
+			site_root
+		);
+		return Ok(Router::new());
+	}
+
+	let metadata = fs::metadata(site_root)
+		.await
+		.with_context(|| format!("failed to read the site root `{}`", site_root))?;
+	if !metadata.is_dir() {
+		anyhow::bail!("the site root `{}` is not a directory", site_root);
+	}
+
+	Ok(Router::new()
 		.leptos_routes(
 			&config.leptos_options,
 			leptos_axum::generate_route_list(frontend::render),
 			frontend::render,
 		)
-		.with_state(config.leptos_options)
-		.with_state(state.clone())
+		.fallback_service(static_files_router(site_root))
+		.layer(middleware::map_response(revalidate_by_default))
+		.with_state(config.leptos_options.clone())
+		.with_state(state.clone()))
 }
 
-/// Reads all files in a directory and its subdirectories
-async fn read_files(path: &str) -> Vec<String> {
-	let mut files = Vec::new();
-	let mut read_dir = fs::read_dir(path)
-		.await
-		.unwrap_or_else(|_| panic!("failed to read directory: `{}`", path));
-	while let Some(entry) = read_dir.next_entry().await.expect("failed to read entry") {
-		let path = entry.path();
-		if path.is_dir() {
-			files.extend(Box::pin(read_files(path.to_str().unwrap())).await);
-		} else {
-			files.push(path.to_str().unwrap().to_string());
+/// Sets the `Cache-Control` header of a response to `no-cache` if it doesn't
+/// have one already. The HTML shell rendered by leptos doesn't set one by
+/// itself, and must be revalidated on every request just like the shell
+/// served from the site root, so that a new release is picked up right away.
+async fn revalidate_by_default(mut response: Response) -> Response {
+	response
+		.headers_mut()
+		.entry(header::CACHE_CONTROL)
+		.or_insert(HeaderValue::from_static("no-cache"));
+	response
+}
+
+/// A router that serves the static files of the dashboard from the site root,
+/// resolving the file for each request as it comes in, along with the
+/// `Cache-Control` header for the file (see [`cache_control_for`]).
+fn static_files_router(site_root: &str) -> Router {
+	let serve_dir = ServeDir::new(site_root);
+
+	Router::new().fallback_service(service_fn(move |request: Request<Body>| {
+		let cache_control = cache_control_for(request.uri().path());
+		let serve_dir = serve_dir.clone();
+
+		async move {
+			let mut response = serve_dir.oneshot(request).await?;
+			if response.status().is_success() || response.status() == StatusCode::NOT_MODIFIED {
+				response
+					.headers_mut()
+					.insert(header::CACHE_CONTROL, cache_control);
+			}
+			Ok::<_, Infallible>(response)
 		}
+	}))
+}
+
+/// Returns the `Cache-Control` header to serve a static file with. Files with
+/// a content hash in their name (see [`is_fingerprinted`]) never change, and
+/// are cached forever. Everything else, including the HTML shell, is
+/// revalidated on every request, so that a new release is picked up right
+/// away.
+fn cache_control_for(path: &str) -> HeaderValue {
+	if is_fingerprinted(path) {
+		HeaderValue::from_static("public, max-age=31536000, immutable")
+	} else {
+		HeaderValue::from_static("no-cache")
+	}
+}
+
+/// Checks if the name of a file contains a content hash, such as
+/// `dashboard.3f9a2c1b7d.js`. The hash is any part of the name (between dots,
+/// excluding the first part and the extension) that is at least 8 characters
+/// long, made up only of letters, digits, `-` or `_`, and has at least one
+/// digit in it. HTML files are never considered fingerprinted.
+fn is_fingerprinted(path: &str) -> bool {
+	let file_name = path.rsplit('/').next().unwrap_or(path);
+	let parts = file_name.split('.').collect::<Vec<_>>();
+
+	if parts.len() < 3 || parts.last().is_some_and(|extension| *extension == "html") {
+		return false;
+	}
+
+	parts[1..parts.len() - 1].iter().any(|part| {
+		part.len() >= 8 &&
+			part.chars()
+				.all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') &&
+			part.chars().any(|c| c.is_ascii_digit())
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn fingerprinted_assets_are_immutable() {
+		assert_eq!(
+			cache_control_for("/pkg/dashboard.3f9a2c1b7d.js"),
+			"public, max-age=31536000, immutable"
+		);
+		assert_eq!(
+			cache_control_for("/pkg/dashboard.a1B2c3D4_e5.wasm"),
+			"public, max-age=31536000, immutable"
+		);
+	}
+
+	#[test]
+	fn other_files_are_revalidated() {
+		assert_eq!(cache_control_for("/index.html"), "no-cache");
+		assert_eq!(cache_control_for("/pkg/dashboard.js"), "no-cache");
+		assert_eq!(cache_control_for("/pkg/dashboard.min.css"), "no-cache");
+		assert_eq!(cache_control_for("/404.3f9a2c1b7d.html"), "no-cache");
+	}
+
+	#[tokio::test]
+	async fn resolves_files_in_nested_directories() {
+		let site_root = std::env::temp_dir().join(format!("patr-site-root-{}", Uuid::new_v4()));
+		let nested = site_root.join("pkg/fonts/inter");
+		fs::create_dir_all(&nested).await.unwrap();
+		fs::write(nested.join("inter.3f9a2c1b7d.woff2"), "font")
+			.await
+			.unwrap();
+
+		let router = static_files_router(site_root.to_str().unwrap());
+		let response = router
+			.clone()
+			.oneshot(
+				Request::get("/pkg/fonts/inter/inter.3f9a2c1b7d.woff2")
+					.body(Body::empty())
+					.unwrap(),
+			)
+			.await
+			.unwrap();
+		assert_eq!(response.status(), StatusCode::OK);
+		assert_eq!(
+			response.headers()[header::CACHE_CONTROL],
+			"public, max-age=31536000, immutable"
+		);
+
+		let response = router
+			.oneshot(
+				Request::get("/pkg/fonts/missing.woff2")
+					.body(Body::empty())
+					.unwrap(),
+			)
+			.await
+			.unwrap();
+		assert_eq!(response.status(), StatusCode::NOT_FOUND);
+		assert!(response.headers().get(header::CACHE_CONTROL).is_none());
+
+		fs::remove_dir_all(&site_root).await.unwrap();
+	}
+
+	#[tokio::test]
+	async fn rendered_pages_are_revalidated() {
+		let router = Router::new()
+			.route("/login", axum::routing::get(|| async { "<html></html>" }))
+			.route(
+				"/pkg/dashboard.3f9a2c1b7d.js",
+				axum::routing::get(|| async {
+					(
+						[(header::CACHE_CONTROL, "public, max-age=31536000, immutable")],
+						"",
+					)
+				}),
+			)
+			.layer(middleware::map_response(revalidate_by_default));
+
+		let response = router
+			.clone()
+			.oneshot(Request::get("/login").body(Body::empty()).unwrap())
+			.await
+			.unwrap();
+		assert_eq!(response.headers()[header::CACHE_CONTROL], "no-cache");
+
+		let response = router
+			.oneshot(
+				Request::get("/pkg/dashboard.3f9a2c1b7d.js")
+					.body(Body::empty())
+					.unwrap(),
+			)
+			.await
+			.unwrap();
+		assert_eq!(
+			response.headers()[header::CACHE_CONTROL],
+			"public, max-age=31536000, immutable"
+		);
 	}
-	files
 }
@@ -0,0 +1,286 @@
+use axum::{
+	body::Body,
+	extract::{Path, Query, Request, State},
+	http::{header, HeaderValue, Uri},
+	middleware::{self, Next},
+	response::{IntoResponse, Response},
+	routing::get,
+	Router,
+};
+use s3::Bucket;
+use serde::Deserialize;
+use time::OffsetDateTime;
+
+use crate::{
+	prelude::*,
+	utils::{
+		config::{S3Config, SignedUrlConfig},
+		signed_url,
+	},
+};
+
+/// The route that the blobs of the container registry are downloaded from
+const CONTAINER_REGISTRY_BLOB_ROUTE: &str = "/download/container-registry/blob/:digest";
+
+/// The route that the assets of the uploads of static sites are downloaded
+/// from
+const STATIC_SITE_ASSET_ROUTE: &str = "/download/static-site/:static_site_id/:upload_id/*asset";
+
+/// The query params of a signed URL, which are checked by [`verify_signature`]
+/// before the download is served
+#[derive(Debug, Clone, Deserialize)]
+struct SignatureQuery {
+	/// The time (as a unix timestamp) after which the URL is no longer valid
+	expires: i64,
+	/// The signature of the path and the expiry of the URL
+	signature: String,
+}
+
+/// The path that a blob of the container registry is downloaded from
+pub fn container_registry_blob_path(digest: &str) -> String {
+	format!(
+		"/download/container-registry/blob/{}",
+		signed_url::encode_path(digest)
+	)
+}
+
+/// The path that an asset of an upload of a static site is downloaded from
+pub fn static_site_asset_path(static_site_id: Uuid, upload_id: Uuid, asset: &str) -> String {
+	format!(
+		"/download/static-site/{static_site_id}/{upload_id}/{}",
+		signed_url::encode_path(asset.trim_start_matches('/'))
+	)
+}
+
+/// Sets up the routes that files are downloaded from using signed URLs. These
+/// don't require a token, since the permissions of the user are checked when
+/// the URL is issued. Instead, the signature of every request is verified
+/// before it reaches the handler.
+#[instrument(skip(state))]
+pub fn setup_routes(state: &AppState) -> Router {
+	Router::new()
+		.route(
+			CONTAINER_REGISTRY_BLOB_ROUTE,
+			get(download_container_registry_blob),
+		)
+		.route(STATIC_SITE_ASSET_ROUTE, get(download_static_site_asset))
+		.route_layer(middleware::from_fn_with_state(
+			state.clone(),
+			verify_signature,
+		))
+		.with_state(state.clone())
+}
+
+/// The middleware that verifies the signature of a signed URL. Requests
+/// without a signature, with an invalid signature, or with an expired one are
+/// rejected with [`ErrorType::InvalidSignature`].
+async fn verify_signature(State(state): State<AppState>, request: Request, next: Next) -> Response {
+	let now = state.clock.now();
+	if let Err(error) = check_signature(&state.config.security.signed_urls, request.uri(), now) {
+		return error.into_response();
+	}
+
+	next.run(request).await
+}
+
+/// Checks the signature of a signed URL, using the expiry and the signature in
+/// its query params
+fn check_signature(
+	config: &SignedUrlConfig,
+	uri: &Uri,
+	now: OffsetDateTime,
+) -> Result<(), ErrorType> {
+	let Ok(Query(SignatureQuery { expires, signature })) = Query::try_from_uri(uri) else {
+		debug!("Download requested without a signature");
+		return Err(ErrorType::InvalidSignature);
+	};
+
+	signed_url::verify_path(config, uri.path(), expires, &signature, now)
+		.inspect_err(|_| debug!("Invalid signature for `{}`", uri.path()))
+}
+
+/// Downloads a blob (such as an image layer) of the container registry,
+/// streamed from S3
+async fn download_container_registry_blob(
+	State(state): State<AppState>,
+	Path(digest): Path<String>,
+) -> Result<Response, ErrorType> {
+	info!("Downloading blob `{digest}` using a signed URL");
+
+	// The same key that the registry stores the blobs at
+	stream_object(&state.config.s3, &format!("registry/blobs/{digest}")).await
+}
+
+/// Downloads an asset of an upload of a static site, streamed from the R2
+/// bucket that the ingress serves static sites from
+async fn download_static_site_asset(
+	State(state): State<AppState>,
+	Path((static_site_id, upload_id, asset)): Path<(Uuid, Uuid, String)>,
+) -> Result<Response, ErrorType> {
+	info!("Downloading asset `{asset}` of static site `{static_site_id}` using a signed URL");
+
+	let Some(storage) = &state.config.cloudflare.static_site_storage else {
+		return Err(ErrorType::server_error(
+			"Cannot download static site asset, since no static site storage is configured",
+		));
+	};
+
+	// The same layout that the ingress serves static sites from
+	stream_object(storage, &format!("{static_site_id}/{upload_id}/{asset}")).await
+}
+
+/// Streams an object from S3 as the response, without buffering it in memory
+async fn stream_object(config: &S3Config, key: &str) -> Result<Response, ErrorType> {
+	let bucket = Bucket::new(
+		&config.bucket,
+		s3::Region::Custom {
+			region: config.region.clone(),
+			endpoint: config.endpoint.clone(),
+		},
+		s3::creds::Credentials::new(Some(&config.key), Some(&config.secret), None, None, None)?,
+	)?;
+
+	let (head, status_code) = bucket.head_object(key).await?;
+	if status_code == 404 {
+		return Err(ErrorType::ResourceDoesNotExist);
+	}
+
+	let object = bucket.get_object_stream(key).await?;
+	if !(200..300).contains(&object.status_code) {
+		return Err(ErrorType::server_error(format!(
+			"S3 returned status code {} for `{key}`",
+			object.status_code
+		)));
+	}
+
+	let mut response = Body::from_stream(object.bytes).into_response();
+	let headers = response.headers_mut();
+	for (name, value) in [
+		(header::CONTENT_TYPE, head.content_type),
+		(
+			header::CONTENT_LENGTH,
+			head.content_length.map(|length| length.to_string()),
+		),
+		(header::ETAG, head.e_tag),
+	] {
+		if let Some(value) = value.and_then(|value| HeaderValue::from_str(&value).ok()) {
+			headers.insert(name, value);
+		}
+	}
+
+	Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+	use axum::{body, http::StatusCode, routing::get, Router};
+	use time::Duration;
+	use tower::ServiceExt;
+
+	use super::*;
+
+	/// A router with the same routes and the same signature check as the one
+	/// that serves the downloads, which returns the params of the route that
+	/// was matched instead of downloading anything
+	fn router(config: SignedUrlConfig, now: OffsetDateTime) -> Router {
+		Router::new()
+			.route(
+				CONTAINER_REGISTRY_BLOB_ROUTE,
+				get(|Path(digest): Path<String>| async move { digest }),
+			)
+			.route(STATIC_SITE_ASSET_ROUTE, get(static_site_asset))
+			.route_layer(middleware::from_fn(move |request: Request, next: Next| {
+				let config = config.clone();
+				async move {
+					match check_signature(&config, request.uri(), now) {
+						Ok(()) => next.run(request).await,
+						Err(error) => error.into_response(),
+					}
+				}
+			}))
+	}
+
+	/// Returns the params of the route that static site assets are downloaded
+	/// from
+	async fn static_site_asset(
+		Path((static_site_id, upload_id, asset)): Path<(Uuid, Uuid, String)>,
+	) -> String {
+		format!("{static_site_id}/{upload_id}/{asset}")
+	}
+
+	/// Requests the given URL, returning the status code and the body
+	async fn download(url: &str, now: OffsetDateTime) -> (StatusCode, String) {
+		let config = SignedUrlConfig {
+			secret: Some("secret".to_string()),
+			..Default::default()
+		};
+		let response = router(config, now)
+			.oneshot(Request::get(url).body(Body::empty()).unwrap())
+			.await
+			.unwrap();
+		let status = response.status();
+		let body = body::to_bytes(response.into_body(), usize::MAX)
+			.await
+			.unwrap();
+
+		(status, String::from_utf8(body.to_vec()).unwrap())
+	}
+
+	/// Signs the given path with the secret that [`download`] verifies with
+	fn sign(path: &str, expires_at: OffsetDateTime) -> String {
+		let config = SignedUrlConfig {
+			secret: Some("secret".to_string()),
+			..Default::default()
+		};
+
+		signed_url::sign_path(&config, path, expires_at).unwrap()
+	}
+
+	#[tokio::test]
+	async fn signed_urls_reach_the_download() {
+		let now = OffsetDateTime::now_utc();
+		let expires_at = now + Duration::minutes(5);
+
+		let url = sign(&container_registry_blob_path("sha256:abc"), expires_at);
+		assert_eq!(
+			download(&url, now).await,
+			(StatusCode::OK, "sha256:abc".to_string())
+		);
+
+		let (static_site_id, upload_id) = (Uuid::new_v4(), Uuid::new_v4());
+		let url = sign(
+			&static_site_asset_path(static_site_id, upload_id, "/assets/my logo.png"),
+			expires_at,
+		);
+		assert_eq!(
+			download(&url, now).await,
+			(
+				StatusCode::OK,
+				format!("{static_site_id}/{upload_id}/assets/my logo.png")
+			)
+		);
+	}
+
+	#[tokio::test]
+	async fn unsigned_expired_and_tampered_urls_are_forbidden() {
+		let now = OffsetDateTime::now_utc();
+		let path = container_registry_blob_path("sha256:abc");
+		let url = sign(&path, now + Duration::minutes(5));
+
+		for url in [
+			path.clone(),
+			format!("{path}?expires={}", now.unix_timestamp() + 60),
+			url.replacen("abc", "def", 1),
+			url.replace("expires=", "expires=1"),
+			sign(&path, now - Duration::seconds(1)),
+		] {
+			let (status, body) = download(&url, now).await;
+			assert_eq!(status, StatusCode::FORBIDDEN, "{url}");
+			assert!(body.contains("invalidSignature"), "{url}: {body}");
+		}
+
+		// The same URL is rejected once it has expired
+		let (status, _) = download(&url, now + Duration::minutes(5)).await;
+		assert_eq!(status, StatusCode::FORBIDDEN);
+	}
+}
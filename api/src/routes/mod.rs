@@ -17,18 +17,27 @@ pub mod api_patr_cloud;
 #[path = "app.patr.cloud/mod.rs"]
 pub mod app_patr_cloud;
 
+/// The routes that files are downloaded from using signed URLs
+mod download;
+/// The endpoint that serves the OpenAPI document of the API
+mod openapi;
+/// The health endpoint, and the readiness endpoint, which checks the
+/// dependencies of the API
+mod ready;
+
 // /// The routes for serving https://registry.patr.cloud as a docker registry
 // #[path = "registry.patr.cloud/mod.rs"]
 // mod registry_patr_cloud;
 
-/// Sets up the routes for the API, across all domains.
+/// Sets up the routes for the API, across all domains. Fails if the routes for
+/// any of the domains could not be set up.
 #[instrument(skip(state))]
-pub async fn setup_routes(state: &AppState) -> Router {
+pub async fn setup_routes(state: &AppState) -> Result<Router, anyhow::Error> {
 	let api_router = api_patr_cloud::setup_routes(state).await;
-	let app_router = app_patr_cloud::setup_routes(state).await;
+	let app_router = app_patr_cloud::setup_routes(state).await?;
 	// let registry_router = registry_patr_cloud::setup_routes(state).await;
 
-	Router::new()
+	Ok(Router::new()
 		.fallback(any(|Host(hostname), request: Request<Body>| async move {
 			match hostname.as_str() {
 				"api.patr.cloud" => api_router.oneshot(request).await,
@@ -41,4 +50,9 @@ pub async fn setup_routes(state: &AppState) -> Router {
 			}
 		}))
 		.with_state(state.clone())
+		// The health and readiness endpoints are served on any host, so that
+		// they can be used by probes that reach the server using its IP
+		// address. They are never shed, since they aren't behind the API's
+		// layers
+		.merge(ready::setup_routes(state)))
 }
@@ -0,0 +1,219 @@
+use axum::{routing::get, Json, Router};
+use models::{
+	api::{
+		auth::oauth::*,
+		user::*,
+		workspace::{
+			container_registry::*,
+			database::*,
+			deployment::{
+				alert_rule::*,
+				build::*,
+				change_request::*,
+				deploy_history::*,
+				error_page::*,
+				image_scan::*,
+				schedule::*,
+				template::*,
+				*,
+			},
+			domain::*,
+			egress_policy::*,
+			managed_url::*,
+			rbac::{role::*, user::*, *},
+			runner::*,
+			secret::*,
+			static_site::*,
+			volume::*,
+			*,
+		},
+	},
+	utils::OpenApiDocument,
+};
+
+use crate::prelude::*;
+
+/// Sets up the endpoint that serves the OpenAPI document of the API. This
+/// doesn't require authentication, so that it can be used by any client. The
+/// document is generated once, when the routes are set up.
+#[instrument(skip(state))]
+pub fn setup_routes(state: &AppState) -> Router {
+	let document = Json(openapi_document().to_json(env!("CARGO_PKG_VERSION")));
+
+	Router::new()
+		.route(
+			"/openapi.json",
+			get(move || {
+				let document = document.clone();
+				async move { document }
+			}),
+		)
+		.with_state(state.clone())
+}
+
+/// The OpenAPI document of the API, describing every endpoint that can be
+/// accessed by the API. Endpoints that are only accessible by the Web UI are
+/// left out by [`OpenApiDocument::add_endpoint`], so any endpoint that is
+/// mounted on the API can be added here.
+fn openapi_document() -> OpenApiDocument {
+	let mut document = OpenApiDocument::default();
+	document
+		.add_endpoint::<OAuthAuthorizeRequest>()
+		.add_endpoint::<OAuthIntrospectRequest>()
+		.add_endpoint::<OAuthRevokeTokenRequest>()
+		.add_endpoint::<OAuthTokenRequest>()
+		.add_endpoint::<GetUserDetailsRequest>()
+		.add_endpoint::<GetUserInfoRequest>()
+		.add_endpoint::<ListUserWorkspacesRequest>()
+		.add_endpoint::<ActivateMfaRequest>()
+		.add_endpoint::<DisableTotpRequest>()
+		.add_endpoint::<EnableTotpRequest>()
+		.add_endpoint::<CreateWorkspaceRequest>()
+		.add_endpoint::<DeleteWorkspaceRequest>()
+		.add_endpoint::<GetApiUsageRequest>()
+		.add_endpoint::<GetFeatureFlagsRequest>()
+		.add_endpoint::<GetWorkspaceInfoRequest>()
+		.add_endpoint::<IsWorkspaceNameAvailableRequest>()
+		.add_endpoint::<ListWorkspaceActivityRequest>()
+		.add_endpoint::<PollWorkspaceUpdatesRequest>()
+		.add_endpoint::<SearchWorkspaceResourcesRequest>()
+		.add_endpoint::<UpdateWorkspaceInfoRequest>()
+		.add_endpoint::<CreateContainerRepositoryRequest>()
+		.add_endpoint::<DeleteContainerRepositoryImageRequest>()
+		.add_endpoint::<DeleteContainerRepositoryRequest>()
+		.add_endpoint::<GetContainerRepositoryBlobDownloadUrlRequest>()
+		.add_endpoint::<GetContainerRepositoryExposedPortsRequest>()
+		.add_endpoint::<GetContainerRepositoryImageDetailsRequest>()
+		.add_endpoint::<GetContainerRepositoryInfoRequest>()
+		.add_endpoint::<ListContainerRepositoriesRequest>()
+		.add_endpoint::<ListContainerRepositoryTagsRequest>()
+		.add_endpoint::<CreateDatabaseRequest>()
+		.add_endpoint::<DeleteDatabaseRequest>()
+		.add_endpoint::<GetDatabaseConnectionRequest>()
+		.add_endpoint::<GetDatabaseRequest>()
+		.add_endpoint::<ListAllDatabaseMachineTypeRequest>()
+		.add_endpoint::<ListDatabaseRequest>()
+		.add_endpoint::<RotateDatabaseCredentialsRequest>()
+		.add_endpoint::<AbortDeploymentCanaryRequest>()
+		.add_endpoint::<BatchGetDeploymentStatusRequest>()
+		.add_endpoint::<CreateDeploymentRequest>()
+		.add_endpoint::<DeleteDeploymentRequest>()
+		.add_endpoint::<DownloadDeploymentLogsRequest>()
+		.add_endpoint::<GetDefaultDeploymentMachineTypeRequest>()
+		.add_endpoint::<GetDeploymentAccessLogsRequest>()
+		.add_endpoint::<GetDeploymentInfoRequest>()
+		.add_endpoint::<GetDeploymentLogsRequest>()
+		.add_endpoint::<GetDeploymentMetricRequest>()
+		.add_endpoint::<GetEffectiveDeploymentConfigRequest>()
+		.add_endpoint::<ListAllDeploymentMachineTypeRequest>()
+		.add_endpoint::<ListAllDeploymentsRequest>()
+		.add_endpoint::<ListDeletedDeploymentsRequest>()
+		.add_endpoint::<ListDeploymentReplicasRequest>()
+		.add_endpoint::<ListDeploymentRequest>()
+		.add_endpoint::<PromoteDeploymentCanaryRequest>()
+		.add_endpoint::<PromoteDeploymentRequest>()
+		.add_endpoint::<ReconcileDeploymentRequest>()
+		.add_endpoint::<ReportDeploymentAccessLogsRequest>()
+		.add_endpoint::<ReportDeploymentActivityRequest>()
+		.add_endpoint::<ReportDeploymentReadinessRequest>()
+		.add_endpoint::<ReportDeploymentReconciliationRequest>()
+		.add_endpoint::<RestoreDeploymentRequest>()
+		.add_endpoint::<SetDefaultDeploymentMachineTypeRequest>()
+		.add_endpoint::<SetDeploymentCanaryRequest>()
+		.add_endpoint::<StartDeploymentRequest>()
+		.add_endpoint::<StopDeploymentRequest>()
+		.add_endpoint::<StreamDeploymentLogsRequest>()
+		.add_endpoint::<TestDeploymentPortRequest>()
+		.add_endpoint::<UpdateDeploymentRequest>()
+		.add_endpoint::<ValidateDeploymentConfigRequest>()
+		.add_endpoint::<CreateDeploymentAlertRuleRequest>()
+		.add_endpoint::<DeleteDeploymentAlertRuleRequest>()
+		.add_endpoint::<ListDeploymentAlertRulesRequest>()
+		.add_endpoint::<UpdateDeploymentAlertRuleRequest>()
+		.add_endpoint::<SetDeploymentBuildSourceRequest>()
+		.add_endpoint::<StartDeploymentBuildRequest>()
+		.add_endpoint::<StreamDeploymentBuildLogsRequest>()
+		.add_endpoint::<UpdateDeploymentBuildRequest>()
+		.add_endpoint::<ListDeploymentChangeRequestsRequest>()
+		.add_endpoint::<ReviewDeploymentChangeRequest>()
+		.add_endpoint::<DeleteDeploymentDeployHistoryRequest>()
+		.add_endpoint::<ListDeploymentDeployHistoryRequest>()
+		.add_endpoint::<GetDeploymentErrorPagesRequest>()
+		.add_endpoint::<GetIngressErrorPagesRequest>()
+		.add_endpoint::<GetWorkspaceDeploymentErrorPagesRequest>()
+		.add_endpoint::<SetDeploymentErrorPagesRequest>()
+		.add_endpoint::<SetWorkspaceDeploymentErrorPagesRequest>()
+		.add_endpoint::<GetDeploymentImageScanRequest>()
+		.add_endpoint::<ScanDeploymentImageRequest>()
+		.add_endpoint::<CreateDeploymentScheduleRequest>()
+		.add_endpoint::<DeleteDeploymentScheduleRequest>()
+		.add_endpoint::<ListDeploymentSchedulesRequest>()
+		.add_endpoint::<UpdateDeploymentScheduleRequest>()
+		.add_endpoint::<CreateDeploymentTemplateRequest>()
+		.add_endpoint::<DeleteDeploymentTemplateRequest>()
+		.add_endpoint::<GetDeploymentTemplateInfoRequest>()
+		.add_endpoint::<ListDeploymentTemplatesRequest>()
+		.add_endpoint::<UpdateDeploymentTemplateRequest>()
+		.add_endpoint::<AddDNSRecordRequest>()
+		.add_endpoint::<AddDomainToWorkspaceRequest>()
+		.add_endpoint::<DeleteDNSRecordRequest>()
+		.add_endpoint::<DeleteDomainInWorkspaceRequest>()
+		.add_endpoint::<GetDomainDNSRecordRequest>()
+		.add_endpoint::<GetDomainInfoInWorkspaceRequest>()
+		.add_endpoint::<GetDomainsForWorkspaceRequest>()
+		.add_endpoint::<IsDomainPersonalRequest>()
+		.add_endpoint::<UpdateDomainDNSRecordRequest>()
+		.add_endpoint::<VerifyDomainInWorkspaceRequest>()
+		.add_endpoint::<DeleteEgressPolicyRequest>()
+		.add_endpoint::<GetEgressPolicyRequest>()
+		.add_endpoint::<SetEgressPolicyRequest>()
+		.add_endpoint::<CreateManagedURLRequest>()
+		.add_endpoint::<DeleteManagedURLRequest>()
+		.add_endpoint::<ListManagedURLRequest>()
+		.add_endpoint::<UpdateManagedURLRequest>()
+		.add_endpoint::<VerifyManagedURLConfigurationRequest>()
+		.add_endpoint::<CheckPermissionsRequest>()
+		.add_endpoint::<GetCurrentPermissionsRequest>()
+		.add_endpoint::<ListAllPermissionsRequest>()
+		.add_endpoint::<ListAllResourceTypesRequest>()
+		.add_endpoint::<CreateNewRoleRequest>()
+		.add_endpoint::<DeleteRoleRequest>()
+		.add_endpoint::<ExportWorkspaceRolesRequest>()
+		.add_endpoint::<GetRoleInfoRequest>()
+		.add_endpoint::<ImportWorkspaceRolesRequest>()
+		.add_endpoint::<ListAllRolesRequest>()
+		.add_endpoint::<ListUsersForRoleRequest>()
+		.add_endpoint::<UpdateRoleRequest>()
+		.add_endpoint::<ListUsersInWorkspaceRequest>()
+		.add_endpoint::<RemoveUserFromWorkspaceRequest>()
+		.add_endpoint::<UpdateUserRolesInWorkspaceRequest>()
+		.add_endpoint::<AddRunnerToWorkspaceRequest>()
+		.add_endpoint::<DeleteRunnerRequest>()
+		.add_endpoint::<GetRunnerInfoRequest>()
+		.add_endpoint::<GetRunnerPullSecretRequest>()
+		.add_endpoint::<ListRunnersForWorkspaceRequest>()
+		.add_endpoint::<StreamRunnerDataForWorkspaceRequest>()
+		.add_endpoint::<CreatePullSecretRequest>()
+		.add_endpoint::<CreateSecretRequest>()
+		.add_endpoint::<DeleteSecretRequest>()
+		.add_endpoint::<ListSecretsForWorkspaceRequest>()
+		.add_endpoint::<UpdateSecretRequest>()
+		.add_endpoint::<CreateStaticSiteRequest>()
+		.add_endpoint::<DeleteStaticSiteRequest>()
+		.add_endpoint::<GetStaticSiteAssetDownloadUrlRequest>()
+		.add_endpoint::<GetStaticSiteInfoRequest>()
+		.add_endpoint::<ListStaticSiteRequest>()
+		.add_endpoint::<ListStaticSiteUploadHistoryRequest>()
+		.add_endpoint::<RevertStaticSiteRequest>()
+		.add_endpoint::<StartStaticSiteRequest>()
+		.add_endpoint::<StopStaticSiteRequest>()
+		.add_endpoint::<UpdateStaticSiteRequest>()
+		.add_endpoint::<UploadStaticSiteRequest>()
+		.add_endpoint::<CreateVolumeRequest>()
+		.add_endpoint::<DeleteVolumeRequest>()
+		.add_endpoint::<GetVolumeInfoRequest>()
+		.add_endpoint::<ListVolumesInWorkspaceRequest>()
+		.add_endpoint::<UpdateVolumeRequest>();
+
+	document
+}
@@ -0,0 +1,119 @@
+use std::{future::Future, time::Instant};
+
+use axum::{extract::State, http::StatusCode, routing::get, Json, Router};
+use rustis::commands::{ConnectionCommands, PingOptions};
+use serde::Serialize;
+
+use crate::prelude::*;
+
+/// The result of checking a single dependency of the API
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase", tag = "status")]
+enum DependencyCheck {
+	/// The dependency responded within the timeout
+	#[serde(rename_all = "camelCase")]
+	Ok {
+		/// The time taken for the dependency to respond, in milliseconds
+		duration_millis: u64,
+	},
+	/// The dependency returned an error, or didn't respond within the timeout
+	#[serde(rename_all = "camelCase")]
+	Failed {
+		/// The reason the check failed
+		error: String,
+	},
+}
+
+impl DependencyCheck {
+	/// Whether the dependency is healthy
+	const fn is_ok(&self) -> bool {
+		matches!(self, Self::Ok { .. })
+	}
+}
+
+/// The response of the readiness endpoint
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ReadinessResponse {
+	/// The result of checking the database
+	database: DependencyCheck,
+	/// The result of checking Redis
+	redis: DependencyCheck,
+}
+
+/// Sets up the health and readiness endpoints
+#[instrument(skip(state))]
+pub fn setup_routes(state: &AppState) -> Router {
+	Router::new()
+		.route(constants::HEALTH_CHECK_PATH, get(get_health))
+		.route(constants::READINESS_CHECK_PATH, get(get_readiness))
+		.with_state(state.clone())
+}
+
+/// The handler for the health endpoint. This always responds with a `200` as
+/// long as the server is able to handle requests, so that the server isn't
+/// restarted when only its dependencies are down.
+async fn get_health() -> StatusCode {
+	StatusCode::OK
+}
+
+/// The handler for the readiness endpoint. The database (using `SELECT 1`) and
+/// Redis (using `PING`) are checked concurrently using the connections in the
+/// [`AppState`], each with its own timeout, and the time taken by each of them
+/// is reported. The endpoint only responds with a `200` if both of them are
+/// healthy, and a `503` otherwise.
+async fn get_readiness(State(state): State<AppState>) -> (StatusCode, Json<ReadinessResponse>) {
+	let (database, redis) = futures::future::join(
+		check_dependency(async {
+			query!(
+				r#"
+				SELECT 1 AS "one!";
+				"#
+			)
+			.fetch_one(&state.database)
+			.await
+			.map(|_| ())
+			.map_err(|err| err.to_string())
+		}),
+		check_dependency(async {
+			state
+				.redis
+				.get()
+				.ping::<String>(PingOptions::default())
+				.await
+				.map(|_| ())
+				.map_err(|err| err.to_string())
+		}),
+	)
+	.await;
+
+	let status_code = if database.is_ok() && redis.is_ok() {
+		StatusCode::OK
+	} else {
+		warn!("API is not ready. Database: {database:?}, Redis: {redis:?}");
+		StatusCode::SERVICE_UNAVAILABLE
+	};
+
+	(status_code, Json(ReadinessResponse { database, redis }))
+}
+
+/// Runs the check for a dependency with the [readiness check
+/// timeout][constants::READINESS_CHECK_TIMEOUT], and measures the time it took
+async fn check_dependency(check: impl Future<Output = Result<(), String>>) -> DependencyCheck {
+	let start = Instant::now();
+	match check
+		.timeout(constants::READINESS_CHECK_TIMEOUT.unsigned_abs())
+		.await
+	{
+		Ok(Ok(())) => DependencyCheck::Ok {
+			duration_millis: start.elapsed().as_millis() as u64,
+		},
+		Ok(Err(error)) => DependencyCheck::Failed { error },
+		Err(_) => DependencyCheck::Failed {
+			error: format!(
+				"timed out after {} milliseconds",
+				constants::READINESS_CHECK_TIMEOUT.whole_milliseconds()
+			),
+		},
+	}
+}
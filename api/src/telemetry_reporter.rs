@@ -0,0 +1,148 @@
+use std::pin::pin;
+
+use futures::future::Either;
+use rustis::commands::{SetCondition, SetExpiration, StringCommands};
+use serde::Serialize;
+
+use crate::prelude::*;
+
+/// The anonymized data that is reported by the telemetry reporter. This must
+/// only ever contain the version of the instance and aggregate counts. No user
+/// data, secrets, names or IDs of any resource are to be added here.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TelemetryPayload {
+	/// The version of the API that the instance is running
+	version: &'static str,
+	/// The number of users on the instance
+	users: i64,
+	/// The number of workspaces on the instance
+	workspaces: i64,
+	/// The number of deployments across all workspaces
+	deployments: i64,
+	/// The number of static sites across all workspaces
+	static_sites: i64,
+	/// The number of managed URLs across all workspaces
+	managed_urls: i64,
+	/// The number of managed databases across all workspaces
+	managed_databases: i64,
+	/// The number of runners across all workspaces
+	runners: i64,
+	/// The number of domains across all workspaces
+	domains: i64,
+}
+
+/// Runs a background task that periodically reports the version of the
+/// instance and the aggregate counts of its resources to the configured
+/// telemetry endpoint. This only runs if telemetry is explicitly enabled (or
+/// previewed) in the config, and is off by default. The payload is always
+/// logged before it is sent, so that it can be inspected. A lock in Redis is
+/// held for each interval, so that running multiple instances of the API
+/// doesn't report more than once.
+#[instrument(skip(state))]
+pub async fn run(state: &AppState) {
+	let config = &state.config.telemetry;
+	if !config.enabled && !config.preview {
+		return;
+	}
+
+	let mut interval = tokio::time::interval(std::time::Duration::from_secs(
+		config.interval_hours.max(1) * 60 * 60,
+	));
+
+	let mut exit_signal = pin!(crate::exit_signal());
+
+	loop {
+		let Either::Right(_) =
+			futures::future::select(&mut exit_signal, pin!(interval.tick())).await
+		else {
+			// Left branch is the exit signal
+			info!("Received SIGINT, stopping telemetry reporter");
+			break;
+		};
+
+		if let Err(err) = report(state).await {
+			warn!("Failed to report telemetry: {err}");
+		}
+	}
+}
+
+/// Collects the telemetry payload, logs it and sends it to the configured
+/// endpoint, unless telemetry is only being previewed
+async fn report(state: &AppState) -> Result<(), ErrorType> {
+	let config = &state.config.telemetry;
+
+	// Claim the interval, so that other instances of the API don't report it
+	let claimed: bool = state
+		.redis
+		.get()
+		.set_with_options(
+			redis::keys::telemetry_report_lock(),
+			"",
+			SetCondition::NX,
+			SetExpiration::Ex(config.interval_hours.max(1) * 60 * 60),
+			false,
+		)
+		.await
+		.map_err(ErrorType::server_error)?;
+	if !claimed {
+		return Ok(());
+	}
+
+	let payload = collect_payload(state).await?;
+	let payload_json = serde_json::to_string(&payload).map_err(ErrorType::server_error)?;
+
+	if config.preview {
+		info!("Telemetry preview (not sent): {payload_json}");
+		return Ok(());
+	}
+
+	info!("Sending telemetry to `{}`: {payload_json}", config.endpoint);
+
+	reqwest::Client::builder()
+		.timeout(constants::TELEMETRY_REQUEST_TIMEOUT.unsigned_abs())
+		.build()
+		.map_err(ErrorType::server_error)?
+		.post(&config.endpoint)
+		.json(&payload)
+		.send()
+		.await?
+		.error_for_status()?;
+
+	Ok(())
+}
+
+/// Counts the resources on the instance for the telemetry payload
+async fn collect_payload(state: &AppState) -> Result<TelemetryPayload, ErrorType> {
+	let mut database = state.database.begin().await?;
+
+	let counts = query!(
+		r#"
+		SELECT
+			(SELECT COUNT(*) FROM "user") AS "users!",
+			(SELECT COUNT(*) FROM workspace WHERE deleted IS NULL) AS "workspaces!",
+			(SELECT COUNT(*) FROM deployment WHERE deleted IS NULL) AS "deployments!",
+			(SELECT COUNT(*) FROM static_site WHERE deleted IS NULL) AS "static_sites!",
+			(SELECT COUNT(*) FROM managed_url WHERE deleted IS NULL) AS "managed_urls!",
+			(SELECT COUNT(*) FROM managed_database WHERE deleted IS NULL) AS "managed_databases!",
+			(SELECT COUNT(*) FROM runner WHERE deleted IS NULL) AS "runners!",
+			(SELECT COUNT(*) FROM workspace_domain WHERE deleted IS NULL) AS "domains!";
+		"#
+	)
+	.fetch_one(&mut *database)
+	.await?;
+
+	database.commit().await?;
+
+	Ok(TelemetryPayload {
+		version: env!("CARGO_PKG_VERSION"),
+		users: counts.users,
+		workspaces: counts.workspaces,
+		deployments: counts.deployments,
+		static_sites: counts.static_sites,
+		managed_urls: counts.managed_urls,
+		managed_databases: counts.managed_databases,
+		runners: counts.runners,
+		domains: counts.domains,
+	})
+}
@@ -0,0 +1,49 @@
+use std::sync::{Arc, Mutex};
+
+use time::OffsetDateTime;
+
+/// The source of the current time for the API. Time-sensitive checks (such as
+/// the validity of tokens and revocation timestamps) should get the current
+/// time from the clock in the [`AppState`][crate::AppState], instead of calling
+/// [`OffsetDateTime::now_utc`] directly, so that the time can be controlled in
+/// tests.
+#[derive(Debug, Clone, Default)]
+pub struct Clock {
+	/// The time that the clock is currently stopped at, if it is controlled
+	/// manually. The clock follows the system time if this is `None`
+	stopped_at: Option<Arc<Mutex<OffsetDateTime>>>,
+}
+
+impl Clock {
+	/// Create a clock that follows the system time
+	pub const fn system() -> Self {
+		Self { stopped_at: None }
+	}
+
+	/// Create a clock that is stopped at the given time, and only moves when it
+	/// is advanced manually. Clones of the clock share the same time.
+	#[cfg(test)]
+	pub fn stopped_at(time: OffsetDateTime) -> Self {
+		Self {
+			stopped_at: Some(Arc::new(Mutex::new(time))),
+		}
+	}
+
+	/// Move a stopped clock forward by the given duration. This does nothing if
+	/// the clock follows the system time.
+	#[cfg(test)]
+	pub fn advance(&self, duration: time::Duration) {
+		if let Some(stopped_at) = &self.stopped_at {
+			let mut time = stopped_at.lock().unwrap_or_else(|err| err.into_inner());
+			*time += duration;
+		}
+	}
+
+	/// The current time, in UTC
+	pub fn now(&self) -> OffsetDateTime {
+		match &self.stopped_at {
+			Some(stopped_at) => *stopped_at.lock().unwrap_or_else(|err| err.into_inner()),
+			None => OffsetDateTime::now_utc(),
+		}
+	}
+}
@@ -1,43 +1,141 @@
 use std::{
+	collections::{BTreeMap, BTreeSet},
 	env,
 	fmt::{Display, Formatter},
 	net::SocketAddr,
 };
 
 use config::{Config, Environment, File};
+use models::{api::auth::RecoveryMethodType, utils::Uuid, QuotaResource};
 use serde::{Deserialize, Serialize};
+use sqlx::types::ipnetwork::IpNetwork;
+use tracing_subscriber::filter::LevelFilter;
+
+use crate::{prelude::*, utils::constants};
 
 /// Parses the configuration of the application and returns the parsed config.
-/// In case of any errors while parsing, this function will panic.
+/// In case of any errors while parsing, or if the parsed config is invalid,
+/// this function will panic.
 ///
 /// This should ideally be only called once during initialization and the parsed
-/// config should be used for the lifetime of the application.
+/// config should be used for the lifetime of the application. Only the
+/// [`ReloadableConfig`] can be changed afterwards, using [`load_config`].
 pub fn parse_config() -> AppConfig {
+	match load_config() {
+		Ok(config) => config,
+		Err(err) => panic!("{err:#}"),
+	}
+}
+
+/// Reads the configuration of the application from the config file and the
+/// environment variables, failing if it cannot be parsed or is invalid
+pub fn load_config() -> anyhow::Result<AppConfig> {
 	let env = if cfg!(debug_assertions) {
 		"dev".to_string()
 	} else {
 		env::var("PATR_ENV").unwrap_or_else(|_| "prod".into())
 	};
 
-	match env.as_ref() {
+	let config = match env.as_ref() {
 		"prod" | "production" => Config::builder()
 			.add_source(File::with_name("config").required(false))
 			.set_default("environment", "production")
-			.expect("unable to set environment to production"),
+			.context("unable to set environment to production")?,
 		"dev" | "development" => Config::builder()
 			.add_source(File::with_name("./config/api").required(false))
 			.add_source(File::with_name("../config/api").required(false))
 			.set_default("environment", "development")
-			.expect("unable to set environment to development"),
+			.context("unable to set environment to development")?,
 		_ => {
-			panic!("Unknown running environment found!");
+			anyhow::bail!("Unknown running environment found!");
 		}
 	}
 	.add_source(Environment::with_prefix("PATR").separator("_"))
 	.build()
-	.expect("unable to merge with environment variables")
-	.try_deserialize()
-	.expect("unable to parse settings")
+	.context("unable to merge with environment variables")?
+	.try_deserialize::<AppConfig>()
+	.context("unable to parse settings")?;
+
+	if let Err(err) = config.redis.validate() {
+		anyhow::bail!("Invalid Redis config: {err}");
+	}
+	if let Err(err) = config.recovery_methods.validate() {
+		anyhow::bail!("Invalid recovery methods config: {err}");
+	}
+
+	Ok(config)
+}
+
+/// The parts of the config that can be changed while the API is running, by
+/// sending it a `SIGHUP`. These are only the operational settings, such as the
+/// log levels, the rate limits and the timeouts. Everything else, including
+/// all the secrets and the credentials of the database, is fixed at startup.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReloadableConfig {
+	/// The configuration for the logs of the API, including the access log
+	pub logging: LoggingConfig,
+	/// The limits on the number of accounts that can be created
+	pub signup_rate_limit: SignupRateLimitConfig,
+	/// The locking of accounts after too many failed password attempts
+	pub account_lockout: AccountLockoutConfig,
+	/// The configuration for communicating with the runners of workspaces
+	pub runner: RunnerConfig,
+	/// The configuration for testing the reachability of the exposed ports of
+	/// deployments
+	pub port_test: PortTestConfig,
+}
+
+impl ReloadableConfig {
+	/// Takes the parts of the given config that can be reloaded
+	pub fn from_config(config: &AppConfig) -> Self {
+		Self {
+			logging: config.logging.clone(),
+			signup_rate_limit: config.security.signup_rate_limit.clone(),
+			account_lockout: config.security.account_lockout.clone(),
+			runner: config.runner.clone(),
+			port_test: config.port_test.clone(),
+		}
+	}
+
+	/// Overwrites the parts of the given config that can be reloaded with
+	/// these values
+	pub fn apply_to(&self, config: &mut AppConfig) {
+		config.logging = self.logging.clone();
+		config.security.signup_rate_limit = self.signup_rate_limit.clone();
+		config.security.account_lockout = self.account_lockout.clone();
+		config.runner = self.runner.clone();
+		config.port_test = self.port_test.clone();
+	}
+
+	/// The names of the settings that are different in the other config, as
+	/// they are named in the config file
+	pub fn changed_settings(&self, other: &Self) -> Vec<String> {
+		let (Ok(serde_json::Value::Object(current)), Ok(serde_json::Value::Object(other))) =
+			(serde_json::to_value(self), serde_json::to_value(other))
+		else {
+			return vec![];
+		};
+
+		current
+			.iter()
+			.flat_map(|(section, current)| {
+				let other = other.get(section);
+				match (current, other) {
+					(
+						serde_json::Value::Object(current),
+						Some(serde_json::Value::Object(other)),
+					) => current
+						.iter()
+						.filter(|(key, value)| other.get(*key) != Some(*value))
+						.map(|(key, _)| format!("{section}.{key}"))
+						.collect(),
+					(current, other) if other != Some(current) => vec![section.clone()],
+					_ => vec![],
+				}
+			})
+			.collect()
+	}
 }
 
 /// The global application configuration
@@ -56,6 +154,21 @@ pub struct AppConfig {
 	/// The secret used to sign JWTs
 	#[serde(alias = "jwtsecret")]
 	pub jwt_secret: String,
+	/// The issuer (iss) of the JWTs minted by this instance. Tokens with any
+	/// other issuer are rejected. Defaults to [`constants::JWT_ISSUER`]
+	#[serde(default = "default_jwt_issuer", alias = "jwtissuer")]
+	pub jwt_issuer: String,
+	/// The audience (aud) of the JWTs minted by this instance. Tokens that
+	/// aren't intended for this audience are rejected. Defaults to
+	/// [`constants::PATR_JWT_AUDIENCE`]
+	#[serde(default = "default_jwt_audience", alias = "jwtaudience")]
+	pub jwt_audience: String,
+	/// The list of networks of the reverse proxies / load balancers that are
+	/// trusted to set the `X-Forwarded-For`, `Forwarded` and
+	/// `CF-Connecting-IP` headers. Forwarding headers from any other peer are
+	/// ignored, and the socket peer is used as the client IP instead
+	#[serde(default, alias = "trustedproxies")]
+	pub trusted_proxies: Vec<IpNetwork>,
 	/// The environment the application is running in. This is set at runtime
 	/// based on an environment variable and if the application is compiled with
 	/// debug mode.
@@ -67,13 +180,86 @@ pub struct AppConfig {
 	/// The configuration for Redis. This is used for caching, rate limiting and
 	/// for subscribing to events from the database on websockets
 	pub redis: RedisConfig,
-	// pub email: EmailConfig,
+	/// The configuration for the SMTP server that emails are sent through
+	pub email: EmailConfig,
 	/// The cloudflare settings to use for the API
 	pub cloudflare: CloudflareConfig,
 	/// The opentelemetry endpoint to send traces to
 	pub opentelemetry: OpenTelemetryConfig,
 	/// The configuration for IpInfo to get IpAddress details
 	pub ipinfo: IpInfoConfig,
+	/// The configuration for testing the reachability of the exposed ports of
+	/// deployments
+	#[serde(default, alias = "porttest")]
+	pub port_test: PortTestConfig,
+	/// The configuration for compressing the responses of the API
+	#[serde(default)]
+	pub compression: CompressionConfig,
+	/// The configuration for the access log that is emitted for every request
+	#[serde(default)]
+	pub logging: LoggingConfig,
+	/// The configuration for how long the web logins of users stay valid
+	#[serde(default)]
+	pub session: SessionConfig,
+	/// The configuration for how long the permissions of a login are cached
+	/// before they are reloaded from the database
+	#[serde(default, alias = "permissioncache")]
+	pub permission_cache: PermissionCacheConfig,
+	/// The configuration for shedding load when the API is handling too many
+	/// requests at once
+	#[serde(default, alias = "loadshedding")]
+	pub load_shedding: LoadSheddingConfig,
+	/// The configuration for the paginated list endpoints
+	#[serde(default)]
+	pub pagination: PaginationConfig,
+	/// The configuration for the limits on the deployments of workspaces
+	#[serde(default)]
+	pub deployment: DeploymentConfig,
+	/// The default quotas on the number of resources that each workspace can
+	/// have
+	#[serde(default)]
+	pub quota: QuotaConfig,
+	/// The configuration for the backend that the values of secrets are
+	/// stored in
+	#[serde(default)]
+	pub secrets: SecretsConfig,
+	/// The configuration for scanning the images of deployments for
+	/// vulnerabilities
+	#[serde(default, alias = "imagescan")]
+	pub image_scan: ImageScanConfig,
+	/// The configuration for reporting anonymized telemetry. This is disabled
+	/// unless explicitly enabled
+	#[serde(default)]
+	pub telemetry: TelemetryConfig,
+	/// The configuration for protecting the API against abuse
+	#[serde(default)]
+	pub security: SecurityConfig,
+	/// The configuration for the recovery methods that new accounts can be
+	/// created with
+	#[serde(default, alias = "recoverymethods")]
+	pub recovery_methods: RecoveryMethodsConfig,
+	/// The configuration for communicating with the runners of workspaces
+	#[serde(default)]
+	pub runner: RunnerConfig,
+	/// The configuration for clients that poll for the updates of workspaces
+	/// instead of using websockets
+	#[serde(default, alias = "longpoll")]
+	pub long_poll: LongPollConfig,
+	/// The feature flags that are enabled on this instance, by the name of the
+	/// flag. These can be overridden for each workspace in the database. Any
+	/// flag that isn't present is disabled
+	#[serde(default, alias = "featureflags")]
+	pub feature_flags: BTreeMap<String, bool>,
+}
+
+/// The default value for the issuer of the JWTs
+fn default_jwt_issuer() -> String {
+	constants::JWT_ISSUER.to_string()
+}
+
+/// The default value for the audience of the JWTs
+fn default_jwt_audience() -> String {
+	constants::PATR_JWT_AUDIENCE.to_string()
 }
 
 /// The environment the application is running in
@@ -154,6 +340,39 @@ pub struct RedisConfig {
 	pub database: u8,
 	/// Whether or not to use TLS to connect to the Redis server
 	pub secure: bool,
+	/// The number of connections to the Redis server. Commands are multiplexed
+	/// on each connection, and spread across the connections, so that a slow
+	/// command only holds up the commands on the same connection. The default
+	/// is 4
+	#[serde(default = "default_redis_pool_size", alias = "poolsize")]
+	pub pool_size: u16,
+	/// The number of milliseconds after which a Redis command fails if the
+	/// server hasn't replied to it. The default is 1000
+	#[serde(
+		default = "default_redis_command_timeout_millis",
+		alias = "commandtimeoutmillis"
+	)]
+	pub command_timeout_millis: u64,
+}
+
+impl RedisConfig {
+	/// The duration after which a Redis command fails if the server hasn't
+	/// replied to it
+	pub fn command_timeout(&self) -> std::time::Duration {
+		std::time::Duration::from_millis(self.command_timeout_millis)
+	}
+
+	/// Checks that the pool has at least one connection, and that commands
+	/// have a timeout, since a timeout of 0 means that commands never time out
+	pub fn validate(&self) -> Result<(), String> {
+		if self.pool_size == 0 {
+			return Err("the Redis pool size must be at least 1".to_string());
+		}
+		if self.command_timeout_millis == 0 {
+			return Err("the Redis command timeout must be greater than 0".to_string());
+		}
+		Ok(())
+	}
 }
 
 /// The default value for the Redis database
@@ -161,6 +380,16 @@ fn default_redis_database() -> u8 {
 	0
 }
 
+/// The default number of connections to the Redis server
+fn default_redis_pool_size() -> u16 {
+	4
+}
+
+/// The default number of milliseconds after which a Redis command fails
+fn default_redis_command_timeout_millis() -> u64 {
+	1000
+}
+
 /// The configuration for Cloudflare to use for the API. This is used to
 /// setup DNS records and for Cloudflare Tunnels.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -171,6 +400,16 @@ pub struct CloudflareConfig {
 	/// The API key to use to connect to Cloudflare
 	#[serde(alias = "apikey")]
 	pub api_key: String,
+	/// The token that the ingress worker uses to report the activity and the
+	/// access logs of deployments. If this is not set, everything reported by
+	/// the ingress is rejected
+	#[serde(default, alias = "ingresstoken")]
+	pub ingress_token: Option<String>,
+	/// The R2 bucket that the ingress serves static sites from, accessed
+	/// through its S3-compatible API. The assets of static sites can't be
+	/// downloaded through the API if this isn't set
+	#[serde(default, alias = "staticsitestorage")]
+	pub static_site_storage: Option<S3Config>,
 }
 
 /// The configuration for the SMTP server to use to send emails to users
@@ -195,6 +434,13 @@ pub struct EmailConfig {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct OpenTelemetryConfig {
+	/// Whether traces and metrics are exported to the [tracing
+	/// endpoint][TracingConfig::endpoint]. Defaults to `true`. Self-hosted
+	/// instances without a collector can disable this, in which case the logs
+	/// are only written to stdout. Exporting is best-effort either way, so an
+	/// unreachable collector never stops the API from running
+	#[serde(default = "default_opentelemetry_enabled")]
+	pub enabled: bool,
 	/// The metrics configuration for the opentelemetry endpoint
 	pub tracing: TracingConfig,
 	/// The loki configuration to use for logs
@@ -203,6 +449,11 @@ pub struct OpenTelemetryConfig {
 	pub metrics: MetricsConfig,
 }
 
+/// The default value for whether traces and metrics are exported
+const fn default_opentelemetry_enabled() -> bool {
+	true
+}
+
 /// The configuration for the opentelemetry endpoint to send traces to
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -217,6 +468,42 @@ pub struct TracingConfig {
 pub struct LogsConfig {
 	/// The endpoint to send logs to
 	pub endpoint: String,
+	/// How long (in hours) the logs are retained in Loki. Logs older than
+	/// this are never queried for
+	#[serde(default = "default_logs_retention_hours", alias = "retentionhours")]
+	pub retention_hours: u32,
+	/// The maximum time range (in hours) that the logs of a deployment can be
+	/// downloaded for in a single request. Longer ranges are truncated
+	#[serde(
+		default = "default_max_logs_download_range_hours",
+		alias = "maxdownloadrangehours"
+	)]
+	pub max_download_range_hours: u32,
+	/// How long (in hours) the access logs of deployments are retained in
+	/// Loki. Access logs older than this are never queried for. This should
+	/// match the retention of the `logStream="access"` streams in Loki, which
+	/// is kept short since there can be a lot of them
+	#[serde(
+		default = "default_access_log_retention_hours",
+		alias = "accesslogretentionhours"
+	)]
+	pub access_log_retention_hours: u32,
+}
+
+/// The default value for how long the logs are retained in Loki
+const fn default_logs_retention_hours() -> u32 {
+	constants::DEFAULT_LOGS_RETENTION_HOURS
+}
+
+/// The default value for how long the access logs are retained in Loki
+const fn default_access_log_retention_hours() -> u32 {
+	constants::DEFAULT_ACCESS_LOG_RETENTION_HOURS
+}
+
+/// The default value for the maximum time range that logs can be downloaded
+/// for in a single request
+const fn default_max_logs_download_range_hours() -> u32 {
+	constants::DEFAULT_MAX_LOGS_DOWNLOAD_RANGE_HOURS
 }
 
 /// The configuration for Mimir to use for metrics
@@ -238,3 +525,826 @@ pub struct IpInfoConfig {
 	/// The token for connecting to ipinfo.io
 	pub token: String,
 }
+
+/// The configuration for testing the reachability of the exposed ports of
+/// deployments from within the platform network
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortTestConfig {
+	/// The host to connect to for a deployment. `{deploymentId}` and
+	/// `{workspaceId}` are replaced with the IDs of the deployment being tested
+	#[serde(alias = "hosttemplate")]
+	pub host_template: String,
+	/// The time (in milliseconds) after which the port is considered
+	/// unreachable
+	#[serde(alias = "timeoutmillis")]
+	pub timeout_millis: u64,
+}
+
+impl Default for PortTestConfig {
+	fn default() -> Self {
+		Self {
+			host_template: "service-{deploymentId}".to_string(),
+			timeout_millis: 5000,
+		}
+	}
+}
+
+/// The configuration for compressing the responses of the API. The encoding is
+/// chosen based on the `Accept-Encoding` header of the request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompressionConfig {
+	/// The minimum size (in bytes) of a response body for it to be compressed.
+	/// Responses smaller than this are sent as is, since compressing them
+	/// isn't worth the overhead. Streaming responses with an unknown size are
+	/// always compressed, chunk by chunk.
+	#[serde(alias = "minsizebytes")]
+	pub min_size_bytes: u16,
+}
+
+impl Default for CompressionConfig {
+	fn default() -> Self {
+		Self {
+			min_size_bytes: 1024,
+		}
+	}
+}
+
+/// The configuration for the logs of the API, along with the access log, which
+/// has a structured line for every request made to the API, suitable for
+/// shipping to a log aggregator
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoggingConfig {
+	/// Whether the fields of each access log line should be logged as a
+	/// single JSON object instead of as separate tracing fields
+	#[serde(default)]
+	pub json: bool,
+	/// The level that the access log lines are emitted at
+	#[serde(default)]
+	pub level: AccessLogLevel,
+	/// The most verbose level that the logs of the API are emitted at. If not
+	/// set, all logs are emitted in development, and everything but the trace
+	/// logs in production
+	#[serde(default, alias = "maxlevel")]
+	pub max_level: Option<LogLevel>,
+}
+
+impl Default for LoggingConfig {
+	fn default() -> Self {
+		Self {
+			json: false,
+			level: AccessLogLevel::Info,
+			max_level: None,
+		}
+	}
+}
+
+impl LoggingConfig {
+	/// The most verbose level that the logs of the API should be emitted at
+	/// when running in the given environment
+	pub fn max_level_filter(&self, environment: &RunningEnvironment) -> LevelFilter {
+		match self.max_level {
+			Some(LogLevel::Trace) => LevelFilter::TRACE,
+			Some(LogLevel::Debug) => LevelFilter::DEBUG,
+			Some(LogLevel::Info) => LevelFilter::INFO,
+			Some(LogLevel::Warn) => LevelFilter::WARN,
+			Some(LogLevel::Error) => LevelFilter::ERROR,
+			None if *environment == RunningEnvironment::Development => LevelFilter::TRACE,
+			None => LevelFilter::DEBUG,
+		}
+	}
+}
+
+/// The configuration for how long the web logins of users stay valid. A login
+/// expires once it reaches its absolute timeout, or once it has not been used
+/// for longer than its idle timeout, whichever comes first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionConfig {
+	/// The number of hours after logging in that a login expires, regardless
+	/// of how active it is
+	#[serde(alias = "absolutetimeouthours")]
+	pub absolute_timeout_hours: u32,
+	/// The number of hours that a login can go without any requests being
+	/// made with it before it expires
+	#[serde(alias = "idletimeouthours")]
+	pub idle_timeout_hours: u32,
+}
+
+impl SessionConfig {
+	/// The duration after logging in that a login expires
+	pub fn absolute_timeout(&self) -> time::Duration {
+		time::Duration::hours(self.absolute_timeout_hours.into())
+	}
+
+	/// The duration that a login can go without being used before it expires
+	pub fn idle_timeout(&self) -> time::Duration {
+		time::Duration::hours(self.idle_timeout_hours.into())
+	}
+}
+
+impl Default for SessionConfig {
+	fn default() -> Self {
+		Self {
+			absolute_timeout_hours: 30 * 24,
+			idle_timeout_hours: 7 * 24,
+		}
+	}
+}
+
+/// The configuration for how long the permissions of a login are cached in
+/// Redis. Cached permissions older than the soft TTL are still used, but are
+/// reloaded in the background so that the next requests get the fresh ones.
+/// Cached permissions older than the hard TTL are never used, and the request
+/// waits for them to be reloaded.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PermissionCacheConfig {
+	/// The number of seconds after which cached permissions are reloaded in
+	/// the background
+	#[serde(alias = "softttlseconds")]
+	pub soft_ttl_seconds: u32,
+	/// The number of seconds after which cached permissions can no longer be
+	/// used. This cannot be longer than [`CACHED_PERMISSIONS_VALIDITY`][1],
+	/// since the revocations of permissions are only kept for that long
+	///
+	/// [1]: constants::CACHED_PERMISSIONS_VALIDITY
+	#[serde(alias = "hardttlseconds")]
+	pub hard_ttl_seconds: u32,
+}
+
+impl PermissionCacheConfig {
+	/// The duration after which cached permissions are reloaded in the
+	/// background. This is never longer than the [`hard_ttl`][Self::hard_ttl]
+	pub fn soft_ttl(&self) -> time::Duration {
+		time::Duration::seconds(self.soft_ttl_seconds.into()).min(self.hard_ttl())
+	}
+
+	/// The duration after which cached permissions can no longer be used
+	pub fn hard_ttl(&self) -> time::Duration {
+		time::Duration::seconds(self.hard_ttl_seconds.into())
+			.min(constants::CACHED_PERMISSIONS_VALIDITY)
+	}
+}
+
+impl Default for PermissionCacheConfig {
+	fn default() -> Self {
+		Self {
+			soft_ttl_seconds: 15 * 60,
+			hard_ttl_seconds: constants::CACHED_PERMISSIONS_VALIDITY.whole_seconds() as u32,
+		}
+	}
+}
+
+/// The configuration for shedding load when the API is handling too many
+/// requests at once. Requests past the limit are rejected with a `503` instead
+/// of being queued, so that the requests that are already being handled can
+/// finish in time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadSheddingConfig {
+	/// The maximum number of requests that can be handled at once. Health and
+	/// readiness checks are not counted towards this limit
+	#[serde(alias = "maxinflightrequests")]
+	pub max_in_flight_requests: usize,
+	/// The number of seconds that a rejected request is asked to wait (in the
+	/// `Retry-After` header) before it is retried
+	#[serde(alias = "retryafterseconds")]
+	pub retry_after_seconds: u64,
+}
+
+impl Default for LoadSheddingConfig {
+	fn default() -> Self {
+		Self {
+			max_in_flight_requests: 1024,
+			retry_after_seconds: 5,
+		}
+	}
+}
+
+/// The configuration for protecting the API against abuse
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SecurityConfig {
+	/// The limits on the number of accounts that can be created
+	#[serde(default, alias = "signupratelimit")]
+	pub signup_rate_limit: SignupRateLimitConfig,
+	/// The locking of accounts after too many failed password attempts
+	#[serde(default, alias = "accountlockout")]
+	pub account_lockout: AccountLockoutConfig,
+	/// The support operators that can impersonate users
+	#[serde(default)]
+	pub impersonation: ImpersonationConfig,
+	/// The signed URLs that are issued to download files without a token
+	#[serde(default, alias = "signedurls")]
+	pub signed_urls: SignedUrlConfig,
+	/// The tokens that are sent to users to reset their password
+	#[serde(default, alias = "passwordreset")]
+	pub password_reset: PasswordResetConfig,
+}
+
+/// The limits on the number of accounts that can be created from the same IP
+/// address or with a recovery email on the same domain within a window. Only
+/// accounts that are actually created are counted, so that requests failing
+/// validation can be retried without being penalized
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignupRateLimitConfig {
+	/// The length (in seconds) of the window that accounts are counted in.
+	/// Rejected requests are asked to wait until the window ends (in the
+	/// `Retry-After` header) before they are retried
+	#[serde(alias = "windowseconds")]
+	pub window_seconds: u64,
+	/// The maximum number of accounts that can be created from the same IP
+	/// address within the window
+	#[serde(alias = "maxperip")]
+	pub max_per_ip: u64,
+	/// The maximum number of accounts that can be created with a recovery
+	/// email on the same domain within the window
+	#[serde(alias = "maxperemaildomain")]
+	pub max_per_email_domain: u64,
+}
+
+impl Default for SignupRateLimitConfig {
+	fn default() -> Self {
+		Self {
+			window_seconds: 60 * 60,
+			max_per_ip: 5,
+			max_per_email_domain: 50,
+		}
+	}
+}
+
+/// The locking of an account after too many consecutive failed password
+/// attempts for it. Unlike the limits on sign ups, this is tracked per account
+/// instead of per IP address, so that accounts are protected even from attacks
+/// that rotate their IP addresses
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountLockoutConfig {
+	/// The number of consecutive failed password attempts after which the
+	/// account is locked
+	#[serde(alias = "maxfailedattempts")]
+	pub max_failed_attempts: u64,
+	/// How long (in seconds) the account stays locked for. Failed attempts
+	/// older than this are forgotten. Rejected requests are asked to wait at
+	/// most this long (in the `Retry-After` header) before they are retried
+	#[serde(alias = "cooldownseconds")]
+	pub cooldown_seconds: u64,
+}
+
+impl Default for AccountLockoutConfig {
+	fn default() -> Self {
+		Self {
+			max_failed_attempts: 10,
+			cooldown_seconds: 15 * 60,
+		}
+	}
+}
+
+/// The support operators that can start impersonated sessions, acting as other
+/// users to reproduce what they see. No one can impersonate users unless they
+/// are listed here. Impersonated sessions can't be refreshed, so they end once
+/// they expire
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImpersonationConfig {
+	/// The user IDs of the operators that can impersonate users
+	#[serde(default)]
+	pub operators: BTreeSet<Uuid>,
+	/// How long (in minutes) an impersonated session is valid for
+	#[serde(alias = "sessionminutes")]
+	pub session_minutes: u32,
+}
+
+impl ImpersonationConfig {
+	/// The duration after which an impersonated session expires
+	pub fn session_duration(&self) -> time::Duration {
+		time::Duration::minutes(self.session_minutes.into())
+	}
+}
+
+impl Default for ImpersonationConfig {
+	fn default() -> Self {
+		Self {
+			operators: BTreeSet::new(),
+			session_minutes: 15,
+		}
+	}
+}
+
+/// The tokens that are sent to users to reset their password. A token can only
+/// be used once, and all the outstanding tokens of a user are invalidated once
+/// their password is changed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PasswordResetConfig {
+	/// How long (in minutes) a password reset token is valid for after it is
+	/// sent. A new token can't be requested until the previous one expires
+	#[serde(alias = "tokenlifetimeminutes")]
+	pub token_lifetime_minutes: u32,
+}
+
+impl PasswordResetConfig {
+	/// The duration after which a password reset token expires
+	pub fn token_lifetime(&self) -> time::Duration {
+		time::Duration::minutes(self.token_lifetime_minutes.into())
+	}
+}
+
+impl Default for PasswordResetConfig {
+	fn default() -> Self {
+		Self {
+			token_lifetime_minutes: 2 * 60,
+		}
+	}
+}
+
+/// The signed URLs that are issued to download container image layers and
+/// static site assets without a token. The permissions of the user are only
+/// checked when the URL is issued, so the expiry should be kept short
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignedUrlConfig {
+	/// How long (in seconds) a signed URL is valid for after it is issued
+	#[serde(alias = "expiryseconds")]
+	pub expiry_seconds: u32,
+	/// The secret that URLs are signed with. This must not be shared with any
+	/// other secret, so that it can be rotated on its own. Signed URLs can't be
+	/// issued (or used) if this isn't set
+	#[serde(default)]
+	pub secret: Option<String>,
+}
+
+impl SignedUrlConfig {
+	/// The duration after which a signed URL expires
+	pub fn expiry(&self) -> time::Duration {
+		time::Duration::seconds(self.expiry_seconds.into())
+	}
+}
+
+impl Default for SignedUrlConfig {
+	fn default() -> Self {
+		Self {
+			expiry_seconds: 5 * 60,
+			secret: None,
+		}
+	}
+}
+
+/// The recovery methods that new accounts can be created with. The OTP to
+/// complete the sign up (and any password reset) is sent to the recovery
+/// method of the account, so a recovery method should only be enabled if this
+/// instance has a provider configured to send messages to it. Self-hosted
+/// instances without a mail or SMS provider should disable the corresponding
+/// methods, so that accounts aren't created that can never be verified
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecoveryMethodsConfig {
+	/// Whether accounts can be created with a recovery email. This requires a
+	/// mail provider to be configured
+	pub email: bool,
+	/// Whether accounts can be created with a recovery phone number. This
+	/// requires an SMS provider to be configured
+	#[serde(alias = "phonenumber")]
+	pub phone_number: bool,
+}
+
+impl RecoveryMethodsConfig {
+	/// Checks if accounts can be created with the given type of recovery
+	/// method
+	pub fn is_supported(&self, method_type: RecoveryMethodType) -> bool {
+		match method_type {
+			RecoveryMethodType::Email => self.email,
+			RecoveryMethodType::PhoneNumber => self.phone_number,
+		}
+	}
+
+	/// Fails with [`ErrorType::RecoveryMethodUnavailable`] if accounts can't
+	/// be created (or recovered) with the given type of recovery method, since
+	/// this instance can't deliver an OTP to it
+	pub fn ensure_supported(&self, method_type: RecoveryMethodType) -> Result<(), ErrorType> {
+		if !self.is_supported(method_type) {
+			debug!("Recovery method `{method_type:?}` is not supported");
+			return Err(ErrorType::RecoveryMethodUnavailable);
+		}
+		Ok(())
+	}
+
+	/// The types of recovery methods that accounts can be created with
+	pub fn supported(&self) -> Vec<RecoveryMethodType> {
+		[RecoveryMethodType::Email, RecoveryMethodType::PhoneNumber]
+			.into_iter()
+			.filter(|method_type| self.is_supported(*method_type))
+			.collect()
+	}
+
+	/// Checks that at least one recovery method is enabled, since accounts
+	/// can't be created (or recovered) without one
+	pub fn validate(&self) -> Result<(), String> {
+		if self.supported().is_empty() {
+			return Err("at least one recovery method must be enabled".to_string());
+		}
+		Ok(())
+	}
+}
+
+impl Default for RecoveryMethodsConfig {
+	fn default() -> Self {
+		Self {
+			email: true,
+			phone_number: false,
+		}
+	}
+}
+
+/// The configuration for communicating with the runners of workspaces. Each
+/// message sent to a runner is timed out, and retried a limited number of
+/// times with an exponential backoff, so that a slow or disconnected runner
+/// doesn't hold up the requests that need to reach it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunnerConfig {
+	/// The number of milliseconds after which sending a message to a runner is
+	/// considered to have failed
+	#[serde(alias = "requesttimeoutmillis")]
+	pub request_timeout_millis: u64,
+	/// The number of times that sending a message to a runner is retried
+	/// before the runner is considered unreachable
+	#[serde(alias = "maxretries")]
+	pub max_retries: u32,
+	/// The number of milliseconds to wait before the first retry. This is
+	/// doubled for every retry after it
+	#[serde(alias = "retrybackoffmillis")]
+	pub retry_backoff_millis: u64,
+}
+
+impl RunnerConfig {
+	/// The duration after which sending a message to a runner is considered to
+	/// have failed
+	pub fn request_timeout(&self) -> std::time::Duration {
+		std::time::Duration::from_millis(self.request_timeout_millis)
+	}
+
+	/// The duration to wait before the given retry (starting from 0) of
+	/// sending a message to a runner
+	pub fn retry_backoff(&self, retry: u32) -> std::time::Duration {
+		std::time::Duration::from_millis(
+			self.retry_backoff_millis
+				.saturating_mul(2u64.saturating_pow(retry)),
+		)
+	}
+}
+
+impl Default for RunnerConfig {
+	fn default() -> Self {
+		Self {
+			request_timeout_millis: 5000,
+			max_retries: 2,
+			retry_backoff_millis: 250,
+		}
+	}
+}
+
+/// The configuration for clients that poll for the updates of workspaces
+/// instead of using websockets. Each poll holds on to a request until an
+/// update is made or the timeout is reached, so the number of polls that can
+/// wait at once is limited
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LongPollConfig {
+	/// The number of seconds that a poll waits for an update, if the client
+	/// doesn't ask for a timeout
+	#[serde(alias = "defaulttimeoutseconds")]
+	pub default_timeout_seconds: u32,
+	/// The maximum number of seconds that a poll can wait for an update. Polls
+	/// that ask for a longer timeout wait for this long instead
+	#[serde(alias = "maxtimeoutseconds")]
+	pub max_timeout_seconds: u32,
+	/// The maximum number of polls that can wait for an update at once on
+	/// this instance. Polls made once this is reached fail right away, instead
+	/// of waiting
+	#[serde(default = "default_max_concurrent_polls", alias = "maxconcurrentpolls")]
+	pub max_concurrent_polls: usize,
+}
+
+/// The default value for the maximum number of polls that can wait for an
+/// update at once
+fn default_max_concurrent_polls() -> usize {
+	1000
+}
+
+impl LongPollConfig {
+	/// The duration that a poll waits for an update, given the timeout that
+	/// the client asked for
+	pub fn timeout(&self, requested_seconds: Option<u32>) -> std::time::Duration {
+		let seconds = requested_seconds
+			.unwrap_or(self.default_timeout_seconds)
+			.min(self.max_timeout_seconds);
+		std::time::Duration::from_secs(seconds.into())
+	}
+}
+
+impl Default for LongPollConfig {
+	fn default() -> Self {
+		Self {
+			default_timeout_seconds: 25,
+			max_timeout_seconds: 30,
+			max_concurrent_polls: default_max_concurrent_polls(),
+		}
+	}
+}
+
+/// The configuration for the paginated list endpoints
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaginationConfig {
+	/// The maximum number of items that can be requested per page. Requests
+	/// for more items than this are given this many items per page
+	#[serde(alias = "maxpagesize")]
+	pub max_page_size: usize,
+}
+
+impl Default for PaginationConfig {
+	fn default() -> Self {
+		Self { max_page_size: 100 }
+	}
+}
+
+/// The configuration for the limits on the deployments of workspaces
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeploymentConfig {
+	/// The maximum number of replicas that any deployment can be scaled to.
+	/// This is the limit for workspaces that don't have one of their own, and
+	/// the upper bound for the ones that do
+	#[serde(alias = "maxreplicas")]
+	pub max_replicas: u16,
+}
+
+impl Default for DeploymentConfig {
+	fn default() -> Self {
+		Self { max_replicas: 20 }
+	}
+}
+
+/// The default quotas on the number of resources that each workspace can have.
+/// A workspace with a quota of its own uses that instead. Resources without a
+/// quota are unlimited
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuotaConfig {
+	/// The maximum number of deployments that a workspace can have
+	#[serde(default, alias = "maxdeployments")]
+	pub max_deployments: Option<u32>,
+	/// The maximum number of runners that a workspace can have
+	#[serde(default, alias = "maxrunners")]
+	pub max_runners: Option<u32>,
+}
+
+impl QuotaConfig {
+	/// The quota of the given kind of resource, if it is limited
+	pub fn limit(&self, resource: QuotaResource) -> Option<u32> {
+		match resource {
+			QuotaResource::Deployment => self.max_deployments,
+			QuotaResource::Runner => self.max_runners,
+		}
+	}
+}
+
+/// The configuration for reporting anonymized telemetry about a self-hosted
+/// instance. Telemetry is off by default and is only sent once `enabled` is
+/// explicitly set. The payload only contains the version of the instance and
+/// the number of users, workspaces and resources on it. It never contains any
+/// user data, secrets or names of workspaces or resources. Setting `preview`
+/// logs the exact payload that would be sent, without sending it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TelemetryConfig {
+	/// Whether telemetry is sent to the endpoint. Defaults to `false`
+	#[serde(default)]
+	pub enabled: bool,
+	/// Whether the payload is only logged instead of being sent, so that it
+	/// can be inspected before enabling telemetry. This takes effect even if
+	/// telemetry is not enabled
+	#[serde(default)]
+	pub preview: bool,
+	/// The endpoint that the payload is posted to as JSON
+	#[serde(default = "default_telemetry_endpoint")]
+	pub endpoint: String,
+	/// How often (in hours) the payload is reported
+	#[serde(default = "default_telemetry_interval_hours", alias = "intervalhours")]
+	pub interval_hours: u64,
+}
+
+impl Default for TelemetryConfig {
+	fn default() -> Self {
+		Self {
+			enabled: false,
+			preview: false,
+			endpoint: default_telemetry_endpoint(),
+			interval_hours: default_telemetry_interval_hours(),
+		}
+	}
+}
+
+/// The default value for the endpoint that telemetry is reported to
+fn default_telemetry_endpoint() -> String {
+	constants::DEFAULT_TELEMETRY_ENDPOINT.to_string()
+}
+
+/// The default value for how often telemetry is reported
+const fn default_telemetry_interval_hours() -> u64 {
+	24
+}
+
+/// The level that the access log lines are emitted at
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AccessLogLevel {
+	/// Emit the access log lines at the trace level
+	Trace,
+	/// Emit the access log lines at the debug level
+	Debug,
+	/// Emit the access log lines at the info level
+	#[default]
+	Info,
+	/// Emit the access log lines at the warn level
+	Warn,
+	/// Emit the access log lines at the error level
+	Error,
+}
+
+/// The most verbose level that the logs of the API are emitted at. Logs that
+/// are more verbose than this level are dropped
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LogLevel {
+	/// Emit all logs
+	Trace,
+	/// Emit everything but the trace logs
+	Debug,
+	/// Emit the info, warn and error logs
+	Info,
+	/// Emit only the warn and error logs
+	Warn,
+	/// Emit only the error logs
+	Error,
+}
+
+/// The configuration for the backend that the values of secrets are stored in.
+/// The names of the secrets are always stored in the database, along with the
+/// rest of the resources of a workspace
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SecretsConfig {
+	/// The backend that the values of secrets are stored in
+	#[serde(default)]
+	pub backend: SecretStoreBackend,
+}
+
+/// The backend that the values of secrets are stored in
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum SecretStoreBackend {
+	/// The values are stored in the database, along with the names of the
+	/// secrets
+	#[default]
+	Database,
+	/// The values are stored in the KV (version 2) secrets engine of a
+	/// HashiCorp Vault server
+	#[serde(rename_all = "camelCase")]
+	Vault {
+		/// The address of the Vault server, for example
+		/// `https://vault.example.com:8200`
+		endpoint: String,
+		/// The token used to authenticate with the Vault server
+		token: String,
+		/// The path that the KV secrets engine is mounted at
+		#[serde(default = "default_vault_mount")]
+		mount: String,
+	},
+}
+
+/// The configuration for scanning the images of deployments for
+/// vulnerabilities
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageScanConfig {
+	/// The scanner that images are scanned with
+	#[serde(default)]
+	pub scanner: ImageScannerBackend,
+}
+
+/// The scanner that the images of deployments are scanned with
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ImageScannerBackend {
+	/// Images are not scanned. Every scan fails, so workspaces that block
+	/// images with critical vulnerabilities can't deploy anything
+	#[default]
+	Disabled,
+	/// Images are scanned by an HTTP service, which is sent the image to scan
+	/// and responds with the vulnerabilities that it found
+	#[serde(rename_all = "camelCase")]
+	Http {
+		/// The URL that the images to scan are sent to
+		endpoint: String,
+		/// The bearer token used to authenticate with the scanner, if it
+		/// needs one
+		#[serde(default)]
+		token: Option<String>,
+		/// The number of seconds after which a scan is considered to have
+		/// failed
+		#[serde(
+			default = "default_image_scan_timeout_seconds",
+			alias = "timeoutseconds"
+		)]
+		timeout_seconds: u64,
+	},
+}
+
+/// The default number of seconds after which a scan is considered to have
+/// failed
+const fn default_image_scan_timeout_seconds() -> u64 {
+	300
+}
+
+/// The default path that the KV secrets engine of Vault is mounted at
+fn default_vault_mount() -> String {
+	constants::DEFAULT_VAULT_KV_MOUNT.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn changed_settings_are_named_as_in_the_config_file() {
+		let current = ReloadableConfig::default();
+		assert!(current.changed_settings(&current.clone()).is_empty());
+
+		let mut reloaded = current.clone();
+		reloaded.logging.max_level = Some(LogLevel::Warn);
+		reloaded.signup_rate_limit.max_per_ip += 1;
+
+		assert_eq!(
+			current.changed_settings(&reloaded),
+			["logging.maxLevel", "signupRateLimit.maxPerIp"]
+		);
+	}
+
+	#[test]
+	fn at_least_one_recovery_method_must_be_enabled() {
+		assert!(RecoveryMethodsConfig::default().validate().is_ok());
+		assert!(RecoveryMethodsConfig {
+			email: false,
+			phone_number: true,
+		}
+		.validate()
+		.is_ok());
+		assert!(RecoveryMethodsConfig {
+			email: false,
+			phone_number: false,
+		}
+		.validate()
+		.is_err());
+	}
+
+	#[test]
+	fn only_enabled_recovery_methods_are_supported() {
+		let phone_only = RecoveryMethodsConfig {
+			email: false,
+			phone_number: true,
+		};
+
+		assert_eq!(phone_only.supported(), [RecoveryMethodType::PhoneNumber]);
+		assert_eq!(
+			phone_only.ensure_supported(RecoveryMethodType::PhoneNumber),
+			Ok(())
+		);
+		assert_eq!(
+			phone_only.ensure_supported(RecoveryMethodType::Email),
+			Err(ErrorType::RecoveryMethodUnavailable)
+		);
+	}
+
+	#[test]
+	fn long_poll_timeouts_are_capped_to_the_maximum() {
+		let config = LongPollConfig {
+			default_timeout_seconds: 25,
+			max_timeout_seconds: 30,
+			max_concurrent_polls: default_max_concurrent_polls(),
+		};
+
+		assert_eq!(config.timeout(None), std::time::Duration::from_secs(25));
+		assert_eq!(config.timeout(Some(0)), std::time::Duration::ZERO);
+		assert_eq!(config.timeout(Some(10)), std::time::Duration::from_secs(10));
+		assert_eq!(
+			config.timeout(Some(3600)),
+			std::time::Duration::from_secs(30)
+		);
+	}
+}
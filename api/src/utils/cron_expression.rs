@@ -0,0 +1,221 @@
+use std::{ops::RangeInclusive, str::FromStr};
+
+use time::OffsetDateTime;
+
+use crate::prelude::*;
+
+/// A parsed cron expression, in the standard 5-field format:
+/// `minute hour day-of-month month day-of-week`. Each field can be a `*`, a
+/// single value, a range (`1-5`), a step (`*/15` or `0-30/10`) or a
+/// comma-separated list of any of those. Months and days of the week can also
+/// be given by their three-letter names (`JAN`, `MON`, etc).
+///
+/// As with most cron implementations, if both the day of the month and the day
+/// of the week are restricted, the expression matches when either of them
+/// matches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CronExpression {
+	/// The minutes (0-59) that the expression matches, as a bitmask
+	minutes: u64,
+	/// The hours (0-23) that the expression matches, as a bitmask
+	hours: u64,
+	/// The days of the month (1-31) that the expression matches, as a bitmask
+	days_of_month: u64,
+	/// The months (1-12) that the expression matches, as a bitmask
+	months: u64,
+	/// The days of the week (0-6, starting on Sunday) that the expression
+	/// matches, as a bitmask
+	days_of_week: u64,
+	/// Whether the day of the month field was anything other than `*`
+	is_day_of_month_restricted: bool,
+	/// Whether the day of the week field was anything other than `*`
+	is_day_of_week_restricted: bool,
+}
+
+impl CronExpression {
+	/// Checks if the expression matches the minute of the given time. The time
+	/// should already be converted to the timezone that the expression is
+	/// meant to be evaluated in.
+	pub fn matches(&self, time: OffsetDateTime) -> bool {
+		let is_set = |mask: u64, value: u8| mask & (1 << value) != 0;
+
+		let day_of_month_matches = is_set(self.days_of_month, time.day());
+		let day_of_week_matches =
+			is_set(self.days_of_week, time.weekday().number_days_from_sunday());
+		let day_matches = if self.is_day_of_month_restricted && self.is_day_of_week_restricted {
+			day_of_month_matches || day_of_week_matches
+		} else {
+			day_of_month_matches && day_of_week_matches
+		};
+
+		is_set(self.minutes, time.minute()) &&
+			is_set(self.hours, time.hour()) &&
+			is_set(self.months, u8::from(time.month())) &&
+			day_matches
+	}
+}
+
+impl FromStr for CronExpression {
+	type Err = ErrorType;
+
+	fn from_str(expression: &str) -> Result<Self, Self::Err> {
+		const MONTHS: &[&str] = &[
+			"JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC",
+		];
+		const DAYS_OF_WEEK: &[&str] = &["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+		let [minute, hour, day_of_month, month, day_of_week] = expression
+			.split_whitespace()
+			.collect::<Vec<_>>()
+			.try_into()
+			.map_err(|_| ErrorType::InvalidCronExpression)?;
+
+		// Sunday can be given as both 0 and 7
+		let days_of_week = parse_field(day_of_week, 0..=7, DAYS_OF_WEEK, 0)?;
+		let days_of_week = (days_of_week | (days_of_week >> 7)) & 0x7f;
+
+		Ok(Self {
+			minutes: parse_field(minute, 0..=59, &[], 0)?,
+			hours: parse_field(hour, 0..=23, &[], 0)?,
+			days_of_month: parse_field(day_of_month, 1..=31, &[], 0)?,
+			months: parse_field(month, 1..=12, MONTHS, 1)?,
+			days_of_week,
+			is_day_of_month_restricted: day_of_month != "*",
+			is_day_of_week_restricted: day_of_week != "*",
+		})
+	}
+}
+
+/// Parses a single field of a cron expression into a bitmask of the values
+/// that it matches. `names` are the alternate names of the values, starting
+/// from `first_name_value`.
+fn parse_field(
+	field: &str,
+	range: RangeInclusive<u8>,
+	names: &[&str],
+	first_name_value: u8,
+) -> Result<u64, ErrorType> {
+	let parse_value = |value: &str| -> Result<u8, ErrorType> {
+		let value = names
+			.iter()
+			.position(|name| name.eq_ignore_ascii_case(value))
+			.and_then(|index| u8::try_from(index).ok())
+			.map(|index| index + first_name_value)
+			.map_or_else(|| value.parse::<u8>(), Ok)
+			.map_err(|_| ErrorType::InvalidCronExpression)?;
+
+		if range.contains(&value) {
+			Ok(value)
+		} else {
+			Err(ErrorType::InvalidCronExpression)
+		}
+	};
+
+	let mut mask = 0;
+	for part in field.split(',') {
+		let (values, step) = match part.split_once('/') {
+			Some((values, step)) => (
+				values,
+				step.parse::<u8>()
+					.ok()
+					.filter(|step| *step > 0)
+					.ok_or(ErrorType::InvalidCronExpression)?,
+			),
+			None => (part, 1),
+		};
+
+		let (start, end) = if values == "*" {
+			(*range.start(), *range.end())
+		} else if let Some((start, end)) = values.split_once('-') {
+			(parse_value(start)?, parse_value(end)?)
+		} else {
+			let value = parse_value(values)?;
+			// A step on a single value (`5/15`) runs until the end of the range
+			if step > 1 {
+				(value, *range.end())
+			} else {
+				(value, value)
+			}
+		};
+
+		if start > end {
+			return Err(ErrorType::InvalidCronExpression);
+		}
+
+		for value in (start..=end).step_by(usize::from(step)) {
+			mask |= 1 << value;
+		}
+	}
+
+	Ok(mask)
+}
+
+#[cfg(test)]
+mod tests {
+	use time::{Date, Month, OffsetDateTime, Time};
+
+	use super::CronExpression;
+
+	/// Returns the given time in January 2024, in UTC. 1st January 2024 is a
+	/// Monday
+	fn january(day: u8, hour: u8, minute: u8) -> OffsetDateTime {
+		Date::from_calendar_date(2024, Month::January, day)
+			.unwrap()
+			.with_time(Time::from_hms(hour, minute, 0).unwrap())
+			.assume_utc()
+	}
+
+	#[test]
+	fn matches_fixed_time() {
+		let expression = "30 9 * * MON-FRI".parse::<CronExpression>().unwrap();
+
+		// Monday
+		assert!(expression.matches(january(1, 9, 30)));
+		assert!(!expression.matches(january(1, 9, 31)));
+		// Sunday
+		assert!(!expression.matches(january(7, 9, 30)));
+	}
+
+	#[test]
+	fn matches_steps_and_lists() {
+		let expression = "*/15 0,12 * * *".parse::<CronExpression>().unwrap();
+
+		assert!(expression.matches(january(1, 0, 45)));
+		assert!(expression.matches(january(1, 12, 0)));
+		assert!(!expression.matches(january(1, 12, 10)));
+		assert!(!expression.matches(january(1, 6, 0)));
+	}
+
+	#[test]
+	fn matches_either_day_when_both_are_restricted() {
+		let expression = "0 0 1 * 7".parse::<CronExpression>().unwrap();
+
+		// 1st of the month, a Monday
+		assert!(expression.matches(january(1, 0, 0)));
+		// A Sunday
+		assert!(expression.matches(january(7, 0, 0)));
+		assert!(!expression.matches(january(2, 0, 0)));
+	}
+
+	#[test]
+	fn rejects_invalid_expressions() {
+		for expression in [
+			"",
+			"* * * *",
+			"* * * * * *",
+			"60 * * * *",
+			"* 24 * * *",
+			"* * 0 * *",
+			"* * * 13 *",
+			"* * * * 8",
+			"5-1 * * * *",
+			"*/0 * * * *",
+			"foo * * * *",
+		] {
+			assert!(
+				expression.parse::<CronExpression>().is_err(),
+				"`{expression}` should be invalid"
+			);
+		}
+	}
+}
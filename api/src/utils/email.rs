@@ -0,0 +1,72 @@
+use std::sync::OnceLock;
+
+use lettre::{
+	message::{header::ContentType, Mailbox},
+	transport::smtp::authentication::Credentials,
+	AsyncSmtpTransport,
+	AsyncTransport,
+	Message,
+	Tokio1Executor,
+};
+
+use crate::{prelude::*, utils::config::EmailConfig};
+
+/// The SMTP transport that is shared by the whole API, created on first use.
+/// It keeps a pool of connections to the SMTP server, instead of connecting
+/// again for every email.
+static TRANSPORT: OnceLock<AsyncSmtpTransport<Tokio1Executor>> = OnceLock::new();
+
+/// Gets the SMTP transport for the given configuration, creating it if it
+/// doesn't exist yet
+fn transport(
+	config: &EmailConfig,
+) -> Result<&'static AsyncSmtpTransport<Tokio1Executor>, ErrorType> {
+	if let Some(transport) = TRANSPORT.get() {
+		return Ok(transport);
+	}
+
+	let builder = if config.secure {
+		AsyncSmtpTransport::<Tokio1Executor>::relay(&config.host)
+			.map_err(ErrorType::server_error)?
+	} else {
+		AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&config.host)
+	};
+	let transport = builder
+		.port(config.port)
+		.credentials(Credentials::new(
+			config.username.clone(),
+			config.password.clone(),
+		))
+		.build();
+
+	Ok(TRANSPORT.get_or_init(|| transport))
+}
+
+/// Sends a plain text email to the given address, from the address in the
+/// configuration
+pub async fn send_email(
+	config: &EmailConfig,
+	to: &str,
+	subject: &str,
+	body: String,
+) -> Result<(), ErrorType> {
+	let message = Message::builder()
+		.from(
+			config
+				.from
+				.parse::<Mailbox>()
+				.map_err(ErrorType::server_error)?,
+		)
+		.to(to.parse::<Mailbox>().map_err(ErrorType::server_error)?)
+		.subject(subject)
+		.header(ContentType::TEXT_PLAIN)
+		.body(body)
+		.map_err(ErrorType::server_error)?;
+
+	transport(config)?
+		.send(message)
+		.await
+		.map_err(ErrorType::server_error)?;
+
+	Ok(())
+}
@@ -0,0 +1,11 @@
+use std::sync::OnceLock;
+
+/// The HTTP client that is shared by the whole API, for the requests it makes
+/// to other services. Reusing the client keeps the connections to those
+/// services pooled, instead of making a new connection for every request.
+pub fn http_client() -> &'static reqwest::Client {
+	/// The client, created on first use
+	static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+
+	CLIENT.get_or_init(reqwest::Client::new)
+}
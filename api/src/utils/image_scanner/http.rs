@@ -0,0 +1,90 @@
+use std::time::Duration;
+
+use models::api::workspace::deployment::image_scan::ImageVulnerability;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+use super::{ImageScanner, ScannedImage};
+use crate::prelude::*;
+
+/// An [`ImageScanner`] that sends the image to scan to an HTTP service, which
+/// pulls and scans the image and responds with the vulnerabilities it found.
+/// This can be a thin wrapper around any scanner, such as Trivy or Grype.
+pub struct HttpImageScanner {
+	/// The client used to make requests to the scanner
+	client: Client,
+	/// The URL that the images to scan are sent to
+	endpoint: String,
+	/// The bearer token used to authenticate with the scanner
+	token: Option<String>,
+	/// The time after which a scan is considered to have failed
+	timeout: Duration,
+}
+
+/// The body sent to the scanner
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ScanRequest<'a> {
+	/// The image to scan, including its tag
+	image: &'a str,
+}
+
+/// The response of the scanner
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ScanResponse {
+	/// The digest of the image that was pulled and scanned, if the scanner
+	/// reports it
+	#[serde(default)]
+	digest: Option<String>,
+	/// The vulnerabilities found in the image
+	vulnerabilities: Vec<ImageVulnerability>,
+}
+
+impl HttpImageScanner {
+	/// Creates a new [`HttpImageScanner`] that sends images to the given URL
+	pub fn new(endpoint: &str, token: Option<&str>, timeout_seconds: u64) -> Self {
+		Self {
+			client: Client::new(),
+			endpoint: endpoint.to_string(),
+			token: token.map(str::to_string),
+			timeout: Duration::from_secs(timeout_seconds),
+		}
+	}
+}
+
+impl ImageScanner for HttpImageScanner {
+	async fn scan(&self, image: &str) -> Result<ScannedImage, ErrorType> {
+		let mut request = self
+			.client
+			.post(&self.endpoint)
+			.timeout(self.timeout)
+			.json(&ScanRequest { image });
+		if let Some(token) = &self.token {
+			request = request.bearer_auth(token);
+		}
+
+		let ScanResponse {
+			digest,
+			vulnerabilities,
+		} = request
+			.send()
+			.await
+			.and_then(|response| response.error_for_status())
+			.map_err(|err| {
+				warn!("Unable to scan image `{image}`: {err}");
+				ErrorType::ImageScanFailed
+			})?
+			.json()
+			.await
+			.map_err(|err| {
+				warn!("Invalid response from the scanner for image `{image}`: {err}");
+				ErrorType::ImageScanFailed
+			})?;
+
+		Ok(ScannedImage {
+			digest,
+			vulnerabilities,
+		})
+	}
+}
@@ -0,0 +1,73 @@
+use std::future::Future;
+
+use models::api::workspace::deployment::image_scan::ImageVulnerability;
+
+use crate::{
+	prelude::*,
+	utils::config::{ImageScanConfig, ImageScannerBackend},
+};
+
+/// The scanner that sends images to be scanned to an HTTP service
+mod http;
+/// The scanner that is used when scanning is disabled
+mod noop;
+
+pub use self::{http::HttpImageScanner, noop::NoopImageScanner};
+
+/// A scanner that finds the vulnerabilities in the image of a deployment. The
+/// scanner only reports what it finds. Storing the results and enforcing the
+/// policy of the workspace is done by the API.
+///
+/// Scans can take minutes, so they must never be run while a database
+/// transaction is open. They are run by the
+/// [`deployment_image_scanner`][crate::deployment_image_scanner] instead.
+pub trait ImageScanner {
+	/// Scans the given image, including its tag or digest, and returns what
+	/// was found in it
+	fn scan(&self, image: &str) -> impl Future<Output = Result<ScannedImage, ErrorType>> + Send;
+}
+
+/// The result of scanning an image
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScannedImage {
+	/// The digest of the image that was scanned, if the scanner reported it.
+	/// This is the image that the tag pointed to when it was scanned
+	pub digest: Option<String>,
+	/// The vulnerabilities found in the image
+	pub vulnerabilities: Vec<ImageVulnerability>,
+}
+
+/// The [`ImageScanner`] that is selected by the configuration of the API.
+pub enum AppImageScanner {
+	/// Images are not scanned
+	Noop(NoopImageScanner),
+	/// Images are scanned by an HTTP service
+	Http(HttpImageScanner),
+}
+
+impl AppImageScanner {
+	/// Creates the image scanner that is selected by the configuration
+	pub fn new(config: &ImageScanConfig) -> Self {
+		match &config.scanner {
+			ImageScannerBackend::Disabled => Self::Noop(NoopImageScanner),
+			ImageScannerBackend::Http {
+				endpoint,
+				token,
+				timeout_seconds,
+			} => Self::Http(HttpImageScanner::new(
+				endpoint,
+				token.as_deref(),
+				*timeout_seconds,
+			)),
+		}
+	}
+}
+
+impl ImageScanner for AppImageScanner {
+	async fn scan(&self, image: &str) -> Result<ScannedImage, ErrorType> {
+		match self {
+			Self::Noop(scanner) => scanner.scan(image).await,
+			Self::Http(scanner) => scanner.scan(image).await,
+		}
+	}
+}
@@ -0,0 +1,14 @@
+use super::{ImageScanner, ScannedImage};
+use crate::prelude::*;
+
+/// An [`ImageScanner`] that is used when scanning is disabled. Every scan
+/// fails, so that workspaces that block images with critical vulnerabilities
+/// can't deploy images that were never scanned.
+pub struct NoopImageScanner;
+
+impl ImageScanner for NoopImageScanner {
+	async fn scan(&self, image: &str) -> Result<ScannedImage, ErrorType> {
+		debug!("Image scanning is disabled. Not scanning `{image}`");
+		Err(ErrorType::ImageScanFailed)
+	}
+}
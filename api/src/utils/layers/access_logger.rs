@@ -0,0 +1,365 @@
+use std::{
+	convert::Infallible,
+	future::Future,
+	marker::PhantomData,
+	sync::{Arc, OnceLock},
+	task::{Context, Poll},
+	time::Instant,
+};
+
+use arc_swap::ArcSwap;
+use axum::{
+	body::Body,
+	http::{HeaderValue, Request},
+	response::Response,
+	RequestExt,
+};
+use preprocess::Preprocessable;
+use tower::{Layer, Service};
+use tracing::Level;
+
+use crate::{
+	prelude::*,
+	utils::{
+		config::{AccessLogLevel, ReloadableConfig},
+		extractors::ClientIP,
+	},
+};
+
+/// Emits an access log event at the given (runtime) level. The level of a
+/// tracing event must be known at compile time, hence the match.
+macro_rules! access_log {
+	($level:expr, $($args:tt)+) => {
+		match $level {
+			AccessLogLevel::Trace => tracing::event!(target: "access_log", Level::TRACE, $($args)+),
+			AccessLogLevel::Debug => tracing::event!(target: "access_log", Level::DEBUG, $($args)+),
+			AccessLogLevel::Info => tracing::event!(target: "access_log", Level::INFO, $($args)+),
+			AccessLogLevel::Warn => tracing::event!(target: "access_log", Level::WARN, $($args)+),
+			AccessLogLevel::Error => tracing::event!(target: "access_log", Level::ERROR, $($args)+),
+		}
+	};
+}
+
+tokio::task_local! {
+	/// The login ID of the request that is currently being handled, set by the
+	/// [`AuthenticationLayer`][1] once the request is authenticated so that
+	/// it can be included in the access log.
+	///
+	/// [1]: super::AuthenticationLayer
+	static ACCESS_LOG_LOGIN_ID: OnceLock<Uuid>;
+}
+
+/// Records the login ID of the request that is currently being handled, so
+/// that it is included in its access log line. Does nothing if the request is
+/// not being logged.
+pub fn record_access_log_login_id(login_id: Uuid) {
+	_ = ACCESS_LOG_LOGIN_ID.try_with(|cell| cell.set(login_id));
+}
+
+/// The [`tower::Layer`] used to emit a structured access log line for every
+/// request made to an endpoint, containing the method, endpoint, status code,
+/// duration, login ID, client IP and request ID of the request. Requests that
+/// fail with an [`ErrorType`] are logged along with the error. The request ID
+/// is taken from the `X-Request-ID` header of the request if present, and is
+/// generated otherwise. Either way, it is sent back in the response headers.
+pub struct AccessLoggerLayer<E> {
+	/// The reloadable configuration, which contains that of the access log
+	config: Arc<ArcSwap<ReloadableConfig>>,
+	/// The endpoint that is being logged
+	endpoint: PhantomData<E>,
+}
+
+impl<E> AccessLoggerLayer<E>
+where
+	E: ApiEndpoint,
+	<E::RequestBody as Preprocessable>::Processed: Send,
+{
+	/// Create a new instance of the [`AccessLoggerLayer`] with the given
+	/// configuration. The configuration of the access log is read for every
+	/// request, so that it can be reloaded without restarting the API.
+	pub fn new(config: Arc<ArcSwap<ReloadableConfig>>) -> Self {
+		Self {
+			config,
+			endpoint: PhantomData,
+		}
+	}
+}
+
+impl<E> Clone for AccessLoggerLayer<E> {
+	fn clone(&self) -> Self {
+		Self {
+			config: self.config.clone(),
+			endpoint: PhantomData,
+		}
+	}
+}
+
+impl<S, E> Layer<S> for AccessLoggerLayer<E>
+where
+	E: ApiEndpoint,
+	<E::RequestBody as Preprocessable>::Processed: Send,
+{
+	type Service = AccessLoggerService<S, E>;
+
+	fn layer(&self, inner: S) -> Self::Service {
+		AccessLoggerService {
+			inner,
+			config: self.config.clone(),
+			endpoint: PhantomData,
+		}
+	}
+}
+
+/// The underlying service that runs when the [`AccessLoggerLayer`] is used.
+pub struct AccessLoggerService<S, E> {
+	/// The inner service that will be called with the request
+	inner: S,
+	/// The reloadable configuration, which contains that of the access log
+	config: Arc<ArcSwap<ReloadableConfig>>,
+	/// The endpoint that is being logged
+	endpoint: PhantomData<E>,
+}
+
+impl<S, E> Clone for AccessLoggerService<S, E>
+where
+	S: Clone,
+{
+	fn clone(&self) -> Self {
+		Self {
+			inner: self.inner.clone(),
+			config: self.config.clone(),
+			endpoint: PhantomData,
+		}
+	}
+}
+
+impl<S, E> Service<Request<Body>> for AccessLoggerService<S, E>
+where
+	S: Service<Request<Body>, Response = Response, Error = Infallible> + Clone + Send + 'static,
+	S::Future: Send,
+	E: ApiEndpoint,
+	<E::RequestBody as Preprocessable>::Processed: Send,
+{
+	type Error = Infallible;
+	type Response = Response;
+
+	type Future = impl Future<Output = Result<Self::Response, Self::Error>>;
+
+	fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+		self.inner.poll_ready(cx)
+	}
+
+	#[instrument(skip(self, req), name = "AccessLoggerService")]
+	fn call(&mut self, mut req: Request<Body>) -> Self::Future {
+		let mut inner = self.inner.clone();
+		let config = self.config.load().logging.clone();
+
+		async move {
+			let method = req.method().clone();
+			let Ok(ClientIP(client_ip)) = req.extract_parts().await;
+			let request_id = req
+				.headers()
+				.get(constants::REQUEST_ID_HEADER)
+				.and_then(|value| value.to_str().ok())
+				.filter(|value| !value.is_empty() && value.len() <= 128)
+				.map(String::from)
+				.unwrap_or_else(|| Uuid::new_v4().to_string());
+
+			let start = Instant::now();
+			let login_id = OnceLock::new();
+			let (response, login_id) = ACCESS_LOG_LOGIN_ID
+				.scope(login_id, async move {
+					let response = inner.call(req).await;
+					let login_id = ACCESS_LOG_LOGIN_ID.with(|login_id| login_id.get().copied());
+					(response, login_id)
+				})
+				.await;
+			let Ok(mut response) = response;
+			let duration_millis = start.elapsed().as_secs_f64() * 1000.0;
+
+			if let Ok(value) = HeaderValue::from_str(&request_id) {
+				response
+					.headers_mut()
+					.insert(constants::REQUEST_ID_HEADER, value);
+			}
+
+			let endpoint = endpoint_name::<E>();
+			let status = response.status().as_u16();
+			let error = response.extensions().get::<ErrorType>().copied();
+
+			if config.json {
+				let line = serde_json::json!({
+					"method": method.as_str(),
+					"endpoint": endpoint,
+					"status": status,
+					"durationMillis": duration_millis,
+					"loginId": login_id,
+					"clientIp": client_ip,
+					"requestId": request_id,
+					"error": error,
+				});
+				access_log!(config.level, "{}", line);
+			} else {
+				access_log!(
+					config.level,
+					method = %method,
+					endpoint,
+					status,
+					duration_millis,
+					login_id = ?login_id,
+					client_ip = %client_ip,
+					request_id = %request_id,
+					error = ?error,
+					"{} {} {}",
+					method,
+					endpoint,
+					status
+				);
+			}
+
+			Ok(response)
+		}
+	}
+}
+
+/// Returns a stable name for the endpoint, taken from the name of its type
+/// without the module path and the `Request` suffix. For example,
+/// `CreateDeploymentRequest` is logged as `CreateDeployment`.
+fn endpoint_name<E>() -> &'static str {
+	let name = std::any::type_name::<E>();
+	let name = name.rsplit("::").next().unwrap_or(name);
+	name.strip_suffix("Request").unwrap_or(name)
+}
+
+#[cfg(test)]
+mod tests {
+	use std::{
+		io,
+		net::SocketAddr,
+		sync::{Arc, Mutex},
+	};
+
+	use axum::extract::ConnectInfo;
+	use models::api::auth::LoginRequest;
+	use tower::{service_fn, ServiceExt};
+
+	use super::*;
+
+	/// Collects everything that is logged, so that the access log lines can be
+	/// checked
+	#[derive(Clone, Default)]
+	struct CapturedLogs(Arc<Mutex<Vec<u8>>>);
+
+	impl io::Write for CapturedLogs {
+		fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+			self.0.lock().unwrap().extend_from_slice(buf);
+			Ok(buf.len())
+		}
+
+		fn flush(&mut self) -> io::Result<()> {
+			Ok(())
+		}
+	}
+
+	impl CapturedLogs {
+		/// Returns the JSON access log lines that were logged so far
+		fn access_log_lines(&self) -> Vec<serde_json::Value> {
+			String::from_utf8(self.0.lock().unwrap().clone())
+				.unwrap()
+				.lines()
+				.filter(|line| line.contains("access_log"))
+				.filter_map(|line| line.find("{\"").map(|start| &line[start..]))
+				.map(|line| serde_json::from_str(line).unwrap())
+				.collect()
+		}
+	}
+
+	/// Sends a request with the given request ID header through the
+	/// [`AccessLoggerLayer`], to a handler that authenticates the request as
+	/// the given login and fails with the given error. Returns the response
+	/// and the access log lines that were logged.
+	async fn logged_request(
+		request_id: Option<&str>,
+		login_id: Uuid,
+		error: ErrorType,
+	) -> (Response, Vec<serde_json::Value>) {
+		let mut config = ReloadableConfig::default();
+		config.logging.json = true;
+		let layer = AccessLoggerLayer::<LoginRequest>::new(Arc::new(ArcSwap::from_pointee(config)));
+		let service = layer.layer(service_fn(move |_: Request<Body>| async move {
+			record_access_log_login_id(login_id);
+			let mut response = Response::new(Body::empty());
+			response.extensions_mut().insert(error);
+			Ok::<_, Infallible>(response)
+		}));
+
+		let mut request = Request::builder()
+			.uri("/auth/sign-in")
+			.method("POST")
+			.extension(ConnectInfo(SocketAddr::from(([10, 0, 0, 1], 4000))));
+		if let Some(request_id) = request_id {
+			request = request.header(constants::REQUEST_ID_HEADER, request_id);
+		}
+
+		let logs = CapturedLogs::default();
+		let subscriber = tracing_subscriber::fmt()
+			.with_max_level(Level::TRACE)
+			.with_ansi(false)
+			.with_writer({
+				let logs = logs.clone();
+				move || logs.clone()
+			})
+			.finish();
+		let _guard = tracing::subscriber::set_default(subscriber);
+
+		let Ok(response) = service.oneshot(request.body(Body::empty()).unwrap()).await;
+		(response, logs.access_log_lines())
+	}
+
+	#[tokio::test]
+	async fn requests_are_logged_with_their_login_and_error() {
+		let login_id = Uuid::new_v4();
+		let (response, lines) =
+			logged_request(Some("some-request"), login_id, ErrorType::WrongParameters).await;
+
+		assert_eq!(
+			response.headers()[constants::REQUEST_ID_HEADER],
+			"some-request"
+		);
+		assert_eq!(lines.len(), 1);
+		assert_eq!(lines[0]["method"], "POST");
+		assert_eq!(lines[0]["endpoint"], "Login");
+		assert_eq!(lines[0]["status"], 200);
+		assert_eq!(lines[0]["loginId"], login_id.to_string());
+		assert_eq!(lines[0]["clientIp"], "10.0.0.1");
+		assert_eq!(lines[0]["requestId"], "some-request");
+		assert_eq!(lines[0]["error"], "wrongParameters");
+	}
+
+	#[tokio::test]
+	async fn request_ids_are_generated_when_missing_or_too_long() {
+		let too_long = "a".repeat(129);
+		for request_id in [None, Some(""), Some(too_long.as_str())] {
+			let (response, lines) =
+				logged_request(request_id, Uuid::new_v4(), ErrorType::WrongParameters).await;
+
+			let generated = response.headers()[constants::REQUEST_ID_HEADER]
+				.to_str()
+				.unwrap();
+			assert!(Uuid::parse_str(generated).is_ok());
+			assert_eq!(lines[0]["requestId"], generated);
+		}
+	}
+
+	#[test]
+	fn endpoints_are_named_after_their_request_type() {
+		assert_eq!(endpoint_name::<LoginRequest>(), "Login");
+		assert_eq!(endpoint_name::<String>(), "String");
+	}
+
+	#[test]
+	fn login_ids_are_ignored_outside_of_a_logged_request() {
+		record_access_log_login_id(Uuid::new_v4());
+		assert!(ACCESS_LOG_LOGIN_ID.try_with(|_| ()).is_err());
+	}
+}
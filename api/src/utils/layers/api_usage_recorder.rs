@@ -0,0 +1,188 @@
+use std::{
+	convert::Infallible,
+	future::Future,
+	sync::Arc,
+	task::{Context, Poll},
+	time::Instant,
+};
+
+use axum::{
+	body::Body,
+	extract::RawPathParams,
+	http::{Method, Request, StatusCode},
+	response::Response,
+	RequestExt,
+};
+use rustis::{
+	client::Client as RedisClient,
+	commands::{ExpireOption, GenericCommands, ListCommands, SetCommands},
+};
+use time::OffsetDateTime;
+use tower::{Layer, Service};
+
+use crate::{models::redis::ApiUsageEvent, prelude::*};
+
+/// The [`tower::Layer`] used to record the usage of an API endpoint by a
+/// workspace. Every request made to an endpoint that has a `workspace_id` in
+/// its path is recorded to Redis, bucketed by time, so that the
+/// [`api_usage_rollup`][1] task can aggregate them into the database. Requests
+/// that are rejected for being unauthenticated are not recorded, so that a
+/// user cannot pollute the usage of a workspace they are not a part of. `HEAD`
+/// requests are not recorded either.
+///
+/// [1]: crate::api_usage_rollup
+#[derive(Clone)]
+pub struct ApiUsageRecorderLayer {
+	/// The Redis client used to store the usage events
+	redis: RedisClient,
+	/// The endpoint that is being recorded, in the format `METHOD /path`
+	endpoint: Arc<str>,
+}
+
+impl ApiUsageRecorderLayer {
+	/// Create a new instance of the [`ApiUsageRecorderLayer`] for the given
+	/// endpoint
+	pub fn new(redis: RedisClient, endpoint: impl Into<Arc<str>>) -> Self {
+		Self {
+			redis,
+			endpoint: endpoint.into(),
+		}
+	}
+}
+
+impl<S> Layer<S> for ApiUsageRecorderLayer {
+	type Service = ApiUsageRecorderService<S>;
+
+	fn layer(&self, inner: S) -> Self::Service {
+		ApiUsageRecorderService {
+			inner,
+			redis: self.redis.clone(),
+			endpoint: self.endpoint.clone(),
+		}
+	}
+}
+
+/// The underlying service that runs when the [`ApiUsageRecorderLayer`] is
+/// used.
+#[derive(Clone)]
+pub struct ApiUsageRecorderService<S> {
+	/// The inner service that will be called with the request
+	inner: S,
+	/// The Redis client used to store the usage events
+	redis: RedisClient,
+	/// The endpoint that is being recorded, in the format `METHOD /path`
+	endpoint: Arc<str>,
+}
+
+impl<S> Service<Request<Body>> for ApiUsageRecorderService<S>
+where
+	S: Service<Request<Body>, Response = Response, Error = Infallible> + Clone + Send + 'static,
+	S::Future: Send,
+{
+	type Error = Infallible;
+	type Response = Response;
+
+	type Future = impl Future<Output = Result<Self::Response, Self::Error>>;
+
+	fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+		self.inner.poll_ready(cx)
+	}
+
+	#[instrument(skip(self, req), name = "ApiUsageRecorderService")]
+	fn call(&mut self, mut req: Request<Body>) -> Self::Future {
+		let mut inner = self.inner.clone();
+		let redis = self.redis.clone();
+		let endpoint = self.endpoint.clone();
+
+		async move {
+			// HEAD requests don't have any side effects, including being
+			// recorded as usage
+			if req.method() == Method::HEAD {
+				return inner.call(req).await;
+			}
+
+			let workspace_id = req
+				.extract_parts::<RawPathParams>()
+				.await
+				.ok()
+				.and_then(|params| {
+					params
+						.iter()
+						.find(|(key, _)| *key == "workspace_id")
+						.and_then(|(_, value)| Uuid::parse_str(value).ok())
+				});
+
+			let start = Instant::now();
+			let response = inner.call(req).await?;
+			let latency_millis = u64::try_from(start.elapsed().as_millis()).unwrap_or(u64::MAX);
+
+			let Some(workspace_id) = workspace_id else {
+				return Ok(response);
+			};
+
+			if matches!(
+				response.status(),
+				StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN
+			) {
+				return Ok(response);
+			}
+
+			let event = ApiUsageEvent {
+				workspace_id,
+				endpoint: endpoint.to_string(),
+				status_code: response.status().as_u16(),
+				latency_millis,
+			};
+
+			// Recording the usage must never slow down or fail the request
+			tokio::spawn(async move {
+				if let Err(err) = record_usage_event(&redis, event).await {
+					warn!("Failed to record API usage: {err}");
+				}
+			});
+
+			Ok(response)
+		}
+	}
+}
+
+/// Pushes the usage event into the Redis list of the current time bucket, and
+/// marks the bucket as pending so that the rollup task picks it up.
+async fn record_usage_event(redis: &RedisClient, event: ApiUsageEvent) -> Result<(), ErrorType> {
+	let bucket_start = api_usage_bucket_start(OffsetDateTime::now_utc());
+	let bucket_key = redis::keys::api_usage_bucket(bucket_start);
+
+	redis
+		.rpush(
+			&bucket_key,
+			serde_json::to_string(&event).map_err(ErrorType::server_error)?,
+		)
+		.await
+		.map_err(ErrorType::server_error)?;
+	// In case the rollup task isn't running, make sure the events don't pile up
+	redis
+		.expire(
+			&bucket_key,
+			constants::API_USAGE_EVENT_RETENTION
+				.whole_seconds()
+				.unsigned_abs(),
+			ExpireOption::None,
+		)
+		.await
+		.map_err(ErrorType::server_error)?;
+	redis
+		.sadd(redis::keys::api_usage_pending_buckets(), bucket_start)
+		.await
+		.map_err(ErrorType::server_error)?;
+
+	Ok(())
+}
+
+/// Returns the start of the usage bucket (as a unix timestamp) that the given
+/// time falls in
+pub fn api_usage_bucket_start(time: OffsetDateTime) -> i64 {
+	let timestamp = time.unix_timestamp();
+	let bucket_size = constants::API_USAGE_BUCKET_SIZE.whole_seconds();
+
+	timestamp - timestamp.rem_euclid(bucket_size)
+}
@@ -7,23 +7,32 @@ use std::{
 };
 
 use argon2::{Algorithm, Argon2, PasswordHash, PasswordVerifier, Version};
+use axum::http::Method;
+use axum_extra::routing::TypedPath;
 use jsonwebtoken::{DecodingKey, TokenData, Validation};
 use models::{
 	rbac::{ResourcePermissionType, WorkspacePermission},
-	utils::{AppAuthentication, BearerToken, HasHeader},
+	utils::{ApiToken, AppAuthentication, BearerToken, HasHeader},
 	RequestUserData,
 };
 use preprocess::Preprocessable;
-use rustis::{
-	client::Client as RedisClient,
-	commands::{GenericCommands, StringCommands},
-};
+use rustis::{client::Client as RedisClient, commands::StringCommands};
 use time::OffsetDateTime;
 use tower::{Layer, Service};
 
 use crate::{
-	models::{access_token_data::AccessTokenData, redis::UserPermissionCache},
+	models::{
+		access_token_data::AccessTokenData,
+		auth_audit_log::{AuthAuditLogEntry, AuthAuditLogEvent},
+		redis::UserPermissionCache,
+	},
 	prelude::*,
+	utils::{
+		config::PermissionCacheConfig,
+		layers::record_access_log_login_id,
+		Clock,
+		SingleFlight,
+	},
 };
 
 /// The type of client used for a request. This is used to determine
@@ -53,6 +62,9 @@ where
 {
 	/// The type of client that is allowed to make the request
 	client_type: ClientType,
+	/// The state of the application, used to reload cached permissions in the
+	/// background, outside of the request's transaction
+	state: AppState,
 	/// The endpoint type that this layer will handle
 	endpoint: PhantomData<E>,
 }
@@ -63,10 +75,11 @@ where
 	<E::RequestBody as Preprocessable>::Processed: Send,
 {
 	/// Helper function to initialize an authentication layer
-	pub fn new(client_type: ClientType) -> Self {
+	pub fn new(client_type: ClientType, state: AppState) -> Self {
 		Self {
 			endpoint: PhantomData,
 			client_type,
+			state,
 		}
 	}
 }
@@ -83,6 +96,7 @@ where
 		AuthenticationService {
 			inner,
 			client_type: self.client_type,
+			state: self.state.clone(),
 			authenticator: PhantomData,
 			endpoint: PhantomData,
 		}
@@ -98,6 +112,7 @@ where
 		Self {
 			endpoint: PhantomData,
 			client_type: self.client_type,
+			state: self.state.clone(),
 		}
 	}
 }
@@ -112,6 +127,9 @@ where
 	inner: S,
 	/// The type of client that is allowed to make the request
 	client_type: ClientType,
+	/// The state of the application, used to reload cached permissions in the
+	/// background, outside of the request's transaction
+	state: AppState,
 	/// The type of authenticator that will be used to authenticate the request
 	authenticator: PhantomData<A>,
 	/// The endpoint type that this layer will handle
@@ -139,6 +157,7 @@ where
 	fn call(&mut self, req: AppRequest<'a, E>) -> Self::Future {
 		let mut inner = self.inner.clone();
 		let client_type = self.client_type;
+		let state = self.state.clone();
 		async move {
 			trace!("Authenticating request");
 			let BearerToken(token) = req.request.headers.get_header();
@@ -147,34 +166,14 @@ where
 			let user_data = match client_type {
 				ClientType::ApiToken => {
 					trace!("Parsing authentication header as an API token");
-					let (refresh_token, login_id) = token
-						.strip_prefix("patrv1.")
-						.ok_or_else(|| {
-							warn!("Invalid API token provided: {}", token);
-							ErrorType::MalformedApiToken
-						})?
-						.split_once('.')
-						.ok_or_else(|| {
-							warn!("Invalid API token provided: {}", token);
-							ErrorType::MalformedApiToken
-						})?;
-
-					let refresh_token = Uuid::parse_str(refresh_token).map_err(|err| {
-						warn!("Invalid API token provided: {}", token);
-						warn!(
-							"Cannot parse refresh token `{}` as UUID: {}",
-							refresh_token, err
-						);
-						ErrorType::MalformedApiToken
-					})?;
-					trace!("Refresh token parsed as UUID");
-
-					let login_id = Uuid::parse_str(login_id).map_err(|err| {
-						warn!("Invalid API token provided: {}", token);
-						warn!("Cannot parse loginId `{}` as UUID: {}", login_id, err);
-						ErrorType::MalformedApiToken
+					let ApiToken {
+						version,
+						refresh_token,
+						login_id,
+					} = ApiToken::parse(token).inspect_err(|err| {
+						warn!("Invalid API token provided: {err}");
 					})?;
-					trace!("Login ID parsed as UUID");
+					trace!("API token parsed as {version:?}");
 
 					info!("Extracting information about API token");
 					let Some(token) = query!(
@@ -208,44 +207,32 @@ where
 					.await?
 					else {
 						warn!("API token not found");
+						// Verify against a decoy hash anyway, so that a token that doesn't exist
+						// takes as long to reject as one with an invalid refresh token
+						verify_api_token_hash(&req.config.password_pepper, &refresh_token, None)?;
 						// No specific error for API token not found, since we don't want to leak
 						// information about whether a loginId is valid or if it's expired
 						return Err(ErrorType::AuthorizationTokenInvalid);
 					};
 					trace!("Token extracted from database");
 
-					if let Some(nbf) = token.token_nbf {
-						trace!("Token has an NBF");
-						if OffsetDateTime::now_utc() < nbf {
-							info!("API token is not valid yet");
-							return Err(ErrorType::AuthorizationTokenInvalid);
-						}
-					} else {
-						trace!("Token does not have an NBF");
-					}
-					trace!("Token passed NBF check");
-
-					if let Some(exp) = token.token_exp {
-						trace!("Token has an EXP");
-						if OffsetDateTime::now_utc() > exp {
-							info!("API token has expired");
-							return Err(ErrorType::AuthorizationTokenInvalid);
-						}
-					} else {
-						trace!("Token does not have an EXP");
+					let verification = verify_api_token_hash(
+						&req.config.password_pepper,
+						&refresh_token,
+						Some(&token.token_hash),
+					)?;
+					if verification != ApiTokenHashVerification::Valid {
+						warn!("API token has invalid refresh token");
+						return Err(ErrorType::AuthorizationTokenInvalid);
 					}
-					trace!("Token passed EXP check");
+					info!("API token valid");
 
-					if let Some(revoked) = token.revoked {
-						trace!("Token has a revoked timestamp");
-						if OffsetDateTime::now_utc() > revoked {
-							info!("API token has been revoked");
-							return Err(ErrorType::AuthorizationTokenInvalid);
-						}
-					} else {
-						trace!("Token does not have a revoked timestamp");
-					}
-					trace!("Token passed revoked timestamp check");
+					validate_api_token_time(
+						&req.clock,
+						token.token_nbf,
+						token.token_exp,
+						token.revoked,
+					)?;
 
 					if let Some(allowed_ips) = token.allowed_ips {
 						if !allowed_ips
@@ -257,27 +244,10 @@ where
 						}
 					}
 
-					let Ok(password_hash) = PasswordHash::new(&token.token_hash) else {
-						error!("Unable to parse password hash: {}", token.token_hash);
-						return Err(ErrorType::server_error("password hash parsing failed"));
-					};
-					let success = Argon2::new_with_secret(
-						req.config.password_pepper.as_bytes(),
-						Algorithm::Argon2id,
-						Version::V0x13,
-						constants::HASHING_PARAMS,
-					)
-					.map_err(ErrorType::server_error)?
-					.verify_password(refresh_token.as_bytes(), &password_hash)
-					.is_ok();
-
-					if !success {
-						warn!("API token has invalid refresh token");
-						return Err(ErrorType::AuthorizationTokenInvalid);
-					}
-					info!("API token valid");
+					record_login_activity(&state, client_type, login_id, req.clock.now());
 
 					let permissions = get_permissions_for_login_id(
+						&state,
 						req.database,
 						req.redis,
 						&login_id,
@@ -298,19 +268,7 @@ where
 				ClientType::WebDashboard => {
 					trace!("Parsing authentication header as a JWT");
 
-					let TokenData {
-						header: _,
-						claims:
-							AccessTokenData {
-								iss,
-								sub,
-								aud,
-								exp,
-								nbf,
-								iat: _,
-								jti,
-							},
-					} = jsonwebtoken::decode(
+					let TokenData { header: _, claims } = jsonwebtoken::decode::<AccessTokenData>(
 						token,
 						&DecodingKey::from_secret(req.config.jwt_secret.as_ref()),
 						&{
@@ -330,40 +288,34 @@ where
 					})?;
 					trace!("Authentication header is a valid JWT");
 
-					if iss != constants::JWT_ISSUER {
-						warn!("Invalid JWT issuer: {}", iss);
-						return Err(ErrorType::MalformedAccessToken);
-					}
-					trace!("JWT issuer valid");
-
-					// The token should have been issued within the last `REFRESH_TOKEN_VALIDITY`
-					// duration
-					if OffsetDateTime::now_utc()
-						.sub(jti.get_timestamp().ok_or(ErrorType::MalformedAccessToken)?) >
-						AccessTokenData::REFRESH_TOKEN_VALIDITY
-					{
-						warn!("JWT is too old");
-						return Err(ErrorType::AuthorizationTokenInvalid);
-					}
-					trace!("JWT JTI valid");
+					claims.validate_issuer_and_audience(
+						&req.config.jwt_issuer,
+						&req.config.jwt_audience,
+					)?;
+					trace!("JWT issuer and audience valid");
 
-					if OffsetDateTime::now_utc() < nbf {
-						warn!("JWT is not valid yet");
-						return Err(ErrorType::AuthorizationTokenInvalid);
-					}
-					trace!("JWT NBF valid");
+					let AccessTokenData {
+						iss: _,
+						sub,
+						aud: _,
+						exp,
+						nbf,
+						iat: _,
+						jti,
+					} = claims;
 
-					if OffsetDateTime::now_utc() > exp {
-						warn!("JWT has expired");
-						return Err(ErrorType::AuthorizationTokenInvalid);
-					}
-					trace!("JWT EXP valid");
+					validate_access_token_time(&req.clock, &jti, nbf, exp)?;
 
 					let Some(user) = query! {
 						r#"
 						SELECT
 							"user".*,
-							web_login.token_expiry
+							web_login.token_expiry,
+							COALESCE(
+								web_login.last_activity,
+								web_login.created
+							) AS "last_activity!",
+							web_login_impersonation.operator_id AS "impersonated_by?"
 						FROM
 							"user"
 						INNER JOIN
@@ -374,6 +326,10 @@ where
 							web_login
 						ON
 							user_login.login_id = web_login.login_id
+						LEFT JOIN
+							web_login_impersonation
+						ON
+							web_login.login_id = web_login_impersonation.login_id
 						WHERE
 							user_login.login_id = $1 AND
 							user_login.login_type = 'web_login';
@@ -390,27 +346,22 @@ where
 					};
 					trace!("Web login exists in the database");
 
-					if OffsetDateTime::now_utc() > user.token_expiry {
+					let now = req.clock.now();
+					if now > user.token_expiry {
 						warn!("Web login has expired");
 						return Err(ErrorType::AuthorizationTokenInvalid);
 					}
 
-					if !aud
-						.clone()
-						.into_iter()
-						.any(|item| item == constants::PATR_JWT_AUDIENCE)
-					{
-						warn!(
-							"Invalid JWT audience: `{}`",
-							match aud {
-								OneOrMore::One(aud) => aud,
-								OneOrMore::Multiple(aud) => format!("[{}]", aud.join(", ")),
-							}
-						);
-						return Err(ErrorType::MalformedAccessToken);
+					if now - user.last_activity > req.config.session.idle_timeout() {
+						warn!("Web login has been idle for too long");
+						return Err(ErrorType::AuthorizationTokenInvalid);
 					}
+					trace!("Web login is not idle");
+
+					record_login_activity(&state, client_type, sub, now);
 
 					let permissions = get_permissions_for_login_id(
+						&state,
 						req.database,
 						req.redis,
 						&sub,
@@ -426,16 +377,24 @@ where
 						.created(user.created)
 						.login_id(sub)
 						.permissions(permissions)
+						.impersonated_by(user.impersonated_by.map(Uuid::from))
 						.build()
 				}
 			};
 
+			record_access_log_login_id(user_data.login_id);
+
+			if let Some(operator_id) = user_data.impersonated_by {
+				authorize_impersonated_request(&state, &req, &user_data, operator_id).await?;
+			}
+
 			let AppRequest {
 				request,
 				database,
 				redis,
 				client_ip,
 				config,
+				clock,
 			} = req;
 			let req = AuthenticatedAppRequest {
 				request,
@@ -443,6 +402,7 @@ where
 				redis,
 				client_ip,
 				config,
+				clock,
 				user_data,
 			};
 			inner.call(req).await
@@ -461,25 +421,332 @@ where
 		Self {
 			inner: self.inner.clone(),
 			client_type: self.client_type,
+			state: self.state.clone(),
 			authenticator: PhantomData,
 			endpoint: PhantomData,
 		}
 	}
 }
 
+/// Checks if an endpoint can be requested with an impersonated session.
+/// Endpoints that delete anything or that change the security of the account
+/// (see [`constants::IMPERSONATION_FORBIDDEN_PATHS`]) are not allowed.
+fn is_allowed_while_impersonating(method: &Method, path: &str) -> bool {
+	*method != Method::DELETE &&
+		!constants::IMPERSONATION_FORBIDDEN_PATHS
+			.iter()
+			.any(|forbidden| {
+				path.strip_prefix(forbidden)
+					.is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+			})
+}
+
+/// Records a request made with an impersonated session in the auth audit log,
+/// and rejects it if the endpoint is not allowed while impersonating a user.
+/// The entry is written using its own database connection, so that it is kept
+/// even if the request fails and its transaction is rolled back.
+async fn authorize_impersonated_request<E>(
+	state: &AppState,
+	req: &AppRequest<'_, E>,
+	user_data: &RequestUserData,
+	operator_id: Uuid,
+) -> Result<(), ErrorType>
+where
+	E: ApiEndpoint,
+	<E::RequestBody as Preprocessable>::Processed: Send,
+{
+	let path = <E::RequestPath as TypedPath>::PATH;
+	let allowed = is_allowed_while_impersonating(&E::METHOD, path);
+
+	info!(
+		"Operator `{}` is requesting `{} {}` as user `{}`",
+		operator_id,
+		E::METHOD,
+		path,
+		user_data.id
+	);
+
+	AuthAuditLogEntry {
+		login_id: user_data.login_id,
+		user_id: user_data.id,
+		operator_id: Some(operator_id),
+		event: if allowed {
+			AuthAuditLogEvent::ImpersonatedRequest
+		} else {
+			AuthAuditLogEvent::ImpersonatedRequestDenied
+		},
+		details: format!("{} {}", E::METHOD, path),
+		ip_address: req.client_ip,
+		timestamp: req.clock.now(),
+	}
+	.record(&mut state.database.acquire().await?)
+	.await?;
+
+	if !allowed {
+		warn!(
+			"Operator `{}` is not allowed to request `{} {}` while impersonating",
+			operator_id,
+			E::METHOD,
+			path
+		);
+		return Err(ErrorType::ImpersonationForbidden);
+	}
+
+	Ok(())
+}
+
+/// The result of verifying the refresh token of an API token against its hash
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ApiTokenHashVerification {
+	/// The refresh token matches the hash of the API token
+	Valid,
+	/// The refresh token does not match the hash of the API token
+	Invalid,
+	/// The API token does not exist, and the refresh token was verified
+	/// against the [`DECOY_API_TOKEN_HASH`][1] instead
+	///
+	/// [1]: constants::DECOY_API_TOKEN_HASH
+	Decoy,
+}
+
+/// Verifies the refresh token of an API token against the hash stored for it.
+/// If the API token does not exist, the refresh token is verified against a
+/// decoy hash instead, so that the time taken doesn't reveal whether the
+/// token exists.
+fn verify_api_token_hash(
+	password_pepper: &str,
+	refresh_token: &Uuid,
+	token_hash: Option<&str>,
+) -> Result<ApiTokenHashVerification, ErrorType> {
+	let hash = token_hash.unwrap_or(constants::DECOY_API_TOKEN_HASH);
+	let Ok(password_hash) = PasswordHash::new(hash) else {
+		error!("Unable to parse password hash: {}", hash);
+		return Err(ErrorType::server_error("password hash parsing failed"));
+	};
+	let success = Argon2::new_with_secret(
+		password_pepper.as_bytes(),
+		Algorithm::Argon2id,
+		Version::V0x13,
+		constants::HASHING_PARAMS,
+	)
+	.map_err(ErrorType::server_error)?
+	.verify_password(refresh_token.as_bytes(), &password_hash)
+	.is_ok();
+
+	Ok(match (token_hash, success) {
+		(None, _) => ApiTokenHashVerification::Decoy,
+		(Some(_), true) => ApiTokenHashVerification::Valid,
+		(Some(_), false) => ApiTokenHashVerification::Invalid,
+	})
+}
+
+/// Checks if an API token can be used at the current time, based on its NBF,
+/// EXP and revoked timestamps. Tokens without any of these timestamps are not
+/// restricted by them.
+fn validate_api_token_time(
+	clock: &Clock,
+	nbf: Option<OffsetDateTime>,
+	exp: Option<OffsetDateTime>,
+	revoked: Option<OffsetDateTime>,
+) -> Result<(), ErrorType> {
+	let now = clock.now();
+
+	if let Some(nbf) = nbf {
+		trace!("Token has an NBF");
+		if now < nbf {
+			info!("API token is not valid yet");
+			return Err(ErrorType::AuthorizationTokenInvalid);
+		}
+	} else {
+		trace!("Token does not have an NBF");
+	}
+	trace!("Token passed NBF check");
+
+	if let Some(exp) = exp {
+		trace!("Token has an EXP");
+		if now > exp {
+			info!("API token has expired");
+			return Err(ErrorType::AuthorizationTokenInvalid);
+		}
+	} else {
+		trace!("Token does not have an EXP");
+	}
+	trace!("Token passed EXP check");
+
+	if let Some(revoked) = revoked {
+		trace!("Token has a revoked timestamp");
+		if now > revoked {
+			info!("API token has been revoked");
+			return Err(ErrorType::AuthorizationTokenInvalid);
+		}
+	} else {
+		trace!("Token does not have a revoked timestamp");
+	}
+	trace!("Token passed revoked timestamp check");
+
+	Ok(())
+}
+
+/// Checks if a JWT can be used at the current time, based on when it was issued
+/// (the timestamp of its JTI), its NBF and its EXP.
+fn validate_access_token_time(
+	clock: &Clock,
+	jti: &Uuid,
+	nbf: OffsetDateTime,
+	exp: OffsetDateTime,
+) -> Result<(), ErrorType> {
+	let now = clock.now();
+
+	// The token should have been issued within the last
+	// `REFRESH_TOKEN_VALIDITY` duration
+	if now.sub(jti.get_timestamp().ok_or(ErrorType::MalformedAccessToken)?) >
+		AccessTokenData::REFRESH_TOKEN_VALIDITY
+	{
+		warn!("JWT is too old");
+		return Err(ErrorType::AuthorizationTokenInvalid);
+	}
+	trace!("JWT JTI valid");
+
+	if now < nbf {
+		warn!("JWT is not valid yet");
+		return Err(ErrorType::AuthorizationTokenInvalid);
+	}
+	trace!("JWT NBF valid");
+
+	if now > exp {
+		warn!("JWT has expired");
+		return Err(ErrorType::AuthorizationTokenInvalid);
+	}
+	trace!("JWT EXP valid");
+
+	Ok(())
+}
+
+/// Checks if data that was cached at the given time has been invalidated by a
+/// revocation timestamp (in seconds since the UNIX epoch). Since the
+/// revocation timestamps only have a precision of seconds, data cached within
+/// the same second as the revocation is also considered invalid.
+fn is_revoked_by(creation_time: OffsetDateTime, revocation_timestamp: Option<i64>) -> bool {
+	revocation_timestamp.is_some_and(|revoked_at| creation_time.unix_timestamp() <= revoked_at)
+}
+
+/// Checks if data that was cached at the given time has been invalidated by any
+/// of the given revocation timestamps
+fn is_revoked_by_any(
+	creation_time: OffsetDateTime,
+	revocation_timestamps: impl IntoIterator<Item = Option<i64>>,
+) -> bool {
+	revocation_timestamps
+		.into_iter()
+		.any(|revocation_timestamp| is_revoked_by(creation_time, revocation_timestamp))
+}
+
+/// Gets the keys of the revocation timestamps that cover the permissions of a
+/// login, which are cached for the given workspaces. The permissions are
+/// revoked by the revocation timestamp of the user, of the login, of any of the
+/// workspaces, or by the global one.
+fn revocation_keys_for_permissions<'a>(
+	login_id: &Uuid,
+	user_id: &Uuid,
+	workspace_ids: impl IntoIterator<Item = &'a Uuid>,
+) -> Vec<String> {
+	[
+		redis::keys::user_id_revocation_timestamp(user_id),
+		redis::keys::login_id_revocation_timestamp(login_id),
+	]
+	.into_iter()
+	.chain(
+		workspace_ids
+			.into_iter()
+			.map(redis::keys::workspace_id_revocation_timestamp),
+	)
+	.chain([redis::keys::global_revocation_timestamp()])
+	.collect()
+}
+
+/// Check if data cached at the given time has been revoked by any of the
+/// revocation timestamps stored in the given keys. If the timestamps can't be
+/// read, the data is considered revoked, so that it is reloaded from the
+/// database like on a cache miss.
+async fn is_revoked_by_keys(
+	state: &AppState,
+	redis_connection: &mut RedisClient,
+	creation_time: OffsetDateTime,
+	keys: Vec<String>,
+) -> bool {
+	match redis_connection
+		.mget::<_, _, Option<i64>, Vec<Option<i64>>>(keys)
+		.await
+	{
+		Ok(revocation_timestamps) => is_revoked_by_any(creation_time, revocation_timestamps),
+		Err(err) => {
+			state.redis.record_error(&err);
+			warn!("Failed to get the revocation timestamps: {err}");
+			true
+		}
+	}
+}
+
+/// The permission loads for each login ID that are currently in-flight in this
+/// process
+static PERMISSION_LOADS: SingleFlight<
+	Uuid,
+	Result<BTreeMap<Uuid, WorkspacePermission>, ErrorType>,
+> = SingleFlight::new();
+
+/// How fresh the permissions cached in Redis are
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CachedPermissionsFreshness {
+	/// The cached permissions are within the soft TTL, and can be used as-is
+	Fresh,
+	/// The cached permissions are past the soft TTL. They can still be used,
+	/// but should be reloaded in the background
+	Stale,
+	/// The cached permissions are past the hard TTL, and must be reloaded
+	/// before they can be used
+	Expired,
+}
+
+/// Gets how fresh permissions that were cached at the given time are, based on
+/// the soft and hard TTLs of the cache
+fn cached_permissions_freshness(
+	config: &PermissionCacheConfig,
+	creation_time: OffsetDateTime,
+	now: OffsetDateTime,
+) -> CachedPermissionsFreshness {
+	let age = now - creation_time;
+	if age >= config.hard_ttl() {
+		CachedPermissionsFreshness::Expired
+	} else if age >= config.soft_ttl() {
+		CachedPermissionsFreshness::Stale
+	} else {
+		CachedPermissionsFreshness::Fresh
+	}
+}
+
 /// Get all the permissions for a given login ID. This will first check the
-/// Redis cache, and if the data is not found, it will query the database and
-/// then store the result in the Redis cache.
-#[tracing::instrument(skip(db_connection, redis_connection))]
+/// Redis cache, and if the data is not found, has been revoked, or is past
+/// the hard TTL, it will query the database and then store the result in the
+/// Redis cache. Cached data that is past the soft TTL is still returned, but
+/// is reloaded in the background.
+#[tracing::instrument(skip(state, db_connection, redis_connection))]
 async fn get_permissions_for_login_id(
+	state: &AppState,
 	db_connection: &mut DatabaseConnection,
 	redis_connection: &mut RedisClient,
 	login_id: &Uuid,
 	user_id: &Uuid,
 ) -> Result<BTreeMap<Uuid, WorkspacePermission>, ErrorType> {
+	// A cache that can't be read is treated as a cache miss, so that a slow or
+	// unreachable Redis server doesn't fail the request
 	let redis_data: Option<String> = redis_connection
 		.get(redis::keys::permission_for_login_id(login_id))
-		.await?;
+		.await
+		.unwrap_or_else(|err| {
+			state.redis.record_error(&err);
+			warn!("Failed to get the cached permissions of loginId `{login_id}`: {err}");
+			None
+		});
 	if let Some(Ok(data)) = redis_data
 		.as_deref()
 		.map(serde_json::from_str::<UserPermissionCache>)
@@ -493,89 +760,190 @@ async fn get_permissions_for_login_id(
 		// workspace), a timestamp is set in redis. When a request is processed, if this
 		// timestamp exists in Redis, and the data inserted into redis was inserted
 		// after this timestamp, it is considered valid.
+		// The revocation timestamps are never deleted here, since they are shared
+		// by every login that they cover. They expire on their own once no cached
+		// data can be older than them.
 
-		// Check user revocation, then loginId revocation, then workspace ID revocation
-		'is_valid: {
-			let revoked = redis_connection
-				.get::<_, Option<i64>>(redis::keys::user_id_revocation_timestamp(user_id))
-				.await?
-				.and_then(|time| OffsetDateTime::from_unix_timestamp(time).ok())
-				.filter(|time| {
-					// If the timestamp exists, and the token was inserted into Redis before the
-					// timestamp, then the data in Redis is considered invalid
-					data.creation_time < *time
-				})
-				.is_some();
-
-			if revoked {
-				break 'is_valid;
-			}
-
-			let revoked = redis_connection
-				.get::<_, Option<i64>>(redis::keys::login_id_revocation_timestamp(login_id))
-				.await?
-				.and_then(|time| OffsetDateTime::from_unix_timestamp(time).ok())
-				.filter(|time| {
-					// If the timestamp exists, and the token was inserted into Redis before the
-					// timestamp, then the data in Redis is considered invalid
-					data.creation_time < *time
-				})
-				.is_some();
-
-			if revoked {
-				_ = redis_connection
-					.del(redis::keys::login_id_revocation_timestamp(login_id))
-					.await;
-				break 'is_valid;
-			}
+		let is_valid = !is_revoked_by_keys(
+			state,
+			redis_connection,
+			data.creation_time,
+			revocation_keys_for_permissions(login_id, user_id, data.permission.keys()),
+		)
+		.await;
 
-			for workspace_id in data.permission.keys() {
-				let revoked = redis_connection
-					.get::<_, Option<i64>>(redis::keys::workspace_id_revocation_timestamp(
-						workspace_id,
-					))
-					.await?
-					.and_then(|time| OffsetDateTime::from_unix_timestamp(time).ok())
-					.filter(|time| {
-						// If the timestamp exists, and the token was inserted into Redis before the
-						// timestamp, then the data in Redis is considered invalid
-						data.creation_time < *time
-					})
-					.is_some();
-
-				if revoked {
-					_ = redis_connection
-						.del(redis::keys::workspace_id_revocation_timestamp(workspace_id))
-						.await;
-					break 'is_valid;
+		if is_valid {
+			match cached_permissions_freshness(
+				&state.config.permission_cache,
+				data.creation_time,
+				state.clock.now(),
+			) {
+				CachedPermissionsFreshness::Fresh => return Ok(data.permission),
+				CachedPermissionsFreshness::Stale => {
+					trace!("Cached permissions are stale. Reloading them in the background");
+					reload_permissions_in_background(state, *login_id);
+					return Ok(data.permission);
+				}
+				CachedPermissionsFreshness::Expired => {
+					trace!("Cached permissions have expired. Reloading them");
 				}
 			}
+		} else {
+			trace!("Cached permissions have been revoked. Reloading them");
+		}
+	}
 
-			let revoked = redis_connection
-				.get::<_, Option<i64>>(redis::keys::global_revocation_timestamp())
-				.await?
-				.and_then(|time| OffsetDateTime::from_unix_timestamp(time).ok())
-				.filter(|time| {
-					// If the timestamp exists, and the token was inserted into Redis before the
-					// timestamp, then the data in Redis is considered invalid
-					data.creation_time < *time
-				})
-				.is_some();
-
-			if revoked {
-				_ = redis_connection
-					.del(redis::keys::global_revocation_timestamp())
-					.await;
-				break 'is_valid;
-			}
+	// On a cold cache, a burst of requests for the same login ID would all
+	// query the database at the same time. Only let one of them load the
+	// permissions, and share the result with the rest.
+	PERMISSION_LOADS
+		.run(*login_id, || {
+			load_permissions_for_login_id(
+				db_connection,
+				redis_connection,
+				&state.config.permission_cache,
+				&state.clock,
+				login_id,
+			)
+		})
+		.await
+}
 
-			// None of the revocation timestamps exist, so the data in Redis is
-			// valid and can be used
-		};
+/// Record the activity of a login in a background task, at most once every
+/// [`constants::LOGIN_ACTIVITY_DEBOUNCE`] instead of on every request. This is
+/// the last activity of a web login, or the last use of an API token. The
+/// activity uses its own database connection, so that it is kept even if the
+/// request fails, and the debounce is only set once the activity is written,
+/// so that the next request retries it if it couldn't be written. Read-only
+/// requests don't record any activity.
+fn record_login_activity(
+	state: &AppState,
+	client_type: ClientType,
+	login_id: Uuid,
+	now: OffsetDateTime,
+) {
+	if redis::is_read_only() {
+		return;
+	}
+
+	let state = state.clone();
+	tokio::spawn(async move {
+		if let Err(err) = write_login_activity(&state, client_type, &login_id, now).await {
+			warn!("Failed to record the activity of loginId `{login_id}`: {err:?}");
+		}
+	});
+}
 
-		return Ok(data.permission);
+/// Write the activity of a login to the database, unless it was already written
+/// within the last [`constants::LOGIN_ACTIVITY_DEBOUNCE`]
+async fn write_login_activity(
+	state: &AppState,
+	client_type: ClientType,
+	login_id: &Uuid,
+	now: OffsetDateTime,
+) -> Result<(), ErrorType> {
+	let redis = state.redis.get();
+	let debounce_key = redis::keys::login_activity_debounce(login_id);
+	if redis
+		.get::<_, Option<String>>(&debounce_key)
+		.await?
+		.is_some()
+	{
+		trace!("Activity of loginId `{login_id}` was recorded recently");
+		return Ok(());
+	}
+
+	let mut db_connection = state.database.acquire().await?;
+	match client_type {
+		ClientType::WebDashboard => {
+			query!(
+				r#"
+				UPDATE
+					web_login
+				SET
+					last_activity = $2
+				WHERE
+					login_id = $1;
+				"#,
+				login_id as _,
+				now,
+			)
+			.execute(&mut *db_connection)
+			.await?;
+		}
+		ClientType::ApiToken => {
+			query!(
+				r#"
+				UPDATE
+					user_api_token
+				SET
+					last_used = $2
+				WHERE
+					token_id = $1;
+				"#,
+				login_id as _,
+				now,
+			)
+			.execute(&mut *db_connection)
+			.await?;
+		}
 	}
 
+	redis
+		.setex(
+			&debounce_key,
+			constants::LOGIN_ACTIVITY_DEBOUNCE
+				.whole_seconds()
+				.unsigned_abs(),
+			"",
+		)
+		.await?;
+
+	Ok(())
+}
+
+/// Reload the permissions for a given login ID in a background task, without
+/// blocking the current request. The reload uses its own database connection,
+/// since the request's transaction can't outlive the request.
+fn reload_permissions_in_background(state: &AppState, login_id: Uuid) {
+	let state = state.clone();
+	tokio::spawn(async move {
+		let result = PERMISSION_LOADS
+			.run(login_id, || async {
+				let mut db_connection = state.database.acquire().await?;
+				let mut redis_connection = state.redis.get();
+				load_permissions_for_login_id(
+					&mut db_connection,
+					&mut redis_connection,
+					&state.config.permission_cache,
+					&state.clock,
+					&login_id,
+				)
+				.await
+			})
+			.await;
+
+		if let Err(err) = result {
+			warn!("Failed to reload the permissions of loginId `{login_id}`: {err}");
+		}
+	});
+}
+
+/// Load all the permissions for a given login ID from the database and store
+/// them in the Redis cache. This should only be called through
+/// [`PERMISSION_LOADS`], so that concurrent loads for the same login ID are
+/// deduplicated.
+#[tracing::instrument(skip(db_connection, redis_connection, cache_config, clock))]
+async fn load_permissions_for_login_id(
+	db_connection: &mut DatabaseConnection,
+	redis_connection: &mut RedisClient,
+	cache_config: &PermissionCacheConfig,
+	clock: &Clock,
+	login_id: &Uuid,
+) -> Result<BTreeMap<Uuid, WorkspacePermission>, ErrorType> {
+	// The permissions are considered cached from before they are loaded, so
+	// that a revocation made while they are being loaded also revokes them
+	let creation_time = clock.now();
 	let mut workspace_permissions = BTreeMap::<Uuid, WorkspacePermission>::new();
 
 	query!(
@@ -743,15 +1111,15 @@ async fn get_permissions_for_login_id(
 		}
 	});
 
-	redis_connection
+	// The permissions were loaded from the database, so they can still be used
+	// even if they couldn't be cached
+	_ = redis_connection
 		.setex(
 			redis::keys::permission_for_login_id(login_id),
-			constants::CACHED_PERMISSIONS_VALIDITY
-				.whole_seconds()
-				.unsigned_abs(),
+			cache_config.hard_ttl().whole_seconds().unsigned_abs(),
 			serde_json::to_string(&UserPermissionCache {
 				permission: workspace_permissions.clone(),
-				creation_time: OffsetDateTime::now_utc(),
+				creation_time,
 			})?,
 		)
 		.await
@@ -760,7 +1128,327 @@ async fn get_permissions_for_login_id(
 				"Error setting the permissions for the loginId `{login_id}`: `{}`",
 				err
 			);
-		})?;
+		});
 
 	Ok(workspace_permissions)
 }
+
+#[cfg(test)]
+mod tests {
+	use argon2::{password_hash::SaltString, PasswordHasher};
+	use time::Duration;
+
+	use super::*;
+
+	#[test]
+	fn api_token_is_only_valid_between_nbf_and_exp() {
+		let clock = Clock::stopped_at(OffsetDateTime::UNIX_EPOCH + Duration::days(1));
+		let nbf = clock.now() + Duration::seconds(10);
+		let exp = nbf + Duration::hours(1);
+
+		assert!(validate_api_token_time(&clock, Some(nbf), Some(exp), None).is_err());
+
+		clock.advance(Duration::seconds(10));
+		assert!(validate_api_token_time(&clock, Some(nbf), Some(exp), None).is_ok());
+
+		clock.advance(Duration::hours(1));
+		assert!(validate_api_token_time(&clock, Some(nbf), Some(exp), None).is_ok());
+
+		clock.advance(Duration::nanoseconds(1));
+		assert!(validate_api_token_time(&clock, Some(nbf), Some(exp), None).is_err());
+		assert!(validate_api_token_time(&clock, Some(nbf), None, None).is_ok());
+	}
+
+	#[test]
+	fn api_token_is_invalid_after_being_revoked() {
+		let clock = Clock::stopped_at(OffsetDateTime::UNIX_EPOCH + Duration::days(1));
+		let revoked = clock.now() + Duration::minutes(5);
+
+		assert!(validate_api_token_time(&clock, None, None, Some(revoked)).is_ok());
+
+		clock.advance(Duration::minutes(5));
+		assert!(validate_api_token_time(&clock, None, None, Some(revoked)).is_ok());
+
+		clock.advance(Duration::seconds(1));
+		assert!(validate_api_token_time(&clock, None, None, Some(revoked)).is_err());
+	}
+
+	#[test]
+	fn missing_api_token_is_verified_against_the_decoy_hash() {
+		let refresh_token = Uuid::new_v4();
+
+		let decoy_hash = PasswordHash::new(constants::DECOY_API_TOKEN_HASH).unwrap();
+		assert_eq!(
+			argon2::Params::try_from(&decoy_hash).unwrap(),
+			constants::HASHING_PARAMS
+		);
+
+		assert_eq!(
+			verify_api_token_hash("pepper", &refresh_token, None).unwrap(),
+			ApiTokenHashVerification::Decoy
+		);
+	}
+
+	#[test]
+	fn api_token_hash_is_verified_with_the_pepper() {
+		let refresh_token = Uuid::new_v4();
+		let token_hash = Argon2::new_with_secret(
+			b"pepper",
+			Algorithm::Argon2id,
+			Version::V0x13,
+			constants::HASHING_PARAMS,
+		)
+		.unwrap()
+		.hash_password(
+			refresh_token.as_bytes(),
+			SaltString::generate(&mut rand::thread_rng()).as_salt(),
+		)
+		.unwrap()
+		.to_string();
+
+		assert_eq!(
+			verify_api_token_hash("pepper", &refresh_token, Some(&token_hash)).unwrap(),
+			ApiTokenHashVerification::Valid
+		);
+		assert_eq!(
+			verify_api_token_hash("other pepper", &refresh_token, Some(&token_hash)).unwrap(),
+			ApiTokenHashVerification::Invalid
+		);
+	}
+
+	#[test]
+	fn access_token_is_only_valid_between_nbf_and_exp() {
+		let clock = Clock::stopped_at(OffsetDateTime::now_utc());
+		let jti = Uuid::now_v1();
+		let nbf = clock.now() + Duration::seconds(1);
+		let exp = nbf + constants::ACCESS_TOKEN_VALIDITY;
+
+		assert!(validate_access_token_time(&clock, &jti, nbf, exp).is_err());
+
+		clock.advance(Duration::seconds(1));
+		assert!(validate_access_token_time(&clock, &jti, nbf, exp).is_ok());
+
+		clock.advance(constants::ACCESS_TOKEN_VALIDITY);
+		assert!(validate_access_token_time(&clock, &jti, nbf, exp).is_ok());
+
+		clock.advance(Duration::seconds(1));
+		assert!(validate_access_token_time(&clock, &jti, nbf, exp).is_err());
+	}
+
+	#[test]
+	fn access_token_is_invalid_once_the_login_is_too_old() {
+		let clock = Clock::stopped_at(OffsetDateTime::now_utc());
+		let jti = Uuid::now_v1();
+		let nbf = clock.now() - Duration::seconds(1);
+		let exp = OffsetDateTime::now_utc() + AccessTokenData::REFRESH_TOKEN_VALIDITY * 2;
+
+		clock.advance(AccessTokenData::REFRESH_TOKEN_VALIDITY - Duration::seconds(1));
+		assert!(validate_access_token_time(&clock, &jti, nbf, exp).is_ok());
+
+		clock.advance(Duration::seconds(2));
+		assert!(validate_access_token_time(&clock, &jti, nbf, exp).is_err());
+	}
+
+	#[test]
+	fn impersonated_sessions_cannot_make_destructive_requests() {
+		assert!(is_allowed_while_impersonating(
+			&Method::GET,
+			"/workspace/:workspace_id"
+		));
+		assert!(is_allowed_while_impersonating(&Method::PATCH, "/user"));
+		assert!(is_allowed_while_impersonating(
+			&Method::POST,
+			"/user/verify-email"
+		));
+		assert!(is_allowed_while_impersonating(&Method::GET, "/user/login"));
+
+		assert!(!is_allowed_while_impersonating(
+			&Method::DELETE,
+			"/workspace/:workspace_id"
+		));
+		assert!(!is_allowed_while_impersonating(
+			&Method::POST,
+			"/user/change-password"
+		));
+		assert!(!is_allowed_while_impersonating(&Method::POST, "/user/mfa"));
+		assert!(!is_allowed_while_impersonating(
+			&Method::POST,
+			"/user/mfa/totp"
+		));
+		assert!(!is_allowed_while_impersonating(
+			&Method::POST,
+			"/user/api-token/bulk-revoke"
+		));
+		assert!(!is_allowed_while_impersonating(
+			&Method::POST,
+			"/auth/impersonate"
+		));
+	}
+
+	#[test]
+	fn cached_permissions_are_fresh_within_the_soft_ttl() {
+		let config = PermissionCacheConfig {
+			soft_ttl_seconds: 60,
+			hard_ttl_seconds: 3600,
+		};
+		let clock = Clock::stopped_at(OffsetDateTime::UNIX_EPOCH + Duration::days(1));
+		let creation_time = clock.now();
+
+		assert_eq!(
+			cached_permissions_freshness(&config, creation_time, clock.now()),
+			CachedPermissionsFreshness::Fresh
+		);
+
+		clock.advance(Duration::seconds(59));
+		assert_eq!(
+			cached_permissions_freshness(&config, creation_time, clock.now()),
+			CachedPermissionsFreshness::Fresh
+		);
+	}
+
+	#[test]
+	fn cached_permissions_are_stale_between_the_soft_and_hard_ttl() {
+		let config = PermissionCacheConfig {
+			soft_ttl_seconds: 60,
+			hard_ttl_seconds: 3600,
+		};
+		let clock = Clock::stopped_at(OffsetDateTime::UNIX_EPOCH + Duration::days(1));
+		let creation_time = clock.now();
+
+		clock.advance(Duration::seconds(60));
+		assert_eq!(
+			cached_permissions_freshness(&config, creation_time, clock.now()),
+			CachedPermissionsFreshness::Stale
+		);
+
+		clock.advance(Duration::seconds(3539));
+		assert_eq!(
+			cached_permissions_freshness(&config, creation_time, clock.now()),
+			CachedPermissionsFreshness::Stale
+		);
+	}
+
+	#[test]
+	fn cached_permissions_expire_after_the_hard_ttl() {
+		let config = PermissionCacheConfig {
+			soft_ttl_seconds: 60,
+			hard_ttl_seconds: 3600,
+		};
+		let clock = Clock::stopped_at(OffsetDateTime::UNIX_EPOCH + Duration::days(1));
+		let creation_time = clock.now();
+
+		clock.advance(Duration::hours(1));
+		assert_eq!(
+			cached_permissions_freshness(&config, creation_time, clock.now()),
+			CachedPermissionsFreshness::Expired
+		);
+
+		// The hard TTL can't be longer than the revocations are kept for
+		let config = PermissionCacheConfig {
+			soft_ttl_seconds: u32::MAX,
+			hard_ttl_seconds: u32::MAX,
+		};
+		clock.advance(constants::CACHED_PERMISSIONS_VALIDITY);
+		assert_eq!(
+			cached_permissions_freshness(&config, creation_time, clock.now()),
+			CachedPermissionsFreshness::Expired
+		);
+	}
+
+	#[test]
+	fn cached_data_is_revoked_by_timestamps_at_or_after_its_creation() {
+		let clock = Clock::stopped_at(
+			OffsetDateTime::UNIX_EPOCH + Duration::days(1) + Duration::milliseconds(500),
+		);
+		let creation_time = clock.now();
+
+		assert!(!is_revoked_by(creation_time, None));
+		assert!(!is_revoked_by(
+			creation_time,
+			Some((creation_time - Duration::seconds(1)).unix_timestamp())
+		));
+
+		// A revocation later within the same second is truncated to that second
+		clock.advance(Duration::milliseconds(200));
+		assert!(is_revoked_by(
+			creation_time,
+			Some(clock.now().unix_timestamp())
+		));
+
+		clock.advance(Duration::minutes(1));
+		assert!(is_revoked_by(
+			creation_time,
+			Some(clock.now().unix_timestamp())
+		));
+	}
+
+	#[test]
+	fn cached_permissions_are_revoked_by_the_keys_that_cover_them() {
+		let clock = Clock::stopped_at(
+			OffsetDateTime::UNIX_EPOCH + Duration::days(1) + Duration::milliseconds(500),
+		);
+		let login_id = Uuid::new_v4();
+		let user_id = Uuid::new_v4();
+		let workspace_id = Uuid::new_v4();
+		let other_workspace_id = Uuid::new_v4();
+
+		let cached = UserPermissionCache {
+			permission: BTreeMap::from([(
+				workspace_id,
+				WorkspacePermission::Member {
+					permissions: BTreeMap::new(),
+				},
+			)]),
+			creation_time: clock.now(),
+		};
+		let keys = revocation_keys_for_permissions(&login_id, &user_id, cached.permission.keys());
+
+		// The revocation timestamps that are set when a user is demoted, when
+		// an API token is revoked, when a role of the workspace is updated and
+		// when every cached permission is revoked
+		clock.advance(Duration::milliseconds(200));
+		for revocation_key in [
+			redis::keys::user_id_revocation_timestamp(&user_id),
+			redis::keys::login_id_revocation_timestamp(&login_id),
+			redis::keys::workspace_id_revocation_timestamp(&workspace_id),
+			redis::keys::global_revocation_timestamp(),
+		] {
+			let revocations = BTreeMap::from([(revocation_key, clock.now().unix_timestamp())]);
+			assert!(is_revoked_by_any(
+				cached.creation_time,
+				keys.iter().map(|key| revocations.get(key).copied())
+			));
+		}
+
+		// Revocations of other users, logins and workspaces don't revoke them
+		let revocations = BTreeMap::from([
+			(
+				redis::keys::user_id_revocation_timestamp(&Uuid::new_v4()),
+				clock.now().unix_timestamp(),
+			),
+			(
+				redis::keys::login_id_revocation_timestamp(&Uuid::new_v4()),
+				clock.now().unix_timestamp(),
+			),
+			(
+				redis::keys::workspace_id_revocation_timestamp(&other_workspace_id),
+				clock.now().unix_timestamp(),
+			),
+		]);
+		assert!(!is_revoked_by_any(
+			cached.creation_time,
+			keys.iter().map(|key| revocations.get(key).copied())
+		));
+
+		// Permissions that are loaded again after the revocation are valid
+		let revocations = BTreeMap::from([(
+			redis::keys::user_id_revocation_timestamp(&user_id),
+			clock.now().unix_timestamp(),
+		)]);
+		clock.advance(Duration::seconds(1));
+		assert!(!is_revoked_by_any(
+			clock.now(),
+			keys.iter().map(|key| revocations.get(key).copied())
+		));
+	}
+}
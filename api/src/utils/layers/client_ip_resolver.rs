@@ -0,0 +1,290 @@
+use std::{
+	net::{IpAddr, SocketAddr},
+	str::FromStr,
+	sync::Arc,
+	task::{Context, Poll},
+};
+
+use axum::{
+	extract::ConnectInfo,
+	http::{HeaderMap, Request},
+};
+use sqlx::types::ipnetwork::IpNetwork;
+use tower::{Layer, Service};
+
+use crate::prelude::*;
+
+/// The [`tower::Layer`] used to resolve the real IP address of the client when
+/// the server is running behind a reverse proxy or load balancer. If the socket
+/// peer of the request is one of the configured trusted proxies, the client IP
+/// is derived from the `CF-Connecting-IP`, `Forwarded` or `X-Forwarded-For`
+/// headers. Otherwise, these headers are ignored and the socket peer is used.
+///
+/// The resolved IP address replaces the [`ConnectInfo`] of the request, so
+/// that every part of the application (the `allowed_ips` check of API tokens,
+/// login and rate limiting keys, etc.) sees the same client IP address.
+#[derive(Clone, Debug)]
+pub struct ClientIpResolverLayer {
+	/// The list of networks whose forwarding headers will be trusted
+	trusted_proxies: Arc<[IpNetwork]>,
+}
+
+impl ClientIpResolverLayer {
+	/// Create a new instance of the [`ClientIpResolverLayer`] with the given
+	/// list of trusted proxies
+	pub fn new(trusted_proxies: impl Into<Arc<[IpNetwork]>>) -> Self {
+		Self {
+			trusted_proxies: trusted_proxies.into(),
+		}
+	}
+}
+
+impl<S> Layer<S> for ClientIpResolverLayer {
+	type Service = ClientIpResolverService<S>;
+
+	fn layer(&self, inner: S) -> Self::Service {
+		ClientIpResolverService {
+			inner,
+			trusted_proxies: self.trusted_proxies.clone(),
+		}
+	}
+}
+
+/// The underlying service that runs when the [`ClientIpResolverLayer`] is
+/// used.
+#[derive(Clone, Debug)]
+pub struct ClientIpResolverService<S> {
+	/// The inner service that will be called with the resolved client IP
+	inner: S,
+	/// The list of networks whose forwarding headers will be trusted
+	trusted_proxies: Arc<[IpNetwork]>,
+}
+
+impl<S, B> Service<Request<B>> for ClientIpResolverService<S>
+where
+	S: Service<Request<B>>,
+{
+	type Error = S::Error;
+	type Future = S::Future;
+	type Response = S::Response;
+
+	fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+		self.inner.poll_ready(cx)
+	}
+
+	fn call(&mut self, mut req: Request<B>) -> Self::Future {
+		if let Some(ConnectInfo(peer)) = req.extensions().get::<ConnectInfo<SocketAddr>>().copied()
+		{
+			let client_ip = resolve_client_ip(peer.ip(), req.headers(), &self.trusted_proxies);
+			if client_ip != peer.ip() {
+				trace!("Resolved client IP `{client_ip}` from trusted proxy `{peer}`");
+				req.extensions_mut()
+					.insert(ConnectInfo(SocketAddr::new(client_ip, peer.port())));
+			}
+		}
+		self.inner.call(req)
+	}
+}
+
+/// Resolves the IP address of the client given the socket peer and the
+/// headers of the request. Forwarding headers are only used if the peer is a
+/// trusted proxy. The forwarded chain is walked from the right (the hop closest
+/// to this server), skipping over any trusted proxies, so that a client cannot
+/// spoof its address by prepending values to the header.
+pub fn resolve_client_ip(
+	peer: IpAddr,
+	headers: &HeaderMap,
+	trusted_proxies: &[IpNetwork],
+) -> IpAddr {
+	let is_trusted = |ip: IpAddr| trusted_proxies.iter().any(|network| network.contains(ip));
+
+	if !is_trusted(peer) {
+		return peer;
+	}
+
+	if let Some(cf_connecting_ip) = headers
+		.get("CF-Connecting-IP")
+		.and_then(|value| value.to_str().ok())
+		.and_then(|value| IpAddr::from_str(value.trim()).ok())
+	{
+		return cf_connecting_ip;
+	}
+
+	let forwarded_chain = headers
+		.get_all("Forwarded")
+		.iter()
+		.filter_map(|value| value.to_str().ok())
+		.flat_map(|value| value.split(','))
+		.filter_map(parse_forwarded_for)
+		.collect::<Vec<_>>();
+	let forwarded_chain = if forwarded_chain.is_empty() {
+		headers
+			.get_all("X-Forwarded-For")
+			.iter()
+			.filter_map(|value| value.to_str().ok())
+			.flat_map(|value| value.split(','))
+			.map(|value| IpAddr::from_str(value.trim()).ok())
+			.collect::<Option<Vec<_>>>()
+			.unwrap_or_default()
+	} else {
+		forwarded_chain
+	};
+
+	forwarded_chain
+		.iter()
+		.rev()
+		.copied()
+		.find(|ip| !is_trusted(*ip))
+		.or_else(|| forwarded_chain.first().copied())
+		.unwrap_or(peer)
+}
+
+/// Parses the `for` parameter of a single element of the `Forwarded` header,
+/// as described in RFC 7239. Obfuscated identifiers and `unknown` are ignored.
+fn parse_forwarded_for(element: &str) -> Option<IpAddr> {
+	let value = element.split(';').find_map(|pair| {
+		let (key, value) = pair.trim().split_once('=')?;
+		key.trim()
+			.eq_ignore_ascii_case("for")
+			.then(|| value.trim().trim_matches('"'))
+	})?;
+
+	if let Some(ipv6) = value.strip_prefix('[') {
+		// IPv6 addresses are enclosed in brackets, optionally followed by a
+		// port
+		return IpAddr::from_str(ipv6.split(']').next()?).ok();
+	}
+
+	IpAddr::from_str(value)
+		.ok()
+		.or_else(|| SocketAddr::from_str(value).ok().map(|addr| addr.ip()))
+}
+
+#[cfg(test)]
+mod tests {
+	use axum::http::HeaderValue;
+	use tower::ServiceExt;
+
+	use super::*;
+
+	/// The address of the load balancer in front of the API, which is the
+	/// only trusted proxy in these tests
+	const PROXY: &str = "10.0.0.5";
+
+	/// Resolves the client IP of a request from the given peer, with the given
+	/// forwarding headers
+	fn resolve(peer: &str, headers: &[(&'static str, &str)]) -> IpAddr {
+		let headers = headers
+			.iter()
+			.map(|(name, value)| (*name, HeaderValue::from_str(value).unwrap()))
+			.fold(HeaderMap::new(), |mut headers, (name, value)| {
+				headers.append(name, value);
+				headers
+			});
+
+		resolve_client_ip(
+			peer.parse().unwrap(),
+			&headers,
+			&["10.0.0.0/24".parse().unwrap()],
+		)
+	}
+
+	#[test]
+	fn forwarding_headers_from_untrusted_peers_are_ignored() {
+		for header in ["X-Forwarded-For", "Forwarded", "CF-Connecting-IP"] {
+			let value = if header == "Forwarded" {
+				"for=1.2.3.4"
+			} else {
+				"1.2.3.4"
+			};
+			assert_eq!(
+				resolve("203.0.113.7", &[(header, value)]),
+				"203.0.113.7".parse::<IpAddr>().unwrap(),
+				"{header} was trusted"
+			);
+		}
+	}
+
+	#[test]
+	fn spoofed_addresses_prepended_to_the_chain_are_ignored() {
+		// The client sent `X-Forwarded-For: 1.2.3.4`, and the proxy appended
+		// the address it received the request from
+		assert_eq!(
+			resolve(PROXY, &[("X-Forwarded-For", "1.2.3.4, 198.51.100.9")]),
+			"198.51.100.9".parse::<IpAddr>().unwrap()
+		);
+		assert_eq!(
+			resolve(
+				PROXY,
+				&[("Forwarded", "for=1.2.3.4, for=198.51.100.9;proto=https")]
+			),
+			"198.51.100.9".parse::<IpAddr>().unwrap()
+		);
+	}
+
+	#[test]
+	fn trusted_proxies_in_the_chain_are_skipped() {
+		// The request went through two trusted proxies, with each of them
+		// appending the address it received the request from, in separate
+		// headers
+		assert_eq!(
+			resolve(
+				PROXY,
+				&[
+					("X-Forwarded-For", "198.51.100.9"),
+					("X-Forwarded-For", "10.0.0.7"),
+				]
+			),
+			"198.51.100.9".parse::<IpAddr>().unwrap()
+		);
+		assert_eq!(
+			resolve(
+				PROXY,
+				&[("Forwarded", r#"for="[2001:db8::1]:4711", for=10.0.0.7"#)]
+			),
+			"2001:db8::1".parse::<IpAddr>().unwrap()
+		);
+
+		// If every address is a trusted proxy, the first one is the client
+		assert_eq!(
+			resolve(PROXY, &[("X-Forwarded-For", "10.0.0.8, 10.0.0.7")]),
+			"10.0.0.8".parse::<IpAddr>().unwrap()
+		);
+	}
+
+	#[test]
+	fn malformed_chains_fall_back_to_the_peer() {
+		assert_eq!(
+			resolve(PROXY, &[("X-Forwarded-For", "1.2.3.4, not-an-ip")]),
+			PROXY.parse::<IpAddr>().unwrap()
+		);
+		assert_eq!(
+			resolve(PROXY, &[("Forwarded", "for=unknown")]),
+			PROXY.parse::<IpAddr>().unwrap()
+		);
+		assert_eq!(resolve(PROXY, &[]), PROXY.parse::<IpAddr>().unwrap());
+	}
+
+	#[tokio::test]
+	async fn the_resolved_ip_replaces_the_peer_of_the_request() {
+		let service = ClientIpResolverLayer::new(vec!["10.0.0.0/24".parse().unwrap()]).layer(
+			tower::service_fn(|req: Request<()>| async move {
+				let ConnectInfo(peer) = req
+					.extensions()
+					.get::<ConnectInfo<SocketAddr>>()
+					.copied()
+					.unwrap();
+				Ok::<_, std::convert::Infallible>(peer)
+			}),
+		);
+
+		let request = Request::builder()
+			.header("X-Forwarded-For", "198.51.100.9")
+			.extension(ConnectInfo(SocketAddr::new(PROXY.parse().unwrap(), 443)))
+			.body(())
+			.unwrap();
+		let Ok(peer) = service.oneshot(request).await;
+
+		assert_eq!(peer, "198.51.100.9:443".parse::<SocketAddr>().unwrap());
+	}
+}
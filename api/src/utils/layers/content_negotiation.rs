@@ -0,0 +1,314 @@
+use std::{
+	convert::Infallible,
+	future::Future,
+	task::{Context, Poll},
+};
+
+use axum::{
+	body::Body,
+	http::{header, HeaderMap, HeaderValue, Request},
+	response::{IntoResponse, Response},
+};
+use models::ApiErrorResponse;
+use tower::{Layer, Service};
+
+use crate::prelude::*;
+
+/// The encodings that the request and response bodies of an endpoint can use
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BodyEncoding {
+	/// The body is encoded as JSON. This is what the endpoints themselves use,
+	/// and is the default when no (or an unknown) encoding is asked for
+	Json,
+	/// The body is encoded as [MessagePack](https://msgpack.org)
+	MessagePack,
+}
+
+impl BodyEncoding {
+	/// Get the encoding of a request body from the `Content-Type` header of the
+	/// request. Anything that isn't MessagePack is treated as JSON
+	pub fn from_content_type(headers: &HeaderMap) -> Self {
+		headers
+			.get(header::CONTENT_TYPE)
+			.and_then(|value| value.to_str().ok())
+			.and_then(Self::from_media_type)
+			.unwrap_or(Self::Json)
+	}
+
+	/// Get the encoding that the response body should use from the `Accept`
+	/// header of the request. The supported encoding with the highest quality
+	/// is picked, preferring the one listed first if there's a tie. Media
+	/// types that aren't supported are ignored, and JSON is used if none of
+	/// the listed ones are supported
+	pub fn from_accept(headers: &HeaderMap) -> Self {
+		headers
+			.get_all(header::ACCEPT)
+			.iter()
+			.filter_map(|value| value.to_str().ok())
+			.flat_map(|value| value.split(','))
+			.filter_map(|media_range| {
+				let mut parts = media_range.split(';');
+				let encoding = parts.next().and_then(Self::from_media_type)?;
+				let quality = parts
+					.filter_map(|param| param.trim().strip_prefix("q="))
+					.find_map(|quality| quality.trim().parse::<f32>().ok())
+					.unwrap_or(1.0);
+				(quality > 0.0).then_some((encoding, quality))
+			})
+			.fold(
+				None,
+				|best: Option<(Self, f32)>, (encoding, quality)| match best {
+					Some((_, best_quality)) if best_quality >= quality => best,
+					_ => Some((encoding, quality)),
+				},
+			)
+			.map(|(encoding, _)| encoding)
+			.unwrap_or(Self::Json)
+	}
+
+	/// Get the encoding for a media type, ignoring any parameters. Wildcards
+	/// are treated as JSON, since that is the default encoding
+	fn from_media_type(media_type: &str) -> Option<Self> {
+		let media_type = media_type.split(';').next()?.trim();
+		if media_type.eq_ignore_ascii_case(constants::MSGPACK_CONTENT_TYPE) ||
+			media_type.eq_ignore_ascii_case("application/x-msgpack")
+		{
+			Some(Self::MessagePack)
+		} else if media_type.eq_ignore_ascii_case("application/json") ||
+			media_type == "application/*" ||
+			media_type == "*/*"
+		{
+			Some(Self::Json)
+		} else {
+			None
+		}
+	}
+}
+
+/// The [`tower::Layer`] used to negotiate the encoding of the request and
+/// response bodies of an endpoint. The endpoints only deal with JSON, so a
+/// request body sent as MessagePack (using the `Content-Type` header) is
+/// converted to JSON before it is parsed, and a JSON response (including
+/// errors) is converted to MessagePack if the client prefers it (using the
+/// `Accept` header). Requests that don't ask for MessagePack are left as is.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ContentNegotiationLayer;
+
+impl ContentNegotiationLayer {
+	/// Create a new instance of the [`ContentNegotiationLayer`]
+	pub fn new() -> Self {
+		Self
+	}
+}
+
+impl<S> Layer<S> for ContentNegotiationLayer {
+	type Service = ContentNegotiationService<S>;
+
+	fn layer(&self, inner: S) -> Self::Service {
+		ContentNegotiationService { inner }
+	}
+}
+
+/// The underlying service that runs when the [`ContentNegotiationLayer`] is
+/// used.
+#[derive(Clone, Debug)]
+pub struct ContentNegotiationService<S> {
+	/// The inner service that will be called with the request
+	inner: S,
+}
+
+impl<S> Service<Request<Body>> for ContentNegotiationService<S>
+where
+	S: Service<Request<Body>, Response = Response, Error = Infallible> + Clone + Send + 'static,
+	S::Future: Send,
+{
+	type Error = Infallible;
+	type Response = Response;
+
+	type Future = impl Future<Output = Result<Self::Response, Self::Error>>;
+
+	fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+		self.inner.poll_ready(cx)
+	}
+
+	#[instrument(skip(self, req), name = "ContentNegotiationService")]
+	fn call(&mut self, req: Request<Body>) -> Self::Future {
+		let mut inner = self.inner.clone();
+
+		async move {
+			let response_encoding = BodyEncoding::from_accept(req.headers());
+
+			let req = match BodyEncoding::from_content_type(req.headers()) {
+				BodyEncoding::Json => req,
+				BodyEncoding::MessagePack => match msgpack_request_to_json(req).await {
+					Ok(req) => req,
+					Err(err) => {
+						debug!("Error converting MessagePack body to JSON: {err}");
+						let response = ApiErrorResponse::error_with_message(
+							ErrorType::WrongParameters,
+							"Invalid body",
+						)
+						.into_response();
+						return Ok(encode_response(response, response_encoding).await);
+					}
+				},
+			};
+
+			let response = inner.call(req).await?;
+
+			Ok(encode_response(response, response_encoding).await)
+		}
+	}
+}
+
+/// Convert a request with a MessagePack body to one with the same body encoded
+/// as JSON, so that it can be parsed like any other request
+async fn msgpack_request_to_json(req: Request<Body>) -> Result<Request<Body>, String> {
+	let (mut parts, body) = req.into_parts();
+	let body = axum::body::to_bytes(body, constants::MAX_MSGPACK_REQUEST_BODY_SIZE)
+		.await
+		.map_err(|err| err.to_string())?;
+	let body = msgpack_to_json(&body)?;
+
+	parts.headers.insert(
+		header::CONTENT_TYPE,
+		HeaderValue::from_static("application/json"),
+	);
+	parts.headers.remove(header::CONTENT_LENGTH);
+
+	Ok(Request::from_parts(parts, Body::from(body)))
+}
+
+/// Encode a response in the negotiated encoding. Only JSON responses are
+/// converted, so responses with any other content type (such as streams or
+/// downloads) are left as is
+async fn encode_response(response: Response, encoding: BodyEncoding) -> Response {
+	let mut response = match encoding {
+		BodyEncoding::MessagePack if is_json_response(&response) => {
+			let (mut parts, body) = response.into_parts();
+			let body = match axum::body::to_bytes(body, usize::MAX).await {
+				Ok(body) => body,
+				Err(err) => {
+					return ErrorType::server_error(format!("Error reading response body: {err}"))
+						.into_response();
+				}
+			};
+
+			match json_to_msgpack(&body) {
+				Ok(body) => {
+					parts.headers.insert(
+						header::CONTENT_TYPE,
+						HeaderValue::from_static(constants::MSGPACK_CONTENT_TYPE),
+					);
+					parts.headers.remove(header::CONTENT_LENGTH);
+					Response::from_parts(parts, Body::from(body))
+				}
+				Err(err) => {
+					// The body was not converted, so it is sent as is
+					warn!("Error converting response body to MessagePack: {err}");
+					Response::from_parts(parts, Body::from(body))
+				}
+			}
+		}
+		_ => response,
+	};
+
+	response
+		.headers_mut()
+		.append(header::VARY, HeaderValue::from_static("accept"));
+
+	response
+}
+
+/// Check if the body of a response is JSON, based on its content type
+fn is_json_response(response: &Response) -> bool {
+	response
+		.headers()
+		.get(header::CONTENT_TYPE)
+		.and_then(|value| value.to_str().ok())
+		.and_then(|value| value.split(';').next())
+		.is_some_and(|media_type| media_type.trim().eq_ignore_ascii_case("application/json"))
+}
+
+/// Convert a MessagePack encoded body to JSON
+fn msgpack_to_json(body: &[u8]) -> Result<Vec<u8>, String> {
+	let value = rmp_serde::from_slice::<serde_json::Value>(body).map_err(|err| err.to_string())?;
+	serde_json::to_vec(&value).map_err(|err| err.to_string())
+}
+
+/// Convert a JSON encoded body to MessagePack. Structs are encoded as maps, so
+/// that the field names are kept
+fn json_to_msgpack(body: &[u8]) -> Result<Vec<u8>, String> {
+	let value = serde_json::from_slice::<serde_json::Value>(body).map_err(|err| err.to_string())?;
+	rmp_serde::to_vec_named(&value).map_err(|err| err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+	use models::api::workspace::deployment::CreateDeploymentRequest;
+
+	use super::*;
+
+	#[test]
+	fn create_deployment_request_round_trips_through_msgpack() {
+		let request = serde_json::from_value::<CreateDeploymentRequest>(serde_json::json!({
+			"name": "my-deployment",
+			"registry": "registry.patr.cloud",
+			"repositoryId": "0192d5e3-8bfa-7c3a-9a0e-6a1b2c3d4e5f",
+			"imageTag": "latest",
+			"runner": "0192d5e3-8bfa-7c3a-9a0e-6a1b2c3d4e60",
+			"deployOnPush": true,
+			"minHorizontalScale": 1,
+			"maxHorizontalScale": 3,
+			"ports": { "8080": "http" },
+			"environmentVariables": { "PORT": "8080" },
+			"scaleToZeroAfter": 600,
+			"logLevel": "warn",
+			"deployOnCreate": false,
+		}))
+		.unwrap();
+
+		let json = serde_json::to_vec(&request).unwrap();
+		let msgpack = json_to_msgpack(&json).unwrap();
+		let decoded = msgpack_to_json(&msgpack).unwrap();
+
+		assert_eq!(
+			serde_json::from_slice::<CreateDeploymentRequest>(&decoded).unwrap(),
+			request
+		);
+	}
+
+	#[test]
+	fn negotiates_msgpack_only_when_asked_for() {
+		let headers = |accept: &str| {
+			let mut headers = HeaderMap::new();
+			headers.insert(header::ACCEPT, HeaderValue::from_str(accept).unwrap());
+			headers
+		};
+
+		assert_eq!(
+			BodyEncoding::from_accept(&HeaderMap::new()),
+			BodyEncoding::Json
+		);
+		assert_eq!(
+			BodyEncoding::from_accept(&headers("text/html")),
+			BodyEncoding::Json
+		);
+		assert_eq!(
+			BodyEncoding::from_accept(&headers("application/msgpack")),
+			BodyEncoding::MessagePack
+		);
+		assert_eq!(
+			BodyEncoding::from_accept(&headers("application/json, application/msgpack")),
+			BodyEncoding::Json
+		);
+		assert_eq!(
+			BodyEncoding::from_accept(&headers("application/json;q=0.5, application/msgpack")),
+			BodyEncoding::MessagePack
+		);
+		assert_eq!(
+			BodyEncoding::from_accept(&headers("application/msgpack;q=0, */*")),
+			BodyEncoding::Json
+		);
+	}
+}
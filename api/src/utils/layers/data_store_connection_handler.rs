@@ -9,7 +9,10 @@ use models::prelude::*;
 use preprocess::Preprocessable;
 use tower::{Layer, Service};
 
-use crate::prelude::*;
+use crate::{
+	prelude::*,
+	utils::{long_poll, runner},
+};
 
 /// A [`tower::Layer`] that can be used to parse the request and call the inner
 /// service with the parsed request. Ideally, this will automatically be done by
@@ -25,6 +28,9 @@ where
 	/// inner service fails, the database transaction will be automatically
 	/// rolled back, otherwise it will be committed.
 	state: AppState,
+	/// If true, the database transaction is always rolled back, so that the
+	/// request cannot have any side effects on the database
+	read_only: bool,
 	/// The endpoint type that this layer will handle.
 	phantom: PhantomData<E>,
 }
@@ -43,6 +49,19 @@ where
 		Self {
 			phantom: PhantomData,
 			state,
+			read_only: false,
+		}
+	}
+
+	/// Create a new instance of the [`DataStoreConnectionLayer`] with the given
+	/// state, where the database transaction is always rolled back, even if
+	/// the inner service succeeds. This is used for `HEAD` requests, which
+	/// run the handler of the `GET` endpoint but must not have side effects.
+	pub fn read_only(state: AppState) -> Self {
+		Self {
+			phantom: PhantomData,
+			state,
+			read_only: true,
 		}
 	}
 }
@@ -59,6 +78,7 @@ where
 		DataStoreConnectionService {
 			inner,
 			state: self.state.clone(),
+			read_only: self.read_only,
 			phantom: PhantomData,
 		}
 	}
@@ -82,6 +102,9 @@ where
 	/// inner service fails, the database transaction will be automatically
 	/// rolled back, otherwise it will be committed.
 	state: AppState,
+	/// If true, the database transaction is always rolled back, so that the
+	/// request cannot have any side effects on the database
+	read_only: bool,
 	/// The endpoint type that this service will handle.
 	phantom: PhantomData<E>,
 }
@@ -106,10 +129,11 @@ where
 
 	#[instrument(skip(self, request), name = "DataStoreConnectionService")]
 	fn call(&mut self, (request, client_ip): (ApiRequest<E>, IpAddr)) -> Self::Future {
-		let mut state = self.state.clone();
+		let state = self.state.clone();
 		let mut inner = self.inner.clone();
-		async {
-			let redis = &mut state.redis;
+		let read_only = self.read_only;
+		async move {
+			let mut redis = state.redis.get();
 
 			let Ok(mut database) = state.database.begin().await else {
 				debug!("Failed to begin database transaction");
@@ -118,17 +142,43 @@ where
 				));
 			};
 
+			let config = state.current_config();
 			let req = UnprocessedAppRequest {
 				request,
 				database: &mut database,
-				redis,
+				redis: &mut redis,
 				client_ip,
-				config: state.config.clone(),
+				config: config.clone(),
+				clock: state.clock.clone(),
 			};
 
 			info!("Calling inner service");
 
-			match inner.call(req).await {
+			// Messages to runners, revocations of cached permissions and
+			// deferred responses are held until the transaction is committed,
+			// and are dropped if it isn't
+			let handled = redis::hold_revocations(runner::hold_messages(
+				long_poll::hold_deferred_response::<_, E>(inner.call(req)),
+			));
+			// Read-only requests must not write to Redis either, since those
+			// writes can't be rolled back along with the transaction
+			let (((result, deferred_response), runner_messages), revocations) = if read_only {
+				redis::read_only(handled).await
+			} else {
+				handled.await
+			};
+
+			match result {
+				Ok(response) if read_only => {
+					info!("Inner service called successfully. Discarding changes");
+					let Ok(()) = database.rollback().await else {
+						debug!("Failed to rollback database transaction");
+						return Err(ErrorType::server_error(
+							"unable to rollback database transaction",
+						));
+					};
+					Ok(response)
+				}
 				Ok(response) => {
 					info!("Inner service called successfully");
 					let Ok(()) = database.commit().await else {
@@ -137,6 +187,22 @@ where
 							"unable to commit database transaction",
 						));
 					};
+					// The revocations are set before responding, so that the
+					// next request already sees the new permissions
+					if !revocations.is_empty() {
+						redis::set_pending_revocations(&redis, revocations, state.clock.now())
+							.await;
+					}
+					// The runners are notified before responding too, so that a
+					// runner that can't be reached is reported. The changes are
+					// already committed by then, and the runner picks them up
+					// on its next full reconciliation
+					runner::publish_pending_messages(&redis, &config.runner, runner_messages)
+						.await?;
+					if let Some(deferred_response) = deferred_response {
+						info!("Waiting for the deferred response");
+						return deferred_response.await;
+					}
 					Ok(response)
 				}
 				Err(error) => {
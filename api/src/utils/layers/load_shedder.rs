@@ -0,0 +1,184 @@
+use std::{
+	convert::Infallible,
+	future::Future,
+	sync::{
+		atomic::{AtomicUsize, Ordering},
+		Arc,
+	},
+	task::{Context, Poll},
+};
+
+use axum::{
+	body::Body,
+	http::{header, HeaderValue, Request},
+	response::{IntoResponse, Response},
+};
+use opentelemetry::{
+	global,
+	metrics::{Counter, UpDownCounter},
+};
+use tower::{Layer, Service};
+
+use crate::{prelude::*, utils::config::LoadSheddingConfig};
+
+/// The [`tower::Layer`] used to shed load when the API is handling too many
+/// requests at once. The number of requests that are currently being handled
+/// is tracked, and any request past the configured limit is rejected right
+/// away with [`ErrorType::ServerOverloaded`] and a `Retry-After` header,
+/// instead of being queued. Health and readiness checks (see
+/// [`constants::LOAD_SHEDDING_EXEMPT_PATHS`]) are never rejected, nor counted.
+///
+/// The number of requests being handled is exported as the
+/// `http.server.active_requests` metric, and the number of rejected requests
+/// as the `http.server.shed_requests` metric.
+#[derive(Clone, Debug)]
+pub struct LoadShedderLayer {
+	/// The configuration for shedding load
+	config: LoadSheddingConfig,
+	/// The number of requests that are currently being handled
+	in_flight: Arc<AtomicUsize>,
+	/// The metric of the number of requests that are currently being handled
+	active_requests: UpDownCounter<i64>,
+	/// The metric of the number of requests that were rejected
+	shed_requests: Counter<u64>,
+}
+
+impl LoadShedderLayer {
+	/// Create a new instance of the [`LoadShedderLayer`] with the given
+	/// configuration
+	pub fn new(config: LoadSheddingConfig) -> Self {
+		let meter = global::meter("Patr API");
+
+		Self {
+			config,
+			in_flight: Arc::new(AtomicUsize::new(0)),
+			active_requests: meter
+				.i64_up_down_counter("http.server.active_requests")
+				.with_description("The number of requests that are currently being handled")
+				.build(),
+			shed_requests: meter
+				.u64_counter("http.server.shed_requests")
+				.with_description("The number of requests that were rejected as overloaded")
+				.build(),
+		}
+	}
+}
+
+impl<S> Layer<S> for LoadShedderLayer {
+	type Service = LoadShedderService<S>;
+
+	fn layer(&self, inner: S) -> Self::Service {
+		LoadShedderService {
+			inner,
+			layer: self.clone(),
+		}
+	}
+}
+
+/// The underlying service that runs when the [`LoadShedderLayer`] is used.
+#[derive(Clone, Debug)]
+pub struct LoadShedderService<S> {
+	/// The inner service that will be called with the request
+	inner: S,
+	/// The layer that this service was created from, which holds the state
+	/// shared between all the clones of the service
+	layer: LoadShedderLayer,
+}
+
+impl<S> Service<Request<Body>> for LoadShedderService<S>
+where
+	S: Service<Request<Body>, Response = Response, Error = Infallible> + Clone + Send + 'static,
+	S::Future: Send,
+{
+	type Error = Infallible;
+	type Response = Response;
+
+	type Future = impl Future<Output = Result<Self::Response, Self::Error>>;
+
+	fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+		self.inner.poll_ready(cx)
+	}
+
+	#[instrument(skip(self, req), name = "LoadShedderService")]
+	fn call(&mut self, req: Request<Body>) -> Self::Future {
+		let mut inner = self.inner.clone();
+		let layer = self.layer.clone();
+
+		async move {
+			if constants::LOAD_SHEDDING_EXEMPT_PATHS.contains(&req.uri().path()) {
+				return inner.call(req).await;
+			}
+
+			let Some(_guard) = InFlightGuard::acquire(&layer) else {
+				warn!(
+					"Rejecting request to `{}` as the server is overloaded",
+					req.uri().path()
+				);
+				layer.shed_requests.add(1, &[]);
+
+				let mut response = ErrorType::ServerOverloaded.into_response();
+				response.headers_mut().insert(
+					header::RETRY_AFTER,
+					HeaderValue::from(layer.config.retry_after_seconds),
+				);
+				return Ok(response);
+			};
+
+			inner.call(req).await
+		}
+	}
+}
+
+/// Counts a request as being handled for as long as it is alive, so that the
+/// request stops being counted even if its future is dropped midway (for
+/// example, when the client disconnects)
+struct InFlightGuard<'a> {
+	/// The layer whose requests are being counted
+	layer: &'a LoadShedderLayer,
+}
+
+impl<'a> InFlightGuard<'a> {
+	/// Counts a new request as being handled, unless the limit of requests that
+	/// can be handled at once is already reached
+	fn acquire(layer: &'a LoadShedderLayer) -> Option<Self> {
+		layer
+			.in_flight
+			.fetch_update(Ordering::AcqRel, Ordering::Acquire, |in_flight| {
+				(in_flight < layer.config.max_in_flight_requests).then_some(in_flight + 1)
+			})
+			.ok()?;
+		layer.active_requests.add(1, &[]);
+
+		Some(Self { layer })
+	}
+}
+
+impl Drop for InFlightGuard<'_> {
+	fn drop(&mut self) {
+		self.layer.in_flight.fetch_sub(1, Ordering::AcqRel);
+		self.layer.active_requests.add(-1, &[]);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn rejects_requests_past_the_limit() {
+		let layer = LoadShedderLayer::new(LoadSheddingConfig {
+			max_in_flight_requests: 2,
+			retry_after_seconds: 1,
+		});
+
+		let first = InFlightGuard::acquire(&layer);
+		let second = InFlightGuard::acquire(&layer);
+		assert!(first.is_some() && second.is_some());
+		assert!(InFlightGuard::acquire(&layer).is_none());
+		assert_eq!(layer.in_flight.load(Ordering::Acquire), 2);
+
+		drop(first);
+		assert!(InFlightGuard::acquire(&layer).is_some());
+		assert_eq!(layer.in_flight.load(Ordering::Acquire), 1);
+	}
+}
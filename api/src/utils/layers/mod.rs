@@ -1,17 +1,34 @@
+/// Emits a structured access log line for every request made to an endpoint
+mod access_logger;
+/// Records the usage of the API by each workspace, so that it can be aggregated
+/// into usage analytics for the workspace
+mod api_usage_recorder;
 /// Handles functions that processes authenticated requests
 mod auth_endpoint_handler;
 /// Handles the authentication of the requests in case the route is protected
 mod authenticator;
+/// Resolves the real IP address of the client when the request is forwarded by
+/// a trusted reverse proxy, and ignores forwarding headers otherwise
+mod client_ip_resolver;
+/// Negotiates the encoding of the request and response bodies, converting them
+/// from and to MessagePack when the client asks for it
+mod content_negotiation;
 /// Handles the creation of a database transaction and a redis connection and
 /// passes it to the next layer
 mod data_store_connection_handler;
 /// Handles functions that processes unauthenticated requests
 mod endpoint_handler;
+/// Rejects requests with a `503` when the API is already handling too many
+/// requests at once, instead of queuing them
+mod load_shedder;
 /// Handles the creation of a login id and the validation of the login id. This
 /// layer is also responsible for the swapping of the login id in case it is
 /// required, as described in the documentation for
 /// [`UserWebLogin`][models::api::user::UserWebLogin]
 mod login_id_manager;
+/// Responds to `OPTIONS` requests with the methods that are allowed on the
+/// requested path
+mod options_handler;
 /// Handles the preprocessing of the request, such as the validation of the
 /// request body and returning the error if the request body is invalid
 mod preprocess_handler;
@@ -25,11 +42,17 @@ mod request_parser;
 mod user_agent_validation_layer;
 
 pub use self::{
+	access_logger::*,
+	api_usage_recorder::*,
 	auth_endpoint_handler::*,
 	authenticator::*,
+	client_ip_resolver::*,
+	content_negotiation::*,
 	data_store_connection_handler::*,
 	endpoint_handler::*,
+	load_shedder::*,
 	login_id_manager::*,
+	options_handler::*,
 	preprocess_handler::*,
 	request_parser::*,
 	user_agent_validation_layer::*,
@@ -0,0 +1,150 @@
+use std::{
+	convert::Infallible,
+	future::Future,
+	task::{Context, Poll},
+};
+
+use axum::{
+	body::Body,
+	http::{header, HeaderValue, Method, Request, StatusCode},
+	response::Response,
+};
+use tower::{Layer, Service};
+
+use crate::prelude::*;
+
+/// The [`tower::Layer`] used to respond to `OPTIONS` requests with the methods
+/// that are allowed on the requested path. The router already knows the
+/// allowed methods of every path, and lists them in the `Allow` header when it
+/// rejects a request with a method that isn't allowed. This layer turns that
+/// rejection into a successful response for `OPTIONS` requests. Paths that
+/// don't exist are still responded to with a `404`.
+#[derive(Clone, Debug, Default)]
+pub struct OptionsHandlerLayer;
+
+impl OptionsHandlerLayer {
+	/// Create a new instance of the [`OptionsHandlerLayer`]
+	pub const fn new() -> Self {
+		Self
+	}
+}
+
+impl<S> Layer<S> for OptionsHandlerLayer {
+	type Service = OptionsHandlerService<S>;
+
+	fn layer(&self, inner: S) -> Self::Service {
+		OptionsHandlerService { inner }
+	}
+}
+
+/// The underlying service that runs when the [`OptionsHandlerLayer`] is used.
+#[derive(Clone, Debug)]
+pub struct OptionsHandlerService<S> {
+	/// The inner service that will be called with the request
+	inner: S,
+}
+
+impl<S> Service<Request<Body>> for OptionsHandlerService<S>
+where
+	S: Service<Request<Body>, Response = Response, Error = Infallible> + Clone + Send + 'static,
+	S::Future: Send,
+{
+	type Error = Infallible;
+	type Response = Response;
+
+	type Future = impl Future<Output = Result<Self::Response, Self::Error>>;
+
+	fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+		self.inner.poll_ready(cx)
+	}
+
+	#[instrument(skip(self, req), name = "OptionsHandlerService")]
+	fn call(&mut self, req: Request<Body>) -> Self::Future {
+		let mut inner = self.inner.clone();
+
+		async move {
+			if req.method() != Method::OPTIONS {
+				return inner.call(req).await;
+			}
+
+			let Ok(response) = inner.call(req).await;
+			if response.status() != StatusCode::METHOD_NOT_ALLOWED {
+				return Ok(response);
+			}
+			let Some(allowed) = response
+				.headers()
+				.get(header::ALLOW)
+				.and_then(|value| value.to_str().ok())
+			else {
+				return Ok(response);
+			};
+
+			let mut allowed = allowed
+				.split(',')
+				.map(str::trim)
+				.filter(|method| !method.is_empty())
+				.map(String::from)
+				.collect::<Vec<_>>();
+			allowed.push(Method::OPTIONS.to_string());
+
+			let mut options_response = Response::new(Body::empty());
+			*options_response.status_mut() = StatusCode::NO_CONTENT;
+			if let Ok(value) = HeaderValue::from_str(&allowed.join(", ")) {
+				options_response.headers_mut().insert(header::ALLOW, value);
+			}
+
+			Ok(options_response)
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use axum::{routing::get, Router};
+	use tower::ServiceExt;
+
+	use super::*;
+
+	/// Sends a request with the given method and path to a router with a
+	/// single `GET` route, through the [`OptionsHandlerLayer`]
+	async fn respond(method: Method, path: &str) -> Response {
+		let router = Router::new()
+			.route("/deployment", get(|| async { "deployment" }))
+			.layer(OptionsHandlerLayer::new());
+		let request = Request::builder()
+			.method(method)
+			.uri(path)
+			.body(Body::empty())
+			.unwrap();
+
+		let Ok(response) = router.oneshot(request).await;
+		response
+	}
+
+	#[tokio::test]
+	async fn options_requests_are_responded_to_with_the_allowed_methods() {
+		let response = respond(Method::OPTIONS, "/deployment").await;
+
+		assert_eq!(response.status(), StatusCode::NO_CONTENT);
+		let allowed = response.headers()[header::ALLOW].to_str().unwrap();
+		let mut allowed = allowed.split(", ").collect::<Vec<_>>();
+		allowed.sort_unstable();
+		assert_eq!(allowed, ["GET", "HEAD", "OPTIONS"]);
+	}
+
+	#[tokio::test]
+	async fn other_requests_are_left_alone() {
+		assert_eq!(
+			respond(Method::GET, "/deployment").await.status(),
+			StatusCode::OK
+		);
+		assert_eq!(
+			respond(Method::POST, "/deployment").await.status(),
+			StatusCode::METHOD_NOT_ALLOWED
+		);
+		assert_eq!(
+			respond(Method::OPTIONS, "/unknown").await.status(),
+			StatusCode::NOT_FOUND
+		);
+	}
+}
@@ -112,6 +112,7 @@ where
 				redis,
 				client_ip,
 				config,
+				clock,
 			} = req;
 			let req = AppRequest {
 				request: ProcessedApiRequest::try_from(request).map_err(
@@ -127,6 +128,7 @@ where
 				redis,
 				client_ip,
 				config,
+				clock,
 			};
 			inner.call(req).await
 		}
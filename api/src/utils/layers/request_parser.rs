@@ -8,8 +8,8 @@ use std::{
 
 use axum::{
 	body::Body,
-	extract::Path,
-	http::Request,
+	extract::{path::ErrorKind, rejection::PathRejection, Path},
+	http::{HeaderValue, Request},
 	response::{IntoResponse, Response},
 	RequestExt,
 };
@@ -17,21 +17,25 @@ use models::{
 	prelude::*,
 	utils::{FromAxumRequest, GenericResponse, Headers, IntoAxumResponse},
 	ApiErrorResponse,
+	ApiSuccessResponse,
 };
 use preprocess::Preprocessable;
 use tower::{Layer, Service};
 
-use crate::utils::extractors::ClientIP;
+use crate::utils::{constants, extractors::ClientIP};
 
 /// A [`tower::Layer`] that can be used to parse the request and call the inner
 /// service with the parsed request. Ideally, this will automatically be done by
 /// [`RouterExt::mount_endpoint`], and you should not need to use this directly.
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug)]
 pub struct RequestParserLayer<E>
 where
 	E: ApiEndpoint,
 	<E::RequestBody as Preprocessable>::Processed: Send,
 {
+	/// The maximum number of items that can be requested per page, for
+	/// paginated endpoints
+	max_page_size: usize,
 	/// The endpoint type that this layer will handle.
 	phantom: PhantomData<E>,
 }
@@ -41,9 +45,11 @@ where
 	E: ApiEndpoint,
 	<E::RequestBody as Preprocessable>::Processed: Send,
 {
-	/// Create a new instance of the [`RequestParserLayer`]
-	pub const fn new() -> Self {
+	/// Create a new instance of the [`RequestParserLayer`], limiting the page
+	/// size of paginated endpoints to the given maximum
+	pub const fn new(max_page_size: usize) -> Self {
 		Self {
+			max_page_size,
 			phantom: PhantomData,
 		}
 	}
@@ -60,6 +66,7 @@ where
 	fn layer(&self, inner: S) -> Self::Service {
 		RequestParserService {
 			inner,
+			max_page_size: self.max_page_size,
 			phantom: PhantomData,
 		}
 	}
@@ -78,6 +85,9 @@ where
 {
 	/// The inner service that will be called with the parsed request.
 	inner: S,
+	/// The maximum number of items that can be requested per page, for
+	/// paginated endpoints
+	max_page_size: usize,
 	/// The endpoint type that this service will handle.
 	phantom: PhantomData<E>,
 }
@@ -103,20 +113,19 @@ where
 	#[instrument(skip(self, req), name = "RequestParserService")]
 	fn call(&mut self, mut req: Request<Body>) -> Self::Future {
 		let mut inner = self.inner.clone();
-		async {
+		let max_page_size = self.max_page_size;
+		async move {
 			debug!("Parsing request for URL: {}", req.uri());
 
-			let Ok(Path(path)) = req.extract_parts().await.inspect_err(|err| {
-				debug!("Failed to parse path `{}`: {}", req.uri().path(), err);
-			}) else {
-				return Ok(ApiErrorResponse::error_with_message(
-					ErrorType::WrongParameters,
-					"Invalid Request URL",
-				)
-				.into_response());
+			let path = match req.extract_parts().await {
+				Ok(Path(path)) => path,
+				Err(err) => {
+					debug!("Failed to parse path `{}`: {}", req.uri().path(), err);
+					return Ok(path_rejection_response(&err).into_response());
+				}
 			};
 
-			let Ok(query) = serde_urlencoded::from_str(req.uri().query().unwrap_or_default())
+			let Ok(mut query) = serde_urlencoded::from_str(req.uri().query().unwrap_or_default())
 				.inspect_err(|err| {
 					debug!("Failed to parse query `{:?}`: {}", req.uri().query(), err);
 				})
@@ -154,6 +163,11 @@ where
 				.into_response());
 			};
 
+			let page_size = E::page_size_mut(&mut query).map(|count| {
+				*count = clamp_page_size(*count, max_page_size);
+				*count
+			});
+
 			debug!("Request parsed successfully");
 
 			let request = ApiRequest {
@@ -165,7 +179,7 @@ where
 
 			info!("Calling inner service");
 
-			let response = inner
+			let mut response = inner
 				.call((request, client_ip))
 				.await
 				.inspect(|_| info!("Inner service called successfully"))
@@ -173,12 +187,12 @@ where
 					if response.body.is::<GenericResponse>() {
 						response.body.into_axum_response()
 					} else {
-						(
-							response.status_code,
-							response.headers.to_header_map(),
-							response.body.into_axum_response(),
-						)
-							.into_response()
+						ApiSuccessResponse::<E> {
+							status_code: response.status_code,
+							headers: response.headers,
+							body: response.body,
+						}
+						.into_response()
 					}
 				})
 				.unwrap_or_else(|error| {
@@ -187,10 +201,228 @@ where
 					} else {
 						warn!("Inner service failed: {:?}", error);
 					}
-					ApiErrorResponse::error(error).into_response()
+					error.into_response()
 				});
 
+			if let Some(page_size) = page_size {
+				response
+					.headers_mut()
+					.insert(constants::PAGE_SIZE_HEADER, HeaderValue::from(page_size));
+			}
+
 			Ok(response)
 		}
 	}
 }
+
+/// Converts a rejection of the path of the request into an error response. A
+/// path parameter that doesn't parse (such as an ID that isn't a valid UUID)
+/// means that the resource it refers to can't exist, so it is reported as
+/// [`ErrorType::InvalidPathParameter`] instead of a generic bad request.
+fn path_rejection_response(rejection: &PathRejection) -> ApiErrorResponse {
+	let PathRejection::FailedToDeserializePathParams(rejection) = rejection else {
+		return ApiErrorResponse::error_with_message(
+			ErrorType::WrongParameters,
+			"Invalid Request URL",
+		);
+	};
+
+	match rejection.kind() {
+		ErrorKind::ParseErrorAtKey { key, .. } | ErrorKind::InvalidUtf8InPathParam { key } => {
+			ApiErrorResponse::error_with_message(
+				ErrorType::InvalidPathParameter,
+				format!("The `{key}` in the URL is not valid"),
+			)
+		}
+		ErrorKind::ParseErrorAtIndex { .. } | ErrorKind::ParseError { .. } => {
+			ApiErrorResponse::error(ErrorType::InvalidPathParameter)
+		}
+		_ => {
+			ApiErrorResponse::error_with_message(ErrorType::WrongParameters, "Invalid Request URL")
+		}
+	}
+}
+
+/// Limits the number of items requested per page to the given maximum. A page
+/// size of 0 is treated as if no page size was requested, and the default page
+/// size is used instead.
+fn clamp_page_size(count: usize, max_page_size: usize) -> usize {
+	if count == 0 {
+		Paginated::<()>::DEFAULT_PAGE_SIZE.min(max_page_size)
+	} else {
+		count.min(max_page_size)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::{
+		net::SocketAddr,
+		sync::{Arc, Mutex},
+	};
+
+	use axum::{
+		body,
+		extract::ConnectInfo,
+		http::{header, StatusCode},
+		routing::get_service,
+		Router,
+	};
+	use axum_extra::routing::TypedPath;
+	use models::{
+		api::workspace::deployment::{
+			GetDeploymentInfoRequest,
+			ListDeploymentRequest,
+			ListDeploymentResponse,
+			ListDeploymentResponseHeaders,
+		},
+		utils::TotalCountHeader,
+		ApiErrorResponseBody,
+	};
+	use tower::ServiceExt;
+
+	use super::*;
+
+	/// Calls an endpoint with IDs in its path through the request parser, with
+	/// the given URL
+	async fn call_with_url(url: &str) -> Response {
+		let inner = tower::service_fn(|_: (ApiRequest<GetDeploymentInfoRequest>, IpAddr)| async {
+			Err::<AppResponse<GetDeploymentInfoRequest>, _>(ErrorType::InternalServerError)
+		});
+		let parser = RequestParserLayer::<GetDeploymentInfoRequest>::new(100).layer(inner);
+
+		Router::new()
+			.route(
+				<<GetDeploymentInfoRequest as ApiEndpoint>::RequestPath as TypedPath>::PATH,
+				get_service(parser),
+			)
+			.oneshot(
+				Request::get(url)
+					.header(header::AUTHORIZATION, "Bearer some-token")
+					.header(header::USER_AGENT, "patr-tests")
+					.extension(ConnectInfo(SocketAddr::from(([10, 0, 0, 1], 4000))))
+					.body(Body::empty())
+					.unwrap(),
+			)
+			.await
+			.unwrap()
+	}
+
+	#[tokio::test]
+	async fn invalid_uuids_in_the_path_are_not_found() {
+		let url = format!("/workspace/{}/deployment/not-a-uuid", Uuid::new_v4());
+		let response = call_with_url(&url).await;
+
+		assert_eq!(response.status(), StatusCode::NOT_FOUND);
+		let body = body::to_bytes(response.into_body(), usize::MAX)
+			.await
+			.unwrap();
+		let body = serde_json::from_slice::<ApiErrorResponseBody>(&body).unwrap();
+		assert_eq!(body.code, ErrorType::InvalidPathParameter);
+		assert_eq!(body.message, "The `deployment_id` in the URL is not valid");
+	}
+
+	/// Lists the deployments of a workspace through the request parser, with
+	/// the given query and a maximum page size of 100. Returns the response,
+	/// along with the page size that the handler was called with.
+	async fn list_with_query(query: &str) -> (Response, usize) {
+		let requested_count = Arc::new(Mutex::new(None));
+		let inner = tower::service_fn({
+			let requested_count = requested_count.clone();
+			move |(request, _): (ApiRequest<ListDeploymentRequest>, IpAddr)| {
+				*requested_count.lock().unwrap() = Some(request.query.count);
+				async {
+					Ok::<_, ErrorType>(
+						AppResponse::builder()
+							.body(ListDeploymentResponse {
+								deployments: vec![],
+							})
+							.headers(ListDeploymentResponseHeaders {
+								total_count: TotalCountHeader(0),
+							})
+							.status_code(StatusCode::OK)
+							.build(),
+					)
+				}
+			}
+		});
+		let parser = RequestParserLayer::<ListDeploymentRequest>::new(100).layer(inner);
+
+		let request = Request::get(format!("/workspace/{}/deployment?{query}", Uuid::new_v4()))
+			.header(header::AUTHORIZATION, "Bearer some-token")
+			.header(header::USER_AGENT, "patr-tests")
+			.extension(ConnectInfo(SocketAddr::from(([10, 0, 0, 1], 4000))))
+			.body(Body::empty())
+			.unwrap();
+		let response = Router::new()
+			.route(
+				<<ListDeploymentRequest as ApiEndpoint>::RequestPath as TypedPath>::PATH,
+				get_service(parser),
+			)
+			.oneshot(request)
+			.await
+			.unwrap();
+
+		let requested_count = requested_count
+			.lock()
+			.unwrap()
+			.expect("the handler was not called");
+		(response, requested_count)
+	}
+
+	#[tokio::test]
+	async fn paginated_endpoints_are_called_with_the_clamped_page_size() {
+		let (response, count) = list_with_query("count=100000&page=2").await;
+		assert_eq!(response.status(), StatusCode::OK);
+		assert_eq!(count, 100);
+		assert_eq!(response.headers()[constants::PAGE_SIZE_HEADER], "100");
+
+		let (response, count) = list_with_query("count=10").await;
+		assert_eq!(count, 10);
+		assert_eq!(response.headers()[constants::PAGE_SIZE_HEADER], "10");
+
+		let (response, count) = list_with_query("count=0").await;
+		assert_eq!(count, Paginated::<()>::DEFAULT_PAGE_SIZE);
+		assert_eq!(
+			response.headers()[constants::PAGE_SIZE_HEADER],
+			Paginated::<()>::DEFAULT_PAGE_SIZE.to_string()
+		);
+
+		let (response, count) = list_with_query("").await;
+		assert_eq!(count, Paginated::<()>::DEFAULT_PAGE_SIZE);
+		assert_eq!(
+			response.headers()[constants::PAGE_SIZE_HEADER],
+			Paginated::<()>::DEFAULT_PAGE_SIZE.to_string()
+		);
+	}
+
+	#[tokio::test]
+	async fn unpaginated_endpoints_have_no_page_size() {
+		let url = format!(
+			"/workspace/{}/deployment/{}?count=100000",
+			Uuid::new_v4(),
+			Uuid::new_v4()
+		);
+		let response = call_with_url(&url).await;
+
+		// The request reached the handler, which failed it
+		assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+		assert!(response
+			.headers()
+			.get(constants::PAGE_SIZE_HEADER)
+			.is_none());
+	}
+
+	#[test]
+	fn page_size_is_clamped_to_the_maximum() {
+		assert_eq!(clamp_page_size(100000, 100), 100);
+		assert_eq!(clamp_page_size(100, 100), 100);
+		assert_eq!(clamp_page_size(10, 100), 10);
+	}
+
+	#[test]
+	fn zero_page_size_uses_the_default() {
+		assert_eq!(clamp_page_size(0, 100), Paginated::<()>::DEFAULT_PAGE_SIZE);
+		assert_eq!(clamp_page_size(0, 10), 10);
+	}
+}
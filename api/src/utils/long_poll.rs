@@ -0,0 +1,205 @@
+use std::{
+	any::Any,
+	cell::RefCell,
+	collections::BTreeMap,
+	future::Future,
+	pin::Pin,
+	sync::{Arc, Mutex, OnceLock},
+	time::Duration,
+};
+
+use futures::StreamExt;
+use models::prelude::*;
+use preprocess::Preprocessable;
+use rustis::client::Client as RedisClient;
+use tokio::{
+	sync::{watch, OwnedSemaphorePermit, Semaphore},
+	task,
+};
+
+use crate::{prelude::*, utils::config::LongPollConfig};
+
+tokio::task_local! {
+	/// The response that the handler of the current request deferred until
+	/// its database transaction is committed, if any
+	static DEFERRED_RESPONSE: RefCell<Option<Box<dyn Any + Send>>>;
+}
+
+/// A response that is produced once the database transaction of its request
+/// has been committed
+pub type DeferredResponse<E> =
+	Pin<Box<dyn Future<Output = Result<AppResponse<E>, ErrorType>> + Send>>;
+
+/// The listeners for the updates of each workspace that are being waited for
+/// on this instance. Each one is fed by a single subscription to the channel of
+/// the workspace, no matter how many polls are waiting on it. The value is
+/// bumped every time an update is published, and is 0 until the subscription
+/// has started.
+static WORKSPACE_UPDATE_LISTENERS: Mutex<BTreeMap<Uuid, watch::Sender<u64>>> =
+	Mutex::new(BTreeMap::new());
+
+/// The slots for the polls that are waiting for an update on this instance
+static POLL_SLOTS: OnceLock<Arc<Semaphore>> = OnceLock::new();
+
+/// Runs the given future (usually the handler of a request), along with the
+/// response it deferred using [`defer_response`], if any. The deferred
+/// response is only awaited once the database transaction of the request is
+/// committed, so that a request that waits for something doesn't hold on to
+/// its transaction while waiting.
+pub async fn hold_deferred_response<F, E>(future: F) -> (F::Output, Option<DeferredResponse<E>>)
+where
+	F: Future,
+	E: ApiEndpoint,
+	<E::RequestBody as Preprocessable>::Processed: Send,
+{
+	DEFERRED_RESPONSE
+		.scope(RefCell::new(None), async move {
+			let output = future.await;
+			let deferred = DEFERRED_RESPONSE
+				.with(|deferred| deferred.take())
+				.and_then(|deferred| deferred.downcast::<DeferredResponse<E>>().ok())
+				.map(|deferred| *deferred);
+			(output, deferred)
+		})
+		.await
+}
+
+/// Replaces the response of the current request with the given one, once the
+/// database transaction of the request has been committed. The future must not
+/// use the database, since its transaction is gone by then. The response
+/// returned by the handler is used instead if the transaction is rolled back,
+/// such as for `HEAD` requests, or if there is no request to defer it for.
+pub fn defer_response<E, F>(response: F)
+where
+	E: ApiEndpoint,
+	<E::RequestBody as Preprocessable>::Processed: Send,
+	F: Future<Output = Result<AppResponse<E>, ErrorType>> + Send + 'static,
+{
+	let response: DeferredResponse<E> = Box::pin(response);
+	_ = DEFERRED_RESPONSE.try_with(|deferred| {
+		deferred.replace(Some(Box::new(response)));
+	});
+}
+
+/// Takes one of the slots for the polls that are waiting for an update on
+/// this instance, which is held until the poll returns. Fails with
+/// [`ErrorType::ServerOverloaded`] if all of them are taken.
+pub fn acquire_poll_slot(config: &LongPollConfig) -> Result<OwnedSemaphorePermit, ErrorType> {
+	POLL_SLOTS
+		.get_or_init(|| Arc::new(Semaphore::new(config.max_concurrent_polls)))
+		.clone()
+		.try_acquire_owned()
+		.map_err(|_| {
+			warn!("All the slots for polls are taken");
+			ErrorType::ServerOverloaded
+		})
+}
+
+/// Listens for the updates of a workspace, until it is dropped
+pub struct WorkspaceUpdateListener {
+	/// The number of updates published since the subscription started
+	receiver: watch::Receiver<u64>,
+}
+
+impl WorkspaceUpdateListener {
+	/// Starts listening for the updates of a workspace. This only returns once
+	/// the subscription to the channel of the workspace has started, so that
+	/// any update made after this returns is seen by [`Self::wait`].
+	pub async fn start(redis: &RedisClient, workspace_id: Uuid) -> Result<Self, ErrorType> {
+		let mut receiver = {
+			let mut listeners = WORKSPACE_UPDATE_LISTENERS
+				.lock()
+				.unwrap_or_else(|poisoned| poisoned.into_inner());
+			match listeners.get(&workspace_id) {
+				Some(sender) => sender.subscribe(),
+				None => {
+					let (sender, receiver) = watch::channel(0);
+					listeners.insert(workspace_id, sender.clone());
+					task::spawn(subscribe_to_workspace_updates(
+						redis.clone(),
+						workspace_id,
+						sender,
+					));
+					receiver
+				}
+			}
+		};
+
+		// Waiting for the subscription also marks the current value as seen
+		receiver
+			.wait_for(|published| *published > 0)
+			.await
+			.map_err(|_| ErrorType::server_error("unable to subscribe to workspace updates"))?;
+
+		Ok(Self { receiver })
+	}
+
+	/// Waits for the next update of the workspace, up to the given timeout.
+	/// Returns false if there wasn't any.
+	pub async fn wait(&mut self, timeout: Duration) -> bool {
+		matches!(
+			tokio::time::timeout(timeout, self.receiver.changed()).await,
+			Ok(Ok(()))
+		)
+	}
+}
+
+/// Subscribes to the channel of a workspace, bumping the value of the given
+/// sender every time an update is published on it. The subscription is
+/// stopped once nothing is listening for the updates anymore.
+async fn subscribe_to_workspace_updates(
+	redis: RedisClient,
+	workspace_id: Uuid,
+	sender: watch::Sender<u64>,
+) {
+	let channel = redis::keys::workspace_updates_channel(&workspace_id);
+	let mut pub_sub = redis.create_pub_sub();
+
+	if let Err(err) = pub_sub.subscribe(&channel).await {
+		warn!("Error subscribing to `{channel}`: {err}");
+		remove_listener(workspace_id, &sender, true);
+		return;
+	}
+	sender.send_replace(1);
+
+	let mut interval = tokio::time::interval(Duration::from_secs(1));
+	loop {
+		tokio::select! {
+			message = pub_sub.next() => {
+				if !matches!(message, Some(Ok(_))) {
+					warn!("The subscription to `{channel}` was closed");
+					remove_listener(workspace_id, &sender, true);
+					break;
+				}
+				sender.send_modify(|published| *published += 1);
+			}
+			_ = interval.tick() => {
+				if remove_listener(workspace_id, &sender, false) {
+					break;
+				}
+			}
+		}
+	}
+
+	_ = pub_sub
+		.unsubscribe(&channel)
+		.await
+		.inspect_err(|err| warn!("Error unsubscribing from `{channel}`: {err}"));
+}
+
+/// Removes the listener of a workspace if nothing is listening on it anymore,
+/// or regardless if `force` is set. The check is done while holding the lock,
+/// so that no one starts listening on a listener that is being removed. Only
+/// the task subscribed for the listener removes it, and a new one is only
+/// added once it is gone, so the listener of the workspace is always the one
+/// of the given sender. Returns true if the listener was removed.
+fn remove_listener(workspace_id: Uuid, sender: &watch::Sender<u64>, force: bool) -> bool {
+	let mut listeners = WORKSPACE_UPDATE_LISTENERS
+		.lock()
+		.unwrap_or_else(|poisoned| poisoned.into_inner());
+	if !force && sender.receiver_count() > 0 {
+		return false;
+	}
+	listeners.remove(&workspace_id);
+	true
+}
@@ -0,0 +1,161 @@
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use rand::{distributions::Alphanumeric, Rng, RngCore};
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use sha2::{Digest, Sha256};
+use totp_rs::{Algorithm as TotpAlgorithm, Secret, TOTP};
+
+use crate::{prelude::*, utils::config::AppConfig};
+
+/// The issuer that is shown in authenticator apps for the TOTP of a user
+const TOTP_ISSUER: &str = "Patr";
+
+/// The number of recovery codes that are generated when TOTP is enabled
+const RECOVERY_CODE_COUNT: usize = 10;
+
+/// The number of characters in each half of a recovery code
+const RECOVERY_CODE_HALF_LENGTH: usize = 5;
+
+/// Derives the key used to encrypt the TOTP secrets of users from the password
+/// pepper, so that the secrets can't be used by anyone who only has access to
+/// the database
+fn encryption_key(config: &AppConfig) -> Result<LessSafeKey, ErrorType> {
+	let key = Sha256::new()
+		.chain_update(b"patr-totp-secret-encryption")
+		.chain_update(config.password_pepper.as_bytes())
+		.finalize();
+
+	UnboundKey::new(&AES_256_GCM, &key)
+		.map(LessSafeKey::new)
+		.map_err(ErrorType::server_error)
+}
+
+/// Encrypts the TOTP secret of a user to be stored in the database. The
+/// returned value is the base64 encoded nonce followed by the ciphertext
+pub fn encrypt_totp_secret(config: &AppConfig, secret: &str) -> Result<String, ErrorType> {
+	let mut nonce = [0u8; NONCE_LEN];
+	rand::thread_rng().fill_bytes(&mut nonce);
+
+	let mut ciphertext = secret.as_bytes().to_vec();
+	encryption_key(config)?
+		.seal_in_place_append_tag(
+			Nonce::assume_unique_for_key(nonce),
+			Aad::empty(),
+			&mut ciphertext,
+		)
+		.map_err(ErrorType::server_error)?;
+
+	Ok(BASE64.encode([nonce.as_slice(), ciphertext.as_slice()].concat()))
+}
+
+/// Decrypts the TOTP secret of a user that was encrypted using
+/// [`encrypt_totp_secret`]
+pub fn decrypt_totp_secret(config: &AppConfig, encrypted: &str) -> Result<String, ErrorType> {
+	let encrypted = BASE64.decode(encrypted).map_err(ErrorType::server_error)?;
+	if encrypted.len() < NONCE_LEN {
+		return Err(ErrorType::server_error(
+			"encrypted TOTP secret is too short",
+		));
+	}
+	let (nonce, ciphertext) = encrypted.split_at(NONCE_LEN);
+
+	let mut ciphertext = ciphertext.to_vec();
+	let secret = encryption_key(config)?
+		.open_in_place(
+			Nonce::try_assume_unique_for_key(nonce).map_err(ErrorType::server_error)?,
+			Aad::empty(),
+			&mut ciphertext,
+		)
+		.map_err(ErrorType::server_error)?;
+
+	String::from_utf8(secret.to_vec()).map_err(ErrorType::server_error)
+}
+
+/// Generates a new base32 encoded TOTP secret
+pub fn generate_totp_secret() -> String {
+	Secret::generate_secret().to_encoded().to_string()
+}
+
+/// The URI that is used to add the TOTP of a user to an authenticator app,
+/// usually shown as a QR code
+pub fn totp_provisioning_uri(username: &str, secret: &str) -> String {
+	format!(
+		"otpauth://totp/{issuer}:{username}?secret={secret}&issuer={issuer}&algorithm=SHA1&digits=6&period=30",
+		issuer = TOTP_ISSUER,
+	)
+}
+
+/// Checks if the given OTP is currently valid for the given base32 encoded TOTP
+/// secret
+pub fn verify_totp(secret: &str, otp: &str) -> Result<bool, ErrorType> {
+	let totp = TOTP::new(
+		TotpAlgorithm::SHA1,
+		6,
+		1,
+		30,
+		Secret::Encoded(secret.to_string())
+			.to_bytes()
+			.map_err(ErrorType::server_error)?,
+	)
+	.map_err(ErrorType::server_error)?;
+
+	totp.check_current(otp).map_err(ErrorType::server_error)
+}
+
+/// Generates a new set of single-use recovery codes, which can be used to
+/// login in place of an OTP if the authenticator app is lost
+pub fn generate_recovery_codes() -> Vec<String> {
+	let mut rng = rand::thread_rng();
+	(0..RECOVERY_CODE_COUNT)
+		.map(|_| {
+			let code = (&mut rng)
+				.sample_iter(Alphanumeric)
+				.take(RECOVERY_CODE_HALF_LENGTH * 2)
+				.map(|char| char.to_ascii_lowercase() as char)
+				.collect::<String>();
+			let (first, second) = code.split_at(RECOVERY_CODE_HALF_LENGTH);
+			format!("{first}-{second}")
+		})
+		.collect()
+}
+
+/// Hashes a recovery code to be stored in the database. Recovery codes are
+/// random and long enough that a fast hash is sufficient, which also allows
+/// them to be looked up by their hash. The code is normalized before hashing,
+/// so that it can be entered in any case and with or without the dash
+pub fn hash_recovery_code(code: &str) -> String {
+	let normalized = code
+		.chars()
+		.filter(char::is_ascii_alphanumeric)
+		.map(|char| char.to_ascii_lowercase())
+		.collect::<String>();
+
+	Sha256::digest(normalized.as_bytes())
+		.iter()
+		.map(|byte| format!("{byte:02x}"))
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn recovery_codes_are_unique_and_normalized_when_hashed() {
+		let codes = generate_recovery_codes();
+		assert_eq!(codes.len(), RECOVERY_CODE_COUNT);
+		assert!(codes
+			.iter()
+			.all(|code| code.len() == RECOVERY_CODE_HALF_LENGTH * 2 + 1));
+
+		let hashes = codes
+			.iter()
+			.map(|code| hash_recovery_code(code))
+			.collect::<std::collections::BTreeSet<_>>();
+		assert_eq!(hashes.len(), codes.len());
+
+		assert_eq!(
+			hash_recovery_code("abcde-12345"),
+			hash_recovery_code(" ABCDE12345 ")
+		);
+	}
+}
@@ -15,17 +15,66 @@ pub mod layers;
 /// [2]: axum::Router
 pub mod extractors;
 
+/// Contains the backends that the values of secrets are stored in.
+pub mod secret_store;
+
+/// Contains the scanners that the images of deployments are scanned for
+/// vulnerabilities with.
+pub mod image_scanner;
+
+/// Contains the utilities used for the TOTP based multi-factor authentication
+/// of users, such as encrypting their secrets and generating recovery codes.
+pub mod mfa;
+
+/// Contains the utilities used to send messages to the runners of workspaces,
+/// with a timeout and retries.
+pub mod runner;
+
+/// Contains the utilities used to hold long polls open without holding on to
+/// a database transaction, such as listening for the updates of workspaces.
+pub mod long_poll;
+
+/// Contains the utilities used to sign URLs, so that files can be downloaded
+/// without a token for a short while after the URL is issued.
+pub mod signed_url;
+
+/// Contains the utilities used to send emails to users, such as their OTPs and
+/// the notifications of alerts.
+pub mod email;
+
 /// Contains the extension traits that will be used with the axum [`Router`][1]
 /// to mount the various endpoints on the router.
 ///
 /// [1]: axum::Router
 mod router_ext;
 
+/// Contains the source of the current time, which can be controlled in tests.
+mod clock;
+/// Contains the parser for cron expressions, used to evaluate the schedules of
+/// deployments.
+mod cron_expression;
+/// Contains the HTTP client that is shared for the requests the API makes to
+/// other services.
+mod http_client;
+/// Contains the extension traits that will be used to convert optional
+/// database rows into a uniform "not found" error.
+mod optional_row_ext;
+/// Contains the utility used to deduplicate concurrent loads of the same data,
+/// so that only one of them hits the database.
+mod single_flight;
 /// Contains the extension traits that will be used to timeout futures as
 /// they're executing.
 mod timeout_ext;
 
-pub use self::{router_ext::RouterExt, timeout_ext::TimeoutExt};
+pub use self::{
+	clock::Clock,
+	cron_expression::CronExpression,
+	http_client::http_client,
+	optional_row_ext::OptionalRowExt,
+	router_ext::RouterExt,
+	single_flight::SingleFlight,
+	timeout_ext::TimeoutExt,
+};
 
 /// A list of constants that will be used throughout the application. This is
 /// mostly kept to prevent typos.
@@ -34,10 +83,12 @@ pub mod constants {
 
 	use semver::Version;
 
-	/// The issuer (iss) of the JWT. This is currently the URL of Patr API.
+	/// The default issuer (iss) of the JWT. This is currently the URL of Patr
+	/// API. Self-hosted instances can override this in the config.
 	pub const JWT_ISSUER: &str = "https://api.patr.cloud";
 
-	/// The `aud` field in Patr's JWT
+	/// The default `aud` field in Patr's JWT. Self-hosted instances can
+	/// override this in the config.
 	pub const PATR_JWT_AUDIENCE: &str = "https://app.patr.cloud";
 
 	/// The parameters that will be used to hash, using argon2 as the hashing
@@ -50,10 +101,34 @@ pub mod constants {
 			panic!("Failed to create hashing params");
 		};
 
-	/// How long a refresh token, once generated, is valid for without any
-	/// activity. After this duration of no activity on the refresh token, it
-	/// will be considered expired.
-	pub const INACTIVE_REFRESH_TOKEN_VALIDITY: time::Duration = time::Duration::days(30);
+	/// A hash that no API token will ever match, hashed with the same
+	/// [`HASHING_PARAMS`]. When an API token that doesn't exist is used, it is
+	/// verified against this hash instead, so that the request takes as long
+	/// as it would for a token that exists, and the response time can't be
+	/// used to find out which login IDs are valid.
+	pub const DECOY_API_TOKEN_HASH: &str = concat!(
+		"$argon2id$v=19$m=8192,t=4,p=4$",
+		"I1Lo7BnGxxMbALLXo+veBA$",
+		"tLd6pDFaYkBob9wDZKFSSncnG5n0olSyJ0LeLFzvoEk"
+	);
+
+	/// How often the last activity of a login (the last activity of a web
+	/// login, or the last use of an API token) is written to the database.
+	/// Requests made within this duration of the last write do not update it
+	/// again, so the idle timeout of a login is only accurate to this duration
+	pub const LOGIN_ACTIVITY_DEBOUNCE: time::Duration = time::Duration::minutes(1);
+
+	/// The paths that cannot be requested with an impersonated session, along
+	/// with all the paths under them, since they change the security of the
+	/// account. Endpoints that delete anything are not allowed either
+	pub const IMPERSONATION_FORBIDDEN_PATHS: &[&str] = &[
+		"/auth/impersonate",
+		"/user/change-password",
+		"/user/mfa",
+		"/user/api-token",
+		"/user/recovery-email",
+		"/user/update-phone-number",
+	];
 
 	/// How long an access token is valid before it needs to be refreshed using
 	/// a refresh token (which will be provided at login)
@@ -97,7 +172,188 @@ pub mod constants {
 	/// ticket with the team.
 	pub const DEFAULT_WORKSPACE_LIMIT: i32 = 10;
 
+	/// How long the MFA token issued after the password of a user with MFA
+	/// enabled is verified is valid for. The OTP of the user has to be verified
+	/// within this time, or the user has to login again
+	pub const MFA_LOGIN_TOKEN_VALIDITY: time::Duration = time::Duration::minutes(5);
+
+	/// The maximum number of invalid codes that can be used to verify an MFA
+	/// token before it is invalidated
+	pub const MAX_MFA_LOGIN_ATTEMPTS: u64 = 5;
+
+	/// How long a user has to verify an OTP to activate MFA after enabling
+	/// TOTP, before the generated secret and recovery codes are discarded
+	pub const MFA_ENROLLMENT_VALIDITY: time::Duration = time::Duration::minutes(5);
+
 	/// The maximum number of times a user can attempt to reset a password
 	/// before getting banned altogether
 	pub const MAX_PASSWORD_RESET_ATTEMPTS: u16 = 5;
+
+	/// The maximum number of API tokens that can be revoked in a single bulk
+	/// revoke request
+	pub const MAX_BULK_REVOKE_API_TOKENS: usize = 100;
+
+	/// The maximum number of resources returned when searching a workspace
+	pub const MAX_WORKSPACE_SEARCH_RESULTS: usize = 20;
+
+	/// The number of entries of the activity feed of a workspace that are
+	/// returned in a page, if the number is not specified
+	pub const DEFAULT_WORKSPACE_ACTIVITY_LIMIT: u32 = 50;
+
+	/// The size of each time bucket that the API usage of a workspace is
+	/// aggregated into
+	pub const API_USAGE_BUCKET_SIZE: time::Duration = time::Duration::minutes(5);
+
+	/// How often the API usage rollup task aggregates the recorded usage events
+	/// into the database
+	pub const API_USAGE_ROLLUP_INTERVAL: time::Duration = time::Duration::minutes(1);
+
+	/// How long the raw API usage events are kept in Redis if they are not
+	/// aggregated by the rollup task
+	pub const API_USAGE_EVENT_RETENTION: time::Duration = time::Duration::hours(6);
+
+	/// The upper bounds, in milliseconds, of the buckets of the histogram that
+	/// the latencies of the requests to an endpoint are counted into. Latencies
+	/// above the last bound are counted in an extra bucket of their own
+	pub const API_USAGE_LATENCY_BOUNDS_MILLIS: [u64; 12] = [
+		5, 10, 25, 50, 100, 250, 500, 1_000, 2_500, 5_000, 10_000, 30_000,
+	];
+
+	/// The maximum time range that the API usage of a workspace can be queried
+	/// for in a single request
+	pub const MAX_API_USAGE_QUERY_RANGE: time::Duration = time::Duration::days(31);
+
+	/// How often the deployment scheduler wakes up to check if a new minute has
+	/// started. This is well under a minute so that no minute is skipped
+	pub const DEPLOYMENT_SCHEDULER_INTERVAL: time::Duration = time::Duration::seconds(15);
+
+	/// How often the deployment alert evaluator wakes up to check if a new
+	/// minute has started. The alert rules are evaluated once every minute
+	pub const DEPLOYMENT_ALERT_EVALUATOR_INTERVAL: time::Duration = time::Duration::seconds(15);
+
+	/// The time after which a webhook that an alert notification is sent to is
+	/// considered unreachable
+	pub const DEPLOYMENT_ALERT_WEBHOOK_TIMEOUT: time::Duration = time::Duration::seconds(10);
+
+	/// How often the deployment image scanner checks for images that were
+	/// requested to be scanned
+	pub const DEPLOYMENT_IMAGE_SCANNER_INTERVAL: time::Duration = time::Duration::seconds(5);
+
+	/// The number of scans of the images of each deployment that are kept.
+	/// Older scans are deleted, and have to be run again if their image is
+	/// deployed again
+	pub const MAX_IMAGE_SCANS_PER_DEPLOYMENT: i64 = 5;
+
+	/// How long the scan of an image that is only known by its tag can be
+	/// used to allow deploying it. The digest that a tag points to is only
+	/// known to the scanner, so the tag is scanned again after this, in case it
+	/// was moved to a different image
+	pub const IMAGE_TAG_SCAN_TTL: time::Duration = time::Duration::minutes(15);
+
+	/// How often the deployment idle scaler checks for deployments that have
+	/// not received any requests for long enough to be scaled to zero
+	pub const DEPLOYMENT_IDLE_SCALER_INTERVAL: time::Duration = time::Duration::minutes(1);
+
+	/// The shortest period of inactivity that a deployment can be configured to
+	/// be scaled to zero after. Activity is only reported by the ingress once
+	/// a minute, so anything shorter would scale down deployments in use
+	pub const MIN_SCALE_TO_ZERO_AFTER_SECONDS: u32 = 5 * 60;
+
+	/// How long a deleted deployment can be restored for, before it is
+	/// permanently removed by the deployment purger
+	pub const DEPLOYMENT_RESTORE_GRACE_PERIOD: time::Duration = time::Duration::days(7);
+
+	/// How often the deployment purger wakes up to permanently remove the
+	/// deployments whose grace period is over
+	pub const DEPLOYMENT_PURGE_INTERVAL: time::Duration = time::Duration::hours(1);
+
+	/// The domain that managed databases are reachable on, as a subdomain with
+	/// the ID of the database
+	pub const MANAGED_DATABASE_DOMAIN: &str = "db.patr.cloud";
+
+	/// How often the database credential revoker wakes up to disable the
+	/// rotated credentials of databases whose grace period is over
+	pub const DATABASE_CREDENTIAL_REVOKE_INTERVAL: time::Duration = time::Duration::minutes(1);
+
+	/// How long (in hours) the logs of deployments are retained in Loki, if not
+	/// configured otherwise
+	pub const DEFAULT_LOGS_RETENTION_HOURS: u32 = 24 * 30;
+
+	/// How long (in hours) the access logs of deployments are retained in
+	/// Loki, if not configured otherwise
+	pub const DEFAULT_ACCESS_LOG_RETENTION_HOURS: u32 = 24;
+
+	/// The maximum time range (in hours) that the logs of a deployment can be
+	/// downloaded for in a single request, if not configured otherwise
+	pub const DEFAULT_MAX_LOGS_DOWNLOAD_RANGE_HOURS: u32 = 24;
+
+	/// The number of log lines that are fetched from Loki at a time when
+	/// streaming a download of the logs of a deployment
+	pub const LOGS_DOWNLOAD_BATCH_SIZE: u32 = 1000;
+
+	/// The header used to identify a request in the access logs. If a request
+	/// does not have one, it is generated. Either way, it is sent back in the
+	/// response
+	pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+	/// The header used to tell the client how many items per page were used for
+	/// a paginated request, after the requested page size is limited to the
+	/// configured maximum
+	pub const PAGE_SIZE_HEADER: &str = "x-page-size";
+
+	/// The content type of request and response bodies encoded as MessagePack.
+	/// Clients can opt into MessagePack by sending it as the `Content-Type` of
+	/// the request and in the `Accept` header for the response
+	pub const MSGPACK_CONTENT_TYPE: &str = "application/msgpack";
+
+	/// The maximum size of a MessagePack request body that is converted to
+	/// JSON before being parsed. This is the same as the default limit that
+	/// axum applies to JSON bodies
+	pub const MAX_MSGPACK_REQUEST_BODY_SIZE: usize = 2 * 1024 * 1024;
+
+	/// The path of the health endpoint, which responds as long as the server
+	/// is running, without checking any of its dependencies
+	pub const HEALTH_CHECK_PATH: &str = "/health";
+
+	/// The path of the readiness endpoint, which checks the dependencies (the
+	/// database and Redis) of the server
+	pub const READINESS_CHECK_PATH: &str = "/ready";
+
+	/// The paths that are never rejected when the API is overloaded, so that
+	/// health and readiness checks keep reflecting the actual state of the
+	/// server instead of failing whenever it is busy
+	pub const LOAD_SHEDDING_EXEMPT_PATHS: &[&str] = &[HEALTH_CHECK_PATH, READINESS_CHECK_PATH];
+
+	/// The time after which each dependency (the database and Redis) checked
+	/// by the readiness endpoint is considered unhealthy
+	pub const READINESS_CHECK_TIMEOUT: time::Duration = time::Duration::seconds(2);
+
+	/// The path that the KV secrets engine of Vault is mounted at, if the
+	/// values of secrets are stored in Vault and the path is not configured
+	pub const DEFAULT_VAULT_KV_MOUNT: &str = "secret";
+
+	/// The endpoint that anonymized telemetry is reported to, if telemetry is
+	/// enabled and the endpoint is not configured
+	pub const DEFAULT_TELEMETRY_ENDPOINT: &str = "https://telemetry.patr.cloud/v1/report";
+
+	/// The time after which the telemetry endpoint is considered unreachable
+	pub const TELEMETRY_REQUEST_TIMEOUT: time::Duration = time::Duration::seconds(10);
+
+	/// The number of updates that are kept in the stream of updates of each
+	/// workspace. Polls with a cursor older than the oldest update kept fail,
+	/// since they would miss the updates in between
+	pub const WORKSPACE_UPDATES_MAX_LEN: usize = 1000;
+
+	/// How long a notification from the database is claimed for by the
+	/// instance of the API that adds it to the updates of its workspace. The
+	/// same notification received again within this time isn't added again
+	pub const WORKSPACE_UPDATE_CLAIM_TTL: time::Duration = time::Duration::seconds(60);
+
+	/// How long the stream of updates of a workspace is kept after the last
+	/// update is added to it
+	pub const WORKSPACE_UPDATES_RETENTION: time::Duration = time::Duration::days(1);
+
+	/// The maximum number of updates that are returned by a single poll for
+	/// the updates of a workspace. The rest are returned by the next polls
+	pub const WORKSPACE_UPDATES_PER_POLL: usize = 100;
 }
@@ -0,0 +1,25 @@
+use models::ErrorType;
+
+/// An extension trait for [`Option`]s, usually the result of a
+/// `fetch_optional` query, that allows them to be converted into a
+/// [`Result`] with a uniform "not found" error.
+pub trait OptionalRowExt<T>
+where
+	Self: Sized,
+{
+	/// Convert the option into a result, returning
+	/// [`ErrorType::ResourceDoesNotExist`] if the value is [`None`].
+	fn or_not_found(self) -> Result<T, ErrorType> {
+		self.or_not_found_with(ErrorType::ResourceDoesNotExist)
+	}
+
+	/// Convert the option into a result, returning the given error if the
+	/// value is [`None`].
+	fn or_not_found_with(self, error: ErrorType) -> Result<T, ErrorType>;
+}
+
+impl<T> OptionalRowExt<T> for Option<T> {
+	fn or_not_found_with(self, error: ErrorType) -> Result<T, ErrorType> {
+		self.ok_or(error)
+	}
+}
@@ -1,6 +1,7 @@
 use std::{net::IpAddr, sync::RwLock};
 
 use axum::{
+	http::Method,
 	routing::{MethodFilter, MethodRouter},
 	Router,
 };
@@ -16,8 +17,11 @@ use tower::{
 };
 
 use super::layers::{
+	AccessLoggerLayer,
+	ApiUsageRecorderLayer,
 	AuthenticationLayer,
 	ClientType,
+	ContentNegotiationLayer,
 	PreprocessLayer,
 	RequestParserLayer,
 	UserAgentValidationLayer,
@@ -36,6 +40,10 @@ use crate::{
 /// Extension trait for axum Router to mount an API endpoint directly along with
 /// the required request parser, Rate limiter, Audit logger and Auth
 /// middlewares, using tower layers.
+///
+/// `GET` endpoints are also mounted for `HEAD` requests. These run the same
+/// handler, so that the headers are identical, but any changes made to the
+/// database are rolled back and the body is not sent.
 pub trait RouterExt<S>
 where
 	S: Clone + Send + Sync + 'static,
@@ -104,24 +112,44 @@ where
 
 		// Setup the layers for the backend
 		if <E as ApiEndpoint>::API_ALLOWED || cfg!(debug_assertions) {
-			self.route(
+			let method_router = |method: MethodFilter, data_store: DataStoreConnectionLayer<E>| {
+				MethodRouter::<S>::new().on(method, || async {}).layer(
+					ServiceBuilder::new()
+						.layer(AccessLoggerLayer::<E>::new(state.reloadable_config.clone()))
+						.layer(ContentNegotiationLayer::new())
+						// .layer(todo!("Add rate limiter checker middleware here")),
+						.layer(RequestParserLayer::new(
+							state.config.pagination.max_page_size,
+						))
+						.layer(data_store)
+						// .layer(todo!("Add rate limiter value updater middleware here"))
+						.layer(PreprocessLayer::new())
+						.layer(UserAgentValidationLayer::new())
+						.layer(EndpointLayer::new(handler.clone())),
+				)
+			};
+
+			let router = self.route(
 				<<E as ApiEndpoint>::RequestPath as TypedPath>::PATH,
-				MethodRouter::<S>::new()
-					.on(
-						MethodFilter::try_from(<E as ApiEndpoint>::METHOD).unwrap(),
-						|| async {},
-					)
-					.layer(
-						ServiceBuilder::new()
-							// .layer(todo!("Add rate limiter checker middleware here")),
-							.layer(RequestParserLayer::new())
-							.layer(DataStoreConnectionLayer::with_state(state.clone()))
-							// .layer(todo!("Add rate limiter value updater middleware here"))
-							.layer(PreprocessLayer::new())
-							.layer(UserAgentValidationLayer::new())
-							.layer(EndpointLayer::new(handler)),
+				method_router(
+					MethodFilter::try_from(<E as ApiEndpoint>::METHOD).unwrap(),
+					DataStoreConnectionLayer::with_state(state.clone()),
+				),
+			);
+
+			// A HEAD request runs the same handler as the GET request, so that
+			// the headers are identical, but its changes are discarded
+			if <E as ApiEndpoint>::METHOD == Method::GET {
+				router.route(
+					<<E as ApiEndpoint>::RequestPath as TypedPath>::PATH,
+					method_router(
+						MethodFilter::HEAD,
+						DataStoreConnectionLayer::read_only(state.clone()),
 					),
-			)
+				)
+			} else {
+				router
+			}
 		} else {
 			self
 		}
@@ -154,7 +182,10 @@ where
 						.layer(DataStoreConnectionLayer::with_state(state.clone()))
 						.layer(PreprocessLayer::new())
 						.layer(UserAgentValidationLayer::new())
-						.layer(AuthenticationLayer::new(ClientType::WebDashboard))
+						.layer(AuthenticationLayer::new(
+							ClientType::WebDashboard,
+							state.clone(),
+						))
 						// .layer(todo!("Add permission checker middleware here"))
 						// .layer(todo!("Add rate limiter value updater middleware here"))
 						// .layer(todo!("Add audit logger middleware here"))
@@ -171,27 +202,54 @@ where
 
 		// Setup the layers for the backend
 		if <E as ApiEndpoint>::API_ALLOWED || cfg!(debug_assertions) {
-			self.route(
+			let method_router = |method: MethodFilter, data_store: DataStoreConnectionLayer<E>| {
+				MethodRouter::<S>::new().on(method, || async {}).layer(
+					ServiceBuilder::new()
+						.layer(AccessLoggerLayer::<E>::new(state.reloadable_config.clone()))
+						.layer(ApiUsageRecorderLayer::new(
+							state.redis.get(),
+							format!("{} {}", E::METHOD, <E::RequestPath as TypedPath>::PATH),
+						))
+						.layer(ContentNegotiationLayer::new())
+						// .layer(todo!("Add rate limiter checker middleware here")),
+						.layer(RequestParserLayer::new(
+							state.config.pagination.max_page_size,
+						))
+						.layer(data_store)
+						.layer(PreprocessLayer::new())
+						.layer(UserAgentValidationLayer::new())
+						.layer(AuthenticationLayer::new(
+							ClientType::ApiToken,
+							state.clone(),
+						))
+						// .layer(todo!("Add permission checker middleware here"))
+						// .layer(todo!("Add rate limiter value updater middleware here"))
+						// .layer(todo!("Add audit logger middleware here"))
+						.layer(AuthEndpointLayer::new(handler.clone())),
+				)
+			};
+
+			let router = self.route(
 				<<E as ApiEndpoint>::RequestPath as TypedPath>::PATH,
-				MethodRouter::<S>::new()
-					.on(
-						MethodFilter::try_from(<E as ApiEndpoint>::METHOD).unwrap(),
-						|| async {},
-					)
-					.layer(
-						ServiceBuilder::new()
-							// .layer(todo!("Add rate limiter checker middleware here")),
-							.layer(RequestParserLayer::new())
-							.layer(DataStoreConnectionLayer::with_state(state.clone()))
-							.layer(PreprocessLayer::new())
-							.layer(UserAgentValidationLayer::new())
-							.layer(AuthenticationLayer::new(ClientType::ApiToken))
-							// .layer(todo!("Add permission checker middleware here"))
-							// .layer(todo!("Add rate limiter value updater middleware here"))
-							// .layer(todo!("Add audit logger middleware here"))
-							.layer(AuthEndpointLayer::new(handler)),
+				method_router(
+					MethodFilter::try_from(<E as ApiEndpoint>::METHOD).unwrap(),
+					DataStoreConnectionLayer::with_state(state.clone()),
+				),
+			);
+
+			// A HEAD request runs the same handler as the GET request, so that
+			// the headers are identical, but its changes are discarded
+			if <E as ApiEndpoint>::METHOD == Method::GET {
+				router.route(
+					<<E as ApiEndpoint>::RequestPath as TypedPath>::PATH,
+					method_router(
+						MethodFilter::HEAD,
+						DataStoreConnectionLayer::read_only(state.clone()),
 					),
-			)
+				)
+			} else {
+				router
+			}
 		} else {
 			self
 		}
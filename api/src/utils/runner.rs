@@ -0,0 +1,281 @@
+use std::{cell::RefCell, fmt::Display, future::Future};
+
+use models::api::workspace::runner::StreamRunnerDataForWorkspaceServerMsg;
+use rustis::{client::Client as RedisClient, commands::PubSubCommands};
+
+use crate::{
+	prelude::*,
+	utils::{config::RunnerConfig, TimeoutExt},
+};
+
+tokio::task_local! {
+	/// The messages to runners that were sent while handling the current
+	/// request. These are only published once the database transaction of the
+	/// request is committed, so that a runner never acts on changes that were
+	/// rolled back.
+	static PENDING_MESSAGES: RefCell<Vec<PendingMessage>>;
+}
+
+/// A message to a runner that is waiting to be published
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingMessage {
+	/// The workspace that the runner belongs to
+	workspace_id: Uuid,
+	/// The runner that the message is for
+	runner_id: Uuid,
+	/// The serialized message
+	payload: String,
+}
+
+/// Sends a message to a runner over the Redis channel that its connection is
+/// subscribed to.
+///
+/// When called while handling a request, the message is only queued, and is
+/// published after the database transaction of the request is committed,
+/// before the response is sent (see [`publish_pending_messages`]). If the
+/// request fails, the message is dropped along with the rest of the changes of
+/// the request.
+///
+/// Otherwise, the message is published right away. Each attempt is timed out,
+/// and the message is retried with an exponential backoff (as configured in
+/// [`RunnerConfig`]) if it times out or fails. Once all the retries are
+/// exhausted, [`ErrorType::RunnerUnreachable`] is returned. A runner that isn't
+/// connected is not an error, since runners reconcile all their resources
+/// whenever they connect.
+#[instrument(skip(redis, config, message))]
+pub async fn send_message(
+	redis: &RedisClient,
+	config: &RunnerConfig,
+	workspace_id: Uuid,
+	runner_id: Uuid,
+	message: &StreamRunnerDataForWorkspaceServerMsg,
+) -> Result<(), ErrorType> {
+	let message = PendingMessage {
+		workspace_id,
+		runner_id,
+		payload: serde_json::to_string(message).map_err(ErrorType::server_error)?,
+	};
+
+	let Some(message) = queue_message(message) else {
+		return Ok(());
+	};
+
+	publish(redis, config, &message).await.map(|_| ())
+}
+
+/// Sends a message to a runner right away, even while handling a request,
+/// and only succeeds if the runner is connected to receive it. This is used
+/// for messages that must not be missed by a runner that is offline, which
+/// have to be retried until a runner receives them.
+#[instrument(skip(redis, config, message))]
+pub async fn deliver_message(
+	redis: &RedisClient,
+	config: &RunnerConfig,
+	workspace_id: Uuid,
+	runner_id: Uuid,
+	message: &StreamRunnerDataForWorkspaceServerMsg,
+) -> Result<(), ErrorType> {
+	let message = PendingMessage {
+		workspace_id,
+		runner_id,
+		payload: serde_json::to_string(message).map_err(ErrorType::server_error)?,
+	};
+
+	match publish(redis, config, &message).await? {
+		0 => Err(ErrorType::RunnerUnreachable),
+		_ => Ok(()),
+	}
+}
+
+/// Runs the given future (usually the handler of a request), holding on to the
+/// messages it sends to runners instead of publishing them. The messages are
+/// returned along with the output of the future, to be published with
+/// [`publish_pending_messages`] once the changes of the request are committed.
+pub async fn hold_messages<F>(future: F) -> (F::Output, Vec<PendingMessage>)
+where
+	F: Future,
+{
+	PENDING_MESSAGES
+		.scope(RefCell::new(Vec::new()), async move {
+			let output = future.await;
+			let messages = PENDING_MESSAGES.with(|messages| messages.take());
+			(output, messages)
+		})
+		.await
+}
+
+/// Publishes the messages that were held while handling a request, with the
+/// timeout and the retries of the config. Every message is published even if
+/// some of them fail. If any of the runners stays unreachable once the retries
+/// are exhausted, [`ErrorType::RunnerUnreachable`] is returned. The changes of
+/// the request are already committed by then, and the runner picks them up on
+/// its next full reconciliation.
+pub async fn publish_pending_messages(
+	redis: &RedisClient,
+	config: &RunnerConfig,
+	messages: Vec<PendingMessage>,
+) -> Result<(), ErrorType> {
+	publish_all(messages, |message| async move {
+		publish(redis, config, &message).await
+	})
+	.await
+}
+
+/// Publishes each of the messages with the given function, returning the last
+/// error once all of them have been tried
+async fn publish_all<F, Fut>(messages: Vec<PendingMessage>, mut publish: F) -> Result<(), ErrorType>
+where
+	F: FnMut(PendingMessage) -> Fut,
+	Fut: Future<Output = Result<usize, ErrorType>>,
+{
+	let mut result = Ok(());
+	for message in messages {
+		let runner_id = message.runner_id;
+		if let Err(err) = publish(message).await {
+			warn!("Failed to send a message to runner `{}`: {err}", runner_id);
+			result = Err(err);
+		}
+	}
+	result
+}
+
+/// Adds the message to the messages held for the current request, if any.
+/// Returns the message back if there is no request to hold it for.
+fn queue_message(message: PendingMessage) -> Option<PendingMessage> {
+	let mut message = Some(message);
+	_ = PENDING_MESSAGES.try_with(|messages| {
+		messages.borrow_mut().extend(message.take());
+	});
+	message
+}
+
+/// Publishes a message to the channel of the runner, with the timeout and the
+/// retries of the config. Returns the number of connections of the runner that
+/// received the message.
+async fn publish(
+	redis: &RedisClient,
+	config: &RunnerConfig,
+	message: &PendingMessage,
+) -> Result<usize, ErrorType> {
+	let PendingMessage {
+		workspace_id,
+		runner_id,
+		payload,
+	} = message;
+	let channel = format!("{}/runner/{}/stream", workspace_id, runner_id);
+
+	retry_publish(config, runner_id, || async {
+		redis.publish(&channel, payload).await
+	})
+	.await
+}
+
+/// Makes attempts to publish a message to a runner with the given function,
+/// timing out each attempt and retrying with an exponential backoff, as
+/// configured. Returns the number of connections of the runner that received
+/// the message.
+async fn retry_publish<F, Fut, E>(
+	config: &RunnerConfig,
+	runner_id: &Uuid,
+	mut attempt: F,
+) -> Result<usize, ErrorType>
+where
+	F: FnMut() -> Fut,
+	Fut: Future<Output = Result<usize, E>>,
+	E: Display,
+{
+	for retry in 0..=config.max_retries {
+		if retry > 0 {
+			tokio::time::sleep(config.retry_backoff(retry - 1)).await;
+		}
+
+		match attempt().timeout(config.request_timeout()).await {
+			Ok(Ok(receivers)) => {
+				if receivers == 0 {
+					debug!("Runner `{}` is not connected", runner_id);
+				}
+				return Ok(receivers);
+			}
+			Ok(Err(err)) => warn!("Failed to send message to runner `{}`: {}", runner_id, err),
+			Err(_) => warn!("Timed out sending message to runner `{}`", runner_id),
+		}
+	}
+
+	info!(
+		"Runner `{}` is unreachable after {} retries",
+		runner_id, config.max_retries
+	);
+	Err(ErrorType::RunnerUnreachable)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// A message for a random runner
+	fn message() -> PendingMessage {
+		PendingMessage {
+			workspace_id: Uuid::new_v4(),
+			runner_id: Uuid::new_v4(),
+			payload: r#"{"type":"deploymentDeleted"}"#.to_string(),
+		}
+	}
+
+	#[tokio::test]
+	async fn messages_are_held_while_handling_a_request() {
+		let (first, second) = (message(), message());
+
+		let (returned, held) =
+			hold_messages(async { (queue_message(first.clone()), queue_message(second.clone())) })
+				.await;
+
+		assert_eq!(returned, (None, None));
+		assert_eq!(held, vec![first, second]);
+	}
+
+	#[tokio::test]
+	async fn messages_are_sent_right_away_outside_a_request() {
+		let message = message();
+
+		assert_eq!(queue_message(message.clone()), Some(message));
+	}
+
+	#[tokio::test]
+	async fn unreachable_runners_are_retried_until_exhausted() {
+		let config = RunnerConfig {
+			request_timeout_millis: 10,
+			max_retries: 2,
+			retry_backoff_millis: 1,
+		};
+		let mut attempts = 0;
+
+		let result = retry_publish(&config, &Uuid::new_v4(), || {
+			attempts += 1;
+			std::future::pending::<Result<usize, String>>()
+		})
+		.await;
+
+		assert_eq!(result, Err(ErrorType::RunnerUnreachable));
+		assert_eq!(attempts, 3);
+	}
+
+	#[tokio::test]
+	async fn unreachable_runners_fail_the_pending_messages() {
+		let (unreachable, reachable) = (message(), message());
+		let mut published = Vec::new();
+
+		let result = publish_all(vec![unreachable.clone(), reachable.clone()], |message| {
+			let result = if message == unreachable {
+				Err(ErrorType::RunnerUnreachable)
+			} else {
+				Ok(1)
+			};
+			published.push(message);
+			async move { result }
+		})
+		.await;
+
+		assert_eq!(result, Err(ErrorType::RunnerUnreachable));
+		assert_eq!(published, vec![unreachable, reachable]);
+	}
+}
@@ -0,0 +1,125 @@
+use time::OffsetDateTime;
+
+use super::{SecretMetadata, SecretStore};
+use crate::prelude::*;
+
+/// A [`SecretStore`] that stores the values of secrets in the `secret_value`
+/// table of the database. This is the default backend.
+pub struct DatabaseSecretStore<'a> {
+	/// The connection to the database, usually the transaction of a request
+	connection: &'a mut DatabaseConnection,
+}
+
+impl<'a> DatabaseSecretStore<'a> {
+	/// Creates a new [`DatabaseSecretStore`] that uses the given connection
+	pub fn new(connection: &'a mut DatabaseConnection) -> Self {
+		Self { connection }
+	}
+}
+
+impl SecretStore for DatabaseSecretStore<'_> {
+	async fn get(
+		&mut self,
+		workspace_id: Uuid,
+		secret_id: Uuid,
+	) -> Result<Option<String>, ErrorType> {
+		let value = query!(
+			r#"
+			SELECT
+				value
+			FROM
+				secret_value
+			WHERE
+				secret_id = $1 AND
+				workspace_id = $2;
+			"#,
+			secret_id as _,
+			workspace_id as _,
+		)
+		.fetch_optional(&mut *self.connection)
+		.await?
+		.map(|row| row.value);
+
+		Ok(value)
+	}
+
+	async fn put(
+		&mut self,
+		workspace_id: Uuid,
+		secret_id: Uuid,
+		value: &str,
+	) -> Result<(), ErrorType> {
+		query!(
+			r#"
+			INSERT INTO
+				secret_value(
+					secret_id,
+					workspace_id,
+					value,
+					updated
+				)
+			VALUES
+				($1, $2, $3, $4)
+			ON CONFLICT(secret_id) DO UPDATE SET
+				value = EXCLUDED.value,
+				updated = EXCLUDED.updated;
+			"#,
+			secret_id as _,
+			workspace_id as _,
+			value,
+			OffsetDateTime::now_utc(),
+		)
+		.execute(&mut *self.connection)
+		.await?;
+
+		Ok(())
+	}
+
+	async fn delete(&mut self, workspace_id: Uuid, secret_id: Uuid) -> Result<(), ErrorType> {
+		query!(
+			r#"
+			DELETE FROM
+				secret_value
+			WHERE
+				secret_id = $1 AND
+				workspace_id = $2;
+			"#,
+			secret_id as _,
+			workspace_id as _,
+		)
+		.execute(&mut *self.connection)
+		.await?;
+
+		Ok(())
+	}
+
+	async fn list_metadata(
+		&mut self,
+		workspace_id: Uuid,
+	) -> Result<Vec<SecretMetadata>, ErrorType> {
+		// Only the metadata columns are selected, so that the values never
+		// leave the database
+		let metadata = query!(
+			r#"
+			SELECT
+				secret_id,
+				updated
+			FROM
+				secret_value
+			WHERE
+				workspace_id = $1;
+			"#,
+			workspace_id as _,
+		)
+		.fetch_all(&mut *self.connection)
+		.await?
+		.into_iter()
+		.map(|row| SecretMetadata {
+			secret_id: row.secret_id.into(),
+			updated: Some(row.updated),
+		})
+		.collect();
+
+		Ok(metadata)
+	}
+}
@@ -0,0 +1,240 @@
+use std::future::Future;
+
+use time::OffsetDateTime;
+
+use crate::{
+	prelude::*,
+	utils::config::{SecretStoreBackend, SecretsConfig},
+};
+
+/// The backend that stores the values of secrets in the database
+mod database;
+/// The backend that stores the values of secrets in HashiCorp Vault
+mod vault;
+
+pub use self::{database::DatabaseSecretStore, vault::VaultSecretStore};
+
+/// The metadata of a secret stored in a [`SecretStore`]. This intentionally
+/// does not contain the value of the secret.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SecretMetadata {
+	/// The ID of the secret
+	pub secret_id: Uuid,
+	/// The time at which the value of the secret was last updated, if the
+	/// backend keeps track of it
+	pub updated: Option<OffsetDateTime>,
+}
+
+/// A backend that the values of secrets are stored in. The names and the
+/// permissions of secrets are always stored in the database as resources, and
+/// only their values are stored in the backend, identified by the workspace and
+/// the ID of the secret.
+pub trait SecretStore {
+	/// Gets the value of a secret. Returns `None` if the secret has no value
+	/// stored in the backend.
+	fn get(
+		&mut self,
+		workspace_id: Uuid,
+		secret_id: Uuid,
+	) -> impl Future<Output = Result<Option<String>, ErrorType>> + Send;
+
+	/// Stores the value of a secret, replacing the existing value, if any
+	fn put(
+		&mut self,
+		workspace_id: Uuid,
+		secret_id: Uuid,
+		value: &str,
+	) -> impl Future<Output = Result<(), ErrorType>> + Send;
+
+	/// Deletes the value of a secret. Deleting a secret that has no value
+	/// stored in the backend is not an error.
+	fn delete(
+		&mut self,
+		workspace_id: Uuid,
+		secret_id: Uuid,
+	) -> impl Future<Output = Result<(), ErrorType>> + Send;
+
+	/// Lists the metadata of all the secrets of a workspace that have a value
+	/// stored in the backend. Implementations must never read the values of
+	/// the secrets to do so.
+	fn list_metadata(
+		&mut self,
+		workspace_id: Uuid,
+	) -> impl Future<Output = Result<Vec<SecretMetadata>, ErrorType>> + Send;
+}
+
+/// The [`SecretStore`] that is selected by the configuration of the API.
+pub enum AppSecretStore<'a> {
+	/// The values of secrets are stored in the database
+	Database(DatabaseSecretStore<'a>),
+	/// The values of secrets are stored in Vault
+	Vault(VaultSecretStore),
+}
+
+impl<'a> AppSecretStore<'a> {
+	/// Creates the secret store that is selected by the configuration. The
+	/// database backend uses the given connection, so that the values of
+	/// secrets are changed in the same transaction as the rest of the request.
+	pub fn new(config: &SecretsConfig, connection: &'a mut DatabaseConnection) -> Self {
+		match &config.backend {
+			SecretStoreBackend::Database => Self::Database(DatabaseSecretStore::new(connection)),
+			SecretStoreBackend::Vault {
+				endpoint,
+				token,
+				mount,
+			} => Self::Vault(VaultSecretStore::new(endpoint, token, mount)),
+		}
+	}
+}
+
+impl SecretStore for AppSecretStore<'_> {
+	async fn get(
+		&mut self,
+		workspace_id: Uuid,
+		secret_id: Uuid,
+	) -> Result<Option<String>, ErrorType> {
+		match self {
+			Self::Database(store) => store.get(workspace_id, secret_id).await,
+			Self::Vault(store) => store.get(workspace_id, secret_id).await,
+		}
+	}
+
+	async fn put(
+		&mut self,
+		workspace_id: Uuid,
+		secret_id: Uuid,
+		value: &str,
+	) -> Result<(), ErrorType> {
+		match self {
+			Self::Database(store) => store.put(workspace_id, secret_id, value).await,
+			Self::Vault(store) => store.put(workspace_id, secret_id, value).await,
+		}
+	}
+
+	async fn delete(&mut self, workspace_id: Uuid, secret_id: Uuid) -> Result<(), ErrorType> {
+		match self {
+			Self::Database(store) => store.delete(workspace_id, secret_id).await,
+			Self::Vault(store) => store.delete(workspace_id, secret_id).await,
+		}
+	}
+
+	async fn list_metadata(
+		&mut self,
+		workspace_id: Uuid,
+	) -> Result<Vec<SecretMetadata>, ErrorType> {
+		match self {
+			Self::Database(store) => store.list_metadata(workspace_id).await,
+			Self::Vault(store) => store.list_metadata(workspace_id).await,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::BTreeMap;
+
+	use time::OffsetDateTime;
+
+	use super::{SecretMetadata, SecretStore};
+	use crate::prelude::*;
+
+	/// A [`SecretStore`] that keeps the values of secrets in memory, and counts
+	/// the number of times a value is read
+	#[derive(Default)]
+	struct InMemorySecretStore {
+		values: BTreeMap<(Uuid, Uuid), (String, OffsetDateTime)>,
+		value_reads: usize,
+	}
+
+	impl SecretStore for InMemorySecretStore {
+		async fn get(
+			&mut self,
+			workspace_id: Uuid,
+			secret_id: Uuid,
+		) -> Result<Option<String>, ErrorType> {
+			self.value_reads += 1;
+			Ok(self
+				.values
+				.get(&(workspace_id, secret_id))
+				.map(|(value, _)| value.clone()))
+		}
+
+		async fn put(
+			&mut self,
+			workspace_id: Uuid,
+			secret_id: Uuid,
+			value: &str,
+		) -> Result<(), ErrorType> {
+			self.values.insert(
+				(workspace_id, secret_id),
+				(value.to_string(), OffsetDateTime::now_utc()),
+			);
+			Ok(())
+		}
+
+		async fn delete(&mut self, workspace_id: Uuid, secret_id: Uuid) -> Result<(), ErrorType> {
+			self.values.remove(&(workspace_id, secret_id));
+			Ok(())
+		}
+
+		async fn list_metadata(
+			&mut self,
+			workspace_id: Uuid,
+		) -> Result<Vec<SecretMetadata>, ErrorType> {
+			Ok(self
+				.values
+				.iter()
+				.filter(|((workspace, _), _)| *workspace == workspace_id)
+				.map(|((_, secret_id), (_, updated))| SecretMetadata {
+					secret_id: *secret_id,
+					updated: Some(*updated),
+				})
+				.collect())
+		}
+	}
+
+	#[tokio::test]
+	async fn stores_and_replaces_values() {
+		let mut store = InMemorySecretStore::default();
+		let (workspace_id, secret_id) = (Uuid::new_v4(), Uuid::new_v4());
+
+		assert_eq!(store.get(workspace_id, secret_id).await.unwrap(), None);
+
+		store.put(workspace_id, secret_id, "first").await.unwrap();
+		store.put(workspace_id, secret_id, "second").await.unwrap();
+		assert_eq!(
+			store.get(workspace_id, secret_id).await.unwrap().as_deref(),
+			Some("second")
+		);
+
+		store.delete(workspace_id, secret_id).await.unwrap();
+		assert_eq!(store.get(workspace_id, secret_id).await.unwrap(), None);
+		// Deleting a secret without a value is not an error
+		store.delete(workspace_id, secret_id).await.unwrap();
+	}
+
+	#[tokio::test]
+	async fn lists_metadata_of_workspace_without_reading_values() {
+		let mut store = InMemorySecretStore::default();
+		let (workspace_id, other_workspace_id) = (Uuid::new_v4(), Uuid::new_v4());
+		let (first, second, other) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+
+		store.put(workspace_id, first, "first").await.unwrap();
+		store.put(workspace_id, second, "second").await.unwrap();
+		store.put(other_workspace_id, other, "other").await.unwrap();
+
+		let mut secret_ids = store
+			.list_metadata(workspace_id)
+			.await
+			.unwrap()
+			.into_iter()
+			.map(|metadata| metadata.secret_id)
+			.collect::<Vec<_>>();
+		secret_ids.sort();
+		let mut expected = vec![first, second];
+		expected.sort();
+
+		assert_eq!(secret_ids, expected);
+		assert_eq!(store.value_reads, 0);
+	}
+}
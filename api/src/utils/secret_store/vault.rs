@@ -0,0 +1,196 @@
+use reqwest::{header::HeaderName, Client, StatusCode};
+use serde::{Deserialize, Serialize};
+
+use super::{SecretMetadata, SecretStore};
+use crate::prelude::*;
+
+/// The header used to authenticate with Vault
+const VAULT_TOKEN_HEADER: HeaderName = HeaderName::from_static("x-vault-token");
+
+/// A [`SecretStore`] that stores the values of secrets in the KV (version 2)
+/// secrets engine of a HashiCorp Vault server. Each secret is stored at
+/// `<mount>/<workspace_id>/<secret_id>`, with its value in the `value` key.
+pub struct VaultSecretStore {
+	/// The client used to make requests to Vault
+	client: Client,
+	/// The address of the Vault server
+	endpoint: String,
+	/// The token used to authenticate with Vault
+	token: String,
+	/// The path that the KV secrets engine is mounted at
+	mount: String,
+}
+
+/// The data of a secret in Vault
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct VaultSecretData {
+	/// The value of the secret
+	value: String,
+}
+
+/// The body used to write a secret to Vault, and the `data` of the response
+/// when reading a secret from Vault
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct VaultSecret {
+	/// The data of the secret
+	data: VaultSecretData,
+}
+
+/// The response of Vault when reading a secret
+#[derive(Debug, Clone, Deserialize)]
+struct VaultReadResponse {
+	/// The secret along with its metadata
+	data: VaultSecret,
+}
+
+/// The response of Vault when listing the secrets under a path
+#[derive(Debug, Clone, Deserialize)]
+struct VaultListResponse {
+	/// The keys under the path
+	data: VaultListKeys,
+}
+
+/// The keys under a path in Vault
+#[derive(Debug, Clone, Deserialize)]
+struct VaultListKeys {
+	/// The names of the keys. Keys that contain other keys end with a `/`
+	keys: Vec<String>,
+}
+
+impl VaultSecretStore {
+	/// Creates a new [`VaultSecretStore`] that connects to the given Vault
+	/// server
+	pub fn new(endpoint: &str, token: &str, mount: &str) -> Self {
+		Self {
+			client: Client::new(),
+			endpoint: endpoint.trim_end_matches('/').to_string(),
+			token: token.to_string(),
+			mount: mount.trim_matches('/').to_string(),
+		}
+	}
+
+	/// The URL of the data of a secret
+	fn data_url(&self, workspace_id: Uuid, secret_id: Uuid) -> String {
+		format!(
+			"{}/v1/{}/data/{}/{}",
+			self.endpoint, self.mount, workspace_id, secret_id
+		)
+	}
+
+	/// The URL of the metadata of a path, which can be a secret or a workspace
+	fn metadata_url(&self, path: &str) -> String {
+		format!("{}/v1/{}/metadata/{}", self.endpoint, self.mount, path)
+	}
+}
+
+impl SecretStore for VaultSecretStore {
+	async fn get(
+		&mut self,
+		workspace_id: Uuid,
+		secret_id: Uuid,
+	) -> Result<Option<String>, ErrorType> {
+		let response = self
+			.client
+			.get(self.data_url(workspace_id, secret_id))
+			.header(VAULT_TOKEN_HEADER, &self.token)
+			.send()
+			.await
+			.map_err(ErrorType::server_error)?;
+
+		if response.status() == StatusCode::NOT_FOUND {
+			return Ok(None);
+		}
+
+		let VaultReadResponse {
+			data: VaultSecret {
+				data: VaultSecretData { value },
+			},
+		} = response
+			.error_for_status()
+			.map_err(ErrorType::server_error)?
+			.json()
+			.await
+			.map_err(ErrorType::server_error)?;
+
+		Ok(Some(value))
+	}
+
+	async fn put(
+		&mut self,
+		workspace_id: Uuid,
+		secret_id: Uuid,
+		value: &str,
+	) -> Result<(), ErrorType> {
+		self.client
+			.post(self.data_url(workspace_id, secret_id))
+			.header(VAULT_TOKEN_HEADER, &self.token)
+			.json(&VaultSecret {
+				data: VaultSecretData {
+					value: value.to_string(),
+				},
+			})
+			.send()
+			.await
+			.and_then(|response| response.error_for_status())
+			.map_err(ErrorType::server_error)?;
+
+		Ok(())
+	}
+
+	async fn delete(&mut self, workspace_id: Uuid, secret_id: Uuid) -> Result<(), ErrorType> {
+		// Deleting the metadata deletes all the versions of the secret
+		let response = self
+			.client
+			.delete(self.metadata_url(&format!("{}/{}", workspace_id, secret_id)))
+			.header(VAULT_TOKEN_HEADER, &self.token)
+			.send()
+			.await
+			.map_err(ErrorType::server_error)?;
+
+		if response.status() != StatusCode::NOT_FOUND {
+			response
+				.error_for_status()
+				.map_err(ErrorType::server_error)?;
+		}
+
+		Ok(())
+	}
+
+	async fn list_metadata(
+		&mut self,
+		workspace_id: Uuid,
+	) -> Result<Vec<SecretMetadata>, ErrorType> {
+		// Listing only returns the keys under the path, and never the values
+		let response = self
+			.client
+			.get(self.metadata_url(&workspace_id.to_string()))
+			.query(&[("list", "true")])
+			.header(VAULT_TOKEN_HEADER, &self.token)
+			.send()
+			.await
+			.map_err(ErrorType::server_error)?;
+
+		// Vault responds with a 404 if there are no secrets under the path
+		if response.status() == StatusCode::NOT_FOUND {
+			return Ok(Vec::new());
+		}
+
+		let VaultListResponse {
+			data: VaultListKeys { keys },
+		} = response
+			.error_for_status()
+			.map_err(ErrorType::server_error)?
+			.json()
+			.await
+			.map_err(ErrorType::server_error)?;
+
+		Ok(keys
+			.iter()
+			.filter_map(|key| key.parse::<Uuid>().ok())
+			.map(|secret_id| SecretMetadata {
+				secret_id,
+				updated: None,
+			})
+			.collect())
+	}
+}
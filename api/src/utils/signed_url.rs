@@ -0,0 +1,203 @@
+use std::fmt::Write;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD as BASE64_URL, Engine};
+use ring::hmac;
+use time::OffsetDateTime;
+
+use crate::{prelude::*, utils::config::SignedUrlConfig};
+
+/// The query param that the expiry of a signed URL is passed in, as a unix
+/// timestamp
+pub const EXPIRES_QUERY_PARAM: &str = "expires";
+
+/// The query param that the signature of a signed URL is passed in
+pub const SIGNATURE_QUERY_PARAM: &str = "signature";
+
+/// The key used to sign URLs, from the secret that is only used for signed
+/// URLs. This is `None` if no secret is configured, in which case signed URLs
+/// can neither be issued nor used
+fn signing_key(config: &SignedUrlConfig) -> Option<hmac::Key> {
+	config
+		.secret
+		.as_deref()
+		.filter(|secret| !secret.is_empty())
+		.map(|secret| hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes()))
+}
+
+/// The message that is signed for a URL. The expiry is a part of the message,
+/// so that it can't be extended without invalidating the signature
+fn signed_message(path: &str, expires: i64) -> String {
+	format!("{path}\n{expires}")
+}
+
+/// Signs the path of a URL so that it can be accessed without a token until it
+/// expires. The path must already be percent-encoded, exactly as it will be
+/// requested. The returned URL is the path with the expiry and the signature
+/// appended as query params
+pub fn sign_path(
+	config: &SignedUrlConfig,
+	path: &str,
+	expires_at: OffsetDateTime,
+) -> Result<String, ErrorType> {
+	let key = signing_key(config).ok_or_else(|| {
+		ErrorType::server_error("Cannot sign URL, since no secret is configured for signed URLs")
+	})?;
+
+	Ok(sign_path_with_key(&key, path, expires_at))
+}
+
+/// Signs the path of a URL using the given key. See [`sign_path`]
+fn sign_path_with_key(key: &hmac::Key, path: &str, expires_at: OffsetDateTime) -> String {
+	let expires = expires_at.unix_timestamp();
+	let signature = hmac::sign(key, signed_message(path, expires).as_bytes());
+
+	format!(
+		"{path}?{EXPIRES_QUERY_PARAM}={expires}&{SIGNATURE_QUERY_PARAM}={}",
+		BASE64_URL.encode(signature.as_ref())
+	)
+}
+
+/// Verifies the signature of a URL that was signed using [`sign_path`]. Fails
+/// with [`ErrorType::InvalidSignature`] if the URL has expired, or if the path
+/// or the expiry was tampered with
+pub fn verify_path(
+	config: &SignedUrlConfig,
+	path: &str,
+	expires: i64,
+	signature: &str,
+	now: OffsetDateTime,
+) -> Result<(), ErrorType> {
+	let Some(key) = signing_key(config) else {
+		debug!("Signed URLs are disabled, since no secret is configured for them");
+		return Err(ErrorType::InvalidSignature);
+	};
+
+	verify_path_with_key(&key, path, expires, signature, now)
+}
+
+/// Verifies the signature of a URL using the given key. See [`verify_path`]
+fn verify_path_with_key(
+	key: &hmac::Key,
+	path: &str,
+	expires: i64,
+	signature: &str,
+	now: OffsetDateTime,
+) -> Result<(), ErrorType> {
+	if expires <= now.unix_timestamp() {
+		return Err(ErrorType::InvalidSignature);
+	}
+
+	let signature = BASE64_URL
+		.decode(signature)
+		.map_err(|_| ErrorType::InvalidSignature)?;
+
+	hmac::verify(key, signed_message(path, expires).as_bytes(), &signature)
+		.map_err(|_| ErrorType::InvalidSignature)
+}
+
+/// Percent-encodes a path, leaving the `/` separators and the unreserved
+/// characters as they are, so that the path is signed exactly as it will be
+/// requested
+pub fn encode_path(path: &str) -> String {
+	path.bytes()
+		.fold(String::with_capacity(path.len()), |mut encoded, byte| {
+			if byte.is_ascii_alphanumeric() || b"-._~/".contains(&byte) {
+				encoded.push(byte as char);
+			} else {
+				_ = write!(encoded, "%{byte:02X}");
+			}
+			encoded
+		})
+}
+
+#[cfg(test)]
+mod tests {
+	use time::Duration;
+
+	use super::*;
+
+	fn query_params(url: &str) -> (&str, i64, &str) {
+		let (path, query) = url.split_once('?').unwrap();
+		let (expires, signature) = query.split_once('&').unwrap();
+		(
+			path,
+			expires.trim_start_matches("expires=").parse().unwrap(),
+			signature.trim_start_matches("signature="),
+		)
+	}
+
+	#[test]
+	fn signed_urls_are_verified_until_they_expire() {
+		let key = hmac::Key::new(hmac::HMAC_SHA256, b"secret");
+		let now = OffsetDateTime::now_utc();
+		let url = sign_path_with_key(&key, "/download/a%20b", now + Duration::minutes(5));
+		let (path, expires, signature) = query_params(&url);
+
+		assert_eq!(path, "/download/a%20b");
+		assert_eq!(
+			verify_path_with_key(&key, path, expires, signature, now),
+			Ok(())
+		);
+		assert_eq!(
+			verify_path_with_key(&key, path, expires, signature, now + Duration::minutes(5)),
+			Err(ErrorType::InvalidSignature)
+		);
+	}
+
+	#[test]
+	fn tampered_urls_are_rejected() {
+		let key = hmac::Key::new(hmac::HMAC_SHA256, b"secret");
+		let now = OffsetDateTime::now_utc();
+		let url = sign_path_with_key(&key, "/download/a", now + Duration::minutes(5));
+		let (path, expires, signature) = query_params(&url);
+
+		assert_eq!(
+			verify_path_with_key(&key, "/download/b", expires, signature, now),
+			Err(ErrorType::InvalidSignature)
+		);
+		assert_eq!(
+			verify_path_with_key(&key, path, expires + 60, signature, now),
+			Err(ErrorType::InvalidSignature)
+		);
+		assert_eq!(
+			verify_path_with_key(&key, path, expires, "not-a-signature", now),
+			Err(ErrorType::InvalidSignature)
+		);
+	}
+
+	#[test]
+	fn urls_are_only_signed_with_a_secret() {
+		let now = OffsetDateTime::now_utc();
+		let mut config = SignedUrlConfig {
+			secret: None,
+			..Default::default()
+		};
+
+		assert_eq!(
+			sign_path(&config, "/download/a", now + Duration::minutes(5)),
+			Err(ErrorType::InternalServerError)
+		);
+		assert_eq!(
+			verify_path(&config, "/download/a", now.unix_timestamp() + 60, "", now),
+			Err(ErrorType::InvalidSignature)
+		);
+
+		config.secret = Some("secret".to_string());
+		let url = sign_path(&config, "/download/a", now + Duration::minutes(5)).unwrap();
+		let (path, expires, signature) = query_params(&url);
+		assert_eq!(verify_path(&config, path, expires, signature, now), Ok(()));
+
+		// URLs signed with another secret aren't accepted
+		config.secret = Some("another secret".to_string());
+		assert_eq!(
+			verify_path(&config, path, expires, signature, now),
+			Err(ErrorType::InvalidSignature)
+		);
+	}
+
+	#[test]
+	fn paths_are_percent_encoded() {
+		assert_eq!(encode_path("assets/my logo.png"), "assets/my%20logo.png");
+		assert_eq!(encode_path("a/b-c_d.e~f"), "a/b-c_d.e~f");
+	}
+}
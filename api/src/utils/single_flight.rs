@@ -0,0 +1,204 @@
+use std::{collections::BTreeMap, future::Future, sync::Mutex};
+
+use tokio::sync::broadcast::{self, Sender};
+
+/// Deduplicates concurrent loads of the same key within this process. When
+/// multiple tasks try to load the same key at the same time, only the first
+/// one (the leader) actually runs the load. The rest wait for the leader to
+/// finish and receive a clone of its result. This prevents a thundering herd
+/// of identical database queries when a cache is cold.
+#[derive(Debug)]
+pub struct SingleFlight<K, V> {
+	/// The loads that are currently in-flight, along with the channel that
+	/// their result will be broadcast on
+	in_flight: Mutex<BTreeMap<K, Sender<V>>>,
+}
+
+impl<K, V> SingleFlight<K, V>
+where
+	K: Ord + Clone,
+	V: Clone,
+{
+	/// Create a new instance of [`SingleFlight`] with no in-flight loads
+	pub const fn new() -> Self {
+		Self {
+			in_flight: Mutex::new(BTreeMap::new()),
+		}
+	}
+
+	/// Load the value for the given key. If a load for the same key is already
+	/// in-flight, this will wait for that load to finish and return its result
+	/// instead of running `load`. If the in-flight load is cancelled before it
+	/// finishes, `load` will be run by the waiting task instead.
+	pub async fn run<F, Fut>(&self, key: K, load: F) -> V
+	where
+		F: FnOnce() -> Fut,
+		Fut: Future<Output = V>,
+	{
+		let receiver = {
+			let mut in_flight = self.lock();
+			if let Some(sender) = in_flight.get(&key) {
+				Some(sender.subscribe())
+			} else {
+				in_flight.insert(key.clone(), broadcast::channel(1).0);
+				None
+			}
+		};
+
+		if let Some(mut receiver) = receiver {
+			return match receiver.recv().await {
+				Ok(value) => value,
+				Err(_) => load().await,
+			};
+		}
+
+		// Make sure that the key is removed even if this future is dropped
+		// midway, so that the waiting tasks don't wait forever
+		let guard = InFlightGuard {
+			single_flight: self,
+			key: Some(key),
+		};
+
+		let value = load().await;
+		guard.finish(value.clone());
+
+		value
+	}
+
+	/// Lock the map of in-flight loads. The lock is never held across an await
+	/// point, so a poisoned lock can safely be recovered from.
+	fn lock(&self) -> std::sync::MutexGuard<'_, BTreeMap<K, Sender<V>>> {
+		self.in_flight
+			.lock()
+			.unwrap_or_else(|poisoned| poisoned.into_inner())
+	}
+}
+
+impl<K, V> Default for SingleFlight<K, V>
+where
+	K: Ord + Clone,
+	V: Clone,
+{
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// Removes the key of the leader from the in-flight loads when dropped
+struct InFlightGuard<'a, K, V>
+where
+	K: Ord + Clone,
+	V: Clone,
+{
+	/// The [`SingleFlight`] that the load is registered in
+	single_flight: &'a SingleFlight<K, V>,
+	/// The key of the load. This is taken once the load has finished
+	key: Option<K>,
+}
+
+impl<K, V> InFlightGuard<'_, K, V>
+where
+	K: Ord + Clone,
+	V: Clone,
+{
+	/// Broadcast the result of the load to all the waiting tasks
+	fn finish(mut self, value: V) {
+		let sender = self
+			.key
+			.take()
+			.and_then(|key| self.single_flight.lock().remove(&key));
+		if let Some(sender) = sender {
+			// If there are no waiting tasks, there's no one to send it to
+			_ = sender.send(value);
+		}
+	}
+}
+
+impl<K, V> Drop for InFlightGuard<'_, K, V>
+where
+	K: Ord + Clone,
+	V: Clone,
+{
+	fn drop(&mut self) {
+		if let Some(key) = self.key.take() {
+			self.single_flight.lock().remove(&key);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::{future, sync::Arc, time::Duration};
+
+	use super::SingleFlight;
+
+	/// Spawns a task that loads the given key, with a load that returns the
+	/// given value after a while
+	fn spawn_load(
+		single_flight: &Arc<SingleFlight<u32, u32>>,
+		key: u32,
+		value: u32,
+	) -> tokio::task::JoinHandle<u32> {
+		let single_flight = single_flight.clone();
+		tokio::spawn(async move {
+			single_flight
+				.run(key, || async move {
+					tokio::time::sleep(Duration::from_millis(100)).await;
+					value
+				})
+				.await
+		})
+	}
+
+	#[tokio::test]
+	async fn concurrent_loads_share_the_result_of_the_first() {
+		let single_flight = Arc::new(SingleFlight::new());
+
+		let leader = spawn_load(&single_flight, 1, 0);
+		tokio::time::sleep(Duration::from_millis(10)).await;
+		let waiters = (1..16)
+			.map(|value| spawn_load(&single_flight, 1, value))
+			.collect::<Vec<_>>();
+
+		assert_eq!(leader.await.unwrap(), 0);
+		for waiter in waiters {
+			assert_eq!(waiter.await.unwrap(), 0);
+		}
+	}
+
+	#[tokio::test]
+	async fn loads_of_different_keys_are_not_shared() {
+		let single_flight = Arc::new(SingleFlight::new());
+
+		let first = spawn_load(&single_flight, 1, 1);
+		let second = spawn_load(&single_flight, 2, 2);
+
+		assert_eq!(first.await.unwrap(), 1);
+		assert_eq!(second.await.unwrap(), 2);
+	}
+
+	#[tokio::test]
+	async fn finished_loads_are_not_cached() {
+		let single_flight = SingleFlight::<u32, u32>::new();
+
+		assert_eq!(single_flight.run(1, || async { 1 }).await, 1);
+		assert_eq!(single_flight.run(1, || async { 2 }).await, 2);
+	}
+
+	#[tokio::test]
+	async fn waiters_load_by_themselves_if_the_leader_is_cancelled() {
+		let single_flight = Arc::new(SingleFlight::new());
+
+		let leader = tokio::spawn({
+			let single_flight = single_flight.clone();
+			async move { single_flight.run(1, future::pending).await }
+		});
+		tokio::time::sleep(Duration::from_millis(10)).await;
+		let waiter = spawn_load(&single_flight, 1, 2);
+		tokio::time::sleep(Duration::from_millis(10)).await;
+		leader.abort();
+
+		assert_eq!(waiter.await.unwrap(), 2);
+		assert_eq!(single_flight.run(1, || async { 3 }).await, 3);
+	}
+}
@@ -44,6 +44,7 @@ pub(super) async fn execute(
 			},
 		created,
 		recovery_email,
+		pending_recovery_email,
 		recovery_phone_number,
 		is_mfa_enabled,
 	} = make_request(
@@ -72,6 +73,10 @@ pub(super) async fn execute(
 				"Recovery Email",
 				recovery_email.as_deref().unwrap_or_default(),
 			])
+			.add_row([
+				"Pending Recovery Email",
+				pending_recovery_email.as_deref().unwrap_or_default(),
+			])
 			.add_row([
 				"Recovery Phone Number",
 				recovery_phone_number
@@ -93,6 +98,7 @@ pub(super) async fn execute(
 			},
 			created,
 			recovery_email,
+			pending_recovery_email,
 			recovery_phone_number,
 			is_mfa_enabled,
 		}
@@ -21,7 +21,8 @@ pub struct LoginArgs {
 	/// as the username and your API token as the password.
 	#[arg(short = 'p', long)]
 	pub password: String,
-	/// The OTP provided by the MFA method, if any
+	/// The OTP provided by the MFA method (or one of your recovery codes), if
+	/// you have MFA enabled. You'll be prompted for it if it isn't provided
 	#[arg(long = "mfa")]
 	pub mfa_otp: Option<String>,
 }
@@ -56,10 +57,7 @@ pub(super) async fn execute(
 		std::process::ExitCode::FAILURE.exit_process();
 	}
 
-	let LoginResponse {
-		access_token,
-		refresh_token,
-	} = make_request(
+	let (access_token, refresh_token) = match make_request(
 		ApiRequest::<LoginRequest>::builder()
 			.query(())
 			.headers(LoginRequestHeaders {
@@ -69,12 +67,47 @@ pub(super) async fn execute(
 			.body(LoginRequest {
 				user_id: args.user_id,
 				password: args.password,
-				mfa_otp: args.mfa_otp,
 			})
 			.build(),
 	)
 	.await?
-	.body;
+	.body
+	.response
+	{
+		LoginResponseType::LoggedIn {
+			access_token,
+			refresh_token,
+		} => (access_token, refresh_token),
+		LoginResponseType::MfaRequired { mfa_token } => {
+			let code = match args.mfa_otp {
+				Some(code) => code,
+				None => {
+					eprint!("Enter the code from your authenticator app (or a recovery code): ");
+					let mut code = String::new();
+					std::io::stdin().read_line(&mut code)?;
+					code.trim().to_string()
+				}
+			};
+
+			let VerifyMfaLoginResponse {
+				access_token,
+				refresh_token,
+			} = make_request(
+				ApiRequest::<VerifyMfaLoginRequest>::builder()
+					.query(())
+					.headers(VerifyMfaLoginRequestHeaders {
+						user_agent: UserAgent::from_static(constants::USER_AGENT_STRING),
+					})
+					.path(VerifyMfaLoginPath)
+					.body(VerifyMfaLoginRequest { mfa_token, code })
+					.build(),
+			)
+			.await?
+			.body;
+
+			(access_token, refresh_token)
+		}
+	};
 
 	let token = BearerToken::from_str(&access_token)?;
 
@@ -131,8 +164,10 @@ pub(super) async fn execute(
 	CommandOutput {
 		text: format!("Logged in as `{username}`. Hello {first_name} {last_name}!"),
 		json: ApiSuccessResponseBody::new(LoginResponse {
-			access_token,
-			refresh_token,
+			response: LoginResponseType::LoggedIn {
+				access_token,
+				refresh_token,
+			},
 		})
 		.to_json_value(),
 	}
@@ -37,8 +37,10 @@ where
 			status_code: http::StatusCode::INTERNAL_SERVER_ERROR,
 			body: ApiErrorResponseBody {
 				success: False,
-				error: ErrorType::server_error(err.clone()),
+				code: ErrorType::server_error(err.clone()),
 				message: err,
+				quota: None,
+				field: None,
 			},
 		})?;
 	let builder = REQUEST_CLIENT
@@ -83,8 +85,10 @@ where
 				status_code: http::StatusCode::INTERNAL_SERVER_ERROR,
 				body: ApiErrorResponseBody {
 					success: False,
-					error: ErrorType::server_error(error.to_string()),
+					code: ErrorType::server_error(error.to_string()),
 					message: error.to_string(),
+					quota: None,
+					field: None,
 				},
 			});
 		}
@@ -107,8 +111,10 @@ where
 			status_code: http::StatusCode::INTERNAL_SERVER_ERROR,
 			body: ApiErrorResponseBody {
 				success: False,
-				error: ErrorType::server_error("invalid headers"),
+				code: ErrorType::server_error("invalid headers"),
 				message: "invalid headers".to_string(),
+				quota: None,
+				field: None,
 			},
 		});
 	};
@@ -132,8 +138,10 @@ where
 				status_code: http::StatusCode::INTERNAL_SERVER_ERROR,
 				body: ApiErrorResponseBody {
 					success: False,
-					error: ErrorType::server_error(error.to_string()),
+					code: ErrorType::server_error(error.to_string()),
 					message: error.to_string(),
+					quota: None,
+					field: None,
 				},
 			})
 		}
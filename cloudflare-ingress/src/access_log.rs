@@ -0,0 +1,193 @@
+use std::{cell::RefCell, time::Duration};
+
+use worker::*;
+
+use crate::{
+	models::{DeploymentAccessLog, DeploymentAccessLogsRequest},
+	utils::constants,
+};
+
+thread_local! {
+	/// The access logs that this isolate of the worker hasn't reported yet.
+	/// Logs that are still pending when the isolate is evicted are dropped,
+	/// like the ones that fail to be reported.
+	static PENDING_ACCESS_LOGS: RefCell<AccessLogBatch> = RefCell::default();
+}
+
+/// A batch of access logs that haven't been reported to the Patr API yet
+#[derive(Debug, Default)]
+struct AccessLogBatch {
+	/// The logs in the batch
+	logs: Vec<DeploymentAccessLog>,
+	/// The ID of the batch, which changes every time a new batch is started
+	id: u64,
+}
+
+/// What has to be done with a batch of access logs after a log was added to
+/// it
+#[derive(Debug)]
+enum PendingReport {
+	/// The batch is full, and these logs have to be reported now
+	Full(Vec<DeploymentAccessLog>),
+	/// A new batch with the given ID was started, which has to be reported
+	/// once it is old enough, unless it fills up before then
+	Started(u64),
+	/// The log was added to a batch that is already waiting to be reported
+	Added,
+}
+
+impl AccessLogBatch {
+	/// Adds a log to the batch, starting a new batch if it is empty
+	fn push(&mut self, log: DeploymentAccessLog) -> PendingReport {
+		let started = self.logs.is_empty();
+		if started {
+			self.id += 1;
+		}
+		self.logs.push(log);
+
+		if self.logs.len() >= constants::ACCESS_LOG_BATCH_SIZE {
+			PendingReport::Full(std::mem::take(&mut self.logs))
+		} else if started {
+			PendingReport::Started(self.id)
+		} else {
+			PendingReport::Added
+		}
+	}
+
+	/// Takes the logs of the batch with the given ID to report them, unless
+	/// it was already reported
+	fn take(&mut self, id: u64) -> Option<Vec<DeploymentAccessLog>> {
+		(self.id == id && !self.logs.is_empty()).then(|| std::mem::take(&mut self.logs))
+	}
+}
+
+/// Records a request made to a deployment, to be stored in its access logs.
+/// The logs are reported to the Patr API in batches, instead of reporting each
+/// request on its own. A batch is reported once it is full, or
+/// [`constants::ACCESS_LOG_BATCH_MAX_AGE_MILLIS`] after it was started. Batches
+/// are reported in the background, so reporting them never fails or delays
+/// the request itself.
+pub fn record(log: DeploymentAccessLog, env: &Env, ctx: &Context) {
+	let pending = PENDING_ACCESS_LOGS.with_borrow_mut(|batch| batch.push(log));
+	if matches!(pending, PendingReport::Added) {
+		return;
+	}
+
+	let token = match env.secret(constants::INGRESS_TOKEN) {
+		Ok(token) => token.to_string(),
+		Err(err) => {
+			console_error!("Cannot report access logs without an ingress token: {err}");
+			return;
+		}
+	};
+
+	ctx.wait_until(async move {
+		let logs = match pending {
+			PendingReport::Full(logs) => logs,
+			PendingReport::Started(id) => {
+				Delay::from(Duration::from_millis(
+					constants::ACCESS_LOG_BATCH_MAX_AGE_MILLIS,
+				))
+				.await;
+				let Some(logs) = PENDING_ACCESS_LOGS.with_borrow_mut(|batch| batch.take(id)) else {
+					return;
+				};
+				logs
+			}
+			PendingReport::Added => return,
+		};
+
+		let count = logs.len();
+		if let Err(err) = report(logs, &token).await {
+			console_error!("Failed to report {count} access logs: {err}");
+		}
+	});
+}
+
+/// Reports a batch of access logs to the Patr API, using the
+/// [`constants::INGRESS_TOKEN`] secret
+async fn report(logs: Vec<DeploymentAccessLog>, token: &str) -> Result<()> {
+	let mut headers = Headers::new();
+	headers.set("user-agent", "patr-ingress")?;
+	headers.set("content-type", "application/json")?;
+	headers.set("authorization", &format!("Bearer {token}"))?;
+
+	let response = Fetch::Request(Request::new_with_init(
+		&format!("{}/deployment/access-logs", constants::PATR_API_URL),
+		&RequestInit {
+			body: Some(serde_json::to_string(&DeploymentAccessLogsRequest { logs })?.into()),
+			headers,
+			method: Method::Post,
+			..Default::default()
+		},
+	)?)
+	.send()
+	.await?;
+
+	if response.status_code() != 200 {
+		return Err(Error::RustError(format!(
+			"unexpected status code {}",
+			response.status_code()
+		)));
+	}
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{AccessLogBatch, PendingReport};
+	use crate::{models::DeploymentAccessLog, utils::constants};
+
+	fn log() -> DeploymentAccessLog {
+		DeploymentAccessLog {
+			deployment_id: "deployment".to_string(),
+			timestamp: "2024-01-01T00:00:00.000Z".to_string(),
+			method: "GET".to_string(),
+			path: "/".to_string(),
+			status: 200,
+			latency_millis: 1,
+			canary: false,
+		}
+	}
+
+	#[test]
+	fn full_batches_are_reported_right_away() {
+		let mut batch = AccessLogBatch::default();
+
+		let PendingReport::Started(id) = batch.push(log()) else {
+			panic!("the first log should start a batch");
+		};
+		for _ in 2..constants::ACCESS_LOG_BATCH_SIZE {
+			assert!(matches!(batch.push(log()), PendingReport::Added));
+		}
+		let PendingReport::Full(logs) = batch.push(log()) else {
+			panic!("the batch should be full");
+		};
+		assert_eq!(logs.len(), constants::ACCESS_LOG_BATCH_SIZE);
+
+		// The batch was already reported, so it isn't reported again
+		assert!(batch.take(id).is_none());
+	}
+
+	#[test]
+	fn batches_are_only_reported_once() {
+		let mut batch = AccessLogBatch::default();
+
+		let PendingReport::Started(first) = batch.push(log()) else {
+			panic!("the first log should start a batch");
+		};
+		assert!(matches!(batch.push(log()), PendingReport::Added));
+		assert_eq!(batch.take(first).map(|logs| logs.len()), Some(2));
+		assert!(batch.take(first).is_none());
+
+		// The next log starts a new batch, which isn't taken by the report of
+		// the previous one
+		let PendingReport::Started(second) = batch.push(log()) else {
+			panic!("the next log should start a new batch");
+		};
+		assert_ne!(first, second);
+		assert!(batch.take(first).is_none());
+		assert_eq!(batch.take(second).map(|logs| logs.len()), Some(1));
+	}
+}
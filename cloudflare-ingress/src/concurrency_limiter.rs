@@ -0,0 +1,225 @@
+use std::{
+	cell::RefCell,
+	collections::{HashMap, HashSet},
+};
+
+use worker::*;
+
+use crate::utils::constants;
+
+thread_local! {
+	/// The deployments that this isolate of the worker has seen a limit for.
+	/// Only their limiters are asked for the number of requests in flight, so
+	/// that no limiter is created for deployments that aren't limited.
+	static LIMITED_DEPLOYMENTS: RefCell<HashSet<String>> = RefCell::default();
+}
+
+/// The durable object that limits the number of requests that are forwarded to
+/// a deployment at once. There is a single instance of it for each deployment
+/// (across all data centers), which keeps track of the requests that are
+/// currently being forwarded. A request is only forwarded if it can acquire a
+/// lease on a slot, and the lease is released once the deployment has
+/// responded.
+///
+/// Leases expire after [`constants::DEPLOYMENT_CONCURRENCY_LEASE_TTL_MILLIS`],
+/// so that the slot of a request whose invocation was cancelled before it
+/// could release its lease is freed up again.
+///
+/// The leases are only kept in memory. The object is not evicted while it
+/// receives requests, and once it is evicted there are no requests left to
+/// count, so the leases don't need to be persisted.
+#[durable_object]
+pub struct DeploymentConcurrencyLimiter {
+	/// The leases of the requests that are currently being forwarded, with the
+	/// time (in milliseconds since the epoch) that each of them expires at
+	leases: HashMap<u64, u64>,
+	/// The ID of the next lease to be acquired
+	next_lease: u64,
+}
+
+impl DeploymentConcurrencyLimiter {
+	/// Removes the leases that have expired
+	fn remove_expired_leases(&mut self) {
+		let now = Date::now().as_millis();
+		self.leases.retain(|_, expiry| *expiry > now);
+	}
+}
+
+#[durable_object]
+impl DurableObject for DeploymentConcurrencyLimiter {
+	fn new(_state: State, _env: Env) -> Self {
+		Self {
+			leases: HashMap::new(),
+			next_lease: 0,
+		}
+	}
+
+	async fn fetch(&mut self, req: Request) -> Result<Response> {
+		let url = req.url()?;
+		let param = |name: &str| {
+			url.query_pairs()
+				.find(|(key, _)| key == name)
+				.and_then(|(_, value)| value.parse::<u64>().ok())
+				.ok_or_else(|| Error::RustError(format!("invalid {name}")))
+		};
+		self.remove_expired_leases();
+
+		match url.path() {
+			"/acquire" => {
+				let limit = param("limit")?;
+
+				if self.leases.len() as u64 >= limit {
+					return Response::error(
+						"limit reached",
+						constants::STATUS_CODE_TOO_MANY_REQUESTS,
+					);
+				}
+
+				let lease = self.next_lease;
+				self.next_lease += 1;
+				self.leases.insert(
+					lease,
+					Date::now().as_millis() + constants::DEPLOYMENT_CONCURRENCY_LEASE_TTL_MILLIS,
+				);
+				Response::ok(lease.to_string())
+			}
+			"/release" => {
+				self.leases.remove(&param("lease")?);
+				Response::ok(self.leases.len().to_string())
+			}
+			"/in-flight" => Response::ok(self.leases.len().to_string()),
+			_ => Response::error("not found", 404),
+		}
+	}
+}
+
+/// A slot acquired to forward a request to a deployment, which has to be
+/// released using [`release`] once the deployment has responded
+pub struct Lease {
+	/// The deployment that the slot was acquired for
+	deployment_id: String,
+	/// The ID of the lease, or `None` if the limiter couldn't be reached and
+	/// the request was let through without a slot
+	id: Option<u64>,
+}
+
+/// Gets the stub of the [`DeploymentConcurrencyLimiter`] of a deployment. The
+/// limiter is created close to the given location hint if there is one, so
+/// that acquiring a slot only adds a round trip within the region that the
+/// request is forwarded to anyway.
+fn get_limiter(env: &Env, deployment_id: &str, location_hint: Option<&str>) -> Result<Stub> {
+	let id = env
+		.durable_object(constants::DEPLOYMENT_CONCURRENCY_LIMITER)?
+		.id_from_name(deployment_id)?;
+	match location_hint {
+		Some(location_hint) => id.get_stub_with_location_hint(location_hint),
+		None => id.get_stub(),
+	}
+}
+
+/// Tries to acquire a lease on a slot to forward a request to a deployment,
+/// given the maximum number of requests that can be forwarded to it at once.
+/// Returns `None` if the limit has been reached. If the limiter can't be
+/// reached, the request is let through, so that the limiter never makes a
+/// deployment unreachable.
+pub async fn acquire(
+	env: &Env,
+	deployment_id: &str,
+	location_hint: Option<&str>,
+	limit: u32,
+) -> Option<Lease> {
+	LIMITED_DEPLOYMENTS.with_borrow_mut(|deployments| {
+		if !deployments.contains(deployment_id) {
+			deployments.insert(deployment_id.to_string());
+		}
+	});
+
+	let response = async {
+		let mut response = get_limiter(env, deployment_id, location_hint)?
+			.fetch_with_str(&format!(
+				"{}/acquire?limit={}",
+				constants::DEPLOYMENT_CONCURRENCY_LIMITER_URL,
+				limit
+			))
+			.await?;
+		if response.status_code() == constants::STATUS_CODE_TOO_MANY_REQUESTS {
+			return Ok(None);
+		}
+		let lease = response
+			.text()
+			.await?
+			.parse::<u64>()
+			.map_err(|err| Error::RustError(err.to_string()))?;
+		Ok::<_, Error>(Some(lease))
+	};
+
+	match response.await {
+		Ok(Some(lease)) => Some(Lease {
+			deployment_id: deployment_id.to_string(),
+			id: Some(lease),
+		}),
+		Ok(None) => None,
+		Err(err) => {
+			console_error!("Failed to acquire a slot for deployment `{deployment_id}`: {err}");
+			Some(Lease {
+				deployment_id: deployment_id.to_string(),
+				id: None,
+			})
+		}
+	}
+}
+
+/// Releases a lease that was acquired using [`acquire`], once the deployment
+/// has responded to the request. This is done in the background, so that the
+/// response isn't held up by it. If the invocation is cancelled before this,
+/// the lease expires by itself.
+pub fn release(env: &Env, lease: Lease, location_hint: Option<&str>, ctx: &Context) {
+	let Lease {
+		deployment_id,
+		id: Some(id),
+	} = lease
+	else {
+		return;
+	};
+
+	let limiter = get_limiter(env, &deployment_id, location_hint);
+	ctx.wait_until(async move {
+		let response = async {
+			limiter?
+				.fetch_with_str(&format!(
+					"{}/release?lease={}",
+					constants::DEPLOYMENT_CONCURRENCY_LIMITER_URL,
+					id
+				))
+				.await
+		};
+
+		if let Err(err) = response.await {
+			console_error!("Failed to release a slot for deployment `{deployment_id}`: {err}");
+		}
+	});
+}
+
+/// Gets the number of requests that are currently being forwarded to a
+/// deployment, if its requests are known to be limited and its limiter can be
+/// reached
+pub async fn get_in_flight(
+	env: &Env,
+	deployment_id: &str,
+	location_hint: Option<&str>,
+) -> Option<u32> {
+	if !LIMITED_DEPLOYMENTS.with_borrow(|deployments| deployments.contains(deployment_id)) {
+		return None;
+	}
+
+	let mut response = get_limiter(env, deployment_id, location_hint)
+		.ok()?
+		.fetch_with_str(&format!(
+			"{}/in-flight",
+			constants::DEPLOYMENT_CONCURRENCY_LIMITER_URL
+		))
+		.await
+		.ok()?;
+
+	response.text().await.ok()?.parse().ok()
+}
@@ -4,16 +4,41 @@
 //! This crate is the worker that runs on cloudflare before a request is sent to
 //! any one of Patr's Kubernetes clusters.
 
-use std::collections::HashMap;
+use std::{cell::RefCell, collections::HashMap};
 
 use url::Host;
 use worker::*;
 
-use self::{models::IngressKVData, utils::constants};
-
+use self::{
+	models::{
+		DeploymentAccessLog,
+		DeploymentActivityRequest,
+		DeploymentActivityResponse,
+		DeploymentCanaryRequests,
+		DeploymentErrorPageKind,
+		DeploymentErrorPagesResponse,
+		DeploymentUpstream,
+		IngressKVData,
+	},
+	utils::constants,
+};
+
+mod access_log;
+mod concurrency_limiter;
 mod models;
 mod utils;
 
+thread_local! {
+	/// The requests sent to each version of the deployments with a canary that
+	/// haven't been reported yet, by deployment. Each isolate of the worker
+	/// counts the requests that it forwarded, and sends the counts along with
+	/// the next activity that it reports on the deployment, instead of
+	/// reporting every request on its own. Counts that fail to be reported are
+	/// dropped, since they're only used to compare the versions.
+	static CANARY_REQUESTS: RefCell<HashMap<String, DeploymentCanaryRequests>> =
+		RefCell::default();
+}
+
 /// The main function that is called when a request is made to the worker.
 #[event(fetch)]
 pub async fn main(req: Request, env: Env, ctx: Context) -> Result<Response> {
@@ -364,8 +389,203 @@ pub async fn main(req: Request, env: Env, ctx: Context) -> Result<Response> {
 			deployment_id,
 			port,
 			region,
+			location_hint,
 		} => {
-			Fetch::Request(Request::new_with_init(
+			let started_at = Date::now().as_millis();
+			let location_hint = location_hint.as_deref();
+			let activity =
+				report_deployment_activity(&deployment_id, location_hint, &env, &ctx).await;
+
+			// A share of the requests to a deployment with a canary is sent to
+			// the canary instead, based on its weight. Requests are only ever
+			// sent to replicas that are ready
+			let upstream = activity.select_upstream(
+				activity
+					.canary_weight
+					.is_some_and(|weight| js_sys::Math::random() * 100.0 < f64::from(weight)),
+			);
+			let canary = upstream == Some(DeploymentUpstream::Canary);
+
+			let DeploymentActivityResponse {
+				warming,
+				max_concurrent_requests,
+				access_logging,
+				canary_weight,
+				error_pages_version,
+				ready_replicas: _,
+				canary_ready_replicas: _,
+			} = activity;
+
+			// Only deployments that opted in have their requests logged, since
+			// the paths of requests can contain personal data. The requests to
+			// deployments with a canary are always counted, so that both
+			// versions can be compared
+			let log_access = |status: u16| {
+				let latency_millis = Date::now().as_millis().saturating_sub(started_at);
+				if canary_weight.is_some() {
+					CANARY_REQUESTS.with_borrow_mut(|requests| {
+						requests.entry(deployment_id.clone()).or_default().record(
+							canary,
+							status,
+							latency_millis,
+						)
+					});
+				}
+				if !access_logging {
+					return;
+				}
+				let path = match url.query() {
+					Some(query) => format!("{}?{}", url.path(), query),
+					None => url.path().to_string(),
+				};
+				access_log::record(
+					DeploymentAccessLog {
+						deployment_id: deployment_id.clone(),
+						timestamp: js_sys::Date::new(&(started_at as f64).into())
+							.to_iso_string()
+							.into(),
+						method: req.method().to_string(),
+						path,
+						status,
+						latency_millis,
+						canary,
+					},
+					&env,
+					&ctx,
+				);
+			};
+
+			// Custom error pages are only served to clients that accept HTML,
+			// which are usually browsers
+			let html_accepted = accepts_html(req.headers().get("accept")?.as_deref());
+			let error_pages_version = error_pages_version.filter(|_| html_accepted);
+
+			// The first request to a deployment that was scaled to zero starts
+			// it, and has to be retried once one of its replicas is ready.
+			// Browsers are shown a page that refreshes itself until then
+			if warming {
+				let mut headers = Headers::new();
+				headers.set(
+					"retry-after",
+					&constants::DEPLOYMENT_WARMING_RETRY_AFTER_SECONDS.to_string(),
+				)?;
+				headers.set(constants::DEPLOYMENT_WARMING_HEADER, "true")?;
+
+				log_access(constants::STATUS_CODE_SERVICE_UNAVAILABLE);
+				let error_page = get_error_page(
+					&deployment_id,
+					error_pages_version.as_deref(),
+					DeploymentErrorPageKind::WarmingUp,
+					&env,
+					&ctx,
+				)
+				.await;
+				if let Some(html) = error_page {
+					// Custom pages are refreshed by the browser using the
+					// header, since they can't be expected
+					// to refresh themselves
+					headers.set(
+						"refresh",
+						&constants::DEPLOYMENT_WARMING_RETRY_AFTER_SECONDS.to_string(),
+					)?;
+					return error_page_response(
+						&html,
+						DeploymentErrorPageKind::WarmingUp.status_code(),
+						&headers,
+					);
+				}
+				if html_accepted {
+					return error_page_response(
+						&warming_page(),
+						constants::STATUS_CODE_SERVICE_UNAVAILABLE,
+						&headers,
+					);
+				}
+				return Ok(Response::error(
+					"deployment is starting, please retry shortly",
+					constants::STATUS_CODE_SERVICE_UNAVAILABLE,
+				)?
+				.with_headers(headers));
+			}
+
+			// While a deployment is scaling up, none of its replicas might be
+			// ready yet. Requests are rejected until one of them is, instead of
+			// being forwarded to a replica that can't handle them
+			if upstream.is_none() {
+				let mut headers = Headers::new();
+				headers.set(
+					"retry-after",
+					&constants::DEPLOYMENT_NOT_READY_RETRY_AFTER_SECONDS.to_string(),
+				)?;
+
+				log_access(constants::STATUS_CODE_SERVICE_UNAVAILABLE);
+				let error_page = get_error_page(
+					&deployment_id,
+					error_pages_version.as_deref(),
+					DeploymentErrorPageKind::ServiceUnavailable,
+					&env,
+					&ctx,
+				)
+				.await;
+				if let Some(html) = error_page {
+					return error_page_response(
+						&html,
+						DeploymentErrorPageKind::ServiceUnavailable.status_code(),
+						&headers,
+					);
+				}
+				if html_accepted {
+					return error_page_response(
+						&warming_page(),
+						constants::STATUS_CODE_SERVICE_UNAVAILABLE,
+						&headers,
+					);
+				}
+				return Ok(Response::error(
+					"deployment has no ready replicas, please retry shortly",
+					constants::STATUS_CODE_SERVICE_UNAVAILABLE,
+				)?
+				.with_headers(headers));
+			}
+
+			// Requests past the limit of the deployment are rejected instead of
+			// overwhelming its replicas
+			let mut lease = None;
+			if let Some(limit) = max_concurrent_requests {
+				lease =
+					concurrency_limiter::acquire(&env, &deployment_id, location_hint, limit).await;
+				if lease.is_none() {
+					let mut headers = Headers::new();
+					headers.set(
+						"retry-after",
+						&constants::DEPLOYMENT_CONCURRENCY_LIMITED_RETRY_AFTER_SECONDS.to_string(),
+					)?;
+
+					log_access(constants::STATUS_CODE_SERVICE_UNAVAILABLE);
+					let error_page = get_error_page(
+						&deployment_id,
+						error_pages_version.as_deref(),
+						DeploymentErrorPageKind::ServiceUnavailable,
+						&env,
+						&ctx,
+					)
+					.await;
+					if let Some(html) = error_page {
+						return error_page_response(
+							&html,
+							DeploymentErrorPageKind::ServiceUnavailable.status_code(),
+							&headers,
+						);
+					}
+					return Ok(Response::error(
+						"deployment is handling too many requests, please retry shortly",
+						constants::STATUS_CODE_SERVICE_UNAVAILABLE,
+					)?
+					.with_headers(headers));
+				}
+			}
+
+			let response = Fetch::Request(Request::new_with_init(
 				url.as_str(),
 				&RequestInit {
 					body: req.inner().body().map(Into::into),
@@ -378,9 +598,14 @@ pub async fn main(req: Request, env: Env, ctx: Context) -> Result<Response> {
 						}),
 						polish: Some(PolishConfig::Off),
 						resolve_override: Some(format!(
-							"https://{}-{}.{}.{}",
+							"https://{}-{}{}.{}.{}",
 							port,
 							deployment_id,
+							if canary {
+								constants::DEPLOYMENT_CANARY_HOST_SUFFIX
+							} else {
+								""
+							},
 							region,
 							constants::DEFAULT_PATR_DOMAIN
 						)),
@@ -392,11 +617,326 @@ pub async fn main(req: Request, env: Env, ctx: Context) -> Result<Response> {
 				},
 			)?)
 			.send()
-			.await
+			.await;
+
+			// A request that couldn't be forwarded fails with an internal error
+			log_access(
+				response
+					.as_ref()
+					.map_or(constants::STATUS_CODE_INTERNAL_SERVER_ERROR, |response| {
+						response.status_code()
+					}),
+			);
+
+			// The slot is held until the deployment has responded, even if
+			// forwarding the request failed
+			if let Some(lease) = lease {
+				concurrency_limiter::release(&env, lease, location_hint, &ctx);
+			}
+
+			// The custom error pages of the deployment only replace the
+			// failure to get a response from it. Responses of the deployment
+			// itself, even if they are errors, are always passed on as is
+			let kind = match &response {
+				Ok(response) => {
+					DeploymentErrorPageKind::from_forwarding_failure(response.status_code())
+				}
+				Err(_) => Some(DeploymentErrorPageKind::BadGateway),
+			};
+			let error_page = match kind {
+				Some(kind) => get_error_page(
+					&deployment_id,
+					error_pages_version.as_deref(),
+					kind,
+					&env,
+					&ctx,
+				)
+				.await
+				.map(|html| (kind, html)),
+				None => None,
+			};
+
+			match (response, error_page) {
+				(Ok(response), None) => Ok(response),
+				(Ok(_), Some((kind, html))) => {
+					error_page_response(&html, kind.status_code(), &Headers::new())
+				}
+				(Err(err), None) => Err(err),
+				(Err(err), Some((kind, html))) => {
+					console_error!(
+						"Failed to forward request to deployment `{deployment_id}`: {err}"
+					);
+					error_page_response(&html, kind.status_code(), &Headers::new())
+				}
+			}
 		}
 	}
 }
 
+/// Reports to the Patr API that a deployment received a request, so that it is
+/// not scaled to zero while it is being used. The report is debounced using the
+/// cache, so that it is only made once every
+/// [`constants::DEPLOYMENT_ACTIVITY_DEBOUNCE_SECONDS`]. Returns whether the
+/// deployment was scaled to zero and was started by this request, in which case
+/// the request has to be retried once the deployment is ready, along with the
+/// maximum number of requests that can be forwarded to the deployment at once
+/// and the weight of its canary. The limit and the weight are cached along with
+/// the report, so changes to them take effect within the debounce period. The
+/// readiness of the replicas of a deployment that has none ready is only cached
+/// for [`constants::DEPLOYMENT_NOT_READY_RETRY_AFTER_SECONDS`].
+/// The report is made with the [`constants::INGRESS_TOKEN`] secret. Failing to
+/// report the activity never fails the request itself, and leaves the requests
+/// unlimited.
+async fn report_deployment_activity(
+	deployment_id: &str,
+	location_hint: Option<&str>,
+	env: &Env,
+	ctx: &Context,
+) -> DeploymentActivityResponse {
+	let cache_store = Cache::default();
+	let cache_key = format!(
+		"{}/deployment/{}/activity",
+		constants::PATR_API_URL,
+		deployment_id
+	);
+
+	if let Ok(Some(mut cached)) = cache_store.get(&cache_key, true).await {
+		return cached.json().await.unwrap_or_default();
+	}
+
+	let token = match env.secret(constants::INGRESS_TOKEN) {
+		Ok(token) => token.to_string(),
+		Err(err) => {
+			console_error!("Cannot report activity without an ingress token: {err}");
+			return DeploymentActivityResponse::default();
+		}
+	};
+
+	let report = async {
+		let mut headers = Headers::new();
+		headers.set("user-agent", "patr-ingress")?;
+		headers.set("content-type", "application/json")?;
+		headers.set("authorization", &format!("Bearer {token}"))?;
+
+		// The utilization of the limit is reported along with the activity, as
+		// are the requests counted for a canary
+		let body = serde_json::to_string(&DeploymentActivityRequest {
+			concurrent_requests: concurrency_limiter::get_in_flight(
+				env,
+				deployment_id,
+				location_hint,
+			)
+			.await,
+			requests: CANARY_REQUESTS.with_borrow_mut(|requests| requests.remove(deployment_id)),
+		})?;
+
+		let mut response = Fetch::Request(Request::new_with_init(
+			&cache_key,
+			&RequestInit {
+				body: Some(body.into()),
+				headers,
+				method: Method::Post,
+				..Default::default()
+			},
+		)?)
+		.send()
+		.await?;
+
+		if response.status_code() != 200 {
+			return Err(Error::RustError(format!(
+				"unexpected status code {}",
+				response.status_code()
+			)));
+		}
+
+		response.json::<DeploymentActivityResponse>().await
+	};
+
+	let activity = match report.await {
+		Ok(activity) => activity,
+		Err(err) => {
+			console_error!("Failed to report activity of deployment `{deployment_id}`: {err}");
+			return DeploymentActivityResponse::default();
+		}
+	};
+
+	// Only this request started the deployment, so the requests that hit the
+	// cache are forwarded as usual. A deployment without any ready replicas is
+	// checked again sooner, so that requests are forwarded to it soon after one
+	// of its replicas becomes ready
+	let max_age = if activity.select_upstream(false).is_some() {
+		constants::DEPLOYMENT_ACTIVITY_DEBOUNCE_SECONDS
+	} else {
+		constants::DEPLOYMENT_NOT_READY_RETRY_AFTER_SECONDS
+	};
+	let debounced = Response::from_json(&DeploymentActivityResponse {
+		warming: false,
+		max_concurrent_requests: activity.max_concurrent_requests,
+		access_logging: activity.access_logging,
+		canary_weight: activity.canary_weight,
+		error_pages_version: activity.error_pages_version.clone(),
+		ready_replicas: activity.ready_replicas,
+		canary_ready_replicas: activity.canary_ready_replicas,
+	})
+	.and_then(|response| {
+		let mut headers = Headers::new();
+		headers.set("cache-control", &format!("max-age={max_age}"))?;
+		Ok(response.with_headers(headers))
+	});
+	if let Ok(debounced) = debounced {
+		ctx.wait_until(async move {
+			let _ = cache_store.put(cache_key, debounced).await;
+		});
+	}
+
+	activity
+}
+
+/// Gets the custom error page of a deployment to serve in the given situation,
+/// given the version of its error pages, if it has any. The pages are only
+/// fetched from the Patr API when one of them has to be served, and are cached
+/// by their version for [`constants::DEPLOYMENT_ERROR_PAGES_CACHE_SECONDS`], so
+/// that they are only fetched again once they change. Failing to get them
+/// serves the ingress's own error response instead.
+async fn get_error_page(
+	deployment_id: &str,
+	version: Option<&str>,
+	kind: DeploymentErrorPageKind,
+	env: &Env,
+	ctx: &Context,
+) -> Option<String> {
+	let version = version?;
+	let cache_store = Cache::default();
+	let cache_key = format!(
+		"{}/deployment/{}/error-pages/{}",
+		constants::PATR_API_URL,
+		deployment_id,
+		version
+	);
+
+	if let Ok(Some(mut cached)) = cache_store.get(&cache_key, true).await {
+		let mut cached = cached.json::<DeploymentErrorPagesResponse>().await.ok()?;
+		return cached.error_pages.remove(&kind);
+	}
+
+	let token = match env.secret(constants::INGRESS_TOKEN) {
+		Ok(token) => token.to_string(),
+		Err(err) => {
+			console_error!("Cannot get error pages without an ingress token: {err}");
+			return None;
+		}
+	};
+
+	let fetch = async {
+		let mut headers = Headers::new();
+		headers.set("user-agent", "patr-ingress")?;
+		headers.set("authorization", &format!("Bearer {token}"))?;
+
+		let mut response = Fetch::Request(Request::new_with_init(
+			&format!(
+				"{}/deployment/{}/error-pages",
+				constants::PATR_API_URL,
+				deployment_id
+			),
+			&RequestInit {
+				headers,
+				method: Method::Get,
+				..Default::default()
+			},
+		)?)
+		.send()
+		.await?;
+
+		if response.status_code() != 200 {
+			return Err(Error::RustError(format!(
+				"unexpected status code {}",
+				response.status_code()
+			)));
+		}
+
+		response.json::<DeploymentErrorPagesResponse>().await
+	};
+
+	let mut error_pages = match fetch.await {
+		Ok(error_pages) => error_pages,
+		Err(err) => {
+			console_error!("Failed to get error pages of deployment `{deployment_id}`: {err}");
+			return None;
+		}
+	};
+
+	let cached = Response::from_json(&error_pages).and_then(|response| {
+		let mut headers = Headers::new();
+		headers.set(
+			"cache-control",
+			&format!(
+				"max-age={}",
+				constants::DEPLOYMENT_ERROR_PAGES_CACHE_SECONDS
+			),
+		)?;
+		Ok(response.with_headers(headers))
+	});
+	if let Ok(cached) = cached {
+		ctx.wait_until(async move {
+			let _ = cache_store.put(cache_key, cached).await;
+		});
+	}
+
+	error_pages.error_pages.remove(&kind)
+}
+
+/// Creates a response that serves a custom error page of a deployment, with the
+/// given status code and headers. Error pages are never cached, so that the
+/// response of the deployment is served again as soon as it is available.
+fn error_page_response(html: &str, status: u16, headers: &Headers) -> Result<Response> {
+	let mut response = Response::from_html(html)?.with_status(status);
+	for (name, value) in headers.entries() {
+		response.headers_mut().set(&name, &value)?;
+	}
+	response.headers_mut().set("cache-control", "no-store")?;
+
+	Ok(response)
+}
+
+/// Checks if a request with the given `Accept` header was made by a client
+/// that accepts HTML responses, which is usually a browser
+fn accepts_html(accept: Option<&str>) -> bool {
+	accept.is_some_and(|accept| {
+		accept.split(',').any(|media_range| {
+			let mut params = media_range.split(';').map(str::trim);
+			let media_type = params.next().unwrap_or_default();
+			let rejected = params.any(|param| {
+				param
+					.strip_prefix("q=")
+					.and_then(|quality| quality.parse::<f32>().ok())
+					.is_some_and(|quality| quality <= 0.0)
+			});
+			!rejected && media_type.eq_ignore_ascii_case("text/html")
+		})
+	})
+}
+
+/// The page that browsers are shown while a deployment that was scaled to zero
+/// is starting, if the deployment doesn't have a custom one. The page refreshes
+/// itself after the same time that other clients are asked to retry after.
+fn warming_page() -> String {
+	format!(
+		concat!(
+			"<!DOCTYPE html>",
+			"<html>",
+			"<head>",
+			r#"<meta http-equiv="refresh" content="{}">"#,
+			"<title>Starting up</title>",
+			"</head>",
+			"<body>",
+			"<p>This site is starting up. The page will refresh once it is ready.</p>",
+			"</body>",
+			"</html>"
+		),
+		constants::DEPLOYMENT_WARMING_RETRY_AFTER_SECONDS
+	)
+}
+
 /// Gets the path of the URL without the mount point. A request stripped of it's
 /// mount point will be made in the case of static sites since they are stored
 /// in a bucket with the mount point as the root.
@@ -682,4 +1222,36 @@ mod tests {
 			);
 		}
 	}
+
+	mod accepting_html {
+		use crate::accepts_html;
+
+		#[test]
+		pub fn test_browser_accept_header() {
+			assert!(accepts_html(Some(
+				"text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8"
+			)));
+		}
+
+		#[test]
+		pub fn test_html_with_parameters() {
+			assert!(accepts_html(Some(
+				"application/json, Text/HTML; charset=utf-8"
+			)));
+		}
+
+		#[test]
+		pub fn test_clients_that_dont_ask_for_html() {
+			assert!(!accepts_html(None));
+			assert!(!accepts_html(Some("")));
+			assert!(!accepts_html(Some("*/*")));
+			assert!(!accepts_html(Some("application/json")));
+			assert!(!accepts_html(Some("text/html-fragment")));
+		}
+
+		#[test]
+		pub fn test_html_that_is_refused() {
+			assert!(!accepts_html(Some("application/json, text/html;q=0")));
+		}
+	}
 }
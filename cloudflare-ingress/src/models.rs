@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -21,12 +23,342 @@ pub enum IngressKVData {
 		deployment_id: String,
 		port: u16,
 		region: String,
+		/// The Cloudflare location hint closest to the region, if any, which
+		/// the concurrency limiter of the deployment is created close to
+		#[serde(default)]
+		location_hint: Option<String>,
 	},
 }
 
+/// The request made to the Patr API to report the activity of a deployment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeploymentActivityRequest {
+	/// The number of requests currently being forwarded to the deployment, if
+	/// they are limited
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub concurrent_requests: Option<u32>,
+	/// The requests sent to each version of a deployment with a canary since
+	/// the activity of the deployment was last reported
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub requests: Option<DeploymentCanaryRequests>,
+}
+
+/// The requests sent to a deployment with a canary, by the version that they
+/// were sent to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeploymentCanaryRequests {
+	/// The requests sent to the primary replicas of the deployment
+	pub primary: DeploymentVersionRequests,
+	/// The requests sent to the canary of the deployment
+	pub canary: DeploymentVersionRequests,
+}
+
+impl DeploymentCanaryRequests {
+	/// Counts a request that was sent to the given version of the deployment,
+	/// and was responded to with the given status code after the given number
+	/// of milliseconds
+	pub fn record(&mut self, canary: bool, status: u16, latency_millis: u64) {
+		let version = if canary {
+			&mut self.canary
+		} else {
+			&mut self.primary
+		};
+		version.requests += 1;
+		if status >= 500 {
+			version.server_errors += 1;
+		}
+		version.total_latency_millis += latency_millis;
+	}
+}
+
+/// The requests sent to one version of a deployment with a canary
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeploymentVersionRequests {
+	/// The number of requests
+	pub requests: u64,
+	/// The number of requests that failed with a `5xx` status code
+	pub server_errors: u64,
+	/// The sum of the milliseconds it took to respond to each of the requests
+	pub total_latency_millis: u64,
+}
+
+/// The response of the Patr API when the activity of a deployment is reported
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeploymentActivityResponse {
+	/// Whether the deployment was scaled to zero and is being started again
+	pub warming: bool,
+	/// The maximum number of requests that can be forwarded to the deployment
+	/// at once, if they are limited
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub max_concurrent_requests: Option<u32>,
+	/// Whether the requests to the deployment should be reported to the Patr
+	/// API as access logs
+	#[serde(default)]
+	pub access_logging: bool,
+	/// The percentage of the requests to the deployment that should be sent to
+	/// its canary, if it has one that is running
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub canary_weight: Option<u8>,
+	/// The version of the custom error pages of the deployment, if it has any.
+	/// The pages themselves are only fetched when one of them has to be
+	/// served, and are cached by their version
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub error_pages_version: Option<String>,
+	/// The number of primary replicas of the deployment that are ready to
+	/// receive requests, if it is known
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub ready_replicas: Option<u16>,
+	/// The number of replicas of the canary of the deployment that are ready to
+	/// receive requests, if it has a canary and it is known
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub canary_ready_replicas: Option<u16>,
+}
+
+impl DeploymentActivityResponse {
+	/// Selects the replicas of the deployment to forward a request to, given
+	/// whether the request was picked to be sent to the canary. Replicas that
+	/// are known to not be ready are never selected, so a request picked for a
+	/// canary that isn't ready is sent to the primary replicas instead, and the
+	/// other way around. Replicas whose readiness isn't known are assumed to be
+	/// ready. Returns `None` if none of the replicas are ready
+	pub fn select_upstream(&self, picked_canary: bool) -> Option<DeploymentUpstream> {
+		let primary_ready = self.ready_replicas.map_or(true, |ready| ready > 0);
+		let canary_ready = self.canary_weight.is_some() &&
+			self.canary_ready_replicas.map_or(true, |ready| ready > 0);
+
+		match (picked_canary, primary_ready, canary_ready) {
+			(true, _, true) | (false, false, true) => Some(DeploymentUpstream::Canary),
+			(_, true, _) => Some(DeploymentUpstream::Primary),
+			(_, false, false) => None,
+		}
+	}
+}
+
+/// The replicas of a deployment that a request is forwarded to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeploymentUpstream {
+	/// The primary replicas of the deployment
+	Primary,
+	/// The replicas of the canary of the deployment
+	Canary,
+}
+
+/// The custom error pages of a deployment, as returned by the Patr API to the
+/// ingress
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeploymentErrorPagesResponse {
+	/// The HTML of the custom error pages to serve instead of the ingress's
+	/// own error responses, by the situation that they are served in
+	#[serde(default)]
+	pub error_pages: HashMap<DeploymentErrorPageKind, String>,
+}
+
+/// The situations in which a custom error page is served instead of the
+/// ingress's own error response. The responses of the deployment itself are
+/// never replaced
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DeploymentErrorPageKind {
+	/// The request couldn't be forwarded to the deployment
+	BadGateway,
+	/// The deployment is handling as many requests as it is allowed to, or
+	/// none of its replicas are ready
+	ServiceUnavailable,
+	/// The deployment didn't respond to the request in time
+	GatewayTimeout,
+	/// The deployment was scaled to zero and is being started again
+	WarmingUp,
+}
+
+impl DeploymentErrorPageKind {
+	/// Gets the kind of error page to serve instead of a response with the
+	/// given status code, if it is one that Cloudflare generates when it
+	/// couldn't get a response from the deployment (`520` to `526`). Any other
+	/// status code is from the deployment itself, and is never replaced
+	pub fn from_forwarding_failure(status: u16) -> Option<Self> {
+		match status {
+			522 | 524 => Some(Self::GatewayTimeout),
+			520..=526 => Some(Self::BadGateway),
+			_ => None,
+		}
+	}
+
+	/// Gets the status code that the error page is served with
+	pub fn status_code(&self) -> u16 {
+		match self {
+			Self::BadGateway => 502,
+			Self::ServiceUnavailable | Self::WarmingUp => 503,
+			Self::GatewayTimeout => 504,
+		}
+	}
+}
+
+/// The request made to the Patr API to report a batch of requests made to
+/// deployments, for their access logs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeploymentAccessLogsRequest {
+	/// The requests made to the deployments
+	pub logs: Vec<DeploymentAccessLog>,
+}
+
+/// A request made to a deployment, for its access logs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeploymentAccessLog {
+	/// The deployment ID that received the request
+	pub deployment_id: String,
+	/// The time the request was received at, in RFC 3339 format
+	pub timestamp: String,
+	/// The HTTP method of the request
+	pub method: String,
+	/// The path of the request, including the query string
+	pub path: String,
+	/// The status code that the request was responded to with
+	pub status: u16,
+	/// The number of milliseconds it took to respond to the request
+	pub latency_millis: u64,
+	/// Whether the request was sent to the canary of the deployment instead of
+	/// its primary replicas
+	#[serde(default)]
+	pub canary: bool,
+}
+
 impl IngressKVData {
 	/// Check if the data is a redirect
 	pub fn is_redirect(&self) -> bool {
 		matches!(self, IngressKVData::Redirect { .. })
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::{
+		DeploymentActivityResponse,
+		DeploymentCanaryRequests,
+		DeploymentErrorPageKind,
+		DeploymentUpstream,
+		DeploymentVersionRequests,
+	};
+
+	/// Creates the activity response of a deployment with the given readiness
+	fn activity(
+		ready_replicas: Option<u16>,
+		canary_weight: Option<u8>,
+		canary_ready_replicas: Option<u16>,
+	) -> DeploymentActivityResponse {
+		DeploymentActivityResponse {
+			ready_replicas,
+			canary_weight,
+			canary_ready_replicas,
+			..Default::default()
+		}
+	}
+
+	#[test]
+	fn requests_are_forwarded_to_ready_replicas() {
+		let ready = activity(Some(2), Some(10), Some(1));
+		assert_eq!(
+			ready.select_upstream(false),
+			Some(DeploymentUpstream::Primary)
+		);
+		assert_eq!(
+			ready.select_upstream(true),
+			Some(DeploymentUpstream::Canary)
+		);
+	}
+
+	#[test]
+	fn requests_are_not_forwarded_to_unready_replicas() {
+		// The canary isn't ready, so requests picked for it go to the primary
+		// replicas instead
+		assert_eq!(
+			activity(Some(2), Some(50), Some(0)).select_upstream(true),
+			Some(DeploymentUpstream::Primary)
+		);
+
+		// The primary replicas aren't ready, but the canary is
+		assert_eq!(
+			activity(Some(0), Some(50), Some(1)).select_upstream(false),
+			Some(DeploymentUpstream::Canary)
+		);
+	}
+
+	#[test]
+	fn requests_are_not_forwarded_when_no_replicas_are_ready() {
+		assert_eq!(activity(Some(0), None, None).select_upstream(false), None);
+		assert_eq!(
+			activity(Some(0), Some(50), Some(0)).select_upstream(true),
+			None
+		);
+		assert_eq!(
+			activity(Some(0), Some(50), Some(0)).select_upstream(false),
+			None
+		);
+	}
+
+	#[test]
+	fn requests_are_counted_by_version() {
+		let mut requests = DeploymentCanaryRequests::default();
+		requests.record(false, 200, 30);
+		requests.record(false, 404, 10);
+		requests.record(true, 502, 5);
+
+		assert_eq!(
+			requests,
+			DeploymentCanaryRequests {
+				primary: DeploymentVersionRequests {
+					requests: 2,
+					server_errors: 0,
+					total_latency_millis: 40,
+				},
+				canary: DeploymentVersionRequests {
+					requests: 1,
+					server_errors: 1,
+					total_latency_millis: 5,
+				},
+			}
+		);
+	}
+
+	#[test]
+	fn replicas_with_unknown_readiness_are_assumed_to_be_ready() {
+		assert_eq!(
+			activity(None, None, None).select_upstream(false),
+			Some(DeploymentUpstream::Primary)
+		);
+		assert_eq!(
+			activity(Some(0), Some(50), None).select_upstream(false),
+			Some(DeploymentUpstream::Canary)
+		);
+	}
+
+	#[test]
+	fn only_failures_to_forward_are_replaced_with_error_pages() {
+		for status in [200, 404, 500, 502, 503, 504, 527] {
+			assert_eq!(
+				DeploymentErrorPageKind::from_forwarding_failure(status),
+				None,
+				"{status}"
+			);
+		}
+
+		assert_eq!(
+			DeploymentErrorPageKind::from_forwarding_failure(521),
+			Some(DeploymentErrorPageKind::BadGateway)
+		);
+		assert_eq!(
+			DeploymentErrorPageKind::from_forwarding_failure(524),
+			Some(DeploymentErrorPageKind::GatewayTimeout)
+		);
+		assert_eq!(
+			DeploymentErrorPageKind::from_forwarding_failure(524).map(|kind| kind.status_code()),
+			Some(504)
+		);
+	}
+}
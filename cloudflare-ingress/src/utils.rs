@@ -8,9 +8,69 @@ pub mod constants {
 	pub const INGRESS_KV: &str = "INGRESS_KV";
 	/// The cloudflare R2 bucket that stores all the static sites
 	pub const STATIC_SITE_BUCKET: &str = "STATIC_SITE_BUCKET";
+	/// The cloudflare durable object namespace that limits the number of
+	/// requests forwarded to a deployment at once
+	pub const DEPLOYMENT_CONCURRENCY_LIMITER: &str = "DEPLOYMENT_CONCURRENCY_LIMITER";
+	/// The URL that requests to the deployment concurrency limiter are made
+	/// to. Durable objects need an absolute URL, but only the path is used
+	pub const DEPLOYMENT_CONCURRENCY_LIMITER_URL: &str = "https://concurrency-limiter";
+	/// The number of milliseconds after which a slot acquired to forward a
+	/// request to a deployment is freed up, even if it wasn't released. This
+	/// is longer than Cloudflare waits for a response from an origin
+	pub const DEPLOYMENT_CONCURRENCY_LEASE_TTL_MILLIS: u64 = 120 * 1000;
+	/// The cloudflare secret with the token that the ingress uses to report the
+	/// activity and the access logs of deployments to the Patr API
+	pub const INGRESS_TOKEN: &str = "INGRESS_TOKEN";
 
 	/// The default status code for a temporary redirect
 	pub const STATUS_CODE_TEMPORAL_REDIRECT: u16 = 307;
 	/// The default status code for a permanent redirect
 	pub const STATUS_CODE_PERMANENT_REDIRECT: u16 = 308;
+	/// The status code returned while a deployment that was scaled to zero is
+	/// starting again, when none of the replicas of a deployment are ready, or
+	/// when a deployment is handling as many requests as it is allowed to
+	pub const STATUS_CODE_SERVICE_UNAVAILABLE: u16 = 503;
+	/// The status code returned by the deployment concurrency limiter when
+	/// the limit of a deployment has been reached
+	pub const STATUS_CODE_TOO_MANY_REQUESTS: u16 = 429;
+	/// The status code that is logged for requests to a deployment that
+	/// couldn't be forwarded to it
+	pub const STATUS_CODE_INTERNAL_SERVER_ERROR: u16 = 500;
+
+	/// The URL of the Patr API, which the activity of deployments is reported
+	/// to
+	pub const PATR_API_URL: &str = "https://api.patr.cloud";
+	/// The number of seconds for which the activity of a deployment is only
+	/// reported once (per data center), so that the API isn't called for
+	/// every request
+	pub const DEPLOYMENT_ACTIVITY_DEBOUNCE_SECONDS: u32 = 60;
+	/// The number of seconds that clients are asked to wait before retrying a
+	/// request to a deployment that is starting after being scaled to zero
+	pub const DEPLOYMENT_WARMING_RETRY_AFTER_SECONDS: u32 = 5;
+	/// The header that is set on responses for a deployment that is starting
+	/// after being scaled to zero
+	pub const DEPLOYMENT_WARMING_HEADER: &str = "x-patr-deployment-warming";
+	/// The number of seconds that clients are asked to wait before retrying a
+	/// request to a deployment that is handling as many requests as it is
+	/// allowed to
+	pub const DEPLOYMENT_CONCURRENCY_LIMITED_RETRY_AFTER_SECONDS: u32 = 1;
+	/// The number of seconds that clients are asked to wait before retrying a
+	/// request to a deployment that has no ready replicas, which is also how
+	/// long the ingress waits before checking the readiness of the deployment
+	/// again
+	pub const DEPLOYMENT_NOT_READY_RETRY_AFTER_SECONDS: u32 = 5;
+	/// The number of seconds for which the custom error pages of a deployment
+	/// are cached (per data center). They are cached by their version, so
+	/// changes to them are served as soon as the activity of the deployment is
+	/// reported again
+	pub const DEPLOYMENT_ERROR_PAGES_CACHE_SECONDS: u32 = 24 * 60 * 60;
+	/// The maximum number of access logs that are reported to the Patr API in
+	/// a single batch, which is the most that the API accepts at once
+	pub const ACCESS_LOG_BATCH_SIZE: usize = 1000;
+	/// The number of milliseconds after which a batch of access logs is
+	/// reported to the Patr API, even if it isn't full
+	pub const ACCESS_LOG_BATCH_MAX_AGE_MILLIS: u64 = 10 * 1000;
+	/// The suffix added to the deployment ID in the host of a deployment's
+	/// managed URL to reach the replicas of its canary instead
+	pub const DEPLOYMENT_CANARY_HOST_SUFFIX: &str = "-canary";
 }
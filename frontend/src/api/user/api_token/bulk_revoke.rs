@@ -0,0 +1,34 @@
+use leptos::server_fn::codec::Json;
+use models::api::user::*;
+
+use crate::prelude::*;
+
+#[server(
+	BulkRevokeApiTokensFn,
+	endpoint = "/user/api-token/bulk-revoke",
+	input = Json
+)]
+pub async fn bulk_revoke_api_tokens(
+	access_token: Option<String>,
+	token_ids: Vec<Uuid>,
+) -> Result<BulkRevokeApiTokensResponse, ServerFnError<ErrorType>> {
+	use std::str::FromStr;
+
+	let access_token = BearerToken::from_str(access_token.unwrap().as_str())
+		.map_err(|_| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+
+	make_api_call::<BulkRevokeApiTokensRequest>(
+		ApiRequest::builder()
+			.path(BulkRevokeApiTokensPath)
+			.query(())
+			.headers(BulkRevokeApiTokensRequestHeaders {
+				authorization: access_token,
+				user_agent: UserAgent::from_static("hyper/0.12.2"),
+			})
+			.body(BulkRevokeApiTokensRequest { token_ids })
+			.build(),
+	)
+	.await
+	.map(|res| res.body)
+	.map_err(ServerFnError::WrappedServerError)
+}
@@ -5,6 +5,9 @@ use crate::prelude::*;
 #[server(LoadApiTokenFn, endpoint = "/user/api-token")]
 pub async fn load_api_tokens_list(
 	access_token: Option<String>,
+	status: Option<ApiTokenStatus>,
+	order_by: Option<ApiTokenSortBy>,
+	order: Option<ListOrder>,
 ) -> Result<ListApiTokensResponse, ServerFnError<ErrorType>> {
 	use std::str::FromStr;
 
@@ -12,7 +15,11 @@ pub async fn load_api_tokens_list(
 		ApiRequest::builder()
 			.path(ListApiTokensPath)
 			.query(Paginated {
-				data: (),
+				data: ListApiTokensQuery {
+					status,
+					order_by,
+					order,
+				},
 				page: 0,
 				count: 10,
 			})
@@ -1,3 +1,4 @@
+mod bulk_revoke;
 mod create;
 mod get_token;
 mod list;
@@ -5,4 +6,12 @@ mod regenerate;
 mod revoke;
 mod update;
 
-pub use self::{create::*, get_token::*, list::*, regenerate::*, revoke::*, update::*};
+pub use self::{
+	bulk_revoke::*,
+	create::*,
+	get_token::*,
+	list::*,
+	regenerate::*,
+	revoke::*,
+	update::*,
+};
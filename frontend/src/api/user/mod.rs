@@ -5,8 +5,9 @@ use crate::prelude::*;
 mod activate_mfa;
 mod api_token;
 mod change_passsword;
+mod recovery_email;
 
-pub use self::{activate_mfa::*, api_token::*, change_passsword::*};
+pub use self::{activate_mfa::*, api_token::*, change_passsword::*, recovery_email::*};
 
 /// Load user data from the server
 #[server]
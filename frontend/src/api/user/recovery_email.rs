@@ -0,0 +1,88 @@
+use models::api::user::*;
+
+use crate::prelude::*;
+
+/// Request a change of the recovery email of the user. An OTP is sent to the
+/// new email, which has to be verified before the change is committed
+#[server(ChangeRecoveryEmailFn, endpoint = "/user/recovery-email/change")]
+pub async fn change_recovery_email(
+	access_token: Option<String>,
+	email: String,
+) -> Result<ChangeRecoveryEmailResponse, ServerFnError<ErrorType>> {
+	use std::str::FromStr;
+
+	make_api_call::<ChangeRecoveryEmailRequest>(
+		ApiRequest::builder()
+			.path(ChangeRecoveryEmailPath)
+			.query(())
+			.headers(ChangeRecoveryEmailRequestHeaders {
+				authorization: BearerToken::from_str(
+					access_token.unwrap_or_default().to_string().as_str(),
+				)
+				.map_err(|_| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?,
+				user_agent: UserAgent::from_static("hyper/0.12.2"),
+			})
+			.body(ChangeRecoveryEmailRequest { email })
+			.build(),
+	)
+	.await
+	.map(|res| res.body)
+	.map_err(ServerFnError::WrappedServerError)
+}
+
+/// Verify the pending recovery email of the user with the OTP sent to it
+#[server(VerifyRecoveryEmailFn, endpoint = "/user/recovery-email/verify")]
+pub async fn verify_recovery_email(
+	access_token: Option<String>,
+	email: String,
+	verification_token: String,
+) -> Result<VerifyUserEmailResponse, ServerFnError<ErrorType>> {
+	use std::str::FromStr;
+
+	make_api_call::<VerifyUserEmailRequest>(
+		ApiRequest::builder()
+			.path(VerifyUserEmailPath)
+			.query(())
+			.headers(VerifyUserEmailRequestHeaders {
+				authorization: BearerToken::from_str(
+					access_token.unwrap_or_default().to_string().as_str(),
+				)
+				.map_err(|_| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?,
+				user_agent: UserAgent::from_static("hyper/0.12.2"),
+			})
+			.body(VerifyUserEmailRequest {
+				email,
+				verification_token,
+			})
+			.build(),
+	)
+	.await
+	.map(|res| res.body)
+	.map_err(ServerFnError::WrappedServerError)
+}
+
+/// Cancel the pending change of the recovery email of the user
+#[server(CancelRecoveryEmailChangeFn, endpoint = "/user/recovery-email/cancel")]
+pub async fn cancel_recovery_email_change(
+	access_token: Option<String>,
+) -> Result<CancelRecoveryEmailChangeResponse, ServerFnError<ErrorType>> {
+	use std::str::FromStr;
+
+	make_api_call::<CancelRecoveryEmailChangeRequest>(
+		ApiRequest::builder()
+			.path(CancelRecoveryEmailChangePath)
+			.query(())
+			.headers(CancelRecoveryEmailChangeRequestHeaders {
+				authorization: BearerToken::from_str(
+					access_token.unwrap_or_default().to_string().as_str(),
+				)
+				.map_err(|_| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?,
+				user_agent: UserAgent::from_static("hyper/0.12.2"),
+			})
+			.body(CancelRecoveryEmailChangeRequest)
+			.build(),
+	)
+	.await
+	.map(|res| res.body)
+	.map_err(ServerFnError::WrappedServerError)
+}
@@ -0,0 +1,41 @@
+use models::api::workspace::database::*;
+
+use crate::prelude::*;
+
+#[server(
+	GetDatabaseConnectionFn,
+	endpoint = "/infrastructure/database/connection"
+)]
+pub async fn get_database_connection(
+	access_token: Option<String>,
+	workspace_id: Option<Uuid>,
+	database_id: Uuid,
+) -> Result<GetDatabaseConnectionResponse, ServerFnError<ErrorType>> {
+	use std::str::FromStr;
+
+	let access_token = access_token
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+	let access_token = BearerToken::from_str(access_token.as_str())
+		.map_err(|_| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+
+	let workspace_id = workspace_id
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::WrongParameters))?;
+
+	make_api_call::<GetDatabaseConnectionRequest>(
+		ApiRequest::builder()
+			.path(GetDatabaseConnectionPath {
+				workspace_id,
+				database_id,
+			})
+			.query(())
+			.headers(GetDatabaseConnectionRequestHeaders {
+				authorization: access_token,
+				user_agent: UserAgent::from_static("todo"),
+			})
+			.body(GetDatabaseConnectionRequest)
+			.build(),
+	)
+	.await
+	.map(|res| res.body)
+	.map_err(ServerFnError::WrappedServerError)
+}
@@ -1,8 +1,19 @@
 mod create;
 mod delete;
 mod get;
+mod get_connection;
 mod list;
 mod list_machine_types;
+mod rotate_credentials;
 mod update;
 
-pub use self::{create::*, delete::*, get::*, list::*, list_machine_types::*, update::*};
+pub use self::{
+	create::*,
+	delete::*,
+	get::*,
+	get_connection::*,
+	list::*,
+	list_machine_types::*,
+	rotate_credentials::*,
+	update::*,
+};
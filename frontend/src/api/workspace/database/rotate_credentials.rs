@@ -0,0 +1,42 @@
+use models::api::workspace::database::*;
+
+use crate::prelude::*;
+
+#[server(
+	RotateDatabaseCredentialsFn,
+	endpoint = "/infrastructure/database/rotate-credentials"
+)]
+pub async fn rotate_database_credentials(
+	access_token: Option<String>,
+	workspace_id: Option<Uuid>,
+	database_id: Uuid,
+	request: RotateDatabaseCredentialsRequest,
+) -> Result<RotateDatabaseCredentialsResponse, ServerFnError<ErrorType>> {
+	use std::str::FromStr;
+
+	let access_token = access_token
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+	let access_token = BearerToken::from_str(access_token.as_str())
+		.map_err(|_| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+
+	let workspace_id = workspace_id
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::WrongParameters))?;
+
+	make_api_call::<RotateDatabaseCredentialsRequest>(
+		ApiRequest::builder()
+			.path(RotateDatabaseCredentialsPath {
+				workspace_id,
+				database_id,
+			})
+			.query(())
+			.headers(RotateDatabaseCredentialsRequestHeaders {
+				authorization: access_token,
+				user_agent: UserAgent::from_static("todo"),
+			})
+			.body(request)
+			.build(),
+	)
+	.await
+	.map(|res| res.body)
+	.map_err(ServerFnError::WrappedServerError)
+}
@@ -0,0 +1,36 @@
+use models::api::workspace::deployment::*;
+
+use crate::prelude::*;
+
+/// Get the current status of multiple deployments at once
+#[server(
+	BatchGetDeploymentStatusFn,
+	endpoint = "/infrastructure/deployment/batch-status"
+)]
+pub async fn batch_get_deployment_status(
+	access_token: Option<String>,
+	workspace_id: Uuid,
+	deployment_ids: Vec<Uuid>,
+) -> Result<BatchGetDeploymentStatusResponse, ServerFnError<ErrorType>> {
+	use std::str::FromStr;
+
+	let access_token = access_token
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+	let access_token = BearerToken::from_str(access_token.as_str())
+		.map_err(|_| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+
+	make_api_call::<BatchGetDeploymentStatusRequest>(
+		ApiRequest::builder()
+			.path(BatchGetDeploymentStatusPath { workspace_id })
+			.query(())
+			.headers(BatchGetDeploymentStatusRequestHeaders {
+				authorization: access_token,
+				user_agent: UserAgent::from_static("todo"),
+			})
+			.body(BatchGetDeploymentStatusRequest { deployment_ids })
+			.build(),
+	)
+	.await
+	.map(|res| res.body)
+	.map_err(ServerFnError::WrappedServerError)
+}
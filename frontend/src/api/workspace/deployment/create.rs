@@ -38,5 +38,5 @@ pub async fn create_deployment(
 		leptos_axum::redirect("/deployment");
 		res.body
 	})
-	.map_err(ServerFnError::WrappedServerError)
+	.map_err(into_server_fn_error)
 }
@@ -0,0 +1,45 @@
+use leptos::server_fn::codec::Json;
+use models::api::workspace::deployment::alert_rule::*;
+
+use crate::prelude::*;
+
+/// Server function to create an alert rule for a deployment
+#[server(
+	CreateDeploymentAlertRuleFn,
+	input = Json,
+	endpoint = "/infrastructure/deployment/alert-rule/create"
+)]
+pub async fn create_deployment_alert_rule(
+	access_token: Option<String>,
+	workspace_id: Option<Uuid>,
+	deployment_id: Uuid,
+	alert_rule: CreateDeploymentAlertRuleRequest,
+) -> Result<CreateDeploymentAlertRuleResponse, ServerFnError<ErrorType>> {
+	use std::str::FromStr;
+
+	let access_token = access_token
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+	let access_token = BearerToken::from_str(access_token.as_str())
+		.map_err(|_| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+
+	let workspace_id = workspace_id
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::WrongParameters))?;
+
+	make_api_call::<CreateDeploymentAlertRuleRequest>(
+		ApiRequest::builder()
+			.path(CreateDeploymentAlertRulePath {
+				workspace_id,
+				deployment_id,
+			})
+			.query(())
+			.headers(CreateDeploymentAlertRuleRequestHeaders {
+				authorization: access_token,
+				user_agent: UserAgent::from_static("todo"),
+			})
+			.body(alert_rule)
+			.build(),
+	)
+	.await
+	.map(|res| res.body)
+	.map_err(ServerFnError::WrappedServerError)
+}
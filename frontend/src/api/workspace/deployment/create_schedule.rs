@@ -0,0 +1,45 @@
+use leptos::server_fn::codec::Json;
+use models::api::workspace::deployment::schedule::*;
+
+use crate::prelude::*;
+
+/// Server function to create a schedule for a deployment
+#[server(
+	CreateDeploymentScheduleFn,
+	input = Json,
+	endpoint = "/infrastructure/deployment/schedule/create"
+)]
+pub async fn create_deployment_schedule(
+	access_token: Option<String>,
+	workspace_id: Option<Uuid>,
+	deployment_id: Uuid,
+	schedule: CreateDeploymentScheduleRequest,
+) -> Result<CreateDeploymentScheduleResponse, ServerFnError<ErrorType>> {
+	use std::str::FromStr;
+
+	let access_token = access_token
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+	let access_token = BearerToken::from_str(access_token.as_str())
+		.map_err(|_| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+
+	let workspace_id = workspace_id
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::WrongParameters))?;
+
+	make_api_call::<CreateDeploymentScheduleRequest>(
+		ApiRequest::builder()
+			.path(CreateDeploymentSchedulePath {
+				workspace_id,
+				deployment_id,
+			})
+			.query(())
+			.headers(CreateDeploymentScheduleRequestHeaders {
+				authorization: access_token,
+				user_agent: UserAgent::from_static("todo"),
+			})
+			.body(schedule)
+			.build(),
+	)
+	.await
+	.map(|res| res.body)
+	.map_err(ServerFnError::WrappedServerError)
+}
@@ -0,0 +1,39 @@
+use leptos::server_fn::codec::Json;
+use models::api::workspace::deployment::template::*;
+
+use crate::prelude::*;
+
+/// Server function to create a deployment template
+#[server(
+	CreateDeploymentTemplateFn,
+	input = Json,
+	endpoint = "/infrastructure/deployment/template/create"
+)]
+pub async fn create_deployment_template(
+	access_token: Option<String>,
+	workspace_id: Option<Uuid>,
+	template: CreateDeploymentTemplateRequest,
+) -> Result<CreateDeploymentTemplateResponse, ServerFnError<ErrorType>> {
+	use std::str::FromStr;
+
+	let access_token = BearerToken::from_str(access_token.unwrap().as_str())
+		.map_err(|_| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+
+	let workspace_id = workspace_id
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::WrongParameters))?;
+
+	make_api_call::<CreateDeploymentTemplateRequest>(
+		ApiRequest::builder()
+			.path(CreateDeploymentTemplatePath { workspace_id })
+			.query(())
+			.headers(CreateDeploymentTemplateRequestHeaders {
+				authorization: access_token,
+				user_agent: UserAgent::from_static("todo"),
+			})
+			.body(template)
+			.build(),
+	)
+	.await
+	.map(|res| res.body)
+	.map_err(ServerFnError::WrappedServerError)
+}
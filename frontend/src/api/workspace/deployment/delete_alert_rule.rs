@@ -0,0 +1,44 @@
+use models::api::workspace::deployment::alert_rule::*;
+
+use crate::prelude::*;
+
+/// Server function to delete an alert rule of a deployment
+#[server(
+	DeleteDeploymentAlertRuleFn,
+	endpoint = "/infrastructure/deployment/alert-rule/delete"
+)]
+pub async fn delete_deployment_alert_rule(
+	access_token: Option<String>,
+	workspace_id: Option<Uuid>,
+	deployment_id: Uuid,
+	alert_rule_id: Uuid,
+) -> Result<DeleteDeploymentAlertRuleResponse, ServerFnError<ErrorType>> {
+	use std::str::FromStr;
+
+	let access_token = access_token
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+	let access_token = BearerToken::from_str(access_token.as_str())
+		.map_err(|_| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+
+	let workspace_id = workspace_id
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::WrongParameters))?;
+
+	make_api_call::<DeleteDeploymentAlertRuleRequest>(
+		ApiRequest::builder()
+			.path(DeleteDeploymentAlertRulePath {
+				workspace_id,
+				deployment_id,
+				alert_rule_id,
+			})
+			.query(())
+			.headers(DeleteDeploymentAlertRuleRequestHeaders {
+				authorization: access_token,
+				user_agent: UserAgent::from_static("todo"),
+			})
+			.body(DeleteDeploymentAlertRuleRequest)
+			.build(),
+	)
+	.await
+	.map(|res| res.body)
+	.map_err(ServerFnError::WrappedServerError)
+}
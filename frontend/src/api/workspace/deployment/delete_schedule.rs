@@ -0,0 +1,44 @@
+use models::api::workspace::deployment::schedule::*;
+
+use crate::prelude::*;
+
+/// Server function to delete a schedule of a deployment
+#[server(
+	DeleteDeploymentScheduleFn,
+	endpoint = "/infrastructure/deployment/schedule/delete"
+)]
+pub async fn delete_deployment_schedule(
+	access_token: Option<String>,
+	workspace_id: Option<Uuid>,
+	deployment_id: Uuid,
+	schedule_id: Uuid,
+) -> Result<DeleteDeploymentScheduleResponse, ServerFnError<ErrorType>> {
+	use std::str::FromStr;
+
+	let access_token = access_token
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+	let access_token = BearerToken::from_str(access_token.as_str())
+		.map_err(|_| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+
+	let workspace_id = workspace_id
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::WrongParameters))?;
+
+	make_api_call::<DeleteDeploymentScheduleRequest>(
+		ApiRequest::builder()
+			.path(DeleteDeploymentSchedulePath {
+				workspace_id,
+				deployment_id,
+				schedule_id,
+			})
+			.query(())
+			.headers(DeleteDeploymentScheduleRequestHeaders {
+				authorization: access_token,
+				user_agent: UserAgent::from_static("todo"),
+			})
+			.body(DeleteDeploymentScheduleRequest)
+			.build(),
+	)
+	.await
+	.map(|res| res.body)
+	.map_err(ServerFnError::WrappedServerError)
+}
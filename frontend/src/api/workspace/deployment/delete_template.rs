@@ -0,0 +1,40 @@
+use models::api::workspace::deployment::template::*;
+
+use crate::prelude::*;
+
+/// Server function to delete a deployment template
+#[server(
+	DeleteDeploymentTemplateFn,
+	endpoint = "/infrastructure/deployment/template/delete"
+)]
+pub async fn delete_deployment_template(
+	access_token: Option<String>,
+	workspace_id: Option<Uuid>,
+	template_id: Uuid,
+) -> Result<DeleteDeploymentTemplateResponse, ServerFnError<ErrorType>> {
+	use std::str::FromStr;
+
+	let access_token = BearerToken::from_str(access_token.unwrap().as_str())
+		.map_err(|_| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+
+	let workspace_id = workspace_id
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::WrongParameters))?;
+
+	make_api_call::<DeleteDeploymentTemplateRequest>(
+		ApiRequest::builder()
+			.path(DeleteDeploymentTemplatePath {
+				workspace_id,
+				template_id,
+			})
+			.query(())
+			.headers(DeleteDeploymentTemplateRequestHeaders {
+				authorization: access_token,
+				user_agent: UserAgent::from_static("todo"),
+			})
+			.body(DeleteDeploymentTemplateRequest)
+			.build(),
+	)
+	.await
+	.map(|res| res.body)
+	.map_err(ServerFnError::WrappedServerError)
+}
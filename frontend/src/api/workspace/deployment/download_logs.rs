@@ -0,0 +1,83 @@
+use models::api::workspace::deployment::*;
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::prelude::*;
+
+/// The logs of a deployment that were downloaded, to be saved as a file by the
+/// browser
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DownloadedDeploymentLogs {
+	/// The name of the file to save the logs as
+	pub file_name: String,
+	/// The content type of the file
+	pub content_type: String,
+	/// The contents of the file
+	pub content: String,
+	/// If the requested time range was too long, the time (in RFC 3339) up to
+	/// which the logs were downloaded
+	pub truncated_at: Option<String>,
+}
+
+#[server(
+	DownloadDeploymentLogsFn,
+	endpoint = "/infrastructure/deployment/download_logs"
+)]
+pub async fn download_deployment_logs(
+	access_token: Option<String>,
+	workspace_id: Option<Uuid>,
+	deployment_id: Uuid,
+	start_time: OffsetDateTime,
+	end_time: Option<OffsetDateTime>,
+	format: DeploymentLogFormat,
+) -> Result<DownloadedDeploymentLogs, ServerFnError<ErrorType>> {
+	use std::str::FromStr;
+
+	let access_token = access_token
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+	let access_token = BearerToken::from_str(access_token.as_str())
+		.map_err(|_| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+
+	let workspace_id = workspace_id
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::WrongParameters))?;
+
+	let response = make_api_call::<DownloadDeploymentLogsRequest>(
+		ApiRequest::builder()
+			.path(DownloadDeploymentLogsPath {
+				workspace_id,
+				deployment_id,
+			})
+			.query(DownloadDeploymentLogsQuery {
+				start_time,
+				end_time,
+				format: Some(format),
+				search: None,
+			})
+			.headers(DownloadDeploymentLogsRequestHeaders {
+				authorization: access_token,
+				user_agent: UserAgent::from_static("todo"),
+			})
+			.body(DownloadDeploymentLogsRequest)
+			.build(),
+	)
+	.await
+	.map_err(ServerFnError::WrappedServerError)?
+	.body
+	.0;
+
+	let truncated_at = response
+		.headers()
+		.get(LOGS_TRUNCATED_AT_HEADER)
+		.and_then(|value| value.to_str().ok())
+		.map(String::from);
+	let content = axum::body::to_bytes(response.into_body(), usize::MAX)
+		.await
+		.map_err(|_| ServerFnError::WrappedServerError(ErrorType::InternalServerError))?;
+
+	Ok(DownloadedDeploymentLogs {
+		file_name: format!("{}-logs.{}", deployment_id, format.file_extension()),
+		content_type: format.content_type().to_string(),
+		content: String::from_utf8_lossy(&content).into_owned(),
+		truncated_at,
+	})
+}
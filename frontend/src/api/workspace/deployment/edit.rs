@@ -1,13 +1,17 @@
 use models::api::workspace::deployment::*;
+use time::OffsetDateTime;
 
 use crate::prelude::*;
 
-/// The Server Function for updating a deployment
+/// The Server Function for updating a deployment. If the time the deployment
+/// was last updated at is given, the deployment is only updated if it hasn't
+/// been modified since then.
 #[server(UpdateDeploymentFn, endpoint = "/infrastructure/deployment/update")]
 pub async fn update_deployment(
 	access_token: Option<String>,
 	workspace_id: Option<Uuid>,
 	deployment_id: Option<Uuid>,
+	last_updated_at: Option<OffsetDateTime>,
 	deployment_info: UpdateDeploymentRequest,
 ) -> Result<UpdateDeploymentResponse, ServerFnError<ErrorType>> {
 	use std::str::FromStr;
@@ -31,6 +35,8 @@ pub async fn update_deployment(
 			.headers(UpdateDeploymentRequestHeaders {
 				authorization: access_token,
 				user_agent: UserAgent::from_static("todo"),
+				if_match: last_updated_at
+					.map(|updated_at| Deployment::etag_for(updated_at).into()),
 			})
 			.body(deployment_info)
 			.build(),
@@ -24,7 +24,9 @@ pub async fn get_deployment(
 				deployment_id,
 				workspace_id,
 			})
-			.query(())
+			.query(GetDeploymentInfoQuery {
+				reveal_secret_values: false,
+			})
 			.headers(GetDeploymentInfoRequestHeaders {
 				authorization: access_token,
 				user_agent: UserAgent::from_static("todo"),
@@ -0,0 +1,37 @@
+use models::api::workspace::deployment::*;
+
+use crate::prelude::*;
+
+#[server(
+	GetDefaultDeploymentMachineTypeFn,
+	endpoint = "/infrastructure/deployment/default-machine-type/get"
+)]
+pub async fn get_default_machine_type(
+	access_token: Option<String>,
+	workspace_id: Option<Uuid>,
+) -> Result<GetDefaultDeploymentMachineTypeResponse, ServerFnError<ErrorType>> {
+	use std::str::FromStr;
+
+	let access_token = access_token
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+	let access_token = BearerToken::from_str(access_token.as_str())
+		.map_err(|_| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+
+	let workspace_id = workspace_id
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::WrongParameters))?;
+
+	make_api_call::<GetDefaultDeploymentMachineTypeRequest>(
+		ApiRequest::builder()
+			.path(GetDefaultDeploymentMachineTypePath { workspace_id })
+			.query(())
+			.headers(GetDefaultDeploymentMachineTypeRequestHeaders {
+				authorization: access_token,
+				user_agent: UserAgent::from_static("todo"),
+			})
+			.body(GetDefaultDeploymentMachineTypeRequest)
+			.build(),
+	)
+	.await
+	.map(|res| res.body)
+	.map_err(ServerFnError::WrappedServerError)
+}
@@ -10,6 +10,7 @@ pub async fn get_deployment_logs(
 	deployment_id: Uuid,
 	end_time: Option<OffsetDateTime>,
 	limit: Option<u32>,
+	replica_id: Option<String>,
 ) -> Result<GetDeploymentLogsResponse, ServerFnError<ErrorType>> {
 	use std::str::FromStr;
 
@@ -31,6 +32,9 @@ pub async fn get_deployment_logs(
 				end_time,
 				limit,
 				search: None,
+				parse_json: true,
+				level: None,
+				replica_id,
 			})
 			.headers(GetDeploymentLogsRequestHeaders {
 				authorization: access_token,
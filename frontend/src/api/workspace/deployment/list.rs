@@ -12,6 +12,7 @@ pub async fn list_deployments(
 	workspace_id: Uuid,
 	page: Option<usize>,
 	count: Option<usize>,
+	label_selector: Option<DeploymentLabelSelector>,
 ) -> Result<(usize, ListDeploymentResponse), ServerFnError<ErrorType>> {
 	use std::str::FromStr;
 
@@ -23,7 +24,11 @@ pub async fn list_deployments(
 		ApiRequest::builder()
 			.path(ListDeploymentPath { workspace_id })
 			.query(Paginated {
-				data: (),
+				data: ListDeploymentQuery {
+					order_by: None,
+					order: None,
+					label_selector,
+				},
 				page: page.unwrap_or(0),
 				count: count.unwrap_or(10),
 			})
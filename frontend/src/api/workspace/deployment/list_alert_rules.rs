@@ -0,0 +1,42 @@
+use models::api::workspace::deployment::alert_rule::*;
+
+use crate::prelude::*;
+
+/// List the alert rules of a deployment
+#[server(
+	ListDeploymentAlertRulesFn,
+	endpoint = "/infrastructure/deployment/alert-rule/list"
+)]
+pub async fn list_deployment_alert_rules(
+	access_token: Option<String>,
+	workspace_id: Option<Uuid>,
+	deployment_id: Uuid,
+) -> Result<ListDeploymentAlertRulesResponse, ServerFnError<ErrorType>> {
+	use std::str::FromStr;
+
+	let access_token = access_token
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+	let access_token = BearerToken::from_str(access_token.as_str())
+		.map_err(|_| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+
+	let workspace_id = workspace_id
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::WrongParameters))?;
+
+	make_api_call::<ListDeploymentAlertRulesRequest>(
+		ApiRequest::builder()
+			.path(ListDeploymentAlertRulesPath {
+				workspace_id,
+				deployment_id,
+			})
+			.query(())
+			.headers(ListDeploymentAlertRulesRequestHeaders {
+				authorization: access_token,
+				user_agent: UserAgent::from_static("todo"),
+			})
+			.body(ListDeploymentAlertRulesRequest)
+			.build(),
+	)
+	.await
+	.map(|res| res.body)
+	.map_err(ServerFnError::WrappedServerError)
+}
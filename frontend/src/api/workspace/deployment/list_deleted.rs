@@ -0,0 +1,41 @@
+use models::api::workspace::deployment::*;
+
+use crate::prelude::*;
+
+/// List the deleted deployments of a workspace that can still be restored
+#[server(
+	ListDeletedDeploymentsFn,
+	endpoint = "/infrastructure/deployment/deleted/list"
+)]
+pub async fn list_deleted_deployments(
+	access_token: Option<String>,
+	workspace_id: Uuid,
+	page: Option<usize>,
+	count: Option<usize>,
+) -> Result<(usize, ListDeletedDeploymentsResponse), ServerFnError<ErrorType>> {
+	use std::str::FromStr;
+
+	let access_token = access_token
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+	let access_token = BearerToken::from_str(access_token.as_str())
+		.map_err(|_| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+
+	make_api_call::<ListDeletedDeploymentsRequest>(
+		ApiRequest::builder()
+			.path(ListDeletedDeploymentsPath { workspace_id })
+			.query(Paginated {
+				data: (),
+				page: page.unwrap_or(0),
+				count: count.unwrap_or(10),
+			})
+			.headers(ListDeletedDeploymentsRequestHeaders {
+				authorization: access_token,
+				user_agent: UserAgent::from_static("todo"),
+			})
+			.body(ListDeletedDeploymentsRequest)
+			.build(),
+	)
+	.await
+	.map(|res| (res.headers.total_count.0, res.body))
+	.map_err(ServerFnError::WrappedServerError)
+}
@@ -0,0 +1,41 @@
+use models::api::workspace::deployment::*;
+
+use crate::prelude::*;
+
+#[server(
+	ListDeploymentReplicasFn,
+	endpoint = "/infrastructure/deployment/replicas/list"
+)]
+pub async fn list_deployment_replicas(
+	access_token: Option<String>,
+	workspace_id: Option<Uuid>,
+	deployment_id: Uuid,
+) -> Result<ListDeploymentReplicasResponse, ServerFnError<ErrorType>> {
+	use std::str::FromStr;
+
+	let access_token = access_token
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+	let access_token = BearerToken::from_str(access_token.as_str())
+		.map_err(|_| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+
+	let workspace_id = workspace_id
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::WrongParameters))?;
+
+	make_api_call::<ListDeploymentReplicasRequest>(
+		ApiRequest::builder()
+			.path(ListDeploymentReplicasPath {
+				workspace_id,
+				deployment_id,
+			})
+			.query(())
+			.headers(ListDeploymentReplicasRequestHeaders {
+				authorization: access_token,
+				user_agent: UserAgent::from_static("todo"),
+			})
+			.body(ListDeploymentReplicasRequest)
+			.build(),
+	)
+	.await
+	.map(|res| res.body)
+	.map_err(ServerFnError::WrappedServerError)
+}
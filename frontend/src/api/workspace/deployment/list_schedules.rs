@@ -0,0 +1,42 @@
+use models::api::workspace::deployment::schedule::*;
+
+use crate::prelude::*;
+
+/// List the schedules of a deployment
+#[server(
+	ListDeploymentSchedulesFn,
+	endpoint = "/infrastructure/deployment/schedule/list"
+)]
+pub async fn list_deployment_schedules(
+	access_token: Option<String>,
+	workspace_id: Option<Uuid>,
+	deployment_id: Uuid,
+) -> Result<ListDeploymentSchedulesResponse, ServerFnError<ErrorType>> {
+	use std::str::FromStr;
+
+	let access_token = access_token
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+	let access_token = BearerToken::from_str(access_token.as_str())
+		.map_err(|_| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+
+	let workspace_id = workspace_id
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::WrongParameters))?;
+
+	make_api_call::<ListDeploymentSchedulesRequest>(
+		ApiRequest::builder()
+			.path(ListDeploymentSchedulesPath {
+				workspace_id,
+				deployment_id,
+			})
+			.query(())
+			.headers(ListDeploymentSchedulesRequestHeaders {
+				authorization: access_token,
+				user_agent: UserAgent::from_static("todo"),
+			})
+			.body(ListDeploymentSchedulesRequest)
+			.build(),
+	)
+	.await
+	.map(|res| res.body)
+	.map_err(ServerFnError::WrappedServerError)
+}
@@ -0,0 +1,42 @@
+use models::api::workspace::deployment::template::*;
+
+use crate::prelude::*;
+
+/// List the deployment templates of a workspace
+#[server(
+	ListDeploymentTemplatesFn,
+	endpoint = "/infrastructure/deployment/template/list"
+)]
+pub async fn list_deployment_templates(
+	access_token: Option<String>,
+	workspace_id: Option<Uuid>,
+	page: Option<usize>,
+	count: Option<usize>,
+) -> Result<(usize, ListDeploymentTemplatesResponse), ServerFnError<ErrorType>> {
+	use std::str::FromStr;
+
+	let access_token = BearerToken::from_str(access_token.unwrap().as_str())
+		.map_err(|_| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+
+	let workspace_id = workspace_id
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::WrongParameters))?;
+
+	make_api_call::<ListDeploymentTemplatesRequest>(
+		ApiRequest::builder()
+			.path(ListDeploymentTemplatesPath { workspace_id })
+			.query(Paginated {
+				data: (),
+				page: page.unwrap_or(0),
+				count: count.unwrap_or(10),
+			})
+			.headers(ListDeploymentTemplatesRequestHeaders {
+				authorization: access_token,
+				user_agent: UserAgent::from_static("todo"),
+			})
+			.body(ListDeploymentTemplatesRequest)
+			.build(),
+	)
+	.await
+	.map(|res| (res.headers.total_count.0, res.body))
+	.map_err(ServerFnError::WrappedServerError)
+}
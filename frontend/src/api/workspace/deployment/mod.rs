@@ -1,25 +1,71 @@
+mod batch_get_status;
 mod create;
+mod create_alert_rule;
+mod create_schedule;
+mod create_template;
 mod delete;
+mod delete_alert_rule;
+mod delete_schedule;
+mod delete_template;
+mod download_logs;
 mod edit;
 mod get;
+mod get_default_machine_type;
+mod get_effective_config;
 mod get_logs;
 mod image_history;
 mod list;
+mod list_alert_rules;
+mod list_deleted;
 mod list_machines;
+mod list_replicas;
+mod list_schedules;
+mod list_templates;
+mod promote;
+mod reconcile;
+mod restore;
+mod set_default_machine_type;
 mod start;
 mod stop;
 mod stream_logs;
+mod test_port;
+mod update_alert_rule;
+mod update_schedule;
+mod update_template;
 
 pub use self::{
+	batch_get_status::*,
 	create::*,
+	create_alert_rule::*,
+	create_schedule::*,
+	create_template::*,
 	delete::*,
+	delete_alert_rule::*,
+	delete_schedule::*,
+	delete_template::*,
+	download_logs::*,
 	edit::*,
 	get::*,
+	get_default_machine_type::*,
+	get_effective_config::*,
 	get_logs::*,
 	image_history::*,
 	list::*,
+	list_alert_rules::*,
+	list_deleted::*,
 	list_machines::*,
+	list_replicas::*,
+	list_schedules::*,
+	list_templates::*,
+	promote::*,
+	reconcile::*,
+	restore::*,
+	set_default_machine_type::*,
 	start::*,
 	stop::*,
 	stream_logs::*,
+	test_port::*,
+	update_alert_rule::*,
+	update_schedule::*,
+	update_template::*,
 };
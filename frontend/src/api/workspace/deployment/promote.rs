@@ -0,0 +1,36 @@
+use models::api::workspace::deployment::*;
+
+use crate::prelude::*;
+
+#[server(PromoteDeploymentFn, endpoint = "/infrastructure/deployment/promote")]
+pub async fn promote_deployment(
+	access_token: Option<String>,
+	workspace_id: Uuid,
+	deployment_id: Uuid,
+	request: PromoteDeploymentRequest,
+) -> Result<PromoteDeploymentResponse, ServerFnError<ErrorType>> {
+	use std::str::FromStr;
+
+	let access_token = access_token
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+	let access_token = BearerToken::from_str(access_token.as_str())
+		.map_err(|_| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+
+	make_api_call::<PromoteDeploymentRequest>(
+		ApiRequest::builder()
+			.path(PromoteDeploymentPath {
+				deployment_id,
+				workspace_id,
+			})
+			.query(())
+			.headers(PromoteDeploymentRequestHeaders {
+				authorization: access_token,
+				user_agent: UserAgent::from_static("todo"),
+			})
+			.body(request)
+			.build(),
+	)
+	.await
+	.map(|res| res.body)
+	.map_err(ServerFnError::WrappedServerError)
+}
@@ -0,0 +1,38 @@
+use models::api::workspace::deployment::*;
+
+use crate::prelude::*;
+
+#[server(
+	ReconcileDeploymentFn,
+	endpoint = "/infrastructure/deployment/reconcile"
+)]
+pub async fn reconcile_deployment(
+	access_token: Option<String>,
+	workspace_id: Uuid,
+	deployment_id: Uuid,
+) -> Result<ReconcileDeploymentResponse, ServerFnError<ErrorType>> {
+	use std::str::FromStr;
+
+	let access_token = access_token
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+	let access_token = BearerToken::from_str(access_token.as_str())
+		.map_err(|_| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+
+	make_api_call::<ReconcileDeploymentRequest>(
+		ApiRequest::builder()
+			.path(ReconcileDeploymentPath {
+				deployment_id,
+				workspace_id,
+			})
+			.query(())
+			.headers(ReconcileDeploymentRequestHeaders {
+				authorization: access_token,
+				user_agent: UserAgent::from_static("todo"),
+			})
+			.body(ReconcileDeploymentRequest)
+			.build(),
+	)
+	.await
+	.map(|res| res.body)
+	.map_err(ServerFnError::WrappedServerError)
+}
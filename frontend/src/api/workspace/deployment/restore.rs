@@ -0,0 +1,36 @@
+use models::api::workspace::deployment::*;
+
+use crate::prelude::*;
+
+/// Restore a deleted deployment
+#[server(RestoreDeploymentFn, endpoint = "/infrastructure/deployment/restore")]
+pub async fn restore_deployment(
+	access_token: Option<String>,
+	workspace_id: Uuid,
+	deployment_id: Uuid,
+) -> Result<RestoreDeploymentResponse, ServerFnError<ErrorType>> {
+	use std::str::FromStr;
+
+	let access_token = access_token
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+	let access_token = BearerToken::from_str(access_token.as_str())
+		.map_err(|_| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+
+	make_api_call::<RestoreDeploymentRequest>(
+		ApiRequest::builder()
+			.path(RestoreDeploymentPath {
+				deployment_id,
+				workspace_id,
+			})
+			.query(())
+			.headers(RestoreDeploymentRequestHeaders {
+				authorization: access_token,
+				user_agent: UserAgent::from_static("todo"),
+			})
+			.body(RestoreDeploymentRequest)
+			.build(),
+	)
+	.await
+	.map(|res| res.body)
+	.map_err(ServerFnError::WrappedServerError)
+}
@@ -0,0 +1,38 @@
+use models::api::workspace::deployment::*;
+
+use crate::prelude::*;
+
+#[server(
+	SetDefaultDeploymentMachineTypeFn,
+	endpoint = "/infrastructure/deployment/default-machine-type/set"
+)]
+pub async fn set_default_machine_type(
+	access_token: Option<String>,
+	workspace_id: Option<Uuid>,
+	machine_type_id: Option<Uuid>,
+) -> Result<SetDefaultDeploymentMachineTypeResponse, ServerFnError<ErrorType>> {
+	use std::str::FromStr;
+
+	let access_token = access_token
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+	let access_token = BearerToken::from_str(access_token.as_str())
+		.map_err(|_| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+
+	let workspace_id = workspace_id
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::WrongParameters))?;
+
+	make_api_call::<SetDefaultDeploymentMachineTypeRequest>(
+		ApiRequest::builder()
+			.path(SetDefaultDeploymentMachineTypePath { workspace_id })
+			.query(())
+			.headers(SetDefaultDeploymentMachineTypeRequestHeaders {
+				authorization: access_token,
+				user_agent: UserAgent::from_static("todo"),
+			})
+			.body(SetDefaultDeploymentMachineTypeRequest { machine_type_id })
+			.build(),
+	)
+	.await
+	.map(|res| res.body)
+	.map_err(ServerFnError::WrappedServerError)
+}
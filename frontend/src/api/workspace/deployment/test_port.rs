@@ -0,0 +1,43 @@
+use models::api::workspace::deployment::*;
+
+use crate::prelude::*;
+
+/// Server function to test whether an exposed port of a deployment is
+/// reachable
+#[server(
+	TestDeploymentPortFn,
+	endpoint = "/infrastructure/deployment/test-port"
+)]
+pub async fn test_deployment_port(
+	access_token: Option<String>,
+	workspace_id: Option<Uuid>,
+	deployment_id: Uuid,
+	port: u16,
+) -> Result<TestDeploymentPortResponse, ServerFnError<ErrorType>> {
+	use std::str::FromStr;
+
+	let access_token = BearerToken::from_str(access_token.unwrap().as_str())
+		.map_err(|_| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+
+	let workspace_id = workspace_id
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::WrongParameters))?;
+
+	make_api_call::<TestDeploymentPortRequest>(
+		ApiRequest::builder()
+			.path(TestDeploymentPortPath {
+				workspace_id,
+				deployment_id,
+				port,
+			})
+			.query(())
+			.headers(TestDeploymentPortRequestHeaders {
+				authorization: access_token,
+				user_agent: UserAgent::from_static("todo"),
+			})
+			.body(TestDeploymentPortRequest)
+			.build(),
+	)
+	.await
+	.map(|res| res.body)
+	.map_err(ServerFnError::WrappedServerError)
+}
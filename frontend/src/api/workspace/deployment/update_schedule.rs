@@ -0,0 +1,47 @@
+use leptos::server_fn::codec::Json;
+use models::api::workspace::deployment::schedule::*;
+
+use crate::prelude::*;
+
+/// Server function to update a schedule of a deployment
+#[server(
+	UpdateDeploymentScheduleFn,
+	input = Json,
+	endpoint = "/infrastructure/deployment/schedule/update"
+)]
+pub async fn update_deployment_schedule(
+	access_token: Option<String>,
+	workspace_id: Option<Uuid>,
+	deployment_id: Uuid,
+	schedule_id: Uuid,
+	schedule: UpdateDeploymentScheduleRequest,
+) -> Result<UpdateDeploymentScheduleResponse, ServerFnError<ErrorType>> {
+	use std::str::FromStr;
+
+	let access_token = access_token
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+	let access_token = BearerToken::from_str(access_token.as_str())
+		.map_err(|_| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+
+	let workspace_id = workspace_id
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::WrongParameters))?;
+
+	make_api_call::<UpdateDeploymentScheduleRequest>(
+		ApiRequest::builder()
+			.path(UpdateDeploymentSchedulePath {
+				workspace_id,
+				deployment_id,
+				schedule_id,
+			})
+			.query(())
+			.headers(UpdateDeploymentScheduleRequestHeaders {
+				authorization: access_token,
+				user_agent: UserAgent::from_static("todo"),
+			})
+			.body(schedule)
+			.build(),
+	)
+	.await
+	.map(|res| res.body)
+	.map_err(ServerFnError::WrappedServerError)
+}
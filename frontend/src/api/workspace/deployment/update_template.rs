@@ -0,0 +1,43 @@
+use leptos::server_fn::codec::Json;
+use models::api::workspace::deployment::template::*;
+
+use crate::prelude::*;
+
+/// Server function to update a deployment template
+#[server(
+	UpdateDeploymentTemplateFn,
+	input = Json,
+	endpoint = "/infrastructure/deployment/template/update"
+)]
+pub async fn update_deployment_template(
+	access_token: Option<String>,
+	workspace_id: Option<Uuid>,
+	template_id: Uuid,
+	template: UpdateDeploymentTemplateRequest,
+) -> Result<UpdateDeploymentTemplateResponse, ServerFnError<ErrorType>> {
+	use std::str::FromStr;
+
+	let access_token = BearerToken::from_str(access_token.unwrap().as_str())
+		.map_err(|_| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+
+	let workspace_id = workspace_id
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::WrongParameters))?;
+
+	make_api_call::<UpdateDeploymentTemplateRequest>(
+		ApiRequest::builder()
+			.path(UpdateDeploymentTemplatePath {
+				workspace_id,
+				template_id,
+			})
+			.query(())
+			.headers(UpdateDeploymentTemplateRequestHeaders {
+				authorization: access_token,
+				user_agent: UserAgent::from_static("todo"),
+			})
+			.body(template)
+			.build(),
+	)
+	.await
+	.map(|res| res.body)
+	.map_err(ServerFnError::WrappedServerError)
+}
@@ -0,0 +1,42 @@
+use models::api::workspace::*;
+use time::OffsetDateTime;
+
+use crate::prelude::*;
+
+#[server(GetApiUsageFn, endpoint = "/workspace/get_api_usage")]
+pub async fn get_api_usage(
+	access_token: Option<String>,
+	workspace_id: Option<Uuid>,
+	start_time: OffsetDateTime,
+	end_time: Option<OffsetDateTime>,
+	endpoint: Option<String>,
+) -> Result<GetApiUsageResponse, ServerFnError<ErrorType>> {
+	use std::str::FromStr;
+
+	let access_token = access_token
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+	let access_token = BearerToken::from_str(access_token.as_str())
+		.map_err(|_| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+
+	let workspace_id = workspace_id
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::WrongParameters))?;
+
+	make_api_call::<GetApiUsageRequest>(
+		ApiRequest::builder()
+			.path(GetApiUsagePath { workspace_id })
+			.query(GetApiUsageQuery {
+				start_time,
+				end_time,
+				endpoint,
+			})
+			.headers(GetApiUsageRequestHeaders {
+				authorization: access_token,
+				user_agent: UserAgent::from_static("todo"),
+			})
+			.body(GetApiUsageRequest)
+			.build(),
+	)
+	.await
+	.map(|res| res.body)
+	.map_err(ServerFnError::WrappedServerError)
+}
@@ -0,0 +1,34 @@
+use models::api::workspace::egress_policy::*;
+
+use crate::prelude::*;
+
+#[server(GetEgressPolicyFn, endpoint = "/workspace/get_egress_policy")]
+pub async fn get_egress_policy(
+	access_token: Option<String>,
+	workspace_id: Option<Uuid>,
+) -> Result<GetEgressPolicyResponse, ServerFnError<ErrorType>> {
+	use std::str::FromStr;
+
+	let access_token = access_token
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+	let access_token = BearerToken::from_str(access_token.as_str())
+		.map_err(|_| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+
+	let workspace_id = workspace_id
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::WrongParameters))?;
+
+	make_api_call::<GetEgressPolicyRequest>(
+		ApiRequest::builder()
+			.path(GetEgressPolicyPath { workspace_id })
+			.query(())
+			.headers(GetEgressPolicyRequestHeaders {
+				authorization: access_token,
+				user_agent: UserAgent::from_static("todo"),
+			})
+			.body(GetEgressPolicyRequest)
+			.build(),
+	)
+	.await
+	.map(|res| res.body)
+	.map_err(ServerFnError::WrappedServerError)
+}
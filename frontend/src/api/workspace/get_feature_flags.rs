@@ -0,0 +1,34 @@
+use models::api::workspace::*;
+
+use crate::prelude::*;
+
+#[server(GetFeatureFlagsFn, endpoint = "/workspace/get_feature_flags")]
+pub async fn get_feature_flags(
+	access_token: Option<String>,
+	workspace_id: Option<Uuid>,
+) -> Result<GetFeatureFlagsResponse, ServerFnError<ErrorType>> {
+	use std::str::FromStr;
+
+	let access_token = access_token
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+	let access_token = BearerToken::from_str(access_token.as_str())
+		.map_err(|_| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+
+	let workspace_id = workspace_id
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::WrongParameters))?;
+
+	make_api_call::<GetFeatureFlagsRequest>(
+		ApiRequest::builder()
+			.path(GetFeatureFlagsPath { workspace_id })
+			.query(())
+			.headers(GetFeatureFlagsRequestHeaders {
+				authorization: access_token,
+				user_agent: UserAgent::from_static("todo"),
+			})
+			.body(GetFeatureFlagsRequest)
+			.build(),
+	)
+	.await
+	.map(|res| res.body)
+	.map_err(ServerFnError::WrappedServerError)
+}
@@ -0,0 +1,43 @@
+use models::api::workspace::*;
+
+use crate::prelude::*;
+
+#[server(
+	ListWorkspaceActivityFn,
+	endpoint = "/workspace/list_workspace_activity"
+)]
+pub async fn list_workspace_activity(
+	access_token: Option<String>,
+	workspace_id: Option<Uuid>,
+	cursor: Option<String>,
+	category: Option<WorkspaceActivityCategory>,
+) -> Result<ListWorkspaceActivityResponse, ServerFnError<ErrorType>> {
+	use std::str::FromStr;
+
+	let access_token = access_token
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+	let access_token = BearerToken::from_str(access_token.as_str())
+		.map_err(|_| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+
+	let workspace_id = workspace_id
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::WrongParameters))?;
+
+	make_api_call::<ListWorkspaceActivityRequest>(
+		ApiRequest::builder()
+			.path(ListWorkspaceActivityPath { workspace_id })
+			.query(ListWorkspaceActivityQuery {
+				cursor,
+				limit: None,
+				category,
+			})
+			.headers(ListWorkspaceActivityRequestHeaders {
+				authorization: access_token,
+				user_agent: UserAgent::from_static("todo"),
+			})
+			.body(ListWorkspaceActivityRequest)
+			.build(),
+	)
+	.await
+	.map(|res| res.body)
+	.map_err(ServerFnError::WrappedServerError)
+}
@@ -0,0 +1,45 @@
+use models::api::{user::ApiTokenStatus, workspace::*};
+
+use crate::prelude::*;
+
+#[server(
+	ListWorkspaceApiTokensFn,
+	endpoint = "/workspace/list_workspace_api_tokens"
+)]
+pub async fn list_workspace_api_tokens(
+	access_token: Option<String>,
+	workspace_id: Option<Uuid>,
+	status: Option<ApiTokenStatus>,
+	user_id: Option<Uuid>,
+	page: Option<usize>,
+	page_size: Option<usize>,
+) -> Result<(usize, ListWorkspaceApiTokensResponse), ServerFnError<ErrorType>> {
+	use std::str::FromStr;
+
+	let access_token = access_token
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+	let access_token = BearerToken::from_str(access_token.as_str())
+		.map_err(|_| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+
+	let workspace_id = workspace_id
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::WrongParameters))?;
+
+	make_api_call::<ListWorkspaceApiTokensRequest>(
+		ApiRequest::builder()
+			.path(ListWorkspaceApiTokensPath { workspace_id })
+			.query(Paginated {
+				data: ListWorkspaceApiTokensQuery { status, user_id },
+				page: page.unwrap_or(0),
+				count: page_size.unwrap_or(10),
+			})
+			.headers(ListWorkspaceApiTokensRequestHeaders {
+				authorization: access_token,
+				user_agent: UserAgent::from_static("todo"),
+			})
+			.body(ListWorkspaceApiTokensRequest)
+			.build(),
+	)
+	.await
+	.map(|res| (res.headers.total_count.0, res.body))
+	.map_err(ServerFnError::WrappedServerError)
+}
@@ -87,3 +87,39 @@ pub async fn create_managed_url(
 	})
 	.map_err(ServerFnError::WrappedServerError)
 }
+
+/// Creates a managed URL from a request that is already built, which allows
+/// creating managed URLs with path rules
+#[server(
+	CreateManagedURLWithRulesFn,
+	input = Json,
+	endpoint = "/domain-config/managed-url/create-with-rules"
+)]
+pub async fn create_managed_url_with_rules(
+	access_token: Option<String>,
+	workspace_id: Option<Uuid>,
+	request: CreateManagedURLRequest,
+) -> Result<CreateManagedURLResponse, ServerFnError<ErrorType>> {
+	let access_token = access_token
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+	let access_token = BearerToken::from_str(access_token.as_str())
+		.map_err(|_| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+
+	let workspace_id = workspace_id
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::WrongParameters))?;
+
+	make_api_call::<CreateManagedURLRequest>(
+		ApiRequest::builder()
+			.path(CreateManagedURLPath { workspace_id })
+			.query(())
+			.headers(CreateManagedURLRequestHeaders {
+				authorization: access_token,
+				user_agent: UserAgent::from_static("todo"),
+			})
+			.body(request)
+			.build(),
+	)
+	.await
+	.map(|res| res.body)
+	.map_err(ServerFnError::WrappedServerError)
+}
@@ -49,3 +49,45 @@ pub async fn update_managed_url(
 	.map(|res| res.body)
 	.map_err(ServerFnError::WrappedServerError)
 }
+
+/// Updates a managed URL from a request that is already built, which allows
+/// updating the path rules of a managed URL
+#[server(
+	UpdateManagedURLRulesFn,
+	input = Json,
+	endpoint = "/domain-config/managed-url/update-rules"
+)]
+pub async fn update_managed_url_rules(
+	access_token: Option<String>,
+	workspace_id: Option<Uuid>,
+	managed_url_id: Uuid,
+	request: UpdateManagedURLRequest,
+) -> Result<UpdateManagedURLResponse, ServerFnError<ErrorType>> {
+	use std::str::FromStr;
+
+	let access_token = access_token
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+	let access_token = BearerToken::from_str(access_token.as_str())
+		.map_err(|_| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+
+	let workspace_id = workspace_id
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::WrongParameters))?;
+
+	make_api_call::<UpdateManagedURLRequest>(
+		ApiRequest::builder()
+			.path(UpdateManagedURLPath {
+				workspace_id,
+				managed_url_id,
+			})
+			.query(())
+			.headers(UpdateManagedURLRequestHeaders {
+				authorization: access_token,
+				user_agent: UserAgent::from_static("todo"),
+			})
+			.body(request)
+			.build(),
+	)
+	.await
+	.map(|res| res.body)
+	.map_err(ServerFnError::WrappedServerError)
+}
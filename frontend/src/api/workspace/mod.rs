@@ -2,20 +2,38 @@ mod create_workspace;
 mod database;
 mod deployment;
 mod domain;
+mod get_api_usage;
+mod get_egress_policy;
+mod get_feature_flags;
 mod get_workspace_info;
+mod list_workspace_activity;
+mod list_workspace_api_tokens;
 mod list_workspaces;
 mod managed_url;
 mod rbac;
+mod revoke_workspace_api_token;
 mod runner;
+mod search_workspace_resources;
+mod set_egress_policy;
+mod volume;
 
 pub use self::{
 	create_workspace::*,
 	database::*,
 	deployment::*,
 	domain::*,
+	get_api_usage::*,
+	get_egress_policy::*,
+	get_feature_flags::*,
 	get_workspace_info::*,
+	list_workspace_activity::*,
+	list_workspace_api_tokens::*,
 	list_workspaces::*,
 	managed_url::*,
 	rbac::*,
+	revoke_workspace_api_token::*,
 	runner::*,
+	search_workspace_resources::*,
+	set_egress_policy::*,
+	volume::*,
 };
@@ -1,11 +1,16 @@
+use leptos::server_fn::codec::Json;
 use models::api::workspace::rbac::*;
 
 use crate::prelude::*;
 
+/// Server function to list all the permissions, optionally filtered by a
+/// search term and a category
 #[server(ListAppPermissionsFn, endpoint = "/workspace/rbac/permissions")]
 pub async fn list_all_permissions(
 	access_token: Option<String>,
 	workspace_id: Uuid,
+	search: Option<String>,
+	category: Option<String>,
 ) -> Result<ListAllPermissionsResponse, ServerFnError<ErrorType>> {
 	use std::str::FromStr;
 
@@ -15,7 +20,7 @@ pub async fn list_all_permissions(
 	make_api_call::<ListAllPermissionsRequest>(
 		ApiRequest::builder()
 			.path(ListAllPermissionsPath { workspace_id })
-			.query(())
+			.query(ListAllPermissionsQuery { search, category })
 			.headers(ListAllPermissionsRequestHeaders {
 				authorization: access_token,
 				user_agent: UserAgent::from_static("hyper/0.12.2"),
@@ -27,3 +32,96 @@ pub async fn list_all_permissions(
 	.map(|res| res.body)
 	.map_err(ServerFnError::WrappedServerError)
 }
+
+/// Server function to check which of the given permissions the current user
+/// has in a workspace
+#[server(
+	CheckPermissionsFn,
+	input = Json,
+	endpoint = "/workspace/rbac/check-permissions"
+)]
+pub async fn check_permissions(
+	access_token: Option<String>,
+	workspace_id: Uuid,
+	request: CheckPermissionsRequest,
+) -> Result<CheckPermissionsResponse, ServerFnError<ErrorType>> {
+	use std::str::FromStr;
+
+	let access_token = BearerToken::from_str(access_token.unwrap().as_str())
+		.map_err(|_| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+
+	make_api_call::<CheckPermissionsRequest>(
+		ApiRequest::builder()
+			.path(CheckPermissionsPath { workspace_id })
+			.query(())
+			.headers(CheckPermissionsRequestHeaders {
+				authorization: access_token,
+				user_agent: UserAgent::from_static("hyper/0.12.2"),
+			})
+			.body(request)
+			.build(),
+	)
+	.await
+	.map(|res| res.body)
+	.map_err(ServerFnError::WrappedServerError)
+}
+
+/// Server function to export all the roles of a workspace along with their
+/// permissions
+#[server(ExportWorkspaceRolesFn, endpoint = "/workspace/rbac/role/export")]
+pub async fn export_workspace_roles(
+	access_token: Option<String>,
+	workspace_id: Uuid,
+) -> Result<role::ExportWorkspaceRolesResponse, ServerFnError<ErrorType>> {
+	use std::str::FromStr;
+
+	let access_token = BearerToken::from_str(access_token.unwrap().as_str())
+		.map_err(|_| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+
+	make_api_call::<role::ExportWorkspaceRolesRequest>(
+		ApiRequest::builder()
+			.path(role::ExportWorkspaceRolesPath { workspace_id })
+			.query(())
+			.headers(role::ExportWorkspaceRolesRequestHeaders {
+				authorization: access_token,
+				user_agent: UserAgent::from_static("hyper/0.12.2"),
+			})
+			.body(role::ExportWorkspaceRolesRequest)
+			.build(),
+	)
+	.await
+	.map(|res| res.body)
+	.map_err(ServerFnError::WrappedServerError)
+}
+
+/// Server function to import roles that were exported from another workspace
+#[server(
+	ImportWorkspaceRolesFn,
+	input = Json,
+	endpoint = "/workspace/rbac/role/import"
+)]
+pub async fn import_workspace_roles(
+	access_token: Option<String>,
+	workspace_id: Uuid,
+	request: role::ImportWorkspaceRolesRequest,
+) -> Result<role::ImportWorkspaceRolesResponse, ServerFnError<ErrorType>> {
+	use std::str::FromStr;
+
+	let access_token = BearerToken::from_str(access_token.unwrap().as_str())
+		.map_err(|_| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+
+	make_api_call::<role::ImportWorkspaceRolesRequest>(
+		ApiRequest::builder()
+			.path(role::ImportWorkspaceRolesPath { workspace_id })
+			.query(())
+			.headers(role::ImportWorkspaceRolesRequestHeaders {
+				authorization: access_token,
+				user_agent: UserAgent::from_static("hyper/0.12.2"),
+			})
+			.body(request)
+			.build(),
+	)
+	.await
+	.map(|res| res.body)
+	.map_err(ServerFnError::WrappedServerError)
+}
@@ -0,0 +1,41 @@
+use models::api::workspace::*;
+
+use crate::prelude::*;
+
+#[server(
+	RevokeWorkspaceApiTokenFn,
+	endpoint = "/workspace/revoke_workspace_api_token"
+)]
+pub async fn revoke_workspace_api_token(
+	access_token: Option<String>,
+	workspace_id: Option<Uuid>,
+	token_id: Uuid,
+) -> Result<RevokeWorkspaceApiTokenResponse, ServerFnError<ErrorType>> {
+	use std::str::FromStr;
+
+	let access_token = access_token
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+	let access_token = BearerToken::from_str(access_token.as_str())
+		.map_err(|_| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+
+	let workspace_id = workspace_id
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::WrongParameters))?;
+
+	make_api_call::<RevokeWorkspaceApiTokenRequest>(
+		ApiRequest::builder()
+			.path(RevokeWorkspaceApiTokenPath {
+				workspace_id,
+				token_id,
+			})
+			.query(())
+			.headers(RevokeWorkspaceApiTokenRequestHeaders {
+				authorization: access_token,
+				user_agent: UserAgent::from_static("todo"),
+			})
+			.body(RevokeWorkspaceApiTokenRequest)
+			.build(),
+	)
+	.await
+	.map(|res| res.body)
+	.map_err(ServerFnError::WrappedServerError)
+}
@@ -29,5 +29,5 @@ pub async fn create_runner(
 	)
 	.await
 	.map(|res| res.body)
-	.map_err(ServerFnError::WrappedServerError)
+	.map_err(into_server_fn_error)
 }
@@ -6,7 +6,10 @@ use crate::prelude::*;
 pub async fn list_runners(
 	access_token: Option<String>,
 	workspace_id: Uuid,
-) -> Result<ListRunnersForWorkspaceResponse, ServerFnError<ErrorType>> {
+	page: Option<usize>,
+	count: Option<usize>,
+	status: Option<RunnerStatus>,
+) -> Result<(usize, ListRunnersForWorkspaceResponse), ServerFnError<ErrorType>> {
 	use std::str::FromStr;
 
 	let access_token = BearerToken::from_str(access_token.unwrap().as_str())
@@ -16,9 +19,9 @@ pub async fn list_runners(
 		ApiRequest::builder()
 			.path(ListRunnersForWorkspacePath { workspace_id })
 			.query(Paginated {
-				data: (),
-				page: 0,
-				count: 10,
+				data: ListRunnersForWorkspaceQuery { status },
+				page: page.unwrap_or(0),
+				count: count.unwrap_or(10),
 			})
 			.headers(ListRunnersForWorkspaceRequestHeaders {
 				authorization: access_token,
@@ -28,6 +31,39 @@ pub async fn list_runners(
 			.build(),
 	)
 	.await
-	.map(|res| res.body)
+	.map(|res| (res.headers.total_count.0, res.body))
 	.map_err(ServerFnError::WrappedServerError)
 }
+
+/// Lists every runner in the workspace, fetching one page at a time until the
+/// total count reported by the API is reached. Used by the runner dropdowns,
+/// which need to show all the runners instead of a single page
+pub async fn list_all_runners(
+	access_token: Option<String>,
+	workspace_id: Uuid,
+	status: Option<RunnerStatus>,
+) -> Result<ListRunnersForWorkspaceResponse, ServerFnError<ErrorType>> {
+	/// The number of runners to fetch in each request
+	const PAGE_SIZE: usize = 100;
+
+	let mut runners = Vec::new();
+	for page in 0.. {
+		let (total_count, response) = list_runners(
+			access_token.clone(),
+			workspace_id,
+			Some(page),
+			Some(PAGE_SIZE),
+			status,
+		)
+		.await?;
+
+		let page_len = response.runners.len();
+		runners.extend(response.runners);
+
+		if page_len < PAGE_SIZE || runners.len() >= total_count {
+			break;
+		}
+	}
+
+	Ok(ListRunnersForWorkspaceResponse { runners })
+}
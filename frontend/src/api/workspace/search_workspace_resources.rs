@@ -0,0 +1,35 @@
+use models::api::workspace::*;
+
+use crate::prelude::*;
+
+#[server(SearchWorkspaceResourcesFn, endpoint = "/workspace/search")]
+pub async fn search_workspace_resources(
+	access_token: Option<String>,
+	workspace_id: Option<Uuid>,
+	query: String,
+) -> Result<SearchWorkspaceResourcesResponse, ServerFnError<ErrorType>> {
+	use std::str::FromStr;
+
+	let access_token = access_token
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+	let access_token = BearerToken::from_str(access_token.as_str())
+		.map_err(|_| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+
+	let workspace_id = workspace_id
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::WrongParameters))?;
+
+	make_api_call::<SearchWorkspaceResourcesRequest>(
+		ApiRequest::builder()
+			.path(SearchWorkspaceResourcesPath { workspace_id })
+			.query(SearchWorkspaceResourcesQuery { query })
+			.headers(SearchWorkspaceResourcesRequestHeaders {
+				authorization: access_token,
+				user_agent: UserAgent::from_static("todo"),
+			})
+			.body(SearchWorkspaceResourcesRequest)
+			.build(),
+	)
+	.await
+	.map(|res| res.body)
+	.map_err(ServerFnError::WrappedServerError)
+}
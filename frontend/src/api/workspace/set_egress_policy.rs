@@ -0,0 +1,38 @@
+use models::api::workspace::egress_policy::*;
+
+use crate::prelude::*;
+
+#[server(SetEgressPolicyFn, endpoint = "/workspace/set_egress_policy")]
+pub async fn set_egress_policy(
+	access_token: Option<String>,
+	workspace_id: Option<Uuid>,
+	policy: WorkspaceEgressPolicy,
+) -> Result<SetEgressPolicyResponse, ServerFnError<ErrorType>> {
+	use std::str::FromStr;
+
+	let access_token = access_token
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+	let access_token = BearerToken::from_str(access_token.as_str())
+		.map_err(|_| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+
+	let workspace_id = workspace_id
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::WrongParameters))?;
+
+	make_api_call::<SetEgressPolicyRequest>(
+		ApiRequest::builder()
+			.path(SetEgressPolicyPath { workspace_id })
+			.query(())
+			.headers(SetEgressPolicyRequestHeaders {
+				authorization: access_token,
+				user_agent: UserAgent::from_static("todo"),
+			})
+			.body(SetEgressPolicyRequest {
+				allow: policy.allow,
+				deny: policy.deny,
+			})
+			.build(),
+	)
+	.await
+	.map(|res| res.body)
+	.map_err(ServerFnError::WrappedServerError)
+}
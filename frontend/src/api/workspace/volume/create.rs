@@ -0,0 +1,35 @@
+use models::api::workspace::volume::*;
+
+use crate::prelude::*;
+
+#[server(CreateVolumeFn, endpoint = "/infrastructure/volume/create")]
+pub async fn create_volume(
+	access_token: Option<String>,
+	workspace_id: Option<Uuid>,
+	request: CreateVolumeRequest,
+) -> Result<CreateVolumeResponse, ServerFnError<ErrorType>> {
+	use std::str::FromStr;
+
+	let access_token = access_token
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+	let access_token = BearerToken::from_str(access_token.as_str())
+		.map_err(|_| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+
+	let workspace_id = workspace_id
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::WrongParameters))?;
+
+	make_api_call::<CreateVolumeRequest>(
+		ApiRequest::builder()
+			.path(CreateVolumePath { workspace_id })
+			.query(())
+			.headers(CreateVolumeRequestHeaders {
+				authorization: access_token,
+				user_agent: UserAgent::from_static("todo"),
+			})
+			.body(request)
+			.build(),
+	)
+	.await
+	.map(|res| res.body)
+	.map_err(ServerFnError::WrappedServerError)
+}
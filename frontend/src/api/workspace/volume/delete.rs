@@ -0,0 +1,38 @@
+use models::api::workspace::volume::*;
+
+use crate::prelude::*;
+
+#[server(DeleteVolumeFn, endpoint = "/infrastructure/volume/delete")]
+pub async fn delete_volume(
+	access_token: Option<String>,
+	workspace_id: Option<Uuid>,
+	volume_id: Uuid,
+) -> Result<DeleteVolumeResponse, ServerFnError<ErrorType>> {
+	use std::str::FromStr;
+
+	let access_token = access_token
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+	let access_token = BearerToken::from_str(access_token.as_str())
+		.map_err(|_| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+
+	let workspace_id = workspace_id
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::WrongParameters))?;
+
+	make_api_call::<DeleteVolumeRequest>(
+		ApiRequest::builder()
+			.path(DeleteVolumePath {
+				workspace_id,
+				volume_id,
+			})
+			.query(())
+			.headers(DeleteVolumeRequestHeaders {
+				authorization: access_token,
+				user_agent: UserAgent::from_static("todo"),
+			})
+			.body(DeleteVolumeRequest)
+			.build(),
+	)
+	.await
+	.map(|res| res.body)
+	.map_err(ServerFnError::WrappedServerError)
+}
@@ -0,0 +1,38 @@
+use models::api::workspace::volume::*;
+
+use crate::prelude::*;
+
+#[server(GetVolumeFn, endpoint = "/infrastructure/volume/get")]
+pub async fn get_volume(
+	access_token: Option<String>,
+	workspace_id: Option<Uuid>,
+	volume_id: Uuid,
+) -> Result<GetVolumeInfoResponse, ServerFnError<ErrorType>> {
+	use std::str::FromStr;
+
+	let access_token = access_token
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+	let access_token = BearerToken::from_str(access_token.as_str())
+		.map_err(|_| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+
+	let workspace_id = workspace_id
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::WrongParameters))?;
+
+	make_api_call::<GetVolumeInfoRequest>(
+		ApiRequest::builder()
+			.path(GetVolumeInfoPath {
+				workspace_id,
+				volume_id,
+			})
+			.query(())
+			.headers(GetVolumeInfoRequestHeaders {
+				authorization: access_token,
+				user_agent: UserAgent::from_static("todo"),
+			})
+			.body(GetVolumeInfoRequest)
+			.build(),
+	)
+	.await
+	.map(|res| res.body)
+	.map_err(ServerFnError::WrappedServerError)
+}
@@ -0,0 +1,40 @@
+use models::api::workspace::volume::*;
+
+use crate::prelude::*;
+
+#[server(ListVolumesFn, endpoint = "/infrastructure/volume/list")]
+pub async fn list_volumes(
+	access_token: Option<String>,
+	workspace_id: Option<Uuid>,
+	page: Option<usize>,
+	page_size: Option<usize>,
+) -> Result<(usize, ListVolumesInWorkspaceResponse), ServerFnError<ErrorType>> {
+	use std::str::FromStr;
+
+	let access_token = access_token
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+	let access_token = BearerToken::from_str(access_token.as_str())
+		.map_err(|_| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+
+	let workspace_id = workspace_id
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::WrongParameters))?;
+
+	make_api_call::<ListVolumesInWorkspaceRequest>(
+		ApiRequest::builder()
+			.path(ListVolumesInWorkspacePath { workspace_id })
+			.query(Paginated {
+				data: (),
+				page: page.unwrap_or(0),
+				count: page_size.unwrap_or(10),
+			})
+			.headers(ListVolumesInWorkspaceRequestHeaders {
+				authorization: access_token,
+				user_agent: UserAgent::from_static("todo"),
+			})
+			.body(ListVolumesInWorkspaceRequest)
+			.build(),
+	)
+	.await
+	.map(|res| (res.headers.total_count.0, res.body))
+	.map_err(ServerFnError::WrappedServerError)
+}
@@ -0,0 +1,7 @@
+mod create;
+mod delete;
+mod get;
+mod list;
+mod update;
+
+pub use self::{create::*, delete::*, get::*, list::*, update::*};
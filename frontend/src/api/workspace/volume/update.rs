@@ -0,0 +1,39 @@
+use models::api::workspace::volume::*;
+
+use crate::prelude::*;
+
+#[server(UpdateVolumeFn, endpoint = "/infrastructure/volume/update")]
+pub async fn update_volume(
+	access_token: Option<String>,
+	workspace_id: Option<Uuid>,
+	volume_id: Uuid,
+	request: UpdateVolumeRequest,
+) -> Result<UpdateVolumeResponse, ServerFnError<ErrorType>> {
+	use std::str::FromStr;
+
+	let access_token = access_token
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+	let access_token = BearerToken::from_str(access_token.as_str())
+		.map_err(|_| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+
+	let workspace_id = workspace_id
+		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::WrongParameters))?;
+
+	make_api_call::<UpdateVolumeRequest>(
+		ApiRequest::builder()
+			.path(UpdateVolumePath {
+				workspace_id,
+				volume_id,
+			})
+			.query(())
+			.headers(UpdateVolumeRequestHeaders {
+				authorization: access_token,
+				user_agent: UserAgent::from_static("todo"),
+			})
+			.body(request)
+			.build(),
+	)
+	.await
+	.map(|res| res.body)
+	.map_err(ServerFnError::WrappedServerError)
+}
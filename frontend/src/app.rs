@@ -9,6 +9,8 @@ pub fn AppOutletView() -> impl IntoView {
 	let (state, _) = AuthState::load();
 	let app_type = expect_context::<AppType>();
 
+	provide_feature_flags();
+
 	move || match state.get() {
 		AuthState::LoggedOut => view! {
 			<PageContainer class="bg-image">
@@ -35,6 +37,8 @@ pub fn AppOutletView() -> impl IntoView {
 				<main class="fc-fs-ct full-width px-lg">
 					<Outlet />
 				</main>
+
+				{app_type.is_managed().then(|| view! { <WorkspaceSearchPalette /> })}
 			</div>
 		}
 		.into_view(),
@@ -69,9 +73,6 @@ pub fn App() -> impl IntoView {
 	let (state, _) = AuthState::load();
 	let app_type = AppType::Managed;
 
-	// TODO: When redirecting to login, the URL should include the path that the
-	// user was trying to access. This way, after login, the user is redirected
-	// to the page they were trying to access.
 	provide_context(app_type);
 	provide_toaster();
 
@@ -81,12 +82,7 @@ pub fn App() -> impl IntoView {
 		<Router>
 			<Routes>
 				// Logged in routes
-				<ProtectedRoute
-					path={AppRoutes::Empty}
-					view={AppOutletView}
-					redirect_path={AppRoutes::LoggedOutRoute(LoggedOutRoute::Login)}
-					condition={move || state.get().is_logged_in()}
-				>
+				<LoginRequiredRoute path={AppRoutes::Empty} view={AppOutletView}>
 					<ProfileRoutes />
 					<InfrastructureRoutes />
 					<Route path={LoggedInRoute::ManagedUrl} view={ManagedUrlPage}>
@@ -99,10 +95,10 @@ pub fn App() -> impl IntoView {
 						path={AppRoutes::Empty}
 						view={HomePage}
 					/>
-				</ProtectedRoute>
+				</LoginRequiredRoute>
 				<ProtectedRoute
 					path={"".to_string()}
-					redirect_path={AppRoutes::LoggedInRoute(LoggedInRoute::Home).to_string()}
+					redirect_path={DeploymentsDashboardRoute {}.to_string()}
 					view={AppOutletView}
 					condition={move || state.get().is_logged_out()}
 				>
@@ -76,6 +76,12 @@ pub mod otp_input;
 /// The page title component is used to display the title of a page,
 /// specifically the title of a dashboard page.
 pub mod page_title;
+/// The pagination component.
+///
+/// The pagination component is used to display the prev / next buttons and the
+/// page numbers below a paginated list of resources. It updates the page signal
+/// that the list is fetched with.
+pub mod pagination;
 /// The Popover Component
 ///
 /// The Popover Component, used to display a tooltip when user hovers / clicks
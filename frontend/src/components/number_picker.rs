@@ -14,8 +14,8 @@ pub fn NumberPicker(
 	#[prop(optional, default = 1)]
 	min: u16,
 	/// The max value of the input
-	#[prop(optional, default = 10)]
-	max: u16,
+	#[prop(into, optional, default = 10.into())]
+	max: MaybeSignal<u16>,
 	/// The Initial Value
 	#[prop(into)]
 	value: RwSignal<u16>,
@@ -36,14 +36,14 @@ pub fn NumberPicker(
 	let on_minus = move || {
 		value.update(|v| {
 			let changed_val = *v - 1;
-			*v = cmp::max(cmp::min(changed_val, max), min)
+			*v = cmp::max(cmp::min(changed_val, max.get()), min)
 		})
 	};
 	let on_plus = move || {
 		value.update(|v| {
 			let changed_val = *v + 1;
 
-			*v = cmp::max(cmp::min(changed_val, max), min)
+			*v = cmp::max(cmp::min(changed_val, max.get()), min)
 		})
 	};
 
@@ -65,7 +65,7 @@ pub fn NumberPicker(
 				class="mx-md text-white text-center outline-primary-focus py-xxs br-sm"
 				type="number"
 				min={min}
-				max={max}
+				max={move || max.get()}
 				prop:value={value}
 			/>
 
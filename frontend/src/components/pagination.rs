@@ -0,0 +1,96 @@
+use std::rc::Rc;
+
+use ev::MouseEvent;
+
+use crate::imports::*;
+
+/// Get the number of pages needed to show the given number of resources, with
+/// [`RESOURCES_PER_PAGE`][1] resources on each page
+///
+/// [1]: constants::RESOURCES_PER_PAGE
+fn get_num_pages(total_count: usize) -> usize {
+	total_count.div_ceil(constants::RESOURCES_PER_PAGE)
+}
+
+#[component]
+pub fn Pagination(
+	/// The current page, starting from 0. This is the same signal that is
+	/// passed to the list query, so changing the page refetches the list
+	#[prop(into)]
+	current_page: RwSignal<usize>,
+	/// The total number of resources across all pages
+	#[prop(into)]
+	total_count: MaybeSignal<usize>,
+	/// Additional classes to apply to the outer div, if any
+	#[prop(into, optional)]
+	class: MaybeSignal<String>,
+) -> impl IntoView {
+	let total_pages = Signal::derive(move || get_num_pages(total_count.get()));
+
+	let is_first_page = Signal::derive(move || current_page.get() == 0);
+	let is_last_page = Signal::derive(move || current_page.get() + 1 >= total_pages.get());
+
+	let on_click_prev = move |_: &MouseEvent| {
+		current_page.update(|page| *page = page.saturating_sub(1));
+	};
+
+	let on_click_next = move |_: &MouseEvent| {
+		if !is_last_page.get() {
+			current_page.update(|page| *page += 1);
+		}
+	};
+
+	let outer_div_class = move || {
+		class.with(|cname| {
+			format!(
+				"flex justify-center items-center text-white gap-xl mt-auto pb-xl {}",
+				cname
+			)
+		})
+	};
+
+	view! {
+		<div class={outer_div_class}>
+			<Link
+				on_click={Rc::new(on_click_prev)}
+				disabled={is_first_page}
+				style_variant={LinkStyleVariant::Contained}
+				r#type={Variant::Button}
+			>
+				<Icon icon={IconType::ChevronLeft} size={Size::ExtraSmall} color={Color::Black} />
+				"Prev"
+			</Link>
+
+			<For
+				each={move || (0..total_pages.get()).collect::<Vec<_>>()}
+				key={|page| *page}
+				let:page
+			>
+				<Link
+					on_click={Rc::new(move |_| current_page.set(page))}
+					class={Signal::derive(move || {
+						if current_page.get() == page {
+							"text-primary".to_string()
+						} else {
+							String::new()
+						}
+					})}
+					style_variant={LinkStyleVariant::Plain}
+					r#type={Variant::Button}
+				>
+					{page + 1}
+				</Link>
+			</For>
+
+			<Link
+				on_click={Rc::new(on_click_next)}
+				disabled={is_last_page}
+				style_variant={LinkStyleVariant::Contained}
+				r#type={Variant::Button}
+			>
+				"Next"
+				<Icon icon={IconType::ChevronRight} size={Size::ExtraSmall} color={Color::Black} />
+			</Link>
+		</div>
+	}
+}
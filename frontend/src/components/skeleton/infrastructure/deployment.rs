@@ -1,49 +1,75 @@
 use crate::imports::*;
 
+/// The skeleton for the deployment card in the deployment list. This has the
+/// same layout as the deployment card, so that the list doesn't shift once
+/// the deployments are loaded.
 #[component]
-pub fn DeploymentSkeletonCard(
+pub fn DeploymentCardSkeleton(
 	/// Additional class names to apply to the outer div, if any
 	#[prop(into, optional)]
 	class: MaybeSignal<String>,
 ) -> impl IntoView {
 	let class = move || {
 		format!(
-			"bg-secondary-light rounded-sm p-lg flex-col items-start justify-start deployment-card {}",
+			"bg-secondary-light rounded-sm p-lg flex flex-col items-start justify-between gap-md deployment-card {}",
 			class.get(),
 		)
 	};
 
 	view! {
 		<div class={class}>
-			<div class="flex justify-start items-center w-full px-xxs">
-				<h4 class="w-1/2">
-					<Skeleton class="full-width skeleton-div-sm"/>
-				</h4>
+			<div class="fr-fs-ct gap-md w-full px-xxs">
+				<Skeleton class="skeleton-text-lg" />
+				<Skeleton class="skeleton-text-sm" />
 			</div>
 
-			<div class="flex items-start justify-start w-full my-auto py-xxs f-wrap">
-
-				{(0..5).collect::<Vec<_>>()
-					.into_iter()
+			<div class="deployment-card-items w-full">
+				// The registry, repository, image tag, machine type and live links
+				{(0..5)
 					.map(|_| {
 						view! {
-							<div class="w-1/2 p-xxs">
-								<Skeleton class="full-width skeleton-div-sm"/>
+							<div class="bg-secondary-medium rounded-sm flex flex-col items-start justify-center gap-xxs">
+								<Skeleton class="skeleton-text-sm h-2" />
+								<Skeleton class="w-[15ch] h-4" />
 							</div>
 						}
 					})
 					.collect_view()}
+			</div>
 
+			<div class="flex justify-between items-center mt-xs w-full px-xxs">
+				<Skeleton class="skeleton-button" />
+				<Skeleton class="skeleton-text-lg" />
 			</div>
+		</div>
+	}
+}
 
-			<div class="flex justify-between items-center w-full px-xxs">
-				<div class="w-1/2">
-					<Skeleton class="skeleton-button"/>
-				</div>
+/// The skeleton for the details of a deployment, shown while the deployment is
+/// being loaded. This has the same layout as the details tab, so that the page
+/// doesn't shift once the deployment is loaded.
+#[component]
+pub fn DeploymentDetailsSkeleton() -> impl IntoView {
+	view! {
+		<div class="flex flex-col items-start justify-start w-full px-xl pb-xl mt-xl text-white gap-md fit-wide-screen mx-auto">
+			{(0..6)
+				.map(|_| {
+					view! {
+						<div class="flex w-full">
+							<div class="flex-2 flex items-start justify-start">
+								<Skeleton class="skeleton-text-md mt-sm" />
+							</div>
+
+							<div class="flex-10 flex flex-col items-start justify-start">
+								<Skeleton class="w-full row-card br-sm" />
+							</div>
+						</div>
+					}
+				})
+				.collect_view()}
 
-				<div class="w-1/2">
-					<Skeleton class="skeleton-text-half-width"/>
-				</div>
+			<div class="flex w-full justify-end">
+				<Skeleton class="skeleton-button" />
 			</div>
 		</div>
 	}
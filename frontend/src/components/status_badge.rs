@@ -16,10 +16,23 @@ pub enum Status {
 	/// Indicates that the component has been stopped
 	#[default]
 	Stopped,
+	/// Indicates that the component is waiting to be scheduled
+	Pending,
+	/// Indicates that the image of the component is being pulled
+	Pulling,
 	/// Indicates that the component is deploying
 	Deploying,
+	/// Indicates that the component is starting up
+	Starting,
 	/// Indicates that the component is running
 	Running,
+	/// Indicates that the component is running, but not all of it is healthy
+	Degraded,
+	/// Indicates that the component is being stopped
+	Stopping,
+	/// Indicates that the component is scaled to zero, and is started by the
+	/// next request it receives
+	Cold,
 	/// Indicates that the component is live
 	Live,
 	/// Indicates that the resource is unreachable
@@ -31,10 +44,16 @@ impl Status {
 	pub const fn from_deployment_status(deployment_status: DeploymentStatus) -> Self {
 		match deployment_status {
 			DeploymentStatus::Created => Self::Created,
+			DeploymentStatus::Pending => Self::Pending,
+			DeploymentStatus::Pulling => Self::Pulling,
 			DeploymentStatus::Deploying => Self::Deploying,
+			DeploymentStatus::Starting => Self::Starting,
 			DeploymentStatus::Errored => Self::Errored,
 			DeploymentStatus::Running => Self::Running,
+			DeploymentStatus::Degraded => Self::Degraded,
+			DeploymentStatus::Stopping => Self::Stopping,
 			DeploymentStatus::Stopped => Self::Stopped,
+			DeploymentStatus::Cold => Self::Cold,
 			DeploymentStatus::Unreachable => Self::Unreachable,
 		}
 	}
@@ -58,7 +77,13 @@ impl Status {
 			Self::Created => "bg-info",
 			Self::Pushed => "bg-info",
 			Self::Stopped => "bg-grey",
+			Self::Stopping => "bg-grey",
+			Self::Cold => "bg-info",
+			Self::Pending => "bg-info",
+			Self::Pulling => "bg-warning",
 			Self::Deploying => "bg-warning",
+			Self::Starting => "bg-warning",
+			Self::Degraded => "bg-warning",
 			Self::Running => "bg-success",
 			Self::Live => "bg-success",
 		}
@@ -73,7 +98,13 @@ impl Status {
 			Self::Created => "created",
 			Self::Pushed => "pushed",
 			Self::Stopped => "stopped",
+			Self::Stopping => "stopping",
+			Self::Cold => "cold",
+			Self::Pending => "pending",
+			Self::Pulling => "pulling",
 			Self::Deploying => "deploying",
+			Self::Starting => "starting",
+			Self::Degraded => "degraded",
 			Self::Running => "running",
 			Self::Live => "live",
 		}
@@ -123,6 +123,47 @@ impl ToasterContext {
 		})
 	}
 
+	/// Add a success toast with the given message to the queue.
+	pub fn success(&self, message: &str) {
+		self.toast(
+			ToastData::builder()
+				.message(message)
+				.level(AlertType::Success),
+		);
+	}
+
+	/// Add an error toast to the queue, with a user-friendly message for the
+	/// given error. Errors stay on screen for longer than other toasts, so
+	/// that they can be read.
+	pub fn error(&self, error: &ServerFnError<ErrorType>) {
+		self.toast(
+			ToastData::builder()
+				.message(&error_message(error))
+				.level(AlertType::Error)
+				.expiry(Some(5_000)),
+		);
+	}
+
+	/// Add a toast for the result of an action to the queue. Failures always
+	/// show an error toast, while successes only show a toast if a message is
+	/// given. Since the toasts are shown globally, a success toast added right
+	/// before navigating is shown on the page that is navigated to, as a
+	/// confirmation of the action.
+	pub fn toast_result<T>(
+		&self,
+		result: &Result<T, ServerFnError<ErrorType>>,
+		success_message: Option<&str>,
+	) {
+		match result {
+			Ok(_) => {
+				if let Some(message) = success_message {
+					self.success(message);
+				}
+			}
+			Err(error) => self.error(error),
+		}
+	}
+
 	/// Clear all toasts.
 	pub fn clear(&self) {
 		for toast in &self.queue.get_untracked() {
@@ -149,6 +190,19 @@ impl ToasterContext {
 	}
 }
 
+/// A user-friendly message for the error of a failed action. Errors returned by
+/// the API are shown with the message of their [`ErrorType`], and any other
+/// error (such as the API not being reachable) with a generic message. Errors
+/// sent with a message of their own (such as the usage of an exceeded quota,
+/// like "You are using 10/10 deployments") are shown with that message.
+pub fn error_message(error: &ServerFnError<ErrorType>) -> String {
+	match error {
+		ServerFnError::WrappedServerError(error) => error.message().into(),
+		ServerFnError::ServerError(message) => message.clone(),
+		_ => "Something went wrong. Please check your connection and try again".to_string(),
+	}
+}
+
 /// Provide the Toaster Context
 pub fn provide_toaster() {
 	if use_context::<ToasterContext>().is_none() {
@@ -27,6 +27,7 @@ pub mod prelude {
 			number_picker::*,
 			otp_input::*,
 			page_title::*,
+			pagination::*,
 			popover::*,
 			sidebar::*,
 			skeleton::*,
@@ -1,40 +1,95 @@
 use ev::SubmitEvent;
-use models::api::auth::LoginResponse;
+use models::api::auth::{LoginResponseType, VerifyMfaLoginResponse};
+use serde::{Deserialize, Serialize};
 
 use crate::prelude::*;
 
+/// The outcome of logging in with a username and password
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LoginOutcome {
+	/// The user is logged in
+	LoggedIn(AuthState),
+	/// The user has MFA enabled, and the login has to be completed by
+	/// verifying an OTP (or a recovery code) along with the given token
+	MfaRequired {
+		/// The token that proves that the password of the user was verified
+		mfa_token: String,
+	},
+}
+
 /// The API endpoint for logging in to the application. This endpoint is used to
-/// authenticate the user and get the JWT tokens for the user.
+/// authenticate the user and get the JWT tokens for the user. If the user has
+/// MFA enabled, the login has to be completed using [`verify_mfa_login`].
 #[server(LoginApi, endpoint = "auth/sign-in")]
 pub async fn login(
 	user_id: String,
 	password: String,
-	mfa_otp: Option<String>,
-) -> Result<AuthState, ServerFnError<ErrorType>> {
-	use std::str::FromStr;
+) -> Result<LoginOutcome, ServerFnError<ErrorType>> {
+	use models::api::auth::*;
 
-	use models::api::{auth::*, user::*};
-
-	let LoginResponse {
-		access_token,
-		refresh_token,
-	} = make_api_call::<LoginRequest>(
+	let response = make_api_call::<LoginRequest>(
 		ApiRequest::builder()
 			.path(LoginPath)
 			.query(())
 			.headers(LoginRequestHeaders {
 				user_agent: UserAgent::from_static("hyper/0.12.2"),
 			})
-			.body(LoginRequest {
-				user_id,
-				password,
-				mfa_otp,
+			.body(LoginRequest { user_id, password })
+			.build(),
+	)
+	.await?
+	.body
+	.response;
+
+	match response {
+		LoginResponseType::LoggedIn {
+			access_token,
+			refresh_token,
+		} => Ok(LoginOutcome::LoggedIn(
+			load_auth_state(access_token, refresh_token).await?,
+		)),
+		LoginResponseType::MfaRequired { mfa_token } => Ok(LoginOutcome::MfaRequired { mfa_token }),
+	}
+}
+
+/// The API endpoint for completing the login of a user that has MFA enabled,
+/// using the token returned by [`login`] and an OTP or a recovery code.
+#[server(VerifyMfaLoginApi, endpoint = "auth/sign-in/mfa")]
+pub async fn verify_mfa_login(
+	mfa_token: String,
+	code: String,
+) -> Result<AuthState, ServerFnError<ErrorType>> {
+	use models::api::auth::*;
+
+	let VerifyMfaLoginResponse {
+		access_token,
+		refresh_token,
+	} = make_api_call::<VerifyMfaLoginRequest>(
+		ApiRequest::builder()
+			.path(VerifyMfaLoginPath)
+			.query(())
+			.headers(VerifyMfaLoginRequestHeaders {
+				user_agent: UserAgent::from_static("hyper/0.12.2"),
 			})
+			.body(VerifyMfaLoginRequest { mfa_token, code })
 			.build(),
 	)
 	.await?
 	.body;
 
+	load_auth_state(access_token, refresh_token).await
+}
+
+/// Creates the [`AuthState`] for a new login, using the last used workspace of
+/// the user
+async fn load_auth_state(
+	access_token: String,
+	refresh_token: String,
+) -> Result<AuthState, ServerFnError<ErrorType>> {
+	use std::str::FromStr;
+
+	use models::api::user::*;
+
 	let workspaces = make_api_call::<ListUserWorkspacesRequest>(
 		ApiRequest::builder()
 			.path(ListUserWorkspacesPath)
@@ -85,6 +140,10 @@ pub fn LoginForm(
 	let username_error = create_rw_signal("".to_owned());
 	let password_error = create_rw_signal("".to_owned());
 
+	let mfa_token = create_rw_signal(None::<String>);
+	let mfa_code = create_rw_signal("".to_owned());
+	let mfa_code_error = create_rw_signal("".to_owned());
+
 	let loading = create_rw_signal(false);
 
 	let on_submit_login = move |ev: SubmitEvent| {
@@ -92,6 +151,43 @@ pub fn LoginForm(
 		loading.set(true);
 		username_error.set("".to_string());
 		password_error.set("".to_string());
+		mfa_code_error.set("".to_string());
+
+		if let Some(token) = mfa_token.get() {
+			if mfa_code.get().is_empty() {
+				mfa_code_error.set("Code cannot be empty".to_owned());
+				loading.set(false);
+				return;
+			}
+
+			let next = next.clone();
+
+			spawn_local(async move {
+				match verify_mfa_login(token, mfa_code.get_untracked()).await {
+					Ok(auth_state) => {
+						set_state.set(Some(auth_state));
+						use_navigate()(
+							&next_path_or(next, DeploymentsDashboardRoute {}),
+							NavigateOptions::default(),
+						);
+					}
+					Err(ServerFnError::WrappedServerError(ErrorType::MfaLoginTokenInvalid)) => {
+						mfa_token.set(None);
+						mfa_code.set("".to_owned());
+						password_error.set(ErrorType::MfaLoginTokenInvalid.message().into());
+					}
+					Err(ServerFnError::WrappedServerError(ErrorType::MfaOtpInvalid)) => {
+						mfa_code_error.set("Invalid code".to_owned());
+					}
+					Err(err) => {
+						mfa_code_error.set(err.to_string());
+					}
+				}
+
+				loading.set(false);
+			});
+			return;
+		}
 
 		if username.get().is_empty() {
 			username_error.set("Username / email cannot be empty".to_owned());
@@ -108,14 +204,17 @@ pub fn LoginForm(
 		let next = next.clone();
 
 		spawn_local(async move {
-			match login(username.get_untracked(), password.get_untracked(), None).await {
-				Ok(auth_state) => {
+			match login(username.get_untracked(), password.get_untracked()).await {
+				Ok(LoginOutcome::LoggedIn(auth_state)) => {
 					set_state.set(Some(auth_state));
 					use_navigate()(
-						&next.unwrap_or_else(|| DeploymentsDashboardRoute {}.to_string()),
+						&next_path_or(next, DeploymentsDashboardRoute {}),
 						NavigateOptions::default(),
 					);
 				}
+				Ok(LoginOutcome::MfaRequired { mfa_token: token }) => {
+					mfa_token.set(Some(token));
+				}
 				Err(ServerFnError::WrappedServerError(ErrorType::UserNotFound)) => {
 					username_error.set("User Not Found".to_owned());
 					password_error.set("".to_owned());
@@ -192,7 +291,35 @@ pub fn LoginForm(
 					value={password}
 				/>
 
-				<input name="mfa_otp" type="hidden" />
+				{move || mfa_token
+					.get()
+					.is_some()
+					.then(|| view! {
+						<Input
+							name="mfa_code"
+							class="w-full"
+							id="mfa_code"
+							r#type={InputType::Text}
+							placeholder="Authenticator Code / Recovery Code"
+							start_icon={Some(
+								IconProps::builder().icon(IconType::Shield).size(Size::ExtraSmall).build(),
+							)}
+							disabled={loading}
+							on_input={Box::new(move |ev| {
+								mfa_code.set(event_target_value(&ev));
+							})}
+							value={mfa_code}
+						/>
+					})}
+
+				{move || mfa_code_error
+					.get()
+					.some_if_not_empty()
+					.map(|message| view! {
+						<Alert r#type={AlertType::Error} class="mt-xs">
+							{&message}
+						</Alert>
+					})}
 
 				{move || password_error
 					.get()
@@ -1,18 +1,54 @@
+use std::rc::Rc;
+
 use ev::SubmitEvent;
 use leptos_use::{signal_debounced_with_options, utils::DebounceOptions};
 use models::api::auth::*;
 
 use crate::prelude::*;
 
-/// Server Function to sign up a new user
+/// Server Function to list the recovery methods that this instance supports
+/// for new accounts, from the capabilities of the instance
+#[server(GetInstanceCapabilities, endpoint = "auth/instance-capabilities")]
+pub async fn list_supported_recovery_methods(
+) -> Result<Vec<RecoveryMethodType>, ServerFnError<ErrorType>> {
+	Ok(make_api_call::<GetInstanceCapabilitiesRequest>(
+		ApiRequest::builder()
+			.path(GetInstanceCapabilitiesPath)
+			.query(())
+			.headers(GetInstanceCapabilitiesRequestHeaders {
+				user_agent: UserAgent::from_static("hyper/0.12.2"),
+			})
+			.body(GetInstanceCapabilitiesRequest)
+			.build(),
+	)
+	.await?
+	.body
+	.recovery_methods)
+}
+
+/// Server Function to sign up a new user. The recovery contact is the email
+/// or the phone number of the user, depending on the recovery method chosen.
+/// The country code is only used for phone numbers
 #[server(CreateAccount, endpoint = "auth/sign-up")]
 pub async fn sign_up(
 	first_name: String,
 	last_name: String,
-	email: String,
 	username: String,
 	password: String,
+	recovery_method_type: RecoveryMethodType,
+	recovery_contact: String,
+	phone_country_code: String,
 ) -> Result<(), ServerFnError<ErrorType>> {
+	let recovery_method = match recovery_method_type {
+		RecoveryMethodType::Email => RecoveryMethod::Email {
+			recovery_email: recovery_contact,
+		},
+		RecoveryMethodType::PhoneNumber => RecoveryMethod::PhoneNumber {
+			recovery_phone_country_code: phone_country_code,
+			recovery_phone_number: recovery_contact,
+		},
+	};
+
 	make_api_call::<CreateAccountRequest>(
 		ApiRequest::builder()
 			.path(CreateAccountPath)
@@ -25,9 +61,7 @@ pub async fn sign_up(
 				password,
 				first_name,
 				last_name,
-				recovery_method: RecoveryMethod::Email {
-					recovery_email: email,
-				},
+				recovery_method,
 			})
 			.build(),
 	)
@@ -62,8 +96,31 @@ pub fn SignUpForm(
 
 	let last_name = create_rw_signal(last_name.unwrap_or_else(|| "".to_owned()));
 
+	let supported_recovery_methods =
+		create_resource(|| (), move |_| list_supported_recovery_methods());
+	let recovery_method_type = create_rw_signal(RecoveryMethodType::Email);
+	// Only offer the recovery methods that the instance can send messages to
+	create_effect(move |_| {
+		if let Some(Ok(methods)) = supported_recovery_methods.get() {
+			if !methods.contains(&recovery_method_type.get_untracked()) {
+				if let Some(method) = methods.first() {
+					recovery_method_type.set(*method);
+				}
+			}
+		}
+	});
+	let is_recovery_method_supported = move |method_type: RecoveryMethodType| {
+		supported_recovery_methods.with(|methods| {
+			matches!(methods, Some(Ok(methods)) if methods.contains(&method_type))
+		})
+	};
+
 	let email = create_rw_signal(email.unwrap_or_else(|| "".to_owned()));
 	let email_error = create_rw_signal("".to_owned());
+
+	let phone_country_code = create_rw_signal("".to_owned());
+	let phone_number = create_rw_signal("".to_owned());
+	let phone_number_error = create_rw_signal("".to_owned());
 	// let email_checking = create_resource(
 	// 	move || {
 	// 		signal_debounced_with_options(
@@ -161,6 +218,7 @@ pub fn SignUpForm(
 		loading.set(true);
 
 		email_error.set("".to_string());
+		phone_number_error.set("".to_string());
 		username_error.set("".to_string());
 		password_error.set("".to_string());
 		password_confirm_error.set("".to_string());
@@ -170,10 +228,20 @@ pub fn SignUpForm(
 			return;
 		}
 
-		if email.get().is_empty() {
-			email_error.set("Email cannot be empty".to_string());
-			loading.set(false);
-			return;
+		match recovery_method_type.get() {
+			RecoveryMethodType::Email if email.get().is_empty() => {
+				email_error.set("Email cannot be empty".to_string());
+				loading.set(false);
+				return;
+			}
+			RecoveryMethodType::PhoneNumber
+				if phone_country_code.get().is_empty() || phone_number.get().is_empty() =>
+			{
+				phone_number_error.set("Phone number cannot be empty".to_string());
+				loading.set(false);
+				return;
+			}
+			_ => (),
 		}
 
 		if username.get().is_empty() {
@@ -197,12 +265,19 @@ pub fn SignUpForm(
 		let next = next.clone();
 
 		spawn_local(async move {
+			let recovery_method_type = recovery_method_type.get_untracked();
+			let recovery_contact = match recovery_method_type {
+				RecoveryMethodType::Email => email.get_untracked(),
+				RecoveryMethodType::PhoneNumber => phone_number.get_untracked(),
+			};
 			match sign_up(
 				first_name.get_untracked(),
 				last_name.get_untracked(),
-				email.get_untracked(),
 				username.get_untracked(),
 				password.get_untracked(),
+				recovery_method_type,
+				recovery_contact,
+				phone_country_code.get_untracked(),
 			)
 			.await
 			{
@@ -226,6 +301,23 @@ pub fn SignUpForm(
 				Err(ServerFnError::WrappedServerError(ErrorType::EmailUnavailable)) => {
 					email_error.set("Email not available".to_owned());
 				}
+				Err(ServerFnError::WrappedServerError(ErrorType::InvalidEmail)) => {
+					email_error.set("Invalid email".to_owned());
+				}
+				Err(ServerFnError::WrappedServerError(ErrorType::PhoneUnavailable)) => {
+					phone_number_error.set("Phone number not available".to_owned());
+				}
+				Err(ServerFnError::WrappedServerError(ErrorType::InvalidPhoneNumber)) => {
+					phone_number_error.set("Invalid phone number".to_owned());
+				}
+				Err(ServerFnError::WrappedServerError(
+					error @ ErrorType::RecoveryMethodUnavailable,
+				)) => match recovery_method_type {
+					RecoveryMethodType::Email => email_error.set(error.message().into()),
+					RecoveryMethodType::PhoneNumber => {
+						phone_number_error.set(error.message().into())
+					}
+				},
 				Err(e) => {
 					password_error.set(e.to_string());
 				}
@@ -310,23 +402,97 @@ pub fn SignUpForm(
 					</Alert>
 				</Show>
 
-				<Input
-					class="w-full mt-lg"
-					r#type={InputType::Email}
-					name="email"
-					id="email"
-					placeholder="proton@gmail.com"
-					start_icon={Some(IconProps::builder().icon(IconType::Mail).build())}
-					value={email}
-					on_input={Box::new(move |ev| { email.set(event_target_value(&ev)) })}
-				/>
+				<Show
+					when={move || recovery_method_type.get() == RecoveryMethodType::PhoneNumber}
+					fallback={move || {
+						view! {
+							<Input
+								class="w-full mt-lg"
+								r#type={InputType::Email}
+								name="email"
+								id="email"
+								placeholder="proton@gmail.com"
+								start_icon={Some(IconProps::builder().icon(IconType::Mail).build())}
+								value={email}
+								on_input={Box::new(move |ev| { email.set(event_target_value(&ev)) })}
+							/>
+
+							<Show when={move || !email_error.get().is_empty()}>
+								<Alert r#type={AlertType::Error} class="mt-xs">
+									{move || email_error.get()}
+								</Alert>
+							</Show>
+						}
+					}}
+				>
+					<div class="flex justify-center items-start w-full mt-lg">
+						<div class="flex flex-col items-start justify-start flex-col-3 pr-xxs">
+							<Input
+								class="py-xs"
+								r#type={InputType::Text}
+								id="phone_country_code"
+								name="phone_country_code"
+								placeholder="US"
+								value={phone_country_code}
+								on_input={Box::new(move |ev| {
+									phone_country_code.set(event_target_value(&ev).to_uppercase())
+								})}
+							/>
+						</div>
+
+						<div class="flex flex-col items-start justify-start flex-col-9 pl-xxs">
+							<Input
+								class="py-xs"
+								r#type={InputType::Phone}
+								id="phone_number"
+								name="phone_number"
+								placeholder="Phone Number"
+								value={phone_number}
+								on_input={Box::new(move |ev| {
+									phone_number.set(event_target_value(&ev))
+								})}
+							/>
+						</div>
+					</div>
 
-				<Show when={move || !email_error.get().is_empty()}>
-					<Alert r#type={AlertType::Error} class="mt-xs">
-						{move || email_error.get()}
-					</Alert>
+					<Show when={move || !phone_number_error.get().is_empty()}>
+						<Alert r#type={AlertType::Error} class="mt-xs">
+							{move || phone_number_error.get()}
+						</Alert>
+					</Show>
 				</Show>
 
+				<Transition>
+					<Show when={move || {
+						is_recovery_method_supported(RecoveryMethodType::Email) &&
+							is_recovery_method_supported(RecoveryMethodType::PhoneNumber)
+					}}>
+						<Link
+							class="mt-xs"
+							r#type={Variant::Button}
+							style_variant={LinkStyleVariant::Plain}
+							on_click={Rc::new(move |_| {
+								recovery_method_type
+									.update(|method_type| {
+										*method_type = match method_type {
+											RecoveryMethodType::Email => {
+												RecoveryMethodType::PhoneNumber
+											}
+											RecoveryMethodType::PhoneNumber => {
+												RecoveryMethodType::Email
+											}
+										};
+									})
+							})}
+						>
+							{move || match recovery_method_type.get() {
+								RecoveryMethodType::Email => "USE PHONE NUMBER INSTEAD",
+								RecoveryMethodType::PhoneNumber => "USE EMAIL INSTEAD",
+							}}
+						</Link>
+					</Show>
+				</Transition>
+
 				<Input
 					r#type={InputType::Password}
 					id="password"
@@ -39,6 +39,7 @@ pub fn UpdateManagedUrl(
 				ManagedUrlType::ProxyStaticSite { static_site_id } => static_site_id.to_string(),
 				ManagedUrlType::Redirect { url, .. } => url.clone(),
 				ManagedUrlType::ProxyUrl { url, .. } => url.clone(),
+				ManagedUrlType::PathMap { .. } => String::new(),
 			}),
 		);
 	let port = create_rw_signal::<u16>(store_managed_url.with_value(|val| {
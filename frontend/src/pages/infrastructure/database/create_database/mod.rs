@@ -6,7 +6,7 @@ use models::api::workspace::database::DatabaseEngine;
 use strum::VariantNames;
 
 pub use self::head::*;
-use crate::{pages::DatabaseTypeCard, prelude::*};
+use crate::{pages::DatabaseTypeCard, prelude::*, queries::list_all_runners_query};
 
 #[derive(Clone, Debug)]
 pub struct DatabaseInfo {
@@ -26,12 +26,7 @@ pub fn CreateDatabase() -> impl IntoView {
 		database_type: None,
 	});
 
-	let runner_list = create_resource(
-		move || (access_token.get(), current_workspace_id.get()),
-		move |(access_token, workspace_id)| async move {
-			list_runners(access_token, workspace_id.unwrap()).await
-		},
-	);
+	let runner_list = list_all_runners_query();
 
 	let name_error = create_rw_signal("".to_string());
 	let db_type_error = create_rw_signal("".to_string());
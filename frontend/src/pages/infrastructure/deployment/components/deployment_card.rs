@@ -45,7 +45,7 @@ pub fn DeploymentCard(
 			store_deployment.with_value(move |deployment| deployment.get().id.clone());
 
 		match status {
-			DeploymentStatus::Running => {
+			status if status.is_running() || status == DeploymentStatus::Cold => {
 				stop_deployment_action.dispatch(deployment_id);
 			}
 			DeploymentStatus::Created | DeploymentStatus::Stopped => {
@@ -180,9 +180,10 @@ pub fn DeploymentCard(
 				<Link
 					disabled={store_deployment
 						.with_value(move |deployment| {
-							deployment.get().status.clone() == DeploymentStatus::Deploying
-								|| deployment.get().status.clone() == DeploymentStatus::Errored
-								|| deployment.get().status.clone() == DeploymentStatus::Unreachable
+							let status = deployment.get().status;
+							status.is_transitioning()
+								|| status == DeploymentStatus::Errored
+								|| status == DeploymentStatus::Unreachable
 						})}
 					style_variant={LinkStyleVariant::Contained}
 				>
@@ -190,7 +191,7 @@ pub fn DeploymentCard(
 						let deployment = store_deployment
 							.with_value(move |deployment| deployment.get());
 						match deployment.status.clone() {
-							DeploymentStatus::Running => {
+							status if status.is_running() || status == DeploymentStatus::Cold => {
 								view! {
 									<Icon
 										icon={IconType::PauseCircle}
@@ -1,7 +1,7 @@
 use std::{collections::BTreeMap, rc::Rc};
 
 use ev::MouseEvent;
-use models::api::workspace::deployment::EnvironmentVariableValue;
+use models::{api::workspace::deployment::EnvironmentVariableValue, utils::DotEnv};
 
 use crate::prelude::*;
 
@@ -19,6 +19,9 @@ pub fn EnvInput(
 	/// On Pressing Add Button
 	#[prop(into, optional, default = Callback::new(|_| ()))]
 	on_add: Callback<(String, String)>,
+	/// On Importing Variables From A Pasted `.env` File
+	#[prop(into, optional, default = Callback::new(|_| ()))]
+	on_import: Callback<BTreeMap<String, EnvironmentVariableValue>>,
 ) -> impl IntoView {
 	let outer_div_class = class.with(|cname| format!("flex full-width {}", cname));
 	let store_envs = store_value(envs_list.clone());
@@ -26,6 +29,69 @@ pub fn EnvInput(
 	let env_name = create_rw_signal("".to_string());
 	let env_value = create_rw_signal("".to_string());
 
+	let show_import = create_rw_signal(false);
+	let dotenv_content = create_rw_signal("".to_string());
+	let import_errors = create_rw_signal(Vec::<String>::new());
+	let import_warnings = create_rw_signal(Vec::<String>::new());
+
+	let import_dotenv = move || {
+		let DotEnv {
+			mut variables,
+			defined_on,
+			errors,
+			duplicates,
+		} = dotenv_content.with(|content| DotEnv::parse(content));
+
+		// Variables that are already set (secrets included) are never
+		// replaced by an import, so that pasting a file can't overwrite them
+		// by accident
+		let existing = envs_list.with(|envs| {
+			variables
+				.keys()
+				.filter(|name| envs.contains_key(*name))
+				.cloned()
+				.collect::<Vec<_>>()
+		});
+		for name in &existing {
+			variables.remove(name);
+		}
+
+		import_warnings.set(
+			duplicates
+				.into_iter()
+				.map(|duplicate| {
+					format!(
+						"line {}: `{}` is already defined on line {}. The last value is used",
+						duplicate.line, duplicate.key, duplicate.previous_line
+					)
+				})
+				.chain(existing.into_iter().map(|name| {
+					format!(
+						"line {}: `{}` is already set. Delete it first to import it",
+						defined_on.get(&name).copied().unwrap_or_default(),
+						name
+					)
+				}))
+				.collect(),
+		);
+		// The pasted content is kept if any of the lines are invalid, so that
+		// they can be fixed and imported again
+		if errors.is_empty() {
+			dotenv_content.set("".to_string());
+			show_import.set(false);
+		}
+		import_errors.set(errors.iter().map(ToString::to_string).collect());
+
+		if !variables.is_empty() {
+			on_import.call(
+				variables
+					.into_iter()
+					.map(|(name, value)| (name, EnvironmentVariableValue::String(value)))
+					.collect(),
+			);
+		}
+	};
+
 	view! {
 		<div class={outer_div_class}>
 			<div class="flex-col-2 fr-fs-ct mb-auto mt-md">
@@ -118,6 +184,66 @@ pub fn EnvInput(
 						</Link>
 					</div>
 				</form>
+
+				<Show
+					when={move || show_import.get()}
+					fallback={move || {
+						view! {
+							<button
+								class="text-sm text-primary mt-xs"
+								on:click={move |_| show_import.set(true)}
+							>
+								"Import from a .env file"
+							</button>
+						}
+					}}
+				>
+					<div class="flex w-full mt-xs">
+						<div class="flex-col-11 fc-fs-fs">
+							<textarea
+								id="envImport"
+								rows=6
+								class="w-full px-xl py-sm br-sm bg-secondary-light text-white"
+								placeholder="Paste the contents of a .env file, e.g. KEY=VALUE"
+								prop:value={move || dotenv_content.get()}
+								on:input={move |ev| dotenv_content.set(event_target_value(&ev))}
+							/>
+						</div>
+
+						<div class="flex-col-1 fr-ct-fs">
+							<Link
+								style_variant={LinkStyleVariant::Contained}
+								class="br-sm p-xs ml-md"
+								on_click={Rc::new(move |ev| {
+									ev.prevent_default();
+									import_dotenv()
+								})}
+							>
+								<Icon icon={IconType::Plus} color={Color::Secondary} />
+							</Link>
+						</div>
+					</div>
+				</Show>
+
+				<For
+					each={move || import_errors.get()}
+					key={|error| error.clone()}
+					let:error
+				>
+					<Alert r#type={AlertType::Error} class="mt-xs">
+						{error.clone()}
+					</Alert>
+				</For>
+
+				<For
+					each={move || import_warnings.get()}
+					key={|warning| warning.clone()}
+					let:warning
+				>
+					<Alert r#type={AlertType::Warning} class="mt-xs">
+						{warning.clone()}
+					</Alert>
+				</For>
 			</div>
 		</div>
 	}
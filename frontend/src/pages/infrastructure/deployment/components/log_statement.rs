@@ -42,7 +42,32 @@ pub fn LogStatement(
 				Err(_) => view! {}.into_view(),
 			}}
 			" - "
-			<span class="px-sm">{store_log.with_value(|log| log.get().log.clone())}</span>
+			{move || {
+				store_log
+					.with_value(|log| log.get().parsed)
+					.and_then(|parsed| parsed.level)
+					.map(|level| {
+						let color = level_color(&level);
+						view! { <span class={format!("text-xxs pl-sm text-{color}")}>{level}</span> }
+					})
+			}}
+			<span class="px-sm">
+				{store_log
+					.with_value(|log| {
+						let log = log.get();
+						log.parsed.and_then(|parsed| parsed.message).unwrap_or(log.log)
+					})}
+			</span>
 		</div>
 	}
 }
+
+/// The color that a log level is shown in. Unknown levels are shown in grey
+fn level_color(level: &str) -> Color {
+	match level.to_ascii_lowercase().as_str() {
+		"error" | "err" | "fatal" | "critical" | "panic" => Color::Error,
+		"warn" | "warning" => Color::Warning,
+		"info" | "notice" => Color::Info,
+		_ => Color::Grey,
+	}
+}
@@ -23,6 +23,9 @@ pub fn PortInput(
 	/// On Pressing Add Button
 	#[prop(into, optional, default = Callback::new(|_| ()))]
 	on_add: Callback<(String, String)>,
+	/// On Pressing Test Connection Button. Only shown on the update screen
+	#[prop(into, optional, default = Callback::new(|_| ()))]
+	on_test: Callback<String>,
 	/// The Error For Port Input
 	#[prop(into, optional)]
 	error: MaybeSignal<String>,
@@ -109,6 +112,21 @@ pub fn PortInput(
 											}
 										})}
 
+									{is_update_screen
+										.get()
+										.then(|| {
+											view! {
+												<div class="flex-2 flex items-center justify-center pl-sm">
+													<button
+														class="btn btn-plain text-xs"
+														on:click={move |_| on_test.call(child.0.to_string())}
+													>
+														"TEST CONNECTION"
+													</button>
+												</div>
+											}
+										})}
+
 									<div class="flex-1 flex items-center justify-center pl-sm">
 										<button on:click={move |ev| {
 											on_delete.call(child.0.to_string())
@@ -1,10 +1,44 @@
 use super::{DeploymentInfo, DetailsPageError};
-use crate::{prelude::*, queries::list_runners_query};
+use crate::{
+	prelude::*,
+	queries::{list_all_runners_query, list_deployment_templates_query},
+};
+
+#[component]
+fn TemplateDropdown() -> impl IntoView {
+	let deployment_info = expect_context::<RwSignal<DeploymentInfo>>();
+	let templates_list = list_deployment_templates_query(Signal::derive(|| 0));
+
+	view! {
+		<InputDropdown
+			placeholder="No Template"
+			class="w-full"
+			value={deployment_info
+				.with(|info| info.template_id.map(|id| id.to_string()).unwrap_or_default())}
+			on_select={move |id: String| {
+				deployment_info.update(|info| info.template_id = Uuid::parse_str(id.as_str()).ok())
+			}}
+			options={Signal::derive(move || match templates_list.get() {
+				Some(Ok((_, data))) => {
+					data.templates
+						.iter()
+						.map(|x| InputDropdownOption {
+							id: x.id.to_string(),
+							disabled: false,
+							label: x.name.clone(),
+						})
+						.collect::<Vec<_>>()
+				}
+				_ => vec![],
+			})}
+		/>
+	}
+}
 
 #[component]
 fn RunnerDropdown() -> impl IntoView {
 	let deployment_info = expect_context::<RwSignal<DeploymentInfo>>();
-	let runners_list = list_runners_query();
+	let runners_list = list_all_runners_query();
 
 	view! {
 		<InputDropdown
@@ -231,6 +265,26 @@ pub fn DeploymentDetails(
 						}
 							.into_view()
 					})}
+				{app_type
+					.is_managed()
+					.then(|| {
+						view! {
+							<div class="flex my-xs w-full mb-md">
+								<div class="flex-2 flex justify-start items-center">
+									<label class="text-white text-sm flex justify-start items-center">
+										"Template"
+									</label>
+								</div>
+
+								<div class="flex-10 flex flex-col items-start justify-start">
+									<Transition>
+										<TemplateDropdown />
+									</Transition>
+								</div>
+							</div>
+						}
+							.into_view()
+					})}
 			</div>
 		</div>
 	}
@@ -26,6 +26,7 @@ pub fn CreateDeployment() -> impl IntoView {
 			AppType::SelfHosted => Some(Uuid::nil()),
 		},
 		machine_type: None,
+		template_id: None,
 		deploy_on_create: false,
 		deploy_on_push: false,
 		min_horizontal_scale: None,
@@ -100,7 +101,8 @@ pub fn CreateDeployment() -> impl IntoView {
 				}
 			}
 			Page::Scaling => {
-				if deployment_info.machine_type.is_none() {
+				// The machine type can be filled in from the template
+				if deployment_info.machine_type.is_none() && deployment_info.template_id.is_none() {
 					scale_page_error.update(|errors| {
 						errors.machine_type = "Please select a Machine Type".to_string()
 					});
@@ -1,4 +1,4 @@
-use std::str::FromStr;
+use std::{collections::BTreeMap, str::FromStr};
 
 use ev::MouseEvent;
 use models::api::workspace::deployment::{EnvironmentVariableValue, ExposedPortType};
@@ -60,6 +60,14 @@ pub fn RunningDetails(
 								info.environment_variables.remove(name.as_str());
 							});
 					}}
+					on_import={move |variables: BTreeMap<String, EnvironmentVariableValue>| {
+						deployment_info
+							.update(|info| {
+								for (name, value) in variables {
+									info.environment_variables.entry(name).or_insert(value);
+								}
+							});
+					}}
 					envs_list={Signal::derive(move || deployment_info.get().environment_variables)}
 				/>
 
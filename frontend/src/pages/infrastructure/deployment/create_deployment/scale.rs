@@ -1,5 +1,9 @@
 use super::super::components::*;
-use crate::{pages::DeploymentInfo, prelude::*, queries::list_machines_query};
+use crate::{
+	pages::DeploymentInfo,
+	prelude::*,
+	queries::{get_default_machine_type_query, get_workspace_query, list_machines_query},
+};
 
 /// A component that allows the user to scale their deployment
 #[component]
@@ -9,7 +13,37 @@ pub fn ScaleDeployment() -> impl IntoView {
 
 	let deployment_info = expect_context::<RwSignal<DeploymentInfo>>();
 
+	let (state, _) = AuthState::load();
+	let workspace = get_workspace_query(Signal::derive(move || {
+		state.get().get_last_used_workspace_id().unwrap_or_default()
+	}));
+	let max_replicas = Signal::derive(move || {
+		workspace
+			.get()
+			.and_then(Result::ok)
+			.map(|response| response.max_replicas)
+	});
+
+	// Keep the chosen scale within the limit of the workspace, once it's known
+	create_effect(move |_| {
+		if let Some(max_replicas) = max_replicas.get() {
+			max_horizontal.update(|value| *value = (*value).min(max_replicas));
+			min_horizontal.update(|value| *value = (*value).min(max_replicas));
+		}
+	});
+
 	let machine_list = list_machines_query();
+	let default_machine_type = get_default_machine_type_query();
+
+	// Pre-select the default machine type of the workspace, if it has one and
+	// the user hasn't chosen a machine type yet
+	create_effect(move |_| {
+		if let Some(Ok(response)) = default_machine_type.get() {
+			deployment_info.update(|info| {
+				info.machine_type = info.machine_type.or(response.machine_type_id);
+			});
+		}
+	});
 
 	view! {
 		<div class="fc-fs-fs w-full px-xl mt-xl text-white text-sm fit-wide-screen mx-auto gap-md">
@@ -24,6 +58,19 @@ pub fn ScaleDeployment() -> impl IntoView {
 					<p class="w-full tracking-[1px] text-xxs">
 						"Choose the minimum and maximum number of instances for your deployment "
 					</p>
+					{move || {
+						max_replicas
+							.get()
+							.map(|max_replicas| {
+								view! {
+									<p class="w-full text-grey text-xxs">
+										{format!(
+											"Deployments in this workspace can be scaled to at most {max_replicas} instances",
+										)}
+									</p>
+								}
+							})
+					}}
 
 					<div class="flex flex-col justify-start items-start gap-xl">
 						<div
@@ -36,6 +83,7 @@ pub fn ScaleDeployment() -> impl IntoView {
 
 							<NumberPicker
 								value={min_horizontal}
+								max={Signal::derive(move || max_replicas.get().unwrap_or(10))}
 								style_variant={SecondaryColorVariant::Medium}
 								on_change={move |_| {
 									deployment_info
@@ -56,6 +104,7 @@ pub fn ScaleDeployment() -> impl IntoView {
 
 							<NumberPicker
 								value={max_horizontal}
+								max={Signal::derive(move || max_replicas.get().unwrap_or(10))}
 								style_variant={SecondaryColorVariant::Medium}
 								on_change={move |_| {
 									deployment_info
@@ -1,9 +1,12 @@
-mod footer;
 mod head;
 
+use std::collections::BTreeMap;
+
 use convert_case::*;
+use leptos_use::use_interval_fn;
+use models::api::workspace::deployment::{BatchGetDeploymentStatusResponse, DeploymentStatus};
 
-use self::{footer::*, head::*};
+use self::head::*;
 use super::{components::*, utils::*};
 use crate::{prelude::*, queries::list_deployments_query};
 
@@ -29,7 +32,7 @@ fn LoadingDeployments() -> impl IntoView {
 				key={|state| state.clone()}
 				let:_
 			>
-				<DeploymentSkeletonCard />
+				<DeploymentCardSkeleton />
 			</For>
 		</ContainerGrid>
 	}
@@ -48,13 +51,58 @@ pub fn DeploymentDashboard() -> impl IntoView {
 		);
 	});
 
-	let deployment_list = list_deployments_query(deployment_page.into());
+	let deployment_list = list_deployments_query(deployment_page.into(), Signal::derive(|| None));
 
 	let total_count = Signal::derive(move || match deployment_list.get() {
 		Some(Ok((count, _))) => count,
 		_ => 0,
 	});
 
+	// Only the statuses of the deployments on the current page are polled, in
+	// a single request, so that the cards stay fresh without fetching the
+	// whole list again. The polled statuses are dropped whenever the list is
+	// fetched again, since the list has the latest ones then
+	let deployment_statuses = create_rw_signal(BTreeMap::<Uuid, DeploymentStatus>::new());
+	create_effect(move |_| {
+		deployment_list.with(|_| ());
+		deployment_statuses.set(BTreeMap::new());
+	});
+	use_interval_fn(
+		move || {
+			let Some(Ok((_, data))) = deployment_list.get_untracked() else {
+				return;
+			};
+			let deployment_ids = data
+				.deployments
+				.iter()
+				.map(|deployment| deployment.id)
+				.collect::<Vec<_>>();
+			let access_token = state.get_untracked().get_access_token();
+			let Some(workspace_id) = state.get_untracked().get_last_used_workspace_id() else {
+				return;
+			};
+			if deployment_ids.is_empty() {
+				return;
+			}
+
+			spawn_local(async move {
+				let Ok(BatchGetDeploymentStatusResponse { statuses }) =
+					batch_get_deployment_status(access_token, workspace_id, deployment_ids).await
+				else {
+					return;
+				};
+				deployment_statuses.update(|deployment_statuses| {
+					deployment_statuses.extend(
+						statuses
+							.into_iter()
+							.filter_map(|result| Some((result.deployment_id, result.status?))),
+					);
+				});
+			});
+		},
+		constants::DEPLOYMENT_STATUS_POLL_INTERVAL_MILLIS,
+	);
+
 	view! {
 		<DeploymentDashboardHead />
 
@@ -76,7 +124,15 @@ pub fn DeploymentDashboard() -> impl IntoView {
 									key={|state| state.id}
 									let:child
 								>
-									<DeploymentCard deployment={child} />
+									<DeploymentCard deployment={Signal::derive(move || {
+										let mut deployment = child.clone();
+										if let Some(status) = deployment_statuses
+											.with(|statuses| statuses.get(&deployment.id).cloned())
+										{
+											deployment.data.status = status;
+										}
+										deployment
+									})} />
 								</For>
 							</ContainerGrid>
 						}
@@ -96,13 +152,13 @@ pub fn DeploymentDashboard() -> impl IntoView {
 						}
 							.into_view()
 					}
-					_ => view! {
+					None => view! {
 						<LoadingDeployments />
 					}.into_view(),
 				}}
 			</Transition>
 
-			<DeploymentDashboardFooter
+			<Pagination
 				total_count={total_count}
 				current_page={deployment_page}
 			/>
@@ -1,10 +1,13 @@
-use std::str::FromStr;
+use std::{collections::BTreeMap, str::FromStr};
 
 use ev::MouseEvent;
 use models::api::workspace::deployment::*;
 
 use super::{super::components::*, DeploymentInfoContext};
-use crate::{prelude::*, queries::update_deployment_query};
+use crate::{
+	prelude::*,
+	queries::{test_deployment_port_query, update_deployment_query},
+};
 
 /// Details tab for a deployment
 #[component]
@@ -15,16 +18,35 @@ pub fn ManageDeploymentDetailsTab() -> impl IntoView {
 	let update_deployment_body = create_rw_signal(UpdateDeploymentRequest::new());
 
 	let update_deployment_action = update_deployment_query();
+	let test_port_action = test_deployment_port_query();
+	let tested_port = create_rw_signal(None::<u16>);
+	let show_effective_config = create_rw_signal(false);
 
 	let on_click_submit = move |ev: MouseEvent| {
 		ev.prevent_default();
 
 		if let Some(deployment_info) = deployment_info.get() {
-			update_deployment_action
-				.dispatch((deployment_info.deployment.id, update_deployment_body.get()));
+			update_deployment_action.dispatch((
+				deployment_info.deployment.id,
+				deployment_info.deployment.updated_at,
+				update_deployment_body.get(),
+			));
 		}
 	};
 
+	// Once the deployment is updated, keep track of its new version, and only
+	// send the changes made after that in the next update
+	create_effect(move |_| {
+		if let Some(Ok(response)) = update_deployment_action.value().get() {
+			deployment_info.update(|info| {
+				if let Some(info) = info {
+					info.deployment.data.updated_at = response.updated_at;
+				}
+			});
+			update_deployment_body.set(UpdateDeploymentRequest::new());
+		}
+	});
+
 	move || {
 		match deployment_info.get() {
 			Some(info) => {
@@ -173,9 +195,59 @@ pub fn ManageDeploymentDetailsTab() -> impl IntoView {
 										});
 								}
 							}}
+							on_test={move |port_number: String| {
+								if let (Ok(port), Some(info)) = (
+									port_number.parse::<u16>(),
+									deployment_info.get(),
+								) {
+									tested_port.set(Some(port));
+									test_port_action.dispatch((info.deployment.id, port));
+								}
+							}}
 							is_update_screen=true
 						/>
 
+						{move || {
+							let port = tested_port.get()?;
+							let result = test_port_action.value().get()?;
+							Some(match result {
+								Ok(TestDeploymentPortResponse {
+									reachable: true,
+									latency_millis,
+									http_status,
+									..
+								}) => view! {
+									<Alert r#type={AlertType::Success} class="mb-md">
+										{format!(
+											"Port {port} is reachable{}{}",
+											latency_millis
+												.map(|latency| format!(" in {latency}ms"))
+												.unwrap_or_default(),
+											http_status
+												.map(|status| format!(" (HTTP {status})"))
+												.unwrap_or_default(),
+										)}
+									</Alert>
+								}
+									.into_view(),
+								Ok(TestDeploymentPortResponse { error, .. }) => view! {
+									<Alert r#type={AlertType::Error} class="mb-md">
+										{format!(
+											"Port {port} is unreachable: {}",
+											error.unwrap_or_default(),
+										)}
+									</Alert>
+								}
+									.into_view(),
+								Err(err) => view! {
+									<Alert r#type={AlertType::Error} class="mb-md">
+										{format!("Unable to test port {port}: {err}")}
+									</Alert>
+								}
+									.into_view(),
+							})
+						}}
+
 						<EnvInput
 							on_add={move |(name, value): (String, String)| {
 								let env_val = EnvironmentVariableValue::String(value);
@@ -212,10 +284,31 @@ pub fn ManageDeploymentDetailsTab() -> impl IntoView {
 											.map(|info| info.running_details.environment_variables);
 									});
 							}}
-							envs_list={
-								let envs = info.running_details.environment_variables.clone();
-								Signal::derive(move || envs.clone())
-							}
+							on_import={move |variables: BTreeMap<String, EnvironmentVariableValue>| {
+								deployment_info
+									.update(|info| {
+										if let Some(info) = info {
+											for (name, value) in variables {
+												info.running_details
+													.environment_variables
+													.entry(name)
+													.or_insert(value);
+											}
+										}
+									});
+								update_deployment_body
+									.update(|body| {
+										body.environment_variables = deployment_info
+											.get()
+											.map(|info| info.running_details.environment_variables);
+									});
+							}}
+							envs_list={Signal::derive(move || {
+								deployment_info
+									.get()
+									.map(|info| info.running_details.environment_variables)
+									.unwrap_or_default()
+							})}
 						/>
 
 						<ProbeInput
@@ -247,8 +340,7 @@ pub fn ManageDeploymentDetailsTab() -> impl IntoView {
 									.update(|body| {
 										body.startup_probe = deployment_info
 											.get()
-											.map(|info| info.running_details.startup_probe)
-											.flatten();
+											.map(|info| info.running_details.startup_probe);
 									});
 							}}
 							on_input_path={move |(port, path): (String, String)| {
@@ -268,8 +360,7 @@ pub fn ManageDeploymentDetailsTab() -> impl IntoView {
 									.update(|body| {
 										body.startup_probe = deployment_info
 											.get()
-											.map(|info| info.running_details.startup_probe)
-											.flatten();
+											.map(|info| info.running_details.startup_probe);
 									});
 							}}
 							on_delete={move |_| {
@@ -281,7 +372,7 @@ pub fn ManageDeploymentDetailsTab() -> impl IntoView {
 									});
 								update_deployment_body
 									.update(|body| {
-										body.startup_probe = None;
+										body.startup_probe = Some(None);
 									});
 							}}
 						/>
@@ -315,8 +406,7 @@ pub fn ManageDeploymentDetailsTab() -> impl IntoView {
 									.update(|body| {
 										body.liveness_probe = deployment_info
 											.get()
-											.map(|info| info.running_details.liveness_probe)
-											.flatten();
+											.map(|info| info.running_details.liveness_probe);
 									});
 							}}
 							on_input_path={move |(port, path): (String, String)| {
@@ -336,8 +426,7 @@ pub fn ManageDeploymentDetailsTab() -> impl IntoView {
 									.update(|body| {
 										body.liveness_probe = deployment_info
 											.get()
-											.map(|info| info.running_details.liveness_probe)
-											.flatten();
+											.map(|info| info.running_details.liveness_probe);
 									});
 							}}
 							on_delete={move |_| {
@@ -349,13 +438,35 @@ pub fn ManageDeploymentDetailsTab() -> impl IntoView {
 									});
 								update_deployment_body
 									.update(|body| {
-										body.liveness_probe = None;
+										body.liveness_probe = Some(None);
 									});
 							}}
 						/>
 					</div>
 
+					<Show when={move || show_effective_config.get()}>
+						<div class="flex w-full fit-wide-screen mx-auto px-xl pb-md text-white">
+							<EffectiveDeploymentConfig deployment_id={
+								let deployment_id = info.deployment.id;
+								Signal::derive(move || deployment_id)
+							} />
+						</div>
+					</Show>
+
 					<div class="flex justify-end items-center gap-md w-full fit-wide-screen mx-auto mt-auto pb-xl px-xl">
+						<button
+							type="button"
+							class="flex items-center justify-center btn-plain text-sm"
+							on:click={move |_| show_effective_config.update(|show| *show = !*show)}
+						>
+							{move || {
+								if show_effective_config.get() {
+									"HIDE EFFECTIVE CONFIG"
+								} else {
+									"SHOW EFFECTIVE CONFIG"
+								}
+							}}
+						</button>
 						<button
 							type="submit"
 							class="flex items-center justify-center btn btn-primary"
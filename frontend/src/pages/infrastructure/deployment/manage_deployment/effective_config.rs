@@ -0,0 +1,107 @@
+use models::api::workspace::deployment::*;
+
+use crate::{prelude::*, queries::get_effective_deployment_config_query};
+
+/// Shows the effective config of a deployment, with every default filled in,
+/// and where each value that isn't set on the deployment itself comes from
+#[component]
+pub fn EffectiveDeploymentConfig(
+	/// The ID of the deployment
+	#[prop(into)]
+	deployment_id: Signal<Uuid>,
+) -> impl IntoView {
+	let effective_config = get_effective_deployment_config_query(deployment_id);
+
+	view! {
+		<Transition>
+			{move || match effective_config.get() {
+				Some(Ok(config)) => {
+					view! {
+						<div class="flex flex-col items-start justify-start w-full gap-xs">
+							{effective_config_rows(&config)
+								.into_iter()
+								.map(|(field, value, source)| {
+									view! {
+										<div class="flex w-full text-sm">
+											<p class="flex-4 text-grey">{field}</p>
+											<p class="flex-5 txt-of-ellipsis of-hidden">{value}</p>
+											<p class="flex-3 text-grey">{source}</p>
+										</div>
+									}
+								})
+								.collect_view()}
+						</div>
+					}
+						.into_view()
+				}
+				Some(Err(error)) => {
+					view! {
+						<Alert r#type={AlertType::Error}>{error_message(&error)}</Alert>
+					}
+						.into_view()
+				}
+				None => view! {}.into_view(),
+			}}
+		</Transition>
+	}
+}
+
+/// The rows to show for the effective config of a deployment, as the path of
+/// the field, its value, and where the value comes from
+fn effective_config_rows(
+	config: &GetEffectiveDeploymentConfigResponse,
+) -> Vec<(String, String, &'static str)> {
+	let source = |field: &str| match config.inherited_values.get(field) {
+		Some(DeploymentConfigValueSource::Template) => "From template",
+		Some(DeploymentConfigValueSource::MachineType) => "Machine type default",
+		None => "Set on deployment",
+	};
+
+	let DeploymentMachineType {
+		cpu_count,
+		memory_count,
+	} = &config.machine_type.data;
+	let mut rows = vec![(
+		"machineType".to_string(),
+		format!("{cpu_count} vCPU, {:.2} GB", *memory_count as f32 / 4.0),
+		source("machineType"),
+	)];
+
+	let resources = &config.running_details.resources;
+	for (field, value, unit) in [
+		("resources.cpuRequest", resources.cpu_request, "m"),
+		("resources.cpuLimit", resources.cpu_limit, "m"),
+		("resources.memoryRequest", resources.memory_request, "Mi"),
+		("resources.memoryLimit", resources.memory_limit, "Mi"),
+	] {
+		rows.push((
+			field.to_string(),
+			value.map_or_else(
+				|| "Decided by the runner".to_string(),
+				|value| format!("{value}{unit}"),
+			),
+			source(field),
+		));
+	}
+
+	for (name, value) in &config.running_details.environment_variables {
+		let field = format!("environmentVariables.{name}");
+		let value = match value {
+			EnvironmentVariableValue::String(value) => value.clone(),
+			EnvironmentVariableValue::Secret { from_secret } => format!("Secret {from_secret}"),
+		};
+		let source = source(&field);
+		rows.push((field, value, source));
+	}
+
+	for (field, probe) in [
+		("startupProbe", &config.running_details.startup_probe),
+		("livenessProbe", &config.running_details.liveness_probe),
+	] {
+		if let Some(DeploymentProbe { port, path }) = probe {
+			rows.push((field.to_string(), format!("{port}{path}"), source(field)));
+		}
+	}
+
+	rows
+}
@@ -98,7 +98,7 @@ pub fn StartStopButton() -> impl IntoView {
 		if let Some(deployment_info) = deployment_info.get() {
 			let status = deployment_info.deployment.status.clone();
 			match status {
-				DeploymentStatus::Running => {
+				status if status.is_running() || status == DeploymentStatus::Cold => {
 					stop_deployment_action.dispatch(deployment_info.deployment.id.clone());
 				}
 				DeploymentStatus::Created | DeploymentStatus::Stopped => {
@@ -124,18 +124,19 @@ pub fn StartStopButton() -> impl IntoView {
 				})}
 				style_variant={LinkStyleVariant::Contained}
 				disabled={match deployment_info.deployment.status {
-					DeploymentStatus::Running
-					| DeploymentStatus::Created
-					| DeploymentStatus::Stopped => false,
+					status if status.is_running() => false,
+					DeploymentStatus::Cold => false,
+					DeploymentStatus::Created | DeploymentStatus::Stopped => false,
 					_ => true,
 				}}
 			>
 				<Icon
-					icon={match Status::from_deployment_status(
-						deployment_info.clone().deployment.clone().status.clone(),
-					) {
-						Status::Running => IconType::PauseCircle,
-						_ => IconType::PlayCircle,
+					icon={if deployment_info.deployment.status.is_running()
+						|| deployment_info.deployment.status == DeploymentStatus::Cold
+					{
+						IconType::PauseCircle
+					} else {
+						IconType::PlayCircle
 					}}
 					size={Size::ExtraSmall}
 					class="mr-xs"
@@ -146,7 +147,7 @@ pub fn StartStopButton() -> impl IntoView {
 						deployment_info.deployment.clone().status.clone(),
 					);
 					match status {
-						Status::Running => "STOP",
+						Status::Running | Status::Degraded | Status::Cold => "STOP",
 						Status::Created | Status::Stopped => "START",
 						_ => status.get_status_text(),
 					}
@@ -1,11 +1,11 @@
 use std::rc::Rc;
 
 use ev::MouseEvent;
-use models::api::workspace::deployment::DeploymentLog;
+use models::api::workspace::deployment::{DeploymentLog, DeploymentLogFormat};
 use time::{macros::format_description, Duration, OffsetDateTime};
 
 use super::{super::components::*, DeploymentInfoContext};
-use crate::prelude::*;
+use crate::{prelude::*, queries::download_deployment_logs_query};
 
 /// List Logs for a deployment
 #[component]
@@ -16,26 +16,75 @@ pub fn ManageDeploymentsLogs() -> impl IntoView {
 	let logs_list = create_rw_signal::<Vec<DeploymentLog>>(vec![]);
 
 	let end_time = create_rw_signal(OffsetDateTime::now_utc());
+	// The replica to show the logs of. If empty, the logs of all the replicas
+	// are shown together
+	let selected_replica = create_rw_signal(String::new());
+
+	let replicas = create_resource(
+		move || {
+			(
+				state.get().get_access_token(),
+				state.get().get_last_used_workspace_id(),
+			)
+		},
+		move |(access_token, workspace_id)| async move {
+			list_deployment_replicas(
+				access_token,
+				workspace_id,
+				deployment_info.get().unwrap().deployment.id,
+			)
+			.await
+		},
+	);
+	let replica_options = Signal::derive(move || {
+		let mut options = vec![InputDropdownOption {
+			id: String::new(),
+			label: "All Replicas".to_string(),
+			disabled: false,
+		}];
+		if let Some(Ok(response)) = replicas.get() {
+			options.extend(
+				response
+					.replicas
+					.into_iter()
+					.map(|replica| InputDropdownOption {
+						id: replica.clone(),
+						label: replica,
+						disabled: false,
+					}),
+			);
+		}
+		options
+	});
+
 	let deployment_logs = create_resource(
 		move || {
 			(
 				state.get().get_access_token(),
 				state.get().get_last_used_workspace_id(),
 				end_time.get(),
+				selected_replica.get(),
 			)
 		},
-		move |(access_token, workspace_id, end_time)| async move {
+		move |(access_token, workspace_id, end_time, replica_id)| async move {
 			get_deployment_logs(
 				access_token,
 				workspace_id,
 				deployment_info.get().unwrap().deployment.id,
 				Some(end_time),
 				Some(25),
+				Some(replica_id).filter(|replica_id| !replica_id.is_empty()),
 			)
 			.await
 		},
 	);
 
+	// Start over from the latest logs when a different replica is chosen
+	let on_select_replica = move |_: String| {
+		logs_list.set(vec![]);
+		end_time.set(OffsetDateTime::now_utc());
+	};
+
 	create_effect(move |_| match deployment_logs.get() {
 		Some(Ok(new_logs)) => logs_list.update(|logs| {
 			logs.extend(new_logs.logs.into_iter());
@@ -43,6 +92,7 @@ pub fn ManageDeploymentsLogs() -> impl IntoView {
 			logs.extend((0..25).map(|x| DeploymentLog {
 				timestamp: end_time.get() - Duration::seconds(x * 100),
 				log: format!("This is a log {x}"),
+				parsed: None,
 			}))
 			// TO HERE
 		}),
@@ -57,6 +107,26 @@ pub fn ManageDeploymentsLogs() -> impl IntoView {
 		}
 	};
 
+	let download_logs_action = download_deployment_logs_query();
+	let on_click_download = move |format: DeploymentLogFormat| {
+		move |_: &MouseEvent| {
+			if let Some(deployment_info) = deployment_info.get() {
+				download_logs_action.dispatch((
+					deployment_info.deployment.id,
+					OffsetDateTime::now_utc() - Duration::days(1),
+					format,
+				));
+			}
+		}
+	};
+	let truncated_at = move || {
+		download_logs_action
+			.value()
+			.get()
+			.and_then(|logs| logs.ok())
+			.and_then(|logs| logs.truncated_at)
+	};
+
 	let date_formater = format_description!("[year]-[month]-[day] [hour]:[minute]");
 
 	view! {
@@ -72,7 +142,35 @@ pub fn ManageDeploymentsLogs() -> impl IntoView {
 									>
 										"LOAD MORE"
 									</Link>
+									<div class="flex items-center justify-end gap-md">
+										<InputDropdown
+											placeholder="All Replicas"
+											variant={SecondaryColorVariant::Medium}
+											value={selected_replica}
+											options={replica_options}
+											on_select={on_select_replica}
+										/>
+										<Link
+											on_click={Rc::new(on_click_download(DeploymentLogFormat::Text))}
+											disabled={download_logs_action.pending()}
+										>
+											"DOWNLOAD LAST 24H"
+										</Link>
+										<Link
+											on_click={Rc::new(on_click_download(DeploymentLogFormat::Json))}
+											disabled={download_logs_action.pending()}
+										>
+											"DOWNLOAD AS JSON"
+										</Link>
+									</div>
 								</div>
+								{move || truncated_at().map(|truncated_at| view! {
+									<p class="w-full mb-xs text-warning text-sm">
+										{format!(
+											"The logs were too long to download at once, and were only downloaded up to {truncated_at}"
+										)}
+									</p>
+								})}
 								<div class="w-full h-full br-sm bg-secondary px-xl py-md flex flex-col items-start justify-start overflow-auto">
 									<For
 										each={move || logs_list.get()}
@@ -1,4 +1,5 @@
 mod details;
+mod effective_config;
 mod head;
 mod image_history;
 mod image_history_card;
@@ -13,6 +14,7 @@ use models::api::workspace::deployment::*;
 
 pub use self::{
 	details::*,
+	effective_config::*,
 	head::*,
 	image_history::*,
 	image_history_card::*,
@@ -77,7 +79,15 @@ pub fn ManageDeploymentsContent(
 						}
 					}
 				}
-				None => view! { <div>"Loading"</div> }.into_view(),
+				None => {
+					view! {
+						<ManageDeploymentHeader />
+						<ContainerBody class="gap-md">
+							<DeploymentDetailsSkeleton />
+						</ContainerBody>
+					}
+						.into_view()
+				}
 			}}
 		</Transition>
 	}
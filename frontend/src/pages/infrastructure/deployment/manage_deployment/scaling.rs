@@ -7,7 +7,7 @@ use super::DeploymentInfoContext;
 use crate::{
 	pages::infrastructure::deployment::components::MachineTypeCard,
 	prelude::*,
-	queries::{list_machines_query, update_deployment_query},
+	queries::{get_workspace_query, list_machines_query, update_deployment_query},
 };
 
 #[component]
@@ -26,6 +26,17 @@ fn UpdateScale(
 
 	let deployment_info_context = expect_context::<DeploymentInfoContext>().0;
 
+	let (state, _) = AuthState::load();
+	let workspace = get_workspace_query(Signal::derive(move || {
+		state.get().get_last_used_workspace_id().unwrap_or_default()
+	}));
+	let max_replicas = Signal::derive(move || {
+		workspace
+			.get()
+			.and_then(Result::ok)
+			.map_or(10, |response| response.max_replicas)
+	});
+
 	view! {
 		<div class="w-full flex items-center justify-center">
 			<div class="flex-2 flex flex-col items-center justify-center">
@@ -33,6 +44,7 @@ fn UpdateScale(
 
 				<NumberPicker
 					value={min_horizontal_value}
+					max={max_replicas}
 					style_variant={SecondaryColorVariant::Medium}
 					on_change={move |_| update_deployment_body.update(|body| {
 						body.min_horizontal_scale = Some(min_horizontal_value.get());
@@ -44,6 +56,12 @@ fn UpdateScale(
 				<p class="text-warning text-xxs">
 					"Any excess volumes will be removed if the number of instances is reduced."
 				</p>
+				<p class="text-grey text-xxs">
+					{move || format!(
+						"Deployments in this workspace can be scaled to at most {} instances",
+						max_replicas.get(),
+					)}
+				</p>
 			</div>
 
 			<div class="flex-2 flex flex-col items-center justify-center">
@@ -51,6 +69,7 @@ fn UpdateScale(
 
 				<NumberPicker
 					value={max_horizontal_value}
+					max={max_replicas}
 					style_variant={SecondaryColorVariant::Medium}
 					on_change={move |_| update_deployment_body.update(|body| {
 						body.max_horizontal_scale = Some(max_horizontal_value.get());
@@ -128,11 +147,27 @@ pub fn ManageDeploymentScaling() -> impl IntoView {
 	let on_submit = move |ev: &MouseEvent| {
 		ev.prevent_default();
 		if let Some(deployment_info) = deployment_info.get() {
-			update_deployment_action
-				.dispatch((deployment_info.deployment.id, update_deployment_body.get()));
+			update_deployment_action.dispatch((
+				deployment_info.deployment.id,
+				deployment_info.deployment.updated_at,
+				update_deployment_body.get(),
+			));
 		}
 	};
 
+	// Once the deployment is updated, keep track of its new version, and only
+	// send the changes made after that in the next update
+	create_effect(move |_| {
+		if let Some(Ok(response)) = update_deployment_action.value().get() {
+			deployment_info.update(|info| {
+				if let Some(info) = info {
+					info.deployment.data.updated_at = response.updated_at;
+				}
+			});
+			update_deployment_body.set(UpdateDeploymentRequest::new());
+		}
+	});
+
 	view! {
 		<div class="flex flex-col items-start justify-start w-full px-xl mt-xl
 			text-white text-sm fit-wide-screen mx-auto gap-md"
@@ -1,4 +1,4 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
 use leptos::prelude::*;
 use models::{
@@ -34,6 +34,8 @@ pub struct DeploymentInfo {
 	pub runner_id: Option<Uuid>,
 	/// The Machine Type to use for the deployment
 	pub machine_type: Option<Uuid>,
+	/// The Deployment Template to fill the unset values from
+	pub template_id: Option<Uuid>,
 	/// Whether to deploy on create
 	pub deploy_on_create: bool,
 	/// Whether to deploy on push
@@ -89,6 +91,11 @@ impl DeploymentInfo {
 				.map(|(port, path)| DeploymentProbe { port, path }),
 			volumes: self.volumes.clone(),
 			config_mounts: BTreeMap::from([]),
+			resources: DeploymentResources::default(),
+			scale_to_zero_after: None,
+			log_level: None,
+			max_concurrent_requests: None,
+			access_logging: false,
 		};
 
 		Some(CreateDeploymentRequest {
@@ -97,8 +104,14 @@ impl DeploymentInfo {
 			name: self.name.clone()?,
 			runner: self.runner_id.clone()?,
 			image_tag: self.image_tag.clone()?,
-			machine_type: self.machine_type.clone()?,
+			machine_type: self.machine_type.clone(),
+			template_id: self.template_id.clone(),
 			deploy_on_create: self.deploy_on_create,
+			pull_secret_id: None,
+			secret_variables: BTreeSet::new(),
+			labels: BTreeMap::new(),
+			depends_on: BTreeSet::new(),
+			canary: None,
 		})
 	}
 }
@@ -1,5 +1,10 @@
+use std::collections::BTreeSet;
+
 use ev::MouseEvent;
-use models::{api::user::UserApiToken, prelude::*};
+use models::{
+	api::user::{ApiTokenStatus, ListedApiToken},
+	prelude::*,
+};
 use time::format_description;
 
 use crate::prelude::*;
@@ -11,7 +16,9 @@ pub fn ApiTokenCard(
 	class: MaybeSignal<String>,
 	/// The User API Token
 	#[prop(into)]
-	token: MaybeSignal<WithId<UserApiToken>>,
+	token: MaybeSignal<WithId<ListedApiToken>>,
+	/// The IDs of the tokens that are selected for a bulk action
+	selected: RwSignal<BTreeSet<Uuid>>,
 ) -> impl IntoView {
 	let outer_class = class.with(|cname| {
 		format!(
@@ -32,13 +39,19 @@ pub fn ApiTokenCard(
 		let format =
 			format_description::parse("[year]-[month]-[day] [hour]:[minute]:[second]").unwrap();
 
-		token.get().data.created.clone().format(&format.clone())
+		token
+			.get()
+			.data
+			.token
+			.created
+			.clone()
+			.format(&format.clone())
 	}) {
 		Ok(date) => date.into_view(),
 		Err(_) => "Invalid Date".into_view(),
 	};
 
-	let expiry = move || match store_token.with_value(|token| token.get().data.token_exp) {
+	let expiry = move || match store_token.with_value(|token| token.get().data.token.token_exp) {
 		Some(expiry) => match expiry.format(&format.clone()) {
 			Ok(date) => date.into_view(),
 			Err(_) => "Invalid Date".into_view(),
@@ -46,9 +59,29 @@ pub fn ApiTokenCard(
 		None => "Never".into_view(),
 	};
 
+	let status = move || {
+		let (text, color) = match store_token.with_value(|token| token.get().data.status) {
+			ApiTokenStatus::Active => ("active", Color::Success),
+			ApiTokenStatus::Expired => ("expired", Color::Warning),
+			ApiTokenStatus::Revoked => ("revoked", Color::Error),
+		};
+
+		view! { <StatusBadge text={Some(text.to_string())} color={Some(color)} /> }
+	};
+
 	let token_id =
 		Signal::derive(move || store_token.with_value(|token| token.get().id.to_string()));
 
+	let id = store_token.with_value(|token| token.get().id);
+	let is_selected = move || selected.with(|selected| selected.contains(&id));
+	let on_toggle_select = move |_| {
+		selected.update(|selected| {
+			if !selected.remove(&id) {
+				selected.insert(id);
+			}
+		})
+	};
+
 	let on_click_link = move |ev: MouseEvent| {
 		ev.prevent_default();
 		logging::log!("/user/api-tokens/{}", token_id.get());
@@ -60,11 +93,23 @@ pub fn ApiTokenCard(
 
 	view! {
 		<tr on:click={on_click_link} tab_index=0 class={outer_class} aria_label="Select API Token">
-			<td class="flex-4 flex items-center justify-center">
-				{move || store_token.with_value(|token| token.get().name.clone())}
+			<td
+				class="flex-1 flex items-center justify-center"
+				on:click={move |ev: MouseEvent| ev.stop_propagation()}
+			>
+				<input
+					type="checkbox"
+					aria_label="Select for bulk action"
+					prop:checked={is_selected}
+					on:input={on_toggle_select}
+				/>
+			</td>
+			<td class="flex-3 flex items-center justify-center">
+				{move || store_token.with_value(|token| token.get().data.token.name.clone())}
 			</td>
-			<td class="flex-4 flex items-center justify-center">{expiry.clone()}</td>
-			<td class="flex-4 flex items-center justify-center">{date.clone()}</td>
+			<td class="flex-3 flex items-center justify-center">{status}</td>
+			<td class="flex-2 flex items-center justify-center">{expiry.clone()}</td>
+			<td class="flex-3 flex items-center justify-center">{date.clone()}</td>
 		</tr>
 	}
 }
@@ -18,7 +18,10 @@ pub fn PermissionsDropdown(
 	/// Input Resource Type
 	input_resource_type: RwSignal<String>,
 ) -> impl IntoView {
-	let all_permissions = get_all_permissions_query();
+	let search = create_rw_signal(String::new());
+	let all_permissions = get_all_permissions_query(Signal::derive(move || {
+		Some(search.get()).filter(|search| !search.trim().is_empty())
+	}));
 
 	let filtered_permissions = create_memo(move |_| {
 		let permissions = all_permissions.get();
@@ -77,6 +80,16 @@ pub fn PermissionsDropdown(
 
 	view! {
 		<Transition>
+			<Input
+				class="w-full mb-xs"
+				r#type={InputType::Text}
+				placeholder="Search Permissions"
+				variant={SecondaryColorVariant::Medium}
+				on_input={Box::new(move |ev| {
+					search.set(event_target_value(&ev));
+				})}
+				value={search}
+			/>
 			<CheckboxDropdown
 				placeholder={"Select Permissions".to_string()}
 				options={permission_options}
@@ -14,7 +14,7 @@ pub fn ResourceDropdownOptions(
 	input_resources: RwSignal<Vec<String>>,
 ) -> impl IntoView {
 	let current_page = create_rw_signal::<usize>(0);
-	let deployments_list = list_deployments_query(current_page.into());
+	let deployments_list = list_deployments_query(current_page.into(), Signal::derive(|| None));
 
 	let resource_list_options = create_rw_signal::<Vec<InputDropdownOption>>(vec![]);
 
@@ -1,4 +1,11 @@
-use crate::{prelude::*, queries::list_api_tokens_query};
+use std::{collections::BTreeSet, rc::Rc};
+
+use models::api::user::{ApiTokenSortBy, ApiTokenStatus};
+
+use crate::{
+	prelude::*,
+	queries::{bulk_revoke_api_tokens_query, list_api_tokens_query},
+};
 
 mod components;
 mod create_token;
@@ -21,18 +28,55 @@ pub fn ApiTokensTab() -> impl IntoView {
 /// List all the API tokens
 #[component]
 pub fn ListApiTokens() -> impl IntoView {
-	let token_list = list_api_tokens_query();
+	let status_filter = create_rw_signal(None::<ApiTokenStatus>);
+	let order_by = create_rw_signal(None::<ApiTokenSortBy>);
+	let order = create_rw_signal(None::<ListOrder>);
+
+	let token_list = list_api_tokens_query(status_filter.into(), order_by.into(), order.into());
+
+	let selected = create_rw_signal(BTreeSet::<Uuid>::new());
+	let bulk_revoke_action = bulk_revoke_api_tokens_query();
+	let bulk_revoke_pending = bulk_revoke_action.pending();
+
+	// Clear the selection and reload the list once the tokens are revoked
+	create_effect(move |_| {
+		if let Some(Ok(_)) = bulk_revoke_action.value().get() {
+			selected.update(BTreeSet::clear);
+			token_list.refetch();
+		}
+	});
 
 	view! {
-		<Link r#type={Variant::Link} style_variant={LinkStyleVariant::Contained} to="create">
-			"Create New Token"
-			<Icon
-				icon={IconType::Plus}
-				size={Size::ExtraSmall}
-				class="ml-xs"
-				color={Color::Black}
-			/>
-		</Link>
+		<div class="flex items-center justify-start w-full gap-md">
+			<Link r#type={Variant::Link} style_variant={LinkStyleVariant::Contained} to="create">
+				"Create New Token"
+				<Icon
+					icon={IconType::Plus}
+					size={Size::ExtraSmall}
+					class="ml-xs"
+					color={Color::Black}
+				/>
+			</Link>
+
+			<Show when={move || selected.with(|selected| !selected.is_empty())}>
+				<Link
+					r#type={Variant::Button}
+					style_variant={LinkStyleVariant::Outlined}
+					color={Color::Error}
+					disabled={bulk_revoke_pending}
+					on_click={Rc::new(move |_| {
+						bulk_revoke_action
+							.dispatch(selected.with_untracked(|selected| {
+								selected.iter().copied().collect()
+							}));
+					})}
+				>
+					{move || {
+						format!("Revoke Selected ({})", selected.with(BTreeSet::len))
+					}}
+				</Link>
+			</Show>
+		</div>
 
 		<Transition>
 			{move || match token_list.get() {
@@ -41,9 +85,11 @@ pub fn ListApiTokens() -> impl IntoView {
 						Ok(data) => {
 							view! {
 								<TableDashboard
-									column_grids={vec![4, 4, 4]}
+									column_grids={vec![1, 3, 3, 2, 3]}
 									headings={vec![
+										"".into_view(),
 										"Name".into_view(),
+										"Status".into_view(),
 										"Expiry".into_view(),
 										"Created At".into_view(),
 									]}
@@ -54,7 +100,7 @@ pub fn ListApiTokens() -> impl IntoView {
 												key={|state| state.id}
 												let:child
 											>
-												<ApiTokenCard token={child} />
+												<ApiTokenCard token={child} selected={selected} />
 											</For>
 										}
 										.into_view()
@@ -1,3 +1,5 @@
+use std::rc::Rc;
+
 use crate::prelude::*;
 
 #[component]
@@ -5,14 +7,75 @@ pub fn ContactInfo(
 	/// Basic User with Id
 	// #[prop(into)]
 	user_email: Option<String>,
+	/// The new recovery email that is pending verification, if any
+	pending_email: Option<String>,
 ) -> impl IntoView {
+	let user_email = create_rw_signal(user_email);
+	let pending_email = create_rw_signal(pending_email);
+
+	let new_email = create_rw_signal("".to_owned());
+	let verification_token = create_rw_signal("".to_owned());
+	let email_error = create_rw_signal("".to_owned());
+
+	let change_email_action = change_recovery_email_query();
+	let verify_email_action = verify_recovery_email_query();
+	let cancel_change_action = cancel_recovery_email_change_query();
+
+	let handle_error = move |error: ServerFnError<ErrorType>| match error {
+		ServerFnError::WrappedServerError(err) => email_error.set(err.message().into()),
+		err => email_error.set(err.to_string()),
+	};
+
+	create_effect(move |_| match change_email_action.value().get() {
+		Some(Ok(_)) => {
+			pending_email.set(Some(new_email.get_untracked()));
+			new_email.set("".to_owned());
+			email_error.set("".to_owned());
+		}
+		Some(Err(err)) => handle_error(err),
+		None => {}
+	});
+
+	create_effect(move |_| match verify_email_action.value().get() {
+		Some(Ok(_)) => {
+			user_email.set(pending_email.get_untracked());
+			pending_email.set(None);
+			verification_token.set("".to_owned());
+			email_error.set("".to_owned());
+		}
+		Some(Err(err)) => handle_error(err),
+		None => {}
+	});
+
+	create_effect(move |_| match cancel_change_action.value().get() {
+		Some(Ok(_)) => {
+			pending_email.set(None);
+			verification_token.set("".to_owned());
+			email_error.set("".to_owned());
+		}
+		Some(Err(err)) => handle_error(err),
+		None => {}
+	});
+
+	let on_submit_new_email = move |ev: ev::SubmitEvent| {
+		ev.prevent_default();
+		change_email_action.dispatch(new_email.get_untracked());
+	};
+
+	let on_submit_verification = move |ev: ev::SubmitEvent| {
+		ev.prevent_default();
+		if let Some(email) = pending_email.get_untracked() {
+			verify_email_action.dispatch((email, verification_token.get_untracked()));
+		}
+	};
+
 	view! {
 		<section class="text-white flex flex-col items-start justify-start w-full px-xl py-lg br-sm bg-secondary-light">
 			<div class="flex items-center justify-start w-full pb-sm border-b border-border-color">
 				<h2 class="tracking-[1px] text-md">"Contact Info"</h2>
 			</div>
 
-			<div class="w-full flex items-start justify-start gap-md pt-md">
+			<div class="w-full flex flex-col items-start justify-start gap-md pt-md">
 				<div class="flex w-full px-md">
 					<div class="flex-col-2 flex items-start justify-start">
 						<label html_for="primaryEmail" class="mt-sm txt-sm">
@@ -22,7 +85,7 @@ pub fn ContactInfo(
 
 					<div class="flex-col-10 flex flex-col items-start justify-start">
 
-						{match user_email {
+						{move || match user_email.get() {
 							Some(email) => {
 								view! {
 									<InputDropdown
@@ -43,6 +106,93 @@ pub fn ContactInfo(
 					</div>
 				</div>
 
+			<div class="flex w-full px-md">
+				<div class="flex-col-2 flex items-start justify-start">
+					<label html_for="newEmail" class="mt-sm txt-sm">
+						"Change Email"
+					</label>
+				</div>
+
+				<div class="flex-col-10 flex flex-col items-start justify-start gap-xs">
+					{move || match pending_email.get() {
+						Some(email) => {
+							view! {
+								<small class="txt-xxs txt-grey">
+									"A verification code has been sent to "
+									<strong>{email}</strong>
+									". Your current email stays active until the new one is verified."
+								</small>
+								<form class="flex w-full gap-xs" on:submit={on_submit_verification}>
+									<Input
+										id="verificationToken"
+										class="w-full"
+										r#type={InputType::Text}
+										placeholder="Enter Verification Code"
+										variant={SecondaryColorVariant::Medium}
+										disabled={verify_email_action.pending()}
+										on_input={Box::new(move |ev| {
+											verification_token.set(event_target_value(&ev));
+										})}
+										value={verification_token}
+									/>
+									<Link
+										style_variant={LinkStyleVariant::Contained}
+										should_submit=true
+										disabled={verify_email_action.pending()}
+									>
+										"VERIFY"
+									</Link>
+									<Link
+										on_click={Rc::new(move |_| cancel_change_action.dispatch(()))}
+										style_variant={LinkStyleVariant::Plain}
+										should_submit=false
+										disabled={cancel_change_action.pending()}
+									>
+										"CANCEL"
+									</Link>
+								</form>
+							}
+								.into_view()
+						}
+						None => {
+							view! {
+								<form class="flex w-full gap-xs" on:submit={on_submit_new_email}>
+									<Input
+										id="newEmail"
+										class="w-full"
+										r#type={InputType::Email}
+										placeholder="Enter New Email Address"
+										variant={SecondaryColorVariant::Medium}
+										disabled={change_email_action.pending()}
+										on_input={Box::new(move |ev| {
+											new_email.set(event_target_value(&ev));
+										})}
+										value={new_email}
+									/>
+									<Link
+										style_variant={LinkStyleVariant::Contained}
+										should_submit=true
+										disabled={change_email_action.pending()}
+									>
+										"CHANGE"
+									</Link>
+								</form>
+							}
+								.into_view()
+						}
+					}}
+
+					{move || email_error
+						.get()
+						.some_if_not_empty()
+						.map(|message| view! {
+							<Alert r#type={AlertType::Error} class="mt-xs">
+								{&message}
+							</Alert>
+						})}
+				</div>
+			</div>
+
 			// <div class="flex full-width px-md">
 			// <div class="flex-col-2 fr-fs-fs">
 			// <label html_for="primaryEmail" class="mt-sm txt-sm">
@@ -19,7 +19,10 @@ pub fn ProfileSettings() -> impl IntoView {
 							Ok(data) => {
 								view! {
 									<BasicInfo basic_user_info={data.clone().basic_user_info} />
-									<ContactInfo user_email={data.clone().recovery_email} />
+									<ContactInfo
+										user_email={data.clone().recovery_email}
+										pending_email={data.clone().pending_recovery_email}
+									/>
 								}
 									.into_view()
 							}
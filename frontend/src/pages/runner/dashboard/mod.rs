@@ -7,7 +7,13 @@ use crate::{prelude::*, queries::*};
 /// The Runner Dashboard page
 #[component]
 pub fn RunnerDashboard() -> impl IntoView {
-	let runners_list = list_runners_query();
+	let runner_page = create_rw_signal(0);
+	let runners_list = list_runners_query(runner_page.into());
+
+	let total_count = Signal::derive(move || match runners_list.get() {
+		Some(Ok((count, _))) => count,
+		_ => 0,
+	});
 
 	view! {
 		<RunnerDashboardHead />
@@ -17,7 +23,7 @@ pub fn RunnerDashboard() -> impl IntoView {
 				render_items={view! {
 					<Transition>
 						{move || match runners_list.get() {
-							Some(Ok(data)) => {
+							Some(Ok((_, data))) => {
 								view! {
 									<For
 										each={move || data.runners.clone()}
@@ -36,6 +42,11 @@ pub fn RunnerDashboard() -> impl IntoView {
 				}
 					.into_view()}
 			/>
+
+			<Pagination
+				total_count={total_count}
+				current_page={runner_page}
+			/>
 		</ContainerBody>
 	}
 }
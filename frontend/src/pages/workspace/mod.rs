@@ -2,10 +2,11 @@ use crate::prelude::*;
 
 mod create;
 mod manage_workspace;
+mod search;
 mod sidebar;
 mod tabs;
 
-pub use self::{create::*, manage_workspace::*, sidebar::*, tabs::*};
+pub use self::{create::*, manage_workspace::*, search::*, sidebar::*, tabs::*};
 
 #[component]
 pub fn WorkspacePage() -> impl IntoView {
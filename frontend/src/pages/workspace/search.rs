@@ -0,0 +1,117 @@
+use leptos_use::{
+	signal_debounced_with_options,
+	use_document,
+	use_event_listener,
+	utils::DebounceOptions,
+};
+use models::{api::workspace::WorkspaceSearchResult, rbac::ResourceType};
+
+use crate::{prelude::*, queries::search_workspace_resources_query};
+
+/// Gets the page to navigate to for a search result
+fn search_result_path(result: &WorkspaceSearchResult) -> String {
+	match result.resource_type {
+		ResourceType::Deployment => format!("{}/{}", LoggedInRoute::Deployment, result.id),
+		ResourceType::Runner => format!("{}/{}", LoggedInRoute::Runners, result.id),
+		ResourceType::Database => format!("{}/{}", LoggedInRoute::Database, result.id),
+		// Secrets don't have a page of their own
+		ResourceType::Secret => LoggedInRoute::Secret.to_string(),
+		_ => LoggedInRoute::Home.to_string(),
+	}
+}
+
+/// A command palette to search for resources in the current workspace. It is
+/// opened with `Ctrl+K` (or `Cmd+K`) and closed with `Escape`.
+#[component]
+pub fn WorkspaceSearchPalette() -> impl IntoView {
+	let show_palette = create_rw_signal(false);
+	let query = create_rw_signal(String::new());
+
+	let debounced_query = signal_debounced_with_options(
+		query,
+		constants::DEFAULT_DEBOUNCE_TIME,
+		DebounceOptions::default().max_wait(Some(constants::MAX_DEBOUNCE_TIME)),
+	);
+	let search_results = search_workspace_resources_query(debounced_query);
+
+	_ = use_event_listener(use_document(), ev::keydown, move |e| {
+		if (e.ctrl_key() || e.meta_key()) && e.key() == "k" {
+			e.prevent_default();
+			show_palette.update(|show| *show = !*show);
+		} else if e.key() == "Escape" {
+			show_palette.set(false);
+		}
+	});
+
+	let navigate = store_value(use_navigate());
+	let on_select = move |result: &WorkspaceSearchResult| {
+		show_palette.set(false);
+		query.set(String::new());
+		navigate.with_value(|navigate| navigate(&search_result_path(result), Default::default()));
+	};
+
+	view! {
+		<Show when={move || show_palette.get()}>
+			<Modal color_variant={SecondaryColorVariant::Light}>
+				<div class="center-modal text-white text-sm flex flex-col items-start justify-start \
+				bg-secondary-light br-sm p-xl show-center-modal gap-md">
+					<Input
+						class="w-full"
+						placeholder="Search deployments, runners, databases and secrets"
+						r#type={InputType::Text}
+						id="workspaceSearch"
+						value={Signal::derive(move || query.get())}
+						on_input={Box::new(move |ev| query.set(event_target_value(&ev)))}
+					/>
+
+					<Transition>
+						{move || match search_results.get() {
+							Some(Ok(response)) if response.results.is_empty() => {
+								(!debounced_query.get().trim().is_empty())
+									.then(|| view! { <p class="text-grey">"No resources found"</p> })
+									.into_view()
+							}
+							Some(Ok(response)) => {
+								view! {
+									<ul class="w-full flex flex-col items-start justify-start">
+										{response
+											.results
+											.into_iter()
+											.map(|result| {
+												let store_result = store_value(result.clone());
+												view! {
+													<li
+														tabindex={0}
+														class="w-full px-xl py-sm flex justify-between items-center \
+														cursor-pointer border-border-color border-b-2"
+														on:click={move |_| store_result.with_value(on_select)}
+														on:keydown={move |e| {
+															if e.key() == "Enter" {
+																store_result.with_value(on_select);
+															}
+														}}
+													>
+														<span>{result.name}</span>
+														<span class="text-grey text-xxs">
+															{result.resource_type.to_string()}
+														</span>
+													</li>
+												}
+											})
+											.collect_view()}
+									</ul>
+								}
+									.into_view()
+							}
+							Some(Err(_)) => {
+								view! { <p class="text-grey">"Couldn't search the workspace"</p> }
+									.into_view()
+							}
+							None => view! {}.into_view(),
+						}}
+					</Transition>
+				</div>
+			</Modal>
+		</Show>
+	}
+}
@@ -1,6 +1,178 @@
-use models::api::workspace::Workspace;
+use std::rc::Rc;
 
-use crate::prelude::*;
+use models::api::workspace::{egress_policy::WorkspaceEgressPolicy, Workspace};
+
+use crate::{
+	prelude::*,
+	queries::{
+		get_default_machine_type_query,
+		get_egress_policy_query,
+		list_machines_query,
+		set_default_machine_type_query,
+		set_egress_policy_query,
+	},
+};
+
+/// The ID of the dropdown option used to remove the default machine type
+const NO_DEFAULT_MACHINE_TYPE: &str = "none";
+
+#[component]
+fn DefaultMachineTypeSetting() -> impl IntoView {
+	let machine_list = list_machines_query();
+	let default_machine_type = get_default_machine_type_query();
+	let set_default_machine_type = set_default_machine_type_query();
+
+	let selected = create_rw_signal(String::new());
+
+	create_effect(move |_| {
+		if let Some(Ok(response)) = default_machine_type.get() {
+			selected.set(
+				response
+					.machine_type_id
+					.map(|id| id.to_string())
+					.unwrap_or_else(|| NO_DEFAULT_MACHINE_TYPE.to_string()),
+			);
+		}
+	});
+
+	let options = Signal::derive(move || {
+		let mut options = vec![InputDropdownOption {
+			id: NO_DEFAULT_MACHINE_TYPE.to_string(),
+			label: "No default".to_string(),
+			disabled: false,
+		}];
+		if let Some(Ok(data)) = machine_list.get() {
+			options.extend(data.machine_types.into_iter().map(|machine_type| {
+				InputDropdownOption {
+					id: machine_type.id.to_string(),
+					label: format!(
+						"{} vCPU, {} MB RAM",
+						machine_type.data.cpu_count, machine_type.data.memory_count
+					),
+					disabled: false,
+				}
+			}));
+		}
+		options
+	});
+
+	view! {
+		<div class="flex my-xs w-full">
+			<div class="flex-2 flex flex-col items-start justify-start mt-md">
+				<label
+					html_for="defaultMachineType"
+					class="text-white text-sm flex items-center justify-start"
+				>
+					"Default Machine Type"
+				</label>
+				<span class="text-grey">
+					"New deployments use this machine type unless they choose one"
+				</span>
+			</div>
+
+			<div class="flex-10 flex items-start justify-start">
+				<Transition>
+					<InputDropdown
+						class="w-full"
+						placeholder={"Select a machine type".to_string()}
+						options={options}
+						value={selected}
+						loading={Signal::derive(move || {
+							set_default_machine_type.pending().get()
+						})}
+						on_select={move |id: String| {
+							set_default_machine_type.dispatch(Uuid::parse_str(&id).ok());
+						}}
+					/>
+				</Transition>
+			</div>
+		</div>
+	}
+}
+
+/// Splits a comma separated list of egress rules into the individual rules.
+/// The rules themselves are validated by the API
+fn parse_egress_rules(rules: &str) -> Vec<String> {
+	rules
+		.split(',')
+		.map(str::trim)
+		.filter(|rule| !rule.is_empty())
+		.map(str::to_string)
+		.collect()
+}
+
+#[component]
+fn EgressPolicySetting() -> impl IntoView {
+	let egress_policy = get_egress_policy_query();
+	let set_egress_policy = set_egress_policy_query();
+
+	let allow = create_rw_signal(String::new());
+	let deny = create_rw_signal(String::new());
+
+	create_effect(move |_| {
+		if let Some(Ok(response)) = egress_policy.get() {
+			allow.set(response.policy.allow.join(", "));
+			deny.set(response.policy.deny.join(", "));
+		}
+	});
+
+	view! {
+		<div class="flex my-xs w-full">
+			<div class="flex-2 flex flex-col items-start justify-start mt-md">
+				<label
+					html_for="egressAllow"
+					class="text-white text-sm flex items-center justify-start"
+				>
+					"Egress Policy"
+				</label>
+				<span class="text-grey">
+					"The IP ranges (CIDRs) and domains that deployments can connect to. If any are allowed, all other destinations are blocked"
+				</span>
+			</div>
+
+			<div class="flex-10 flex flex-col items-start justify-start gap-xs">
+				<Input
+					r#type={InputType::Text}
+					placeholder="Allowed, e.g. 10.0.0.0/8, api.example.com"
+					class="w-full"
+					id="egressAllow"
+					name="egressAllow"
+					value={Signal::derive(move || allow.get())}
+					on_input={Box::new(move |ev| {
+						ev.prevent_default();
+						allow.set(event_target_value(&ev));
+					})}
+				/>
+				<Input
+					r#type={InputType::Text}
+					placeholder="Denied, e.g. 169.254.169.254/32"
+					class="w-full"
+					id="egressDeny"
+					name="egressDeny"
+					value={Signal::derive(move || deny.get())}
+					on_input={Box::new(move |ev| {
+						ev.prevent_default();
+						deny.set(event_target_value(&ev));
+					})}
+				/>
+				<Link
+					r#type={Variant::Button}
+					style_variant={LinkStyleVariant::Contained}
+					disabled={Signal::derive(move || set_egress_policy.pending().get())}
+					on_click={Rc::new(move |_| {
+						set_egress_policy
+							.dispatch(WorkspaceEgressPolicy {
+								allow: parse_egress_rules(&allow.get_untracked()),
+								deny: parse_egress_rules(&deny.get_untracked()),
+							});
+					})}
+				>
+					"SAVE EGRESS POLICY"
+				</Link>
+			</div>
+		</div>
+	}
+}
 
 #[component]
 fn ShowWorkspaceInfo(
@@ -69,6 +241,10 @@ fn ShowWorkspaceInfo(
 					</div>
 				</div>
 			</div>
+
+			<DefaultMachineTypeSetting />
+
+			<EgressPolicySetting />
 		</div>
 	}
 }
@@ -0,0 +1,52 @@
+use models::api::workspace::database::*;
+
+use crate::prelude::*;
+
+/// Query to get the credentials to connect to a database, along with the
+/// status of their rotation
+pub fn get_database_connection_query(
+	database_id: Signal<Uuid>,
+) -> Resource<
+	(Option<String>, Option<Uuid>, Uuid),
+	Result<GetDatabaseConnectionResponse, ServerFnError<ErrorType>>,
+> {
+	let (state, _) = AuthState::load();
+
+	create_resource(
+		move || {
+			(
+				state.get().get_access_token(),
+				state.get().get_last_used_workspace_id(),
+				database_id.get(),
+			)
+		},
+		move |(access_token, workspace_id, database_id)| async move {
+			get_database_connection(access_token, workspace_id, database_id).await
+		},
+	)
+}
+
+/// Query to rotate the credentials of a database, Returns an action to be
+/// dispatched with the ID of the database and the rotation request. The
+/// previous credentials keep working until the end of the grace period.
+pub fn rotate_database_credentials_query() -> Action<
+	(Uuid, RotateDatabaseCredentialsRequest),
+	Result<RotateDatabaseCredentialsResponse, ServerFnError<ErrorType>>,
+> {
+	let (state, _) = AuthState::load();
+
+	let access_token = state.get().get_access_token();
+	let workspace_id = state.get().get_last_used_workspace_id();
+
+	create_action(
+		move |(database_id, request): &(Uuid, RotateDatabaseCredentialsRequest)| {
+			let access_token = access_token.clone();
+			let database_id = *database_id;
+			let request = request.clone();
+
+			async move {
+				rotate_database_credentials(access_token, workspace_id, database_id, request).await
+			}
+		},
+	)
+}
@@ -0,0 +1,108 @@
+use models::api::workspace::deployment::alert_rule::*;
+
+use crate::prelude::*;
+
+/// Query to list all the alert rules of a deployment
+pub fn list_deployment_alert_rules_query(
+	deployment_id: Signal<Uuid>,
+) -> Resource<
+	(Option<String>, Option<Uuid>, Uuid),
+	Result<ListDeploymentAlertRulesResponse, ServerFnError<ErrorType>>,
+> {
+	let (state, _) = AuthState::load();
+
+	create_resource(
+		move || {
+			(
+				state.get().get_access_token(),
+				state.get().get_last_used_workspace_id(),
+				deployment_id.get(),
+			)
+		},
+		move |(access_token, workspace_id, deployment_id)| async move {
+			list_deployment_alert_rules(access_token, workspace_id, deployment_id).await
+		},
+	)
+}
+
+/// Query to create an alert rule for a deployment, Returns an action to be
+/// dispatched on submit.
+pub fn create_deployment_alert_rule_query() -> Action<
+	(Uuid, CreateDeploymentAlertRuleRequest),
+	Result<CreateDeploymentAlertRuleResponse, ServerFnError<ErrorType>>,
+> {
+	let (state, _) = AuthState::load();
+
+	let access_token = state.get().get_access_token();
+	let workspace_id = state.get().get_last_used_workspace_id();
+
+	create_action(
+		move |(deployment_id, request): &(Uuid, CreateDeploymentAlertRuleRequest)| {
+			let request = request.clone();
+			let access_token = access_token.clone();
+			let deployment_id = *deployment_id;
+
+			async move {
+				create_deployment_alert_rule(access_token, workspace_id, deployment_id, request)
+					.await
+			}
+		},
+	)
+}
+
+/// Query to update an alert rule of a deployment, Returns an action to be
+/// dispatched on submit.
+pub fn update_deployment_alert_rule_query() -> Action<
+	(Uuid, Uuid, UpdateDeploymentAlertRuleRequest),
+	Result<UpdateDeploymentAlertRuleResponse, ServerFnError<ErrorType>>,
+> {
+	let (state, _) = AuthState::load();
+
+	let access_token = state.get().get_access_token();
+	let workspace_id = state.get().get_last_used_workspace_id();
+
+	create_action(
+		move |(deployment_id, alert_rule_id, request): &(
+			Uuid,
+			Uuid,
+			UpdateDeploymentAlertRuleRequest,
+		)| {
+			let request = request.clone();
+			let access_token = access_token.clone();
+			let deployment_id = *deployment_id;
+			let alert_rule_id = *alert_rule_id;
+
+			async move {
+				update_deployment_alert_rule(
+					access_token,
+					workspace_id,
+					deployment_id,
+					alert_rule_id,
+					request,
+				)
+				.await
+			}
+		},
+	)
+}
+
+/// Query to delete an alert rule of a deployment, Returns an action to be
+/// dispatched on submit.
+pub fn delete_deployment_alert_rule_query(
+) -> Action<(Uuid, Uuid), Result<DeleteDeploymentAlertRuleResponse, ServerFnError<ErrorType>>> {
+	let (state, _) = AuthState::load();
+
+	let access_token = state.get().get_access_token();
+	let workspace_id = state.get().get_last_used_workspace_id();
+
+	create_action(move |(deployment_id, alert_rule_id): &(Uuid, Uuid)| {
+		let access_token = access_token.clone();
+		let deployment_id = *deployment_id;
+		let alert_rule_id = *alert_rule_id;
+
+		async move {
+			delete_deployment_alert_rule(access_token, workspace_id, deployment_id, alert_rule_id)
+				.await
+		}
+	})
+}
@@ -1,13 +1,25 @@
 use models::api::workspace::deployment::*;
 use time::OffsetDateTime;
 
+mod alert_rule;
+mod schedule;
+mod template;
+
+pub use self::{alert_rule::*, schedule::*, template::*};
 use crate::prelude::*;
 
-/// Query to list all deployments for a workspace
+/// Query to list all deployments for a workspace, optionally only the ones
+/// that match a label selector
 pub fn list_deployments_query(
 	page: Signal<usize>,
+	label_selector: Signal<Option<DeploymentLabelSelector>>,
 ) -> Resource<
-	(Option<String>, Option<Uuid>, usize),
+	(
+		Option<String>,
+		Option<Uuid>,
+		usize,
+		Option<DeploymentLabelSelector>,
+	),
 	Result<(usize, ListDeploymentResponse), ServerFnError<ErrorType>>,
 > {
 	let (state, _) = AuthState::load();
@@ -18,15 +30,17 @@ pub fn list_deployments_query(
 				state.get().get_access_token(),
 				state.get().get_last_used_workspace_id(),
 				page.get(),
+				label_selector.get(),
 			)
 		},
-		move |(access_token, workspace_id, page)| async move {
+		move |(access_token, workspace_id, page, label_selector)| async move {
 			if let Some(workspace_id) = workspace_id {
 				list_deployments(
 					access_token,
 					workspace_id,
 					Some(page),
 					Some(constants::RESOURCES_PER_PAGE),
+					label_selector,
 				)
 				.await
 			} else {
@@ -60,15 +74,42 @@ pub fn get_deployment_query(
 	)
 }
 
+/// Query to get the effective config of a deployment, with every default
+/// filled in and where the inherited values come from
+pub fn get_effective_deployment_config_query(
+	deployment_id: Signal<Uuid>,
+) -> Resource<
+	(Option<String>, Option<Uuid>, Uuid),
+	Result<GetEffectiveDeploymentConfigResponse, ServerFnError<ErrorType>>,
+> {
+	let (state, _) = AuthState::load();
+	create_resource(
+		move || {
+			(
+				state.get().get_access_token(),
+				state.get().get_last_used_workspace_id(),
+				deployment_id.get(),
+			)
+		},
+		move |(access_token, workspace_id, deployment_id)| async move {
+			get_effective_deployment_config(access_token, workspace_id, deployment_id).await
+		},
+	)
+}
+
 /// Query to create a deployment, Returns an action to be dispatched on submit.
+/// A toast is shown for the result of the action, and on success, the action
+/// will navigate to the created deployment.
 pub fn create_deployment_query(
 ) -> Action<CreateDeploymentRequest, Result<CreateDeploymentResponse, ServerFnError<ErrorType>>> {
 	let (state, _) = AuthState::load();
+	let toaster = expect_toaster();
 
 	let access_token = state.get().get_access_token();
 	let workspace_id = state.get().get_last_used_workspace_id();
 
 	create_action(move |request: &CreateDeploymentRequest| {
+		let toaster = toaster.clone();
 		let request = request.clone();
 		let navigate = use_navigate();
 
@@ -81,6 +122,7 @@ pub fn create_deployment_query(
 				request.clone(),
 			)
 			.await;
+			toaster.toast_result(&response, Some("Deployment created"));
 
 			if let Ok(ref response) = response {
 				navigate(
@@ -95,14 +137,18 @@ pub fn create_deployment_query(
 }
 
 /// Query to delete a deployment, Returns an action to be dispatched on submit.
+/// The deployment can be restored using [`restore_deployment_query`] until it
+/// is permanently removed. A toast is shown for the result of the action.
 pub fn delete_deployment_query(
 ) -> Action<Uuid, Result<DeleteDeploymentResponse, ServerFnError<ErrorType>>> {
 	let (state, _) = AuthState::load();
+	let toaster = expect_toaster();
 
 	let access_token = state.get().get_access_token();
 	let workspace_id = state.get().get_last_used_workspace_id();
 
 	create_action(move |deployment_id: &Uuid| {
+		let toaster = toaster.clone();
 		let navigate = use_navigate();
 		let access_token = access_token.clone();
 
@@ -110,6 +156,7 @@ pub fn delete_deployment_query(
 		async move {
 			let response =
 				delete_deployment(access_token.clone(), workspace_id, deployment_id).await;
+			toaster.toast_result(&response, Some("Deployment deleted"));
 
 			if response.is_ok() {
 				navigate("/deployments", Default::default());
@@ -120,37 +167,190 @@ pub fn delete_deployment_query(
 	})
 }
 
+/// Query to list the deleted deployments of the current workspace that can
+/// still be restored
+pub fn list_deleted_deployments_query(
+	page: Signal<usize>,
+) -> Resource<
+	(Option<String>, Option<Uuid>, usize),
+	Result<(usize, ListDeletedDeploymentsResponse), ServerFnError<ErrorType>>,
+> {
+	let (state, _) = AuthState::load();
+
+	create_resource(
+		move || {
+			(
+				state.get().get_access_token(),
+				state.get().get_last_used_workspace_id(),
+				page.get(),
+			)
+		},
+		move |(access_token, workspace_id, page)| async move {
+			if let Some(workspace_id) = workspace_id {
+				list_deleted_deployments(
+					access_token,
+					workspace_id,
+					Some(page),
+					Some(constants::RESOURCES_PER_PAGE),
+				)
+				.await
+			} else {
+				Err(ServerFnError::WrappedServerError(
+					ErrorType::WrongParameters,
+				))
+			}
+		},
+	)
+}
+
+/// Query to restore a deleted deployment, Returns an action to be dispatched
+/// with the ID of the deployment. A toast is shown for the result of the
+/// action.
+pub fn restore_deployment_query(
+) -> Action<Uuid, Result<RestoreDeploymentResponse, ServerFnError<ErrorType>>> {
+	let (state, _) = AuthState::load();
+	let toaster = expect_toaster();
+
+	let access_token = state.get().get_access_token();
+	let workspace_id = state.get().get_last_used_workspace_id();
+
+	create_action(move |deployment_id: &Uuid| {
+		let toaster = toaster.clone();
+		let access_token = access_token.clone();
+		let deployment_id = *deployment_id;
+
+		async move {
+			let response = match workspace_id {
+				Some(workspace_id) => {
+					restore_deployment(access_token, workspace_id, deployment_id).await
+				}
+				None => Err(ServerFnError::WrappedServerError(
+					ErrorType::WrongParameters,
+				)),
+			};
+			toaster.toast_result(&response, Some("Deployment restored"));
+
+			response
+		}
+	})
+}
+
 /// Query to start a deployment, Returns an action to be dispatched on submit.
+/// A toast is shown for the result of the action.
 pub fn start_deployment_query(
 ) -> Action<Uuid, Result<StartDeploymentResponse, ServerFnError<ErrorType>>> {
 	let (state, _) = AuthState::load();
+	let toaster = expect_toaster();
 
 	let access_token = state.get().get_access_token();
 	let workspace_id = state.get().get_last_used_workspace_id().unwrap();
 
 	create_action(move |deployment_id: &Uuid| {
+		let toaster = toaster.clone();
 		let access_token = access_token.clone();
 
 		let deployment_id = deployment_id.clone();
 
-		async move { start_deployment(access_token, workspace_id, deployment_id).await }
+		async move {
+			let response = start_deployment(access_token, workspace_id, deployment_id).await;
+			toaster.toast_result(&response, Some("Deployment started"));
+
+			response
+		}
 	})
 }
 
+/// Query to promote a deployment to another deployment, Returns an action to be
+/// dispatched with the target deployment ID and the promotion request.
+pub fn promote_deployment_query() -> Action<
+	(Uuid, PromoteDeploymentRequest),
+	Result<PromoteDeploymentResponse, ServerFnError<ErrorType>>,
+> {
+	let (state, _) = AuthState::load();
+
+	let access_token = state.get().get_access_token();
+	let workspace_id = state.get().get_last_used_workspace_id();
+
+	create_action(
+		move |(deployment_id, request): &(Uuid, PromoteDeploymentRequest)| {
+			let access_token = access_token.clone();
+			let deployment_id = *deployment_id;
+			let request = request.clone();
+
+			async move {
+				let workspace_id = workspace_id.ok_or(ServerFnError::WrappedServerError(
+					ErrorType::WrongParameters,
+				))?;
+
+				promote_deployment(access_token, workspace_id, deployment_id, request).await
+			}
+		},
+	)
+}
+
 /// Query to stop a deployment, Returns an action to be dispatched on submit.
+/// A toast is shown for the result of the action.
 pub fn stop_deployment_query(
 ) -> Action<Uuid, Result<StopDeploymentResponse, ServerFnError<ErrorType>>> {
 	let (state, _) = AuthState::load();
+	let toaster = expect_toaster();
 
 	let access_token = state.get().get_access_token();
 	let workspace_id = state.get().get_last_used_workspace_id().unwrap();
 
 	create_action(move |deployment_id: &Uuid| {
+		let toaster = toaster.clone();
 		let access_token = access_token.clone();
 
 		let deployment_id = deployment_id.clone();
 
-		async move { stop_deployment(access_token, workspace_id, deployment_id).await }
+		async move {
+			let response = stop_deployment(access_token, workspace_id, deployment_id).await;
+			toaster.toast_result(&response, Some("Deployment stopped"));
+
+			response
+		}
+	})
+}
+
+/// Query to force the runner of a deployment to reconcile it again, Returns an
+/// action to be dispatched on click.
+pub fn reconcile_deployment_query(
+) -> Action<Uuid, Result<ReconcileDeploymentResponse, ServerFnError<ErrorType>>> {
+	let (state, _) = AuthState::load();
+
+	let access_token = state.get().get_access_token();
+	let workspace_id = state.get().get_last_used_workspace_id();
+
+	create_action(move |deployment_id: &Uuid| {
+		let access_token = access_token.clone();
+		let deployment_id = *deployment_id;
+
+		async move {
+			let workspace_id = workspace_id.ok_or(ServerFnError::WrappedServerError(
+				ErrorType::WrongParameters,
+			))?;
+
+			reconcile_deployment(access_token, workspace_id, deployment_id).await
+		}
+	})
+}
+
+/// Query to test whether an exposed port of a deployment is reachable, Returns
+/// an action to be dispatched with the deployment ID and the port to test.
+pub fn test_deployment_port_query(
+) -> Action<(Uuid, u16), Result<TestDeploymentPortResponse, ServerFnError<ErrorType>>> {
+	let (state, _) = AuthState::load();
+
+	let access_token = state.get().get_access_token();
+	let workspace_id = state.get().get_last_used_workspace_id();
+
+	create_action(move |(deployment_id, port): &(Uuid, u16)| {
+		let access_token = access_token.clone();
+		let deployment_id = *deployment_id;
+		let port = *port;
+
+		async move { test_deployment_port(access_token, workspace_id, deployment_id, port).await }
 	})
 }
 
@@ -164,6 +364,53 @@ pub fn list_machines_query(
 	)
 }
 
+/// Query to get the default machine type of the workspace, used for new
+/// deployments that don't choose one
+pub fn get_default_machine_type_query() -> Resource<
+	(Option<String>, Option<Uuid>),
+	Result<GetDefaultDeploymentMachineTypeResponse, ServerFnError<ErrorType>>,
+> {
+	let (state, _) = AuthState::load();
+	create_resource(
+		move || {
+			(
+				state.get().get_access_token(),
+				state.get().get_last_used_workspace_id(),
+			)
+		},
+		move |(access_token, workspace_id)| async move {
+			get_default_machine_type(access_token, workspace_id).await
+		},
+	)
+}
+
+/// Query to set the default machine type of the workspace, Returns an action
+/// to be dispatched with the machine type, or `None` to remove the default. A
+/// toast is shown for the result of the action.
+pub fn set_default_machine_type_query(
+) -> Action<Option<Uuid>, Result<SetDefaultDeploymentMachineTypeResponse, ServerFnError<ErrorType>>>
+{
+	let (state, _) = AuthState::load();
+	let toaster = expect_toaster();
+
+	let access_token = state.get().get_access_token();
+	let workspace_id = state.get().get_last_used_workspace_id();
+
+	create_action(move |machine_type_id: &Option<Uuid>| {
+		let toaster = toaster.clone();
+		let access_token = access_token.clone();
+		let machine_type_id = *machine_type_id;
+
+		async move {
+			let response =
+				set_default_machine_type(access_token, workspace_id, machine_type_id).await;
+			toaster.toast_result(&response, Some("Default machine type updated"));
+
+			response
+		}
+	})
+}
+
 /// Query to get the running logs of a deployment
 pub fn get_deployment_logs_query(
 	deployment_id: Signal<Uuid>,
@@ -188,14 +435,64 @@ pub fn get_deployment_logs_query(
 			)
 		},
 		move |(access_token, workspace_id, deployment_id, end_time)| async move {
-			get_deployment_logs(access_token, workspace_id, deployment_id, end_time, limit).await
+			get_deployment_logs(
+				access_token,
+				workspace_id,
+				deployment_id,
+				end_time,
+				limit,
+				None,
+			)
+			.await
+		},
+	)
+}
+
+/// Query to download the logs of a deployment for a time range and save them as
+/// a file, Returns an action to be dispatched on click.
+pub fn download_deployment_logs_query() -> Action<
+	(Uuid, OffsetDateTime, DeploymentLogFormat),
+	Result<DownloadedDeploymentLogs, ServerFnError<ErrorType>>,
+> {
+	let (state, _) = AuthState::load();
+
+	let access_token = state.get().get_access_token();
+	let workspace_id = state.get().get_last_used_workspace_id();
+
+	create_action(
+		move |(deployment_id, start_time, format): &(Uuid, OffsetDateTime, DeploymentLogFormat)| {
+			let access_token = access_token.clone();
+			let (deployment_id, start_time, format) = (*deployment_id, *start_time, *format);
+
+			async move {
+				let logs = download_deployment_logs(
+					access_token,
+					workspace_id,
+					deployment_id,
+					start_time,
+					None,
+					format,
+				)
+				.await?;
+
+				if let Err(err) = download_file(&logs.file_name, &logs.content_type, &logs.content)
+				{
+					logging::error!("Failed to save logs: {:?}", err);
+				}
+
+				Ok(logs)
+			}
 		},
 	)
 }
 
-/// Query to update a deployment, Returns an action to be dispatched on submit.
+/// Query to update a deployment, Returns an action to be dispatched on submit
+/// with the deployment ID, the time the deployment was last updated at and the
+/// fields to update. Only the fields that are set in the request are updated,
+/// and the update is rejected if the deployment was modified since it was last
+/// updated at the given time.
 pub fn update_deployment_query() -> Action<
-	(Uuid, UpdateDeploymentRequest),
+	(Uuid, OffsetDateTime, UpdateDeploymentRequest),
 	Result<UpdateDeploymentResponse, ServerFnError<ErrorType>>,
 > {
 	let (state, _) = AuthState::load();
@@ -204,8 +501,13 @@ pub fn update_deployment_query() -> Action<
 	let workspace_id = state.get().get_last_used_workspace_id();
 
 	create_action(
-		move |(deployment_id, request): &(Uuid, UpdateDeploymentRequest)| {
+		move |(deployment_id, last_updated_at, request): &(
+			Uuid,
+			OffsetDateTime,
+			UpdateDeploymentRequest,
+		)| {
 			let request = request.clone();
+			let last_updated_at = *last_updated_at;
 
 			let access_token = access_token.clone();
 			let deployment_id = deployment_id.clone();
@@ -215,6 +517,7 @@ pub fn update_deployment_query() -> Action<
 					access_token.clone(),
 					workspace_id,
 					Some(deployment_id),
+					Some(last_updated_at),
 					request.clone(),
 				)
 				.await
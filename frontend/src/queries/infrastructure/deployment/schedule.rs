@@ -0,0 +1,106 @@
+use models::api::workspace::deployment::schedule::*;
+
+use crate::prelude::*;
+
+/// Query to list all the schedules of a deployment
+pub fn list_deployment_schedules_query(
+	deployment_id: Signal<Uuid>,
+) -> Resource<
+	(Option<String>, Option<Uuid>, Uuid),
+	Result<ListDeploymentSchedulesResponse, ServerFnError<ErrorType>>,
+> {
+	let (state, _) = AuthState::load();
+
+	create_resource(
+		move || {
+			(
+				state.get().get_access_token(),
+				state.get().get_last_used_workspace_id(),
+				deployment_id.get(),
+			)
+		},
+		move |(access_token, workspace_id, deployment_id)| async move {
+			list_deployment_schedules(access_token, workspace_id, deployment_id).await
+		},
+	)
+}
+
+/// Query to create a schedule for a deployment, Returns an action to be
+/// dispatched on submit.
+pub fn create_deployment_schedule_query() -> Action<
+	(Uuid, CreateDeploymentScheduleRequest),
+	Result<CreateDeploymentScheduleResponse, ServerFnError<ErrorType>>,
+> {
+	let (state, _) = AuthState::load();
+
+	let access_token = state.get().get_access_token();
+	let workspace_id = state.get().get_last_used_workspace_id();
+
+	create_action(
+		move |(deployment_id, request): &(Uuid, CreateDeploymentScheduleRequest)| {
+			let request = request.clone();
+			let access_token = access_token.clone();
+			let deployment_id = *deployment_id;
+
+			async move {
+				create_deployment_schedule(access_token, workspace_id, deployment_id, request).await
+			}
+		},
+	)
+}
+
+/// Query to update a schedule of a deployment, Returns an action to be
+/// dispatched on submit.
+pub fn update_deployment_schedule_query() -> Action<
+	(Uuid, Uuid, UpdateDeploymentScheduleRequest),
+	Result<UpdateDeploymentScheduleResponse, ServerFnError<ErrorType>>,
+> {
+	let (state, _) = AuthState::load();
+
+	let access_token = state.get().get_access_token();
+	let workspace_id = state.get().get_last_used_workspace_id();
+
+	create_action(
+		move |(deployment_id, schedule_id, request): &(
+			Uuid,
+			Uuid,
+			UpdateDeploymentScheduleRequest,
+		)| {
+			let request = request.clone();
+			let access_token = access_token.clone();
+			let deployment_id = *deployment_id;
+			let schedule_id = *schedule_id;
+
+			async move {
+				update_deployment_schedule(
+					access_token,
+					workspace_id,
+					deployment_id,
+					schedule_id,
+					request,
+				)
+				.await
+			}
+		},
+	)
+}
+
+/// Query to delete a schedule of a deployment, Returns an action to be
+/// dispatched on submit.
+pub fn delete_deployment_schedule_query(
+) -> Action<(Uuid, Uuid), Result<DeleteDeploymentScheduleResponse, ServerFnError<ErrorType>>> {
+	let (state, _) = AuthState::load();
+
+	let access_token = state.get().get_access_token();
+	let workspace_id = state.get().get_last_used_workspace_id();
+
+	create_action(move |(deployment_id, schedule_id): &(Uuid, Uuid)| {
+		let access_token = access_token.clone();
+		let deployment_id = *deployment_id;
+		let schedule_id = *schedule_id;
+
+		async move {
+			delete_deployment_schedule(access_token, workspace_id, deployment_id, schedule_id).await
+		}
+	})
+}
@@ -0,0 +1,92 @@
+use models::api::workspace::deployment::template::*;
+
+use crate::prelude::*;
+
+/// Query to list all deployment templates for a workspace
+pub fn list_deployment_templates_query(
+	page: Signal<usize>,
+) -> Resource<
+	(Option<String>, Option<Uuid>, usize),
+	Result<(usize, ListDeploymentTemplatesResponse), ServerFnError<ErrorType>>,
+> {
+	let (state, _) = AuthState::load();
+
+	create_resource(
+		move || {
+			(
+				state.get().get_access_token(),
+				state.get().get_last_used_workspace_id(),
+				page.get(),
+			)
+		},
+		move |(access_token, workspace_id, page)| async move {
+			list_deployment_templates(
+				access_token,
+				workspace_id,
+				Some(page),
+				Some(constants::RESOURCES_PER_PAGE),
+			)
+			.await
+		},
+	)
+}
+
+/// Query to create a deployment template, Returns an action to be dispatched
+/// on submit.
+pub fn create_deployment_template_query() -> Action<
+	CreateDeploymentTemplateRequest,
+	Result<CreateDeploymentTemplateResponse, ServerFnError<ErrorType>>,
+> {
+	let (state, _) = AuthState::load();
+
+	let access_token = state.get().get_access_token();
+	let workspace_id = state.get().get_last_used_workspace_id();
+
+	create_action(move |request: &CreateDeploymentTemplateRequest| {
+		let request = request.clone();
+		let access_token = access_token.clone();
+
+		async move { create_deployment_template(access_token, workspace_id, request).await }
+	})
+}
+
+/// Query to update a deployment template, Returns an action to be dispatched
+/// on submit.
+pub fn update_deployment_template_query() -> Action<
+	(Uuid, UpdateDeploymentTemplateRequest),
+	Result<UpdateDeploymentTemplateResponse, ServerFnError<ErrorType>>,
+> {
+	let (state, _) = AuthState::load();
+
+	let access_token = state.get().get_access_token();
+	let workspace_id = state.get().get_last_used_workspace_id();
+
+	create_action(
+		move |(template_id, request): &(Uuid, UpdateDeploymentTemplateRequest)| {
+			let request = request.clone();
+			let access_token = access_token.clone();
+			let template_id = *template_id;
+
+			async move {
+				update_deployment_template(access_token, workspace_id, template_id, request).await
+			}
+		},
+	)
+}
+
+/// Query to delete a deployment template, Returns an action to be dispatched
+/// on submit.
+pub fn delete_deployment_template_query(
+) -> Action<Uuid, Result<DeleteDeploymentTemplateResponse, ServerFnError<ErrorType>>> {
+	let (state, _) = AuthState::load();
+
+	let access_token = state.get().get_access_token();
+	let workspace_id = state.get().get_last_used_workspace_id();
+
+	create_action(move |template_id: &Uuid| {
+		let access_token = access_token.clone();
+		let template_id = *template_id;
+
+		async move { delete_deployment_template(access_token, workspace_id, template_id).await }
+	})
+}
@@ -0,0 +1,65 @@
+use models::api::workspace::managed_url::*;
+
+use crate::prelude::*;
+
+/// Query to list the managed URLs of a workspace
+pub fn list_managed_urls_query() -> Resource<
+	(Option<String>, Option<Uuid>),
+	Result<ListManagedURLResponse, ServerFnError<ErrorType>>,
+> {
+	let (state, _) = AuthState::load();
+
+	create_resource(
+		move || {
+			(
+				state.get().get_access_token(),
+				state.get().get_last_used_workspace_id(),
+			)
+		},
+		move |(access_token, workspace_id)| async move {
+			list_managed_urls(workspace_id, access_token).await
+		},
+	)
+}
+
+/// Query to create a managed URL of any type, including path maps, Returns an
+/// action to be dispatched on submit.
+pub fn create_managed_url_query(
+) -> Action<CreateManagedURLRequest, Result<CreateManagedURLResponse, ServerFnError<ErrorType>>> {
+	let (state, _) = AuthState::load();
+
+	let access_token = state.get().get_access_token();
+	let workspace_id = state.get().get_last_used_workspace_id();
+
+	create_action(move |request: &CreateManagedURLRequest| {
+		let access_token = access_token.clone();
+		let request = request.clone();
+
+		async move { create_managed_url_with_rules(access_token, workspace_id, request).await }
+	})
+}
+
+/// Query to update the type and the path rules of a managed URL, Returns an
+/// action to be dispatched with the ID of the managed URL and the update
+/// request.
+pub fn update_managed_url_query() -> Action<
+	(Uuid, UpdateManagedURLRequest),
+	Result<UpdateManagedURLResponse, ServerFnError<ErrorType>>,
+> {
+	let (state, _) = AuthState::load();
+
+	let access_token = state.get().get_access_token();
+	let workspace_id = state.get().get_last_used_workspace_id();
+
+	create_action(
+		move |(managed_url_id, request): &(Uuid, UpdateManagedURLRequest)| {
+			let access_token = access_token.clone();
+			let managed_url_id = *managed_url_id;
+			let request = request.clone();
+
+			async move {
+				update_managed_url_rules(access_token, workspace_id, managed_url_id, request).await
+			}
+		},
+	)
+}
@@ -1,3 +1,6 @@
+mod database;
 mod deployment;
+mod managed_url;
+mod volume;
 
-pub use self::deployment::*;
+pub use self::{database::*, deployment::*, managed_url::*, volume::*};
@@ -0,0 +1,107 @@
+use models::api::workspace::volume::*;
+
+use crate::prelude::*;
+
+/// Query to list all volumes for a workspace
+pub fn list_volumes_query(
+	page: Signal<usize>,
+) -> Resource<
+	(Option<String>, Option<Uuid>, usize),
+	Result<(usize, ListVolumesInWorkspaceResponse), ServerFnError<ErrorType>>,
+> {
+	let (state, _) = AuthState::load();
+
+	create_resource(
+		move || {
+			(
+				state.get().get_access_token(),
+				state.get().get_last_used_workspace_id(),
+				page.get(),
+			)
+		},
+		move |(access_token, workspace_id, page)| async move {
+			list_volumes(
+				access_token,
+				workspace_id,
+				Some(page),
+				Some(constants::RESOURCES_PER_PAGE),
+			)
+			.await
+		},
+	)
+}
+
+/// Query to get a volume by id
+pub fn get_volume_query(
+	volume_id: Signal<Uuid>,
+) -> Resource<
+	(Option<String>, Option<Uuid>, Uuid),
+	Result<GetVolumeInfoResponse, ServerFnError<ErrorType>>,
+> {
+	let (state, _) = AuthState::load();
+
+	create_resource(
+		move || {
+			(
+				state.get().get_access_token(),
+				state.get().get_last_used_workspace_id(),
+				volume_id.get(),
+			)
+		},
+		move |(access_token, workspace_id, volume_id)| async move {
+			get_volume(access_token, workspace_id, volume_id).await
+		},
+	)
+}
+
+/// Query to create a volume, Returns an action to be dispatched on submit.
+pub fn create_volume_query(
+) -> Action<CreateVolumeRequest, Result<CreateVolumeResponse, ServerFnError<ErrorType>>> {
+	let (state, _) = AuthState::load();
+
+	let access_token = state.get().get_access_token();
+	let workspace_id = state.get().get_last_used_workspace_id();
+
+	create_action(move |request: &CreateVolumeRequest| {
+		let access_token = access_token.clone();
+		let request = request.clone();
+
+		async move { create_volume(access_token, workspace_id, request).await }
+	})
+}
+
+/// Query to update a volume, Returns an action to be dispatched with the ID of
+/// the volume and the update request.
+pub fn update_volume_query(
+) -> Action<(Uuid, UpdateVolumeRequest), Result<UpdateVolumeResponse, ServerFnError<ErrorType>>> {
+	let (state, _) = AuthState::load();
+
+	let access_token = state.get().get_access_token();
+	let workspace_id = state.get().get_last_used_workspace_id();
+
+	create_action(move |(volume_id, request): &(Uuid, UpdateVolumeRequest)| {
+		let access_token = access_token.clone();
+		let volume_id = *volume_id;
+		let request = request.clone();
+
+		async move { update_volume(access_token, workspace_id, volume_id, request).await }
+	})
+}
+
+/// Query to delete a volume, Returns an action to be dispatched with the ID of
+/// the volume. Volumes that are still mounted by a deployment cannot be
+/// deleted.
+pub fn delete_volume_query() -> Action<Uuid, Result<DeleteVolumeResponse, ServerFnError<ErrorType>>>
+{
+	let (state, _) = AuthState::load();
+
+	let access_token = state.get().get_access_token();
+	let workspace_id = state.get().get_last_used_workspace_id();
+
+	create_action(move |volume_id: &Uuid| {
+		let access_token = access_token.clone();
+		let volume_id = *volume_id;
+
+		async move { delete_volume(access_token, workspace_id, volume_id).await }
+	})
+}
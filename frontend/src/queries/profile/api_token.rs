@@ -2,15 +2,35 @@ use models::api::{user::*, workspace::rbac::ListAllPermissionsResponse};
 
 use crate::prelude::*;
 
-/// Query to list all API tokens
+/// Query to list all API tokens, optionally filtered by their status and
+/// sorted by the given field
 pub fn list_api_tokens_query(
-) -> Resource<Option<String>, Result<ListApiTokensResponse, ServerFnError<ErrorType>>> {
+	status: Signal<Option<ApiTokenStatus>>,
+	order_by: Signal<Option<ApiTokenSortBy>>,
+	order: Signal<Option<ListOrder>>,
+) -> Resource<
+	(
+		Option<String>,
+		Option<ApiTokenStatus>,
+		Option<ApiTokenSortBy>,
+		Option<ListOrder>,
+	),
+	Result<ListApiTokensResponse, ServerFnError<ErrorType>>,
+> {
 	let (state, _) = AuthState::load();
-	let access_token = state.get().get_access_token();
 
 	create_resource(
-		move || state.get().get_access_token(),
-		move |access_token| async move { load_api_tokens_list(access_token).await },
+		move || {
+			(
+				state.get().get_access_token(),
+				status.get(),
+				order_by.get(),
+				order.get(),
+			)
+		},
+		move |(access_token, status, order_by, order)| async move {
+			load_api_tokens_list(access_token, status, order_by, order).await
+		},
 	)
 }
 
@@ -26,9 +46,12 @@ pub fn get_api_token_query(
 	)
 }
 
-/// Query to get all permissions
-pub fn get_all_permissions_query() -> Resource<
-	(Option<String>, Option<Uuid>),
+/// Query to get all permissions, optionally filtered by a search term that is
+/// matched against the name and description of each permission
+pub fn get_all_permissions_query(
+	search: Signal<Option<String>>,
+) -> Resource<
+	(Option<String>, Option<Uuid>, Option<String>),
 	Result<ListAllPermissionsResponse, ServerFnError<ErrorType>>,
 > {
 	let (state, _) = AuthState::load();
@@ -38,11 +61,12 @@ pub fn get_all_permissions_query() -> Resource<
 			(
 				state.get().get_access_token(),
 				state.get().get_last_used_workspace_id(),
+				search.get(),
 			)
 		},
-		move |(access_token, workspace_id)| async move {
+		move |(access_token, workspace_id, search)| async move {
 			if let Some(workspace_id) = workspace_id {
-				list_all_permissions(access_token, workspace_id).await
+				list_all_permissions(access_token, workspace_id, search, None).await
 			} else {
 				Err(ServerFnError::WrappedServerError(ErrorType::Unauthorized))
 			}
@@ -63,3 +87,48 @@ pub fn create_api_token_query(
 		async move { create_api_token(access_token.clone(), request.clone()).await }
 	})
 }
+
+/// Query to revoke multiple API tokens at once. A toast is shown with the
+/// number of tokens that were revoked, along with an error toast if any of
+/// them couldn't be revoked.
+pub fn bulk_revoke_api_tokens_query(
+) -> Action<Vec<Uuid>, Result<BulkRevokeApiTokensResponse, ServerFnError<ErrorType>>> {
+	let (state, _) = AuthState::load();
+	let toaster = expect_toaster();
+	let access_token = state.get().get_access_token();
+
+	create_action(move |token_ids: &Vec<Uuid>| {
+		let toaster = toaster.clone();
+		let access_token = access_token.clone();
+		let token_ids = token_ids.clone();
+
+		async move {
+			let response = bulk_revoke_api_tokens(access_token, token_ids).await;
+
+			match &response {
+				Ok(BulkRevokeApiTokensResponse { results }) => {
+					let failed = results
+						.iter()
+						.filter(|result| result.error.is_some())
+						.count();
+					let revoked = results.len() - failed;
+
+					if revoked > 0 {
+						toaster.success(&format!("{revoked} token(s) revoked"));
+					}
+					if failed > 0 {
+						toaster.toast(
+							ToastData::builder()
+								.message(&format!("{failed} token(s) could not be revoked"))
+								.level(AlertType::Error)
+								.expiry(Some(5_000)),
+						);
+					}
+				}
+				Err(error) => toaster.error(error),
+			}
+
+			response
+		}
+	})
+}
@@ -1,3 +1,4 @@
 mod api_token;
+mod recovery_email;
 
-pub use self::api_token::*;
+pub use self::{api_token::*, recovery_email::*};
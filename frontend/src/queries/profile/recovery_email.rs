@@ -0,0 +1,47 @@
+use models::api::user::*;
+
+use crate::prelude::*;
+
+/// Query to request a change of the recovery email of the user. Returns an
+/// action to be dispatched with the new email.
+pub fn change_recovery_email_query(
+) -> Action<String, Result<ChangeRecoveryEmailResponse, ServerFnError<ErrorType>>> {
+	let (state, _) = AuthState::load();
+	let access_token = state.get().get_access_token();
+
+	create_action(move |email: &String| {
+		let access_token = access_token.clone();
+		let email = email.clone();
+
+		async move { change_recovery_email(access_token, email).await }
+	})
+}
+
+/// Query to verify the pending recovery email of the user. Returns an action
+/// to be dispatched with the pending email and the OTP that was sent to it.
+pub fn verify_recovery_email_query(
+) -> Action<(String, String), Result<VerifyUserEmailResponse, ServerFnError<ErrorType>>> {
+	let (state, _) = AuthState::load();
+	let access_token = state.get().get_access_token();
+
+	create_action(move |(email, verification_token): &(String, String)| {
+		let access_token = access_token.clone();
+		let email = email.clone();
+		let verification_token = verification_token.clone();
+
+		async move { verify_recovery_email(access_token, email, verification_token).await }
+	})
+}
+
+/// Query to cancel the pending change of the recovery email of the user
+pub fn cancel_recovery_email_change_query(
+) -> Action<(), Result<CancelRecoveryEmailChangeResponse, ServerFnError<ErrorType>>> {
+	let (state, _) = AuthState::load();
+	let access_token = state.get().get_access_token();
+
+	create_action(move |_: &()| {
+		let access_token = access_token.clone();
+
+		async move { cancel_recovery_email_change(access_token).await }
+	})
+}
@@ -2,8 +2,45 @@ use models::api::workspace::runner::*;
 
 use crate::prelude::*;
 
-/// Query to list all runners for a workspace
-pub fn list_runners_query() -> Resource<
+/// Query to list the runners for a workspace, one page at a time. Both online
+/// and offline runners are listed. Returns the total number of runners along
+/// with the runners in the page
+pub fn list_runners_query(
+	page: Signal<usize>,
+) -> Resource<
+	(Option<String>, Option<Uuid>, usize),
+	Result<(usize, ListRunnersForWorkspaceResponse), ServerFnError<ErrorType>>,
+> {
+	let (state, _) = AuthState::load();
+
+	create_resource(
+		move || {
+			(
+				state.get().get_access_token(),
+				state.get().get_last_used_workspace_id(),
+				page.get(),
+			)
+		},
+		move |(access_token, workspace_id, page)| async move {
+			if let Some(workspace_id) = workspace_id {
+				list_runners(
+					access_token,
+					workspace_id,
+					Some(page),
+					Some(constants::RESOURCES_PER_PAGE),
+					None,
+				)
+				.await
+			} else {
+				Err(ServerFnError::WrappedServerError(ErrorType::Unauthorized))
+			}
+		},
+	)
+}
+
+/// Query to list all the runners for a workspace, across every page. Used by
+/// the dropdowns that let the user pick a runner
+pub fn list_all_runners_query() -> Resource<
 	(Option<String>, Option<Uuid>),
 	Result<ListRunnersForWorkspaceResponse, ServerFnError<ErrorType>>,
 > {
@@ -18,7 +55,7 @@ pub fn list_runners_query() -> Resource<
 		},
 		move |(access_token, workspace_id)| async move {
 			if let Some(workspace_id) = workspace_id {
-				list_runners(access_token, workspace_id).await
+				list_all_runners(access_token, workspace_id, None).await
 			} else {
 				Err(ServerFnError::WrappedServerError(ErrorType::Unauthorized))
 			}
@@ -50,15 +87,17 @@ pub fn get_runner_query(
 
 /// Query to create a runner, Returns an action to be dispatched on submit.
 /// The action will navigate to the created runner and invalidate the runners
-/// list.
+/// list. A toast is shown for the result of the action.
 pub fn create_runner_query(
 ) -> Action<String, Result<AddRunnerToWorkspaceResponse, ServerFnError<ErrorType>>> {
 	let (state, _) = AuthState::load();
+	let toaster = expect_toaster();
 
 	let access_token = state.get().get_access_token();
 	let workspace_id = state.get().get_last_used_workspace_id();
 
 	create_action(move |runner_name: &String| {
+		let toaster = toaster.clone();
 		let navigate = use_navigate();
 
 		let access_token = access_token.clone();
@@ -66,6 +105,7 @@ pub fn create_runner_query(
 
 		async move {
 			let response = create_runner(access_token, workspace_id, runner_name).await;
+			toaster.toast_result(&response, Some("Runner created"));
 
 			if let Ok(ref response) = response {
 				navigate(
@@ -81,15 +121,17 @@ pub fn create_runner_query(
 
 /// Query to delete a runner, Returns an action to be dispatched on submit.
 /// The action will navigate to the runners list and invalidate the runners
-/// list and the runner.
+/// list and the runner. A toast is shown for the result of the action.
 pub fn delete_runner_query() -> Action<Uuid, Result<DeleteRunnerResponse, ServerFnError<ErrorType>>>
 {
 	let (state, _) = AuthState::load();
+	let toaster = expect_toaster();
 
 	let access_token = state.get().get_access_token();
 	let workspace_id = state.get().get_last_used_workspace_id();
 
 	create_action(move |runner_id: &Uuid| {
+		let toaster = toaster.clone();
 		let navigate = use_navigate();
 
 		let access_token = access_token.clone();
@@ -97,6 +139,7 @@ pub fn delete_runner_query() -> Action<Uuid, Result<DeleteRunnerResponse, Server
 
 		async move {
 			let response = delete_runner(access_token, workspace_id, runner_id).await;
+			toaster.toast_result(&response, Some("Runner deleted"));
 
 			if let Ok(_) = response {
 				navigate("/runners", Default::default());
@@ -1,6 +1,44 @@
-use models::api::{user::ListUserWorkspacesResponse, workspace::GetWorkspaceInfoResponse};
+use models::api::{
+	user::{ApiTokenStatus, ListUserWorkspacesResponse},
+	workspace::{
+		egress_policy::{GetEgressPolicyResponse, SetEgressPolicyResponse, WorkspaceEgressPolicy},
+		rbac::{
+			role::{
+				ExportWorkspaceRolesResponse,
+				ImportWorkspaceRolesRequest,
+				ImportWorkspaceRolesResponse,
+			},
+			CheckPermissionsRequest,
+			CheckPermissionsResponse,
+		},
+		GetApiUsageResponse,
+		GetFeatureFlagsResponse,
+		GetWorkspaceInfoResponse,
+		ListWorkspaceActivityResponse,
+		ListWorkspaceApiTokensResponse,
+		RevokeWorkspaceApiTokenResponse,
+		SearchWorkspaceResourcesResponse,
+		WorkspaceActivityCategory,
+	},
+};
+use time::OffsetDateTime;
 
-use crate::{get_workspace_info, list_user_workspace, prelude::*};
+use crate::{
+	check_permissions,
+	export_workspace_roles,
+	get_api_usage,
+	get_egress_policy,
+	get_feature_flags,
+	get_workspace_info,
+	import_workspace_roles,
+	list_user_workspace,
+	list_workspace_activity,
+	list_workspace_api_tokens,
+	prelude::*,
+	revoke_workspace_api_token,
+	search_workspace_resources,
+	set_egress_policy,
+};
 
 /// Query to list all workspaces
 pub fn list_workspaces_query(
@@ -17,7 +55,9 @@ pub fn list_workspaces_query(
 	)
 }
 
-/// Query to get a workspace
+/// Query to get a workspace. The response also says whether the current user
+/// is the super admin of the workspace, which is used to only show destructive
+/// actions (like deleting the workspace) to the super admin
 pub fn get_workspace_query(
 	workspace_id: Signal<Uuid>,
 ) -> Resource<(Option<String>, Uuid), Result<GetWorkspaceInfoResponse, ServerFnError<ErrorType>>> {
@@ -33,3 +73,308 @@ pub fn get_workspace_query(
 		},
 	)
 }
+
+/// Query to check which of the given permissions the current user has in the
+/// current workspace. Used to hide actions that the user is not authorized to
+/// perform, instead of rendering buttons that fail when clicked.
+pub fn check_permissions_query(
+	permissions: Vec<Permission>,
+	resource_id: Option<Uuid>,
+) -> Resource<
+	(Option<String>, Option<Uuid>),
+	Result<CheckPermissionsResponse, ServerFnError<ErrorType>>,
+> {
+	let (state, _) = AuthState::load();
+
+	create_resource(
+		move || {
+			(
+				state.get().get_access_token(),
+				state.get().get_last_used_workspace_id(),
+			)
+		},
+		move |(access_token, workspace_id)| {
+			let request = CheckPermissionsRequest {
+				permissions: permissions.clone(),
+				resource_id,
+			};
+			async move {
+				let workspace_id = workspace_id.ok_or(ServerFnError::WrappedServerError(
+					ErrorType::WrongParameters,
+				))?;
+				check_permissions(access_token, workspace_id, request).await
+			}
+		},
+	)
+}
+
+/// Query to get the API usage of the current workspace, used by the analytics
+/// page. Refetches whenever the time range or the endpoint filter changes.
+pub fn get_api_usage_query(
+	start_time: Signal<OffsetDateTime>,
+	end_time: Signal<Option<OffsetDateTime>>,
+	endpoint: Signal<Option<String>>,
+) -> Resource<
+	(
+		Option<String>,
+		Option<Uuid>,
+		OffsetDateTime,
+		Option<OffsetDateTime>,
+		Option<String>,
+	),
+	Result<GetApiUsageResponse, ServerFnError<ErrorType>>,
+> {
+	let (state, _) = AuthState::load();
+
+	create_resource(
+		move || {
+			(
+				state.get().get_access_token(),
+				state.get().get_last_used_workspace_id(),
+				start_time.get(),
+				end_time.get(),
+				endpoint.get(),
+			)
+		},
+		move |(access_token, workspace_id, start_time, end_time, endpoint)| async move {
+			get_api_usage(access_token, workspace_id, start_time, end_time, endpoint).await
+		},
+	)
+}
+
+/// Query to search for resources in the current workspace by their name, used
+/// by the search palette. Refetches whenever the search query changes, and
+/// doesn't make a request while the query is empty.
+pub fn search_workspace_resources_query(
+	query: Signal<String>,
+) -> Resource<
+	(Option<String>, Option<Uuid>, String),
+	Result<SearchWorkspaceResourcesResponse, ServerFnError<ErrorType>>,
+> {
+	let (state, _) = AuthState::load();
+
+	create_resource(
+		move || {
+			(
+				state.get().get_access_token(),
+				state.get().get_last_used_workspace_id(),
+				query.get().trim().to_string(),
+			)
+		},
+		move |(access_token, workspace_id, query)| async move {
+			if query.is_empty() {
+				return Ok(SearchWorkspaceResourcesResponse { results: vec![] });
+			}
+
+			search_workspace_resources(access_token, workspace_id, query).await
+		},
+	)
+}
+
+/// Query to get the feature flags of the current workspace. Refetches whenever
+/// the user logs in or switches to a different workspace.
+pub fn get_feature_flags_query() -> Resource<
+	(Option<String>, Option<Uuid>),
+	Result<GetFeatureFlagsResponse, ServerFnError<ErrorType>>,
+> {
+	let (state, _) = AuthState::load();
+
+	create_resource(
+		move || {
+			(
+				state.get().get_access_token(),
+				state.get().get_last_used_workspace_id(),
+			)
+		},
+		move |(access_token, workspace_id)| async move {
+			get_feature_flags(access_token, workspace_id).await
+		},
+	)
+}
+
+/// Query to list the API tokens that have access to the current workspace, for
+/// the token management page of the workspace. Only the super admin of the
+/// workspace can list them.
+pub fn list_workspace_api_tokens_query(
+	status: Signal<Option<ApiTokenStatus>>,
+	user_id: Signal<Option<Uuid>>,
+	page: Signal<usize>,
+) -> Resource<
+	(
+		Option<String>,
+		Option<Uuid>,
+		Option<ApiTokenStatus>,
+		Option<Uuid>,
+		usize,
+	),
+	Result<(usize, ListWorkspaceApiTokensResponse), ServerFnError<ErrorType>>,
+> {
+	let (state, _) = AuthState::load();
+
+	create_resource(
+		move || {
+			(
+				state.get().get_access_token(),
+				state.get().get_last_used_workspace_id(),
+				status.get(),
+				user_id.get(),
+				page.get(),
+			)
+		},
+		move |(access_token, workspace_id, status, user_id, page)| async move {
+			list_workspace_api_tokens(
+				access_token,
+				workspace_id,
+				status,
+				user_id,
+				Some(page),
+				Some(constants::RESOURCES_PER_PAGE),
+			)
+			.await
+		},
+	)
+}
+
+/// Query to list the activity of the current workspace, for the activity page.
+/// The page is selected by the cursor returned with the previous page, and
+/// the feed is refetched from the newest activity whenever the category filter
+/// changes.
+pub fn list_workspace_activity_query(
+	cursor: Signal<Option<String>>,
+	category: Signal<Option<WorkspaceActivityCategory>>,
+) -> Resource<
+	(
+		Option<String>,
+		Option<Uuid>,
+		Option<String>,
+		Option<WorkspaceActivityCategory>,
+	),
+	Result<ListWorkspaceActivityResponse, ServerFnError<ErrorType>>,
+> {
+	let (state, _) = AuthState::load();
+
+	create_resource(
+		move || {
+			(
+				state.get().get_access_token(),
+				state.get().get_last_used_workspace_id(),
+				cursor.get(),
+				category.get(),
+			)
+		},
+		move |(access_token, workspace_id, cursor, category)| async move {
+			list_workspace_activity(access_token, workspace_id, cursor, category).await
+		},
+	)
+}
+
+/// Query to revoke an API token that has access to the current workspace,
+/// Returns an action to be dispatched with the ID of the token.
+pub fn revoke_workspace_api_token_query(
+) -> Action<Uuid, Result<RevokeWorkspaceApiTokenResponse, ServerFnError<ErrorType>>> {
+	let (state, _) = AuthState::load();
+
+	let access_token = state.get().get_access_token();
+	let workspace_id = state.get().get_last_used_workspace_id();
+
+	create_action(move |token_id: &Uuid| {
+		let access_token = access_token.clone();
+		let token_id = *token_id;
+
+		async move { revoke_workspace_api_token(access_token, workspace_id, token_id).await }
+	})
+}
+
+/// Query to export all the roles of the current workspace, along with their
+/// permissions, to be imported into another workspace
+pub fn export_workspace_roles_query() -> Resource<
+	(Option<String>, Option<Uuid>),
+	Result<ExportWorkspaceRolesResponse, ServerFnError<ErrorType>>,
+> {
+	let (state, _) = AuthState::load();
+
+	create_resource(
+		move || {
+			(
+				state.get().get_access_token(),
+				state.get().get_last_used_workspace_id(),
+			)
+		},
+		move |(access_token, workspace_id)| async move {
+			let workspace_id = workspace_id.ok_or(ServerFnError::WrappedServerError(
+				ErrorType::WrongParameters,
+			))?;
+			export_workspace_roles(access_token, workspace_id).await
+		},
+	)
+}
+
+/// Query to import roles exported from another workspace into the current
+/// workspace, Returns an action to be dispatched with the import request.
+pub fn import_workspace_roles_query() -> Action<
+	ImportWorkspaceRolesRequest,
+	Result<ImportWorkspaceRolesResponse, ServerFnError<ErrorType>>,
+> {
+	let (state, _) = AuthState::load();
+
+	let access_token = state.get().get_access_token();
+	let workspace_id = state.get().get_last_used_workspace_id();
+
+	create_action(move |request: &ImportWorkspaceRolesRequest| {
+		let access_token = access_token.clone();
+		let request = request.clone();
+
+		async move {
+			let workspace_id = workspace_id.ok_or(ServerFnError::WrappedServerError(
+				ErrorType::WrongParameters,
+			))?;
+			import_workspace_roles(access_token, workspace_id, request).await
+		}
+	})
+}
+
+/// Query to get the egress policy of the current workspace, which decides which
+/// destinations the deployments of the workspace can connect to
+pub fn get_egress_policy_query() -> Resource<
+	(Option<String>, Option<Uuid>),
+	Result<GetEgressPolicyResponse, ServerFnError<ErrorType>>,
+> {
+	let (state, _) = AuthState::load();
+
+	create_resource(
+		move || {
+			(
+				state.get().get_access_token(),
+				state.get().get_last_used_workspace_id(),
+			)
+		},
+		move |(access_token, workspace_id)| async move {
+			get_egress_policy(access_token, workspace_id).await
+		},
+	)
+}
+
+/// Query to set the egress policy of the current workspace, Returns an action
+/// to be dispatched with the new policy. An empty policy allows all
+/// destinations. A toast is shown for the result of the action.
+pub fn set_egress_policy_query(
+) -> Action<WorkspaceEgressPolicy, Result<SetEgressPolicyResponse, ServerFnError<ErrorType>>> {
+	let (state, _) = AuthState::load();
+	let toaster = expect_toaster();
+
+	let access_token = state.get().get_access_token();
+	let workspace_id = state.get().get_last_used_workspace_id();
+
+	create_action(move |policy: &WorkspaceEgressPolicy| {
+		let toaster = toaster.clone();
+		let access_token = access_token.clone();
+		let policy = policy.clone();
+
+		async move {
+			let response = set_egress_policy(access_token, workspace_id, policy).await;
+			toaster.toast_result(&response, Some("Egress policy updated"));
+
+			response
+		}
+	})
+}
@@ -1,4 +1,4 @@
-use leptos_router::{Outlet, ProtectedRoute};
+use leptos_router::Outlet;
 
 use crate::{prelude::*, utils::AuthState};
 
@@ -26,17 +26,10 @@ pub fn LoggedInRoutesView() -> impl IntoView {
 /// Contains all the routes for when the user is logged in
 #[component(transparent)]
 pub fn LoggedInRoutesComponent() -> impl IntoView {
-	let (state, _) = AuthState::load();
-
 	view! {
-		<ProtectedRoute
-			path={AppRoutes::Empty}
-			view={LoggedInRoutesView}
-			redirect_path={AppRoutes::LoggedOutRoute(LoggedOutRoute::Login)}
-			condition={move || state.get().is_logged_in()}
-		>
+		<LoginRequiredRoute path={AppRoutes::Empty} view={LoggedInRoutesView}>
 			<WorkspacedRoutes />
 			<NotWorkspacedRoutes />
-		</ProtectedRoute>
+		</LoginRequiredRoute>
 	}
 }
@@ -1,15 +1,22 @@
-use std::marker::PhantomData;
+use std::{fmt::Display, marker::PhantomData};
 
 use axum_extra::routing::TypedPath;
 use leptos::*;
 use leptos_router::{
+	use_location,
 	use_params as use_router_params,
 	use_query as use_router_query,
 	Params,
+	Redirect,
 	Route,
 };
 use serde::{de::DeserializeOwned, Serialize};
 
+use crate::{
+	routes::{LoginQuery, LoginRoute},
+	utils::AuthState,
+};
+
 /// A trait for types that can be used as a route in the application.
 /// It also provides the path as well as the query parameters for the route.
 pub trait TypedRoute:
@@ -38,14 +45,129 @@ where
 	F: Fn(R::Query, R) -> V + 'static,
 	V: IntoView,
 {
+	let (state, _) = AuthState::load();
 	let query: R::Query = use_router_query().get_untracked().unwrap_or_default();
 	let params: R = use_router_params()
 		.get_untracked()
 		.expect("cannot parse params");
 
 	view! {
-		<Route view={move || view(query.clone(), params.clone())} path={<R as TypedPath>::PATH}>
+		<Route
+			view={move || {
+				if R::REQUIRES_LOGIN && state.get().is_logged_out() {
+					view! { <RedirectToLogin /> }.into_view()
+				} else {
+					view(query.clone(), params.clone()).into_view()
+				}
+			}}
+			path={<R as TypedPath>::PATH}
+		>
+			{children()}
+		</Route>
+	}
+}
+
+/// A route that can only be accessed when the user is logged in. If the user
+/// is logged out, they are redirected to the login page, which sends them back
+/// to the route they were trying to access once they log in.
+#[component(transparent)]
+pub fn LoginRequiredRoute<P, F, V>(
+	/// The path of the route
+	path: P,
+	/// The view for the route, rendered only if the user is logged in
+	view: F,
+	/// The Children of the route
+	#[prop(optional, default = Box::new(|| Fragment::new(vec![])))]
+	children: Children,
+) -> impl IntoView
+where
+	P: Display + 'static,
+	F: Fn() -> V + 'static,
+	V: IntoView,
+{
+	let (state, _) = AuthState::load();
+
+	view! {
+		<Route
+			view={move || {
+				if state.get().is_logged_in() {
+					view().into_view()
+				} else {
+					view! { <RedirectToLogin /> }.into_view()
+				}
+			}}
+			path={path.to_string()}
+		>
 			{children()}
 		</Route>
 	}
 }
+
+/// Redirects to the login page, with the `next` query param pointing back to
+/// the current route (including its query params)
+#[component]
+pub fn RedirectToLogin() -> impl IntoView {
+	let location = use_location();
+	let search = location.search.get_untracked();
+	let search = search.trim_start_matches('?');
+
+	let next = if search.is_empty() {
+		location.pathname.get_untracked()
+	} else {
+		format!("{}?{}", location.pathname.get_untracked(), search)
+	};
+
+	view! { <Redirect path={login_path_with_next(&next)} /> }
+}
+
+/// Builds the path to the login page, with the `next` query param set to the
+/// given path, so that the user is sent back to it after logging in
+pub fn login_path_with_next(next: &str) -> String {
+	let query = serde_urlencoded::to_string(LoginQuery {
+		next: Some(next.to_string()),
+		user_id: None,
+	})
+	.unwrap_or_default();
+
+	if query.is_empty() {
+		LoginRoute {}.to_string()
+	} else {
+		format!("{}?{}", LoginRoute {}, query)
+	}
+}
+
+/// Returns the path to send the user to after logging in. The `next` query
+/// param is only followed if it is a path on this app, so that the login page
+/// cannot be used to redirect users to other sites. Otherwise, the user is sent
+/// to the given default path.
+pub fn next_path_or(next: Option<String>, default: impl Display) -> String {
+	next.filter(|next| next.starts_with('/') && !next.starts_with("//") && !next.starts_with("/\\"))
+		.unwrap_or_else(|| default.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{login_path_with_next, next_path_or};
+
+	#[test]
+	fn login_path_encodes_next() {
+		assert_eq!(
+			login_path_with_next("/deployment?page=2"),
+			"/login?next=%2Fdeployment%3Fpage%3D2"
+		);
+	}
+
+	#[test]
+	fn next_path_only_follows_local_paths() {
+		assert_eq!(
+			next_path_or(Some("/deployment/abc".to_string()), "/"),
+			"/deployment/abc"
+		);
+		assert_eq!(next_path_or(None, "/deployment"), "/deployment");
+		assert_eq!(
+			next_path_or(Some("https://example.com".to_string()), "/"),
+			"/"
+		);
+		assert_eq!(next_path_or(Some("//example.com".to_string()), "/"), "/");
+	}
+}
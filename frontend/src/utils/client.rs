@@ -8,6 +8,7 @@ use std::{
 use axum::extract::ConnectInfo;
 use axum_extra::routing::TypedPath;
 use http::Method;
+use leptos::ServerFnError;
 use matchit::Router;
 use models::{ApiEndpoint, ApiRequest, AppResponse, ErrorType};
 use preprocess::Preprocessable;
@@ -70,3 +71,17 @@ where
 		.oneshot((request, socket_addr.ip()))
 		.await
 }
+
+/// Converts an error returned by the API into the error of a server function.
+/// Errors are sent to the browser as their code, which would lose the details
+/// that errors such as [`ErrorType::QuotaExceeded`] and
+/// [`ErrorType::ReplicaLimitExceeded`] carry. Such errors are sent with the
+/// message describing them instead, so that it can be shown to the user.
+pub fn into_server_fn_error(error: ErrorType) -> ServerFnError<ErrorType> {
+	match error {
+		ErrorType::QuotaExceeded(_) |
+		ErrorType::ReplicaLimitExceeded(_) |
+		ErrorType::InvalidImageReference(_) => ServerFnError::ServerError(error.detailed_message()),
+		_ => ServerFnError::WrappedServerError(error),
+	}
+}
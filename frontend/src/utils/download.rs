@@ -0,0 +1,26 @@
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{Blob, BlobPropertyBag, HtmlAnchorElement, Url};
+
+use crate::prelude::*;
+
+/// Saves the given content as a file on the user's device. This creates an
+/// object URL for the content and clicks a temporary link to it, which makes
+/// the browser download it instead of navigating to it.
+pub fn download_file(file_name: &str, content_type: &str, content: &str) -> Result<(), JsValue> {
+	let options = BlobPropertyBag::new();
+	options.set_type(content_type);
+	let blob = Blob::new_with_str_sequence_and_options(
+		&js_sys::Array::of1(&JsValue::from_str(content)),
+		&options,
+	)?;
+	let url = Url::create_object_url_with_blob(&blob)?;
+
+	let anchor = document()
+		.create_element("a")?
+		.unchecked_into::<HtmlAnchorElement>();
+	anchor.set_href(&url);
+	anchor.set_download(file_name);
+	anchor.click();
+
+	Url::revoke_object_url(&url)
+}
@@ -0,0 +1,41 @@
+use models::api::workspace::FeatureFlags;
+
+use crate::{prelude::*, queries::get_feature_flags_query};
+
+/// The context that holds the feature flags of the current workspace. This is
+/// fetched once after login (and again on switching workspaces), so that the
+/// UI for features that aren't enabled can be hidden without each page having
+/// to fetch the flags separately.
+#[derive(Clone, Copy)]
+pub struct FeatureFlagsContext(Signal<FeatureFlags>);
+
+/// Provide the feature flags of the current workspace to all the child
+/// components. Until the flags are loaded (or if they fail to load), all the
+/// flags are considered to be disabled.
+pub fn provide_feature_flags() {
+	if use_context::<FeatureFlagsContext>().is_some() {
+		return;
+	}
+
+	let feature_flags = get_feature_flags_query();
+
+	provide_context(FeatureFlagsContext(Signal::derive(move || {
+		feature_flags
+			.get()
+			.and_then(Result::ok)
+			.map(|response| response.flags)
+			.unwrap_or_default()
+	})));
+}
+
+/// Returns a signal of whether the given feature flag is enabled for the
+/// current workspace. If the feature flags have not been provided, the flag is
+/// always disabled.
+pub fn use_feature_flag(flag: &'static str) -> Signal<bool> {
+	let feature_flags = use_context::<FeatureFlagsContext>();
+
+	Signal::derive(move || {
+		feature_flags
+			.is_some_and(|FeatureFlagsContext(flags)| flags.with(|flags| flags.is_enabled(flag)))
+	})
+}
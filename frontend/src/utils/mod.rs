@@ -13,8 +13,13 @@ mod app_route;
 /// The color enum. This enum is used to specify the color of a component. These
 /// include the primary and secondary colors of the app.
 mod color;
+/// Helpers to save files generated by the app on the user's device
+mod download;
 /// A module containing extension traits for various types
 mod ext_traits;
+/// The feature flags of the current workspace, used to hide UI for features
+/// that aren't enabled
+mod feature_flags;
 mod hooks;
 mod routes;
 mod sidebar_items;
@@ -37,7 +42,9 @@ pub use self::{
 	alignment::*,
 	app_route::*,
 	color::*,
+	download::*,
 	ext_traits::*,
+	feature_flags::*,
 	hooks::*,
 	routes::*,
 	sidebar_items::*,
@@ -56,6 +63,9 @@ pub mod constants {
 	pub const AUTH_STATE: &str = "authState";
 	/// The Number of resources to fetch per page
 	pub const RESOURCES_PER_PAGE: usize = 2;
+	/// How often (in milliseconds) the statuses of the deployments on the
+	/// current page of the dashboard are refreshed
+	pub const DEPLOYMENT_STATUS_POLL_INTERVAL_MILLIS: u64 = 5000;
 	/// The path to the feather icons sprite
 	pub const FEATHER_IMG: &str = "/icons/sprite/feather-sprite.svg";
 	/// The default debounce time for input fields
@@ -47,6 +47,9 @@ pub struct ApiEndpoint {
 	response_headers: Option<FieldsNamed>,
 	/// The body of the response.
 	response: Option<FieldsNamed>,
+	/// Whether the endpoint returns a raw response (such as a file download)
+	/// instead of a JSON body.
+	raw_response: bool,
 }
 
 impl Parse for ApiEndpoint {
@@ -90,6 +93,7 @@ impl Parse for ApiEndpoint {
 		let mut request_headers = None;
 		let mut response_headers = None;
 		let mut response = None;
+		let mut raw_response = None;
 		let mut api_allowed = None;
 
 		while !input.is_empty() {
@@ -147,6 +151,14 @@ impl Parse for ApiEndpoint {
 
 					response = Some(input.parse()?);
 				}
+				"raw_response" => {
+					if raw_response.is_some() {
+						return Err(Error::new(ident.span(), "Duplicate field"));
+					}
+					input.parse::<Token![=]>()?;
+
+					raw_response = Some(input.parse::<LitBool>()?.value);
+				}
 				"authentication" | "auth" => {
 					if auth.is_some() {
 						return Err(Error::new(ident.span(), "Duplicate field"));
@@ -172,6 +184,14 @@ impl Parse for ApiEndpoint {
 			}
 		}
 		let api_allowed = api_allowed.unwrap_or(true);
+		let raw_response = raw_response.unwrap_or(false);
+
+		if raw_response && response.is_some() {
+			return Err(Error::new(
+				input.span(),
+				"Cannot have a response body with a raw response",
+			));
+		}
 
 		Ok(Self {
 			documentation,
@@ -189,6 +209,7 @@ impl Parse for ApiEndpoint {
 
 			response_headers,
 			response,
+			raw_response,
 		})
 	}
 }
@@ -213,8 +234,12 @@ pub fn parse(input: TokenStream) -> TokenStream {
 
 		response_headers,
 		response,
+		raw_response,
 	} = parse_macro_input!(input as ApiEndpoint);
 
+	let name_str = name.to_string();
+	let documentation_str = documentation.trim();
+
 	let (path_default_impl, path_body) = if let Some(body) = path_body {
 		(
 			quote::quote! {},
@@ -265,6 +290,15 @@ pub fn parse(input: TokenStream) -> TokenStream {
 			()
 		}
 	};
+	let page_size_impl = if paginate_query.unwrap_or(false) {
+		quote::quote! {
+			fn page_size_mut(query: &mut Self::RequestQuery) -> Option<&mut usize> {
+				Some(&mut query.count)
+			}
+		}
+	} else {
+		quote::quote! {}
+	};
 	let query_decl = if let Some(query) = query {
 		quote::quote! {
 			#[::preprocess::sync]
@@ -280,6 +314,7 @@ pub fn parse(input: TokenStream) -> TokenStream {
 				serde::Serialize,
 				serde::Deserialize,
 			)]
+			#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
 			#[serde(rename_all = "camelCase")]
 			pub struct #query_type_name #query
 
@@ -384,6 +419,42 @@ pub fn parse(input: TokenStream) -> TokenStream {
 		}
 	};
 
+	let (response_type, response_decl) = if raw_response {
+		(
+			quote::quote!(models::utils::GenericResponse),
+			quote::quote!(),
+		)
+	} else {
+		(
+			quote::quote!(#response_name),
+			quote::quote! {
+				/// The response body for the #name endpoint.
+				///
+				/// The documentation for the endpoint is below:
+				///
+				#[doc = #documentation]
+				#[derive(
+					Debug,
+					Clone,
+					PartialEq,
+					serde::Serialize,
+					serde::Deserialize,
+				)]
+				#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
+				#[serde(rename_all = "camelCase")]
+				pub struct #response_name #response_body
+
+				impl models::utils::RequiresRequestHeaders for #response_name {
+					type RequiredRequestHeaders = ();
+				}
+
+				impl models::utils::RequiresResponseHeaders for #response_name {
+					type RequiredResponseHeaders = ();
+				}
+			},
+		)
+	};
+
 	quote::quote! {
 		/// The URL path for the #name endpoint.
 		///
@@ -400,6 +471,7 @@ pub fn parse(input: TokenStream) -> TokenStream {
 			serde::Deserialize,
 			axum_extra::routing::TypedPath,
 		)]
+		#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
 		#[typed_path(#path)]
 		pub struct #path_name #path_body
 
@@ -420,6 +492,7 @@ pub fn parse(input: TokenStream) -> TokenStream {
 			serde::Serialize,
 			serde::Deserialize,
 		)]
+		#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
 		#[serde(rename_all = "camelCase")]
 		pub struct #request_name #request_body
 
@@ -433,28 +506,7 @@ pub fn parse(input: TokenStream) -> TokenStream {
 
 		#response_headers_decl
 
-		/// The response body for the #name endpoint.
-		///
-		/// The documentation for the endpoint is below:
-		///
-		#[doc = #documentation]
-		#[derive(
-			Debug,
-			Clone,
-			PartialEq,
-			serde::Serialize,
-			serde::Deserialize,
-		)]
-		#[serde(rename_all = "camelCase")]
-		pub struct #response_name #response_body
-
-		impl models::utils::RequiresRequestHeaders for #response_name {
-			type RequiredRequestHeaders = ();
-		}
-
-		impl models::utils::RequiresResponseHeaders for #response_name {
-			type RequiredResponseHeaders = ();
-		}
+		#response_decl
 
 		impl models::ApiEndpoint for #request_name {
 			const METHOD: ::http::Method = ::http::Method::#method;
@@ -469,7 +521,12 @@ pub fn parse(input: TokenStream) -> TokenStream {
 			#auth_impl
 
 			type ResponseHeaders = #response_headers_name;
-			type ResponseBody = #response_name;
+			type ResponseBody = #response_type;
+
+			const NAME: &'static str = #name_str;
+			const DOCUMENTATION: &'static str = #documentation_str;
+
+			#page_size_impl
 		}
 	}
 	.into()
@@ -231,6 +231,9 @@ pub fn parse(input: TokenStream) -> TokenStream {
 		client_msg,
 	} = parse_macro_input!(input as ApiEndpoint);
 
+	let name_str = name.to_string();
+	let documentation_str = documentation.trim();
+
 	let (path_default_impl, path_body) = if let Some(body) = path_body {
 		(
 			quote::quote! {},
@@ -297,6 +300,7 @@ pub fn parse(input: TokenStream) -> TokenStream {
 				serde::Serialize,
 				serde::Deserialize,
 			)]
+			#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
 			#[serde(rename_all = "camelCase")]
 			pub struct #query_name #query
 
@@ -417,6 +421,7 @@ pub fn parse(input: TokenStream) -> TokenStream {
 			serde::Deserialize,
 			axum_extra::routing::TypedPath,
 		)]
+		#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
 		#[typed_path(#path)]
 		pub struct #path_name #path_body
 
@@ -491,6 +496,9 @@ pub fn parse(input: TokenStream) -> TokenStream {
 
 			type ResponseHeaders = #response_headers_name;
 			type ResponseBody = models::utils::GenericResponse;
+
+			const NAME: &'static str = #name_str;
+			const DOCUMENTATION: &'static str = #documentation_str;
 		}
 	}
 	.into()
@@ -1,8 +1,21 @@
 use proc_macro::TokenStream;
 use proc_macro2::TokenStream as TokenStream2;
-use syn::{parse_macro_input, spanned::Spanned, Data, DataStruct, DeriveInput, Error, Field};
+use syn::{
+	parse_macro_input,
+	spanned::Spanned,
+	Data,
+	DataStruct,
+	DeriveInput,
+	Error,
+	Field,
+	GenericArgument,
+	PathArguments,
+	Type,
+};
 
-/// Provides a derive macro for the `HasHeaders` trait.
+/// Provides a derive macro for the `HasHeaders` trait. Fields of the type
+/// `Option<T>` are optional headers, which are not required to be present in
+/// the header map.
 pub fn parse(input: TokenStream) -> TokenStream {
 	let DeriveInput { data, ident, .. } = parse_macro_input!(input as DeriveInput);
 
@@ -23,6 +36,7 @@ pub fn parse(input: TokenStream) -> TokenStream {
 	let has_header_impls = fields
 		.clone()
 		.into_iter()
+		.filter(|field| optional_header_type(&field.ty).is_none())
 		.map(|field| {
 			let Field {
 				ty,
@@ -44,10 +58,20 @@ pub fn parse(input: TokenStream) -> TokenStream {
 		.into_iter()
 		.map(|field| {
 			let Field {
-				ident: field_ident, ..
+				ident: field_ident,
+				ty,
+				..
 			} = field;
-			quote::quote! {
-				::headers::HeaderMapExt::typed_insert(&mut map, self.#field_ident.clone());
+			if optional_header_type(&ty).is_some() {
+				quote::quote! {
+					if let Some(value) = &self.#field_ident {
+						::headers::HeaderMapExt::typed_insert(&mut map, value.clone());
+					}
+				}
+			} else {
+				quote::quote! {
+					::headers::HeaderMapExt::typed_insert(&mut map, self.#field_ident.clone());
+				}
 			}
 		})
 		.collect::<TokenStream2>();
@@ -56,6 +80,18 @@ pub fn parse(input: TokenStream) -> TokenStream {
 		.into_iter()
 		.map(|field| {
 			let Field { ident, ty, .. } = field;
+			if let Some(ty) = optional_header_type(&ty) {
+				return quote::quote! {
+					#ident: ::headers::HeaderMapExt::typed_try_get::<#ty>(map)
+						.map_err(|err| {
+							tracing::debug!(
+								"Failed to parse header `{}`",
+								<#ty as ::headers::Header>::name().as_str()
+							);
+							err
+						})?,
+				};
+			}
 			quote::quote! {
 				#ident: ::headers::HeaderMapExt::typed_get::<#ty>(map)
 					.ok_or_else(|| {
@@ -89,3 +125,22 @@ pub fn parse(input: TokenStream) -> TokenStream {
 	}
 	.into()
 }
+
+/// Returns the type of the header if the given type is an optional header
+/// (`Option<T>`), or `None` if the header is required.
+fn optional_header_type(ty: &Type) -> Option<&Type> {
+	let Type::Path(path) = ty else {
+		return None;
+	};
+	let segment = path.path.segments.last()?;
+	if segment.ident != "Option" {
+		return None;
+	}
+	let PathArguments::AngleBracketed(arguments) = &segment.arguments else {
+		return None;
+	};
+	match arguments.args.first()? {
+		GenericArgument::Type(ty) => Some(ty),
+		_ => None,
+	}
+}
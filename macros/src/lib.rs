@@ -70,6 +70,8 @@ mod version;
 ///     response_headers = {
 ///         pub header1: AcceptRanges,
 ///     },
+///     // Can also use raw_response = true instead, to return a custom
+///     // `GenericResponse` (such as a file download) instead of a JSON body
 ///     response = {
 ///         pub body_param1: String,
 ///     },
@@ -163,7 +165,8 @@ pub fn declare_stream_endpoint(input: TokenStream) -> TokenStream {
 }
 
 /// A derive macro that makes it easy to implement `HasHeader` for every single
-/// field in the given struct.
+/// field in the given struct. Fields of the type `Option<T>` are treated as
+/// optional headers, and don't implement `HasHeader`.
 #[proc_macro_derive(HasHeaders)]
 pub fn has_headers(input: TokenStream) -> TokenStream {
 	has_headers::parse(input)
@@ -2,16 +2,14 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
 	prelude::*,
-	utils::{
-		constants::{PHONE_NUMBER_COUNTRY_CODE_REGEX, PHONE_NUMBER_REGEX, USERNAME_VALIDITY_REGEX},
-		validate_password,
-	},
+	utils::{constants::USERNAME_VALIDITY_REGEX, validate_password},
 };
 
 /// Recovery method options provided to the user when they forget their
 /// passsword and request a password change by hitting the ForgetPassword API
 /// endpoint. The curent recovery options are email and phone number.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
 #[serde(untagged)]
 #[preprocess::sync]
 pub enum RecoveryMethod {
@@ -20,24 +18,52 @@ pub enum RecoveryMethod {
 	PhoneNumber {
 		/// The country code of the phone number. Example: US, IN, etc.
 		/// POLICY:
-		/// Plus sign followed by 1 to 4 digits
-		#[preprocess(trim, regex = PHONE_NUMBER_COUNTRY_CODE_REGEX)]
+		/// 2 uppercase letters of a known country
+		#[preprocess(trim)]
 		recovery_phone_country_code: String,
-		/// The phone number of the user
+		/// The phone number of the user, without the calling code of the
+		/// country
 		/// POLICY:
-		/// Standard 10-digit format
-		#[preprocess(trim, regex = PHONE_NUMBER_REGEX)]
+		/// Must be in the E.164 format when prefixed with the calling code of
+		/// the country. Spaces, hyphens, dots and parentheses are ignored
+		#[preprocess(trim)]
 		recovery_phone_number: String,
 	},
 	#[serde(rename_all = "camelCase")]
 	/// Email
 	Email {
 		/// The email address of the user
-		#[preprocess(email)]
+		#[preprocess(trim)]
 		recovery_email: String,
 	},
 }
 
+impl RecoveryMethod {
+	/// The type of this recovery method
+	pub fn method_type(&self) -> RecoveryMethodType {
+		match self {
+			Self::PhoneNumber {
+				recovery_phone_country_code: _,
+				recovery_phone_number: _,
+			} => RecoveryMethodType::PhoneNumber,
+			Self::Email { recovery_email: _ } => RecoveryMethodType::Email,
+		}
+	}
+}
+
+/// The types of recovery methods that an account can be created with. An
+/// instance can only support the recovery methods that it is able to send
+/// messages to.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub enum RecoveryMethodType {
+	/// A phone number, that messages are sent to by SMS
+	PhoneNumber,
+	/// An email address
+	Email,
+}
+
 macros::declare_api_endpoint!(
 	/// The route to create a new user account
 	CreateAccount,
@@ -65,7 +91,13 @@ macros::declare_api_endpoint!(
 		/// The last name of the user
 		#[preprocess(trim, length(min = 1))]
 		pub last_name: String,
-		/// The recovery method the user would recover their account with
+		/// The recovery method the user would recover their account with. This
+		/// must be one of the recovery methods supported by the instance (see
+		/// [`GetInstanceCapabilities`][1]). The email or phone number is
+		/// validated by the handler, so that an error specific to the field
+		/// can be returned
+		///
+		/// [1]: super::GetInstanceCapabilitiesRequest
 		#[serde(flatten)]
 		pub recovery_method: RecoveryMethod,
 	},
@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 
+use super::RecoveryMethodType;
 use crate::prelude::*;
 
 /// Recovery method options provided to the user.
@@ -8,6 +9,7 @@ use crate::prelude::*;
 /// ForgetPassword API endpoint, these are the options presented to them. The
 /// current recovery options are email and phone number.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub enum PreferredRecoveryOption {
 	/// Send OTP to phone number
@@ -16,6 +18,16 @@ pub enum PreferredRecoveryOption {
 	RecoveryEmail,
 }
 
+impl PreferredRecoveryOption {
+	/// The type of recovery method that the OTP is sent to
+	pub const fn method_type(&self) -> RecoveryMethodType {
+		match self {
+			Self::RecoveryPhoneNumber => RecoveryMethodType::PhoneNumber,
+			Self::RecoveryEmail => RecoveryMethodType::Email,
+		}
+	}
+}
+
 macros::declare_api_endpoint!(
 	/// Route when user forgets their password and raises a password change request.
 	/// This will send an OTP to the selected recovery method.
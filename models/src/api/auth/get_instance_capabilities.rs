@@ -0,0 +1,22 @@
+use super::RecoveryMethodType;
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route to get the capabilities of this instance, which depend on how it
+	/// is configured. Instances that aren't configured to send emails or SMS
+	/// can't verify (or recover accounts with) those recovery methods, so the
+	/// sign up form should only offer the methods listed here. This does not
+	/// need to be authenticated, since it is needed before signing up
+	GetInstanceCapabilities,
+	GET "/auth/instance-capabilities",
+	api = false,
+	request_headers = {
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	response = {
+		/// The recovery methods that accounts can be created and recovered
+		/// with
+		pub recovery_methods: Vec<RecoveryMethodType>,
+	}
+);
@@ -0,0 +1,37 @@
+use time::OffsetDateTime;
+
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route for a support operator to start a short-lived session acting as another user, to
+	/// reproduce what the user sees. Every request made with the session is audited along
+	/// with the operator, and destructive actions are not allowed with it.
+	ImpersonateUser,
+	POST "/auth/impersonate",
+	api = false,
+	request_headers = {
+		/// Token used to authorize the operator
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::PlainTokenAuthenticator
+	},
+	request = {
+		/// The ID of the user to impersonate
+		pub user_id: Uuid,
+		/// Why the user is being impersonated, such as the support ticket being
+		/// worked on. This is recorded in the audit log
+		#[preprocess(trim, length(min = 4))]
+		pub reason: String,
+	},
+	response = {
+		/// The access token of the impersonated session. There is no refresh
+		/// token, so the session cannot be used after it expires
+		pub access_token: String,
+		/// When the impersonated session expires
+		#[cfg_attr(not(target_arch = "wasm32"), schemars(with = "String"))]
+		pub expires_at: OffsetDateTime,
+	}
+);
@@ -0,0 +1,22 @@
+use super::RecoveryMethodType;
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route to list the recovery methods that this instance supports for new
+	/// accounts. This is the same as the `recoveryMethods` of
+	/// [`GetInstanceCapabilities`][1], and is only kept for the clients that
+	/// still use it. New clients should get the capabilities instead.
+	///
+	/// [1]: super::GetInstanceCapabilitiesRequest
+	ListSupportedRecoveryMethods,
+	GET "/auth/supported-recovery-methods",
+	api = false,
+	request_headers = {
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	response = {
+		/// The recovery methods that accounts can be created with
+		pub recovery_methods: Vec<RecoveryMethodType>,
+	}
+);
@@ -1,7 +1,36 @@
-use crate::{
-	prelude::*,
-	utils::{constants::OTP_VERIFICATION_TOKEN_REGEX, validate_password},
-};
+use serde::{Deserialize, Serialize};
+
+use crate::{prelude::*, utils::validate_password};
+
+/// The response from the Login endpoint
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum LoginResponseType {
+	/// The user is logged in
+	#[serde(rename_all = "camelCase")]
+	LoggedIn {
+		/// The access token is used to authenticate the user, implying that the
+		/// user is logged in once the route is completed successfully.
+		access_token: String,
+		/// The access token has a expiry, and the refresh token (below) is used
+		/// to renew the access token.
+		/// It contains the login_id and the refresh_token concatenated
+		/// together.
+		refresh_token: String,
+	},
+	/// The password is valid, but the user has multi-factor authentication
+	/// enabled. The login has to be completed using the [`VerifyMfaLogin`][1]
+	/// endpoint with this token and an OTP (or a recovery code)
+	///
+	/// [1]: super::VerifyMfaLoginRequest
+	#[serde(rename_all = "camelCase")]
+	MfaRequired {
+		/// The short-lived token that proves that the password of the user was
+		/// verified
+		mfa_token: String,
+	},
+}
 
 macros::declare_api_endpoint!(
 	/// Route to login and start a new user session. This route will generate all
@@ -26,18 +55,12 @@ macros::declare_api_endpoint!(
 		/// At least one special character (e.g., !@#$%^&*)
 		#[preprocess(trim, length(min = 8), custom = "validate_password")]
 		pub password: String,
-		/// If a user has a multi-factor authentication enabled, the OTP to authenticate the identity
-		/// of the user
-		#[preprocess(optional(trim, length(min = 6, max = 7), regex = OTP_VERIFICATION_TOKEN_REGEX))]
-		pub mfa_otp: Option<String>,
 	},
 	response = {
-		/// The access token is used to authenticate the user, implying that the user is logged in
-		/// once the route is completed successfully.
-		pub access_token: String,
-		/// The access token has a expiry, and the refresh token (below) is used to
-		/// renew the access token.
-		/// It contains the login_id and the refresh_token concatenated together.
-		pub refresh_token: String,
+		/// The response from the Login endpoint. If the user has multi-factor
+		/// authentication enabled, the login has to be completed with a second
+		/// step
+		#[serde(flatten)]
+		pub response: LoginResponseType,
 	}
 );
@@ -7,12 +7,20 @@ mod complete_sign_up;
 mod create_account;
 /// The endpoint to trigger a forgot password flow
 mod forgot_password;
+/// The endpoint to get the capabilities of the instance, such as the
+/// recovery methods it supports
+mod get_instance_capabilities;
+/// The endpoint for a support operator to impersonate a user
+mod impersonate_user;
 /// The endpoint to check if an email is valid
 mod is_email_valid;
 /// The endpoint to check if a username is valid
 mod is_username_valid;
 /// The endpoint to list the recovery options for a user
 mod list_recovery_options;
+/// The endpoint to list the recovery methods supported by the instance, kept
+/// for the clients that don't get the capabilities of the instance yet
+mod list_supported_recovery_methods;
 /// The endpoint to login
 mod login;
 /// The endpoint to logout
@@ -23,17 +31,23 @@ mod renew_access_token;
 mod resend_otp;
 /// The endpoint to reset the password
 mod reset_password;
+/// The endpoint to complete a login with multi-factor authentication
+mod verify_mfa_login;
 
 pub use self::{
 	complete_sign_up::*,
 	create_account::*,
 	forgot_password::*,
+	get_instance_capabilities::*,
+	impersonate_user::*,
 	is_email_valid::*,
 	is_username_valid::*,
 	list_recovery_options::*,
+	list_supported_recovery_methods::*,
 	login::*,
 	logout::*,
 	renew_access_token::*,
 	resend_otp::*,
 	reset_password::*,
+	verify_mfa_login::*,
 };
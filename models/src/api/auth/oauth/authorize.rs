@@ -4,6 +4,7 @@ use crate::prelude::*;
 
 /// The type of request that the third-party app is making.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub enum OAuthAuthorizeResponseType {
 	/// The third-party app is requesting a temporary authorization code.
@@ -14,6 +15,7 @@ pub enum OAuthAuthorizeResponseType {
 
 /// The method used to hash the code challenge.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub enum CodeChallengeHashMethod {
 	/// The code challenge is hashed using the SHA-256 algorithm.
@@ -8,6 +8,7 @@ use crate::{
 
 /// The response from the OAuthIntrospect endpoint
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase", untagged)]
 pub enum OAuthIntrospectResponseType {
 	/// If the access token is valid
@@ -23,6 +24,7 @@ pub enum OAuthIntrospectResponseType {
 		scope: String,
 		/// The expiry time of the access token
 		#[serde(with = "time::serde::timestamp")]
+		#[cfg_attr(not(target_arch = "wasm32"), schemars(with = "i64"))]
 		expires_at: OffsetDateTime,
 	},
 	/// If the access token is invalid
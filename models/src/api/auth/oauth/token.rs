@@ -4,6 +4,7 @@ use crate::prelude::*;
 
 /// The grant type for the request
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub enum OAuthTokenGrantType {
 	/// The request is for a temporary authorization code that will be exchanged
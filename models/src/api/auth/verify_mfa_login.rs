@@ -0,0 +1,37 @@
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route to complete the login of a user that has multi-factor
+	/// authentication enabled. This is the second step of the login, after the
+	/// password is verified by the [`Login`][1] endpoint. Either an OTP from the
+	/// authenticator app of the user or one of their recovery codes can be used.
+	/// A recovery code can only be used once.
+	///
+	/// [1]: super::LoginRequest
+	VerifyMfaLogin,
+	POST "/auth/sign-in/mfa",
+	api = false,
+	request_headers = {
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	request = {
+		/// The token returned by the Login endpoint once the password was
+		/// verified
+		#[preprocess(trim, length(min = 1))]
+		pub mfa_token: String,
+		/// The OTP generated by the authenticator app of the user, or one of the
+		/// recovery codes of the user
+		#[preprocess(trim, length(min = 6, max = 11))]
+		pub code: String,
+	},
+	response = {
+		/// The access token is used to authenticate the user, implying that the
+		/// user is logged in once the route is completed successfully.
+		pub access_token: String,
+		/// The access token has a expiry, and the refresh token (below) is used
+		/// to renew the access token.
+		/// It contains the login_id and the refresh_token concatenated together.
+		pub refresh_token: String,
+	}
+);
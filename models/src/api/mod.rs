@@ -66,6 +66,22 @@ where
 	}
 }
 
+/// A source of an aggregate list that could not be loaded. Endpoints that
+/// aggregate results from multiple sources, such as all the workspaces of a
+/// user, return the results of the sources that could be loaded along with a
+/// warning for each one that couldn't, instead of failing the whole request.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AggregateWarning {
+	/// The ID of the source that could not be loaded, such as the ID of a
+	/// workspace
+	pub source_id: Uuid,
+	/// The name of the source, so that it can be shown to the user
+	pub source_name: String,
+	/// Why the source could not be loaded
+	pub message: String,
+}
+
 #[cfg(test)]
 mod test {
 	use serde_test::{assert_tokens, Token};
@@ -0,0 +1,48 @@
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Revoke multiple API tokens at once. Each token is revoked independently,
+	/// so a token that cannot be revoked does not fail the rest of the batch.
+	/// The result for each token is returned in the same order as the IDs in
+	/// the request. Revoking a token that is already revoked succeeds without
+	/// changing anything.
+	BulkRevokeApiTokens,
+	POST "/user/api-token/bulk-revoke",
+	api = false,
+	request_headers = {
+		/// The authorization token
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::PlainTokenAuthenticator
+	},
+	request = {
+		/// The IDs of the tokens to revoke
+		#[preprocess(none)]
+		pub token_ids: Vec<Uuid>,
+	},
+	response = {
+		/// The result of revoking each token, in the same order as the
+		/// `tokenIds` in the request
+		pub results: Vec<BulkRevokeApiTokenResult>,
+	}
+);
+
+/// The result of revoking a single token in a [`BulkRevokeApiTokensRequest`]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct BulkRevokeApiTokenResult {
+	/// The ID of the token
+	pub token_id: Uuid,
+	/// The error that occured when revoking the token. The token was revoked
+	/// (or was already revoked) if this is `None`. This is
+	/// [`ErrorType::ResourceDoesNotExist`] if there is no such token, and
+	/// [`ErrorType::Unauthorized`] if the token belongs to another user
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub error: Option<ErrorType>,
+}
@@ -1,8 +1,12 @@
+use std::collections::BTreeMap;
+
 use super::{UserApiToken, UserApiTokenProcessed};
-use crate::prelude::*;
+use crate::{prelude::*, rbac::WorkspacePermission};
 
 macros::declare_api_endpoint!(
-	/// Create a new API token for the user with the given permissions.
+	/// Create a new API token for the user with the given permissions. The
+	/// token is only granted the requested permissions that the user has
+	/// themselves, and the rest are dropped.
 	CreateApiToken,
 	POST "/user/api-token",
 	api = false,
@@ -26,5 +30,10 @@ macros::declare_api_endpoint!(
 		pub id: Uuid,
 		/// The token itself
 		pub token: String,
+		/// The permissions that the token was actually granted, for each
+		/// workspace. This is the intersection of the requested permissions
+		/// with the permissions of the user, so any requested permission that
+		/// is missing here was dropped because the user doesn't have it
+		pub granted_permissions: BTreeMap<Uuid, WorkspacePermission>,
 	}
 );
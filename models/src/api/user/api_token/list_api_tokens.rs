@@ -1,6 +1,85 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
 use super::UserApiToken;
 use crate::prelude::*;
 
+/// The status of an API token, derived from its expiry and revocation times
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub enum ApiTokenStatus {
+	/// The token has not expired and has not been revoked
+	Active,
+	/// The token has gone past its expiry time
+	Expired,
+	/// The token has been revoked by the user
+	Revoked,
+}
+
+impl ApiTokenStatus {
+	/// Derive the status of a token from its expiry and revocation times, as
+	/// of the given time. A revoked token is always considered revoked, even if
+	/// it has also expired.
+	pub fn from_timestamps(
+		token_exp: Option<OffsetDateTime>,
+		revoked: Option<OffsetDateTime>,
+		now: OffsetDateTime,
+	) -> Self {
+		if revoked.is_some_and(|revoked| revoked <= now) {
+			Self::Revoked
+		} else if token_exp.is_some_and(|exp| exp < now) {
+			Self::Expired
+		} else {
+			Self::Active
+		}
+	}
+}
+
+impl Display for ApiTokenStatus {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Active => write!(f, "active"),
+			Self::Expired => write!(f, "expired"),
+			Self::Revoked => write!(f, "revoked"),
+		}
+	}
+}
+
+/// The field to sort the list of API tokens by
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub enum ApiTokenSortBy {
+	/// Sort by the time the token was created
+	#[default]
+	Created,
+	/// Sort by the time the token was last used. Tokens that have never been
+	/// used are always listed last
+	LastUsed,
+}
+
+/// An API token in the list of API tokens of a user, along with its derived
+/// status
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct ListedApiToken {
+	/// The API token
+	#[serde(flatten)]
+	pub token: UserApiToken,
+	/// The status of the token, derived from its expiry and revocation times
+	pub status: ApiTokenStatus,
+	/// The last time the token was used to authenticate a request, if ever.
+	/// This is only updated about once a minute, so later uses within the
+	/// same minute are not reflected here
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[cfg_attr(not(target_arch = "wasm32"), schemars(with = "Option<String>"))]
+	pub last_used: Option<OffsetDateTime>,
+}
+
 macros::declare_api_endpoint!(
 	/// List all API tokens for a particular user.
 	ListApiTokens,
@@ -15,6 +94,16 @@ macros::declare_api_endpoint!(
 	authentication = {
 		AppAuthentication::<Self>::PlainTokenAuthenticator
 	},
+	query = {
+		/// Only list the tokens with this status. If not specified, all the
+		/// tokens that have not been revoked are listed
+		pub status: Option<ApiTokenStatus>,
+		/// The field to sort the list of tokens by. Defaults to the time the
+		/// token was created
+		pub order_by: Option<ApiTokenSortBy>,
+		/// The order to sort the list of tokens in. Defaults to descending
+		pub order: Option<ListOrder>,
+	},
 	pagination = true,
 	response_headers = {
 		/// The total number of databases in the requested workspace
@@ -22,6 +111,37 @@ macros::declare_api_endpoint!(
 	},
 	response = {
 		/// The list of API tokens
-		pub tokens: Vec<WithId<UserApiToken>>,
+		pub tokens: Vec<WithId<ListedApiToken>>,
 	}
 );
+
+#[cfg(test)]
+mod test {
+	use time::{Duration, OffsetDateTime};
+
+	use super::ApiTokenStatus;
+
+	#[test]
+	fn status_is_derived_from_timestamps() {
+		let now = OffsetDateTime::UNIX_EPOCH + Duration::days(1);
+		let past = Some(now - Duration::hours(1));
+		let future = Some(now + Duration::hours(1));
+
+		assert_eq!(
+			ApiTokenStatus::from_timestamps(None, None, now),
+			ApiTokenStatus::Active
+		);
+		assert_eq!(
+			ApiTokenStatus::from_timestamps(future, future, now),
+			ApiTokenStatus::Active
+		);
+		assert_eq!(
+			ApiTokenStatus::from_timestamps(past, None, now),
+			ApiTokenStatus::Expired
+		);
+		assert_eq!(
+			ApiTokenStatus::from_timestamps(past, past, now),
+			ApiTokenStatus::Revoked
+		);
+	}
+}
@@ -2,6 +2,8 @@ use std::collections::BTreeMap;
 
 use crate::{prelude::*, utils::constants::RESOURCE_NAME_REGEX};
 
+/// The endpoint to revoke multiple API tokens at once
+mod bulk_revoke_api_tokens;
 /// The endpoint to create an API token
 mod create_api_token;
 /// The endpoint to get the information of an API token
@@ -20,6 +22,7 @@ use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
 
 pub use self::{
+	bulk_revoke_api_tokens::*,
 	create_api_token::*,
 	get_api_token_info::*,
 	list_api_tokens::*,
@@ -42,6 +45,7 @@ use crate::rbac::WorkspacePermission;
 /// I mean, if we're anyway gonna store everything in the audit log, then why
 /// store anything in the login ID table? Ehh, idk.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct UserApiToken {
 	/// A user-friendly name for the token. This is used to identify the token
@@ -58,18 +62,22 @@ pub struct UserApiToken {
 	/// Any token that is used before the nbf (not before) should be rejected.
 	/// Tokens are only valid after this time.
 	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[cfg_attr(not(target_arch = "wasm32"), schemars(with = "Option<String>"))]
 	pub token_nbf: Option<OffsetDateTime>,
 	/// Any token that is used after the exp (expiry) should be rejected. Tokens
 	/// are only valid before this time.
 	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[cfg_attr(not(target_arch = "wasm32"), schemars(with = "Option<String>"))]
 	pub token_exp: Option<OffsetDateTime>,
 	/// The IP addresses that are allowed to use this token. If this is not
 	/// specified, then any IP address can use this token. This can also take a
 	/// CIDR range, to allow a range of IP addresses.
 	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[cfg_attr(not(target_arch = "wasm32"), schemars(with = "Option<Vec<String>>"))]
 	pub allowed_ips: Option<Vec<IpNetwork>>,
 	/// The time at which this token was created.
 	#[serde(default = "default_created")]
+	#[cfg_attr(not(target_arch = "wasm32"), schemars(with = "String"))]
 	pub created: OffsetDateTime,
 }
 
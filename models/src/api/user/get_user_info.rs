@@ -21,9 +21,13 @@ macros::declare_api_endpoint!(
 		#[serde(flatten)]
 		pub basic_user_info: WithId<BasicUserInfo>,
 		/// When the user account was created
+		#[cfg_attr(not(target_arch = "wasm32"), schemars(with = "String"))]
 		pub created: OffsetDateTime,
 		/// The primary recovery email of the user
 		pub recovery_email: Option<String>,
+		/// The new recovery email of the user that is yet to be verified, if the
+		/// user has requested to change their recovery email
+		pub pending_recovery_email: Option<String>,
 		/// The primary phone number of the user
 		pub recovery_phone_number: Option<UserPhoneNumber>,
 		/// Check if MFA is enabled or not
@@ -1,7 +1,10 @@
 use crate::prelude::*;
 
 macros::declare_api_endpoint!(
-	/// Activate multifactor authentication of a user
+	/// Activate multifactor authentication of a user, by verifying an OTP
+	/// generated from the secret returned by the [`EnableTotp`][1] endpoint
+	///
+	/// [1]: super::EnableTotpRequest
 	ActivateMfa,
 	POST "/user/mfa",
 	request_headers = {
@@ -1,8 +1,9 @@
 use crate::prelude::*;
 
 macros::declare_api_endpoint!(
-	/// Deactivate multifactor authentication of a user
-	DeactivateMfa,
+	/// Disable TOTP based multi-factor authentication of a user. This also
+	/// removes all the recovery codes of the user
+	DisableTotp,
 	DELETE "/user/mfa",
 	request_headers = {
 		/// The authorization token
@@ -12,7 +13,7 @@ macros::declare_api_endpoint!(
 		AppAuthentication::<Self>::PlainTokenAuthenticator
 	},
 	request = {
-		/// The one time password to deactivate mfa
+		/// The one time password to disable mfa
 		#[preprocess(none)]
 		pub otp: String,
 	},
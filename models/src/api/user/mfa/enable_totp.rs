@@ -0,0 +1,27 @@
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Start enabling TOTP based multi-factor authentication for a user. This
+	/// generates a new TOTP secret and a set of recovery codes, which only take
+	/// effect once an OTP generated from the secret is verified using the
+	/// [`ActivateMfa`][1] endpoint
+	///
+	/// [1]: super::ActivateMfaRequest
+	EnableTotp,
+	POST "/user/mfa/totp",
+	request_headers = {
+		/// The authorization token
+		pub authorization: BearerToken,
+	},
+	authentication = {
+		AppAuthentication::<Self>::PlainTokenAuthenticator
+	},
+	response = {
+		/// The `otpauth://` URI containing the TOTP secret, to be added to an
+		/// authenticator app (usually by scanning it as a QR code)
+		pub provisioning_uri: String,
+		/// The single-use recovery codes that can be used to login in place of
+		/// an OTP if the authenticator app is lost. These are only shown once
+		pub recovery_codes: Vec<String>,
+	},
+);
@@ -1,8 +1,9 @@
-/// The endpoint to activate MFA for a user
+/// The endpoint to activate MFA for a user, by verifying an OTP generated
+/// from the secret returned while enabling TOTP
 mod activate_mfa;
-/// The endpoint to deactivate MFA for a user
-mod deactivate_mfa;
-/// The endpoint to get the MFA secret for a user while activating MFA
-mod get_mfa_secret;
+/// The endpoint to disable TOTP based MFA for a user
+mod disable_totp;
+/// The endpoint to start enabling TOTP based MFA for a user
+mod enable_totp;
 
-pub use self::{activate_mfa::*, deactivate_mfa::*, get_mfa_secret::*};
+pub use self::{activate_mfa::*, disable_totp::*, enable_totp::*};
@@ -34,6 +34,7 @@ pub use self::{
 /// The phone number of a user. This is used to send OTPs, notifications, etc to
 /// the user.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct UserPhoneNumber {
 	/// The country code of the phone number. This is a 2 letter code, such as
@@ -51,6 +52,7 @@ pub struct UserPhoneNumber {
 /// to be public. For privacy reasons, things like their email address and phone
 /// number are not public.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct BasicUserInfo {
 	/// The username of the user. This is unique to the user.
@@ -0,0 +1,18 @@
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Cancel the pending change of the recovery email of the currently
+	/// authenticated user, if any. The current recovery email is left unchanged.
+	CancelRecoveryEmailChange,
+	DELETE "/user/recovery-email",
+	api = false,
+	request_headers = {
+		/// The authorization token
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::PlainTokenAuthenticator
+	},
+);
@@ -0,0 +1,26 @@
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Change the recovery email of the currently authenticated user. An OTP will be
+	/// sent to the new email address, and the change only takes effect once the new
+	/// email address is verified using the [`super::VerifyUserEmailRequest`]
+	/// endpoint. Until then, the current recovery email stays active. Requesting a
+	/// new change replaces any change that is pending.
+	ChangeRecoveryEmail,
+	POST "/user/recovery-email",
+	api = false,
+	request_headers = {
+		/// The authorization token
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::PlainTokenAuthenticator
+	},
+	request = {
+		/// The new recovery email
+		#[preprocess(email)]
+		pub email: String,
+	},
+);
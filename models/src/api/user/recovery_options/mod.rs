@@ -1,5 +1,7 @@
-/// The endpoint to update the email of a user
-mod update_user_email;
+/// The endpoint to cancel a pending change of the recovery email of a user
+mod cancel_recovery_email_change;
+/// The endpoint to change the recovery email of a user
+mod change_recovery_email;
 /// The endpoint to update the phone number of a user
 mod update_user_phone_number;
 /// The endpoint to verify the email of a user
@@ -8,7 +10,8 @@ mod verify_user_email;
 mod verify_user_phone_number;
 
 pub use self::{
-	update_user_email::*,
+	cancel_recovery_email_change::*,
+	change_recovery_email::*,
 	update_user_phone_number::*,
 	verify_user_email::*,
 	verify_user_phone_number::*,
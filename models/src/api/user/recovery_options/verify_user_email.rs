@@ -2,8 +2,10 @@ use crate::prelude::*;
 
 macros::declare_api_endpoint!(
 	/// Verify the email for the currently authenticated user. This endpoint is used to
-	/// verify the email address after the user has requested to change their email
-	/// using the [`super::update_user_email`] endpoint.
+	/// verify the email address after the user has requested to change their recovery
+	/// email using the [`super::ChangeRecoveryEmailRequest`] endpoint. Once verified,
+	/// the new email replaces the current recovery email, and any password reset that
+	/// is in progress is invalidated.
 	VerifyUserEmail,
 	POST "/user/verify-email",
 	api = false,
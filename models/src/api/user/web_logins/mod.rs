@@ -163,12 +163,15 @@ use crate::prelude::*;
 /// we not only secure things for our users, but also inform them about security
 /// events that might affect their account.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct UserWebLogin {
 	/// The time at which this login expires. If the expiry has elapsed the user
 	/// should be automatically logged out.
+	#[cfg_attr(not(target_arch = "wasm32"), schemars(with = "String"))]
 	pub token_expiry: OffsetDateTime,
 	/// When this login was created.
+	#[cfg_attr(not(target_arch = "wasm32"), schemars(with = "String"))]
 	pub created: OffsetDateTime,
 	/// Which IP address this login was created from
 	pub created_ip: IpAddr,
@@ -0,0 +1,38 @@
+use time::OffsetDateTime;
+
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Issues a short-lived signed URL to download a layer (blob) of an image
+	/// in a container repository. The URL can be used without a token until it
+	/// expires, so that it can be handed to tools that can't authenticate.
+	GetContainerRepositoryBlobDownloadUrl,
+	GET "/workspace/:workspace_id/container-registry/:repository_id/blob/:digest/download-url" {
+		/// The workspace the container repository is in.
+		pub workspace_id: Uuid,
+		/// The id of the repository that the blob belongs to.
+		pub repository_id: Uuid,
+		/// The digest of the blob to download.
+		pub digest: String,
+	},
+	request_headers = {
+		/// The authorization token
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.repository_id,
+			permission: Permission::ContainerRegistryRepository(ContainerRegistryRepositoryPermission::View),
+		}
+	},
+	response = {
+		/// The signed URL that the blob can be downloaded from, relative to
+		/// the API
+		pub url: String,
+		/// The time after which the URL can no longer be used
+		#[cfg_attr(not(target_arch = "wasm32"), schemars(with = "String"))]
+		pub expires_at: OffsetDateTime,
+	}
+);
@@ -5,12 +5,14 @@ use crate::prelude::*;
 
 /// The response body for the ListContainerRepositories endpoint.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
 pub struct ContainerRepositoryTagAndDigestInfo {
 	/// The tag of the repository
 	pub tag: String,
 	/// The digest that this tag points to
 	pub digest: String,
 	/// The last updated time of the tag
+	#[cfg_attr(not(target_arch = "wasm32"), schemars(with = "String"))]
 	pub last_updated: OffsetDateTime,
 }
 
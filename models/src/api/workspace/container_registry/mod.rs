@@ -9,6 +9,8 @@ mod delete_repository;
 mod delete_repository_image;
 /// The endpoint to get the exposed ports of an image in a repository
 mod get_exposed_ports;
+/// The endpoint to issue a signed URL to download a blob of a repository
+mod get_repository_blob_download_url;
 /// The endpoint to get the details of an image in a repository
 mod get_repository_image_details;
 /// The endpoint to get the details of a repository
@@ -23,6 +25,7 @@ pub use self::{
 	delete_repository::*,
 	delete_repository_image::*,
 	get_exposed_ports::*,
+	get_repository_blob_download_url::*,
 	get_repository_image_details::*,
 	get_repository_info::*,
 	list_repositories::*,
@@ -51,6 +54,7 @@ pub struct ContainerRepositoryImageInfo {
 /// Represents a repository of container images in Patr's in-build container
 /// registry.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct ContainerRepository {
 	/// The name of the repository.
@@ -61,10 +65,12 @@ pub struct ContainerRepository {
 	/// updated.
 	///
 	/// TODO: Change this to audit log
+	#[cfg_attr(not(target_arch = "wasm32"), schemars(with = "String"))]
 	pub last_updated: OffsetDateTime,
 	/// The time the repository was created.nlas
 	///
 	/// TODO: Change this to audit log
+	#[cfg_attr(not(target_arch = "wasm32"), schemars(with = "String"))]
 	pub created: OffsetDateTime,
 }
 
@@ -0,0 +1,34 @@
+use super::{DatabaseConnection, DatabaseCredentialRotation};
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route to get the credentials to connect to a database, along with the
+	/// status of their rotation
+	GetDatabaseConnection,
+	GET "/workspace/:workspace_id/infrastructure/database/:database_id/connection" {
+		/// The workspace ID of the user
+		pub workspace_id: Uuid,
+		/// The ID of the database to get the connection details of
+		pub database_id: Uuid
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.database_id,
+			permission: Permission::Database(DatabasePermission::View)
+		}
+	},
+	response = {
+		/// The current credentials of the database
+		pub connection: DatabaseConnection,
+		/// The URL to connect to the database with the current credentials
+		pub connection_string: String,
+		/// The status of the rotation of the credentials
+		pub rotation: DatabaseCredentialRotation,
+	}
+);
@@ -0,0 +1,46 @@
+use super::DatabaseCredentialRotation;
+use crate::prelude::*;
+
+/// How long the previous credentials of a database keep working after a
+/// rotation, if no grace period is given
+pub const DEFAULT_DATABASE_CREDENTIAL_GRACE_PERIOD_SECONDS: u32 = 60 * 60;
+
+/// The longest grace period that the previous credentials of a database can be
+/// kept working for after a rotation
+pub const MAX_DATABASE_CREDENTIAL_GRACE_PERIOD_SECONDS: u32 = 7 * 24 * 60 * 60;
+
+macros::declare_api_endpoint!(
+	/// Route to issue new credentials for a database. The previous credentials
+	/// keep working until the end of the grace period, so that the apps using
+	/// them can pick up the new ones without an outage, and are disabled after.
+	RotateDatabaseCredentials,
+	POST "/workspace/:workspace_id/infrastructure/database/:database_id/credentials/rotate" {
+		/// The workspace ID of the user
+		pub workspace_id: Uuid,
+		/// The ID of the database to rotate the credentials of
+		pub database_id: Uuid
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.database_id,
+			permission: Permission::Database(DatabasePermission::Edit)
+		}
+	},
+	request = {
+		/// How long (in seconds) the previous credentials keep working for.
+		/// Defaults to [`DEFAULT_DATABASE_CREDENTIAL_GRACE_PERIOD_SECONDS`]
+		#[preprocess(range(max = Some(MAX_DATABASE_CREDENTIAL_GRACE_PERIOD_SECONDS)))]
+		pub grace_period_seconds: Option<u32>,
+	},
+	response = {
+		/// The status of the rotation of the credentials. The new credentials
+		/// can be fetched using the [`GetDatabaseConnectionRequest`]
+		pub rotation: DatabaseCredentialRotation,
+	}
+);
@@ -0,0 +1,258 @@
+use std::fmt::{self, Debug};
+
+use serde::{Deserialize, Serialize};
+
+use super::DatabaseEngine;
+
+/// A user that a runner creates on the engine of a managed database. The
+/// password is left out of the [`Debug`] output, so that it doesn't end up in
+/// the logs.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DatabaseUser {
+	/// The username of the user
+	pub username: String,
+	/// The password of the user
+	pub password: String,
+	/// The user that the database was created with. Users issued by rotating
+	/// the credentials get the privileges of this user, so that they can access
+	/// the same data
+	pub owner: String,
+}
+
+impl Debug for DatabaseUser {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("DatabaseUser")
+			.field("username", &self.username)
+			.field("password", &"********")
+			.field("owner", &self.owner)
+			.finish()
+	}
+}
+
+/// A command to run in the container of a managed database to change its
+/// users. The statements are piped to the standard input of the command
+/// instead of being passed as arguments, so that passwords never show up in
+/// the process list of the container.
+#[derive(Clone, PartialEq, Eq)]
+pub struct DatabaseUserCommand {
+	/// The command and its arguments
+	pub command: Vec<String>,
+	/// The statements to pipe to the standard input of the command
+	pub script: String,
+}
+
+impl DatabaseEngine {
+	/// The command that creates the given user on a database of this engine,
+	/// or changes its password if it already exists
+	pub fn create_user_command(&self, user: &DatabaseUser) -> DatabaseUserCommand {
+		let DatabaseUser {
+			username,
+			password,
+			owner,
+		} = user;
+
+		let script = match self {
+			Self::Postgres => {
+				let (username, password, owner) = (
+					sql_identifier(username),
+					sql_literal(password),
+					sql_identifier(owner),
+				);
+				let mut script = format!(
+					concat!(
+						"SELECT EXISTS(SELECT FROM pg_roles WHERE rolname = {}) ",
+						"AS user_exists \\gset\n",
+						"\\if :user_exists\n",
+						"ALTER ROLE {username} LOGIN PASSWORD {password};\n",
+						"\\else\n",
+						"CREATE ROLE {username} LOGIN CREATEDB PASSWORD {password};\n",
+						"\\endif\n",
+					),
+					sql_literal(&user.username),
+					username = username,
+					password = password,
+				);
+				if user.username != user.owner {
+					script.push_str(&format!("GRANT {owner} TO {username};\n"));
+				}
+				script
+			}
+			Self::Mysql => {
+				let (username, password) = (mysql_literal(username), mysql_literal(password));
+				format!(
+					concat!(
+						"CREATE USER IF NOT EXISTS {username}@'%' IDENTIFIED BY {password};\n",
+						"ALTER USER {username}@'%' IDENTIFIED BY {password};\n",
+						"GRANT ALL PRIVILEGES ON *.* TO {username}@'%';\n",
+					),
+					username = username,
+					password = password,
+				)
+			}
+			Self::Mongo => {
+				let (username, password) = (js_string(username), js_string(password));
+				format!(
+					concat!(
+						"if (db.getUser({username})) {{\n",
+						"\tdb.updateUser({username}, {{ pwd: {password} }});\n",
+						"}} else {{\n",
+						"\tdb.createUser({{ user: {username}, pwd: {password}, ",
+						"roles: [\"root\"] }});\n",
+						"}}\n",
+					),
+					username = username,
+					password = password,
+				)
+			}
+			Self::Redis => format!(
+				"ACL SETUSER {} reset on {} ~* &* +@all\n",
+				redis_string(username),
+				redis_string(&format!(">{password}")),
+			),
+		};
+
+		DatabaseUserCommand {
+			command: self.admin_command(),
+			script,
+		}
+	}
+
+	/// The command that drops the given user from a database of this engine,
+	/// if it exists. On engines where users own the objects they create, the
+	/// objects are handed over to the owner of the database first
+	pub fn drop_user_command(&self, username: &str, owner: &str) -> DatabaseUserCommand {
+		let script = match self {
+			Self::Postgres => format!(
+				concat!(
+					"SELECT EXISTS(SELECT FROM pg_roles WHERE rolname = {}) ",
+					"AS user_exists \\gset\n",
+					"\\if :user_exists\n",
+					"REASSIGN OWNED BY {username} TO {owner};\n",
+					"DROP OWNED BY {username};\n",
+					"DROP ROLE {username};\n",
+					"\\endif\n",
+				),
+				sql_literal(username),
+				username = sql_identifier(username),
+				owner = sql_identifier(owner),
+			),
+			Self::Mysql => format!("DROP USER IF EXISTS {}@'%';\n", mysql_literal(username)),
+			Self::Mongo => {
+				let username = js_string(username);
+				format!("if (db.getUser({username})) {{\n\tdb.dropUser({username});\n}}\n")
+			}
+			Self::Redis => format!("ACL DELUSER {}\n", redis_string(username)),
+		};
+
+		DatabaseUserCommand {
+			command: self.admin_command(),
+			script,
+		}
+	}
+
+	/// The command that runs the statements from its standard input as the
+	/// administrator of a database of this engine, using the credentials that
+	/// the container of the database was started with
+	fn admin_command(&self) -> Vec<String> {
+		let command: &[&str] = match self {
+			Self::Postgres => &[
+				"psql",
+				"--no-psqlrc",
+				"--set=ON_ERROR_STOP=1",
+				"--username=postgres",
+				"--dbname=postgres",
+			],
+			Self::Mysql => &["sh", "-c", r#"exec mysql -uroot -p"$MYSQL_ROOT_PASSWORD""#],
+			Self::Mongo => &[
+				"sh",
+				"-c",
+				concat!(
+					"exec mongosh --quiet ",
+					r#"-u "$MONGO_INITDB_ROOT_USERNAME" -p "$MONGO_INITDB_ROOT_PASSWORD" admin"#
+				),
+			],
+			Self::Redis => &["redis-cli"],
+		};
+		command.iter().map(ToString::to_string).collect()
+	}
+}
+
+/// Quotes an identifier for a Postgres statement
+fn sql_identifier(value: &str) -> String {
+	format!("\"{}\"", value.replace('"', "\"\""))
+}
+
+/// Quotes a string literal for a Postgres statement
+fn sql_literal(value: &str) -> String {
+	format!("'{}'", value.replace('\'', "''"))
+}
+
+/// Quotes a string literal for a MySQL statement, where backslashes are escape
+/// characters as well
+fn mysql_literal(value: &str) -> String {
+	format!("'{}'", value.replace('\\', "\\\\").replace('\'', "''"))
+}
+
+/// Quotes a string for a mongosh script
+fn js_string(value: &str) -> String {
+	serde_json::to_string(value).expect("strings should always serialize to JSON")
+}
+
+/// Quotes an argument of a command for `redis-cli`
+fn redis_string(value: &str) -> String {
+	format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn values_are_quoted_for_each_engine() {
+		let user = DatabaseUser {
+			username: "patr_a1b2".to_string(),
+			password: r#"it's a "secret"\"#.to_string(),
+			owner: "patr".to_string(),
+		};
+
+		let postgres = DatabaseEngine::Postgres.create_user_command(&user).script;
+		assert!(postgres
+			.contains(r#"CREATE ROLE "patr_a1b2" LOGIN CREATEDB PASSWORD 'it''s a "secret"\';"#));
+		assert!(postgres.contains(r#"GRANT "patr" TO "patr_a1b2";"#));
+
+		let mysql = DatabaseEngine::Mysql.create_user_command(&user).script;
+		assert!(mysql.contains(r#"IDENTIFIED BY 'it''s a "secret"\\';"#));
+
+		let mongo = DatabaseEngine::Mongo.create_user_command(&user).script;
+		assert!(mongo.contains(r#"pwd: "it's a \"secret\"\\""#));
+
+		let redis = DatabaseEngine::Redis.create_user_command(&user).script;
+		assert_eq!(
+			redis,
+			"ACL SETUSER \"patr_a1b2\" reset on \">it's a \\\"secret\\\"\\\\\" ~* &* +@all\n"
+		);
+	}
+
+	#[test]
+	fn the_owner_is_not_granted_to_itself() {
+		let user = DatabaseUser {
+			username: "patr".to_string(),
+			password: "password".to_string(),
+			owner: "patr".to_string(),
+		};
+
+		let script = DatabaseEngine::Postgres.create_user_command(&user).script;
+		assert!(!script.contains("GRANT"));
+	}
+
+	#[test]
+	fn dropped_users_hand_over_their_objects_to_the_owner() {
+		let script = DatabaseEngine::Postgres
+			.drop_user_command("patr_a1b2", "patr")
+			.script;
+
+		assert!(script.contains(r#"REASSIGN OWNED BY "patr_a1b2" TO "patr";"#));
+		assert!(script.contains(r#"DROP ROLE "patr_a1b2";"#));
+	}
+}
@@ -0,0 +1,25 @@
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route to abort the canary of a deployment. The canary is removed, and
+	/// all the requests are sent to the deployment's current image again
+	AbortDeploymentCanary,
+	DELETE "/workspace/:workspace_id/deployment/:deployment_id/canary" {
+		/// The workspace ID of the user
+		pub workspace_id: Uuid,
+		/// The deployment ID to abort the canary of
+		pub deployment_id: Uuid,
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.deployment_id,
+			permission: Permission::Deployment(DeploymentPermission::Edit),
+		}
+	}
+);
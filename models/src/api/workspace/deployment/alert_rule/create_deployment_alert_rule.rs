@@ -0,0 +1,50 @@
+use super::{AlertNotificationChannel, DeploymentAlertMetric};
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route to create a new alert rule that monitors the resource usage of a
+	/// deployment
+	CreateDeploymentAlertRule,
+	POST "/workspace/:workspace_id/deployment/:deployment_id/alert-rule" {
+		/// The workspace ID of the user
+		pub workspace_id: Uuid,
+		/// The ID of the deployment to create the alert rule for
+		pub deployment_id: Uuid,
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.deployment_id,
+			permission: Permission::Deployment(DeploymentPermission::Edit),
+		}
+	},
+	request = {
+		/// The metric that is monitored by the rule
+		#[preprocess(none)]
+		pub metric: DeploymentAlertMetric,
+		/// The usage (as a percentage) above which the rule is breached
+		#[preprocess(range(min = 1, max = 100))]
+		pub threshold: u8,
+		/// The time (in seconds) the usage has to stay above the threshold
+		/// before the alert fires
+		#[preprocess(none)]
+		pub duration_seconds: u32,
+		/// The time (in seconds) the usage has to stay below the threshold
+		/// before a firing alert is resolved
+		#[preprocess(none)]
+		pub resolve_duration_seconds: u32,
+		/// Where the notifications of the rule are sent
+		#[preprocess(none)]
+		pub notification_channel: AlertNotificationChannel,
+	},
+	response = {
+		/// The ID of the created alert rule
+		#[serde(flatten)]
+		pub id: WithId<()>,
+	}
+);
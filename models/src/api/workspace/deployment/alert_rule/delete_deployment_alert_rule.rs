@@ -0,0 +1,27 @@
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route to delete an alert rule of a deployment. If the alert is firing,
+	/// no resolution notification is sent for it
+	DeleteDeploymentAlertRule,
+	DELETE "/workspace/:workspace_id/deployment/:deployment_id/alert-rule/:alert_rule_id" {
+		/// The workspace ID of the user
+		pub workspace_id: Uuid,
+		/// The ID of the deployment that the alert rule belongs to
+		pub deployment_id: Uuid,
+		/// The ID of the alert rule to delete
+		pub alert_rule_id: Uuid,
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.deployment_id,
+			permission: Permission::Deployment(DeploymentPermission::Edit),
+		}
+	}
+);
@@ -0,0 +1,30 @@
+use super::DeploymentAlertRule;
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route to list all the alert rules of a deployment, along with whether
+	/// they are currently firing
+	ListDeploymentAlertRules,
+	GET "/workspace/:workspace_id/deployment/:deployment_id/alert-rule" {
+		/// The workspace ID of the user
+		pub workspace_id: Uuid,
+		/// The ID of the deployment to list the alert rules of
+		pub deployment_id: Uuid,
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.deployment_id,
+			permission: Permission::Deployment(DeploymentPermission::View),
+		}
+	},
+	response = {
+		/// The list of alert rules of the deployment
+		pub alert_rules: Vec<WithId<DeploymentAlertRule>>,
+	}
+);
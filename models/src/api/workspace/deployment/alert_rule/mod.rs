@@ -0,0 +1,123 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::prelude::*;
+
+/// The endpoint to create an alert rule for a deployment
+mod create_deployment_alert_rule;
+/// The endpoint to delete an alert rule of a deployment
+mod delete_deployment_alert_rule;
+/// The endpoint to list all the alert rules of a deployment
+mod list_deployment_alert_rules;
+/// The endpoint to update an alert rule of a deployment
+mod update_deployment_alert_rule;
+
+pub use self::{
+	create_deployment_alert_rule::*,
+	delete_deployment_alert_rule::*,
+	list_deployment_alert_rules::*,
+	update_deployment_alert_rule::*,
+};
+
+/// A rule that fires an alert when the resource usage of a deployment stays
+/// above a threshold for a period of time. The alert is resolved once the usage
+/// stays below the threshold for another period of time. A notification is sent
+/// only when the alert fires and when it is resolved, and not on every
+/// evaluation in between.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct DeploymentAlertRule {
+	/// The metric that is monitored by this rule
+	pub metric: DeploymentAlertMetric,
+	/// The usage (as a percentage of the resources allocated to the
+	/// deployment) above which the rule is breached
+	pub threshold: u8,
+	/// The time (in seconds) the usage has to stay above the threshold before
+	/// the alert fires
+	pub duration_seconds: u32,
+	/// The time (in seconds) the usage has to stay below the threshold before a
+	/// firing alert is resolved
+	pub resolve_duration_seconds: u32,
+	/// Where the notifications of this rule are sent
+	pub notification_channel: AlertNotificationChannel,
+	/// The time since which the alert has been firing. This is `None` if the
+	/// alert is not firing
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[cfg_attr(not(target_arch = "wasm32"), schemars(with = "Option<String>"))]
+	pub firing_since: Option<OffsetDateTime>,
+}
+
+/// The resource usage metric that an alert rule monitors
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(sqlx::Type, schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(
+	not(target_arch = "wasm32"),
+	sqlx(type_name = "DEPLOYMENT_ALERT_METRIC", rename_all = "snake_case")
+)]
+pub enum DeploymentAlertMetric {
+	/// The CPU usage, as a percentage of the CPUs of the machine type
+	CpuUsage,
+	/// The memory usage, as a percentage of the memory of the machine type
+	MemoryUsage,
+}
+
+/// The channel that the notifications of an alert rule are sent to
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum AlertNotificationChannel {
+	/// The notification is sent by email
+	#[serde(rename_all = "camelCase")]
+	Email {
+		/// The email address to send the notification to
+		email: String,
+	},
+	/// The notification is sent as a JSON [`DeploymentAlertNotification`] in
+	/// a `POST` request to a webhook
+	#[serde(rename_all = "camelCase")]
+	Webhook {
+		/// The URL of the webhook. Must be an `http` or `https` URL
+		url: String,
+	},
+}
+
+/// The state of an alert that a notification is sent for
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub enum DeploymentAlertState {
+	/// The usage stayed above the threshold for the duration of the rule
+	Fired,
+	/// The usage stayed below the threshold for the resolve duration of the
+	/// rule
+	Resolved,
+}
+
+/// The body of the notification sent to a webhook when an alert fires or is
+/// resolved
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct DeploymentAlertNotification {
+	/// The ID of the alert rule
+	pub alert_rule_id: Uuid,
+	/// The ID of the workspace that the deployment belongs to
+	pub workspace_id: Uuid,
+	/// The ID of the deployment
+	pub deployment_id: Uuid,
+	/// The name of the deployment
+	pub deployment_name: String,
+	/// The metric that is monitored by the rule
+	pub metric: DeploymentAlertMetric,
+	/// The threshold of the rule, as a percentage
+	pub threshold: u8,
+	/// The usage at the time of the notification, as a percentage
+	pub value: f64,
+	/// Whether the alert fired or was resolved
+	pub state: DeploymentAlertState,
+	/// The time at which the alert fired or was resolved
+	#[cfg_attr(not(target_arch = "wasm32"), schemars(with = "String"))]
+	pub timestamp: OffsetDateTime,
+}
@@ -0,0 +1,44 @@
+use super::AlertNotificationChannel;
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route to update an alert rule of a deployment. A firing alert keeps
+	/// firing until it is resolved under the updated rule
+	UpdateDeploymentAlertRule,
+	PATCH "/workspace/:workspace_id/deployment/:deployment_id/alert-rule/:alert_rule_id" {
+		/// The workspace ID of the user
+		pub workspace_id: Uuid,
+		/// The ID of the deployment that the alert rule belongs to
+		pub deployment_id: Uuid,
+		/// The ID of the alert rule to update
+		pub alert_rule_id: Uuid,
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.deployment_id,
+			permission: Permission::Deployment(DeploymentPermission::Edit),
+		}
+	},
+	request = {
+		/// The new usage (as a percentage) above which the rule is breached
+		#[preprocess(optional(range(min = 1, max = 100)))]
+		pub threshold: Option<u8>,
+		/// The new time (in seconds) the usage has to stay above the threshold
+		/// before the alert fires
+		#[preprocess(none)]
+		pub duration_seconds: Option<u32>,
+		/// The new time (in seconds) the usage has to stay below the threshold
+		/// before a firing alert is resolved
+		#[preprocess(none)]
+		pub resolve_duration_seconds: Option<u32>,
+		/// The new channel that the notifications of the rule are sent to
+		#[preprocess(none)]
+		pub notification_channel: Option<AlertNotificationChannel>,
+	}
+);
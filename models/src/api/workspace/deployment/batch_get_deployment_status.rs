@@ -0,0 +1,62 @@
+use serde::{Deserialize, Serialize};
+
+use super::{DeploymentReplicaReadiness, DeploymentStatus};
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route to get the current status of multiple deployments at once, so that
+	/// a list of deployments can be kept up to date without fetching each of
+	/// them. Only the first [`MAX_DEPLOYMENT_STATUS_BATCH_SIZE`][1] IDs are
+	/// looked up, and the rest are left out of the response. The result for
+	/// each ID is returned in the same order as the IDs in the request.
+	///
+	/// [1]: crate::utils::constants::MAX_DEPLOYMENT_STATUS_BATCH_SIZE
+	BatchGetDeploymentStatus,
+	POST "/workspace/:workspace_id/deployment/batch-status" {
+		/// The workspace ID of the user
+		pub workspace_id: Uuid,
+	},
+	authentication = {
+		AppAuthentication::<Self>::WorkspaceMembershipAuthenticator {
+			extract_workspace_id: |req| req.path.workspace_id,
+		}
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	request = {
+		/// The IDs of the deployments to get the status of
+		#[preprocess(none)]
+		pub deployment_ids: Vec<Uuid>,
+	},
+	response = {
+		/// The status of each deployment, in the same order as the
+		/// `deploymentIds` in the request
+		pub statuses: Vec<DeploymentStatusResult>,
+	}
+);
+
+/// The status of a single deployment in a [`BatchGetDeploymentStatusRequest`]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct DeploymentStatusResult {
+	/// The ID of the deployment
+	pub deployment_id: Uuid,
+	/// The current status of the deployment, if it could be read
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub status: Option<DeploymentStatus>,
+	/// How many of the primary replicas of the deployment are ready to receive
+	/// requests, out of all of them, if the runner has reported it
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub replicas: Option<DeploymentReplicaReadiness>,
+	/// The error that occured when reading the status of the deployment. This
+	/// is [`ErrorType::ResourceDoesNotExist`] if there is no such deployment
+	/// in the workspace, and [`ErrorType::Unauthorized`] if the user is not
+	/// allowed to view the deployment
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub error: Option<ErrorType>,
+}
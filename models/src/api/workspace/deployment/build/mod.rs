@@ -0,0 +1,95 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::prelude::*;
+
+/// The endpoint to set the source that the image of a deployment is built from
+mod set_deployment_build_source;
+/// The endpoint to start building the image of a deployment from its source
+mod start_deployment_build;
+/// The endpoint to stream the logs of a build of a deployment
+mod stream_deployment_build_logs;
+/// The endpoint for the runner to report the progress of a build
+mod update_deployment_build;
+
+pub use self::{
+	set_deployment_build_source::*,
+	start_deployment_build::*,
+	stream_deployment_build_logs::*,
+	update_deployment_build::*,
+};
+
+/// The git repository that the image of a deployment is built from. The built
+/// image is pushed to the Patr registry repository of the deployment, so only
+/// deployments that use the Patr registry can be built from source.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct DeploymentBuildSource {
+	/// The URL of the git repository to clone
+	pub git_url: String,
+	/// The branch of the git repository to build
+	pub branch: String,
+	/// The path of the Dockerfile within the repository. Defaults to the
+	/// `Dockerfile` at the root of the repository
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub dockerfile_path: Option<String>,
+}
+
+/// The status of a build of a deployment's image. This is tracked separately
+/// from the status of the deployment, since a deployment keeps running its
+/// current image while a new one is being built.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(sqlx::Type, schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(
+	not(target_arch = "wasm32"),
+	sqlx(type_name = "DEPLOYMENT_BUILD_STATUS", rename_all = "lowercase")
+)]
+pub enum DeploymentBuildStatus {
+	/// The build is waiting to be picked up by the runner
+	Queued,
+	/// The image is being built and pushed
+	Building,
+	/// The image was built and pushed, and the deployment has been updated to
+	/// run it
+	Succeeded,
+	/// The image could not be built or pushed. The deployment keeps running
+	/// the image that it was running before the build
+	Failed,
+}
+
+impl DeploymentBuildStatus {
+	/// Returns true if the build has finished, whether it succeeded or not
+	pub fn is_finished(&self) -> bool {
+		matches!(self, Self::Succeeded | Self::Failed)
+	}
+}
+
+/// A build of a deployment's image from its build source
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct DeploymentBuild {
+	/// The source that the image is built from, as it was when the build was
+	/// started
+	#[serde(flatten)]
+	pub source: DeploymentBuildSource,
+	/// The tag that the built image is pushed with
+	pub image_tag: String,
+	/// The status of the build
+	pub status: DeploymentBuildStatus,
+	/// The digest of the built image, once the build has succeeded
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub image_digest: Option<String>,
+	/// The reason the build failed, if it did
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub error: Option<String>,
+	/// The time the build was started
+	#[cfg_attr(not(target_arch = "wasm32"), schemars(with = "String"))]
+	pub created: OffsetDateTime,
+	/// The time the build finished, whether it succeeded or not
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[cfg_attr(not(target_arch = "wasm32"), schemars(with = "Option<String>"))]
+	pub finished: Option<OffsetDateTime>,
+}
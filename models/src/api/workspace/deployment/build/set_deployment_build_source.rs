@@ -0,0 +1,33 @@
+use super::DeploymentBuildSource;
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route to set (or remove) the git repository that the image of a
+	/// deployment is built from. Only deployments that use the Patr registry
+	/// can be built from source
+	SetDeploymentBuildSource,
+	PUT "/workspace/:workspace_id/deployment/:deployment_id/build-source" {
+		/// The workspace ID of the user
+		pub workspace_id: Uuid,
+		/// The ID of the deployment to set the build source of
+		pub deployment_id: Uuid,
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.deployment_id,
+			permission: Permission::Deployment(DeploymentPermission::Edit),
+		}
+	},
+	request = {
+		/// The source to build the image of the deployment from. If this is
+		/// `None`, the deployment is no longer built from source
+		#[preprocess(none)]
+		pub build_source: Option<DeploymentBuildSource>,
+	}
+);
@@ -0,0 +1,32 @@
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route to start building the image of a deployment from its build source.
+	/// Once the build succeeds, the deployment is updated to run the built
+	/// image. If the build fails, the deployment keeps running its current
+	/// image
+	StartDeploymentBuild,
+	POST "/workspace/:workspace_id/deployment/:deployment_id/build" {
+		/// The workspace ID of the user
+		pub workspace_id: Uuid,
+		/// The ID of the deployment to build
+		pub deployment_id: Uuid,
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.deployment_id,
+			permission: Permission::Deployment(DeploymentPermission::Edit),
+		}
+	},
+	response = {
+		/// The ID of the build that was started
+		#[serde(flatten)]
+		pub id: WithId<()>,
+	}
+);
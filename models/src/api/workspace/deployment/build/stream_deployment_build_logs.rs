@@ -0,0 +1,34 @@
+use crate::{api::workspace::deployment::DeploymentLog, prelude::*};
+
+macros::declare_stream_endpoint!(
+	/// Route to stream the logs of a build of a deployment
+	StreamDeploymentBuildLogs,
+	GET "/workspace/:workspace_id/deployment/:deployment_id/build/:build_id/logs/stream" {
+		/// The workspace ID of the user
+		pub workspace_id: Uuid,
+		/// The ID of the deployment that is being built
+		pub deployment_id: Uuid,
+		/// The ID of the build to stream the logs of
+		pub build_id: Uuid,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.deployment_id,
+			permission: Permission::Deployment(DeploymentPermission::View),
+		}
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	server_msg = {
+		/// There is new log data for the build
+		LogData {
+			/// The new logs of the build
+			logs: Vec<DeploymentLog>,
+		},
+	},
+	client_msg = {},
+);
@@ -0,0 +1,40 @@
+use super::DeploymentBuildStatus;
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route for the runner to report the progress of a build of a deployment.
+	/// When a build is reported as succeeded, the deployment is updated to run
+	/// the built image
+	UpdateDeploymentBuild,
+	PATCH "/workspace/:workspace_id/deployment/:deployment_id/build/:build_id" {
+		/// The workspace ID of the user
+		pub workspace_id: Uuid,
+		/// The ID of the deployment that is being built
+		pub deployment_id: Uuid,
+		/// The ID of the build to update
+		pub build_id: Uuid,
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.deployment_id,
+			permission: Permission::Deployment(DeploymentPermission::Edit),
+		}
+	},
+	request = {
+		/// The new status of the build
+		#[preprocess(none)]
+		pub status: DeploymentBuildStatus,
+		/// The digest of the built image. Required when the build has succeeded
+		#[preprocess(none)]
+		pub image_digest: Option<String>,
+		/// The reason the build failed, if it did
+		#[preprocess(none)]
+		pub error: Option<String>,
+	}
+);
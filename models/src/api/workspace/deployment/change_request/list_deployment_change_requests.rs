@@ -0,0 +1,29 @@
+use super::DeploymentChangeRequest;
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route to list the changes to a deployment that are waiting for approval
+	ListDeploymentChangeRequests,
+	GET "/workspace/:workspace_id/deployment/:deployment_id/change-request" {
+		/// The workspace ID of the user
+		pub workspace_id: Uuid,
+		/// The ID of the deployment to list the pending changes of
+		pub deployment_id: Uuid,
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.deployment_id,
+			permission: Permission::Deployment(DeploymentPermission::View),
+		}
+	},
+	response = {
+		/// The changes that are waiting for approval, the oldest first
+		pub change_requests: Vec<WithId<DeploymentChangeRequest>>,
+	}
+);
@@ -0,0 +1,50 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use super::UpdateDeploymentRequest;
+use crate::prelude::*;
+
+/// The endpoint to list the changes to a deployment that are waiting for
+/// approval
+mod list_deployment_change_requests;
+/// The endpoint to approve or reject a change to a deployment
+mod review_deployment_change;
+
+pub use self::{list_deployment_change_requests::*, review_deployment_change::*};
+
+/// A change to the configuration of a deployment that is waiting for approval.
+/// When a workspace requires changes to deployments to be approved, updating a
+/// deployment creates one of these instead of applying the change, and the
+/// change is only applied once another user approves it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct DeploymentChangeRequest {
+	/// The ID of the user who requested the change. This user cannot approve
+	/// the change themselves
+	pub requested_by: Uuid,
+	/// The changes to the deployment, exactly as they were requested. Secret
+	/// environment variables are masked
+	pub changes: UpdateDeploymentRequest,
+	/// The time the change was requested at
+	#[cfg_attr(not(target_arch = "wasm32"), schemars(with = "String"))]
+	pub created_at: OffsetDateTime,
+}
+
+/// The decision made when reviewing a change to a deployment
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
+#[cfg_attr(not(target_arch = "wasm32"), derive(sqlx::Type))]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(
+	not(target_arch = "wasm32"),
+	sqlx(type_name = "DEPLOYMENT_CHANGE_STATUS", rename_all = "snake_case")
+)]
+pub enum DeploymentChangeStatus {
+	/// The change is waiting to be reviewed
+	Pending,
+	/// The change was approved and applied to the deployment
+	Approved,
+	/// The change was rejected and was not applied
+	Rejected,
+}
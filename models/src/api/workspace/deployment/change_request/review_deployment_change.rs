@@ -0,0 +1,39 @@
+use super::DeploymentChangeStatus;
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route to approve or reject a change to a deployment that is waiting for
+	/// approval. An approved change is applied to the deployment right away.
+	/// The user who requested the change cannot approve it
+	ReviewDeploymentChange,
+	POST "/workspace/:workspace_id/deployment/:deployment_id/change-request/:change_request_id" {
+		/// The workspace ID of the user
+		pub workspace_id: Uuid,
+		/// The ID of the deployment that the change is for
+		pub deployment_id: Uuid,
+		/// The ID of the change to review
+		pub change_request_id: Uuid,
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.deployment_id,
+			permission: Permission::Deployment(DeploymentPermission::Edit),
+		}
+	},
+	request = {
+		/// Whether the change is approved. An approved change is applied to
+		/// the deployment, and a rejected change is discarded
+		#[preprocess(none)]
+		pub approve: bool,
+	},
+	response = {
+		/// The status of the change after it was reviewed
+		pub status: DeploymentChangeStatus,
+	}
+);
@@ -1,3 +1,5 @@
+use std::collections::{BTreeMap, BTreeSet};
+
 use super::{DeploymentRegistry, DeploymentRunningDetails};
 use crate::{prelude::*, utils::constants::RESOURCE_NAME_REGEX};
 
@@ -36,13 +38,45 @@ macros::declare_api_endpoint!(
 		#[preprocess(none)]
 		pub runner: Uuid,
 		/// The machine type the deployment pod will run on
-		/// Different machine types will have different resource allocation
+		/// Different machine types will have different resource allocation.
+		/// If not provided, the machine type of the template will be used, or
+		/// the default machine type of the workspace if the template doesn't
+		/// have one either
+		#[preprocess(none)]
+		#[serde(default, skip_serializing_if = "Option::is_none")]
+		pub machine_type: Option<Uuid>,
+		/// The deployment template to use for any values that are not set in
+		/// this request. Values explicitly set in this request always take
+		/// precedence over the ones in the template
+		#[preprocess(none)]
+		#[serde(default, skip_serializing_if = "Option::is_none")]
+		pub template_id: Option<Uuid>,
+		/// The pull secret to use to pull the image of the deployment, in case
+		/// the image is hosted on a private external registry
 		#[preprocess(none)]
-		pub machine_type: Uuid,
+		#[serde(default, skip_serializing_if = "Option::is_none")]
+		pub pull_secret_id: Option<Uuid>,
 		/// The details of the deployment which contains information related to configuration
 		#[preprocess(none)]
 		#[serde(flatten)]
 		pub running_details: DeploymentRunningDetails,
+		/// The names of the environment variables whose values are secret.
+		/// These are masked in all responses, but are still set on the running
+		/// deployment. This only applies to variables with a plain value
+		#[preprocess(none)]
+		#[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+		pub secret_variables: BTreeSet<String>,
+		/// The labels to attach to the deployment, to organize the deployments
+		/// in the workspace. These are only metadata, and don't affect how or
+		/// where the deployment runs
+		#[preprocess(none)]
+		#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+		pub labels: BTreeMap<String, String>,
+		/// The deployments in the workspace that the deployment depends on. The
+		/// deployment is only started once all of them are healthy
+		#[preprocess(none)]
+		#[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+		pub depends_on: BTreeSet<Uuid>,
 		/// Option to start the deployment once it is created
 		#[preprocess(none)]
 		pub deploy_on_create: bool,
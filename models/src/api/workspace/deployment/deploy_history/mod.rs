@@ -11,10 +11,14 @@ pub use self::{delete_deploy_history::*, list_deploy_history::*};
 /// The deployment history of a deployment. This is a list of the images digests
 /// the deployment has ran and the timestamp of when the digest previously ran
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct DeploymentDeployHistory {
-	/// The images digests the deployment has ran
+	/// The images digests the deployment has ran. Images from registries other
+	/// than the Patr registry, whose digests aren't known, are listed by their
+	/// reference instead, such as `registry.hub.docker.com/library/nginx:1.27`
 	pub image_digest: String,
 	/// The timestamp of when the digest previously ran
+	#[cfg_attr(not(target_arch = "wasm32"), schemars(with = "String"))]
 	pub created: OffsetDateTime,
 }
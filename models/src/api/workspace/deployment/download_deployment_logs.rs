@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::prelude::*;
+
+/// The format that the logs of a deployment can be downloaded in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub enum DeploymentLogFormat {
+	/// Plain text, with one log line per line, prefixed by its timestamp
+	#[default]
+	Text,
+	/// Newline-delimited JSON, with one [`DeploymentLog`][1] per line
+	///
+	/// [1]: super::DeploymentLog
+	Json,
+}
+
+impl DeploymentLogFormat {
+	/// The content type of the downloaded file
+	pub const fn content_type(&self) -> &'static str {
+		match self {
+			Self::Text => "text/plain; charset=utf-8",
+			Self::Json => "application/x-ndjson",
+		}
+	}
+
+	/// The extension of the downloaded file
+	pub const fn file_extension(&self) -> &'static str {
+		match self {
+			Self::Text => "log",
+			Self::Json => "ndjson",
+		}
+	}
+}
+
+/// The header that is set on the response when the requested time range was
+/// longer than what can be downloaded at once, and only the start of the range
+/// was downloaded. The value is the time (in RFC 3339) up to which the logs
+/// were downloaded.
+pub const LOGS_TRUNCATED_AT_HEADER: &str = "x-logs-truncated-at";
+
+macros::declare_api_endpoint!(
+	/// Route to download the logs of a deployment for a time range, as a file.
+	/// The logs are streamed in chronological order, so that large ranges can
+	/// be downloaded without being buffered in memory.
+	DownloadDeploymentLogs,
+	GET "/workspace/:workspace_id/deployment/:deployment_id/logs/download" {
+		/// The workspace ID of the user
+		pub workspace_id: Uuid,
+		/// The deployment ID to download the logs for
+		pub deployment_id: Uuid,
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.deployment_id,
+			permission: Permission::Deployment(DeploymentPermission::View),
+		}
+	},
+	query = {
+		/// The time from which the logs should be downloaded. Logs older than
+		/// the retention period are not available
+		#[cfg_attr(not(target_arch = "wasm32"), schemars(with = "String"))]
+		pub start_time: OffsetDateTime,
+		/// The time up until which the logs should be downloaded. Defaults to
+		/// the current time
+		#[cfg_attr(not(target_arch = "wasm32"), schemars(with = "Option<String>"))]
+		pub end_time: Option<OffsetDateTime>,
+		/// The format to download the logs in. Defaults to plain text
+		pub format: Option<DeploymentLogFormat>,
+		/// The search query to filter logs
+		pub search: Option<String>,
+	},
+	raw_response = true,
+);
@@ -0,0 +1,32 @@
+use super::DeploymentErrorPages;
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route to get the custom error pages of a deployment. Only the pages set
+	/// on the deployment itself are returned, not the defaults of the
+	/// workspace
+	GetDeploymentErrorPages,
+	GET "/workspace/:workspace_id/deployment/:deployment_id/error-pages" {
+		/// The workspace ID of the user
+		pub workspace_id: Uuid,
+		/// The deployment ID to get the error pages of
+		pub deployment_id: Uuid,
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.deployment_id,
+			permission: Permission::Deployment(DeploymentPermission::View),
+		}
+	},
+	response = {
+		/// The HTML of the custom error pages of the deployment, by the
+		/// situation that they are served in
+		pub error_pages: DeploymentErrorPages,
+	}
+);
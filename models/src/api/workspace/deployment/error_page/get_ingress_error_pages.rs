@@ -0,0 +1,28 @@
+use super::DeploymentErrorPages;
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route for the ingress to get the custom error pages that it should serve
+	/// for a deployment, with the pages of the deployment taking precedence
+	/// over the defaults of its workspace. The ingress only gets the pages when
+	/// it has to serve one, and caches them by the version returned along with
+	/// the activity of the deployment. Only the ingress can get them, using the
+	/// token that it is configured with
+	GetIngressErrorPages,
+	GET "/deployment/:deployment_id/error-pages" {
+		/// The deployment ID to get the error pages of
+		pub deployment_id: Uuid,
+	},
+	request_headers = {
+		/// The token that the ingress is configured with
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	response = {
+		/// The HTML of the custom error pages that the ingress should serve
+		/// instead of its own error responses, by the situation that they are
+		/// served in
+		pub error_pages: DeploymentErrorPages,
+	}
+);
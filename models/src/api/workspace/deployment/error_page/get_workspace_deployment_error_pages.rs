@@ -0,0 +1,28 @@
+use super::DeploymentErrorPages;
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route to get the default custom error pages of the deployments in a
+	/// workspace
+	GetWorkspaceDeploymentErrorPages,
+	GET "/workspace/:workspace_id/deployment/error-pages" {
+		/// The workspace ID to get the default error pages of
+		pub workspace_id: Uuid,
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::WorkspaceMembershipAuthenticator {
+			extract_workspace_id: |req| req.path.workspace_id,
+		}
+	},
+	response = {
+		/// The HTML of the default custom error pages, by the situation that
+		/// they are served in
+		pub error_pages: DeploymentErrorPages,
+	}
+);
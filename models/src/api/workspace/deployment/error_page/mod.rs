@@ -0,0 +1,140 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::*;
+
+/// The endpoint to get the custom error pages of a deployment
+mod get_deployment_error_pages;
+/// The endpoint for the ingress to get the custom error pages that it should
+/// serve for a deployment
+mod get_ingress_error_pages;
+/// The endpoint to get the default custom error pages of the deployments in a
+/// workspace
+mod get_workspace_deployment_error_pages;
+/// The endpoint to set the custom error pages of a deployment
+mod set_deployment_error_pages;
+/// The endpoint to set the default custom error pages of the deployments in a
+/// workspace
+mod set_workspace_deployment_error_pages;
+
+pub use self::{
+	get_deployment_error_pages::*,
+	get_ingress_error_pages::*,
+	get_workspace_deployment_error_pages::*,
+	set_deployment_error_pages::*,
+	set_workspace_deployment_error_pages::*,
+};
+
+/// The maximum size of the HTML of a custom error page, in bytes. The error
+/// pages are cached by the ingress, so they are kept small
+pub const MAX_DEPLOYMENT_ERROR_PAGE_SIZE: usize = 64 * 1024;
+
+/// The custom error pages of a deployment, by the situation that they are
+/// served in. Situations without a custom error page use the default error
+/// page of the workspace, if it has one, or the ingress's own error response
+pub type DeploymentErrorPages = BTreeMap<DeploymentErrorPageKind, String>;
+
+/// The situations in which the ingress serves a custom error page instead of
+/// its own error response. The responses of the deployment itself are never
+/// replaced, and custom error pages are only served to clients that accept
+/// HTML
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(sqlx::Type, schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(
+	not(target_arch = "wasm32"),
+	sqlx(type_name = "DEPLOYMENT_ERROR_PAGE_KIND", rename_all = "snake_case")
+)]
+pub enum DeploymentErrorPageKind {
+	/// The request couldn't be forwarded to the deployment. This is served
+	/// with a `502 Bad Gateway`
+	BadGateway,
+	/// The deployment is handling as many requests as it is allowed to, or none
+	/// of its replicas are ready. This is served with a `503 Service
+	/// Unavailable`
+	ServiceUnavailable,
+	/// The deployment didn't respond to the request in time. This is served
+	/// with a `504 Gateway Timeout`
+	GatewayTimeout,
+	/// The deployment was scaled to zero and is being started again. If this
+	/// page is not set, the ingress serves a page that refreshes itself until
+	/// the deployment is ready. This is served with a `503 Service
+	/// Unavailable`
+	WarmingUp,
+}
+
+/// Validates the HTML of a custom error page. The page must be an HTML
+/// document (starting with a doctype or an `<html>` tag), and must not be
+/// larger than [`MAX_DEPLOYMENT_ERROR_PAGE_SIZE`], since it is served as is
+/// with a `text/html` content type
+pub fn validate_deployment_error_page(html: &str) -> Result<(), ErrorType> {
+	if html.len() > MAX_DEPLOYMENT_ERROR_PAGE_SIZE {
+		return Err(ErrorType::ErrorPageTooLarge);
+	}
+
+	let start = html
+		.trim_start_matches('\u{feff}')
+		.trim_start()
+		.chars()
+		.take("<!doctype html".len())
+		.collect::<String>()
+		.to_lowercase();
+	if !start.starts_with("<!doctype html") && !start.starts_with("<html") {
+		return Err(ErrorType::InvalidErrorPage);
+	}
+
+	// Pages with null bytes are binary files, not HTML
+	if html.contains('\0') {
+		return Err(ErrorType::InvalidErrorPage);
+	}
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn html_documents_are_valid_error_pages() {
+		for html in [
+			"<!DOCTYPE html><html><body>Down for maintenance</body></html>",
+			"\n  <html lang=\"en\"><body>Oops</body></html>",
+			"\u{feff}<!doctype html>",
+		] {
+			assert_eq!(validate_deployment_error_page(html), Ok(()), "{html}");
+		}
+	}
+
+	#[test]
+	fn pages_that_are_not_html_are_rejected() {
+		for html in [
+			"",
+			"   ",
+			"Down for maintenance",
+			"{\"error\": \"down\"}",
+			"<div>Oops</div>",
+			"<html>\0</html>",
+		] {
+			assert_eq!(
+				validate_deployment_error_page(html),
+				Err(ErrorType::InvalidErrorPage),
+				"{html:?}"
+			);
+		}
+	}
+
+	#[test]
+	fn pages_larger_than_the_limit_are_rejected() {
+		let html = format!(
+			"<html>{}</html>",
+			"a".repeat(MAX_DEPLOYMENT_ERROR_PAGE_SIZE)
+		);
+
+		assert_eq!(
+			validate_deployment_error_page(&html),
+			Err(ErrorType::ErrorPageTooLarge)
+		);
+	}
+}
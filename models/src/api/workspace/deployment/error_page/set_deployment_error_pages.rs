@@ -0,0 +1,37 @@
+use super::DeploymentErrorPages;
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route to set the custom error pages of a deployment, which the ingress
+	/// serves instead of its own error responses when the deployment is
+	/// unavailable. This replaces all the existing error pages of the
+	/// deployment, so the situations that are left out fall back to the
+	/// defaults of the workspace
+	SetDeploymentErrorPages,
+	PUT "/workspace/:workspace_id/deployment/:deployment_id/error-pages" {
+		/// The workspace ID of the user
+		pub workspace_id: Uuid,
+		/// The deployment ID to set the error pages of
+		pub deployment_id: Uuid,
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.deployment_id,
+			permission: Permission::Deployment(DeploymentPermission::Edit),
+		}
+	},
+	request = {
+		/// The HTML of the custom error pages, by the situation that they are
+		/// served in. Each page must be an HTML document of at most
+		/// [`MAX_DEPLOYMENT_ERROR_PAGE_SIZE`][super::MAX_DEPLOYMENT_ERROR_PAGE_SIZE]
+		/// bytes
+		#[preprocess(none)]
+		pub error_pages: DeploymentErrorPages,
+	}
+);
@@ -0,0 +1,34 @@
+use super::DeploymentErrorPages;
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route to set the default custom error pages of the deployments in a
+	/// workspace. These are served for the deployments that don't have a
+	/// custom error page of their own for the situation. This replaces all the
+	/// existing default error pages of the workspace
+	SetWorkspaceDeploymentErrorPages,
+	PUT "/workspace/:workspace_id/deployment/error-pages" {
+		/// The workspace ID to set the default error pages of
+		pub workspace_id: Uuid,
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.workspace_id,
+			permission: Permission::EditWorkspace,
+		}
+	},
+	request = {
+		/// The HTML of the default custom error pages, by the situation that
+		/// they are served in. Each page must be an HTML document of at most
+		/// [`MAX_DEPLOYMENT_ERROR_PAGE_SIZE`][super::MAX_DEPLOYMENT_ERROR_PAGE_SIZE]
+		/// bytes
+		#[preprocess(none)]
+		pub error_pages: DeploymentErrorPages,
+	}
+);
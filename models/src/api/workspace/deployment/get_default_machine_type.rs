@@ -0,0 +1,29 @@
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route to get the default machine type of a workspace. New deployments
+	/// that don't have a machine type (either in the request or from their
+	/// template) use this machine type
+	GetDefaultDeploymentMachineType,
+	GET "/workspace/:workspace_id/deployment/default-machine-type" {
+		/// The workspace ID to get the default machine type of
+		pub workspace_id: Uuid,
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::WorkspaceMembershipAuthenticator {
+			extract_workspace_id: |req| req.path.workspace_id,
+		}
+	},
+	response = {
+		/// The ID of the default machine type. If this is not set, new
+		/// deployments must have a machine type set explicitly
+		#[serde(default, skip_serializing_if = "Option::is_none")]
+		pub machine_type_id: Option<Uuid>,
+	}
+);
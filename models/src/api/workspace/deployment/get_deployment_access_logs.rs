@@ -0,0 +1,47 @@
+use time::OffsetDateTime;
+
+use super::DeploymentAccessLog;
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route to get the access logs of a deployment, which are the requests
+	/// made to it through a managed URL. These are only recorded while
+	/// [`access_logging`][super::DeploymentRunningDetails::access_logging] is
+	/// enabled for the deployment, and are kept separately from the logs of
+	/// the deployment. Access logs can contain personal data in the paths of
+	/// the requests
+	GetDeploymentAccessLogs,
+	GET "/workspace/:workspace_id/deployment/:deployment_id/access-logs" {
+		/// The workspace ID of the user
+		pub workspace_id: Uuid,
+		/// The deployment ID to get the access logs for
+		pub deployment_id: Uuid,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.deployment_id,
+			permission: Permission::Deployment(DeploymentPermission::View),
+		}
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	query = {
+		/// The time up until which the access logs should be fetched
+		#[cfg_attr(not(target_arch = "wasm32"), schemars(with = "Option<String>"))]
+		pub end_time: Option<OffsetDateTime>,
+		/// The limit of access logs to fetch. Defaults to 100
+		#[preprocess(range(max = Some(500)))]
+		pub limit: Option<u32>,
+		/// The search query to filter the access logs by, such as a path or a
+		/// status code
+		pub search: Option<String>,
+	},
+	response = {
+		/// The access logs of the deployment, newest first
+		pub logs: Vec<DeploymentAccessLog>
+	}
+);
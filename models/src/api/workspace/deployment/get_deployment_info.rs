@@ -1,4 +1,13 @@
-use super::{Deployment, DeploymentRunningDetails};
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::{
+	build::{DeploymentBuild, DeploymentBuildSource},
+	Deployment,
+	DeploymentCanaryRequests,
+	DeploymentReconciliationStatus,
+	DeploymentReplicaReadiness,
+	DeploymentRunningDetails,
+};
 use crate::prelude::*;
 
 macros::declare_api_endpoint!(
@@ -10,6 +19,13 @@ macros::declare_api_endpoint!(
 		/// The deployment ID to get the event details for
 		pub deployment_id: Uuid
 	},
+	query = {
+		/// Whether to return the actual values of the secret environment
+		/// variables instead of masking them. This requires the permission to
+		/// edit the deployment, and is used by runners to apply the variables
+		#[serde(default)]
+		pub reveal_secret_values: bool,
+	},
 	request_headers = {
 		/// Token used to authorize user
 		pub authorization: BearerToken,
@@ -45,5 +61,47 @@ macros::declare_api_endpoint!(
 		/// volumes - The volumes
 		#[serde(flatten)]
 		pub running_details: DeploymentRunningDetails,
+		/// The names of the environment variables that are specific to this
+		/// deployment's environment, and are preserved when another deployment
+		/// is promoted to this one
+		#[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+		pub environment_specific_variables: BTreeSet<String>,
+		/// The names of the environment variables whose values are secret.
+		/// Their values are replaced with a mask, unless they are explicitly
+		/// revealed
+		#[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+		pub secret_variables: BTreeSet<String>,
+		/// The git repository that the image of the deployment is built from,
+		/// if it is built from source
+		#[serde(default, skip_serializing_if = "Option::is_none")]
+		pub build_source: Option<DeploymentBuildSource>,
+		/// The latest build of the deployment's image, if any. The status of
+		/// the build is separate from the status of the deployment, which keeps
+		/// running its current image until a build succeeds
+		#[serde(default, skip_serializing_if = "Option::is_none")]
+		pub latest_build: Option<WithId<DeploymentBuild>>,
+		/// Whether the runner has applied the latest changes made to the
+		/// deployment. This is separate from the status of the deployment,
+		/// which is what the deployment is doing
+		#[serde(default)]
+		pub reconciliation_status: DeploymentReconciliationStatus,
+		/// The deployments in the workspace that depend on this deployment.
+		/// This deployment is only stopped once they have stopped
+		#[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+		pub dependents: BTreeSet<Uuid>,
+		/// The dependencies of this deployment, and of every deployment that it
+		/// depends on (directly or indirectly), keyed by the deployment. The
+		/// deployments are started in the order of this graph, with the ones
+		/// that don't depend on anything starting first
+		#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+		pub dependency_graph: BTreeMap<Uuid, BTreeSet<Uuid>>,
+		/// How many of the primary replicas of the deployment are ready to
+		/// receive requests, out of all of them, if the runner has reported it
+		#[serde(default, skip_serializing_if = "Option::is_none")]
+		pub replicas: Option<DeploymentReplicaReadiness>,
+		/// The requests made to each version of the deployment since its
+		/// canary was started, if it has one
+		#[serde(default, skip_serializing_if = "Option::is_none")]
+		pub canary_requests: Option<DeploymentCanaryRequests>,
 	}
 );
@@ -26,12 +26,27 @@ macros::declare_api_endpoint!(
 	},
 	query = {
 		/// The time up until which the deployment logs should be fetched
+		#[cfg_attr(not(target_arch = "wasm32"), schemars(with = "Option<String>"))]
 		pub end_time: Option<OffsetDateTime>,
 		/// The limit of logs to fetch. Defaults to 100
 		#[preprocess(range(max = Some(500)))]
 		pub limit: Option<u32>,
-		/// The search query to filter logs
+		/// The search query to filter logs. For logs that are parsed, only the
+		/// values of their fields are searched
 		pub search: Option<String>,
+		/// Whether logs that are JSON objects should be parsed, extracting
+		/// their level, timestamp and message. Logs that aren't JSON objects
+		/// are returned as plain text
+		#[serde(default)]
+		pub parse_json: bool,
+		/// The level to filter logs by, whether the logs are parsed or not.
+		/// The level of logs that are JSON objects is read from their `level`,
+		/// `lvl` or `severity` field, and any log that doesn't have this level
+		/// is excluded
+		pub level: Option<String>,
+		/// The ID of the replica to get the logs of. If not set, the logs of
+		/// all the replicas of the deployment are merged together
+		pub replica_id: Option<String>,
 	},
 	response = {
 		/// The deployment logs containing:
@@ -28,6 +28,7 @@ macros::declare_api_endpoint!(
 	query = {
 		/// The duration for when the deployment metrics are fetched
 		#[preprocess(range(max = Some(Duration::days(14))))]
+		#[cfg_attr(not(target_arch = "wasm32"), schemars(with = "Option<String>"))]
 		pub interval: Option<Duration>,
 	},
 	response = {
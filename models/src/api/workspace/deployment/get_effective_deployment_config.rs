@@ -0,0 +1,69 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::{DeploymentMachineType, DeploymentRunningDetails};
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route to get the effective config of a deployment. Unlike the config
+	/// returned when getting the deployment info, which only has the values
+	/// stored on the deployment, this has every default filled in, along with
+	/// where each value that wasn't set on the deployment itself came from.
+	/// This can be used to find out why a value is set when it wasn't set
+	/// explicitly. Secret environment variables are masked, and variables
+	/// from secrets only have the ID of the secret.
+	GetEffectiveDeploymentConfig,
+	GET "/workspace/:workspace_id/deployment/:deployment_id/effective-config" {
+		/// The workspace ID of the user
+		pub workspace_id: Uuid,
+		/// The deployment ID to get the effective config of
+		pub deployment_id: Uuid,
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.deployment_id,
+			permission: Permission::Deployment(DeploymentPermission::View),
+		}
+	},
+	response = {
+		/// The running details of the deployment, with every default filled
+		/// in
+		#[serde(flatten)]
+		pub running_details: DeploymentRunningDetails,
+		/// The machine type that the deployment runs on
+		pub machine_type: WithId<DeploymentMachineType>,
+		/// The template that the deployment was created from, if it still
+		/// exists
+		#[serde(default, skip_serializing_if = "Option::is_none")]
+		pub template_id: Option<Uuid>,
+		/// Where the values that weren't set on the deployment itself come
+		/// from, by the path of the field (such as `environmentVariables.PORT`
+		/// or `resources.cpuLimit`). Any value that isn't listed here is set on
+		/// the deployment
+		#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+		pub inherited_values: BTreeMap<String, DeploymentConfigValueSource>,
+	}
+);
+
+/// Where a value in the effective config of a deployment comes from, when it
+/// isn't set on the deployment itself
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub enum DeploymentConfigValueSource {
+	/// The value is the same as the one in the template that the deployment
+	/// was created from. Values of templates are copied to a deployment when
+	/// it is created, so this is only an indication of where the value came
+	/// from, and changing the template doesn't change the deployment
+	Template,
+	/// The value defaults to the capacity of the machine type of the
+	/// deployment
+	MachineType,
+}
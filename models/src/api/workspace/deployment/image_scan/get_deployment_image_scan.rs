@@ -0,0 +1,32 @@
+use super::DeploymentImageScan;
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route to get the result of the last scan of a deployment's image, with
+	/// the number of vulnerabilities by their severity and the list of the
+	/// vulnerabilities that were found
+	GetDeploymentImageScan,
+	GET "/workspace/:workspace_id/deployment/:deployment_id/image-scan" {
+		/// The workspace ID of the user
+		pub workspace_id: Uuid,
+		/// The ID of the deployment whose image scan should be returned
+		pub deployment_id: Uuid,
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.deployment_id,
+			permission: Permission::Deployment(DeploymentPermission::View),
+		}
+	},
+	response = {
+		/// The result of the last scan
+		#[serde(flatten)]
+		pub scan: DeploymentImageScan,
+	}
+);
@@ -0,0 +1,153 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+/// The endpoint to get the result of the last scan of a deployment's image
+mod get_deployment_image_scan;
+/// The endpoint to scan the image of a deployment for vulnerabilities
+mod scan_deployment_image;
+
+pub use self::{get_deployment_image_scan::*, scan_deployment_image::*};
+
+/// How severe a vulnerability found in an image is, as reported by the
+/// scanner. The variants are ordered from the least to the most severe.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(sqlx::Type, schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(
+	not(target_arch = "wasm32"),
+	sqlx(type_name = "IMAGE_VULNERABILITY_SEVERITY", rename_all = "lowercase")
+)]
+pub enum ImageVulnerabilitySeverity {
+	/// The scanner could not determine the severity of the vulnerability
+	Unknown,
+	/// The vulnerability has a low severity
+	Low,
+	/// The vulnerability has a medium severity
+	Medium,
+	/// The vulnerability has a high severity
+	High,
+	/// The vulnerability is critical. Deploying images with critical
+	/// vulnerabilities can be blocked by the policy of a workspace
+	Critical,
+}
+
+/// A vulnerability found in a package of an image
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct ImageVulnerability {
+	/// The identifier of the vulnerability, usually its CVE ID
+	pub id: String,
+	/// The name of the package that has the vulnerability
+	pub package: String,
+	/// The version of the package that is installed in the image
+	pub installed_version: String,
+	/// The first version of the package that fixes the vulnerability, if
+	/// there is one
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub fixed_version: Option<String>,
+	/// How severe the vulnerability is
+	pub severity: ImageVulnerabilitySeverity,
+	/// A short description of the vulnerability, if the scanner has one
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub title: Option<String>,
+}
+
+/// The number of vulnerabilities found in an image, by their severity
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct ImageVulnerabilityCounts {
+	/// The number of critical vulnerabilities
+	pub critical: u32,
+	/// The number of vulnerabilities with a high severity
+	pub high: u32,
+	/// The number of vulnerabilities with a medium severity
+	pub medium: u32,
+	/// The number of vulnerabilities with a low severity
+	pub low: u32,
+	/// The number of vulnerabilities with an unknown severity
+	pub unknown: u32,
+}
+
+impl ImageVulnerabilityCounts {
+	/// Counts the given vulnerabilities by their severity
+	pub fn from_vulnerabilities(vulnerabilities: &[ImageVulnerability]) -> Self {
+		vulnerabilities
+			.iter()
+			.fold(Self::default(), |mut counts, vulnerability| {
+				match vulnerability.severity {
+					ImageVulnerabilitySeverity::Critical => counts.critical += 1,
+					ImageVulnerabilitySeverity::High => counts.high += 1,
+					ImageVulnerabilitySeverity::Medium => counts.medium += 1,
+					ImageVulnerabilitySeverity::Low => counts.low += 1,
+					ImageVulnerabilitySeverity::Unknown => counts.unknown += 1,
+				}
+				counts
+			})
+	}
+}
+
+/// The result of scanning the image of a deployment for vulnerabilities
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct DeploymentImageScan {
+	/// The image that was scanned, including its tag or digest
+	pub image: String,
+	/// The digest of the image that was scanned, if it is known. Scans are
+	/// matched to the image of a deployment by its digest, since the tag can be
+	/// moved to a different image at any time
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub image_digest: Option<String>,
+	/// The time the image was scanned
+	#[cfg_attr(not(target_arch = "wasm32"), schemars(with = "String"))]
+	pub scanned: OffsetDateTime,
+	/// The number of vulnerabilities found, by their severity
+	pub counts: ImageVulnerabilityCounts,
+	/// The vulnerabilities found in the image, the most severe first
+	pub vulnerabilities: Vec<ImageVulnerability>,
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn vulnerability(id: &str, severity: ImageVulnerabilitySeverity) -> ImageVulnerability {
+		ImageVulnerability {
+			id: id.to_string(),
+			package: "openssl".to_string(),
+			installed_version: "3.0.0".to_string(),
+			fixed_version: None,
+			severity,
+			title: None,
+		}
+	}
+
+	#[test]
+	fn vulnerabilities_are_counted_by_severity() {
+		let counts = ImageVulnerabilityCounts::from_vulnerabilities(&[
+			vulnerability("CVE-2024-0001", ImageVulnerabilitySeverity::Critical),
+			vulnerability("CVE-2024-0002", ImageVulnerabilitySeverity::Critical),
+			vulnerability("CVE-2024-0003", ImageVulnerabilitySeverity::Low),
+			vulnerability("CVE-2024-0004", ImageVulnerabilitySeverity::Unknown),
+		]);
+
+		assert_eq!(
+			counts,
+			ImageVulnerabilityCounts {
+				critical: 2,
+				high: 0,
+				medium: 0,
+				low: 1,
+				unknown: 1,
+			}
+		);
+	}
+
+	#[test]
+	fn severities_are_ordered_from_least_to_most_severe() {
+		assert!(ImageVulnerabilitySeverity::Critical > ImageVulnerabilitySeverity::High);
+		assert!(ImageVulnerabilitySeverity::Low > ImageVulnerabilitySeverity::Unknown);
+	}
+}
@@ -0,0 +1,29 @@
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route to scan the current image of a deployment for vulnerabilities
+	/// using the scanner configured for the Patr instance. The image is
+	/// scanned in the background, and the result can be fetched with
+	/// [`GetDeploymentImageScanRequest`][super::GetDeploymentImageScanRequest]
+	/// once the scan has finished. If no scanner is configured, the request
+	/// fails with [`ErrorType::ImageScanFailed`]
+	ScanDeploymentImage,
+	POST "/workspace/:workspace_id/deployment/:deployment_id/image-scan" {
+		/// The workspace ID of the user
+		pub workspace_id: Uuid,
+		/// The ID of the deployment whose image should be scanned
+		pub deployment_id: Uuid,
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.deployment_id,
+			permission: Permission::Deployment(DeploymentPermission::Edit),
+		}
+	},
+);
@@ -0,0 +1,41 @@
+use serde::{Deserialize, Serialize};
+
+use super::Deployment;
+use crate::{api::AggregateWarning, prelude::*};
+
+/// A deployment along with the workspace that it belongs to
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceDeployment {
+	/// The ID of the workspace that the deployment belongs to
+	pub workspace_id: Uuid,
+	/// The deployment
+	#[serde(flatten)]
+	pub deployment: WithId<Deployment>,
+}
+
+macros::declare_api_endpoint!(
+	/// Route to list the deployments across all the workspaces that the user
+	/// is a part of. If the deployments of some of the workspaces can't be
+	/// loaded, the deployments of the rest are still returned, along with a
+	/// warning for each workspace that couldn't be loaded
+	ListAllDeployments,
+	GET "/user/deployments",
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::PlainTokenAuthenticator
+	},
+	response = {
+		/// The deployments that the user can view, from all the workspaces
+		/// that could be loaded
+		pub deployments: Vec<WorkspaceDeployment>,
+		/// The workspaces whose deployments could not be loaded
+		pub warnings: Vec<AggregateWarning>,
+	}
+);
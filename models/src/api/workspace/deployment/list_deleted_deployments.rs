@@ -0,0 +1,34 @@
+use super::DeletedDeployment;
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route to list the deployments in a workspace that were deleted, but can still be
+	/// restored using the [`super::RestoreDeploymentRequest`] endpoint. Deleted
+	/// deployments are permanently removed once their grace period is over.
+	ListDeletedDeployments,
+	GET "/workspace/:workspace_id/deployment/deleted" {
+		/// The workspace ID of the user
+		pub workspace_id: Uuid,
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::WorkspaceMembershipAuthenticator {
+			extract_workspace_id: |req| req.path.workspace_id,
+		}
+	},
+	pagination = true,
+	response_headers = {
+		/// The total number of deleted deployments in the requested workspace
+		pub total_count: TotalCountHeader,
+	},
+	response = {
+		/// The list of deleted deployments in the workspace, along with the time
+		/// they were deleted and the time they will be permanently removed
+		pub deployments: Vec<WithId<DeletedDeployment>>,
+	}
+);
@@ -1,6 +1,83 @@
+use std::{collections::BTreeMap, fmt::Display, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
 use super::Deployment;
 use crate::prelude::*;
 
+/// A filter on the labels of deployments. This is written as `key=value`
+/// pairs separated by commas, for example `team=payments,env=prod`. A
+/// deployment matches the selector only if it has all the labels in it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct DeploymentLabelSelector(pub BTreeMap<String, String>);
+
+impl FromStr for DeploymentLabelSelector {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let mut labels = BTreeMap::new();
+		for pair in s.split(',').map(str::trim).filter(|pair| !pair.is_empty()) {
+			let Some((key, value)) = pair.split_once('=') else {
+				return Err(format!(
+					"Label selector `{pair}` must be in the format `key=value`"
+				));
+			};
+			let (key, value) = (key.trim(), value.trim());
+			if !super::is_valid_label_key(key) || !super::is_valid_label_value(value) {
+				return Err(format!("Invalid label selector `{pair}`"));
+			}
+			if labels.insert(key.to_string(), value.to_string()).is_some() {
+				return Err(format!("Label `{key}` is selected more than once"));
+			}
+		}
+
+		Ok(Self(labels))
+	}
+}
+
+impl TryFrom<String> for DeploymentLabelSelector {
+	type Error = String;
+
+	fn try_from(value: String) -> Result<Self, Self::Error> {
+		value.parse()
+	}
+}
+
+impl Display for DeploymentLabelSelector {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		for (index, (key, value)) in self.0.iter().enumerate() {
+			if index > 0 {
+				write!(f, ",")?;
+			}
+			write!(f, "{key}={value}")?;
+		}
+		Ok(())
+	}
+}
+
+impl From<DeploymentLabelSelector> for String {
+	fn from(value: DeploymentLabelSelector) -> Self {
+		value.to_string()
+	}
+}
+
+/// Label selectors are sent as a string of `key=value` pairs
+#[cfg(not(target_arch = "wasm32"))]
+impl schemars::JsonSchema for DeploymentLabelSelector {
+	fn is_referenceable() -> bool {
+		false
+	}
+
+	fn schema_name() -> String {
+		"DeploymentLabelSelector".to_string()
+	}
+
+	fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+		String::json_schema(gen)
+	}
+}
+
 macros::declare_api_endpoint!(
 	/// Route to list all the deployments in a workspace
 	ListDeployment,
@@ -19,6 +96,19 @@ macros::declare_api_endpoint!(
 			extract_workspace_id: |req| req.path.workspace_id,
 		}
 	},
+	query = {
+		/// The field to sort the list of deployments by. Defaults to the time
+		/// the deployment was created. Deployments that have the same value
+		/// for the field are always sorted by their creation time and then by
+		/// their ID, so that the order is stable across pages
+		pub order_by: Option<ListOrderBy>,
+		/// The order to sort the list of deployments in. Defaults to
+		/// descending
+		pub order: Option<ListOrder>,
+		/// Only list the deployments that have all of these labels, for example
+		/// `team=payments,env=prod`
+		pub label_selector: Option<DeploymentLabelSelector>,
+	},
 	pagination = true,
 	response_headers = {
 		/// The total number of deployment in the requested workspace
@@ -0,0 +1,30 @@
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route to list the replicas that the runner of a deployment last reported
+	/// running for it. The IDs of these replicas can be used to only get the
+	/// logs of a single replica of the deployment
+	ListDeploymentReplicas,
+	GET "/workspace/:workspace_id/deployment/:deployment_id/replicas" {
+		/// The workspace ID of the user
+		pub workspace_id: Uuid,
+		/// The deployment ID to list the replicas of
+		pub deployment_id: Uuid,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.deployment_id,
+			permission: Permission::Deployment(DeploymentPermission::View),
+		}
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	response = {
+		/// The IDs of the replicas of the deployment, sorted by their ID
+		pub replicas: Vec<String>,
+	}
+);
@@ -1,47 +1,141 @@
-use std::{collections::BTreeMap, fmt::Display, str::FromStr};
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	fmt::Display,
+	str::FromStr,
+};
 
+use headers::ETag;
 use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
 use time::OffsetDateTime;
 
+/// Alert rules that notify when the resource usage of a deployment stays above
+/// a threshold
+pub mod alert_rule;
+/// Building the image of a deployment from a git repository, instead of using
+/// a pre-built image
+pub mod build;
+/// Changes to deployments that are waiting for approval, for workspaces that
+/// require a second user to approve every change
+pub mod change_request;
 /// The history of a deployment's deploys. This contains the image digest and
 /// the timestamp of when the deploy was created
 pub mod deploy_history;
+/// Custom error pages that the ingress serves when a deployment is
+/// unavailable, set on a deployment or as the defaults of a workspace
+pub mod error_page;
+/// Scanning the images of deployments for vulnerabilities, using the scanner
+/// configured for the Patr instance
+pub mod image_scan;
+/// Schedules that automatically start and stop a deployment based on cron
+/// expressions
+pub mod schedule;
+/// Workspace-level deployment templates, which can be used to pre-fill the
+/// configuration of new deployments
+pub mod template;
 
+/// The endpoint to abort the canary of a deployment
+mod abort_deployment_canary;
+/// The endpoint to get the status of multiple deployments at once
+mod batch_get_deployment_status;
 /// The endpoint to create a deployment
 mod create_deployment;
 /// The endpoint to delete a deployment
 mod delete_deployment;
+/// The endpoint to download the logs of a deployment for a time range
+mod download_deployment_logs;
+/// The endpoint to get the default machine type of a workspace
+mod get_default_machine_type;
+/// The endpoint to get the access logs of a deployment
+mod get_deployment_access_logs;
 /// The endpoint to get the details of a deployment
 mod get_deployment_info;
 /// The endpoint to get the logs of a deployment
 mod get_deployment_logs;
 /// The endpoint to get the metrics of a deployment
 mod get_deployment_metric;
+/// The endpoint to get the effective config of a deployment, with every
+/// default filled in
+mod get_effective_deployment_config;
 /// The endpoint to list all the machine types for deployments
 mod list_all_deployment_machine_type;
+/// The endpoint to list the deployments across all the workspaces of a user
+mod list_all_deployments;
+/// The endpoint to list the deleted deployments in a workspace that can still
+/// be restored
+mod list_deleted_deployments;
 /// The endpoint to list all the deployments in a workspace
 mod list_deployment;
+/// The endpoint to list the replicas of a deployment that have logged recently
+mod list_deployment_replicas;
+/// The endpoint to promote the image and configuration of a deployment to
+/// another deployment
+mod promote_deployment;
+/// The endpoint to promote the canary of a deployment to be its primary image
+mod promote_deployment_canary;
+/// The endpoint to force the runner to reconcile a deployment
+mod reconcile_deployment;
+/// The endpoint for the ingress to report the access logs of the requests made
+/// to deployments
+mod report_deployment_access_logs;
+/// The endpoint for the ingress to report that a deployment received a request
+mod report_deployment_activity;
+/// The endpoint for the runner to report how many replicas of a deployment are
+/// ready
+mod report_deployment_readiness;
+/// The endpoint for the runner to report the result of reconciling a deployment
+mod report_deployment_reconciliation;
+/// The endpoint to restore a deleted deployment
+mod restore_deployment;
+/// The endpoint to set the default machine type of a workspace
+mod set_default_machine_type;
+/// The endpoint to start a canary of a deployment, or to change its weight
+mod set_deployment_canary;
 /// The endpoint to start a deployment
 mod start_deployment;
 /// The endpoint to stop a deployment
 mod stop_deployment;
 /// The endpoint to stream the logs of a deployment
 mod stream_deployment_logs;
+/// The endpoint to test the reachability of an exposed port of a deployment
+mod test_deployment_port;
 /// The endpoint to update a deployment's details
 mod update_deployment;
+/// The endpoint to validate the config of a deployment without creating it
+mod validate_deployment_config;
 
 pub use self::{
+	abort_deployment_canary::*,
+	batch_get_deployment_status::*,
 	create_deployment::*,
 	delete_deployment::*,
+	download_deployment_logs::*,
+	get_default_machine_type::*,
+	get_deployment_access_logs::*,
 	get_deployment_info::*,
 	get_deployment_logs::*,
 	get_deployment_metric::*,
+	get_effective_deployment_config::*,
 	list_all_deployment_machine_type::*,
+	list_all_deployments::*,
+	list_deleted_deployments::*,
 	list_deployment::*,
+	list_deployment_replicas::*,
+	promote_deployment::*,
+	promote_deployment_canary::*,
+	reconcile_deployment::*,
+	report_deployment_access_logs::*,
+	report_deployment_activity::*,
+	report_deployment_readiness::*,
+	report_deployment_reconciliation::*,
+	restore_deployment::*,
+	set_default_machine_type::*,
+	set_deployment_canary::*,
 	start_deployment::*,
 	stop_deployment::*,
 	stream_deployment_logs::*,
+	test_deployment_port::*,
 	update_deployment::*,
+	validate_deployment_config::*,
 };
 use crate::{prelude::*, utils::constants};
 
@@ -51,6 +145,7 @@ use crate::{prelude::*, utils::constants};
 /// deployment. The machine type can be used to classify the deployment based on
 /// the resources it requires.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
 pub struct DeploymentMachineType {
 	/// The number of CPU nodes allocated to the deployment. This is the number
 	/// of vCPUs in case of cloud deployments and the number of physical CPUs in
@@ -87,6 +182,246 @@ pub struct Deployment {
 	pub machine_type: Uuid,
 	/// The current image digest the deployment is running
 	pub current_live_digest: Option<String>,
+	/// The pull secret used to pull the image of the deployment, if the image
+	/// is hosted on a private external registry. Only the ID of the secret is
+	/// exposed, never the credentials themselves
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub pull_secret_id: Option<Uuid>,
+	/// The time the deployment was created
+	#[cfg_attr(not(target_arch = "wasm32"), schemars(with = "String"))]
+	pub created_at: OffsetDateTime,
+	/// The time the deployment was last updated. This is the same as the time
+	/// it was created, if it was never updated
+	#[cfg_attr(not(target_arch = "wasm32"), schemars(with = "String"))]
+	pub updated_at: OffsetDateTime,
+	/// The labels attached to the deployment, to organize the deployments in a
+	/// workspace (for example, by team or environment). These are only
+	/// metadata, and don't affect how or where the deployment runs
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	pub labels: BTreeMap<String, String>,
+	/// The deployments (in the same workspace) that this deployment depends
+	/// on. The runner only starts this deployment once all of them are
+	/// healthy, and only stops them once this deployment has stopped
+	#[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+	pub depends_on: BTreeSet<Uuid>,
+	/// The canary of the deployment, if any. A canary runs another tag of the
+	/// same image alongside the deployment, and is sent a share of the
+	/// requests made through a managed URL until it is either promoted or
+	/// aborted
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub canary: Option<DeploymentCanary>,
+}
+
+/// A canary of a deployment, which runs another tag of the deployment's image
+/// alongside the primary replicas. The ingress sends a share of the requests
+/// made through a managed URL to the canary, based on its weight, so that a new
+/// version can be tried out on a part of the traffic before it is promoted.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct DeploymentCanary {
+	/// The image tag that the canary runs, from the same image as the
+	/// deployment
+	pub image_tag: String,
+	/// The percentage (from 0 to 100) of the requests that are sent to the
+	/// canary. The rest are sent to the primary replicas of the deployment
+	pub weight: u8,
+}
+
+impl DeploymentCanary {
+	/// Splits the replicas of a deployment between its primary replicas and
+	/// its canary. The canary gets its weight's share of the minimum number of
+	/// replicas (at least one), and the primary replicas scale within what is
+	/// left, so that the deployment and its canary never run more replicas
+	/// than the maximum of the deployment together. Returns `None` if the
+	/// maximum leaves no room for both the primary replicas and the canary.
+	pub fn replicas(
+		&self,
+		min_horizontal_scale: u16,
+		max_horizontal_scale: u16,
+	) -> Option<DeploymentCanaryReplicas> {
+		if max_horizontal_scale < 2 {
+			return None;
+		}
+
+		let canary = (u32::from(min_horizontal_scale) * u32::from(self.weight))
+			.div_ceil(100)
+			.clamp(1, u32::from(max_horizontal_scale - 1)) as u16;
+		let max_primary = max_horizontal_scale - canary;
+		let min_primary = min_horizontal_scale
+			.saturating_sub(canary)
+			.clamp(1, max_primary);
+
+		Some(DeploymentCanaryReplicas {
+			canary,
+			min_primary,
+			max_primary,
+		})
+	}
+}
+
+/// How the replicas of a deployment are split between its primary replicas and
+/// its canary. See [`DeploymentCanary::replicas`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeploymentCanaryReplicas {
+	/// The number of replicas of the canary
+	pub canary: u16,
+	/// The minimum number of primary replicas
+	pub min_primary: u16,
+	/// The maximum number of primary replicas
+	pub max_primary: u16,
+}
+
+/// The requests made to a deployment with a canary since the canary was
+/// started, by the version that they were sent to, so that both versions can
+/// be compared before the canary is promoted. The ingress counts the requests
+/// and sends the counts along with the activity it reports.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct DeploymentCanaryRequests {
+	/// The requests sent to the primary replicas of the deployment
+	pub primary: DeploymentVersionRequests,
+	/// The requests sent to the canary of the deployment
+	pub canary: DeploymentVersionRequests,
+}
+
+/// The requests sent to one version of a deployment with a canary
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct DeploymentVersionRequests {
+	/// The number of requests
+	pub requests: u64,
+	/// The number of requests that failed with a `5xx` status code, or that
+	/// couldn't be forwarded at all
+	pub server_errors: u64,
+	/// The sum of the milliseconds it took to respond to each of the requests,
+	/// which divided by the number of requests is their average latency
+	pub total_latency_millis: u64,
+}
+
+impl DeploymentVersionRequests {
+	/// Counts a request that was responded to with the given status code after
+	/// the given number of milliseconds
+	pub fn record(&mut self, status: u16, latency_millis: u64) {
+		self.requests += 1;
+		if status >= 500 {
+			self.server_errors += 1;
+		}
+		self.total_latency_millis += latency_millis;
+	}
+}
+
+impl Deployment {
+	/// Checks that the labels of a deployment are valid. A deployment can have
+	/// at most [`constants::MAX_DEPLOYMENT_LABELS`] labels. The keys and values
+	/// can be at most [`constants::MAX_DEPLOYMENT_LABEL_LENGTH`] characters
+	/// long, can only contain alphanumeric characters, `-`, `_` and `.`, and
+	/// must start and end with an alphanumeric character. Values can also be
+	/// empty.
+	pub fn validate_labels(labels: &BTreeMap<String, String>) -> Result<(), ErrorType> {
+		if labels.len() > constants::MAX_DEPLOYMENT_LABELS {
+			return Err(ErrorType::WrongParameters);
+		}
+
+		let all_valid = labels
+			.iter()
+			.all(|(key, value)| is_valid_label_key(key) && is_valid_label_value(value));
+		if !all_valid {
+			return Err(ErrorType::WrongParameters);
+		}
+
+		Ok(())
+	}
+
+	/// Checks that a deployment can depend on the given deployments, given the
+	/// dependencies of all the other deployments in the workspace. A deployment
+	/// can depend on at most [`constants::MAX_DEPLOYMENT_DEPENDENCIES`]
+	/// deployments, and cannot depend on itself, either directly or through
+	/// the deployments it depends on. The existing dependencies of the
+	/// deployment in the graph are ignored, since they are being replaced.
+	pub fn validate_dependencies(
+		deployment_id: Uuid,
+		depends_on: &BTreeSet<Uuid>,
+		dependency_graph: &BTreeMap<Uuid, BTreeSet<Uuid>>,
+	) -> Result<(), ErrorType> {
+		if depends_on.len() > constants::MAX_DEPLOYMENT_DEPENDENCIES {
+			return Err(ErrorType::WrongParameters);
+		}
+
+		// Walk through everything that the deployment would depend on. If the
+		// deployment itself is reached, the dependencies form a cycle
+		let mut visited = BTreeSet::new();
+		let mut pending = depends_on.iter().copied().collect::<Vec<_>>();
+		while let Some(dependency_id) = pending.pop() {
+			if dependency_id == deployment_id {
+				return Err(ErrorType::DependencyCycle);
+			}
+
+			if visited.insert(dependency_id) {
+				pending.extend(
+					dependency_graph
+						.get(&dependency_id)
+						.into_iter()
+						.flatten()
+						.copied(),
+				);
+			}
+		}
+
+		Ok(())
+	}
+
+	/// The ETag of the current version of the deployment, which changes every
+	/// time the deployment is updated. This can be sent in the `If-Match`
+	/// header when updating the deployment, so that changes made since the
+	/// deployment was fetched are not overwritten
+	pub fn etag(&self) -> ETag {
+		Self::etag_for(self.updated_at)
+	}
+
+	/// The ETag of the version of a deployment that was last updated at the
+	/// given time
+	pub fn etag_for(updated_at: OffsetDateTime) -> ETag {
+		format!("\"{}\"", updated_at.unix_timestamp_nanos())
+			.parse()
+			.expect("a quoted number is always a valid ETag")
+	}
+}
+
+/// Checks if a string can be used as the key of a label of a deployment
+fn is_valid_label_key(key: &str) -> bool {
+	!key.is_empty() && is_valid_label_value(key)
+}
+
+/// Checks if a string can be used as the value of a label of a deployment
+fn is_valid_label_value(value: &str) -> bool {
+	let is_alphanumeric = |c: Option<char>| c.map_or(true, |c| c.is_ascii_alphanumeric());
+
+	value.len() <= constants::MAX_DEPLOYMENT_LABEL_LENGTH &&
+		value
+			.chars()
+			.all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')) &&
+		is_alphanumeric(value.chars().next()) &&
+		is_alphanumeric(value.chars().last())
+}
+
+/// A deployment that was deleted, but can still be restored
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct DeletedDeployment {
+	/// The details of the deployment at the time it was deleted
+	#[serde(flatten)]
+	pub deployment: Deployment,
+	/// The time the deployment was deleted
+	#[cfg_attr(not(target_arch = "wasm32"), schemars(with = "String"))]
+	pub deleted: OffsetDateTime,
+	/// The time after which the deployment is permanently removed, and can no
+	/// longer be restored
+	#[cfg_attr(not(target_arch = "wasm32"), schemars(with = "String"))]
+	pub purge_after: OffsetDateTime,
 }
 
 /// Deployment running details
@@ -120,6 +455,199 @@ pub struct DeploymentRunningDetails {
 	/// mounted on
 	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
 	pub volumes: BTreeMap<Uuid, String>,
+	/// The CPU and memory requested by each replica of the deployment, and
+	/// their limits
+	#[serde(default)]
+	pub resources: DeploymentResources,
+	/// The number of seconds without any incoming requests (through a managed
+	/// URL) after which the deployment is scaled to zero replicas. The next
+	/// request starts the deployment again, and is slower than usual since it
+	/// has to wait for a replica to start. If this is `None`, the deployment
+	/// is never scaled to zero
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub scale_to_zero_after: Option<u32>,
+	/// The minimum level of the logs of the deployment that are captured by
+	/// the runner. Logs below this level are dropped when they are logged, and
+	/// are never stored, so lowering the level later does not recover them.
+	/// If this is `None`, all the logs are captured
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub log_level: Option<DeploymentLogLevel>,
+	/// The maximum number of requests (through a managed URL) that each
+	/// replica of the deployment handles at once. Requests past the combined
+	/// limit of all the ready replicas are rejected by the ingress with a
+	/// `503 Service Unavailable`, instead of being forwarded to the replicas.
+	/// If this is `None` (or zero), the number of requests is not limited
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub max_concurrent_requests: Option<u32>,
+	/// Whether the ingress keeps an access log of the requests made to the
+	/// deployment through a managed URL, with the method, path, status and
+	/// latency of each request. The paths of requests can contain personal
+	/// data (such as emails or tokens in query params), so this is off by
+	/// default. Access logs are kept separately from the logs of the
+	/// deployment, and only for a short while, since there can be a lot of
+	/// them
+	#[serde(default)]
+	pub access_logging: bool,
+}
+
+/// The CPU and memory that each replica of a deployment requests and is limited
+/// to. The requested amount is always reserved for a replica, while the limit
+/// is the most that the replica can use if there are spare resources. A limit
+/// that isn't set defaults to its request (see [`Self::with_default_limits`]).
+/// If neither of them is set, the limit is the capacity of the machine type,
+/// and the request is left to the runner to decide.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct DeploymentResources {
+	/// The CPU requested by each replica, in millicores
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub cpu_request: Option<u32>,
+	/// The most CPU that each replica can use, in millicores
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub cpu_limit: Option<u32>,
+	/// The memory requested by each replica, in MiB
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub memory_request: Option<u32>,
+	/// The most memory that each replica can use, in MiB
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub memory_limit: Option<u32>,
+}
+
+impl DeploymentResources {
+	/// The number of MiB in each unit of memory of a machine type, which is
+	/// counted in 0.25 GB increments
+	pub const MIB_PER_MEMORY_UNIT: u32 = 256;
+	/// The number of millicores in each CPU of a machine type
+	pub const MILLICORES_PER_CPU: u32 = 1000;
+
+	/// Returns the resources with each limit that isn't set defaulted to its
+	/// request, so that only the requests need to be set in simpler setups
+	pub fn with_default_limits(self) -> Self {
+		Self {
+			cpu_limit: self.cpu_limit.or(self.cpu_request),
+			memory_limit: self.memory_limit.or(self.memory_request),
+			..self
+		}
+	}
+
+	/// Returns the resources with the values that are set in the given update
+	/// replacing their current values, and the rest of the values kept as
+	/// they are. Like [`Self::with_default_limits`], a limit that isn't set in
+	/// the update defaults to its request if the request is set in the update.
+	pub fn updated_with(self, update: Self) -> Self {
+		let update = update.with_default_limits();
+		Self {
+			cpu_request: update.cpu_request.or(self.cpu_request),
+			cpu_limit: update.cpu_limit.or(self.cpu_limit),
+			memory_request: update.memory_request.or(self.memory_request),
+			memory_limit: update.memory_limit.or(self.memory_limit),
+		}
+	}
+
+	/// Checks that the requests and limits are not zero, and that the requests
+	/// are not more than their limits. The errors contain the name of the
+	/// invalid field, such as `resources.cpuRequest`. Only the first error is
+	/// returned, see [`Self::request_errors`] for all of them.
+	pub fn validate_requests(&self) -> Result<(), ErrorType> {
+		first_error(self.request_errors())
+	}
+
+	/// Finds all the errors that [`Self::validate_requests`] checks for, in
+	/// the order that they are checked in
+	pub fn request_errors(&self) -> Vec<ErrorType> {
+		let mut errors = Vec::new();
+
+		for (request_field, request, limit_field, limit) in [
+			(
+				"resources.cpuRequest",
+				self.cpu_request,
+				"resources.cpuLimit",
+				self.cpu_limit,
+			),
+			(
+				"resources.memoryRequest",
+				self.memory_request,
+				"resources.memoryLimit",
+				self.memory_limit,
+			),
+		] {
+			if request == Some(0) {
+				errors.push(ErrorType::InvalidResourceValue(request_field));
+			}
+			if limit == Some(0) {
+				errors.push(ErrorType::InvalidResourceValue(limit_field));
+			}
+			if request
+				.zip(limit)
+				.is_some_and(|(request, limit)| request > limit)
+			{
+				errors.push(ErrorType::ResourceRequestExceedsLimit(request_field));
+			}
+		}
+
+		errors
+	}
+
+	/// Checks the requests using [`Self::validate_requests`], and that the
+	/// limits are not more than the capacity of the given machine type. Since
+	/// a limit that isn't set is the capacity of the machine type, its request
+	/// cannot be more than the capacity either. The errors contain the name of
+	/// the invalid field, such as `resources.cpuLimit`. Only the first error
+	/// is returned, see [`Self::errors`] for all of them.
+	pub fn validate(&self, machine_type: &DeploymentMachineType) -> Result<(), ErrorType> {
+		first_error(self.errors(machine_type))
+	}
+
+	/// Finds all the errors that [`Self::validate`] checks for, in the order
+	/// that they are checked in
+	pub fn errors(&self, machine_type: &DeploymentMachineType) -> Vec<ErrorType> {
+		let mut errors = self.request_errors();
+
+		// A capacity that doesn't fit is more than any request or limit can be
+		let cpu_capacity = u32::from(machine_type.cpu_count)
+			.checked_mul(Self::MILLICORES_PER_CPU)
+			.unwrap_or(u32::MAX);
+		let memory_capacity = machine_type
+			.memory_count
+			.checked_mul(Self::MIB_PER_MEMORY_UNIT)
+			.unwrap_or(u32::MAX);
+
+		for (request_field, request, limit_field, limit, capacity) in [
+			(
+				"resources.cpuRequest",
+				self.cpu_request,
+				"resources.cpuLimit",
+				self.cpu_limit,
+				cpu_capacity,
+			),
+			(
+				"resources.memoryRequest",
+				self.memory_request,
+				"resources.memoryLimit",
+				self.memory_limit,
+				memory_capacity,
+			),
+		] {
+			if limit.is_some_and(|limit| limit > capacity) {
+				errors.push(ErrorType::ResourceLimitExceedsMachineType(limit_field));
+			}
+			if limit.is_none() && request.is_some_and(|request| request > capacity) {
+				errors.push(ErrorType::ResourceRequestExceedsLimit(request_field));
+			}
+		}
+
+		errors
+	}
+}
+
+/// Turns the errors found by a validation into its result, which fails with
+/// the first of the errors if any were found
+fn first_error(errors: Vec<ErrorType>) -> Result<(), ErrorType> {
+	match errors.into_iter().next() {
+		Some(error) => Err(error),
+		None => Ok(()),
+	}
 }
 
 /// The type of environment variable
@@ -303,7 +831,10 @@ impl DeploymentRegistry {
 }
 
 /// All the possible deployment status a deployment can be
-/// in during its life cycle
+/// in during its life cycle. The status that is stored is the state that the
+/// deployment was last put in, and is refined using the readiness of its
+/// replicas (see [`DeploymentStatus::with_replica_readiness`]) before it is
+/// returned by the API.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[cfg_attr(not(target_arch = "wasm32"), derive(sqlx::Type, schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
@@ -314,25 +845,138 @@ impl DeploymentRegistry {
 pub enum DeploymentStatus {
 	/// Deployment has been created
 	Created,
+	/// Deployment is waiting to be scheduled on the runner
+	Pending,
+	/// The image of the deployment is being pulled
+	Pulling,
 	/// Deployment is deploying
 	Deploying,
+	/// The containers of the deployment have been created, but none of the
+	/// replicas are ready yet
+	Starting,
 	/// Deployment is running
 	Running,
+	/// Deployment is running, but fewer replicas are ready than requested
+	Degraded,
+	/// Deployment is being stopped
+	Stopping,
 	/// Deployment has stopped
 	Stopped,
+	/// Deployment was scaled to zero replicas after not receiving any requests
+	/// for its [`scale_to_zero_after`][1] period, and is started again by the
+	/// next request it receives
+	///
+	/// [1]: DeploymentRunningDetails::scale_to_zero_after
+	Cold,
 	/// Deployment has errored and stopped
 	Errored,
 	/// The deployment's runner is not reachable
 	Unreachable,
 }
 
+/// The number of replicas of a deployment that are ready to receive requests,
+/// out of all its replicas, as last reported by its runner
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct DeploymentReplicaReadiness {
+	/// The number of replicas that are ready
+	pub ready: u16,
+	/// The total number of replicas, ready or not
+	pub total: u16,
+}
+
+impl DeploymentReplicaReadiness {
+	/// Whether this readiness can be reported by a runner. No more replicas
+	/// than there are can be ready, and there can be at most
+	/// [`constants::MAX_REPORTED_REPLICAS`] of them
+	pub fn is_valid(&self) -> bool {
+		self.ready <= self.total && self.total <= constants::MAX_REPORTED_REPLICAS
+	}
+}
+
+impl DeploymentStatus {
+	/// Refines the status of a deployment that is meant to be running using the
+	/// readiness of its replicas that was last reported by its runner, out of
+	/// all the replicas that the runner is running for it. The status is
+	/// returned as-is if the runner hasn't reported the readiness of the
+	/// deployment yet, or if the deployment is not meant to be running.
+	pub fn with_replica_readiness(self, replicas: Option<DeploymentReplicaReadiness>) -> Self {
+		let Some(DeploymentReplicaReadiness {
+			ready: ready_replicas,
+			total: desired_replicas,
+		}) = replicas
+		else {
+			return self;
+		};
+
+		match self {
+			Self::Deploying | Self::Starting | Self::Running | Self::Degraded => {
+				if ready_replicas == 0 {
+					if self == Self::Deploying {
+						Self::Deploying
+					} else {
+						Self::Starting
+					}
+				} else if ready_replicas < desired_replicas {
+					Self::Degraded
+				} else {
+					Self::Running
+				}
+			}
+			status => status,
+		}
+	}
+
+	/// Whether the deployment is serving traffic. This is what consumers that
+	/// only understand a running / not running flag should use. A degraded
+	/// deployment is still considered to be running.
+	pub fn is_running(&self) -> bool {
+		matches!(self, Self::Running | Self::Degraded)
+	}
+
+	/// Whether the deployment is running with all of its replicas ready
+	pub fn is_healthy(&self) -> bool {
+		matches!(self, Self::Running)
+	}
+
+	/// Whether the deployments that depend on a deployment with this status
+	/// can be started, given the readiness of its replicas that was reported
+	/// by its runner, if any. Until the readiness is reported, the status is
+	/// only the one that was requested, so the deployment isn't known to be
+	/// healthy yet. A deployment that is scaled to zero is started by the
+	/// first request it receives, so it never holds up the deployments that
+	/// depend on it.
+	pub fn satisfies_dependency(&self, replicas: Option<DeploymentReplicaReadiness>) -> bool {
+		match self {
+			Self::Cold => true,
+			status => replicas.is_some() && status.is_healthy(),
+		}
+	}
+
+	/// Whether the deployment is in the middle of a transition from one state
+	/// to another, during which it cannot be started or stopped
+	pub fn is_transitioning(&self) -> bool {
+		matches!(
+			self,
+			Self::Pending | Self::Pulling | Self::Deploying | Self::Starting | Self::Stopping
+		)
+	}
+}
+
 impl Display for DeploymentStatus {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 		match self {
 			Self::Created => write!(f, "created"),
+			Self::Pending => write!(f, "pending"),
+			Self::Pulling => write!(f, "pulling"),
 			Self::Deploying => write!(f, "deploying"),
+			Self::Starting => write!(f, "starting"),
 			Self::Running => write!(f, "running"),
+			Self::Degraded => write!(f, "degraded"),
+			Self::Stopping => write!(f, "stopping"),
 			Self::Stopped => write!(f, "stopped"),
+			Self::Cold => write!(f, "cold"),
 			Self::Errored => write!(f, "errored"),
 			Self::Unreachable => write!(f, "unreachable"),
 		}
@@ -346,9 +990,15 @@ impl FromStr for DeploymentStatus {
 		let s = s.to_lowercase();
 		match s.as_str() {
 			"created" => Ok(Self::Created),
+			"pending" => Ok(Self::Pending),
+			"pulling" => Ok(Self::Pulling),
 			"deploying" => Ok(Self::Deploying),
+			"starting" => Ok(Self::Starting),
 			"running" => Ok(Self::Running),
+			"degraded" => Ok(Self::Degraded),
+			"stopping" => Ok(Self::Stopping),
 			"stopped" => Ok(Self::Stopped),
+			"cold" => Ok(Self::Cold),
 			"errored" => Ok(Self::Errored),
 			"unreachable" => Ok(Self::Unreachable),
 			_ => Err(s),
@@ -356,11 +1006,59 @@ impl FromStr for DeploymentStatus {
 	}
 }
 
+/// Whether the runner of a deployment has applied the latest changes made to
+/// it. Deployments are applied by their runner asynchronously, so a deployment
+/// that was just saved may not have been applied yet.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(sqlx::Type, schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(
+	not(target_arch = "wasm32"),
+	sqlx(
+		type_name = "DEPLOYMENT_RECONCILIATION_STATE",
+		rename_all = "snake_case"
+	)
+)]
+pub enum DeploymentReconciliationState {
+	/// The runner has applied the latest changes to the deployment
+	InSync,
+	/// The deployment has changed since the runner last applied it
+	#[default]
+	Pending,
+	/// The runner failed to apply the latest changes to the deployment, and
+	/// will retry
+	Failed,
+}
+
+/// The reconciliation status of a deployment, which tells whether the changes
+/// made to the deployment have been applied by its runner yet
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct DeploymentReconciliationStatus {
+	/// Whether the latest changes to the deployment have been applied
+	pub state: DeploymentReconciliationState,
+	/// The reason the runner failed to apply the deployment, if it did
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub reason: Option<String>,
+	/// The last time the runner tried to apply the deployment, if it ever did
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[cfg_attr(not(target_arch = "wasm32"), schemars(with = "Option<String>"))]
+	pub last_attempt: Option<OffsetDateTime>,
+	/// The number of times the deployment has been changed in a way that the
+	/// runner has to apply. The runner reports the generation that it applied,
+	/// so that a report for older changes doesn't mark newer ones as applied
+	#[serde(default)]
+	pub generation: u64,
+}
+
 /// Deployment metrics
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct DeploymentMetric {
 	/// The timestamp of the metric
+	#[cfg_attr(not(target_arch = "wasm32"), schemars(with = "String"))]
 	pub timestamp: OffsetDateTime,
 	/// The cpu usage of a pod
 	pub cpu_usage: String,
@@ -372,12 +1070,536 @@ pub struct DeploymentMetric {
 	pub network_usage_rx: String,
 }
 
+/// A request made to a deployment through a managed URL, as recorded by the
+/// ingress when [`access_logging`][DeploymentRunningDetails::access_logging] is
+/// enabled for the deployment
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct DeploymentAccessLog {
+	/// The time the request was received at
+	#[cfg_attr(not(target_arch = "wasm32"), schemars(with = "String"))]
+	pub timestamp: OffsetDateTime,
+	/// The HTTP method of the request
+	pub method: String,
+	/// The path of the request, including the query string. This can contain
+	/// personal data, depending on how the deployment uses its URLs
+	pub path: String,
+	/// The status code of the response. Requests that the ingress rejected or
+	/// failed to forward are logged with the status it responded with
+	pub status: u16,
+	/// The number of milliseconds it took for the response to be returned
+	pub latency_millis: u64,
+	/// Whether the request was sent to the canary of the deployment instead of
+	/// its primary replicas
+	#[serde(default)]
+	pub canary: bool,
+}
+
 /// Deployment logs
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct DeploymentLog {
 	/// Timestamp of a deployment log
+	#[cfg_attr(not(target_arch = "wasm32"), schemars(with = "String"))]
 	pub timestamp: OffsetDateTime,
 	/// The logs of a deployment
 	pub log: String,
+	/// The fields extracted from the log, if the logs were requested to be
+	/// parsed and the log is a JSON object. Otherwise, the log is plain text
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub parsed: Option<ParsedDeploymentLog>,
+}
+
+/// The fields of a deployment log that was logged as a JSON object
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct ParsedDeploymentLog {
+	/// The level of the log, taken from the `level`, `lvl` or `severity` field
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub level: Option<String>,
+	/// The timestamp of the log as it was logged by the deployment, taken from
+	/// the `timestamp`, `time`, `ts` or `@timestamp` field
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub timestamp: Option<String>,
+	/// The message of the log, taken from the `message` or `msg` field
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub message: Option<String>,
+	/// The rest of the fields of the log
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	pub fields: BTreeMap<String, serde_json::Value>,
+}
+
+impl ParsedDeploymentLog {
+	/// Parses a log line that was logged as a JSON object, extracting its
+	/// level, timestamp and message. Returns `None` if the line is not a JSON
+	/// object, in which case the line should be treated as plain text
+	pub fn parse(line: &str) -> Option<Self> {
+		let serde_json::Value::Object(fields) = serde_json::from_str(line.trim()).ok()? else {
+			return None;
+		};
+		let mut fields = fields.into_iter().collect::<BTreeMap<_, _>>();
+
+		Some(Self {
+			level: Self::take_field(&mut fields, &["level", "lvl", "severity"]),
+			timestamp: Self::take_field(&mut fields, &["timestamp", "time", "ts", "@timestamp"]),
+			message: Self::take_field(&mut fields, &["message", "msg"]),
+			fields,
+		})
+	}
+
+	/// Removes the first of the given keys that is present in the fields,
+	/// returning its value as a string
+	fn take_field(
+		fields: &mut BTreeMap<String, serde_json::Value>,
+		keys: &[&str],
+	) -> Option<String> {
+		keys.iter()
+			.find_map(|key| fields.remove(*key))
+			.map(|value| match value {
+				serde_json::Value::String(value) => value,
+				value => value.to_string(),
+			})
+	}
+}
+
+/// The level of a deployment log, in increasing order of severity. This is
+/// used as the minimum level of the logs that are captured for a deployment
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(sqlx::Type, schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(
+	not(target_arch = "wasm32"),
+	sqlx(type_name = "DEPLOYMENT_LOG_LEVEL", rename_all = "lowercase")
+)]
+pub enum DeploymentLogLevel {
+	/// Trace logs
+	Trace,
+	/// Debug logs
+	Debug,
+	/// Informational logs
+	Info,
+	/// Warnings
+	Warn,
+	/// Errors
+	Error,
+}
+
+impl Display for DeploymentLogLevel {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Trace => write!(f, "trace"),
+			Self::Debug => write!(f, "debug"),
+			Self::Info => write!(f, "info"),
+			Self::Warn => write!(f, "warn"),
+			Self::Error => write!(f, "error"),
+		}
+	}
+}
+
+impl FromStr for DeploymentLogLevel {
+	type Err = String;
+
+	/// Parses a log level, accepting the common aliases used by logging
+	/// libraries
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let s = s.to_lowercase();
+		match s.as_str() {
+			"trace" => Ok(Self::Trace),
+			"debug" => Ok(Self::Debug),
+			"info" | "information" | "notice" => Ok(Self::Info),
+			"warn" | "warning" => Ok(Self::Warn),
+			"error" | "err" | "fatal" | "critical" | "panic" => Ok(Self::Error),
+			_ => Err(s),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::{BTreeMap, BTreeSet};
+
+	use preprocess::Preprocessable;
+
+	use super::{
+		Deployment,
+		DeploymentCanary,
+		DeploymentCanaryReplicas,
+		DeploymentLabelSelector,
+		DeploymentLogLevel,
+		DeploymentMachineType,
+		DeploymentReplicaReadiness,
+		DeploymentResources,
+		DeploymentStatus,
+		ParsedDeploymentLog,
+		SetDeploymentCanaryRequest,
+	};
+	use crate::{prelude::Uuid, utils::constants, ErrorType};
+
+	#[test]
+	fn canaries_never_exceed_the_maximum_replicas() {
+		let canary = |weight| DeploymentCanary {
+			image_tag: "v2".to_string(),
+			weight,
+		};
+
+		assert_eq!(
+			canary(10).replicas(4, 10),
+			Some(DeploymentCanaryReplicas {
+				canary: 1,
+				min_primary: 3,
+				max_primary: 9,
+			})
+		);
+		assert_eq!(
+			canary(50).replicas(5, 5),
+			Some(DeploymentCanaryReplicas {
+				canary: 3,
+				min_primary: 2,
+				max_primary: 2,
+			})
+		);
+		// Both versions always get at least one replica
+		assert_eq!(
+			canary(100).replicas(2, 2),
+			Some(DeploymentCanaryReplicas {
+				canary: 1,
+				min_primary: 1,
+				max_primary: 1,
+			})
+		);
+		assert_eq!(
+			canary(0).replicas(1, 3),
+			Some(DeploymentCanaryReplicas {
+				canary: 1,
+				min_primary: 1,
+				max_primary: 2,
+			})
+		);
+		assert_eq!(canary(50).replicas(1, 1), None);
+
+		for weight in [0, 1, 33, 50, 99, 100] {
+			for max in 2..=20 {
+				for min in 1..=max {
+					let replicas = canary(weight).replicas(min, max).unwrap();
+					assert!(replicas.canary + replicas.max_primary <= max);
+					assert!(replicas.canary + replicas.min_primary >= min);
+					assert!(replicas.min_primary <= replicas.max_primary);
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn canary_image_tags_keep_their_case() {
+		let request = |image_tag: &str| {
+			SetDeploymentCanaryRequest {
+				image_tag: image_tag.to_string(),
+				weight: 10,
+			}
+			.preprocess()
+			.map(|request| request.image_tag)
+		};
+
+		assert_eq!(request(" v2.0-RC1 ").ok().as_deref(), Some("v2.0-RC1"));
+		assert_eq!(
+			request("Latest_Build").ok().as_deref(),
+			Some("Latest_Build")
+		);
+		assert!(request("").is_err());
+		assert!(request(".hidden").is_err());
+		assert!(request("v2:latest").is_err());
+		assert!(request(&"a".repeat(129)).is_err());
+	}
+
+	#[test]
+	fn dependency_cycles_are_rejected() {
+		let [a, b, c, d] = [(); 4].map(|_| Uuid::new_v4());
+		// c depends on b, which depends on a
+		let graph = BTreeMap::from([(b, BTreeSet::from([a])), (c, BTreeSet::from([b]))]);
+
+		assert_eq!(
+			Deployment::validate_dependencies(d, &BTreeSet::from([c, a]), &graph),
+			Ok(())
+		);
+		// The existing dependencies of a deployment are replaced
+		assert_eq!(
+			Deployment::validate_dependencies(b, &BTreeSet::from([d]), &graph),
+			Ok(())
+		);
+
+		assert_eq!(
+			Deployment::validate_dependencies(a, &BTreeSet::from([a]), &graph),
+			Err(ErrorType::DependencyCycle)
+		);
+		assert_eq!(
+			Deployment::validate_dependencies(a, &BTreeSet::from([b]), &graph),
+			Err(ErrorType::DependencyCycle)
+		);
+		assert_eq!(
+			Deployment::validate_dependencies(a, &BTreeSet::from([d, c]), &graph),
+			Err(ErrorType::DependencyCycle)
+		);
+
+		let too_many = (0..=constants::MAX_DEPLOYMENT_DEPENDENCIES)
+			.map(|_| Uuid::new_v4())
+			.collect();
+		assert_eq!(
+			Deployment::validate_dependencies(d, &too_many, &graph),
+			Err(ErrorType::WrongParameters)
+		);
+	}
+
+	#[test]
+	fn status_is_refined_by_replica_readiness() {
+		let readiness = |ready, total| Some(DeploymentReplicaReadiness { ready, total });
+
+		assert_eq!(
+			DeploymentStatus::Running.with_replica_readiness(readiness(0, 2)),
+			DeploymentStatus::Starting
+		);
+		assert_eq!(
+			DeploymentStatus::Running.with_replica_readiness(readiness(1, 2)),
+			DeploymentStatus::Degraded
+		);
+		assert_eq!(
+			DeploymentStatus::Degraded.with_replica_readiness(readiness(2, 2)),
+			DeploymentStatus::Running
+		);
+		assert_eq!(
+			DeploymentStatus::Deploying.with_replica_readiness(readiness(0, 2)),
+			DeploymentStatus::Deploying
+		);
+		assert_eq!(
+			DeploymentStatus::Stopped.with_replica_readiness(readiness(2, 2)),
+			DeploymentStatus::Stopped
+		);
+		assert_eq!(
+			DeploymentStatus::Cold.with_replica_readiness(readiness(0, 2)),
+			DeploymentStatus::Cold
+		);
+
+		// Only the replicas that the runner runs count, no matter how many the
+		// deployment asks for, since the docker runner only ever runs one
+		assert_eq!(
+			DeploymentStatus::Running.with_replica_readiness(readiness(1, 1)),
+			DeploymentStatus::Running
+		);
+		// Until the readiness is reported, nothing is known about it
+		assert_eq!(
+			DeploymentStatus::Running.with_replica_readiness(None),
+			DeploymentStatus::Running
+		);
+	}
+
+	#[test]
+	fn degraded_is_running_but_not_healthy() {
+		assert!(DeploymentStatus::Degraded.is_running());
+		assert!(!DeploymentStatus::Degraded.is_healthy());
+		assert!(DeploymentStatus::Running.is_healthy());
+		assert!(!DeploymentStatus::Starting.is_running());
+	}
+
+	#[test]
+	fn dependencies_are_only_satisfied_once_known_to_be_healthy() {
+		let ready = Some(DeploymentReplicaReadiness { ready: 2, total: 2 });
+
+		assert!(DeploymentStatus::Running.satisfies_dependency(ready));
+		assert!(DeploymentStatus::Cold.satisfies_dependency(None));
+		// Without any readiness reported, the status was never refined
+		assert!(!DeploymentStatus::Running.satisfies_dependency(None));
+		assert!(!DeploymentStatus::Degraded.satisfies_dependency(ready));
+		assert!(!DeploymentStatus::Stopped.satisfies_dependency(ready));
+	}
+
+	#[test]
+	fn json_logs_are_parsed_and_plain_text_is_not() {
+		let parsed = ParsedDeploymentLog::parse(
+			r#"{"level":"warn","ts":1700000000,"msg":"disk almost full","disk":"/dev/sda"}"#,
+		)
+		.unwrap();
+		assert_eq!(parsed.level.as_deref(), Some("warn"));
+		assert_eq!(parsed.timestamp.as_deref(), Some("1700000000"));
+		assert_eq!(parsed.message.as_deref(), Some("disk almost full"));
+		assert_eq!(parsed.fields.len(), 1);
+
+		assert_eq!(ParsedDeploymentLog::parse("Listening on port 3000"), None);
+		assert_eq!(ParsedDeploymentLog::parse(r#"{"level":"info","#), None);
+		assert_eq!(ParsedDeploymentLog::parse("[1, 2, 3]"), None);
+	}
+
+	#[test]
+	fn log_levels_accept_common_aliases() {
+		assert_eq!("INFO".parse(), Ok(DeploymentLogLevel::Info));
+		assert_eq!("warning".parse(), Ok(DeploymentLogLevel::Warn));
+		assert_eq!("fatal".parse(), Ok(DeploymentLogLevel::Error));
+		assert!("verbose".parse::<DeploymentLogLevel>().is_err());
+		assert!(DeploymentLogLevel::Debug < DeploymentLogLevel::Warn);
+	}
+
+	#[test]
+	fn labels_and_label_selectors_are_validated() {
+		let labels = BTreeMap::from([
+			("team".to_string(), "payments".to_string()),
+			("cost-center".to_string(), "cc_1042".to_string()),
+			("canary".to_string(), "".to_string()),
+		]);
+		assert_eq!(Deployment::validate_labels(&labels), Ok(()));
+
+		for (key, value) in [
+			("", "payments"),
+			("-team", "payments"),
+			("team", "pay ments"),
+		] {
+			let labels = BTreeMap::from([(key.to_string(), value.to_string())]);
+			assert_eq!(
+				Deployment::validate_labels(&labels),
+				Err(ErrorType::WrongParameters)
+			);
+		}
+		let labels = BTreeMap::from([("team".to_string(), "a".repeat(64))]);
+		assert_eq!(
+			Deployment::validate_labels(&labels),
+			Err(ErrorType::WrongParameters)
+		);
+
+		let selector = "team=payments, env=prod".parse::<DeploymentLabelSelector>();
+		assert_eq!(
+			selector,
+			Ok(DeploymentLabelSelector(BTreeMap::from([
+				("env".to_string(), "prod".to_string()),
+				("team".to_string(), "payments".to_string()),
+			])))
+		);
+		assert_eq!(selector.unwrap().to_string(), "env=prod,team=payments");
+		assert!("team".parse::<DeploymentLabelSelector>().is_err());
+		assert!("team=a,team=b".parse::<DeploymentLabelSelector>().is_err());
+		assert!("team=pay ments".parse::<DeploymentLabelSelector>().is_err());
+	}
+
+	#[test]
+	fn resources_are_validated_against_limits_and_machine_type() {
+		let machine_type = DeploymentMachineType {
+			cpu_count: 1,
+			memory_count: 4,
+		};
+
+		let resources = DeploymentResources {
+			cpu_request: Some(250),
+			memory_request: Some(512),
+			..Default::default()
+		}
+		.with_default_limits();
+		assert_eq!(resources.cpu_limit, Some(250));
+		assert_eq!(resources.memory_limit, Some(512));
+		assert_eq!(resources.validate(&machine_type), Ok(()));
+
+		let resources = DeploymentResources {
+			cpu_request: Some(500),
+			cpu_limit: Some(250),
+			..Default::default()
+		};
+		assert_eq!(
+			resources.validate(&machine_type),
+			Err(ErrorType::ResourceRequestExceedsLimit(
+				"resources.cpuRequest"
+			))
+		);
+
+		let resources = DeploymentResources {
+			memory_limit: Some(2048),
+			..Default::default()
+		};
+		assert_eq!(
+			resources.validate(&machine_type),
+			Err(ErrorType::ResourceLimitExceedsMachineType(
+				"resources.memoryLimit"
+			))
+		);
+
+		let resources = DeploymentResources {
+			memory_request: Some(0),
+			..Default::default()
+		};
+		assert_eq!(
+			resources.validate(&machine_type),
+			Err(ErrorType::InvalidResourceValue("resources.memoryRequest"))
+		);
+	}
+
+	#[test]
+	fn all_the_invalid_resources_are_found() {
+		let machine_type = DeploymentMachineType {
+			cpu_count: 1,
+			memory_count: 4,
+		};
+
+		let resources = DeploymentResources {
+			cpu_request: Some(0),
+			cpu_limit: Some(2000),
+			memory_request: Some(2048),
+			memory_limit: Some(1024),
+		};
+		assert_eq!(
+			resources.errors(&machine_type),
+			[
+				ErrorType::InvalidResourceValue("resources.cpuRequest"),
+				ErrorType::ResourceRequestExceedsLimit("resources.memoryRequest"),
+				ErrorType::ResourceLimitExceedsMachineType("resources.cpuLimit"),
+			]
+		);
+		assert_eq!(
+			resources.validate(&machine_type),
+			Err(ErrorType::InvalidResourceValue("resources.cpuRequest"))
+		);
+
+		// The capacity of a huge machine type doesn't overflow
+		let machine_type = DeploymentMachineType {
+			cpu_count: u16::MAX,
+			memory_count: u32::MAX,
+		};
+		let resources = DeploymentResources {
+			memory_request: Some(u32::MAX),
+			..Default::default()
+		};
+		assert!(resources.errors(&machine_type).is_empty());
+	}
+
+	#[test]
+	fn updated_resources_keep_the_values_that_are_not_updated() {
+		let current = DeploymentResources {
+			cpu_request: Some(250),
+			cpu_limit: Some(500),
+			memory_request: Some(256),
+			memory_limit: Some(1024),
+		};
+
+		let updated = current.updated_with(DeploymentResources {
+			memory_limit: Some(2048),
+			..Default::default()
+		});
+		assert_eq!(
+			updated,
+			DeploymentResources {
+				memory_limit: Some(2048),
+				..current
+			}
+		);
+
+		// A request that is updated without its limit sets the limit too
+		let updated = current.updated_with(DeploymentResources {
+			cpu_request: Some(1000),
+			..Default::default()
+		});
+		assert_eq!(
+			updated,
+			DeploymentResources {
+				cpu_request: Some(1000),
+				cpu_limit: Some(1000),
+				..current
+			}
+		);
+	}
 }
@@ -0,0 +1,66 @@
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::*;
+
+/// A part of a deployment's configuration that can be copied over when
+/// promoting one deployment to another. The image is always copied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub enum PromotedDeploymentConfig {
+	/// The environment variables of the deployment. Variables that are marked
+	/// as environment-specific on either deployment are never copied or
+	/// overwritten
+	EnvironmentVariables,
+	/// The config mounts of the deployment
+	ConfigMounts,
+	/// The exposed ports of the deployment
+	Ports,
+	/// The startup and liveness probes of the deployment
+	Probes,
+	/// The machine type of the deployment, along with its CPU and memory
+	/// requests and limits
+	MachineType,
+	/// The minimum and maximum horizontal scale of the deployment, along with
+	/// the period of inactivity after which it is scaled to zero and the
+	/// maximum number of requests that each replica handles at once
+	Scaling,
+}
+
+macros::declare_api_endpoint!(
+	/// Route to promote a deployment to another one, such as from a staging
+	/// deployment to a production deployment. The image of the source
+	/// deployment, along with the selected parts of its configuration, is
+	/// copied to the target deployment, which is then redeployed.
+	PromoteDeployment,
+	POST "/workspace/:workspace_id/deployment/:deployment_id/promote" {
+		/// The workspace ID of the user
+		pub workspace_id: Uuid,
+		/// The deployment ID of the target deployment to promote to
+		pub deployment_id: Uuid,
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.deployment_id,
+			permission: Permission::Deployment(DeploymentPermission::Edit),
+		}
+	},
+	request = {
+		/// The deployment ID of the source deployment to promote from
+		#[preprocess(none)]
+		pub source_deployment_id: Uuid,
+		/// The parts of the source deployment's configuration to copy to the
+		/// target deployment, in addition to the image
+		#[preprocess(none)]
+		#[serde(default)]
+		pub config: BTreeSet<PromotedDeploymentConfig>,
+	}
+);
@@ -0,0 +1,27 @@
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route to promote the canary of a deployment. The image tag of the
+	/// canary becomes the image tag of the deployment, which is redeployed
+	/// with it, creating a new revision in its deploy history. All the
+	/// requests are then sent to the deployment, and the canary is removed
+	PromoteDeploymentCanary,
+	POST "/workspace/:workspace_id/deployment/:deployment_id/canary/promote" {
+		/// The workspace ID of the user
+		pub workspace_id: Uuid,
+		/// The deployment ID to promote the canary of
+		pub deployment_id: Uuid,
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.deployment_id,
+			permission: Permission::Deployment(DeploymentPermission::Edit),
+		}
+	}
+);
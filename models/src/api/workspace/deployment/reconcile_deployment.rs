@@ -0,0 +1,26 @@
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route to force the runner of a deployment to reconcile it again, such as
+	/// after a failed reconciliation. The deployment is marked as pending until
+	/// the runner reports back
+	ReconcileDeployment,
+	POST "/workspace/:workspace_id/deployment/:deployment_id/reconcile" {
+		/// The workspace ID of the user
+		pub workspace_id: Uuid,
+		/// The deployment ID to reconcile
+		pub deployment_id: Uuid,
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.deployment_id,
+			permission: Permission::Deployment(DeploymentPermission::Edit),
+		}
+	}
+);
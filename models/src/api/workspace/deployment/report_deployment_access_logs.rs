@@ -0,0 +1,120 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route for the ingress to report the requests made to deployments
+	/// through managed URLs, to be stored in the access logs of the
+	/// deployments. The ingress collects the requests and reports them in
+	/// batches of at most [`MAX_ACCESS_LOG_BATCH_SIZE`][1], instead of
+	/// reporting each request on its own. Requests are only stored if access
+	/// logging is enabled for their deployment. Since access logs can contain
+	/// personal data, only the ingress can report them, using the token that
+	/// it is configured with
+	///
+	/// [1]: crate::utils::constants::MAX_ACCESS_LOG_BATCH_SIZE
+	ReportDeploymentAccessLogs,
+	POST "/deployment/access-logs",
+	request_headers = {
+		/// The token that the ingress is configured with
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	request = {
+		/// The requests made to the deployments
+		#[preprocess(none)]
+		pub logs: Vec<ReportedDeploymentAccessLog>,
+	}
+);
+
+/// A request made to a deployment, as reported by the ingress in a
+/// [`ReportDeploymentAccessLogsRequest`]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct ReportedDeploymentAccessLog {
+	/// The deployment ID that received the request
+	pub deployment_id: Uuid,
+	/// The time the request was received at
+	#[serde(with = "time::serde::rfc3339")]
+	#[cfg_attr(not(target_arch = "wasm32"), schemars(with = "String"))]
+	pub timestamp: OffsetDateTime,
+	/// The HTTP method of the request
+	pub method: String,
+	/// The path of the request, including the query string
+	pub path: String,
+	/// The status code of the response
+	pub status: u16,
+	/// The number of milliseconds it took for the response to be returned
+	pub latency_millis: u64,
+	/// Whether the request was sent to the canary of the deployment instead of
+	/// its primary replicas
+	#[serde(default)]
+	pub canary: bool,
+}
+
+impl ReportedDeploymentAccessLog {
+	/// Checks that the request is one that the ingress could have made, so
+	/// that nothing else ends up in the access logs of a deployment
+	pub fn is_valid(&self) -> bool {
+		let method = self.method.trim();
+		!method.is_empty() &&
+			method.len() <= 16 &&
+			!self.path.is_empty() &&
+			self.path.len() <= 2048 &&
+			(100..=599).contains(&self.status)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use time::OffsetDateTime;
+
+	use super::ReportedDeploymentAccessLog;
+	use crate::prelude::*;
+
+	#[test]
+	fn only_requests_the_ingress_could_make_are_valid() {
+		let log = ReportedDeploymentAccessLog {
+			deployment_id: Uuid::nil(),
+			timestamp: OffsetDateTime::UNIX_EPOCH,
+			method: "GET".to_string(),
+			path: "/?page=1".to_string(),
+			status: 200,
+			latency_millis: 12,
+			canary: false,
+		};
+		assert!(log.is_valid());
+
+		for invalid in [
+			ReportedDeploymentAccessLog {
+				method: " ".to_string(),
+				..log.clone()
+			},
+			ReportedDeploymentAccessLog {
+				method: "GET".repeat(6),
+				..log.clone()
+			},
+			ReportedDeploymentAccessLog {
+				path: String::new(),
+				..log.clone()
+			},
+			ReportedDeploymentAccessLog {
+				path: "/".repeat(2049),
+				..log.clone()
+			},
+			ReportedDeploymentAccessLog {
+				status: 99,
+				..log.clone()
+			},
+			ReportedDeploymentAccessLog {
+				status: 600,
+				..log.clone()
+			},
+		] {
+			assert!(!invalid.is_valid(), "{invalid:?}");
+		}
+	}
+}
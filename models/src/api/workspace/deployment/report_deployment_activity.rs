@@ -0,0 +1,83 @@
+use super::DeploymentCanaryRequests;
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route for the ingress to report that a deployment received a request
+	/// through a managed URL. This keeps a deployment that scales to zero from
+	/// being scaled down, and starts it again if it was already scaled down.
+	/// Since the reported activity decides whether a deployment keeps running,
+	/// only the ingress can report it, using the token that it is configured
+	/// with
+	ReportDeploymentActivity,
+	POST "/deployment/:deployment_id/activity" {
+		/// The deployment ID that received a request
+		pub deployment_id: Uuid,
+	},
+	request_headers = {
+		/// The token that the ingress is configured with
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	request = {
+		/// The number of requests to the deployment that the ingress is
+		/// currently forwarding, if the number of requests to the deployment
+		/// is limited. This is only used to report the utilization of the
+		/// limit in the metrics
+		#[preprocess(none)]
+		#[serde(default, skip_serializing_if = "Option::is_none")]
+		pub concurrent_requests: Option<u32>,
+		/// The requests that the ingress sent to each version of a deployment
+		/// with a canary since it last reported activity on the deployment.
+		/// They are added to the counts of the canary
+		#[preprocess(none)]
+		#[serde(default, skip_serializing_if = "Option::is_none")]
+		pub requests: Option<DeploymentCanaryRequests>,
+	},
+	response = {
+		/// Whether the deployment was scaled to zero and is being started
+		/// again. Requests to the deployment will not be served until one of
+		/// its replicas is ready, so the ingress can ask clients to retry
+		/// later instead of forwarding them
+		pub warming: bool,
+		/// The maximum number of requests that the ingress can forward to the
+		/// deployment at once, which is the limit of each replica multiplied
+		/// by the number of ready replicas (or one, if none are ready yet). If
+		/// the runner doesn't report the readiness of the replicas, the
+		/// minimum number of replicas of the deployment is used instead.
+		/// Requests past this limit are rejected. If this is `None`, the
+		/// number of requests is not limited
+		#[serde(default, skip_serializing_if = "Option::is_none")]
+		pub max_concurrent_requests: Option<u32>,
+		/// Whether the ingress should keep an access log of the requests made
+		/// to the deployment, and report them using
+		/// [`ReportDeploymentAccessLogsRequest`][super::ReportDeploymentAccessLogsRequest]
+		#[serde(default)]
+		pub access_logging: bool,
+		/// The percentage of the requests to the deployment that the ingress
+		/// should send to its canary, if it has one and its runner has reported
+		/// that it is running. The ingress counts the requests sent to each
+		/// version, and reports the counts along with its activity, so that
+		/// both versions can be compared
+		#[serde(default, skip_serializing_if = "Option::is_none")]
+		pub canary_weight: Option<u8>,
+		/// The version of the custom error pages that the ingress should serve
+		/// instead of its own error responses, if the deployment or its
+		/// workspace has any. This changes whenever the pages do. The pages
+		/// themselves are only fetched using
+		/// [`GetIngressErrorPagesRequest`][super::error_page::GetIngressErrorPagesRequest]
+		/// when one of them has to be served, and are cached by this version
+		#[serde(default, skip_serializing_if = "Option::is_none")]
+		pub error_pages_version: Option<String>,
+		/// The number of primary replicas of the deployment that are ready to
+		/// receive requests, if the runner has reported it. The ingress never
+		/// forwards requests to replicas that are known to not be ready
+		#[serde(default, skip_serializing_if = "Option::is_none")]
+		pub ready_replicas: Option<u16>,
+		/// The number of replicas of the canary of the deployment that are
+		/// ready to receive requests, if it has a canary and the runner has
+		/// reported it
+		#[serde(default, skip_serializing_if = "Option::is_none")]
+		pub canary_ready_replicas: Option<u16>,
+	}
+);
@@ -0,0 +1,42 @@
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route for the runner to report how many replicas of a deployment are
+	/// ready to receive requests, as determined by their health checks. The
+	/// ingress only forwards requests to replicas that are ready
+	ReportDeploymentReadiness,
+	PUT "/workspace/:workspace_id/deployment/:deployment_id/readiness" {
+		/// The workspace ID of the user
+		pub workspace_id: Uuid,
+		/// The deployment ID whose readiness changed
+		pub deployment_id: Uuid,
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.deployment_id,
+			permission: Permission::Deployment(DeploymentPermission::Edit),
+		}
+	},
+	request = {
+		/// The readiness of the primary replicas of the deployment
+		#[preprocess(none)]
+		pub replicas: DeploymentReplicaReadiness,
+		/// The number of replicas of the canary of the deployment that are
+		/// ready, if the deployment has a canary
+		#[preprocess(none)]
+		#[serde(default, skip_serializing_if = "Option::is_none")]
+		pub canary_ready_replicas: Option<u16>,
+		/// The IDs of the replicas (including the canary's) that the runner is
+		/// running for the deployment. These are the names of their pods (or
+		/// containers), which their logs are labelled with
+		#[preprocess(none)]
+		#[serde(default, skip_serializing_if = "Vec::is_empty")]
+		pub replica_ids: Vec<String>,
+	}
+);
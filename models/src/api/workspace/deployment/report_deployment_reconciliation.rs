@@ -0,0 +1,35 @@
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route for the runner to report the result of reconciling a deployment
+	ReportDeploymentReconciliation,
+	PUT "/workspace/:workspace_id/deployment/:deployment_id/reconciliation" {
+		/// The workspace ID of the user
+		pub workspace_id: Uuid,
+		/// The deployment ID that was reconciled
+		pub deployment_id: Uuid,
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.deployment_id,
+			permission: Permission::Deployment(DeploymentPermission::Edit),
+		}
+	},
+	request = {
+		/// The reason the deployment could not be reconciled. If this is
+		/// `None`, the deployment was reconciled successfully
+		#[preprocess(none)]
+		pub error: Option<String>,
+		/// The generation of the deployment that was reconciled, as given in
+		/// its reconciliation status when the runner fetched it. The report is
+		/// ignored if the deployment has changed since then
+		#[preprocess(none)]
+		pub generation: u64,
+	}
+);
@@ -0,0 +1,26 @@
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route to restore a deleted deployment, as long as it has not been permanently
+	/// removed yet. The deployment is restored with the same configuration it had when
+	/// it was deleted, and is applied again by its runner.
+	RestoreDeployment,
+	POST "/workspace/:workspace_id/deployment/:deployment_id/restore" {
+		/// The workspace ID of the user
+		pub workspace_id: Uuid,
+		/// The deployment to be restored
+		pub deployment_id: Uuid,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.deployment_id,
+			permission: Permission::Deployment(DeploymentPermission::Delete),
+		}
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+);
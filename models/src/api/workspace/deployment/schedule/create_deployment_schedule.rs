@@ -0,0 +1,48 @@
+use super::ScheduleConflictResolution;
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route to create a new schedule that automatically starts and / or stops a
+	/// deployment
+	CreateDeploymentSchedule,
+	POST "/workspace/:workspace_id/deployment/:deployment_id/schedule" {
+		/// The workspace ID of the user
+		pub workspace_id: Uuid,
+		/// The ID of the deployment to create the schedule for
+		pub deployment_id: Uuid,
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.deployment_id,
+			permission: Permission::Deployment(DeploymentPermission::Edit),
+		}
+	},
+	request = {
+		/// The cron expression at which the deployment should be started
+		#[preprocess(optional(trim))]
+		#[serde(default)]
+		pub start_cron: Option<String>,
+		/// The cron expression at which the deployment should be stopped
+		#[preprocess(optional(trim))]
+		#[serde(default)]
+		pub stop_cron: Option<String>,
+		/// The IANA timezone that the cron expressions are evaluated in
+		#[preprocess(trim)]
+		pub timezone: String,
+		/// What to do when both cron expressions match the same minute
+		#[preprocess(none)]
+		#[serde(default)]
+		pub conflict_resolution: ScheduleConflictResolution,
+	},
+	response = {
+		/// The ID of the created schedule
+		#[serde(flatten)]
+		pub id: WithId<()>,
+	}
+);
@@ -0,0 +1,27 @@
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route to delete a schedule of a deployment. The deployment is left in
+	/// whatever state it currently is in
+	DeleteDeploymentSchedule,
+	DELETE "/workspace/:workspace_id/deployment/:deployment_id/schedule/:schedule_id" {
+		/// The workspace ID of the user
+		pub workspace_id: Uuid,
+		/// The ID of the deployment that the schedule belongs to
+		pub deployment_id: Uuid,
+		/// The ID of the schedule to delete
+		pub schedule_id: Uuid,
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.deployment_id,
+			permission: Permission::Deployment(DeploymentPermission::Edit),
+		}
+	}
+);
@@ -0,0 +1,29 @@
+use super::DeploymentSchedule;
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route to list all the schedules of a deployment
+	ListDeploymentSchedules,
+	GET "/workspace/:workspace_id/deployment/:deployment_id/schedule" {
+		/// The workspace ID of the user
+		pub workspace_id: Uuid,
+		/// The ID of the deployment to list the schedules of
+		pub deployment_id: Uuid,
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.deployment_id,
+			permission: Permission::Deployment(DeploymentPermission::View),
+		}
+	},
+	response = {
+		/// The list of schedules of the deployment
+		pub schedules: Vec<WithId<DeploymentSchedule>>,
+	}
+);
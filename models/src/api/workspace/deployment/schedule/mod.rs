@@ -0,0 +1,61 @@
+use serde::{Deserialize, Serialize};
+
+/// The endpoint to create a schedule for a deployment
+mod create_deployment_schedule;
+/// The endpoint to delete a schedule of a deployment
+mod delete_deployment_schedule;
+/// The endpoint to list all the schedules of a deployment
+mod list_deployment_schedules;
+/// The endpoint to update a schedule of a deployment
+mod update_deployment_schedule;
+
+pub use self::{
+	create_deployment_schedule::*,
+	delete_deployment_schedule::*,
+	list_deployment_schedules::*,
+	update_deployment_schedule::*,
+};
+
+/// A schedule that automatically starts and / or stops a deployment at the
+/// times given by cron expressions. For example, a deployment can be stopped
+/// every night and started again every morning to save costs.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct DeploymentSchedule {
+	/// The cron expression (`minute hour day-of-month month day-of-week`) at
+	/// which the deployment should be started
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub start_cron: Option<String>,
+	/// The cron expression (`minute hour day-of-month month day-of-week`) at
+	/// which the deployment should be stopped
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub stop_cron: Option<String>,
+	/// The IANA timezone that the cron expressions are evaluated in. For
+	/// example, `Asia/Kolkata` or `UTC`
+	pub timezone: String,
+	/// What to do when both the start and the stop cron expressions match the
+	/// same minute
+	#[serde(default)]
+	pub conflict_resolution: ScheduleConflictResolution,
+}
+
+/// The action that is taken when both the start and the stop cron expressions
+/// of a schedule match the same minute
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(sqlx::Type, schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(
+	not(target_arch = "wasm32"),
+	sqlx(
+		type_name = "DEPLOYMENT_SCHEDULE_CONFLICT_RESOLUTION",
+		rename_all = "snake_case"
+	)
+)]
+pub enum ScheduleConflictResolution {
+	/// The deployment is stopped
+	#[default]
+	StopWins,
+	/// The deployment is started
+	StartWins,
+}
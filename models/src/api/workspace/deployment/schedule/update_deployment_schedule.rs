@@ -0,0 +1,43 @@
+use super::ScheduleConflictResolution;
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route to update a schedule of a deployment
+	UpdateDeploymentSchedule,
+	PATCH "/workspace/:workspace_id/deployment/:deployment_id/schedule/:schedule_id" {
+		/// The workspace ID of the user
+		pub workspace_id: Uuid,
+		/// The ID of the deployment that the schedule belongs to
+		pub deployment_id: Uuid,
+		/// The ID of the schedule to update
+		pub schedule_id: Uuid,
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.deployment_id,
+			permission: Permission::Deployment(DeploymentPermission::Edit),
+		}
+	},
+	request = {
+		/// The new cron expression at which the deployment should be started.
+		/// An empty string removes the start action from the schedule
+		#[preprocess(optional(trim))]
+		pub start_cron: Option<String>,
+		/// The new cron expression at which the deployment should be stopped.
+		/// An empty string removes the stop action from the schedule
+		#[preprocess(optional(trim))]
+		pub stop_cron: Option<String>,
+		/// The new timezone that the cron expressions are evaluated in
+		#[preprocess(optional(trim))]
+		pub timezone: Option<String>,
+		/// What to do when both cron expressions match the same minute
+		#[preprocess(none)]
+		pub conflict_resolution: Option<ScheduleConflictResolution>,
+	}
+);
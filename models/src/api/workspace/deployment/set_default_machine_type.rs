@@ -0,0 +1,30 @@
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route to set the default machine type of a workspace, which is used for
+	/// new deployments that don't have a machine type set. The default can be
+	/// removed by setting it to `null`
+	SetDefaultDeploymentMachineType,
+	PUT "/workspace/:workspace_id/deployment/default-machine-type" {
+		/// The workspace ID to set the default machine type of
+		pub workspace_id: Uuid,
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.workspace_id,
+			permission: Permission::EditWorkspace,
+		}
+	},
+	request = {
+		/// The ID of the machine type to use by default. This must be one of
+		/// the machine types that are available for deployments
+		#[preprocess(none)]
+		pub machine_type_id: Option<Uuid>,
+	},
+);
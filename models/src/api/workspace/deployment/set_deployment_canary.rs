@@ -0,0 +1,35 @@
+use crate::{prelude::*, utils::constants::IMAGE_TAG_REGEX};
+
+macros::declare_api_endpoint!(
+	/// Route to start a canary of a deployment, which runs another tag of the
+	/// deployment's image alongside it, or to change the tag or the weight of
+	/// an existing canary. The weight is the percentage of the requests made
+	/// through a managed URL that are sent to the canary
+	SetDeploymentCanary,
+	PUT "/workspace/:workspace_id/deployment/:deployment_id/canary" {
+		/// The workspace ID of the user
+		pub workspace_id: Uuid,
+		/// The deployment ID to set the canary of
+		pub deployment_id: Uuid,
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.deployment_id,
+			permission: Permission::Deployment(DeploymentPermission::Edit),
+		}
+	},
+	request = {
+		/// The image tag for the canary to run. Tags are case-sensitive
+		#[preprocess(trim, regex = IMAGE_TAG_REGEX)]
+		pub image_tag: String,
+		/// The percentage (from 0 to 100) of the requests to send to the canary
+		#[preprocess(range(max = 100))]
+		pub weight: u8,
+	}
+);
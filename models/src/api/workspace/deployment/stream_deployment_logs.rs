@@ -26,7 +26,16 @@ macros::declare_stream_endpoint!(
 	},
 	query = {
 		/// The time from which the deployment logs should be fetched
+		#[cfg_attr(not(target_arch = "wasm32"), schemars(with = "Option<String>"))]
 		pub start_time: Option<OffsetDateTime>,
+		/// Whether logs that are JSON objects should be parsed, extracting
+		/// their level, timestamp and message. Logs that aren't JSON objects
+		/// are sent as plain text
+		#[serde(default)]
+		pub parse_json: bool,
+		/// The ID of the replica to stream the logs of. If not set, the logs
+		/// of all the replicas of the deployment are streamed
+		pub replica_id: Option<String>,
 	},
 	server_msg = {
 		/// There is new log data for the deployment
@@ -0,0 +1,54 @@
+use std::collections::BTreeMap;
+
+use crate::{
+	api::workspace::deployment::{DeploymentProbe, EnvironmentVariableValue},
+	prelude::*,
+	utils::constants::RESOURCE_NAME_REGEX,
+};
+
+macros::declare_api_endpoint!(
+	/// Route to create a new deployment template in a workspace
+	CreateDeploymentTemplate,
+	POST "/workspace/:workspace_id/deployment-template" {
+		/// The workspace ID of the user
+		pub workspace_id: Uuid,
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.workspace_id,
+			permission: Permission::Deployment(DeploymentPermission::Create),
+		}
+	},
+	request = {
+		/// The name of the template
+		#[preprocess(trim, regex = RESOURCE_NAME_REGEX)]
+		pub name: String,
+		/// The machine type that deployments created from this template will use
+		#[preprocess(none)]
+		#[serde(default)]
+		pub machine_type: Option<Uuid>,
+		/// The environment variables of the template
+		#[preprocess(none)]
+		#[serde(default)]
+		pub environment_variables: BTreeMap<String, EnvironmentVariableValue>,
+		/// The startup probe of the template
+		#[preprocess(none)]
+		#[serde(default)]
+		pub startup_probe: Option<DeploymentProbe>,
+		/// The liveness probe of the template
+		#[preprocess(none)]
+		#[serde(default)]
+		pub liveness_probe: Option<DeploymentProbe>,
+	},
+	response = {
+		/// The ID of the created template
+		#[serde(flatten)]
+		pub id: WithId<()>,
+	}
+);
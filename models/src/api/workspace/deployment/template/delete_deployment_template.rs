@@ -0,0 +1,25 @@
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route to delete a deployment template. Deployments that were created
+	/// from this template are not affected
+	DeleteDeploymentTemplate,
+	DELETE "/workspace/:workspace_id/deployment-template/:template_id" {
+		/// The workspace ID of the user
+		pub workspace_id: Uuid,
+		/// The ID of the template to delete
+		pub template_id: Uuid,
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.workspace_id,
+			permission: Permission::Deployment(DeploymentPermission::Create),
+		}
+	}
+);
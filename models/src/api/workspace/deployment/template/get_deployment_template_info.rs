@@ -0,0 +1,29 @@
+use super::DeploymentTemplate;
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route to get the details of a deployment template
+	GetDeploymentTemplateInfo,
+	GET "/workspace/:workspace_id/deployment-template/:template_id" {
+		/// The workspace ID of the user
+		pub workspace_id: Uuid,
+		/// The ID of the template to get the details of
+		pub template_id: Uuid,
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::WorkspaceMembershipAuthenticator {
+			extract_workspace_id: |req| req.path.workspace_id,
+		}
+	},
+	response = {
+		/// The details of the template
+		#[serde(flatten)]
+		pub template: WithId<DeploymentTemplate>,
+	}
+);
@@ -0,0 +1,31 @@
+use super::DeploymentTemplate;
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route to list all the deployment templates in a workspace
+	ListDeploymentTemplates,
+	GET "/workspace/:workspace_id/deployment-template" {
+		/// The workspace ID of the user
+		pub workspace_id: Uuid,
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	pagination = true,
+	authentication = {
+		AppAuthentication::<Self>::WorkspaceMembershipAuthenticator {
+			extract_workspace_id: |req| req.path.workspace_id,
+		}
+	},
+	response_headers = {
+		/// The total number of items in the pagination
+		pub total_count: TotalCountHeader,
+	},
+	response = {
+		/// The list of deployment templates in the workspace
+		pub templates: Vec<WithId<DeploymentTemplate>>,
+	}
+);
@@ -0,0 +1,54 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+/// The endpoint to create a deployment template
+mod create_deployment_template;
+/// The endpoint to delete a deployment template
+mod delete_deployment_template;
+/// The endpoint to get the details of a deployment template
+mod get_deployment_template_info;
+/// The endpoint to list all the deployment templates in a workspace
+mod list_deployment_templates;
+/// The endpoint to update a deployment template
+mod update_deployment_template;
+
+pub use self::{
+	create_deployment_template::*,
+	delete_deployment_template::*,
+	get_deployment_template_info::*,
+	list_deployment_templates::*,
+	update_deployment_template::*,
+};
+use super::{DeploymentProbe, EnvironmentVariableValue};
+use crate::prelude::*;
+
+/// A deployment template is a workspace-level set of deployment configuration
+/// that can be referenced when creating a deployment. Any value that is not
+/// explicitly set in the create deployment request is filled in from the
+/// template. The values are copied into the deployment at creation time, so
+/// changing or deleting a template does not affect existing deployments.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct DeploymentTemplate {
+	/// The name of the template
+	pub name: String,
+	/// The machine type that deployments created from this template will use
+	/// if they don't specify one
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub machine_type: Option<Uuid>,
+	/// The environment variables that deployments created from this template
+	/// will have. Environment variables set in the create deployment request
+	/// take precedence over the ones with the same name in the template
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	pub environment_variables: BTreeMap<String, EnvironmentVariableValue>,
+	/// The startup probe that deployments created from this template will use
+	/// if they don't specify one
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub startup_probe: Option<DeploymentProbe>,
+	/// The liveness probe that deployments created from this template will use
+	/// if they don't specify one
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub liveness_probe: Option<DeploymentProbe>,
+}
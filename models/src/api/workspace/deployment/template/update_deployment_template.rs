@@ -0,0 +1,95 @@
+use std::collections::BTreeMap;
+
+use crate::{
+	api::workspace::deployment::{DeploymentProbe, EnvironmentVariableValue},
+	prelude::*,
+	utils::constants::RESOURCE_NAME_REGEX,
+};
+
+macros::declare_api_endpoint!(
+	/// Route to update a deployment template. Deployments that were already
+	/// created from this template are not affected
+	UpdateDeploymentTemplate,
+	PATCH "/workspace/:workspace_id/deployment-template/:template_id" {
+		/// The workspace ID of the user
+		pub workspace_id: Uuid,
+		/// The ID of the template to update
+		pub template_id: Uuid,
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.workspace_id,
+			permission: Permission::Deployment(DeploymentPermission::Create),
+		}
+	},
+	request = {
+		/// The new name of the template
+		#[preprocess(optional(trim, regex = RESOURCE_NAME_REGEX))]
+		pub name: Option<String>,
+		/// The new machine type of the template. Setting this to `null` removes
+		/// the machine type from the template
+		#[preprocess(none)]
+		#[serde(
+			default,
+			deserialize_with = "crate::utils::deserialize_nullable",
+			skip_serializing_if = "Option::is_none"
+		)]
+		pub machine_type: Option<Option<Uuid>>,
+		/// The new set of environment variables of the template. This replaces
+		/// all the existing environment variables of the template
+		#[preprocess(none)]
+		pub environment_variables: Option<BTreeMap<String, EnvironmentVariableValue>>,
+		/// The new startup probe of the template. Setting this to `null`
+		/// removes the startup probe from the template
+		#[preprocess(none)]
+		#[serde(
+			default,
+			deserialize_with = "crate::utils::deserialize_nullable",
+			skip_serializing_if = "Option::is_none"
+		)]
+		pub startup_probe: Option<Option<DeploymentProbe>>,
+		/// The new liveness probe of the template. Setting this to `null`
+		/// removes the liveness probe from the template
+		#[preprocess(none)]
+		#[serde(
+			default,
+			deserialize_with = "crate::utils::deserialize_nullable",
+			skip_serializing_if = "Option::is_none"
+		)]
+		pub liveness_probe: Option<Option<DeploymentProbe>>,
+	}
+);
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn null_fields_are_removed_from_the_template() {
+		let request = serde_json::from_str::<UpdateDeploymentTemplateRequest>(
+			r#"{"machineType":null,"startupProbe":null,"livenessProbe":{"port":80,"path":"/"}}"#,
+		)
+		.unwrap();
+		assert_eq!(request.name, None);
+		assert_eq!(request.machine_type, Some(None));
+		assert_eq!(request.startup_probe, Some(None));
+		assert_eq!(
+			request.liveness_probe,
+			Some(Some(DeploymentProbe {
+				port: 80,
+				path: "/".to_string(),
+			}))
+		);
+
+		let request = serde_json::from_str::<UpdateDeploymentTemplateRequest>("{}").unwrap();
+		assert_eq!(request.machine_type, None);
+		assert_eq!(request.startup_probe, None);
+		assert_eq!(request.liveness_probe, None);
+	}
+}
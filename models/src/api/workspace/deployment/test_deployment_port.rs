@@ -0,0 +1,43 @@
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route to test whether an exposed port of a deployment is reachable from
+	/// within the platform network. A TCP connection is made to the port, and
+	/// for HTTP ports, a GET request is made as well. UDP ports are sent an
+	/// empty datagram, and are only reported unreachable if it is refused.
+	TestDeploymentPort,
+	POST "/workspace/:workspace_id/deployment/:deployment_id/port/:port/test" {
+		/// The workspace ID of the user
+		pub workspace_id: Uuid,
+		/// The deployment ID of the deployment to test
+		pub deployment_id: Uuid,
+		/// The exposed port of the deployment to test
+		pub port: u16,
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.deployment_id,
+			permission: Permission::Deployment(DeploymentPermission::View),
+		}
+	},
+	response = {
+		/// Whether the port is reachable or not
+		pub reachable: bool,
+		/// The time taken (in milliseconds) to reach the port. For HTTP ports,
+		/// this includes the time taken to get a response to the GET request
+		#[serde(default, skip_serializing_if = "Option::is_none")]
+		pub latency_millis: Option<u64>,
+		/// The status code of the HTTP response, in case of HTTP ports
+		#[serde(default, skip_serializing_if = "Option::is_none")]
+		pub http_status: Option<u16>,
+		/// The reason the port could not be reached, if any
+		#[serde(default, skip_serializing_if = "Option::is_none")]
+		pub error: Option<String>,
+	}
+);
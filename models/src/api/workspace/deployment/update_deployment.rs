@@ -1,10 +1,16 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
-use super::{DeploymentProbe, EnvironmentVariableValue, ExposedPortType};
+use headers::IfMatch;
+use time::OffsetDateTime;
+
+use super::{DeploymentProbe, DeploymentResources, EnvironmentVariableValue, ExposedPortType};
 use crate::{prelude::*, utils::constants::RESOURCE_NAME_REGEX};
 
 macros::declare_api_endpoint!(
-	/// Route to update a deployment
+	/// Route to update a deployment. Only the fields that are provided are
+	/// updated, and the rest are left unchanged. If the workspace requires
+	/// changes to deployments to be approved, the change is stored until
+	/// another user approves it instead
 	UpdateDeployment,
 	PATCH "/workspace/:workspace_id/deployment/:deployment_id" {
 		/// The workspace ID of the user
@@ -17,6 +23,10 @@ macros::declare_api_endpoint!(
 		pub authorization: BearerToken,
 		/// The user-agent used to access this API
 		pub user_agent: UserAgent,
+		/// The ETag of the version of the deployment that the update is based
+		/// on (see [`Deployment::etag`][super::Deployment::etag]). If provided,
+		/// the deployment is only updated if it hasn't been modified since
+		pub if_match: Option<IfMatch>,
 	},
 	authentication = {
 		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
@@ -50,18 +60,114 @@ macros::declare_api_endpoint!(
 		#[preprocess(none)]
 		pub environment_variables:
 			Option<BTreeMap<String, EnvironmentVariableValue>>,
-		/// To update the startup probe
+		/// To update which environment variables are specific to this
+		/// deployment's environment. These are preserved when another
+		/// deployment is promoted to this one. If not provided while updating
+		/// the environment variables, existing variables keep their flag
+		#[preprocess(none)]
+		#[serde(default, skip_serializing_if = "Option::is_none")]
+		pub environment_specific_variables: Option<BTreeSet<String>>,
+		/// To update which environment variables have secret values, which are
+		/// masked in all responses. If not provided while updating the
+		/// environment variables, existing variables keep their flag. The
+		/// masked value of a secret variable can be sent back as-is to keep
+		/// its value unchanged
+		#[preprocess(none)]
+		#[serde(default, skip_serializing_if = "Option::is_none")]
+		pub secret_variables: Option<BTreeSet<String>>,
+		/// To update the startup probe. Setting this to `null` removes the
+		/// startup probe
 		#[preprocess(none)]
-		pub startup_probe: Option<DeploymentProbe>,
-		/// To update the liveness probe
+		#[serde(
+			default,
+			deserialize_with = "crate::utils::deserialize_nullable",
+			skip_serializing_if = "Option::is_none"
+		)]
+		pub startup_probe: Option<Option<DeploymentProbe>>,
+		/// To update the liveness probe. Setting this to `null` removes the
+		/// liveness probe
 		#[preprocess(none)]
-		pub liveness_probe: Option<DeploymentProbe>,
+		#[serde(
+			default,
+			deserialize_with = "crate::utils::deserialize_nullable",
+			skip_serializing_if = "Option::is_none"
+		)]
+		pub liveness_probe: Option<Option<DeploymentProbe>>,
 		/// To update the config mount
 		#[preprocess(none)]
 		pub config_mounts: Option<BTreeMap<String, Base64String>>,
 		/// To update the volumes attached to the deployment
 		#[preprocess(none)]
 		pub volumes: Option<BTreeMap<Uuid, String>>,
+		/// To update the CPU and memory requests and limits. Only the values
+		/// that are set are updated, and a limit that isn't set defaults to
+		/// its request if the request is set
+		#[preprocess(none)]
+		#[serde(default, skip_serializing_if = "Option::is_none")]
+		pub resources: Option<DeploymentResources>,
+		/// To update the number of seconds without any incoming requests after
+		/// which the deployment is scaled to zero. Setting this to `null`
+		/// stops the deployment from being scaled to zero
+		#[preprocess(none)]
+		#[serde(
+			default,
+			deserialize_with = "crate::utils::deserialize_nullable",
+			skip_serializing_if = "Option::is_none"
+		)]
+		pub scale_to_zero_after: Option<Option<u32>>,
+		/// To update the minimum level of the logs that are captured for the
+		/// deployment. Setting this to `null` captures all the logs again.
+		/// Logs that were dropped before the level was lowered are not
+		/// recovered
+		#[preprocess(none)]
+		#[serde(
+			default,
+			deserialize_with = "crate::utils::deserialize_nullable",
+			skip_serializing_if = "Option::is_none"
+		)]
+		pub log_level: Option<Option<DeploymentLogLevel>>,
+		/// To update the maximum number of requests that each replica of the
+		/// deployment handles at once. Setting this to `null` (or zero) stops
+		/// the number of requests from being limited
+		#[preprocess(none)]
+		#[serde(
+			default,
+			deserialize_with = "crate::utils::deserialize_nullable",
+			skip_serializing_if = "Option::is_none"
+		)]
+		pub max_concurrent_requests: Option<Option<u32>>,
+		/// To update whether the ingress keeps an access log of the requests
+		/// made to the deployment. Access logs can contain personal data in
+		/// the paths of requests
+		#[preprocess(none)]
+		#[serde(default, skip_serializing_if = "Option::is_none")]
+		pub access_logging: Option<bool>,
+		/// To update the labels of the deployment. All the existing labels are
+		/// replaced, so an empty map removes all of them
+		#[preprocess(none)]
+		#[serde(default, skip_serializing_if = "Option::is_none")]
+		pub labels: Option<BTreeMap<String, String>>,
+		/// To update the deployments that the deployment depends on. All the
+		/// existing dependencies are replaced, so an empty list removes all of
+		/// them
+		#[preprocess(none)]
+		#[serde(default, skip_serializing_if = "Option::is_none")]
+		pub depends_on: Option<BTreeSet<Uuid>>,
+	},
+	response = {
+		/// The time the deployment was updated at, which is the
+		/// [`updated_at`][super::Deployment::updated_at] of the new version of
+		/// the deployment. This can be used to get the ETag of the new version
+		/// without fetching the deployment again. If the change is waiting for
+		/// approval, this is the time the current version was updated at
+		#[cfg_attr(not(target_arch = "wasm32"), schemars(with = "String"))]
+		pub updated_at: OffsetDateTime,
+		/// The ID of the change request that was created instead of updating
+		/// the deployment, if the workspace requires changes to deployments to
+		/// be approved (see
+		/// [`ReviewDeploymentChangeRequest`][super::change_request::ReviewDeploymentChangeRequest])
+		#[serde(default, skip_serializing_if = "Option::is_none")]
+		pub change_request_id: Option<Uuid>,
 	}
 );
 
@@ -76,11 +182,20 @@ impl UpdateDeploymentRequest {
 			min_horizontal_scale: None,
 			max_horizontal_scale: None,
 			environment_variables: None,
+			environment_specific_variables: None,
+			secret_variables: None,
 			liveness_probe: None,
 			startup_probe: None,
 			config_mounts: None,
 			runner: None,
 			volumes: None,
+			resources: None,
+			scale_to_zero_after: None,
+			log_level: None,
+			max_concurrent_requests: None,
+			access_logging: None,
+			labels: None,
+			depends_on: None,
 		}
 	}
 
@@ -96,10 +211,19 @@ impl UpdateDeploymentRequest {
 			.or(self.max_horizontal_scale.as_ref().map(|_| 0))
 			.or(self.ports.as_ref().map(|_| 0))
 			.or(self.environment_variables.as_ref().map(|_| 0))
+			.or(self.environment_specific_variables.as_ref().map(|_| 0))
+			.or(self.secret_variables.as_ref().map(|_| 0))
 			.or(self.startup_probe.as_ref().map(|_| 0))
 			.or(self.liveness_probe.as_ref().map(|_| 0))
 			.or(self.config_mounts.as_ref().map(|_| 0))
 			.or(self.volumes.as_ref().map(|_| 0))
+			.or(self.resources.as_ref().map(|_| 0))
+			.or(self.scale_to_zero_after.as_ref().map(|_| 0))
+			.or(self.log_level.as_ref().map(|_| 0))
+			.or(self.max_concurrent_requests.as_ref().map(|_| 0))
+			.or(self.access_logging.as_ref().map(|_| 0))
+			.or(self.labels.as_ref().map(|_| 0))
+			.or(self.depends_on.as_ref().map(|_| 0))
 			.is_none()
 	}
 }
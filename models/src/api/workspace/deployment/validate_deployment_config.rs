@@ -0,0 +1,88 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route to validate the config of a deployment without creating it. This
+	/// does not create or modify any resource, and only requires the user to be
+	/// a member of the workspace, so that it can be used in CI pipelines before
+	/// a deployment is created or imported.
+	ValidateDeploymentConfig,
+	POST "/workspace/:workspace_id/deployment/validate" {
+		/// The workspace ID of the user
+		pub workspace_id: Uuid,
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::WorkspaceMembershipAuthenticator {
+			extract_workspace_id: |req| req.path.workspace_id,
+		}
+	},
+	request = {
+		/// The config of the deployment to validate. This is the same document
+		/// that is used to create a deployment. It is accepted as-is, so that
+		/// errors in the shape of the document are reported in the response
+		/// instead of rejecting the request
+		#[preprocess(none)]
+		#[serde(flatten)]
+		pub config: BTreeMap<String, serde_json::Value>,
+	},
+	response = {
+		/// Whether the config is valid, and can be used to create a deployment
+		pub valid: bool,
+		/// All the errors found in the config. This is empty if the config is
+		/// valid
+		pub errors: Vec<DeploymentConfigError>,
+		/// The resources that the deployment would use, along with the
+		/// resources already used by the deployments in the workspace. This is
+		/// only present if the machine type of the config could be resolved
+		#[serde(default, skip_serializing_if = "Option::is_none")]
+		pub resource_impact: Option<DeploymentResourceImpact>,
+	}
+);
+
+/// An error found when validating the config of a deployment
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct DeploymentConfigError {
+	/// The field of the config that the error is for. This is `None` if the
+	/// error is for the config as a whole
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub field: Option<String>,
+	/// The error that would be returned if the config was used to create a
+	/// deployment
+	pub error: ErrorType,
+	/// A human readable description of the error
+	pub message: String,
+}
+
+/// The resources that a deployment would use in a workspace
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct DeploymentResourceImpact {
+	/// The number of CPUs that each replica of the deployment would use
+	pub cpu_count: u32,
+	/// The memory that each replica of the deployment would use, in multiples
+	/// of 0.25 GB
+	pub memory_count: u64,
+	/// The number of CPUs that the deployment would use at its maximum scale
+	pub max_cpu_count: u32,
+	/// The memory that the deployment would use at its maximum scale, in
+	/// multiples of 0.25 GB
+	pub max_memory_count: u64,
+	/// The number of CPUs already used by the deployments in the workspace at
+	/// their maximum scale
+	pub workspace_cpu_count: u32,
+	/// The memory already used by the deployments in the workspace at their
+	/// maximum scale, in multiples of 0.25 GB
+	pub workspace_memory_count: u64,
+}
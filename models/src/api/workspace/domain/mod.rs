@@ -7,7 +7,7 @@ use std::{
 use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
 
-use crate::prelude::*;
+use crate::{prelude::*, utils::constants};
 
 /// The endpoint to add a DNS record to a domain
 mod add_dns_record;
@@ -45,16 +45,19 @@ pub use self::{
 
 /// The domain metadata information
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct Domain {
 	/// The name of the domain
 	pub name: String,
 	/// Last verified time of the domain
+	#[cfg_attr(not(target_arch = "wasm32"), schemars(with = "Option<String>"))]
 	pub last_unverified: Option<OffsetDateTime>,
 }
 
 /// The domain information in a workspace
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct WorkspaceDomain {
 	/// The domain metadata
@@ -92,6 +95,7 @@ pub struct PatrControlledDomain {
 
 /// The DNS record type of a domain
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
 #[allow(clippy::upper_case_acronyms)]
 #[serde(tag = "type")]
 pub enum DnsRecordValue {
@@ -195,6 +199,73 @@ impl DnsRecordValue {
 			_ => None,
 		}
 	}
+
+	/// Checks that the value of the record is valid for its type. A and AAAA
+	/// records must point to an address that can be routed to, MX and CNAME
+	/// records must point to a valid hostname, and TXT records cannot be empty
+	/// or longer than [`constants::MAX_TXT_RECORD_LENGTH`]. If the record is
+	/// invalid, the error has the name of the invalid field.
+	pub fn validate(&self) -> Result<(), ErrorType> {
+		let is_valid = match self {
+			Self::A { target, .. } => {
+				!target.is_unspecified() && !target.is_broadcast() && !target.is_multicast()
+			}
+			Self::AAAA { target, .. } => !target.is_unspecified() && !target.is_multicast(),
+			Self::MX { target, .. } | Self::CNAME { target, .. } => is_valid_hostname(target),
+			Self::TXT { target } => {
+				!target.is_empty() && target.len() <= constants::MAX_TXT_RECORD_LENGTH
+			}
+		};
+
+		if is_valid {
+			Ok(())
+		} else {
+			Err(ErrorType::InvalidDnsRecord("target"))
+		}
+	}
+
+	/// Checks that the record can be added to a name that already has records
+	/// of the given types (as returned by the [`Display`] impl). A CNAME record
+	/// aliases the whole name, so it cannot coexist with any other record of
+	/// the same name, including another CNAME record.
+	pub fn validate_coexistence<'a>(
+		&self,
+		existing_types: impl IntoIterator<Item = &'a str>,
+	) -> Result<(), ErrorType> {
+		let mut existing_types = existing_types.into_iter().peekable();
+		let has_conflict = if self.is_cname_record() {
+			existing_types.peek().is_some()
+		} else {
+			existing_types.any(|r#type| r#type == "CNAME")
+		};
+
+		if has_conflict {
+			Err(ErrorType::InvalidDnsRecord("name"))
+		} else {
+			Ok(())
+		}
+	}
+}
+
+/// Checks if a string is a hostname that a DNS record can point to. The
+/// hostname can optionally be fully qualified with a trailing `.`, must have at
+/// least two labels, each of which can be at most 63 characters long, can only
+/// contain alphanumeric characters, `-` and `_`, and must not start or end with
+/// a `-`.
+fn is_valid_hostname(hostname: &str) -> bool {
+	let hostname = hostname.strip_suffix('.').unwrap_or(hostname);
+	let labels = hostname.split('.').collect::<Vec<_>>();
+
+	hostname.len() <= 253 &&
+		labels.len() >= 2 &&
+		labels.iter().all(|label| {
+			(1..=63).contains(&label.len()) &&
+				label
+					.chars()
+					.all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') &&
+				!label.starts_with('-') &&
+				!label.ends_with('-')
+		})
 }
 
 impl Display for DnsRecordValue {
@@ -211,6 +282,7 @@ impl Display for DnsRecordValue {
 
 /// Type of domain nameserver
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
 #[cfg_attr(not(target_arch = "wasm32"), derive(sqlx::Type))]
 #[serde(rename_all = "camelCase")]
 #[cfg_attr(
@@ -260,6 +332,7 @@ impl FromStr for DomainNameserverType {
 
 /// The DNS record information of patr domain
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct PatrDomainDnsRecord {
 	/// The domain ID
@@ -272,3 +345,138 @@ pub struct PatrDomainDnsRecord {
 	/// The time to live
 	pub ttl: u32,
 }
+
+#[cfg(test)]
+mod tests {
+	use std::net::{Ipv4Addr, Ipv6Addr};
+
+	use super::DnsRecordValue;
+	use crate::{utils::constants, ErrorType};
+
+	fn cname(target: &str) -> DnsRecordValue {
+		DnsRecordValue::CNAME {
+			target: target.to_string(),
+			proxied: false,
+		}
+	}
+
+	#[test]
+	fn a_records_must_be_routable() {
+		for (target, valid) in [
+			(Ipv4Addr::new(1, 1, 1, 1), true),
+			(Ipv4Addr::UNSPECIFIED, false),
+			(Ipv4Addr::BROADCAST, false),
+			(Ipv4Addr::new(224, 0, 0, 1), false),
+		] {
+			let record = DnsRecordValue::A {
+				target,
+				proxied: true,
+			};
+			assert_eq!(record.validate().is_ok(), valid, "{target}");
+		}
+	}
+
+	#[test]
+	fn aaaa_records_must_be_routable() {
+		for (target, valid) in [
+			("2606:4700:4700::1111".parse().unwrap(), true),
+			(Ipv6Addr::UNSPECIFIED, false),
+			("ff02::1".parse().unwrap(), false),
+		] {
+			let record = DnsRecordValue::AAAA {
+				target,
+				proxied: true,
+			};
+			assert_eq!(record.validate().is_ok(), valid, "{target}");
+		}
+	}
+
+	#[test]
+	fn a_records_only_accept_ipv4_addresses() {
+		let record = serde_json::from_value::<DnsRecordValue>(serde_json::json!({
+			"type": "A",
+			"target": "2606:4700:4700::1111",
+			"proxied": false,
+		}));
+		assert!(record.is_err());
+
+		let record = serde_json::from_value::<DnsRecordValue>(serde_json::json!({
+			"type": "AAAA",
+			"target": "1.1.1.1",
+			"proxied": false,
+		}));
+		assert!(record.is_err());
+	}
+
+	#[test]
+	fn mx_records_must_point_to_a_hostname() {
+		for (target, valid) in [
+			("mail.example.com", true),
+			("mail.example.com.", true),
+			("mail", false),
+			("-mail.example.com", false),
+			("mail..example.com", false),
+			("mail example.com", false),
+			("", false),
+		] {
+			let record = DnsRecordValue::MX {
+				priority: 10,
+				target: target.to_string(),
+			};
+			assert_eq!(record.validate().is_ok(), valid, "`{target}`");
+		}
+
+		let record = serde_json::from_value::<DnsRecordValue>(serde_json::json!({
+			"type": "MX",
+			"target": "mail.example.com",
+		}));
+		assert!(record.is_err(), "MX records require a priority");
+	}
+
+	#[test]
+	fn txt_records_have_a_length_limit() {
+		for (length, valid) in [
+			(0, false),
+			(1, true),
+			(constants::MAX_TXT_RECORD_LENGTH, true),
+			(constants::MAX_TXT_RECORD_LENGTH + 1, false),
+		] {
+			let record = DnsRecordValue::TXT {
+				target: "a".repeat(length),
+			};
+			assert_eq!(record.validate().is_ok(), valid, "length {length}");
+		}
+	}
+
+	#[test]
+	fn cname_records_must_point_to_a_hostname() {
+		assert_eq!(cname("_dmarc.example.com").validate(), Ok(()));
+		assert_eq!(
+			cname("not a hostname").validate(),
+			Err(ErrorType::InvalidDnsRecord("target"))
+		);
+	}
+
+	#[test]
+	fn cname_records_cannot_coexist_with_other_records() {
+		let a = DnsRecordValue::A {
+			target: Ipv4Addr::new(1, 1, 1, 1),
+			proxied: false,
+		};
+
+		assert_eq!(cname("example.com").validate_coexistence([]), Ok(()));
+		assert_eq!(a.validate_coexistence(["A", "TXT"]), Ok(()));
+		assert_eq!(
+			cname("example.com").validate_coexistence(["TXT"]),
+			Err(ErrorType::InvalidDnsRecord("name"))
+		);
+		assert_eq!(
+			cname("example.com").validate_coexistence(["CNAME"]),
+			Err(ErrorType::InvalidDnsRecord("name"))
+		);
+		assert_eq!(
+			a.validate_coexistence(["CNAME"]),
+			Err(ErrorType::InvalidDnsRecord("name"))
+		);
+	}
+}
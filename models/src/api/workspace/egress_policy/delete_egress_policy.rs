@@ -0,0 +1,23 @@
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route to remove the egress policy of a workspace, which allows the
+	/// deployments of the workspace to connect to any destination again
+	DeleteEgressPolicy,
+	DELETE "/workspace/:workspace_id/egress-policy" {
+		/// The ID of the workspace to remove the egress policy of
+		pub workspace_id: Uuid,
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.workspace_id,
+			permission: Permission::EditWorkspace,
+		}
+	},
+);
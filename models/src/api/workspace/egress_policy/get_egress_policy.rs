@@ -0,0 +1,30 @@
+use super::WorkspaceEgressPolicy;
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route to get the egress policy of a workspace, which decides which
+	/// destinations the deployments of the workspace can connect to. This is
+	/// also used by the runners to enforce the policy.
+	GetEgressPolicy,
+	GET "/workspace/:workspace_id/egress-policy" {
+		/// The ID of the workspace to get the egress policy of
+		pub workspace_id: Uuid,
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::WorkspaceMembershipAuthenticator {
+			extract_workspace_id: |req| req.path.workspace_id,
+		}
+	},
+	response = {
+		/// The egress policy of the workspace. This is empty if the workspace
+		/// doesn't have a policy, which allows all destinations
+		#[serde(flatten)]
+		pub policy: WorkspaceEgressPolicy,
+	}
+);
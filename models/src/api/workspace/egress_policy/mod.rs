@@ -0,0 +1,328 @@
+use std::{collections::BTreeSet, fmt::Display, str::FromStr};
+
+pub use ipnetwork::IpNetwork;
+use serde::{Deserialize, Serialize};
+
+/// The endpoint to remove the egress policy of a workspace
+mod delete_egress_policy;
+/// The endpoint to get the egress policy of a workspace
+mod get_egress_policy;
+/// The endpoint to set the egress policy of a workspace
+mod set_egress_policy;
+
+pub use self::{delete_egress_policy::*, get_egress_policy::*, set_egress_policy::*};
+use crate::{prelude::*, utils::constants};
+
+/// The policy that decides which destinations the deployments of a workspace
+/// can make outbound connections to. Each rule is either a CIDR range (or a
+/// single IP address) or a domain name. If there are any allowed rules, only
+/// those destinations can be reached. Denied rules block destinations that
+/// would otherwise be reachable. An empty policy allows all destinations.
+///
+/// DNS lookups to the resolver of the runner are always allowed, so that the
+/// deployments can resolve the domains they are allowed to connect to. Runners
+/// that can't enforce the policy refuse to run the deployments of a workspace
+/// with a non-empty policy, instead of letting them connect anywhere.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceEgressPolicy {
+	/// The destinations that the deployments are allowed to connect to
+	#[serde(default)]
+	pub allow: Vec<String>,
+	/// The destinations that the deployments are not allowed to connect to
+	#[serde(default)]
+	pub deny: Vec<String>,
+}
+
+impl WorkspaceEgressPolicy {
+	/// Checks if the policy doesn't restrict any destinations
+	pub fn is_empty(&self) -> bool {
+		self.allow.is_empty() && self.deny.is_empty()
+	}
+
+	/// Checks that all the rules of the policy are valid [`EgressRule`]s. A
+	/// policy can have at most [`constants::MAX_EGRESS_POLICY_RULES`] rules in
+	/// total, and the same rule cannot be listed more than once, either in the
+	/// same list or in both the lists.
+	pub fn validate(&self) -> Result<(), ErrorType> {
+		if self.allow.len() + self.deny.len() > constants::MAX_EGRESS_POLICY_RULES {
+			return Err(ErrorType::WrongParameters);
+		}
+
+		let mut rules = BTreeSet::new();
+		for rule in self.allow.iter().chain(&self.deny) {
+			let rule = rule.parse::<EgressRule>()?;
+			if !rules.insert(rule) {
+				return Err(ErrorType::WrongParameters);
+			}
+		}
+
+		Ok(())
+	}
+}
+
+/// A single rule of a [`WorkspaceEgressPolicy`], which matches the
+/// destinations of outbound connections
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum EgressRule {
+	/// A range of IP addresses. A single IP address is a range with the full
+	/// prefix length. The address must not have any bits set outside the
+	/// prefix, so `10.0.0.0/8` is valid but `10.0.0.1/8` is not
+	Cidr(IpNetwork),
+	/// A domain name, such as `api.example.com`. The rule matches the
+	/// addresses that the domain resolves to
+	Domain(String),
+}
+
+impl FromStr for EgressRule {
+	type Err = ErrorType;
+
+	fn from_str(rule: &str) -> Result<Self, Self::Err> {
+		let rule = rule.trim();
+
+		if rule.starts_with(|c: char| c.is_ascii_digit()) || rule.contains(':') {
+			let network = rule
+				.parse::<IpNetwork>()
+				.map_err(|_| ErrorType::WrongParameters)?;
+			if network.network() != network.ip() {
+				return Err(ErrorType::WrongParameters);
+			}
+			return Ok(Self::Cidr(network));
+		}
+
+		if is_valid_domain(rule) {
+			Ok(Self::Domain(rule.to_ascii_lowercase()))
+		} else {
+			Err(ErrorType::WrongParameters)
+		}
+	}
+}
+
+impl Display for EgressRule {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Cidr(network) => write!(f, "{}", network),
+			Self::Domain(domain) => write!(f, "{}", domain),
+		}
+	}
+}
+
+/// A range of IP addresses that the deployments are allowed to connect to,
+/// along with the ranges within it that they are not
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AllowedIpBlock {
+	/// The range of addresses that is allowed
+	pub cidr: IpNetwork,
+	/// The ranges within [`Self::cidr`] that are denied. None of them are
+	/// within another one
+	pub except: Vec<IpNetwork>,
+}
+
+/// Works out the ranges of IP addresses that can be connected to, given the
+/// allowed and the denied ranges of a policy. Without any allowed ranges,
+/// every address is allowed. An allowed range that is entirely within a denied
+/// range is left out, and the denied ranges within an allowed range are
+/// excepted from it. Denied ranges outside all the allowed ranges don't need to
+/// be excepted, since they are never allowed to begin with.
+pub fn allowed_ip_blocks(allow: &[IpNetwork], deny: &[IpNetwork]) -> Vec<AllowedIpBlock> {
+	let everything = ["0.0.0.0/0", "::/0"]
+		.into_iter()
+		.filter_map(|block| block.parse::<IpNetwork>().ok())
+		.collect::<Vec<_>>();
+	let allow = if allow.is_empty() { &everything } else { allow };
+
+	/// Checks if the `outer` range contains all of the `inner` range
+	fn contains(outer: &IpNetwork, inner: &IpNetwork) -> bool {
+		outer.is_ipv4() == inner.is_ipv4() &&
+			outer.prefix() <= inner.prefix() &&
+			outer.contains(inner.network())
+	}
+
+	allow
+		.iter()
+		.filter(|block| !deny.iter().any(|denied| contains(denied, block)))
+		.map(|block| {
+			let within = deny
+				.iter()
+				.filter(|denied| contains(block, denied))
+				.collect::<Vec<_>>();
+			let mut except = within
+				.iter()
+				.filter(|denied| {
+					!within
+						.iter()
+						.any(|other| other != *denied && contains(other, denied))
+				})
+				.map(|denied| **denied)
+				.collect::<Vec<_>>();
+			except.sort();
+			except.dedup();
+
+			AllowedIpBlock {
+				cidr: *block,
+				except,
+			}
+		})
+		.collect()
+}
+
+/// Checks if a string is a fully qualified domain name. The domain must have at
+/// least two labels, each of which can be at most 63 characters long, can only
+/// contain alphanumeric characters and `-`, and must not start or end with a
+/// `-`. The top level domain cannot be numeric.
+fn is_valid_domain(domain: &str) -> bool {
+	let labels = domain.split('.').collect::<Vec<_>>();
+
+	domain.len() <= 253 &&
+		labels.len() >= 2 &&
+		labels.iter().all(|label| {
+			(1..=63).contains(&label.len()) &&
+				label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') &&
+				!label.starts_with('-') &&
+				!label.ends_with('-')
+		}) &&
+		labels
+			.last()
+			.is_some_and(|tld| !tld.chars().all(|c| c.is_ascii_digit()))
+}
+
+#[cfg(test)]
+mod tests {
+	use ipnetwork::IpNetwork;
+
+	use super::{allowed_ip_blocks, AllowedIpBlock, EgressRule, WorkspaceEgressPolicy};
+	use crate::{
+		api::workspace::runner::StreamRunnerDataForWorkspaceServerMsg,
+		prelude::Uuid,
+		ErrorType,
+	};
+
+	#[test]
+	fn parses_cidrs_and_domains() {
+		assert_eq!(
+			"10.0.0.0/8".parse::<EgressRule>().unwrap().to_string(),
+			"10.0.0.0/8"
+		);
+		assert_eq!(
+			"1.1.1.1".parse::<EgressRule>().unwrap().to_string(),
+			"1.1.1.1/32"
+		);
+		assert_eq!(
+			"2001:db8::/32".parse::<EgressRule>().unwrap().to_string(),
+			"2001:db8::/32"
+		);
+		assert_eq!(
+			"API.Example.com".parse::<EgressRule>().unwrap(),
+			EgressRule::Domain("api.example.com".to_string())
+		);
+	}
+
+	#[test]
+	fn rejects_malformed_rules() {
+		for rule in [
+			"10.0.0.1/8",
+			"10.0.0.0/33",
+			"300.0.0.0/8",
+			"2001:db8::/129",
+			"localhost",
+			"-example.com",
+			"example-.com",
+			"exa_mple.com",
+			"example..com",
+			"*.example.com",
+			"1.2.3.4.5",
+			"",
+		] {
+			assert_eq!(
+				rule.parse::<EgressRule>(),
+				Err(ErrorType::WrongParameters),
+				"`{rule}` should be rejected"
+			);
+		}
+	}
+
+	#[test]
+	fn rejects_duplicate_rules() {
+		let policy = WorkspaceEgressPolicy {
+			allow: vec!["example.com".to_string(), "10.0.0.0/8".to_string()],
+			deny: vec!["10.1.0.0/16".to_string()],
+		};
+		assert_eq!(policy.validate(), Ok(()));
+
+		let policy = WorkspaceEgressPolicy {
+			allow: vec!["example.com".to_string()],
+			deny: vec!["Example.com".to_string()],
+		};
+		assert_eq!(policy.validate(), Err(ErrorType::WrongParameters));
+	}
+
+	fn networks(networks: &[&str]) -> Vec<IpNetwork> {
+		networks
+			.iter()
+			.map(|network| network.parse().unwrap())
+			.collect()
+	}
+
+	#[test]
+	fn denied_ranges_within_allowed_ranges_are_excepted() {
+		assert_eq!(
+			allowed_ip_blocks(
+				&networks(&["10.0.0.0/8"]),
+				&networks(&["10.1.0.0/16", "10.1.2.0/24", "192.168.0.0/16"])
+			),
+			vec![AllowedIpBlock {
+				cidr: "10.0.0.0/8".parse().unwrap(),
+				except: networks(&["10.1.0.0/16"]),
+			}]
+		);
+	}
+
+	#[test]
+	fn allowed_ranges_within_denied_ranges_are_left_out() {
+		assert_eq!(
+			allowed_ip_blocks(
+				&networks(&["10.1.0.0/16", "10.0.0.0/8", "172.16.0.0/12"]),
+				&networks(&["10.0.0.0/8"])
+			),
+			vec![AllowedIpBlock {
+				cidr: "172.16.0.0/12".parse().unwrap(),
+				except: vec![],
+			}]
+		);
+	}
+
+	#[test]
+	fn everything_is_allowed_without_allowed_ranges() {
+		assert_eq!(
+			allowed_ip_blocks(&[], &networks(&["10.0.0.0/8", "2001:db8::/32"])),
+			vec![
+				AllowedIpBlock {
+					cidr: "0.0.0.0/0".parse().unwrap(),
+					except: networks(&["10.0.0.0/8"]),
+				},
+				AllowedIpBlock {
+					cidr: "::/0".parse().unwrap(),
+					except: networks(&["2001:db8::/32"]),
+				},
+			]
+		);
+	}
+
+	#[test]
+	fn policy_update_round_trips_to_runners() {
+		let message = StreamRunnerDataForWorkspaceServerMsg::WorkspaceEgressPolicyUpdated {
+			workspace_id: Uuid::nil(),
+			policy: WorkspaceEgressPolicy {
+				allow: vec!["example.com".to_string()],
+				deny: vec!["10.0.0.0/8".to_string()],
+			},
+		};
+
+		let json = serde_json::to_string(&message).unwrap();
+		assert_eq!(
+			serde_json::from_str::<StreamRunnerDataForWorkspaceServerMsg>(&json).unwrap(),
+			message
+		);
+	}
+}
@@ -0,0 +1,37 @@
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route to set the egress policy of a workspace, replacing the existing
+	/// policy (if any). The rules are validated before the policy is saved, and
+	/// the runners of the workspace are notified so that they can enforce the
+	/// new policy.
+	SetEgressPolicy,
+	PUT "/workspace/:workspace_id/egress-policy" {
+		/// The ID of the workspace to set the egress policy of
+		pub workspace_id: Uuid,
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.workspace_id,
+			permission: Permission::EditWorkspace,
+		}
+	},
+	request = {
+		/// The destinations that the deployments are allowed to connect to.
+		/// Each rule is either a CIDR range (or an IP address) or a domain
+		#[preprocess(none)]
+		#[serde(default)]
+		pub allow: Vec<String>,
+		/// The destinations that the deployments are not allowed to connect
+		/// to. Each rule is either a CIDR range (or an IP address) or a domain
+		#[preprocess(none)]
+		#[serde(default)]
+		pub deny: Vec<String>,
+	},
+);
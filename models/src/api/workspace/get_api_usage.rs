@@ -0,0 +1,43 @@
+use time::OffsetDateTime;
+
+use super::ApiUsageBucket;
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route to get the usage of the API by a workspace, aggregated into time
+	/// buckets per endpoint. This includes the number of requests, the number
+	/// of errors and the latency percentiles of each endpoint.
+	GetApiUsage,
+	GET "/workspace/:workspace_id/api-usage" {
+		/// The ID of the workspace to get the API usage for
+		pub workspace_id: Uuid,
+	},
+	authentication = {
+		AppAuthentication::<Self>::WorkspaceMembershipAuthenticator {
+			extract_workspace_id: |req| req.path.workspace_id,
+		}
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	query = {
+		/// The time from which the usage should be fetched
+		#[cfg_attr(not(target_arch = "wasm32"), schemars(with = "String"))]
+		pub start_time: OffsetDateTime,
+		/// The time up until which the usage should be fetched. Defaults to the
+		/// current time
+		#[cfg_attr(not(target_arch = "wasm32"), schemars(with = "Option<String>"))]
+		pub end_time: Option<OffsetDateTime>,
+		/// Only fetch the usage of this endpoint, in the format `METHOD /path`
+		pub endpoint: Option<String>,
+	},
+	response = {
+		/// The size of each time bucket, in seconds
+		pub bucket_size_seconds: u64,
+		/// The usage of the API, ordered by the start of the bucket
+		pub usage: Vec<ApiUsageBucket>,
+	}
+);
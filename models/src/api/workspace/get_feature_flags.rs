@@ -0,0 +1,88 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route to get the feature flags that are enabled for a workspace. This is
+	/// used by the frontend to hide UI for features that aren't enabled yet on
+	/// this instance or for this workspace.
+	GetFeatureFlags,
+	GET "/workspace/:workspace_id/feature-flags" {
+		/// The ID of the workspace to get the feature flags for
+		pub workspace_id: Uuid,
+	},
+	authentication = {
+		AppAuthentication::<Self>::WorkspaceMembershipAuthenticator {
+			extract_workspace_id: |req| req.path.workspace_id,
+		}
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	response = {
+		/// The feature flags of the workspace
+		pub flags: FeatureFlags,
+	}
+);
+
+/// The feature flags of a workspace, by the name of the flag. Any flag that is
+/// not present is considered to be disabled.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
+#[serde(transparent)]
+pub struct FeatureFlags(pub BTreeMap<String, bool>);
+
+impl FeatureFlags {
+	/// Checks if the given flag is enabled. Unknown flags are always disabled.
+	pub fn is_enabled(&self, flag: &str) -> bool {
+		self.0.get(flag).copied().unwrap_or(false)
+	}
+}
+
+/// The names of the feature flags that are currently in use
+pub mod feature_flag {
+	/// Autoscaling of deployments based on their resource usage
+	pub const AUTOSCALING: &str = "autoscaling";
+	/// Metrics of deployments, like CPU and memory usage
+	pub const METRICS: &str = "metrics";
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::BTreeMap;
+
+	use serde_test::{assert_tokens, Token};
+
+	use super::FeatureFlags;
+
+	#[test]
+	fn assert_feature_flags_types() {
+		assert_tokens(
+			&FeatureFlags(BTreeMap::from([
+				("autoscaling".to_string(), true),
+				("metrics".to_string(), false),
+			])),
+			&[
+				Token::Map { len: Some(2) },
+				Token::Str("autoscaling"),
+				Token::Bool(true),
+				Token::Str("metrics"),
+				Token::Bool(false),
+				Token::MapEnd,
+			],
+		);
+	}
+
+	#[test]
+	fn unknown_flags_are_disabled() {
+		let flags = FeatureFlags(BTreeMap::from([("metrics".to_string(), true)]));
+
+		assert!(flags.is_enabled("metrics"));
+		assert!(!flags.is_enabled("autoscaling"));
+	}
+}
@@ -22,5 +22,18 @@ macros::declare_api_endpoint!(
 		/// The details of the workspace requested
 		#[serde(flatten)]
 		pub workspace: WithId<Workspace>,
+		/// Whether the user making the request is the super admin of the
+		/// workspace. Only the super admin can perform destructive actions on
+		/// the workspace, such as deleting it
+		pub is_super_admin: bool,
+		/// The maximum number of replicas that a deployment in the workspace
+		/// can be scaled to
+		pub max_replicas: u16,
+		/// Whether deploying images with critical vulnerabilities is blocked
+		/// in the workspace
+		pub block_critical_vulnerabilities: bool,
+		/// Whether changes to the deployments of the workspace need to be
+		/// approved by another user before they are applied
+		pub require_deployment_change_approval: bool,
 	}
 );
@@ -0,0 +1,103 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::prelude::*;
+
+/// The category of an entry in the activity feed of a workspace, which says
+/// which part of the workspace the activity is about
+#[derive(
+	Debug,
+	Clone,
+	Copy,
+	PartialEq,
+	Eq,
+	PartialOrd,
+	Ord,
+	Hash,
+	Serialize,
+	Deserialize,
+	strum::EnumString,
+	strum::Display,
+	strum::VariantNames,
+)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
+#[strum(serialize_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub enum WorkspaceActivityCategory {
+	/// A deployment was created, deleted, deployed or had an event, such as
+	/// being started by a schedule or an alert firing
+	Deployment,
+	/// An API token was granted access to the workspace, or was revoked
+	ApiToken,
+	/// The roles of a member of the workspace were changed, or a member was
+	/// removed from the workspace
+	Member,
+	/// A domain or a DNS record was created, changed or deleted
+	Dns,
+	/// Any other resource of the workspace was created, changed or deleted
+	Resource,
+	/// The settings of the workspace itself were changed
+	Workspace,
+}
+
+/// A single entry in the activity feed of a workspace
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceActivity {
+	/// The time at which the activity happened
+	#[cfg_attr(not(target_arch = "wasm32"), schemars(with = "String"))]
+	pub timestamp: OffsetDateTime,
+	/// The part of the workspace that the activity is about
+	pub category: WorkspaceActivityCategory,
+	/// What happened, such as `created`, `deleted`, `deployed` or
+	/// `scheduledStart`
+	pub action: String,
+	/// The ID of the resource, API token or user that the activity is about
+	pub resource_id: Option<Uuid>,
+	/// The ID of the user that performed the activity, if it was performed by
+	/// a user instead of by Patr
+	pub user_id: Option<Uuid>,
+}
+
+macros::declare_api_endpoint!(
+	/// Route to list everything that happened in a workspace as a single
+	/// chronological feed, newest first. The feed merges the events of the
+	/// deployments, the API tokens, the members and the resources of the
+	/// workspace, and is paginated using a cursor, so that entries aren't
+	/// skipped or repeated when new activity happens between pages.
+	ListWorkspaceActivity,
+	GET "/workspace/:workspace_id/activity" {
+		/// The ID of the workspace to list the activity of
+		pub workspace_id: Uuid,
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.workspace_id,
+			permission: Permission::ViewActivity,
+		}
+	},
+	query = {
+		/// The cursor returned by the previous page, to get the activity that
+		/// happened before it. The newest activity is returned if not provided
+		pub cursor: Option<String>,
+		/// The maximum number of entries to return. Defaults to 50
+		#[preprocess(range(max = Some(200)))]
+		pub limit: Option<u32>,
+		/// Only return the activity of this category
+		pub category: Option<WorkspaceActivityCategory>,
+	},
+	response = {
+		/// The activity of the workspace, newest first
+		pub activity: Vec<WorkspaceActivity>,
+		/// The cursor to get the next (older) page of activity with. This is
+		/// `None` if there is no more activity
+		pub next_cursor: Option<String>,
+	}
+);
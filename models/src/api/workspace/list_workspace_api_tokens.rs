@@ -0,0 +1,63 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+	api::user::{ApiTokenStatus, ListedApiToken},
+	prelude::*,
+};
+
+/// An API token that has been granted access to a workspace, along with the
+/// user that owns it
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceApiToken {
+	/// The API token. The permissions of the token are not listed
+	#[serde(flatten)]
+	pub token: ListedApiToken,
+	/// The ID of the user that owns the token
+	pub user_id: Uuid,
+	/// The username of the user that owns the token
+	pub username: String,
+	/// Whether the token has super admin access to the workspace, instead of
+	/// access to specific resources
+	pub is_super_admin: bool,
+}
+
+macros::declare_api_endpoint!(
+	/// Route to list all the API tokens that have been granted access to a
+	/// workspace, across all the users of the workspace. Only the super admin
+	/// of the workspace can list them.
+	ListWorkspaceApiTokens,
+	GET "/workspace/:workspace_id/api-token" {
+		/// The ID of the workspace to list the API tokens of
+		pub workspace_id: Uuid,
+	},
+	api = false,
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::WorkspaceSuperAdminAuthenticator {
+			extract_workspace_id: |req| req.path.workspace_id,
+		}
+	},
+	query = {
+		/// Only list the tokens with this status. If not specified, all the
+		/// tokens that have not been revoked are listed
+		pub status: Option<ApiTokenStatus>,
+		/// Only list the tokens owned by this user
+		pub user_id: Option<Uuid>,
+	},
+	pagination = true,
+	response_headers = {
+		/// The total number of API tokens that have access to the workspace
+		pub total_count: TotalCountHeader,
+	},
+	response = {
+		/// The list of API tokens
+		pub tokens: Vec<WithId<WorkspaceApiToken>>,
+	}
+);
@@ -30,7 +30,7 @@ macros::declare_api_endpoint!(
 		/// The path of the URL
 		#[preprocess(trim, lowercase)]
 		pub path: String,
-		/// The URL type (Deployment, Static Site, Proxy or Redirect)
+		/// The URL type (Deployment, Static Site, Proxy, Redirect or Path Map)
 		#[preprocess(none)]
 		pub url_type: ManagedUrlType,
 	},
@@ -1,3 +1,5 @@
+use std::cmp::Ordering;
+
 use serde::{Deserialize, Serialize};
 use strum::{Display, EnumDiscriminants, EnumString, VariantNames};
 
@@ -21,23 +23,9 @@ pub use self::{
 };
 use crate::utils::Uuid;
 
-/// Which field to order the list by for paginated requests
-#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
-#[serde(rename_all = "camelCase")]
-pub enum ListOrderBy {
-	/// Order the list by the status of the resource
-	Status,
-	/// Order the list by the name of the resource
-	Name,
-	/// Order the list by when the resource was last updated
-	LastUpdated,
-	/// Order the list by when the resource was created
-	#[default]
-	Created,
-}
-
 /// Managed URL information
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct ManagedUrl {
 	/// Subdomain of the URL
@@ -66,6 +54,7 @@ pub struct ManagedUrl {
 	EnumString,
 	VariantNames,
 )]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
 #[strum_discriminants(
 	name(ManagedUrlTypeDiscriminant),
 	derive(strum::Display, EnumString),
@@ -118,4 +107,81 @@ pub enum ManagedUrlType {
 		/// If the URL is a http only
 		http_only: bool,
 	},
+	/// URL routes requests to different deployments based on the prefix of
+	/// their path
+	#[serde(rename_all = "camelCase")]
+	PathMap {
+		/// The rules to route requests by, ordered from the most specific path
+		/// prefix to the least specific one
+		rules: Vec<ManagedUrlPathRule>,
+	},
+}
+
+/// A rule of a [`ManagedUrlType::PathMap`] URL, which routes the requests whose
+/// path starts with the given prefix (relative to the path of the URL) to a
+/// port of a deployment
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct ManagedUrlPathRule {
+	/// The prefix of the path that the rule matches, such as `/api`
+	pub path_prefix: String,
+	/// Deployment ID of the deployment to route the requests to
+	pub deployment_id: Uuid,
+	/// Deployment port of the deployment to route the requests to
+	pub port: u16,
+}
+
+impl ManagedUrlPathRule {
+	/// Compares the rules by how specific their path prefixes are, so that
+	/// sorting by this puts the rule that should be matched first at the front.
+	/// A prefix with more path segments is more specific, then a longer prefix,
+	/// and prefixes that are equally specific are ordered alphabetically.
+	pub fn cmp_specificity(&self, other: &Self) -> Ordering {
+		let segments = |prefix: &str| {
+			prefix
+				.split('/')
+				.filter(|segment| !segment.is_empty())
+				.count()
+		};
+
+		segments(&other.path_prefix)
+			.cmp(&segments(&self.path_prefix))
+			.then_with(|| other.path_prefix.len().cmp(&self.path_prefix.len()))
+			.then_with(|| self.path_prefix.cmp(&other.path_prefix))
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::ManagedUrlPathRule;
+	use crate::utils::Uuid;
+
+	fn rule(path_prefix: &str) -> ManagedUrlPathRule {
+		ManagedUrlPathRule {
+			path_prefix: path_prefix.to_string(),
+			deployment_id: Uuid::nil(),
+			port: 80,
+		}
+	}
+
+	#[test]
+	fn path_rules_are_ordered_by_specificity() {
+		let mut rules = vec![
+			rule("/"),
+			rule("/api"),
+			rule("/api/v1"),
+			rule("/apiv2"),
+			rule("/docs"),
+		];
+		rules.sort_by(ManagedUrlPathRule::cmp_specificity);
+
+		assert_eq!(
+			rules
+				.iter()
+				.map(|rule| rule.path_prefix.as_str())
+				.collect::<Vec<_>>(),
+			["/api/v1", "/apiv2", "/docs", "/api", "/"]
+		);
+	}
 }
@@ -27,7 +27,7 @@ macros::declare_api_endpoint!(
 		#[preprocess(trim, lowercase)]
 		pub path: String,
 		/// The new type of the updated URL which can be
-		/// Deployment, Static Site, Proxy or Redirect
+		/// Deployment, Static Site, Proxy, Redirect or Path Map
 		#[preprocess(none)]
 		pub url_type: ManagedUrlType,
 	},
@@ -12,6 +12,9 @@ pub mod database;
 pub mod deployment;
 /// All the modules that corresponds to Patr Domains
 pub mod domain;
+/// This module contains all the models that corresponds to the egress policy
+/// of a workspace
+pub mod egress_policy;
 /// This module contains all the managed URL models
 pub mod managed_url;
 /// This module contains all the models that corresponds to the RBAC of Patr
@@ -30,24 +33,47 @@ pub mod volume;
 mod create_workspace;
 /// The endpoint to delete a workspace
 mod delete_workspace;
+/// The endpoint to get the API usage of a workspace
+mod get_api_usage;
+/// The endpoint to get the feature flags of a workspace
+mod get_feature_flags;
 /// The endpoint to get the details of a workspace
 mod get_workspace_info;
 /// The endpoint to check if a workspace name is available
 mod is_name_available;
+/// The endpoint to list the activity of a workspace
+mod list_workspace_activity;
+/// The endpoint to list the API tokens that have access to a workspace
+mod list_workspace_api_tokens;
+/// The endpoint to wait for the next updates of a workspace, as a fallback for
+/// clients that can't use websockets
+mod poll_workspace_updates;
+/// The endpoint to revoke an API token that has access to a workspace
+mod revoke_workspace_api_token;
+/// The endpoint to search for resources in a workspace
+mod search_workspace_resources;
 /// The endpoint to update the details of a workspace
 mod update_workspace_info;
 
 pub use self::{
 	create_workspace::*,
 	delete_workspace::*,
+	get_api_usage::*,
+	get_feature_flags::*,
 	get_workspace_info::*,
 	is_name_available::*,
+	list_workspace_activity::*,
+	list_workspace_api_tokens::*,
+	poll_workspace_updates::*,
+	revoke_workspace_api_token::*,
+	search_workspace_resources::*,
 	update_workspace_info::*,
 };
 
 /// The details of a workspace. A workspace contains all the resources that will
 /// be created. A resource cannot exist outside of a workspace.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct Workspace {
 	/// The name of the workspace. This must be unique across Patr. This is used
@@ -88,3 +114,37 @@ pub struct WorkspaceAuditLog {
 	/// Was the request successful or not
 	pub request_success: bool,
 }
+
+/// The usage of a single endpoint of the API by a workspace, within a time
+/// bucket
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct ApiUsageBucket {
+	/// The start of the time bucket
+	#[cfg_attr(not(target_arch = "wasm32"), schemars(with = "String"))]
+	pub bucket_start: OffsetDateTime,
+	/// The endpoint that was called, in the format `METHOD /path`
+	pub endpoint: String,
+	/// The number of requests made to the endpoint
+	pub request_count: u64,
+	/// The number of requests that resulted in an error
+	pub error_count: u64,
+	/// The median latency of the requests, in milliseconds
+	pub p50_latency_millis: u64,
+	/// The 95th percentile latency of the requests, in milliseconds
+	pub p95_latency_millis: u64,
+}
+
+/// An update made to a workspace, as published on the Redis channel of the
+/// workspace
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceUpdate {
+	/// The cursor of the update. Polling with this cursor returns the updates
+	/// made after this one
+	pub cursor: String,
+	/// The data of the update, as it was published
+	pub data: Value,
+}
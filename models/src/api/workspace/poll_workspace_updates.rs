@@ -0,0 +1,50 @@
+use super::WorkspaceUpdate;
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route to wait for the next updates of a workspace, for clients that
+	/// can't hold a websocket open. The request is held until there is an
+	/// update after the given cursor, or until the timeout is reached, in
+	/// which case no updates are returned. The cursor returned in the response
+	/// should be sent with the next poll, so that no updates are missed
+	/// between polls. If some of the updates after the cursor are no longer
+	/// kept, the poll fails with [`ErrorType::CursorExpired`].
+	///
+	/// Only the updates about resources that the user has the permission to
+	/// view are returned.
+	PollWorkspaceUpdates,
+	GET "/workspace/:workspace_id/updates" {
+		/// The ID of the workspace to get the updates of
+		pub workspace_id: Uuid,
+	},
+	authentication = {
+		AppAuthentication::<Self>::WorkspaceMembershipAuthenticator {
+			extract_workspace_id: |req| req.path.workspace_id,
+		}
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	query = {
+		/// The cursor returned by the previous poll. Only the updates after it
+		/// are returned. If not given, only the updates made after the poll
+		/// starts are returned
+		pub cursor: Option<String>,
+		/// The number of seconds to wait for an update before returning
+		/// without any. This is limited to the maximum allowed by the Patr
+		/// instance, and a timeout of 0 returns right away
+		pub timeout_seconds: Option<u32>,
+	},
+	response = {
+		/// The updates after the cursor, the oldest first. This is empty if
+		/// there weren't any updates before the timeout
+		pub updates: Vec<WorkspaceUpdate>,
+		/// The cursor to send with the next poll. This is the cursor of the
+		/// last update returned, or the cursor that was sent if there weren't
+		/// any updates
+		pub cursor: String,
+	}
+);
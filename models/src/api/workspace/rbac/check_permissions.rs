@@ -0,0 +1,41 @@
+use std::collections::BTreeMap;
+
+use crate::{prelude::*, rbac::Permission};
+
+macros::declare_api_endpoint!(
+	/// Route to check which of the given permissions the current user has in a
+	/// workspace. This is used by the dashboard to hide actions that the user is
+	/// not authorized to perform.
+	CheckPermissions,
+	POST "/workspace/:workspace_id/rbac/check-permissions" {
+		/// The ID of the workspace
+		pub workspace_id: Uuid
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::WorkspaceMembershipAuthenticator {
+			extract_workspace_id: |req| req.path.workspace_id
+		}
+	},
+	request = {
+		/// The list of permissions to check
+		#[preprocess(none)]
+		pub permissions: Vec<Permission>,
+		/// The resource to check the permissions on. If not provided, the
+		/// permissions are checked on the workspace itself, which is what is
+		/// used for actions like creating a new resource in the workspace.
+		#[preprocess(none)]
+		#[serde(default, skip_serializing_if = "Option::is_none")]
+		pub resource_id: Option<Uuid>,
+	},
+	response = {
+		/// A map of the permission (as a string, such as `deployment::create`)
+		/// to whether the user is allowed to perform it or not
+		pub permissions: BTreeMap<String, bool>,
+	}
+);
@@ -1,19 +1,31 @@
 use serde::{Deserialize, Serialize};
 
-use crate::prelude::*;
+use crate::{prelude::*, rbac::ResourceType};
 
 /// The permission metadata
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
+#[cfg_attr(not(target_arch = "wasm32"), schemars(rename = "PermissionInfo"))]
 #[serde(rename_all = "camelCase")]
 pub struct Permission {
 	/// The name of the permission
 	pub name: String,
 	/// The description of the permission
 	pub description: String,
+	/// The category of the permission, used to group permissions together
+	/// when displaying them. This is `None` if the permission is not known
+	/// to the API, for example if it was added by a newer version.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub category: Option<String>,
+	/// The type of resource that the permission is granted on
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub resource_type: Option<ResourceType>,
 }
 
 macros::declare_api_endpoint!(
-	/// Route to list all the permissions
+	/// Route to list all the permissions. The permissions can optionally be filtered
+	/// by a search term and a category. All the permissions are listed if no filter
+	/// is provided.
 	ListAllPermissions,
 	GET "/workspace/:workspace_id/rbac/permission" {
 		/// The ID of the workspace
@@ -25,6 +37,13 @@ macros::declare_api_endpoint!(
 		/// The user-agent used to access this API
 		pub user_agent: UserAgent,
 	},
+	query = {
+		/// Only list the permissions whose name or description contains this
+		/// search term, ignoring case
+		pub search: Option<String>,
+		/// Only list the permissions in this category
+		pub category: Option<String>,
+	},
 	authentication = {
 		AppAuthentication::<Self>::WorkspaceMembershipAuthenticator {
 			extract_workspace_id: |req| req.path.workspace_id
@@ -34,6 +53,8 @@ macros::declare_api_endpoint!(
 		/// The list permissions that contains:
 		/// - name - The name of the permission
 		/// - description - The description of the permission
+		/// - category - The category the permission is grouped under
+		/// - resourceType - The type of resource the permission applies to
 		pub permissions: Vec<WithId<Permission>>
 	}
 );
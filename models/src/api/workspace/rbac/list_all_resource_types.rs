@@ -4,6 +4,8 @@ use crate::prelude::*;
 
 /// The Resource Type metadata
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
+#[cfg_attr(not(target_arch = "wasm32"), schemars(rename = "ResourceTypeInfo"))]
 #[serde(rename_all = "camelCase")]
 pub struct ResourceType {
 	/// The name of the resource type
@@ -0,0 +1,33 @@
+use super::ExportedRole;
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route to export all the roles of a workspace, along with their permissions, as a
+	/// portable document that can be imported into another workspace using the
+	/// [`super::ImportWorkspaceRolesRequest`] endpoint.
+	ExportWorkspaceRoles,
+	GET "/workspaces/:workspace_id/rbac/role/export" {
+		/// The ID of the workspace
+		pub workspace_id: Uuid
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.workspace_id,
+			permission: Permission::ViewRoles,
+		}
+	},
+	response = {
+		/// The ID of the workspace that the roles were exported from.
+		/// Permissions on this workspace itself are granted on the workspace
+		/// that the roles are imported into.
+		pub exported_from: Uuid,
+		/// The exported roles of the workspace
+		pub roles: Vec<ExportedRole>,
+	}
+);
@@ -0,0 +1,49 @@
+use super::{ExportedRole, Role};
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route to import roles that were exported using the
+	/// [`super::ExportWorkspaceRolesRequest`] endpoint into a workspace. Only the
+	/// super admin of the workspace can import roles. Permissions are matched by their
+	/// name, and permissions on the workspace that the roles were exported
+	/// from are granted on this workspace instead. Other resources that don't
+	/// belong to the workspace are left out, since they can't be accessed from
+	/// this workspace anyway.
+	ImportWorkspaceRoles,
+	POST "/workspaces/:workspace_id/rbac/role/import" {
+		/// The ID of the workspace
+		pub workspace_id: Uuid
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.workspace_id,
+			permission: Permission::ModifyRoles,
+		}
+	},
+	request = {
+		/// The ID of the workspace that the roles were exported from
+		#[preprocess(none)]
+		pub exported_from: Uuid,
+		/// The roles to import
+		#[preprocess(none)]
+		pub roles: Vec<ExportedRole>,
+		/// If a role with the same name already exists in the workspace, import the
+		/// role under a new name instead of skipping it
+		#[serde(default)]
+		#[preprocess(none)]
+		pub rename_on_conflict: bool,
+	},
+	response = {
+		/// The roles that were imported
+		pub imported: Vec<WithId<Role>>,
+		/// The names of the roles that were not imported, because a role with the
+		/// same name already exists in the workspace
+		pub conflicts: Vec<String>,
+	}
+);
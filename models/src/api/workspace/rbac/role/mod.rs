@@ -2,8 +2,12 @@
 mod create_new_role;
 /// The endpoint to delete a role in the workspace
 mod delete_role;
+/// The endpoint to export all the roles in the workspace
+mod export_roles;
 /// The endpoint to get the details of a role in the workspace
 mod get_role_info;
+/// The endpoint to import roles into the workspace
+mod import_roles;
 /// The endpoint to list all the roles in the workspace
 mod list_all_roles;
 /// The endpoint to list all the users for a role in the workspace
@@ -11,19 +15,25 @@ mod list_users_for_role;
 /// The endpoint to update the details of a role in the workspace
 mod update_role;
 
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
 pub use self::{
 	create_new_role::*,
 	delete_role::*,
+	export_roles::*,
 	get_role_info::*,
+	import_roles::*,
 	list_all_roles::*,
 	list_users_for_role::*,
 	update_role::*,
 };
+use crate::rbac::ResourcePermissionType;
 
 /// The role metadata
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct Role {
 	/// The name of the role
@@ -32,3 +42,16 @@ pub struct Role {
 	#[serde(default, skip_serializing_if = "String::is_empty")]
 	pub description: String,
 }
+
+/// A role along with its permissions, in a form that can be imported into any
+/// workspace. Permissions are identified by their name instead of their ID.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct ExportedRole {
+	/// The name and description of the role
+	#[serde(flatten)]
+	pub role: Role,
+	/// The permissions of the role, keyed by the name of the permission
+	pub permissions: BTreeMap<String, ResourcePermissionType>,
+}
@@ -0,0 +1,28 @@
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route to revoke an API token that has been granted access to a
+	/// workspace, for example when the user that owns it leaves the workspace.
+	/// Only the permissions of the token on this workspace are removed, and the
+	/// token keeps working for the other workspaces it has access to. Only the
+	/// super admin of the workspace can revoke tokens of other users.
+	RevokeWorkspaceApiToken,
+	DELETE "/workspace/:workspace_id/api-token/:token_id" {
+		/// The ID of the workspace that the token has access to
+		pub workspace_id: Uuid,
+		/// The ID of the token to revoke
+		pub token_id: Uuid,
+	},
+	api = false,
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::WorkspaceSuperAdminAuthenticator {
+			extract_workspace_id: |req| req.path.workspace_id,
+		}
+	},
+);
@@ -0,0 +1,61 @@
+use std::fmt::{self, Debug};
+
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route for a runner to get the credentials of a pull secret, so that it
+	/// can pull the images of its deployments from a private registry. Only the
+	/// pull secrets of the deployments that run on the runner can be fetched.
+	GetRunnerPullSecret,
+	GET "/workspace/:workspace_id/runner/:runner_id/pull-secret/:secret_id" {
+		/// The ID of the workspace
+		pub workspace_id: Uuid,
+		/// The ID of the runner
+		pub runner_id: Uuid,
+		/// The ID of the pull secret
+		pub secret_id: Uuid,
+	},
+	request_headers = {
+		/// Token used to authorize the runner
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.runner_id,
+			permission: Permission::Runner(RunnerPermission::View),
+		}
+	},
+	response = {
+		/// The credentials of the pull secret
+		#[serde(flatten)]
+		pub credentials: RegistryCredentials,
+	}
+);
+
+/// The credentials used to pull images from a private registry. The password
+/// is left out of the [`Debug`] output, so that it doesn't end up in the logs.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct RegistryCredentials {
+	/// The registry that the credentials are for. Example: `ghcr.io`
+	pub registry: String,
+	/// The username used to login to the registry
+	pub username: String,
+	/// The password or access token used to login to the registry
+	pub password: String,
+}
+
+impl Debug for RegistryCredentials {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("RegistryCredentials")
+			.field("registry", &self.registry)
+			.field("username", &self.username)
+			.field("password", &"********")
+			.finish()
+	}
+}
@@ -1,8 +1,22 @@
+use serde::{Deserialize, Serialize};
+
 use super::Runner;
 use crate::prelude::*;
 
+/// The status of a runner to filter the list of runners by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub enum RunnerStatus {
+	/// The runner is currently connected to the Patr API
+	Online,
+	/// The runner is not connected to the Patr API
+	Offline,
+}
+
 macros::declare_api_endpoint!(
-	/// Route to list all the runners of a workspace
+	/// Route to list all the runners of a workspace. The runners are sorted by
+	/// the time they were created, with the newest first
 	ListRunnersForWorkspace,
 	GET "/workspace/:workspace_id/runner" {
 		/// The ID of the workspace
@@ -14,14 +28,20 @@ macros::declare_api_endpoint!(
 		/// The user-agent used to access this API
 		pub user_agent: UserAgent,
 	},
-	pagination = true,
 	authentication = {
 		AppAuthentication::<Self>::WorkspaceMembershipAuthenticator {
 			extract_workspace_id: |req| req.path.workspace_id,
 		}
 	},
+	query = {
+		/// Only list the runners with this status. Both online and offline
+		/// runners are listed if this is not given
+		pub status: Option<RunnerStatus>,
+	},
+	pagination = true,
 	response_headers = {
-		/// The total number of items in the pagination
+		/// The total number of runners that match the filter, across all the
+		/// pages
 		pub total_count: TotalCountHeader,
 	},
 	response = {
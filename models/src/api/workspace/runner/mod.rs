@@ -2,6 +2,8 @@
 mod add_runner_to_workspace;
 /// The endpoint to get the details of a runner in a workspace
 mod get_runner_info;
+/// The endpoint for a runner to get the credentials of a pull secret
+mod get_runner_pull_secret;
 /// The endpoint to list all the runners in a workspace
 mod list_runners_for_workspace;
 /// The endpoint to remove a runner from a workspace
@@ -15,6 +17,7 @@ use time::OffsetDateTime;
 pub use self::{
 	add_runner_to_workspace::*,
 	get_runner_info::*,
+	get_runner_pull_secret::*,
 	list_runners_for_workspace::*,
 	remove_runner_from_workspace::*,
 	stream_runner_data_for_workspace::*,
@@ -30,6 +33,7 @@ pub use self::{
 /// the deployments in any way they want. This includes running the deployments
 /// on a VM, kubernetes, or even on other PaaS providers.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct Runner {
 	/// The name of the runner
@@ -37,5 +41,13 @@ pub struct Runner {
 	/// Whether the runner is connected to the Patr API currently or not
 	pub connected: bool,
 	/// The last timestamp the runner was seen online
+	#[cfg_attr(not(target_arch = "wasm32"), schemars(with = "Option<String>"))]
 	pub last_seen: Option<OffsetDateTime>,
+	/// The time the runner was created
+	#[cfg_attr(not(target_arch = "wasm32"), schemars(with = "String"))]
+	pub created_at: OffsetDateTime,
+	/// The time the runner was last updated. This is the same as the time it
+	/// was created, if it was never updated
+	#[cfg_attr(not(target_arch = "wasm32"), schemars(with = "String"))]
+	pub updated_at: OffsetDateTime,
 }
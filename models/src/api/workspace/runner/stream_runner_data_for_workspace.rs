@@ -1,5 +1,9 @@
 use crate::{
-	api::workspace::deployment::{Deployment, DeploymentRunningDetails},
+	api::workspace::{
+		database::{DatabaseEngine, DatabaseUser},
+		deployment::{build::DeploymentBuildSource, Deployment, DeploymentRunningDetails},
+		egress_policy::WorkspaceEgressPolicy,
+	},
 	prelude::*,
 	rbac::ResourceType,
 };
@@ -49,6 +53,59 @@ macros::declare_stream_endpoint!(
 			/// The ID of the deployment that was deleted
 			id: Uuid
 		},
+		/// The user has asked for a deployment to be reconciled again
+		DeploymentReconciliationRequested {
+			/// The ID of the deployment to reconcile
+			id: Uuid
+		},
+		/// The user has started a build of a deployment's image from its build
+		/// source. The runner should build the image, push it with the given
+		/// image reference and report the result of the build back
+		DeploymentBuildRequested {
+			/// The ID of the deployment whose image is being built
+			deployment_id: Uuid,
+			/// The ID of the build
+			build_id: Uuid,
+			/// The source to build the image from
+			#[serde(flatten)]
+			build_source: DeploymentBuildSource,
+			/// The image reference (including the tag) to push the built image to
+			image: String,
+		},
+		/// The user has changed the egress policy of the workspace. The runner
+		/// should enforce the new policy for all the deployments it runs
+		WorkspaceEgressPolicyUpdated {
+			/// The ID of the workspace whose egress policy was changed
+			workspace_id: Uuid,
+			/// The new egress policy of the workspace. This is empty if the
+			/// policy was removed
+			#[serde(flatten)]
+			policy: WorkspaceEgressPolicy,
+		},
+		/// New credentials were issued for a managed database. The runner
+		/// should create the user on the database engine, or update its
+		/// password if it already exists
+		DatabaseUserCreated {
+			/// The ID of the database to create the user on
+			database_id: Uuid,
+			/// The engine of the database
+			engine: DatabaseEngine,
+			/// The user to create
+			user: DatabaseUser,
+		},
+		/// The grace period of rotated credentials of a managed database is
+		/// over. The runner should drop the user from the database engine
+		DatabaseUserDropped {
+			/// The ID of the database to drop the user from
+			database_id: Uuid,
+			/// The engine of the database
+			engine: DatabaseEngine,
+			/// The username of the user to drop
+			username: String,
+			/// The user that the database was created with, which takes over
+			/// anything that the dropped user owns
+			owner: String,
+		},
 	},
 	client_msg = {},
 );
@@ -60,6 +117,11 @@ impl StreamRunnerDataForWorkspaceServerMsg {
 			Self::DeploymentCreated { .. } => ResourceType::Deployment,
 			Self::DeploymentUpdated { .. } => ResourceType::Deployment,
 			Self::DeploymentDeleted { .. } => ResourceType::Deployment,
+			Self::DeploymentReconciliationRequested { .. } => ResourceType::Deployment,
+			Self::DeploymentBuildRequested { .. } => ResourceType::Deployment,
+			Self::WorkspaceEgressPolicyUpdated { .. } => ResourceType::Workspace,
+			Self::DatabaseUserCreated { .. } => ResourceType::Database,
+			Self::DatabaseUserDropped { .. } => ResourceType::Database,
 		}
 	}
 }
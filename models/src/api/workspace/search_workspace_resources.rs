@@ -0,0 +1,50 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{prelude::*, rbac::ResourceType};
+
+/// A resource in a workspace that matched a search
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceSearchResult {
+	/// The ID of the resource
+	pub id: Uuid,
+	/// The name of the resource
+	pub name: String,
+	/// The type of the resource. This can be a deployment, a runner, a
+	/// database or a secret
+	pub resource_type: ResourceType,
+}
+
+macros::declare_api_endpoint!(
+	/// Route to search for resources in a workspace by their name. Deployments,
+	/// runners, databases and secrets are searched, and only the resources that
+	/// the user has permission to view are returned
+	SearchWorkspaceResources,
+	GET "/workspace/:workspace_id/search" {
+		/// The workspace ID to search in
+		pub workspace_id: Uuid,
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::WorkspaceMembershipAuthenticator {
+			extract_workspace_id: |req| req.path.workspace_id,
+		}
+	},
+	query = {
+		/// The text to search for in the names of the resources
+		#[preprocess(trim, length(min = 1, max = 255))]
+		pub query: String,
+	},
+	response = {
+		/// The resources that matched the search, with the best matches first.
+		/// Exact matches come first, followed by names that start with the
+		/// query, and then names that contain it
+		pub results: Vec<WorkspaceSearchResult>,
+	}
+);
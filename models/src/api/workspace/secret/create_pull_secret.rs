@@ -0,0 +1,43 @@
+use crate::{prelude::*, utils::constants::RESOURCE_NAME_REGEX};
+
+macros::declare_api_endpoint!(
+	/// Route to create a pull secret, which stores the credentials used to pull
+	/// images from a private external registry. The credentials are never
+	/// returned by any endpoint once they are stored.
+	CreatePullSecret,
+	POST "/workspace/:workspace_id/secret/pull-secret" {
+		/// The ID of the workspace
+		pub workspace_id: Uuid
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.workspace_id,
+			permission: Permission::Secret(SecretPermission::Create),
+		}
+	},
+	request = {
+		/// The name of the secret
+		#[preprocess(trim, regex = RESOURCE_NAME_REGEX)]
+		pub name: String,
+		/// The registry that the credentials are for. Example: `ghcr.io`
+		#[preprocess(trim, lowercase)]
+		pub registry: String,
+		/// The username used to login to the registry
+		#[preprocess(trim)]
+		pub username: String,
+		/// The password or access token used to login to the registry
+		#[preprocess(none)]
+		pub password: String,
+	},
+	response = {
+		/// The ID of the created secret
+		#[serde(flatten)]
+		pub id: WithId<()>
+	}
+);
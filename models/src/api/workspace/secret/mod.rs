@@ -1,7 +1,11 @@
 use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
 
 use crate::prelude::*;
 
+/// The endpoint to create a pull secret for a private registry in the
+/// workspace
+mod create_pull_secret;
 /// The endpoint to create a secret in the workspace
 mod create_secret;
 /// The endpoint to delete a secret in the workspace
@@ -12,6 +16,7 @@ mod list_secrets_for_workspace;
 mod update_secret;
 
 pub use self::{
+	create_pull_secret::*,
 	create_secret::*,
 	delete_secret::*,
 	list_secrets_for_workspace::*,
@@ -22,6 +27,7 @@ pub use self::{
 /// secret value. This is to ensure that Patr does not have
 /// access to any user sensitive information.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct Secret {
 	/// The name of the secret
@@ -29,4 +35,14 @@ pub struct Secret {
 	/// The deployment the secret is attached to
 	#[serde(skip_serializing_if = "Option::is_none")]
 	pub deployment_id: Option<Uuid>,
+	/// The registry that the secret holds the pull credentials for, if the
+	/// secret is a pull secret. The credentials themselves are never returned
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub pull_secret_registry: Option<String>,
+	/// The time at which the value of the secret was last updated, if the
+	/// backend that the value is stored in keeps track of it. The value itself
+	/// is never returned
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[cfg_attr(not(target_arch = "wasm32"), schemars(with = "Option<String>"))]
+	pub value_updated: Option<OffsetDateTime>,
 }
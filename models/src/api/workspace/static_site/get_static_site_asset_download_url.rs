@@ -0,0 +1,45 @@
+use time::OffsetDateTime;
+
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route to issue a short-lived signed URL to download a single asset of a
+	/// static site. The URL can be used without a token until it expires, so
+	/// that it can be shared with tools that can't authenticate.
+	GetStaticSiteAssetDownloadUrl,
+	GET "/workspace/:workspace_id/infrastructure/static-site/:static_site_id/asset-download-url" {
+		/// The workspace ID of the user
+		pub workspace_id: Uuid,
+		/// The static site ID to download the asset of
+		pub static_site_id: Uuid
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.static_site_id,
+			permission: Permission::StaticSite(StaticSitePermission::View),
+		}
+	},
+	query = {
+		/// The path of the asset within the static site, such as
+		/// `assets/logo.png`
+		#[preprocess(trim, length(min = 1))]
+		pub path: String,
+		/// The upload to download the asset from. Defaults to the upload that
+		/// is currently live
+		pub upload_id: Option<Uuid>,
+	},
+	response = {
+		/// The signed URL that the asset can be downloaded from, relative to
+		/// the API
+		pub url: String,
+		/// The time after which the URL can no longer be used
+		#[cfg_attr(not(target_arch = "wasm32"), schemars(with = "String"))]
+		pub expires_at: OffsetDateTime,
+	}
+);
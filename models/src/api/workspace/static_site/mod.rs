@@ -5,6 +5,8 @@ use time::OffsetDateTime;
 mod create_static_site;
 /// The endpoint to delete a static site
 mod delete_static_site;
+/// The endpoint to issue a signed URL to download an asset of a static site
+mod get_static_site_asset_download_url;
 /// The endpoint to get the details of a static site
 mod get_static_site_info;
 /// The endpoint to list all the static sites in a workspace
@@ -25,6 +27,7 @@ mod upload_static_site;
 pub use self::{
 	create_static_site::*,
 	delete_static_site::*,
+	get_static_site_asset_download_url::*,
 	get_static_site_info::*,
 	list_static_site::*,
 	list_upload_history::*,
@@ -39,6 +42,7 @@ use crate::utils::Uuid;
 
 /// Static site
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct StaticSite {
 	/// Name of the static site
@@ -61,6 +65,7 @@ pub struct StaticSite {
 
 /// Static site details
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct StaticSiteDetails {
 	// add more details here, like metrics, etc.
@@ -68,6 +73,7 @@ pub struct StaticSiteDetails {
 
 /// Static site upload history
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct StaticSiteUploadHistory {
 	/// The upload ID
@@ -77,7 +83,9 @@ pub struct StaticSiteUploadHistory {
 	/// The user ID of the user who uploaded
 	pub uploaded_by: Uuid,
 	/// The timestamp of when the static site was created
+	#[cfg_attr(not(target_arch = "wasm32"), schemars(with = "String"))]
 	pub created: OffsetDateTime,
 	/// The timestamp of when the static site was processed
+	#[cfg_attr(not(target_arch = "wasm32"), schemars(with = "Option<String>"))]
 	pub processed: Option<OffsetDateTime>,
 }
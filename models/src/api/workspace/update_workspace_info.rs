@@ -23,5 +23,19 @@ macros::declare_api_endpoint!(
 		/// The new name of the workspace
 		#[preprocess(optional(trim, regex = RESOURCE_NAME_REGEX))]
 		pub name: Option<String>,
+		/// The new maximum number of replicas that a deployment in the
+		/// workspace can be scaled to. The limit can only be lowered, so this
+		/// cannot be more than the current limit of the workspace
+		#[preprocess(optional(range(min = 1)))]
+		pub max_replicas: Option<u16>,
+		/// Whether deploying images with critical vulnerabilities should be
+		/// blocked in the workspace. Images are scanned before they are
+		/// deployed if this is set
+		#[preprocess(none)]
+		pub block_critical_vulnerabilities: Option<bool>,
+		/// Whether changes to the deployments of the workspace need to be
+		/// approved by another user before they are applied
+		#[preprocess(none)]
+		pub require_deployment_change_approval: Option<bool>,
 	},
 );
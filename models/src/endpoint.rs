@@ -57,6 +57,12 @@ where
 	/// If true, this route can be accessed by the API. Otherwise, it'll only be
 	/// accessible by the Web UI
 	const API_ALLOWED: bool;
+	/// The name of the endpoint, used as the ID of its operation in the
+	/// OpenAPI document of the API
+	const NAME: &'static str;
+	/// The documentation of the endpoint, used as the summary of its operation
+	/// in the OpenAPI document of the API
+	const DOCUMENTATION: &'static str;
 
 	/// The path that should be used for this endpoint. This should be a valid
 	/// HTML URL Path and can contain URL parameters as a struct. For example,
@@ -129,4 +135,14 @@ where
 	{
 		Self::Authenticator::default()
 	}
+
+	/// The number of items requested per page, if the query of this endpoint
+	/// is paginated (see [`Paginated`][1]). This is used to limit the page size
+	/// requested by a client. Endpoints declared with `pagination = true`
+	/// implement this automatically.
+	///
+	/// [1]: crate::api::Paginated
+	fn page_size_mut(_query: &mut Self::RequestQuery) -> Option<&mut usize> {
+		None
+	}
 }
@@ -1,16 +1,36 @@
 use std::{error::Error as StdError, fmt::Display, str::FromStr};
 
 use axum::http::StatusCode;
+use schemars::{
+	gen::SchemaGenerator,
+	schema::{InstanceType, Schema, SchemaObject},
+	JsonSchema,
+};
 use serde::{Deserialize, Serialize};
-use strum::{Display, EnumIter, IntoEnumIterator};
+use strum::{Display, EnumIter, IntoEnumIterator, IntoStaticStr};
 
 use crate::prelude::*;
 
-/// A list of all the possible errors that can be returned by the API
+/// A list of all the possible errors that can be returned by the API. Errors
+/// are always serialized as their code, so that clients can match on them. The
+/// details of an error (such as the usage of the quota of
+/// [`ErrorType::QuotaExceeded`]) are sent as separate fields of the
+/// [`ApiErrorResponseBody`]
 #[derive(
-	Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Display, EnumIter,
+	Debug,
+	Clone,
+	Copy,
+	PartialEq,
+	Eq,
+	PartialOrd,
+	Ord,
+	Serialize,
+	Deserialize,
+	Display,
+	IntoStaticStr,
+	EnumIter,
 )]
-#[serde(rename_all = "camelCase")]
+#[serde(into = "&'static str", try_from = "String")]
 #[strum(serialize_all = "camelCase")]
 pub enum ErrorType {
 	/// The email provided is invalid
@@ -56,8 +76,9 @@ pub enum ErrorType {
 	/// The phone number provided is not available. It is being used by another
 	/// account
 	PhoneUnavailable,
-	/// The reset token used to reset the given user's password is invalid.
-	InvalidPasswordResetToken,
+	/// The token used to reset the given user's password is unknown, has
+	/// expired or has already been used.
+	ResetTokenInvalid,
 	/// The resource that the user is trying to access does not exist.
 	ResourceDoesNotExist,
 	/// The resource already exists
@@ -92,6 +113,192 @@ pub enum ErrorType {
 	RunnerAlreadyConnected,
 	/// The operation is not allowed in the current runner mode
 	InvalidRunnerMode,
+	/// The image of the deployment is hosted on a private registry, but no pull
+	/// secret was provided to pull it
+	MissingPullSecret,
+	/// The cron expression provided is invalid
+	InvalidCronExpression,
+	/// The container image reference provided is invalid. The part of the
+	/// reference that is invalid, such as its tag, is sent along with the error
+	InvalidImageReference(&'static str),
+	/// The server is handling too many requests at the moment and cannot accept
+	/// any more. The request can be retried after the time given in the
+	/// `Retry-After` header
+	ServerOverloaded,
+	/// More than one volume is mounted on the same path of a deployment
+	VolumeMountConflict,
+	/// A deployment with a volume mounted cannot run more than one replica,
+	/// since volumes can only be attached to one replica at a time
+	CannotScaleWithVolume,
+	/// Only deployments that use the Patr registry can be built from source,
+	/// since the built image is pushed to the deployment's repository
+	BuildSourceRequiresPatrRegistry,
+	/// The deployment does not have a build source to build its image from
+	NoBuildSource,
+	/// A build of the deployment is already queued or in progress
+	BuildAlreadyInProgress,
+	/// The build cannot be moved to the given status from its current status
+	InvalidBuildStatusTransition,
+	/// The path, or one of the path rules, of the managed URL is already routed
+	/// by another managed URL on the same host
+	ManagedUrlRuleConflict,
+	/// The intermediate token issued after the password of a user was verified
+	/// is invalid or has expired, and the user has to login again
+	MfaLoginTokenInvalid,
+	/// The token used to verify an email address is invalid or has expired
+	InvalidEmailVerificationToken,
+	/// A permission with the given name does not exist
+	UnknownPermission,
+	/// Too many accounts have been created from the same IP address or email
+	/// domain recently. The request can be retried after the number of seconds
+	/// that the error carries, which is sent in the `Retry-After` header
+	TooManySignups(u64),
+	/// The runner could not be reached, even after retrying. The changes that
+	/// were meant for it are saved, and are applied on its next reconciliation
+	RunnerUnreachable,
+	/// The resource was modified since the version given in the `If-Match`
+	/// header
+	ResourceModified,
+	/// The CPU or memory requested by a deployment is more than its limit. The
+	/// name of the field of the request is sent along with the error
+	ResourceRequestExceedsLimit(&'static str),
+	/// The CPU or memory limit of a deployment is more than the capacity of its
+	/// machine type. The name of the field of the limit is sent along with the
+	/// error
+	ResourceLimitExceedsMachineType(&'static str),
+	/// A CPU or memory request or limit of a deployment is zero. The name of
+	/// the field is sent along with the error
+	InvalidResourceValue(&'static str),
+	/// The phone number is not a valid number in the E.164 format for its
+	/// country
+	InvalidPhoneNumber,
+	/// The recovery method cannot be used, since this instance is not
+	/// configured to send messages to it
+	RecoveryMethodUnavailable,
+	/// The account has been locked because of too many failed sign in attempts.
+	/// The request can be retried after the number of seconds that the error
+	/// carries, which is sent in the `Retry-After` header
+	AccountLocked(u64),
+	/// The dependencies of a deployment would make it depend on itself, either
+	/// directly or through other deployments
+	DependencyCycle,
+	/// The action cannot be performed with an impersonated session, since it
+	/// is destructive or changes the security of the account
+	ImpersonationForbidden,
+	/// The signature of a signed URL is invalid, or the URL has expired
+	InvalidSignature,
+	/// A deployment cannot be scaled to more replicas than the workspace
+	/// allows. The limit of the workspace is sent along with the error
+	ReplicaLimitExceeded(u16),
+	/// The value of a DNS record is not valid for its type, or the record
+	/// conflicts with another record of the same name. The name of the
+	/// invalid field is sent along with the error
+	InvalidDnsRecord(&'static str),
+	/// The replica requested is not one of the replicas that the runner of the
+	/// deployment last reported running for it
+	ReplicaNotFound,
+	/// The image of the deployment has critical vulnerabilities, and the policy
+	/// of the workspace blocks deploying such images
+	ImageScanBlocked,
+	/// The image of the deployment could not be scanned for vulnerabilities
+	ImageScanFailed,
+	/// The image of the deployment has not been scanned for vulnerabilities
+	/// yet, and the policy of the workspace requires a scan before it can be
+	/// deployed. The image is being scanned in the background
+	ImageScanPending,
+	/// The API token does not start with the prefix of any known version of the
+	/// format of API tokens
+	UnknownApiTokenPrefix,
+	/// The API token does not have exactly a refresh token and a login ID after
+	/// its prefix
+	MalformedApiTokenSegments,
+	/// The refresh token or the login ID of the API token is not a valid UUID
+	MalformedApiTokenUuid,
+	/// The custom error page is larger than the maximum size that is allowed
+	ErrorPageTooLarge,
+	/// The custom error page is empty or is not an HTML document
+	InvalidErrorPage,
+	/// One of the IDs in the URL of the request is not valid, so the resource
+	/// that it refers to can't exist
+	InvalidPathParameter,
+	/// The user tried to approve a change to a deployment that they requested
+	/// themselves. Changes need to be approved by a different user
+	SelfApprovalNotAllowed,
+	/// None of the permissions requested for an API token are held by the user
+	/// creating it, so the token would not have any permissions
+	NoPermissionsGranted,
+	/// The cursor is older than the oldest update that is still kept, so the
+	/// updates after it can't all be returned. The client has to fetch the
+	/// current state again and poll without a cursor
+	CursorExpired,
+	/// The workspace already has as many resources of a kind as its quota
+	/// allows, so another one can't be created. The usage of the quota is sent
+	/// along with the error, so that it can be shown to the user
+	QuotaExceeded(QuotaUsage),
+}
+
+/// The usage of the quota of a workspace on a kind of resource, which is sent
+/// along with [`ErrorType::QuotaExceeded`] errors
+#[derive(
+	Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct QuotaUsage {
+	/// The kind of resource that the quota limits
+	pub resource: QuotaResource,
+	/// The number of resources of the kind that the workspace has
+	pub current: u32,
+	/// The maximum number of resources of the kind that the workspace can have
+	pub limit: u32,
+}
+
+impl QuotaUsage {
+	/// A user-friendly message describing the usage of the quota, such as
+	/// "You are using 10/10 deployments"
+	pub fn message(&self) -> String {
+		format!(
+			"You are using {}/{} {}. Delete some of them to create more",
+			self.current,
+			self.limit,
+			self.resource.plural_name()
+		)
+	}
+}
+
+/// The kinds of resources whose number in a workspace is limited by a quota
+#[derive(
+	Debug,
+	Clone,
+	Copy,
+	Default,
+	PartialEq,
+	Eq,
+	PartialOrd,
+	Ord,
+	Serialize,
+	Deserialize,
+	JsonSchema,
+	IntoStaticStr,
+	EnumIter,
+)]
+#[serde(rename_all = "camelCase")]
+#[strum(serialize_all = "camelCase")]
+pub enum QuotaResource {
+	/// The deployments of a workspace, excluding the deleted ones
+	#[default]
+	Deployment,
+	/// The runners of a workspace, excluding the deleted ones
+	Runner,
+}
+
+impl QuotaResource {
+	/// The plural name of the resource, as shown to the user
+	pub const fn plural_name(self) -> &'static str {
+		match self {
+			Self::Deployment => "deployments",
+			Self::Runner => "runners",
+		}
+	}
 }
 
 impl ErrorType {
@@ -118,7 +325,7 @@ impl ErrorType {
 			Self::UsernameUnavailable => StatusCode::CONFLICT,
 			Self::EmailUnavailable => StatusCode::CONFLICT,
 			Self::PhoneUnavailable => StatusCode::CONFLICT,
-			Self::InvalidPasswordResetToken => StatusCode::BAD_REQUEST,
+			Self::ResetTokenInvalid => StatusCode::BAD_REQUEST,
 			Self::ResourceDoesNotExist => StatusCode::NOT_FOUND,
 			Self::ResourceAlreadyExists => StatusCode::CONFLICT,
 			Self::ResourceInUse => StatusCode::UNPROCESSABLE_ENTITY,
@@ -135,6 +342,48 @@ impl ErrorType {
 			Self::RoleInUse => StatusCode::CONFLICT,
 			Self::RunnerAlreadyConnected => StatusCode::CONFLICT,
 			Self::InvalidRunnerMode => StatusCode::FORBIDDEN,
+			Self::MissingPullSecret => StatusCode::BAD_REQUEST,
+			Self::InvalidCronExpression => StatusCode::BAD_REQUEST,
+			Self::InvalidImageReference(_) => StatusCode::BAD_REQUEST,
+			Self::ServerOverloaded => StatusCode::SERVICE_UNAVAILABLE,
+			Self::VolumeMountConflict => StatusCode::CONFLICT,
+			Self::CannotScaleWithVolume => StatusCode::BAD_REQUEST,
+			Self::BuildSourceRequiresPatrRegistry => StatusCode::BAD_REQUEST,
+			Self::NoBuildSource => StatusCode::BAD_REQUEST,
+			Self::BuildAlreadyInProgress => StatusCode::CONFLICT,
+			Self::InvalidBuildStatusTransition => StatusCode::BAD_REQUEST,
+			Self::ManagedUrlRuleConflict => StatusCode::CONFLICT,
+			Self::MfaLoginTokenInvalid => StatusCode::UNAUTHORIZED,
+			Self::InvalidEmailVerificationToken => StatusCode::BAD_REQUEST,
+			Self::UnknownPermission => StatusCode::BAD_REQUEST,
+			Self::TooManySignups(_) => StatusCode::TOO_MANY_REQUESTS,
+			Self::RunnerUnreachable => StatusCode::SERVICE_UNAVAILABLE,
+			Self::ResourceModified => StatusCode::PRECONDITION_FAILED,
+			Self::ResourceRequestExceedsLimit(_) => StatusCode::BAD_REQUEST,
+			Self::ResourceLimitExceedsMachineType(_) => StatusCode::BAD_REQUEST,
+			Self::InvalidResourceValue(_) => StatusCode::BAD_REQUEST,
+			Self::InvalidPhoneNumber => StatusCode::BAD_REQUEST,
+			Self::RecoveryMethodUnavailable => StatusCode::BAD_REQUEST,
+			Self::AccountLocked(_) => StatusCode::TOO_MANY_REQUESTS,
+			Self::DependencyCycle => StatusCode::BAD_REQUEST,
+			Self::ImpersonationForbidden => StatusCode::FORBIDDEN,
+			Self::InvalidSignature => StatusCode::FORBIDDEN,
+			Self::ReplicaLimitExceeded(_) => StatusCode::BAD_REQUEST,
+			Self::InvalidDnsRecord(_) => StatusCode::BAD_REQUEST,
+			Self::ReplicaNotFound => StatusCode::NOT_FOUND,
+			Self::ImageScanBlocked => StatusCode::BAD_REQUEST,
+			Self::ImageScanFailed => StatusCode::SERVICE_UNAVAILABLE,
+			Self::ImageScanPending => StatusCode::CONFLICT,
+			Self::UnknownApiTokenPrefix => StatusCode::BAD_REQUEST,
+			Self::MalformedApiTokenSegments => StatusCode::BAD_REQUEST,
+			Self::MalformedApiTokenUuid => StatusCode::BAD_REQUEST,
+			Self::ErrorPageTooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+			Self::InvalidErrorPage => StatusCode::BAD_REQUEST,
+			Self::InvalidPathParameter => StatusCode::NOT_FOUND,
+			Self::SelfApprovalNotAllowed => StatusCode::FORBIDDEN,
+			Self::NoPermissionsGranted => StatusCode::BAD_REQUEST,
+			Self::CursorExpired => StatusCode::GONE,
+			Self::QuotaExceeded(_) => StatusCode::FORBIDDEN,
 		}
 	}
 
@@ -164,9 +413,7 @@ impl ErrorType {
 			Self::UsernameUnavailable => "An account already exists with that username",
 			Self::EmailUnavailable => "An account already exists with that email",
 			Self::PhoneUnavailable => "An account already exists with that phone number",
-			Self::InvalidPasswordResetToken => {
-				"The token provided to reset your password is not valid"
-			}
+			Self::ResetTokenInvalid => "The token provided to reset your password is not valid",
 			Self::ResourceDoesNotExist => "The resource you are trying to access does not exist",
 			Self::ResourceAlreadyExists => "Resource already exists with the given details",
 			Self::ResourceInUse => "Resource is currently in use",
@@ -183,6 +430,97 @@ impl ErrorType {
 			Self::RoleInUse => "The role is currently assigned to users and cannot be deleted",
 			Self::RunnerAlreadyConnected => "Another instance of the same runner ID is already connected",
 			Self::InvalidRunnerMode => "That operation is not allowed in the mode the runner is currently in",
+			Self::MissingPullSecret => "The image is private. Please provide a pull secret to pull the image",
+			Self::InvalidCronExpression => "The cron expression provided is invalid",
+			Self::InvalidImageReference(_) => "The image reference provided is invalid. Please check the registry, image name and tag",
+			Self::ServerOverloaded => "The server is overloaded at the moment. Please try again later",
+			Self::VolumeMountConflict => "Two volumes cannot be mounted on the same path",
+			Self::CannotScaleWithVolume => "A deployment with a volume mounted cannot be scaled beyond one replica",
+			Self::BuildSourceRequiresPatrRegistry => "Only deployments using the Patr registry can be built from source",
+			Self::NoBuildSource => "The deployment does not have a build source",
+			Self::BuildAlreadyInProgress => "A build of this deployment is already in progress",
+			Self::InvalidBuildStatusTransition => "The build cannot be moved to the given status",
+			Self::ManagedUrlRuleConflict => "The path conflicts with another managed URL on the same host",
+			Self::MfaLoginTokenInvalid => "Your sign in session has expired, please sign in again",
+			Self::InvalidEmailVerificationToken => "The verification code is invalid or has expired",
+			Self::UnknownPermission => "One or more of the permissions do not exist",
+			Self::TooManySignups(_) => "Too many accounts have been created recently. Please try again later",
+			Self::RunnerUnreachable => "The runner could not be reached. The changes have been saved, and will be applied the next time the runner reconciles. Please check that it is running and connected",
+			Self::ResourceModified => "The resource has been modified since it was last fetched. Please refresh and try again",
+			Self::ResourceRequestExceedsLimit(_) => "The requested CPU or memory cannot be more than its limit",
+			Self::ResourceLimitExceedsMachineType(_) => "The CPU or memory limit cannot be more than the capacity of the machine type",
+			Self::InvalidResourceValue(_) => "The CPU and memory requests and limits must be more than zero",
+			Self::InvalidPhoneNumber => "Invalid phone number",
+			Self::RecoveryMethodUnavailable => "This recovery method is not supported by this instance",
+			Self::AccountLocked(_) => "Too many failed sign in attempts. Please try again later",
+			Self::DependencyCycle => "A deployment cannot depend on itself, directly or through other deployments",
+			Self::ImpersonationForbidden => "This action is not allowed while impersonating a user",
+			Self::InvalidSignature => "The URL is invalid or has expired",
+			Self::ReplicaLimitExceeded(_) => "The deployment cannot be scaled beyond the maximum number of replicas allowed for the workspace",
+			Self::InvalidDnsRecord(_) => "The DNS record is not valid for its type, or conflicts with an existing record",
+			Self::ReplicaNotFound => "The replica could not be found for this deployment",
+			Self::ImageScanBlocked => "The image has critical vulnerabilities and cannot be deployed in this workspace",
+			Self::ImageScanFailed => "The image could not be scanned for vulnerabilities",
+			Self::ImageScanPending => "The image is being scanned for vulnerabilities. Please try again once the scan has finished",
+			Self::UnknownApiTokenPrefix => "The API token does not start with a known prefix",
+			Self::MalformedApiTokenSegments => "The API token must have a refresh token and a login ID after its prefix",
+			Self::MalformedApiTokenUuid => "The refresh token or the login ID of the API token is not a valid UUID",
+			Self::ErrorPageTooLarge => "The error page is larger than the maximum size allowed",
+			Self::InvalidErrorPage => "The error page must be an HTML document",
+			Self::InvalidPathParameter => "The resource you are trying to access does not exist",
+			Self::SelfApprovalNotAllowed => "A change must be approved by a different user than the one who requested it",
+			Self::NoPermissionsGranted => "You do not have any of the permissions requested for the API token",
+			Self::CursorExpired => "Some of the updates after the cursor are no longer available. Please refresh and try again",
+			Self::QuotaExceeded(_) => "The workspace has reached its quota for this kind of resource",
+		}
+	}
+
+	/// The usage of the quota that was exceeded, if this is an
+	/// [`ErrorType::QuotaExceeded`] error
+	pub fn quota_usage(&self) -> Option<QuotaUsage> {
+		match self {
+			Self::QuotaExceeded(usage) => Some(*usage),
+			_ => None,
+		}
+	}
+
+	/// The name of the field of the request that caused the error, for the
+	/// errors that are caused by a specific field, such as the
+	/// `resources.cpuLimit` of an
+	/// [`ErrorType::ResourceLimitExceedsMachineType`] error
+	pub fn field(&self) -> Option<&'static str> {
+		match self {
+			Self::ResourceRequestExceedsLimit(field) |
+			Self::ResourceLimitExceedsMachineType(field) |
+			Self::InvalidResourceValue(field) |
+			Self::InvalidDnsRecord(field) => Some(*field),
+			_ => None,
+		}
+	}
+
+	/// The number of seconds after which the request can be retried, for the
+	/// errors that are only temporary. This is sent in the `Retry-After`
+	/// header of the response
+	pub fn retry_after(&self) -> Option<u64> {
+		match self {
+			Self::TooManySignups(seconds) | Self::AccountLocked(seconds) => Some(*seconds),
+			_ => None,
+		}
+	}
+
+	/// A user-friendly message describing the error, including the details
+	/// that the error carries, such as the usage of the quota of an
+	/// [`ErrorType::QuotaExceeded`] error
+	pub fn detailed_message(&self) -> String {
+		match self {
+			Self::QuotaExceeded(usage) => usage.message(),
+			Self::ReplicaLimitExceeded(limit) => {
+				format!("Deployments in this workspace can be scaled to at most {limit} replicas")
+			}
+			Self::InvalidImageReference(part) => {
+				format!("The {part} of the image reference is invalid")
+			}
+			_ => self.message().into(),
 		}
 	}
 
@@ -203,6 +541,35 @@ impl FromStr for ErrorType {
 	}
 }
 
+impl TryFrom<String> for ErrorType {
+	type Error = ErrorType;
+
+	fn try_from(code: String) -> Result<Self, Self::Error> {
+		code.parse()
+	}
+}
+
+/// Errors are serialized as their code, so their schema is an enum of all the
+/// codes
+impl JsonSchema for ErrorType {
+	fn schema_name() -> String {
+		"ErrorType".to_string()
+	}
+
+	fn json_schema(_: &mut SchemaGenerator) -> Schema {
+		SchemaObject {
+			instance_type: Some(InstanceType::String.into()),
+			enum_values: Some(
+				Self::iter()
+					.map(|error_type| error_type.to_string().into())
+					.collect(),
+			),
+			..Default::default()
+		}
+		.into()
+	}
+}
+
 impl<Error> From<Error> for ErrorType
 where
 	Error: StdError + Send + Sync + 'static,
@@ -216,3 +583,112 @@ where
 		Self::InternalServerError
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn errors_are_serialized_as_their_code() {
+		let quota_exceeded = ErrorType::QuotaExceeded(QuotaUsage {
+			resource: QuotaResource::Runner,
+			current: 10,
+			limit: 10,
+		});
+
+		assert_eq!(
+			serde_json::to_value(ErrorType::WrongParameters).unwrap(),
+			"wrongParameters"
+		);
+		assert_eq!(
+			serde_json::to_value(quota_exceeded).unwrap(),
+			"quotaExceeded"
+		);
+		assert_eq!(quota_exceeded.to_string(), "quotaExceeded");
+	}
+
+	#[test]
+	fn errors_are_parsed_back_from_their_code() {
+		assert_eq!(
+			serde_json::from_value::<ErrorType>("wrongParameters".into()).unwrap(),
+			ErrorType::WrongParameters
+		);
+		assert_eq!(
+			"quotaExceeded".parse::<ErrorType>(),
+			Ok(ErrorType::QuotaExceeded(QuotaUsage::default()))
+		);
+		assert!(serde_json::from_value::<ErrorType>("notAnError".into()).is_err());
+	}
+
+	#[test]
+	fn quota_usage_is_shown_to_the_user() {
+		let usage = QuotaUsage {
+			resource: QuotaResource::Deployment,
+			current: 10,
+			limit: 10,
+		};
+
+		assert!(
+			usage.message().contains("10/10 deployments"),
+			"{}",
+			usage.message()
+		);
+		assert_eq!(ErrorType::QuotaExceeded(usage).quota_usage(), Some(usage));
+		assert_eq!(ErrorType::WrongParameters.quota_usage(), None);
+		assert_eq!(
+			ErrorType::QuotaExceeded(usage).detailed_message(),
+			usage.message()
+		);
+	}
+
+	#[test]
+	fn temporary_errors_can_be_retried() {
+		assert_eq!(ErrorType::AccountLocked(120).retry_after(), Some(120));
+		assert_eq!(ErrorType::TooManySignups(60).retry_after(), Some(60));
+		assert_eq!(ErrorType::WrongParameters.retry_after(), None);
+	}
+
+	#[test]
+	fn replica_limit_is_shown_to_the_user() {
+		let error = ErrorType::ReplicaLimitExceeded(20);
+
+		assert_eq!(error.to_string(), "replicaLimitExceeded");
+		assert!(
+			error.detailed_message().contains("at most 20 replicas"),
+			"{}",
+			error.detailed_message()
+		);
+		assert_eq!(
+			"replicaLimitExceeded".parse::<ErrorType>(),
+			Ok(ErrorType::ReplicaLimitExceeded(0))
+		);
+	}
+
+	#[test]
+	fn invalid_part_of_an_image_reference_is_shown_to_the_user() {
+		let error = ErrorType::InvalidImageReference("tag");
+
+		assert_eq!(error.to_string(), "invalidImageReference");
+		assert_eq!(
+			error.detailed_message(),
+			"The tag of the image reference is invalid"
+		);
+		assert_eq!(
+			"invalidImageReference".parse::<ErrorType>(),
+			Ok(ErrorType::InvalidImageReference(""))
+		);
+	}
+
+	#[test]
+	fn invalid_fields_are_sent_along_with_the_error() {
+		let error = ErrorType::ResourceLimitExceedsMachineType("resources.cpuLimit");
+
+		assert_eq!(error.to_string(), "resourceLimitExceedsMachineType");
+		assert_eq!(error.field(), Some("resources.cpuLimit"));
+		assert_eq!(ErrorType::WrongParameters.field(), None);
+
+		let error = ErrorType::InvalidDnsRecord("priority");
+		assert_eq!(error.to_string(), "invalidDnsRecord");
+		assert_eq!(error.field(), Some("priority"));
+	}
+}
@@ -45,6 +45,7 @@ pub mod prelude {
 			BearerToken,
 			GeoLocation,
 			ListOrder,
+			ListOrderBy,
 			LoginId,
 			OneOrMore,
 			Paginated,
@@ -33,6 +33,7 @@ use crate::prelude::*;
 	Deserialize,
 	VariantNames,
 )]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
 #[strum(serialize_all = "camelCase")]
 #[serde(rename_all = "camelCase")]
 pub enum ResourceType {
@@ -103,6 +104,7 @@ pub enum ResourceType {
 	Deserialize,
 	VariantNames,
 )]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
 #[strum(serialize_all = "camelCase")]
 #[serde(rename_all = "camelCase")]
 pub enum DatabasePermission {
@@ -141,6 +143,7 @@ pub enum DatabasePermission {
 	Deserialize,
 	VariantNames,
 )]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
 #[strum(serialize_all = "camelCase")]
 #[serde(rename_all = "camelCase")]
 pub enum DnsRecordPermission {
@@ -173,6 +176,7 @@ pub enum DnsRecordPermission {
 	Deserialize,
 	VariantNames,
 )]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
 #[strum(serialize_all = "camelCase")]
 #[serde(rename_all = "camelCase")]
 pub enum DomainPermission {
@@ -210,6 +214,7 @@ pub enum DomainPermission {
 	Deserialize,
 	VariantNames,
 )]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
 #[strum(serialize_all = "camelCase")]
 #[serde(rename_all = "camelCase")]
 pub enum ManagedURLPermission {
@@ -251,6 +256,7 @@ pub enum ManagedURLPermission {
 	Deserialize,
 	VariantNames,
 )]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
 #[strum(serialize_all = "camelCase")]
 #[serde(rename_all = "camelCase")]
 pub enum RunnerPermission {
@@ -291,6 +297,7 @@ pub enum RunnerPermission {
 	Deserialize,
 	VariantNames,
 )]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
 #[strum(serialize_all = "camelCase")]
 #[serde(rename_all = "camelCase")]
 pub enum DeploymentPermission {
@@ -334,6 +341,7 @@ pub enum DeploymentPermission {
 	Deserialize,
 	VariantNames,
 )]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
 #[strum(serialize_all = "camelCase")]
 #[serde(rename_all = "camelCase")]
 pub enum ContainerRegistryRepositoryPermission {
@@ -382,6 +390,7 @@ pub enum ContainerRegistryRepositoryPermission {
 	Deserialize,
 	VariantNames,
 )]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
 #[strum(serialize_all = "camelCase")]
 #[serde(rename_all = "camelCase")]
 pub enum StaticSitePermission {
@@ -431,6 +440,7 @@ pub enum StaticSitePermission {
 	Deserialize,
 	VariantNames,
 )]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
 #[strum(serialize_all = "camelCase")]
 #[serde(rename_all = "camelCase")]
 pub enum SecretPermission {
@@ -464,6 +474,7 @@ pub enum SecretPermission {
 	Deserialize,
 	VariantNames,
 )]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
 #[strum(serialize_all = "camelCase")]
 #[serde(rename_all = "camelCase")]
 pub enum BillingPermission {
@@ -496,6 +507,7 @@ pub enum BillingPermission {
 	Deserialize,
 	VariantNames,
 )]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
 #[strum(serialize_all = "camelCase")]
 #[serde(rename_all = "camelCase")]
 pub enum VolumePermission {
@@ -528,6 +540,7 @@ pub enum VolumePermission {
 	VariantNames,
 	RecursiveEnumIter,
 )]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
 #[strum(serialize_all = "camelCase")]
 #[serde(rename_all = "camelCase")]
 pub enum Permission {
@@ -574,6 +587,10 @@ pub enum Permission {
 	/// This permission allows the user to edit a workspace, but not delete it.
 	/// Only the super admin of a workspace can delete it.
 	EditWorkspace,
+	/// View the activity feed of a workspace, which lists everything that
+	/// happened in the workspace, such as changes to deployments, API tokens
+	/// and members.
+	ViewActivity,
 }
 
 impl Permission {
@@ -600,10 +617,55 @@ impl Permission {
 			Permission::ViewRoles => self.get_documentation(),
 			Permission::ModifyRoles => self.get_documentation(),
 			Permission::EditWorkspace => self.get_documentation(),
+			Permission::ViewActivity => self.get_documentation(),
 		}
 		.expect("Documentation not found")
 		.to_string()
 	}
+
+	/// Returns a human readable category of the permission, which can be used
+	/// to group permissions together when displaying them to the user.
+	pub fn category(&self) -> &'static str {
+		match self {
+			Permission::Domain(_) => "Domains",
+			Permission::DnsRecord(_) => "DNS Records",
+			Permission::Deployment(_) => "Deployments",
+			Permission::Volume(_) => "Volumes",
+			Permission::ContainerRegistryRepository(_) => "Container Registry",
+			Permission::Billing(_) => "Billing",
+			Permission::ManagedURL(_) => "Managed URLs",
+			Permission::Runner(_) => "Runners",
+			Permission::Database(_) => "Databases",
+			Permission::StaticSite(_) => "Static Sites",
+			Permission::Secret(_) => "Secrets",
+			Permission::ViewRoles | Permission::ModifyRoles => "Roles",
+			Permission::EditWorkspace | Permission::ViewActivity => "Workspace",
+		}
+	}
+
+	/// Returns the type of resource that the permission is granted on. Billing,
+	/// role and workspace permissions are granted on the workspace itself.
+	pub fn resource_type(&self) -> ResourceType {
+		match self {
+			Permission::Domain(_) => ResourceType::Domain,
+			Permission::DnsRecord(_) => ResourceType::DnsRecord,
+			Permission::Deployment(_) => ResourceType::Deployment,
+			Permission::Volume(_) => ResourceType::Volume,
+			Permission::ContainerRegistryRepository(_) => {
+				ResourceType::ContainerRegistryRepository
+			}
+			Permission::ManagedURL(_) => ResourceType::ManagedURL,
+			Permission::Runner(_) => ResourceType::Runner,
+			Permission::Database(_) => ResourceType::Database,
+			Permission::StaticSite(_) => ResourceType::StaticSite,
+			Permission::Secret(_) => ResourceType::Secret,
+			Permission::Billing(_) |
+			Permission::ViewRoles |
+			Permission::ModifyRoles |
+			Permission::EditWorkspace |
+			Permission::ViewActivity => ResourceType::Workspace,
+		}
+	}
 }
 
 impl FromStr for Permission {
@@ -629,6 +691,7 @@ impl FromStr for Permission {
 			"viewRoles" => Self::ViewRoles,
 			"modifyRoles" => Self::ModifyRoles,
 			"editWorkspace" => Self::EditWorkspace,
+			"viewActivity" => Self::ViewActivity,
 			_ => return Err(strum::ParseError::VariantNotFound),
 		})
 	}
@@ -679,6 +742,7 @@ where
 
 /// Represents the kind of permission that is granted on a workspace.
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase", tag = "type")]
 pub enum WorkspacePermission {
 	/// The user is the super admin of the workspace.
@@ -702,6 +766,23 @@ impl WorkspacePermission {
 		matches!(self, WorkspacePermission::Member { .. })
 	}
 
+	/// Returns true if the current [`WorkspacePermission`] instance allows the
+	/// given permission ID on the given resource ID. This uses the same
+	/// include / exclude logic as [`WorkspacePermission::is_superset_of`].
+	///
+	/// The permissions don't know which resources belong to the workspace, so
+	/// an exclude permission allows any resource that isn't excluded, even one
+	/// of another workspace. The caller must make sure that the resource
+	/// belongs to the workspace that these permissions are for.
+	pub fn has_permission_on_resource(&self, permission_id: Uuid, resource_id: Uuid) -> bool {
+		self.is_superset_of(&WorkspacePermission::Member {
+			permissions: BTreeMap::from([(
+				permission_id,
+				ResourcePermissionType::Include(BTreeSet::from([resource_id])),
+			)]),
+		})
+	}
+
 	/// Returns true if the current [`WorkspacePermission`] instance has more or
 	/// equal permissions than the other [`WorkspacePermission`] instance.
 	pub fn is_superset_of(&self, other: &WorkspacePermission) -> bool {
@@ -793,10 +874,43 @@ impl WorkspacePermission {
 				}),
 		}
 	}
+
+	/// Returns the permissions that both the current [`WorkspacePermission`]
+	/// instance and the other [`WorkspacePermission`] instance allow, or
+	/// [`None`] if they don't have any permissions in common. The intersection
+	/// is always a subset of both of them, as per
+	/// [`WorkspacePermission::is_superset_of`].
+	pub fn intersection(&self, other: &WorkspacePermission) -> Option<WorkspacePermission> {
+		match (self, other) {
+			(Self::SuperAdmin, other) => Some(other.clone()),
+			(this, Self::SuperAdmin) => Some(this.clone()),
+			(
+				Self::Member {
+					permissions: self_permissions,
+				},
+				Self::Member {
+					permissions: other_permissions,
+				},
+			) => {
+				let permissions = self_permissions
+					.iter()
+					.filter_map(|(permission_id, self_resources)| {
+						let other_resources = other_permissions.get(permission_id)?;
+						self_resources
+							.intersection(other_resources)
+							.map(|resources| (*permission_id, resources))
+					})
+					.collect::<BTreeMap<_, _>>();
+
+				(!permissions.is_empty()).then_some(Self::Member { permissions })
+			}
+		}
+	}
 }
 
 /// Represents the type of permission that is granted on a set of Resource IDs.
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, EnumDiscriminants)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
 #[serde(
 	rename_all = "camelCase",
 	tag = "permissionType",
@@ -828,6 +942,29 @@ pub enum ResourcePermissionType {
 }
 
 impl ResourcePermissionType {
+	/// Returns the resources that both the current [`ResourcePermissionType`]
+	/// instance and the other [`ResourcePermissionType`] instance allow, or
+	/// [`None`] if they don't allow any resource in common.
+	pub fn intersection(&self, other: &ResourcePermissionType) -> Option<ResourcePermissionType> {
+		let intersection = match (self, other) {
+			(Self::Include(self_resources), Self::Include(other_resources)) => {
+				Self::Include(self_resources & other_resources)
+			}
+			(Self::Include(included), Self::Exclude(excluded)) |
+			(Self::Exclude(excluded), Self::Include(included)) => {
+				Self::Include(included - excluded)
+			}
+			(Self::Exclude(self_resources), Self::Exclude(other_resources)) => {
+				Self::Exclude(self_resources | other_resources)
+			}
+		};
+
+		match &intersection {
+			Self::Include(resources) if resources.is_empty() => None,
+			_ => Some(intersection),
+		}
+	}
+
 	/// Inserts a new resource ID into the current [`ResourcePermissionType`]
 	/// instance based on the type of permission.
 	pub fn insert(&mut self, resource_id: Uuid) {
@@ -841,3 +978,143 @@ impl ResourcePermissionType {
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn member(
+		permissions: impl IntoIterator<Item = (Uuid, ResourcePermissionType)>,
+	) -> WorkspacePermission {
+		WorkspacePermission::Member {
+			permissions: permissions.into_iter().collect(),
+		}
+	}
+
+	fn resources<const N: usize>(ids: [Uuid; N]) -> BTreeSet<Uuid> {
+		BTreeSet::from(ids)
+	}
+
+	#[test]
+	fn resource_intersections_keep_only_the_common_resources() {
+		let (a, b, c) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+
+		assert_eq!(
+			ResourcePermissionType::Include(resources([a, b]))
+				.intersection(&ResourcePermissionType::Include(resources([b, c]))),
+			Some(ResourcePermissionType::Include(resources([b])))
+		);
+		assert_eq!(
+			ResourcePermissionType::Include(resources([a, b]))
+				.intersection(&ResourcePermissionType::Exclude(resources([a]))),
+			Some(ResourcePermissionType::Include(resources([b])))
+		);
+		assert_eq!(
+			ResourcePermissionType::Exclude(resources([a]))
+				.intersection(&ResourcePermissionType::Include(resources([a, c]))),
+			Some(ResourcePermissionType::Include(resources([c])))
+		);
+		assert_eq!(
+			ResourcePermissionType::Exclude(resources([a]))
+				.intersection(&ResourcePermissionType::Exclude(resources([b]))),
+			Some(ResourcePermissionType::Exclude(resources([a, b])))
+		);
+	}
+
+	#[test]
+	fn resource_intersections_without_common_resources_are_empty() {
+		let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+
+		assert_eq!(
+			ResourcePermissionType::Include(resources([a]))
+				.intersection(&ResourcePermissionType::Include(resources([b]))),
+			None
+		);
+		assert_eq!(
+			ResourcePermissionType::Include(resources([a]))
+				.intersection(&ResourcePermissionType::Exclude(resources([a]))),
+			None
+		);
+	}
+
+	#[test]
+	fn workspace_intersections_with_a_super_admin_keep_the_other_permissions() {
+		let permission = member([(
+			Uuid::new_v4(),
+			ResourcePermissionType::Exclude(BTreeSet::new()),
+		)]);
+
+		assert_eq!(
+			WorkspacePermission::SuperAdmin.intersection(&permission),
+			Some(permission.clone())
+		);
+		assert_eq!(
+			permission.intersection(&WorkspacePermission::SuperAdmin),
+			Some(permission)
+		);
+		assert_eq!(
+			WorkspacePermission::SuperAdmin.intersection(&WorkspacePermission::SuperAdmin),
+			Some(WorkspacePermission::SuperAdmin)
+		);
+	}
+
+	#[test]
+	fn workspace_intersections_drop_the_permissions_that_are_not_held() {
+		let (held, not_held, resource) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+		let creator = member([(held, ResourcePermissionType::Include(resources([resource])))]);
+		let requested = member([
+			(held, ResourcePermissionType::Exclude(BTreeSet::new())),
+			(not_held, ResourcePermissionType::Exclude(BTreeSet::new())),
+		]);
+
+		let granted = creator.intersection(&requested).unwrap();
+
+		assert_eq!(
+			granted,
+			member([(held, ResourcePermissionType::Include(resources([resource])))])
+		);
+		assert!(creator.is_superset_of(&granted));
+		assert!(requested.is_superset_of(&granted));
+	}
+
+	#[test]
+	fn workspace_intersections_without_common_permissions_are_empty() {
+		let creator = member([(
+			Uuid::new_v4(),
+			ResourcePermissionType::Exclude(BTreeSet::new()),
+		)]);
+		let requested = member([(
+			Uuid::new_v4(),
+			ResourcePermissionType::Exclude(BTreeSet::new()),
+		)]);
+
+		assert_eq!(creator.intersection(&requested), None);
+	}
+
+	#[test]
+	fn included_resources_are_the_only_ones_permitted() {
+		let (permission, included, other) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+		let workspace = member([(
+			permission,
+			ResourcePermissionType::Include(resources([included])),
+		)]);
+
+		assert!(workspace.has_permission_on_resource(permission, included));
+		assert!(!workspace.has_permission_on_resource(permission, other));
+		assert!(!workspace.has_permission_on_resource(Uuid::new_v4(), included));
+	}
+
+	#[test]
+	fn excluded_resources_are_the_only_ones_denied() {
+		let (permission, excluded, other) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+		let workspace = member([(
+			permission,
+			ResourcePermissionType::Exclude(resources([excluded])),
+		)]);
+
+		assert!(!workspace.has_permission_on_resource(permission, excluded));
+		assert!(workspace.has_permission_on_resource(permission, other));
+		assert!(!workspace.has_permission_on_resource(Uuid::new_v4(), other));
+		assert!(WorkspacePermission::SuperAdmin.has_permission_on_resource(permission, excluded));
+	}
+}
@@ -1,7 +1,13 @@
 use std::fmt::Display;
 
-use axum::{http::StatusCode, response::IntoResponse, Json};
+use axum::{
+	http::{header, HeaderValue, StatusCode},
+	response::IntoResponse,
+	Extension,
+	Json,
+};
 use preprocess::Preprocessable;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use typed_builder::TypedBuilder;
 
@@ -74,7 +80,7 @@ where
 /// manually.
 ///
 /// Use [`ApiSuccessResponse`] to create a success response.
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ApiSuccessResponseBody<T> {
 	/// Whether the request was successful or not. This is always true.
@@ -124,8 +130,10 @@ impl ApiErrorResponse {
 			status_code: error.default_status_code(),
 			body: ApiErrorResponseBody {
 				success: False,
-				message: error.message().into(),
-				error,
+				message: error.detailed_message(),
+				quota: error.quota_usage(),
+				field: error.field().map(String::from),
+				code: error,
 			},
 		}
 	}
@@ -137,8 +145,10 @@ impl ApiErrorResponse {
 			status_code: error.default_status_code(),
 			body: ApiErrorResponseBody {
 				success: False,
-				error,
+				code: error,
 				message: message.to_string(),
+				quota: error.quota_usage(),
+				field: error.field().map(String::from),
 			},
 		}
 	}
@@ -153,7 +163,32 @@ impl ApiErrorResponse {
 
 impl IntoResponse for ApiErrorResponse {
 	fn into_response(self) -> axum::response::Response {
-		(self.status_code, Json(self.body)).into_response()
+		// Temporary errors tell the client when the request can be retried
+		let retry_after = self
+			.body
+			.code
+			.retry_after()
+			.map(|seconds| [(header::RETRY_AFTER, HeaderValue::from(seconds))]);
+
+		// The error is added to the extensions so that the layers wrapping the
+		// endpoint (such as the access logger) know which error occurred
+		(
+			self.status_code,
+			retry_after,
+			Extension(self.body.code),
+			Json(self.body),
+		)
+			.into_response()
+	}
+}
+
+/// Every [`ErrorType`] is converted to a response through [`ApiErrorResponse`],
+/// so that the status code is always derived from the variant, and the body is
+/// always an [`ApiErrorResponseBody`], containing the code of the error (that
+/// the client can match on) and a user-friendly message.
+impl IntoResponse for ErrorType {
+	fn into_response(self) -> axum::response::Response {
+		ApiErrorResponse::error(self).into_response()
 	}
 }
 
@@ -171,15 +206,25 @@ where
 /// manually.
 ///
 /// Use [`ApiErrorResponse`] to create an error response.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ApiErrorResponseBody {
 	/// Whether the request was successful or not. This is always false.
 	pub success: False,
-	/// The error type of the response.
-	pub error: ErrorType,
+	/// The error type of the response, as a code that the client can match on.
+	pub code: ErrorType,
 	/// A user-friendly message describing the error.
 	pub message: String,
+	/// The usage of the quota that was exceeded, for
+	/// [`ErrorType::QuotaExceeded`] errors. Its fields are sent alongside the
+	/// error code, and are left out for any other error.
+	#[serde(flatten)]
+	pub quota: Option<QuotaUsage>,
+	/// The name of the field of the request that caused the error, such as
+	/// `resources.cpuLimit`, for the errors that are caused by a specific
+	/// field. This is left out for any other error.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub field: Option<String>,
 }
 
 /// This struct represents the JSON body of a response from the API.
@@ -196,3 +241,95 @@ pub enum ApiResponseBody<T> {
 	/// Error response
 	Error(ApiErrorResponseBody),
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn errors_map_to_their_status_codes() {
+		for (error, status_code) in [
+			(ErrorType::WorkspaceNotEmpty, StatusCode::FAILED_DEPENDENCY),
+			(ErrorType::Unauthorized, StatusCode::UNAUTHORIZED),
+			(ErrorType::ResourceDoesNotExist, StatusCode::NOT_FOUND),
+			(
+				ErrorType::InternalServerError,
+				StatusCode::INTERNAL_SERVER_ERROR,
+			),
+		] {
+			let response = error.into_response();
+			assert_eq!(response.status(), status_code, "for error {error}");
+			assert_eq!(response.extensions().get::<ErrorType>(), Some(&error));
+		}
+	}
+
+	#[test]
+	fn error_body_has_code_and_message() {
+		let body = serde_json::to_value(ApiErrorResponse::error(ErrorType::WorkspaceNotEmpty).body)
+			.unwrap();
+		let message: String = ErrorType::WorkspaceNotEmpty.message().into();
+
+		assert_eq!(
+			body,
+			serde_json::json!({
+				"success": false,
+				"code": "workspaceNotEmpty",
+				"message": message,
+			})
+		);
+	}
+
+	#[test]
+	fn quota_exceeded_error_body_has_the_usage_of_the_quota() {
+		let error = ErrorType::QuotaExceeded(QuotaUsage {
+			resource: QuotaResource::Deployment,
+			current: 10,
+			limit: 10,
+		});
+		let body = serde_json::to_value(ApiErrorResponse::error(error).body).unwrap();
+
+		assert_eq!(body["code"], "quotaExceeded");
+		assert_eq!(body["resource"], "deployment");
+		assert_eq!(body["current"], 10);
+		assert_eq!(body["limit"], 10);
+		assert!(body["message"]
+			.as_str()
+			.unwrap()
+			.contains("10/10 deployments"));
+
+		let body = serde_json::from_value::<ApiErrorResponseBody>(body).unwrap();
+		assert_eq!(body.quota, error.quota_usage());
+	}
+
+	#[test]
+	fn error_body_has_the_field_that_caused_the_error() {
+		let error = ErrorType::ResourceRequestExceedsLimit("resources.memoryRequest");
+		let body = serde_json::to_value(ApiErrorResponse::error(error).body).unwrap();
+
+		assert_eq!(body["code"], "resourceRequestExceedsLimit");
+		assert_eq!(body["field"], "resources.memoryRequest");
+
+		let body = serde_json::from_value::<ApiErrorResponseBody>(body).unwrap();
+		assert_eq!(body.field.as_deref(), Some("resources.memoryRequest"));
+		assert_eq!(body.quota, None);
+	}
+
+	#[test]
+	fn temporary_errors_are_sent_with_retry_after() {
+		let response = ErrorType::AccountLocked(120).into_response();
+		assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+		assert_eq!(response.headers()[header::RETRY_AFTER], "120");
+
+		let response = ErrorType::WrongParameters.into_response();
+		assert!(!response.headers().contains_key(header::RETRY_AFTER));
+	}
+
+	#[test]
+	fn error_body_with_message_keeps_the_code() {
+		let response = ApiErrorResponse::error_with_message(ErrorType::Unauthorized, "Nope");
+
+		assert_eq!(response.status_code, StatusCode::UNAUTHORIZED);
+		assert_eq!(response.body.code, ErrorType::Unauthorized);
+		assert_eq!(response.body.message, "Nope");
+	}
+}
@@ -25,4 +25,9 @@ pub struct RequestUserData {
 	pub login_id: Uuid,
 	/// The permissions that the user has on all workspaces.
 	pub permissions: BTreeMap<Uuid, WorkspacePermission>,
+	/// The userId of the support operator that is impersonating the user, if
+	/// the request is made with an impersonated session.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[builder(default)]
+	pub impersonated_by: Option<Uuid>,
 }
@@ -0,0 +1,161 @@
+use std::fmt::Display;
+
+use crate::prelude::*;
+
+/// The prefix of API tokens in the first version of their format, which is
+/// `patrv1.<refresh_token>.<login_id>`
+pub const API_TOKEN_PREFIX_V1: &str = "patrv1.";
+
+/// The separator between the refresh token and the login ID of an API token
+const API_TOKEN_SEPARATOR: char = '.';
+
+/// The versions of the format of API tokens. Each version is identified by the
+/// prefix of the token, so that the format can be changed without breaking the
+/// tokens that have already been issued.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ApiTokenVersion {
+	/// `patrv1.<refresh_token>.<login_id>`
+	V1,
+}
+
+impl ApiTokenVersion {
+	/// All the versions of the format that are accepted, the latest last
+	pub const ALL: [Self; 1] = [Self::V1];
+	/// The version that new API tokens are issued in
+	pub const LATEST: Self = Self::V1;
+
+	/// The prefix that identifies API tokens of this version
+	pub const fn prefix(self) -> &'static str {
+		match self {
+			Self::V1 => API_TOKEN_PREFIX_V1,
+		}
+	}
+}
+
+/// An API token, split into its parts. The refresh token is the secret part of
+/// the token, and the login ID identifies the token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ApiToken {
+	/// The version of the format of the token
+	pub version: ApiTokenVersion,
+	/// The secret part of the token, which is verified against its hash
+	pub refresh_token: Uuid,
+	/// The login ID of the token
+	pub login_id: Uuid,
+}
+
+impl ApiToken {
+	/// Creates a new API token in the latest version of the format
+	pub const fn new(refresh_token: Uuid, login_id: Uuid) -> Self {
+		Self {
+			version: ApiTokenVersion::LATEST,
+			refresh_token,
+			login_id,
+		}
+	}
+
+	/// Parses an API token, returning an error that describes exactly how the
+	/// token is malformed, if it is:
+	/// - [`ErrorType::UnknownApiTokenPrefix`] if the token doesn't start with
+	///   the prefix of any version of the format
+	/// - [`ErrorType::MalformedApiTokenSegments`] if the token doesn't have
+	///   exactly a refresh token and a login ID after the prefix
+	/// - [`ErrorType::MalformedApiTokenUuid`] if the refresh token or the login
+	///   ID is not a valid UUID
+	pub fn parse(token: &str) -> Result<Self, ErrorType> {
+		let (version, rest) = ApiTokenVersion::ALL
+			.into_iter()
+			.find_map(|version| {
+				token
+					.strip_prefix(version.prefix())
+					.map(|rest| (version, rest))
+			})
+			.ok_or(ErrorType::UnknownApiTokenPrefix)?;
+
+		let mut segments = rest.split(API_TOKEN_SEPARATOR);
+		let (Some(refresh_token), Some(login_id), None) =
+			(segments.next(), segments.next(), segments.next())
+		else {
+			return Err(ErrorType::MalformedApiTokenSegments);
+		};
+
+		Ok(Self {
+			version,
+			refresh_token: Uuid::parse_str(refresh_token)
+				.map_err(|_| ErrorType::MalformedApiTokenUuid)?,
+			login_id: Uuid::parse_str(login_id).map_err(|_| ErrorType::MalformedApiTokenUuid)?,
+		})
+	}
+}
+
+impl Display for ApiToken {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(
+			f,
+			"{}{}{}{}",
+			self.version.prefix(),
+			self.refresh_token,
+			API_TOKEN_SEPARATOR,
+			self.login_id
+		)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn tokens_are_parsed_back_from_their_display() {
+		let token = ApiToken::new(Uuid::new_v4(), Uuid::new_v4());
+		let displayed = token.to_string();
+
+		assert!(displayed.starts_with(API_TOKEN_PREFIX_V1));
+		assert_eq!(ApiToken::parse(&displayed), Ok(token));
+	}
+
+	#[test]
+	fn tokens_without_a_known_prefix_are_rejected() {
+		let token = format!("patrv0.{}.{}", Uuid::new_v4(), Uuid::new_v4());
+
+		assert_eq!(
+			ApiToken::parse(&token),
+			Err(ErrorType::UnknownApiTokenPrefix)
+		);
+		assert_eq!(ApiToken::parse(""), Err(ErrorType::UnknownApiTokenPrefix));
+	}
+
+	#[test]
+	fn tokens_with_the_wrong_number_of_segments_are_rejected() {
+		let (refresh_token, login_id) = (Uuid::new_v4(), Uuid::new_v4());
+
+		for token in [
+			format!("patrv1.{refresh_token}"),
+			format!("patrv1.{refresh_token}.{login_id}.{login_id}"),
+			format!("patrv1.{refresh_token}.{login_id}."),
+		] {
+			assert_eq!(
+				ApiToken::parse(&token),
+				Err(ErrorType::MalformedApiTokenSegments),
+				"{token}"
+			);
+		}
+	}
+
+	#[test]
+	fn tokens_with_invalid_uuids_are_rejected() {
+		let uuid = Uuid::new_v4();
+
+		for token in [
+			format!("patrv1.not-a-uuid.{uuid}"),
+			format!("patrv1.{uuid}.not-a-uuid"),
+			format!("patrv1..{uuid}"),
+		] {
+			assert_eq!(
+				ApiToken::parse(&token),
+				Err(ErrorType::MalformedApiTokenUuid),
+				"{token}"
+			);
+		}
+	}
+}
@@ -10,8 +10,10 @@ use serde::{de::Error, Serialize};
 /// A wrapper around a `Vec<u8>` that implements `Display` and `Serialize` to
 /// encode the data as base64. Mostly used for config mount values.
 #[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, JsonSchema)]
+#[schemars(transparent)]
 pub struct Base64String {
 	/// The data that is being wrapped.
+	#[schemars(with = "String")]
 	data: Vec<u8>,
 }
 
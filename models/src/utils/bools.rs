@@ -1,5 +1,10 @@
 use std::ops::Deref;
 
+use schemars::{
+	gen::SchemaGenerator,
+	schema::{InstanceType, Schema, SchemaObject},
+	JsonSchema,
+};
 use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
 
 /// A type that can be used to represent a constant `false` boolean.
@@ -98,6 +103,44 @@ impl AsRef<bool> for False {
 	}
 }
 
+impl JsonSchema for True {
+	fn is_referenceable() -> bool {
+		false
+	}
+
+	fn schema_name() -> String {
+		"True".to_string()
+	}
+
+	fn json_schema(_: &mut SchemaGenerator) -> Schema {
+		constant_bool_schema(true)
+	}
+}
+
+impl JsonSchema for False {
+	fn is_referenceable() -> bool {
+		false
+	}
+
+	fn schema_name() -> String {
+		"False".to_string()
+	}
+
+	fn json_schema(_: &mut SchemaGenerator) -> Schema {
+		constant_bool_schema(false)
+	}
+}
+
+/// The schema of a boolean that can only have the given value
+fn constant_bool_schema(value: bool) -> Schema {
+	SchemaObject {
+		instance_type: Some(InstanceType::Boolean.into()),
+		enum_values: Some(vec![value.into()]),
+		..Default::default()
+	}
+	.into()
+}
+
 #[cfg(test)]
 mod tests {
 	use serde_test::{assert_tokens, Token};
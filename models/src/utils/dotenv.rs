@@ -0,0 +1,342 @@
+use std::collections::BTreeMap;
+
+use thiserror::Error;
+
+/// The result of parsing the contents of a `.env` file. Lines that can't be
+/// parsed don't fail the whole file, so that the valid variables can still be
+/// used while the invalid lines are reported back.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DotEnv {
+	/// The variables in the file. If a key is defined more than once, the last
+	/// value is used
+	pub variables: BTreeMap<String, String>,
+	/// The line that each of the variables is defined on, starting from 1. If
+	/// a key is defined more than once, this is the line it is last defined on
+	pub defined_on: BTreeMap<String, usize>,
+	/// The lines that could not be parsed, in the order they appear
+	pub errors: Vec<DotEnvLineError>,
+	/// The keys that were defined more than once, in the order they appear
+	pub duplicates: Vec<DotEnvDuplicateKey>,
+}
+
+/// A line of a `.env` file that could not be parsed
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("line {line}: {error}")]
+pub struct DotEnvLineError {
+	/// The line number, starting from 1. For a multiline value, this is the
+	/// line that the variable starts on
+	pub line: usize,
+	/// Why the line could not be parsed
+	pub error: DotEnvError,
+}
+
+/// A key that is defined more than once in a `.env` file. The value defined
+/// last is the one that is used.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DotEnvDuplicateKey {
+	/// The key that is defined more than once
+	pub key: String,
+	/// The line that the key was previously defined on
+	pub previous_line: usize,
+	/// The line that overrides the previous definition
+	pub line: usize,
+}
+
+/// The reason a line of a `.env` file could not be parsed
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum DotEnvError {
+	/// The line is not a comment and doesn't have a `=`
+	#[error("expected `KEY=VALUE`")]
+	MissingSeparator,
+	/// The key is not a valid environment variable name
+	#[error("invalid key `{0}`")]
+	InvalidKey(String),
+	/// A quoted value is never closed. The value would run until the end of
+	/// the file, on the given line, so the lines after the one it starts on
+	/// are parsed as usual instead
+	#[error("the quoted value is never closed (it would run until line {last_line})")]
+	UnterminatedQuote {
+		/// The last line of the file
+		last_line: usize,
+	},
+	/// There is something other than a comment after a quoted value
+	#[error("unexpected `{0}` after the quoted value")]
+	TrailingCharacters(String),
+}
+
+impl DotEnv {
+	/// Parses the contents of a `.env` file. Each variable is a `KEY=VALUE`
+	/// line, optionally prefixed with `export`. Blank lines and lines starting
+	/// with `#` are ignored. Values can be:
+	/// - Unquoted, in which case they are trimmed and anything after a ` #` is
+	///   treated as a comment
+	/// - Single quoted, in which case they are taken as is
+	/// - Double quoted, in which case `\n`, `\r`, `\t`, `\"` and `\\` are
+	///   unescaped
+	///
+	/// Quoted values can span multiple lines. A quoted value that is never
+	/// closed is reported as an error, and parsing resumes on the line after
+	/// the one it starts on.
+	pub fn parse(input: &str) -> Self {
+		let mut dotenv = Self::default();
+		let lines = input
+			.lines()
+			.enumerate()
+			.map(|(index, line)| (index + 1, line))
+			.collect::<Vec<_>>();
+
+		let mut index = 0;
+		while let Some(&(line_number, line)) = lines.get(index) {
+			index += 1;
+			let line = line.trim_start();
+			if line.is_empty() || line.starts_with('#') {
+				continue;
+			}
+
+			let (consumed_lines, result) = parse_variable(line_number, line, &lines[index..]);
+			index += consumed_lines;
+			match result {
+				Ok((key, value)) => {
+					if let Some(previous_line) = dotenv.defined_on.insert(key.clone(), line_number)
+					{
+						dotenv.duplicates.push(DotEnvDuplicateKey {
+							key: key.clone(),
+							previous_line,
+							line: line_number,
+						});
+					}
+					dotenv.variables.insert(key, value);
+				}
+				Err(error) => dotenv.errors.push(DotEnvLineError {
+					line: line_number,
+					error,
+				}),
+			}
+		}
+
+		dotenv
+	}
+}
+
+/// Parses a single variable on the given line, given the lines that follow
+/// it, which are used if its value is a quoted value that spans multiple
+/// lines. Returns the number of following lines that are part of the
+/// variable, along with the variable itself.
+fn parse_variable(
+	line_number: usize,
+	line: &str,
+	following_lines: &[(usize, &str)],
+) -> (usize, Result<(String, String), DotEnvError>) {
+	let line = line
+		.strip_prefix("export")
+		.filter(|rest| rest.starts_with(char::is_whitespace))
+		.unwrap_or(line);
+	let Some((key, value)) = line.split_once('=') else {
+		return (0, Err(DotEnvError::MissingSeparator));
+	};
+
+	let key = key.trim();
+	if !is_valid_key(key) {
+		return (0, Err(DotEnvError::InvalidKey(key.to_string())));
+	}
+
+	let trimmed = value.trim_start();
+	let Some(quote @ ('"' | '\'')) = trimmed.chars().next() else {
+		// A `#` is only the start of a comment if it follows a whitespace, so
+		// that values like `color#1` are kept as they are
+		let value = value
+			.char_indices()
+			.find(|&(index, c)| c == '#' && value[..index].ends_with(char::is_whitespace))
+			.map_or(value, |(index, _)| &value[..index]);
+		return (0, Ok((key.to_string(), value.trim().to_string())));
+	};
+
+	// The lines after an unterminated quote are still parsed on their own, so
+	// that a single missing quote doesn't hide the rest of the file
+	let Some((value, rest, consumed_lines)) =
+		parse_quoted_value(quote, &trimmed[1..], following_lines)
+	else {
+		let last_line = following_lines
+			.last()
+			.map_or(line_number, |&(last_line, _)| last_line);
+		return (0, Err(DotEnvError::UnterminatedQuote { last_line }));
+	};
+
+	let rest = rest.trim();
+	if !rest.is_empty() && !rest.starts_with('#') {
+		return (
+			consumed_lines,
+			Err(DotEnvError::TrailingCharacters(rest.to_string())),
+		);
+	}
+
+	(consumed_lines, Ok((key.to_string(), value)))
+}
+
+/// Parses a quoted value, starting right after the opening quote, given the
+/// lines that follow the one it starts on. Returns the value, whatever is left
+/// on the line after the closing quote, and the number of following lines
+/// that the value spans. Returns `None` if the value is never closed.
+fn parse_quoted_value<'a>(
+	quote: char,
+	mut line: &'a str,
+	following_lines: &[(usize, &'a str)],
+) -> Option<(String, &'a str, usize)> {
+	let mut value = String::new();
+	let mut following_lines = following_lines.iter();
+	let mut consumed_lines = 0;
+
+	loop {
+		let mut chars = line.char_indices();
+		while let Some((index, c)) = chars.next() {
+			match c {
+				c if c == quote => return Some((value, &line[index + 1..], consumed_lines)),
+				'\\' if quote == '"' => match chars.next() {
+					Some((_, 'n')) => value.push('\n'),
+					Some((_, 'r')) => value.push('\r'),
+					Some((_, 't')) => value.push('\t'),
+					Some((_, escaped @ ('"' | '\\'))) => value.push(escaped),
+					Some((_, other)) => {
+						value.push('\\');
+						value.push(other);
+					}
+					None => value.push('\\'),
+				},
+				c => value.push(c),
+			}
+		}
+
+		let &(_, next) = following_lines.next()?;
+		consumed_lines += 1;
+		value.push('\n');
+		line = next;
+	}
+}
+
+/// Checks if a key is a valid environment variable name for a deployment,
+/// which can have letters, digits, `_`, `-` and `.`, but can't start with a
+/// digit
+fn is_valid_key(key: &str) -> bool {
+	key.chars()
+		.next()
+		.is_some_and(|first| !first.is_ascii_digit()) &&
+		key.chars()
+			.all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn simple_variables_are_parsed() {
+		let dotenv = DotEnv::parse(
+			"# The database\nDATABASE_HOST=localhost\n\nexport DATABASE_PORT = 5432 # default\n",
+		);
+
+		assert_eq!(
+			dotenv.variables,
+			BTreeMap::from([
+				("DATABASE_HOST".to_string(), "localhost".to_string()),
+				("DATABASE_PORT".to_string(), "5432".to_string()),
+			])
+		);
+		assert!(dotenv.errors.is_empty());
+		assert!(dotenv.duplicates.is_empty());
+	}
+
+	#[test]
+	fn quoted_values_are_unescaped() {
+		let dotenv = DotEnv::parse(concat!(
+			"DOUBLE=\"hello \\\"world\\\"\\n\" # comment\n",
+			"SINGLE='no \\n escapes # here'\n",
+			"HASH=color#1\n",
+			"EMPTY=\n",
+		));
+
+		assert_eq!(dotenv.variables["DOUBLE"], "hello \"world\"\n");
+		assert_eq!(dotenv.variables["SINGLE"], "no \\n escapes # here");
+		assert_eq!(dotenv.variables["HASH"], "color#1");
+		assert_eq!(dotenv.variables["EMPTY"], "");
+		assert!(dotenv.errors.is_empty());
+	}
+
+	#[test]
+	fn quoted_values_can_span_multiple_lines() {
+		let dotenv = DotEnv::parse(concat!(
+			"KEY=\"-----BEGIN KEY-----\n",
+			"abc\n",
+			"-----END KEY-----\"\n",
+			"INVALID\n",
+		));
+
+		assert_eq!(
+			dotenv.variables["KEY"],
+			"-----BEGIN KEY-----\nabc\n-----END KEY-----"
+		);
+		assert_eq!(
+			dotenv.errors,
+			vec![DotEnvLineError {
+				line: 4,
+				error: DotEnvError::MissingSeparator,
+			}]
+		);
+	}
+
+	#[test]
+	fn invalid_lines_are_reported_with_their_line_number() {
+		let dotenv = DotEnv::parse(concat!(
+			"VALID=1\n",
+			"NO_SEPARATOR\n",
+			"1INVALID=key\n",
+			"TRAILING=\"value\" extra\n",
+			"UNTERMINATED='value\n",
+			"PARSED_AFTER_UNTERMINATED=1\n",
+		));
+
+		assert_eq!(
+			dotenv.variables,
+			BTreeMap::from([
+				("VALID".to_string(), "1".to_string()),
+				("PARSED_AFTER_UNTERMINATED".to_string(), "1".to_string()),
+			])
+		);
+		assert_eq!(
+			dotenv.errors,
+			vec![
+				DotEnvLineError {
+					line: 2,
+					error: DotEnvError::MissingSeparator,
+				},
+				DotEnvLineError {
+					line: 3,
+					error: DotEnvError::InvalidKey("1INVALID".to_string()),
+				},
+				DotEnvLineError {
+					line: 4,
+					error: DotEnvError::TrailingCharacters("extra".to_string()),
+				},
+				DotEnvLineError {
+					line: 5,
+					error: DotEnvError::UnterminatedQuote { last_line: 6 },
+				},
+			]
+		);
+	}
+
+	#[test]
+	fn duplicate_keys_take_the_last_value() {
+		let dotenv = DotEnv::parse("KEY=first\nOTHER=1\nKEY=second\n");
+
+		assert_eq!(dotenv.variables["KEY"], "second");
+		assert_eq!(dotenv.defined_on["KEY"], 3);
+		assert_eq!(
+			dotenv.duplicates,
+			vec![DotEnvDuplicateKey {
+				key: "KEY".to_string(),
+				previous_line: 1,
+				line: 3,
+			}]
+		);
+	}
+}
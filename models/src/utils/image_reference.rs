@@ -0,0 +1,384 @@
+use std::{fmt::Display, str::FromStr};
+
+use thiserror::Error;
+
+/// The registry that is used when an image reference does not specify one
+pub const DEFAULT_IMAGE_REGISTRY: &str = "docker.io";
+/// The tag that is used when an image reference does not specify one
+pub const DEFAULT_IMAGE_TAG: &str = "latest";
+
+/// A parsed and normalized reference to a container image, in the format
+/// `[registry[:port]/]repository[:tag][@digest]`. Implicit values are filled in
+/// with their defaults, so that two references to the same image always
+/// compare (and display) the same:
+/// - A missing registry defaults to [`DEFAULT_IMAGE_REGISTRY`]. The other
+///   hostnames of Docker Hub are normalized to it as well.
+/// - Official Docker Hub images are given the `library/` namespace.
+/// - A missing tag defaults to [`DEFAULT_IMAGE_TAG`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ImageReference {
+	/// The registry the image is hosted on, including the port, if any
+	pub registry: String,
+	/// The repository of the image within the registry
+	pub repository: String,
+	/// The tag of the image
+	pub tag: String,
+	/// The digest of the image, if the reference is pinned to one
+	pub digest: Option<String>,
+}
+
+/// The reason an image reference could not be parsed
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ImageReferenceError {
+	/// The reference is empty
+	#[error("the image reference is empty")]
+	Empty,
+	/// The registry hostname or port is invalid
+	#[error("invalid registry `{0}`")]
+	InvalidRegistry(String),
+	/// The repository is invalid
+	#[error("invalid repository `{0}`")]
+	InvalidRepository(String),
+	/// The tag is invalid
+	#[error("invalid tag `{0}`")]
+	InvalidTag(String),
+	/// The digest is invalid
+	#[error("invalid digest `{0}`")]
+	InvalidDigest(String),
+}
+
+impl ImageReferenceError {
+	/// The part of the image reference that is invalid, as it is named in the
+	/// message shown to the user
+	pub const fn invalid_part(&self) -> &'static str {
+		match self {
+			Self::Empty | Self::InvalidRepository(_) => "image name",
+			Self::InvalidRegistry(_) => "registry",
+			Self::InvalidTag(_) => "tag",
+			Self::InvalidDigest(_) => "digest",
+		}
+	}
+}
+
+impl ImageReference {
+	/// Parses an image reference from the registry, image name and tag stored
+	/// separately, as they are in a deployment. The tag can also be a digest
+	/// (`sha256:...`), or a tag followed by a digest (`1.0@sha256:...`). An
+	/// empty registry or tag is treated as missing.
+	pub fn from_parts(
+		registry: &str,
+		image_name: &str,
+		image_tag: &str,
+	) -> Result<Self, ImageReferenceError> {
+		let (registry, image_name, image_tag) =
+			(registry.trim(), image_name.trim(), image_tag.trim());
+
+		if image_name.is_empty() {
+			return Err(ImageReferenceError::Empty);
+		}
+		// The registry is given separately, so the image name must not contain
+		// one of its own
+		if !registry.is_empty() && split_registry(image_name).0.is_some() {
+			return Err(ImageReferenceError::InvalidRepository(
+				image_name.to_string(),
+			));
+		}
+
+		let mut reference = if registry.is_empty() {
+			image_name.to_string()
+		} else {
+			format!("{registry}/{image_name}")
+		};
+		if !image_tag.is_empty() {
+			if is_digest(image_tag) {
+				reference.push('@');
+			} else {
+				reference.push(':');
+			}
+			reference.push_str(image_tag);
+		}
+
+		reference.parse()
+	}
+}
+
+impl FromStr for ImageReference {
+	type Err = ImageReferenceError;
+
+	fn from_str(reference: &str) -> Result<Self, Self::Err> {
+		let reference = reference.trim();
+		if reference.is_empty() {
+			return Err(ImageReferenceError::Empty);
+		}
+
+		let (rest, digest) = match reference.split_once('@') {
+			Some((rest, digest)) => {
+				if !is_digest(digest) {
+					return Err(ImageReferenceError::InvalidDigest(digest.to_string()));
+				}
+				(rest, Some(digest.to_string()))
+			}
+			None => (reference, None),
+		};
+
+		let (registry, rest) = split_registry(rest);
+
+		// A `:` after the registry can only be the start of the tag
+		let (repository, tag) = match rest.rsplit_once(':') {
+			Some((repository, tag)) => {
+				if !is_tag(tag) {
+					return Err(ImageReferenceError::InvalidTag(tag.to_string()));
+				}
+				(repository, tag.to_string())
+			}
+			None => (rest, DEFAULT_IMAGE_TAG.to_string()),
+		};
+
+		let registry = match registry {
+			Some(registry) => {
+				if !is_registry(registry) {
+					return Err(ImageReferenceError::InvalidRegistry(registry.to_string()));
+				}
+				match registry.to_ascii_lowercase().as_str() {
+					"index.docker.io" | "registry.hub.docker.com" | "registry-1.docker.io" => {
+						DEFAULT_IMAGE_REGISTRY.to_string()
+					}
+					registry => registry.to_string(),
+				}
+			}
+			None => DEFAULT_IMAGE_REGISTRY.to_string(),
+		};
+
+		if !is_repository(repository) {
+			return Err(ImageReferenceError::InvalidRepository(
+				repository.to_string(),
+			));
+		}
+		let repository = if registry == DEFAULT_IMAGE_REGISTRY && !repository.contains('/') {
+			format!("library/{repository}")
+		} else {
+			repository.to_string()
+		};
+
+		Ok(Self {
+			registry,
+			repository,
+			tag,
+			digest,
+		})
+	}
+}
+
+impl Display for ImageReference {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}/{}:{}", self.registry, self.repository, self.tag)?;
+		if let Some(digest) = &self.digest {
+			write!(f, "@{digest}")?;
+		}
+		Ok(())
+	}
+}
+
+/// Splits the registry out of a reference, if it has one. As with Docker, the
+/// first component is only considered a registry if it looks like a hostname:
+/// it contains a `.` or a `:` (a port), or is `localhost`.
+fn split_registry(reference: &str) -> (Option<&str>, &str) {
+	match reference.split_once('/') {
+		Some((registry, rest)) if registry.contains(['.', ':']) || registry == "localhost" => {
+			(Some(registry), rest)
+		}
+		_ => (None, reference),
+	}
+}
+
+/// Checks if the given string is a valid registry hostname, with an optional
+/// port
+fn is_registry(registry: &str) -> bool {
+	let (host, port) = match registry.split_once(':') {
+		Some((host, port)) => (host, Some(port)),
+		None => (registry, None),
+	};
+
+	let is_host_valid = host.split('.').all(|label| {
+		!label.is_empty() &&
+			!label.starts_with('-') &&
+			!label.ends_with('-') &&
+			label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+	});
+	let is_port_valid = match port {
+		Some(port) => {
+			port.chars().all(|c| c.is_ascii_digit()) &&
+				port.parse::<u16>().is_ok_and(|port| port > 0)
+		}
+		None => true,
+	};
+
+	is_host_valid && is_port_valid
+}
+
+/// Checks if the given string is a valid repository. Each `/`-separated
+/// component must be made of lowercase letters and digits, optionally
+/// separated by a `.`, a `_`, `__`, or any number of `-`.
+fn is_repository(repository: &str) -> bool {
+	repository.len() <= 255 &&
+		repository.split('/').all(|component| {
+			let is_alphanumeric = |c: char| c.is_ascii_lowercase() || c.is_ascii_digit();
+			let is_separator = |separator: &str| {
+				matches!(separator, "." | "_" | "__") ||
+					(!separator.is_empty() && separator.chars().all(|c| c == '-'))
+			};
+
+			component.starts_with(is_alphanumeric) &&
+				component.ends_with(is_alphanumeric) &&
+				component
+					.split(is_alphanumeric)
+					.filter(|separator| !separator.is_empty())
+					.all(is_separator)
+		})
+}
+
+/// Checks if the given string is a valid tag
+fn is_tag(tag: &str) -> bool {
+	let is_word = |c: char| c.is_ascii_alphanumeric() || c == '_';
+
+	(1..=128).contains(&tag.len()) &&
+		tag.starts_with(is_word) &&
+		tag.chars().all(|c| is_word(c) || c == '.' || c == '-')
+}
+
+/// Checks if the given string is a valid digest, in the format
+/// `algorithm:hex`. `sha256` and `sha512` digests must also be of the right
+/// length.
+fn is_digest(digest: &str) -> bool {
+	let Some((algorithm, encoded)) = digest.split_once(':') else {
+		return false;
+	};
+
+	let is_algorithm_valid = !algorithm.is_empty() &&
+		algorithm.split(['+', '.', '_', '-']).all(|component| {
+			!component.is_empty() &&
+				component
+					.chars()
+					.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+		});
+	let is_encoded_valid = match algorithm {
+		"sha256" => encoded.len() == 64 && encoded.chars().all(|c| c.is_ascii_hexdigit()),
+		"sha512" => encoded.len() == 128 && encoded.chars().all(|c| c.is_ascii_hexdigit()),
+		_ => {
+			encoded.len() >= 32 &&
+				encoded
+					.chars()
+					.all(|c| c.is_ascii_alphanumeric() || matches!(c, '=' | '_' | '-'))
+		}
+	};
+
+	is_algorithm_valid && is_encoded_valid
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{ImageReference, ImageReferenceError};
+
+	const DIGEST: &str = "sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+	#[test]
+	fn normalizes_implicit_defaults() {
+		let reference = "nginx".parse::<ImageReference>().unwrap();
+
+		assert_eq!(reference.registry, "docker.io");
+		assert_eq!(reference.repository, "library/nginx");
+		assert_eq!(reference.tag, "latest");
+		assert_eq!(reference.digest, None);
+		assert_eq!(reference.to_string(), "docker.io/library/nginx:latest");
+
+		for equivalent in [
+			"library/nginx",
+			"docker.io/nginx:latest",
+			"index.docker.io/library/nginx",
+			"registry.hub.docker.com/nginx:latest",
+		] {
+			assert_eq!(equivalent.parse::<ImageReference>().unwrap(), reference);
+		}
+	}
+
+	#[test]
+	fn parses_digests() {
+		let reference = format!("ghcr.io/owner/app@{DIGEST}")
+			.parse::<ImageReference>()
+			.unwrap();
+		assert_eq!(reference.repository, "owner/app");
+		assert_eq!(reference.tag, "latest");
+		assert_eq!(reference.digest.as_deref(), Some(DIGEST));
+
+		let reference = format!("ghcr.io/owner/app:1.2.3@{DIGEST}")
+			.parse::<ImageReference>()
+			.unwrap();
+		assert_eq!(reference.tag, "1.2.3");
+		assert_eq!(reference.digest.as_deref(), Some(DIGEST));
+		assert_eq!(
+			reference.to_string(),
+			format!("ghcr.io/owner/app:1.2.3@{DIGEST}")
+		);
+
+		assert!(matches!(
+			"ghcr.io/owner/app@sha256:abc".parse::<ImageReference>(),
+			Err(ImageReferenceError::InvalidDigest(_))
+		));
+	}
+
+	#[test]
+	fn parses_ports_in_registry() {
+		let reference = "localhost:5000/app".parse::<ImageReference>().unwrap();
+		assert_eq!(reference.registry, "localhost:5000");
+		assert_eq!(reference.repository, "app");
+		assert_eq!(reference.tag, "latest");
+
+		let reference = "registry.example.com:8443/team/app:v2"
+			.parse::<ImageReference>()
+			.unwrap();
+		assert_eq!(reference.registry, "registry.example.com:8443");
+		assert_eq!(reference.repository, "team/app");
+		assert_eq!(reference.tag, "v2");
+
+		assert!(matches!(
+			"registry.example.com:99999/app".parse::<ImageReference>(),
+			Err(ImageReferenceError::InvalidRegistry(_))
+		));
+	}
+
+	#[test]
+	fn parses_from_parts() {
+		let reference = ImageReference::from_parts("", "nginx", "").unwrap();
+		assert_eq!(reference.to_string(), "docker.io/library/nginx:latest");
+
+		let reference = ImageReference::from_parts("localhost:5000", "app", DIGEST).unwrap();
+		assert_eq!(reference.tag, "latest");
+		assert_eq!(reference.digest.as_deref(), Some(DIGEST));
+
+		let err = ImageReference::from_parts("docker.io", "ghcr.io/app", "latest").unwrap_err();
+		assert_eq!(err.invalid_part(), "image name");
+		let err = ImageReference::from_parts("docker.io", "app", "bad tag").unwrap_err();
+		assert_eq!(err.invalid_part(), "tag");
+	}
+
+	#[test]
+	fn rejects_invalid_references() {
+		for reference in [
+			"",
+			"Nginx",
+			"nginx:",
+			"nginx:-latest",
+			"nginx:la test",
+			"/nginx",
+			"nginx/",
+			"team//app",
+			"app.",
+			"-registry.com/app",
+		] {
+			assert!(
+				reference.parse::<ImageReference>().is_err(),
+				"`{reference}` should be invalid"
+			);
+		}
+	}
+}
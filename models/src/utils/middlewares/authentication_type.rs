@@ -13,6 +13,18 @@ impl RequiresRequestHeaders for NoAuthentication {
 	type RequiredRequestHeaders = ();
 }
 
+impl HasAuthentication for NoAuthentication {
+	const REQUIRES_AUTHENTICATION: bool = false;
+}
+
+/// A trait implemented by the authenticators of API endpoints, to tell whether
+/// an endpoint can only be accessed with a token. This is used to document the
+/// security requirements of the endpoints.
+pub trait HasAuthentication {
+	/// Whether the endpoint requires a bearer token to be accessed
+	const REQUIRES_AUTHENTICATION: bool;
+}
+
 /// This enum represents the different types of authentication that can be used
 /// for an API endpoint.
 ///
@@ -103,6 +115,14 @@ where
 	type RequiredRequestHeaders = (BearerToken,);
 }
 
+impl<E> HasAuthentication for AppAuthentication<E>
+where
+	E: ApiEndpoint,
+	<E::RequestBody as Preprocessable>::Processed: Send,
+{
+	const REQUIRES_AUTHENTICATION: bool = true;
+}
+
 impl<E> Debug for AppAuthentication<E>
 where
 	E: ApiEndpoint,
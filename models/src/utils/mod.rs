@@ -1,7 +1,11 @@
 use std::borrow::Cow;
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+/// The format of API tokens, with a parser that splits a token into its parts
+/// and the prefixes of each version of the format.
+mod api_token;
 /// This module contains all the utilities used for parsing a request and using
 /// it in the [`crate::ApiEndpoint`] request struct.
 mod axum_request;
@@ -15,6 +19,10 @@ mod base64string;
 /// A set of constant booleans that are used to ensure that the values are
 /// forced to be either true or false.
 mod bools;
+/// A parser for the contents of `.env` files, used to import the environment
+/// variables of a deployment in bulk. Invalid lines are reported with their
+/// line numbers instead of being dropped.
+mod dotenv;
 /// Represents a location on the planet. This is used to represent the location
 /// of a user, a login, etc. Basically just a latitude and longitude.
 mod geo_location;
@@ -22,6 +30,10 @@ mod geo_location;
 /// headers are present in a struct as well as provide what headers are required
 /// for an endpoint.
 mod header_utils;
+/// A parser for references to container images, such as
+/// `registry.example.com:5000/team/app:1.0`, which normalizes the implicit
+/// registry and tag of a reference.
+mod image_reference;
 /// A set of middlewares that are used by the API to perform certain tasks, like
 /// authentication, audit logging, etc.
 mod middlewares;
@@ -29,6 +41,13 @@ mod middlewares;
 /// a value that can be either a single value or a list of values, such as
 /// audience in a JWT, a dependency string in a CI yaml file, etc.
 mod one_or_many;
+/// A helper to deserialize the fields of partial updates that can be explicitly
+/// set to null, as opposed to not being provided at all.
+mod nullable;
+/// Builds the OpenAPI document of the API from the types of the endpoints,
+/// using the [`schemars::JsonSchema`] implementations of their params and
+/// bodies.
+mod openapi;
 /// A set of utilities to parse a paginated response from the API. A paginated
 /// request enforces a response header to be present, which provides the total
 /// number of items in the response.
@@ -48,14 +67,19 @@ mod uuid;
 mod websocket;
 
 pub use self::{
+	api_token::*,
 	axum_request::*,
 	axum_response::*,
 	base64string::*,
 	bools::*,
+	dotenv::*,
 	geo_location::*,
 	header_utils::*,
+	image_reference::*,
 	middlewares::*,
+	nullable::*,
 	one_or_many::*,
+	openapi::*,
 	paginated::*,
 	stringified_u16::*,
 	tuple_utils::*,
@@ -153,6 +177,17 @@ pub mod constants {
 	pub const PHONE_NUMBER_REGEX: &str =
 		macros::verify_regex!(r"^\(?\d{3}\)?[-.\s]?\d{3}[-.\s]?\d{4}$");
 
+	/// The Regex to validate a phone number in the E.164 format. The number
+	/// must start with a plus sign, followed by the calling code of the country
+	/// and the subscriber number, with at most 15 digits in total. Example:
+	/// `+14155552671`
+	pub const E164_PHONE_NUMBER_REGEX: &str = macros::verify_regex!(r"^\+[1-9]\d{1,14}$");
+
+	/// The Regex to check if an email address is syntactically valid. The
+	/// email must have a local part and a domain with at least one dot,
+	/// separated by a single `@`, without any whitespace.
+	pub const EMAIL_REGEX: &str = macros::verify_regex!(r"^[^@\s]+@[^@\s]+\.[^@\s]+$");
+
 	/// The Regex to validate OTP of the user. The OTP must be a 6-digit number.
 	/// The OTP can be of the format `123456` or `123-456`.
 	pub const OTP_VERIFICATION_TOKEN_REGEX: &str = macros::verify_regex!(r"^(\d{3}\-?\d{3})$");
@@ -162,6 +197,12 @@ pub mod constants {
 	/// digits, letters, hyphens, underscores, spaces and dots.
 	pub const RESOURCE_NAME_REGEX: &str = macros::verify_regex!(r"^[a-zA-Z0-9\-_ \.]{4,255}$");
 
+	/// The Regex to validate the tag of an image. Tags are case-sensitive, can
+	/// be up to 128 characters long, can have letters, digits, underscores,
+	/// dots and hyphens, and can't start with a dot or a hyphen.
+	pub const IMAGE_TAG_REGEX: &str =
+		macros::verify_regex!(r"^[a-zA-Z0-9_][a-zA-Z0-9_\.\-]{0,127}$");
+
 	/// The Regex to validate a DNS record name.
 	///
 	/// The DNS record name must be in the format `@`, `www`, `subdomain`, etc.
@@ -170,10 +211,65 @@ pub mod constants {
 	pub const DNS_RECORD_NAME_REGEX: &str = macros::verify_regex!(
 		r"^((([a-z0-9].)([a-z0-9\-]*){0,63}([a-z0-9].).)(\.([a-z0-9].)([a-z0-9\-_]*){0,63}([a-z0-9]*)))|\@$"
 	);
+
+	/// The value that is returned in place of the value of a secret environment
+	/// variable of a deployment. Sending this value back when updating the
+	/// environment variables keeps the existing value of the variable
+	/// unchanged.
+	pub const MASKED_ENVIRONMENT_VARIABLE_VALUE: &str = "••••••••";
+
+	/// The maximum number of labels that can be attached to a deployment
+	pub const MAX_DEPLOYMENT_LABELS: usize = 64;
+
+	/// The maximum length of the key and the value of a label of a deployment
+	pub const MAX_DEPLOYMENT_LABEL_LENGTH: usize = 63;
+
+	/// The maximum number of deployments that a deployment can depend on
+	pub const MAX_DEPLOYMENT_DEPENDENCIES: usize = 16;
+
+	/// The maximum number of replicas of a deployment (or of its canary) that
+	/// a runner can report the readiness of
+	pub const MAX_REPORTED_REPLICAS: u16 = 256;
+
+	/// The maximum number of rules (allowed and denied combined) that the
+	/// egress policy of a workspace can have
+	pub const MAX_EGRESS_POLICY_RULES: usize = 256;
+
+	/// The maximum number of deployments whose status can be fetched in a
+	/// single batch. IDs past this are left out of the response
+	pub const MAX_DEPLOYMENT_STATUS_BATCH_SIZE: usize = 100;
+
+	/// The maximum number of access logs that the ingress can report in a
+	/// single batch
+	pub const MAX_ACCESS_LOG_BATCH_SIZE: usize = 1000;
+
+	/// The maximum length of the value of a TXT record. Longer values are
+	/// split into multiple strings of at most 255 characters by the
+	/// nameservers, but the record as a whole cannot be longer than this
+	pub const MAX_TXT_RECORD_LENGTH: usize = 2048;
+}
+
+/// Which field to order the list by for paginated requests
+#[derive(
+	Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize, JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum ListOrderBy {
+	/// Order the list by the status of the resource
+	Status,
+	/// Order the list by the name of the resource
+	Name,
+	/// Order the list by when the resource was last updated
+	LastUpdated,
+	/// Order the list by when the resource was created
+	#[default]
+	Created,
 }
 
 /// Ordering of the list for paginated requests
-#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+#[derive(
+	Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize, JsonSchema,
+)]
 #[serde(rename_all = "camelCase")]
 pub enum ListOrder {
 	/// Ascending order
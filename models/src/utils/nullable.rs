@@ -0,0 +1,52 @@
+use serde::{Deserialize, Deserializer};
+
+/// Deserializes a field of a partial update that can be explicitly set to
+/// `null`, to distinguish it from a field that wasn't provided at all. A field
+/// that isn't provided is `None` (which requires `#[serde(default)]` on the
+/// field), a field that is `null` is `Some(None)`, and a field with a value is
+/// `Some(Some(value))`.
+///
+/// ## Example
+/// ```ignore
+/// #[serde(
+/// 	default,
+/// 	deserialize_with = "models::utils::deserialize_nullable",
+/// 	skip_serializing_if = "Option::is_none"
+/// )]
+/// pub startup_probe: Option<Option<DeploymentProbe>>,
+/// ```
+pub fn deserialize_nullable<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+	D: Deserializer<'de>,
+	T: Deserialize<'de>,
+{
+	Option::<T>::deserialize(deserializer).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+	use serde::{Deserialize, Serialize};
+
+	#[derive(Debug, PartialEq, Serialize, Deserialize)]
+	struct Patch {
+		#[serde(
+			default,
+			deserialize_with = "super::deserialize_nullable",
+			skip_serializing_if = "Option::is_none"
+		)]
+		value: Option<Option<u16>>,
+	}
+
+	#[test]
+	fn distinguishes_null_from_missing_fields() {
+		for (json, value) in [
+			("{}", None),
+			(r#"{"value":null}"#, Some(None)),
+			(r#"{"value":80}"#, Some(Some(80))),
+		] {
+			let patch = Patch { value };
+			assert_eq!(serde_json::from_str::<Patch>(json).unwrap(), patch);
+			assert_eq!(serde_json::to_string(&patch).unwrap(), json);
+		}
+	}
+}
@@ -0,0 +1,347 @@
+use std::collections::BTreeMap;
+
+use axum_extra::routing::TypedPath;
+use schemars::{
+	gen::{SchemaGenerator, SchemaSettings},
+	schema::{InstanceType, ObjectValidation, Schema, SchemaObject},
+	JsonSchema,
+};
+use serde_json::{json, Map, Value};
+
+use crate::{
+	utils::{GenericResponse, HasAuthentication, WebSocketUpgrade},
+	ApiEndpoint,
+	ApiErrorResponseBody,
+	ApiSuccessResponseBody,
+};
+
+/// The name of the security scheme used by authenticated endpoints
+const SECURITY_SCHEME: &str = "bearerAuth";
+
+/// The schema of the body of a request or a response of an endpoint, as it is
+/// described in the OpenAPI document. Bodies that aren't JSON, such as a
+/// websocket upgrade or a raw response (like a file download), don't have a
+/// schema.
+pub trait BodySchema {
+	/// The schema of the body when it is sent in a request
+	fn request_schema(gen: &mut SchemaGenerator) -> Option<Schema>;
+	/// The schema of the body when it is sent in a successful response,
+	/// including the `success` field that is added to all responses
+	fn response_schema(gen: &mut SchemaGenerator) -> Option<Schema>;
+}
+
+impl<T> BodySchema for T
+where
+	T: JsonSchema,
+{
+	fn request_schema(gen: &mut SchemaGenerator) -> Option<Schema> {
+		Some(T::json_schema(gen))
+	}
+
+	fn response_schema(gen: &mut SchemaGenerator) -> Option<Schema> {
+		Some(ApiSuccessResponseBody::<T>::json_schema(gen))
+	}
+}
+
+impl BodySchema for GenericResponse {
+	fn request_schema(_: &mut SchemaGenerator) -> Option<Schema> {
+		None
+	}
+
+	fn response_schema(_: &mut SchemaGenerator) -> Option<Schema> {
+		None
+	}
+}
+
+impl<ServerMsg, ClientMsg> BodySchema for WebSocketUpgrade<ServerMsg, ClientMsg> {
+	fn request_schema(_: &mut SchemaGenerator) -> Option<Schema> {
+		None
+	}
+
+	fn response_schema(_: &mut SchemaGenerator) -> Option<Schema> {
+		None
+	}
+}
+
+/// An OpenAPI 3.0 document of the API. Endpoints are added to it one by one,
+/// and the schemas of their params and bodies are generated from their types
+/// using [`schemars`]. Types that are used by multiple endpoints are described
+/// once in the component schemas of the document and referenced from there.
+#[derive(Debug, Clone)]
+pub struct OpenApiDocument {
+	/// The generator of the schemas, which collects the component schemas
+	generator: SchemaGenerator,
+	/// The schema of the body of the error responses, which is the same for
+	/// all endpoints
+	error_response: Schema,
+	/// The operations of each path, by the lowercase HTTP method
+	paths: BTreeMap<String, Map<String, Value>>,
+}
+
+impl Default for OpenApiDocument {
+	fn default() -> Self {
+		let mut generator = SchemaSettings::openapi3().into_generator();
+		let error_response = generator.subschema_for::<ApiErrorResponseBody>();
+
+		Self {
+			generator,
+			error_response,
+			paths: BTreeMap::new(),
+		}
+	}
+}
+
+impl OpenApiDocument {
+	/// Adds an endpoint to the document. Endpoints that can only be accessed
+	/// by the Web UI are left out, since they aren't a part of the API.
+	pub fn add_endpoint<E>(&mut self) -> &mut Self
+	where
+		E: ApiEndpoint,
+		E::RequestPath: JsonSchema,
+		E::RequestQuery: JsonSchema,
+		E::RequestBody: BodySchema,
+		E::ResponseBody: BodySchema,
+		E::Authenticator: HasAuthentication,
+	{
+		if !E::API_ALLOWED {
+			return self;
+		}
+
+		let mut parameters = self.parameters::<E::RequestPath>("path");
+		parameters.extend(self.parameters::<E::RequestQuery>("query"));
+
+		let success_response = match E::ResponseBody::response_schema(&mut self.generator) {
+			Some(schema) => json!({
+				"description": "The request was successful",
+				"content": {
+					"application/json": {
+						"schema": without_metadata(schema)
+					}
+				}
+			}),
+			None => json!({
+				"description": "The request was successful",
+			}),
+		};
+		let error_response = json!({
+			"description": "The request failed",
+			"content": {
+				"application/json": {
+					"schema": self.error_response
+				}
+			}
+		});
+
+		let security = if E::Authenticator::REQUIRES_AUTHENTICATION {
+			json!([{ SECURITY_SCHEME: [] }])
+		} else {
+			json!([])
+		};
+
+		let mut operation = json!({
+			"operationId": E::NAME,
+			"summary": E::DOCUMENTATION,
+			"parameters": parameters,
+			"responses": {
+				"2XX": success_response,
+				"4XX": error_response.clone(),
+				"5XX": error_response,
+			},
+			"security": security,
+		});
+
+		// Endpoints that don't take a body are declared with a unit struct,
+		// whose schema is `null`
+		let request_body = E::RequestBody::request_schema(&mut self.generator)
+			.map(without_metadata)
+			.filter(|schema| !schema.has_type(InstanceType::Null));
+		if let Some(schema) = request_body {
+			operation["requestBody"] = json!({
+				"required": true,
+				"content": {
+					"application/json": {
+						"schema": schema
+					}
+				}
+			});
+		}
+
+		self.paths
+			.entry(openapi_path(<E::RequestPath as TypedPath>::PATH))
+			.or_default()
+			.insert(E::METHOD.as_str().to_lowercase(), operation);
+
+		self
+	}
+
+	/// Generates the OpenAPI document, with the given version of the API
+	pub fn to_json(&self, version: &str) -> Value {
+		json!({
+			"openapi": "3.0.3",
+			"info": {
+				"title": "Patr API",
+				"version": version,
+			},
+			"paths": self.paths,
+			"components": {
+				"schemas": self.generator.definitions(),
+				"securitySchemes": {
+					SECURITY_SCHEME: {
+						"type": "http",
+						"scheme": "bearer"
+					}
+				}
+			}
+		})
+	}
+
+	/// Generates the OpenAPI parameter objects of the fields of a path or a
+	/// query, in the given location (`path` or `query`). Path params are always
+	/// required, while query params are required only if they don't have a
+	/// default value.
+	fn parameters<T>(&mut self, location: &str) -> Vec<Value>
+	where
+		T: JsonSchema,
+	{
+		let Some(object) = T::json_schema(&mut self.generator).into_object().object else {
+			return Vec::new();
+		};
+		let ObjectValidation {
+			properties,
+			required,
+			..
+		} = *object;
+
+		properties
+			.into_iter()
+			.map(|(name, schema)| {
+				let mut schema = schema.into_object();
+				let description = schema
+					.metadata
+					.as_mut()
+					.and_then(|metadata| metadata.description.take());
+
+				let mut parameter = json!({
+					"name": name,
+					"in": location,
+					"required": location == "path" || required.contains(&name),
+					"schema": schema,
+				});
+				if let Some(description) = description {
+					parameter["description"] = description.into();
+				}
+				parameter
+			})
+			.collect()
+	}
+}
+
+/// Removes the title and the description of a schema. The bodies of the
+/// endpoints are documented by the documentation of the endpoint itself, so
+/// the documentation of the generated structs isn't repeated in them.
+fn without_metadata(schema: Schema) -> SchemaObject {
+	SchemaObject {
+		metadata: None,
+		..schema.into_object()
+	}
+}
+
+/// Converts an axum path, such as `/workspace/:workspace_id`, to an OpenAPI
+/// path, such as `/workspace/{workspace_id}`
+fn openapi_path(path: &str) -> String {
+	path.split('/')
+		.map(|segment| match segment.strip_prefix(':') {
+			Some(param) => format!("{{{param}}}"),
+			None => segment.to_string(),
+		})
+		.collect::<Vec<_>>()
+		.join("/")
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json::json;
+
+	use super::OpenApiDocument;
+	use crate::api::{
+		auth::{oauth::OAuthTokenRequest, LoginRequest},
+		workspace::deployment::{
+			CreateDeploymentRequest,
+			GetDeploymentInfoRequest,
+			ListDeploymentRequest,
+			StreamDeploymentLogsRequest,
+		},
+	};
+
+	#[test]
+	fn endpoints_are_described_from_their_types() {
+		let document = OpenApiDocument::default()
+			.add_endpoint::<GetDeploymentInfoRequest>()
+			.add_endpoint::<CreateDeploymentRequest>()
+			.add_endpoint::<ListDeploymentRequest>()
+			.add_endpoint::<LoginRequest>()
+			.add_endpoint::<OAuthTokenRequest>()
+			.add_endpoint::<StreamDeploymentLogsRequest>()
+			.to_json("1.0.0");
+
+		let get = &document["paths"]["/workspace/{workspace_id}/deployment/{deployment_id}"]["get"];
+		assert_eq!(get["operationId"], "GetDeploymentInfo");
+		assert_eq!(get["security"], json!([{ "bearerAuth": [] }]));
+		assert_eq!(get["parameters"][0]["name"], "deployment_id");
+		assert_eq!(get["parameters"][0]["in"], "path");
+		assert_eq!(get["parameters"][0]["required"], true);
+		assert_eq!(get["parameters"][2]["name"], "revealSecretValues");
+		assert_eq!(get["parameters"][2]["in"], "query");
+		assert_eq!(get["parameters"][2]["required"], false);
+		assert!(get.get("requestBody").is_none());
+
+		// Nested types are described in the component schemas, instead of
+		// being left opaque
+		let response = get["responses"]["2XX"]["content"]["application/json"]["schema"].to_string();
+		assert!(response.contains("\"success\""));
+		assert!(response.contains("#/components/schemas/DeploymentStatus"));
+		let status = &document["components"]["schemas"]["DeploymentStatus"];
+		assert_eq!(status["type"], "string");
+		assert!(status["enum"]
+			.as_array()
+			.unwrap()
+			.contains(&"running".into()));
+
+		let create = &document["paths"]["/workspace/{workspace_id}/deployment"]["post"];
+		let request = &create["requestBody"]["content"]["application/json"]["schema"];
+		assert!(request["properties"]["dependsOn"].is_object());
+		assert!(request["required"]
+			.as_array()
+			.unwrap()
+			.contains(&"name".into()));
+
+		let list = &document["paths"]["/workspace/{workspace_id}/deployment"]["get"];
+		let parameters = list["parameters"].as_array().unwrap();
+		assert!(parameters
+			.iter()
+			.any(|parameter| parameter["name"] == "page"));
+		assert!(parameters
+			.iter()
+			.any(|parameter| parameter["name"] == "count"));
+
+		// Endpoints that can only be used by the Web UI aren't documented
+		assert!(document["paths"].get("/auth/sign-in").is_none());
+		let token = &document["paths"]["/auth/oauth/token"]["post"];
+		assert_eq!(token["security"], json!([]));
+
+		// Websockets don't have a JSON body
+		let stream = &document["paths"]
+			["/workspace/{workspace_id}/deployment/{deployment_id}/logs/stream"]["get"];
+		assert!(stream.get("requestBody").is_none());
+		assert!(stream["responses"]["2XX"].get("content").is_none());
+
+		let error = &document["components"]["schemas"]["ApiErrorResponseBody"];
+		assert_eq!(error["properties"]["success"]["enum"], json!([false]));
+		assert!(error["properties"]["resource"].is_object());
+		let error_codes = &document["components"]["schemas"]["ErrorType"]["enum"];
+		assert!(error_codes
+			.as_array()
+			.unwrap()
+			.contains(&"quotaExceeded".into()));
+	}
+}
@@ -1,5 +1,6 @@
 use headers::{Error, Header};
 use http::{HeaderName, HeaderValue};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use super::{AddTuple, RequiresResponseHeaders};
@@ -16,12 +17,14 @@ use super::{AddTuple, RequiresResponseHeaders};
 /// 14 (assuming the items are zero-indexed). This means that the offset is the
 /// index of the first item that should be returned and the count is the number
 /// of items that should be returned.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, PartialOrd)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, PartialOrd, JsonSchema)]
 pub struct Paginated<T = ()> {
 	/// Any other query parameters that should be included in the request.
 	#[serde(flatten)]
 	pub data: T,
-	/// The number of items that should be returned per page.
+	/// The number of items that should be returned per page. The API limits
+	/// this to a maximum page size, and uses the default page size if this is
+	/// 0. The page size that was used is returned in the `X-Page-Size` header.
 	#[serde(default = "default_page_size")]
 	pub count: usize,
 	/// The page number that should be returned. This is zero-indexed. So to get
@@ -6,7 +6,7 @@ use serde::{de::Error, Deserialize, Serialize};
 /// A wrapper around a `u16` that serializes and deserializes as a string.
 /// Mostly used as keys in maps.
 #[derive(Clone, Copy, Debug, PartialEq, Hash, Eq, PartialOrd, Ord, JsonSchema)]
-pub struct StringifiedU16(u16);
+pub struct StringifiedU16(#[schemars(with = "String")] u16);
 
 impl StringifiedU16 {
 	/// Create a new instance of the [`StringifiedU16`] with the given value.
@@ -48,6 +48,10 @@ pub async fn initialize_deployment_tables(
 			min_horizontal_scale INTEGER NOT NULL,
 			max_horizontal_scale INTEGER NOT NULL,
 			machine_type TEXT NOT NULL,
+			cpu_request INTEGER,
+			cpu_limit INTEGER,
+			memory_request INTEGER,
+			memory_limit INTEGER,
 			deploy_on_push BOOLEAN NOT NULL,
 			startup_probe_port INTEGER,
 			startup_probe_path TEXT,
@@ -60,14 +64,27 @@ pub async fn initialize_deployment_tables(
 				liveness_probe_port_type IN ('http')
 			),
 			current_live_digest TEXT,
+			reconciliation_status TEXT NOT NULL DEFAULT 'pending' CHECK(
+				reconciliation_status IN ('in_sync', 'pending', 'failed')
+			),
+			reconciliation_error TEXT,
+			last_reconciliation_attempt DATETIME,
+			reconciliation_generation INTEGER NOT NULL DEFAULT 0,
+			created DATETIME NOT NULL,
+			updated DATETIME NOT NULL,
 			deleted DATETIME,
 
 			CHECK( 
 				status IN (
 					'created', 
 					'pushed', 
+					'pending', 
+					'pulling', 
 					'deploying', 
+					'starting', 
 					'running', 
+					'degraded', 
+					'stopping', 
 					'stopped', 
 					'errored', 
 					'deleted'
@@ -1,7 +1,11 @@
 use std::{future::Future, time::Duration};
 
 use futures::Stream;
-use models::api::workspace::deployment::*;
+use models::api::workspace::{
+	database::{DatabaseEngine, DatabaseUser},
+	deployment::{build::DeploymentBuildSource, *},
+	egress_policy::WorkspaceEgressPolicy,
+};
 use serde::{de::DeserializeOwned, Serialize};
 
 use crate::prelude::*;
@@ -18,6 +22,12 @@ pub trait RunnerExecutor: Sized {
 	/// tracing and logs.
 	const RUNNER_INTERNAL_NAME: &'static str;
 
+	/// Whether the runner can run the canary of a deployment alongside its
+	/// primary replicas. Deployments with a canary are still run on runners
+	/// that can't, but without their canary, and the canary is reported as
+	/// failed. By default, canaries are not supported.
+	const SUPPORTS_CANARIES: bool = false;
+
 	/// The settings type for the runner. This is used to store any additional
 	/// settings needed for the runner.
 	type Settings: Serialize + DeserializeOwned + Clone + Send + Sync;
@@ -45,4 +55,74 @@ pub trait RunnerExecutor: Sized {
 	/// This function should return a stream of all the running deployment IDs
 	/// in the runner, sorted by the deployment ID.
 	fn list_running_deployments<'a>(&self) -> impl Future<Output = impl Stream<Item = Uuid> + 'a>;
+
+	/// This function is called when a build of a deployment's image is
+	/// requested. The runner should clone the given source, build the image and
+	/// push it to the given image reference, returning the digest of the pushed
+	/// image, or the reason the build failed. The logs of the build should be
+	/// shipped to Loki with the `buildId` label, so that they can be streamed.
+	///
+	/// The returned future is run in the background, so it must not borrow the
+	/// runner. By default, building images is not supported.
+	fn build_deployment_image(
+		&self,
+		build_id: Uuid,
+		build_source: DeploymentBuildSource,
+		image: String,
+	) -> impl Future<Output = Result<String, String>> + Send + 'static {
+		_ = (build_id, build_source, image);
+		async { Err("This runner does not support building images from source".to_string()) }
+	}
+
+	/// This function is called when the egress policy of the workspace is
+	/// changed, and whenever all the resources are reconciled. The runner
+	/// should only let its deployments connect to the destinations allowed by
+	/// the policy. The runner should return the reason if the policy failed to
+	/// apply, in which case it is applied again on the next full
+	/// reconciliation. Until it applies, none of the deployments are run, so
+	/// that they can't connect to destinations that the policy doesn't allow.
+	/// By default, only empty egress policies are supported.
+	fn apply_egress_policy(
+		&self,
+		policy: WorkspaceEgressPolicy,
+	) -> impl Future<Output = Result<(), String>> {
+		let result = if policy.is_empty() {
+			Ok(())
+		} else {
+			Err("This runner does not support enforcing egress policies".to_string())
+		};
+		async { result }
+	}
+
+	/// This function is called when new credentials are issued for a managed
+	/// database. The runner should create the user on the engine of the
+	/// database (see [`DatabaseEngine::create_user_command`]), or update its
+	/// password if it already exists. The runner should return the reason if
+	/// the user could not be created. By default, managed databases are not
+	/// supported.
+	fn create_database_user(
+		&self,
+		database_id: Uuid,
+		engine: DatabaseEngine,
+		user: DatabaseUser,
+	) -> impl Future<Output = Result<(), String>> {
+		_ = (database_id, engine, user);
+		async { Err("This runner does not support managed databases".to_string()) }
+	}
+
+	/// This function is called when the grace period of rotated credentials of
+	/// a managed database is over. The runner should drop the user from the
+	/// engine of the database (see [`DatabaseEngine::drop_user_command`]).
+	/// Dropping a user that doesn't exist is not an error. By default, managed
+	/// databases are not supported.
+	fn drop_database_user(
+		&self,
+		database_id: Uuid,
+		engine: DatabaseEngine,
+		username: String,
+		owner: String,
+	) -> impl Future<Output = Result<(), String>> {
+		_ = (database_id, engine, username, owner);
+		async { Err("This runner does not support managed databases".to_string()) }
+	}
 }
@@ -1,5 +1,6 @@
 use http::StatusCode;
 use models::api::workspace::{deployment::*, runner::StreamRunnerDataForWorkspaceServerMsg};
+use time::OffsetDateTime;
 
 use crate::prelude::*;
 
@@ -23,6 +24,10 @@ pub async fn create_deployment(
 						image_tag,
 						runner: _,
 						machine_type,
+						template_id: _,
+						// Pull secrets are stored by Patr, and are not available to
+						// self-hosted runners
+						pull_secret_id: _,
 						running_details:
 							DeploymentRunningDetails {
 								deploy_on_push,
@@ -34,8 +39,18 @@ pub async fn create_deployment(
 								liveness_probe,
 								config_mounts,
 								volumes,
+								resources,
+								scale_to_zero_after,
+								log_level,
+								max_concurrent_requests,
+								access_logging,
 							},
 						deploy_on_create,
+						// Self-hosted runners are only accessed by their owner, so there is
+						// no need to mask any values
+						secret_variables: _,
+						labels,
+						depends_on,
 					},
 			},
 		database,
@@ -45,7 +60,57 @@ pub async fn create_deployment(
 ) -> Result<AppResponse<CreateDeploymentRequest>, ErrorType> {
 	trace!("Creating deployment: {}", name);
 
+	// Deployment templates are only resolved by the Patr API, so a runner
+	// needs the machine type to be set explicitly
+	let machine_type = machine_type.ok_or(ErrorType::WrongParameters)?;
+
+	// The capacity of machine types is only known to the Patr API, so only the
+	// requests can be checked against the limits here
+	let resources = resources.with_default_limits();
+	resources.validate_requests()?;
+
+	// Requests to self-hosted runners don't go through the Patr ingress, so
+	// there's no way to know when a deployment is idle
+	if scale_to_zero_after.is_some() {
+		debug!("Deployments on self-hosted runners cannot be scaled to zero");
+		return Err(ErrorType::WrongParameters);
+	}
+
+	// The logs of deployments on self-hosted runners are not captured by Patr
+	if log_level.is_some() {
+		debug!("Deployments on self-hosted runners cannot have a log level");
+		return Err(ErrorType::WrongParameters);
+	}
+
+	// Requests to self-hosted runners don't go through the Patr ingress, so
+	// there's nothing to limit the requests to a deployment
+	if max_concurrent_requests.is_some_and(|limit| limit > 0) {
+		debug!("Deployments on self-hosted runners cannot limit their concurrent requests");
+		return Err(ErrorType::WrongParameters);
+	}
+
+	// Requests to self-hosted runners don't go through the Patr ingress, so
+	// there's nothing to log them
+	if access_logging {
+		debug!("Deployments on self-hosted runners cannot have access logs");
+		return Err(ErrorType::WrongParameters);
+	}
+
+	// Labels are only stored by the Patr API
+	if !labels.is_empty() {
+		debug!("Deployments on self-hosted runners cannot have labels");
+		return Err(ErrorType::WrongParameters);
+	}
+
+	// The order in which deployments are started is only tracked by the Patr
+	// API
+	if !depends_on.is_empty() {
+		debug!("Deployments on self-hosted runners cannot depend on other deployments");
+		return Err(ErrorType::WrongParameters);
+	}
+
 	let deployment_id = Uuid::new_v4();
+	let now = OffsetDateTime::now_utc();
 
 	let status = if deploy_on_create {
 		DeploymentStatus::Running
@@ -63,6 +128,10 @@ pub async fn create_deployment(
 				image_tag,
 				status,
 				machine_type,
+				cpu_request,
+				cpu_limit,
+				memory_request,
+				memory_limit,
 				min_horizontal_scale,
 				max_horizontal_scale,
 				deploy_on_push,
@@ -73,6 +142,8 @@ pub async fn create_deployment(
 				liveness_probe_path,
 				liveness_probe_port_type,
 				current_live_digest,
+				created,
+				updated,
 				deleted
 			)
 		VALUES
@@ -93,7 +164,13 @@ pub async fn create_deployment(
 				$14,
 				$15,
 				$16,
+				$17,
+				$18,
+				$19,
+				$20,
 				NULL,
+				$21,
+				$21,
 				NULL
 			);
 		"#,
@@ -105,6 +182,10 @@ pub async fn create_deployment(
 	.bind(image_tag.to_string())
 	.bind(status)
 	.bind(machine_type)
+	.bind(resources.cpu_request)
+	.bind(resources.cpu_limit)
+	.bind(resources.memory_request)
+	.bind(resources.memory_limit)
 	.bind(min_horizontal_scale)
 	.bind(max_horizontal_scale)
 	.bind(deploy_on_push)
@@ -114,6 +195,7 @@ pub async fn create_deployment(
 	.bind(liveness_probe.as_ref().map(|probe| probe.port))
 	.bind(liveness_probe.as_ref().map(|probe| probe.path.as_str()))
 	.bind(liveness_probe.as_ref().map(|_| ExposedPortType::Http))
+	.bind(now)
 	.execute(&mut **database)
 	.await?;
 
@@ -238,6 +320,13 @@ pub async fn create_deployment(
 					runner: Uuid::nil(),
 					machine_type,
 					current_live_digest: None,
+					pull_secret_id: None,
+					created_at: now,
+					updated_at: now,
+					labels: Default::default(),
+					depends_on: Default::default(),
+					// Canaries are only routed to by the Patr ingress
+					canary: None,
 				},
 			),
 			running_details: DeploymentRunningDetails {
@@ -250,6 +339,11 @@ pub async fn create_deployment(
 				liveness_probe,
 				config_mounts,
 				volumes,
+				resources,
+				scale_to_zero_after: None,
+				log_level: None,
+				max_concurrent_requests: None,
+				access_logging: false,
 			},
 		})
 		.expect("Failed to send deployment created message");
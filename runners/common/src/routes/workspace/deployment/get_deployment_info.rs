@@ -1,4 +1,4 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
 use axum::http::StatusCode;
 use models::api::workspace::deployment::*;
@@ -6,7 +6,8 @@ use models::api::workspace::deployment::*;
 use crate::prelude::*;
 
 /// The handler to get the deployment info. This will return the deployment
-/// details for the given deployment ID.
+/// details for the given deployment ID. Environment variable values are never
+/// masked here, since self-hosted runners are only accessed by their owner.
 pub async fn get_deployment_info(
 	AppRequest {
 		request:
@@ -15,7 +16,9 @@ pub async fn get_deployment_info(
 					workspace_id: _,
 					deployment_id,
 				},
-				query: (),
+				query: GetDeploymentInfoQuery {
+					reveal_secret_values: _,
+				},
 				headers:
 					GetDeploymentInfoRequestHeaders {
 						authorization: _,
@@ -150,6 +153,10 @@ pub async fn get_deployment_info(
 			min_horizontal_scale,
 			max_horizontal_scale,
 			machine_type,
+			cpu_request,
+			cpu_limit,
+			memory_request,
+			memory_limit,
 			deploy_on_push,
 			startup_probe_port,
 			startup_probe_path,
@@ -157,7 +164,13 @@ pub async fn get_deployment_info(
 			liveness_probe_port,
 			liveness_probe_path,
 			liveness_probe_port_type,
-			current_live_digest
+			current_live_digest,
+			reconciliation_status,
+			reconciliation_error,
+			last_reconciliation_attempt,
+			reconciliation_generation,
+			created,
+			updated
 		FROM
 			deployment
 		WHERE
@@ -197,6 +210,13 @@ pub async fn get_deployment_info(
 					runner: Uuid::nil(),
 					current_live_digest,
 					machine_type,
+					pull_secret_id: None,
+					created_at: row.try_get("created")?,
+					updated_at: row.try_get("updated")?,
+					labels: BTreeMap::new(),
+					depends_on: BTreeSet::new(),
+					// Canaries are only routed to by the Patr ingress
+					canary: None,
 				},
 			),
 			running_details: DeploymentRunningDetails {
@@ -215,7 +235,37 @@ pub async fn get_deployment_info(
 					.map(|(port, path)| DeploymentProbe { port, path }),
 				config_mounts,
 				volumes,
+				resources: DeploymentResources {
+					cpu_request: row.try_get("cpu_request")?,
+					cpu_limit: row.try_get("cpu_limit")?,
+					memory_request: row.try_get("memory_request")?,
+					memory_limit: row.try_get("memory_limit")?,
+				},
+				// Only the Patr ingress reports the activity needed to scale to zero
+				scale_to_zero_after: None,
+				// The logs of deployments on self-hosted runners are not captured
+				log_level: None,
+				// Only the Patr ingress limits the requests to a deployment
+				max_concurrent_requests: None,
+				// Only the Patr ingress keeps access logs
+				access_logging: false,
+			},
+			environment_specific_variables: BTreeSet::new(),
+			// Values are never masked by self-hosted runners
+			secret_variables: BTreeSet::new(),
+			// Building from source is only supported by the managed Patr API
+			build_source: None,
+			latest_build: None,
+			reconciliation_status: DeploymentReconciliationStatus {
+				state: row.try_get("reconciliation_status")?,
+				reason: row.try_get("reconciliation_error")?,
+				last_attempt: row.try_get("last_reconciliation_attempt")?,
+				generation: row.try_get::<i64, _>("reconciliation_generation")? as u64,
 			},
+			dependents: BTreeSet::new(),
+			dependency_graph: BTreeMap::new(),
+			replicas: None,
+			canary_requests: None,
 		})
 	})
 	.ok_or(ErrorType::ResourceDoesNotExist)??;
@@ -1,20 +1,29 @@
 use http::StatusCode;
-use models::{api::workspace::deployment::*, utils::Uuid};
+use models::{
+	api::workspace::deployment::*,
+	utils::{ListOrder, ListOrderBy, Uuid},
+};
 
 use crate::prelude::*;
 
 /// The handler to list all deployments in the workspace. This will return
-/// all the deployments.
+/// all the deployments, sorted so that the order is stable across pages.
 pub async fn list_deployment(
 	AppRequest {
 		request:
 			ProcessedApiRequest {
 				path: ListDeploymentPath { workspace_id: _ },
-				query: Paginated {
-					data: (),
-					count,
-					page,
-				},
+				query:
+					Paginated {
+						data:
+							ListDeploymentQuery {
+								order_by,
+								order,
+								label_selector,
+							},
+						count,
+						page,
+					},
 				headers:
 					ListDeploymentRequestHeaders {
 						authorization: _,
@@ -29,26 +38,20 @@ pub async fn list_deployment(
 ) -> Result<AppResponse<ListDeploymentRequest>, ErrorType> {
 	trace!("Listing all deployments");
 
-	let rows = query(
-		r#"
-		SELECT
-			id,
-			name,
-			status,
-			registry,
-			image_name,
-			image_tag,
-			machine_type,
-			current_live_digest
-		FROM
-			deployment
-		LIMIT $1 OFFSET $2;
-		"#,
-	)
-	.bind(u32::try_from(count)?)
-	.bind(u32::try_from(count * page)?)
-	.fetch_all(&mut **database)
-	.await?;
+	// Deployments on self-hosted runners can't have labels, so no deployment
+	// matches a label selector
+	let rows = if label_selector.is_some_and(|selector| !selector.0.is_empty()) {
+		Vec::new()
+	} else {
+		get_deployment_page(
+			&mut **database,
+			order_by.unwrap_or_default(),
+			order.unwrap_or_default(),
+			count,
+			page,
+		)
+		.await?
+	};
 
 	let total_count = rows.len();
 
@@ -77,6 +80,13 @@ pub async fn list_deployment(
 					runner: Uuid::nil(),
 					current_live_digest: None,
 					machine_type,
+					pull_secret_id: None,
+					created_at: row.try_get("created")?,
+					updated_at: row.try_get("updated")?,
+					labels: Default::default(),
+					depends_on: Default::default(),
+					// Canaries are only routed to by the Patr ingress
+					canary: None,
 				},
 			))
 		})
@@ -91,3 +101,155 @@ pub async fn list_deployment(
 		.build()
 		.into_result()
 }
+
+/// Gets a page of deployments, sorted by the given field and then by their
+/// creation time and ID. The ID is always the last thing sorted by, so that
+/// deployments with the same values are still in the same order on every
+/// page, and none of them are skipped or repeated.
+async fn get_deployment_page(
+	connection: &mut DatabaseConnection,
+	order_by: ListOrderBy,
+	order: ListOrder,
+	count: usize,
+	page: usize,
+) -> Result<Vec<<DatabaseType as sqlx::Database>::Row>, ErrorType> {
+	let rows = query(
+		r#"
+		SELECT
+			id,
+			name,
+			status,
+			registry,
+			image_name,
+			image_tag,
+			machine_type,
+			current_live_digest,
+			created,
+			updated
+		FROM
+			deployment
+		ORDER BY
+			CASE WHEN $3 AND $5 THEN name END ASC,
+			CASE WHEN $3 AND NOT $5 THEN name END DESC,
+			CASE WHEN $4 AND $5 THEN status END ASC,
+			CASE WHEN $4 AND NOT $5 THEN status END DESC,
+			CASE WHEN $6 AND $5 THEN updated END ASC,
+			CASE WHEN $6 AND NOT $5 THEN updated END DESC,
+			CASE WHEN $5 THEN created END ASC,
+			CASE WHEN $5 THEN id END ASC,
+			created DESC,
+			id DESC
+		LIMIT $1 OFFSET $2;
+		"#,
+	)
+	.bind(u32::try_from(count)?)
+	.bind(u32::try_from(count * page)?)
+	.bind(order_by == ListOrderBy::Name)
+	.bind(order_by == ListOrderBy::Status)
+	.bind(order == ListOrder::Ascending)
+	.bind(order_by == ListOrderBy::LastUpdated)
+	.fetch_all(&mut *connection)
+	.await?;
+
+	Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::BTreeSet;
+
+	use models::utils::{ListOrder, ListOrderBy};
+	use sqlx::Connection;
+
+	use super::get_deployment_page;
+	use crate::prelude::*;
+
+	#[tokio::test]
+	async fn every_deployment_appears_on_exactly_one_page() {
+		let mut connection = DatabaseConnection::connect("sqlite::memory:")
+			.await
+			.unwrap();
+
+		query(
+			r#"
+			CREATE TABLE deployment(
+				id TEXT NOT NULL PRIMARY KEY,
+				name TEXT NOT NULL,
+				status TEXT NOT NULL,
+				registry TEXT NOT NULL,
+				image_name TEXT NOT NULL,
+				image_tag TEXT NOT NULL,
+				machine_type TEXT NOT NULL,
+				current_live_digest TEXT,
+				created DATETIME NOT NULL,
+				updated DATETIME NOT NULL
+			);
+			"#,
+		)
+		.execute(&mut connection)
+		.await
+		.unwrap();
+
+		// Lots of deployments share the same name, status, creation and update
+		// time, so that only the tie-breakers keep the order stable
+		let mut ids = BTreeSet::new();
+		for index in 0..23 {
+			let id = Uuid::new_v4().to_string();
+			query(
+				r#"
+				INSERT INTO
+					deployment(
+						id,
+						name,
+						status,
+						registry,
+						image_name,
+						image_tag,
+						machine_type,
+						created,
+						updated
+					)
+				VALUES
+					($1, $2, $3, 'docker.io', 'nginx', 'latest', $4, $5, $6);
+				"#,
+			)
+			.bind(&id)
+			.bind(format!("deployment-{}", index % 3))
+			.bind(if index % 2 == 0 { "running" } else { "stopped" })
+			.bind(Uuid::nil().to_string())
+			.bind(format!("2024-01-0{} 00:00:00", index % 4 + 1))
+			.bind(format!("2024-02-0{} 00:00:00", index % 5 + 1))
+			.execute(&mut connection)
+			.await
+			.unwrap();
+			ids.insert(id);
+		}
+
+		for order_by in [
+			ListOrderBy::Created,
+			ListOrderBy::Name,
+			ListOrderBy::Status,
+			ListOrderBy::LastUpdated,
+		] {
+			for order in [ListOrder::Ascending, ListOrder::Descending] {
+				let mut listed = Vec::new();
+				for page in 0..5 {
+					let rows = get_deployment_page(&mut connection, order_by, order, 5, page)
+						.await
+						.unwrap();
+					listed.extend(
+						rows.iter()
+							.map(|row| row.try_get::<String, _>("id").unwrap()),
+					);
+				}
+
+				assert_eq!(listed.len(), ids.len(), "{order_by:?} {order:?}");
+				assert_eq!(
+					listed.into_iter().collect::<BTreeSet<_>>(),
+					ids,
+					"{order_by:?} {order:?}"
+				);
+			}
+		}
+	}
+}
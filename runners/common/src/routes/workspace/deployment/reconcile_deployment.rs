@@ -0,0 +1,65 @@
+use axum::http::StatusCode;
+use models::api::workspace::{deployment::*, runner::StreamRunnerDataForWorkspaceServerMsg};
+
+use crate::prelude::*;
+
+/// The handler to force the runner to reconcile a deployment again. The
+/// deployment is marked as pending until it has been reconciled.
+pub async fn reconcile_deployment(
+	AppRequest {
+		request:
+			ProcessedApiRequest {
+				path: ReconcileDeploymentPath {
+					workspace_id: _,
+					deployment_id,
+				},
+				query: (),
+				headers:
+					ReconcileDeploymentRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: ReconcileDeploymentRequestProcessed,
+			},
+		database,
+		runner_changes_sender,
+		config: _,
+	}: AppRequest<'_, ReconcileDeploymentRequest>,
+) -> Result<AppResponse<ReconcileDeploymentRequest>, ErrorType> {
+	info!("Reconciling deployment: {deployment_id}");
+
+	let result = query(
+		r#"
+		UPDATE
+			deployment
+		SET
+			reconciliation_status = 'pending',
+			reconciliation_generation = reconciliation_generation + 1
+		WHERE
+			id = $1 AND
+			deleted IS NULL;
+		"#,
+	)
+	.bind(deployment_id)
+	.execute(&mut **database)
+	.await?;
+
+	if result.rows_affected() == 0 {
+		return Err(ErrorType::ResourceDoesNotExist);
+	}
+
+	runner_changes_sender
+		.send(
+			StreamRunnerDataForWorkspaceServerMsg::DeploymentReconciliationRequested {
+				id: deployment_id,
+			},
+		)
+		.expect("Failed to send deployment reconciliation message");
+
+	AppResponse::builder()
+		.body(ReconcileDeploymentResponse)
+		.headers(())
+		.status_code(StatusCode::ACCEPTED)
+		.build()
+		.into_result()
+}
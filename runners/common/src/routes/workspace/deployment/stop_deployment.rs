@@ -1,5 +1,6 @@
 use http::StatusCode;
 use models::{api::workspace::deployment::*, prelude::*};
+use time::OffsetDateTime;
 
 use crate::prelude::*;
 
@@ -33,12 +34,16 @@ pub async fn stop_deployment(
 		UPDATE
 			deployment
 		SET
-			status = 'stopped'
+			status = 'stopped',
+			reconciliation_status = 'pending',
+			reconciliation_generation = reconciliation_generation + 1,
+			updated = $2
 		WHERE
 			id = $1
 		"#,
 	)
 	.bind(deployment_id)
+	.bind(OffsetDateTime::now_utc())
 	.execute(&mut **database)
 	.await?;
 
@@ -1,5 +1,6 @@
 use axum::http::StatusCode;
 use models::api::workspace::deployment::*;
+use time::OffsetDateTime;
 
 use crate::{
 	app::{AppRequest, ProcessedApiRequest},
@@ -9,8 +10,11 @@ use crate::{
 /// Update deployment details. This endpoint is used to update the deployment
 /// details. The deployment details that can be updated are the name, machine
 /// type, deploy on push, min horizontal scale, max horizontal scale, ports,
-/// environment variables, startup probe, liveness probe, config mounts, and
-/// volumes. At least one of the values must be updated.
+/// environment variables, startup probe, liveness probe, config mounts,
+/// volumes, and CPU and memory requests and limits. At least one of the values
+/// must be updated, and only the values that are provided are updated. If an
+/// `If-Match` header is given, the deployment is only updated if it hasn't been
+/// modified since the version in the header.
 pub async fn update_deployment(
 	AppRequest {
 		request:
@@ -24,6 +28,7 @@ pub async fn update_deployment(
 					UpdateDeploymentRequestHeaders {
 						authorization: _,
 						user_agent: _,
+						if_match,
 					},
 				body:
 					UpdateDeploymentRequestProcessed {
@@ -35,10 +40,19 @@ pub async fn update_deployment(
 						max_horizontal_scale,
 						ports,
 						environment_variables,
+						environment_specific_variables: _,
+						secret_variables: _,
 						startup_probe,
 						liveness_probe,
 						config_mounts,
 						volumes,
+						resources,
+						scale_to_zero_after,
+						log_level,
+						max_concurrent_requests,
+						access_logging,
+						labels,
+						depends_on,
 					},
 			},
 		database,
@@ -62,6 +76,7 @@ pub async fn update_deployment(
 		.or(liveness_probe.as_ref().map(|_| 0))
 		.or(config_mounts.as_ref().map(|_| 0))
 		.or(volumes.as_ref().map(|_| 0))
+		.or(resources.as_ref().map(|_| 0))
 		.is_none()
 	{
 		debug!(
@@ -71,10 +86,61 @@ pub async fn update_deployment(
 		return Err(ErrorType::WrongParameters);
 	}
 
-	query(
+	// Requests to self-hosted runners don't go through the Patr ingress, so
+	// there's no way to know when a deployment is idle
+	if scale_to_zero_after.flatten().is_some() {
+		debug!("Deployment `{deployment_id}` on a self-hosted runner cannot be scaled to zero");
+		return Err(ErrorType::WrongParameters);
+	}
+
+	// The logs of deployments on self-hosted runners are not captured by Patr
+	if log_level.flatten().is_some() {
+		debug!("Deployment `{deployment_id}` on a self-hosted runner cannot have a log level");
+		return Err(ErrorType::WrongParameters);
+	}
+
+	// Requests to self-hosted runners don't go through the Patr ingress, so
+	// there's nothing to limit the requests to a deployment
+	if max_concurrent_requests
+		.flatten()
+		.is_some_and(|limit| limit > 0)
+	{
+		debug!(
+			"Deployment `{deployment_id}` on a self-hosted runner cannot limit its concurrent requests"
+		);
+		return Err(ErrorType::WrongParameters);
+	}
+
+	// Requests to self-hosted runners don't go through the Patr ingress, so
+	// there's nothing to log them
+	if access_logging == Some(true) {
+		debug!("Deployment `{deployment_id}` on a self-hosted runner cannot have access logs");
+		return Err(ErrorType::WrongParameters);
+	}
+
+	// Labels are only stored by the Patr API
+	if labels.is_some_and(|labels| !labels.is_empty()) {
+		debug!("Deployment `{deployment_id}` on a self-hosted runner cannot have labels");
+		return Err(ErrorType::WrongParameters);
+	}
+
+	// The order in which deployments are started is only tracked by the Patr
+	// API
+	if depends_on.is_some_and(|depends_on| !depends_on.is_empty()) {
+		debug!(
+			"Deployment `{deployment_id}` on a self-hosted runner cannot depend on other deployments"
+		);
+		return Err(ErrorType::WrongParameters);
+	}
+
+	let deployment = query(
 		r#"
 		SELECT
-			id
+			updated,
+			cpu_request,
+			cpu_limit,
+			memory_request,
+			memory_limit
 		FROM
 			deployment
 		WHERE
@@ -86,6 +152,35 @@ pub async fn update_deployment(
 	.fetch_optional(&mut **database)
 	.await?
 	.ok_or(ErrorType::ResourceDoesNotExist)?;
+	let updated: OffsetDateTime = deployment.try_get("updated")?;
+
+	// Only the resources that are set are updated. The capacity of machine
+	// types is only known to the Patr API, so only the requests can be checked
+	// against the limits here
+	let resources = resources
+		.map(|resources| {
+			let current = DeploymentResources {
+				cpu_request: deployment.try_get("cpu_request")?,
+				cpu_limit: deployment.try_get("cpu_limit")?,
+				memory_request: deployment.try_get("memory_request")?,
+				memory_limit: deployment.try_get("memory_limit")?,
+			};
+			Ok::<_, ErrorType>(current.updated_with(resources))
+		})
+		.transpose()?;
+	if let Some(resources) = resources {
+		resources.validate_requests()?;
+	}
+
+	if let Some(if_match) = if_match {
+		if !if_match.precondition_passes(&Deployment::etag_for(updated)) {
+			debug!(
+				"Deployment `{}` was modified since the version it was fetched at",
+				deployment_id
+			);
+			return Err(ErrorType::ResourceModified);
+		}
+	}
 
 	if let Some(ports) = ports {
 		if ports.is_empty() {
@@ -131,11 +226,15 @@ pub async fn update_deployment(
 	}
 
 	// Updating deployment details
+	let updated_at = OffsetDateTime::now_utc();
 	query(
 		r#"
 		UPDATE
 			deployment
 		SET
+			reconciliation_status = 'pending',
+			reconciliation_generation = reconciliation_generation + 1,
+			updated = $11,
 			name = COALESCE($1, name),
 			machine_type = COALESCE($2, machine_type),
 			deploy_on_push = COALESCE($3, deploy_on_push),
@@ -145,6 +244,8 @@ pub async fn update_deployment(
 				CASE
 					WHEN $6 = 0 THEN
 						NULL
+					WHEN $6 IS NULL THEN
+						startup_probe_port
 					ELSE
 						$6
 				END
@@ -153,6 +254,8 @@ pub async fn update_deployment(
 				CASE
 					WHEN $6 = 0 THEN
 						NULL
+					WHEN $6 IS NULL THEN
+						startup_probe_path
 					ELSE
 						$7
 				END
@@ -171,6 +274,8 @@ pub async fn update_deployment(
 				CASE
 					WHEN $8 = 0 THEN
 						NULL
+					WHEN $8 IS NULL THEN
+						liveness_probe_port
 					ELSE
 						$8
 				END
@@ -179,6 +284,8 @@ pub async fn update_deployment(
 				CASE
 					WHEN $8 = 0 THEN
 						NULL
+					WHEN $8 IS NULL THEN
+						liveness_probe_path
 					ELSE
 						$9
 				END
@@ -192,7 +299,11 @@ pub async fn update_deployment(
 					ELSE
 						'http'
 				END
-			)
+			),
+			cpu_request = CASE WHEN $12 THEN $13 ELSE cpu_request END,
+			cpu_limit = CASE WHEN $12 THEN $14 ELSE cpu_limit END,
+			memory_request = CASE WHEN $12 THEN $15 ELSE memory_request END,
+			memory_limit = CASE WHEN $12 THEN $16 ELSE memory_limit END
 		WHERE
 			id = $10;
 		"#,
@@ -202,11 +313,36 @@ pub async fn update_deployment(
 	.bind(deploy_on_push)
 	.bind(min_horizontal_scale)
 	.bind(max_horizontal_scale)
-	.bind(startup_probe.as_ref().map(|probe| probe.port))
-	.bind(startup_probe.as_ref().map(|probe| probe.path.as_str()))
-	.bind(liveness_probe.as_ref().map(|probe| probe.port))
-	.bind(liveness_probe.as_ref().map(|probe| probe.path.as_str()))
+	// A port of 0 removes the probe, and no port leaves it unchanged
+	.bind(
+		startup_probe
+			.as_ref()
+			.map(|probe| probe.as_ref().map_or(0, |probe| probe.port)),
+	)
+	.bind(
+		startup_probe
+			.as_ref()
+			.and_then(Option::as_ref)
+			.map(|probe| probe.path.as_str()),
+	)
+	.bind(
+		liveness_probe
+			.as_ref()
+			.map(|probe| probe.as_ref().map_or(0, |probe| probe.port)),
+	)
+	.bind(
+		liveness_probe
+			.as_ref()
+			.and_then(Option::as_ref)
+			.map(|probe| probe.path.as_str()),
+	)
 	.bind(deployment_id)
+	.bind(updated_at)
+	.bind(resources.is_some())
+	.bind(resources.and_then(|resources| resources.cpu_request))
+	.bind(resources.and_then(|resources| resources.cpu_limit))
+	.bind(resources.and_then(|resources| resources.memory_request))
+	.bind(resources.and_then(|resources| resources.memory_limit))
 	.execute(&mut **database)
 	.await?;
 
@@ -335,7 +471,10 @@ pub async fn update_deployment(
 	}
 
 	AppResponse::builder()
-		.body(UpdateDeploymentResponse)
+		.body(UpdateDeploymentResponse {
+			updated_at,
+			change_request_id: None,
+		})
 		.headers(())
 		.status_code(StatusCode::ACCEPTED)
 		.build()
@@ -1,7 +1,11 @@
-use std::{collections::BTreeMap, pin::pin};
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	pin::pin,
+};
 
 use futures::StreamExt;
-use models::api::workspace::deployment::*;
+use models::api::workspace::deployment::{build::*, *};
+use time::OffsetDateTime;
 use tokio::time::{Duration, Instant};
 
 use crate::{prelude::*, utils::delayed_future::DelayedFuture};
@@ -67,14 +71,35 @@ where
 		self.reconciliation_list
 			.retain(|message| message.value() != &deployment_id);
 
+		// The reason the deployment could not be reconciled, which is reported
+		// back along with the result
+		let mut error = None;
+		// The generation of the deployment that is being reconciled. The
+		// result isn't reported if the deployment couldn't be fetched (or was
+		// deleted), since the generation it would be for is unknown
+		let mut generation = None;
+		// Deployments that are still waiting for the deployments they depend on
+		// (or that depend on them) aren't reported until the wait times out
+		let mut is_waiting = false;
+
 		let result = 'reconcile: {
 			let GetDeploymentInfoResponse {
-				deployment,
+				mut deployment,
 				running_details,
+				environment_specific_variables: _,
+				secret_variables: _,
+				build_source: _,
+				latest_build: _,
+				reconciliation_status,
+				dependents,
+				dependency_graph: _,
+				replicas: _,
+				canary_requests: _,
 			} = match self.get_deployment_info(deployment_id).await {
 				Ok(response) => response,
 				Err(ErrorType::ResourceDoesNotExist) => {
 					info!("Deployment `{}` does not exist. Deleting", deployment_id);
+					self.dependency_waits.remove(&deployment_id);
 					break 'reconcile self.delete_deployment(deployment_id).await;
 				}
 				Err(err) => {
@@ -85,18 +110,87 @@ where
 					break 'reconcile Err(Duration::from_secs(5));
 				}
 			};
+			generation = Some(reconciliation_status.generation);
+
+			if let Some(pending_id) = self
+				.get_pending_dependency(&deployment.data, &dependents)
+				.await
+			{
+				let waiting_since = *self
+					.dependency_waits
+					.entry(deployment_id)
+					.or_insert_with(Instant::now);
+
+				if waiting_since.elapsed() < constants::DEPLOYMENT_DEPENDENCY_TIMEOUT {
+					debug!(
+						"Deployment `{}` is waiting for deployment `{}`",
+						deployment_id, pending_id
+					);
+					is_waiting = true;
+					break 'reconcile Err(Duration::from_secs(5));
+				}
+
+				if deployment.status == DeploymentStatus::Stopped {
+					// The user asked for the deployment to stop, so it is
+					// stopped even if the deployments that depend on it
+					// haven't
+					warn!(
+						"Deployment `{}` still has running dependents. Stopping it anyway",
+						deployment_id
+					);
+				} else {
+					error = Some(format!(
+						"Deployment `{}`, which this deployment depends on, is not healthy",
+						pending_id
+					));
+					break 'reconcile Err(Duration::from_secs(30));
+				}
+			}
+			self.dependency_waits.remove(&deployment_id);
+
+			// A deployment that can't be held to the egress policy of the
+			// workspace is not run at all
+			if let Some(egress_policy_error) = &self.egress_policy_error {
+				error = Some(format!(
+					"The egress policy of the workspace can't be enforced: {egress_policy_error}"
+				));
+				if let Err(err) = self.delete_deployment(deployment_id).await {
+					break 'reconcile Err(err);
+				}
+				break 'reconcile Err(Duration::from_secs(60));
+			}
+
+			// The canary is never reported as ready by runners that can't
+			// run it, so the ingress sends all the requests to the
+			// deployment
+			let canary_error = if deployment.canary.is_some() && !E::SUPPORTS_CANARIES {
+				deployment.data.canary = None;
+				Some("This runner does not support canaries".to_string())
+			} else {
+				None
+			};
 
 			if let Err(err) = self
 				.executor
 				.upsert_deployment(deployment, running_details)
 				.await
 			{
+				error = Some(format!(
+					"The runner failed to apply the deployment. Retrying in {} seconds",
+					err.as_secs()
+				));
 				break 'reconcile Err(err);
 			}
 
+			error = canary_error;
 			Ok(())
 		};
 
+		if let Some(generation) = generation.filter(|_| !is_waiting) {
+			self.report_reconciliation(deployment_id, generation, error)
+				.await;
+		}
+
 		if let Err(wait_time) = result {
 			self.reconciliation_list.push(DelayedFuture::new(
 				Instant::now() + wait_time,
@@ -107,6 +201,167 @@ where
 		self.recheck_next_reconcile_future();
 	}
 
+	/// Gets the deployment that has to change its state before the given
+	/// deployment can be applied, if any. A deployment that is stopped waits
+	/// for all the deployments that depend on it to stop first, and any other
+	/// deployment waits for all the deployments it depends on to be healthy
+	/// (see [`DeploymentStatus::satisfies_dependency`]). A deployment that is
+	/// scaled to zero doesn't wait for anything, since it is started by the
+	/// requests it receives.
+	async fn get_pending_dependency(
+		&self,
+		deployment: &Deployment,
+		dependents: &BTreeSet<Uuid>,
+	) -> Option<Uuid> {
+		match deployment.status {
+			DeploymentStatus::Cold => None,
+			DeploymentStatus::Stopped => {
+				for &dependent_id in dependents {
+					match self.get_deployment_status(dependent_id).await {
+						Ok((status, _)) if status.is_running() || status.is_transitioning() => {
+							return Some(dependent_id);
+						}
+						// A dependent that was deleted doesn't need to be stopped
+						Ok(_) | Err(ErrorType::ResourceDoesNotExist) => (),
+						Err(_) => return Some(dependent_id),
+					}
+				}
+				None
+			}
+			_ => {
+				for &dependency_id in &deployment.depends_on {
+					let is_healthy = self
+						.get_deployment_status(dependency_id)
+						.await
+						.is_ok_and(|(status, replicas)| status.satisfies_dependency(replicas));
+					if !is_healthy {
+						return Some(dependency_id);
+					}
+				}
+				None
+			}
+		}
+	}
+
+	/// Get the current status of a deployment, along with the readiness of its
+	/// replicas if it was reported. Unlike [`Self::get_deployment_info`], this
+	/// doesn't need the values of the secret environment variables of the
+	/// deployment.
+	async fn get_deployment_status(
+		&self,
+		deployment_id: Uuid,
+	) -> Result<(DeploymentStatus, Option<DeploymentReplicaReadiness>), ErrorType> {
+		match &self.state.config.mode {
+			RunnerMode::SelfHosted {
+				password_pepper: _,
+				jwt_secret: _,
+			} => self
+				.get_deployment_info(deployment_id)
+				.await
+				.map(|response| (response.deployment.status, response.replicas)),
+			RunnerMode::Managed {
+				workspace_id,
+				runner_id: _,
+				api_token,
+				user_agent,
+			} => client::make_request(
+				ApiRequest::<GetDeploymentInfoRequest>::builder()
+					.path(GetDeploymentInfoPath {
+						workspace_id: *workspace_id,
+						deployment_id,
+					})
+					.headers(GetDeploymentInfoRequestHeaders {
+						authorization: api_token.clone(),
+						user_agent: user_agent.clone(),
+					})
+					.query(GetDeploymentInfoQuery {
+						reveal_secret_values: false,
+					})
+					.body(GetDeploymentInfoRequest)
+					.build(),
+			)
+			.await
+			.map(|response| (response.body.deployment.status, response.body.replicas))
+			.map_err(|err| err.body.code),
+		}
+	}
+
+	/// Report the result of reconciling a deployment, so that users can see
+	/// whether their changes have been applied. The result is stored in the
+	/// local database if the runner is self-hosted, or sent to the API if the
+	/// runner is managed. Failing to report is not retried, since the next
+	/// reconciliation will report again. The report is only applied if the
+	/// deployment is still at the given generation.
+	async fn report_reconciliation(
+		&self,
+		deployment_id: Uuid,
+		generation: u64,
+		error: Option<String>,
+	) {
+		let state = if error.is_some() {
+			DeploymentReconciliationState::Failed
+		} else {
+			DeploymentReconciliationState::InSync
+		};
+
+		let result = match &self.state.config.mode {
+			RunnerMode::SelfHosted {
+				password_pepper: _,
+				jwt_secret: _,
+			} => query(
+				r#"
+				UPDATE
+					deployment
+				SET
+					reconciliation_status = $1,
+					reconciliation_error = $2,
+					last_reconciliation_attempt = $3
+				WHERE
+					id = $4 AND
+					reconciliation_generation = $5;
+				"#,
+			)
+			.bind(state)
+			.bind(error)
+			.bind(OffsetDateTime::now_utc())
+			.bind(deployment_id)
+			.bind(generation as i64)
+			.execute(&self.state.database)
+			.await
+			.map(|_| ())
+			.map_err(|err| format!("{:?}", err)),
+			RunnerMode::Managed {
+				workspace_id,
+				runner_id: _,
+				api_token,
+				user_agent,
+			} => client::make_request(
+				ApiRequest::<ReportDeploymentReconciliationRequest>::builder()
+					.path(ReportDeploymentReconciliationPath {
+						workspace_id: *workspace_id,
+						deployment_id,
+					})
+					.headers(ReportDeploymentReconciliationRequestHeaders {
+						authorization: api_token.clone(),
+						user_agent: user_agent.clone(),
+					})
+					.query(())
+					.body(ReportDeploymentReconciliationRequest { error, generation })
+					.build(),
+			)
+			.await
+			.map(|_| ())
+			.map_err(|err| format!("{:?}", err.body.code)),
+		};
+
+		if let Err(err) = result {
+			warn!(
+				"Failed to report the reconciliation of deployment `{}`: {}",
+				deployment_id, err
+			);
+		}
+	}
+
 	/// Get all the local deployments. This function will get all the local
 	/// deployments from the SQLite database.
 	async fn get_all_local_deployments(&mut self) -> Result<Vec<Uuid>, ErrorType> {
@@ -261,6 +516,10 @@ where
 						min_horizontal_scale,
 						max_horizontal_scale,
 						machine_type,
+						cpu_request,
+						cpu_limit,
+						memory_request,
+						memory_limit,
 						deploy_on_push,
 						startup_probe_port,
 						startup_probe_path,
@@ -268,7 +527,13 @@ where
 						liveness_probe_port,
 						liveness_probe_path,
 						liveness_probe_port_type,
-						current_live_digest
+						current_live_digest,
+						reconciliation_status,
+						reconciliation_error,
+						last_reconciliation_attempt,
+						reconciliation_generation,
+						created,
+						updated
 					FROM
 						deployment
 					WHERE
@@ -310,6 +575,13 @@ where
 								runner: Uuid::nil(),
 								current_live_digest,
 								machine_type,
+								pull_secret_id: None,
+								created_at: row.try_get("created")?,
+								updated_at: row.try_get("updated")?,
+								labels: Default::default(),
+								depends_on: Default::default(),
+								// Canaries are only routed to by the Patr ingress
+								canary: None,
 							},
 						),
 						running_details: DeploymentRunningDetails {
@@ -328,7 +600,37 @@ where
 								.map(|(port, path)| DeploymentProbe { port, path }),
 							config_mounts,
 							volumes,
+							resources: DeploymentResources {
+								cpu_request: row.try_get("cpu_request")?,
+								cpu_limit: row.try_get("cpu_limit")?,
+								memory_request: row.try_get("memory_request")?,
+								memory_limit: row.try_get("memory_limit")?,
+							},
+							// Only the Patr ingress reports the activity needed to scale to zero
+							scale_to_zero_after: None,
+							// The logs of deployments on self-hosted runners are not captured
+							log_level: None,
+							// Only the Patr ingress limits the requests to a deployment
+							max_concurrent_requests: None,
+							// Only the Patr ingress keeps access logs
+							access_logging: false,
+						},
+						environment_specific_variables: BTreeSet::new(),
+						secret_variables: BTreeSet::new(),
+						// Building from source is only supported by the managed Patr API
+						build_source: None,
+						latest_build: None,
+						reconciliation_status: DeploymentReconciliationStatus {
+							state: row.try_get("reconciliation_status")?,
+							reason: row.try_get("reconciliation_error")?,
+							last_attempt: row.try_get("last_reconciliation_attempt")?,
+							generation: row.try_get::<i64, _>("reconciliation_generation")? as u64,
 						},
+						// Deployments on self-hosted runners can't depend on each other
+						dependents: BTreeSet::new(),
+						dependency_graph: BTreeMap::new(),
+						replicas: None,
+						canary_requests: None,
 					})
 				})
 				.ok_or(ErrorType::ResourceDoesNotExist)?
@@ -348,7 +650,11 @@ where
 						authorization: api_token.clone(),
 						user_agent: user_agent.clone(),
 					})
-					.query(())
+					// The actual values of secret environment variables are needed to
+					// run the deployment
+					.query(GetDeploymentInfoQuery {
+						reveal_secret_values: true,
+					})
 					.body(GetDeploymentInfoRequest)
 					.build(),
 			)
@@ -360,7 +666,7 @@ where
 					deployment_id, err
 				);
 				debug!("Retrying in 5 seconds");
-				err.body.error
+				err.body.code
 			}),
 		}
 	}
@@ -389,4 +695,85 @@ where
 
 		Ok(())
 	}
+
+	/// Build the image of a deployment from its build source in the background,
+	/// reporting the progress of the build to the Patr API. Building from
+	/// source is only supported in managed mode, since the built image is
+	/// pushed to the Patr registry.
+	pub(super) fn build_deployment_image(
+		&self,
+		deployment_id: Uuid,
+		build_id: Uuid,
+		build_source: DeploymentBuildSource,
+		image: String,
+	) {
+		let RunnerMode::Managed {
+			workspace_id,
+			runner_id: _,
+			api_token,
+			user_agent,
+		} = &self.state.config.mode
+		else {
+			warn!(
+				"Ignoring build `{}` of deployment `{}` in self-hosted mode",
+				build_id, deployment_id
+			);
+			return;
+		};
+
+		let report = {
+			let workspace_id = *workspace_id;
+			let api_token = api_token.clone();
+			let user_agent = user_agent.clone();
+
+			move |status, image_digest, error| {
+				client::make_request(
+					ApiRequest::<UpdateDeploymentBuildRequest>::builder()
+						.path(UpdateDeploymentBuildPath {
+							workspace_id,
+							deployment_id,
+							build_id,
+						})
+						.headers(UpdateDeploymentBuildRequestHeaders {
+							authorization: api_token.clone(),
+							user_agent: user_agent.clone(),
+						})
+						.query(())
+						.body(UpdateDeploymentBuildRequest {
+							status,
+							image_digest,
+							error,
+						})
+						.build(),
+				)
+			}
+		};
+		let build = self
+			.executor
+			.build_deployment_image(build_id, build_source, image);
+
+		tokio::spawn(async move {
+			info!("Building image of deployment `{}`", deployment_id);
+			if let Err(err) = report(DeploymentBuildStatus::Building, None, None).await {
+				warn!(
+					"Failed to report build `{}` as started: {:?}",
+					build_id, err
+				);
+			}
+
+			let result = match build.await {
+				Ok(digest) => report(DeploymentBuildStatus::Succeeded, Some(digest), None).await,
+				Err(error) => {
+					warn!("Build `{}` failed: {}", build_id, error);
+					report(DeploymentBuildStatus::Failed, None, Some(error)).await
+				}
+			};
+			if let Err(err) = result {
+				error!(
+					"Failed to report the result of build `{}`: {:?}",
+					build_id, err
+				);
+			}
+		});
+	}
 }
@@ -0,0 +1,73 @@
+use models::api::workspace::egress_policy::*;
+
+use crate::prelude::*;
+
+impl<E> super::Runner<E>
+where
+	E: RunnerExecutor + Clone + 'static,
+{
+	/// Reconcile the egress policy of the workspace. The policy is fetched from
+	/// the API and applied by the executor. Self-hosted runners don't have an
+	/// egress policy, so nothing is applied for them.
+	pub(super) async fn reconcile_egress_policy(&mut self) {
+		let RunnerMode::Managed {
+			workspace_id,
+			runner_id: _,
+			api_token,
+			user_agent,
+		} = &self.state.config.mode
+		else {
+			trace!("Egress policies are not supported in self-hosted mode");
+			return;
+		};
+
+		let policy = client::make_request(
+			ApiRequest::<GetEgressPolicyRequest>::builder()
+				.path(GetEgressPolicyPath {
+					workspace_id: *workspace_id,
+				})
+				.headers(GetEgressPolicyRequestHeaders {
+					authorization: api_token.clone(),
+					user_agent: user_agent.clone(),
+				})
+				.query(())
+				.body(GetEgressPolicyRequest)
+				.build(),
+		)
+		.await;
+
+		match policy {
+			Ok(response) => {
+				self.apply_egress_policy(response.body.policy).await;
+			}
+			Err(err) => {
+				// The policy will be fetched again on the next full
+				// reconciliation
+				warn!("Failed to get the egress policy: {:?}", err.body.code);
+			}
+		}
+	}
+
+	/// Apply an egress policy to all the deployments of the runner. If the
+	/// policy fails to apply, it is applied again on the next full
+	/// reconciliation, since the latest policy is fetched then, and the
+	/// deployments are not run until it does. Returns whether the deployments
+	/// went from being allowed to run to not, or the other way around, in which
+	/// case they have to be reconciled again.
+	pub(super) async fn apply_egress_policy(&mut self, policy: WorkspaceEgressPolicy) -> bool {
+		info!(
+			"Applying egress policy with {} allowed and {} denied rules",
+			policy.allow.len(),
+			policy.deny.len()
+		);
+
+		let error = self.executor.apply_egress_policy(policy).await.err();
+		if let Some(err) = &error {
+			warn!("Failed to apply the egress policy: {}", err);
+		}
+
+		let changed = error.is_some() != self.egress_policy_error.is_some();
+		self.egress_policy_error = error;
+		changed
+	}
+}
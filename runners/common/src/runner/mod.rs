@@ -1,4 +1,4 @@
-use std::{future::IntoFuture, net::SocketAddr, pin::pin};
+use std::{collections::BTreeMap, future::IntoFuture, net::SocketAddr, pin::pin};
 
 use futures::{
 	future::{self, BoxFuture, Either},
@@ -11,7 +11,7 @@ use tokio::{
 	net::TcpListener,
 	sync::mpsc::unbounded_channel,
 	task,
-	time::{self, Duration},
+	time::{self, Duration, Instant},
 };
 use tokio_stream::wrappers::UnboundedReceiverStream;
 use tracing::{level_filters::LevelFilter, Dispatch, Level};
@@ -25,6 +25,8 @@ use crate::{db, prelude::*, utils::delayed_future::DelayedFuture};
 
 /// All deployment related functions for the runner
 mod deployment;
+/// All functions related to the egress policy of the workspace
+mod egress_policy;
 
 /// The runner is the main struct that is used to run the resources.
 ///
@@ -43,6 +45,14 @@ where
 	/// A list of resources that need to be reconciled at a later time
 	/// This is used to retry resources that failed to reconcile
 	reconciliation_list: Vec<DelayedFuture<Uuid>>,
+	/// The time since which each deployment has been waiting for the
+	/// deployments it depends on (or that depend on it) before it can be
+	/// reconciled
+	dependency_waits: BTreeMap<Uuid, Instant>,
+	/// The reason the egress policy of the workspace could not be applied, if
+	/// it couldn't. No deployments are run while this is set, since they would
+	/// be able to connect to destinations that the policy doesn't allow
+	egress_policy_error: Option<String>,
 	/// The future that will resolve to the next resource that needs to be
 	/// reconciled
 	next_reconcile_future: BoxFuture<'static, Uuid>,
@@ -271,6 +281,8 @@ where
 				executor,
 				state,
 				reconciliation_list,
+				dependency_waits: BTreeMap::new(),
+				egress_policy_error: None,
 				next_reconcile_future,
 			},
 			runner_changes_receiver,
@@ -282,6 +294,7 @@ where
 	/// runner is responsible for.
 	async fn reconcile_all(&mut self) {
 		// Reconcile all resources
+		self.reconcile_egress_policy().await;
 		self.reconcile_all_deployments().await;
 	}
 
@@ -290,6 +303,69 @@ where
 	/// message is for.
 	async fn handle_server_message(&mut self, msg: StreamRunnerDataForWorkspaceServerMsg) {
 		info!("Handling server message: {:?}", msg);
+
+		// Builds don't change the deployment until they succeed, at which point
+		// the API updates the deployment. So they are not reconciled here
+		if let StreamRunnerDataForWorkspaceServerMsg::DeploymentBuildRequested {
+			deployment_id,
+			build_id,
+			build_source,
+			image,
+		} = msg
+		{
+			self.build_deployment_image(deployment_id, build_id, build_source, image);
+			return;
+		}
+
+		// The egress policy applies to the whole workspace, so it is applied
+		// as is instead of being reconciled as a single resource
+		if let StreamRunnerDataForWorkspaceServerMsg::WorkspaceEgressPolicyUpdated {
+			workspace_id: _,
+			policy,
+		} = msg
+		{
+			if self.apply_egress_policy(policy).await {
+				self.reconcile_all_deployments().await;
+			}
+			return;
+		}
+
+		// Users of databases are changed as they are, since the credentials
+		// are only ever sent by the API
+		match msg {
+			StreamRunnerDataForWorkspaceServerMsg::DatabaseUserCreated {
+				database_id,
+				engine,
+				user,
+			} => {
+				let username = user.username.clone();
+				if let Err(err) = self
+					.executor
+					.create_database_user(database_id, engine, user)
+					.await
+				{
+					warn!("Failed to create user `{username}` of database `{database_id}`: {err}");
+				}
+				return;
+			}
+			StreamRunnerDataForWorkspaceServerMsg::DatabaseUserDropped {
+				database_id,
+				engine,
+				username,
+				owner,
+			} => {
+				if let Err(err) = self
+					.executor
+					.drop_database_user(database_id, engine, username.clone(), owner)
+					.await
+				{
+					warn!("Failed to drop user `{username}` of database `{database_id}`: {err}");
+				}
+				return;
+			}
+			_ => (),
+		}
+
 		// if this resource is already queued for reconciliation, remove that
 		let resource_id = get_resource_id_from_message(&msg);
 
@@ -402,5 +478,10 @@ fn get_resource_id_from_message(message: &StreamRunnerDataForWorkspaceServerMsg)
 		DeploymentCreated { deployment, .. } => deployment.id,
 		DeploymentUpdated { deployment, .. } => deployment.id,
 		DeploymentDeleted { id } => *id,
+		DeploymentReconciliationRequested { id } => *id,
+		DeploymentBuildRequested { deployment_id, .. } => *deployment_id,
+		WorkspaceEgressPolicyUpdated { workspace_id, .. } => *workspace_id,
+		DatabaseUserCreated { database_id, .. } => *database_id,
+		DatabaseUserDropped { database_id, .. } => *database_id,
 	}
 }
@@ -43,8 +43,10 @@ where
 			status_code: http::StatusCode::INTERNAL_SERVER_ERROR,
 			body: ApiErrorResponseBody {
 				success: False,
-				error: ErrorType::server_error(err.clone()),
+				code: ErrorType::server_error(err.clone()),
 				message: err,
+				quota: None,
+				field: None,
 			},
 		})?;
 	let builder = REQUEST_CLIENT
@@ -89,8 +91,10 @@ where
 				status_code: http::StatusCode::INTERNAL_SERVER_ERROR,
 				body: ApiErrorResponseBody {
 					success: False,
-					error: ErrorType::server_error(error.to_string()),
+					code: ErrorType::server_error(error.to_string()),
 					message: error.to_string(),
+					quota: None,
+					field: None,
 				},
 			});
 		}
@@ -113,8 +117,10 @@ where
 			status_code: http::StatusCode::INTERNAL_SERVER_ERROR,
 			body: ApiErrorResponseBody {
 				success: False,
-				error: ErrorType::server_error("invalid headers"),
+				code: ErrorType::server_error("invalid headers"),
 				message: "invalid headers".to_string(),
+				quota: None,
+				field: None,
 			},
 		});
 	};
@@ -138,8 +144,10 @@ where
 				status_code: http::StatusCode::INTERNAL_SERVER_ERROR,
 				body: ApiErrorResponseBody {
 					success: False,
-					error: ErrorType::server_error(error.to_string()),
+					code: ErrorType::server_error(error.to_string()),
 					message: error.to_string(),
+					quota: None,
+					field: None,
 				},
 			})
 		}
@@ -176,8 +184,10 @@ where
 				status_code: StatusCode::INTERNAL_SERVER_ERROR,
 				body: ApiErrorResponseBody {
 					success: False,
-					error: ErrorType::server_error(&err),
+					code: ErrorType::server_error(&err),
 					message: err.to_string(),
+					quota: None,
+					field: None,
 				},
 			},)?
 		))
@@ -186,8 +196,10 @@ where
 			status_code: StatusCode::INTERNAL_SERVER_ERROR,
 			body: ApiErrorResponseBody {
 				success: False,
-				error: ErrorType::server_error(&err),
+				code: ErrorType::server_error(&err),
 				message: err.to_string(),
+				quota: None,
+				field: None,
 			},
 		})?
 		.into_client_request()
@@ -195,8 +207,10 @@ where
 			status_code: StatusCode::INTERNAL_SERVER_ERROR,
 			body: ApiErrorResponseBody {
 				success: False,
-				error: ErrorType::server_error(&err),
+				code: ErrorType::server_error(&err),
 				message: err.to_string(),
+				quota: None,
+				field: None,
 			},
 		})?;
 	for (header, value) in request.headers.to_header_map().iter() {
@@ -218,8 +232,10 @@ where
 						error!("Failed to parse error body: {}", err);
 						ApiErrorResponseBody {
 							success: False,
-							error: ErrorType::server_error(&err),
+							code: ErrorType::server_error(&err),
 							message: err.to_string(),
+							quota: None,
+							field: None,
 						}
 					}),
 				}
@@ -228,8 +244,10 @@ where
 				status_code: StatusCode::INTERNAL_SERVER_ERROR,
 				body: ApiErrorResponseBody {
 					success: False,
-					error: ErrorType::server_error(err.to_string()),
+					code: ErrorType::server_error(err.to_string()),
 					message: err.to_string(),
+					quota: None,
+					field: None,
 				},
 			},
 		})?
@@ -61,4 +61,9 @@ pub mod constants {
 	/// The Last Name key to be used in the meta_data table. This is used to
 	/// store the last name of the user that is currently logged in.
 	pub const LAST_NAME_KEY: &str = "last_name";
+	/// How long a deployment waits for the deployments it depends on to become
+	/// healthy (or for the deployments that depend on it to stop) before the
+	/// wait is reported as a failed reconciliation
+	pub const DEPLOYMENT_DEPENDENCY_TIMEOUT: std::time::Duration =
+		std::time::Duration::from_secs(5 * 60);
 }
@@ -5,24 +5,48 @@
 //! incoming WebSocket connections from the Patr API. The runner is responsible
 //! for creating, updating, and deleting deployments in the given runner.
 
-use std::{collections::HashMap, time::Duration};
+use std::{
+	collections::HashMap,
+	time::{Duration, Instant},
+};
 
 use bollard::{
+	auth::DockerCredentials,
 	container::{
 		Config,
 		CreateContainerOptions,
+		InspectContainerOptions,
 		ListContainersOptions,
 		RemoveContainerOptions,
 		StopContainerOptions,
 	},
-	image::CreateImageOptions,
-	secret::CreateImageInfo,
+	exec::{CreateExecOptions, StartExecResults},
+	image::{BuildImageOptions, CreateImageOptions, PushImageOptions},
+	secret::{BuildInfo, CreateImageInfo, HostConfig, PushImageInfo},
 	Docker,
 };
 use common::prelude::*;
-use futures::{Stream, StreamExt};
-use models::api::workspace::deployment::*;
+use futures::{Future, Stream, StreamExt};
+use models::api::workspace::{
+	database::{DatabaseEngine, DatabaseUser, DatabaseUserCommand},
+	deployment::{build::DeploymentBuildSource, *},
+	runner::{
+		GetRunnerPullSecretPath,
+		GetRunnerPullSecretRequest,
+		GetRunnerPullSecretRequestHeaders,
+		RegistryCredentials,
+	},
+};
 use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
+
+/// How long the container of a deployment has to become ready after it was
+/// started, before the runner stops checking on it
+const READINESS_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// How often the readiness of the container of a deployment is checked while
+/// waiting for it to become ready
+const READINESS_CHECK_INTERVAL: Duration = Duration::from_secs(2);
 
 /// The configuration for the runner.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -34,6 +58,9 @@ pub struct DockerSettings {}
 struct DockerRunner {
 	/// The [`Docker`] client.
 	docker: Docker,
+	/// The mode the runner is running in, used to fetch the credentials of
+	/// pull secrets from the Patr API
+	mode: RunnerMode,
 }
 
 impl RunnerExecutor for DockerRunner {
@@ -41,9 +68,12 @@ impl RunnerExecutor for DockerRunner {
 
 	const RUNNER_INTERNAL_NAME: &'static str = env!("CARGO_CRATE_NAME");
 
-	async fn create(_: &RunnerSettings<Self::Settings>) -> Self {
+	async fn create(settings: &RunnerSettings<Self::Settings>) -> Self {
 		let docker = Docker::connect_with_local_defaults().unwrap();
-		Self { docker }
+		Self {
+			docker,
+			mode: settings.mode.clone(),
+		}
 	}
 
 	#[allow(unused_variables)]
@@ -60,6 +90,12 @@ impl RunnerExecutor for DockerRunner {
 					runner: _,
 					machine_type,
 					current_live_digest,
+					pull_secret_id,
+					created_at: _,
+					updated_at: _,
+					labels: _,
+					depends_on: _,
+					canary: _,
 				},
 		}: WithId<Deployment>,
 		DeploymentRunningDetails {
@@ -72,6 +108,11 @@ impl RunnerExecutor for DockerRunner {
 			liveness_probe,
 			config_mounts,
 			volumes,
+			resources,
+			scale_to_zero_after: _,
+			log_level: _,
+			max_concurrent_requests: _,
+			access_logging: _,
 		}: DeploymentRunningDetails,
 	) -> Result<(), Duration> {
 		// Check if the container exists, first.
@@ -119,6 +160,24 @@ impl RunnerExecutor for DockerRunner {
 				})?;
 		}
 
+		// A deployment that is scaled to zero has no containers until it
+		// receives a request again
+		if status == DeploymentStatus::Cold {
+			info!("Deployment `{}` is scaled to zero", id);
+			self.report_readiness(
+				id,
+				DeploymentReplicaReadiness { ready: 0, total: 0 },
+				Vec::new(),
+			)
+			.await;
+			return Ok(());
+		}
+
+		let credentials = match pull_secret_id {
+			Some(pull_secret_id) => Some(self.get_pull_credentials(pull_secret_id).await?),
+			None => None,
+		};
+
 		info!("Pulling latest image...");
 		let mut pull_image = self.docker.create_image(
 			Some(CreateImageOptions {
@@ -135,7 +194,7 @@ impl RunnerExecutor for DockerRunner {
 				..Default::default()
 			}),
 			None,
-			None,
+			credentials,
 		);
 		while let Some(result) = pull_image.next().await {
 			match result {
@@ -211,6 +270,20 @@ impl RunnerExecutor for DockerRunner {
 						String::from("patr.deploymentId"),
 						id.to_string(),
 					)])),
+					// Docker has no equivalent of a CPU request, so only the
+					// limit is applied for the CPU
+					host_config: Some(HostConfig {
+						nano_cpus: resources
+							.cpu_limit
+							.map(|millicores| i64::from(millicores) * 1_000_000),
+						memory: resources
+							.memory_limit
+							.map(|mib| i64::from(mib) * 1024 * 1024),
+						memory_reservation: resources
+							.memory_request
+							.map(|mib| i64::from(mib) * 1024 * 1024),
+						..Default::default()
+					}),
 					..Default::default()
 				},
 			)
@@ -230,6 +303,13 @@ impl RunnerExecutor for DockerRunner {
 			})?;
 		info!("Container started");
 
+		// The container is checked on in the background, so that the
+		// reconciliation isn't held up until it is ready
+		tokio::spawn(
+			self.clone()
+				.wait_for_readiness(id, container.id, name, startup_probe),
+		);
+
 		Ok(())
 	}
 
@@ -310,6 +390,416 @@ impl RunnerExecutor for DockerRunner {
 		}
 		Ok(())
 	}
+
+	fn build_deployment_image(
+		&self,
+		build_id: Uuid,
+		build_source: DeploymentBuildSource,
+		image: String,
+	) -> impl Future<Output = Result<String, String>> + Send + 'static {
+		let docker = self.docker.clone();
+		let mode = self.mode.clone();
+
+		async move {
+			let RunnerMode::Managed {
+				workspace_id,
+				runner_id: _,
+				api_token,
+				user_agent: _,
+			} = mode
+			else {
+				return Err("Images can only be built in managed mode".to_string());
+			};
+
+			// The daemon clones the repository itself when given a git URL as
+			// the context, so there's no need to check it out here
+			info!("Building image `{}` for build `{}`", image, build_id);
+			let mut build = docker.build_image(
+				BuildImageOptions {
+					remote: format!("{}#{}", build_source.git_url, build_source.branch),
+					dockerfile: build_source
+						.dockerfile_path
+						.unwrap_or_else(|| "Dockerfile".to_string()),
+					t: image.clone(),
+					pull: true,
+					rm: true,
+					forcerm: true,
+					..Default::default()
+				},
+				None,
+				None,
+			);
+			while let Some(result) = build.next().await {
+				match result {
+					Ok(BuildInfo {
+						error: Some(error), ..
+					}) => return Err(error),
+					Ok(BuildInfo {
+						stream: Some(output),
+						..
+					}) => info!(build_id = %build_id, "{}", output.trim_end()),
+					Ok(_) => (),
+					Err(err) => return Err(format!("Error building image: {err}")),
+				}
+			}
+
+			let (repository, tag) = image
+				.rsplit_once(':')
+				.ok_or_else(|| format!("The image `{image}` has no tag"))?;
+
+			// The Patr registry authenticates runners with their API token
+			let mut push = docker.push_image(
+				repository,
+				Some(PushImageOptions { tag }),
+				Some(DockerCredentials {
+					username: Some(workspace_id.to_string()),
+					password: Some(api_token.0.token().to_string()),
+					serveraddress: Some(PatrRegistry.to_string()),
+					..Default::default()
+				}),
+			);
+			while let Some(result) = push.next().await {
+				match result {
+					Ok(PushImageInfo {
+						error: Some(error), ..
+					}) => return Err(error),
+					Ok(PushImageInfo {
+						status: Some(status),
+						..
+					}) => trace!("Image push status: {}", status),
+					Ok(_) => (),
+					Err(err) => return Err(format!("Error pushing image: {err}")),
+				}
+			}
+
+			// Once pushed, the image is known by the digest the registry
+			// stored it with
+			let digest = docker
+				.inspect_image(&image)
+				.await
+				.map_err(|err| format!("Error inspecting image: {err}"))?
+				.repo_digests
+				.unwrap_or_default()
+				.into_iter()
+				.find_map(|repo_digest| {
+					repo_digest
+						.strip_prefix(repository)
+						.and_then(|digest| digest.strip_prefix('@'))
+						.map(ToString::to_string)
+				})
+				.ok_or_else(|| format!("The pushed image `{image}` has no digest"))?;
+
+			if let Err(err) = docker.remove_image(&image, None, None).await {
+				warn!("Error removing the built image `{}`: {}", image, err);
+			}
+
+			Ok(digest)
+		}
+	}
+
+	async fn create_database_user(
+		&self,
+		database_id: Uuid,
+		engine: DatabaseEngine,
+		user: DatabaseUser,
+	) -> Result<(), String> {
+		self.exec_in_database(database_id, engine.create_user_command(&user))
+			.await
+	}
+
+	async fn drop_database_user(
+		&self,
+		database_id: Uuid,
+		engine: DatabaseEngine,
+		username: String,
+		owner: String,
+	) -> Result<(), String> {
+		self.exec_in_database(database_id, engine.drop_user_command(&username, &owner))
+			.await
+	}
+}
+
+impl DockerRunner {
+	/// Gets the credentials of a pull secret from the Patr API, to pull the
+	/// image of a deployment from a private registry. Self-hosted runners have
+	/// no pull secrets, so this always fails for them.
+	async fn get_pull_credentials(&self, secret_id: Uuid) -> Result<DockerCredentials, Duration> {
+		let RunnerMode::Managed {
+			workspace_id,
+			runner_id,
+			api_token,
+			user_agent,
+		} = &self.mode
+		else {
+			error!("Pull secrets are not supported in self-hosted mode");
+			return Err(Duration::from_secs(60));
+		};
+
+		let RegistryCredentials {
+			registry,
+			username,
+			password,
+		} = client::make_request(
+			ApiRequest::<GetRunnerPullSecretRequest>::builder()
+				.path(GetRunnerPullSecretPath {
+					workspace_id: *workspace_id,
+					runner_id: *runner_id,
+					secret_id,
+				})
+				.headers(GetRunnerPullSecretRequestHeaders {
+					authorization: api_token.clone(),
+					user_agent: user_agent.clone(),
+				})
+				.query(())
+				.body(GetRunnerPullSecretRequest)
+				.build(),
+		)
+		.await
+		.map_err(|err| {
+			error!("Error getting pull secret: {:?}", err.body.code);
+			Duration::from_secs(5)
+		})?
+		.body
+		.credentials;
+
+		Ok(DockerCredentials {
+			username: Some(username),
+			password: Some(password),
+			serveraddress: Some(registry),
+			..Default::default()
+		})
+	}
+
+	/// Reports the readiness of the container of a deployment to the Patr API,
+	/// so that the ingress only forwards requests to it once it is ready.
+	/// Docker runs a single container for each deployment, and doesn't run
+	/// canaries, so there is never a canary to report the readiness of. The
+	/// name of the container is reported as the ID of its replica, since its
+	/// logs are labelled with it. Self-hosted runners have no one to report to,
+	/// so this does nothing for them.
+	async fn report_readiness(
+		&self,
+		deployment_id: Uuid,
+		replicas: DeploymentReplicaReadiness,
+		replica_ids: Vec<String>,
+	) {
+		let RunnerMode::Managed {
+			workspace_id,
+			runner_id: _,
+			api_token,
+			user_agent,
+		} = &self.mode
+		else {
+			return;
+		};
+
+		let response = client::make_request(
+			ApiRequest::<ReportDeploymentReadinessRequest>::builder()
+				.path(ReportDeploymentReadinessPath {
+					workspace_id: *workspace_id,
+					deployment_id,
+				})
+				.headers(ReportDeploymentReadinessRequestHeaders {
+					authorization: api_token.clone(),
+					user_agent: user_agent.clone(),
+				})
+				.query(())
+				.body(ReportDeploymentReadinessRequest {
+					replicas,
+					canary_ready_replicas: None,
+					replica_ids,
+				})
+				.build(),
+		)
+		.await;
+
+		if let Err(err) = response {
+			warn!(
+				"Failed to report the readiness of deployment `{}`: {:?}",
+				deployment_id, err.body.code
+			);
+		}
+	}
+
+	/// Waits for the container of a deployment that was just started to be
+	/// ready, reporting it as not ready until then. A container is ready once
+	/// it is running and, if the deployment has a startup probe, once the
+	/// probe responds successfully. The container is left reported as not
+	/// ready if it doesn't become ready within [`READINESS_TIMEOUT`], or if it
+	/// is replaced in the meantime.
+	async fn wait_for_readiness(
+		self,
+		deployment_id: Uuid,
+		container_id: String,
+		container_name: String,
+		startup_probe: Option<DeploymentProbe>,
+	) {
+		self.report_readiness(
+			deployment_id,
+			DeploymentReplicaReadiness { ready: 0, total: 1 },
+			vec![container_name.clone()],
+		)
+		.await;
+
+		let probe_client = match reqwest::Client::builder()
+			.timeout(READINESS_CHECK_INTERVAL)
+			.build()
+		{
+			Ok(client) => client,
+			Err(err) => {
+				error!("Error creating the client for startup probes: {}", err);
+				return;
+			}
+		};
+
+		let deadline = Instant::now() + READINESS_TIMEOUT;
+		while Instant::now() < deadline {
+			if self
+				.is_container_ready(&probe_client, &container_id, startup_probe.as_ref())
+				.await
+			{
+				info!("Deployment `{}` is ready", deployment_id);
+				self.report_readiness(
+					deployment_id,
+					DeploymentReplicaReadiness { ready: 1, total: 1 },
+					vec![container_name],
+				)
+				.await;
+				return;
+			}
+			tokio::time::sleep(READINESS_CHECK_INTERVAL).await;
+		}
+
+		warn!(
+			"Deployment `{}` did not become ready within {:?}",
+			deployment_id, READINESS_TIMEOUT
+		);
+	}
+
+	/// Checks if a container is running and, given the startup probe of its
+	/// deployment, if the probe responds successfully on the address of the
+	/// container
+	async fn is_container_ready(
+		&self,
+		probe_client: &reqwest::Client,
+		container_id: &str,
+		startup_probe: Option<&DeploymentProbe>,
+	) -> bool {
+		let Ok(container) = self
+			.docker
+			.inspect_container(container_id, None::<InspectContainerOptions>)
+			.await
+		else {
+			return false;
+		};
+
+		let is_running = container
+			.state
+			.and_then(|state| state.running)
+			.unwrap_or(false);
+		let Some(DeploymentProbe { port, path }) = startup_probe else {
+			return is_running;
+		};
+		let Some(ip_address) = container
+			.network_settings
+			.and_then(|settings| settings.ip_address)
+			.filter(|ip_address| !ip_address.is_empty())
+		else {
+			return false;
+		};
+
+		is_running &&
+			probe_client
+				.get(format!(
+					"http://{}:{}/{}",
+					ip_address,
+					port,
+					path.trim_start_matches('/')
+				))
+				.send()
+				.await
+				.is_ok_and(|response| {
+					response.status().is_success() || response.status().is_redirection()
+				})
+	}
+
+	/// Runs a command in the container of a managed database, piping the
+	/// script of the command to its standard input. The output of the command
+	/// is discarded, since the errors of the engines can quote the statements
+	/// that were run, passwords included.
+	async fn exec_in_database(
+		&self,
+		database_id: Uuid,
+		DatabaseUserCommand { command, script }: DatabaseUserCommand,
+	) -> Result<(), String> {
+		let container_id = self
+			.docker
+			.list_containers(Some(ListContainersOptions {
+				filters: HashMap::from([(
+					String::from("label"),
+					vec![format!("patr.databaseId={}", database_id)],
+				)]),
+				..Default::default()
+			}))
+			.await
+			.map_err(|err| format!("Error listing containers: {err}"))?
+			.into_iter()
+			.find_map(|container| container.id)
+			.ok_or_else(|| format!("No container is running database `{database_id}`"))?;
+
+		let exec_id = self
+			.docker
+			.create_exec(
+				&container_id,
+				CreateExecOptions {
+					cmd: Some(command),
+					attach_stdin: Some(true),
+					attach_stdout: Some(true),
+					attach_stderr: Some(true),
+					..Default::default()
+				},
+			)
+			.await
+			.map_err(|err| format!("Error creating exec: {err}"))?
+			.id;
+
+		let StartExecResults::Attached {
+			mut output,
+			mut input,
+		} = self
+			.docker
+			.start_exec(&exec_id, None)
+			.await
+			.map_err(|err| format!("Error starting exec: {err}"))?
+		else {
+			return Err("The exec was started detached".to_string());
+		};
+
+		input
+			.write_all(script.as_bytes())
+			.await
+			.map_err(|err| format!("Error writing to exec: {err}"))?;
+		input
+			.shutdown()
+			.await
+			.map_err(|err| format!("Error closing the input of exec: {err}"))?;
+
+		while let Some(log) = output.next().await {
+			log.map_err(|err| format!("Error reading the output of exec: {err}"))?;
+		}
+
+		let exit_code = self
+			.docker
+			.inspect_exec(&exec_id)
+			.await
+			.map_err(|err| format!("Error inspecting exec: {err}"))?
+			.exit_code;
+
+		match exit_code {
+			Some(0) => Ok(()),
+			exit_code => Err(format!("The command exited with code {exit_code:?}")),
+		}
+	}
 }
 
 #[tokio::main]
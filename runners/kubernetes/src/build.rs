@@ -0,0 +1,293 @@
+use std::{collections::BTreeMap, str::FromStr, sync::Arc};
+
+use k8s_openapi::{
+	api::{batch::v1::*, core::v1::*},
+	ByteString,
+};
+use kube::{
+	api::{ListParams, Patch, PatchParams, PostParams, Resource},
+	core::ObjectMeta,
+	runtime::wait::await_condition,
+	Api,
+};
+use models::{
+	api::workspace::deployment::{build::*, PatrRegistry},
+	prelude::*,
+};
+
+use crate::{client::make_request, constants, prelude::*};
+
+/// The image of the executor that builds images in the cluster. Kaniko builds
+/// images without access to a container runtime, so the build doesn't need a
+/// privileged pod.
+const BUILDER_IMAGE: &str = "gcr.io/kaniko-project/executor:v1.23.2";
+
+/// How long a build can run before it is stopped and reported as failed
+const BUILD_TIMEOUT_SECONDS: i64 = 60 * 60;
+
+/// How long a finished build job is kept around, so that its pod can still be
+/// inspected after the build
+const FINISHED_BUILD_TTL_SECONDS: i32 = 60 * 60;
+
+/// Builds the image of a deployment from its build source in a job, reporting
+/// the progress of the build to the Patr API. The pod of the build is labeled
+/// with the build ID, so that its logs are shipped with the `buildId` label.
+pub async fn build_image(
+	state: Arc<AppState>,
+	deployment_id: Uuid,
+	build_id: Uuid,
+	build_source: DeploymentBuildSource,
+	image: String,
+) {
+	info!("Building image of deployment `{}`", deployment_id);
+	if let Err(err) = report_build(
+		&state,
+		deployment_id,
+		build_id,
+		DeploymentBuildStatus::Building,
+		None,
+		None,
+	)
+	.await
+	{
+		warn!("Failed to report build `{}` as started: {}", build_id, err);
+	}
+
+	let result = match run_build(&state, build_id, build_source, image).await {
+		Ok(digest) => {
+			report_build(
+				&state,
+				deployment_id,
+				build_id,
+				DeploymentBuildStatus::Succeeded,
+				Some(digest),
+				None,
+			)
+			.await
+		}
+		Err(error) => {
+			warn!("Build `{}` failed: {}", build_id, error);
+			report_build(
+				&state,
+				deployment_id,
+				build_id,
+				DeploymentBuildStatus::Failed,
+				None,
+				Some(error),
+			)
+			.await
+		}
+	};
+	if let Err(err) = result {
+		error!(
+			"Failed to report the result of build `{}`: {}",
+			build_id, err
+		);
+	}
+}
+
+/// Runs the job that builds the image and pushes it, waiting for it to finish.
+/// Returns the digest of the pushed image, or the reason the build failed.
+async fn run_build(
+	state: &AppState,
+	build_id: Uuid,
+	DeploymentBuildSource {
+		git_url,
+		branch,
+		dockerfile_path,
+	}: DeploymentBuildSource,
+	image: String,
+) -> Result<String, String> {
+	let namespace = state.workspace_id.to_string();
+	let name = format!("build-{}", build_id);
+	let labels = BTreeMap::from([
+		(constants::BUILD_ID.to_string(), build_id.to_string()),
+		(constants::WORKSPACE_ID.to_string(), namespace.clone()),
+	]);
+
+	// Kaniko clones git contexts over HTTPS, with the scheme replaced by `git`
+	let context = format!(
+		"git://{}#refs/heads/{}",
+		git_url
+			.strip_prefix("https://")
+			.or_else(|| git_url.strip_prefix("http://"))
+			.unwrap_or(&git_url),
+		branch
+	);
+
+	let jobs = Api::<Job>::namespaced(state.client.clone(), &namespace);
+	let job = jobs
+		.create(
+			&PostParams::default(),
+			&Job {
+				metadata: ObjectMeta {
+					name: Some(name.clone()),
+					labels: Some(labels.clone()),
+					..ObjectMeta::default()
+				},
+				spec: Some(JobSpec {
+					backoff_limit: Some(0),
+					active_deadline_seconds: Some(BUILD_TIMEOUT_SECONDS),
+					ttl_seconds_after_finished: Some(FINISHED_BUILD_TTL_SECONDS),
+					template: PodTemplateSpec {
+						metadata: Some(ObjectMeta {
+							labels: Some(labels),
+							..ObjectMeta::default()
+						}),
+						spec: Some(PodSpec {
+							restart_policy: Some("Never".to_string()),
+							containers: vec![Container {
+								name: "builder".to_string(),
+								image: Some(BUILDER_IMAGE.to_string()),
+								args: Some(vec![
+									format!("--context={}", context),
+									format!(
+										"--dockerfile={}",
+										dockerfile_path.as_deref().unwrap_or("Dockerfile")
+									),
+									format!("--destination={}", image),
+									// The digest is read back from the
+									// termination message of the pod
+									"--digest-file=/dev/termination-log".to_string(),
+								]),
+								termination_message_policy: Some(
+									"FallbackToLogsOnError".to_string(),
+								),
+								volume_mounts: Some(vec![VolumeMount {
+									name: "registry-credentials".to_string(),
+									mount_path: "/kaniko/.docker".to_string(),
+									read_only: Some(true),
+									..VolumeMount::default()
+								}]),
+								..Container::default()
+							}],
+							volumes: Some(vec![Volume {
+								name: "registry-credentials".to_string(),
+								secret: Some(SecretVolumeSource {
+									secret_name: Some(name.clone()),
+									items: Some(vec![KeyToPath {
+										key: ".dockerconfigjson".to_string(),
+										path: "config.json".to_string(),
+										..KeyToPath::default()
+									}]),
+									..SecretVolumeSource::default()
+								}),
+								..Volume::default()
+							}]),
+							..PodSpec::default()
+						}),
+					},
+					..JobSpec::default()
+				}),
+				..Job::default()
+			},
+		)
+		.await
+		.map_err(|err| format!("Error creating the build job: {err}"))?;
+
+	// The Patr registry authenticates runners with their API token. The
+	// credentials are owned by the job, so that they're removed along with it
+	let registry = PatrRegistry.to_string();
+	let docker_config = serde_json::json!({
+		"auths": {
+			registry: {
+				"username": state.workspace_id.to_string(),
+				"password": state.patr_token,
+			}
+		}
+	});
+	Api::<Secret>::namespaced(state.client.clone(), &namespace)
+		.patch(
+			&name,
+			&PatchParams::apply(&name),
+			&Patch::Apply(Secret {
+				metadata: ObjectMeta {
+					name: Some(name.clone()),
+					owner_references: job.controller_owner_ref(&()).map(|owner| vec![owner]),
+					..ObjectMeta::default()
+				},
+				type_: Some("kubernetes.io/dockerconfigjson".to_string()),
+				data: Some(BTreeMap::from([(
+					".dockerconfigjson".to_string(),
+					ByteString(docker_config.to_string().into_bytes()),
+				)])),
+				..Secret::default()
+			}),
+		)
+		.await
+		.map_err(|err| format!("Error creating the registry credentials: {err}"))?;
+
+	// The job fails by itself once it runs past its deadline, so this doesn't
+	// wait forever
+	let job = await_condition(jobs, &name, |job: Option<&Job>| {
+		job.and_then(|job| job.status.as_ref())
+			.and_then(|status| status.conditions.as_ref())
+			.is_some_and(|conditions| {
+				conditions.iter().any(|condition| {
+					matches!(condition.type_.as_str(), "Complete" | "Failed") &&
+						condition.status == "True"
+				})
+			})
+	})
+	.await
+	.map_err(|err| format!("Error waiting for the build job: {err}"))?
+	.ok_or_else(|| "The build job was deleted".to_string())?;
+	let succeeded = job
+		.status
+		.and_then(|status| status.succeeded)
+		.is_some_and(|succeeded| succeeded > 0);
+
+	let message = Api::<Pod>::namespaced(state.client.clone(), &namespace)
+		.list(&ListParams::default().labels(&format!("job-name={}", name)))
+		.await
+		.map_err(|err| format!("Error getting the pod of the build job: {err}"))?
+		.items
+		.into_iter()
+		.filter_map(|pod| pod.status?.container_statuses)
+		.flatten()
+		.find_map(|container| container.state?.terminated?.message)
+		.map(|message| message.trim().to_string());
+
+	match (succeeded, message) {
+		(true, Some(digest)) if digest.starts_with("sha256:") => Ok(digest),
+		(true, _) => Err("The build did not report the digest of the image".to_string()),
+		(false, Some(message)) if !message.is_empty() => Err(message),
+		(false, _) => Err("The build job failed".to_string()),
+	}
+}
+
+/// Reports the progress of a build to the Patr API
+async fn report_build(
+	state: &AppState,
+	deployment_id: Uuid,
+	build_id: Uuid,
+	status: DeploymentBuildStatus,
+	image_digest: Option<String>,
+	error: Option<String>,
+) -> Result<(), AppError> {
+	make_request(
+		ApiRequest::<UpdateDeploymentBuildRequest>::builder()
+			.path(UpdateDeploymentBuildPath {
+				workspace_id: state.workspace_id,
+				deployment_id,
+				build_id,
+			})
+			.headers(UpdateDeploymentBuildRequestHeaders {
+				authorization: BearerToken::from_str(&state.patr_token).map_err(|err| {
+					ErrorType::server_error(format!("invalid patr token. Error: `{}`", err))
+				})?,
+				user_agent: UserAgent::from_static("deployment-controller"),
+			})
+			.query(())
+			.body(UpdateDeploymentBuildRequest {
+				status,
+				image_digest,
+				error,
+			})
+			.build(),
+	)
+	.await
+	.map_err(|err| err.body.code)?;
+
+	Ok(())
+}
@@ -42,8 +42,10 @@ where
 			status_code: http::StatusCode::INTERNAL_SERVER_ERROR,
 			body: ApiErrorResponseBody {
 				success: False,
-				error: ErrorType::server_error(err.clone()),
+				code: ErrorType::server_error(err.clone()),
 				message: err,
+				quota: None,
+				field: None,
 			},
 		})?;
 	let builder = REQUEST_CLIENT
@@ -84,8 +86,10 @@ where
 				status_code: StatusCode::INTERNAL_SERVER_ERROR,
 				body: ApiErrorResponseBody {
 					success: False,
-					error: ErrorType::server_error(error.to_string()),
+					code: ErrorType::server_error(error.to_string()),
 					message: error.to_string(),
+					quota: None,
+					field: None,
 				},
 			});
 		}
@@ -108,8 +112,10 @@ where
 			status_code: StatusCode::INTERNAL_SERVER_ERROR,
 			body: ApiErrorResponseBody {
 				success: False,
-				error: ErrorType::server_error("invalid headers"),
+				code: ErrorType::server_error("invalid headers"),
 				message: "invalid headers".to_string(),
+				quota: None,
+				field: None,
 			},
 		});
 	};
@@ -133,8 +139,10 @@ where
 				status_code: StatusCode::INTERNAL_SERVER_ERROR,
 				body: ApiErrorResponseBody {
 					success: False,
-					error: ErrorType::server_error(error.to_string()),
+					code: ErrorType::server_error(error.to_string()),
 					message: error.to_string(),
+					quota: None,
+					field: None,
 				},
 			})
 		}
@@ -167,8 +175,10 @@ where
 		status_code: StatusCode::INTERNAL_SERVER_ERROR,
 		body: ApiErrorResponseBody {
 			success: False,
-			error: ErrorType::server_error(err),
+			code: ErrorType::server_error(err),
 			message: err.to_string(),
+			quota: None,
+			field: None,
 		},
 	})?;
 	for (header, value) in request.headers.to_header_map().iter() {
@@ -190,8 +200,10 @@ where
 						error!("Failed to parse error body: {}", err);
 						ApiErrorResponseBody {
 							success: False,
-							error: ErrorType::server_error(&err),
+							code: ErrorType::server_error(&err),
 							message: err.to_string(),
+							quota: None,
+							field: None,
 						}
 					}),
 				}
@@ -200,8 +212,10 @@ where
 				status_code: StatusCode::INTERNAL_SERVER_ERROR,
 				body: ApiErrorResponseBody {
 					success: False,
-					error: ErrorType::server_error(err),
+					code: ErrorType::server_error(err),
 					message: err.to_string(),
+					quota: None,
+					field: None,
 				},
 			},
 		})?
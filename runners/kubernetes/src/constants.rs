@@ -1,8 +1,34 @@
 /// A camelCased string containing the text "deploymentId".
 pub const DEPLOYMENT_ID: &str = "deploymentId";
 
+/// A camelCased string containing the text "buildId".
+pub const BUILD_ID: &str = "buildId";
+
+/// A camelCased string containing the text "databaseId".
+pub const DATABASE_ID: &str = "databaseId";
+
 /// A camelCased string containing the text "workspaceId".
 pub const WORKSPACE_ID: &str = "workspaceId";
 
+/// The label on the network policies that enforce the egress policy of the
+/// workspace, so that they can be found to resolve their domains again.
+pub const EGRESS_POLICY: &str = "patr.cloud/egress-policy";
+
+/// How often the domains in the egress policy of the workspace are resolved
+/// again, so that the network policies follow the addresses they point to.
+pub const EGRESS_POLICY_REFRESH_INTERVAL: std::time::Duration =
+	std::time::Duration::from_secs(5 * 60);
+
 /// A camelCased string containing the text "runner".
 pub const RUNNER: &str = "runner";
+
+/// The annotation on the pods of a deployment with the minimum level of the
+/// logs that are captured from them. Logs below this level are dropped by the
+/// log shipper, and are never stored.
+pub const LOG_LEVEL_ANNOTATION: &str = "patr.cloud/log-level";
+
+/// The suffix added to the ID of a deployment to name the resources of its
+/// canary, and to label the pods of the canary so that they aren't selected by
+/// the deployment itself. This is also the suffix that the ingress adds to the
+/// deployment ID in the host of a managed URL to reach the canary.
+pub const CANARY_SUFFIX: &str = "-canary";
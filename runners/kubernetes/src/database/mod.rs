@@ -1 +1,105 @@
+use k8s_openapi::api::core::v1::Pod;
+use kube::api::{Api, AttachParams, ListParams};
+use models::{
+	api::workspace::database::{DatabaseEngine, DatabaseUser, DatabaseUserCommand},
+	prelude::*,
+};
+use tokio::io::AsyncWriteExt;
 
+use crate::{constants, prelude::*};
+
+/// Creates a user on the engine of a managed database, or updates its password
+/// if it already exists. Failures are only logged, since the API doesn't wait
+/// for the user to be created.
+pub async fn create_user(
+	state: &AppState,
+	database_id: Uuid,
+	engine: DatabaseEngine,
+	user: DatabaseUser,
+) {
+	let command = engine.create_user_command(&user);
+	if let Err(err) = exec_in_database(state, database_id, command).await {
+		warn!(
+			"Failed to create user `{}` of database `{database_id}`: {err}",
+			user.username
+		);
+	}
+}
+
+/// Drops a user from the engine of a managed database, handing over anything
+/// that it owns to the owner of the database. Failures are only logged, and the
+/// API asks for the user to be dropped again until it's gone.
+pub async fn drop_user(
+	state: &AppState,
+	database_id: Uuid,
+	engine: DatabaseEngine,
+	username: String,
+	owner: String,
+) {
+	let command = engine.drop_user_command(&username, &owner);
+	if let Err(err) = exec_in_database(state, database_id, command).await {
+		warn!("Failed to drop user `{username}` of database `{database_id}`: {err}");
+	}
+}
+
+/// Runs a command in the pod of a managed database, piping the script of the
+/// command to its standard input. The output of the command is not captured,
+/// since the errors of the engines can quote the statements that were run,
+/// passwords included.
+async fn exec_in_database(
+	state: &AppState,
+	database_id: Uuid,
+	DatabaseUserCommand { command, script }: DatabaseUserCommand,
+) -> Result<(), AppError> {
+	let pods = Api::<Pod>::namespaced(state.client.clone(), &state.workspace_id.to_string());
+
+	let pod = pods
+		.list(&ListParams::default().labels(&format!("{}={}", constants::DATABASE_ID, database_id)))
+		.await?
+		.items
+		.into_iter()
+		.find_map(|pod| pod.metadata.name)
+		.ok_or_else(|| {
+			AppError::InternalError(format!("No pod is running database `{database_id}`"))
+		})?;
+
+	let mut process = pods
+		.exec(
+			&pod,
+			command,
+			&AttachParams::default()
+				.stdin(true)
+				.stdout(false)
+				.stderr(false),
+		)
+		.await?;
+
+	let mut stdin = process
+		.stdin()
+		.ok_or_else(|| AppError::InternalError("The exec has no stdin".to_string()))?;
+	stdin
+		.write_all(script.as_bytes())
+		.await
+		.map_err(|err| AppError::InternalError(err.to_string()))?;
+	stdin
+		.shutdown()
+		.await
+		.map_err(|err| AppError::InternalError(err.to_string()))?;
+	drop(stdin);
+
+	let status = match process.take_status() {
+		Some(status) => status.await,
+		None => None,
+	};
+	process
+		.join()
+		.await
+		.map_err(|err| AppError::InternalError(err.to_string()))?;
+
+	match status.and_then(|status| status.status).as_deref() {
+		Some("Success") => Ok(()),
+		status => Err(AppError::InternalError(format!(
+			"The command exited with status {status:?}"
+		))),
+	}
+}
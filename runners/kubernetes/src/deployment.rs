@@ -25,7 +25,7 @@ use k8s_openapi::{
 	ByteString,
 };
 use kube::{
-	api::{DeleteParams, Patch, PatchParams, PropagationPolicy, Resource},
+	api::{DeleteParams, ListParams, Patch, PatchParams, PropagationPolicy, Resource},
 	core::ObjectMeta,
 	runtime::{
 		controller::{Action, Controller},
@@ -36,7 +36,17 @@ use kube::{
 	Client,
 };
 use models::{
-	api::workspace::{container_registry::*, deployment::*, volume::*},
+	api::workspace::{
+		container_registry::*,
+		deployment::*,
+		runner::{
+			GetRunnerPullSecretPath,
+			GetRunnerPullSecretRequest,
+			GetRunnerPullSecretRequestHeaders,
+			RegistryCredentials,
+		},
+		volume::*,
+	},
 	prelude::*,
 };
 use sha2::{Digest, Sha512};
@@ -50,7 +60,7 @@ use tokio::{
 };
 use tokio_stream::wrappers::{BroadcastStream, UnboundedReceiverStream};
 
-use crate::{client::make_request, constants, prelude::*};
+use crate::{client::make_request, constants, models::PatrDeploymentSpec, prelude::*};
 
 /// Starts the deployment controller. This function will spawn a new task that
 /// will run the controller. This function will return a sender that can be
@@ -105,6 +115,10 @@ async fn run_controller(
 		Api::<Ingress>::all(client.clone()),
 		watcher::Config::default(),
 	)
+	.owns(
+		Api::<NetworkPolicy>::all(client.clone()),
+		watcher::Config::default(),
+	)
 	.reconcile_all_on(UnboundedReceiverStream::new(reconcile_receiver))
 	.reconcile_on(BroadcastStream::new(patr_update_sender).filter_map(
 		|_input: Result<(), tokio_stream::wrappers::errors::BroadcastStreamRecvError>| async move {
@@ -125,6 +139,74 @@ fn error_policy(_obj: Arc<PatrDeployment>, _err: &AppError, _ctx: Arc<AppState>)
 	Action::requeue(Duration::from_secs(5))
 }
 
+/// Creates (or updates) the `kubernetes.io/dockerconfigjson` secret that the
+/// pods of deployments using the given pull secret pull their image with. The
+/// credentials are fetched from the Patr API. The secret is shared by all the
+/// deployments of the workspace that use the same pull secret, so it isn't
+/// owned by any one of them.
+async fn apply_pull_secret(
+	ctx: &AppState,
+	namespace: &str,
+	pull_secret_id: Uuid,
+) -> Result<(), AppError> {
+	let RegistryCredentials {
+		registry,
+		username,
+		password,
+	} = make_request(
+		ApiRequest::<GetRunnerPullSecretRequest>::builder()
+			.path(GetRunnerPullSecretPath {
+				workspace_id: ctx.workspace_id,
+				runner_id: ctx.region_id,
+				secret_id: pull_secret_id,
+			})
+			.headers(GetRunnerPullSecretRequestHeaders {
+				authorization: BearerToken::from_str(&ctx.patr_token).map_err(|err| {
+					ErrorType::server_error(format!("invalid patr token. Error: `{}`", err))
+				})?,
+				user_agent: UserAgent::from_static("deployment-controller"),
+			})
+			.query(())
+			.body(GetRunnerPullSecretRequest)
+			.build(),
+	)
+	.await
+	.map_err(|err| err.body.code)?
+	.body
+	.credentials;
+
+	let docker_config = serde_json::json!({
+		"auths": {
+			registry: {
+				"username": username,
+				"password": password,
+			}
+		}
+	});
+
+	let name = format!("pull-secret-{}", pull_secret_id);
+	Api::<Secret>::namespaced(ctx.client.clone(), namespace)
+		.patch(
+			&name,
+			&PatchParams::apply(&name),
+			&Patch::Apply(Secret {
+				metadata: ObjectMeta {
+					name: Some(name.clone()),
+					..ObjectMeta::default()
+				},
+				type_: Some("kubernetes.io/dockerconfigjson".to_string()),
+				data: Some(BTreeMap::from([(
+					".dockerconfigjson".to_string(),
+					ByteString(docker_config.to_string().into_bytes()),
+				)])),
+				..Secret::default()
+			}),
+		)
+		.await?;
+
+	Ok(())
+}
+
 /// Reconciles the state of the cluster with the state of the Patr API. This
 /// function is called whenever a new `PatrDeployment` is created, updated, or
 /// deleted. In case a child object (an object owned by this controller) is
@@ -193,6 +275,11 @@ async fn reconcile(
 		)
 		.await?;
 
+	if let Some(pull_secret_id) = spec.deployment.pull_secret_id {
+		trace!("Patching pull secret `{pull_secret_id}`");
+		apply_pull_secret(&ctx, namespace, pull_secret_id).await?;
+	}
+
 	let machine_type = make_request(
 		ApiRequest::<ListAllDeploymentMachineTypeRequest>::builder()
 			.path(ListAllDeploymentMachineTypePath {
@@ -206,7 +293,7 @@ async fn reconcile(
 			.build(),
 	)
 	.await
-	.map_err(|err| err.body.error)?
+	.map_err(|err| err.body.code)?
 	.body
 	.machine_types
 	.into_iter()
@@ -295,7 +382,7 @@ async fn reconcile(
 				.build(),
 		)
 		.await
-		.map_err(|err| err.body.error)?
+		.map_err(|err| err.body.code)?
 		.body
 		.volume;
 
@@ -392,7 +479,7 @@ async fn reconcile(
 					.build(),
 			)
 			.await
-			.map_err(|err| err.body.error)?
+			.map_err(|err| err.body.code)?
 			.body
 			.repository;
 
@@ -404,6 +491,35 @@ async fn reconcile(
 		} => format!("{}/{}", registry, image_name),
 	};
 
+	// The canary runs another tag of the same image, which is never pinned. Its
+	// replicas are taken out of the replicas of the deployment, so that the
+	// deployment and its canary never run more than the maximum together
+	let canary = spec
+		.deployment
+		.canary
+		.as_ref()
+		.filter(|_| spec.running_details.volumes.is_empty())
+		.and_then(|canary| {
+			let replicas = canary.replicas(
+				spec.running_details.min_horizontal_scale,
+				spec.running_details.max_horizontal_scale,
+			);
+			if replicas.is_none() {
+				warn!(
+					"Deployment `{}` can't run more than one replica. Not running its canary",
+					spec.deployment.id
+				);
+			}
+			Some((format!("{}:{}", image_name, canary.image_tag), replicas?))
+		});
+	let (min_replicas, max_replicas) = canary.as_ref().map_or(
+		(
+			spec.running_details.min_horizontal_scale,
+			spec.running_details.max_horizontal_scale,
+		),
+		|(_, replicas)| (replicas.min_primary, replicas.max_primary),
+	);
+
 	let image_name = if let Some(current_live_digest) = &spec.deployment.current_live_digest {
 		format!("{}@{}", image_name, current_live_digest)
 	} else {
@@ -425,213 +541,307 @@ async fn reconcile(
 		owner_references: Some(vec![owner_reference.clone()]),
 		..ObjectMeta::default()
 	};
-	let replicas = Some(spec.running_details.min_horizontal_scale.into());
+	// A deployment that is scaled to zero keeps its resources, but has no
+	// replicas until it receives a request again
+	let is_cold = spec.deployment.status == DeploymentStatus::Cold;
+	let replicas = Some(if is_cold { 0 } else { min_replicas.into() });
 	let selector = LabelSelector {
 		match_expressions: None,
 		match_labels: Some(labels.clone()),
 	};
-	let template =
-		PodTemplateSpec {
-			spec: Some(PodSpec {
-				containers: vec![Container {
-					name: format!(
-						"{}-{}",
-						if spec.running_details.volumes.is_empty() {
-							"deployment"
-						} else {
-							"sts"
-						},
-						spec.deployment.id
-					),
-					image: Some(image_name),
-					image_pull_policy: Some("Always".to_string()),
-					ports: Some(
-						spec.running_details
-							.ports
-							.keys()
-							.map(|port| ContainerPort {
-								container_port: port.value().into(),
-								..ContainerPort::default()
-							})
-							.collect::<Vec<_>>(),
-					),
-					startup_probe: spec
-						.running_details
-						.startup_probe
-						.as_ref()
-						.map(|probe| Probe {
-							http_get: Some(HTTPGetAction {
-								path: Some(probe.path.clone()),
-								port: IntOrString::Int(probe.port as i32),
-								scheme: Some("HTTP".to_string()),
-								..HTTPGetAction::default()
-							}),
-							failure_threshold: Some(15),
-							period_seconds: Some(10),
-							timeout_seconds: Some(3),
-							..Probe::default()
+	let template = PodTemplateSpec {
+		spec: Some(PodSpec {
+			containers: vec![Container {
+				name: format!(
+					"{}-{}",
+					if spec.running_details.volumes.is_empty() {
+						"deployment"
+					} else {
+						"sts"
+					},
+					spec.deployment.id
+				),
+				image: Some(image_name),
+				image_pull_policy: Some("Always".to_string()),
+				ports: Some(
+					spec.running_details
+						.ports
+						.keys()
+						.map(|port| ContainerPort {
+							container_port: port.value().into(),
+							..ContainerPort::default()
+						})
+						.collect::<Vec<_>>(),
+				),
+				startup_probe: spec
+					.running_details
+					.startup_probe
+					.as_ref()
+					.map(|probe| Probe {
+						http_get: Some(HTTPGetAction {
+							path: Some(probe.path.clone()),
+							port: IntOrString::Int(probe.port as i32),
+							scheme: Some("HTTP".to_string()),
+							..HTTPGetAction::default()
 						}),
-					liveness_probe: spec.running_details.liveness_probe.as_ref().map(|probe| {
-						Probe {
-							http_get: Some(HTTPGetAction {
-								path: Some(probe.path.clone()),
-								port: IntOrString::Int(probe.port as i32),
-								scheme: Some("HTTP".to_string()),
-								..HTTPGetAction::default()
-							}),
-							failure_threshold: Some(15),
-							period_seconds: Some(10),
-							timeout_seconds: Some(3),
-							..Probe::default()
-						}
+						failure_threshold: Some(15),
+						period_seconds: Some(10),
+						timeout_seconds: Some(3),
+						..Probe::default()
 					}),
-					env: Some(
-						spec.running_details
-							.environment_variables
-							.iter()
-							.map(|(name, value)| {
-								use EnvironmentVariableValue::*;
-								EnvVar {
-									name: name.clone(),
-									value: Some(match value {
-										String(value) => value.clone(),
-										Secret { from_secret } => {
-											format!(
-												"vault:secret/data/{}/{}#data",
-												namespace, from_secret
-											)
-										}
-									}),
-									..EnvVar::default()
-								}
-							})
-							.chain([
-								EnvVar {
-									name: "PATR".to_string(),
-									value: Some("true".to_string()),
-									..EnvVar::default()
-								},
-								EnvVar {
-									name: "WORKSPACE_ID".to_string(),
-									value: Some(namespace.to_string()),
-									..EnvVar::default()
-								},
-								EnvVar {
-									name: "DEPLOYMENT_ID".to_string(),
-									value: Some(spec.deployment.id.to_string()),
-									..EnvVar::default()
-								},
-								EnvVar {
-									name: "DEPLOYMENT_NAME".to_string(),
-									value: Some(spec.deployment.name.clone()),
-									..EnvVar::default()
-								},
-								EnvVar {
-									name: "CONFIG_MAP_HASH".to_string(),
-									value: Some(config_map_hash),
-									..EnvVar::default()
-								},
-								EnvVar {
-									name: "VAULT_AUTH_METHOD".to_string(),
-									value: Some("token".to_string()),
-									..EnvVar::default()
-								},
-								EnvVar {
-									name: "VAULT_TOKEN".to_string(),
-									value: Some(ctx.patr_token.clone()),
-									..Default::default()
-								},
-							])
-							.collect::<Vec<_>>(),
+				liveness_probe: spec
+					.running_details
+					.liveness_probe
+					.as_ref()
+					.map(|probe| Probe {
+						http_get: Some(HTTPGetAction {
+							path: Some(probe.path.clone()),
+							port: IntOrString::Int(probe.port as i32),
+							scheme: Some("HTTP".to_string()),
+							..HTTPGetAction::default()
+						}),
+						failure_threshold: Some(15),
+						period_seconds: Some(10),
+						timeout_seconds: Some(3),
+						..Probe::default()
+					}),
+				env: Some(
+					spec.running_details
+						.environment_variables
+						.iter()
+						.map(|(name, value)| {
+							use EnvironmentVariableValue::*;
+							EnvVar {
+								name: name.clone(),
+								value: Some(match value {
+									String(value) => value.clone(),
+									Secret { from_secret } => {
+										format!(
+											"vault:secret/data/{}/{}#data",
+											namespace, from_secret
+										)
+									}
+								}),
+								..EnvVar::default()
+							}
+						})
+						.chain([
+							EnvVar {
+								name: "PATR".to_string(),
+								value: Some("true".to_string()),
+								..EnvVar::default()
+							},
+							EnvVar {
+								name: "WORKSPACE_ID".to_string(),
+								value: Some(namespace.to_string()),
+								..EnvVar::default()
+							},
+							EnvVar {
+								name: "DEPLOYMENT_ID".to_string(),
+								value: Some(spec.deployment.id.to_string()),
+								..EnvVar::default()
+							},
+							EnvVar {
+								name: "DEPLOYMENT_NAME".to_string(),
+								value: Some(spec.deployment.name.clone()),
+								..EnvVar::default()
+							},
+							EnvVar {
+								name: "CONFIG_MAP_HASH".to_string(),
+								value: Some(config_map_hash),
+								..EnvVar::default()
+							},
+							EnvVar {
+								name: "VAULT_AUTH_METHOD".to_string(),
+								value: Some("token".to_string()),
+								..EnvVar::default()
+							},
+							EnvVar {
+								name: "VAULT_TOKEN".to_string(),
+								value: Some(ctx.patr_token.clone()),
+								..Default::default()
+							},
+						])
+						.collect::<Vec<_>>(),
+				),
+				resources: Some(ResourceRequirements {
+					// The limits default to the capacity of the machine type,
+					// unless the deployment sets its own limits
+					limits: Some(
+						[
+							(
+								"memory".to_string(),
+								Quantity(spec.running_details.resources.memory_limit.map_or_else(
+									|| format!("{:.1}G", (machine_type.memory_count as f64) / 4f64),
+									|mib| format!("{mib}Mi"),
+								)),
+							),
+							(
+								"cpu".to_string(),
+								Quantity(spec.running_details.resources.cpu_limit.map_or_else(
+									|| format!("{:.1}", machine_type.cpu_count as f64),
+									|millicores| format!("{millicores}m"),
+								)),
+							),
+						]
+						.into(),
 					),
-					resources: Some(ResourceRequirements {
-						limits: Some(
-							[
-								(
-									"memory".to_string(),
-									Quantity(format!(
-										"{:.1}G",
-										(machine_type.memory_count as f64) / 4f64
-									)),
-								),
-								(
-									"cpu".to_string(),
-									Quantity(format!("{:.1}", machine_type.cpu_count as f64)),
+					// https://blog.kubecost.com/blog/requests-and-limits/#the-tradeoffs
+					// using too low values for resource request
+					// will result in frequent pod restarts if
+					// memory usage increases and may result in
+					// starvation
+					//
+					// currently used 5% of the minimum deployment
+					// machine type as a request values, unless the
+					// deployment sets its own requests
+					requests: Some(
+						[
+							(
+								"memory".to_string(),
+								Quantity(
+									spec.running_details
+										.resources
+										.memory_request
+										.map_or_else(|| "25M".to_owned(), |mib| format!("{mib}Mi")),
 								),
-							]
-							.into(),
-						),
-						// https://blog.kubecost.com/blog/requests-and-limits/#the-tradeoffs
-						// using too low values for resource request
-						// will result in frequent pod restarts if
-						// memory usage increases and may result in
-						// starvation
-						//
-						// currently used 5% of the minimum deployment
-						// machine type as a request values
-						requests: Some(
-							[
-								("memory".to_string(), Quantity("25M".to_owned())),
-								("cpu".to_string(), Quantity("50m".to_owned())),
-							]
-							.into_iter()
-							.collect(),
-						),
-						claims: None,
-					}),
-					volume_mounts: if !volume_mounts.is_empty() {
-						Some(volume_mounts)
-					} else {
-						None
-					},
-					..Container::default()
-				}],
-				volumes: if !volumes.is_empty() {
-					Some(volumes)
+							),
+							(
+								"cpu".to_string(),
+								Quantity(spec.running_details.resources.cpu_request.map_or_else(
+									|| "50m".to_owned(),
+									|millicores| format!("{millicores}m"),
+								)),
+							),
+						]
+						.into_iter()
+						.collect(),
+					),
+					claims: None,
+				}),
+				volume_mounts: if !volume_mounts.is_empty() {
+					Some(volume_mounts)
 				} else {
 					None
 				},
-				image_pull_secrets: spec.deployment.registry.is_patr_registry().then(|| {
+				..Container::default()
+			}],
+			volumes: if !volumes.is_empty() {
+				Some(volumes)
+			} else {
+				None
+			},
+			image_pull_secrets: {
+				let mut image_pull_secrets = Vec::new();
+				if spec.deployment.registry.is_patr_registry() {
 					// TODO: for now patr registry is not supported
 					// for user clusters, need to create a separate
 					// secret for each private repo in future
-					vec![LocalObjectReference {
+					image_pull_secrets.push(LocalObjectReference {
 						name: Some("patr-regcred".to_string()),
-					}]
-				}),
-				..PodSpec::default()
-			}),
-			metadata: Some(ObjectMeta {
-				labels: Some(labels.clone()),
-				annotations: Some(
-					[
-						(
-							"vault.security.banzaicloud.io/vault-addr".to_string(),
-							"https://secrets.patr.cloud".to_string(),
-						),
-						(
-							"vault.security.banzaicloud.io/vault-skip-verify".to_string(),
-							"false".to_string(),
-						),
-						(
-							"vault.security.banzaicloud.io/vault-agent".to_string(),
-							"false".to_string(),
-						),
-						(
-							"vault.security.banzaicloud.io/vault-role".to_string(),
-							"vault".to_string(),
-						),
-						(
-							"vault.security.banzaicloud.io/vault-path".to_string(),
-							"kubernetes".to_string(),
-						),
-					]
-					.into(),
-				),
+					});
+				}
+				if let Some(pull_secret_id) = spec.deployment.pull_secret_id {
+					// The credentials of the pull secret are synced into
+					// the namespace of the workspace as a
+					// `kubernetes.io/dockerconfigjson` secret by
+					// `apply_pull_secret`
+					image_pull_secrets.push(LocalObjectReference {
+						name: Some(format!("pull-secret-{}", pull_secret_id)),
+					});
+				}
+				(!image_pull_secrets.is_empty()).then_some(image_pull_secrets)
+			},
+			..PodSpec::default()
+		}),
+		metadata: Some(ObjectMeta {
+			labels: Some(labels.clone()),
+			annotations: Some(
+				[
+					(
+						"vault.security.banzaicloud.io/vault-addr".to_string(),
+						"https://secrets.patr.cloud".to_string(),
+					),
+					(
+						"vault.security.banzaicloud.io/vault-skip-verify".to_string(),
+						"false".to_string(),
+					),
+					(
+						"vault.security.banzaicloud.io/vault-agent".to_string(),
+						"false".to_string(),
+					),
+					(
+						"vault.security.banzaicloud.io/vault-role".to_string(),
+						"vault".to_string(),
+					),
+					(
+						"vault.security.banzaicloud.io/vault-path".to_string(),
+						"kubernetes".to_string(),
+					),
+				]
+				.into_iter()
+				// The log shipper drops the logs below the deployment's log
+				// level before they are stored
+				.chain(spec.running_details.log_level.map(|log_level| {
+					(
+						constants::LOG_LEVEL_ANNOTATION.to_string(),
+						log_level.to_string(),
+					)
+				}))
+				.collect(),
+			),
+			owner_references: Some(vec![owner_reference.clone()]),
+			..ObjectMeta::default()
+		}),
+	};
+
+	// The pods of the canary are labelled with a different deployment ID, so
+	// that they aren't selected by the deployment (or its service) itself
+	let canary_id = format!("{}{}", spec.deployment.id, constants::CANARY_SUFFIX);
+	let canary_labels = labels
+		.clone()
+		.into_iter()
+		.chain([(constants::DEPLOYMENT_ID.to_string(), canary_id.clone())])
+		.collect::<BTreeMap<_, _>>();
+	let canary_deployment = canary.map(|(canary_image_name, canary_replicas)| {
+		let mut template = template.clone();
+		if let Some(metadata) = &mut template.metadata {
+			metadata.labels = Some(canary_labels.clone());
+		}
+		if let Some(container) = template
+			.spec
+			.as_mut()
+			.and_then(|pod_spec| pod_spec.containers.first_mut())
+		{
+			container.image = Some(canary_image_name);
+		}
+
+		KubeDeployment {
+			metadata: ObjectMeta {
+				name: Some(format!("deployment-{}", canary_id)),
+				namespace: Some(namespace.to_string()),
+				labels: Some(canary_labels.clone()),
 				owner_references: Some(vec![owner_reference.clone()]),
 				..ObjectMeta::default()
+			},
+			spec: Some(DeploymentSpec {
+				replicas: Some(
+					if is_cold {
+						0
+					} else {
+						canary_replicas.canary.into()
+					},
+				),
+				selector: LabelSelector {
+					match_expressions: None,
+					match_labels: Some(canary_labels.clone()),
+				},
+				template,
+				..DeploymentSpec::default()
 			}),
-		};
+			..KubeDeployment::default()
+		}
+	});
 
 	if spec.running_details.volumes.is_empty() {
 		let kubernetes_deployment = KubeDeployment {
@@ -675,38 +885,49 @@ async fn reconcile(
 			)
 			.await?;
 
-		// HPA - horizontal pod autoscaler
-		let kubernetes_hpa = HorizontalPodAutoscaler {
-			metadata: ObjectMeta {
-				name: Some(format!("hpa-{}", spec.deployment.id)),
-				namespace: Some(namespace.to_string()),
-				owner_references: Some(vec![owner_reference.clone()]),
-				..ObjectMeta::default()
-			},
-			spec: Some(HorizontalPodAutoscalerSpec {
-				scale_target_ref: CrossVersionObjectReference {
-					api_version: Some("apps/v1".to_string()),
-					kind: "Deployment".to_string(),
-					name: format!("deployment-{}", spec.deployment.id),
-				},
-				min_replicas: Some(spec.running_details.min_horizontal_scale.into()),
-				max_replicas: spec.running_details.max_horizontal_scale.into(),
-				target_cpu_utilization_percentage: Some(80),
-			}),
-			..HorizontalPodAutoscaler::default()
-		};
-
-		// Create the HPA defined above
-		trace!("creating horizontal pod autoscaler");
 		let hpa_api = Api::<HorizontalPodAutoscaler>::namespaced(ctx.client.clone(), namespace);
 
-		hpa_api
-			.patch(
-				&format!("hpa-{}", spec.deployment.id),
-				&PatchParams::apply(&format!("hpa-{}", spec.deployment.id)),
-				&Patch::Apply(kubernetes_hpa),
-			)
-			.await?;
+		if is_cold {
+			// The HPA would scale a deployment that is scaled to zero back up
+			trace!("deleting horizontal pod autoscaler of cold deployment");
+			hpa_api
+				.delete_opt(
+					&format!("hpa-{}", spec.deployment.id),
+					&DeleteParams::default(),
+				)
+				.await?;
+		} else {
+			// HPA - horizontal pod autoscaler
+			let kubernetes_hpa = HorizontalPodAutoscaler {
+				metadata: ObjectMeta {
+					name: Some(format!("hpa-{}", spec.deployment.id)),
+					namespace: Some(namespace.to_string()),
+					owner_references: Some(vec![owner_reference.clone()]),
+					..ObjectMeta::default()
+				},
+				spec: Some(HorizontalPodAutoscalerSpec {
+					scale_target_ref: CrossVersionObjectReference {
+						api_version: Some("apps/v1".to_string()),
+						kind: "Deployment".to_string(),
+						name: format!("deployment-{}", spec.deployment.id),
+					},
+					min_replicas: Some(min_replicas.into()),
+					max_replicas: max_replicas.into(),
+					target_cpu_utilization_percentage: Some(80),
+				}),
+				..HorizontalPodAutoscaler::default()
+			};
+
+			// Create the HPA defined above
+			trace!("creating horizontal pod autoscaler");
+			hpa_api
+				.patch(
+					&format!("hpa-{}", spec.deployment.id),
+					&PatchParams::apply(&format!("hpa-{}", spec.deployment.id)),
+					&Patch::Apply(kubernetes_hpa),
+				)
+				.await?;
+		}
 	} else {
 		let kubernetes_sts = StatefulSet {
 			metadata,
@@ -807,7 +1028,70 @@ async fn reconcile(
 			.await?;
 	}
 
-	// Create the ingress defined above
+	let has_canary = canary_deployment.is_some();
+	if let Some(canary_deployment) = canary_deployment {
+		trace!("creating canary deployment");
+		Api::<KubeDeployment>::namespaced(ctx.client.clone(), namespace)
+			.patch(
+				&format!("deployment-{}", canary_id),
+				&PatchParams::apply(&format!("deployment-{}", canary_id)),
+				&Patch::Apply(canary_deployment),
+			)
+			.await?;
+
+		trace!("creating canary service");
+		Api::<Service>::namespaced(ctx.client.clone(), namespace)
+			.patch(
+				&format!("service-{}", canary_id),
+				&PatchParams::apply(&format!("service-{}", canary_id)),
+				&Patch::Apply(Service {
+					metadata: ObjectMeta {
+						name: Some(format!("service-{}", canary_id)),
+						owner_references: Some(vec![owner_reference.clone()]),
+						..ObjectMeta::default()
+					},
+					spec: Some(ServiceSpec {
+						ports: Some(
+							spec.running_details
+								.ports
+								.keys()
+								.map(|port| ServicePort {
+									port: port.value() as i32,
+									target_port: Some(IntOrString::Int(port.value() as i32)),
+									name: Some(format!("port-{}", port)),
+									..ServicePort::default()
+								})
+								.collect::<Vec<_>>(),
+						),
+						selector: Some(canary_labels.clone()),
+						..ServiceSpec::default()
+					}),
+					..Service::default()
+				}),
+			)
+			.await?;
+	} else {
+		trace!("deleting the canary deployment and service if there are any");
+		Api::<KubeDeployment>::namespaced(ctx.client.clone(), namespace)
+			.delete_opt(
+				&format!("deployment-{}", canary_id),
+				&DeleteParams::default(),
+			)
+			.await?;
+		Api::<Service>::namespaced(ctx.client.clone(), namespace)
+			.delete_opt(&format!("service-{}", canary_id), &DeleteParams::default())
+			.await?;
+	}
+
+	// Create the ingress defined above. The host of the canary is routed to
+	// the deployment itself when there is no canary, so that the requests that
+	// the ingress sends to it before learning that it was removed still work
+	let primary_id = spec.deployment.id.to_string();
+	let canary_service_id = if has_canary {
+		canary_id.clone()
+	} else {
+		primary_id.clone()
+	};
 	trace!("creating ingress");
 	Api::<Ingress>::namespaced(ctx.client.clone(), namespace)
 		.patch(
@@ -832,16 +1116,22 @@ async fn reconcile(
 							.ports
 							.iter()
 							.filter(|(_, port_type)| *port_type == &ExposedPortType::Http)
-							.map(|(port, _)| IngressRule {
+							.flat_map(|(port, _)| {
+								[
+									(port, primary_id.clone(), primary_id.clone()),
+									(port, canary_id.clone(), canary_service_id.clone()),
+								]
+							})
+							.map(|(port, host_id, service_id)| IngressRule {
 								host: Some(format!(
 									"{}-{}.{}.onpatr.cloud",
-									port, spec.deployment.id, ctx.region_id,
+									port, host_id, ctx.region_id,
 								)),
 								http: Some(HTTPIngressRuleValue {
 									paths: vec![HTTPIngressPath {
 										backend: IngressBackend {
 											service: Some(IngressServiceBackend {
-												name: format!("service-{}", spec.deployment.id),
+												name: format!("service-{}", service_id),
 												port: Some(ServiceBackendPort {
 													number: Some(port.value().into()),
 													..ServiceBackendPort::default()
@@ -863,5 +1153,198 @@ async fn reconcile(
 		)
 		.await?;
 
+	trace!("applying the egress policy of the workspace");
+	let egress_policy = crate::egress_policy::get_policy(&ctx).await?;
+
+	Api::<NetworkPolicy>::namespaced(ctx.client.clone(), namespace)
+		.patch(
+			&format!("egress-{}", spec.deployment.id),
+			&PatchParams::apply(&format!("egress-{}", spec.deployment.id)),
+			&Patch::Apply(
+				crate::egress_policy::network_policy(
+					format!("egress-{}", spec.deployment.id),
+					&egress_policy,
+					[(
+						constants::DEPLOYMENT_ID.to_string(),
+						spec.deployment.id.to_string(),
+					)]
+					.into(),
+					ObjectMeta {
+						owner_references: Some(vec![owner_reference.clone()]),
+						..ObjectMeta::default()
+					},
+				)
+				.await,
+			),
+		)
+		.await?;
+
+	let canary_egress_api = Api::<NetworkPolicy>::namespaced(ctx.client.clone(), namespace);
+	if has_canary {
+		canary_egress_api
+			.patch(
+				&format!("egress-{}", canary_id),
+				&PatchParams::apply(&format!("egress-{}", canary_id)),
+				&Patch::Apply(
+					crate::egress_policy::network_policy(
+						format!("egress-{}", canary_id),
+						&egress_policy,
+						[(constants::DEPLOYMENT_ID.to_string(), canary_id.clone())].into(),
+						ObjectMeta {
+							owner_references: Some(vec![owner_reference.clone()]),
+							..ObjectMeta::default()
+						},
+					)
+					.await,
+				),
+			)
+			.await?;
+	} else {
+		canary_egress_api
+			.delete_opt(&format!("egress-{}", canary_id), &DeleteParams::default())
+			.await?;
+	}
+
+	if let Err(err) = report_readiness(&ctx, namespace, spec, &canary_id, has_canary).await {
+		warn!(
+			"Failed to report the readiness of deployment `{}`: {}",
+			spec.deployment.id, err
+		);
+	}
+
 	Ok(Action::requeue(Duration::from_secs(3600)))
 }
+
+/// Reports how many replicas of a deployment (and of its canary) are ready to
+/// the Patr API, so that the ingress only forwards requests to replicas that
+/// are ready. The status of a workload changes as its replicas pass their
+/// health checks, which triggers a reconciliation, so the readiness is
+/// reported every time it changes. The names of the pods of the deployment
+/// are reported along with it, since the logs of each replica are labelled
+/// with the name of its pod.
+async fn report_readiness(
+	ctx: &AppState,
+	namespace: &str,
+	spec: &PatrDeploymentSpec,
+	canary_id: &str,
+	has_canary: bool,
+) -> Result<(), AppError> {
+	// Deployments with volumes are run as a stateful set instead
+	let (ready, total) = if spec.running_details.volumes.is_empty() {
+		Api::<KubeDeployment>::namespaced(ctx.client.clone(), namespace)
+			.get_opt(&format!("deployment-{}", spec.deployment.id))
+			.await?
+			.and_then(|deployment| deployment.status)
+			.map(|status| (status.ready_replicas, status.replicas))
+			.unwrap_or_default()
+	} else {
+		Api::<StatefulSet>::namespaced(ctx.client.clone(), namespace)
+			.get_opt(&format!("sts-{}", spec.deployment.id))
+			.await?
+			.and_then(|sts| sts.status)
+			.map(|status| (status.ready_replicas, Some(status.replicas)))
+			.unwrap_or_default()
+	};
+
+	let canary_ready_replicas = if has_canary {
+		let ready = Api::<KubeDeployment>::namespaced(ctx.client.clone(), namespace)
+			.get_opt(&format!("deployment-{}", canary_id))
+			.await?
+			.and_then(|deployment| deployment.status)
+			.and_then(|status| status.ready_replicas);
+		Some(replica_count(ready))
+	} else {
+		None
+	};
+
+	// The pods of the canary are labelled with the ID of the canary instead
+	let mut replica_ids = Api::<Pod>::namespaced(ctx.client.clone(), namespace)
+		.list(&ListParams::default().labels(&format!(
+			"{} in ({}, {})",
+			constants::DEPLOYMENT_ID,
+			spec.deployment.id,
+			canary_id
+		)))
+		.await?
+		.into_iter()
+		.filter_map(|pod| pod.metadata.name)
+		.collect::<Vec<_>>();
+	replica_ids.truncate(models::utils::constants::MAX_REPORTED_REPLICAS.into());
+
+	make_request(
+		ApiRequest::<ReportDeploymentReadinessRequest>::builder()
+			.path(ReportDeploymentReadinessPath {
+				workspace_id: ctx.workspace_id,
+				deployment_id: spec.deployment.id,
+			})
+			.headers(ReportDeploymentReadinessRequestHeaders {
+				authorization: BearerToken::from_str(&ctx.patr_token).map_err(|err| {
+					ErrorType::server_error(format!("invalid patr token. Error: `{}`", err))
+				})?,
+				user_agent: UserAgent::from_static("deployment-controller"),
+			})
+			.query(())
+			.body(ReportDeploymentReadinessRequest {
+				replicas: replica_readiness(ready, total),
+				canary_ready_replicas,
+				replica_ids,
+			})
+			.build(),
+	)
+	.await
+	.map_err(|err| err.body.code)?;
+
+	Ok(())
+}
+
+/// Turns a number of replicas from the status of a workload into the number
+/// that is reported to the Patr API, which can't be more than
+/// [`MAX_REPORTED_REPLICAS`](models::utils::constants::MAX_REPORTED_REPLICAS).
+/// Deployments never have more replicas than that.
+fn replica_count(replicas: Option<i32>) -> u16 {
+	replicas
+		.unwrap_or_default()
+		.clamp(0, models::utils::constants::MAX_REPORTED_REPLICAS.into()) as u16
+}
+
+/// Turns the number of ready and total replicas from the status of a workload
+/// into the readiness that is reported to the Patr API. The status of a
+/// workload can count more replicas as ready than it has while it is being
+/// scaled down, so the ready replicas are capped by the total.
+fn replica_readiness(ready: Option<i32>, total: Option<i32>) -> DeploymentReplicaReadiness {
+	let total = replica_count(total);
+	DeploymentReplicaReadiness {
+		ready: replica_count(ready).min(total),
+		total,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use models::{api::workspace::deployment::DeploymentReplicaReadiness, utils::constants};
+
+	use super::replica_readiness;
+
+	#[test]
+	fn reported_readiness_is_always_valid() {
+		assert_eq!(
+			replica_readiness(Some(1), Some(2)),
+			DeploymentReplicaReadiness { ready: 1, total: 2 }
+		);
+		assert_eq!(
+			replica_readiness(None, None),
+			DeploymentReplicaReadiness { ready: 0, total: 0 }
+		);
+		assert_eq!(
+			replica_readiness(Some(3), Some(2)),
+			DeploymentReplicaReadiness { ready: 2, total: 2 }
+		);
+		assert_eq!(
+			replica_readiness(Some(-1), Some(i32::MAX)).total,
+			constants::MAX_REPORTED_REPLICAS
+		);
+		for (ready, total) in [(Some(0), Some(0)), (Some(300), Some(300)), (Some(5), None)] {
+			assert!(replica_readiness(ready, total).is_valid());
+		}
+	}
+}
@@ -0,0 +1,235 @@
+use std::{collections::BTreeMap, str::FromStr};
+
+use k8s_openapi::{
+	api::networking::v1::*,
+	apimachinery::pkg::{apis::meta::v1::LabelSelector, util::intstr::IntOrString},
+};
+use kube::{
+	api::{ListParams, Patch, PatchParams},
+	core::ObjectMeta,
+	Api,
+};
+use models::{api::workspace::egress_policy::*, prelude::*};
+
+use crate::{client::make_request, constants, prelude::*};
+
+/// Gets the egress policy of the workspace from the Patr API
+pub(crate) async fn get_policy(state: &AppState) -> Result<WorkspaceEgressPolicy, AppError> {
+	Ok(make_request(
+		ApiRequest::<GetEgressPolicyRequest>::builder()
+			.path(GetEgressPolicyPath {
+				workspace_id: state.workspace_id,
+			})
+			.headers(GetEgressPolicyRequestHeaders {
+				authorization: BearerToken::from_str(&state.patr_token).map_err(|err| {
+					ErrorType::server_error(format!("invalid patr token. Error: `{}`", err))
+				})?,
+				user_agent: UserAgent::from_static("deployment-controller"),
+			})
+			.query(())
+			.body(GetEgressPolicyRequest)
+			.build(),
+	)
+	.await
+	.map_err(|err| err.body.code)?
+	.body
+	.policy)
+}
+
+/// Creates the `NetworkPolicy` that enforces the egress policy of the
+/// workspace on the pods with the given labels. The network policy is labeled,
+/// so that the domains in it can be resolved again by [`refresh_domains`].
+pub(crate) async fn network_policy(
+	name: String,
+	policy: &WorkspaceEgressPolicy,
+	pod_labels: BTreeMap<String, String>,
+	metadata: ObjectMeta,
+) -> NetworkPolicy {
+	NetworkPolicy {
+		metadata: ObjectMeta {
+			name: Some(name),
+			labels: Some(BTreeMap::from([(
+				constants::EGRESS_POLICY.to_string(),
+				"true".to_string(),
+			)])),
+			..metadata
+		},
+		spec: Some(network_policy_spec(policy, pod_labels).await),
+	}
+}
+
+/// Creates the spec of the `NetworkPolicy` that enforces the egress policy of
+/// the workspace on the pods with the given labels. Domains are resolved to
+/// the addresses they currently point to, so they are resolved again every
+/// [`constants::EGRESS_POLICY_REFRESH_INTERVAL`] by [`refresh_domains`]. DNS
+/// lookups to the DNS service of the cluster are always allowed, so that the
+/// pods can resolve the domains they are allowed to connect to, but no other
+/// traffic on the DNS port is.
+///
+/// An empty policy allows all outbound connections.
+async fn network_policy_spec(
+	policy: &WorkspaceEgressPolicy,
+	pod_labels: BTreeMap<String, String>,
+) -> NetworkPolicySpec {
+	let pod_selector = LabelSelector {
+		match_labels: Some(pod_labels),
+		..LabelSelector::default()
+	};
+
+	if policy.is_empty() {
+		return NetworkPolicySpec {
+			pod_selector,
+			policy_types: Some(vec!["Egress".to_string()]),
+			egress: Some(vec![NetworkPolicyEgressRule::default()]),
+			..NetworkPolicySpec::default()
+		};
+	}
+
+	let allow = resolve_rules(&policy.allow).await;
+	let deny = resolve_rules(&policy.deny).await;
+
+	// Allowed domains that don't resolve to anything must not allow every
+	// destination, so nothing but DNS is allowed then
+	let to = if !policy.allow.is_empty() && allow.is_empty() {
+		vec![]
+	} else {
+		allowed_ip_blocks(&allow, &deny)
+			.into_iter()
+			.map(|AllowedIpBlock { cidr, except }| NetworkPolicyPeer {
+				ip_block: Some(IPBlock {
+					cidr: cidr.to_string(),
+					except: (!except.is_empty())
+						.then(|| except.iter().map(ToString::to_string).collect()),
+				}),
+				..NetworkPolicyPeer::default()
+			})
+			.collect::<Vec<_>>()
+	};
+
+	let mut egress = vec![NetworkPolicyEgressRule {
+		to: Some(vec![NetworkPolicyPeer {
+			namespace_selector: Some(LabelSelector {
+				match_labels: Some(BTreeMap::from([(
+					"kubernetes.io/metadata.name".to_string(),
+					"kube-system".to_string(),
+				)])),
+				..LabelSelector::default()
+			}),
+			pod_selector: Some(LabelSelector {
+				match_labels: Some(BTreeMap::from([(
+					"k8s-app".to_string(),
+					"kube-dns".to_string(),
+				)])),
+				..LabelSelector::default()
+			}),
+			..NetworkPolicyPeer::default()
+		}]),
+		ports: Some(
+			["UDP", "TCP"]
+				.into_iter()
+				.map(|protocol| NetworkPolicyPort {
+					port: Some(IntOrString::Int(53)),
+					protocol: Some(protocol.to_string()),
+					..NetworkPolicyPort::default()
+				})
+				.collect(),
+		),
+	}];
+	if !to.is_empty() {
+		egress.push(NetworkPolicyEgressRule {
+			to: Some(to),
+			..NetworkPolicyEgressRule::default()
+		});
+	}
+
+	NetworkPolicySpec {
+		pod_selector,
+		policy_types: Some(vec!["Egress".to_string()]),
+		egress: Some(egress),
+		..NetworkPolicySpec::default()
+	}
+}
+
+/// Resolves the domains in the egress policy of the workspace again, and
+/// updates the network policies of all the deployments with the addresses that
+/// they now point to. Policies without any domains are left alone, since they
+/// only change when the deployments are reconciled.
+pub(crate) async fn refresh_domains(state: &AppState) -> Result<(), AppError> {
+	let policy = get_policy(state).await?;
+	let has_domains = policy
+		.allow
+		.iter()
+		.chain(&policy.deny)
+		.any(|rule| matches!(rule.parse(), Ok(EgressRule::Domain(_))));
+	if !has_domains {
+		return Ok(());
+	}
+
+	let network_policies =
+		Api::<NetworkPolicy>::namespaced(state.client.clone(), &state.workspace_id.to_string());
+	let existing = network_policies
+		.list(&ListParams::default().labels(&format!("{}=true", constants::EGRESS_POLICY)))
+		.await?;
+
+	for NetworkPolicy { metadata, spec } in existing {
+		let Some(name) = metadata.name else {
+			continue;
+		};
+		let pod_labels = spec
+			.and_then(|spec| spec.pod_selector.match_labels)
+			.unwrap_or_default();
+
+		// The network policy is applied with the same fields as when its
+		// deployment is reconciled, so that none of them are removed
+		trace!("Resolving the domains of network policy `{name}` again");
+		network_policies
+			.patch(
+				&name,
+				&PatchParams::apply(&name),
+				&Patch::Apply(
+					network_policy(
+						name.clone(),
+						&policy,
+						pod_labels,
+						ObjectMeta {
+							owner_references: metadata.owner_references,
+							..ObjectMeta::default()
+						},
+					)
+					.await,
+				),
+			)
+			.await?;
+	}
+
+	Ok(())
+}
+
+/// Parses the rules of an egress policy, resolving the domains to the
+/// addresses that they point to. Rules that can't be parsed or resolved are
+/// skipped, since the API doesn't allow invalid rules to be saved.
+async fn resolve_rules(rules: &[String]) -> Vec<IpNetwork> {
+	let mut resolved = Vec::new();
+
+	for rule in rules {
+		match rule.parse::<EgressRule>() {
+			Ok(EgressRule::Cidr(network)) => resolved.push(network),
+			Ok(EgressRule::Domain(domain)) => {
+				match tokio::net::lookup_host((domain.as_str(), 0)).await {
+					Ok(addresses) => resolved.extend(addresses.filter_map(|address| {
+						match address.ip().to_string().parse::<EgressRule>() {
+							Ok(EgressRule::Cidr(network)) => Some(network),
+							_ => None,
+						}
+					})),
+					Err(err) => warn!("Unable to resolve egress domain `{}`: {}", domain, err),
+				}
+			}
+			Err(err) => warn!("Ignoring invalid egress rule `{}`: {}", rule, err),
+		}
+	}
+
+	resolved.sort();
+	resolved.dedup();
+	resolved
+}
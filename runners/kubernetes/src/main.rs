@@ -17,7 +17,10 @@ use ::models::{
 use app::AppState;
 use futures::StreamExt;
 use prelude::*;
-use tokio::{sync::broadcast, time::Duration};
+use tokio::{
+	sync::broadcast,
+	time::{Duration, Instant},
+};
 
 /// A prelude that re-exports commonly used items.
 pub mod prelude {
@@ -34,6 +37,8 @@ pub mod prelude {
 /// All app state that is shared across the entire application. Used to share
 /// ApiTokens, backend connections, etc.
 mod app;
+/// Building the images of deployments from their build source in the cluster.
+mod build;
 /// The client used to communicate with the Patr API.
 mod client;
 /// All the constants used by the controller.
@@ -44,6 +49,9 @@ mod database;
 /// All functions and business login to run a deployment controller and keep it
 /// in sync with the Patr API data.
 mod deployment;
+/// The network policies that enforce the egress policy of the workspace on the
+/// deployments
+mod egress_policy;
 /// All models used by the controller, including CRDs, etc.
 mod models;
 /// Utility functions used by the controller.
@@ -71,21 +79,70 @@ async fn main() {
 	)
 	.await
 	.unwrap()
-	.for_each(|deployment| async {
-		_ = patr_update_sender.send(());
+	.for_each(|message| {
+		let (state, patr_update_sender) = (&state, &patr_update_sender);
+		async move {
+			match message {
+				StreamRunnerDataForWorkspaceServerMsg::DatabaseUserCreated {
+					database_id,
+					engine,
+					user,
+				} => database::create_user(state, database_id, engine, user).await,
+				StreamRunnerDataForWorkspaceServerMsg::DatabaseUserDropped {
+					database_id,
+					engine,
+					username,
+					owner,
+				} => database::drop_user(state, database_id, engine, username, owner).await,
+				// Builds don't change the deployment until they succeed, at
+				// which point the API updates the deployment
+				StreamRunnerDataForWorkspaceServerMsg::DeploymentBuildRequested {
+					deployment_id,
+					build_id,
+					build_source,
+					image,
+				} => {
+					tokio::spawn(build::build_image(
+						state.clone(),
+						deployment_id,
+						build_id,
+						build_source,
+						image,
+					));
+				}
+				_ => {
+					_ = patr_update_sender.send(());
+				}
+			}
+		}
 	})
 	.await;
 
 	let (reconcile_all_deployments, deployment_controller_task) =
 		deployment::start_controller(state.client.clone(), state.clone(), patr_update_receiver);
 
+	// Ever 1 hour, reconcile everything. The domains of the egress policy are
+	// resolved again more often, since the addresses they point to can change
+	// at any time
+	let mut reconcile_interval = tokio::time::interval_at(
+		Instant::now() + Duration::from_secs(3600),
+		Duration::from_secs(3600),
+	);
+	let mut egress_refresh_interval = tokio::time::interval_at(
+		Instant::now() + constants::EGRESS_POLICY_REFRESH_INTERVAL,
+		constants::EGRESS_POLICY_REFRESH_INTERVAL,
+	);
+
 	loop {
 		tokio::select! {
-			_ = async {
-				// Ever 1 hour, reconcile everything
-				tokio::time::sleep(Duration::from_secs(3600)).await;
+			_ = reconcile_interval.tick() => {
 				_ = reconcile_all_deployments.send(());
-			} => {},
+			},
+			_ = egress_refresh_interval.tick() => {
+				if let Err(err) = egress_policy::refresh_domains(&state).await {
+					warn!("Failed to resolve the domains of the egress policy: {err}");
+				}
+			},
 			_ = exit_signal() => {
 				tracing::info!("Received SIGINT, shutting down");
 